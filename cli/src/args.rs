@@ -25,7 +25,12 @@
 */
 
 use {
-    crate::error::{CliError, CliResult},
+    crate::{
+        codegen::{Codegen, Lang},
+        error::{CliError, CliResult},
+        infer::Infer,
+        profile::{Credentials, Profile},
+    },
     crossterm::{
         event::{self, Event, KeyCode, KeyEvent},
         terminal,
@@ -46,6 +51,8 @@ pub struct ClientConfig {
     pub kind: ClientConfigKind,
     pub username: String,
     pub password: String,
+    /// if set, the password is saved in the keyring once the server accepts it
+    pub save_password: Option<Credentials>,
 }
 
 impl ClientConfig {
@@ -54,6 +61,13 @@ impl ClientConfig {
             kind,
             username,
             password,
+            save_password: None,
+        }
+    }
+    /// Called once the server has accepted the credentials
+    pub fn authenticated(&self) {
+        if let Some(credentials) = &self.save_password {
+            credentials.save(&self.username, &self.password)
         }
     }
 }
@@ -69,6 +83,8 @@ pub enum Task {
     HelpMessage(String),
     OpenShell(ClientConfig),
     ExecOnce(ClientConfig, String),
+    Codegen(ClientConfig, Codegen),
+    Infer(ClientConfig, Infer),
 }
 
 enum TaskInner {
@@ -90,6 +106,17 @@ pub fn parse() -> CliResult<Task> {
         TaskInner::HelpMsg(msg) => return Ok(Task::HelpMessage(msg)),
         TaskInner::OpenShell(args) => args,
     };
+    // options on the command line override the profile's
+    let credentials = match args.remove("--profile") {
+        Some(name) => {
+            let (options, credentials) = Profile::load(&name)?.into_args();
+            for (option, value) in options {
+                args.entry(option).or_insert(value);
+            }
+            Some(credentials)
+        }
+        None => None,
+    };
     let endpoint = match args.remove("--endpoint") {
         None => ClientConfigKind::Tcp("127.0.0.1".into(), 2003),
         Some(ep) => {
@@ -148,21 +175,67 @@ pub fn parse() -> CliResult<Task> {
         }
     };
     let password = match args.remove("--password") {
+        Some(p) => Some(p),
+        None => match &credentials {
+            Some(credentials) => credentials.password(&username)?,
+            None => None,
+        },
+    };
+    let mut save_password = None;
+    let password = match password {
         Some(p) => p,
         None => {
             // let us check the environment variable to see if anything was set
-            match env::var(env_vars::SKYDB_PASSWORD) {
+            let password = match env::var(env_vars::SKYDB_PASSWORD) {
                 Ok(v) => v,
                 Err(_) => read_password("Enter password: ")?,
-            }
+            };
+            // the profile keeps it in the keyring, but it isn't there yet
+            save_password = credentials.filter(Credentials::uses_keyring);
+            password
         }
     };
     let eval = args.remove("--eval");
+    let codegen = match (args.remove("--codegen"), args.remove("--space")) {
+        (Some(lang), Some(space)) => Some(Codegen::new(Lang::from_name(&lang)?, space)),
+        (Some(_), None) => {
+            return Err(CliError::ArgsErr(format!(
+                "must provide --space when using --codegen"
+            )))
+        }
+        (None, Some(_)) => {
+            return Err(CliError::ArgsErr(format!(
+                "--space can only be used with --codegen"
+            )))
+        }
+        (None, None) => None,
+    };
+    let (primary_key, sample) = (args.remove("--primary-key"), args.remove("--sample"));
+    let infer = match (args.remove("--infer"), args.remove("--model")) {
+        (Some(path), Some(model)) => Some(Infer::new(path, model, primary_key, sample)?),
+        (Some(_), None) => {
+            return Err(CliError::ArgsErr(format!(
+                "must provide --model when using --infer"
+            )))
+        }
+        (None, model) if model.is_some() | primary_key.is_some() | sample.is_some() => {
+            return Err(CliError::ArgsErr(format!(
+                "--model, --primary-key and --sample can only be used with --infer"
+            )))
+        }
+        (None, _) => None,
+    };
     if args.is_empty() {
-        let client = ClientConfig::new(endpoint, username, password);
-        match eval {
-            Some(query) => Ok(Task::ExecOnce(client, query)),
-            None => Ok(Task::OpenShell(client)),
+        let mut client = ClientConfig::new(endpoint, username, password);
+        client.save_password = save_password;
+        match (eval, codegen, infer) {
+            (Some(query), None, None) => Ok(Task::ExecOnce(client, query)),
+            (None, Some(codegen), None) => Ok(Task::Codegen(client, codegen)),
+            (None, None, Some(infer)) => Ok(Task::Infer(client, infer)),
+            (None, None, None) => Ok(Task::OpenShell(client)),
+            _ => Err(CliError::ArgsErr(format!(
+                "only one of --eval, --codegen and --infer can be used"
+            ))),
         }
     } else {
         Err(CliError::ArgsErr(format!("found unknown arguments")))
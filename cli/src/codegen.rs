@@ -0,0 +1,452 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    codegen
+    ---
+    `skysh --codegen <rust|typescript> --space <space>` reads the schema of every model in a space (the models are
+    listed by `inspect space` and each schema comes from `describe model`) and prints a type for each model, along
+    with helpers that build the statements to insert, fetch, update and delete a row by its primary key. the types
+    only depend on the layers of each field, so regenerating after a schema change keeps application types in sync.
+    lists can't be passed as parameters, so models with list fields only get the fetch and delete helpers
+*/
+
+use {
+    crate::{
+        error::{CliError, CliResult},
+        json::Json,
+        query::IsConnection,
+    },
+    skytable::{
+        response::{Response, Value},
+        Query,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lang {
+    Rust,
+    TypeScript,
+}
+
+impl Lang {
+    pub fn from_name(name: &str) -> CliResult<Self> {
+        match name {
+            "rust" => Ok(Self::Rust),
+            "typescript" => Ok(Self::TypeScript),
+            _ => Err(CliError::ArgsErr(format!(
+                "unknown language `{name}` for --codegen (expected `rust` or `typescript`)"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Codegen {
+    pub lang: Lang,
+    pub space: String,
+}
+
+impl Codegen {
+    pub fn new(lang: Lang, space: String) -> Self {
+        Self { lang, space }
+    }
+    /// Read the schema of every model in the space and generate the code for them
+    pub fn run(&self, con: &mut impl IsConnection) -> CliResult<String> {
+        let space = fetch_json(con, format!("inspect space {}", self.space))?;
+        let models = space
+            .get("models")
+            .and_then(Json::as_array)
+            .ok_or_else(|| unexpected("inspect space"))?;
+        let mut schemas = Vec::with_capacity(models.len());
+        for model in models {
+            let model = model.as_str().ok_or_else(|| unexpected("inspect space"))?;
+            let schema = fetch_json(con, format!("describe model {}.{model}", self.space))?;
+            schemas.push(ModelSchema::from_json(&self.space, model, &schema)?);
+        }
+        schemas.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(match self.lang {
+            Lang::Rust => generate_rust(&self.space, &schemas),
+            Lang::TypeScript => generate_typescript(&self.space, &schemas),
+        })
+    }
+}
+
+fn unexpected(statement: &str) -> CliError {
+    CliError::CodegenError(format!("unexpected response to `{statement}`"))
+}
+
+fn fetch_json(con: &mut impl IsConnection, statement: String) -> CliResult<Json> {
+    match con.execute_query(Query::new(&statement))? {
+        Response::Value(Value::String(s)) => Json::parse(&s).ok_or_else(|| unexpected(&statement)),
+        Response::Error(e) => Err(CliError::CodegenError(format!(
+            "`{statement}` failed with server error code: {e}"
+        ))),
+        _ => Err(unexpected(&statement)),
+    }
+}
+
+/*
+    schema
+*/
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    nullable: bool,
+    /// the layer types, outermost first (`list`, ..., scalar)
+    layers: Vec<String>,
+}
+
+impl Field {
+    fn is_list(&self) -> bool {
+        self.layers.len() > 1
+    }
+}
+
+#[derive(Debug)]
+struct ModelSchema {
+    name: String,
+    /// `space.model`
+    entity: String,
+    primary_key: String,
+    fields: Vec<Field>,
+}
+
+impl ModelSchema {
+    fn from_json(space: &str, name: &str, schema: &Json) -> CliResult<Self> {
+        let err = || unexpected(&format!("describe model {space}.{name}"));
+        let primary_key = schema
+            .get("primary_key")
+            .and_then(Json::as_str)
+            .ok_or_else(err)?;
+        let mut fields = vec![];
+        for field in schema
+            .get("fields")
+            .and_then(Json::as_array)
+            .ok_or_else(err)?
+        {
+            let name = field.get("name").and_then(Json::as_str).ok_or_else(err)?;
+            let nullable = field
+                .get("nullable")
+                .and_then(Json::as_bool)
+                .ok_or_else(err)?;
+            let mut layers = vec![];
+            for layer in field
+                .get("layers")
+                .and_then(Json::as_array)
+                .ok_or_else(err)?
+            {
+                let ty = layer.get("type").and_then(Json::as_str).ok_or_else(err)?;
+                layers.push(ty.to_owned());
+            }
+            fields.push(Field {
+                name: name.into(),
+                nullable,
+                layers,
+            });
+        }
+        Ok(Self {
+            name: name.into(),
+            entity: format!("{space}.{name}"),
+            primary_key: primary_key.into(),
+            fields,
+        })
+    }
+    fn pk(&self) -> &Field {
+        self.fields
+            .iter()
+            .find(|f| f.name == self.primary_key)
+            .expect("primary key is always a field")
+    }
+    fn has_lists(&self) -> bool {
+        self.fields.iter().any(Field::is_list)
+    }
+    fn non_pk_fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields.iter().filter(|f| f.name != self.primary_key)
+    }
+    fn stmt_insert(&self) -> String {
+        let params = vec!["?"; self.fields.len()];
+        format!("insert into {}({})", self.entity, params.join(", "))
+    }
+    fn stmt_select(&self) -> String {
+        format!(
+            "select * from {} where {} = ?",
+            self.entity, self.primary_key
+        )
+    }
+    fn stmt_update(&self) -> String {
+        let assignments: Vec<String> = self
+            .non_pk_fields()
+            .map(|f| format!("{} = ?", f.name))
+            .collect();
+        format!(
+            "update {} set {} where {} = ?",
+            self.entity,
+            assignments.join(", "),
+            self.primary_key
+        )
+    }
+    fn stmt_delete(&self) -> String {
+        format!("delete from {} where {} = ?", self.entity, self.primary_key)
+    }
+    fn type_name(&self) -> String {
+        self.name
+            .split('_')
+            .map(|part| {
+                let mut chars = part.chars();
+                match chars.next() {
+                    Some(c) => c.to_ascii_uppercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            })
+            .collect()
+    }
+}
+
+/*
+    rust
+*/
+
+const RUST_KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+fn rust_ident(name: &str) -> String {
+    if RUST_KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.into()
+    }
+}
+
+fn rust_type(layers: &[String]) -> String {
+    match layers {
+        [list, inner @ ..] if !inner.is_empty() && list == "list" => {
+            format!("Vec<{}>", rust_type(inner))
+        }
+        [scalar] => match scalar.as_str() {
+            "bool" => "bool",
+            "uint8" => "u8",
+            "uint16" => "u16",
+            "uint32" => "u32",
+            "uint64" => "u64",
+            "sint8" => "i8",
+            "sint16" => "i16",
+            "sint32" => "i32",
+            "sint64" => "i64",
+            "float32" => "f32",
+            "float64" => "f64",
+            "binary" => "Vec<u8>",
+            "string" => "String",
+            // a type that this version of skysh doesn't know about
+            other => return format!("() /* {other} */"),
+        }
+        .into(),
+        _ => "()".into(),
+    }
+}
+
+fn rust_field_type(field: &Field) -> String {
+    let ty = rust_type(&field.layers);
+    if field.nullable {
+        format!("Option<{ty}>")
+    } else {
+        ty
+    }
+}
+
+/// The type that the primary key is passed as (borrowed where possible)
+fn rust_pk_type(field: &Field) -> String {
+    match field.layers[0].as_str() {
+        "string" => "&str".into(),
+        _ => rust_field_type(field),
+    }
+}
+
+fn generate_rust(space: &str, models: &[ModelSchema]) -> String {
+    let mut ret = format!(
+        "// generated by `skysh --codegen rust --space {space}`. regenerate after changing a model's schema\n\n\
+        use skytable::{{Query, Response}};\n"
+    );
+    for model in models {
+        let ty = model.type_name();
+        let pk = model.pk();
+        let pk_ident = rust_ident(&pk.name);
+        ret.push_str(&format!(
+            "\n/// A row of `{}`\n#[derive(Debug, Clone, PartialEq, Response)]\npub struct {ty} {{\n",
+            model.entity
+        ));
+        for field in &model.fields {
+            ret.push_str(&format!(
+                "    pub {}: {},\n",
+                rust_ident(&field.name),
+                rust_field_type(field)
+            ));
+        }
+        ret.push_str(&format!(
+            "}}\n\nimpl {ty} {{\n    pub const MODEL: &'static str = \"{}\";\n",
+            model.entity
+        ));
+        if !model.has_lists() {
+            ret.push_str(&format!(
+                "    /// Insert this row\n    pub fn insert(&self) -> Query {{\n        let mut q = Query::new(\"{}\");\n",
+                model.stmt_insert()
+            ));
+            for field in &model.fields {
+                ret.push_str(&format!(
+                    "        q.push_param(self.{}.clone());\n",
+                    rust_ident(&field.name)
+                ));
+            }
+            ret.push_str("        q\n    }\n");
+        }
+        ret.push_str(&format!(
+            "    /// Fetch the row with the given primary key (parse the response as `{ty}`)\n    \
+            pub fn select({pk_ident}: {}) -> Query {{\n        \
+            let mut q = Query::new(\"{}\");\n        q.push_param({pk_ident});\n        q\n    }}\n",
+            rust_pk_type(pk),
+            model.stmt_select()
+        ));
+        if !model.has_lists() && model.non_pk_fields().next().is_some() {
+            ret.push_str(&format!(
+                "    /// Update every field of the row with this row's primary key\n    \
+                pub fn update(&self) -> Query {{\n        let mut q = Query::new(\"{}\");\n",
+                model.stmt_update()
+            ));
+            for field in model.non_pk_fields().chain([pk]) {
+                ret.push_str(&format!(
+                    "        q.push_param(self.{}.clone());\n",
+                    rust_ident(&field.name)
+                ));
+            }
+            ret.push_str("        q\n    }\n");
+        }
+        ret.push_str(&format!(
+            "    /// Delete the row with the given primary key\n    \
+            pub fn delete({pk_ident}: {}) -> Query {{\n        \
+            let mut q = Query::new(\"{}\");\n        q.push_param({pk_ident});\n        q\n    }}\n}}\n",
+            rust_pk_type(pk),
+            model.stmt_delete()
+        ));
+    }
+    ret
+}
+
+/*
+    typescript
+*/
+
+fn ts_type(layers: &[String]) -> String {
+    match layers {
+        [list, inner @ ..] if !inner.is_empty() && list == "list" => {
+            format!("{}[]", ts_type(inner))
+        }
+        [scalar] => match scalar.as_str() {
+            "bool" => "boolean",
+            "uint8" | "uint16" | "uint32" | "sint8" | "sint16" | "sint32" | "float32"
+            | "float64" => "number",
+            "uint64" | "sint64" => "bigint",
+            "binary" => "Uint8Array",
+            "string" => "string",
+            // a type that this version of skysh doesn't know about
+            _ => "unknown",
+        }
+        .into(),
+        _ => "unknown".into(),
+    }
+}
+
+fn ts_field_type(field: &Field) -> String {
+    let ty = ts_type(&field.layers);
+    if field.nullable {
+        format!("{ty} | null")
+    } else {
+        ty
+    }
+}
+
+fn generate_typescript(space: &str, models: &[ModelSchema]) -> String {
+    let mut ret = format!(
+        "// generated by `skysh --codegen typescript --space {space}`. regenerate after changing a model's schema\n\n\
+        export interface Statement {{\n  query: string;\n  params: unknown[];\n}}\n"
+    );
+    for model in models {
+        let ty = model.type_name();
+        let pk = model.pk();
+        ret.push_str(&format!(
+            "\n/** A row of `{}` */\nexport interface {ty} {{\n",
+            model.entity
+        ));
+        for field in &model.fields {
+            ret.push_str(&format!("  {}: {};\n", field.name, ts_field_type(field)));
+        }
+        ret.push_str(&format!(
+            "}}\n\nexport const {ty}Statements = {{\n  model: \"{}\",\n",
+            model.entity
+        ));
+        if !model.has_lists() {
+            let params: Vec<String> = model
+                .fields
+                .iter()
+                .map(|f| format!("row.{}", f.name))
+                .collect();
+            ret.push_str(&format!(
+                "  insert: (row: {ty}): Statement => ({{ query: \"{}\", params: [{}] }}),\n",
+                model.stmt_insert(),
+                params.join(", ")
+            ));
+        }
+        ret.push_str(&format!(
+            "  select: ({0}: {1}): Statement => ({{ query: \"{2}\", params: [{0}] }}),\n",
+            pk.name,
+            ts_field_type(pk),
+            model.stmt_select()
+        ));
+        if !model.has_lists() && model.non_pk_fields().next().is_some() {
+            let params: Vec<String> = model
+                .non_pk_fields()
+                .chain([pk])
+                .map(|f| format!("row.{}", f.name))
+                .collect();
+            ret.push_str(&format!(
+                "  update: (row: {ty}): Statement => ({{ query: \"{}\", params: [{}] }}),\n",
+                model.stmt_update(),
+                params.join(", ")
+            ));
+        }
+        ret.push_str(&format!(
+            "  delete: ({0}: {1}): Statement => ({{ query: \"{2}\", params: [{0}] }}),\n}};\n",
+            pk.name,
+            ts_field_type(pk),
+            model.stmt_delete()
+        ));
+    }
+    ret
+}
@@ -34,6 +34,8 @@ pub enum CliError {
     ArgsErr(String),
     ClientError(skytable::error::Error),
     IoError(std::io::Error),
+    CodegenError(String),
+    ImportError(String),
 }
 
 impl From<libsky::ArgParseError> for CliError {
@@ -68,6 +70,8 @@ impl fmt::Display for CliError {
             Self::ClientError(e) => write!(f, "client error. {e}"),
             Self::IoError(e) => write!(f, "i/o error. {e}"),
             Self::QueryError(e) => write!(f, "invalid query. {e}"),
+            Self::CodegenError(e) => write!(f, "codegen error. {e}"),
+            Self::ImportError(e) => write!(f, "import error. {e}"),
         }
     }
 }
@@ -0,0 +1,400 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    schema inference
+    ---
+    `skysh --infer <file> --model <space.model>` reads a sample of a JSON lines file (one object per line) and infers a
+    field for every key that it sees: the type comes from the values and the field is nullable if any sampled row has
+    it null or doesn't have it at all. integers widen to signed integers and then to floats as needed, arrays become
+    lists and nested objects are rejected. a field that only ever held nulls (or empty lists) is proposed as a string.
+    the primary key is `--primary-key`, or else `id`, or else the first string or integer field that every sampled
+    row has. the proposed `create model` statement is printed and once it is confirmed, the model is created and every
+    row in the file is inserted. rows past the sample that don't fit the schema stop the load at that line
+*/
+
+use {
+    crate::{
+        error::{CliError, CliResult},
+        json::Json,
+        query::{IsConnection, Item},
+    },
+    skytable::{response::Response, Query},
+    std::{
+        fs::File,
+        io::{self, BufRead, BufReader, Write},
+    },
+};
+
+/// The number of rows read to infer the schema, if not set with `--sample`
+const DEFAULT_SAMPLE: usize = 1000;
+
+#[derive(Debug)]
+pub struct Infer {
+    pub path: String,
+    /// `space.model`
+    pub entity: String,
+    pub primary_key: Option<String>,
+    pub sample: usize,
+}
+
+impl Infer {
+    pub fn new(
+        path: String,
+        entity: String,
+        primary_key: Option<String>,
+        sample: Option<String>,
+    ) -> CliResult<Self> {
+        if entity.split('.').count() != 2 {
+            return Err(CliError::ArgsErr(
+                "--model must be in the form `space.model`".into(),
+            ));
+        }
+        let sample = match sample {
+            Some(sample) => match sample.parse::<usize>() {
+                Ok(sample) if sample != 0 => sample,
+                _ => return Err(CliError::ArgsErr("invalid value for --sample".into())),
+            },
+            None => DEFAULT_SAMPLE,
+        };
+        Ok(Self {
+            path,
+            entity,
+            primary_key,
+            sample,
+        })
+    }
+    /// Read the sample and infer the schema
+    pub fn infer(&self) -> CliResult<Schema> {
+        let mut fields: Vec<Field> = vec![];
+        let mut sampled = 0;
+        for row in self.rows()?.take(self.sample) {
+            let (line, row) = row?;
+            let members = match &row {
+                Json::Object(members) => members,
+                _ => return Err(error(line, "expected an object")),
+            };
+            for (name, value) in members {
+                let ty = Ty::of(value).map_err(|e| error(line, &format!("`{name}` {e}")))?;
+                let null = matches!(value, Json::Null);
+                match fields.iter_mut().find(|f| f.name == *name) {
+                    Some(field) => {
+                        field.ty = field.ty.merge(&ty).ok_or_else(|| {
+                            error(line, &format!("`{name}` has values of different types"))
+                        })?;
+                        field.nullable |= null;
+                    }
+                    None => {
+                        if !is_ident(name) {
+                            return Err(error(line, &format!("`{name}` isn't a valid field name")));
+                        }
+                        fields.push(Field {
+                            name: name.clone(),
+                            ty,
+                            // earlier rows didn't have it
+                            nullable: null | (sampled != 0),
+                        })
+                    }
+                }
+            }
+            for field in fields.iter_mut() {
+                field.nullable |= row.get(&field.name).is_none();
+            }
+            sampled += 1;
+        }
+        if fields.is_empty() {
+            return Err(CliError::ImportError(format!(
+                "found no fields in `{}`",
+                self.path
+            )));
+        }
+        let primary_key = match &self.primary_key {
+            Some(pk) => match fields.iter().find(|f| f.name == *pk) {
+                Some(field) if field.can_be_primary_key() => pk.clone(),
+                Some(_) => {
+                    return Err(CliError::ImportError(format!(
+                        "`{pk}` can't be the primary key (it must be a string or an integer that every row has)"
+                    )))
+                }
+                None => {
+                    return Err(CliError::ImportError(format!(
+                        "`{pk}` isn't a field in the sample"
+                    )))
+                }
+            },
+            None => fields
+                .iter()
+                .find(|f| (f.name == "id") & f.can_be_primary_key())
+                .or_else(|| fields.iter().find(|f| f.can_be_primary_key()))
+                .map(|f| f.name.clone())
+                .ok_or_else(|| {
+                    CliError::ImportError(
+                        "no field can be the primary key (pick one with --primary-key)".into(),
+                    )
+                })?,
+        };
+        Ok(Schema {
+            entity: self.entity.clone(),
+            primary_key,
+            fields,
+            sampled,
+        })
+    }
+    /// Create the model and insert every row in the file, returning the number of rows inserted
+    pub fn load(&self, con: &mut impl IsConnection, schema: &Schema) -> CliResult<u64> {
+        run(con, Query::new(&schema.ddl()), "create model")?;
+        let mut loaded = 0;
+        for row in self.rows()? {
+            let (line, row) = row?;
+            let query = schema
+                .stmt_insert(&row)
+                .map_err(|e| error(line, &format!("{e}. {loaded} rows were inserted")))?;
+            run(
+                con,
+                query,
+                &format!("insert at line {line} (after {loaded} rows)"),
+            )?;
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+    /// Every non-empty line in the file along with its line number
+    fn rows(&self) -> CliResult<impl Iterator<Item = CliResult<(usize, Json)>>> {
+        let file = BufReader::new(File::open(&self.path)?);
+        Ok(file
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+            .map(|(i, line)| {
+                let json = Json::parse(&line?).ok_or_else(|| error(i + 1, "invalid JSON"))?;
+                Ok((i + 1, json))
+            }))
+    }
+}
+
+/// Ask to go ahead with the proposed schema
+pub fn confirm() -> CliResult<bool> {
+    print!("Create the model and load the file? [y/N] ");
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn error(line: usize, e: &str) -> CliError {
+    CliError::ImportError(format!("line {line}: {e}"))
+}
+
+fn run(con: &mut impl IsConnection, query: Query, what: &str) -> CliResult<()> {
+    match con.execute_query(query)? {
+        Response::Error(e) => Err(CliError::ImportError(format!(
+            "{what} failed with server error code: {e}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() | (c == '_'))
+        && chars.all(|c| c.is_ascii_alphanumeric() | (c == '_'))
+}
+
+/*
+    types
+*/
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    /// no values yet (only nulls or empty lists)
+    Unknown,
+    Bool,
+    UInt,
+    SInt,
+    Float,
+    String,
+    List(Box<Ty>),
+}
+
+impl Ty {
+    fn of(value: &Json) -> Result<Self, &'static str> {
+        Ok(match value {
+            Json::Null => Self::Unknown,
+            Json::Bool(_) => Self::Bool,
+            Json::Number(n) if n.parse::<u64>().is_ok() => Self::UInt,
+            Json::Number(n) if n.parse::<i64>().is_ok() => Self::SInt,
+            Json::Number(_) => Self::Float,
+            Json::String(_) => Self::String,
+            Json::Array(items) => {
+                let mut ty = Self::Unknown;
+                for item in items {
+                    if let Json::Null = item {
+                        return Err("has a null in a list");
+                    }
+                    ty = ty
+                        .merge(&Self::of(item)?)
+                        .ok_or("has a list with values of different types")?;
+                }
+                Self::List(Box::new(ty))
+            }
+            Json::Object(_) => return Err("is an object (nested objects aren't supported)"),
+        })
+    }
+    /// The type that can hold the values of both, if any
+    fn merge(&self, other: &Self) -> Option<Self> {
+        Some(match (self, other) {
+            (Self::Unknown, ty) | (ty, Self::Unknown) => ty.clone(),
+            (a, b) if a == b => a.clone(),
+            (Self::UInt, Self::SInt) | (Self::SInt, Self::UInt) => Self::SInt,
+            (Self::UInt | Self::SInt | Self::Float, Self::UInt | Self::SInt | Self::Float) => {
+                Self::Float
+            }
+            (Self::List(a), Self::List(b)) => Self::List(Box::new(a.merge(b)?)),
+            _ => return None,
+        })
+    }
+    fn ddl(&self) -> String {
+        match self {
+            Self::Unknown | Self::String => "string".into(),
+            Self::Bool => "bool".into(),
+            Self::UInt => "uint64".into(),
+            Self::SInt => "sint64".into(),
+            Self::Float => "float64".into(),
+            Self::List(ty) => format!("list {{ type: {} }}", ty.ddl()),
+        }
+    }
+    /// Append the value (as a parameter or a list of parameters) to the query
+    fn encode(&self, value: &Json, query: &mut String, params: &mut Vec<Item>) -> Option<()> {
+        let param = match (self, value) {
+            (Self::Bool, Json::Bool(b)) => Item::Bool(*b),
+            (Self::UInt, Json::Number(n)) => Item::UInt(n.parse().ok()?),
+            (Self::SInt, Json::Number(n)) => Item::SInt(n.parse().ok()?),
+            (Self::Float, Json::Number(n)) => Item::Float(n.parse().ok()?),
+            (Self::Unknown | Self::String, Json::String(s)) => Item::String(s.clone()),
+            (Self::List(ty), Json::Array(items)) => {
+                query.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i != 0 {
+                        query.push_str(", ");
+                    }
+                    ty.encode(item, query, params)?;
+                }
+                query.push(']');
+                return Some(());
+            }
+            _ => return None,
+        };
+        query.push('?');
+        params.push(param);
+        Some(())
+    }
+}
+
+/*
+    schema
+*/
+
+#[derive(Debug)]
+struct Field {
+    name: String,
+    ty: Ty,
+    nullable: bool,
+}
+
+impl Field {
+    fn can_be_primary_key(&self) -> bool {
+        !self.nullable & matches!(self.ty, Ty::String | Ty::UInt | Ty::SInt)
+    }
+}
+
+#[derive(Debug)]
+pub struct Schema {
+    entity: String,
+    primary_key: String,
+    fields: Vec<Field>,
+    /// the number of rows the schema was inferred from
+    pub sampled: usize,
+}
+
+impl Schema {
+    /// The proposed `create model` statement
+    pub fn ddl(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|f| {
+                let prefix = if f.name == self.primary_key {
+                    "primary "
+                } else if f.nullable {
+                    "null "
+                } else {
+                    ""
+                };
+                format!("{prefix}{}: {}", f.name, f.ty.ddl())
+            })
+            .collect();
+        format!("create model {}({})", self.entity, fields.join(", "))
+    }
+    fn stmt_insert(&self, row: &Json) -> Result<Query, String> {
+        if !matches!(row, Json::Object(_)) {
+            return Err("expected an object".into());
+        }
+        let mut values = vec![];
+        let mut params = vec![];
+        for field in &self.fields {
+            let mut value = String::new();
+            match row.get(&field.name) {
+                None | Some(Json::Null) if field.nullable => value.push_str("null"),
+                None | Some(Json::Null) => {
+                    return Err(format!("`{}` is missing but can't be null", field.name))
+                }
+                Some(v) => field.ty.encode(v, &mut value, &mut params).ok_or_else(|| {
+                    format!("`{}` doesn't fit the type `{}`", field.name, field.ty.ddl())
+                })?,
+            }
+            values.push(value);
+        }
+        if let Json::Object(members) = row {
+            if let Some((name, _)) = members
+                .iter()
+                .find(|(name, _)| !self.fields.iter().any(|f| f.name == *name))
+            {
+                return Err(format!("`{name}` isn't a field in the schema"));
+            }
+        }
+        let mut query = Query::new(&format!(
+            "insert into {}({})",
+            self.entity,
+            values.join(", ")
+        ));
+        for param in params {
+            query.push_param(param);
+        }
+        Ok(query)
+    }
+}
@@ -0,0 +1,133 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    json
+    ---
+    just enough JSON to read the descriptions returned by the server and the rows of a JSON lines file. numbers keep
+    their text so that integers and floats can be told apart
+*/
+
+use crate::resp;
+
+#[derive(Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    /// the number as it was written
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(s: &str) -> Option<Self> {
+        let (json, rest) = Self::read(s)?;
+        rest.trim().is_empty().then_some(json)
+    }
+    fn read(s: &str) -> Option<(Self, &str)> {
+        let s = s.trim_start();
+        match s.chars().next()? {
+            '"' => {
+                let (string, rest) = resp::read_json_str(&s[1..]);
+                Some((Self::String(string), rest))
+            }
+            '[' => {
+                let mut items = vec![];
+                let mut s = s[1..].trim_start();
+                if let Some(rest) = s.strip_prefix(']') {
+                    return Some((Self::Array(items), rest));
+                }
+                loop {
+                    let (item, rest) = Self::read(s)?;
+                    items.push(item);
+                    let rest = rest.trim_start();
+                    match rest.strip_prefix(',') {
+                        Some(rest) => s = rest,
+                        None => return Some((Self::Array(items), rest.strip_prefix(']')?)),
+                    }
+                }
+            }
+            '{' => {
+                let mut members = vec![];
+                let mut s = s[1..].trim_start();
+                if let Some(rest) = s.strip_prefix('}') {
+                    return Some((Self::Object(members), rest));
+                }
+                loop {
+                    let (key, rest) = resp::read_json_str(s.trim_start().strip_prefix('"')?);
+                    let (value, rest) = Self::read(rest.trim_start().strip_prefix(':')?)?;
+                    members.push((key, value));
+                    let rest = rest.trim_start();
+                    match rest.strip_prefix(',') {
+                        Some(rest) => s = rest,
+                        None => return Some((Self::Object(members), rest.strip_prefix('}')?)),
+                    }
+                }
+            }
+            _ => {
+                let end = s
+                    .find(|c: char| c.is_whitespace() || matches!(c, ',' | ']' | '}'))
+                    .unwrap_or(s.len());
+                let json = match &s[..end] {
+                    "true" => Self::Bool(true),
+                    "false" => Self::Bool(false),
+                    "null" => Self::Null,
+                    number => {
+                        number.parse::<f64>().ok()?;
+                        Self::Number(number.into())
+                    }
+                };
+                Some((json, &s[end..]))
+            }
+        }
+    }
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
@@ -32,7 +32,11 @@ macro_rules! fatal {
 }
 
 mod args;
+mod codegen;
 mod error;
+mod infer;
+mod json;
+mod profile;
 mod query;
 mod repl;
 mod resp;
@@ -60,6 +64,28 @@ fn run() -> error::CliResult<()> {
             )??;
             resp::format_response(resp, false, false);
         }
+        Task::Codegen(cfg, codegen) => {
+            let code = query::connect(
+                cfg,
+                false,
+                |mut c| codegen.run(&mut c),
+                |mut c| codegen.run(&mut c),
+            )?;
+            print!("{code}");
+        }
+        Task::Infer(cfg, infer) => {
+            let schema = infer.infer()?;
+            println!("Inferred from {} rows:\n{}", schema.sampled, schema.ddl());
+            if infer::confirm()? {
+                let loaded = query::connect(
+                    cfg,
+                    false,
+                    |mut c| infer.load(&mut c, &schema),
+                    |mut c| infer.load(&mut c, &schema),
+                )?;
+                println!("Loaded {loaded} rows into {}", infer.entity);
+            }
+        }
     }
     Ok(())
 }
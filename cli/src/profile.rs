@@ -0,0 +1,272 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    connection profiles
+    ---
+    a profile is a named set of connection options, kept in the profiles file:
+
+    [prod]
+    endpoint = tls@db.example.com:2004
+    tls-cert = /etc/skytable/cert.pem
+    user = admin
+    password = keyring
+
+    the options are the same as the command line options (which take precedence). a profile never holds a password;
+    instead, `password` says where to find it: `env:<VAR>` (an environment variable) or `keyring` (the OS keyring,
+    where it is saved once the password that was asked for is accepted by the server). the keyring can only be used if
+    skysh is built with the `keyring` feature
+*/
+
+use {
+    crate::error::{CliError, CliResult},
+    std::{collections::HashMap, env, fs, io::ErrorKind},
+};
+
+/// Environment variable that overrides the location of the profiles file
+const SKYSH_PROFILES: &str = "SKYSH_PROFILES";
+/// The options a profile can set
+const PROFILE_OPTIONS: [&str; 3] = ["endpoint", "tls-cert", "user"];
+
+#[derive(Debug, PartialEq)]
+pub enum PasswordSource {
+    /// ask for it (or use the usual environment variable)
+    Prompt,
+    /// the OS keyring
+    Keyring,
+    /// an environment variable
+    Env(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Profile {
+    name: String,
+    options: HashMap<String, String>,
+    password: PasswordSource,
+}
+
+impl Profile {
+    /// Load the profile from the profiles file
+    pub fn load(name: &str) -> CliResult<Self> {
+        let path = profiles_path()?;
+        let profiles = match fs::read_to_string(&path) {
+            Ok(p) => p,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(CliError::ArgsErr(format!(
+                    "no profiles file found at `{path}`"
+                )))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Self::parse(&profiles, name)?
+            .ok_or_else(|| CliError::ArgsErr(format!("no profile `{name}` in `{path}`")))
+    }
+    fn parse(profiles: &str, name: &str) -> CliResult<Option<Self>> {
+        let mut profile: Option<Self> = None;
+        let mut in_profile = false;
+        for (i, line) in profiles.lines().enumerate() {
+            let err =
+                |msg: &str| CliError::ArgsErr(format!("bad profiles file (line {}). {msg}", i + 1));
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[') {
+                let Some(section) = section.strip_suffix(']') else {
+                    return Err(err("unterminated profile name"));
+                };
+                in_profile = section.trim() == name;
+                if in_profile {
+                    if profile.is_some() {
+                        return Err(err(&format!("duplicate profile `{name}`")));
+                    }
+                    profile = Some(Self {
+                        name: name.into(),
+                        options: HashMap::new(),
+                        password: PasswordSource::Prompt,
+                    });
+                }
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(err("expected `option = value`"));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let Some(profile) = profile.as_mut().filter(|_| in_profile) else {
+                continue;
+            };
+            if key == "password" {
+                profile.password = match value.strip_prefix("env:") {
+                    Some(var) if !var.is_empty() => PasswordSource::Env(var.into()),
+                    _ if value == "keyring" => {
+                        if !cfg!(feature = "keyring") {
+                            return Err(err(
+                                "this skysh was built without the `keyring` feature. use `password = env:<VAR>`",
+                            ));
+                        }
+                        PasswordSource::Keyring
+                    }
+                    _ => {
+                        return Err(err(
+                            "profiles can't hold a password. use `password = env:<VAR>` or `password = keyring`",
+                        ))
+                    }
+                };
+            } else if PROFILE_OPTIONS.contains(&key) {
+                if profile.options.insert(key.into(), value.into()).is_some() {
+                    return Err(err(&format!("duplicate option `{key}`")));
+                }
+            } else {
+                return Err(err(&format!("unknown option `{key}`")));
+            }
+        }
+        Ok(profile)
+    }
+    /// Returns the options set by this profile, keyed by their command line names
+    pub fn into_args(self) -> (impl Iterator<Item = (String, String)>, Credentials) {
+        let credentials = Credentials {
+            profile: self.name,
+            source: self.password,
+        };
+        let args = self
+            .options
+            .into_iter()
+            .map(|(option, value)| (format!("--{option}"), value));
+        (args, credentials)
+    }
+}
+
+/// Where a profile's password comes from
+pub struct Credentials {
+    profile: String,
+    source: PasswordSource,
+}
+
+impl Credentials {
+    /// Returns the password, if the profile says where to find it. If the password isn't in the keyring yet,
+    /// [`None`] is returned (and the password that is used should be saved with [`Credentials::save`] once it works)
+    pub fn password(&self, user: &str) -> CliResult<Option<String>> {
+        match &self.source {
+            PasswordSource::Prompt => Ok(None),
+            PasswordSource::Env(var) => match env::var(var) {
+                Ok(password) => Ok(Some(password)),
+                Err(_) => Err(CliError::ArgsErr(format!(
+                    "profile `{}` reads the password from `{var}`, but it isn't set",
+                    self.profile
+                ))),
+            },
+            PasswordSource::Keyring => os_keyring::get(&self.keyring_user(user)),
+        }
+    }
+    /// Returns true if the password is kept in the keyring
+    pub fn uses_keyring(&self) -> bool {
+        self.source == PasswordSource::Keyring
+    }
+    /// Save the password in the keyring
+    pub fn save(&self, user: &str, password: &str) {
+        match os_keyring::set(&self.keyring_user(user), password) {
+            Ok(()) => println!(
+                "saved the password for '{user}' (profile `{}`) in the OS keyring",
+                self.profile
+            ),
+            Err(e) => eprintln!("[skysh warning]: {e}"),
+        }
+    }
+    /// The name that the password is saved under in the keyring
+    fn keyring_user(&self, user: &str) -> String {
+        format!("{user}@{}", self.profile)
+    }
+}
+
+#[cfg(feature = "keyring")]
+mod os_keyring {
+    use crate::error::{CliError, CliResult};
+
+    /// The service under which passwords are saved in the OS keyring
+    const KEYRING_SERVICE: &str = "skysh";
+
+    /// Returns the password saved for the user, if there is one
+    pub fn get(user: &str) -> CliResult<Option<String>> {
+        match entry(user)?.get_password() {
+            Ok(password) => Ok(Some(password)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(CliError::ArgsErr(format!(
+                "failed to read password from the OS keyring. {e}"
+            ))),
+        }
+    }
+
+    pub fn set(user: &str, password: &str) -> CliResult<()> {
+        entry(user)?.set_password(password).map_err(keyring_err)
+    }
+
+    fn entry(user: &str) -> CliResult<keyring::Entry> {
+        keyring::Entry::new(KEYRING_SERVICE, user).map_err(keyring_err)
+    }
+
+    fn keyring_err(e: keyring::Error) -> CliError {
+        CliError::ArgsErr(format!("failed to access the OS keyring. {e}"))
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+mod os_keyring {
+    //! without the `keyring` feature, profiles can't ask for the keyring (see `Profile::parse`)
+
+    use crate::error::{CliError, CliResult};
+
+    pub fn get(_: &str) -> CliResult<Option<String>> {
+        Err(unavailable())
+    }
+
+    pub fn set(_: &str, _: &str) -> CliResult<()> {
+        Err(unavailable())
+    }
+
+    fn unavailable() -> CliError {
+        CliError::ArgsErr("this skysh was built without the `keyring` feature".into())
+    }
+}
+
+/// Returns the path of the profiles file
+fn profiles_path() -> CliResult<String> {
+    if let Ok(path) = env::var(SKYSH_PROFILES) {
+        return Ok(path);
+    }
+    let config_dir = if cfg!(windows) {
+        env::var("APPDATA").ok()
+    } else {
+        env::var("XDG_CONFIG_HOME")
+            .ok()
+            .or_else(|| env::var("HOME").ok().map(|home| format!("{home}/.config")))
+    };
+    match config_dir {
+        Some(dir) => Ok(format!("{dir}/skysh/profiles")),
+        None => Err(CliError::ArgsErr(format!(
+            "could not find the profiles file. set `{SKYSH_PROFILES}` to its path"
+        ))),
+    }
+}
@@ -41,9 +41,10 @@ pub fn connect<T>(
     tcp_f: impl Fn(Connection) -> CliResult<T>,
     tls_f: impl Fn(ConnectionTls) -> CliResult<T>,
 ) -> CliResult<T> {
-    match cfg.kind {
+    match &cfg.kind {
         ClientConfigKind::Tcp(host, port) => {
-            let c = Config::new(&host, port, &cfg.username, &cfg.password).connect()?;
+            let c = Config::new(host, *port, &cfg.username, &cfg.password).connect()?;
+            cfg.authenticated();
             if print_con_info {
                 println!(
                     "Authenticated as '{}' on {}:{} over Skyhash/TCP\n---",
@@ -53,7 +54,8 @@ pub fn connect<T>(
             tcp_f(c)
         }
         ClientConfigKind::Tls(host, port, cert) => {
-            let c = Config::new(&host, port, &cfg.username, &cfg.password).connect_tls(&cert)?;
+            let c = Config::new(host, *port, &cfg.username, &cfg.password).connect_tls(cert)?;
+            cfg.authenticated();
             if print_con_info {
                 println!(
                     "Authenticated as '{}' on {}:{} over Skyhash/TLS\n---",
@@ -82,7 +84,8 @@ impl IsConnection for ConnectionTls {
 }
 
 #[derive(Debug, PartialEq)]
-enum Item {
+pub enum Item {
+    Bool(bool),
     UInt(u64),
     SInt(i64),
     Float(f64),
@@ -93,6 +96,7 @@ enum Item {
 impl SQParam for Item {
     fn append_param(&self, buf: &mut Vec<u8>) -> usize {
         match self {
+            Item::Bool(b) => b.append_param(buf),
             Item::UInt(u) => u.append_param(buf),
             Item::SInt(s) => s.append_param(buf),
             Item::Float(f) => f.append_param(buf),
@@ -129,6 +133,11 @@ impl Parameterizer {
     pub fn parameterize(mut self) -> CliResult<ExecKind> {
         while self.not_eof() {
             match self.buf[self.i] {
+                b'x' | b'X' if matches!(self.buf.get(self.i + 1), Some(b'"' | b'\'')) => {
+                    let quote_style = self.buf[self.i + 1];
+                    self.i += 2;
+                    self.read_hex(quote_style)
+                }
                 b if b.is_ascii_alphabetic() || b == b'_' => self.read_ident(),
                 b if b.is_ascii_digit() => self.read_unsigned_integer(),
                 b'-' => self.read_signed_integer(),
@@ -311,19 +320,67 @@ impl Parameterizer {
     }
     fn read_binary(&mut self) -> CliResult<()> {
         self.query.push(b'?');
-        let start = self.i;
+        let mut bin = Vec::new();
         while self.not_eof() {
             let b = self.buf[self.i];
             self.i += 1;
-            if b == b'`' {
-                self.params
-                    .push(Item::Bin(self.buf[start..self.i].to_vec()));
-                return Ok(());
+            match b {
+                b'`' => {
+                    self.params.push(Item::Bin(bin));
+                    return Ok(());
+                }
+                b'\\' => match self.buf.get(self.i) {
+                    Some(b @ (b'\\' | b'`')) => {
+                        bin.push(*b);
+                        self.i += 1;
+                    }
+                    Some(b'x') => {
+                        // a single escaped byte, like `\xff`
+                        match self.buf.get(self.i + 1..self.i + 3).and_then(decode_hex) {
+                            Some(byte) => bin.extend(byte),
+                            None => return Err(CliError::QueryError("invalid byte escape".into())),
+                        }
+                        self.i += 3;
+                    }
+                    _ => return Err(CliError::QueryError("unknown escape sequence".into())),
+                },
+                b => bin.push(b),
             }
         }
         Err(CliError::QueryError("binary literal not terminated".into()))
     }
+    fn read_hex(&mut self, quote_style: u8) -> CliResult<()> {
+        self.query.push(b'?');
+        let start = self.i;
+        while self.not_eof() {
+            let b = self.buf[self.i];
+            self.i += 1;
+            if b == quote_style {
+                return match decode_hex(&self.buf[start..self.i - 1]) {
+                    Some(bin) => {
+                        self.params.push(Item::Bin(bin));
+                        Ok(())
+                    }
+                    None => Err(CliError::QueryError("invalid hex literal".into())),
+                };
+            }
+        }
+        Err(CliError::QueryError("hex literal not terminated".into()))
+    }
     fn not_eof(&self) -> bool {
         self.i < self.buf.len()
     }
 }
+
+/// Decode a string of hex digit pairs (like `ff00`)
+fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    fn digit(d: u8) -> Option<u8> {
+        (d as char).to_digit(16).map(|d| d as u8)
+    }
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.chunks(2)
+        .map(|pair| Some((digit(pair[0])? << 4) | digit(pair[1])?))
+        .collect()
+}
@@ -33,6 +33,10 @@ use {
     },
     crossterm::{cursor, execute, terminal},
     rustyline::{config::Configurer, error::ReadlineError, DefaultEditor},
+    skytable::{
+        response::{Response, Value},
+        Query,
+    },
     std::io::{stdout, ErrorKind},
 };
 
@@ -74,12 +78,20 @@ fn repl<C: IsConnection>(mut con: C) -> CliResult<()> {
         Err(e) => fatal!("error: failed to init REPL. {e}"),
     };
     let mut prompt = "> ".to_owned();
+    let mut timing = false;
     loop {
         match editor.readline(&prompt) {
             Ok(line) => match line.as_str() {
                 "!help" => println!("{TXT_WELCOME}"),
                 "exit" => break,
                 "clear" => clear_screen()?,
+                "\\timing" | "\\timing on" | "\\timing off" => {
+                    timing = match line.strip_prefix("\\timing ") {
+                        Some(toggle) => toggle == "on",
+                        None => !timing,
+                    };
+                    println!("Timing is {}.", if timing { "on" } else { "off" });
+                }
                 _ => {
                     if line.is_empty() {
                         continue;
@@ -103,11 +115,15 @@ fn repl<C: IsConnection>(mut con: C) -> CliResult<()> {
                                     q
                                 }
                             };
-                            if resp::format_response(con.execute_query(q)?, special, true) {
+                            let okay = resp::format_response(con.execute_query(q)?, special, true);
+                            if okay {
                                 if let Some(pr) = new_prompt {
                                     prompt = pr;
                                 }
                             }
+                            if okay | timing {
+                                print_report(&mut con, timing)?;
+                            }
                         }
                         Err(e) => match e {
                             CliError::QueryError(e) => {
@@ -136,6 +152,24 @@ fn repl<C: IsConnection>(mut con: C) -> CliResult<()> {
     Ok(())
 }
 
+/// Print the notices that the server raised for the last statement, and its stats if `timing` is on. Servers that
+/// can't report on the last statement are only warned about if `timing` is on
+fn print_report<C: IsConnection>(con: &mut C, timing: bool) -> CliResult<()> {
+    match con.execute_query(Query::new("sysctl report last"))? {
+        Response::Value(Value::String(report)) => {
+            resp::format_notices(&report, true);
+            if timing {
+                resp::format_stats(&report, true);
+            }
+        }
+        _ if timing => {
+            eprintln!("[skysh warning]: the server didn't report stats for this statement")
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 fn clear_screen() -> std::io::Result<()> {
     let mut stdout = stdout();
     execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
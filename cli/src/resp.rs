@@ -74,6 +74,76 @@ pub fn format_response(resp: Response, print_special: bool, pretty_format: bool)
     true
 }
 
+/// Print the notices (as reported by `sysctl report last`) for a statement
+pub fn format_notices(report: &str, pretty_format: bool) {
+    let Some((_, mut notices)) = report.split_once("\"notices\":[") else {
+        return;
+    };
+    while let Some((_, rest)) = notices.split_once("\"message\":\"") {
+        let (message, rest) = read_json_str(rest);
+        let message = format!("(notice: {message})");
+        if pretty_format {
+            println!("{}", message.yellow());
+        } else {
+            println!("{message}");
+        }
+        notices = rest;
+    }
+}
+
+/// Read the rest of a JSON string (after the opening quote), returning the string and whatever follows it
+pub fn read_json_str(s: &str) -> (String, &str) {
+    let mut out = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return (out, &s[i + 1..]),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                    out.extend(u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32));
+                }
+                Some(c) => out.push(c),
+                None => break,
+            },
+            c => out.push(c),
+        }
+    }
+    (out, "")
+}
+
+/// Print the stats (as reported by `sysctl report last`) for a statement
+pub fn format_stats(report: &str, pretty_format: bool) {
+    let stat = |key: &str| {
+        let (_, v) = report.split_once(&format!("\"{key}\":"))?;
+        v.split(|c: char| !c.is_ascii_digit())
+            .next()?
+            .parse::<u64>()
+            .ok()
+    };
+    let mut stats = vec![];
+    match (stat("rows_returned"), stat("rows_affected")) {
+        (_, Some(rows)) => stats.push(format!("{rows} row(s) affected")),
+        (Some(rows), None) => stats.push(format!("{rows} row(s) returned")),
+        (None, None) => {}
+    }
+    if let Some(us) = stat("exec_us") {
+        stats.push(format!("{:.3} ms on server", us as f64 / 1000.0));
+    }
+    if let (Some(sent), Some(received)) = (stat("bytes_in"), stat("bytes_out")) {
+        stats.push(format!("{sent} bytes sent, {received} bytes received"));
+    }
+    let stats = format!("[{}]", stats.join("; "));
+    if pretty_format {
+        println!("{}", stats.grey().italic());
+    } else {
+        println!("{stats}");
+    }
+}
+
 fn print_row(r: Row, pretty_format: bool) {
     print!("(");
     let mut columns = r.into_values().into_iter().peekable();
@@ -90,7 +160,7 @@ fn print_value(v: Value, print_special: bool, pretty_format: bool) {
     match v {
         Value::Null => pprint!(pretty_format, "null".grey().italic()),
         Value::String(s) => print_string(&s, print_special, pretty_format),
-        Value::Binary(b) => print_binary(&b),
+        Value::Binary(b) => print_binary(&b, pretty_format),
         Value::Bool(b) => print!("{b}"),
         Value::UInt8(i) => print!("{i}"),
         Value::UInt16(i) => print!("{i}"),
@@ -116,16 +186,35 @@ fn print_value(v: Value, print_special: bool, pretty_format: bool) {
     }
 }
 
-fn print_binary(b: &[u8]) {
-    let mut it = b.into_iter().peekable();
-    print!("[");
-    while let Some(byte) = it.next() {
-        print!("{byte}");
-        if it.peek().is_some() {
-            print!(", ");
+/// Print a binary value as a hex literal (that can be used in a query) in the shell, or as base64 otherwise
+fn print_binary(b: &[u8], pretty_format: bool) {
+    if pretty_format {
+        print!("x'");
+        b.iter().for_each(|byte| print!("{byte:02x}"));
+        print!("'");
+    } else {
+        print!("{}", encode_base64(b));
+    }
+}
+
+/// Encode bytes as (padded) standard base64
+fn encode_base64(b: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(b.len().div_ceil(3) * 4);
+    for chunk in b.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | ((*byte as u32) << (16 - i * 8)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
         }
     }
-    print!("]");
+    out
 }
 
 fn print_string(s: &str, print_special: bool, pretty_format: bool) {
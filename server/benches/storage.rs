@@ -0,0 +1,106 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    storage benchmarks: encoding and decoding with the persist object and map specs, and writing and recovering a
+    batch of the model journal, for a narrow and a wide model shape. see CONTRIBUTING.md for how to run these
+*/
+
+use {
+    criterion::{criterion_group, criterion_main, Criterion, Throughput},
+    skyd::bench::{
+        encoding::{self, Dict, EncodedRow, ModelShape, Row},
+        journal::{self, Batch},
+    },
+    std::{env, hint::black_box, process},
+};
+
+fn model(c: &mut Criterion) {
+    for shape in ModelShape::ALL {
+        let model = shape.model();
+        c.bench_function(format!("model_enc_{}", shape.name()), |b| {
+            b.iter(|| encoding::encode_model(black_box(&model)))
+        });
+        let encoded = encoding::encode_model(&model);
+        let mut group = c.benchmark_group("model_dec");
+        group.throughput(Throughput::Bytes(encoded.len() as u64));
+        group.bench_function(shape.name(), |b| {
+            b.iter(|| encoding::decode_model(black_box(&encoded)))
+        });
+        group.finish();
+    }
+    let model = ModelShape::Wide.model();
+    c.bench_function("fieldmap_enc_dec_wide", |b| {
+        b.iter(|| encoding::encode_decode_field_map(black_box(&model)))
+    });
+}
+
+fn dict(c: &mut Criterion) {
+    let dict = Dict::nested();
+    c.bench_function("dict_enc_dec", |b| {
+        b.iter(|| encoding::encode_decode_dict(black_box(&dict)))
+    });
+}
+
+fn row(c: &mut Criterion) {
+    let row = Row::wide();
+    let mut buf = vec![];
+    c.bench_function("row_enc_wide", |b| {
+        b.iter(|| encoding::encode_row(black_box(&row), &mut buf))
+    });
+    let encoded = EncodedRow::new(&row);
+    let mut group = c.benchmark_group("row_dec");
+    group.throughput(Throughput::Bytes(encoded.size() as u64));
+    group.bench_function("wide", |b| {
+        b.iter(|| encoding::decode_row(black_box(&encoded)))
+    });
+    group.finish();
+}
+
+fn model_journal(c: &mut Criterion) {
+    for shape in ModelShape::ALL {
+        let batch = Batch::new(shape);
+        let path = env::temp_dir().join(format!("skyd-bench-{}-{}", process::id(), shape.name()));
+        let path = path.to_str().unwrap();
+        let mut group = c.benchmark_group("journal");
+        group.throughput(Throughput::Elements(journal::BATCH_SIZE as u64));
+        group.bench_function(format!("write_batch_{}", shape.name()), |b| {
+            b.iter(|| {
+                journal::write(&batch, path);
+                journal::remove(&batch, path);
+            })
+        });
+        journal::write(&batch, path);
+        group.bench_function(format!("recover_{}", shape.name()), |b| {
+            b.iter(|| journal::recover(&batch, path))
+        });
+        journal::remove(&batch, path);
+        group.finish();
+    }
+}
+
+criterion_group!(benches, model, dict, row, model_journal);
+criterion_main!(benches);
@@ -28,7 +28,7 @@ use {
     crate::engine::{error::RuntimeResult, fractal},
     core::fmt,
     serde::Deserialize,
-    std::{collections::HashMap, fs},
+    std::{collections::HashMap, fs, net::IpAddr},
 };
 
 /*
@@ -37,6 +37,8 @@ use {
 
 pub type ParsedRawArgs = std::collections::HashMap<String, Vec<String>>;
 pub const ROOT_PASSWORD_MIN_LEN: usize = 16;
+/// What credentials are shown as when a configuration is debug-printed
+const REDACTED: &str = "<redacted>";
 
 #[derive(Debug, PartialEq)]
 pub struct ModifyGuard<T> {
@@ -97,11 +99,17 @@ impl Configuration {
     }
     const DEFAULT_HOST: &'static str = "127.0.0.1";
     const DEFAULT_PORT_TCP: u16 = 2003;
+    /// Returns a hash that tells configurations apart (crash reports carry it). It's computed over the debug output,
+    /// which leaves credentials out
+    pub fn fingerprint(&self) -> u64 {
+        crc::Crc::<u64>::new(&crc::CRC_64_XZ).checksum(format!("{self:?}").as_bytes())
+    }
     pub fn default_dev_mode(auth: DecodedAuth) -> Self {
         Self {
             endpoints: ConfigEndpoint::Insecure(ConfigEndpointTcp {
                 host: Self::DEFAULT_HOST.to_owned(),
                 port: Self::DEFAULT_PORT_TCP,
+                keepalive: ConfigKeepalive::default(),
             }),
             mode: ConfigMode::Dev,
             system: ConfigSystem::new(fractal::GENERAL_EXECUTOR_WINDOW),
@@ -125,12 +133,21 @@ pub enum ConfigEndpoint {
 pub struct ConfigEndpointTcp {
     host: String,
     port: u16,
+    keepalive: ConfigKeepalive,
 }
 
 impl ConfigEndpointTcp {
     #[cfg(test)]
     pub fn new(host: String, port: u16) -> Self {
-        Self { host, port }
+        Self {
+            host,
+            port,
+            keepalive: ConfigKeepalive::default(),
+        }
+    }
+    #[cfg(test)]
+    pub fn with_keepalive(self, keepalive: ConfigKeepalive) -> Self {
+        Self { keepalive, ..self }
     }
     pub fn host(&self) -> &str {
         self.host.as_ref()
@@ -138,9 +155,22 @@ impl ConfigEndpointTcp {
     pub fn port(&self) -> u16 {
         self.port
     }
+    pub fn keepalive(&self) -> ConfigKeepalive {
+        self.keepalive
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+/// How idle connections on an endpoint are handled
+pub struct ConfigKeepalive {
+    /// if set, TCP keepalive probes are sent once a connection has been idle for this many seconds, so that dead
+    /// peers are noticed
+    pub probe_after: Option<u64>,
+    /// if set, connections that haven't sent anything for this many seconds are closed
+    pub idle_timeout: Option<u64>,
+}
+
+#[derive(PartialEq)]
 /// TLS endpoint configuration
 pub struct ConfigEndpointTls {
     pub tcp: ConfigEndpointTcp,
@@ -149,6 +179,17 @@ pub struct ConfigEndpointTls {
     pkey_pass: String,
 }
 
+impl fmt::Debug for ConfigEndpointTls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigEndpointTls")
+            .field("tcp", &self.tcp)
+            .field("cert", &self.cert)
+            .field("private_key", &REDACTED)
+            .field("pkey_pass", &REDACTED)
+            .finish()
+    }
+}
+
 impl ConfigEndpointTls {
     #[cfg(test)]
     pub fn new(
@@ -193,6 +234,61 @@ pub enum ConfigMode {
     Prod,
 }
 
+/*
+    config runtime
+*/
+
+#[derive(Debug, PartialEq, Deserialize, Clone, Copy)]
+/// The runtime used to serve connections
+pub enum ConfigRuntime {
+    /// All connections are served by a single work-stealing thread pool
+    #[serde(rename = "multithreaded")]
+    MultiThreaded,
+    /// Each connection is pinned to one of several single-threaded shards (one per core)
+    #[serde(rename = "sharded")]
+    Sharded,
+}
+
+/*
+    config huge pages
+*/
+
+#[derive(Debug, PartialEq, Deserialize, Clone, Copy)]
+/// How (and if) huge pages are used for index and row memory
+pub enum ConfigHugePages {
+    /// Regular pages only
+    #[serde(rename = "off")]
+    Off,
+    /// Transparent huge pages (the kernel backs our memory with huge pages when it can)
+    #[serde(rename = "transparent")]
+    Transparent,
+    /// Explicit huge pages from the reserved pool (`vm.nr_hugepages`), falling back to transparent huge pages
+    #[serde(rename = "explicit")]
+    Explicit,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone, Copy)]
+/// The storage driver that an instance keeps its data with
+pub enum ConfigStorage {
+    /// The on-disk storage engine (SDSS)
+    #[serde(rename = "sdss")]
+    Sdss,
+    /// Nothing is kept once the server stops
+    #[serde(rename = "memory")]
+    Memory,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone, Copy)]
+/// What happens to writes to a model while its changes can't be written to disk
+pub enum ConfigFlushFailure {
+    /// Reject writes until the model's changes are written again
+    #[serde(rename = "block")]
+    Block,
+    /// Keep accepting writes until the model has too many changes waiting to be written
+    #[serde(rename = "accumulate")]
+    Accumulate,
+}
+
 /*
     config system
 */
@@ -202,16 +298,217 @@ pub enum ConfigMode {
 pub struct ConfigSystem {
     /// time window in seconds for the reliability system to kick-in automatically
     pub reliability_system_window: u64,
+    /// the maximum (approximate) memory in bytes that a single query can use for its intermediate state
+    pub query_memory_limit: u64,
+    /// once the process uses this much memory (in bytes), expensive queries are rejected (0 disables this)
+    pub memory_high_watermark: u64,
+    /// once the process is back below this much memory (in bytes), expensive queries are admitted again
+    pub memory_low_watermark: u64,
+    /// the runtime used to serve connections
+    pub runtime: ConfigRuntime,
+    /// if set, shards are placed and allocate memory with the NUMA topology in mind (sharded runtime only)
+    pub numa_aware: bool,
+    /// the huge pages mode for index and row memory
+    pub huge_pages: ConfigHugePages,
+    /// the storage driver
+    pub storage: ConfigStorage,
+    /// the directory that holds the global journal (the working directory if not set)
+    pub journal_volume: Option<String>,
+    /// the directory that holds model batch files and copies made of them (the working directory if not set)
+    pub batch_volume: Option<String>,
+    /// the directory that journals are archived to as they are committed (not archived if not set)
+    pub archive_dir: Option<String>,
+    /// the directory that crash reports are written to (`crash` in the working directory if not set)
+    pub crash_dir: Option<String>,
+    /// the endpoint on which liveness and readiness probes are served over HTTP (disabled if not set)
+    pub probe_endpoint: Option<ConfigEndpointTcp>,
+    /// if set, anything in the data directory that doesn't belong to a space or model is quarantined on startup
+    pub repair: bool,
+    /// if set, the hottest rows of the last run are warmed on startup, before the server reports that it's ready
+    pub warmup: bool,
+    /// what happens to writes to a model while its changes can't be written to disk
+    pub flush_failure: ConfigFlushFailure,
+    /// the number of changes a model can hold while they can't be written to disk (only for
+    /// [`ConfigFlushFailure::Accumulate`])
+    pub flush_failure_cap: u64,
+    /// the units of maintenance (changes flushed or rows scanned) admitted per second while queries are running (0
+    /// disables throttling)
+    pub maintenance_rate: u64,
+    /// the longest (in milliseconds) that a statement waits for a row lock before it fails (0 waits for as long as it
+    /// takes)
+    pub lock_wait_timeout: u64,
+    /// the longest (in milliseconds) that a statement can run before it fails (0 doesn't limit statements). sessions
+    /// can only shorten it
+    pub statement_timeout: u64,
+    /// if set, sessions can relax the durability of the models that they write to (with `set durability = relaxed`)
+    pub relaxed_sessions: bool,
+    /// if set, the names of spaces, models and fields are matched without regard to case (but keep the case that
+    /// they were created with)
+    pub case_insensitive_names: bool,
+    /// once a model has this many changes that are yet to be written to disk, writes to it are delayed (and
+    /// rejected at twice as many). 0 disables this
+    pub throttle_backlog: u64,
+    /// once a webhook of a model is this many journal bytes behind, writes to the model are delayed (and rejected
+    /// at twice as many). 0 disables this
+    pub throttle_lag: u64,
+    /// the largest response (in bytes) that a single statement can return (0 doesn't limit responses)
+    pub max_response_size: u64,
+    /// the bytes of responses that a connection can be sent in every window (0 doesn't limit connections)
+    pub response_quota: u64,
+    /// the length of the window (in seconds) that the response quota is counted over
+    pub response_quota_window: u64,
+    /// the largest value (in bytes) that a client can upload in chunks (see the protocol's blob frames)
+    pub blob_max_size: u64,
+    /// the number of rows that scans read ahead of the row that they're at, prefetching their data (0 disables this)
+    pub scan_prefetch: u64,
+    /// the bucket that large binary values are moved to (disabled if not set)
+    pub blob_tier: Option<ConfigBlobTier>,
+    /// the instance that models with `upstream: true` cache (disabled if not set)
+    pub upstream: Option<ConfigUpstream>,
+    /// the shard map that drivers fetch to shard keys across instances (see `sysctl report shardmap`)
+    pub shardmap: Option<ConfigShardMap>,
+    /// the OTLP/HTTP collector that trace spans are exported to (tracing is disabled if not set)
+    pub otlp_endpoint: Option<ConfigEndpointTcp>,
+    /// the percentage of traces that are recorded (and exported)
+    pub trace_sample_rate: u8,
+    /// the percentage of a limit past which statements are warned about (0 never warns)
+    pub soft_limit: u8,
 }
 
 impl ConfigSystem {
+    /// The default query memory limit (256 MiB)
+    pub const DEFAULT_QUERY_MEMORY_LIMIT: u64 = 256 * 1024 * 1024;
+    /// The default number of changes a model can hold while they can't be written to disk
+    pub const DEFAULT_FLUSH_FAILURE_CAP: u64 = 1_000_000;
+    /// By default, every trace is recorded
+    pub const DEFAULT_TRACE_SAMPLE_RATE: u8 = 100;
+    /// By default, statements are warned about once they use 80% of a limit
+    pub const DEFAULT_SOFT_LIMIT: u8 = 80;
+    /// The default largest value that can be uploaded in chunks (1 GiB)
+    pub const DEFAULT_BLOB_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+    /// By default, the response quota is counted over a minute
+    pub const DEFAULT_RESPONSE_QUOTA_WINDOW: u64 = 60;
+    /// By default, scans read 8 rows ahead
+    pub const DEFAULT_SCAN_PREFETCH: u64 = 8;
+    /// The most rows that scans can read ahead
+    pub const MAX_SCAN_PREFETCH: u64 = 256;
     pub fn new(reliability_system_window: u64) -> Self {
         Self {
             reliability_system_window,
+            query_memory_limit: Self::DEFAULT_QUERY_MEMORY_LIMIT,
+            memory_high_watermark: 0,
+            memory_low_watermark: 0,
+            runtime: ConfigRuntime::MultiThreaded,
+            numa_aware: false,
+            huge_pages: ConfigHugePages::Off,
+            storage: ConfigStorage::Sdss,
+            journal_volume: None,
+            batch_volume: None,
+            archive_dir: None,
+            crash_dir: None,
+            probe_endpoint: None,
+            repair: false,
+            warmup: false,
+            flush_failure: ConfigFlushFailure::Block,
+            flush_failure_cap: Self::DEFAULT_FLUSH_FAILURE_CAP,
+            maintenance_rate: 0,
+            lock_wait_timeout: 0,
+            statement_timeout: 0,
+            relaxed_sessions: false,
+            case_insensitive_names: false,
+            throttle_backlog: 0,
+            throttle_lag: 0,
+            max_response_size: 0,
+            response_quota: 0,
+            response_quota_window: Self::DEFAULT_RESPONSE_QUOTA_WINDOW,
+            blob_max_size: Self::DEFAULT_BLOB_MAX_SIZE,
+            scan_prefetch: Self::DEFAULT_SCAN_PREFETCH,
+            blob_tier: None,
+            upstream: None,
+            shardmap: None,
+            otlp_endpoint: None,
+            trace_sample_rate: Self::DEFAULT_TRACE_SAMPLE_RATE,
+            soft_limit: Self::DEFAULT_SOFT_LIMIT,
         }
     }
 }
 
+#[derive(PartialEq, Clone)]
+/// An S3-compatible bucket that binary values larger than the threshold are moved to
+pub struct ConfigBlobTier {
+    /// the endpoint that the bucket is served on
+    pub endpoint: ConfigEndpointTcp,
+    /// if set, requests are sent over HTTPS (this can only be unset for a bucket on the loopback interface)
+    pub tls: bool,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// binary values larger than this (in bytes) are moved to the bucket
+    pub threshold: u64,
+}
+
+impl fmt::Debug for ConfigBlobTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigBlobTier")
+            .field("endpoint", &self.endpoint)
+            .field("tls", &self.tls)
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &REDACTED)
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+impl ConfigBlobTier {
+    /// The default region that requests are signed for
+    pub const DEFAULT_REGION: &'static str = "us-east-1";
+    /// By default, binary values larger than 1 MiB are moved to the bucket
+    pub const DEFAULT_THRESHOLD: u64 = 1024 * 1024;
+}
+
+#[derive(PartialEq, Clone)]
+/// An upstream Skytable instance that models with `upstream: true` cache (and forward their writes to)
+pub struct ConfigUpstream {
+    /// the endpoint that the instance is served on (plain TCP)
+    pub endpoint: ConfigEndpointTcp,
+    pub username: String,
+    pub password: String,
+}
+
+impl fmt::Debug for ConfigUpstream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigUpstream")
+            .field("endpoint", &self.endpoint)
+            .field("username", &self.username)
+            .field("password", &REDACTED)
+            .finish()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// A static shard map: the slots (see [`ConfigShardMap::SLOTS`]) that each of the instances that keys are sharded
+/// across owns. Every slot is owned by exactly one node
+pub struct ConfigShardMap {
+    pub nodes: Vec<ConfigShardNode>,
+}
+
+impl ConfigShardMap {
+    /// The number of slots that keys are hashed into
+    pub const SLOTS: u32 = 1 << 16;
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// An instance in the shard map
+pub struct ConfigShardNode {
+    pub name: String,
+    pub endpoint: ConfigEndpointTcp,
+    /// the (inclusive) ranges of slots that the node owns
+    pub ranges: Vec<(u16, u16)>,
+}
+
 /*
     config auth
 */
@@ -222,12 +519,21 @@ pub enum AuthDriver {
     Pwd,
 }
 
-#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[derive(PartialEq, Deserialize, Clone)]
 pub struct ConfigAuth {
     pub plugin: AuthDriver,
     pub root_key: String,
 }
 
+impl fmt::Debug for ConfigAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigAuth")
+            .field("plugin", &self.plugin)
+            .field("root_key", &REDACTED)
+            .finish()
+    }
+}
+
 impl ConfigAuth {
     pub fn new(plugin: AuthDriver, root_key: String) -> Self {
         Self { plugin, root_key }
@@ -262,11 +568,51 @@ pub struct DecodedAuth {
     root_pass: String,
 }
 
-#[derive(Debug, PartialEq, Deserialize)]
+#[derive(Debug, PartialEq, Deserialize, Default)]
 /// Decoded system configuration
 pub struct DecodedSystemConfig {
     mode: Option<ConfigMode>,
     rs_window: Option<u64>,
+    query_mem_limit: Option<u64>,
+    mem_high_watermark: Option<u64>,
+    mem_low_watermark: Option<u64>,
+    runtime: Option<ConfigRuntime>,
+    numa: Option<bool>,
+    huge_pages: Option<ConfigHugePages>,
+    storage: Option<ConfigStorage>,
+    journal_volume: Option<String>,
+    batch_volume: Option<String>,
+    archive_dir: Option<String>,
+    crash_dir: Option<String>,
+    probe_endpoint: Option<String>,
+    repair: Option<bool>,
+    warmup: Option<bool>,
+    flush_failure: Option<ConfigFlushFailure>,
+    flush_failure_cap: Option<u64>,
+    maintenance_rate: Option<u64>,
+    lock_wait_timeout: Option<u64>,
+    statement_timeout: Option<u64>,
+    relaxed_sessions: Option<bool>,
+    case_insensitive_names: Option<bool>,
+    throttle_backlog: Option<u64>,
+    throttle_lag: Option<u64>,
+    max_response_size: Option<u64>,
+    response_quota: Option<u64>,
+    response_quota_window: Option<u64>,
+    blob_max_size: Option<u64>,
+    scan_prefetch: Option<u64>,
+    blob_tier: Option<String>,
+    blob_tier_region: Option<String>,
+    blob_tier_access_key: Option<String>,
+    blob_tier_secret_key: Option<String>,
+    blob_tier_threshold: Option<u64>,
+    upstream: Option<String>,
+    upstream_user: Option<String>,
+    upstream_password: Option<String>,
+    shardmap: Option<String>,
+    otlp_endpoint: Option<String>,
+    trace_sample_rate: Option<u8>,
+    soft_limit: Option<u8>,
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
@@ -284,6 +630,8 @@ pub struct DecodedEPSecureConfig {
     cert: String,
     private_key: String,
     pkey_passphrase: String,
+    keepalive: Option<u64>,
+    idle_timeout: Option<u64>,
 }
 
 #[derive(Debug, PartialEq, Deserialize)]
@@ -291,6 +639,8 @@ pub struct DecodedEPSecureConfig {
 pub struct DecodedEPInsecureConfig {
     host: String,
     port: u16,
+    keepalive: Option<u64>,
+    idle_timeout: Option<u64>,
 }
 
 impl DecodedEPInsecureConfig {
@@ -298,6 +648,8 @@ impl DecodedEPInsecureConfig {
         Self {
             host: host.to_owned(),
             port,
+            keepalive: None,
+            idle_timeout: None,
         }
     }
 }
@@ -386,6 +738,48 @@ pub(super) trait ConfigurationSource {
     const KEY_ENDPOINTS: &'static str;
     const KEY_RUN_MODE: &'static str;
     const KEY_SERVICE_WINDOW: &'static str;
+    const KEY_QUERY_MEM_LIMIT: &'static str;
+    const KEY_MEM_HIGH_WATERMARK: &'static str;
+    const KEY_MEM_LOW_WATERMARK: &'static str;
+    const KEY_RUNTIME: &'static str;
+    const KEY_NUMA: &'static str;
+    const KEY_HUGE_PAGES: &'static str;
+    const KEY_STORAGE: &'static str;
+    const KEY_JOURNAL_VOLUME: &'static str;
+    const KEY_BATCH_VOLUME: &'static str;
+    const KEY_ARCHIVE_DIR: &'static str;
+    const KEY_CRASH_DIR: &'static str;
+    const KEY_PROBE_ENDPOINT: &'static str;
+    const KEY_REPAIR: &'static str;
+    const KEY_WARMUP: &'static str;
+    const KEY_FLUSH_FAILURE: &'static str;
+    const KEY_FLUSH_FAILURE_CAP: &'static str;
+    const KEY_MAINTENANCE_RATE: &'static str;
+    const KEY_LOCK_WAIT_TIMEOUT: &'static str;
+    const KEY_STATEMENT_TIMEOUT: &'static str;
+    const KEY_RELAXED_SESSIONS: &'static str;
+    const KEY_CASE_INSENSITIVE_NAMES: &'static str;
+    const KEY_THROTTLE_BACKLOG: &'static str;
+    const KEY_THROTTLE_LAG: &'static str;
+    const KEY_MAX_RESPONSE_SIZE: &'static str;
+    const KEY_RESPONSE_QUOTA: &'static str;
+    const KEY_RESPONSE_QUOTA_WINDOW: &'static str;
+    const KEY_BLOB_MAX_SIZE: &'static str;
+    const KEY_SCAN_PREFETCH: &'static str;
+    const KEY_BLOB_TIER: &'static str;
+    const KEY_BLOB_TIER_REGION: &'static str;
+    const KEY_BLOB_TIER_ACCESS_KEY: &'static str;
+    const KEY_BLOB_TIER_SECRET_KEY: &'static str;
+    const KEY_BLOB_TIER_THRESHOLD: &'static str;
+    const KEY_UPSTREAM: &'static str;
+    const KEY_UPSTREAM_USER: &'static str;
+    const KEY_UPSTREAM_PASSWORD: &'static str;
+    const KEY_SHARDMAP: &'static str;
+    const KEY_OTLP_ENDPOINT: &'static str;
+    const KEY_TRACE_SAMPLE_RATE: &'static str;
+    const KEY_SOFT_LIMIT: &'static str;
+    const KEY_KEEPALIVE: &'static str;
+    const KEY_IDLE_TIMEOUT: &'static str;
     const SOURCE: ConfigSource;
     /// Formats an error `Invalid value for {key}`
     fn err_invalid_value_for(key: &str) -> ConfigError {
@@ -478,6 +872,8 @@ fn decode_tls_ep(
         cert: tls_cert,
         private_key: tls_key,
         pkey_passphrase: tls_priv_key_passphrase,
+        keepalive: None,
+        idle_timeout: None,
     })
 }
 
@@ -558,7 +954,23 @@ fn arg_decode_endpoints<CS: ConfigurationSource>(
 ) -> RuntimeResult<()> {
     let mut insecure = None;
     let mut secure = None;
+    let (tcp_keepalive, tls_keepalive) = arg_decode_per_endpoint::<CS>(args, CS::KEY_KEEPALIVE)?;
+    let (tcp_idle_timeout, tls_idle_timeout) =
+        arg_decode_per_endpoint::<CS>(args, CS::KEY_IDLE_TIMEOUT)?;
     let Some(endpoints) = args.remove(CS::KEY_ENDPOINTS) else {
+        if tcp_keepalive.is_some()
+            | tls_keepalive.is_some()
+            | tcp_idle_timeout.is_some()
+            | tls_idle_timeout.is_some()
+        {
+            return Err(CS::custom_err(format!(
+                "`{}` and `{}` can only be used along with `{}`",
+                CS::KEY_KEEPALIVE,
+                CS::KEY_IDLE_TIMEOUT,
+                CS::KEY_ENDPOINTS
+            ))
+            .into());
+        }
         return Ok(());
     };
     if endpoints.len() > 2 {
@@ -568,10 +980,18 @@ fn arg_decode_endpoints<CS: ConfigurationSource>(
         let (proto, host, port) = parse_endpoint(CS::SOURCE, &ep)?;
         match proto {
             ConnectionProtocol::Tcp if insecure.is_none() => {
-                insecure = Some(DecodedEPInsecureConfig::new(host, port));
+                insecure = Some(DecodedEPInsecureConfig {
+                    keepalive: tcp_keepalive,
+                    idle_timeout: tcp_idle_timeout,
+                    ..DecodedEPInsecureConfig::new(host, port)
+                });
             }
             ConnectionProtocol::Tls if secure.is_none() => {
-                secure = Some(arg_decode_tls_endpoint::<CS>(args, host, port)?);
+                secure = Some(DecodedEPSecureConfig {
+                    keepalive: tls_keepalive,
+                    idle_timeout: tls_idle_timeout,
+                    ..arg_decode_tls_endpoint::<CS>(args, host, port)?
+                });
             }
             _ => {
                 return Err(CS::custom_err(format!(
@@ -588,6 +1008,41 @@ fn arg_decode_endpoints<CS: ConfigurationSource>(
     Ok(())
 }
 
+/// Decode a setting that is kept per endpoint, returning the values for the TCP and TLS endpoints. Each value is
+/// either `<seconds>` (for every endpoint) or `tcp:<seconds>` or `tls:<seconds>` (for just that endpoint)
+fn arg_decode_per_endpoint<CS: ConfigurationSource>(
+    args: &mut ParsedRawArgs,
+    key: &'static str,
+) -> RuntimeResult<(Option<u64>, Option<u64>)> {
+    let (mut tcp, mut tls) = (None, None);
+    let Some(values) = args.remove(key) else {
+        return Ok((tcp, tls));
+    };
+    if values.len() > 2 {
+        return Err(CS::err_too_many_values_for(key).into());
+    }
+    for value in values {
+        let ((for_tcp, for_tls), seconds) = match value.split_once(':') {
+            None => ((true, true), value.as_str()),
+            Some(("tcp", seconds)) => ((true, false), seconds),
+            Some(("tls", seconds)) => ((false, true), seconds),
+            Some(_) => return Err(CS::err_invalid_value_for(key).into()),
+        };
+        let Ok(seconds) = seconds.parse::<u64>() else {
+            return Err(CS::err_invalid_value_for(key).into());
+        };
+        for (set, slot) in [(for_tcp, &mut tcp), (for_tls, &mut tls)] {
+            if set && slot.replace(seconds).is_some() {
+                return Err(CS::custom_err(format!(
+                    "duplicate values for the same endpoint in `{key}`"
+                ))
+                .into());
+            }
+        }
+    }
+    Ok((tcp, tls))
+}
+
 /// Decode the run mode:
 /// - Dev OR
 /// - Prod
@@ -606,7 +1061,7 @@ fn arg_decode_mode<CS: ConfigurationSource>(
         None => {
             config.system = Some(DecodedSystemConfig {
                 mode: Some(mode),
-                rs_window: None,
+                ..Default::default()
             })
         }
     }
@@ -624,8 +1079,8 @@ fn arg_decode_rs_window<CS: ConfigurationSource>(
             Some(sys) => sys.rs_window = Some(n),
             None => {
                 config.system = Some(DecodedSystemConfig {
-                    mode: None,
                     rs_window: Some(n),
+                    ..Default::default()
                 })
             }
         },
@@ -634,12 +1089,573 @@ fn arg_decode_rs_window<CS: ConfigurationSource>(
     Ok(())
 }
 
+/// Decode a size in bytes for the system configuration
+fn arg_decode_system_size<CS: ConfigurationSource>(
+    key: &'static str,
+    size: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+    set: fn(&mut DecodedSystemConfig, u64),
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&size, key)?;
+    match size[0].parse::<u64>() {
+        Ok(n) => set(config.system.get_or_insert_with(Default::default), n),
+        Err(_) => return Err(CS::err_invalid_value_for(key).into()),
+    }
+    Ok(())
+}
+
+/// Decode the query memory limit
+fn arg_decode_query_mem_limit<CS: ConfigurationSource>(
+    limit: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_QUERY_MEM_LIMIT, limit, config, |sys, n| {
+        sys.query_mem_limit = Some(n)
+    })
+}
+
+/// Decode the high memory watermark
+fn arg_decode_mem_high_watermark<CS: ConfigurationSource>(
+    watermark: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_MEM_HIGH_WATERMARK, watermark, config, |sys, n| {
+        sys.mem_high_watermark = Some(n)
+    })
+}
+
+/// Decode the low memory watermark
+fn arg_decode_mem_low_watermark<CS: ConfigurationSource>(
+    watermark: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_MEM_LOW_WATERMARK, watermark, config, |sys, n| {
+        sys.mem_low_watermark = Some(n)
+    })
+}
+
+/// Decode the runtime:
+/// - Multithreaded OR
+/// - Sharded
+fn arg_decode_runtime<CS: ConfigurationSource>(
+    runtime: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&runtime, CS::KEY_RUNTIME)?;
+    let runtime = match runtime[0].as_str() {
+        "multithreaded" => ConfigRuntime::MultiThreaded,
+        "sharded" => ConfigRuntime::Sharded,
+        _ => return Err(CS::err_invalid_value_for(CS::KEY_RUNTIME).into()),
+    };
+    config.system.get_or_insert_with(Default::default).runtime = Some(runtime);
+    Ok(())
+}
+
+/// Decode NUMA awareness (`true` or `false`)
+fn arg_decode_numa<CS: ConfigurationSource>(
+    numa: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&numa, CS::KEY_NUMA)?;
+    match numa[0].parse::<bool>() {
+        Ok(numa) => config.system.get_or_insert_with(Default::default).numa = Some(numa),
+        Err(_) => return Err(CS::err_invalid_value_for(CS::KEY_NUMA).into()),
+    }
+    Ok(())
+}
+
+/// Decode the huge pages mode:
+/// - Off OR
+/// - Transparent OR
+/// - Explicit
+fn arg_decode_huge_pages<CS: ConfigurationSource>(
+    huge_pages: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&huge_pages, CS::KEY_HUGE_PAGES)?;
+    let huge_pages = match huge_pages[0].as_str() {
+        "off" => ConfigHugePages::Off,
+        "transparent" => ConfigHugePages::Transparent,
+        "explicit" => ConfigHugePages::Explicit,
+        _ => return Err(CS::err_invalid_value_for(CS::KEY_HUGE_PAGES).into()),
+    };
+    config
+        .system
+        .get_or_insert_with(Default::default)
+        .huge_pages = Some(huge_pages);
+    Ok(())
+}
+
+/// Decode the storage driver:
+/// - SDSS OR
+/// - Memory
+fn arg_decode_storage<CS: ConfigurationSource>(
+    storage: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&storage, CS::KEY_STORAGE)?;
+    let storage = match storage[0].as_str() {
+        "sdss" => ConfigStorage::Sdss,
+        "memory" => ConfigStorage::Memory,
+        _ => return Err(CS::err_invalid_value_for(CS::KEY_STORAGE).into()),
+    };
+    config.system.get_or_insert_with(Default::default).storage = Some(storage);
+    Ok(())
+}
+
+/// Decode what happens to writes while a model's changes can't be written to disk:
+/// - Block OR
+/// - Accumulate
+fn arg_decode_flush_failure<CS: ConfigurationSource>(
+    flush_failure: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&flush_failure, CS::KEY_FLUSH_FAILURE)?;
+    let flush_failure = match flush_failure[0].as_str() {
+        "block" => ConfigFlushFailure::Block,
+        "accumulate" => ConfigFlushFailure::Accumulate,
+        _ => return Err(CS::err_invalid_value_for(CS::KEY_FLUSH_FAILURE).into()),
+    };
+    config
+        .system
+        .get_or_insert_with(Default::default)
+        .flush_failure = Some(flush_failure);
+    Ok(())
+}
+
+/// Decode the number of changes a model can hold while they can't be written to disk
+fn arg_decode_flush_failure_cap<CS: ConfigurationSource>(
+    cap: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_FLUSH_FAILURE_CAP, cap, config, |sys, n| {
+        sys.flush_failure_cap = Some(n)
+    })
+}
+
+/// Decode the units of maintenance admitted per second while queries are running
+fn arg_decode_maintenance_rate<CS: ConfigurationSource>(
+    rate: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_MAINTENANCE_RATE, rate, config, |sys, n| {
+        sys.maintenance_rate = Some(n)
+    })
+}
+
+/// Decode the longest that a statement waits for a row lock
+fn arg_decode_lock_wait_timeout<CS: ConfigurationSource>(
+    timeout: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_LOCK_WAIT_TIMEOUT, timeout, config, |sys, n| {
+        sys.lock_wait_timeout = Some(n)
+    })
+}
+
+/// Decode the longest that a statement can run
+fn arg_decode_statement_timeout<CS: ConfigurationSource>(
+    timeout: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_STATEMENT_TIMEOUT, timeout, config, |sys, n| {
+        sys.statement_timeout = Some(n)
+    })
+}
+
+/// Decode the backlog past which writes to a model are throttled
+fn arg_decode_throttle_backlog<CS: ConfigurationSource>(
+    backlog: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_THROTTLE_BACKLOG, backlog, config, |sys, n| {
+        sys.throttle_backlog = Some(n)
+    })
+}
+
+/// Decode the webhook lag past which writes to a model are throttled
+fn arg_decode_throttle_lag<CS: ConfigurationSource>(
+    lag: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_THROTTLE_LAG, lag, config, |sys, n| {
+        sys.throttle_lag = Some(n)
+    })
+}
+
+/// Decode the largest response that a single statement can return
+fn arg_decode_max_response_size<CS: ConfigurationSource>(
+    size: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_MAX_RESPONSE_SIZE, size, config, |sys, n| {
+        sys.max_response_size = Some(n)
+    })
+}
+
+/// Decode the bytes of responses that a connection can be sent in every window
+fn arg_decode_response_quota<CS: ConfigurationSource>(
+    quota: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_RESPONSE_QUOTA, quota, config, |sys, n| {
+        sys.response_quota = Some(n)
+    })
+}
+
+/// Decode the window that the response quota is counted over
+fn arg_decode_response_quota_window<CS: ConfigurationSource>(
+    window: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_RESPONSE_QUOTA_WINDOW, window, config, |sys, n| {
+        sys.response_quota_window = Some(n)
+    })
+}
+
+/// Decode the largest value that can be uploaded in chunks
+fn arg_decode_blob_max_size<CS: ConfigurationSource>(
+    size: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_BLOB_MAX_SIZE, size, config, |sys, n| {
+        sys.blob_max_size = Some(n)
+    })
+}
+
+/// Decode the number of rows that scans read ahead
+fn arg_decode_scan_prefetch<CS: ConfigurationSource>(
+    rows: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_SCAN_PREFETCH, rows, config, |sys, n| {
+        sys.scan_prefetch = Some(n)
+    })
+}
+
+/// Decode a string for the system configuration (which can't be empty)
+fn arg_decode_system_str<CS: ConfigurationSource>(
+    key: &'static str,
+    value: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+    set: fn(&mut DecodedSystemConfig, String),
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&value, key)?;
+    if value[0].is_empty() {
+        return Err(CS::err_invalid_value_for(key).into());
+    }
+    set(
+        config.system.get_or_insert_with(Default::default),
+        value[0].clone(),
+    );
+    Ok(())
+}
+
+/// Decode the blob tier (`host:port/bucket`, validated along with the rest of the configuration)
+fn arg_decode_blob_tier<CS: ConfigurationSource>(
+    tier: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_BLOB_TIER, tier, config, |sys, v| {
+        sys.blob_tier = Some(v)
+    })
+}
+
+/// Decode the region of the blob tier
+fn arg_decode_blob_tier_region<CS: ConfigurationSource>(
+    region: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_BLOB_TIER_REGION, region, config, |sys, v| {
+        sys.blob_tier_region = Some(v)
+    })
+}
+
+/// Decode the access key of the blob tier
+fn arg_decode_blob_tier_access_key<CS: ConfigurationSource>(
+    key: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_BLOB_TIER_ACCESS_KEY, key, config, |sys, v| {
+        sys.blob_tier_access_key = Some(v)
+    })
+}
+
+/// Decode the secret key of the blob tier
+fn arg_decode_blob_tier_secret_key<CS: ConfigurationSource>(
+    key: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_BLOB_TIER_SECRET_KEY, key, config, |sys, v| {
+        sys.blob_tier_secret_key = Some(v)
+    })
+}
+
+/// Decode the size above which binary values are moved to the blob tier
+fn arg_decode_blob_tier_threshold<CS: ConfigurationSource>(
+    size: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_size::<CS>(CS::KEY_BLOB_TIER_THRESHOLD, size, config, |sys, n| {
+        sys.blob_tier_threshold = Some(n)
+    })
+}
+
+/// Decode the upstream endpoint (validated along with the rest of the configuration)
+fn arg_decode_upstream<CS: ConfigurationSource>(
+    upstream: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_UPSTREAM, upstream, config, |sys, v| {
+        sys.upstream = Some(v)
+    })
+}
+
+/// Decode the username used to connect to the upstream
+fn arg_decode_upstream_user<CS: ConfigurationSource>(
+    user: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_UPSTREAM_USER, user, config, |sys, v| {
+        sys.upstream_user = Some(v)
+    })
+}
+
+/// Decode the password used to connect to the upstream
+fn arg_decode_upstream_password<CS: ConfigurationSource>(
+    password: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_UPSTREAM_PASSWORD, password, config, |sys, v| {
+        sys.upstream_password = Some(v)
+    })
+}
+
+/// Decode the shard map (validated along with the rest of the configuration)
+fn arg_decode_shardmap<CS: ConfigurationSource>(
+    shardmap: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_system_str::<CS>(CS::KEY_SHARDMAP, shardmap, config, |sys, v| {
+        sys.shardmap = Some(v)
+    })
+}
+
+/// Decode a data volume (the path to a directory)
+fn arg_decode_volume<CS: ConfigurationSource>(
+    key: &'static str,
+    volume: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+    set: impl FnOnce(&mut DecodedSystemConfig, String),
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&volume, key)?;
+    if volume[0].is_empty() {
+        return Err(CS::err_invalid_value_for(key).into());
+    }
+    set(
+        config.system.get_or_insert_with(Default::default),
+        volume[0].clone(),
+    );
+    Ok(())
+}
+
+/// Decode the journal volume
+fn arg_decode_journal_volume<CS: ConfigurationSource>(
+    volume: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_volume::<CS>(CS::KEY_JOURNAL_VOLUME, volume, config, |sys, v| {
+        sys.journal_volume = Some(v)
+    })
+}
+
+/// Decode the batch volume
+fn arg_decode_batch_volume<CS: ConfigurationSource>(
+    volume: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_volume::<CS>(CS::KEY_BATCH_VOLUME, volume, config, |sys, v| {
+        sys.batch_volume = Some(v)
+    })
+}
+
+/// Decode the archive directory
+fn arg_decode_archive_dir<CS: ConfigurationSource>(
+    dir: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_volume::<CS>(CS::KEY_ARCHIVE_DIR, dir, config, |sys, v| {
+        sys.archive_dir = Some(v)
+    })
+}
+
+/// Decode the crash directory
+fn arg_decode_crash_dir<CS: ConfigurationSource>(
+    dir: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    arg_decode_volume::<CS>(CS::KEY_CRASH_DIR, dir, config, |sys, v| {
+        sys.crash_dir = Some(v)
+    })
+}
+
+/// Decode the probe endpoint (validated along with the rest of the configuration)
+fn arg_decode_probe_endpoint<CS: ConfigurationSource>(
+    endpoint: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&endpoint, CS::KEY_PROBE_ENDPOINT)?;
+    config
+        .system
+        .get_or_insert_with(Default::default)
+        .probe_endpoint = Some(endpoint[0].clone());
+    Ok(())
+}
+
+/// Decode the OTLP collector endpoint (validated along with the rest of the configuration)
+fn arg_decode_otlp_endpoint<CS: ConfigurationSource>(
+    endpoint: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&endpoint, CS::KEY_OTLP_ENDPOINT)?;
+    config
+        .system
+        .get_or_insert_with(Default::default)
+        .otlp_endpoint = Some(endpoint[0].clone());
+    Ok(())
+}
+
+/// Decode the trace sample rate (a percentage, validated along with the rest of the configuration)
+fn arg_decode_trace_sample_rate<CS: ConfigurationSource>(
+    rate: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&rate, CS::KEY_TRACE_SAMPLE_RATE)?;
+    match rate[0].parse::<u8>() {
+        Ok(rate) => {
+            config
+                .system
+                .get_or_insert_with(Default::default)
+                .trace_sample_rate = Some(rate)
+        }
+        Err(_) => return Err(CS::err_invalid_value_for(CS::KEY_TRACE_SAMPLE_RATE).into()),
+    }
+    Ok(())
+}
+
+/// Decode the soft limit (a percentage, validated along with the rest of the configuration)
+fn arg_decode_soft_limit<CS: ConfigurationSource>(
+    pct: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&pct, CS::KEY_SOFT_LIMIT)?;
+    match pct[0].parse::<u8>() {
+        Ok(pct) => {
+            config
+                .system
+                .get_or_insert_with(Default::default)
+                .soft_limit = Some(pct)
+        }
+        Err(_) => return Err(CS::err_invalid_value_for(CS::KEY_SOFT_LIMIT).into()),
+    }
+    Ok(())
+}
+
+/// Decode the repair flag
+fn arg_decode_repair<CS: ConfigurationSource>(
+    repair: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&repair, CS::KEY_REPAIR)?;
+    match repair[0].parse::<bool>() {
+        Ok(repair) => config.system.get_or_insert_with(Default::default).repair = Some(repair),
+        Err(_) => return Err(CS::err_invalid_value_for(CS::KEY_REPAIR).into()),
+    }
+    Ok(())
+}
+
+/// Decode the warmup flag
+fn arg_decode_warmup<CS: ConfigurationSource>(
+    warmup: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&warmup, CS::KEY_WARMUP)?;
+    match warmup[0].parse::<bool>() {
+        Ok(warmup) => config.system.get_or_insert_with(Default::default).warmup = Some(warmup),
+        Err(_) => return Err(CS::err_invalid_value_for(CS::KEY_WARMUP).into()),
+    }
+    Ok(())
+}
+
+/// Decode the flag that lets sessions relax durability
+fn arg_decode_relaxed_sessions<CS: ConfigurationSource>(
+    relaxed: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&relaxed, CS::KEY_RELAXED_SESSIONS)?;
+    match relaxed[0].parse::<bool>() {
+        Ok(relaxed) => {
+            config
+                .system
+                .get_or_insert_with(Default::default)
+                .relaxed_sessions = Some(relaxed)
+        }
+        Err(_) => return Err(CS::err_invalid_value_for(CS::KEY_RELAXED_SESSIONS).into()),
+    }
+    Ok(())
+}
+
+/// Decode the flag that makes names case insensitive
+fn arg_decode_case_insensitive_names<CS: ConfigurationSource>(
+    case_insensitive: &[String],
+    config: &mut ModifyGuard<DecodedConfiguration>,
+) -> RuntimeResult<()> {
+    argck_duplicate_values::<CS>(&case_insensitive, CS::KEY_CASE_INSENSITIVE_NAMES)?;
+    match case_insensitive[0].parse::<bool>() {
+        Ok(case_insensitive) => {
+            config
+                .system
+                .get_or_insert_with(Default::default)
+                .case_insensitive_names = Some(case_insensitive)
+        }
+        Err(_) => return Err(CS::err_invalid_value_for(CS::KEY_CASE_INSENSITIVE_NAMES).into()),
+    }
+    Ok(())
+}
+
+/// Parse a plain TCP endpoint (`hostname:port`), such as the probe endpoint
+fn parse_tcp_endpoint<CS: ConfigurationSource>(
+    key: &'static str,
+    endpoint: &str,
+) -> RuntimeResult<ConfigEndpointTcp> {
+    match endpoint.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() => match port.parse::<u16>() {
+            Ok(port) => Ok(ConfigEndpointTcp {
+                host: host.into(),
+                port,
+                keepalive: ConfigKeepalive::default(),
+            }),
+            Err(_) => Err(CS::err_invalid_value_for(key).into()),
+        },
+        _ => Err(CS::err_invalid_value_for(key).into()),
+    }
+}
+
 /*
     CLI args process
 */
 
 /// CLI help message
 pub(super) const TXT_HELP: &str = include_str!(concat!(env!("OUT_DIR"), "/skyd"));
+/// Check the configuration and exit, instead of starting the server (the rest of the arguments are read as usual)
+pub const CLI_CHECK_CONFIG: &str = "--check-config";
+/// Options that can be passed without a value (in which case they're set to `true`)
+const CLI_FLAGS: [&str; 4] = [
+    CSCommandLine::KEY_REPAIR,
+    CSCommandLine::KEY_WARMUP,
+    CSCommandLine::KEY_RELAXED_SESSIONS,
+    CSCommandLine::KEY_CASE_INSENSITIVE_NAMES,
+];
 
 #[derive(Debug, PartialEq)]
 /// Return from parsing CLI configuration
@@ -680,6 +1696,10 @@ pub fn parse_cli_args<'a, T: 'a + AsRef<str>>(
         if arg == "--version" || arg == "-v" {
             return Ok(CLIConfigParseReturn::Version);
         }
+        if arg == CLI_CHECK_CONFIG {
+            // this is only a mode (see `main`)
+            continue;
+        }
         if !arg.starts_with("--") {
             return Err(ConfigError::with_src(
                 ConfigSource::Cli,
@@ -703,6 +1723,9 @@ pub fn parse_cli_args<'a, T: 'a + AsRef<str>>(
                 ConfigErrorKind::ErrorString(format!("incorrectly formatted argument `{arg}`")),
             )
             .into());
+        } else if CLI_FLAGS.contains(&arg) {
+            arg_key = arg;
+            arg_val = "true".into();
         } else {
             let Some(value) = args_iter.next() else {
                 return Err(ConfigError::with_src(
@@ -737,12 +1760,54 @@ pub fn parse_cli_args<'a, T: 'a + AsRef<str>>(
 
 /// Parse environment variables
 pub fn parse_env_args() -> RuntimeResult<Option<ParsedRawArgs>> {
-    const KEYS: [&str; 8] = [
+    const KEYS: [&str; 50] = [
         CSEnvArgs::KEY_AUTH_DRIVER,
         CSEnvArgs::KEY_AUTH_ROOT_PASSWORD,
         CSEnvArgs::KEY_ENDPOINTS,
         CSEnvArgs::KEY_RUN_MODE,
         CSEnvArgs::KEY_SERVICE_WINDOW,
+        CSEnvArgs::KEY_QUERY_MEM_LIMIT,
+        CSEnvArgs::KEY_MEM_HIGH_WATERMARK,
+        CSEnvArgs::KEY_MEM_LOW_WATERMARK,
+        CSEnvArgs::KEY_RUNTIME,
+        CSEnvArgs::KEY_NUMA,
+        CSEnvArgs::KEY_HUGE_PAGES,
+        CSEnvArgs::KEY_STORAGE,
+        CSEnvArgs::KEY_JOURNAL_VOLUME,
+        CSEnvArgs::KEY_BATCH_VOLUME,
+        CSEnvArgs::KEY_ARCHIVE_DIR,
+        CSEnvArgs::KEY_CRASH_DIR,
+        CSEnvArgs::KEY_PROBE_ENDPOINT,
+        CSEnvArgs::KEY_REPAIR,
+        CSEnvArgs::KEY_WARMUP,
+        CSEnvArgs::KEY_FLUSH_FAILURE,
+        CSEnvArgs::KEY_FLUSH_FAILURE_CAP,
+        CSEnvArgs::KEY_MAINTENANCE_RATE,
+        CSEnvArgs::KEY_LOCK_WAIT_TIMEOUT,
+        CSEnvArgs::KEY_STATEMENT_TIMEOUT,
+        CSEnvArgs::KEY_RELAXED_SESSIONS,
+        CSEnvArgs::KEY_CASE_INSENSITIVE_NAMES,
+        CSEnvArgs::KEY_THROTTLE_BACKLOG,
+        CSEnvArgs::KEY_THROTTLE_LAG,
+        CSEnvArgs::KEY_MAX_RESPONSE_SIZE,
+        CSEnvArgs::KEY_RESPONSE_QUOTA,
+        CSEnvArgs::KEY_RESPONSE_QUOTA_WINDOW,
+        CSEnvArgs::KEY_BLOB_MAX_SIZE,
+        CSEnvArgs::KEY_SCAN_PREFETCH,
+        CSEnvArgs::KEY_BLOB_TIER,
+        CSEnvArgs::KEY_BLOB_TIER_REGION,
+        CSEnvArgs::KEY_BLOB_TIER_ACCESS_KEY,
+        CSEnvArgs::KEY_BLOB_TIER_SECRET_KEY,
+        CSEnvArgs::KEY_BLOB_TIER_THRESHOLD,
+        CSEnvArgs::KEY_UPSTREAM,
+        CSEnvArgs::KEY_UPSTREAM_USER,
+        CSEnvArgs::KEY_UPSTREAM_PASSWORD,
+        CSEnvArgs::KEY_SHARDMAP,
+        CSEnvArgs::KEY_OTLP_ENDPOINT,
+        CSEnvArgs::KEY_TRACE_SAMPLE_RATE,
+        CSEnvArgs::KEY_SOFT_LIMIT,
+        CSEnvArgs::KEY_KEEPALIVE,
+        CSEnvArgs::KEY_IDLE_TIMEOUT,
         CSEnvArgs::KEY_TLS_CERT,
         CSEnvArgs::KEY_TLS_KEY,
         CSEnvArgs::KEY_TLS_PKEY_PASS,
@@ -805,6 +1870,188 @@ fn apply_config_changes<CS: ConfigurationSource>(
             key: CS::KEY_SERVICE_WINDOW,
             f: arg_decode_rs_window::<CS>,
         },
+        // query memory limit
+        DecodeKind::Simple {
+            key: CS::KEY_QUERY_MEM_LIMIT,
+            f: arg_decode_query_mem_limit::<CS>,
+        },
+        // memory watermarks
+        DecodeKind::Simple {
+            key: CS::KEY_MEM_HIGH_WATERMARK,
+            f: arg_decode_mem_high_watermark::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_MEM_LOW_WATERMARK,
+            f: arg_decode_mem_low_watermark::<CS>,
+        },
+        // runtime
+        DecodeKind::Simple {
+            key: CS::KEY_RUNTIME,
+            f: arg_decode_runtime::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_NUMA,
+            f: arg_decode_numa::<CS>,
+        },
+        // huge pages
+        DecodeKind::Simple {
+            key: CS::KEY_HUGE_PAGES,
+            f: arg_decode_huge_pages::<CS>,
+        },
+        // storage driver
+        DecodeKind::Simple {
+            key: CS::KEY_STORAGE,
+            f: arg_decode_storage::<CS>,
+        },
+        // data volumes
+        DecodeKind::Simple {
+            key: CS::KEY_JOURNAL_VOLUME,
+            f: arg_decode_journal_volume::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_BATCH_VOLUME,
+            f: arg_decode_batch_volume::<CS>,
+        },
+        // archive
+        DecodeKind::Simple {
+            key: CS::KEY_ARCHIVE_DIR,
+            f: arg_decode_archive_dir::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_CRASH_DIR,
+            f: arg_decode_crash_dir::<CS>,
+        },
+        // probes
+        DecodeKind::Simple {
+            key: CS::KEY_PROBE_ENDPOINT,
+            f: arg_decode_probe_endpoint::<CS>,
+        },
+        // repair
+        DecodeKind::Simple {
+            key: CS::KEY_REPAIR,
+            f: arg_decode_repair::<CS>,
+        },
+        // warmup
+        DecodeKind::Simple {
+            key: CS::KEY_WARMUP,
+            f: arg_decode_warmup::<CS>,
+        },
+        // flush failures
+        DecodeKind::Simple {
+            key: CS::KEY_FLUSH_FAILURE,
+            f: arg_decode_flush_failure::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_FLUSH_FAILURE_CAP,
+            f: arg_decode_flush_failure_cap::<CS>,
+        },
+        // maintenance
+        DecodeKind::Simple {
+            key: CS::KEY_MAINTENANCE_RATE,
+            f: arg_decode_maintenance_rate::<CS>,
+        },
+        // locks
+        DecodeKind::Simple {
+            key: CS::KEY_LOCK_WAIT_TIMEOUT,
+            f: arg_decode_lock_wait_timeout::<CS>,
+        },
+        // sessions
+        DecodeKind::Simple {
+            key: CS::KEY_STATEMENT_TIMEOUT,
+            f: arg_decode_statement_timeout::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_RELAXED_SESSIONS,
+            f: arg_decode_relaxed_sessions::<CS>,
+        },
+        // names
+        DecodeKind::Simple {
+            key: CS::KEY_CASE_INSENSITIVE_NAMES,
+            f: arg_decode_case_insensitive_names::<CS>,
+        },
+        // write throttling
+        DecodeKind::Simple {
+            key: CS::KEY_THROTTLE_BACKLOG,
+            f: arg_decode_throttle_backlog::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_THROTTLE_LAG,
+            f: arg_decode_throttle_lag::<CS>,
+        },
+        // responses
+        DecodeKind::Simple {
+            key: CS::KEY_MAX_RESPONSE_SIZE,
+            f: arg_decode_max_response_size::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_RESPONSE_QUOTA,
+            f: arg_decode_response_quota::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_RESPONSE_QUOTA_WINDOW,
+            f: arg_decode_response_quota_window::<CS>,
+        },
+        // blobs
+        DecodeKind::Simple {
+            key: CS::KEY_BLOB_MAX_SIZE,
+            f: arg_decode_blob_max_size::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_SCAN_PREFETCH,
+            f: arg_decode_scan_prefetch::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_BLOB_TIER,
+            f: arg_decode_blob_tier::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_BLOB_TIER_REGION,
+            f: arg_decode_blob_tier_region::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_BLOB_TIER_ACCESS_KEY,
+            f: arg_decode_blob_tier_access_key::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_BLOB_TIER_SECRET_KEY,
+            f: arg_decode_blob_tier_secret_key::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_BLOB_TIER_THRESHOLD,
+            f: arg_decode_blob_tier_threshold::<CS>,
+        },
+        // upstream
+        DecodeKind::Simple {
+            key: CS::KEY_UPSTREAM,
+            f: arg_decode_upstream::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_UPSTREAM_USER,
+            f: arg_decode_upstream_user::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_UPSTREAM_PASSWORD,
+            f: arg_decode_upstream_password::<CS>,
+        },
+        // client-side sharding
+        DecodeKind::Simple {
+            key: CS::KEY_SHARDMAP,
+            f: arg_decode_shardmap::<CS>,
+        },
+        // tracing
+        DecodeKind::Simple {
+            key: CS::KEY_OTLP_ENDPOINT,
+            f: arg_decode_otlp_endpoint::<CS>,
+        },
+        DecodeKind::Simple {
+            key: CS::KEY_TRACE_SAMPLE_RATE,
+            f: arg_decode_trace_sample_rate::<CS>,
+        },
+        // soft limits
+        DecodeKind::Simple {
+            key: CS::KEY_SOFT_LIMIT,
+            f: arg_decode_soft_limit::<CS>,
+        },
         // endpoints
         DecodeKind::Complex {
             f: arg_decode_endpoints::<CS>,
@@ -850,6 +2097,48 @@ impl ConfigurationSource for CSCommandLine {
     const KEY_ENDPOINTS: &'static str = "--endpoint";
     const KEY_RUN_MODE: &'static str = "--mode";
     const KEY_SERVICE_WINDOW: &'static str = "--service-window";
+    const KEY_QUERY_MEM_LIMIT: &'static str = "--query-mem-limit";
+    const KEY_MEM_HIGH_WATERMARK: &'static str = "--mem-high-watermark";
+    const KEY_MEM_LOW_WATERMARK: &'static str = "--mem-low-watermark";
+    const KEY_RUNTIME: &'static str = "--runtime";
+    const KEY_NUMA: &'static str = "--numa";
+    const KEY_HUGE_PAGES: &'static str = "--huge-pages";
+    const KEY_STORAGE: &'static str = "--storage";
+    const KEY_JOURNAL_VOLUME: &'static str = "--journal-volume";
+    const KEY_BATCH_VOLUME: &'static str = "--batch-volume";
+    const KEY_ARCHIVE_DIR: &'static str = "--archive-dir";
+    const KEY_CRASH_DIR: &'static str = "--crash-dir";
+    const KEY_PROBE_ENDPOINT: &'static str = "--probe-endpoint";
+    const KEY_REPAIR: &'static str = "--repair";
+    const KEY_WARMUP: &'static str = "--warmup";
+    const KEY_FLUSH_FAILURE: &'static str = "--flush-failure";
+    const KEY_FLUSH_FAILURE_CAP: &'static str = "--flush-failure-cap";
+    const KEY_MAINTENANCE_RATE: &'static str = "--maintenance-rate";
+    const KEY_LOCK_WAIT_TIMEOUT: &'static str = "--lock-wait-timeout";
+    const KEY_STATEMENT_TIMEOUT: &'static str = "--statement-timeout";
+    const KEY_RELAXED_SESSIONS: &'static str = "--relaxed-sessions";
+    const KEY_CASE_INSENSITIVE_NAMES: &'static str = "--case-insensitive-names";
+    const KEY_THROTTLE_BACKLOG: &'static str = "--throttle-backlog";
+    const KEY_THROTTLE_LAG: &'static str = "--throttle-lag";
+    const KEY_MAX_RESPONSE_SIZE: &'static str = "--max-response-size";
+    const KEY_RESPONSE_QUOTA: &'static str = "--response-quota";
+    const KEY_RESPONSE_QUOTA_WINDOW: &'static str = "--response-quota-window";
+    const KEY_BLOB_MAX_SIZE: &'static str = "--blob-max-size";
+    const KEY_SCAN_PREFETCH: &'static str = "--scan-prefetch";
+    const KEY_BLOB_TIER: &'static str = "--blob-tier";
+    const KEY_BLOB_TIER_REGION: &'static str = "--blob-tier-region";
+    const KEY_BLOB_TIER_ACCESS_KEY: &'static str = "--blob-tier-access-key";
+    const KEY_BLOB_TIER_SECRET_KEY: &'static str = "--blob-tier-secret-key";
+    const KEY_BLOB_TIER_THRESHOLD: &'static str = "--blob-tier-threshold";
+    const KEY_UPSTREAM: &'static str = "--upstream";
+    const KEY_UPSTREAM_USER: &'static str = "--upstream-user";
+    const KEY_UPSTREAM_PASSWORD: &'static str = "--upstream-password";
+    const KEY_SHARDMAP: &'static str = "--shardmap";
+    const KEY_OTLP_ENDPOINT: &'static str = "--otlp-endpoint";
+    const KEY_TRACE_SAMPLE_RATE: &'static str = "--trace-sample-rate";
+    const KEY_SOFT_LIMIT: &'static str = "--soft-limit";
+    const KEY_KEEPALIVE: &'static str = "--keepalive";
+    const KEY_IDLE_TIMEOUT: &'static str = "--idle-timeout";
     const SOURCE: ConfigSource = ConfigSource::Cli;
 }
 
@@ -863,6 +2152,48 @@ impl ConfigurationSource for CSEnvArgs {
     const KEY_ENDPOINTS: &'static str = "SKYDB_ENDPOINTS";
     const KEY_RUN_MODE: &'static str = "SKYDB_RUN_MODE";
     const KEY_SERVICE_WINDOW: &'static str = "SKYDB_SERVICE_WINDOW";
+    const KEY_QUERY_MEM_LIMIT: &'static str = "SKYDB_QUERY_MEM_LIMIT";
+    const KEY_MEM_HIGH_WATERMARK: &'static str = "SKYDB_MEM_HIGH_WATERMARK";
+    const KEY_MEM_LOW_WATERMARK: &'static str = "SKYDB_MEM_LOW_WATERMARK";
+    const KEY_RUNTIME: &'static str = "SKYDB_RUNTIME";
+    const KEY_NUMA: &'static str = "SKYDB_NUMA";
+    const KEY_HUGE_PAGES: &'static str = "SKYDB_HUGE_PAGES";
+    const KEY_STORAGE: &'static str = "SKYDB_STORAGE";
+    const KEY_JOURNAL_VOLUME: &'static str = "SKYDB_JOURNAL_VOLUME";
+    const KEY_BATCH_VOLUME: &'static str = "SKYDB_BATCH_VOLUME";
+    const KEY_ARCHIVE_DIR: &'static str = "SKYDB_ARCHIVE_DIR";
+    const KEY_CRASH_DIR: &'static str = "SKYDB_CRASH_DIR";
+    const KEY_PROBE_ENDPOINT: &'static str = "SKYDB_PROBE_ENDPOINT";
+    const KEY_REPAIR: &'static str = "SKYDB_REPAIR";
+    const KEY_WARMUP: &'static str = "SKYDB_WARMUP";
+    const KEY_FLUSH_FAILURE: &'static str = "SKYDB_FLUSH_FAILURE";
+    const KEY_FLUSH_FAILURE_CAP: &'static str = "SKYDB_FLUSH_FAILURE_CAP";
+    const KEY_MAINTENANCE_RATE: &'static str = "SKYDB_MAINTENANCE_RATE";
+    const KEY_LOCK_WAIT_TIMEOUT: &'static str = "SKYDB_LOCK_WAIT_TIMEOUT";
+    const KEY_STATEMENT_TIMEOUT: &'static str = "SKYDB_STATEMENT_TIMEOUT";
+    const KEY_RELAXED_SESSIONS: &'static str = "SKYDB_RELAXED_SESSIONS";
+    const KEY_CASE_INSENSITIVE_NAMES: &'static str = "SKYDB_CASE_INSENSITIVE_NAMES";
+    const KEY_THROTTLE_BACKLOG: &'static str = "SKYDB_THROTTLE_BACKLOG";
+    const KEY_THROTTLE_LAG: &'static str = "SKYDB_THROTTLE_LAG";
+    const KEY_MAX_RESPONSE_SIZE: &'static str = "SKYDB_MAX_RESPONSE_SIZE";
+    const KEY_RESPONSE_QUOTA: &'static str = "SKYDB_RESPONSE_QUOTA";
+    const KEY_RESPONSE_QUOTA_WINDOW: &'static str = "SKYDB_RESPONSE_QUOTA_WINDOW";
+    const KEY_BLOB_MAX_SIZE: &'static str = "SKYDB_BLOB_MAX_SIZE";
+    const KEY_SCAN_PREFETCH: &'static str = "SKYDB_SCAN_PREFETCH";
+    const KEY_BLOB_TIER: &'static str = "SKYDB_BLOB_TIER";
+    const KEY_BLOB_TIER_REGION: &'static str = "SKYDB_BLOB_TIER_REGION";
+    const KEY_BLOB_TIER_ACCESS_KEY: &'static str = "SKYDB_BLOB_TIER_ACCESS_KEY";
+    const KEY_BLOB_TIER_SECRET_KEY: &'static str = "SKYDB_BLOB_TIER_SECRET_KEY";
+    const KEY_BLOB_TIER_THRESHOLD: &'static str = "SKYDB_BLOB_TIER_THRESHOLD";
+    const KEY_UPSTREAM: &'static str = "SKYDB_UPSTREAM";
+    const KEY_UPSTREAM_USER: &'static str = "SKYDB_UPSTREAM_USER";
+    const KEY_UPSTREAM_PASSWORD: &'static str = "SKYDB_UPSTREAM_PASSWORD";
+    const KEY_SHARDMAP: &'static str = "SKYDB_SHARDMAP";
+    const KEY_OTLP_ENDPOINT: &'static str = "SKYDB_OTLP_ENDPOINT";
+    const KEY_TRACE_SAMPLE_RATE: &'static str = "SKYDB_TRACE_SAMPLE_RATE";
+    const KEY_SOFT_LIMIT: &'static str = "SKYDB_SOFT_LIMIT";
+    const KEY_KEEPALIVE: &'static str = "SKYDB_KEEPALIVE";
+    const KEY_IDLE_TIMEOUT: &'static str = "SKYDB_IDLE_TIMEOUT";
     const SOURCE: ConfigSource = ConfigSource::Env;
 }
 
@@ -876,6 +2207,48 @@ impl ConfigurationSource for CSConfigFile {
     const KEY_ENDPOINTS: &'static str = "endpoints";
     const KEY_RUN_MODE: &'static str = "system.mode";
     const KEY_SERVICE_WINDOW: &'static str = "system.service_window";
+    const KEY_QUERY_MEM_LIMIT: &'static str = "system.query_mem_limit";
+    const KEY_MEM_HIGH_WATERMARK: &'static str = "system.mem_high_watermark";
+    const KEY_MEM_LOW_WATERMARK: &'static str = "system.mem_low_watermark";
+    const KEY_RUNTIME: &'static str = "system.runtime";
+    const KEY_NUMA: &'static str = "system.numa";
+    const KEY_HUGE_PAGES: &'static str = "system.huge_pages";
+    const KEY_STORAGE: &'static str = "system.storage";
+    const KEY_JOURNAL_VOLUME: &'static str = "system.journal_volume";
+    const KEY_BATCH_VOLUME: &'static str = "system.batch_volume";
+    const KEY_ARCHIVE_DIR: &'static str = "system.archive_dir";
+    const KEY_CRASH_DIR: &'static str = "system.crash_dir";
+    const KEY_PROBE_ENDPOINT: &'static str = "system.probe_endpoint";
+    const KEY_REPAIR: &'static str = "system.repair";
+    const KEY_WARMUP: &'static str = "system.warmup";
+    const KEY_FLUSH_FAILURE: &'static str = "system.flush_failure";
+    const KEY_FLUSH_FAILURE_CAP: &'static str = "system.flush_failure_cap";
+    const KEY_MAINTENANCE_RATE: &'static str = "system.maintenance_rate";
+    const KEY_LOCK_WAIT_TIMEOUT: &'static str = "system.lock_wait_timeout";
+    const KEY_STATEMENT_TIMEOUT: &'static str = "system.statement_timeout";
+    const KEY_RELAXED_SESSIONS: &'static str = "system.relaxed_sessions";
+    const KEY_CASE_INSENSITIVE_NAMES: &'static str = "system.case_insensitive_names";
+    const KEY_THROTTLE_BACKLOG: &'static str = "system.throttle_backlog";
+    const KEY_THROTTLE_LAG: &'static str = "system.throttle_lag";
+    const KEY_MAX_RESPONSE_SIZE: &'static str = "system.max_response_size";
+    const KEY_RESPONSE_QUOTA: &'static str = "system.response_quota";
+    const KEY_RESPONSE_QUOTA_WINDOW: &'static str = "system.response_quota_window";
+    const KEY_BLOB_MAX_SIZE: &'static str = "system.blob_max_size";
+    const KEY_SCAN_PREFETCH: &'static str = "system.scan_prefetch";
+    const KEY_BLOB_TIER: &'static str = "system.blob_tier";
+    const KEY_BLOB_TIER_REGION: &'static str = "system.blob_tier_region";
+    const KEY_BLOB_TIER_ACCESS_KEY: &'static str = "system.blob_tier_access_key";
+    const KEY_BLOB_TIER_SECRET_KEY: &'static str = "system.blob_tier_secret_key";
+    const KEY_BLOB_TIER_THRESHOLD: &'static str = "system.blob_tier_threshold";
+    const KEY_UPSTREAM: &'static str = "system.upstream";
+    const KEY_UPSTREAM_USER: &'static str = "system.upstream_user";
+    const KEY_UPSTREAM_PASSWORD: &'static str = "system.upstream_password";
+    const KEY_SHARDMAP: &'static str = "system.shardmap";
+    const KEY_OTLP_ENDPOINT: &'static str = "system.otlp_endpoint";
+    const KEY_TRACE_SAMPLE_RATE: &'static str = "system.trace_sample_rate";
+    const KEY_SOFT_LIMIT: &'static str = "system.soft_limit";
+    const KEY_KEEPALIVE: &'static str = "endpoints.*.keepalive";
+    const KEY_IDLE_TIMEOUT: &'static str = "endpoints.*.idle_timeout";
     const SOURCE: ConfigSource = ConfigSource::File;
 }
 
@@ -897,6 +2270,177 @@ macro_rules! err_if {
     }
 }
 
+/// Validate the settings of the blob tier. The bucket (`host:port/bucket`) and the credentials are required if any
+/// of them is set
+fn parse_blob_tier<CS: ConfigurationSource>(
+    tier: Option<String>,
+    region: Option<String>,
+    access_key: Option<String>,
+    secret_key: Option<String>,
+    threshold: Option<u64>,
+) -> RuntimeResult<Option<ConfigBlobTier>> {
+    let Some(tier) = tier else {
+        if region.is_some() | access_key.is_some() | secret_key.is_some() | threshold.is_some() {
+            return Err(CS::custom_err(format!(
+                "the blob tier must be configured with {}",
+                CS::KEY_BLOB_TIER
+            ))
+            .into());
+        }
+        return Ok(None);
+    };
+    // HTTPS unless asked otherwise
+    let (tls, tier) = match tier.strip_prefix("http://") {
+        Some(tier) => (false, tier),
+        None => (true, tier.strip_prefix("https://").unwrap_or(&tier)),
+    };
+    let Some((endpoint, bucket)) = tier.split_once('/') else {
+        return Err(CS::err_invalid_value_for(CS::KEY_BLOB_TIER).into());
+    };
+    let valid_bucket = !bucket.is_empty()
+        && bucket
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() | b.is_ascii_digit() | (b == b'-') | (b == b'.'));
+    if !valid_bucket {
+        return Err(CS::err_invalid_value_for(CS::KEY_BLOB_TIER).into());
+    }
+    let endpoint = parse_tcp_endpoint::<CS>(CS::KEY_BLOB_TIER, endpoint)?;
+    // the credentials and the blobs would be sent in the clear
+    if !tls && !is_loopback(endpoint.host()) {
+        return Err(CS::custom_err(format!(
+            "the blob tier can only use plain HTTP for a bucket on the loopback interface (set {} to an https:// URL)",
+            CS::KEY_BLOB_TIER
+        ))
+        .into());
+    }
+    let (Some(access_key), Some(secret_key)) = (access_key, secret_key) else {
+        return Err(CS::custom_err(format!(
+            "the blob tier needs credentials set with {} and {}",
+            CS::KEY_BLOB_TIER_ACCESS_KEY,
+            CS::KEY_BLOB_TIER_SECRET_KEY
+        ))
+        .into());
+    };
+    let threshold = threshold.unwrap_or(ConfigBlobTier::DEFAULT_THRESHOLD);
+    if threshold == 0 {
+        return Err(CS::err_invalid_value_for(CS::KEY_BLOB_TIER_THRESHOLD).into());
+    }
+    Ok(Some(ConfigBlobTier {
+        endpoint,
+        tls,
+        bucket: bucket.into(),
+        region: region.unwrap_or_else(|| ConfigBlobTier::DEFAULT_REGION.into()),
+        access_key,
+        secret_key,
+        threshold,
+    }))
+}
+
+/// Returns true if the host is the loopback interface
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().map_or(false, |ip| ip.is_loopback())
+}
+
+/// Validate the settings of the upstream. The endpoint and the credentials are required if any of them is set
+fn parse_upstream<CS: ConfigurationSource>(
+    upstream: Option<String>,
+    user: Option<String>,
+    password: Option<String>,
+) -> RuntimeResult<Option<ConfigUpstream>> {
+    let Some(upstream) = upstream else {
+        if user.is_some() | password.is_some() {
+            return Err(CS::custom_err(format!(
+                "the upstream must be configured with {}",
+                CS::KEY_UPSTREAM
+            ))
+            .into());
+        }
+        return Ok(None);
+    };
+    let endpoint = parse_tcp_endpoint::<CS>(CS::KEY_UPSTREAM, &upstream)?;
+    let (Some(username), Some(password)) = (user, password) else {
+        return Err(CS::custom_err(format!(
+            "the upstream needs credentials set with {} and {}",
+            CS::KEY_UPSTREAM_USER,
+            CS::KEY_UPSTREAM_PASSWORD
+        ))
+        .into());
+    };
+    Ok(Some(ConfigUpstream {
+        endpoint,
+        username,
+        password,
+    }))
+}
+
+/// Parse a shard map, given as `<name>@<host>:<port>/<ranges>` for every node (separated by `;`), where the ranges
+/// are the inclusive ranges of slots that the node owns (like `0-1023,4096-8191`). Every slot has to be owned by
+/// exactly one node
+fn parse_shardmap<CS: ConfigurationSource>(shardmap: &str) -> RuntimeResult<ConfigShardMap> {
+    let mut nodes: Vec<ConfigShardNode> = vec![];
+    let mut owned = vec![false; ConfigShardMap::SLOTS as usize];
+    let mut owned_count = 0;
+    for node in shardmap.split(';').map(str::trim) {
+        let Some((name, rest)) = node.split_once('@') else {
+            return Err(CS::err_invalid_value_for(CS::KEY_SHARDMAP).into());
+        };
+        let Some((endpoint, ranges)) = rest.split_once('/') else {
+            return Err(CS::err_invalid_value_for(CS::KEY_SHARDMAP).into());
+        };
+        let valid_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() | (c == '_') | (c == '-'));
+        if !valid_name | nodes.iter().any(|n| n.name == name) {
+            return Err(CS::custom_err(format!(
+                "invalid or duplicate node name `{name}` in {}",
+                CS::KEY_SHARDMAP
+            ))
+            .into());
+        }
+        let endpoint = parse_tcp_endpoint::<CS>(CS::KEY_SHARDMAP, endpoint)?;
+        let mut node_ranges = vec![];
+        for range in ranges.split(',') {
+            let range = range
+                .split_once('-')
+                .and_then(|(start, end)| {
+                    Some((start.parse::<u16>().ok()?, end.parse::<u16>().ok()?))
+                })
+                .filter(|(start, end)| start <= end);
+            let Some((start, end)) = range else {
+                return Err(CS::err_invalid_value_for(CS::KEY_SHARDMAP).into());
+            };
+            for slot in start..=end {
+                if owned[slot as usize] {
+                    return Err(CS::custom_err(format!(
+                        "slot {slot} is owned by more than one node in {}",
+                        CS::KEY_SHARDMAP
+                    ))
+                    .into());
+                }
+                owned[slot as usize] = true;
+                owned_count += 1;
+            }
+            node_ranges.push((start, end));
+        }
+        nodes.push(ConfigShardNode {
+            name: name.into(),
+            endpoint,
+            ranges: node_ranges,
+        });
+    }
+    if owned_count != ConfigShardMap::SLOTS {
+        return Err(CS::custom_err(format!(
+            "every slot (0 to {}) must be owned by a node in {}",
+            ConfigShardMap::SLOTS - 1,
+            CS::KEY_SHARDMAP
+        ))
+        .into());
+    }
+    Ok(ConfigShardMap { nodes })
+}
+
 /// Validate the configuration, and prepare the final configuration
 fn validate_configuration<CS: ConfigurationSource>(
     DecodedConfiguration {
@@ -917,24 +2461,116 @@ fn validate_configuration<CS: ConfigurationSource>(
     };
     // initialize our default configuration
     let mut config = Configuration::default_dev_mode(auth);
+    let (mut probe_endpoint, mut otlp_endpoint, mut blob_tier, mut upstream, mut shardmap) =
+        (None, None, None, None, None);
     // mutate
     if_some!(
         system => |system: DecodedSystemConfig| {
             if_some!(system.mode => |mode| config.mode = mode);
             if_some!(system.rs_window => |window| config.system.reliability_system_window = window);
+            if_some!(system.query_mem_limit => |limit| config.system.query_memory_limit = limit);
+            if_some!(system.mem_high_watermark => |high| config.system.memory_high_watermark = high);
+            if_some!(system.mem_low_watermark => |low| config.system.memory_low_watermark = low);
+            if_some!(system.runtime => |runtime| config.system.runtime = runtime);
+            if_some!(system.numa => |numa| config.system.numa_aware = numa);
+            if_some!(system.huge_pages => |huge_pages| config.system.huge_pages = huge_pages);
+            if_some!(system.storage => |storage| config.system.storage = storage);
+            if_some!(system.journal_volume => |volume| config.system.journal_volume = Some(volume));
+            if_some!(system.batch_volume => |volume| config.system.batch_volume = Some(volume));
+            if_some!(system.archive_dir => |dir| config.system.archive_dir = Some(dir));
+            if_some!(system.crash_dir => |dir| config.system.crash_dir = Some(dir));
+            if_some!(system.repair => |repair| config.system.repair = repair);
+            if_some!(system.warmup => |warmup| config.system.warmup = warmup);
+            if_some!(system.flush_failure => |mode| config.system.flush_failure = mode);
+            if_some!(system.flush_failure_cap => |cap| config.system.flush_failure_cap = cap);
+            if_some!(system.maintenance_rate => |rate| config.system.maintenance_rate = rate);
+            if_some!(system.lock_wait_timeout => |timeout| config.system.lock_wait_timeout = timeout);
+            if_some!(system.statement_timeout => |timeout| config.system.statement_timeout = timeout);
+            if_some!(system.relaxed_sessions => |relaxed| config.system.relaxed_sessions = relaxed);
+            if_some!(system.case_insensitive_names => |ci| config.system.case_insensitive_names = ci);
+            if_some!(system.throttle_backlog => |backlog| config.system.throttle_backlog = backlog);
+            if_some!(system.throttle_lag => |lag| config.system.throttle_lag = lag);
+            if_some!(system.max_response_size => |size| config.system.max_response_size = size);
+            if_some!(system.response_quota => |quota| config.system.response_quota = quota);
+            if_some!(system.response_quota_window => |window| config.system.response_quota_window = window);
+            if_some!(system.blob_max_size => |size| config.system.blob_max_size = size);
+            if_some!(system.scan_prefetch => |rows| config.system.scan_prefetch = rows);
+            if_some!(system.trace_sample_rate => |rate| config.system.trace_sample_rate = rate);
+            if_some!(system.soft_limit => |pct| config.system.soft_limit = pct);
+            probe_endpoint = system.probe_endpoint;
+            otlp_endpoint = system.otlp_endpoint;
+            blob_tier = Some((
+                system.blob_tier,
+                system.blob_tier_region,
+                system.blob_tier_access_key,
+                system.blob_tier_secret_key,
+                system.blob_tier_threshold,
+            ));
+            upstream = Some((system.upstream, system.upstream_user, system.upstream_password));
+            shardmap = system.shardmap;
         }
     );
+    if let Some(ep) = probe_endpoint {
+        config.system.probe_endpoint = Some(parse_tcp_endpoint::<CS>(CS::KEY_PROBE_ENDPOINT, &ep)?);
+    }
+    if let Some(ep) = otlp_endpoint {
+        config.system.otlp_endpoint = Some(parse_tcp_endpoint::<CS>(CS::KEY_OTLP_ENDPOINT, &ep)?);
+    }
+    if let Some((tier, region, access_key, secret_key, threshold)) = blob_tier {
+        config.system.blob_tier =
+            parse_blob_tier::<CS>(tier, region, access_key, secret_key, threshold)?;
+    }
+    if let Some((upstream, user, password)) = upstream {
+        config.system.upstream = parse_upstream::<CS>(upstream, user, password)?;
+    }
+    if let Some(shardmap) = shardmap {
+        config.system.shardmap = Some(parse_shardmap::<CS>(&shardmap)?);
+    }
+    if !(1..=100).contains(&config.system.trace_sample_rate) {
+        return Err(CS::err_invalid_value_for(CS::KEY_TRACE_SAMPLE_RATE).into());
+    }
+    // a soft limit of 100% (or more) would never warn before the limit itself fails a statement
+    if config.system.soft_limit >= 100 {
+        return Err(CS::err_invalid_value_for(CS::KEY_SOFT_LIMIT).into());
+    }
+    if let Some(ep) = &endpoints {
+        let timeouts = ep
+            .insecure
+            .iter()
+            .flat_map(|ep| [ep.keepalive, ep.idle_timeout])
+            .chain(
+                ep.secure
+                    .iter()
+                    .flat_map(|ep| [ep.keepalive, ep.idle_timeout]),
+            );
+        if timeouts.flatten().any(|seconds| seconds == 0) {
+            return Err(ConfigError::with_src(
+                CS::SOURCE,
+                ConfigErrorKind::ErrorString(format!(
+                    "invalid value for `{}` or `{}`. must be nonzero",
+                    CS::KEY_KEEPALIVE,
+                    CS::KEY_IDLE_TIMEOUT
+                )),
+            )
+            .into());
+        }
+    }
     if_some!(
         endpoints => |ep: DecodedEPConfig| {
             let has_insecure = ep.insecure.is_some();
             if_some!(ep.insecure => |insecure: DecodedEPInsecureConfig| {
-                config.endpoints = ConfigEndpoint::Insecure(ConfigEndpointTcp { host: insecure.host, port: insecure.port });
+                config.endpoints = ConfigEndpoint::Insecure(ConfigEndpointTcp {
+                    host: insecure.host,
+                    port: insecure.port,
+                    keepalive: ConfigKeepalive { probe_after: insecure.keepalive, idle_timeout: insecure.idle_timeout },
+                });
             });
             if_some!(ep.secure => |secure: DecodedEPSecureConfig| {
                 let secure_ep = ConfigEndpointTls {
                     tcp: ConfigEndpointTcp {
                         host: secure.host,
                         port: secure.port,
+                        keepalive: ConfigKeepalive { probe_after: secure.keepalive, idle_timeout: secure.idle_timeout },
                     },
                     cert: secure.cert,
                     private_key: secure.private_key,
@@ -953,12 +2589,40 @@ fn validate_configuration<CS: ConfigurationSource>(
             })
         }
     );
+    // if only the high watermark was set, admit expensive queries again once usage drops by 10%
+    if (config.system.memory_high_watermark != 0) & (config.system.memory_low_watermark == 0) {
+        config.system.memory_low_watermark = config.system.memory_high_watermark / 10 * 9;
+    }
     // now check a few things
     err_if!(
         if config.system.reliability_system_window == 0 => ConfigError::with_src(
             CS::SOURCE,
             ConfigErrorKind::ErrorString("invalid value for service window. must be nonzero".into()),
         ).into(),
+        if config.system.query_memory_limit == 0 => ConfigError::with_src(
+            CS::SOURCE,
+            ConfigErrorKind::ErrorString("invalid value for query memory limit. must be nonzero".into()),
+        ).into(),
+        if config.system.blob_max_size == 0 => ConfigError::with_src(
+            CS::SOURCE,
+            ConfigErrorKind::ErrorString("invalid value for blob max size. must be nonzero".into()),
+        ).into(),
+        if config.system.scan_prefetch > ConfigSystem::MAX_SCAN_PREFETCH => ConfigError::with_src(
+            CS::SOURCE,
+            ConfigErrorKind::ErrorString("invalid value for scan prefetch. must be at most 256".into()),
+        ).into(),
+        if config.system.response_quota_window == 0 => ConfigError::with_src(
+            CS::SOURCE,
+            ConfigErrorKind::ErrorString("invalid value for response quota window. must be nonzero".into()),
+        ).into(),
+        if config.system.memory_low_watermark > config.system.memory_high_watermark => ConfigError::with_src(
+            CS::SOURCE,
+            ConfigErrorKind::ErrorString("the low memory watermark must not be above the high memory watermark".into()),
+        ).into(),
+        if config.system.numa_aware & (config.system.runtime != ConfigRuntime::Sharded) => ConfigError::with_src(
+            CS::SOURCE,
+            ConfigErrorKind::ErrorString("NUMA awareness can only be enabled with the sharded runtime".into()),
+        ).into(),
         if config.auth.root_key.len() < ROOT_PASSWORD_MIN_LEN => ConfigError::with_src(
             CS::SOURCE,
             ConfigErrorKind::ErrorString("the root password must have at least 16 characters".into()),
@@ -0,0 +1,320 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    configuration checks
+    ---
+    `skyd --check-config` loads the configuration just like a start would (so an invalid configuration is found the
+    same way) and then checks what a start would otherwise only find out later: that the TLS material can be used, that
+    the directories that we write to are writable and that the endpoints' ports are free. nothing is loaded, and the
+    only thing that's written is a probe file that's removed right away. the findings are printed as JSON, and any
+    error fails the check
+*/
+
+use {
+    super::{
+        config::{self, ConfigEndpoint, ConfigEndpointTcp, ConfigReturn, Configuration},
+        net,
+        storage::encode_str,
+    },
+    openssl::{asn1::Asn1Time, x509::X509},
+    std::{fs, net::TcpListener, path::Path},
+};
+
+/// The file that's created (and removed) to check that a directory is writable
+const PROBE_FILE: &str = ".sky_check_config";
+/// A certificate that expires within these many days is warned about
+const CERT_EXPIRY_WARN_DAYS: u32 = 30;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Finding {
+    check: &'static str,
+    severity: Severity,
+    message: String,
+}
+
+#[derive(Debug, Default)]
+/// The findings of a check
+pub struct Report {
+    findings: Vec<Finding>,
+}
+
+impl Report {
+    fn push(&mut self, check: &'static str, severity: Severity, message: String) {
+        self.findings.push(Finding {
+            check,
+            severity,
+            message,
+        })
+    }
+    fn error(&mut self, check: &'static str, message: String) {
+        self.push(check, Severity::Error, message)
+    }
+    fn warning(&mut self, check: &'static str, message: String) {
+        self.push(check, Severity::Warning, message)
+    }
+    /// Returns true if nothing that was found is an error
+    pub fn passed(&self) -> bool {
+        self.findings.iter().all(|f| f.severity != Severity::Error)
+    }
+    /// `{"ok":<passed>,"findings":[{"check":<check>,"severity":<"error" or "warning">,"message":<message>}, ...]}`
+    pub fn describe(&self) -> String {
+        let mut ret = format!("{{\"ok\":{},\"findings\":[", self.passed());
+        for (i, finding) in self.findings.iter().enumerate() {
+            if i != 0 {
+                ret.push(',');
+            }
+            ret.push_str("{\"check\":");
+            encode_str(&mut ret, finding.check);
+            ret.push_str(",\"severity\":");
+            encode_str(&mut ret, finding.severity.name());
+            ret.push_str(",\"message\":");
+            encode_str(&mut ret, &finding.message);
+            ret.push('}');
+        }
+        ret.push_str("]}");
+        ret
+    }
+}
+
+/// Load the configuration and check it (see the module docs)
+pub fn run() -> Report {
+    let mut report = Report::default();
+    match config::check_configuration() {
+        Ok(ConfigReturn::Config(cfg)) => check(&cfg, &mut report),
+        Ok(ConfigReturn::HelpMessage(_)) => report.error(
+            "config",
+            format!(
+                "`{}` can't be used with `--help` or `--version`",
+                config::CLI_CHECK_CONFIG
+            ),
+        ),
+        Err(e) => report.error("config", e.to_string()),
+    }
+    report
+}
+
+fn check(cfg: &Configuration, report: &mut Report) {
+    // TLS material
+    match &cfg.endpoints {
+        ConfigEndpoint::Insecure(_) => {}
+        ConfigEndpoint::Secure(tls) | ConfigEndpoint::Multi(_, tls) => {
+            check_tls(report, tls.cert(), tls.private_key(), tls.pkey_pass())
+        }
+    }
+    // ports
+    let mut listeners = vec![];
+    match &cfg.endpoints {
+        ConfigEndpoint::Insecure(tcp) => listeners.push(("tcp endpoint", tcp)),
+        ConfigEndpoint::Secure(tls) => listeners.push(("tls endpoint", tls.tcp())),
+        ConfigEndpoint::Multi(tcp, tls) => {
+            listeners.push(("tcp endpoint", tcp));
+            listeners.push(("tls endpoint", tls.tcp()));
+        }
+    }
+    if let Some(probe) = &cfg.system.probe_endpoint {
+        listeners.push(("probe endpoint", probe));
+    }
+    check_ports(report, &listeners);
+    // directories
+    check_dir(report, "working directory", ".");
+    for (what, dir) in [
+        ("journal volume", &cfg.system.journal_volume),
+        ("batch volume", &cfg.system.batch_volume),
+        ("archive directory", &cfg.system.archive_dir),
+        ("crash directory", &cfg.system.crash_dir),
+    ] {
+        if let Some(dir) = dir {
+            check_dir(report, what, dir);
+        }
+    }
+}
+
+/// Check that the certificate and private key (with its passphrase) make a TLS acceptor, and when the certificate
+/// expires
+fn check_tls(report: &mut Report, cert: &str, private_key: &str, pkey_pass: &str) {
+    if let Err(e) = net::Listener::init_tls(cert, private_key, pkey_pass) {
+        report.error(
+            "tls",
+            format!("the certificate and private key can't be used: {e}"),
+        );
+        return;
+    }
+    let (Ok(cert), Ok(now), Ok(soon)) = (
+        X509::from_pem(cert.as_bytes()),
+        Asn1Time::days_from_now(0),
+        Asn1Time::days_from_now(CERT_EXPIRY_WARN_DAYS),
+    ) else {
+        return;
+    };
+    if cert.not_after() < now {
+        report.error(
+            "tls",
+            format!("the certificate expired on {}", cert.not_after()),
+        );
+    } else if cert.not_after() < soon {
+        report.warning(
+            "tls",
+            format!("the certificate expires on {}", cert.not_after()),
+        );
+    }
+}
+
+/// Check that no two listeners share an address, and that we can listen on each of them (the listener is closed right
+/// away)
+fn check_ports(report: &mut Report, listeners: &[(&'static str, &ConfigEndpointTcp)]) {
+    for (i, (what, ep)) in listeners.iter().enumerate() {
+        let (host, port) = (ep.host(), ep.port());
+        if let Some((other, _)) = listeners[..i]
+            .iter()
+            .find(|(_, other)| (other.host(), other.port()) == (host, port))
+        {
+            report.error(
+                "ports",
+                format!("the {what} and the {other} both use {host}:{port}"),
+            );
+        } else if let Err(e) = TcpListener::bind((host, port)) {
+            report.error(
+                "ports",
+                format!("can't listen on {host}:{port} for the {what}: {e}"),
+            );
+        }
+    }
+}
+
+/// Check that a directory is writable. A directory that doesn't exist will be created, so then the closest directory
+/// above it that exists must be writable
+fn check_dir(report: &mut Report, what: &str, dir: &str) {
+    let path = Path::new(dir);
+    if path.is_dir() {
+        if let Err(e) = probe_write(path) {
+            report.error(
+                "directories",
+                format!("the {what} ({dir}) isn't writable: {e}"),
+            );
+        }
+        return;
+    }
+    if path.exists() {
+        report.error(
+            "directories",
+            format!("the {what} ({dir}) isn't a directory"),
+        );
+        return;
+    }
+    let parent = path
+        .ancestors()
+        .skip(1)
+        .map(|p| match p.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => p,
+        })
+        .find(|p| p.is_dir())
+        .unwrap_or(Path::new("."));
+    match probe_write(parent) {
+        Ok(()) => report.warning(
+            "directories",
+            format!("the {what} ({dir}) doesn't exist and will be created"),
+        ),
+        Err(e) => report.error(
+            "directories",
+            format!(
+                "the {what} ({dir}) doesn't exist and can't be created in {}: {e}",
+                parent.display()
+            ),
+        ),
+    }
+}
+
+fn probe_write(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(PROBE_FILE);
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{check_dir, check_ports, Report, Severity},
+        crate::engine::config::ConfigEndpointTcp,
+        std::net::TcpListener,
+    };
+
+    #[test]
+    fn ports() {
+        let taken = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let taken = ConfigEndpointTcp::new("127.0.0.1".into(), taken.local_addr().unwrap().port());
+        let mut report = Report::default();
+        check_ports(&mut report, &[("tcp endpoint", &taken)]);
+        assert!(!report.passed());
+        // the same address twice is reported once, as a conflict
+        let mut report = Report::default();
+        check_ports(
+            &mut report,
+            &[("tcp endpoint", &taken), ("probe endpoint", &taken)],
+        );
+        assert_eq!(report.findings.len(), 2);
+        assert!(report.findings[1].message.contains("both use"));
+    }
+
+    #[test]
+    fn dirs() {
+        let mut report = Report::default();
+        check_dir(&mut report, "working directory", ".");
+        assert!(report.findings.is_empty());
+        // a directory that doesn't exist yet is created when we start
+        check_dir(
+            &mut report,
+            "archive directory",
+            "check-config-test/archive",
+        );
+        assert_eq!(report.findings[0].severity, Severity::Warning);
+        assert!(report.passed());
+        assert!(!std::path::Path::new("check-config-test").exists());
+        // a file can't be used
+        check_dir(&mut report, "journal volume", "Cargo.toml");
+        assert_eq!(report.findings[1].severity, Severity::Error);
+        assert!(!report.passed());
+        assert!(report.describe().starts_with(
+            "{\"ok\":false,\"findings\":[{\"check\":\"directories\",\"severity\":\"warning\","
+        ));
+    }
+}
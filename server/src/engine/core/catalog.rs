@@ -0,0 +1,303 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    system catalog
+    ---
+    the models in the `sys` space describe the instance itself, so that tools can introspect it with plain selects
+    (filters, projections and aggregates included) instead of a sysctl report for everything:
+
+    - `sys.spaces(name, uuid, models)`
+    - `sys.models(name, space_name, model_name, uuid, primary_key, fields, rows, indexes, view_of, decl)`
+    - `sys.fields(name, space_name, model_name, field_name, field_type, nullable, is_primary)`
+    - `sys.indexes(name, space_name, model_name, index_name, fields, ready, entries)`
+    - `sys.connections(id, username, root, connected_at, last_tag)`
+    - `sys.jobs(id, kind, target, owner, status, started_at, finished_at, done, total, progress)`
+
+    the key of a model, field or index is its full name (`space.model`, `space.model.field` and so on). no column is
+    named after a keyword (like `type`) since it couldn't be selected then. catalog models aren't stored anywhere:
+    every statement that reads one gets a model that is built from the live state and thrown away once the statement
+    is done, so a statement sees a consistent catalog but two statements can see different ones. catalog models can't be written to since writes only look at the models that exist, and the `sys` space
+    can't be created. an instance that already had a `sys` space keeps it, and then has no catalog
+*/
+
+use {
+    super::{
+        index::{DcFieldIndex, PrimaryIndexKey, Row},
+        model::{Field, Layer, ModelData, ModelProps},
+        EntityIDRef, GlobalNS,
+    },
+    crate::engine::{
+        data::{cell::Datacell, uuid::Uuid},
+        idx::{IndexBaseSpec, IndexSTSeqCns, STIndex, STIndexSeq},
+        net::connections,
+        sync::atm::cpin,
+    },
+};
+
+/// The space that holds the catalog models
+pub const SYS_SPACE: &str = "sys";
+
+/// Build the catalog model with the given name from the live state, or return [`None`] if it isn't a catalog model
+pub fn build(state: &GlobalNS, entity: EntityIDRef) -> Option<ModelData> {
+    if (entity.space() != SYS_SPACE) || state.namespace().contains_space(SYS_SPACE) {
+        return None;
+    }
+    Some(match entity.entity() {
+        "spaces" => spaces(state),
+        "models" => models(state),
+        "fields" => fields(state),
+        "indexes" => indexes(state),
+        "connections" => connections(),
+        "jobs" => jobs(state),
+        _ => return None,
+    })
+}
+
+fn spaces(state: &GlobalNS) -> ModelData {
+    let catalog = Catalog::new(&[
+        ("name", Layer::str(), false),
+        ("uuid", Layer::str(), false),
+        ("models", Layer::uint64(), false),
+    ]);
+    for (name, space) in state.namespace().idx().read().iter() {
+        catalog.push(vec![
+            string(name),
+            string(&space.get_uuid().to_string()),
+            uint(space.models().len() as u64),
+        ]);
+    }
+    catalog.into_model()
+}
+
+fn models(state: &GlobalNS) -> ModelData {
+    let catalog = Catalog::new(&[
+        ("name", Layer::str(), false),
+        ("space_name", Layer::str(), false),
+        ("model_name", Layer::str(), false),
+        ("uuid", Layer::str(), false),
+        ("primary_key", Layer::str(), false),
+        ("fields", Layer::uint64(), false),
+        ("rows", Layer::uint64(), false),
+        ("indexes", Layer::uint64(), false),
+        ("view_of", Layer::str(), true),
+        ("decl", Layer::str(), false),
+    ]);
+    for (entity, model) in state.namespace().idx_models().read().iter() {
+        let model = model.data();
+        catalog.push(vec![
+            string(&format!("{}.{}", entity.space(), entity.entity())),
+            string(entity.space()),
+            string(entity.entity()),
+            string(&model.get_uuid().to_string()),
+            string(model.p_key()),
+            uint(model.fields().st_len() as u64),
+            uint(model.primary_index().count() as u64),
+            uint(model.secondary_indexes().st_len() as u64),
+            model
+                .props()
+                .view()
+                .map_or_else(Datacell::null, |view| string(view.source())),
+            string(model.describe()),
+        ]);
+    }
+    catalog.into_model()
+}
+
+fn fields(state: &GlobalNS) -> ModelData {
+    let catalog = Catalog::new(&[
+        ("name", Layer::str(), false),
+        ("space_name", Layer::str(), false),
+        ("model_name", Layer::str(), false),
+        ("field_name", Layer::str(), false),
+        ("field_type", Layer::str(), false),
+        ("nullable", Layer::bool(), false),
+        ("is_primary", Layer::bool(), false),
+    ]);
+    for (entity, model) in state.namespace().idx_models().read().iter() {
+        let model = model.data();
+        for (field_name, field) in model.fields().stseq_ord_kv() {
+            catalog.push(vec![
+                string(&format!(
+                    "{}.{}.{}",
+                    entity.space(),
+                    entity.entity(),
+                    field_name.as_str()
+                )),
+                string(entity.space()),
+                string(entity.entity()),
+                string(field_name.as_str()),
+                string(&field.type_name()),
+                Datacell::new_bool(field.is_nullable()),
+                Datacell::new_bool(field_name.as_str() == model.p_key()),
+            ]);
+        }
+    }
+    catalog.into_model()
+}
+
+fn indexes(state: &GlobalNS) -> ModelData {
+    let catalog = Catalog::new(&[
+        ("name", Layer::str(), false),
+        ("space_name", Layer::str(), false),
+        ("model_name", Layer::str(), false),
+        ("index_name", Layer::str(), false),
+        ("fields", Layer::str(), false),
+        ("ready", Layer::bool(), false),
+        ("entries", Layer::uint64(), false),
+    ]);
+    for (entity, model) in state.namespace().idx_models().read().iter() {
+        for (name, idx) in model.data().secondary_indexes().stseq_ord_kv() {
+            catalog.push(vec![
+                string(&format!("{}.{}.{name}", entity.space(), entity.entity())),
+                string(entity.space()),
+                string(entity.entity()),
+                string(name),
+                string(&idx.fields().join(",")),
+                Datacell::new_bool(idx.is_ready()),
+                uint(idx.entries() as u64),
+            ]);
+        }
+    }
+    catalog.into_model()
+}
+
+fn connections() -> ModelData {
+    let catalog = Catalog::new(&[
+        ("id", Layer::uint64(), false),
+        ("username", Layer::str(), false),
+        ("root", Layer::bool(), false),
+        ("connected_at", Layer::uint64(), false),
+        ("last_tag", Layer::str(), true),
+    ]);
+    for connection in connections::list() {
+        catalog.push(vec![
+            uint(connection.id),
+            string(&connection.username),
+            Datacell::new_bool(connection.root),
+            uint(connection.connected_at),
+            connection
+                .last_tag
+                .as_deref()
+                .map_or_else(Datacell::null, string),
+        ]);
+    }
+    catalog.into_model()
+}
+
+fn jobs(state: &GlobalNS) -> ModelData {
+    let catalog = Catalog::new(&[
+        ("id", Layer::uint64(), false),
+        ("kind", Layer::str(), false),
+        ("target", Layer::str(), false),
+        ("owner", Layer::str(), true),
+        ("status", Layer::str(), false),
+        ("started_at", Layer::uint64(), false),
+        ("finished_at", Layer::uint64(), true),
+        ("done", Layer::uint64(), false),
+        ("total", Layer::uint64(), false),
+        ("progress", Layer::uint64(), false),
+    ]);
+    for job in state.jobs().records() {
+        catalog.push(vec![
+            uint(job.id),
+            string(job.kind.name()),
+            string(&job.target),
+            job.owner.as_deref().map_or_else(Datacell::null, string),
+            string(job.status.name()),
+            uint(job.started_at),
+            job.finished_at.map_or_else(Datacell::null, uint),
+            uint(job.done),
+            uint(job.total),
+            uint(job.percent()),
+        ]);
+    }
+    catalog.into_model()
+}
+
+fn string(s: &str) -> Datacell {
+    Datacell::new_str(s.into())
+}
+
+fn uint(u: u64) -> Datacell {
+    Datacell::new_uint_default(u)
+}
+
+/// A catalog model that is being filled
+struct Catalog {
+    model: ModelData,
+}
+
+impl Catalog {
+    /// Create a catalog model with the given fields (name, type and nullability), the first of which is the primary
+    /// key
+    fn new(fields: &[(&str, Layer, bool)]) -> Self {
+        let mut decl = IndexSTSeqCns::idx_init();
+        for (name, layer, nullable) in fields {
+            decl.st_insert(Box::from(*name), Field::new([*layer].into(), *nullable));
+        }
+        let (p_key, p_layer, _) = fields[0];
+        Self {
+            model: ModelData::new_restore_with_props(
+                Uuid::new(),
+                p_key.into(),
+                p_layer.tag(),
+                decl,
+                ModelProps::default(),
+            ),
+        }
+    }
+    /// Add a row, with its cells in the order in which the fields were declared
+    fn push(&self, mut cells: Vec<Datacell>) {
+        let model = &self.model;
+        let pk = PrimaryIndexKey::try_from_dc(cells.remove(0)).unwrap();
+        let mut fields = DcFieldIndex::idx_init_cap(model.fields().st_len());
+        let layout = model
+            .fields()
+            .stseq_ord_key()
+            .filter(|field_id| field_id.as_str() != model.p_key());
+        for (field_id, cell) in layout.zip(cells) {
+            fields.st_insert(
+                unsafe {
+                    // UNSAFE(@ohsayan): the row goes with the model that owns the allocation
+                    field_id.clone()
+                },
+                cell,
+            );
+        }
+        let ds = model.delta_state();
+        let row = Row::new(
+            pk,
+            fields,
+            ds.schema_current_version(),
+            ds.create_new_data_delta_version(),
+        );
+        let _ = model.primary_index().insert(row, &cpin());
+    }
+    fn into_model(self) -> ModelData {
+        self.model
+    }
+}
@@ -24,12 +24,49 @@
  *
 */
 
-use crate::engine::{
-    data::{tag::TagClass, DictEntryGeneric},
-    error::{QueryError, QueryResult},
-    fractal::GlobalInstanceLike,
-    net::protocol::ClientLocalState,
-    ql::dcl::{SysctlCommand, UserDecl, UserDel},
+use crate::{
+    engine::{
+        core::{
+            dml::{generate_rows, import_rdb, import_rdb_in_background, prefetch},
+            lock,
+            model::{
+                backfill_model, backfill_model_in_background, copy_model, copy_model_in_background,
+                create_webhook, describe_hot_keys, describe_indexes, describe_webhooks,
+                drop_webhook, export_models, export_models_in_background, link_views,
+                restore_model, restore_model_in_background, verify_model,
+                verify_model_in_background, Model,
+            },
+            profile, quota, record,
+            relocate::{relocate_data, relocate_data_in_background},
+            snapshot,
+            space::Space,
+            system_db::SystemDatabase,
+            EntityID, EntityIDRef,
+        },
+        data::{tag::TagClass, DictEntryGeneric},
+        error::{QueryError, QueryResult},
+        fractal::{self, soft_limit, throttle::Throttle, GlobalInstanceLike, ModelUniqueID},
+        idx::STIndexSeq,
+        mem,
+        net::{
+            probe,
+            protocol::{ClientLocalState, Response, ResponseType},
+        },
+        ql::{
+            dcl::{CreateStatement, DropStatement, QuotaTarget, SysctlCommand, UserDecl, UserDel},
+            lex::Ident,
+        },
+        storage::{safe_interfaces::paths_v1, DiskUsage, GNSExport, JournalExport},
+        txn::{
+            gns::{
+                model::{AlterModelQuotaTxn, CreateIndexTxn, CreateModelTxn},
+                space::CreateSpaceTxn,
+                sysctl::{AlterUserQuotaTxn, CreateStatementTxn, CreateUserTxn},
+            },
+            ModelIDRef, SpaceIDRef,
+        },
+    },
+    util::os,
 };
 
 const KEY_PASSWORD: &str = "password";
@@ -38,22 +75,763 @@ pub fn exec<G: GlobalInstanceLike>(
     g: G,
     current_user: &ClientLocalState,
     cmd: SysctlCommand,
-) -> QueryResult<()> {
+) -> QueryResult<Response> {
     if cmd.needs_root() & !current_user.is_root() {
         return Err(QueryError::SysPermissionDenied);
     }
+    if matches!(
+        cmd,
+        SysctlCommand::CreateUser(_)
+            | SysctlCommand::DropUser(_)
+            | SysctlCommand::AlterUser(_)
+            | SysctlCommand::AlterQuota {
+                target: QuotaTarget::User(_),
+                ..
+            }
+            | SysctlCommand::ImportGns { .. }
+    ) {
+        // users (like the rest of the schema) are kept in the GNS, which doesn't change while the instance is frozen
+        g.state().freeze().check_instance()?;
+    }
     match cmd {
-        SysctlCommand::CreateUser(new) => create_user(&g, new),
-        SysctlCommand::DropUser(drop) => drop_user(&g, current_user, drop),
-        SysctlCommand::AlterUser(usermod) => alter_user(&g, current_user, usermod),
-        SysctlCommand::ReportStatus => {
-            if g.health().status_okay() {
-                Ok(())
+        SysctlCommand::CreateUser(new) => create_user(&g, new).map(|_| Response::Empty),
+        SysctlCommand::DropUser(drop) => drop_user(&g, current_user, drop).map(|_| Response::Empty),
+        SysctlCommand::AlterUser(usermod) => {
+            alter_user(&g, current_user, usermod).map(|_| Response::Empty)
+        }
+        SysctlCommand::ReportStatus => report_status(&g),
+        SysctlCommand::ReportMemory => Ok(report_memory()),
+        SysctlCommand::ReportStorage => report_storage(&g),
+        SysctlCommand::ReportLive => Ok(report_probe(probe::describe_liveness())),
+        SysctlCommand::ReportReady => Ok(report_probe(
+            probe::Readiness::check(probe::phase(), Some(&g)).describe(),
+        )),
+        SysctlCommand::ReportLast => Ok(report_last(current_user)),
+        SysctlCommand::ReportProfile => {
+            let report = profile::describe();
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ReportScheduler => {
+            let report = g.scheduler().describe();
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ReportShardmap => {
+            let report = fractal::shardmap::describe();
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ReportPrefetch => {
+            let report = prefetch::describe();
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::DecodeJournal { entity, since } => decode_journal(&g, entity, since),
+        SysctlCommand::FlushModel { entity } => flush_model(&g, entity).map(|_| Response::Empty),
+        SysctlCommand::Freeze { space } => freeze(&g, space).map(|_| Response::Empty),
+        SysctlCommand::Unfreeze { space } => {
+            unfreeze(&g, space);
+            Ok(Response::Empty)
+        }
+        SysctlCommand::VerifyModel {
+            entity,
+            rebuild,
+            background: true,
+        } => verify_model_in_background(&g, current_user.username(), entity, rebuild)
+            .map(Response::UInt64),
+        SysctlCommand::VerifyModel {
+            entity,
+            rebuild,
+            background: false,
+        } => {
+            let report = verify_model(&g, entity, rebuild)?.describe();
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::BackfillModel {
+            entity,
+            background: true,
+        } => {
+            backfill_model_in_background(&g, current_user.username(), entity).map(Response::UInt64)
+        }
+        SysctlCommand::BackfillModel {
+            entity,
+            background: false,
+        } => {
+            let report = backfill_model(&g, entity)?.describe();
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::GenerateRows {
+            entity,
+            count,
+            seed,
+        } => {
+            let (rows, seed) = generate_rows(&g, entity, count, seed)?;
+            let report = format!("{{\"rows\":{rows},\"seed\":{seed}}}");
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::CopyModel {
+            entity,
+            from,
+            credentials,
+            background: true,
+        } => copy_model_in_background(&g, current_user.username(), entity, from, credentials)
+            .map(Response::UInt64),
+        SysctlCommand::CopyModel {
+            entity,
+            from,
+            credentials,
+            background: false,
+        } => copy_model(&g, entity, from, credentials).map(Response::UInt64),
+        SysctlCommand::RestoreModel {
+            entity,
+            snapshot,
+            target,
+            background: true,
+        } => restore_model_in_background(&g, current_user.username(), entity, snapshot, target)
+            .map(Response::UInt64),
+        SysctlCommand::RestoreModel {
+            entity,
+            snapshot,
+            target,
+            background: false,
+        } => restore_model(&g, entity, snapshot, target).map(Response::UInt64),
+        SysctlCommand::ExportGns { path } => {
+            GNSExport::write(path, g.state().namespace())?;
+            Ok(Response::Empty)
+        }
+        SysctlCommand::ExportModels {
+            entities,
+            path,
+            background: true,
+        } => export_models_in_background(&g, current_user.username(), &entities, path)
+            .map(Response::UInt64),
+        SysctlCommand::ExportModels {
+            entities,
+            path,
+            background: false,
+        } => {
+            let report = export_models(&g, &entities, path)?;
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ImportGns { path } => import_gns(&g, path).map(|_| Response::Empty),
+        SysctlCommand::ImportRdb {
+            path,
+            mapping,
+            background: true,
+        } => import_rdb_in_background(&g, current_user.username(), path, mapping)
+            .map(Response::UInt64),
+        SysctlCommand::ImportRdb {
+            path,
+            mapping,
+            background: false,
+        } => {
+            let report = import_rdb(&g, path, mapping)?;
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::CreateWebhook {
+            name,
+            entity,
+            props,
+        } => create_webhook(&g, name.as_str(), entity, props).map(|_| Response::Empty),
+        SysctlCommand::DropWebhook { name, entity } => {
+            drop_webhook(&g, name.as_str(), entity).map(|_| Response::Empty)
+        }
+        SysctlCommand::AlterQuota {
+            target: QuotaTarget::User(username),
+            props,
+        } => quota::alter_user_quota(&g, username.as_str(), props).map(|_| Response::Empty),
+        SysctlCommand::AlterQuota {
+            target: QuotaTarget::Model(entity),
+            props,
+        } => quota::alter_model_quota(&g, entity, props).map(|_| Response::Empty),
+        SysctlCommand::ReportQuotas => {
+            let report = quota::describe(&g);
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ReportWebhooks => {
+            let report = describe_webhooks(&g);
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ReportIndexes => {
+            let report = describe_indexes(&g);
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ReportHotKeys => {
+            let report = describe_hot_keys(&g);
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ListJobs => {
+            let report = g.state().jobs().describe();
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ListLocks => {
+            let report = lock::describe(&g);
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::CancelJob { id } => g.state().jobs().cancel(id).map(Response::Bool),
+        SysctlCommand::RecordStart { path, anonymized } => {
+            record::start(path, anonymized).map(|_| Response::Empty)
+        }
+        SysctlCommand::RecordStop => record::stop().map(Response::UInt64),
+        SysctlCommand::RelocateData {
+            path,
+            background: true,
+        } => relocate_data_in_background(&g, current_user.username(), path).map(Response::UInt64),
+        SysctlCommand::RelocateData {
+            path,
+            background: false,
+        } => {
+            let report = relocate_data(&g, path)?;
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::CreateSnapshot {
+            name,
+            background: true,
+        } => snapshot::create_snapshot_in_background(&g, current_user.username(), name.as_str())
+            .map(Response::UInt64),
+        SysctlCommand::CreateSnapshot {
+            name,
+            background: false,
+        } => {
+            let report = snapshot::create_snapshot(&g, name.as_str())?;
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+        SysctlCommand::ScheduleSnapshots { name, every, keep } => {
+            snapshot::schedule_snapshots(&g, name.as_str(), every, keep).map(|_| Response::Empty)
+        }
+        SysctlCommand::DropSnapshot { name } => {
+            snapshot::drop_snapshot(&g, name.as_str()).map(|_| Response::Empty)
+        }
+        SysctlCommand::DropSnapshotSchedule { name } => {
+            snapshot::drop_schedule(&g, name.as_str()).map(|_| Response::Empty)
+        }
+        SysctlCommand::ListSnapshots => {
+            let report = snapshot::describe(&g)?;
+            Ok(Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            })
+        }
+    }
+}
+
+/// Store a statement for the given user (see [`CreateStatement`])
+pub fn create_statement(
+    global: &impl GlobalInstanceLike,
+    username: &str,
+    stmt: CreateStatement,
+) -> QueryResult<()> {
+    // stored statements (like the rest of the schema) are kept in the GNS
+    global.state().freeze().check_instance()?;
+    let name: Box<str> = stmt.name().into();
+    global
+        .state()
+        .namespace()
+        .sys_db()
+        .create_statement(global, username, &name, stmt.into_body())
+}
+
+/// Drop one of the given user's stored statements
+pub fn drop_statement(
+    global: &impl GlobalInstanceLike,
+    username: &str,
+    stmt: DropStatement,
+) -> QueryResult<()> {
+    global.state().freeze().check_instance()?;
+    global
+        .state()
+        .namespace()
+        .sys_db()
+        .drop_statement(global, username, stmt.name())
+}
+
+/// Create the spaces, models (along with their secondary indexes) and users (along with their stored statements) in
+/// the schema package at `path` (see [`GNSExport`]). The instance must be empty, which means that it has no spaces
+/// and no users but root. The package is decoded in full before anything is created, and every object is committed
+/// to the GNS journal just like it would be if it were created with DDL
+pub(in crate::engine) fn import_gns(
+    global: &impl GlobalInstanceLike,
+    path: &str,
+) -> QueryResult<()> {
+    let package = GNSExport::read(path)?;
+    let mut package_models = std::mem::take(&mut *package.idx_models().write());
+    global
+        .state()
+        .namespace()
+        .ddl_with_all_mut(|spaces, models| {
+            // checked under the lock so that nothing changes after a freeze returns
+            global.state().freeze().check_instance()?;
+            let mut users = global.state().namespace().sys_db().users().write();
+            let has_users = users
+                .keys()
+                .any(|username| username.as_ref() != SystemDatabase::ROOT_ACCOUNT);
+            if !spaces.is_empty() | has_users {
+                return Err(QueryError::QExecDdlNotEmpty);
+            }
+            for (space_name, space) in std::mem::take(&mut *package.idx().write()) {
+                global.initialize_space(&space_name, space.get_uuid())?;
+                let txn = CreateSpaceTxn::new(space.props(), &space_name, &space);
+                global.state().gns_driver().driver_context(
+                    global,
+                    |drv| drv.commit_event(txn),
+                    || global.purge_space(&space_name, space.get_uuid()),
+                )?;
+                for model_name in space.models() {
+                    let model = package_models
+                        .remove(&EntityIDRef::new(&space_name, model_name))
+                        .unwrap();
+                    let model = import_model(global, &space_name, &space, model_name, model)?;
+                    models.insert(EntityID::new(&space_name, model_name), model);
+                }
+                spaces.insert(space_name, space);
+            }
+            link_views(models);
+            for (username, user) in std::mem::take(&mut *package.sys_db().users().write()) {
+                if username.as_ref() == SystemDatabase::ROOT_ACCOUNT {
+                    continue;
+                }
+                global.state().gns_driver().driver_context(
+                    global,
+                    |drv| drv.commit_event(CreateUserTxn::new(&username, user.hash())),
+                    || {},
+                )?;
+                for (name, statement) in user.statements() {
+                    global.state().gns_driver().driver_context(
+                        global,
+                        |drv| {
+                            drv.commit_event(CreateStatementTxn::new(
+                                &username,
+                                name,
+                                statement.body(),
+                            ))
+                        },
+                        || {},
+                    )?;
+                }
+                let quota = user.quota();
+                if !quota.is_unlimited() {
+                    global.state().gns_driver().driver_context(
+                        global,
+                        |drv| {
+                            drv.commit_event(AlterUserQuotaTxn::new(
+                                &username,
+                                quota.reads(),
+                                quota.writes(),
+                            ))
+                        },
+                        || {},
+                    )?;
+                }
+                users.insert(username, user);
+            }
+            Ok(())
+        })
+}
+
+/// Create a model (and its secondary indexes) from a schema package, in a space that was just imported
+fn import_model(
+    global: &impl GlobalInstanceLike,
+    space_name: &str,
+    space: &Space,
+    model_name: &str,
+    model: Model,
+) -> QueryResult<Model> {
+    let model = model.into_data();
+    let driver = global.initialize_model_driver(
+        space_name,
+        space.get_uuid(),
+        model_name,
+        model.get_uuid(),
+        model.primary_index().partitions(),
+    )?;
+    let txn = CreateModelTxn::new(SpaceIDRef::new(space_name, space), model_name, &model);
+    global.state().gns_driver().driver_context(
+        global,
+        |drv| drv.commit_event(txn),
+        || global.purge_model_driver(space_name, space.get_uuid(), model_name, model.get_uuid()),
+    )?;
+    for (index_name, index) in model.secondary_indexes().stseq_ord_kv() {
+        let filter = index.filter().map(|filter| filter.to_raw());
+        let txn = CreateIndexTxn::new(
+            ModelIDRef::new_ref(space_name, space, model_name, &model),
+            index_name,
+            index.fields(),
+            index.include(),
+            filter.as_ref(),
+            index.collation(),
+        );
+        global
+            .state()
+            .gns_driver()
+            .driver_context(global, |drv| drv.commit_event(txn), || {})?;
+        // the model is empty, so this only marks the index as ready
+        index.build(&model);
+    }
+    let quota = model.quota();
+    if !quota.is_unlimited() {
+        let txn = AlterModelQuotaTxn::new(
+            ModelIDRef::new_ref(space_name, space, model_name, &model),
+            quota.reads(),
+            quota.writes(),
+        );
+        global
+            .state()
+            .gns_driver()
+            .driver_context(global, |drv| drv.commit_event(txn), || {})?;
+    }
+    Ok(Model::new(model, driver))
+}
+
+/// Returns every event committed to the model's journal after the given LSN as JSON lines (see
+/// [`JournalExport`] for the format)
+fn decode_journal(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    since: Option<u64>,
+) -> QueryResult<Response> {
+    let export = {
+        // only hold the indexes while the journal is copied
+        let spaces = global.state().namespace().idx().read();
+        let models = global.state().namespace().idx_models().read();
+        let (Some(space), Some(model)) = (spaces.get(entity.space()), models.get(&entity)) else {
+            return Err(QueryError::QExecObjectNotFound);
+        };
+        if model.data().primary_index().partitions() != 1 {
+            // every partition has its own journal (and sequence of LSNs)
+            return Err(QueryError::QExecDdlInvalidProperties);
+        }
+        let journal_path = paths_v1::model_path(
+            entity.space(),
+            space.get_uuid(),
+            entity.entity(),
+            model.data().get_uuid(),
+        );
+        JournalExport::snapshot(model, 0, &journal_path)?
+    };
+    let lines = export.decode(since)?;
+    Ok(Response::Serialized {
+        ty: ResponseType::String,
+        size: lines.len(),
+        data: lines.into_bytes(),
+    })
+}
+
+/// Write the model's pending deltas right away (instead of waiting for the background flush)
+fn flush_model(global: &impl GlobalInstanceLike, entity: EntityIDRef) -> QueryResult<()> {
+    let models = global.state().namespace().idx_models().read();
+    let Some(model) = models.get(&entity) else {
+        return Err(QueryError::QExecObjectNotFound);
+    };
+    model.driver().flush(
+        global,
+        ModelUniqueID::new(entity.space(), entity.entity(), model.data().get_uuid()),
+        model.data(),
+    )
+}
+
+/// Freeze the instance (or a space) so that it rejects writes, and then write out everything that is pending for the
+/// models that the freeze covers. Once this returns, nothing in the data directory (or the space's directory) changes
+/// until it is unfrozen
+fn freeze(global: &impl GlobalInstanceLike, space: Option<Ident>) -> QueryResult<()> {
+    let freeze = global.state().freeze();
+    {
+        // hold the spaces so that the space can't be dropped (or created) while we freeze it
+        let spaces = global.state().namespace().idx().read();
+        match space {
+            Some(space) if !spaces.contains_key(space.as_str()) => {
+                return Err(QueryError::QExecObjectNotFound)
+            }
+            Some(space) => freeze.freeze_space(space.as_str()),
+            None => freeze.freeze_instance(),
+        }
+    }
+    // writes that got past the check before the freeze hold the models for reading, so wait for them to finish
+    drop(global.state().namespace().idx_models().write());
+    for (id, model) in global.state().namespace().idx_models().read().iter() {
+        if space.map_or(false, |space| space.as_str() != id.space()) {
+            continue;
+        }
+        model.driver().flush(
+            global,
+            ModelUniqueID::new(id.space(), id.entity(), model.data().get_uuid()),
+            model.data(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Lift the freeze on the instance (or a space). A space that isn't frozen is left as it is
+fn unfreeze(global: &impl GlobalInstanceLike, space: Option<Ident>) {
+    match space {
+        Some(space) => global.state().freeze().unfreeze_space(space.as_str()),
+        None => global.state().freeze().unfreeze_instance(),
+    }
+}
+
+/// Returns the result of a liveness or readiness probe (the same as the probe listener would serve)
+fn report_probe(report: String) -> Response {
+    Response::Serialized {
+        ty: ResponseType::String,
+        size: report.len(),
+        data: report.into_bytes(),
+    }
+}
+
+/// Returns the stats for the last statement run on this connection, or null if this is the first
+fn report_last(current_user: &ClientLocalState) -> Response {
+    match current_user.last_statement() {
+        Some(stats) => {
+            let report = stats.describe();
+            Response::Serialized {
+                ty: ResponseType::String,
+                size: report.len(),
+                data: report.into_bytes(),
+            }
+        }
+        None => Response::Null,
+    }
+}
+
+/// Returns the global allocator's statistics along with the resident set size of the process
+fn report_memory() -> Response {
+    let report = mem::allocator::describe();
+    Response::Serialized {
+        ty: ResponseType::String,
+        size: report.len(),
+        data: report.into_bytes(),
+    }
+}
+
+/// Returns an empty response if the server is healthy, all secondary indexes are ready and the server is not under
+/// memory pressure. Otherwise, the index rebuild progress and/or the memory state is returned. NUMA aware servers
+/// also report the memory stats of every NUMA node, and we report if explicit huge pages ran out or if statements
+/// were warned about going past the soft threshold of a limit
+fn report_status(global: &impl GlobalInstanceLike) -> QueryResult<Response> {
+    if !global.health().status_okay() {
+        return Err(QueryError::SysServerError);
+    }
+    let (mut ready, mut building, mut rows_indexed, mut rows_total) = (0usize, 0usize, 0, 0);
+    for mdl in global.state().namespace().idx_models().read().values() {
+        for idx in mdl.data().secondary_indexes().stseq_ord_value() {
+            if idx.is_ready() {
+                ready += 1;
             } else {
-                Err(QueryError::SysServerError)
+                building += 1;
+                rows_indexed += idx.progress();
+                rows_total += mdl.data().primary_index().count();
             }
         }
     }
+    let mut status = vec![];
+    if building != 0 {
+        status.push(format!(
+            "\"indexes\":{{\"ready\":{ready},\"building\":{building}}},\"rows_indexed\":{rows_indexed},\"rows_total\":{rows_total}"
+        ));
+    }
+    if global.health().is_degraded() | global.health().is_disk_full() {
+        status.push(format!(
+            "\"storage\":{{\"degraded\":{},\"models\":{},\"disk_full\":{}}}",
+            global.health().is_degraded(),
+            global.health().degraded_models(),
+            global.health().is_disk_full()
+        ));
+    }
+    if global.state().freeze().is_frozen() {
+        status.push(format!("\"frozen\":{}", global.state().freeze().describe()));
+    }
+    if let Some(warnings) = soft_limit::describe() {
+        status.push(format!("\"soft_limits\":{warnings}"));
+    }
+    let watermark = global.memory_watermark();
+    if watermark.under_pressure() {
+        status.push(format!(
+            "\"memory\":{{\"pressure\":true,\"usage\":{},\"high_watermark\":{},\"low_watermark\":{},\"rejected\":{}}}",
+            watermark.last_usage(),
+            watermark.high(),
+            watermark.low(),
+            watermark.rejected()
+        ));
+    }
+    let throttle = global.write_throttle();
+    if throttle.is_enabled() {
+        // the models that writes are held back for right now
+        let models: Vec<String> = global
+            .state()
+            .namespace()
+            .idx_models()
+            .read()
+            .iter()
+            .filter_map(|(entity, model)| {
+                let backlog = model.data().delta_state().pending_data_deltas() as u64;
+                let lag = model.data().webhook_lag();
+                (throttle.check(backlog, lag) != Throttle::None).then(|| {
+                    format!(
+                        "{{\"model\":\"{}.{}\",\"backlog\":{backlog},\"lag\":{lag}}}",
+                        entity.space(),
+                        entity.entity()
+                    )
+                })
+            })
+            .collect();
+        if !models.is_empty() | (throttle.delayed() != 0) | (throttle.rejected() != 0) {
+            status.push(format!(
+                "\"write_throttle\":{{\"backlog_threshold\":{},\"lag_threshold\":{},\"delayed\":{},\"delay_ms\":{},\"rejected\":{},\"models\":[{}]}}",
+                throttle.backlog_threshold(),
+                throttle.lag_threshold(),
+                throttle.delayed(),
+                throttle.delayed_for().as_millis(),
+                throttle.rejected(),
+                models.join(",")
+            ));
+        }
+    }
+    if global.is_numa_aware() {
+        let nodes: Vec<String> = os::numa_nodes()
+            .iter()
+            .map(|node| {
+                let memory = os::numa_node_memory(node.id());
+                format!(
+                    "{{\"node\":{},\"cpus\":{},\"total\":{},\"free\":{}}}",
+                    node.id(),
+                    node.cpus().len(),
+                    memory.map_or(0, |m| m.total),
+                    memory.map_or(0, |m| m.free)
+                )
+            })
+            .collect();
+        status.push(format!("\"numa\":[{}]", nodes.join(",")));
+    }
+    let placement = paths_v1::placement();
+    if !placement.is_default() {
+        status.push(format!("\"placement\":{}", placement.describe()));
+    }
+    // only explicit huge pages can run out
+    let huge_page_fallbacks = mem::arena::huge_page_fallbacks();
+    if huge_page_fallbacks != 0 {
+        status.push(format!(
+            "\"huge_pages\":{{\"explicit_fallbacks\":{huge_page_fallbacks}}}"
+        ));
+    }
+    if status.is_empty() {
+        return Ok(Response::Empty);
+    }
+    let status = format!("{{{}}}", status.join(","));
+    Ok(Response::Serialized {
+        ty: ResponseType::String,
+        size: status.len(),
+        data: status.into_bytes(),
+    })
+}
+
+/// Returns the space used on disk by the global namespace journal and by every space and model
+fn report_storage(global: &impl GlobalInstanceLike) -> QueryResult<Response> {
+    let journal = DiskUsage::gns_journal()?;
+    let mut total = journal;
+    let spaces = global.state().namespace().idx().read();
+    let models = global.state().namespace().idx_models().read();
+    let mut space_reports = vec![];
+    for (space_name, space) in spaces.iter() {
+        let mut model_reports = vec![];
+        for model_name in space.models() {
+            let Some(model) = models.get(&EntityIDRef::new(space_name, model_name)) else {
+                continue;
+            };
+            let usage = DiskUsage::of_model(
+                space_name,
+                space.get_uuid(),
+                model_name,
+                model.data().get_uuid(),
+            )?;
+            model_reports.push(format!(
+                "{{\"model\":\"{model_name}\",\"storage\":{},\"flush\":{}}}",
+                usage.describe(),
+                model.describe_flush()
+            ));
+        }
+        let usage = DiskUsage::of_space(space_name, space.get_uuid())?;
+        total += usage.total();
+        space_reports.push(format!(
+            "{{\"space\":\"{space_name}\",\"storage\":{},\"models\":[{}]}}",
+            usage.describe(),
+            model_reports.join(",")
+        ));
+    }
+    let archive = fractal::archive::get().map_or_else(|| "null".into(), |a| a.describe());
+    let report = format!(
+        "{{\"journal\":{journal},\"total\":{total},\"archive\":{archive},\"spaces\":[{}]}}",
+        space_reports.join(",")
+    );
+    Ok(Response::Serialized {
+        ty: ResponseType::String,
+        size: report.len(),
+        data: report.into_bytes(),
+    })
 }
 
 fn alter_user(
@@ -28,7 +28,8 @@ use crate::engine::{
     error::{QueryError, QueryResult},
     fractal::GlobalInstanceLike,
     net::protocol::{ClientLocalState, Response, ResponseType},
-    ql::ddl::Inspect,
+    ql::ddl::{Describe, Inspect},
+    storage::DiskUsage,
 };
 
 pub fn inspect(
@@ -70,21 +71,66 @@ pub fn inspect(
             ret.push_str("],\"settings\":{}}");
             ret
         }
-        Inspect::Model(m) => match g.state().namespace().idx_models().read().get(&m) {
-            Some(m) => {
-                let m = m.data();
-                format!(
-                    "{{\"decl\":\"{}\",\"rows\":{},\"properties\":{{}}}}",
-                    m.describe(),
-                    m.primary_index().count()
-                )
+        Inspect::Model(m) => {
+            let spaces = g.state().namespace().idx().read();
+            let models = g.state().namespace().idx_models().read();
+            match (spaces.get(m.space()), models.get(&m)) {
+                (Some(space), Some(model)) => {
+                    let mdl = model.data();
+                    let usage = DiskUsage::of_model(
+                        m.space(),
+                        space.get_uuid(),
+                        m.entity(),
+                        mdl.get_uuid(),
+                    )?;
+                    let mut ret = format!(
+                        "{{\"decl\":\"{}\",\"rows\":{},\"properties\":{},\"storage\":{},\"flush\":{}}}",
+                        mdl.describe(),
+                        mdl.primary_index().count(),
+                        mdl.props().describe(),
+                        usage.describe(),
+                        model.describe_flush()
+                    );
+                    if let Some(stats) = mdl.stats() {
+                        ret.pop();
+                        ret.push_str(",\"histograms\":");
+                        ret.push_str(&stats.describe());
+                        ret.push('}');
+                    }
+                    if let Some(cache) = mdl.result_cache() {
+                        ret.pop();
+                        ret.push_str(",\"result_cache\":");
+                        ret.push_str(&cache.describe());
+                        ret.push('}');
+                    }
+                    if let Some(overflow) = mdl.overflow() {
+                        ret.pop();
+                        ret.push_str(",\"overflow\":");
+                        ret.push_str(&overflow.describe());
+                        ret.push('}');
+                    }
+                    if let Some(columns) = mdl.columns() {
+                        ret.pop();
+                        ret.push_str(",\"columns\":");
+                        ret.push_str(&columns.describe());
+                        ret.push('}');
+                    }
+                    if let Some(maintained) = mdl.maintained() {
+                        ret.pop();
+                        ret.push_str(",\"maintained\":");
+                        ret.push_str(&maintained.describe());
+                        ret.push('}');
+                    }
+                    ret
+                }
+                _ => return Err(QueryError::QExecObjectNotFound),
             }
-            None => return Err(QueryError::QExecObjectNotFound),
-        },
+        }
         Inspect::Space(s) => match g.state().namespace().idx().read().get(s.as_str()) {
-            Some(s) => {
+            Some(space) => {
+                let usage = DiskUsage::of_space(s.as_str(), space.get_uuid())?;
                 let mut ret = format!("{{\"models\":[");
-                let mut models_iter = s.models().iter().peekable();
+                let mut models_iter = space.models().iter().peekable();
                 while let Some(mdl) = models_iter.next() {
                     ret.push('\"');
                     ret.push_str(&mdl);
@@ -93,7 +139,9 @@ pub fn inspect(
                         ret.push(',');
                     }
                 }
-                ret.push_str("]}}");
+                ret.push_str("],\"storage\":");
+                ret.push_str(&usage.describe());
+                ret.push('}');
                 ret
             }
             None => return Err(QueryError::QExecObjectNotFound),
@@ -105,3 +153,16 @@ pub fn inspect(
         data: ret.into_bytes(),
     })
 }
+
+/// Describe the schema of a model (see [`crate::engine::core::model::ModelData::describe_schema`])
+pub fn describe(g: &impl GlobalInstanceLike, stmt: Describe) -> QueryResult<Response> {
+    let Describe::Model(entity) = stmt;
+    let ret = g
+        .state()
+        .with_model(entity, |mdl| Ok(mdl.describe_schema()))?;
+    Ok(Response::Serialized {
+        ty: ResponseType::String,
+        size: ret.len(),
+        data: ret.into_bytes(),
+    })
+}
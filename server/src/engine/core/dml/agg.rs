@@ -0,0 +1,612 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{sel::RowIteratorAll, QueryMemBudget},
+    crate::engine::{
+        core::model::maintained::MaintainedAggregates,
+        data::{
+            cell::{Datacell, VirtualDatacell},
+            tag::TagClass,
+        },
+        error::{QueryError, QueryResult},
+        fractal::GlobalInstanceLike,
+        idx::STIndex,
+        mem::IntegerRepr,
+        net::protocol::{Response, ResponseType},
+        ql::{
+            dml::sel::{Aggregate, HavingExpr, SelectAggregateStatement},
+            lex::Ident,
+        },
+        sync,
+    },
+    std::{
+        collections::{hash_map::DefaultHasher, BTreeSet, HashMap},
+        hash::{Hash, Hasher},
+        mem,
+    },
+};
+
+/// Compute the aggregates over all the rows matching the where clause (which can use any field). See [`Aggregator`]
+/// for how the rows are grouped and returned. Without a where clause, grouped counts and sums that the model
+/// maintains are read off its totals (see [`crate::engine::core::model::maintained`]), and the aggregates over an
+/// analytic model are computed from its columns (see [`crate::engine::core::model::columnar`]).
+///
+/// Since this is a scan, it is rejected while the server is under memory pressure
+pub fn select_aggregate_resp(
+    global: &impl GlobalInstanceLike,
+    mut select: SelectAggregateStatement,
+) -> QueryResult<Response> {
+    global.admit_expensive_query()?;
+    let mut aggregator = Aggregator::new(select.aggregates(), select.group_by(), select.having());
+    global.state().with_model(select.entity(), |mdl| {
+        if aggregator
+            .fields()
+            .any(|field| !mdl.fields().st_contains(field.as_str()))
+        {
+            return Err(QueryError::QExecUnknownField);
+        }
+        for field in aggregator.fields() {
+            super::blob::check_not_tiered(mdl, field.as_str())?;
+        }
+        let mut budget = QueryMemBudget::new(global);
+        let unfiltered = select.clauses_mut().clauses_mut().is_empty();
+        if let Some(maintained) = mdl.maintained().filter(|_| {
+            unfiltered
+                & MaintainedAggregates::can_answer(
+                    mdl,
+                    aggregator.group_by(),
+                    aggregator.aggregates(),
+                )
+        }) {
+            // the groups are read off the totals, without a scan
+            let group_by = aggregator.group_by().unwrap();
+            return maintained.with_grouping(mdl, group_by.as_str(), |grouping| {
+                for (group, totals) in grouping.groups() {
+                    aggregator.add_totals(
+                        group,
+                        totals.count(),
+                        |field| grouping.sum(totals, field.as_str()),
+                        &mut budget,
+                    )?;
+                }
+                aggregator.finish(&mut budget)
+            });
+        }
+        if let Some(columns) = mdl.columns().filter(|_| unfiltered) {
+            // only the columns that the aggregates use are read
+            return columns.with_columns(mdl, |columns| {
+                for (slot, key) in columns.rows() {
+                    let pk = VirtualDatacell::new_pk(key, mdl.p_tag());
+                    aggregator.add_row(
+                        |field| {
+                            if field.as_str() == mdl.p_key() {
+                                &*pk
+                            } else {
+                                columns.cell(field.as_str(), slot).unwrap()
+                            }
+                        },
+                        &mut budget,
+                    )?;
+                }
+                aggregator.finish(&mut budget)
+            });
+        }
+        let (target, filter) = mdl.resolve_where_filtered(select.clauses_mut())?;
+        let g = sync::atm::cpin();
+        for row in RowIteratorAll::new_filtered(&g, mdl, usize::MAX, target, &filter)? {
+            let (key, data) = row?;
+            let pk = VirtualDatacell::new_pk(key, mdl.p_tag());
+            aggregator.add_row(
+                |field| {
+                    if field.as_str() == mdl.p_key() {
+                        &*pk
+                    } else {
+                        data.fields().st_get(field.as_str()).unwrap()
+                    }
+                },
+                &mut budget,
+            )?;
+        }
+        aggregator.finish(&mut budget)
+    })?;
+    Ok(aggregator.into_response(select.having()))
+}
+
+/// Computes a set of aggregates over rows. Without a `group by`, a single row with the aggregates is returned. With a
+/// `group by`, one row is returned per distinct value of the grouping field (in the order the groups were first
+/// seen), with the value of the grouping field followed by the aggregates, skipping the groups that don't satisfy the
+/// `having` predicates (which are evaluated like a where clause). Every group's state is held in memory (in a hash
+/// table keyed by the group value), so the query fails if they don't fit in the per-query memory budget
+pub(super) struct Aggregator<'a> {
+    /// the requested aggregates, followed by the ones only used by `having`
+    aggregates: Vec<Aggregate<'a>>,
+    /// the number of requested aggregates
+    projected: usize,
+    /// the aggregate that each `having` predicate compares
+    having_slots: Vec<usize>,
+    group_by: Option<Ident<'a>>,
+    groups: Groups,
+    null: Datacell,
+}
+
+impl<'a> Aggregator<'a> {
+    pub(super) fn new(
+        aggregates: &[Aggregate<'a>],
+        group_by: Option<Ident<'a>>,
+        having: &[HavingExpr<'a>],
+    ) -> Self {
+        let mut aggregates = aggregates.to_vec();
+        let projected = aggregates.len();
+        // the aggregates used by `having` are computed along with (but not returned with) the others
+        let having_slots = having
+            .iter()
+            .map(|having| {
+                let aggregate = having.aggregate();
+                aggregates
+                    .iter()
+                    .position(|a| *a == aggregate)
+                    .unwrap_or_else(|| {
+                        aggregates.push(aggregate);
+                        aggregates.len() - 1
+                    })
+            })
+            .collect();
+        Self {
+            aggregates,
+            projected,
+            having_slots,
+            group_by,
+            groups: Groups::new(group_by.is_some()),
+            null: Datacell::null(),
+        }
+    }
+    /// Returns all the fields that the aggregates and the grouping use
+    pub(super) fn fields(&self) -> impl Iterator<Item = Ident<'a>> + '_ {
+        self.aggregates
+            .iter()
+            .filter_map(Aggregate::field)
+            .chain(self.group_by)
+    }
+    /// Add a row, using `field` to get the value of each (existing) field
+    pub(super) fn add_row<'r>(
+        &mut self,
+        field: impl Fn(Ident) -> &'r Datacell,
+        budget: &mut QueryMemBudget,
+    ) -> QueryResult<()> {
+        let group = match self.group_by {
+            Some(group_by) => field(group_by),
+            None => &self.null,
+        };
+        let states = self.groups.get_or_insert(group, &self.aggregates, budget)?;
+        for (aggregate, state) in self.aggregates.iter().zip(states.iter_mut()) {
+            match aggregate.field() {
+                Some(aggregate_field) => {
+                    let value = field(aggregate_field);
+                    // nulls are never counted
+                    if !value.is_null() {
+                        state.add(value, budget)?;
+                    }
+                }
+                None => state.add(&self.null, budget)?,
+            }
+        }
+        Ok(())
+    }
+    /// Returns the requested aggregates, followed by the ones only used by `having`
+    pub(super) fn aggregates(&self) -> &[Aggregate<'a>] {
+        &self.aggregates
+    }
+    /// Returns the grouping field
+    pub(super) fn group_by(&self) -> Option<Ident<'a>> {
+        self.group_by
+    }
+    /// Add a group whose aggregates were already computed, given the number of rows in the group and the sum of each
+    /// summed field. Only `count(*)` and `sum` can be added this way
+    pub(super) fn add_totals(
+        &mut self,
+        group: &Datacell,
+        count: u64,
+        sum: impl Fn(Ident) -> Sum,
+        budget: &mut QueryMemBudget,
+    ) -> QueryResult<()> {
+        let states = self.groups.get_or_insert(group, &self.aggregates, budget)?;
+        for (aggregate, state) in self.aggregates.iter().zip(states.iter_mut()) {
+            *state = match aggregate {
+                Aggregate::Count => AggregateState::Count(count),
+                Aggregate::Sum(field) => AggregateState::Sum(sum(*field)),
+                Aggregate::CountDistinct(_) | Aggregate::TopK(..) => unreachable!(),
+            };
+        }
+        Ok(())
+    }
+    /// Call once all rows have been added
+    pub(super) fn finish(&mut self, budget: &mut QueryMemBudget) -> QueryResult<()> {
+        if self.group_by.is_none() {
+            // there's always a row, even if no rows matched
+            self.groups
+                .get_or_insert(&self.null, &self.aggregates, budget)?;
+        }
+        Ok(())
+    }
+    pub(super) fn into_response(self, having: &[HavingExpr]) -> Response {
+        let mut data = vec![];
+        if self.group_by.is_none() {
+            let (_, row) = self.groups.into_rows().next().unwrap();
+            for cell in row.iter() {
+                super::sel::encode_cell(&mut data, cell);
+            }
+            return Response::Serialized {
+                ty: ResponseType::Row,
+                size: row.len(),
+                data,
+            };
+        }
+        let mut rows = 0;
+        for (group, row) in self.groups.into_rows() {
+            let keep = having
+                .iter()
+                .zip(self.having_slots.iter())
+                .all(|(having, slot)| {
+                    having
+                        .expr()
+                        .eval(|lit| super::cmp_dc_lit(&row[*slot], lit))
+                });
+            if !keep {
+                continue;
+            }
+            IntegerRepr::scoped(self.projected as u64 + 1, |repr| data.extend(repr));
+            data.push(b'\n');
+            super::sel::encode_cell(&mut data, &group);
+            for cell in row[..self.projected].iter() {
+                super::sel::encode_cell(&mut data, cell);
+            }
+            rows += 1;
+        }
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: rows,
+            data,
+        }
+    }
+}
+
+/// The aggregate states of each group, in the order the groups were first seen
+struct Groups {
+    grouped: bool,
+    index: HashMap<CellKey, usize>,
+    groups: Vec<(Datacell, Vec<AggregateState>)>,
+}
+
+impl Groups {
+    fn new(grouped: bool) -> Self {
+        Self {
+            grouped,
+            index: HashMap::new(),
+            groups: Vec::new(),
+        }
+    }
+    /// Returns the states for the group with the given value, creating the group (and accounting for it in the
+    /// budget) if it doesn't exist yet
+    fn get_or_insert(
+        &mut self,
+        value: &Datacell,
+        aggregates: &[Aggregate],
+        budget: &mut QueryMemBudget,
+    ) -> QueryResult<&mut Vec<AggregateState>> {
+        let slot = match self.index.get(&CellKey(value.clone())) {
+            Some(&slot) => slot,
+            None => {
+                if self.grouped {
+                    // the value is kept twice (in the group and in the index)
+                    budget.charge(
+                        QueryMemBudget::cell_size(value) * 2
+                            + mem::size_of::<(Datacell, Vec<AggregateState>)>(),
+                    )?;
+                }
+                let states = aggregates
+                    .iter()
+                    .map(|aggregate| AggregateState::new(aggregate, budget))
+                    .collect::<QueryResult<Vec<_>>>()?;
+                let slot = self.groups.len();
+                self.index.insert(CellKey(value.clone()), slot);
+                self.groups.push((value.clone(), states));
+                slot
+            }
+        };
+        Ok(&mut self.groups[slot].1)
+    }
+    /// Returns the value and the aggregates of each group
+    fn into_rows(self) -> impl Iterator<Item = (Datacell, Vec<Datacell>)> {
+        self.groups.into_iter().map(|(value, states)| {
+            (
+                value,
+                states.into_iter().map(AggregateState::finish).collect(),
+            )
+        })
+    }
+}
+
+/// The running state of an aggregate
+enum AggregateState {
+    Count(u64),
+    CountDistinct(HyperLogLog),
+    TopK(SpaceSaving),
+    Sum(Sum),
+}
+
+impl AggregateState {
+    fn new(aggregate: &Aggregate, budget: &mut QueryMemBudget) -> QueryResult<Self> {
+        Ok(match aggregate {
+            Aggregate::Count => Self::Count(0),
+            Aggregate::CountDistinct(_) => {
+                budget.charge(HyperLogLog::REGISTERS)?;
+                Self::CountDistinct(HyperLogLog::new())
+            }
+            Aggregate::TopK(_, k) => Self::TopK(SpaceSaving::new(*k as usize)),
+            Aggregate::Sum(_) => Self::Sum(Sum::default()),
+        })
+    }
+    fn add(&mut self, value: &Datacell, budget: &mut QueryMemBudget) -> QueryResult<()> {
+        match self {
+            Self::Count(count) => {
+                *count += 1;
+                Ok(())
+            }
+            Self::CountDistinct(hll) => {
+                hll.add(hash_cell(value));
+                Ok(())
+            }
+            Self::TopK(sketch) => sketch.add(value, budget),
+            Self::Sum(sum) => {
+                if sum.add(value) {
+                    Ok(())
+                } else {
+                    Err(QueryError::QExecDmlValidationError)
+                }
+            }
+        }
+    }
+    /// Returns the result of the aggregate: the count for `count(*)`, the estimated count for `count(distinct)`, a
+    /// list of `[value, count]` pairs (most frequent first) for `topk` and the sum for `sum`
+    fn finish(self) -> Datacell {
+        match self {
+            Self::Sum(sum) => sum.finish(),
+            Self::Count(count) => Datacell::new_uint_default(count),
+            Self::CountDistinct(hll) => Datacell::new_uint_default(hll.estimate()),
+            Self::TopK(sketch) => Datacell::new_list(
+                sketch
+                    .top()
+                    .into_iter()
+                    .map(|(value, count)| {
+                        Datacell::new_list(vec![value, Datacell::new_uint_default(count)])
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// The running sum of numeric values. The sum is an unsigned integer if all the values were unsigned integers, a
+/// signed integer if any of them was a signed integer and a float if any of them was a float. Integer sums saturate
+/// instead of overflowing. Values can also be removed from the sum (see
+/// [`crate::engine::core::model::maintained`])
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Sum {
+    int: i128,
+    float: f64,
+    signed: bool,
+    floating: bool,
+}
+
+impl Sum {
+    /// Add a value to the sum. Returns false if the value isn't a number
+    pub fn add(&mut self, value: &Datacell) -> bool {
+        self.apply(value, 1)
+    }
+    /// Remove a value that was added to the sum
+    pub fn remove(&mut self, value: &Datacell) {
+        self.apply(value, -1);
+    }
+    fn apply(&mut self, value: &Datacell, sign: i8) -> bool {
+        match value.kind() {
+            TagClass::UnsignedInt => self.int += value.uint() as i128 * sign as i128,
+            TagClass::SignedInt => {
+                self.int += value.sint() as i128 * sign as i128;
+                self.signed = true;
+            }
+            TagClass::Float => {
+                self.float += value.float() * sign as f64;
+                self.floating = true;
+            }
+            _ => return false,
+        }
+        true
+    }
+    pub fn finish(&self) -> Datacell {
+        if self.floating {
+            Datacell::new_float_default(self.int as f64 + self.float)
+        } else if self.signed {
+            Datacell::new_sint_default(self.int.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+        } else {
+            Datacell::new_uint_default(self.int.clamp(0, u64::MAX as i128) as u64)
+        }
+    }
+}
+
+/// A cell that can be used as a hash map key (cells of different types are always distinct)
+#[derive(Debug, PartialEq, Eq)]
+pub struct CellKey(pub Datacell);
+
+impl Hash for CellKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_cell_into(&self.0, state)
+    }
+}
+
+fn hash_cell_into<H: Hasher>(dc: &Datacell, state: &mut H) {
+    // nulls are only ever used as group keys
+    dc.is_null().hash(state);
+    if dc.is_null() {
+        return;
+    }
+    dc.kind().hash(state);
+    match dc.kind() {
+        TagClass::Bool => dc.bool().hash(state),
+        TagClass::UnsignedInt => dc.uint().hash(state),
+        TagClass::SignedInt => dc.sint().hash(state),
+        // -0.0 == 0.0
+        TagClass::Float => (dc.float() + 0.0).to_bits().hash(state),
+        TagClass::Bin => dc.bin().hash(state),
+        TagClass::Str => dc.str().hash(state),
+        TagClass::List => {
+            let list = dc.list().read();
+            list.len().hash(state);
+            list.iter().for_each(|dc| hash_cell_into(dc, state));
+        }
+    }
+}
+
+/// Hash a cell. The hash is the same across queries and restarts
+fn hash_cell(dc: &Datacell) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_cell_into(dc, &mut hasher);
+    hasher.finish()
+}
+
+/*
+    HyperLogLog
+*/
+
+/// A HyperLogLog sketch that estimates the number of distinct values using a fixed amount of memory (with a standard
+/// error of about 0.8%)
+struct HyperLogLog {
+    registers: Box<[u8]>,
+}
+
+impl HyperLogLog {
+    /// the number of (high) bits of the hash used to pick a register
+    const PRECISION: u32 = 14;
+    const REGISTERS: usize = 1 << Self::PRECISION;
+    fn new() -> Self {
+        Self {
+            registers: vec![0; Self::REGISTERS].into_boxed_slice(),
+        }
+    }
+    fn add(&mut self, hash: u64) {
+        let register = (hash >> (64 - Self::PRECISION)) as usize;
+        // the position of the first set bit in the rest of the hash
+        let rank = ((hash << Self::PRECISION).leading_zeros() + 1).min(64 - Self::PRECISION + 1);
+        self.registers[register] = self.registers[register].max(rank as u8);
+    }
+    fn estimate(&self) -> u64 {
+        let m = Self::REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let (sum, zeros) = self
+            .registers
+            .iter()
+            .fold((0.0, 0usize), |(sum, zeros), r| {
+                (sum + 2f64.powi(-(*r as i32)), zeros + (*r == 0) as usize)
+            });
+        let estimate = alpha * m * m / sum;
+        if (estimate <= 2.5 * m) & (zeros != 0) {
+            // small range correction (linear counting)
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+/*
+    space-saving
+*/
+
+/// A space-saving sketch that tracks the most frequent values using a bounded number of counters. The counts are
+/// exact as long as the number of distinct values doesn't exceed the number of counters; otherwise they may be
+/// overestimated (by at most the smallest count at the time the value was last admitted)
+struct SpaceSaving {
+    k: usize,
+    capacity: usize,
+    counters: Vec<(Datacell, u64)>,
+    index: HashMap<CellKey, usize>,
+    /// the counters ordered by count (and then by position)
+    order: BTreeSet<(u64, usize)>,
+}
+
+impl SpaceSaving {
+    /// the number of counters kept for each value in the result
+    const COUNTERS_PER_K: usize = 10;
+    /// the smallest number of counters kept
+    const MIN_COUNTERS: usize = 64;
+    fn new(k: usize) -> Self {
+        Self {
+            k,
+            capacity: (k * Self::COUNTERS_PER_K).max(Self::MIN_COUNTERS),
+            counters: Vec::new(),
+            index: HashMap::new(),
+            order: BTreeSet::new(),
+        }
+    }
+    fn add(&mut self, value: &Datacell, budget: &mut QueryMemBudget) -> QueryResult<()> {
+        let key = CellKey(value.clone());
+        if let Some(&slot) = self.index.get(&key) {
+            let count = &mut self.counters[slot].1;
+            self.order.remove(&(*count, slot));
+            *count += 1;
+            self.order.insert((*count, slot));
+            return Ok(());
+        }
+        // the value is kept twice (in the counter and in the index)
+        budget.charge(QueryMemBudget::cell_size(value) * 2)?;
+        if self.counters.len() < self.capacity {
+            let slot = self.counters.len();
+            self.counters.push((value.clone(), 1));
+            self.index.insert(key, slot);
+            self.order.insert((1, slot));
+        } else {
+            // replace the least frequent value, which the new value inherits the count of
+            let (min, slot) = self.order.pop_first().unwrap();
+            let (evicted, _) =
+                core::mem::replace(&mut self.counters[slot], (value.clone(), min + 1));
+            budget.release(QueryMemBudget::cell_size(&evicted) * 2);
+            self.index.remove(&CellKey(evicted));
+            self.index.insert(key, slot);
+            self.order.insert((min + 1, slot));
+        }
+        Ok(())
+    }
+    /// Returns the (at most) `k` most frequent values, most frequent first
+    fn top(self) -> Vec<(Datacell, u64)> {
+        let mut counters = self.counters;
+        self.order
+            .iter()
+            .rev()
+            .take(self.k)
+            .map(|(_, slot)| core::mem::replace(&mut counters[*slot], (Datacell::null(), 0)))
+            .collect()
+    }
+}
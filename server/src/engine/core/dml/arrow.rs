@@ -0,0 +1,546 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    arrow streams
+    ---
+    `select all ... format arrow` returns the rows as an Arrow IPC stream (the streaming format of Arrow, with
+    version 5 of its metadata) that pandas, polars or DataFusion can read as they are. the stream is sent as a single
+    binary value: the schema, a record batch for every (up to) `ARROW_BATCH_ROWS` rows, and the end of the stream.
+    the schema has the selected fields in order, with these types:
+    - bool: Bool
+    - uintN and sintN: Int (of N bits, unsigned or signed)
+    - float32 and float64: FloatingPoint (single or double)
+    - binary: Binary, and string: Utf8
+    - list: List (of the type of its elements)
+    every field but the primary key is marked nullable. while the blob tier is enabled, binary fields that can be
+    tiered can't be exported (see `fractal::tier`). the buffers are uncompressed and aligned to 8 bytes. there are
+    only a handful of flatbuffers tables in the metadata, so they're written by hand (see [`FbBuilder`])
+*/
+
+use {
+    super::sel,
+    crate::engine::{
+        core::model::{Layer, ModelData},
+        data::{
+            cell::Datacell,
+            tag::{DataTag, TagSelector},
+        },
+        error::{QueryError, QueryResult},
+        fractal::{tier, GlobalInstanceLike},
+        idx::{STIndex, STIndexSeq},
+        net::protocol::{Response, ResponseType},
+        ql::dml::sel::SelectAllStatement,
+    },
+};
+
+/// The most rows in a record batch
+const ARROW_BATCH_ROWS: usize = 65536;
+/// The version of the metadata (V5)
+const METADATA_VERSION: i16 = 4;
+// message headers
+const HEADER_SCHEMA: u8 = 1;
+const HEADER_RECORD_BATCH: u8 = 3;
+// types
+const TYPE_INT: u8 = 2;
+const TYPE_FLOATING_POINT: u8 = 3;
+const TYPE_BINARY: u8 = 4;
+const TYPE_UTF8: u8 = 5;
+const TYPE_BOOL: u8 = 6;
+const TYPE_LIST: u8 = 12;
+// precisions
+const PRECISION_SINGLE: i16 = 1;
+const PRECISION_DOUBLE: i16 = 2;
+
+/// Run a `select all ... format arrow`, returning the rows as an Arrow IPC stream (see [`sel::select_all`])
+pub fn select_all_arrow_resp(
+    global: &impl GlobalInstanceLike,
+    select: SelectAllStatement,
+) -> QueryResult<Response> {
+    let mut stream = ArrowStream {
+        fields: (!select.wildcard).then(|| {
+            select
+                .fields
+                .iter()
+                .map(|field| field.as_str().into())
+                .collect()
+        }),
+        columns: vec![],
+        column: 0,
+        rows: 0,
+        out: vec![],
+        error: None,
+    };
+    sel::select_all(
+        global,
+        select,
+        &mut stream,
+        |stream, mdl, _| stream.start(mdl),
+        |stream, dc, _| stream.push(dc),
+    )?;
+    let data = stream.finish()?;
+    Ok(Response::Serialized {
+        ty: ResponseType::Binary,
+        size: data.len(),
+        data,
+    })
+}
+
+/// The stream that the rows of a scan are written to as they're read
+struct ArrowStream {
+    /// the selected fields, or [`None`] if all of them are
+    fields: Option<Vec<Box<str>>>,
+    columns: Vec<ArrowArray>,
+    /// the column of the next cell
+    column: usize,
+    /// the number of rows in the current batch
+    rows: usize,
+    out: Vec<u8>,
+    /// set if a batch couldn't be written, in which case the rest of the rows are ignored
+    error: Option<QueryError>,
+}
+
+impl ArrowStream {
+    /// Write the schema, which is that of the selected fields of the model
+    fn start(&mut self, mdl: &ModelData) {
+        let names: Vec<&str> = match &self.fields {
+            Some(fields) => fields.iter().map(|field| &**field).collect(),
+            None => mdl
+                .fields()
+                .stseq_ord_key()
+                .map(|field| field.as_str())
+                .collect(),
+        };
+        // the batches are written as the rows are read, so tiered blobs couldn't be swapped in for their references
+        if let Some(name) = names.iter().find(|name| mdl.is_tierable(name)) {
+            if tier::get().is_some() {
+                self.error = Some(QueryError::QExecDmlTieredField.with_detail("field", name));
+            }
+        }
+        let mut fields = vec![];
+        for name in names {
+            // the fields were checked by the scan
+            let layers = mdl.fields().st_get(name).unwrap().layers();
+            fields.push(schema_field(name, name != mdl.p_key(), layers));
+            self.columns.push(ArrowArray::new(layers));
+        }
+        let schema = Fb::Table(vec![Some(Fb::I16(0)), Some(Fb::Tables(fields))]);
+        write_message(&mut self.out, HEADER_SCHEMA, schema, &[]);
+    }
+    fn push(&mut self, dc: &Datacell) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(e) = self.columns[self.column].push(dc) {
+            self.error = Some(e);
+            return;
+        }
+        self.column += 1;
+        if self.column == self.columns.len() {
+            self.column = 0;
+            self.rows += 1;
+            if self.rows == ARROW_BATCH_ROWS {
+                self.write_batch();
+            }
+        }
+    }
+    fn write_batch(&mut self) {
+        let (mut nodes, mut buffers, mut body) = (vec![], vec![], vec![]);
+        for column in self.columns.iter_mut() {
+            column.write(&mut nodes, &mut buffers, &mut body);
+            column.clear();
+        }
+        let batch = Fb::Table(vec![
+            Some(Fb::I64(self.rows as i64)),
+            Some(Fb::Structs(nodes)),
+            Some(Fb::Structs(buffers)),
+        ]);
+        write_message(&mut self.out, HEADER_RECORD_BATCH, batch, &body);
+        self.rows = 0;
+    }
+    fn finish(mut self) -> QueryResult<Vec<u8>> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        if self.rows != 0 {
+            self.write_batch();
+        }
+        // the end of the stream
+        self.out.extend(u32::MAX.to_le_bytes());
+        self.out.extend(0u32.to_le_bytes());
+        Ok(self.out)
+    }
+}
+
+/// Returns the `Field` for a field with the given layers (its type, and the field of the elements if it's a list)
+fn schema_field(name: &str, nullable: bool, layers: &[Layer]) -> Fb {
+    let (ty, ty_table, children) = match layers[0].tag().tag_selector() {
+        TagSelector::Bool => (TYPE_BOOL, vec![], vec![]),
+        TagSelector::Float32 => (
+            TYPE_FLOATING_POINT,
+            vec![Some(Fb::I16(PRECISION_SINGLE))],
+            vec![],
+        ),
+        TagSelector::Float64 => (
+            TYPE_FLOATING_POINT,
+            vec![Some(Fb::I16(PRECISION_DOUBLE))],
+            vec![],
+        ),
+        TagSelector::Binary => (TYPE_BINARY, vec![], vec![]),
+        TagSelector::String => (TYPE_UTF8, vec![], vec![]),
+        TagSelector::List => (
+            TYPE_LIST,
+            vec![],
+            vec![schema_field("item", true, &layers[1..])],
+        ),
+        int => {
+            let signed = matches!(
+                int,
+                TagSelector::SInt8
+                    | TagSelector::SInt16
+                    | TagSelector::SInt32
+                    | TagSelector::SInt64
+            );
+            let width = ArrowArray::width(int) as i32 * 8;
+            (
+                TYPE_INT,
+                vec![Some(Fb::I32(width)), Some(Fb::U8(signed as u8))],
+                vec![],
+            )
+        }
+    };
+    Fb::Table(vec![
+        Some(Fb::Str(name.into())),
+        Some(Fb::U8(nullable as u8)),
+        Some(Fb::U8(ty)),
+        Some(Fb::Table(ty_table)),
+        None,
+        Some(Fb::Tables(children)),
+    ])
+}
+
+/// Write an encapsulated message: its metadata (prefixed by a continuation marker and its size) and its body
+fn write_message(out: &mut Vec<u8>, header_type: u8, header: Fb, body: &[u8]) {
+    let metadata = FbBuilder::finish(&Fb::Table(vec![
+        Some(Fb::I16(METADATA_VERSION)),
+        Some(Fb::U8(header_type)),
+        Some(header),
+        Some(Fb::I64(body.len() as i64)),
+    ]));
+    out.extend(u32::MAX.to_le_bytes());
+    out.extend((metadata.len() as u32).to_le_bytes());
+    out.extend(metadata);
+    out.extend(body);
+}
+
+/// Returns `len` rounded up to a multiple of `align` (which is a power of two)
+const fn align(len: usize, align: usize) -> usize {
+    (len + align - 1) & !(align - 1)
+}
+
+/*
+    arrays
+*/
+
+/// The array of a column of a record batch (or of the elements of the lists of a column)
+struct ArrowArray {
+    selector: TagSelector,
+    len: usize,
+    null_count: usize,
+    /// a bit for every value, set if it isn't null
+    validity: Vec<u8>,
+    /// the values (a bit for every bool), or the bytes of every binary or string
+    values: Vec<u8>,
+    /// where every binary, string or list starts (in the values or in the array of elements), and where the last ends
+    offsets: Vec<i32>,
+    /// the array of the elements of lists
+    child: Option<Box<ArrowArray>>,
+}
+
+impl ArrowArray {
+    fn new(layers: &[Layer]) -> Self {
+        let selector = layers[0].tag().tag_selector();
+        let mut array = Self {
+            selector,
+            len: 0,
+            null_count: 0,
+            validity: vec![],
+            values: vec![],
+            offsets: vec![],
+            child: (selector == TagSelector::List).then(|| Box::new(Self::new(&layers[1..]))),
+        };
+        array.clear();
+        array
+    }
+    /// Returns the size of every value, or 0 if it isn't a number
+    fn width(selector: TagSelector) -> usize {
+        match selector {
+            TagSelector::UInt8 | TagSelector::SInt8 => 1,
+            TagSelector::UInt16 | TagSelector::SInt16 => 2,
+            TagSelector::UInt32 | TagSelector::SInt32 | TagSelector::Float32 => 4,
+            TagSelector::UInt64 | TagSelector::SInt64 | TagSelector::Float64 => 8,
+            TagSelector::Bool | TagSelector::Binary | TagSelector::String | TagSelector::List => 0,
+        }
+    }
+    fn has_offsets(&self) -> bool {
+        matches!(
+            self.selector,
+            TagSelector::Binary | TagSelector::String | TagSelector::List
+        )
+    }
+    fn clear(&mut self) {
+        self.len = 0;
+        self.null_count = 0;
+        self.validity.clear();
+        self.values.clear();
+        self.offsets.clear();
+        if self.has_offsets() {
+            self.offsets.push(0);
+        }
+        if let Some(child) = self.child.as_mut() {
+            child.clear();
+        }
+    }
+    fn push(&mut self, dc: &Datacell) -> QueryResult<()> {
+        let i = self.len;
+        self.len += 1;
+        if i % 8 == 0 {
+            self.validity.push(0);
+            if self.selector == TagSelector::Bool {
+                self.values.push(0);
+            }
+        }
+        if dc.is_null() {
+            self.null_count += 1;
+            self.values
+                .resize(self.values.len() + Self::width(self.selector), 0);
+            self.end_value()?;
+            return Ok(());
+        }
+        self.validity[i / 8] |= 1 << (i % 8);
+        match self.selector {
+            TagSelector::Bool => self.values[i / 8] |= (dc.bool() as u8) << (i % 8),
+            TagSelector::UInt8 => self.values.push(dc.uint() as u8),
+            TagSelector::UInt16 => self.values.extend((dc.uint() as u16).to_le_bytes()),
+            TagSelector::UInt32 => self.values.extend((dc.uint() as u32).to_le_bytes()),
+            TagSelector::UInt64 => self.values.extend(dc.uint().to_le_bytes()),
+            TagSelector::SInt8 => self.values.push(dc.sint() as u8),
+            TagSelector::SInt16 => self.values.extend((dc.sint() as i16).to_le_bytes()),
+            TagSelector::SInt32 => self.values.extend((dc.sint() as i32).to_le_bytes()),
+            TagSelector::SInt64 => self.values.extend(dc.sint().to_le_bytes()),
+            TagSelector::Float32 => self.values.extend((dc.float() as f32).to_le_bytes()),
+            TagSelector::Float64 => self.values.extend(dc.float().to_le_bytes()),
+            TagSelector::Binary => self.values.extend_from_slice(dc.bin()),
+            TagSelector::String => self.values.extend_from_slice(dc.str().as_bytes()),
+            TagSelector::List => {
+                let child = self.child.as_mut().unwrap();
+                for element in dc.try_list().unwrap().read().iter() {
+                    child.push(element)?;
+                }
+            }
+        }
+        self.end_value()
+    }
+    /// Record where the value that was just pushed ends (if the array has offsets)
+    fn end_value(&mut self) -> QueryResult<()> {
+        let end = match &self.child {
+            Some(child) => child.len,
+            None if self.has_offsets() => self.values.len(),
+            None => return Ok(()),
+        };
+        // the offsets are 32 bits wide, so a batch can only hold so much
+        let end = i32::try_from(end).map_err(|_| QueryError::SysOutOfMemory)?;
+        self.offsets.push(end);
+        Ok(())
+    }
+    /// Write the node and the buffers of this array (and of its elements) to the body of a record batch
+    fn write(&self, nodes: &mut Vec<[i64; 2]>, buffers: &mut Vec<[i64; 2]>, body: &mut Vec<u8>) {
+        nodes.push([self.len as i64, self.null_count as i64]);
+        // the validity bitmap can be left out if there are no nulls
+        let validity = if self.null_count == 0 {
+            &self.validity[..0]
+        } else {
+            &self.validity[..]
+        };
+        write_buffer(buffers, body, validity);
+        if self.has_offsets() {
+            let offsets: Vec<u8> = self.offsets.iter().flat_map(|o| o.to_le_bytes()).collect();
+            write_buffer(buffers, body, &offsets);
+        }
+        match &self.child {
+            Some(child) => child.write(nodes, buffers, body),
+            None => write_buffer(buffers, body, &self.values),
+        }
+    }
+}
+
+/// Append a buffer to the body of a record batch, padding it to 8 bytes
+fn write_buffer(buffers: &mut Vec<[i64; 2]>, body: &mut Vec<u8>, data: &[u8]) {
+    buffers.push([body.len() as i64, data.len() as i64]);
+    body.extend_from_slice(data);
+    body.resize(align(body.len(), 8), 0);
+}
+
+/*
+    flatbuffers
+*/
+
+/// A value in a flatbuffer: a scalar or an offset to a table, a string or a vector. A table has its fields in the
+/// order of their ids (with a union taking two: its type, and then its value)
+#[derive(Debug)]
+enum Fb {
+    U8(u8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Str(Box<str>),
+    Table(Vec<Option<Fb>>),
+    Tables(Vec<Fb>),
+    /// a vector of structs of two longs (a `FieldNode` or a `Buffer`)
+    Structs(Vec<[i64; 2]>),
+}
+
+impl Fb {
+    fn inline_size(&self) -> usize {
+        match self {
+            Self::U8(_) => 1,
+            Self::I16(_) => 2,
+            Self::I64(_) => 8,
+            // offsets are 32 bits wide
+            _ => 4,
+        }
+    }
+}
+
+/// Writes a flatbuffer front to back: every table is preceded by its vtable and followed by the objects that it
+/// refers to, so that every offset points forward
+struct FbBuilder {
+    buf: Vec<u8>,
+}
+
+impl FbBuilder {
+    /// Returns the flatbuffer with the given table as its root, padded to 8 bytes
+    fn finish(root: &Fb) -> Vec<u8> {
+        let mut fb = Self { buf: vec![0; 4] };
+        let root = fb.write(root);
+        fb.patch(0, root);
+        fb.pad(8);
+        fb.buf
+    }
+    fn pad(&mut self, align: usize) {
+        self.buf.resize(self::align(self.buf.len(), align), 0);
+    }
+    /// Point the offset at `at` to `target`
+    fn patch(&mut self, at: usize, target: usize) {
+        self.buf[at..at + 4].copy_from_slice(&((target - at) as u32).to_le_bytes());
+    }
+    /// Write an object, returning where it starts (which is where offsets to it point to)
+    fn write(&mut self, object: &Fb) -> usize {
+        match object {
+            Fb::Table(fields) => self.write_table(fields),
+            Fb::Str(s) => {
+                self.pad(4);
+                let at = self.buf.len();
+                self.buf.extend((s.len() as u32).to_le_bytes());
+                self.buf.extend(s.as_bytes());
+                self.buf.push(0);
+                at
+            }
+            Fb::Tables(tables) => {
+                self.pad(4);
+                let at = self.buf.len();
+                self.buf.extend((tables.len() as u32).to_le_bytes());
+                let slots = self.buf.len();
+                self.buf.resize(slots + 4 * tables.len(), 0);
+                for (i, table) in tables.iter().enumerate() {
+                    let table = self.write(table);
+                    self.patch(slots + 4 * i, table);
+                }
+                at
+            }
+            Fb::Structs(structs) => {
+                // the structs must be aligned to 8 bytes, and they follow the length
+                self.pad(4);
+                if self.buf.len() % 8 == 0 {
+                    self.buf.extend([0; 4]);
+                }
+                let at = self.buf.len();
+                self.buf.extend((structs.len() as u32).to_le_bytes());
+                for [a, b] in structs {
+                    self.buf.extend(a.to_le_bytes());
+                    self.buf.extend(b.to_le_bytes());
+                }
+                at
+            }
+            Fb::U8(_) | Fb::I16(_) | Fb::I32(_) | Fb::I64(_) => {
+                unreachable!("scalars are always inline")
+            }
+        }
+    }
+    fn write_table(&mut self, fields: &[Option<Fb>]) -> usize {
+        // the vtable: its size, the size of the table and where every field is in the table (0 if it's absent)
+        self.pad(2);
+        let vtable = self.buf.len();
+        let vtable_size = 4 + 2 * fields.len();
+        self.buf.resize(vtable + vtable_size, 0);
+        // the table starts with (a negative) offset to its vtable, and is placed so that the fields that follow it
+        // are aligned if they're written from the largest to the smallest
+        while self.buf.len() % 8 != 4 {
+            self.buf.push(0);
+        }
+        let table = self.buf.len();
+        self.buf.extend(((table - vtable) as i32).to_le_bytes());
+        let mut objects = vec![];
+        for size in [8, 4, 2, 1] {
+            for (i, field) in fields.iter().enumerate() {
+                let Some(field) = field.as_ref().filter(|f| f.inline_size() == size) else {
+                    continue;
+                };
+                let at = self.buf.len();
+                let slot = vtable + 4 + 2 * i;
+                self.buf[slot..slot + 2].copy_from_slice(&((at - table) as u16).to_le_bytes());
+                match field {
+                    Fb::U8(v) => self.buf.push(*v),
+                    Fb::I16(v) => self.buf.extend(v.to_le_bytes()),
+                    Fb::I32(v) => self.buf.extend(v.to_le_bytes()),
+                    Fb::I64(v) => self.buf.extend(v.to_le_bytes()),
+                    object => {
+                        self.buf.extend([0; 4]);
+                        objects.push((at, object));
+                    }
+                }
+            }
+        }
+        let table_size = self.buf.len() - table;
+        self.buf[vtable..vtable + 2].copy_from_slice(&(vtable_size as u16).to_le_bytes());
+        self.buf[vtable + 2..vtable + 4].copy_from_slice(&(table_size as u16).to_le_bytes());
+        for (at, object) in objects {
+            let object = self.write(object);
+            self.patch(at, object);
+        }
+        table
+    }
+}
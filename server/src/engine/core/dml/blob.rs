@@ -0,0 +1,265 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::{
+    core::{
+        self,
+        dml::{upd, upstream, QueryExecMeta},
+        index::PrimaryIndexKey,
+        lock,
+        model::{delta::DataDeltaKind, ModelData},
+        query_meta::AssignmentOperator,
+        EntityIDRef,
+    },
+    data::{
+        cell::Datacell,
+        lit::Lit,
+        tag::{DataTag, TagClass},
+    },
+    error::{QueryError, QueryResult},
+    fractal::{jobs::CancelToken, tier, GlobalInstanceLike},
+    idx::{STIndex, STIndexSeq},
+    ql::{dml::upd::AssignmentExpression, lex::Ident},
+    sync,
+};
+
+/// Check that a blob can be written into the given field, so that an upload fails before any of it is sent: the
+/// model has to exist (and not cache an upstream) and the field has to be a binary field that isn't the primary key
+pub fn check_blob_target(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    field: &str,
+) -> QueryResult<()> {
+    global.state().namespace().with_model(entity, |mdl| {
+        upstream::check_not_upstream(mdl)?;
+        check_field(mdl, field)
+    })
+}
+
+/// Write a blob into the given field of the row with the given primary key (like an update, so the field's checks
+/// apply). Fails if the row doesn't exist
+pub fn write_blob(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    key: &Datacell,
+    field: &str,
+    data: &[u8],
+) -> QueryResult<()> {
+    core::with_model_for_data_update(global, entity, |mdl| {
+        upstream::check_not_upstream(mdl)?;
+        check_field(mdl, field)?;
+        let key = PrimaryIndexKey::try_clone_from_dc(key).ok_or(QueryError::QExecDmlRowNotFound)?;
+        let g = sync::atm::cpin();
+        let Some(row) = mdl.primary_index().select_key(&key, &g) else {
+            return Err(QueryError::QExecDmlRowNotFound);
+        };
+        let assignment = AssignmentExpression::new(
+            Ident::from(field),
+            Lit::new_bin(data),
+            AssignmentOperator::Assign,
+        );
+        upd::update_row(mdl, row, &[assignment], None, None, &g)
+            .map(|meta| meta.unwrap_or(QueryExecMeta::zero()))
+    })
+    .map(|_| ())
+}
+
+/// Read up to `len` bytes of a blob, starting at `offset` (nothing is read past the end of the blob). Returns
+/// [`None`] if the field is null. A tiered blob is returned as its reference, which is read with [`read_tiered_blob`]
+/// once the model is no longer held
+pub fn read_blob(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    key: &Datacell,
+    field: &str,
+    offset: u64,
+    len: u64,
+) -> QueryResult<Option<Vec<u8>>> {
+    global.state().namespace().with_model(entity, |mdl| {
+        check_field(mdl, field)?;
+        let key = PrimaryIndexKey::try_clone_from_dc(key).ok_or(QueryError::QExecDmlRowNotFound)?;
+        let g = sync::atm::cpin();
+        let Some(row) = mdl.primary_index().select_key(&key, &g) else {
+            return Err(QueryError::QExecDmlRowNotFound);
+        };
+        let row = lock::read(mdl, row)?;
+        if row.is_tombstoned() {
+            return Err(QueryError::QExecDmlRowNotFound);
+        }
+        let Some(blob) = row.fields().st_get(field).and_then(Datacell::try_bin) else {
+            return Ok(None);
+        };
+        if tier::get().map_or(false, |tier| tier.is_ref(blob)) {
+            return Ok(Some(blob.to_vec()));
+        }
+        Ok(Some(chunk(blob, offset, len).to_vec()))
+    })
+}
+
+/// Read up to `len` bytes of a tiered blob, starting at `offset`, given its reference. This fetches the blob if it
+/// isn't cached, so it has to run on a blocking task
+pub fn read_tiered_blob(reference: &[u8], offset: u64, len: u64) -> QueryResult<Vec<u8>> {
+    match tier::resolve(reference) {
+        Some(Ok(blob)) => Ok(chunk(&blob, offset, len).to_vec()),
+        Some(Err(_)) => Err(QueryError::SysBlobUnavailable),
+        None => Err(QueryError::SysServerError),
+    }
+}
+
+/// Returns up to `len` bytes of the blob, starting at `offset`
+fn chunk(blob: &[u8], offset: u64, len: u64) -> &[u8] {
+    let start = usize::try_from(offset).map_or(blob.len(), |offset| offset.min(blob.len()));
+    let end =
+        usize::try_from(len).map_or(blob.len(), |len| start.saturating_add(len).min(blob.len()));
+    &blob[start..end]
+}
+
+/// Move the binary values of a model that are larger than the blob tier's threshold to its bucket (up to
+/// [`tier::UPLOAD_MAX`] bytes, the rest are moved by a later run), stopping early if the token is cancelled. Returns
+/// the number of values that were moved.
+///
+/// A value is uploaded without its row locked, and is only swapped for its reference if it hasn't changed since. Only
+/// the fields that can be tiered are (see [`ModelData::is_tierable`])
+pub fn tier_blobs(model: &ModelData, token: &CancelToken) -> usize {
+    let Some(tier) = tier::get() else {
+        return 0;
+    };
+    let threshold = tier.threshold();
+    let fields: Vec<&str> = model
+        .fields()
+        .stseq_ord_key()
+        .map(|id| id.as_str())
+        .filter(|id| model.is_tierable(id))
+        .collect();
+    if fields.is_empty() {
+        return 0;
+    }
+    let to_tier = |data: &Datacell| {
+        data.try_bin()
+            .map_or(false, |blob| (blob.len() > threshold) && !tier.is_ref(blob))
+    };
+    let g = sync::atm::cpin();
+    let idx = model.primary_index();
+    // collect the values first since we can't upload them while the scan holds the index latch
+    let mut candidates: Vec<(PrimaryIndexKey, &str)> = vec![];
+    {
+        let _latch = idx.acquire_exclusive();
+        let mut size = 0usize;
+        // rows in the overflow store are left there, and tiered once they're read back
+        'scan: for row in idx.iter(&g).filter(|row| !row.is_spilled()) {
+            let Ok(data) = lock::read(model, row) else {
+                continue;
+            };
+            for field in fields.iter() {
+                if let Some(blob) = data.fields().st_get(*field).filter(|dc| to_tier(dc)) {
+                    if size >= tier::UPLOAD_MAX {
+                        break 'scan;
+                    }
+                    size += blob.try_bin().unwrap().len();
+                    candidates.push((row.d_key().clone(), *field));
+                }
+            }
+        }
+    }
+    let delta_state = model.delta_state();
+    let mut tiered = 0;
+    for (key, field) in candidates {
+        if token.is_cancelled() {
+            break;
+        }
+        let Some(row) = idx.select_key(&key, &g) else {
+            continue;
+        };
+        let Ok(data) = lock::read(model, row) else {
+            continue;
+        };
+        let Some(blob) = data
+            .fields()
+            .st_get(field)
+            .filter(|dc| to_tier(dc))
+            .map(|dc| dc.try_bin().unwrap().to_vec())
+        else {
+            continue;
+        };
+        drop(data);
+        let reference = match tier.put(&blob) {
+            Ok(reference) => reference,
+            Err(e) => {
+                warn!("tier: failed to upload blob: `{e}`. will try again later");
+                break;
+            }
+        };
+        let Ok(mut data) = lock::write(model, row) else {
+            continue;
+        };
+        if data.is_tombstoned() {
+            continue;
+        }
+        // the row might have changed while the blob was uploaded (if so, the object is left behind)
+        match data.fields_mut().st_get_mut(field) {
+            Some(dc) if dc.try_bin() == Some(&blob[..]) => {
+                *dc = Datacell::new_bin(reference.into())
+            }
+            _ => continue,
+        }
+        let new_version = delta_state.create_new_data_delta_version();
+        data.set_txn_revised(new_version);
+        drop(data);
+        model.views().touch(row.d_key());
+        if let Some(columns) = model.columns() {
+            columns.touch(row.d_key());
+        }
+        if let Some(maintained) = model.maintained() {
+            maintained.touch(row.d_key());
+        }
+        delta_state.append_new_data_delta_with(DataDeltaKind::Update, row.clone(), new_version, &g);
+        tiered += 1;
+    }
+    tiered
+}
+
+/// Check that a statement can filter on, aggregate or call a function with the given field. While the blob tier is
+/// enabled, it can't use a field whose values can be tiered, since it would only see the references
+pub(super) fn check_not_tiered(mdl: &ModelData, field: &str) -> QueryResult<()> {
+    if tier::get().is_some() && mdl.is_tierable(field) {
+        return Err(QueryError::QExecDmlTieredField.with_detail("field", field));
+    }
+    Ok(())
+}
+
+fn check_field(mdl: &ModelData, field: &str) -> QueryResult<()> {
+    match mdl.fields().st_get(field) {
+        Some(f)
+            if (field != mdl.p_key())
+                & !f.is_computed()
+                & (f.layers()[0].tag().tag_class() == TagClass::Bin) =>
+        {
+            Ok(())
+        }
+        Some(_) => Err(QueryError::QExecDmlValidationError.with_detail("field", field)),
+        None => Err(QueryError::QExecUnknownField.with_detail("field", field)),
+    }
+}
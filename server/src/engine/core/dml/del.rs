@@ -24,45 +24,329 @@
  *
 */
 
-use crate::engine::{
-    core::{self, dml::QueryExecMeta, model::delta::DataDeltaKind},
-    error::{QueryError, QueryResult},
-    fractal::GlobalInstanceLike,
-    idx::MTIndex,
-    net::protocol::Response,
-    ql::dml::del::DeleteStatement,
-    sync,
+use crate::{
+    engine::{
+        core::{
+            self,
+            dml::{
+                sel::RowIteratorAll, Mutation, OrderedRowLocks, QueryExecMeta, QueryMemBudget,
+                ReturningRows,
+            },
+            index::{DcFieldIndex, PrimaryIndexKey, Row, RowData},
+            lock,
+            model::{
+                delta::{DataDeltaKind, DeltaVersion},
+                history::RowHistory,
+                ModelData,
+            },
+            notice::{Notice, NoticeCode},
+            EntityIDRef,
+        },
+        error::{QueryError, QueryResult},
+        fractal::{jobs::CancelToken, GlobalInstanceLike},
+        net::protocol::Response,
+        ql::dml::{del::DeleteStatement, WhereClause},
+        sync,
+    },
+    util::os,
 };
 
 pub fn delete_resp(
     global: &impl GlobalInstanceLike,
     delete: DeleteStatement,
 ) -> QueryResult<Response> {
-    self::delete(global, delete).map(|_| Response::Empty)
+    let with_lsn = delete.with_lsn();
+    let (removed, version, returning) = self::delete_returning(global, delete)?;
+    Ok(match (removed, returning) {
+        _ if with_lsn => super::lsn_response(version),
+        (removed, Some(returning)) => returning.into_response(removed.is_none()),
+        (Some(removed), None) => Response::UInt64(removed),
+        (None, None) => Response::Empty,
+    })
+}
+
+/// Delete the rows selected by the where clause. A delete of a single primary key (without a limit) fails if the
+/// row doesn't exist and returns [`None`]; all other deletes return the number of rows that were removed.
+///
+/// If the model has soft deletes enabled, the rows are only marked with a tombstone and are purged later
+#[cfg(test)]
+pub fn delete(
+    global: &impl GlobalInstanceLike,
+    delete: DeleteStatement,
+) -> QueryResult<Option<u64>> {
+    self::delete_returning(global, delete).map(|(removed, _, _)| removed)
+}
+
+fn delete_returning<'a>(
+    global: &impl GlobalInstanceLike,
+    mut delete: DeleteStatement<'a>,
+) -> QueryResult<(Option<u64>, Option<DeltaVersion>, Option<ReturningRows<'a>>)> {
+    let limit = delete.limit();
+    let returning = delete.take_returning();
+    let mut removed = None;
+    let mut returning_rows = None;
+    let version = core::with_model_for_data_update(global, delete.entity(), |model| {
+        returning_rows =
+            ReturningRows::for_statement(model, returning, delete.summary(), Mutation::Delete)?;
+        if limit.is_none() & is_point_delete(model, delete.clauses_mut()) {
+            let _idx_latch = model.primary_index().acquire_cd();
+            return delete_point(model, delete.clauses_mut(), returning_rows.as_mut());
+        }
+        global.admit_expensive_query()?;
+        let entity = delete.entity();
+        let (meta, count) = delete_filtered(
+            model,
+            entity,
+            delete.clauses_mut(),
+            limit,
+            &mut QueryMemBudget::new(global),
+            returning_rows.as_mut(),
+        )?;
+        removed = Some(count);
+        Ok(meta)
+    })?;
+    Ok((removed, version, returning_rows))
+}
+
+pub(super) fn is_point_delete(model: &ModelData, where_clause: &mut WhereClause) -> bool {
+    let clauses = where_clause.clauses_mut();
+    (clauses.len() == 1)
+        & clauses
+            .get(model.p_key().as_bytes())
+            .map_or(false, |clause| clause.filter_hint_none())
+}
+
+/// Delete the row with the primary key given by the where clause, failing if it doesn't exist. Must be called with the
+/// index latch held
+pub(super) fn delete_point(
+    model: &ModelData,
+    where_clause: &mut WhereClause,
+    returning: Option<&mut ReturningRows>,
+) -> QueryResult<QueryExecMeta> {
+    let g = sync::atm::cpin();
+    let Some(row) = model
+        .primary_index()
+        .select(model.resolve_where(where_clause)?, &g)
+    else {
+        return Err(QueryError::QExecDmlRowNotFound);
+    };
+    model.hot_keys().record(row.d_key());
+    if model.props().soft_delete() {
+        return tombstone_row(model, row, os::get_epoch_time_secs(), returning, &g)?
+            .ok_or(QueryError::QExecDmlRowNotFound);
+    }
+    // the row is locked before it's removed, so that it's never left half removed if the lock can't be acquired
+    drop(lock::read(model, row)?);
+    let data = lock::write(model, row)?;
+    remove_locked(model, row, &data, returning, &g).ok_or(QueryError::QExecDmlRowNotFound)
+}
+
+fn delete_filtered(
+    model: &ModelData,
+    entity: EntityIDRef,
+    where_clause: &mut WhereClause,
+    limit: Option<u64>,
+    budget: &mut QueryMemBudget,
+    mut returning: Option<&mut ReturningRows>,
+) -> QueryResult<(QueryExecMeta, u64)> {
+    let (target, filter) = model.resolve_where_filtered(where_clause)?;
+    let g = sync::atm::cpin();
+    // collect the keys first since we can't remove rows while the scan holds the index latch
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+    let encodes_rows = returning
+        .as_deref()
+        .map_or(false, ReturningRows::encodes_rows);
+    let keys = RowIteratorAll::new_filtered(&g, model, limit, target, &filter)?
+        .collect_keys(budget, encodes_rows)?;
+    if keys.len() == limit {
+        Notice::raise(
+            NoticeCode::LimitReached,
+            format!("delete stopped at its limit of {limit} rows, so more rows might match"),
+        );
+    }
+    let _idx_latch = model.primary_index().acquire_cd();
+    // rows are locked in the same order as every other statement that locks several rows, so that two statements
+    // never wait for each other
+    let rows = keys
+        .iter()
+        // a row that was removed since the scan is skipped
+        .filter_map(|key| model.primary_index().select_key(key, &g))
+        .map(|row| (entity, model, row))
+        .collect();
+    let mut locks = OrderedRowLocks::write(rows)?;
+    // the row might have been updated or soft deleted since the scan
+    locks.retain(|row, data| {
+        !data.is_tombstoned() && filter.matches(model, row.d_key(), data.fields())
+    });
+    let mut meta = QueryExecMeta::zero();
+    let mut removed = 0;
+    let now = os::get_epoch_time_secs();
+    for (row, data) in locks.iter_mut() {
+        if model.props().soft_delete() {
+            meta = tombstone_locked(model, row, data, now, returning.as_deref_mut(), &g);
+            removed += 1;
+            continue;
+        }
+        if let Some(dp) = remove_locked(model, row, data, returning.as_deref_mut(), &g) {
+            meta = dp;
+            removed += 1;
+        }
+    }
+    Ok((meta, removed))
+}
+
+/// Remove a row that is locked from the primary index (and from the secondary indexes), and publish a delta. Returns
+/// [`None`] if the row was already removed
+fn remove_locked(
+    model: &ModelData,
+    row: &Row,
+    data: &RowData,
+    returning: Option<&mut ReturningRows>,
+    g: &sync::atm::Guard,
+) -> Option<QueryExecMeta> {
+    let delta_state = model.delta_state();
+    let new_version = delta_state.create_new_data_delta_version();
+    let row = model
+        .primary_index()
+        .delete_return_entry_key(row.d_key(), g)?;
+    model.sidx_remove_row(row.d_key(), data.fields());
+    record_history(model, row.d_key(), data.fields());
+    if let Some(returning) = returning {
+        returning.push_row(model, row.d_key(), data.fields());
+    }
+    model.views().touch(row.d_key());
+    if let Some(columns) = model.columns() {
+        columns.touch(row.d_key());
+    }
+    if let Some(maintained) = model.maintained() {
+        maintained.touch(row.d_key());
+    }
+    let dp =
+        delta_state.append_new_data_delta_with(DataDeltaKind::Delete, row.clone(), new_version, g);
+    Some(QueryExecMeta::new(dp, new_version))
+}
+
+/// Record the version of a row that was deleted, if the model keeps history
+fn record_history(model: &ModelData, key: &PrimaryIndexKey, fields: &DcFieldIndex) {
+    if let Some(history) = model.history() {
+        history.record(
+            key,
+            Some(RowHistory::snapshot(fields)),
+            os::get_epoch_time_secs(),
+        );
+    }
 }
 
-pub fn delete(global: &impl GlobalInstanceLike, mut delete: DeleteStatement) -> QueryResult<()> {
-    core::with_model_for_data_update(global, delete.entity(), |model| {
-        let g = sync::atm::cpin();
-        let delta_state = model.delta_state();
-        let _idx_latch = model.primary_index().acquire_cd();
-        // create new version
+/// Mark a row as deleted (instead of removing it) and publish a delta. Returns [`None`] if the row was already soft
+/// deleted
+fn tombstone_row(
+    model: &ModelData,
+    row: &Row,
+    now: u64,
+    returning: Option<&mut ReturningRows>,
+    g: &sync::atm::Guard,
+) -> QueryResult<Option<QueryExecMeta>> {
+    drop(lock::read(model, row)?);
+    let mut row_data = lock::write(model, row)?;
+    if row_data.is_tombstoned() {
+        return Ok(None);
+    }
+    Ok(Some(tombstone_locked(
+        model,
+        row,
+        &mut row_data,
+        now,
+        returning,
+        g,
+    )))
+}
+
+/// Mark a row that is locked for writing (and isn't soft deleted) as deleted, and publish a delta
+fn tombstone_locked(
+    model: &ModelData,
+    row: &Row,
+    row_data: &mut RowData,
+    now: u64,
+    returning: Option<&mut ReturningRows>,
+    g: &sync::atm::Guard,
+) -> QueryExecMeta {
+    let delta_state = model.delta_state();
+    if let Some(history) = model.history() {
+        history.record(
+            row.d_key(),
+            Some(RowHistory::snapshot(row_data.fields())),
+            now,
+        );
+    }
+    let new_version = delta_state.create_new_data_delta_version();
+    row_data.set_tombstone(Some(now));
+    row_data.set_txn_revised(new_version);
+    if let Some(returning) = returning {
+        returning.push_row(model, row.d_key(), row_data.fields());
+    }
+    model.views().touch(row.d_key());
+    if let Some(columns) = model.columns() {
+        columns.touch(row.d_key());
+    }
+    if let Some(maintained) = model.maintained() {
+        maintained.touch(row.d_key());
+    }
+    let dp =
+        delta_state.append_new_data_delta_with(DataDeltaKind::Update, row.clone(), new_version, g);
+    QueryExecMeta::new(dp, new_version)
+}
+
+/// Permanently remove the rows of a soft delete model whose tombstones are older than the model's retention period,
+/// stopping early if the token is cancelled (the rest are purged by a later sweep). Returns the number of rows that
+/// were purged
+pub fn purge_tombstones(model: &ModelData, now: u64, token: &CancelToken) -> usize {
+    if !model.props().soft_delete() {
+        return 0;
+    }
+    let retention = model.props().tombstone_retention();
+    let expired = |row: &Row| {
+        // a soft deleted row is never moved to the overflow store, so there's no need to read one back
+        !row.is_spilled()
+            && lock::read(model, row).map_or(false, |data| {
+                data.tombstone()
+                    .map_or(false, |at| at.saturating_add(retention) <= now)
+            })
+    };
+    let g = sync::atm::cpin();
+    let idx = model.primary_index();
+    // collect the keys first since we can't remove rows while the scan holds the index latch
+    let keys: Vec<PrimaryIndexKey> = {
+        let _latch = idx.acquire_exclusive();
+        idx.iter(&g)
+            .filter(|row| expired(row))
+            .map(|row| row.d_key().clone())
+            .collect()
+    };
+    let delta_state = model.delta_state();
+    let _idx_latch = idx.acquire_cd();
+    let mut purged = 0;
+    for key in keys {
+        if token.is_cancelled() {
+            break;
+        }
+        // the row might have been replaced by an insert since the scan
+        if !idx.select_key(&key, &g).map_or(false, expired) {
+            continue;
+        }
         let new_version = delta_state.create_new_data_delta_version();
-        match model
-            .primary_index()
-            .__raw_index()
-            .mt_delete_return_entry(&model.resolve_where(delete.clauses_mut())?, &g)
-        {
-            Some(row) => {
-                let dp = delta_state.append_new_data_delta_with(
-                    DataDeltaKind::Delete,
-                    row.clone(),
-                    new_version,
-                    &g,
-                );
-                Ok(QueryExecMeta::new(dp))
+        if let Some(row) = idx.delete_return_entry_key(&key, &g) {
+            // soft deleted rows aren't moved to the overflow store, so this can't fail
+            if let Ok(data) = lock::read(model, row) {
+                model.sidx_remove_row(row.d_key(), data.fields());
             }
-            None => Err(QueryError::QExecDmlRowNotFound),
+            delta_state.append_new_data_delta_with(
+                DataDeltaKind::Delete,
+                row.clone(),
+                new_version,
+                &g,
+            );
+            purged += 1;
         }
-    })
+    }
+    purged
 }
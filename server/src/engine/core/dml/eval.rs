@@ -0,0 +1,84 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::{
+    core::{
+        self,
+        dml::{del, ins, upd, upstream, QueryExecMeta},
+        lock,
+    },
+    error::{QueryError, QueryResult},
+    fractal::GlobalInstanceLike,
+    ql::dml::eval::{EvalAction, EvalStatement},
+    sync,
+};
+
+/// Check whether the row exists and run the action that the outcome picks (if there is one), returning the outcome
+/// and the number of rows that the action changed.
+///
+/// The model's index is latched exclusively from the check until the action is done, so no row can be inserted or
+/// removed in between. The condition and any update or delete must look up a single primary key
+pub fn eval(global: &impl GlobalInstanceLike, mut eval: EvalStatement) -> QueryResult<(bool, u64)> {
+    let mut outcome = false;
+    let mut changed = 0;
+    core::with_model_for_data_update(global, eval.entity(), |mdl| {
+        upstream::check_not_upstream(mdl)?;
+        if !upd::is_point_update(mdl, eval.condition_mut()) {
+            return Err(QueryError::QExecDmlWhereHasUnindexedColumn);
+        }
+        let key = mdl.resolve_where(eval.condition_mut())?;
+        let _idx_latch = mdl.primary_index().acquire_exclusive();
+        let g = sync::atm::cpin();
+        let exists = match mdl.primary_index().select(key, &g) {
+            Some(row) => !lock::read(mdl, row)?.is_tombstoned(),
+            None => false,
+        };
+        outcome = exists ^ eval.negated();
+        let meta = match eval.into_action(outcome) {
+            Some(EvalAction::Insert(insert)) => {
+                let (pk, data) = ins::prepare_insert(mdl, insert.data())?;
+                ins::insert_prepared(mdl, pk, data, None, &g)?
+            }
+            Some(EvalAction::Update(mut update)) => {
+                if !upd::is_point_update(mdl, update.clauses_mut()) {
+                    return Err(QueryError::QExecDmlWhereHasUnindexedColumn);
+                }
+                upd::check_expressions(mdl, &update)?;
+                upd::update_point(mdl, &mut update, None)?
+            }
+            Some(EvalAction::Delete(mut delete)) => {
+                if !del::is_point_delete(mdl, delete.clauses_mut()) {
+                    return Err(QueryError::QExecDmlWhereHasUnindexedColumn);
+                }
+                del::delete_point(mdl, delete.clauses_mut(), None)?
+            }
+            None => return Ok(QueryExecMeta::zero()),
+        };
+        changed = 1;
+        Ok(meta)
+    })?;
+    Ok((outcome, changed))
+}
@@ -0,0 +1,781 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    expressions
+    ---
+    the value on the right hand side of a `where` comparison or of an `update` assignment can be an expression over
+    datetimes. there's no separate datetime type: a datetime is an unsigned integer holding the number of seconds
+    since the unix epoch (just like `@timesec()` and `as of`), and an interval is an unsigned integer holding a number
+    of seconds. so:
+
+    - `now()` is the current time
+    - `7 d` (or `7d`, when the query is not parameterized) is an interval. the units are `s`, `m`, `h`, `d` and `w`,
+    along with `second`, `minute`, `hour`, `day` and `week` (and their plurals)
+    - `a + b` and `a - b` add and subtract unsigned integers. an overflow (or underflow) is an error
+    - `year(t)`, `month(t)`, `day(t)`, `hour(t)`, `minute(t)`, `second(t)` and `weekday(t)` (1 is monday) extract a
+    part of a datetime, in UTC
+
+    an expression is evaluated once, when the statement is parsed. this means that `now()` has the same value for every
+    row that a statement touches
+
+    string functions
+    ---
+    `lower(s)`, `upper(s)`, `trim(s)`, `len(s)` (the number of characters), `concat(s1, s2, ...)` and
+    `substr(s, start[, len])` (positions start at 1, and count characters) can be used in the projection of a select,
+    on the left hand side of a `where` comparison and on the right hand side of an `update` assignment. their
+    arguments can be fields, literals or other string functions. unlike the datetime expressions, they're evaluated
+    for every row. they're type checked against the model's fields before any row is read, and a null argument makes
+    the result null
+
+    numeric functions
+    ---
+    `abs(x)`, `min(x1, x2, ...)`, `max(x1, x2, ...)`, `round(x[, digits])` (half away from zero; integers are left
+    as they are) and `cast(x as <type>)` can be used wherever the string functions can, and the two can be nested in
+    each other. a value can be cast into any numeric type (`uint8` to `float64`) or into a `string`. `min` and `max` convert their
+    arguments into a float if one is a float, else into a signed integer if one is signed. `fixed(x, scale)` formats an
+    integer as a decimal with `scale` digits after the point, which is how a `decimal` field (see
+    [`crate::engine::core::model::decimal`]) is displayed: `fixed(1234, 2)` is `"12.34"`.
+
+    conversions are checked: casting into a type that can't hold the value (say, 300 into a `uint8`, -1 into any
+    unsigned integer, 2.5 into an integer (round it first), a large integer into a float that can't represent it exactly, or "abc"
+    into a number) is an error, and so is `abs` of the smallest `sint64`. such an error fails a select or an update,
+    while a filter just leaves out the row for which it happened
+
+    window counters
+    ---
+    `window_count(f, seconds)` returns the number of events that the window counter `f` recorded in the last
+    `seconds` (see [`crate::engine::core::model::window`]). it can be used wherever the string functions can, but its
+    first argument must be a window counter field
+*/
+
+use {
+    crate::{
+        engine::{
+            core::model::{decimal, window::WindowCounter, ModelData, Numeric},
+            data::{
+                cell::Datacell,
+                lit::Lit,
+                tag::{DataTag, FloatSpec, SIntSpec, TagClass, TagSelector, UIntSpec},
+            },
+            error::{QueryError, QueryResult},
+            idx::STIndex,
+            ql::dml::RowExpr,
+        },
+        util::{compiler, os},
+    },
+    std::{borrow::Cow, cmp::Ordering},
+};
+
+/// The maximum number of nested function calls in an expression
+pub const MAX_DEPTH: usize = 8;
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A function that can be used in an expression
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExprFn {
+    Now,
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Weekday,
+}
+
+impl ExprFn {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "now" => Some(Self::Now),
+            "year" => Some(Self::Year),
+            "month" => Some(Self::Month),
+            "day" => Some(Self::Day),
+            "hour" => Some(Self::Hour),
+            "minute" => Some(Self::Minute),
+            "second" => Some(Self::Second),
+            "weekday" => Some(Self::Weekday),
+            _ => None,
+        }
+    }
+    /// Returns true if this function takes a datetime (every function but `now()` does)
+    pub const fn takes_arg(&self) -> bool {
+        !matches!(self, Self::Now)
+    }
+    /// Apply this function. `t` is ignored by `now()`
+    pub fn apply(&self, t: u64) -> u64 {
+        let (days, secs) = (t / SECS_PER_DAY, t % SECS_PER_DAY);
+        match self {
+            Self::Now => os::get_epoch_time_secs(),
+            Self::Year => civil_from_days(days).0,
+            Self::Month => civil_from_days(days).1,
+            Self::Day => civil_from_days(days).2,
+            Self::Hour => secs / 3600,
+            Self::Minute => secs % 3600 / 60,
+            Self::Second => secs % 60,
+            // the epoch was a thursday
+            Self::Weekday => (days + 3) % 7 + 1,
+        }
+    }
+}
+
+/// An arithmetic operator
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArithOp {
+    Add,
+    Sub,
+}
+
+impl ArithOp {
+    /// Returns [`None`] if the result overflows (or underflows)
+    pub fn apply(&self, a: u64, b: u64) -> Option<u64> {
+        match self {
+            Self::Add => a.checked_add(b),
+            Self::Sub => a.checked_sub(b),
+        }
+    }
+}
+
+/// A function that's evaluated for every row: a string or a numeric function (see the module docs)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RowFn {
+    Lower,
+    Upper,
+    Trim,
+    Len,
+    Concat,
+    Substr,
+    Abs,
+    Min,
+    Max,
+    Round,
+    Fixed,
+    WindowCount,
+}
+
+impl RowFn {
+    /// The maximum number of arguments that `concat`, `min` and `max` accept
+    const VARIADIC_MAX_ARGS: usize = 16;
+    /// Rounding to this many decimal places (or more) leaves a float unchanged
+    const ROUND_MAX_DIGITS: u64 = 16;
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "lower" => Some(Self::Lower),
+            "upper" => Some(Self::Upper),
+            "trim" => Some(Self::Trim),
+            "len" => Some(Self::Len),
+            "concat" => Some(Self::Concat),
+            "substr" => Some(Self::Substr),
+            "abs" => Some(Self::Abs),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "round" => Some(Self::Round),
+            "fixed" => Some(Self::Fixed),
+            "window_count" => Some(Self::WindowCount),
+            _ => None,
+        }
+    }
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+            Self::Trim => "trim",
+            Self::Len => "len",
+            Self::Concat => "concat",
+            Self::Substr => "substr",
+            Self::Abs => "abs",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Round => "round",
+            Self::Fixed => "fixed",
+            Self::WindowCount => "window_count",
+        }
+    }
+    /// Returns the minimum and maximum number of arguments
+    pub const fn arity(&self) -> (usize, usize) {
+        match self {
+            Self::Concat => (1, Self::VARIADIC_MAX_ARGS),
+            Self::Min | Self::Max => (2, Self::VARIADIC_MAX_ARGS),
+            Self::Substr => (2, 3),
+            Self::Round => (1, 2),
+            Self::Fixed | Self::WindowCount => (2, 2),
+            _ => (1, 1),
+        }
+    }
+    /// Returns the type of the result given the types of the arguments, or [`None`] if an argument has the wrong type
+    fn returns(&self, args: &[TagClass]) -> Option<TagClass> {
+        let is_uint = |class: &TagClass| *class == TagClass::UnsignedInt;
+        match self {
+            Self::Lower | Self::Upper | Self::Trim | Self::Concat => args
+                .iter()
+                .all(|class| *class == TagClass::Str)
+                .then_some(TagClass::Str),
+            Self::Len => (args[0] == TagClass::Str).then_some(TagClass::UnsignedInt),
+            Self::Substr => ((args[0] == TagClass::Str) & args[1..].iter().all(is_uint))
+                .then_some(TagClass::Str),
+            Self::Abs => is_numeric(args[0]).then_some(args[0]),
+            Self::Round => (is_numeric(args[0]) & args[1..].iter().all(is_uint)).then_some(args[0]),
+            Self::Min | Self::Max => args
+                .iter()
+                .all(|class| is_numeric(*class))
+                .then(|| common_class(args.iter().copied())),
+            Self::Fixed => (matches!(args[0], TagClass::UnsignedInt | TagClass::SignedInt)
+                & is_uint(&args[1]))
+            .then_some(TagClass::Str),
+            Self::WindowCount => {
+                ((args[0] == TagClass::List) & is_uint(&args[1])).then_some(TagClass::UnsignedInt)
+            }
+        }
+    }
+    /// Apply this function. Fails if the result doesn't fit (for example, `abs` of the smallest sint64)
+    fn apply<'r>(&self, args: Vec<Value<'r>>) -> QueryResult<Value<'r>> {
+        if args.iter().any(|arg| matches!(arg, Value::Null)) {
+            return Ok(Value::Null);
+        }
+        let mut args = args.into_iter();
+        let (Some(first), rest) = (args.next(), args) else {
+            return Ok(Value::Null);
+        };
+        let ret = match (self, first) {
+            (Self::Min | Self::Max, first) => {
+                let nums: Option<Vec<Numeric>> = [first]
+                    .into_iter()
+                    .chain(rest)
+                    .map(Value::into_num)
+                    .collect();
+                nums.and_then(|nums| self.extremum(nums)).map(Value::Num)
+            }
+            (Self::Abs, Value::Num(n)) => match n {
+                Numeric::UInt(_) => Some(n),
+                Numeric::SInt(s) => s.checked_abs().map(Numeric::SInt),
+                Numeric::Float(f) => Some(Numeric::Float(f.abs())),
+            }
+            .map(Value::Num),
+            (Self::Round, Value::Num(n)) => {
+                let digits = match rest.map(Value::into_num).next() {
+                    Some(Some(Numeric::UInt(digits))) => digits,
+                    _ => 0,
+                };
+                Some(Value::Num(match n {
+                    Numeric::Float(f) if digits < Self::ROUND_MAX_DIGITS => {
+                        let scale = 10f64.powi(digits as i32);
+                        let scaled = f * scale;
+                        Numeric::Float(if scaled.is_finite() {
+                            scaled.round() / scale
+                        } else {
+                            f
+                        })
+                    }
+                    n => n,
+                }))
+            }
+            (Self::Fixed, Value::Num(n)) => {
+                let int = match n {
+                    Numeric::UInt(u) => u as i128,
+                    Numeric::SInt(s) => s as i128,
+                    Numeric::Float(_) => return Ok(Value::Null),
+                };
+                match rest.map(Value::into_num).next() {
+                    Some(Some(Numeric::UInt(scale))) => {
+                        decimal::fixed(int, scale).map(|s| Value::Str(Cow::Owned(s)))
+                    }
+                    _ => Some(Value::Null),
+                }
+            }
+            (_, Value::Str(s)) => Some(self.apply_str(s, rest)),
+            _ => Some(Value::Null),
+        };
+        match ret {
+            Some(ret) => Ok(ret),
+            None => compiler::cold_rerr(
+                QueryError::QExecDmlValidationError.with_detail("function", self.name()),
+            ),
+        }
+    }
+    fn apply_str<'r>(
+        &self,
+        s: Cow<'r, str>,
+        mut args: impl Iterator<Item = Value<'r>>,
+    ) -> Value<'r> {
+        match self {
+            Self::Lower => Value::Str(Cow::Owned(s.to_lowercase())),
+            Self::Upper => Value::Str(Cow::Owned(s.to_uppercase())),
+            Self::Trim => Value::Str(match s {
+                Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+                Cow::Owned(s) => Cow::Owned(s.trim().to_owned()),
+            }),
+            Self::Len => Value::Num(Numeric::UInt(s.chars().count() as u64)),
+            Self::Concat => {
+                let mut ret = s.into_owned();
+                for arg in args {
+                    match arg {
+                        Value::Str(s) => ret.push_str(&s),
+                        _ => return Value::Null,
+                    }
+                }
+                Value::Str(Cow::Owned(ret))
+            }
+            Self::Substr => {
+                let (start, len) = match (
+                    args.next().and_then(Value::into_num),
+                    args.next().and_then(Value::into_num),
+                ) {
+                    (Some(Numeric::UInt(start)), None) => (start, u64::MAX),
+                    (Some(Numeric::UInt(start)), Some(Numeric::UInt(len))) => (start, len),
+                    _ => return Value::Null,
+                };
+                let skip = usize::try_from(start.saturating_sub(1)).unwrap_or(usize::MAX);
+                let take = usize::try_from(len).unwrap_or(usize::MAX);
+                Value::Str(Cow::Owned(s.chars().skip(skip).take(take).collect()))
+            }
+            _ => Value::Null,
+        }
+    }
+    /// Returns the smallest (`min`) or the largest (`max`) number, after converting all of them into their common
+    /// type. Returns [`None`] if one of them can't be converted
+    fn extremum(&self, nums: Vec<Numeric>) -> Option<Numeric> {
+        let class = common_class(nums.iter().map(Numeric::class));
+        let mut ret = None;
+        for n in nums {
+            let n = convert(n, class)?;
+            let ord = match (ret, n) {
+                (Some(Numeric::UInt(a)), Numeric::UInt(b)) => b.partial_cmp(&a),
+                (Some(Numeric::SInt(a)), Numeric::SInt(b)) => b.partial_cmp(&a),
+                (Some(Numeric::Float(a)), Numeric::Float(b)) => b.partial_cmp(&a),
+                _ => None,
+            };
+            let replace = ret.is_none()
+                | matches!(
+                    (self, ord),
+                    (Self::Min, Some(Ordering::Less)) | (Self::Max, Some(Ordering::Greater))
+                );
+            if replace {
+                ret = Some(n);
+            }
+        }
+        ret
+    }
+}
+
+/// Returns the type that `cast(x as <type>)` converts into, given its name
+pub fn cast_target(name: &str) -> Option<TagSelector> {
+    let ty = match name {
+        "uint8" => TagSelector::UInt8,
+        "uint16" => TagSelector::UInt16,
+        "uint32" => TagSelector::UInt32,
+        "uint64" => TagSelector::UInt64,
+        "sint8" => TagSelector::SInt8,
+        "sint16" => TagSelector::SInt16,
+        "sint32" => TagSelector::SInt32,
+        "sint64" => TagSelector::SInt64,
+        "float32" => TagSelector::Float32,
+        "float64" => TagSelector::Float64,
+        "string" => TagSelector::String,
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// Cast a value into the given type. Returns [`None`] if it doesn't fit: a number that's out of the type's range
+/// (or that isn't a whole number, when casting a float into an integer) or a string that isn't a valid number
+fn cast<'r>(v: Value<'r>, to: TagSelector) -> Option<Value<'r>> {
+    let n = match (v, to.tag_class()) {
+        (Value::Null, _) => return Some(Value::Null),
+        (Value::Str(s), TagClass::Str) => return Some(Value::Str(s)),
+        (Value::Num(n), TagClass::Str) => {
+            let s = match n {
+                Numeric::UInt(u) => u.to_string(),
+                Numeric::SInt(s) => s.to_string(),
+                Numeric::Float(f) => f.to_string(),
+            };
+            return Some(Value::Str(Cow::Owned(s)));
+        }
+        (Value::Str(s), TagClass::UnsignedInt) => Numeric::UInt(s.parse().ok()?),
+        (Value::Str(s), TagClass::SignedInt) => Numeric::SInt(s.parse().ok()?),
+        (Value::Str(s), TagClass::Float) => Numeric::Float(s.parse().ok()?),
+        (Value::Num(n), class) => convert(n, class)?,
+        (Value::Str(_), _) => return None,
+    };
+    let tag = to.into_full();
+    let fits = unsafe {
+        // UNSAFE(@ohsayan): the tag class was matched above
+        match n {
+            Numeric::UInt(u) => UIntSpec::from_full(tag).check(u),
+            Numeric::SInt(s) => SIntSpec::from_full(tag).check(s),
+            Numeric::Float(f) => f.is_finite() & FloatSpec::from_full(tag).check(f),
+        }
+    };
+    let n = match n {
+        Numeric::Float(f) if to == TagSelector::Float32 => Numeric::Float(f as f32 as f64),
+        n => n,
+    };
+    fits.then_some(Value::Num(n))
+}
+
+/// Convert a number into the given class, unless that loses information (see [`Numeric::coerce`])
+fn convert(n: Numeric, class: TagClass) -> Option<Numeric> {
+    if n.class() == class {
+        Some(n)
+    } else {
+        n.coerce(class)
+    }
+}
+
+const fn is_numeric(class: TagClass) -> bool {
+    matches!(
+        class,
+        TagClass::UnsignedInt | TagClass::SignedInt | TagClass::Float
+    )
+}
+
+/// Returns the type that all of the given numeric types can be converted into: a float if there's one, else a signed
+/// integer if there's one, else an unsigned integer
+fn common_class(classes: impl Iterator<Item = TagClass>) -> TagClass {
+    classes.fold(TagClass::UnsignedInt, |a, b| match (a, b) {
+        (TagClass::Float, _) | (_, TagClass::Float) => TagClass::Float,
+        (TagClass::SignedInt, _) | (_, TagClass::SignedInt) => TagClass::SignedInt,
+        _ => TagClass::UnsignedInt,
+    })
+}
+
+/// A value computed by a function (or one of its arguments)
+enum Value<'r> {
+    Null,
+    Str(Cow<'r, str>),
+    Num(Numeric),
+}
+
+impl<'r> Value<'r> {
+    fn from_cell(dc: &'r Datacell) -> Self {
+        if dc.is_null() {
+            return Self::Null;
+        }
+        let v = match dc.kind() {
+            TagClass::Str => dc.try_str().map(|s| Self::Str(Cow::Borrowed(s))),
+            TagClass::UnsignedInt => dc.try_uint().map(|u| Self::Num(Numeric::UInt(u))),
+            TagClass::SignedInt => dc.try_sint().map(|s| Self::Num(Numeric::SInt(s))),
+            TagClass::Float => dc.try_float().map(|f| Self::Num(Numeric::Float(f))),
+            _ => None,
+        };
+        v.unwrap_or(Self::Null)
+    }
+    fn from_lit(lit: &Lit<'r>) -> Self {
+        let v = match lit.kind().tag_class() {
+            TagClass::Str => lit.try_str().map(|s| Self::Str(Cow::Borrowed(s))),
+            TagClass::UnsignedInt => lit.try_uint().map(|u| Self::Num(Numeric::UInt(u))),
+            TagClass::SignedInt => lit.try_sint().map(|s| Self::Num(Numeric::SInt(s))),
+            TagClass::Float => lit.try_float().map(|f| Self::Num(Numeric::Float(f))),
+            _ => None,
+        };
+        v.unwrap_or(Self::Null)
+    }
+    fn into_num(self) -> Option<Numeric> {
+        match self {
+            Self::Num(n) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+/// Type check an expression against the fields of the model, returning the type of its result. Fails if a field
+/// doesn't exist or if an argument has the wrong type
+pub fn check(expr: &RowExpr, mdl: &ModelData) -> QueryResult<TagClass> {
+    let ret = match expr {
+        RowExpr::Field(name) => {
+            return match mdl.fields().st_get(name.as_str()) {
+                Some(field) => Ok(field.layers()[0].tag().tag_class()),
+                None => compiler::cold_rerr(
+                    QueryError::QExecUnknownField.with_detail("field", name.as_str()),
+                ),
+            }
+        }
+        RowExpr::Value(lit) => return Ok(lit.kind().tag_class()),
+        // only a window counter field can be counted
+        RowExpr::Call(RowFn::WindowCount, args)
+            if !matches!(&args[0], RowExpr::Field(name) if mdl
+                .fields()
+                .st_get(name.as_str())
+                .map_or(false, |field| field.window().is_some())) =>
+        {
+            Err(RowFn::WindowCount.name())
+        }
+        RowExpr::Call(f, args) => {
+            let args = args
+                .iter()
+                .map(|arg| check_arg(arg, mdl))
+                .collect::<QueryResult<Vec<_>>>()?;
+            f.returns(&args).ok_or(f.name())
+        }
+        RowExpr::Cast(arg, to) => {
+            let class = check_arg(arg, mdl)?;
+            (is_numeric(class) | (class == TagClass::Str))
+                .then_some(to.tag_class())
+                .ok_or("cast")
+        }
+    };
+    match ret {
+        Ok(class) => Ok(class),
+        Err(function) => compiler::cold_rerr(
+            QueryError::QExecDmlValidationError.with_detail("function", function),
+        ),
+    }
+}
+
+/// Type check the argument of a function (or a cast), which can't be a field whose values can be tiered
+fn check_arg(arg: &RowExpr, mdl: &ModelData) -> QueryResult<TagClass> {
+    if let RowExpr::Field(name) = arg {
+        super::blob::check_not_tiered(mdl, name.as_str())?;
+    }
+    check(arg, mdl)
+}
+
+/// Evaluate an expression for a row, using `field` to get the value of each field (a missing field is null). Fails
+/// if a function's result (or a cast) doesn't fit
+pub fn eval<'r>(
+    expr: &RowExpr,
+    field: impl Fn(&str) -> Option<&'r Datacell>,
+) -> QueryResult<Datacell> {
+    let ret = match value(expr, &field)? {
+        Value::Null => Datacell::null(),
+        Value::Str(s) => Datacell::new_str(s.into()),
+        Value::Num(Numeric::UInt(u)) => Datacell::new_uint_default(u),
+        Value::Num(Numeric::SInt(s)) => Datacell::new_sint_default(s),
+        Value::Num(Numeric::Float(f)) => Datacell::new_float_default(f),
+    };
+    Ok(ret)
+}
+
+/// Evaluate an expression for a row, like [`eval`]. Returns [`None`] if the result is null
+pub fn eval_lit<'r>(
+    expr: &RowExpr,
+    field: impl Fn(&str) -> Option<&'r Datacell>,
+) -> QueryResult<Option<Lit<'static>>> {
+    let ret = match value(expr, &field)? {
+        Value::Null => None,
+        Value::Str(s) => Some(Lit::new_string(s.into_owned())),
+        Value::Num(Numeric::UInt(u)) => Some(Lit::new_uint(u)),
+        Value::Num(Numeric::SInt(s)) => Some(Lit::new_sint(s)),
+        Value::Num(Numeric::Float(f)) => Some(Lit::new_float(f)),
+    };
+    Ok(ret)
+}
+
+fn value<'e, 'r: 'e>(
+    expr: &'e RowExpr<'e>,
+    field: &impl Fn(&str) -> Option<&'r Datacell>,
+) -> QueryResult<Value<'e>> {
+    match expr {
+        RowExpr::Field(name) => Ok(field(name.as_str()).map_or(Value::Null, Value::from_cell)),
+        RowExpr::Value(lit) => Ok(Value::from_lit(lit)),
+        // the counter is read as it is (see [`crate::engine::core::model::window`])
+        RowExpr::Call(RowFn::WindowCount, args) => {
+            let counter = match &args[0] {
+                RowExpr::Field(name) => field(name.as_str()),
+                _ => None,
+            };
+            let secs = value(&args[1], field)?.into_num();
+            Ok(match (counter, secs) {
+                (Some(counter), Some(Numeric::UInt(secs))) => Value::Num(Numeric::UInt(
+                    WindowCounter::count(counter, secs, os::get_epoch_time_secs()),
+                )),
+                _ => Value::Null,
+            })
+        }
+        RowExpr::Call(f, args) => f.apply(
+            args.iter()
+                .map(|arg| value(arg, field))
+                .collect::<QueryResult<_>>()?,
+        ),
+        RowExpr::Cast(arg, to) => match cast(value(arg, field)?, *to) {
+            Some(v) => Ok(v),
+            None => compiler::cold_rerr(
+                QueryError::QExecDmlValidationError.with_detail("function", "cast"),
+            ),
+        },
+    }
+}
+
+/// Returns the number of seconds in the given interval unit, if it is one
+pub fn interval_unit(unit: &[u8]) -> Option<u64> {
+    let secs = match unit {
+        b"s" | b"second" | b"seconds" => 1,
+        b"m" | b"minute" | b"minutes" => 60,
+        b"h" | b"hour" | b"hours" => 60 * 60,
+        b"d" | b"day" | b"days" => SECS_PER_DAY,
+        b"w" | b"week" | b"weeks" => 7 * SECS_PER_DAY,
+        _ => return None,
+    };
+    Some(secs)
+}
+
+/// Returns the (year, month, day) of the given number of days since the epoch (in the proleptic gregorian calendar)
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // shift the epoch to 0000-03-01, so that a leap day is always the last day of a year
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as u64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{ArithOp, ExprFn, RowFn},
+        crate::engine::{
+            data::{cell::Datacell, lit::Lit},
+            ql::dml::RowExpr,
+        },
+    };
+
+    #[test]
+    fn extract() {
+        // 2024-02-29T13:45:30Z, a thursday
+        let t = 1709214330;
+        assert_eq!(
+            [
+                ExprFn::Year,
+                ExprFn::Month,
+                ExprFn::Day,
+                ExprFn::Hour,
+                ExprFn::Minute,
+                ExprFn::Second,
+                ExprFn::Weekday,
+            ]
+            .map(|f| f.apply(t)),
+            [2024, 2, 29, 13, 45, 30, 4]
+        );
+        // 1999-12-31T23:59:59Z, a friday
+        assert_eq!(
+            [ExprFn::Year, ExprFn::Month, ExprFn::Day, ExprFn::Weekday].map(|f| f.apply(946684799)),
+            [1999, 12, 31, 5]
+        );
+        assert_eq!(
+            [ExprFn::Year, ExprFn::Month, ExprFn::Day].map(|f| f.apply(0)),
+            [1970, 1, 1]
+        );
+    }
+
+    #[test]
+    fn arith() {
+        assert_eq!(ArithOp::Sub.apply(10, 3), Some(7));
+        assert_eq!(ArithOp::Sub.apply(3, 10), None);
+        assert_eq!(ArithOp::Add.apply(u64::MAX, 1), None);
+    }
+
+    #[test]
+    fn strings() {
+        let s = |s: &'static str| RowExpr::Value(Lit::new_str(s));
+        let u = |u| RowExpr::Value(Lit::new_uint(u));
+        let eval = |f, args| super::eval(&RowExpr::Call(f, args), |_| None).unwrap();
+        assert_eq!(
+            eval(RowFn::Upper, vec![s("ça va")]),
+            Datacell::from("ÇA VA")
+        );
+        assert_eq!(eval(RowFn::Len, vec![s("ça va")]), Datacell::from(5u64));
+        assert_eq!(eval(RowFn::Trim, vec![s(" a b ")]), Datacell::from("a b"));
+        assert_eq!(
+            eval(RowFn::Substr, vec![s("héllo"), u(2), u(3)]),
+            Datacell::from("éll")
+        );
+        assert_eq!(
+            eval(RowFn::Substr, vec![s("héllo"), u(0)]),
+            Datacell::from("héllo")
+        );
+        assert_eq!(
+            eval(RowFn::Substr, vec![s("héllo"), u(9)]),
+            Datacell::from("")
+        );
+        assert_eq!(
+            eval(RowFn::Concat, vec![s("a"), s(""), s("b")]),
+            Datacell::from("ab")
+        );
+        // a null (or missing) argument makes the result null
+        assert!(eval(RowFn::Concat, vec![s("a"), RowExpr::Field("nick".into())]).is_null());
+    }
+
+    #[test]
+    fn numbers() {
+        let u = |u| RowExpr::Value(Lit::new_uint(u));
+        let i = |i| RowExpr::Value(Lit::new_sint(i));
+        let f = |f| RowExpr::Value(Lit::new_float(f));
+        let eval = |f, args| super::eval(&RowExpr::Call(f, args), |_| None);
+        let cast = |arg, to| {
+            super::eval(
+                &RowExpr::Cast(Box::new(arg), super::cast_target(to).unwrap()),
+                |_| None,
+            )
+        };
+        assert_eq!(eval(RowFn::Abs, vec![i(-5)]), Ok(Datacell::from(5i64)));
+        assert!(eval(RowFn::Abs, vec![i(i64::MIN)]).is_err());
+        assert_eq!(
+            eval(RowFn::Fixed, vec![i(-1234), u(2)]),
+            Ok(Datacell::from("-12.34"))
+        );
+        assert!(eval(RowFn::Fixed, vec![u(1), u(40)]).is_err());
+        assert_eq!(eval(RowFn::Round, vec![f(2.5)]), Ok(Datacell::from(3.0)));
+        assert_eq!(
+            eval(RowFn::Round, vec![f(-1.25), u(1)]),
+            Ok(Datacell::from(-1.3))
+        );
+        assert_eq!(
+            eval(RowFn::Round, vec![u(7), u(2)]),
+            Ok(Datacell::from(7u64))
+        );
+        // the arguments are converted into their common type
+        assert_eq!(
+            eval(RowFn::Min, vec![u(3), i(-2), u(1)]),
+            Ok(Datacell::from(-2i64))
+        );
+        assert_eq!(
+            eval(RowFn::Max, vec![u(3), f(2.5)]),
+            Ok(Datacell::from(3.0))
+        );
+        assert!(eval(RowFn::Max, vec![u(u64::MAX), i(0)]).is_err());
+        // casts are checked
+        assert_eq!(cast(u(300), "uint16"), Ok(Datacell::from(300u64)));
+        assert!(cast(u(300), "uint8").is_err());
+        assert!(cast(i(-1), "uint64").is_err());
+        assert_eq!(cast(i(-128), "sint8"), Ok(Datacell::from(-128i64)));
+        assert!(cast(f(2.5), "uint32").is_err());
+        assert_eq!(cast(f(2.0), "uint32"), Ok(Datacell::from(2u64)));
+        assert!(cast(f(1e40), "float32").is_err());
+        assert_eq!(
+            cast(RowExpr::Value(Lit::new_str("42")), "sint32"),
+            Ok(Datacell::from(42i64))
+        );
+        assert!(cast(RowExpr::Value(Lit::new_str("4x2")), "uint64").is_err());
+        assert_eq!(cast(i(-7), "string"), Ok(Datacell::from("-7")));
+        assert!(cast(RowExpr::Field("nick".into()), "uint8")
+            .unwrap()
+            .is_null());
+    }
+}
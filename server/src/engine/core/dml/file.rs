@@ -0,0 +1,297 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{agg::Aggregator, QueryMemBudget, RowFilter},
+    crate::engine::{
+        core::model::Field,
+        data::{
+            cell::Datacell,
+            tag::{DataTag, TagClass},
+        },
+        error::{QueryError, QueryResult},
+        fractal::GlobalInstanceLike,
+        mem::IntegerRepr,
+        net::protocol::{Response, ResponseType},
+        ql::{
+            ddl::syn::FieldSpec,
+            dml::sel::{FileFormat, FileProjection, SelectFileStatement},
+        },
+    },
+    std::{
+        fs::File,
+        io::{self, BufRead, BufReader},
+    },
+};
+
+/// Run a foreign scan, streaming the records of the file through the where clause and then either the projection or
+/// the aggregates (see [`Aggregator`]). A record that doesn't match the schema fails the query.
+///
+/// Since this is a scan, it is rejected while the server is under memory pressure
+pub fn select_file_resp(
+    global: &impl GlobalInstanceLike,
+    mut select: SelectFileStatement,
+) -> QueryResult<Response> {
+    global.admit_expensive_query()?;
+    let schema = FileSchema::new(select.take_schema())?;
+    let filter = RowFilter::new(
+        select
+            .clauses_mut()
+            .clauses_mut()
+            .drain()
+            .map(|(_, clause)| clause)
+            .collect(),
+    )?;
+    if !filter
+        .fields()
+        .all(|field| schema.position(field).is_some())
+    {
+        return Err(QueryError::QExecUnknownField);
+    }
+    let mut budget = QueryMemBudget::new(global);
+    let mut records = match select.format() {
+        FileFormat::Csv => CsvReader::new(BufReader::new(open(select.path())?)),
+    };
+    let mut record = Vec::new();
+    if select.header() {
+        records.next_record(&mut record, &mut budget)?;
+    }
+    let mut row = Vec::with_capacity(schema.fields.len());
+    let fields = match select.projection() {
+        FileProjection::Aggregates(aggregates) => {
+            let mut aggregator = Aggregator::new(aggregates, select.group_by(), select.having());
+            if !aggregator
+                .fields()
+                .all(|field| schema.position(field.as_str()).is_some())
+            {
+                return Err(QueryError::QExecUnknownField);
+            }
+            while records.next_record(&mut record, &mut budget)? {
+                schema.decode(&mut record, &mut row)?;
+                if filter.matches_with(|field| schema.get(&row, field)) {
+                    aggregator.add_row(
+                        |field| schema.get(&row, field.as_str()).unwrap(),
+                        &mut budget,
+                    )?;
+                }
+            }
+            aggregator.finish(&mut budget)?;
+            return Ok(aggregator.into_response(select.having()));
+        }
+        FileProjection::Wildcard => (0..schema.fields.len()).collect(),
+        FileProjection::Fields(fields) => fields
+            .iter()
+            .map(|field| schema.position(field.as_str()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(QueryError::QExecUnknownField)?,
+    };
+    let limit = select.limit().unwrap_or(u64::MAX);
+    let mut data = Vec::new();
+    let mut rows = 0;
+    while rows < limit && records.next_record(&mut record, &mut budget)? {
+        schema.decode(&mut record, &mut row)?;
+        if !filter.matches_with(|field| schema.get(&row, field)) {
+            continue;
+        }
+        IntegerRepr::scoped(fields.len() as u64, |repr| data.extend(repr));
+        data.push(b'\n');
+        for field in fields.iter() {
+            budget.charge_cell(&row[*field])?;
+            super::sel::encode_cell(&mut data, &row[*field]);
+        }
+        rows += 1;
+    }
+    Ok(Response::Serialized {
+        ty: ResponseType::MultiRow,
+        size: rows as usize,
+        data,
+    })
+}
+
+pub(super) fn open(path: &str) -> QueryResult<File> {
+    File::open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => QueryError::QExecObjectNotFound,
+        _ => QueryError::from(e),
+    })
+}
+
+/// The fields of the records in a file
+struct FileSchema<'a> {
+    fields: Vec<(&'a str, Field)>,
+}
+
+impl<'a> FileSchema<'a> {
+    fn new(spec: Vec<FieldSpec<'a>>) -> QueryResult<Self> {
+        let mut fields: Vec<(&str, Field)> = Vec::with_capacity(spec.len());
+        for FieldSpec {
+            field_name,
+            layers,
+            null,
+            ..
+        } in spec
+        {
+            let field = Field::parse_layers(layers, null)?;
+            if field.layers().len() != 1 {
+                // a list can't be represented in a record
+                return Err(QueryError::QExecDdlInvalidTypeDefinition);
+            }
+            if fields.iter().any(|(name, _)| *name == field_name.as_str()) {
+                return Err(QueryError::QExecDdlModelBadDefinition);
+            }
+            fields.push((field_name.as_str(), field));
+        }
+        Ok(Self { fields })
+    }
+    fn position(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|(name, _)| *name == field)
+    }
+    fn get<'r>(&self, row: &'r [Datacell], field: &str) -> Option<&'r Datacell> {
+        self.position(field).map(|i| &row[i])
+    }
+    /// Decode a record into a row, type checking each value against its field. An empty (unquoted) value is null
+    fn decode(&self, record: &mut Vec<Option<String>>, row: &mut Vec<Datacell>) -> QueryResult<()> {
+        if record.len() != self.fields.len() {
+            return Err(QueryError::QExecDmlValidationError);
+        }
+        row.clear();
+        for ((name, field), value) in self.fields.iter().zip(record.drain(..)) {
+            let mut dc = match value {
+                None => Datacell::null(),
+                Some(value) => self::decode_value(field.layers()[0].tag().tag_class(), value)?,
+            };
+            if !field.vt_data_fpath(&mut dc) {
+                return Err(QueryError::QExecDmlValidationError);
+            }
+            if !dc.is_null() {
+                field.check(name, &dc)?;
+            }
+            row.push(dc);
+        }
+        Ok(())
+    }
+}
+
+/// Decode a value of a field with the given type class from its text form. Lists don't have one
+pub(super) fn decode_value(class: TagClass, value: String) -> QueryResult<Datacell> {
+    let dc = match class {
+        TagClass::Bool => value.parse().ok().map(Datacell::new_bool),
+        TagClass::UnsignedInt => value.parse().ok().map(Datacell::new_uint_default),
+        TagClass::SignedInt => value.parse().ok().map(Datacell::new_sint_default),
+        TagClass::Float => value.parse().ok().map(Datacell::new_float_default),
+        TagClass::Bin => Some(Datacell::new_bin(value.into_bytes().into_boxed_slice())),
+        TagClass::Str => Some(Datacell::new_str(value.into_boxed_str())),
+        TagClass::List => None,
+    };
+    dc.ok_or(QueryError::QExecDmlValidationError)
+}
+
+/// Reads the records of a CSV file (RFC 4180) one at a time. Values can be quoted (with `""` for a quote), in which
+/// case they can also contain commas and line breaks. Blank lines are skipped
+struct CsvReader<R> {
+    reader: R,
+    line: String,
+    /// the size of the last record, which is accounted for in the budget until the next record is read
+    charged: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            charged: 0,
+        }
+    }
+    /// Read the next record, returning false once there are no more records
+    fn next_record(
+        &mut self,
+        record: &mut Vec<Option<String>>,
+        budget: &mut QueryMemBudget,
+    ) -> QueryResult<bool> {
+        record.clear();
+        budget.release(self.charged);
+        self.charged = 0;
+        let mut value = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut after_quotes = false;
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                if in_quotes {
+                    // unterminated quoted value
+                    return Err(QueryError::QExecDmlValidationError);
+                }
+                if record.is_empty() {
+                    return Ok(false);
+                }
+                break;
+            }
+            self.charged += self.line.len();
+            budget.charge(self.line.len())?;
+            let line = self.line.trim_end_matches(['\r', '\n']);
+            if !in_quotes && line.is_empty() {
+                continue;
+            }
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if in_quotes && chars.peek() == Some(&'"') => {
+                        chars.next();
+                        value.push('"');
+                    }
+                    '"' if in_quotes => {
+                        in_quotes = false;
+                        after_quotes = true;
+                    }
+                    '"' if value.is_empty() && !quoted => {
+                        in_quotes = true;
+                        quoted = true;
+                    }
+                    ',' if !in_quotes => {
+                        record.push(Self::finish_value(&mut value, quoted));
+                        quoted = false;
+                        after_quotes = false;
+                    }
+                    // only a comma can follow a quoted value
+                    _ if after_quotes => return Err(QueryError::QExecDmlValidationError),
+                    c => value.push(c),
+                }
+            }
+            if !in_quotes {
+                break;
+            }
+            // the line break is part of the quoted value
+            value.push('\n');
+        }
+        record.push(Self::finish_value(&mut value, quoted));
+        Ok(true)
+    }
+    fn finish_value(value: &mut String, quoted: bool) -> Option<String> {
+        let value = core::mem::take(value);
+        (quoted | !value.is_empty()).then_some(value)
+    }
+}
@@ -0,0 +1,234 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    row generation
+    ---
+    `sysctl generate rows` fills a model with random rows for load tests and demos. every value is generated from the
+    field's type and properties: integers span the type's range and floats [-FLOAT_SPAN, FLOAT_SPAN] (both narrowed
+    to the field's `min` and `max`), strings, binaries and lists are at most MAX_LEN long (or `maxlen`, if lower),
+    fields that can be null are null one time in NULL_ONE_IN and fields with a default get a generated key. primary
+    keys are unique, so a key that is already taken is drawn again (up to MAX_ATTEMPTS times). the rows are then
+    validated and inserted like any other insert (in batches of GENERATE_RUN), so a row that fails a check (like a
+    `regex`, which isn't generated for) fails the statement, leaving the rows before it. with the same seed, the same
+    rows are generated (except for generated keys, which depend on the time)
+*/
+
+use crate::engine::{
+    core::{
+        self,
+        dml::{ins, sel::SampleRng, upstream, QueryExecMeta},
+        model::{check::CheckBound, decimal, Field, Layer, ModelData},
+        EntityIDRef,
+    },
+    data::{
+        cell::Datacell,
+        tag::{DataTag, FloatSpec, SIntSpec, TagSelector, UIntSpec},
+    },
+    error::{QueryError, QueryResult},
+    fractal::GlobalInstanceLike,
+    idx::STIndexSeq,
+    ql::dml::ins::InsertData,
+    sync::atm::cpin,
+};
+
+/// Rows are generated and inserted in batches of this size
+const GENERATE_RUN: u64 = 1024;
+/// The number of times a primary key is drawn before giving up on finding one that isn't taken
+const MAX_ATTEMPTS: usize = 64;
+/// The longest string, binary or list that is generated
+const MAX_LEN: u64 = 16;
+/// The range of floats (unless the field sets one)
+const FLOAT_SPAN: f64 = 1_000_000.0;
+/// A field that can be null is null one in this many times
+const NULL_ONE_IN: u64 = 8;
+const ALPHANUMERIC: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Insert `count` random rows into the model (see the module docs), returning the number of rows inserted and the
+/// seed that was used (which is random if none was given)
+pub fn generate_rows(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    count: u64,
+    seed: Option<u64>,
+) -> QueryResult<(u64, u64)> {
+    let seed = seed.unwrap_or_else(|| SampleRng::new().next_u64());
+    let mut rng = SampleRng::with_seed(seed);
+    let mut generated = 0;
+    while generated < count {
+        let batch = GENERATE_RUN.min(count - generated);
+        core::with_model_for_data_update(global, entity, |mdl| {
+            upstream::check_not_upstream(mdl)?;
+            let _idx_latch = mdl.primary_index().acquire_cd();
+            let g = cpin();
+            let mut meta = QueryExecMeta::zero();
+            for _ in 0..batch {
+                let (pk, data) = (0..MAX_ATTEMPTS)
+                    .find_map(|_| {
+                        let row = random_row(mdl, &mut rng);
+                        match ins::prepare_insert(mdl, InsertData::Ordered(row)) {
+                            Ok((pk, _)) if mdl.primary_index().select_key(&pk, &g).is_some() => {
+                                None
+                            }
+                            r => Some(r),
+                        }
+                    })
+                    .unwrap_or(Err(QueryError::QExecDmlDuplicate
+                        .with_detail("constraint", "primary_key")
+                        .with_detail("field", mdl.p_key())))
+                    .map_err(|e| e.with_detail("row", generated))?;
+                meta = ins::insert_prepared(mdl, pk, data, None, &g)
+                    .map_err(|e| e.with_detail("row", generated))?;
+                generated += 1;
+            }
+            Ok(meta)
+        })?;
+    }
+    Ok((generated, seed))
+}
+
+/// Returns a random row, with a value for every field that isn't computed (in the order they were declared in)
+fn random_row(mdl: &ModelData, rng: &mut SampleRng) -> Vec<Datacell> {
+    mdl.fields()
+        .stseq_ord_kv()
+        .filter(|(_, field)| !field.is_computed())
+        .map(|(field_id, field)| {
+            let pk = field_id.as_str() == mdl.p_key();
+            random_value(field, pk, rng)
+        })
+        .collect()
+}
+
+fn random_value(field: &Field, pk: bool, rng: &mut SampleRng) -> Datacell {
+    if field.is_nullable() & !pk && rng.below(NULL_ONE_IN) == 0 {
+        return Datacell::null();
+    }
+    if let Some(keygen) = field.keygen() {
+        return keygen.generate();
+    }
+    if field.window().is_some() {
+        // a window counter starts with the number of events that it is inserted with
+        return Datacell::new_uint_default(rng.below(MAX_LEN));
+    }
+    if let Some(dec) = field.decimal() {
+        // a decimal is inserted as a string, and any value with as many digits as the precision fits
+        let lim = 10i128.pow(dec.precision() as u32) - 1;
+        let held = random_int(-lim, lim, None, None, rng);
+        return Datacell::new_str(decimal::fixed(held, dec.scale()).unwrap().into_boxed_str());
+    }
+    let checks = field.props().checks();
+    let maxlen = checks.maxlen().unwrap_or(MAX_LEN).min(MAX_LEN);
+    random_layer(field.layers(), checks.min(), checks.max(), maxlen, rng)
+}
+
+/// Returns a random value for the outermost of the given layers. The bounds only apply to the outermost layer
+fn random_layer(
+    layers: &[Layer],
+    min: Option<CheckBound>,
+    max: Option<CheckBound>,
+    maxlen: u64,
+    rng: &mut SampleRng,
+) -> Datacell {
+    let len = rng.below(maxlen + 1);
+    match layers[0].tag().tag_selector() {
+        TagSelector::Bool => Datacell::new_bool(rng.below(2) == 1),
+        selector @ (TagSelector::UInt8
+        | TagSelector::UInt16
+        | TagSelector::UInt32
+        | TagSelector::UInt64) => {
+            let lim = UIntSpec::LIM_MAX[selector.value_word() - 1] as i128;
+            Datacell::new_uint_default(random_int(0, lim, min, max, rng) as u64)
+        }
+        selector @ (TagSelector::SInt8
+        | TagSelector::SInt16
+        | TagSelector::SInt32
+        | TagSelector::SInt64) => {
+            let i = selector.value_word() - 5;
+            let (lo, hi) = (SIntSpec::LIM_MIN[i] as i128, SIntSpec::LIM_MAX[i] as i128);
+            Datacell::new_sint_default(random_int(lo, hi, min, max, rng) as i64)
+        }
+        selector @ (TagSelector::Float32 | TagSelector::Float64) => {
+            let i = selector.value_word() - 9;
+            let lo = bound_f64(min).map_or(-FLOAT_SPAN, |min| min.max(FloatSpec::LIM_MIN[i]));
+            let hi = bound_f64(max).map_or(FLOAT_SPAN, |max| max.min(FloatSpec::LIM_MAX[i]));
+            Datacell::new_float_default((lo + rng.unit() * (hi - lo)).clamp(lo, hi))
+        }
+        TagSelector::Binary => Datacell::new_bin(
+            (0..len)
+                .map(|_| rng.next_u64() as u8)
+                .collect::<Vec<u8>>()
+                .into_boxed_slice(),
+        ),
+        TagSelector::String => Datacell::new_str(
+            (0..len)
+                .map(|_| ALPHANUMERIC[rng.below(ALPHANUMERIC.len() as u64) as usize] as char)
+                .collect::<String>()
+                .into_boxed_str(),
+        ),
+        TagSelector::List => Datacell::new_list(
+            (0..len)
+                .map(|_| random_layer(&layers[1..], None, None, MAX_LEN, rng))
+                .collect(),
+        ),
+    }
+}
+
+/// Returns a random integer in `lo..=hi`, narrowed to the bounds (if any)
+fn random_int(
+    lo: i128,
+    hi: i128,
+    min: Option<CheckBound>,
+    max: Option<CheckBound>,
+    rng: &mut SampleRng,
+) -> i128 {
+    let lo = match min {
+        Some(CheckBound::Int(min)) => min.max(lo),
+        Some(CheckBound::Float(min)) => (min.ceil() as i128).max(lo),
+        None => lo,
+    };
+    let hi = match max {
+        Some(CheckBound::Int(max)) => max.min(hi),
+        Some(CheckBound::Float(max)) => (max.floor() as i128).min(hi),
+        None => hi,
+    };
+    if lo >= hi {
+        // either a single value is allowed or none is (in which case the row fails its check)
+        return lo;
+    }
+    let span = (hi - lo) as u128 + 1;
+    if span > u64::MAX as u128 {
+        lo + rng.next_u64() as i128
+    } else {
+        lo + rng.below(span as u64) as i128
+    }
+}
+
+fn bound_f64(bound: Option<CheckBound>) -> Option<f64> {
+    bound.map(|bound| match bound {
+        CheckBound::Int(i) => i as f64,
+        CheckBound::Float(f) => f,
+    })
+}
@@ -24,59 +24,373 @@
  *
 */
 
-use crate::engine::{
-    core::{
-        self,
-        dml::QueryExecMeta,
-        index::{DcFieldIndex, PrimaryIndexKey, Row},
-        model::{delta::DataDeltaKind, ModelData},
+use {
+    crate::{
+        engine::{
+            core::{
+                self,
+                dml::{upstream, Mutation, QueryExecMeta, ReturningRows},
+                index::{DcFieldIndex, PrimaryIndexKey, Row},
+                lock,
+                model::{
+                    delta::{DataDeltaKind, DeltaVersion},
+                    props::NumericCoercion,
+                    ModelData,
+                },
+                EntityIDRef,
+            },
+            data::cell::Datacell,
+            error::{QueryError, QueryResult},
+            fractal::{GlobalInstanceLike, ModelUniqueID},
+            idx::{IndexBaseSpec, STIndex, STIndexExt, STIndexSeq},
+            net::protocol::Response,
+            ql::dml::ins::{InsertData, InsertStatement},
+            sync::atm::{cpin, Guard},
+        },
+        util::os,
     },
-    error::{QueryError, QueryResult},
-    fractal::GlobalInstanceLike,
-    idx::{IndexBaseSpec, MTIndex, STIndex, STIndexExt, STIndexSeq},
-    net::protocol::Response,
-    ql::dml::ins::{InsertData, InsertStatement},
-    sync::atm::cpin,
+    std::collections::HashSet,
 };
 
 pub fn insert_resp(
     global: &impl GlobalInstanceLike,
     insert: InsertStatement,
 ) -> QueryResult<Response> {
-    self::insert(global, insert).map(|_| Response::Empty)
+    let with_lsn = insert.with_lsn();
+    self::insert_returning(global, insert).map(|(version, returning)| match returning {
+        _ if with_lsn => super::lsn_response(version),
+        Some(returning) => returning.into_response(true),
+        None => Response::Empty,
+    })
 }
 
+#[cfg(test)]
 pub fn insert(global: &impl GlobalInstanceLike, insert: InsertStatement) -> QueryResult<()> {
-    core::with_model_for_data_update(global, insert.entity(), |mdl| {
+    self::insert_returning(global, insert).map(|_| ())
+}
+
+fn insert_returning<'a>(
+    global: &impl GlobalInstanceLike,
+    mut insert: InsertStatement<'a>,
+) -> QueryResult<(Option<DeltaVersion>, Option<ReturningRows<'a>>)> {
+    let returning = insert.take_returning();
+    let summary = insert.summary();
+    let mut returning_rows = None;
+    let version = core::with_model_for_data_update(global, insert.entity(), |mdl| {
+        returning_rows = ReturningRows::for_statement(mdl, returning, summary, Mutation::Insert)?;
         let (pk, data) = prepare_insert(mdl, insert.data())?;
         let _idx_latch = mdl.primary_index().acquire_cd();
         let g = cpin();
+        insert_prepared(mdl, pk, data, returning_rows.as_mut(), &g)
+    })?;
+    Ok((version, returning_rows))
+}
+
+/// Insert the rows of a bulk insert, each with a value for every field (but the computed ones) in the order that the
+/// fields were declared in. Every row is validated before any is inserted, so a bad row rejects all of them. The rows
+/// are then inserted in order, stopping at the first one whose primary key is taken (the rows before it stay
+/// inserted). Either way, the error notes the index of the row. Returns the number of rows inserted
+pub fn bulk_insert(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    rows: Vec<Vec<Datacell>>,
+) -> QueryResult<u64> {
+    let mut inserted = 0;
+    let mut conflict = None;
+    core::with_model_for_data_update(global, entity, |mdl| {
+        upstream::check_not_upstream(mdl)?;
+        let rows = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                prepare_insert(mdl, InsertData::Ordered(row)).map_err(|e| e.with_detail("row", i))
+            })
+            .collect::<QueryResult<Vec<_>>>()?;
+        let _idx_latch = mdl.primary_index().acquire_cd();
+        let g = cpin();
+        // the deltas are checked against the flush threshold once, for all the rows
+        let mut meta = QueryExecMeta::zero();
+        for (pk, data) in rows {
+            match insert_prepared(mdl, pk, data, None, &g) {
+                Ok(row_meta) => meta = row_meta,
+                Err(e) => {
+                    conflict = Some(e);
+                    break;
+                }
+            }
+            inserted += 1;
+        }
+        Ok(meta)
+    })?;
+    match conflict {
+        Some(e) => Err(e.with_detail("row", inserted)),
+        None => Ok(inserted),
+    }
+}
+
+/// Load the rows of a bulk insert (when the session asked for it, see [`crate::engine::core::session`]). Unlike
+/// [`bulk_insert`], the rows don't go through the deltas: once every row is validated and every primary key is known
+/// to be new (a key that is taken, even by a soft deleted row, or that is used twice rejects all of the rows), the
+/// rows are written out right away as one batch for each partition and only then added to the index. Nothing else
+/// can insert into (or delete from) the model while the rows are loaded. If a partition's batch can't be written,
+/// the batches before it (which are durable) stay loaded, the rest are left out and the error notes the number of
+/// rows that were loaded. Returns the number of rows loaded
+pub fn bulk_load(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    rows: Vec<Vec<Datacell>>,
+) -> QueryResult<u64> {
+    let mut loaded = 0;
+    core::with_model_for_data_write(global, entity, |model| {
+        let mdl = model.data();
+        upstream::check_not_upstream(mdl)?;
+        let rows = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                prepare_insert(mdl, InsertData::Ordered(row)).map_err(|e| e.with_detail("row", i))
+            })
+            .collect::<QueryResult<Vec<_>>>()?;
+        let _idx_latch = mdl.primary_index().acquire_exclusive();
+        let g = cpin();
+        let mut keys = HashSet::with_capacity(rows.len());
+        for (i, (pk, _)) in rows.iter().enumerate() {
+            if mdl.primary_index().select_key(pk, &g).is_some() | !keys.insert(pk) {
+                return Err(QueryError::QExecDmlDuplicate
+                    .with_detail("constraint", "primary_key")
+                    .with_detail("field", mdl.p_key())
+                    .with_detail("row", i));
+            }
+        }
+        drop(keys);
+        // the changes made before the load have to come before it in the journals
+        let mdl_id = ModelUniqueID::new(entity.space(), entity.entity(), mdl.get_uuid());
+        model.driver().flush(global, mdl_id.clone(), mdl)?;
         let ds = mdl.delta_state();
-        // create new version
+        let partitions = mdl.primary_index().partitions();
+        let mut batches: Vec<Vec<Row>> = (0..partitions).map(|_| vec![]).collect();
+        for (pk, data) in rows {
+            let partition = match partitions {
+                1 => 0,
+                n => pk.partition(n),
+            };
+            let new_version = ds.create_new_data_delta_version();
+            let row = Row::new(pk, data, ds.schema_current_version(), new_version);
+            batches[partition].push(row);
+        }
+        // every batch is written before any row is added to the index
+        let mut written = Vec::with_capacity(partitions);
+        let mut failure = None;
+        for (partition, rows) in batches.iter().enumerate() {
+            if rows.is_empty() {
+                continue;
+            }
+            match model
+                .driver()
+                .commit_loaded(global, mdl_id.clone(), mdl, partition, rows)
+            {
+                Ok(()) => written.push(partition),
+                Err(e) => {
+                    failure = Some(e);
+                    break;
+                }
+            }
+        }
+        // the batches that were written come back on restart, so their rows are loaded even if a later one failed
+        for row in written
+            .into_iter()
+            .flat_map(|partition| &batches[partition])
+        {
+            // every key was checked above, and the latch keeps it that way
+            let _ = mdl.primary_index().insert(row.clone(), &g);
+            if let Err(e) = index_new_row(mdl, row, None) {
+                failure.get_or_insert(e);
+            }
+            loaded += 1;
+        }
+        match failure {
+            // there are no deltas to flush
+            None => Ok(QueryExecMeta::zero()),
+            Some(e) => Err(e.with_detail("loaded", loaded)),
+        }
+    })?;
+    Ok(loaded)
+}
+
+/// Insert a prepared row, unless its primary key is taken by a row that isn't soft deleted. Must be called with the
+/// index latch held
+pub(super) fn insert_prepared(
+    mdl: &ModelData,
+    pk: PrimaryIndexKey,
+    data: DcFieldIndex,
+    returning: Option<&mut ReturningRows>,
+    g: &Guard,
+) -> QueryResult<QueryExecMeta> {
+    let ds = mdl.delta_state();
+    // create new version
+    let new_version = ds.create_new_data_delta_version();
+    let row = Row::new(pk, data, ds.schema_current_version(), new_version);
+    if mdl.primary_index().insert(row.clone(), g)
+        || (mdl.props().soft_delete() && replace_tombstoned(mdl, &row, g))
+    {
+        index_new_row(mdl, &row, returning)?;
+        // append delta for new version
+        let dp = ds.append_new_data_delta_with(DataDeltaKind::Insert, row, new_version, g);
+        Ok(QueryExecMeta::new(dp, new_version))
+    } else {
+        Err(QueryError::QExecDmlDuplicate
+            .with_detail("constraint", "primary_key")
+            .with_detail("field", mdl.p_key()))
+    }
+}
+
+/// Update everything that is derived from the rows of the model (the secondary indexes, the history, views and so
+/// on) for a row that was just added to the primary index
+fn index_new_row(
+    mdl: &ModelData,
+    row: &Row,
+    returning: Option<&mut ReturningRows>,
+) -> QueryResult<()> {
+    let data = lock::read(mdl, row)?;
+    // index the new row
+    mdl.sidx_insert_row(row.d_key(), data.fields());
+    // the row didn't exist before this
+    if let Some(history) = mdl.history() {
+        history.record(row.d_key(), None, os::get_epoch_time_secs());
+    }
+    if let Some(returning) = returning {
+        returning.push_row(mdl, row.d_key(), data.fields());
+    }
+    drop(data);
+    mdl.views().touch(row.d_key());
+    if let Some(columns) = mdl.columns() {
+        columns.touch(row.d_key());
+    }
+    if let Some(maintained) = mdl.maintained() {
+        maintained.touch(row.d_key());
+    }
+    Ok(())
+}
+
+/// Copy every live (not soft deleted) row of `source` into `target`, a new model with the same fields. The rows are
+/// written out by the flusher in batches, just like any other change. Fails if a row of the source can't be read
+pub fn copy_rows(target: &ModelData, source: &ModelData) -> QueryResult<()> {
+    let g = cpin();
+    let _src_latch = source.primary_index().acquire_exclusive();
+    let _idx_latch = target.primary_index().acquire_cd();
+    let ds = target.delta_state();
+    for row in source.primary_index().iter(&g) {
+        let data = lock::read(source, row)?;
+        if data.is_tombstoned() {
+            continue;
+        }
+        // the fields have to be keyed by the target's allocations
+        let mut fields = DcFieldIndex::idx_init_cap(target.fields().len());
+        for field_id in target.fields().stseq_ord_key() {
+            if let Some(cell) = data.fields().st_get(field_id.as_str()) {
+                fields.st_insert(
+                    unsafe {
+                        // UNSAFE(@ohsayan): the target owns the allocation, and the row goes with the target
+                        field_id.clone()
+                    },
+                    cell.clone(),
+                );
+            }
+        }
+        drop(data);
         let new_version = ds.create_new_data_delta_version();
-        let row = Row::new(pk, data, ds.schema_current_version(), new_version);
-        if mdl.primary_index().__raw_index().mt_insert(row.clone(), &g) {
-            // append delta for new version
-            let dp = ds.append_new_data_delta_with(DataDeltaKind::Insert, row, new_version, &g);
-            Ok(QueryExecMeta::new(dp))
-        } else {
-            Err(QueryError::QExecDmlDuplicate)
+        let row = Row::new(
+            row.d_key().clone(),
+            fields,
+            ds.schema_current_version(),
+            new_version,
+        );
+        let _ = target.primary_index().insert(row.clone(), &g);
+        ds.append_new_data_delta_with(DataDeltaKind::Insert, row, new_version, &g);
+    }
+    Ok(())
+}
+
+/// Insert the rows of a model restored from a snapshot into a new model, returning the number of rows inserted. The
+/// fields of every row are in the model's layout (every field but the primary key, in order), and like
+/// [`copy_rows`], the rows are written out by the flusher in batches like any other insert
+pub fn restore_rows(
+    target: &ModelData,
+    rows: impl Iterator<Item = (PrimaryIndexKey, Vec<Datacell>)>,
+) -> u64 {
+    let g = cpin();
+    let _idx_latch = target.primary_index().acquire_cd();
+    let ds = target.delta_state();
+    let mut restored = 0;
+    for (pk, cells) in rows {
+        let mut fields = DcFieldIndex::idx_init_cap(target.fields().len());
+        let layout = target
+            .fields()
+            .stseq_ord_key()
+            .filter(|field_id| field_id.as_str() != target.p_key());
+        for (field_id, cell) in layout.zip(cells) {
+            fields.st_insert(
+                unsafe {
+                    // UNSAFE(@ohsayan): the target owns the allocation, and the row goes with the target
+                    field_id.clone()
+                },
+                cell,
+            );
         }
-    })
+        let new_version = ds.create_new_data_delta_version();
+        let row = Row::new(pk, fields, ds.schema_current_version(), new_version);
+        let _ = target.primary_index().insert(row.clone(), &g);
+        ds.append_new_data_delta_with(DataDeltaKind::Insert, row, new_version, &g);
+        restored += 1;
+    }
+    restored
+}
+
+/// If the row with the same key was soft deleted, replace it with the new row. Returns true if the row was replaced
+fn replace_tombstoned(mdl: &ModelData, row: &Row, g: &Guard) -> bool {
+    let idx = mdl.primary_index();
+    let Some(old) = idx.select_key(row.d_key(), g) else {
+        return false;
+    };
+    // the old row stays locked until it's removed, so that it can't change in the meantime
+    let Ok(data) = lock::read(mdl, old) else {
+        return false;
+    };
+    if !data.is_tombstoned() {
+        return false;
+    }
+    if idx.delete_return_entry_key(row.d_key(), g).is_some() {
+        mdl.sidx_remove_row(old.d_key(), data.fields());
+    }
+    drop(data);
+    idx.insert(row.clone(), g)
 }
 
 // TODO(@ohsayan): optimize null case
-fn prepare_insert(
+pub(super) fn prepare_insert(
     model: &ModelData,
     insert: InsertData,
 ) -> QueryResult<(PrimaryIndexKey, DcFieldIndex)> {
     let fields = model.fields();
-    let mut okay = fields.len() == insert.column_count();
+    // computed fields are never provided by the client
+    let computed_count = model.computed_field_count();
+    let coercion = model.props().coercion();
+    let lenient = coercion == NumericCoercion::Lenient;
+    let exact = coercion == NumericCoercion::Exact;
+    let required = fields.len() - computed_count;
+    // fields with a default can be left out of a map insert
+    let mut okay = match &insert {
+        InsertData::Ordered(_) => required == insert.column_count(),
+        InsertData::Map(_) => required >= insert.column_count(),
+    };
     let mut prepared_data = DcFieldIndex::idx_init_cap(fields.len());
     match insert {
         InsertData::Ordered(tuple) => {
-            let mut fields = fields.stseq_ord_kv();
+            let mut fields = fields
+                .stseq_ord_kv()
+                .filter(|(_, field)| !field.is_computed());
             let mut tuple = tuple.into_iter();
+            let mut param = 0;
             while (tuple.len() != 0) & okay {
                 let mut data;
                 let field;
@@ -87,7 +401,32 @@ fn prepare_insert(
                     field = fields.next().unwrap_unchecked();
                 }
                 let (field_id, field) = field;
+                let params = param_count(&data);
+                if let Some(counter) = field.window() {
+                    okay &= counter.prepare_insert(&mut data, os::get_epoch_time_secs());
+                }
+                if let Some(decimal) = field.decimal() {
+                    okay &= decimal.prepare_insert(&mut data);
+                }
+                if lenient {
+                    field.coerce_numeric(&mut data);
+                }
+                if let Some((expected, received)) = field.type_mismatch(&data) {
+                    return Err(type_error(
+                        field_id.as_str(),
+                        Some(param),
+                        expected,
+                        received,
+                    ));
+                }
                 okay &= field.vt_data_fpath(&mut data);
+                if okay {
+                    field.check(field_id.as_str(), &data)?;
+                    if exact & !field.is_exact(&data) {
+                        return Err(inexact_error(field_id.as_str(), Some(param)));
+                    }
+                }
+                param += params;
                 okay &= prepared_data.st_insert(
                     unsafe {
                         // UNSAFE(@ohsayan): the model is right here, so we're good
@@ -113,7 +452,31 @@ fn prepare_insert(
                             break;
                         }
                     };
+                okay &= !spec_field.is_computed();
+                if let Some(counter) = spec_field.window() {
+                    okay &= counter.prepare_insert(&mut data, os::get_epoch_time_secs());
+                }
+                if let Some(decimal) = spec_field.decimal() {
+                    okay &= decimal.prepare_insert(&mut data);
+                }
+                if lenient {
+                    spec_field.coerce_numeric(&mut data);
+                }
+                if let Some((expected, received)) = spec_field.type_mismatch(&data) {
+                    return Err(type_error(
+                        spec_field_name.as_str(),
+                        None,
+                        expected,
+                        received,
+                    ));
+                }
                 okay &= spec_field.vt_data_fpath(&mut data);
+                if okay {
+                    spec_field.check(spec_field_name.as_str(), &data)?;
+                    if exact & !spec_field.is_exact(&data) {
+                        return Err(inexact_error(spec_field_name.as_str(), None));
+                    }
+                }
                 prepared_data.st_insert(
                     unsafe {
                         // UNSAFE(@ohsayan): as long as model lives, we're good
@@ -123,7 +486,52 @@ fn prepare_insert(
                 );
                 inserted += 1;
             }
-            okay &= inserted == fields.len();
+            if okay & (inserted != required) {
+                for (field_id, field) in fields.stseq_ord_kv() {
+                    if prepared_data.st_contains(field_id.as_str()) {
+                        continue;
+                    }
+                    let Some(mut data) = field.default_value() else {
+                        continue;
+                    };
+                    okay &= field.vt_data_fpath(&mut data);
+                    if okay {
+                        field.check(field_id.as_str(), &data)?;
+                    }
+                    prepared_data.st_insert(
+                        unsafe {
+                            // UNSAFE(@ohsayan): as long as model lives, we're good
+                            field_id.clone()
+                        },
+                        data,
+                    );
+                    inserted += 1;
+                }
+            }
+            okay &= inserted == required;
+        }
+    }
+    if okay & (computed_count != 0) {
+        for (field_id, field) in fields.stseq_ord_kv() {
+            let Some(expr) = field.computed() else {
+                continue;
+            };
+            let mut data = expr.eval(
+                prepared_data
+                    .st_get(expr.source())
+                    .and_then(Datacell::try_str),
+            );
+            okay &= field.vt_data_fpath(&mut data);
+            if okay {
+                field.check(field_id.as_str(), &data)?;
+            }
+            prepared_data.st_insert(
+                unsafe {
+                    // UNSAFE(@ohsayan): as long as model lives, we're good
+                    field_id.clone()
+                },
+                data,
+            );
         }
     }
     let primary_key = prepared_data.remove(model.p_key());
@@ -138,3 +546,32 @@ fn prepare_insert(
         Err(QueryError::QExecDmlValidationError)
     }
 }
+
+/// The number of parameters that a value is sent as (every element of a list is a parameter of its own)
+fn param_count(data: &Datacell) -> usize {
+    match data.try_list() {
+        Some(list) => list.read().iter().map(param_count).sum(),
+        None => 1,
+    }
+}
+
+/// The error for a value of the wrong type. `param` is the index of the value's (first) parameter, if it's known
+pub(super) fn type_error(
+    field: &str,
+    param: Option<usize>,
+    expected: &str,
+    received: &str,
+) -> QueryError {
+    let e = QueryError::QExecDmlValidationError.with_detail("field", field);
+    let e = match param {
+        Some(param) => e.with_detail("parameter", param),
+        None => e,
+    };
+    e.with_detail("expected", expected)
+        .with_detail("received", received)
+}
+
+/// The error for a float that a `float32` field would round (see [`NumericCoercion::Exact`])
+pub(super) fn inexact_error(field: &str, param: Option<usize>) -> QueryError {
+    type_error(field, param, "float32", "float64")
+}
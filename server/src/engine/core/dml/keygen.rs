@@ -0,0 +1,211 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    key generators
+    ---
+    `uuid4()`, `uuid7()` and `ulid()` generate keys on the server, so that clients don't have to. they can be called
+    (unquoted) as values in an insert, and named by the `generated` property of a string field to fill it in when an
+    insert leaves it out (`id: string { generated: "uuid7" }`). a quoted call is just a string, so `default: "uuid7()"`
+    is a literal default. all of them return a string:
+
+    - `uuid4()` is a random UUID, like `@uuidstr()`
+    - `uuid7()` is a UUID whose first 48 bits are the current time in milliseconds (RFC 9562), followed by 74 random
+    bits
+    - `ulid()` is a ULID: 48 bits of the current time in milliseconds followed by 80 random bits, encoded in Crockford's
+    base32 (26 characters)
+
+    UUIDv7s and ULIDs are monotonic on a node: when several are generated in the same millisecond (or the clock goes
+    back), the random bits of the last one are incremented instead of drawn again, so they always sort in the order
+    they were generated in
+*/
+
+use {
+    crate::engine::data::cell::Datacell,
+    parking_lot::Mutex,
+    std::time::{SystemTime, UNIX_EPOCH},
+    uuid::Uuid,
+};
+
+/// the last UUIDv7 and ULID that were generated
+static LAST_UUID7: Mutex<Monotonic> = parking_lot::const_mutex(Monotonic::new(74));
+static LAST_ULID: Mutex<Monotonic> = parking_lot::const_mutex(Monotonic::new(80));
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// A function that generates a key
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KeyGen {
+    Uuid4,
+    Uuid7,
+    Ulid,
+}
+
+impl KeyGen {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "uuid4" => Some(Self::Uuid4),
+            "uuid7" => Some(Self::Uuid7),
+            "ulid" => Some(Self::Ulid),
+            _ => None,
+        }
+    }
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Uuid4 => "uuid4",
+            Self::Uuid7 => "uuid7",
+            Self::Ulid => "ulid",
+        }
+    }
+    /// Generate a key
+    pub fn generate(&self) -> Datacell {
+        let key = match self {
+            Self::Uuid4 => Uuid::new_v4().to_string(),
+            Self::Uuid7 => uuid7(LAST_UUID7.lock().next(now_ms(), random_bits())),
+            Self::Ulid => ulid(LAST_ULID.lock().next(now_ms(), random_bits())),
+        };
+        Datacell::new_str(key.into_boxed_str())
+    }
+}
+
+/// Lay out a UUIDv7 from its time and its 74 random bits (12 in `rand_a` and 62 in `rand_b`)
+fn uuid7((ms, rand): (u64, u128)) -> String {
+    let rand_a = (rand >> 62) & 0xFFF;
+    let rand_b = rand & ((1 << 62) - 1);
+    let v = ((ms as u128) << 80) | (0x7 << 76) | (rand_a << 64) | (0b10 << 62) | rand_b;
+    Uuid::from_u128(v).to_string()
+}
+
+/// Encode a ULID from its time and its 80 random bits
+fn ulid((ms, rand): (u64, u128)) -> String {
+    let v = ((ms as u128) << 80) | rand;
+    // 26 characters of 5 bits each hold 130 bits, so the first one only holds the top 3 bits
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD[((v >> (i * 5)) & 0x1F) as usize] as char)
+        .collect()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Returns 96 random bits, taken from a random UUID (leaving out its version and variant)
+fn random_bits() -> u128 {
+    let bytes = Uuid::new_v4().into_bytes();
+    bytes[..6]
+        .iter()
+        .chain(&bytes[10..])
+        .fold(0u128, |v, b| (v << 8) | *b as u128)
+}
+
+/// The time and the random bits of the last key that was generated
+#[derive(Debug)]
+struct Monotonic {
+    ms: u64,
+    rand: u128,
+    bits: u32,
+}
+
+impl Monotonic {
+    const fn new(bits: u32) -> Self {
+        Self {
+            ms: 0,
+            rand: 0,
+            bits,
+        }
+    }
+    /// Returns the time and random bits for the next key: fresh random bits if the clock has moved ahead since the
+    /// last key, else the random bits of the last key incremented by one (moving on to the next millisecond if they
+    /// overflow)
+    fn next(&mut self, now_ms: u64, random: u128) -> (u64, u128) {
+        let mask = (1u128 << self.bits) - 1;
+        if now_ms > self.ms {
+            self.ms = now_ms;
+            self.rand = random & mask;
+        } else if self.rand == mask {
+            self.ms += 1;
+            self.rand = random & mask;
+        } else {
+            self.rand += 1;
+        }
+        (self.ms, self.rand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ulid, uuid7, KeyGen, Monotonic};
+
+    #[test]
+    fn names() {
+        for f in [KeyGen::Uuid4, KeyGen::Uuid7, KeyGen::Ulid] {
+            assert_eq!(KeyGen::from_name(f.name()), Some(f));
+        }
+        assert_eq!(KeyGen::from_name("uuid7()"), None);
+        assert_eq!(KeyGen::from_name("uuidstr"), None);
+    }
+
+    #[test]
+    fn monotonic() {
+        let mut last = Monotonic::new(8);
+        assert_eq!(last.next(10, 0x1F0), (10, 0xF0));
+        // same millisecond (and a clock that went back)
+        assert_eq!(last.next(10, 0x05), (10, 0xF1));
+        assert_eq!(last.next(9, 0x05), (10, 0xF2));
+        // the random bits overflow
+        last.rand = 0xFF;
+        assert_eq!(last.next(10, 0x05), (11, 0x05));
+        assert_eq!(last.next(12, 0x07), (12, 0x07));
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!(
+            uuid7((0x0189_9D2B_5A4C, (0xABC << 62) | 0x123)),
+            "01899d2b-5a4c-7abc-8000-000000000123"
+        );
+        assert_eq!(ulid((0, 0)), "00000000000000000000000000");
+        assert_eq!(ulid((1, 1)), "00000000010000000000000001");
+        assert_eq!(
+            ulid(((1 << 48) - 1, (1 << 80) - 1)),
+            "7ZZZZZZZZZZZZZZZZZZZZZZZZZ"
+        );
+    }
+
+    #[test]
+    fn generated_keys_sort_in_order() {
+        for f in [KeyGen::Uuid7, KeyGen::Ulid] {
+            let keys: Vec<String> = (0..100).map(|_| f.generate().str().to_owned()).collect();
+            assert!(keys.windows(2).all(|w| w[0] < w[1]), "failed for {f:?}");
+        }
+        let key = KeyGen::Uuid4.generate();
+        assert_eq!(key.str().len(), 36);
+        assert_eq!(KeyGen::Ulid.generate().str().len(), 26);
+    }
+}
@@ -24,40 +24,645 @@
  *
 */
 
+mod agg;
+mod arrow;
+mod blob;
 mod del;
+mod eval;
+pub(in crate::engine) mod expr;
+mod file;
+mod generate;
 mod ins;
+pub(in crate::engine) mod keygen;
+pub(in crate::engine) mod prefetch;
+mod rdb;
 mod sel;
+mod traverse;
 mod upd;
+mod upstream;
+mod view;
 
-use crate::{
-    engine::{
-        core::model::ModelData,
-        data::{lit::Lit, tag::DataTag},
-        error::{QueryError, QueryResult},
-        ql::dml::WhereClause,
+use {
+    crate::{
+        engine::{
+            core::{
+                index::{DcFieldIndex, PrimaryIndexKey, Row, RowData, SecondaryIndex},
+                lock,
+                model::{DeltaVersion, Field, ModelData},
+                session, util, EntityIDRef,
+            },
+            data::{
+                cell::{Datacell, VirtualDatacell},
+                lit::Lit,
+                tag::{DataTag, TagClass},
+            },
+            error::{QueryError, QueryResult},
+            fractal::{GlobalInstanceLike, Limit},
+            idx::{STIndex, STIndexSeq},
+            mem::IntegerRepr,
+            net::protocol::{Response, ResponseType},
+            ql::{
+                dml::{sel::SelectKeys, RelationalExpr, Returning, Summary, WhereClause},
+                lex::Ident,
+            },
+            storage::encode_key,
+        },
+        util::compiler,
+    },
+    parking_lot::{RwLockReadGuard, RwLockWriteGuard},
+    regex::Regex,
+    std::{
+        cmp::Ordering,
+        collections::HashMap,
+        fmt::Write,
+        mem,
+        ops::{Bound, Deref, DerefMut},
+        time::Instant,
     },
-    util::compiler,
 };
 
+pub use {
+    agg::{select_aggregate_resp, CellKey, Sum},
+    blob::{check_blob_target, read_blob, read_tiered_blob, tier_blobs, write_blob},
+    del::{delete_resp, purge_tombstones},
+    eval::eval,
+    file::select_file_resp,
+    generate::generate_rows,
+    ins::{bulk_insert, bulk_load, copy_rows, insert_resp, restore_rows},
+    rdb::{import_rdb, import_rdb_in_background},
+    sel::{
+        encode_cell, exists_resp, explain_select_all_resp, explain_select_resp, fetch, has_tiered,
+        resolve_tiered, select_all_resp, select_resp,
+    },
+    traverse::traverse_resp,
+    upd::{backfill_rows, update_resp},
+    upstream::{
+        upstream_fetch, upstream_fetch_key, upstream_forget, upstream_refresh, upstream_writes,
+        UpstreamFetch,
+    },
+    view::{fill_view, refresh_views},
+};
 #[cfg(test)]
 pub use {
     del::delete,
     ins::insert,
-    sel::{select_all, select_custom},
+    sel::{exists, select_all_with_total, select_custom, Total},
     upd::{collect_trace_path as update_flow_trace, update},
 };
-pub use {
-    del::delete_resp,
-    ins::insert_resp,
-    sel::{select_all_resp, select_resp},
-    upd::update_resp,
-};
+
+/// The rows that a scan is restricted to
+#[derive(Clone)]
+pub(self) enum ScanTarget<'a, 'm> {
+    /// a range of primary keys
+    Range(Bound<Lit<'a>>, Bound<Lit<'a>>),
+    /// all rows where the first indexed fields are equal to the given values
+    Secondary {
+        name: &'m str,
+        index: &'m SecondaryIndex,
+        values: Vec<PrimaryIndexKey>,
+    },
+}
+
+impl<'a, 'm> ScanTarget<'a, 'm> {
+    /// Estimate the share of the model's rows that the scan visits, using the histogram of the field that the scan is
+    /// restricted on. Returns [`None`] if the model doesn't keep histograms, or there isn't one for the field
+    pub(self) fn selectivity(&self, mdl: &ModelData) -> Option<f64> {
+        let stats = mdl.stats()?;
+        match self {
+            Self::Range(Bound::Unbounded, Bound::Unbounded) => Some(1.0),
+            Self::Range(lo, hi) => Some(
+                stats
+                    .histogram(mdl.p_key())?
+                    .range_selectivity(lit_bound(lo)?, lit_bound(hi)?),
+            ),
+            Self::Secondary { index, values, .. } => {
+                let value = &values[0];
+                let value = value
+                    .uint()
+                    .map(|v| v as f64)
+                    .or_else(|| value.sint().map(|v| v as f64))?;
+                Some(stats.histogram(index.field())?.eq_selectivity(value))
+            }
+        }
+    }
+}
+
+fn lit_bound(b: &Bound<Lit>) -> Option<Bound<f64>> {
+    let value = |lit: &Lit| {
+        lit.try_uint()
+            .map(|v| v as f64)
+            .or_else(|| lit.try_sint().map(|v| v as f64))
+            .or_else(|| lit.try_float())
+    };
+    match b {
+        Bound::Included(lit) => value(lit).map(Bound::Included),
+        Bound::Excluded(lit) => value(lit).map(Bound::Excluded),
+        Bound::Unbounded => Some(Bound::Unbounded),
+    }
+}
+
+/// The clauses of a where clause that couldn't be resolved using an index and have to be checked against each row
+pub(self) struct RowFilter<'a> {
+    clauses: Vec<RelationalExpr<'a>>,
+    /// the compiled patterns for all `MATCHES` clauses, so that each pattern is compiled once per statement
+    patterns: HashMap<&'a str, Regex>,
+}
+
+impl<'a> RowFilter<'a> {
+    /// Create a filter from the given clauses, compiling all the patterns. Fails if a pattern is illegal or exceeds the
+    /// limits
+    pub(self) fn new(clauses: Vec<RelationalExpr<'a>>) -> QueryResult<Self> {
+        let mut patterns = HashMap::new();
+        for pattern in clauses.iter().filter_map(RelationalExpr::matches_pattern) {
+            if !patterns.contains_key(pattern) {
+                match util::compile_regex(pattern, false) {
+                    Some(regex) => {
+                        patterns.insert(pattern, regex);
+                    }
+                    None => return compiler::cold_rerr(QueryError::QExecDmlIllegalPattern),
+                }
+            }
+        }
+        Ok(Self { clauses, patterns })
+    }
+    fn eval(&self, clause: &RelationalExpr<'a>, dc: &Datacell) -> bool {
+        match clause.matches_pattern() {
+            // a null or non-string value never matches
+            Some(pattern) => dc
+                .try_str()
+                .map_or(false, |s| self.patterns[pattern].is_match(s)),
+            None => clause.eval(|lit| cmp_dc_lit(dc, lit)),
+        }
+    }
+    /// Returns true if the row with the given key and fields satisfies all clauses
+    pub(self) fn matches(
+        &self,
+        mdl: &ModelData,
+        key: &PrimaryIndexKey,
+        fields: &DcFieldIndex,
+    ) -> bool {
+        let pk = VirtualDatacell::new_pk(key, mdl.p_tag());
+        self.matches_with(|field| {
+            if field == mdl.p_key() {
+                Some(&*pk)
+            } else {
+                fields.st_get(field)
+            }
+        })
+    }
+    /// Returns true if a row satisfies all clauses, using `field` to get the value of each field (a missing field
+    /// never matches, unless it's read by a function, and neither does a row for which a function fails)
+    pub(self) fn matches_with<'r>(&self, field: impl Fn(&str) -> Option<&'r Datacell>) -> bool {
+        self.clauses.iter().all(|clause| match clause.lhs_expr() {
+            Some(lhs) => expr::eval(lhs, &field).map_or(false, |dc| self.eval(clause, &dc)),
+            None => match field(clause.lhs().as_str()) {
+                Some(dc) => self.eval(clause, dc),
+                None => false,
+            },
+        })
+    }
+    /// Returns true if there are no clauses to check
+    pub(self) fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+    /// Returns the fields used by the clauses
+    pub(self) fn fields(&self) -> impl Iterator<Item = &str> {
+        self.clauses
+            .iter()
+            .flat_map(RelationalExpr::fields)
+            .map(|field| field.as_str())
+    }
+    /// Type check the functions used by the clauses, and check that none of the fields are tiered
+    pub(self) fn check(&self, mdl: &ModelData) -> QueryResult<()> {
+        for field in self.fields() {
+            blob::check_not_tiered(mdl, field)?;
+        }
+        for lhs in self.clauses.iter().filter_map(RelationalExpr::lhs_expr) {
+            expr::check(lhs, mdl)?;
+        }
+        Ok(())
+    }
+}
+
+/// The rows locked by a statement that touches several rows at once: the rows read by a statement that touches several
+/// entities, possibly across spaces (such as `fetch`), or the rows changed by a multi-row update or delete.
+///
+/// Rows are locked in a single global order (by space, then model, then key), and every row is locked just once.
+/// Locking a row may briefly upgrade to a write lock (to apply pending schema changes), so two statements locking the
+/// same rows in different orders could deadlock, and a statement locking a row twice could wait on itself
+pub(self) struct OrderedRowLocks<'a, 'g, G = RwLockReadGuard<'g, RowData>> {
+    rows: Vec<(EntityIDRef<'a>, &'g Row)>,
+    guards: Vec<lock::Held<'static, G>>,
+}
+
+impl<'a, 'g> OrderedRowLocks<'a, 'g> {
+    /// Lock the given rows (of the given models) for reading
+    pub(self) fn read(rows: Vec<(EntityIDRef<'a>, &'g ModelData, &'g Row)>) -> QueryResult<Self> {
+        Self::lock_all(rows, lock::read)
+    }
+}
+
+impl<'a, 'g> OrderedRowLocks<'a, 'g, RwLockWriteGuard<'g, RowData>> {
+    /// Lock the given rows (of the given models) for writing, applying any schema changes that they're missing first
+    pub(self) fn write(rows: Vec<(EntityIDRef<'a>, &'g ModelData, &'g Row)>) -> QueryResult<Self> {
+        Self::lock_all(rows, |mdl, row| {
+            drop(lock::read(mdl, row)?);
+            lock::write(mdl, row)
+        })
+    }
+}
+
+impl<'a, 'g, G: Deref<Target = RowData>> OrderedRowLocks<'a, 'g, G> {
+    fn lock_all(
+        mut rows: Vec<(EntityIDRef<'a>, &'g ModelData, &'g Row)>,
+        acquire: impl Fn(&'g ModelData, &'g Row) -> QueryResult<lock::Held<'static, G>>,
+    ) -> QueryResult<Self> {
+        rows.sort_by(|(e1, _, r1), (e2, _, r2)| {
+            e1.cmp(e2).then_with(|| r1.d_key().cmp(r2.d_key()))
+        });
+        rows.dedup_by(|(e1, _, r1), (e2, _, r2)| (e1 == e2) & (r1.d_key() == r2.d_key()));
+        let guards = rows
+            .iter()
+            .map(|(_, mdl, row)| acquire(*mdl, *row))
+            .collect::<QueryResult<_>>()?;
+        Ok(Self {
+            rows: rows
+                .into_iter()
+                .map(|(entity, _, row)| (entity, row))
+                .collect(),
+            guards,
+        })
+    }
+    /// Returns the data of a locked row
+    ///
+    /// ## Panics
+    ///
+    /// If the row wasn't locked
+    pub(self) fn get(&self, entity: EntityIDRef<'a>, key: &PrimaryIndexKey) -> &RowData {
+        let i = self
+            .rows
+            .binary_search_by(|(e, row)| e.cmp(&entity).then_with(|| row.d_key().cmp(key)))
+            .expect("row not locked");
+        &self.guards[i]
+    }
+    /// Release the rows for which `f` returns false
+    pub(self) fn retain(&mut self, mut f: impl FnMut(&'g Row, &RowData) -> bool) {
+        (self.rows, self.guards) = mem::take(&mut self.rows)
+            .into_iter()
+            .zip(mem::take(&mut self.guards))
+            .filter(|((_, row), data)| f(*row, data))
+            .unzip();
+    }
+    /// Returns the number of rows that are locked
+    pub(self) fn len(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+impl<'a, 'g, G: DerefMut<Target = RowData>> OrderedRowLocks<'a, 'g, G> {
+    /// Returns the locked rows (in the order in which they were locked) along with their data
+    pub(self) fn iter_mut(&mut self) -> impl Iterator<Item = (&'g Row, &mut RowData)> {
+        self.rows
+            .iter()
+            .map(|(_, row)| *row)
+            .zip(self.guards.iter_mut().map(|data| &mut **data))
+    }
+}
+
+/// The number of charges to a query's budget between checks of its deadline
+const DEADLINE_CHECK_EVERY: u64 = 256;
+
+/// Tracks the (approximate) memory used by a query's intermediate state (such as the response being assembled) and
+/// fails the query once it exceeds the configured limit, so that a single huge query can't exhaust the server's memory.
+/// The query is warned (once) when it goes past the soft threshold of the limit.
+///
+/// Since everything that a query reads is charged to it, the budget also fails the query once it runs past its
+/// timeout (see [`session`])
+pub(self) struct QueryMemBudget {
+    used: usize,
+    limit: usize,
+    soft: usize,
+    /// when the query times out, and its timeout (in milliseconds)
+    deadline: Option<(Instant, u64)>,
+    charges: u64,
+}
+
+impl QueryMemBudget {
+    pub(self) fn new(global: &impl GlobalInstanceLike) -> Self {
+        let limit = global.get_query_memory_limit();
+        let soft = match limit {
+            // no limit was set
+            usize::MAX => None,
+            limit => global.get_soft_limit().threshold(limit as u64),
+        };
+        Self {
+            used: 0,
+            limit,
+            soft: soft.map_or(usize::MAX, |soft| soft as usize),
+            deadline: session::deadline(),
+            charges: 0,
+        }
+    }
+    /// Account for `size` more bytes
+    pub(self) fn charge(&mut self, size: usize) -> QueryResult<()> {
+        if let Some((deadline, timeout_ms)) = self.deadline {
+            // reading the clock isn't free, so only some charges check it
+            if compiler::unlikely(self.charges % DEADLINE_CHECK_EVERY == 0)
+                && (Instant::now() >= deadline)
+            {
+                return compiler::cold_rerr(
+                    QueryError::QExecStatementTimeout.with_detail("timeout_ms", timeout_ms),
+                );
+            }
+            self.charges += 1;
+        }
+        self.used = self.used.saturating_add(size);
+        if compiler::unlikely(self.used > self.limit) {
+            return compiler::cold_rerr(QueryError::QExecQueryMemoryLimitExceeded);
+        }
+        if compiler::unlikely(self.used > self.soft) {
+            Limit::QueryMemory.warn(self.used as u64, self.limit as u64);
+            // only warn once
+            self.soft = usize::MAX;
+        }
+        Ok(())
+    }
+    /// Give back `size` bytes (that were charged earlier) once they are freed
+    pub(self) fn release(&mut self, size: usize) {
+        self.used = self.used.saturating_sub(size);
+    }
+    /// Account for a cell
+    pub(self) fn charge_cell(&mut self, dc: &Datacell) -> QueryResult<()> {
+        self.charge(Self::cell_size(dc))
+    }
+    /// Account for a row
+    pub(self) fn charge_row(
+        &mut self,
+        key: &PrimaryIndexKey,
+        fields: &DcFieldIndex,
+    ) -> QueryResult<()> {
+        let size = Self::key_size(key) + fields.st_iter_value().map(Self::cell_size).sum::<usize>();
+        self.charge(size)
+    }
+    /// Account for a primary key
+    pub(self) fn charge_key(&mut self, key: &PrimaryIndexKey) -> QueryResult<()> {
+        self.charge(Self::key_size(key))
+    }
+    fn key_size(key: &PrimaryIndexKey) -> usize {
+        let data = key.str().map(str::len).or(key.bin().map(<[u8]>::len));
+        mem::size_of::<PrimaryIndexKey>() + data.unwrap_or(0)
+    }
+    fn cell_size(dc: &Datacell) -> usize {
+        let data = match dc.kind() {
+            _ if dc.is_null() => 0,
+            TagClass::Str => dc.try_str().map_or(0, str::len),
+            TagClass::Bin => dc.try_bin().map_or(0, <[u8]>::len),
+            TagClass::List => dc.list().read().iter().map(Self::cell_size).sum(),
+            _ => 0,
+        };
+        mem::size_of::<Datacell>() + data
+    }
+}
+
+/// The number of keys that `with keys` returns at most (the counts include every row)
+const SUMMARY_MAX_KEYS: u64 = 1000;
+
+/// The kind of mutation that a summary is for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(self) enum Mutation {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// The rows affected by a mutation that ends with `with summary` or `with keys`
+struct AffectedRows {
+    mutation: Mutation,
+    rows: u64,
+    /// the rows that an update changed a value of (an assignment can leave a field as it was)
+    modified: u64,
+    /// the keys of the rows (for `with keys`), as an unterminated JSON array
+    keys: Option<String>,
+}
+
+impl AffectedRows {
+    fn new(mutation: Mutation, summary: Summary) -> Self {
+        Self {
+            mutation,
+            rows: 0,
+            modified: 0,
+            keys: (summary == Summary::Keys).then(|| "[".to_owned()),
+        }
+    }
+    fn push(&mut self, key: &PrimaryIndexKey, modified: bool) {
+        if let Some(keys) = self.keys.as_mut().filter(|_| self.rows < SUMMARY_MAX_KEYS) {
+            if self.rows != 0 {
+                keys.push(',');
+            }
+            encode_key(keys, key);
+        }
+        self.rows += 1;
+        self.modified += modified as u64;
+    }
+    fn into_response(self) -> Response {
+        let (matched, modified, inserted, deleted) = match self.mutation {
+            Mutation::Insert => (0, 0, self.rows, 0),
+            Mutation::Update => (self.rows, self.modified, 0, 0),
+            Mutation::Delete => (self.rows, 0, 0, self.rows),
+        };
+        let mut summary = format!(
+            "{{\"matched\":{matched},\"modified\":{modified},\"inserted\":{inserted},\"deleted\":{deleted}"
+        );
+        if let Some(keys) = self.keys {
+            let _ = write!(
+                summary,
+                ",\"keys\":{keys}],\"truncated\":{}",
+                self.rows > SUMMARY_MAX_KEYS
+            );
+        }
+        summary.push('}');
+        Response::Serialized {
+            ty: ResponseType::String,
+            size: summary.len(),
+            data: summary.into_bytes(),
+        }
+    }
+}
+
+/// The rows affected by a DML statement with a `returning` clause, encoded as they are mutated. For a statement that
+/// ends with `with summary` or `with keys`, the rows are only counted (and their keys noted) instead
+pub(self) struct ReturningRows<'a> {
+    /// the projected fields ([`None`] for all fields)
+    fields: Option<Vec<Ident<'a>>>,
+    col_count: usize,
+    rows: Vec<Vec<u8>>,
+    affected: Option<AffectedRows>,
+}
+
+impl<'a> ReturningRows<'a> {
+    /// Returns the rows to return for a statement with a `returning` clause or a summary, if it has either
+    pub(self) fn for_statement(
+        mdl: &ModelData,
+        returning: Option<Returning<'a>>,
+        summary: Option<Summary>,
+        mutation: Mutation,
+    ) -> QueryResult<Option<Self>> {
+        match (returning, summary) {
+            (Some(returning), _) => Self::new(mdl, returning).map(Some),
+            (None, Some(summary)) => Ok(Some(Self {
+                fields: None,
+                col_count: 0,
+                rows: Vec::new(),
+                affected: Some(AffectedRows::new(mutation, summary)),
+            })),
+            (None, None) => Ok(None),
+        }
+    }
+    /// Returns true if the rows are encoded (and not just counted)
+    pub(self) fn encodes_rows(&self) -> bool {
+        self.affected.is_none()
+    }
+    pub(self) fn new(mdl: &ModelData, returning: Returning<'a>) -> QueryResult<Self> {
+        let fields = match returning {
+            Returning::Wildcard => None,
+            Returning::Fields(fields) => {
+                if let Some(f) = fields
+                    .iter()
+                    .find(|f| !mdl.fields().st_contains(f.as_str()))
+                {
+                    return compiler::cold_rerr(
+                        QueryError::QExecUnknownField.with_detail("field", f.as_str()),
+                    );
+                }
+                Some(fields)
+            }
+        };
+        Ok(Self {
+            col_count: fields.as_ref().map_or(mdl.fields().len(), Vec::len),
+            fields,
+            rows: Vec::new(),
+            affected: None,
+        })
+    }
+    /// Encode the row with the given key and fields
+    pub(self) fn push_row(
+        &mut self,
+        mdl: &ModelData,
+        key: &PrimaryIndexKey,
+        fields: &DcFieldIndex,
+    ) {
+        if let Some(affected) = self.affected.as_mut() {
+            affected.push(key, true);
+            return;
+        }
+        let pk = VirtualDatacell::new_pk(key, mdl.p_tag());
+        let null = Datacell::null();
+        let mut row = Vec::new();
+        let mut encode = |field: &str| {
+            let dc = if field == mdl.p_key() {
+                &*pk
+            } else {
+                fields.st_get(field).unwrap_or(&null)
+            };
+            sel::encode_cell(&mut row, dc)
+        };
+        match &self.fields {
+            Some(projection) => projection.iter().for_each(|f| encode(f.as_str())),
+            None => mdl
+                .fields()
+                .stseq_ord_key()
+                .for_each(|f| encode(f.as_ref())),
+        }
+        self.rows.push(row);
+    }
+    /// Encode a row that was updated, where `modified` is set if any of its values changed
+    pub(self) fn push_updated_row(
+        &mut self,
+        mdl: &ModelData,
+        key: &PrimaryIndexKey,
+        fields: &DcFieldIndex,
+        modified: bool,
+    ) {
+        match self.affected.as_mut() {
+            Some(affected) => affected.push(key, modified),
+            None => self.push_row(mdl, key, fields),
+        }
+    }
+    /// Returns a single row for a point mutation, and all rows (possibly none) otherwise (or the summary, for a
+    /// statement that asked for one)
+    pub(self) fn into_response(self, point: bool) -> Response {
+        if let Some(affected) = self.affected {
+            return affected.into_response();
+        }
+        if point {
+            Response::Serialized {
+                ty: ResponseType::Row,
+                size: self.col_count,
+                data: self.rows.into_iter().next().unwrap_or_default(),
+            }
+        } else {
+            let mut data = Vec::new();
+            let size = self.rows.len();
+            for row in self.rows {
+                IntegerRepr::scoped(self.col_count as u64, |repr| data.extend(repr));
+                data.push(b'\n');
+                data.extend(row);
+            }
+            Response::Serialized {
+                ty: ResponseType::MultiRow,
+                size,
+                data,
+            }
+        }
+    }
+}
+
+/// Compare a cell with a literal. Returns [`None`] if the values can't be compared (null, lists or mismatched types)
+fn cmp_dc_lit(dc: &Datacell, lit: &Lit) -> Option<Ordering> {
+    if dc.is_null() {
+        return None;
+    }
+    match dc.kind() {
+        TagClass::Bool => Some(dc.try_bool()?.cmp(&lit.try_bool()?)),
+        TagClass::UnsignedInt | TagClass::SignedInt => {
+            let int = |u: Option<u64>, s: Option<i64>| {
+                u.map(|u| u as i128).or_else(|| s.map(|s| s as i128))
+            };
+            Some(int(dc.try_uint(), dc.try_sint())?.cmp(&int(lit.try_uint(), lit.try_sint())?))
+        }
+        TagClass::Float => dc.try_float()?.partial_cmp(&lit.try_float()?),
+        TagClass::Bin => Some(dc.try_bin()?.cmp(lit.try_bin()?)),
+        TagClass::Str => Some(dc.try_str()?.cmp(lit.try_str()?)),
+        TagClass::List => None,
+    }
+}
 
 impl ModelData {
+    /// Replace the values that decimal fields are compared with by the integers held for them (see
+    /// [`crate::engine::core::model::decimal`]). Comparisons on functions are left as they are
+    fn hold_decimals(&self, where_clause: &mut WhereClause) -> QueryResult<()> {
+        for (field, clause) in where_clause.clauses_mut().iter_mut() {
+            let Some(decimal) = self
+                .fields()
+                .st_get(field.as_str())
+                .and_then(Field::decimal)
+            else {
+                continue;
+            };
+            let skip = clause.lhs_expr().is_some() | clause.matches_pattern().is_some();
+            if !(skip || clause.map_rhs(|lit| decimal.held_lit(lit))) {
+                return compiler::cold_rerr(
+                    QueryError::QExecDmlValidationError.with_detail("field", field.as_str()),
+                );
+            }
+        }
+        Ok(())
+    }
     pub(self) fn resolve_where<'a>(
         &self,
         where_clause: &mut WhereClause<'a>,
     ) -> QueryResult<Lit<'a>> {
+        self.hold_decimals(where_clause)?;
         match where_clause.clauses_mut().remove(self.p_key().as_bytes()) {
             Some(clause)
                 if clause.filter_hint_none()
@@ -68,21 +673,200 @@ impl ModelData {
             _ => compiler::cold_rerr(QueryError::QExecDmlWhereHasUnindexedColumn),
         }
     }
+    /// Verify that a multi-get is on the primary key and that all the keys have the right type
+    pub(self) fn validate_select_keys(&self, keys: &SelectKeys) -> QueryResult<()> {
+        let p_tag = self.p_tag().tag_unique();
+        if (keys.field().as_str() == self.p_key())
+            & keys.keys().iter().all(|k| k.kind().tag_unique() == p_tag)
+        {
+            Ok(())
+        } else {
+            compiler::cold_rerr(QueryError::QExecDmlWhereHasUnindexedColumn)
+        }
+    }
+    /// Resolve a where clause that selects a range of primary keys. An empty clause selects all keys
+    pub(self) fn resolve_where_range<'a>(
+        &self,
+        where_clause: &mut WhereClause<'a>,
+    ) -> QueryResult<(Bound<Lit<'a>>, Bound<Lit<'a>>)> {
+        let clauses = where_clause.clauses_mut();
+        if clauses.is_empty() {
+            return Ok((Bound::Unbounded, Bound::Unbounded));
+        }
+        let bounds = clauses
+            .remove(self.p_key().as_bytes())
+            .and_then(|clause| clause.bounds());
+        match bounds {
+            Some((lo, hi))
+                if clauses.is_empty()
+                    & Self::bound_is_tag(&lo, self.p_tag())
+                    & Self::bound_is_tag(&hi, self.p_tag()) =>
+            {
+                Ok((lo, hi))
+            }
+            _ => compiler::cold_rerr(QueryError::QExecDmlWhereHasUnindexedColumn),
+        }
+    }
+    /// Resolve a where clause for a scan: either a range of primary keys, or an equality filter on a field with a
+    /// secondary index (along with the clauses that imply the predicate of a partial index). `MATCHES` clauses,
+    /// clauses on functions and the clauses used for a partial index are returned as a filter that has to be checked
+    /// against each row
+    pub(self) fn resolve_where_scan<'a>(
+        &self,
+        where_clause: &mut WhereClause<'a>,
+    ) -> QueryResult<(ScanTarget<'a, '_>, RowFilter<'a>)> {
+        self.hold_decimals(where_clause)?;
+        let clauses = where_clause.clauses_mut();
+        let patterns: Vec<Ident<'a>> = clauses
+            .iter()
+            .filter(|(_, clause)| clause.matches_pattern().is_some() | clause.lhs_expr().is_some())
+            .map(|(field, _)| *field)
+            .collect();
+        if let Some(field) = patterns
+            .iter()
+            .find(|field| !self.fields().st_contains(field.as_str()))
+        {
+            return compiler::cold_rerr(
+                QueryError::QExecUnknownField.with_detail("field", field.as_str()),
+            );
+        }
+        let mut filter: Vec<_> = patterns
+            .into_iter()
+            .filter_map(|field| clauses.remove(&field))
+            .collect();
+        if let Some(target) = self.resolve_where_secondary(where_clause) {
+            filter.extend(where_clause.clauses_mut().drain().map(|(_, clause)| clause));
+            let filter = RowFilter::new(filter)?;
+            filter.check(self)?;
+            return Ok((target, filter));
+        }
+        let filter = RowFilter::new(filter)?;
+        filter.check(self)?;
+        let (lo, hi) = self.resolve_where_range(where_clause)?;
+        Ok((ScanTarget::Range(lo, hi), filter))
+    }
+    /// Resolve an arbitrary where clause for a scan. A clause on the primary key (or, failing that, an equality
+    /// clause on a field with a secondary index) restricts the scan; all other clauses are returned as a filter that
+    /// has to be checked against each row.
+    ///
+    /// NOTE(@ohsayan): the secondary index clause is also kept in the filter since the field can be updated while
+    /// we're not holding the row's lock, so callers can recheck a row using just the filter
+    pub(self) fn resolve_where_filtered<'a>(
+        &self,
+        where_clause: &mut WhereClause<'a>,
+    ) -> QueryResult<(ScanTarget<'a, '_>, RowFilter<'a>)> {
+        self.hold_decimals(where_clause)?;
+        let clauses = where_clause.clauses_mut();
+        if let Some(field) = clauses
+            .keys()
+            .find(|field| !self.fields().st_contains(field.as_str()))
+        {
+            return compiler::cold_rerr(
+                QueryError::QExecUnknownField.with_detail("field", field.as_str()),
+            );
+        }
+        let range = clauses
+            .get(self.p_key().as_bytes())
+            .and_then(|clause| clause.bounds())
+            .filter(|(lo, hi)| {
+                Self::bound_is_tag(lo, self.p_tag()) & Self::bound_is_tag(hi, self.p_tag())
+            })
+            .map(|(lo, hi)| ScanTarget::Range(lo, hi));
+        let secondary = self
+            .secondary_index_for(clauses, |_| true)
+            .map(|(name, index, values)| ScanTarget::Secondary {
+                name,
+                index,
+                values,
+            });
+        // prefer the key range, unless the histograms say that the secondary index visits fewer rows
+        let target = match (range, secondary) {
+            (Some(range), Some(secondary))
+                if matches!(
+                    (range.selectivity(self), secondary.selectivity(self)),
+                    (Some(r), Some(s)) if s < r
+                ) =>
+            {
+                secondary
+            }
+            (Some(range), _) => {
+                clauses.remove(self.p_key().as_bytes());
+                range
+            }
+            (None, Some(secondary)) => secondary,
+            (None, None) => ScanTarget::Range(Bound::Unbounded, Bound::Unbounded),
+        };
+        let filter = RowFilter::new(clauses.drain().map(|(_, clause)| clause).collect())?;
+        filter.check(self)?;
+        Ok((target, filter))
+    }
+    /// Resolve equality clauses on (a prefix of) the fields of a secondary index, removing them from the where clause.
+    /// Any other clauses have to be on the other indexed fields, or on the fields used by the predicate of a partial
+    /// index (and are left in place)
+    fn resolve_where_secondary<'a>(
+        &self,
+        where_clause: &mut WhereClause<'a>,
+    ) -> Option<ScanTarget<'a, '_>> {
+        let clauses: &HashMap<_, _> = where_clause.clauses_mut();
+        let (name, index, values) = self.secondary_index_for(clauses, |index| {
+            clauses.keys().all(|other| {
+                index.indexes(other.as_str())
+                    | index
+                        .filter()
+                        .map_or(false, |filter| filter.fields().any(|f| f == other.as_str()))
+            })
+        })?;
+        for field in &index.fields()[..values.len()] {
+            where_clause.clauses_mut().remove(field.as_bytes());
+        }
+        Some(ScanTarget::Secondary {
+            name,
+            index,
+            values,
+        })
+    }
+    fn bound_is_tag(b: &Bound<Lit>, tag: impl DataTag) -> bool {
+        match b {
+            Bound::Included(lit) | Bound::Excluded(lit) => {
+                lit.kind().tag_unique() == tag.tag_unique()
+            }
+            Bound::Unbounded => true,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct QueryExecMeta {
     delta_hint: usize,
+    version: Option<DeltaVersion>,
 }
 
 impl QueryExecMeta {
-    pub fn new(delta_hint: usize) -> Self {
-        Self { delta_hint }
+    pub fn new(delta_hint: usize, version: DeltaVersion) -> Self {
+        Self {
+            delta_hint,
+            version: Some(version),
+        }
     }
     pub fn zero() -> Self {
-        Self::new(0)
+        Self {
+            delta_hint: 0,
+            version: None,
+        }
     }
     pub fn delta_hint(&self) -> usize {
         self.delta_hint
     }
+    /// Returns the version (commit sequence number) of the last change made by the query, if it changed anything
+    pub fn version(&self) -> Option<DeltaVersion> {
+        self.version
+    }
+}
+
+/// The response to a mutation that ends with `with lsn`: the commit sequence number of its last change, or null if
+/// nothing was changed
+fn lsn_response(version: Option<DeltaVersion>) -> Response {
+    version.map_or(Response::Null, |version| {
+        Response::UInt64(version.value_u64())
+    })
 }
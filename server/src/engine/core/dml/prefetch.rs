@@ -0,0 +1,199 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    prefetching
+    ---
+    a scan reads its rows one after the other, and the data of a row is an allocation of its own which usually isn't
+    in the CPU's cache by the time that the scan gets to it. so a scan (of a `select all`, including the ones that
+    sort or aggregate, or of an `update` or a `delete`) reads the next `system.scan_prefetch` rows ahead of the row
+    that it is at, and asks the CPU to start loading their data. by the time the scan gets to one of them, its data
+    is (hopefully) already in the cache. the lookahead is 8 rows unless set otherwise (it can be at most 256), and 0
+    turns prefetching off.
+
+    the rows that were prefetched are counted, along with the ones that the scan then got to (a scan that stops early,
+    say because of its limit, wastes the rows that it read ahead of it). `sysctl report prefetch` reports both, and
+    the share of the prefetched rows that were used. only x86_64 has a prefetch hint on stable; on other targets the
+    rows are read ahead, but nothing is prefetched (or counted)
+*/
+
+use {
+    crate::engine::{config::ConfigSystem, core::index::Row},
+    std::{
+        collections::VecDeque,
+        sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+static LOOKAHEAD: AtomicUsize = AtomicUsize::new(ConfigSystem::DEFAULT_SCAN_PREFETCH as usize);
+/// the rows that were prefetched
+static PREFETCHED: AtomicU64 = AtomicU64::new(0);
+/// the prefetched rows that a scan got to
+static USED: AtomicU64 = AtomicU64::new(0);
+
+/// Set the number of rows that scans read ahead (0 turns prefetching off). Anything above
+/// [`ConfigSystem::MAX_SCAN_PREFETCH`] is read as the maximum
+pub fn set_lookahead(rows: u64) {
+    LOOKAHEAD.store(
+        rows.min(ConfigSystem::MAX_SCAN_PREFETCH) as usize,
+        Ordering::Relaxed,
+    )
+}
+
+/// Returns the number of rows that scans read ahead
+pub fn lookahead() -> usize {
+    LOOKAHEAD.load(Ordering::Relaxed)
+}
+
+/// Returns the lookahead and the counters as JSON
+pub fn describe() -> String {
+    let (prefetched, used) = (
+        PREFETCHED.load(Ordering::Relaxed),
+        USED.load(Ordering::Relaxed),
+    );
+    let efficiency = match prefetched {
+        0 => "null".to_owned(),
+        _ => format!("{:.3}", used as f64 / prefetched as f64),
+    };
+    format!(
+        "{{\"lookahead\":{},\"prefetched\":{prefetched},\"used\":{used},\"efficiency\":{efficiency}}}",
+        lookahead()
+    )
+}
+
+/// The rows that a scan read ahead of the row that it is at. The counters are added to the instance's once the scan
+/// is done
+pub(super) struct ReadAhead<'g> {
+    rows: VecDeque<Option<&'g Row>>,
+    lookahead: usize,
+    prefetched: u64,
+    used: u64,
+}
+
+impl<'g> ReadAhead<'g> {
+    pub(super) fn new() -> Self {
+        let lookahead = lookahead();
+        Self {
+            rows: VecDeque::with_capacity(lookahead),
+            lookahead,
+            prefetched: 0,
+            used: 0,
+        }
+    }
+    /// Returns the next row of `source` (which returns [`None`] once the scan is done, and `Some(None)` for a row
+    /// that is gone), after reading ahead of it
+    pub(super) fn next(
+        &mut self,
+        mut source: impl FnMut() -> Option<Option<&'g Row>>,
+    ) -> Option<Option<&'g Row>> {
+        if self.lookahead == 0 {
+            return source();
+        }
+        while self.rows.len() <= self.lookahead {
+            let Some(row) = source() else {
+                break;
+            };
+            if let Some(row) = row {
+                self.prefetched += row.prefetch() as u64;
+            }
+            self.rows.push_back(row);
+        }
+        let row = self.rows.pop_front()?;
+        if row.is_some() & (self.used < self.prefetched) {
+            self.used += 1;
+        }
+        Some(row)
+    }
+}
+
+impl<'g> Drop for ReadAhead<'g> {
+    fn drop(&mut self) {
+        if self.prefetched != 0 {
+            PREFETCHED.fetch_add(self.prefetched, Ordering::Relaxed);
+            USED.fetch_add(self.used, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{set_lookahead, ReadAhead},
+        crate::engine::{
+            config::ConfigSystem,
+            core::{
+                index::{DcFieldIndex, PrimaryIndexKey, Row},
+                model::DeltaVersion,
+            },
+            data::cell::Datacell,
+        },
+    };
+
+    #[test]
+    fn lookahead() {
+        set_lookahead(ConfigSystem::MAX_SCAN_PREFETCH * 2);
+        assert_eq!(super::lookahead(), 256);
+        set_lookahead(ConfigSystem::DEFAULT_SCAN_PREFETCH);
+        assert_eq!(super::lookahead(), 8);
+        assert!(super::describe().starts_with("{\"lookahead\":8,"));
+    }
+
+    #[test]
+    fn read_ahead() {
+        let rows: Vec<Row> = (0..20)
+            .map(|i| {
+                Row::new(
+                    PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(i)).unwrap(),
+                    DcFieldIndex::default(),
+                    DeltaVersion::genesis(),
+                    DeltaVersion::genesis(),
+                )
+            })
+            .collect();
+        // every fifth entry is stale
+        let mut source = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| if i % 5 == 4 { None } else { Some(row) });
+        let mut ahead = ReadAhead {
+            rows: Default::default(),
+            lookahead: 4,
+            prefetched: 0,
+            used: 0,
+        };
+        let mut returned = vec![];
+        while let Some(row) = ahead.next(|| source.next()) {
+            returned.push(row.map(|row| row.d_key().uint().unwrap()));
+        }
+        let expected: Vec<_> = (0..20).map(|i| (i % 5 != 4).then_some(i)).collect();
+        assert_eq!(returned, expected);
+        if cfg!(target_arch = "x86_64") {
+            assert_eq!((ahead.prefetched, ahead.used), (16, 16));
+        } else {
+            assert_eq!((ahead.prefetched, ahead.used), (0, 0));
+        }
+    }
+}
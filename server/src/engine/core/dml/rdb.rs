@@ -0,0 +1,952 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    redis imports
+    ---
+    `sysctl import rdb` loads the keys of a Redis snapshot (an RDB file, up to version 12) into models, for migrating
+    a Redis deployment. strings, lists and hashes are each loaded into the model that their type is mapped to, and
+    every other key is skipped: those of a type that isn't mapped (or that we can't import, like sets or streams),
+    those that have expired and those in any database but the one that is imported (the first, unless another is
+    chosen), since the same key can be in more than one of them.
+
+    a model is keyed by the Redis key, which is decoded like every other value (below). its other fields (but the
+    computed ones) are:
+    - for strings, one field that holds the value
+    - for lists, one list that holds the elements
+    - for hashes, any number of fields, each of which holds the hash's field of the same name (or null if the hash
+      doesn't have it). a hash with a field that the model doesn't have fails the import
+    a value is kept as it is in a binary, and is otherwise decoded from its text like a value of a foreign scan (so
+    that a number is parsed from its decimal form). the keys are inserted in bulk, and an import that fails (or is
+    cancelled) leaves the models with the keys imported so far. hashes with field expirations (from Redis 7.4) and
+    module types other than those saved with their own opcodes can't be read at all, and fail the import
+*/
+
+use {
+    super::file,
+    crate::{
+        engine::{
+            core::{
+                model::{Field, ModelData},
+                EntityIDRef,
+            },
+            data::{
+                cell::Datacell,
+                tag::{DataTag, TagClass},
+                DictEntryGeneric, DictGeneric,
+            },
+            error::{QueryError, QueryResult},
+            fractal::{
+                jobs::{self, Job, JobKind, JobStatus},
+                GlobalInstanceLike,
+            },
+            idx::STIndexSeq,
+        },
+        util::os,
+    },
+    std::{
+        io::{self, BufReader, Read},
+        mem,
+    },
+};
+
+/// The number of keys inserted at a time
+const IMPORT_BATCH: usize = 1024;
+/// The largest string that a snapshot can hold (the largest bulk string that Redis accepts)
+const MAX_STRING: u64 = 512 * 1024 * 1024;
+/// The latest version of the format that we can read
+const RDB_VERSION_MAX: u16 = 12;
+
+// opcodes
+const OP_SLOT_INFO: u8 = 0xF4;
+const OP_FUNCTION2: u8 = 0xF5;
+const OP_MODULE_AUX: u8 = 0xF7;
+const OP_IDLE: u8 = 0xF8;
+const OP_FREQ: u8 = 0xF9;
+const OP_AUX: u8 = 0xFA;
+const OP_RESIZEDB: u8 = 0xFB;
+const OP_EXPIRETIME_MS: u8 = 0xFC;
+const OP_EXPIRETIME: u8 = 0xFD;
+const OP_SELECTDB: u8 = 0xFE;
+const OP_EOF: u8 = 0xFF;
+// types
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_SET: u8 = 2;
+const TYPE_ZSET: u8 = 3;
+const TYPE_HASH: u8 = 4;
+const TYPE_ZSET_2: u8 = 5;
+const TYPE_MODULE_2: u8 = 7;
+const TYPE_HASH_ZIPMAP: u8 = 9;
+const TYPE_LIST_ZIPLIST: u8 = 10;
+const TYPE_SET_INTSET: u8 = 11;
+const TYPE_ZSET_ZIPLIST: u8 = 12;
+const TYPE_HASH_ZIPLIST: u8 = 13;
+const TYPE_LIST_QUICKLIST: u8 = 14;
+const TYPE_STREAM_LISTPACKS: u8 = 15;
+const TYPE_HASH_LISTPACK: u8 = 16;
+const TYPE_ZSET_LISTPACK: u8 = 17;
+const TYPE_LIST_QUICKLIST_2: u8 = 18;
+const TYPE_STREAM_LISTPACKS_2: u8 = 19;
+const TYPE_SET_LISTPACK: u8 = 20;
+const TYPE_STREAM_LISTPACKS_3: u8 = 21;
+
+/// Import the keys of a Redis snapshot into the models that their types are mapped to, returning the number of keys
+/// that were imported and skipped (as a JSON object)
+pub fn import_rdb(
+    global: &impl GlobalInstanceLike,
+    path: &str,
+    mapping: DictGeneric,
+) -> QueryResult<String> {
+    global.admit_expensive_query()?;
+    let mapping = Mapping::new(global, mapping)?;
+    // only a job can be cancelled
+    import(global, path, mapping, None).map(|counts| report(counts.unwrap_or_default()))
+}
+
+/// Import the keys of a Redis snapshot in the background, returning the id of the job
+pub fn import_rdb_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    path: &str,
+    mapping: DictGeneric,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    let mapping = Mapping::new(global, mapping)?;
+    let path: Box<str> = path.into();
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::ImportRdb,
+        path.clone(),
+        move |global, job| match import(global, &path, mapping, Some(job)) {
+            Ok(Some(counts)) => JobStatus::Completed(Some(report(counts))),
+            Ok(None) => JobStatus::Cancelled,
+            Err(e) => JobStatus::failed(e),
+        },
+    ))
+}
+
+fn report((imported, skipped): (u64, u64)) -> String {
+    format!("{{\"imported\":{imported},\"skipped\":{skipped}}}")
+}
+
+/// Returns the number of keys that were imported and skipped, or [`None`] if the job (if any) was cancelled
+fn import(
+    global: &impl GlobalInstanceLike,
+    path: &str,
+    mut mapping: Mapping,
+    job: Option<&Job>,
+) -> QueryResult<Option<(u64, u64)>> {
+    let mut reader = RdbReader::new(BufReader::new(file::open(path)?))?;
+    let now = (os::get_epoch_time() / 1_000_000) as u64;
+    let (mut imported, mut skipped) = (0, 0);
+    while let Some(entry) = reader.next_entry()? {
+        if job.map_or(false, |job| job.token().is_cancelled()) {
+            return Ok(None);
+        }
+        let expired = entry.expires_at.map_or(false, |at| at <= now);
+        let target = match &entry.value {
+            _ if (entry.db != mapping.database) | expired => None,
+            Some(RdbValue::Str(_)) => mapping.strings.as_mut(),
+            Some(RdbValue::List(_)) => mapping.lists.as_mut(),
+            Some(RdbValue::Hash(_)) => mapping.hashes.as_mut(),
+            None => None,
+        };
+        match (target, entry.value) {
+            (Some(target), Some(value)) => {
+                let row = target
+                    .row(&entry.key, value)
+                    .map_err(|e| e.with_detail("key", String::from_utf8_lossy(&entry.key)))?;
+                target.rows.push(row);
+                if target.rows.len() == IMPORT_BATCH {
+                    imported += target.flush(global)?;
+                }
+            }
+            _ => skipped += 1,
+        }
+        if let Some(job) = job {
+            job.set_done(imported + skipped);
+        }
+    }
+    for target in [
+        &mut mapping.strings,
+        &mut mapping.lists,
+        &mut mapping.hashes,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        imported += target.flush(global)?;
+    }
+    Ok(Some((imported, skipped)))
+}
+
+/*
+    mapping
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KeyKind {
+    Str,
+    List,
+    Hash,
+}
+
+/// The models that each type of key is imported into, set with `strings`, `lists` and `hashes` (each a
+/// `<space>.<model>`), and the database that is imported, set with `database`
+struct Mapping {
+    strings: Option<Target>,
+    lists: Option<Target>,
+    hashes: Option<Target>,
+    database: u64,
+}
+
+impl Mapping {
+    fn new(global: &impl GlobalInstanceLike, mut mapping: DictGeneric) -> QueryResult<Self> {
+        let mut target = |key: &str, kind| match mapping.remove(key) {
+            None => Ok(None),
+            Some(DictEntryGeneric::Data(d)) if d.kind() == TagClass::Str => {
+                Target::new(global, d.str(), kind).map(Some)
+            }
+            Some(_) => Err(QueryError::QExecDdlInvalidProperties),
+        };
+        let strings = target("strings", KeyKind::Str)?;
+        let lists = target("lists", KeyKind::List)?;
+        let hashes = target("hashes", KeyKind::Hash)?;
+        let database = match mapping.remove("database") {
+            None => 0,
+            Some(DictEntryGeneric::Data(d)) if d.kind() == TagClass::UnsignedInt => d.uint(),
+            Some(_) => return Err(QueryError::QExecDdlInvalidProperties),
+        };
+        let nothing_mapped = strings.is_none() & lists.is_none() & hashes.is_none();
+        if nothing_mapped | !mapping.is_empty() {
+            return Err(QueryError::QExecDdlInvalidProperties);
+        }
+        Ok(Self {
+            strings,
+            lists,
+            hashes,
+            database,
+        })
+    }
+}
+
+/// A model that a type of key is imported into
+struct Target {
+    space: Box<str>,
+    model: Box<str>,
+    /// the fields (but the computed ones) in the order that they were declared in, with whether each is the primary
+    /// key
+    fields: Vec<(Box<str>, bool, Field)>,
+    /// the rows that haven't been inserted yet
+    rows: Vec<Vec<Datacell>>,
+}
+
+impl Target {
+    fn new(global: &impl GlobalInstanceLike, entity: &str, kind: KeyKind) -> QueryResult<Self> {
+        let Some((space, model)) = entity.split_once('.') else {
+            return Err(QueryError::QExecDdlInvalidProperties);
+        };
+        let read_fields = |mdl: &ModelData| {
+            let fields = mdl
+                .fields()
+                .stseq_ord_kv()
+                .filter(|(_, field)| !field.is_computed())
+                .map(|(name, field)| {
+                    let primary = name.as_str() == mdl.p_key();
+                    (Box::<str>::from(name.as_str()), primary, field.clone())
+                })
+                .collect::<Vec<_>>();
+            Ok(fields)
+        };
+        let fields = global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new(space, model), read_fields)?;
+        let mut values = fields.iter().filter(|(_, primary, _)| !primary);
+        let fits = match kind {
+            KeyKind::Str => (fields.len() == 2) & values.all(|(_, _, f)| f.layers().len() == 1),
+            KeyKind::List => (fields.len() == 2) & values.all(|(_, _, f)| f.layers().len() == 2),
+            KeyKind::Hash => values.all(|(_, _, f)| f.layers().len() == 1),
+        };
+        if !fits {
+            return Err(QueryError::QExecDdlModelBadDefinition.with_detail("model", entity));
+        }
+        Ok(Self {
+            space: space.into(),
+            model: model.into(),
+            fields,
+            rows: vec![],
+        })
+    }
+    /// Returns the row for a key, with a value for every field in order
+    fn row(&self, key: &[u8], value: RdbValue) -> QueryResult<Vec<Datacell>> {
+        let class = |field: &Field, layer: usize| field.layers()[layer].tag().tag_class();
+        let mut row = Vec::with_capacity(self.fields.len());
+        match value {
+            RdbValue::Str(mut value) => {
+                for (_, primary, field) in self.fields.iter() {
+                    let value = if *primary {
+                        key.to_vec()
+                    } else {
+                        mem::take(&mut value)
+                    };
+                    row.push(decode(class(field, 0), value)?);
+                }
+            }
+            RdbValue::List(mut elements) => {
+                for (_, primary, field) in self.fields.iter() {
+                    if *primary {
+                        row.push(decode(class(field, 0), key.to_vec())?);
+                        continue;
+                    }
+                    let elements = mem::take(&mut elements)
+                        .into_iter()
+                        .map(|element| decode(class(field, 1), element))
+                        .collect::<QueryResult<_>>()?;
+                    row.push(Datacell::new_list(elements));
+                }
+            }
+            RdbValue::Hash(mut hash) => {
+                for (name, primary, field) in self.fields.iter() {
+                    let value = if *primary {
+                        Some(key.to_vec())
+                    } else {
+                        hash.iter()
+                            .position(|(f, _)| f == name.as_bytes())
+                            .map(|i| hash.swap_remove(i).1)
+                    };
+                    row.push(match value {
+                        Some(value) => decode(class(field, 0), value)?,
+                        None => Datacell::null(),
+                    });
+                }
+                if let Some((field, _)) = hash.first() {
+                    return Err(QueryError::QExecUnknownField
+                        .with_detail("field", String::from_utf8_lossy(field)));
+                }
+            }
+        }
+        Ok(row)
+    }
+    /// Insert the rows that haven't been inserted yet, returning their number
+    fn flush(&mut self, global: &impl GlobalInstanceLike) -> QueryResult<u64> {
+        if self.rows.is_empty() {
+            return Ok(0);
+        }
+        super::bulk_insert(
+            global,
+            EntityIDRef::new(&self.space, &self.model),
+            mem::take(&mut self.rows),
+        )
+    }
+}
+
+/// Decode a value of a field with the given type class: a binary is the bytes as they are, and everything else is
+/// decoded from its text
+fn decode(class: TagClass, value: Vec<u8>) -> QueryResult<Datacell> {
+    match class {
+        TagClass::Bin => Ok(Datacell::new_bin(value.into_boxed_slice())),
+        class => String::from_utf8(value)
+            .map_err(|_| QueryError::QExecDmlValidationError)
+            .and_then(|value| file::decode_value(class, value)),
+    }
+}
+
+/*
+    snapshots
+*/
+
+/// A value in a snapshot, of a type that can be imported
+#[derive(Debug, PartialEq)]
+enum RdbValue {
+    Str(Vec<u8>),
+    List(Vec<Vec<u8>>),
+    Hash(Vec<(Vec<u8>, Vec<u8>)>),
+}
+
+/// A key in a snapshot
+#[derive(Debug, PartialEq)]
+struct RdbEntry {
+    db: u64,
+    key: Vec<u8>,
+    /// the time that the key expires at (in milliseconds since the epoch)
+    expires_at: Option<u64>,
+    /// [`None`] if the key is of a type that can't be imported
+    value: Option<RdbValue>,
+}
+
+fn malformed<T>() -> QueryResult<T> {
+    Err(QueryError::QExecDmlValidationError)
+}
+
+fn read_error(e: io::Error) -> QueryError {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => QueryError::QExecDmlValidationError,
+        _ => e.into(),
+    }
+}
+
+/// Reads the keys of a snapshot one at a time. A snapshot that is malformed (or truncated) fails with a validation
+/// error, like a malformed record of a foreign scan
+struct RdbReader<R> {
+    r: R,
+    db: u64,
+}
+
+impl<R: Read> RdbReader<R> {
+    fn new(mut r: R) -> QueryResult<Self> {
+        let mut header = [0u8; 9];
+        r.read_exact(&mut header).map_err(read_error)?;
+        let (magic, digits) = header.split_at(5);
+        let version = std::str::from_utf8(digits)
+            .ok()
+            .and_then(|v| v.parse::<u16>().ok());
+        match version {
+            _ if magic != b"REDIS" => malformed(),
+            Some(1..=RDB_VERSION_MAX) => Ok(Self { r, db: 0 }),
+            _ => Err(QueryError::QExecDmlValidationError
+                .with_detail("version", String::from_utf8_lossy(digits))),
+        }
+    }
+    /// Returns the next key, or [`None`] at the end of the snapshot (its checksum isn't verified)
+    fn next_entry(&mut self) -> QueryResult<Option<RdbEntry>> {
+        let mut expires_at = None;
+        loop {
+            match self.byte()? {
+                OP_EOF => return Ok(None),
+                OP_SELECTDB => self.db = self.length()?,
+                OP_EXPIRETIME => {
+                    expires_at = Some(u32::from_le_bytes(self.array()?) as u64 * 1000);
+                }
+                OP_EXPIRETIME_MS => expires_at = Some(u64::from_le_bytes(self.array()?)),
+                OP_RESIZEDB => {
+                    self.length()?;
+                    self.length()?;
+                }
+                OP_AUX => {
+                    self.string()?;
+                    self.string()?;
+                }
+                OP_FREQ => {
+                    self.byte()?;
+                }
+                OP_IDLE => {
+                    self.length()?;
+                }
+                OP_MODULE_AUX => {
+                    self.length()?;
+                    self.skip_module_data()?;
+                }
+                OP_FUNCTION2 => {
+                    self.string()?;
+                }
+                OP_SLOT_INFO => {
+                    for _ in 0..3 {
+                        self.length()?;
+                    }
+                }
+                ty => {
+                    let key = self.string()?;
+                    let value = self.value(ty)?;
+                    return Ok(Some(RdbEntry {
+                        db: self.db,
+                        key,
+                        expires_at,
+                        value,
+                    }));
+                }
+            }
+        }
+    }
+    fn value(&mut self, ty: u8) -> QueryResult<Option<RdbValue>> {
+        let value = match ty {
+            TYPE_STRING => RdbValue::Str(self.string()?),
+            TYPE_LIST => {
+                let mut list = vec![];
+                for _ in 0..self.length()? {
+                    list.push(self.string()?);
+                }
+                RdbValue::List(list)
+            }
+            TYPE_HASH => {
+                let mut hash = vec![];
+                for _ in 0..self.length()? {
+                    hash.push((self.string()?, self.string()?));
+                }
+                RdbValue::Hash(hash)
+            }
+            TYPE_LIST_ZIPLIST => RdbValue::List(self.packed(ziplist)?),
+            TYPE_HASH_ZIPLIST => RdbValue::Hash(pairs(self.packed(ziplist)?)?),
+            TYPE_HASH_LISTPACK => RdbValue::Hash(pairs(self.packed(listpack)?)?),
+            TYPE_LIST_QUICKLIST => {
+                let mut list = vec![];
+                for _ in 0..self.length()? {
+                    list.extend(self.packed(ziplist)?);
+                }
+                RdbValue::List(list)
+            }
+            TYPE_LIST_QUICKLIST_2 => {
+                let mut list = vec![];
+                for _ in 0..self.length()? {
+                    // a node is either a single (large) element, or a listpack
+                    match self.length()? {
+                        1 => list.push(self.string()?),
+                        2 => list.extend(self.packed(listpack)?),
+                        _ => return malformed(),
+                    }
+                }
+                RdbValue::List(list)
+            }
+            ty => {
+                self.skip_value(ty)?;
+                return Ok(None);
+            }
+        };
+        Ok(Some(value))
+    }
+    /// Skip over a value of a type that can't be imported
+    fn skip_value(&mut self, ty: u8) -> QueryResult<()> {
+        match ty {
+            TYPE_SET => {
+                for _ in 0..self.length()? {
+                    self.string()?;
+                }
+            }
+            TYPE_ZSET => {
+                for _ in 0..self.length()? {
+                    self.string()?;
+                    // the score is its text, with 253, 254 and 255 for NaN, +inf and -inf
+                    let len = self.byte()?;
+                    if len < 253 {
+                        self.bytes(len as u64)?;
+                    }
+                }
+            }
+            TYPE_ZSET_2 => {
+                for _ in 0..self.length()? {
+                    self.string()?;
+                    self.array::<8>()?;
+                }
+            }
+            TYPE_HASH_ZIPMAP | TYPE_SET_INTSET | TYPE_ZSET_ZIPLIST | TYPE_ZSET_LISTPACK
+            | TYPE_SET_LISTPACK => {
+                self.string()?;
+            }
+            TYPE_MODULE_2 => {
+                self.length()?;
+                self.skip_module_data()?;
+            }
+            TYPE_STREAM_LISTPACKS | TYPE_STREAM_LISTPACKS_2 | TYPE_STREAM_LISTPACKS_3 => {
+                self.skip_stream(ty)?;
+            }
+            ty => return Err(QueryError::QExecDmlValidationError.with_detail("type", ty)),
+        }
+        Ok(())
+    }
+    fn skip_stream(&mut self, ty: u8) -> QueryResult<()> {
+        // the nodes, each a master ID and a listpack
+        for _ in 0..self.length()? {
+            self.string()?;
+            self.string()?;
+        }
+        // the length and the last ID, and then the first ID, the largest deleted ID and the number of entries added
+        let counters = if ty == TYPE_STREAM_LISTPACKS { 3 } else { 8 };
+        for _ in 0..counters {
+            self.length()?;
+        }
+        // the consumer groups
+        for _ in 0..self.length()? {
+            self.string()?;
+            let counters = if ty == TYPE_STREAM_LISTPACKS { 2 } else { 3 };
+            for _ in 0..counters {
+                self.length()?;
+            }
+            // the pending entries: an ID, the delivery time and the delivery count
+            for _ in 0..self.length()? {
+                self.array::<16>()?;
+                self.array::<8>()?;
+                self.length()?;
+            }
+            // the consumers: a name, the times it was seen (and active) at and its pending IDs
+            for _ in 0..self.length()? {
+                self.string()?;
+                self.array::<8>()?;
+                if ty == TYPE_STREAM_LISTPACKS_3 {
+                    self.array::<8>()?;
+                }
+                for _ in 0..self.length()? {
+                    self.array::<16>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Skip over the data that a module saved, which is a series of opcodes each with a value
+    fn skip_module_data(&mut self) -> QueryResult<()> {
+        loop {
+            match self.length()? {
+                0 => return Ok(()),
+                1 | 2 => {
+                    self.length()?;
+                }
+                3 => {
+                    self.array::<4>()?;
+                }
+                4 => {
+                    self.array::<8>()?;
+                }
+                5 => {
+                    self.string()?;
+                }
+                _ => return malformed(),
+            }
+        }
+    }
+    fn packed(&mut self, f: fn(&[u8]) -> Option<Vec<Vec<u8>>>) -> QueryResult<Vec<Vec<u8>>> {
+        match f(&self.string()?) {
+            Some(entries) => Ok(entries),
+            None => malformed(),
+        }
+    }
+    fn byte(&mut self) -> QueryResult<u8> {
+        self.array::<1>().map(|[b]| b)
+    }
+    fn array<const N: usize>(&mut self) -> QueryResult<[u8; N]> {
+        let mut buf = [0u8; N];
+        self.r.read_exact(&mut buf).map_err(read_error)?;
+        Ok(buf)
+    }
+    fn bytes(&mut self, len: u64) -> QueryResult<Vec<u8>> {
+        if len > MAX_STRING {
+            return malformed();
+        }
+        // not allocated upfront, in case the length is bad
+        let mut buf = vec![];
+        (&mut self.r)
+            .take(len)
+            .read_to_end(&mut buf)
+            .map_err(read_error)?;
+        if buf.len() as u64 != len {
+            return malformed();
+        }
+        Ok(buf)
+    }
+    /// Returns either a length or the kind of a specially encoded string
+    fn length_or_encoding(&mut self) -> QueryResult<Result<u64, u8>> {
+        let b = self.byte()?;
+        Ok(match b >> 6 {
+            0 => Ok((b & 0x3F) as u64),
+            1 => Ok((((b & 0x3F) as u64) << 8) | self.byte()? as u64),
+            2 => match b {
+                0x80 => Ok(u32::from_be_bytes(self.array()?) as u64),
+                0x81 => Ok(u64::from_be_bytes(self.array()?)),
+                _ => return malformed(),
+            },
+            _ => Err(b & 0x3F),
+        })
+    }
+    fn length(&mut self) -> QueryResult<u64> {
+        match self.length_or_encoding()? {
+            Ok(len) => Ok(len),
+            Err(_) => malformed(),
+        }
+    }
+    fn string(&mut self) -> QueryResult<Vec<u8>> {
+        match self.length_or_encoding()? {
+            Ok(len) => self.bytes(len),
+            // integers are kept as their text
+            Err(0) => Ok((self.byte()? as i8).to_string().into_bytes()),
+            Err(1) => Ok(i16::from_le_bytes(self.array()?).to_string().into_bytes()),
+            Err(2) => Ok(i32::from_le_bytes(self.array()?).to_string().into_bytes()),
+            Err(3) => {
+                let (compressed, len) = (self.length()?, self.length()?);
+                if len > MAX_STRING {
+                    return malformed();
+                }
+                match lzf_decompress(&self.bytes(compressed)?, len as usize) {
+                    Some(string) => Ok(string),
+                    None => malformed(),
+                }
+            }
+            Err(_) => malformed(),
+        }
+    }
+}
+
+/// Decompress a string that was compressed with LZF, which is a series of literal runs and back references
+fn lzf_decompress(input: &[u8], len: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(len);
+    let mut i = 0;
+    while i < input.len() {
+        let ctrl = input[i] as usize;
+        i += 1;
+        if ctrl < 32 {
+            let run = input.get(i..i + ctrl + 1)?;
+            out.extend_from_slice(run);
+            i += run.len();
+            continue;
+        }
+        let mut run = ctrl >> 5;
+        if run == 7 {
+            run += *input.get(i)? as usize;
+            i += 1;
+        }
+        let back = (((ctrl & 0x1F) << 8) | *input.get(i)? as usize) + 1;
+        i += 1;
+        if back > out.len() {
+            return None;
+        }
+        // the reference can overlap the bytes that it writes
+        for _ in 0..run + 2 {
+            out.push(out[out.len() - back]);
+        }
+    }
+    (out.len() == len).then_some(out)
+}
+
+/// Returns the (sign extended) value of a little-endian integer of up to 8 bytes
+fn int_le(b: &[u8]) -> i64 {
+    let mut v = [0u8; 8];
+    v[..b.len()].copy_from_slice(b);
+    let shift = 64 - 8 * b.len() as u32;
+    (i64::from_le_bytes(v) << shift) >> shift
+}
+
+fn pairs(entries: Vec<Vec<u8>>) -> QueryResult<Vec<(Vec<u8>, Vec<u8>)>> {
+    if entries.len() % 2 != 0 {
+        return malformed();
+    }
+    let mut pairs = Vec::with_capacity(entries.len() / 2);
+    let mut entries = entries.into_iter();
+    while let (Some(k), Some(v)) = (entries.next(), entries.next()) {
+        pairs.push((k, v));
+    }
+    Ok(pairs)
+}
+
+/// Returns the entries of a ziplist (how small lists and hashes were saved before Redis 7)
+fn ziplist(z: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut entries = vec![];
+    // skip over the size, the offset of the last entry and the number of entries
+    let mut i = 10;
+    loop {
+        // the size of the previous entry
+        match *z.get(i)? {
+            0xFF => return Some(entries),
+            0xFE => i += 5,
+            _ => i += 1,
+        }
+        let enc = *z.get(i)?;
+        let (len, at) = match enc >> 6 {
+            0 => ((enc & 0x3F) as usize, i + 1),
+            1 => (
+                (((enc & 0x3F) as usize) << 8) | *z.get(i + 1)? as usize,
+                i + 2,
+            ),
+            2 => (
+                u32::from_be_bytes(z.get(i + 1..i + 5)?.try_into().ok()?) as usize,
+                i + 5,
+            ),
+            _ => {
+                let width = match enc {
+                    0xC0 => 2,
+                    0xD0 => 4,
+                    0xE0 => 8,
+                    0xF0 => 3,
+                    0xFE => 1,
+                    // an immediate 0 to 12
+                    0xF1..=0xFD => 0,
+                    _ => return None,
+                };
+                let int = match width {
+                    0 => (enc & 0x0F) as i64 - 1,
+                    width => int_le(z.get(i + 1..i + 1 + width)?),
+                };
+                entries.push(int.to_string().into_bytes());
+                i += 1 + width;
+                continue;
+            }
+        };
+        entries.push(z.get(at..at + len)?.to_vec());
+        i = at + len;
+    }
+}
+
+/// Returns the entries of a listpack (how small lists and hashes are saved since Redis 7)
+fn listpack(l: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut entries = vec![];
+    // skip over the size and the number of entries
+    let mut i = 6;
+    loop {
+        let enc = *l.get(i)?;
+        let string = |at: usize, len: usize| Some((l.get(at..at + len)?.to_vec(), at - i + len));
+        let int = |width: usize| Some((int_le(l.get(i + 1..i + 1 + width)?), 1 + width));
+        let (entry, len) = if enc == 0xFF {
+            return Some(entries);
+        } else if enc & 0x80 == 0 {
+            ((enc as i64).to_string().into_bytes(), 1)
+        } else if enc & 0xC0 == 0x80 {
+            string(i + 1, (enc & 0x3F) as usize)?
+        } else if enc & 0xE0 == 0xC0 {
+            // a 13 bit signed integer
+            let v = (((enc & 0x1F) as i64) << 8) | *l.get(i + 1)? as i64;
+            let v = if v >= 1 << 12 { v - (1 << 13) } else { v };
+            (v.to_string().into_bytes(), 2)
+        } else if enc & 0xF0 == 0xE0 {
+            string(
+                i + 2,
+                (((enc & 0x0F) as usize) << 8) | *l.get(i + 1)? as usize,
+            )?
+        } else if enc == 0xF0 {
+            let len = u32::from_le_bytes(l.get(i + 1..i + 5)?.try_into().ok()?);
+            string(i + 5, len as usize)?
+        } else {
+            let (v, len) = match enc {
+                0xF1 => int(2)?,
+                0xF2 => int(3)?,
+                0xF3 => int(4)?,
+                0xF4 => int(8)?,
+                _ => return None,
+            };
+            (v.to_string().into_bytes(), len)
+        };
+        entries.push(entry);
+        // skip over the entry's size, which follows it (so that the listpack can be read backwards)
+        i += len + backlen_size(len);
+    }
+}
+
+fn backlen_size(len: usize) -> usize {
+    match len {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2097150 => 3,
+        2097151..=268435454 => 4,
+        _ => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RdbEntry, RdbReader, RdbValue};
+
+    /// A snapshot with a key of every encoding that we can read, and some that we skip
+    fn snapshot() -> Vec<u8> {
+        let mut rdb = b"REDIS0011".to_vec();
+        // aux fields, the database and its size
+        rdb.extend(b"\xFA\x09redis-ver\x057.2.0\xFE\x00\xFB\x08\x01");
+        // strings: plain, an integer, and compressed (a literal `a` followed by a reference to 23 more)
+        rdb.extend(b"\x00\x08greeting\x05hello");
+        rdb.extend(b"\x00\x07counter\xC0\x2A");
+        rdb.extend(b"\x00\x04long\xC3\x05\x18\x00a\xE0\x0E\x00");
+        // a hash in a listpack
+        rdb.extend(b"\x10\x06user:1\x1B\x1B\x00\x00\x00\x04\x00");
+        rdb.extend(b"\x84name\x05\x85sayan\x06\x83age\x04\x14\x01\xFF");
+        // a list in a quicklist of one packed node (with a 13 bit integer) and one plain node
+        rdb.extend(b"\x12\x05queue\x02\x02\x0D\x0D\x00\x00\x00\x02\x00\x81a\x02\xDF\xFB\x02\xFF");
+        rdb.extend(b"\x01\x03big");
+        // a list in a ziplist (with a 16 bit integer)
+        rdb.extend(b"\x0A\x03old\x12\x12\x00\x00\x00\x0D\x00\x00\x00\x02\x00");
+        rdb.extend(b"\x00\x01p\x03\xC0\x2C\x01\xFF");
+        // a hash
+        rdb.extend(b"\x04\x03cfg\x01\x04mode\x04fast");
+        // a set, which is skipped
+        rdb.extend(b"\x02\x04tags\x01\x01x");
+        // an expired key, and a key in another database
+        rdb.extend(b"\xFC\x01\x00\x00\x00\x00\x00\x00\x00\x00\x04gone\x01x");
+        rdb.extend(b"\xFE\x01\x00\x05other\x01y");
+        rdb.extend(b"\xFF\x00\x00\x00\x00\x00\x00\x00\x00");
+        rdb
+    }
+
+    fn entry(db: u64, key: &str, expires_at: Option<u64>, value: Option<RdbValue>) -> RdbEntry {
+        RdbEntry {
+            db,
+            key: key.as_bytes().to_vec(),
+            expires_at,
+            value,
+        }
+    }
+
+    fn b(s: &str) -> Vec<u8> {
+        s.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn read_snapshot() {
+        let rdb = snapshot();
+        let mut reader = RdbReader::new(&rdb[..]).unwrap();
+        let mut entries = vec![];
+        while let Some(entry) = reader.next_entry().unwrap() {
+            entries.push(entry);
+        }
+        assert_eq!(
+            entries,
+            vec![
+                entry(0, "greeting", None, Some(RdbValue::Str(b("hello")))),
+                entry(0, "counter", None, Some(RdbValue::Str(b("42")))),
+                entry(0, "long", None, Some(RdbValue::Str(b(&"a".repeat(24))))),
+                entry(
+                    0,
+                    "user:1",
+                    None,
+                    Some(RdbValue::Hash(vec![
+                        (b("name"), b("sayan")),
+                        (b("age"), b("20"))
+                    ]))
+                ),
+                entry(
+                    0,
+                    "queue",
+                    None,
+                    Some(RdbValue::List(vec![b("a"), b("-5"), b("big")]))
+                ),
+                entry(0, "old", None, Some(RdbValue::List(vec![b("p"), b("300")]))),
+                entry(
+                    0,
+                    "cfg",
+                    None,
+                    Some(RdbValue::Hash(vec![(b("mode"), b("fast"))]))
+                ),
+                entry(0, "tags", None, None),
+                entry(0, "gone", Some(1), Some(RdbValue::Str(b("x")))),
+                entry(1, "other", None, Some(RdbValue::Str(b("y")))),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_bad_snapshot() {
+        // not a snapshot, too new, and truncated
+        assert!(RdbReader::new(&b"RADIS0011"[..]).is_err());
+        assert!(RdbReader::new(&b"REDIS0013"[..]).is_err());
+        let rdb = snapshot();
+        let mut reader = RdbReader::new(&rdb[..40]).unwrap();
+        assert!(std::iter::from_fn(|| reader.next_entry().transpose()).any(|e| e.is_err()));
+    }
+}
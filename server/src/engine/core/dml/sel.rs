@@ -24,30 +24,66 @@
  *
 */
 
-use crate::engine::{
-    core::{
-        index::{
-            DcFieldIndex, IndexLatchHandleExclusive, PrimaryIndexKey, Row, RowData, RowDataLck,
+use {
+    crate::{
+        engine::{
+            core::{
+                dml::{
+                    expr, prefetch::ReadAhead, OrderedRowLocks, QueryMemBudget, RowFilter,
+                    ScanTarget,
+                },
+                index::{
+                    Collation, IndexLatchHandleExclusive, PrimaryIndexIter, PrimaryIndexKey,
+                    PrimaryIndexKeyProbe, PrimaryIndexKind, Row, RowData, SecondaryIndex,
+                },
+                lock,
+                model::{
+                    columnar::ColumnStore,
+                    history::{HistoricRow, RowHistory},
+                    ModelData,
+                },
+                session::{self, Isolation},
+                EntityIDRef,
+            },
+            data::{
+                cell::{Datacell, VirtualDatacell},
+                lit::Lit,
+                tag::{DataTag, TagClass, TagSelector},
+            },
+            error::{QueryError, QueryResult},
+            fractal::{tier, GlobalInstanceLike},
+            idx::{STIndex, STIndexSeq},
+            mem::IntegerRepr,
+            net::protocol::{Response, ResponseType},
+            ql::{
+                dml::{
+                    sel::{
+                        ExistsStatement, SampleSize, SelectAllStatement, SelectKeys,
+                        SelectStatement,
+                    },
+                    RowExpr,
+                },
+                lex::Ident,
+            },
+            sync,
         },
-        model::ModelData,
+        util::{compiler, os},
     },
-    data::{
-        cell::{Datacell, VirtualDatacell},
-        tag::{DataTag, TagClass},
+    std::{
+        collections::hash_map::RandomState,
+        hash::{BuildHasher, Hasher},
+        mem,
+        ops::Bound,
     },
-    error::{QueryError, QueryResult},
-    fractal::GlobalInstanceLike,
-    idx::{IndexMTRaw, MTIndexExt, STIndex, STIndexSeq},
-    mem::IntegerRepr,
-    net::protocol::{Response, ResponseType},
-    ql::dml::sel::{SelectAllStatement, SelectStatement},
-    sync,
 };
 
 pub fn select_resp(
     global: &impl GlobalInstanceLike,
-    select: SelectStatement,
+    mut select: SelectStatement,
 ) -> QueryResult<Response> {
+    if let Some(keys) = select.take_keys() {
+        return self::select_multi_resp(global, select, keys);
+    }
     let mut data = vec![];
     let mut i = 0usize;
     self::select_custom(global, select, |item| {
@@ -61,12 +97,224 @@ pub fn select_resp(
     })
 }
 
+/// Look up each key (in the order given) and return all rows. Missing (and soft deleted, unless `with deleted` was
+/// set) keys are returned as a row of nulls, unless `skip missing` was set. With `as of`, each row is returned as it
+/// was at that time. Under snapshot isolation (see [`session`]), the rows are locked together before they're read
+fn select_multi_resp(
+    global: &impl GlobalInstanceLike,
+    select: SelectStatement,
+    keys: SelectKeys,
+) -> QueryResult<Response> {
+    global.state().with_model(select.entity(), |mdl| {
+        mdl.validate_select_keys(&keys)?;
+        let fields: Vec<&str> = if select.is_wildcard() {
+            mdl.fields()
+                .stseq_ord_key()
+                .map(|field| field.as_ref())
+                .collect()
+        } else {
+            if let Some(f) = select
+                .fields()
+                .iter()
+                .find(|f| !mdl.fields().st_contains(f.as_str()))
+            {
+                return Err(QueryError::QExecUnknownField.with_detail("field", f.as_str()));
+            }
+            select.fields().iter().map(|f| f.as_str()).collect()
+        };
+        let history = select
+            .as_of()
+            .map(|at| history_as_of(mdl, at).map(|history| (history, at)))
+            .transpose()?;
+        let g = sync::atm::cpin();
+        let found: Vec<_> = keys
+            .keys()
+            .iter()
+            .map(|key| match version_as_of(history, key.clone()) {
+                HistoricRow::Current => (mdl.primary_index().select(key.clone(), &g), None),
+                HistoricRow::Missing => (None, None),
+                HistoricRow::Version(prior) => (None, Some(prior)),
+            })
+            .collect();
+        // under snapshot isolation, all the rows are locked before any of them is read
+        let locks = match session::isolation() {
+            Isolation::Snapshot => Some(OrderedRowLocks::read(
+                found
+                    .iter()
+                    .filter_map(|(row, _)| Some((select.entity(), mdl, (*row)?)))
+                    .collect(),
+            )?),
+            Isolation::ReadCommitted => None,
+        };
+        let null = Datacell::null();
+        let mut budget = QueryMemBudget::new(global);
+        let mut data = Vec::new();
+        let mut rows = 0;
+        for (key, (row, prior)) in keys.keys().iter().zip(found) {
+            let frozen;
+            let row = match row {
+                Some(row) => {
+                    let r: &RowData = match &locks {
+                        Some(locks) => locks.get(select.entity(), row.d_key()),
+                        None => {
+                            frozen = lock::read(mdl, row)?;
+                            &frozen
+                        }
+                    };
+                    Some((row, r)).filter(|(_, r)| select.with_deleted() || !r.is_tombstoned())
+                }
+                None => None,
+            };
+            if row.is_none() & prior.is_none() & keys.skip_missing() {
+                continue;
+            }
+            IntegerRepr::scoped(fields.len() as u64, |repr| data.extend(repr));
+            data.push(b'\n');
+            match (row, prior) {
+                (None, Some(prior)) => {
+                    let pk = VirtualDatacell::new(key.clone(), mdl.p_tag().tag_unique());
+                    for field in fields.iter() {
+                        let dc = if *field == mdl.p_key() {
+                            &*pk
+                        } else {
+                            prior_field(&prior, field).unwrap_or(&null)
+                        };
+                        budget.charge_cell(dc)?;
+                        encode_cell(&mut data, dc);
+                    }
+                }
+                (Some((row, r)), _) => {
+                    mdl.hot_keys().record(row.d_key());
+                    let pk = VirtualDatacell::new_pk(row.d_key(), mdl.p_tag());
+                    for field in fields.iter() {
+                        let dc = if *field == mdl.p_key() {
+                            &*pk
+                        } else {
+                            r.fields().st_get(*field).unwrap_or(&null)
+                        };
+                        budget.charge_cell(dc)?;
+                        encode_cell(&mut data, dc);
+                    }
+                }
+                (None, None) => {
+                    budget.charge(fields.len() * mem::size_of::<Datacell>())?;
+                    fields.iter().for_each(|_| encode_cell(&mut data, &null))
+                }
+            }
+            rows += 1;
+        }
+        Ok(Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: rows,
+            data,
+        })
+    })
+}
+
+/// Look up each key in its model (in the order given), encoding one row per lookup: the status (`0` if the row was
+/// found, the error code otherwise) followed by all the fields of the row if it was found. A failed lookup doesn't
+/// fail the other lookups.
+///
+/// The lookups can span models in different spaces. All the rows are found first and then locked together (see
+/// [`OrderedRowLocks`]), so the rows are read as of the same point in time. If the rows can't be locked in time, every
+/// lookup that found a row fails
+pub fn fetch(global: &impl GlobalInstanceLike, items: &[(EntityIDRef, Datacell)]) -> Vec<u8> {
+    let mdl_idx = global.state().namespace().idx_models().read();
+    let g = sync::atm::cpin();
+    let found: Vec<QueryResult<(&ModelData, &Row)>> = items
+        .iter()
+        .map(|(entity, key)| {
+            let Some(model) = mdl_idx.get(entity) else {
+                return Err(QueryError::QExecObjectNotFound.with_detail("entity", entity));
+            };
+            let mdl = model.data();
+            let key = match PrimaryIndexKey::try_clone_from_dc(key) {
+                Some(key) if key.tag() == mdl.p_tag().tag_unique() => key,
+                _ => return compiler::cold_rerr(QueryError::QExecDmlWhereHasUnindexedColumn),
+            };
+            match mdl.primary_index().select_key(&key, &g) {
+                Some(row) => {
+                    mdl.hot_keys().record(row.d_key());
+                    Ok((mdl, row))
+                }
+                None => Err(QueryError::QExecDmlRowNotFound),
+            }
+        })
+        .collect();
+    let locks = OrderedRowLocks::read(
+        items
+            .iter()
+            .zip(found.iter())
+            .filter_map(|((entity, _), found)| {
+                let (mdl, row) = found.as_ref().ok()?;
+                Some((*entity, *mdl, *row))
+            })
+            .collect(),
+    );
+    let mut budget = QueryMemBudget::new(global);
+    let mut data = Vec::new();
+    let mut row = Vec::new();
+    for ((entity, _), found) in items.iter().zip(found) {
+        row.clear();
+        let r = found.and_then(|(mdl, found)| {
+            let locks = locks.as_ref().map_err(|e| *e)?;
+            fetch_row(
+                mdl,
+                found,
+                locks.get(*entity, found.d_key()),
+                &mut budget,
+                &mut row,
+            )
+        });
+        let (status, fields, row) = match r {
+            Ok(fields) => (0, fields, &row[..]),
+            // discard a partially encoded row
+            Err(e) => (e.value_u8(), 0, &row[..0]),
+        };
+        IntegerRepr::scoped(fields as u64 + 1, |repr| data.extend(repr));
+        data.push(b'\n');
+        encode_cell(&mut data, &Datacell::new_uint_default(status as u64));
+        data.extend_from_slice(row);
+    }
+    data
+}
+
+/// Encode all the fields of the (locked) row, returning the number of fields
+fn fetch_row(
+    mdl: &ModelData,
+    row: &Row,
+    r: &RowData,
+    budget: &mut QueryMemBudget,
+    data: &mut Vec<u8>,
+) -> QueryResult<usize> {
+    if r.is_tombstoned() {
+        return Err(QueryError::QExecDmlRowNotFound);
+    }
+    let pk = VirtualDatacell::new_pk(row.d_key(), mdl.p_tag());
+    let null = Datacell::null();
+    let mut fields = 0;
+    for field in mdl.fields().stseq_ord_key() {
+        let dc = if field.as_str() == mdl.p_key() {
+            &*pk
+        } else {
+            r.fields().st_get(field.as_str()).unwrap_or(&null)
+        };
+        budget.charge_cell(dc)?;
+        encode_cell(data, dc);
+        fields += 1;
+    }
+    Ok(fields)
+}
+
 pub fn select_all_resp(
     global: &impl GlobalInstanceLike,
     select: SelectAllStatement,
 ) -> QueryResult<Response> {
+    if select.arrow {
+        return super::arrow::select_all_arrow_resp(global, select);
+    }
     let mut ret_buf = Vec::new();
-    let i = self::select_all(
+    let (i, total) = self::select_all_with_total(
         global,
         select,
         &mut ret_buf,
@@ -76,30 +324,204 @@ pub fn select_all_resp(
         },
         |buf, data, _| encode_cell(buf, data),
     )?;
-    Ok(Response::Serialized {
-        ty: ResponseType::MultiRow,
-        size: i,
-        data: ret_buf,
-    })
+    match total {
+        Some(total) => Ok(total.into_response(i, ret_buf)),
+        None => Ok(Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: i,
+            data: ret_buf,
+        }),
+    }
 }
 
+/// The most rows that are visited to count the rows matching a select `with total` (unless the page ends further in)
+const TOTAL_SCAN_MAX: usize = 10_000;
+
+/// The number of rows that match a select `with total`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Total {
+    rows: u64,
+    /// unset if the scan stopped at [`TOTAL_SCAN_MAX`] rows (see [`Total::count`])
+    exact: bool,
+}
+
+impl Total {
+    fn new(rows: u64, exact: bool) -> Self {
+        Self { rows, exact }
+    }
+    #[cfg(test)]
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+    #[cfg(test)]
+    pub fn exact(&self) -> bool {
+        self.exact
+    }
+    /// Count the rows that the scan for a select visits, stopping at [`TOTAL_SCAN_MAX`] rows. If it stops, the total
+    /// is estimated from the histogram of the field that the scan is restricted on (if the model keeps histograms and
+    /// no other clause has to be checked); otherwise it is the number of rows that were counted, which is a lower bound
+    fn count(
+        mdl: &ModelData,
+        select: &SelectAllStatement,
+        target: ScanTarget,
+        filter: &RowFilter,
+    ) -> QueryResult<Self> {
+        let max = TOTAL_SCAN_MAX.max(select.offset.saturating_add(select.limit) as usize);
+        let estimate = match filter.is_empty() & !mdl.props().soft_delete() {
+            true => target.selectivity(mdl),
+            false => None,
+        };
+        let g = sync::atm::cpin();
+        let rows = RowIteratorAll::new_filtered(&g, mdl, max, target, filter)?
+            .with_deleted(select.with_deleted)
+            .count();
+        if rows < max {
+            return Ok(Self::new(rows as u64, true));
+        }
+        let estimate = estimate.map_or(0, |selectivity| {
+            (mdl.primary_index().count() as f64 * selectivity) as u64
+        });
+        Ok(Self::new(estimate.max(rows as u64), false))
+    }
+    /// Returns the response for a page of `page_rows` rows (encoded like for a multirow response) along with the
+    /// total: a batch holding the page, followed by a row with the total and whether it is exact
+    ///
+    /// ```text
+    /// 0x15 2\n 0x13 <page rows>\n<page> 0x11 2\n<total: uint64><exact: bool>
+    /// ```
+    fn into_response(self, page_rows: usize, page: Vec<u8>) -> Response {
+        let mut data = vec![ResponseType::MultiRow.value_u8()];
+        IntegerRepr::scoped(page_rows as u64, |repr| data.extend(repr));
+        data.push(b'\n');
+        data.extend(page);
+        data.extend([ResponseType::Row.value_u8(), b'2', b'\n']);
+        encode_cell(&mut data, &Datacell::new_uint_default(self.rows));
+        encode_cell(&mut data, &Datacell::new_bool(self.exact));
+        Response::Serialized {
+            ty: ResponseType::Batch,
+            size: 2,
+            data,
+        }
+    }
+}
+
+/// Select all the rows matching the where clause (skipping `offset` rows and returning at most `limit` rows).
+///
+/// Rows are always returned in a stable order, so that paginating with `LIMIT` and `OFFSET` neither skips nor repeats
+/// rows as long as no rows are inserted or deleted in between:
+/// - with an ordered (btree) primary index, rows are returned in primary key order
+/// - with a hash primary index, rows are returned in hash order. This order is arbitrary, but it only depends on the
+/// keys present and is fixed for the lifetime of the process (the hasher is seeded on startup)
+/// - rows found using a secondary index are returned in index order: by the fields that weren't looked up (as compared
+/// by the index's collation), and then in primary key order (while the index is being built, the order is that of the
+/// primary index)
+///
+/// With `sample`, all matching rows are scanned and a random sample of them is returned (in the same order)
+///
+/// Without a where clause, the rows of an analytic model with a hash primary index are materialized from its columns
+/// (see [`crate::engine::core::model::columnar`]) and returned in slot order. Like hash order, this order only changes
+/// when rows are inserted or deleted
+///
+/// Since this is a scan, it is rejected while the server is under memory pressure
 pub fn select_all<Fm, F, T>(
     global: &impl GlobalInstanceLike,
     select: SelectAllStatement,
     serialize_target: &mut T,
+    f_mdl: Fm,
+    f: F,
+) -> QueryResult<usize>
+where
+    Fm: FnMut(&mut T, &ModelData, usize),
+    F: FnMut(&mut T, &Datacell, usize),
+{
+    self::select_all_with_total(global, select, serialize_target, f_mdl, f).map(|(i, _)| i)
+}
+
+/// Select all the rows matching the where clause like [`select_all`], also returning the total number of matching rows
+/// if the select is `with total`.
+///
+/// Without a where clause, the total is the number of rows in the primary index (unless soft deleted rows have to be
+/// left out). Otherwise, the matching rows are counted with a separate scan that is bounded (see [`Total::count`])
+pub fn select_all_with_total<Fm, F, T>(
+    global: &impl GlobalInstanceLike,
+    mut select: SelectAllStatement,
+    serialize_target: &mut T,
     mut f_mdl: Fm,
     mut f: F,
-) -> QueryResult<usize>
+) -> QueryResult<(usize, Option<Total>)>
 where
     Fm: FnMut(&mut T, &ModelData, usize),
     F: FnMut(&mut T, &Datacell, usize),
 {
-    global.state().namespace().with_model(select.entity, |mdl| {
+    global.admit_expensive_query()?;
+    global.state().with_model(select.entity, |mdl| {
+        let mut budget = QueryMemBudget::new(global);
+        let unfiltered = select.clauses_mut().clauses_mut().is_empty();
+        // every row in the primary index matches
+        let all_rows = unfiltered & (!mdl.props().soft_delete() | select.with_deleted);
+        let mut total = (select.with_total & all_rows)
+            .then(|| Total::new(mdl.primary_index().count() as u64, true));
+        if let Some(columns) = mdl.columns() {
+            if (mdl.props().index() == PrimaryIndexKind::Hash)
+                & select.projection.is_none()
+                & select.sample.is_none()
+                & !select.with_deleted
+                & unfiltered
+                & (total.is_some() | !select.with_total)
+            {
+                return select_all_columnar(
+                    mdl,
+                    columns,
+                    &select,
+                    &mut budget,
+                    serialize_target,
+                    &mut f_mdl,
+                    &mut f,
+                )
+                .map(|i| (i, total));
+            }
+        }
+        let (target, filter) = mdl.resolve_where_scan(select.clauses_mut())?;
+        if select.with_total & total.is_none() {
+            total = Some(Total::count(mdl, &select, target.clone(), &filter)?);
+        }
+        if let Some(lookup) = IndexOnlyLookup::plan(mdl, &select, &target, &filter) {
+            return lookup
+                .select_all(
+                    mdl,
+                    &mut select,
+                    &mut budget,
+                    serialize_target,
+                    &mut f_mdl,
+                    &mut f,
+                )
+                .map(|i| (i, total));
+        }
         let g = sync::atm::cpin();
         let mut i = 0;
-        if select.wildcard {
+        if let Some(projection) = select.projection.take() {
+            check_projection(mdl, &projection)?;
+            f_mdl(serialize_target, mdl, projection.len());
+            for row in scan_rows(&g, mdl, &select, target, &filter)? {
+                let (key, data) = row?;
+                let vdc = VirtualDatacell::new_pk(key, mdl.p_tag());
+                for column in projection.iter() {
+                    let r = expr::eval(column, |field| {
+                        if field == mdl.p_key() {
+                            Some(&*vdc)
+                        } else {
+                            data.fields().st_get(field)
+                        }
+                    })?;
+                    budget.charge_cell(&r)?;
+                    f(serialize_target, &r, projection.len());
+                }
+                i += 1;
+            }
+        } else if select.wildcard {
             f_mdl(serialize_target, mdl, mdl.fields().len());
-            for (key, data) in RowIteratorAll::new(&g, mdl, select.limit as usize) {
+            for row in scan_rows(&g, mdl, &select, target, &filter)? {
+                let (key, data) = row?;
                 let vdc = VirtualDatacell::new_pk(key, mdl.p_tag());
                 for key in mdl.fields().stseq_ord_key() {
                     let r = if key.as_str() == mdl.p_key() {
@@ -107,22 +529,16 @@ where
                     } else {
                         data.fields().get(key).unwrap()
                     };
+                    budget.charge_cell(r)?;
                     f(serialize_target, r, mdl.fields().len());
                 }
                 i += 1;
             }
         } else {
-            // schema check
-            if select.fields.len() > mdl.fields().len()
-                || select
-                    .fields
-                    .iter()
-                    .any(|f| !mdl.fields().st_contains(f.as_str()))
-            {
-                return Err(QueryError::QExecUnknownField);
-            }
+            check_fields(mdl, &select.fields)?;
             f_mdl(serialize_target, mdl, select.fields.len());
-            for (key, data) in RowIteratorAll::new(&g, mdl, select.limit as usize) {
+            for row in scan_rows(&g, mdl, &select, target, &filter)? {
+                let (key, data) = row?;
                 let vdc = VirtualDatacell::new_pk(key, mdl.p_tag());
                 for key in select.fields.iter() {
                     let r = if key.as_str() == mdl.p_key() {
@@ -130,16 +546,402 @@ where
                     } else {
                         data.fields().st_get(key.as_str()).unwrap()
                     };
+                    budget.charge_cell(r)?;
                     f(serialize_target, r, select.fields.len());
                 }
                 i += 1;
             }
         }
+        Ok((i, total))
+    })
+}
+
+/// A lookup on a covering secondary index that can be answered from the index alone (an index-only read), without
+/// visiting the rows
+struct IndexOnlyLookup<'i, 'a> {
+    index: &'i SecondaryIndex,
+    values: &'i [PrimaryIndexKey],
+    filter: &'i RowFilter<'a>,
+}
+
+impl<'i, 'a> IndexOnlyLookup<'i, 'a> {
+    /// Returns an index-only read for the scan if the index is ready and stores every field that the select reads
+    /// (including the fields of the remaining filter). Soft deleted rows stay in the index, so selects on a model with
+    /// soft deletes always read the rows (as do samples, which need to see every matching row). An index with a
+    /// collation other than `binary` can hold several spellings of a value under one key, so it always reads the rows
+    /// as well
+    fn plan(
+        mdl: &ModelData,
+        select: &SelectAllStatement,
+        target: &'i ScanTarget,
+        filter: &'i RowFilter<'a>,
+    ) -> Option<Self> {
+        let ScanTarget::Secondary { index, values, .. } = target else {
+            return None;
+        };
+        let covers = |field: &str| index.covers(mdl, field, values.len());
+        let covers_projection = if select.wildcard {
+            mdl.fields()
+                .stseq_ord_key()
+                .all(|field| covers(field.as_str()))
+        } else {
+            select.fields.iter().all(|field| covers(field.as_str()))
+        };
+        let index_only = index.is_ready()
+            & (index.collation() == Collation::Binary)
+            & !mdl.props().soft_delete()
+            & select.sample.is_none()
+            & covers_projection
+            & filter.fields().all(covers);
+        index_only.then_some(Self {
+            index,
+            values,
+            filter,
+        })
+    }
+    /// Select all the rows where the first indexed fields have the given values, reading the primary key, the looked
+    /// up fields and the included fields from the index. Like any other index lookup, rows are returned in index order
+    ///
+    /// NOTE(@ohsayan): the primary index is only probed to skip stale index entries (see [`SecondaryIndex::lookup`]);
+    /// no row is ever locked or read
+    fn select_all<Fm, F, T>(
+        self,
+        mdl: &ModelData,
+        select: &mut SelectAllStatement,
+        budget: &mut QueryMemBudget,
+        serialize_target: &mut T,
+        f_mdl: &mut Fm,
+        f: &mut F,
+    ) -> QueryResult<usize>
+    where
+        Fm: FnMut(&mut T, &ModelData, usize),
+        F: FnMut(&mut T, &Datacell, usize),
+    {
+        let projection = select.projection.take();
+        let fields: Vec<&str> = if select.wildcard {
+            mdl.fields()
+                .stseq_ord_key()
+                .map(|field| field.as_str())
+                .collect()
+        } else {
+            select.fields.iter().map(|field| field.as_str()).collect()
+        };
+        let columns = match projection.as_ref() {
+            Some(projection) => {
+                check_projection(mdl, projection)?;
+                projection.len()
+            }
+            None => {
+                check_fields(mdl, &select.fields)?;
+                fields.len()
+            }
+        };
+        f_mdl(serialize_target, mdl, columns);
+        let g = sync::atm::cpin();
+        let looked_up = &self.index.fields()[..self.values.len()];
+        let indexed: Vec<_> = looked_up
+            .iter()
+            .zip(self.values)
+            .map(|(field, value)| {
+                let tag = mdl.fields().st_get(field.as_ref()).unwrap().layers()[0].tag();
+                VirtualDatacell::new_pk(value, tag)
+            })
+            .collect();
+        let (mut skip, mut i) = (select.offset as usize, 0);
+        for (key, included) in self.index.lookup_covered(self.values) {
+            if i == select.limit as usize {
+                break;
+            }
+            if mdl.primary_index().select_key(&key, &g).is_none() {
+                // stale index entry
+                continue;
+            }
+            let pk = VirtualDatacell::new_pk(&key, mdl.p_tag());
+            let field = |field: &str| {
+                if field == mdl.p_key() {
+                    Some(&*pk)
+                } else if let Some(pos) = looked_up.iter().position(|f| f.as_ref() == field) {
+                    Some(&*indexed[pos])
+                } else {
+                    self.index
+                        .included_position(field)
+                        .map(|pos| &included[pos])
+                }
+            };
+            if !self.filter.matches_with(&field) {
+                continue;
+            }
+            if skip != 0 {
+                skip -= 1;
+                continue;
+            }
+            match projection.as_ref() {
+                Some(projection) => {
+                    for column in projection.iter() {
+                        let r = expr::eval(column, &field)?;
+                        budget.charge_cell(&r)?;
+                        f(serialize_target, &r, columns);
+                    }
+                }
+                None => {
+                    for name in fields.iter() {
+                        let r = field(name).unwrap();
+                        budget.charge_cell(r)?;
+                        f(serialize_target, r, columns);
+                    }
+                }
+            }
+            i += 1;
+        }
+        Ok(i)
+    }
+}
+
+/// Select all the live rows of an analytic model, materializing every returned row from the columns of the selected
+/// fields
+fn select_all_columnar<Fm, F, T>(
+    mdl: &ModelData,
+    columns: &ColumnStore,
+    select: &SelectAllStatement,
+    budget: &mut QueryMemBudget,
+    serialize_target: &mut T,
+    f_mdl: &mut Fm,
+    f: &mut F,
+) -> QueryResult<usize>
+where
+    Fm: FnMut(&mut T, &ModelData, usize),
+    F: FnMut(&mut T, &Datacell, usize),
+{
+    let fields: Vec<&str> = if select.wildcard {
+        mdl.fields()
+            .stseq_ord_key()
+            .map(|field| field.as_str())
+            .collect()
+    } else {
+        check_fields(mdl, &select.fields)?;
+        select.fields.iter().map(|field| field.as_str()).collect()
+    };
+    f_mdl(serialize_target, mdl, fields.len());
+    columns.with_columns(mdl, |columns| {
+        let mut i = 0;
+        for (slot, key) in columns
+            .rows()
+            .skip(select.offset as usize)
+            .take(select.limit as usize)
+        {
+            let pk = VirtualDatacell::new_pk(key, mdl.p_tag());
+            for field in fields.iter() {
+                let r = if *field == mdl.p_key() {
+                    &*pk
+                } else {
+                    columns.cell(field, slot).unwrap()
+                };
+                budget.charge_cell(r)?;
+                f(serialize_target, r, fields.len());
+            }
+            i += 1;
+        }
         Ok(i)
     })
 }
 
-fn encode_cell(resp: &mut Vec<u8>, item: &Datacell) {
+/// Check that all the selected fields exist
+fn check_fields(mdl: &ModelData, fields: &[Ident]) -> QueryResult<()> {
+    if let Some(f) = fields
+        .iter()
+        .find(|f| !mdl.fields().st_contains(f.as_str()))
+    {
+        return Err(QueryError::QExecUnknownField.with_detail("field", f.as_str()));
+    }
+    if fields.len() > mdl.fields().len() {
+        return Err(QueryError::QExecUnknownField);
+    }
+    Ok(())
+}
+
+/// How a select is executed against the primary index
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScanPlan {
+    /// a single key is looked up
+    PointLookup,
+    /// each key in a list is looked up
+    MultiPointLookup,
+    /// all rows are visited
+    FullScan,
+    /// only the rows in the key range are visited (ordered index)
+    RangeScan,
+    /// all rows are visited and filtered against the key range (unordered index)
+    FilteredFullScan,
+    /// the matching rows are looked up using a secondary index
+    IndexLookup,
+    /// the matching rows are read from a covering secondary index, without visiting the rows
+    IndexOnlyLookup,
+    /// all rows are visited and filtered since the secondary index is still being built
+    IndexPendingScan,
+}
+
+impl ScanPlan {
+    fn for_target(mdl: &ModelData, target: &ScanTarget) -> Self {
+        match target {
+            ScanTarget::Range(Bound::Unbounded, Bound::Unbounded) => Self::FullScan,
+            ScanTarget::Range(_, _) if mdl.props().index() == PrimaryIndexKind::BTree => {
+                Self::RangeScan
+            }
+            ScanTarget::Range(_, _) => Self::FilteredFullScan,
+            ScanTarget::Secondary { index, .. } if index.is_ready() => Self::IndexLookup,
+            ScanTarget::Secondary { .. } => Self::IndexPendingScan,
+        }
+    }
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::PointLookup => "point_lookup",
+            Self::MultiPointLookup => "multi_point_lookup",
+            Self::FullScan => "full_scan",
+            Self::RangeScan => "range_scan",
+            Self::FilteredFullScan | Self::IndexPendingScan => "filtered_full_scan",
+            Self::IndexLookup => "index_lookup",
+            Self::IndexOnlyLookup => "index_only_lookup",
+        }
+    }
+    fn warning(&self) -> Option<&'static str> {
+        match self {
+            Self::FilteredFullScan => Some(
+                "no ordered index on the primary key; the range filter falls back to a full scan",
+            ),
+            Self::IndexPendingScan => Some(
+                "the secondary index is still being built; the filter falls back to a full scan",
+            ),
+            _ => None,
+        }
+    }
+    /// Describe the plan. The estimate (the share of rows and the number of rows visited) is only included if the
+    /// model keeps histograms, and `partial` is only included if the index is a partial index
+    fn describe(
+        &self,
+        index: &str,
+        prefix: Option<usize>,
+        partial: bool,
+        estimate: Option<(f64, usize)>,
+    ) -> Response {
+        let mut ret = format!("{{\"plan\":\"{}\",\"index\":\"{}\",", self.name(), index);
+        if let Some(prefix) = prefix {
+            ret.push_str(&format!("\"prefix\":{prefix},"));
+        }
+        if partial {
+            ret.push_str("\"partial\":true,");
+        }
+        if let Some((selectivity, rows)) = estimate {
+            ret.push_str(&format!(
+                "\"estimate\":{{\"selectivity\":{selectivity},\"rows\":{rows}}},"
+            ));
+        }
+        ret.push_str("\"warnings\":[");
+        if let Some(warning) = self.warning() {
+            ret.push('"');
+            ret.push_str(warning);
+            ret.push('"');
+        }
+        ret.push_str("]}");
+        Response::Serialized {
+            ty: ResponseType::String,
+            size: ret.len(),
+            data: ret.into_bytes(),
+        }
+    }
+}
+
+pub fn exists_resp(
+    global: &impl GlobalInstanceLike,
+    exists: ExistsStatement,
+) -> QueryResult<Response> {
+    self::exists(global, exists).map(Response::Bool)
+}
+
+/// Returns true if any (not soft deleted) row matches the where clause. A lookup by primary key is a point lookup;
+/// anything else is a scan that stops at the first matching row, so no row is ever materialized.
+///
+/// Since a scan might still visit many rows before finding a match, it is rejected while the server is under memory
+/// pressure
+pub fn exists(global: &impl GlobalInstanceLike, mut exists: ExistsStatement) -> QueryResult<bool> {
+    global.state().with_model(exists.entity(), |mdl| {
+        let g = sync::atm::cpin();
+        let clauses = exists.clauses_mut().clauses_mut();
+        let is_point = (clauses.len() == 1)
+            & clauses
+                .get(mdl.p_key().as_bytes())
+                .map_or(false, |clause| clause.filter_hint_none());
+        if is_point {
+            let key = mdl.resolve_where(exists.clauses_mut())?;
+            return match mdl.primary_index().select(key, &g) {
+                Some(row) => {
+                    mdl.hot_keys().record(row.d_key());
+                    Ok(!lock::read(mdl, row)?.is_tombstoned())
+                }
+                None => Ok(false),
+            };
+        }
+        global.admit_expensive_query()?;
+        let (target, filter) = mdl.resolve_where_filtered(exists.clauses_mut())?;
+        let found = RowIteratorAll::new_filtered(&g, mdl, 1, target, &filter)?
+            .next()
+            .is_some();
+        Ok(found)
+    })
+}
+
+pub fn explain_select_resp(
+    global: &impl GlobalInstanceLike,
+    mut select: SelectStatement,
+) -> QueryResult<Response> {
+    global.state().with_model(select.entity(), |mdl| {
+        let plan = match select.take_keys() {
+            Some(keys) => {
+                mdl.validate_select_keys(&keys)?;
+                ScanPlan::MultiPointLookup
+            }
+            None => {
+                mdl.resolve_where(select.clauses_mut())?;
+                ScanPlan::PointLookup
+            }
+        };
+        Ok(plan.describe(mdl.props().index().name(), None, false, None))
+    })
+}
+
+pub fn explain_select_all_resp(
+    global: &impl GlobalInstanceLike,
+    mut select: SelectAllStatement,
+) -> QueryResult<Response> {
+    global.state().with_model(select.entity, |mdl| {
+        let (target, filter) = mdl.resolve_where_scan(select.clauses_mut())?;
+        let plan = match IndexOnlyLookup::plan(mdl, &select, &target, &filter) {
+            Some(_) => ScanPlan::IndexOnlyLookup,
+            None => ScanPlan::for_target(mdl, &target),
+        };
+        let estimate = target.selectivity(mdl).map(|selectivity| {
+            let rows = mdl.primary_index().count() as f64 * selectivity;
+            (selectivity, rows.round() as usize)
+        });
+        Ok(match target {
+            ScanTarget::Secondary {
+                name,
+                index,
+                values,
+            } => plan.describe(
+                name,
+                // the number of fields of a composite index that the lookup uses
+                (index.fields().len() > 1).then_some(values.len()),
+                index.filter().is_some(),
+                estimate,
+            ),
+            ScanTarget::Range(_, _) => {
+                plan.describe(mdl.props().index().name(), None, false, estimate)
+            }
+        })
+    })
+}
+
+pub fn encode_cell(resp: &mut Vec<u8>, item: &Datacell) {
     resp.push((item.tag().tag_selector().value_u8() + 1) * (item.is_init() as u8));
     if item.is_null() {
         return;
@@ -152,6 +954,7 @@ fn encode_cell(resp: &mut Vec<u8>, item: &Datacell) {
             TagClass::SignedInt => IntegerRepr::scoped(item.read_sint(), |b| resp.extend(b)),
             TagClass::Float => resp.extend(item.read_float().to_string().as_bytes()),
             TagClass::Bin | TagClass::Str => {
+                // a tiered blob is written as its reference, and swapped for the blob by `resolve_tiered`
                 let slc = item.read_bin();
                 IntegerRepr::scoped(slc.len() as u64, |b| resp.extend(b));
                 resp.push(b'\n');
@@ -173,6 +976,59 @@ fn encode_cell(resp: &mut Vec<u8>, item: &Datacell) {
     resp.push(b'\n');
 }
 
+/// Returns where the references to tiered blobs (see [`tier`]) that [`encode_cell`] wrote to a response are, as the
+/// range of each one's length and reference
+fn tiered_refs(data: &[u8]) -> Vec<(usize, usize)> {
+    let Some(tier) = tier::get() else {
+        return vec![];
+    };
+    let mut header = vec![TagSelector::Binary.value_u8() + 1];
+    IntegerRepr::scoped(tier::REF_LEN as u64, |b| header.extend(b));
+    header.push(b'\n');
+    let mut refs = vec![];
+    let mut i = 0;
+    while i + header.len() + tier::REF_LEN <= data.len() {
+        let end = i + header.len() + tier::REF_LEN;
+        if data[i..].starts_with(&header) && tier.is_ref(&data[i + header.len()..end]) {
+            refs.push((i + 1, end));
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    refs
+}
+
+/// Returns true if the response has references to tiered blobs that have to be resolved with [`resolve_tiered`]
+pub fn has_tiered(data: &[u8]) -> bool {
+    !tiered_refs(data).is_empty()
+}
+
+/// Swap the references to tiered blobs in an encoded response for the blobs. Blobs that aren't cached are fetched
+/// from the bucket, so this has to run on a blocking task once the statement has released its locks. Fails if a blob
+/// can't be fetched
+pub fn resolve_tiered(data: Vec<u8>) -> QueryResult<Vec<u8>> {
+    let refs = tiered_refs(&data);
+    if refs.is_empty() {
+        return Ok(data);
+    }
+    let mut resolved = Vec::with_capacity(data.len());
+    let mut last = 0;
+    for (start, end) in refs {
+        let blob = match tier::resolve(&data[end - tier::REF_LEN..end]) {
+            Some(Ok(blob)) => blob,
+            _ => return Err(QueryError::SysBlobUnavailable),
+        };
+        resolved.extend_from_slice(&data[last..start]);
+        IntegerRepr::scoped(blob.len() as u64, |b| resolved.extend(b));
+        resolved.push(b'\n');
+        resolved.extend_from_slice(&blob);
+        last = end;
+    }
+    resolved.extend_from_slice(&data[last..]);
+    Ok(resolved)
+}
+
 pub fn select_custom<F>(
     global: &impl GlobalInstanceLike,
     mut select: SelectStatement,
@@ -181,84 +1037,367 @@ pub fn select_custom<F>(
 where
     F: FnMut(&Datacell),
 {
-    global
-        .state()
-        .namespace()
-        .with_model(select.entity(), |mdl| {
-            let target_key = mdl.resolve_where(select.clauses_mut())?;
-            let pkdc = VirtualDatacell::new(target_key.clone(), mdl.p_tag().tag_unique());
-            let g = sync::atm::cpin();
-            let mut read_field = |key, fields: &DcFieldIndex| {
-                match fields.st_get(key) {
-                    Some(dc) => cellfn(dc),
-                    None if key == mdl.p_key() => cellfn(&pkdc),
-                    None => return Err(QueryError::QExecUnknownField),
+    global.state().with_model(select.entity(), |mdl| {
+        let target_key = mdl.resolve_where(select.clauses_mut())?;
+        let projection = select.take_projection();
+        if let Some(projection) = projection.as_ref() {
+            check_projection(mdl, projection)?;
+        }
+        let pkdc = VirtualDatacell::new(target_key.clone(), mdl.p_tag().tag_unique());
+        let g = sync::atm::cpin();
+        let null = Datacell::null();
+        let as_of = select.as_of();
+        let mut read_field = |key: &str, dc: Option<&Datacell>| {
+            match dc {
+                Some(dc) => cellfn(dc),
+                None if key == mdl.p_key() => cellfn(&pkdc),
+                // a prior version doesn't have the fields that were added since
+                None if as_of.is_some() && mdl.fields().st_contains(key) => cellfn(&null),
+                None => return Err(QueryError::QExecUnknownField),
+            }
+            Ok(())
+        };
+        let version = match as_of {
+            Some(at) => version_as_of(Some((history_as_of(mdl, at)?, at)), target_key.clone()),
+            None => HistoricRow::Current,
+        };
+        let with_deleted = select.with_deleted();
+        match version {
+            HistoricRow::Current => {
+                let Some(row) = mdl.primary_index().select(target_key.clone(), &g) else {
+                    return Err(QueryError::QExecDmlRowNotFound);
+                };
+                let r = lock::read(mdl, row)?;
+                if r.is_tombstoned() & !with_deleted {
+                    return Err(QueryError::QExecDmlRowNotFound);
                 }
-                Ok(())
-            };
-            match mdl.primary_index().select(target_key.clone(), &g) {
-                Some(row) => {
-                    let r = row.resolve_schema_deltas_and_freeze(mdl.delta_state());
-                    if select.is_wildcard() {
-                        for key in mdl.fields().stseq_ord_key() {
-                            read_field(key.as_ref(), r.fields())?;
-                        }
-                    } else {
-                        for key in select.into_fields() {
-                            read_field(key.as_str(), r.fields())?;
-                        }
+                mdl.hot_keys().record(row.d_key());
+                if let Some(projection) = projection.as_ref() {
+                    for column in projection {
+                        cellfn(&expr::eval(column, |field| {
+                            if field == mdl.p_key() {
+                                Some(&*pkdc)
+                            } else {
+                                r.fields().st_get(field)
+                            }
+                        })?);
+                    }
+                } else if select.is_wildcard() {
+                    for key in mdl.fields().stseq_ord_key() {
+                        read_field(key.as_ref(), r.fields().st_get(key.as_str()))?;
+                    }
+                } else {
+                    for key in select.into_fields() {
+                        read_field(key.as_str(), r.fields().st_get(key.as_str()))?;
                     }
                 }
-                None => return Err(QueryError::QExecDmlRowNotFound),
             }
-            Ok(())
-        })
+            HistoricRow::Version(prior) => {
+                if let Some(projection) = projection.as_ref() {
+                    for column in projection {
+                        cellfn(&expr::eval(column, |field| {
+                            if field == mdl.p_key() {
+                                Some(&*pkdc)
+                            } else {
+                                prior_field(&prior, field)
+                            }
+                        })?);
+                    }
+                } else if select.is_wildcard() {
+                    for key in mdl.fields().stseq_ord_key() {
+                        read_field(key.as_ref(), prior_field(&prior, key.as_ref()))?;
+                    }
+                } else {
+                    for key in select.into_fields() {
+                        read_field(key.as_str(), prior_field(&prior, key.as_str()))?;
+                    }
+                }
+            }
+            HistoricRow::Missing => return Err(QueryError::QExecDmlRowNotFound),
+        }
+        Ok(())
+    })
+}
+
+/// Type check the functions of a projection
+fn check_projection(mdl: &ModelData, projection: &[RowExpr]) -> QueryResult<()> {
+    for column in projection {
+        expr::check(column, mdl)?;
+    }
+    Ok(())
 }
 
-struct RowIteratorAll<'g> {
-    _g: &'g sync::atm::Guard,
+/// Returns the model's history if the given time is within its retention window
+fn history_as_of(mdl: &ModelData, at: u64) -> QueryResult<&RowHistory> {
+    match mdl.history() {
+        Some(history)
+            if at.saturating_add(mdl.props().history_retention()) >= os::get_epoch_time_secs() =>
+        {
+            Ok(history)
+        }
+        _ => Err(QueryError::QExecDmlHistoryUnavailable),
+    }
+}
+
+/// Returns the version of the row with the given key that was current at the given time (or the current version if
+/// no time was given)
+fn version_as_of(history: Option<(&RowHistory, u64)>, key: Lit) -> HistoricRow {
+    match history {
+        Some((history, at)) => PrimaryIndexKeyProbe::new(key)
+            .map_or(HistoricRow::Missing, |key| history.version_at(&key, at)),
+        None => HistoricRow::Current,
+    }
+}
+
+fn prior_field<'a>(prior: &'a [(Box<str>, Datacell)], field: &str) -> Option<&'a Datacell> {
+    prior
+        .iter()
+        .find(|(name, _)| name.as_ref() == field)
+        .map(|(_, dc)| dc)
+}
+
+/// Returns the rows matching a `select all`, or a sample of them
+fn scan_rows<'a: 'g, 'g>(
+    g: &'g sync::atm::Guard,
     mdl: &'g ModelData,
-    iter: <IndexMTRaw<Row> as MTIndexExt<Row, PrimaryIndexKey, RowDataLck>>::IterEntry<'g, 'g, 'g>,
+    select: &SelectAllStatement,
+    target: ScanTarget<'a, 'g>,
+    filter: &'g RowFilter<'a>,
+) -> QueryResult<Box<dyn Iterator<Item = RowItem<'g>> + 'g>> {
+    // a sample is picked from all matching rows
+    let scan_limit = match select.sample {
+        Some(_) => usize::MAX,
+        None => select.limit as usize,
+    };
+    let rows = RowIteratorAll::new_filtered(g, mdl, scan_limit, target, filter)?
+        .skip_rows(select.offset as usize)
+        .with_deleted(select.with_deleted);
+    Ok(match select.sample {
+        Some(sample) => Box::new(
+            sample_rows(rows, sample, select.limit as usize)?
+                .into_iter()
+                .map(Ok),
+        ),
+        None => Box::new(rows),
+    })
+}
+
+/// A small (non-cryptographic) random number generator used to pick samples and generate rows (splitmix64), seeded
+/// from the system's random source unless a seed is given
+pub(super) struct SampleRng(u64);
+
+impl SampleRng {
+    pub(super) fn new() -> Self {
+        Self::with_seed(RandomState::new().build_hasher().finish())
+    }
+    pub(super) fn with_seed(seed: u64) -> Self {
+        Self(seed)
+    }
+    pub(super) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+    /// Returns a random number in `0..n`
+    pub(super) fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+    /// Returns a random number in `[0, 1)`
+    pub(super) fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+type RowRef<'g> = (
+    &'g PrimaryIndexKey,
+    lock::Held<'static, parking_lot::RwLockReadGuard<'g, RowData>>,
+);
+/// A row returned by a scan, which fails if the row couldn't be read (see [`Row::d_data`])
+type RowItem<'g> = QueryResult<RowRef<'g>>;
+
+/// Pick a uniform random sample of the rows (returning at most `limit` rows), in the order they were scanned. A
+/// fixed size sample is picked with reservoir sampling, so only the rows currently in the sample are held
+fn sample_rows<'g>(
+    rows: impl Iterator<Item = RowItem<'g>>,
+    sample: SampleSize,
+    limit: usize,
+) -> QueryResult<Vec<RowRef<'g>>> {
+    let mut rng = SampleRng::new();
+    match sample {
+        SampleSize::Rows(n) => {
+            let n = limit.min(n as usize);
+            if n == 0 {
+                return Ok(vec![]);
+            }
+            let mut reservoir: Vec<(usize, RowRef)> = Vec::with_capacity(n);
+            for (seen, row) in rows.enumerate() {
+                let row = row?;
+                if reservoir.len() < n {
+                    reservoir.push((seen, row));
+                } else {
+                    let j = rng.below(seen as u64 + 1) as usize;
+                    if j < n {
+                        reservoir[j] = (seen, row);
+                    }
+                }
+            }
+            reservoir.sort_by_key(|(seen, _)| *seen);
+            Ok(reservoir.into_iter().map(|(_, row)| row).collect())
+        }
+        SampleSize::Percent(p) => rows
+            .filter(|_| rng.unit() * 100.0 < p)
+            .take(limit)
+            .collect(),
+    }
+}
+
+enum RowSource<'a, 'g> {
+    Scan(PrimaryIndexIter<'a, 'g, 'g, 'g>),
+    Keys(std::vec::IntoIter<PrimaryIndexKey>),
+}
+
+pub(super) struct RowIteratorAll<'a, 'g> {
+    g: &'g sync::atm::Guard,
+    mdl: &'g ModelData,
+    source: RowSource<'a, 'g>,
+    /// rows are only returned if the looked up fields of the index match
+    filter: Option<(&'g SecondaryIndex, Vec<PrimaryIndexKey>)>,
+    /// rows are only returned if they satisfy all remaining clauses
+    row_filter: Option<&'g RowFilter<'a>>,
     _latch: IndexLatchHandleExclusive<'g>,
     limit: usize,
+    /// the number of matching rows to skip before returning rows
+    skip: usize,
+    /// if set, soft deleted rows are returned as well
+    with_deleted: bool,
+    /// the rows read ahead of the scan
+    ahead: ReadAhead<'g>,
 }
 
-impl<'g> RowIteratorAll<'g> {
-    fn new(g: &'g sync::atm::Guard, mdl: &'g ModelData, limit: usize) -> Self {
+impl<'a, 'g> RowIteratorAll<'a, 'g> {
+    pub(super) fn new_filtered(
+        g: &'g sync::atm::Guard,
+        mdl: &'g ModelData,
+        limit: usize,
+        target: ScanTarget<'a, 'g>,
+        row_filter: &'g RowFilter<'a>,
+    ) -> QueryResult<Self> {
+        let mut me = Self::new(g, mdl, limit, target)?;
+        me.row_filter = Some(row_filter);
+        Ok(me)
+    }
+    /// Collect the keys of all remaining rows, accounting for them in the budget. If `returning`, the rows will be
+    /// returned as well so they're accounted for now (since we can't fail once rows have been mutated)
+    pub(super) fn collect_keys(
+        self,
+        budget: &mut QueryMemBudget,
+        returning: bool,
+    ) -> QueryResult<Vec<PrimaryIndexKey>> {
+        let mut keys = Vec::new();
+        for row in self {
+            let (key, data) = row?;
+            if returning {
+                budget.charge_row(key, data.fields())?;
+            }
+            budget.charge_key(key)?;
+            keys.push(key.clone());
+        }
+        Ok(keys)
+    }
+    /// Skip the first `skip` matching rows
+    pub(super) fn skip_rows(mut self, skip: usize) -> Self {
+        self.skip = skip;
+        self
+    }
+    /// Also return soft deleted rows
+    pub(super) fn with_deleted(mut self, with_deleted: bool) -> Self {
+        self.with_deleted = with_deleted;
+        self
+    }
+    fn new(
+        g: &'g sync::atm::Guard,
+        mdl: &'g ModelData,
+        limit: usize,
+        target: ScanTarget<'a, 'g>,
+    ) -> QueryResult<Self> {
         let idx = mdl.primary_index();
         let latch = idx.acquire_exclusive();
-        Self {
-            _g: g,
+        let (source, filter) = match target {
+            ScanTarget::Range(lo, hi) => (
+                RowSource::Scan(
+                    idx.range(lo, hi, g)
+                        .ok_or(QueryError::QExecDmlWhereHasUnindexedColumn)?,
+                ),
+                None,
+            ),
+            ScanTarget::Secondary { index, values, .. } if index.is_ready() => (
+                RowSource::Keys(index.lookup(&values).into_iter()),
+                Some((index, values)),
+            ),
+            ScanTarget::Secondary { index, values, .. } => {
+                (RowSource::Scan(idx.iter(g)), Some((index, values)))
+            }
+        };
+        Ok(Self {
+            g,
             mdl,
-            iter: idx.__raw_index().mt_iter_entry(g),
+            source,
+            filter,
+            row_filter: None,
             _latch: latch,
             limit,
-        }
+            skip: 0,
+            with_deleted: false,
+            ahead: ReadAhead::new(),
+        })
+    }
+    fn next_row(&mut self) -> Option<Option<&'g Row>> {
+        let (g, mdl, source) = (self.g, self.mdl, &mut self.source);
+        self.ahead.next(|| match source {
+            RowSource::Scan(it) => it.next().map(Some),
+            RowSource::Keys(keys) => keys.next().map(|k| mdl.primary_index().select_key(&k, g)),
+        })
     }
-    fn _next(
-        &mut self,
-    ) -> Option<(
-        &'g PrimaryIndexKey,
-        parking_lot::RwLockReadGuard<'g, RowData>,
-    )> {
+    fn _next(&mut self) -> Option<RowItem<'g>> {
         if self.limit == 0 {
             return None;
         }
-        self.limit -= 1;
-        self.iter.next().map(|row| {
-            (
-                row.d_key(),
-                row.resolve_schema_deltas_and_freeze(self.mdl.delta_state()),
-            )
-        })
+        loop {
+            let Some(row) = self.next_row()? else {
+                // stale index entry
+                continue;
+            };
+            let data = match lock::read(self.mdl, row) {
+                Ok(data) => data,
+                Err(e) => return Some(Err(e)),
+            };
+            if data.is_tombstoned() & !self.with_deleted {
+                continue;
+            }
+            if let Some((index, values)) = &self.filter {
+                if !index.row_matches(data.fields(), values) {
+                    continue;
+                }
+            }
+            if let Some(row_filter) = self.row_filter {
+                if !row_filter.matches(self.mdl, row.d_key(), data.fields()) {
+                    continue;
+                }
+            }
+            if self.skip != 0 {
+                self.skip -= 1;
+                continue;
+            }
+            self.limit -= 1;
+            return Some(Ok((row.d_key(), data)));
+        }
     }
 }
 
-impl<'g> Iterator for RowIteratorAll<'g> {
-    type Item = (
-        &'g PrimaryIndexKey,
-        parking_lot::RwLockReadGuard<'g, RowData>,
-    );
+impl<'a, 'g> Iterator for RowIteratorAll<'a, 'g> {
+    type Item = RowItem<'g>;
     fn next(&mut self) -> Option<Self::Item> {
         self._next()
     }
@@ -0,0 +1,155 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::{
+        engine::{
+            core::{dml::QueryMemBudget, index::PrimaryIndexKey, lock},
+            data::{
+                cell::{Datacell, VirtualDatacell},
+                tag::{DataTag, TagClass},
+            },
+            error::{QueryError, QueryResult},
+            fractal::GlobalInstanceLike,
+            idx::{STIndex, STIndexSeq},
+            mem::IntegerRepr,
+            net::protocol::{Response, ResponseType},
+            ql::dml::traverse::TraverseStatement,
+            sync,
+        },
+        util::compiler,
+    },
+    std::collections::HashSet,
+};
+
+/// Expand the rows reachable from the start row, breadth first, by following the keys in the given list field. Each
+/// reached row is returned once, as its distance from the start (`0` for the start row) followed by all its fields,
+/// nearest rows first. Keys of rows that don't exist (or are soft deleted) are skipped, but the start row must exist.
+///
+/// At most `fanout` keys of every row are followed (the first ones in the list), and the traversal stops once `limit`
+/// rows have been reached
+pub fn traverse_resp(
+    global: &impl GlobalInstanceLike,
+    traverse: TraverseStatement,
+) -> QueryResult<Response> {
+    global
+        .state()
+        .namespace()
+        .with_model(traverse.entity(), |mdl| {
+            let via = traverse.via();
+            let Some(field) = mdl.fields().st_get(via.as_str()) else {
+                return Err(QueryError::QExecUnknownField.with_detail("field", via.as_str()));
+            };
+            // the field must be a list of keys
+            let layers = field.layers();
+            if (layers.len() != 2)
+                | (layers[0].tag().tag_class() != TagClass::List)
+                | (layers[layers.len() - 1].tag().tag_unique() != mdl.p_tag().tag_unique())
+            {
+                return Err(QueryError::QExecDmlValidationError.with_detail("field", via.as_str()));
+            }
+            if traverse.start().kind().tag_unique() != mdl.p_tag().tag_unique() {
+                return compiler::cold_rerr(QueryError::QExecDmlWhereHasUnindexedColumn);
+            }
+            let start = PrimaryIndexKey::try_from_dc(Datacell::from(traverse.start().clone()))
+                .ok_or(QueryError::QExecDmlWhereHasUnindexedColumn)?;
+            let fanout = traverse
+                .fanout()
+                .map_or(usize::MAX, |fanout| fanout as usize);
+            let limit = traverse.limit() as usize;
+            let g = sync::atm::cpin();
+            let mut budget = QueryMemBudget::new(global);
+            let null = Datacell::null();
+            let mut data = Vec::new();
+            let mut rows = 0;
+            let live = match mdl.primary_index().select_key(&start, &g) {
+                Some(row) => !lock::read(mdl, row)?.is_tombstoned(),
+                None => false,
+            };
+            if !live {
+                return Err(QueryError::QExecDmlRowNotFound);
+            }
+            let mut visited = HashSet::new();
+            budget.charge_key(&start)?;
+            visited.insert(start.clone());
+            let mut frontier = vec![start];
+            let mut depth = 0;
+            while (rows < limit) & !frontier.is_empty() {
+                let mut next = Vec::new();
+                for key in frontier {
+                    if rows == limit {
+                        break;
+                    }
+                    let Some(row) = mdl.primary_index().select_key(&key, &g) else {
+                        continue;
+                    };
+                    let r = lock::read(mdl, row)?;
+                    if r.is_tombstoned() {
+                        continue;
+                    }
+                    IntegerRepr::scoped(mdl.fields().len() as u64 + 1, |repr| data.extend(repr));
+                    data.push(b'\n');
+                    super::encode_cell(&mut data, &Datacell::new_uint_default(depth));
+                    let pk = VirtualDatacell::new_pk(row.d_key(), mdl.p_tag());
+                    for field in mdl.fields().stseq_ord_key() {
+                        let dc = if field.as_str() == mdl.p_key() {
+                            &*pk
+                        } else {
+                            r.fields().st_get(field.as_str()).unwrap_or(&null)
+                        };
+                        budget.charge_cell(dc)?;
+                        super::encode_cell(&mut data, dc);
+                    }
+                    rows += 1;
+                    if depth == traverse.depth() {
+                        continue;
+                    }
+                    let Some(adjacent) =
+                        r.fields().st_get(via.as_str()).and_then(Datacell::try_list)
+                    else {
+                        continue;
+                    };
+                    for key in adjacent.read().iter().take(fanout) {
+                        let Some(key) = PrimaryIndexKey::try_clone_from_dc(key) else {
+                            continue;
+                        };
+                        if !visited.contains(&key) {
+                            budget.charge_key(&key)?;
+                            visited.insert(key.clone());
+                            next.push(key);
+                        }
+                    }
+                }
+                frontier = next;
+                depth += 1;
+            }
+            Ok(Response::Serialized {
+                ty: ResponseType::MultiRow,
+                size: rows,
+                data,
+            })
+        })
+}
@@ -28,22 +28,39 @@ use {
     crate::{
         engine::{
             core::{
-                self, dml::QueryExecMeta, model::delta::DataDeltaKind,
+                self,
+                dml::{
+                    expr, ins, sel::RowIteratorAll, Mutation, OrderedRowLocks, QueryExecMeta,
+                    QueryMemBudget, ReturningRows, RowFilter,
+                },
+                index::{PrimaryIndexKey, Row, RowData},
+                lock,
+                model::{
+                    delta::{DataDeltaKind, DeltaVersion},
+                    history::{RowHistory, RowSnapshot},
+                    props::{NumericCoercion, OverflowPolicy},
+                    tag_name, ModelData,
+                },
+                notice::{Notice, NoticeCode},
                 query_meta::AssignmentOperator,
+                EntityIDRef,
             },
             data::{
-                cell::Datacell,
+                cell::{Datacell, VirtualDatacell},
                 lit::Lit,
                 tag::{DataTag, FloatSpec, SIntSpec, TagClass, UIntSpec},
             },
             error::{QueryError, QueryResult},
             fractal::GlobalInstanceLike,
-            idx::STIndex,
+            idx::{STIndex, STIndexSeq},
             net::protocol::Response,
-            ql::dml::upd::{AssignmentExpression, UpdateStatement},
+            ql::dml::{
+                upd::{AssignmentExpression, UpdateStatement},
+                WhereClause,
+            },
             sync,
         },
-        util::compiler,
+        util::{compiler, os},
     },
     std::mem,
 };
@@ -143,6 +160,79 @@ unsafe fn dc_op_float_div(dc: &Datacell, rhs: Lit) -> (bool, Datacell) {
     let kind = FloatSpec::from_full(dc.tag());
     (kind.check(result), Datacell::new_float(result, kind))
 }
+/// Returns what a compound assignment that took a numeric field out of its bounds (or overflowed) leaves the field at,
+/// under the field's overflow policy. Returns [`None`] if it's still an error: when the field errors on overflow, for
+/// a division by zero or for a NaN
+fn dc_op_overflow(
+    policy: OverflowPolicy,
+    operator: AssignmentOperator,
+    dc: &Datacell,
+    rhs: &Lit,
+) -> Option<Datacell> {
+    use AssignmentOperator::{AddAssign, Assign, DivAssign, MulAssign, SubAssign};
+    if (policy == OverflowPolicy::Error) | (operator == Assign) {
+        return None;
+    }
+    match dc.kind() {
+        TagClass::UnsignedInt => {
+            let (a, b) = (dc.try_uint()?, rhs.try_uint()?);
+            let kind = unsafe {
+                // UNSAFE(@ohsayan): +tagck
+                UIntSpec::from_full(dc.tag())
+            };
+            let uint = match (policy, operator) {
+                // an unsigned division only fails when it's by zero
+                (_, DivAssign) => return None,
+                (OverflowPolicy::Wrap, AddAssign) => kind.wrap(a.wrapping_add(b)),
+                (OverflowPolicy::Wrap, SubAssign) => kind.wrap(a.wrapping_sub(b)),
+                (OverflowPolicy::Wrap, _) => kind.wrap(a.wrapping_mul(b)),
+                (_, AddAssign) => kind.saturate(a as i128 + b as i128),
+                (_, SubAssign) => kind.saturate(a as i128 - b as i128),
+                (_, _) => kind.saturate((a as i128).saturating_mul(b as i128)),
+            };
+            Some(Datacell::new_uint(uint, kind))
+        }
+        TagClass::SignedInt => {
+            let (a, b) = (dc.try_sint()?, rhs.try_sint()?);
+            if (operator == DivAssign) & (b == 0) {
+                return None;
+            }
+            let kind = unsafe {
+                // UNSAFE(@ohsayan): +tagck
+                SIntSpec::from_full(dc.tag())
+            };
+            let sint = match (policy, operator) {
+                (OverflowPolicy::Wrap, AddAssign) => kind.wrap(a.wrapping_add(b)),
+                (OverflowPolicy::Wrap, SubAssign) => kind.wrap(a.wrapping_sub(b)),
+                (OverflowPolicy::Wrap, MulAssign) => kind.wrap(a.wrapping_mul(b)),
+                (OverflowPolicy::Wrap, _) => kind.wrap(a.wrapping_div(b)),
+                (_, AddAssign) => kind.saturate(a as i128 + b as i128),
+                (_, SubAssign) => kind.saturate(a as i128 - b as i128),
+                (_, MulAssign) => kind.saturate(a as i128 * b as i128),
+                (_, _) => kind.saturate(a as i128 / b as i128),
+            };
+            Some(Datacell::new_sint(sint, kind))
+        }
+        TagClass::Float => {
+            // floats can't wrap, so this is saturating
+            let (a, b) = (dc.try_float()?, rhs.try_float()?);
+            let kind = unsafe {
+                // UNSAFE(@ohsayan): +tagck
+                FloatSpec::from_full(dc.tag())
+            };
+            let float = match operator {
+                AddAssign => a + b,
+                SubAssign => a - b,
+                MulAssign => a * b,
+                _ if b == 0.0 => return None,
+                _ => a / b,
+            };
+            let float = kind.saturate(float);
+            (!float.is_nan()).then(|| Datacell::new_float(float, kind))
+        }
+        _ => None,
+    }
+}
 // binary
 unsafe fn dc_op_bin_ass(_dc: &Datacell, rhs: Lit) -> (bool, Datacell) {
     let new_bin = rhs.bin();
@@ -254,41 +344,293 @@ pub fn update_resp(
     global: &impl GlobalInstanceLike,
     update: UpdateStatement,
 ) -> QueryResult<Response> {
-    self::update(global, update).map(|_| Response::Empty)
+    let with_lsn = update.with_lsn();
+    let (updated, version, returning) = self::update_returning(global, update)?;
+    Ok(match (updated, returning) {
+        _ if with_lsn => super::lsn_response(version),
+        (updated, Some(returning)) => returning.into_response(updated.is_none()),
+        (Some(updated), None) => Response::UInt64(updated),
+        (None, None) => Response::Empty,
+    })
 }
 
-pub fn update(global: &impl GlobalInstanceLike, mut update: UpdateStatement) -> QueryResult<()> {
-    core::with_model_for_data_update(global, update.entity(), |mdl| {
-        let mut ret = Ok(QueryExecMeta::zero());
-        // prepare row fetch
-        let key = mdl.resolve_where(update.clauses_mut())?;
-        // fetch row
+/// Update the rows selected by the where clause. An update of a single primary key (without a limit) fails if the
+/// row doesn't exist and returns [`None`]; all other updates return the number of rows that were updated. If an
+/// assignment fails for any row, none of the rows are updated.
+///
+/// An update without a where clause has to either set a limit or use `force`
+#[cfg(test)]
+pub fn update(
+    global: &impl GlobalInstanceLike,
+    update: UpdateStatement,
+) -> QueryResult<Option<u64>> {
+    self::update_returning(global, update).map(|(updated, _, _)| updated)
+}
+
+fn update_returning<'a>(
+    global: &impl GlobalInstanceLike,
+    mut update: UpdateStatement<'a>,
+) -> QueryResult<(Option<u64>, Option<DeltaVersion>, Option<ReturningRows<'a>>)> {
+    let (limit, force) = (update.limit(), update.force());
+    let unfiltered = update.clauses_mut().clauses_mut().is_empty();
+    if unfiltered & limit.is_none() & !force {
+        return Err(QueryError::QExecDmlUnfilteredMutation);
+    }
+    let returning = update.take_returning();
+    let mut updated = None;
+    let mut returning_rows = None;
+    let version = core::with_model_for_data_update(global, update.entity(), |mdl| {
+        returning_rows =
+            ReturningRows::for_statement(mdl, returning, update.summary(), Mutation::Update)?;
+        check_expressions(mdl, &update)?;
+        if limit.is_none() & is_point_update(mdl, update.clauses_mut()) {
+            return update_point(mdl, &mut update, returning_rows.as_mut());
+        }
+        global.admit_expensive_query()?;
+        let (target, filter) = mdl.resolve_where_filtered(update.clauses_mut())?;
         let g = sync::atm::cpin();
-        let Some(row) = mdl.primary_index().select(key, &g) else {
-            return Err(QueryError::QExecDmlRowNotFound);
+        // collect the keys first so that we don't hold the index latch while updating rows
+        let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+        let encodes_rows = returning_rows
+            .as_ref()
+            .map_or(false, ReturningRows::encodes_rows);
+        let mut budget = QueryMemBudget::new(global);
+        let keys = RowIteratorAll::new_filtered(&g, mdl, limit, target, &filter)?
+            .collect_keys(&mut budget, encodes_rows)?;
+        if keys.len() == limit {
+            Notice::raise(
+                NoticeCode::LimitReached,
+                format!("update stopped at its limit of {limit} rows, so more rows might match"),
+            );
+        }
+        // lock all the rows before changing any of them, so that a failing assignment can undo the rows that were
+        // already changed (the update applies to all of them or none of them). rows are locked in the same order as
+        // every other statement that locks several rows, so that two statements never wait for each other
+        let entity = update.entity();
+        let rows = keys
+            .iter()
+            // a row that was removed since the scan is skipped
+            .filter_map(|key| mdl.primary_index().select_key(key, &g))
+            .map(|row| (entity, mdl, row))
+            .collect();
+        let mut locks = OrderedRowLocks::write(rows)?;
+        // soft deleted rows can't be updated, and the row might have changed since the scan
+        locks.retain(|row, row_data| {
+            !row_data.is_tombstoned() && filter.matches(mdl, row.d_key(), row_data.fields())
+        });
+        let mut locked: Vec<_> = locks.iter_mut().collect();
+        let mut applied = Vec::with_capacity(locked.len());
+        for i in 0..locked.len() {
+            let (row, row_data_wl) = &mut locked[i];
+            // the old values are held until all the rows are changed, so they count towards the query's memory
+            let changes = RowChanges::apply(mdl, row.d_key(), row_data_wl, update.expressions())
+                .and_then(|changes| match budget.charge(changes.held_size()) {
+                    Ok(()) => Ok(changes),
+                    Err(e) => {
+                        changes.undo(row_data_wl);
+                        Err(e)
+                    }
+                });
+            match changes {
+                Ok(changes) => applied.push(changes),
+                Err(e) => {
+                    // nothing was published yet, so undoing the changes leaves no trace of them
+                    applied
+                        .into_iter()
+                        .zip(locked.iter_mut())
+                        .for_each(|(changes, (_, row_data_wl))| changes.undo(row_data_wl));
+                    return Err(e);
+                }
+            }
+        }
+        let mut ret = QueryExecMeta::zero();
+        let count = locked.len() as u64;
+        for (changes, (row, row_data_wl)) in applied.into_iter().zip(locked.iter_mut()) {
+            ret = changes.publish(mdl, row, row_data_wl, returning_rows.as_mut(), &g);
+        }
+        if unfiltered & (count != 0) {
+            Notice::raise(
+                NoticeCode::UnfilteredMutation,
+                format!("update without a where clause changed {count} rows"),
+            );
+        }
+        updated = Some(count);
+        Ok(ret)
+    })?;
+    Ok((updated, version, returning_rows))
+}
+
+/// Type check the expressions on the right hand side of the assignments
+pub(super) fn check_expressions(mdl: &ModelData, update: &UpdateStatement) -> QueryResult<()> {
+    for rhs in update
+        .expressions()
+        .iter()
+        .filter_map(|assn| assn.rhs_expr.as_ref())
+    {
+        expr::check(rhs, mdl)?;
+    }
+    Ok(())
+}
+
+/// Update the row with the primary key given by the where clause, failing if it doesn't exist
+pub(super) fn update_point(
+    mdl: &ModelData,
+    update: &mut UpdateStatement,
+    returning: Option<&mut ReturningRows>,
+) -> QueryResult<QueryExecMeta> {
+    // prepare row fetch
+    let key = mdl.resolve_where(update.clauses_mut())?;
+    // fetch row
+    let g = sync::atm::cpin();
+    let Some(row) = mdl.primary_index().select(key, &g) else {
+        return Err(QueryError::QExecDmlRowNotFound);
+    };
+    mdl.hot_keys().record(row.d_key());
+    update_row(mdl, row, update.expressions(), None, returning, &g)
+        .map(|meta| meta.unwrap_or(QueryExecMeta::zero()))
+}
+
+pub(super) fn is_point_update(mdl: &ModelData, where_clause: &mut WhereClause) -> bool {
+    let clauses = where_clause.clauses_mut();
+    (clauses.len() == 1)
+        & clauses
+            .get(mdl.p_key().as_bytes())
+            .map_or(false, |clause| clause.filter_hint_none())
+}
+
+/// Apply the assignments to a row and publish a delta. If a filter is given, the row is only updated if it still
+/// matches the filter (returns [`None`] otherwise). The updated row is appended to `returning`, if given
+pub(super) fn update_row(
+    mdl: &ModelData,
+    row: &Row,
+    expressions: &[AssignmentExpression],
+    filter: Option<&RowFilter>,
+    returning: Option<&mut ReturningRows>,
+    g: &sync::atm::Guard,
+) -> QueryResult<Option<QueryExecMeta>> {
+    // lock row
+    let mut row_data_wl = lock::write(mdl, row)?;
+    if row_data_wl.is_tombstoned() {
+        // soft deleted rows can't be updated
+        return match filter {
+            Some(_) => Ok(None),
+            None => Err(QueryError::QExecDmlRowNotFound),
         };
-        // lock row
-        let mut row_data_wl = row.d_data().write();
-        // create new version
-        let ds = mdl.delta_state();
-        let new_version = ds.create_new_data_delta_version();
+    }
+    if let Some(filter) = filter {
+        // the row might have changed since the scan
+        if !filter.matches(mdl, row.d_key(), row_data_wl.fields()) {
+            return Ok(None);
+        }
+    }
+    let changes = RowChanges::apply(mdl, row.d_key(), &mut row_data_wl, expressions)?;
+    Ok(Some(changes.publish(
+        mdl,
+        row,
+        &mut row_data_wl,
+        returning,
+        g,
+    )))
+}
+
+/// The changes that an update made to a row, which can still be undone until they're published
+struct RowChanges<'a> {
+    /// the row as it was before the update, if the model keeps history
+    prior_version: Option<RowSnapshot>,
+    /// the fields that were changed, along with their old values
+    replaced: Vec<(&'a str, Datacell)>,
+    /// the list fields that a value was appended to
+    appended: Vec<&'a str>,
+}
+
+impl<'a> RowChanges<'a> {
+    /// Apply the assignments to the (locked) row, undoing all of them if any of them fails
+    fn apply(
+        mdl: &'a ModelData,
+        pk: &PrimaryIndexKey,
+        row_data_wl: &mut RowData,
+        expressions: &'a [AssignmentExpression],
+    ) -> QueryResult<Self> {
+        let mut ret = Ok(());
+        // keep the current version around in case the model keeps history
+        let prior_version = mdl
+            .history()
+            .map(|_| RowHistory::snapshot(row_data_wl.fields()));
+        // functions see the row as it was before this update
+        let pk = VirtualDatacell::new_pk(pk, mdl.p_tag());
+        let mut computed_rhs: Vec<Option<QueryResult<Option<Lit>>>> = expressions
+            .iter()
+            .map(|assn| {
+                assn.rhs_expr.as_ref().map(|rhs| {
+                    expr::eval_lit(rhs, |field| {
+                        if field == mdl.p_key() {
+                            Some(&*pk)
+                        } else {
+                            row_data_wl.fields().st_get(field)
+                        }
+                    })
+                })
+            })
+            .collect();
         // process changes
         let mut rollback_now = false;
-        let mut rollback_data = Vec::with_capacity(update.expressions().len());
-        let mut assn_expressions = update.into_expressions().into_iter();
+        let mut rollback_data = Vec::with_capacity(expressions.len());
+        let mut appended = vec![];
+        let mut assn_expressions = expressions.iter().zip(computed_rhs.iter_mut());
+        // the index of the next assignment's parameter, as long as every assignment so far took a single parameter
+        let mut params = Some(0);
         /*
             FIXME(@ohsayan): where's my usual magic? I'll do it once we have the SE stabilized
         */
         // apply changes
         while (assn_expressions.len() != 0) & (!rollback_now) {
-            let AssignmentExpression {
-                lhs,
-                rhs,
-                operator_fn,
-            } = unsafe {
+            let (
+                AssignmentExpression {
+                    lhs,
+                    rhs,
+                    rhs_expr,
+                    operator_fn,
+                },
+                computed,
+            ) = unsafe {
                 // UNSAFE(@ohsayan): pre-loop cond
                 assn_expressions.next().unwrap_unchecked()
             };
+            let param = params.filter(|_| rhs_expr.is_none());
+            params = param.map(|param| param + 1);
+            let operator_fn = *operator_fn;
+            let rhs = match computed.take() {
+                Some(Ok(Some(computed))) => computed,
+                Some(Err(e)) => {
+                    // the function's result (or a cast) didn't fit
+                    input_trace("expr;overflow");
+                    rollback_now = true;
+                    ret = Err(e);
+                    break;
+                }
+                Some(Ok(None)) => {
+                    // the function returned null
+                    let Some(fdata) = row_data_wl.fields_mut().st_get_mut(lhs.as_str()) else {
+                        input_trace("fieldnotfound");
+                        rollback_now = true;
+                        ret = Err(QueryError::QExecUnknownField.with_detail("field", lhs.as_str()));
+                        break;
+                    };
+                    let nullable = mdl
+                        .fields()
+                        .st_get(lhs.as_str())
+                        .map_or(false, |fdef| fdef.is_nullable() & !fdef.is_computed());
+                    if !nullable | (operator_fn != AssignmentOperator::Assign) {
+                        input_trace("expr;null");
+                        rollback_now = true;
+                        ret = Err(QueryError::QExecDmlValidationError);
+                        break;
+                    }
+                    rollback_data.push((lhs.as_str(), mem::replace(fdata, Datacell::null())));
+                    continue;
+                }
+                None => rhs.clone(),
+            };
             let field_definition;
             let field_data;
             match (
@@ -302,10 +644,66 @@ pub fn update(global: &impl GlobalInstanceLike, mut update: UpdateStatement) ->
                 _ => {
                     input_trace("fieldnotfound");
                     rollback_now = true;
-                    ret = Err(QueryError::QExecUnknownField);
+                    ret = Err(QueryError::QExecUnknownField.with_detail("field", lhs.as_str()));
                     break;
                 }
             }
+            if field_definition.is_computed() {
+                // computed fields can't be assigned to
+                input_trace("computed");
+                rollback_now = true;
+                ret = Err(QueryError::QExecDmlValidationError);
+                break;
+            }
+            if let Some(counter) = field_definition.window() {
+                // `+=` records events, and `=` starts over (see [`crate::engine::core::model::window`])
+                let now = os::get_epoch_time_secs();
+                let new = match (operator_fn, rhs.try_uint()) {
+                    (AssignmentOperator::AddAssign, Some(n)) => counter.record(field_data, n, now),
+                    (AssignmentOperator::Assign, Some(n)) => counter.start(n, now),
+                    _ => {
+                        input_trace("window;badop");
+                        rollback_now = true;
+                        ret = Err(QueryError::QExecDmlValidationError);
+                        break;
+                    }
+                };
+                input_trace("window");
+                rollback_data.push((lhs.as_str(), mem::replace(field_data, new)));
+                continue;
+            }
+            let rhs = match field_definition.decimal() {
+                // a value is read as a decimal, which is only assigned, added or subtracted (see
+                // [`crate::engine::core::model::decimal`])
+                Some(decimal) => {
+                    let held = match (operator_fn, rhs_expr) {
+                        (AssignmentOperator::MulAssign | AssignmentOperator::DivAssign, _) => None,
+                        // a function's result is already the held integer
+                        (_, Some(_)) => Some(rhs),
+                        (_, None) => decimal.held_lit(&rhs),
+                    };
+                    let Some(held) = held else {
+                        input_trace("decimal;bad");
+                        rollback_now = true;
+                        ret = Err(QueryError::QExecDmlValidationError);
+                        break;
+                    };
+                    held
+                }
+                None => rhs,
+            };
+            let rhs = match mdl.props().coercion() {
+                NumericCoercion::Strict | NumericCoercion::Exact => rhs,
+                NumericCoercion::Lenient => field_definition.coerce_numeric_lit(rhs),
+            };
+            if (mdl.props().coercion() == NumericCoercion::Exact)
+                & !field_definition.is_exact_lit(&rhs)
+            {
+                input_trace("inexact");
+                rollback_now = true;
+                ret = Err(ins::inexact_error(lhs.as_str(), param));
+                break;
+            }
             match (
                 field_definition.layers()[0].tag().tag_class(),
                 rhs.kind().tag_class(),
@@ -313,10 +711,27 @@ pub fn update(global: &impl GlobalInstanceLike, mut update: UpdateStatement) ->
                 (tag_a, tag_b)
                     if (tag_a == tag_b) & (tag_a < TagClass::List) & field_data.is_init() =>
                 {
-                    let (okay, new) = unsafe { OPERATOR[opc(tag_a, operator_fn)](field_data, rhs) };
-                    rollback_now &= !okay;
+                    // the policy needs the value if the assignment overflows
+                    let overflow = field_definition
+                        .props()
+                        .overflow()
+                        .map(|policy| (policy, rhs.clone()));
+                    let (mut okay, mut new) =
+                        unsafe { OPERATOR[opc(tag_a, operator_fn)](field_data, rhs) };
+                    if let (false, Some((policy, rhs))) = (okay, overflow) {
+                        if let Some(held) = dc_op_overflow(policy, operator_fn, field_data, &rhs) {
+                            input_trace("sametag;overflow");
+                            (okay, new) = (true, held);
+                        }
+                    }
                     rollback_data.push((lhs.as_str(), mem::replace(field_data, new)));
                     input_trace("sametag;nonnull");
+                    if !okay {
+                        // out of the field's bounds (or overflowed)
+                        rollback_now = true;
+                        ret = Err(QueryError::QExecDmlValidationError);
+                        break;
+                    }
                 }
                 (tag_a, tag_b)
                     if (tag_a == tag_b)
@@ -340,13 +755,40 @@ pub fn update(global: &impl GlobalInstanceLike, mut update: UpdateStatement) ->
                                 break;
                             }
                         }
+                        if let Err(e) = field_definition.check(lhs.as_str(), field_data) {
+                            input_trace("list;check");
+                            unsafe {
+                                // UNSAFE(@ohsayan): matched tags
+                                field_data.read_list().write().pop();
+                            }
+                            rollback_now = true;
+                            ret = Err(e);
+                            break;
+                        }
+                        appended.push(lhs.as_str());
                     } else {
                         input_trace("list;badtag");
                         rollback_now = true;
-                        ret = Err(QueryError::QExecDmlValidationError);
+                        ret = Err(ins::type_error(
+                            lhs.as_str(),
+                            param,
+                            tag_name(field_definition.layers()[1].tag()),
+                            tag_name(rhs.kind()),
+                        ));
                         break;
                     }
                 }
+                (tag_a, tag_b) if tag_a != tag_b => {
+                    input_trace("badtag");
+                    ret = Err(ins::type_error(
+                        lhs.as_str(),
+                        param,
+                        tag_name(field_definition.layers()[0].tag()),
+                        tag_name(rhs.kind()),
+                    ));
+                    rollback_now = true;
+                    break;
+                }
                 _ => {
                     input_trace("unknown_reason;exitmainloop");
                     ret = Err(QueryError::QExecDmlValidationError);
@@ -355,21 +797,211 @@ pub fn update(global: &impl GlobalInstanceLike, mut update: UpdateStatement) ->
                 }
             }
         }
-        if compiler::unlikely(rollback_now) {
-            input_trace("rollback");
-            rollback_data
-                .into_iter()
-                .for_each(|(field_id, restored_data)| {
-                    row_data_wl.fields_mut().st_update(field_id, restored_data);
-                });
-        } else {
-            // update revised tag
-            row_data_wl.set_txn_revised(new_version);
-            // publish delta
-            let dp =
-                ds.append_new_data_delta_with(DataDeltaKind::Update, row.clone(), new_version, &g);
-            ret = Ok(QueryExecMeta::new(dp))
+        if !rollback_now {
+            // recompute any computed fields whose source was changed
+            for (field_id, field) in mdl.fields().stseq_ord_kv() {
+                let Some(expr) = field.computed() else {
+                    continue;
+                };
+                if expressions
+                    .iter()
+                    .any(|assn| assn.lhs.as_str() == expr.source())
+                {
+                    let new = expr.eval(
+                        row_data_wl
+                            .fields()
+                            .st_get(expr.source())
+                            .and_then(Datacell::try_str),
+                    );
+                    if let Some(old) = row_data_wl.fields_mut().st_get_mut(field_id.as_str()) {
+                        rollback_data.push((field_id.as_str(), mem::replace(old, new)));
+                    }
+                }
+            }
+            // enforce field checks on the new values
+            for (field_id, _) in rollback_data.iter() {
+                if let (Some(field), Some(data)) = (
+                    mdl.fields().st_get(*field_id),
+                    row_data_wl.fields().st_get(*field_id),
+                ) {
+                    if let Err(e) = field.check(field_id, data) {
+                        input_trace("check");
+                        ret = Err(e);
+                        break;
+                    }
+                }
+            }
         }
-        ret
-    })
+        let changes = Self {
+            prior_version,
+            replaced: rollback_data,
+            appended,
+        };
+        match ret {
+            Ok(()) => Ok(changes),
+            Err(e) => {
+                input_trace("rollback");
+                changes.undo(row_data_wl);
+                Err(e)
+            }
+        }
+    }
+    /// Restore the row to what it was before the assignments
+    /// The memory that the changes hold on to until they're published
+    fn held_size(&self) -> usize {
+        let replaced = self
+            .replaced
+            .iter()
+            .map(|(_, dc)| QueryMemBudget::cell_size(dc));
+        let prior_version = self
+            .prior_version
+            .iter()
+            .flat_map(|row| row.iter())
+            .map(|(field, dc)| field.len() + QueryMemBudget::cell_size(dc));
+        mem::size_of::<Self>() + replaced.chain(prior_version).sum::<usize>()
+    }
+    fn undo(self, row_data_wl: &mut RowData) {
+        for field_id in self.appended {
+            if let Some(list) = row_data_wl.fields().st_get(field_id) {
+                unsafe {
+                    // UNSAFE(@ohsayan): only list fields are recorded as appended to
+                    list.read_list().write().pop();
+                }
+            }
+        }
+        self.replaced
+            .into_iter()
+            .for_each(|(field_id, restored_data)| {
+                row_data_wl.fields_mut().st_update(field_id, restored_data);
+            });
+    }
+    /// Publish the changes: update everything that is derived from the row and append a delta
+    fn publish(
+        self,
+        mdl: &ModelData,
+        row: &Row,
+        row_data_wl: &mut RowData,
+        returning: Option<&mut ReturningRows>,
+        g: &sync::atm::Guard,
+    ) -> QueryExecMeta {
+        // sync secondary indexes
+        mdl.sidx_update_row(row.d_key(), &self.replaced, row_data_wl.fields());
+        if let (Some(history), Some(prior_version)) = (mdl.history(), self.prior_version) {
+            history.record(row.d_key(), Some(prior_version), os::get_epoch_time_secs());
+        }
+        // create new version and update revised tag
+        let ds = mdl.delta_state();
+        let new_version = ds.create_new_data_delta_version();
+        row_data_wl.set_txn_revised(new_version);
+        if let Some(returning) = returning {
+            // an assignment can leave a field as it was
+            let modified = self
+                .replaced
+                .iter()
+                .any(|(field_id, old)| row_data_wl.fields().st_get(*field_id) != Some(old));
+            returning.push_updated_row(mdl, row.d_key(), row_data_wl.fields(), modified);
+        }
+        mdl.views().touch(row.d_key());
+        if let Some(columns) = mdl.columns() {
+            columns.touch(row.d_key());
+        }
+        if let Some(maintained) = mdl.maintained() {
+            maintained.touch(row.d_key());
+        }
+        // publish delta
+        let dp = ds.append_new_data_delta_with(DataDeltaKind::Update, row.clone(), new_version, g);
+        QueryExecMeta::new(dp, new_version)
+    }
+}
+
+/// Fill in the default of every field that is backfilled (see [`crate::engine::core::model::Field::backfill`]) and is
+/// null in the rows with the given keys, like an update would. Returns the number of rows that were changed
+pub fn backfill_rows(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    keys: &[PrimaryIndexKey],
+) -> QueryResult<u64> {
+    let mut filled = 0;
+    core::with_model_for_data_update(global, entity, |mdl| {
+        let g = sync::atm::cpin();
+        let mut ret = QueryExecMeta::zero();
+        for key in keys {
+            let Some(row) = mdl.primary_index().select_key(key, &g) else {
+                // removed since the scan
+                continue;
+            };
+            if let Some(meta) = backfill_row(mdl, row, &g)? {
+                ret = meta;
+                filled += 1;
+            }
+        }
+        Ok(ret)
+    })?;
+    Ok(filled)
+}
+
+/// Returns [`None`] if the row has nothing to fill in (or was soft deleted)
+fn backfill_row(
+    mdl: &ModelData,
+    row: &Row,
+    g: &sync::atm::Guard,
+) -> QueryResult<Option<QueryExecMeta>> {
+    // fields added since the row was written are only set (to null) once it's read
+    drop(lock::read(mdl, row)?);
+    let mut row_data_wl = lock::write(mdl, row)?;
+    if row_data_wl.is_tombstoned() {
+        return Ok(None);
+    }
+    // generate and check every value before we touch the row
+    let mut defaults = vec![];
+    for (field_id, field) in mdl.fields().stseq_ord_kv() {
+        if !field.backfills()
+            | !row_data_wl
+                .fields()
+                .st_get(field_id.as_str())
+                .map_or(false, Datacell::is_null)
+        {
+            continue;
+        }
+        let mut data = field.default_value().unwrap();
+        if !field.vt_data_fpath(&mut data) {
+            return Err(QueryError::QExecDmlValidationError.with_detail("field", field_id.as_str()));
+        }
+        field.check(field_id.as_str(), &data)?;
+        defaults.push((field_id.as_str(), data));
+    }
+    if defaults.is_empty() {
+        return Ok(None);
+    }
+    let prior_version = mdl
+        .history()
+        .map(|_| RowHistory::snapshot(row_data_wl.fields()));
+    let ds = mdl.delta_state();
+    let new_version = ds.create_new_data_delta_version();
+    let old: Vec<(&str, Datacell)> = defaults
+        .into_iter()
+        .map(|(field_id, data)| {
+            (
+                field_id,
+                row_data_wl
+                    .fields_mut()
+                    .st_update_return(field_id, data)
+                    .unwrap(),
+            )
+        })
+        .collect();
+    mdl.sidx_update_row(row.d_key(), &old, row_data_wl.fields());
+    if let (Some(history), Some(prior_version)) = (mdl.history(), prior_version) {
+        history.record(row.d_key(), Some(prior_version), os::get_epoch_time_secs());
+    }
+    row_data_wl.set_txn_revised(new_version);
+    mdl.views().touch(row.d_key());
+    if let Some(columns) = mdl.columns() {
+        columns.touch(row.d_key());
+    }
+    if let Some(maintained) = mdl.maintained() {
+        maintained.touch(row.d_key());
+    }
+    let dp = ds.append_new_data_delta_with(DataDeltaKind::Update, row.clone(), new_version, g);
+    Ok(Some(QueryExecMeta::new(dp, new_version)))
 }
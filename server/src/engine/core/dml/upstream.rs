@@ -0,0 +1,257 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::{
+    engine::{
+        core::{
+            self,
+            dml::{ins, QueryExecMeta},
+            index::PrimaryIndexKey,
+            lock,
+            model::{delta::DataDeltaKind, upstream::UpstreamWrites, ModelData},
+            EntityIDRef,
+        },
+        data::{cell::Datacell, lit::Lit},
+        error::{QueryError, QueryResult},
+        fractal::GlobalInstanceLike,
+        idx::STIndexSeq,
+        ql::{
+            dml::{ins::InsertData, WhereClause},
+            lex,
+        },
+        sync,
+    },
+    util::os,
+};
+
+/// A row that has to be fetched from the upstream before a point read of it is served locally
+pub struct UpstreamFetch {
+    /// the query that fetches the row (a `select *` of its primary key)
+    pub query: String,
+    pub params: Vec<u8>,
+    key: PrimaryIndexKey,
+    cached: bool,
+}
+
+impl UpstreamFetch {
+    /// Returns true if there's a local copy of the row (that can be served if the upstream can't be reached)
+    pub fn cached(&self) -> bool {
+        self.cached
+    }
+}
+
+/// If the model caches an upstream and the row that the where clause selects by its primary key wasn't fetched
+/// within the model's ttl, returns the query that fetches it. Reads that don't select a single primary key are only
+/// served locally
+pub fn upstream_fetch(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    where_clause: &mut WhereClause,
+) -> QueryResult<Option<UpstreamFetch>> {
+    global.state().namespace().with_model(entity, |mdl| {
+        let Some(upstream) = mdl.upstream() else {
+            return Ok(None);
+        };
+        let Ok(lit) = mdl.resolve_where(where_clause) else {
+            return Ok(None);
+        };
+        let Some(key) = PrimaryIndexKey::try_from_dc(Datacell::from(lit.clone())) else {
+            return Ok(None);
+        };
+        if upstream.is_fresh(&key, os::get_epoch_time_secs()) {
+            return Ok(None);
+        }
+        Ok(Some(prepare_fetch(mdl, entity, key, &lit)))
+    })
+}
+
+/// Like [`upstream_fetch`], but for the row with the given key (used to warm rows after a restart, see
+/// `model::warmup`)
+pub fn upstream_fetch_key(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    key: &PrimaryIndexKey,
+) -> QueryResult<Option<UpstreamFetch>> {
+    global.state().namespace().with_model(entity, |mdl| {
+        let Some(upstream) = mdl.upstream() else {
+            return Ok(None);
+        };
+        if upstream.is_fresh(key, os::get_epoch_time_secs()) {
+            return Ok(None);
+        }
+        let lit = if let Some(u) = key.uint() {
+            Lit::new_uint(u)
+        } else if let Some(s) = key.sint() {
+            Lit::new_sint(s)
+        } else if let Some(b) = key.bin() {
+            Lit::new_bin(b)
+        } else if let Some(s) = key.str() {
+            Lit::new_str(s)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(prepare_fetch(mdl, entity, key.clone(), &lit)))
+    })
+}
+
+fn prepare_fetch(
+    mdl: &ModelData,
+    entity: EntityIDRef,
+    key: PrimaryIndexKey,
+    lit: &Lit,
+) -> UpstreamFetch {
+    let g = sync::atm::cpin();
+    let cached = mdl
+        .primary_index()
+        .select_key(&key, &g)
+        // a row that can't be read is fetched again
+        .map_or(false, |row| {
+            lock::read(mdl, row).map_or(false, |data| !data.is_tombstoned())
+        });
+    let mut params = vec![];
+    lex::encode_param(&mut params, Some(lit));
+    UpstreamFetch {
+        query: format!(
+            "select * from {}.{} where {} = ?",
+            entity.space(),
+            entity.entity(),
+            mdl.p_key()
+        ),
+        params,
+        key,
+        cached,
+    }
+}
+
+/// Replace the local copy of a row with the one fetched from the upstream (the cells of a `select *`), or remove it
+/// if the upstream doesn't have it. The row is left as it is if it didn't change
+pub fn upstream_refresh(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    fetch: UpstreamFetch,
+    row: Option<Vec<Datacell>>,
+) -> QueryResult<()> {
+    core::with_model_for_data_update(global, entity, |mdl| {
+        let Some(upstream) = mdl.upstream() else {
+            return Ok(QueryExecMeta::zero());
+        };
+        let prepared = match row {
+            Some(row) => {
+                // computed fields are computed again from the rest
+                if row.len() != mdl.fields().len() {
+                    return Err(QueryError::SysUpstreamError.with_detail("columns", row.len()));
+                }
+                let data = row
+                    .into_iter()
+                    .zip(mdl.fields().stseq_ord_value())
+                    .filter(|(_, field)| !field.is_computed())
+                    .map(|(dc, _)| dc)
+                    .collect();
+                let (pk, data) = ins::prepare_insert(mdl, InsertData::Ordered(data))?;
+                if pk != fetch.key {
+                    return Err(QueryError::SysUpstreamError.with_detail("field", mdl.p_key()));
+                }
+                Some(data)
+            }
+            None => None,
+        };
+        let _idx_latch = mdl.primary_index().acquire_cd();
+        let g = sync::atm::cpin();
+        let delta_state = mdl.delta_state();
+        // the local copy stays locked until it's replaced, so that it's never left half removed
+        let current = match mdl.primary_index().select_key(&fetch.key, &g) {
+            Some(row) => Some(lock::read(mdl, row)?),
+            None => None,
+        };
+        let unchanged = match (&current, &prepared) {
+            (Some(current), Some(data)) => current.fields() == data,
+            (None, None) => true,
+            _ => false,
+        };
+        let mut meta = QueryExecMeta::zero();
+        if !unchanged {
+            let new_version = delta_state.create_new_data_delta_version();
+            if let Some(row) = mdl.primary_index().delete_return_entry_key(&fetch.key, &g) {
+                match current.as_ref() {
+                    Some(current) => mdl.sidx_remove_row(row.d_key(), current.fields()),
+                    // added since the lookup
+                    None => mdl.sidx_remove_row(row.d_key(), lock::read(mdl, row)?.fields()),
+                }
+                mdl.views().touch(row.d_key());
+                if let Some(columns) = mdl.columns() {
+                    columns.touch(row.d_key());
+                }
+                if let Some(maintained) = mdl.maintained() {
+                    maintained.touch(row.d_key());
+                }
+                let dp = delta_state.append_new_data_delta_with(
+                    DataDeltaKind::Delete,
+                    row.clone(),
+                    new_version,
+                    &g,
+                );
+                meta = QueryExecMeta::new(dp, new_version);
+            }
+            drop(current);
+            if let Some(data) = prepared {
+                meta = ins::insert_prepared(mdl, fetch.key.clone(), data, None, &g)?;
+            }
+        }
+        upstream.mark_fetched(fetch.key, os::get_epoch_time_secs());
+        Ok(meta)
+    })
+    .map(|_| ())
+}
+
+/// Returns how writes to the model are forwarded, if it caches an upstream
+pub fn upstream_writes(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+) -> QueryResult<Option<UpstreamWrites>> {
+    global
+        .state()
+        .namespace()
+        .with_model(entity, |mdl| Ok(mdl.upstream().map(|u| u.writes())))
+}
+
+/// Consider every row of the model stale (if it caches an upstream), so that they're fetched again when they're
+/// next read
+pub fn upstream_forget(global: &impl GlobalInstanceLike, entity: EntityIDRef) {
+    let _ = global.state().namespace().with_model(entity, |mdl| {
+        if let Some(upstream) = mdl.upstream() {
+            upstream.forget_all();
+        }
+        Ok(())
+    });
+}
+
+/// Fails if the model caches an upstream. Used for writes that aren't statements, and so can't be forwarded to it
+pub(super) fn check_not_upstream(mdl: &ModelData) -> QueryResult<()> {
+    match mdl.upstream() {
+        Some(_) => Err(QueryError::QExecDmlValidationError.with_detail("constraint", "upstream")),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::{
+    core::{
+        dml::QueryExecMeta,
+        index::{DcFieldIndex, PrimaryIndexKey, Row},
+        lock,
+        model::{
+            delta::{DataDeltaKind, DeltaState},
+            Model, ModelData,
+        },
+        EntityID, EntityIDRef,
+    },
+    fractal::GlobalInstanceLike,
+    idx::{IndexBaseSpec, IndexST, STIndex, STIndexSeq},
+    sync::atm::{cpin, Guard},
+};
+
+/// Refresh the views derived from the given model with the rows that have changed since the views were last
+/// refreshed
+pub fn refresh_views(
+    global: &impl GlobalInstanceLike,
+    models: &IndexST<EntityID, Model>,
+    space: &str,
+    source: &ModelData,
+) {
+    let keys = source.views().take_pending();
+    if keys.is_empty() {
+        return;
+    }
+    for link in source.views().links() {
+        if let Some(view) = models.get(&EntityIDRef::new(space, link.name())) {
+            let hint = refresh_view(view.data(), source, &keys);
+            DeltaState::guard_delta_overflow(global, space, link.name(), view.data(), hint);
+        }
+    }
+}
+
+/// Fill a new view with all the rows of the model that it is derived from
+pub fn fill_view(view: &ModelData, source: &ModelData) {
+    let g = cpin();
+    let keys: Vec<PrimaryIndexKey> = {
+        let _idx_latch = source.primary_index().acquire_exclusive();
+        source
+            .primary_index()
+            .iter(&g)
+            .map(|row| row.d_key().clone())
+            .collect()
+    };
+    let _ = refresh_view(view, source, &keys);
+}
+
+/// Bring the rows with the given keys in the view up to date with the rows in the source model. A row is in the view
+/// only if it is live (not soft deleted) in the source model and matches the filter of the view
+fn refresh_view(view: &ModelData, source: &ModelData, keys: &[PrimaryIndexKey]) -> QueryExecMeta {
+    let def = view.props().view().unwrap();
+    let g = cpin();
+    // refreshes are serialized (and exclude readers) so that the view never goes back in time
+    let _idx_latch = view.primary_index().acquire_exclusive();
+    let mut meta = QueryExecMeta::zero();
+    for key in keys {
+        let projected = match source.primary_index().select_key(key, &g) {
+            Some(row) => match lock::read(source, row) {
+                Ok(data) => (!data.is_tombstoned() && def.matches(source, key, data.fields()))
+                    .then(|| project(view, data.fields())),
+                // the view keeps the last version of a row that can't be read
+                Err(_) => continue,
+            },
+            None => None,
+        };
+        let current = view.primary_index().select_key(key, &g);
+        meta = match (current, projected) {
+            (None, None) => continue,
+            (Some(row), Some(fields))
+                if lock::read(view, row).map_or(false, |data| data.fields() == &fields) =>
+            {
+                continue
+            }
+            (Some(row), Some(fields)) => match update_row(view, row, fields, &g) {
+                Some(meta) => meta,
+                None => continue,
+            },
+            (None, Some(fields)) => insert_row(view, key, fields, &g),
+            (Some(row), None) => {
+                // the row stays locked until it's removed, so that it's never left half removed
+                let Ok(data) = lock::read(view, row) else {
+                    continue;
+                };
+                let Some(row) = view.primary_index().delete_return_entry_key(key, &g) else {
+                    continue;
+                };
+                view.sidx_remove_row(row.d_key(), data.fields());
+                drop(data);
+                let ds = view.delta_state();
+                let new_version = ds.create_new_data_delta_version();
+                let dp = ds.append_new_data_delta_with(
+                    DataDeltaKind::Delete,
+                    row.clone(),
+                    new_version,
+                    &g,
+                );
+                QueryExecMeta::new(dp, new_version)
+            }
+        };
+    }
+    meta
+}
+
+/// Pick the fields of the view from a row of the source model
+fn project(view: &ModelData, fields: &DcFieldIndex) -> DcFieldIndex {
+    let mut projected = DcFieldIndex::idx_init_cap(view.fields().len());
+    for field_id in view.fields().stseq_ord_key() {
+        if let Some(data) = fields.st_get(field_id.as_str()) {
+            projected.st_insert(
+                unsafe {
+                    // UNSAFE(@ohsayan): the view owns the allocation, and the row goes with the view
+                    field_id.clone()
+                },
+                data.clone(),
+            );
+        }
+    }
+    projected
+}
+
+fn insert_row(
+    view: &ModelData,
+    key: &PrimaryIndexKey,
+    fields: DcFieldIndex,
+    g: &Guard,
+) -> QueryExecMeta {
+    let ds = view.delta_state();
+    let new_version = ds.create_new_data_delta_version();
+    let row = Row::new(
+        key.clone(),
+        fields,
+        ds.schema_current_version(),
+        new_version,
+    );
+    let _ = view.primary_index().insert(row.clone(), g);
+    // the row was just created, so it's in memory
+    if let Ok(data) = lock::read(view, &row) {
+        view.sidx_insert_row(row.d_key(), data.fields());
+    }
+    let dp = ds.append_new_data_delta_with(DataDeltaKind::Insert, row, new_version, g);
+    QueryExecMeta::new(dp, new_version)
+}
+
+/// Replace the fields of a row of the view, unless the row can't be read
+fn update_row(
+    view: &ModelData,
+    row: &Row,
+    fields: DcFieldIndex,
+    g: &Guard,
+) -> Option<QueryExecMeta> {
+    let mut row_data = lock::write(view, row).ok()?;
+    let ds = view.delta_state();
+    let new_version = ds.create_new_data_delta_version();
+    view.sidx_remove_row(row.d_key(), row_data.fields());
+    *row_data.fields_mut() = fields;
+    view.sidx_insert_row(row.d_key(), row_data.fields());
+    row_data.set_txn_revised(new_version);
+    drop(row_data);
+    let dp = ds.append_new_data_delta_with(DataDeltaKind::Update, row.clone(), new_version, g);
+    Some(QueryExecMeta::new(dp, new_version))
+}
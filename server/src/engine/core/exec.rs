@@ -25,16 +25,45 @@
 */
 
 use crate::engine::{
-    core::{ddl_misc, dml, model::ModelData, space::Space},
-    error::{QueryError, QueryResult},
-    fractal::{Global, GlobalInstanceLike},
-    net::protocol::{ClientLocalState, Response, ResponseType, SQuery},
+    core::{
+        dcl, ddl_misc, dml,
+        model::{result_cache::CacheGeneration, upstream::UpstreamWrites, ModelData},
+        names,
+        notice::{Notice, NoticeCode},
+        profile,
+        quota::{self, Access},
+        record, session,
+        space::Space,
+        write_delay, EntityID, EntityIDRef,
+    },
+    data::cell::Datacell,
+    error::{ErrorDetail, QueryError, QueryResult},
+    fractal::{self, upstream::Upstream, Global, GlobalInstanceLike},
+    net::protocol::{
+        BlobTarget, BulkInsert, ClientLocalState, Response, ResponseType, SQuery, StreamRequest,
+    },
     ql::{
         ast::{traits::ASTNode, InplaceData, State},
-        ddl::Use,
-        lex::KeywordStmt,
+        dcl::{CreateStatement, DropStatement, ExecuteStatement},
+        ddl::{
+            crt::{CreateIndex, CreateModelCopy},
+            SessionSet, Use,
+        },
+        dml::{
+            del::DeleteStatement,
+            eval::EvalStatement,
+            ins::InsertStatement,
+            sel::{
+                Aggregate, ExistsStatement, FetchStatement, SelectAggregateStatement,
+                SelectAllStatement, SelectFileStatement, SelectStatement,
+            },
+            traverse::TraverseStatement,
+            upd::UpdateStatement,
+        },
+        lex::{self, KeywordStmt, SecureLexer, Symbol, Token},
     },
 };
+use crate::util::os;
 
 /*
     ---
@@ -46,29 +75,288 @@ pub async fn dispatch_to_executor<'a>(
     cstate: &mut ClientLocalState,
     query: SQuery<'a>,
 ) -> QueryResult<Response> {
-    let tokens =
-        crate::engine::ql::lex::SecureLexer::new_with_segments(query.query(), query.params())
-            .lex()?;
+    // don't mix up this statement's error detail and notices with those left behind on this thread
+    ErrorDetail::clear();
+    Notice::clear();
+    profile::clear_parsed();
+    // the tokens can point into the spellings of the names that they refer to, so those must outlive the tokens
+    let _spellings;
+    let mut tokens = SecureLexer::new_with_segments(query.query(), query.params()).lex()?;
+    _spellings = names::resolve(global.state().namespace(), cstate.get_cs(), &mut tokens);
+    cstate.lexed(&tokens);
+    record::statement(cstate.id(), &tokens, query.query(), query.params());
     let mut state = State::new_inplace(&tokens);
     state.set_space_maybe(unsafe {
         // UNSAFE(@ohsayan): exclusively used within this scope
         core::mem::transmute(cstate.get_cs())
     });
+    if state.not_exhausted() && Token![set].eq(state.read()) {
+        state.cursor_ahead();
+        return run_set(cstate, state);
+    }
+    if state.not_exhausted() && state.read().ident_eq("explain") {
+        state.cursor_ahead();
+        return run_explain(global, state);
+    }
+    if state.not_exhausted() && state.read().ident_eq("fetch") {
+        state.cursor_ahead();
+        return run_fetch(global, cstate, state).await;
+    }
+    if state.not_exhausted() && state.read().ident_eq("traverse") {
+        state.cursor_ahead();
+        return run_traverse(global, cstate, state);
+    }
+    if state.not_exhausted() && state.read().ident_eq("eval") {
+        state.cursor_ahead();
+        return run_eval(global, cstate, state);
+    }
+    if state.not_exhausted() && state.read().ident_eq("execute") {
+        state.cursor_ahead();
+        return run_execute(global, cstate, state).await;
+    }
+    if state.has_remaining(2)
+        && (Token![create].eq(state.read()) | Token![drop].eq(state.read()))
+        && state.current()[1].ident_eq("statement")
+    {
+        return run_stored_statement_ddl(global, cstate, state).await;
+    }
     let stmt = state.try_statement()?;
+    if (stmt == KeywordStmt::Select) && SelectFileStatement::is_next(&state) {
+        return run_select_file(global, cstate, state).await;
+    }
     if stmt.is_blocking() {
-        run_blocking_stmt(global, cstate, state, stmt).await
+        return run_blocking_stmt(global, cstate, state, stmt).await;
+    }
+    delay_write(global, &state, stmt).await;
+    if let Some(upstream) = fractal::upstream::get() {
+        run_nb_upstream(
+            global,
+            cstate,
+            state,
+            stmt,
+            upstream,
+            query.query(),
+            query.params(),
+        )
+        .await
     } else {
         run_nb(global, cstate, state, stmt)
     }
 }
 
+/// Wait out the delay of a write to a model whose changes (or webhooks) fell behind, if any (see
+/// [`write_delay`]). This looks at the entity without parsing the statement so that the wait happens here and
+/// not on a worker thread
+async fn delay_write(global: &Global, state: &State<'_, InplaceData>, stmt: KeywordStmt) {
+    if !global.write_throttle().is_enabled() {
+        return;
+    }
+    let mut peek = State::new_inplace(state.current());
+    peek.set_space_maybe(state.get_space());
+    let skip = match stmt {
+        KeywordStmt::Insert => peek.not_exhausted() && peek.cursor_eq(Token![into]),
+        KeywordStmt::Delete => peek.not_exhausted() && peek.cursor_eq(Token![from]),
+        KeywordStmt::Update => false,
+        _ => return,
+    };
+    peek.cursor_ahead_if(skip);
+    let Some(entity) = peek.try_entity_ref() else {
+        // the statement fails to parse later anyway
+        return;
+    };
+    if let Some(delay) = write_delay(global, entity) {
+        // we can resume on another thread, so carry over what the statement left behind on this one so far (like
+        // `spawn_blocking_carry` does), and don't run it with what another statement left behind on that one
+        let (detail, notices, parsed) = (
+            ErrorDetail::take_any(),
+            Notice::take_all(),
+            profile::take_parsed(),
+        );
+        tokio::time::sleep(delay).await;
+        ErrorDetail::restore(detail);
+        Notice::restore(notices);
+        profile::restore_parsed(parsed);
+    }
+}
+
+/// Insert the rows of a bulk insert (see [`BulkInsert`]), or load them if the session asked for it, responding with
+/// the number of rows inserted
+pub fn dispatch_bulk_insert(
+    global: &Global,
+    cstate: &mut ClientLocalState,
+    bulk: BulkInsert,
+) -> QueryResult<Response> {
+    ErrorDetail::clear();
+    Notice::clear();
+    profile::clear_parsed();
+    let mut spelled = None;
+    let entity = frame_entity(global, cstate, bulk.entity(), &mut spelled)?;
+    quota::admit(global, cstate.username(), entity, Access::Write)?;
+    let _session = session::enter(cstate.session());
+    let inserted = match cstate.session().bulk_load() {
+        true => dml::bulk_load(global, entity, bulk.into_rows())?,
+        false => dml::bulk_insert(global, entity, bulk.into_rows())?,
+    };
+    cstate.set_rows_affected(inserted);
+    Ok(Response::UInt64(inserted))
+}
+
+/// Run a chunk of a streamed `SELECT ALL` (see [`StreamRequest`]): the matching rows after the first `from` of them
+/// (which come after the statement's own offset), and at most `rows` of them (within the statement's own limit).
+/// Sampled selects and Arrow exports can't be resumed, so they can't be streamed either
+pub fn dispatch_stream_chunk(
+    global: &Global,
+    cstate: &mut ClientLocalState,
+    stream: &StreamRequest,
+    from: u64,
+) -> QueryResult<Response> {
+    ErrorDetail::clear();
+    Notice::clear();
+    profile::clear_parsed();
+    let query = stream.query();
+    let _spellings;
+    let mut tokens = SecureLexer::new_with_segments(query.query(), query.params()).lex()?;
+    _spellings = names::resolve(global.state().namespace(), cstate.get_cs(), &mut tokens);
+    cstate.lexed(&tokens);
+    let mut state = State::new_inplace(&tokens);
+    state.set_space_maybe(unsafe {
+        // UNSAFE(@ohsayan): exclusively used within this scope
+        core::mem::transmute(cstate.get_cs())
+    });
+    if (state.try_statement()? != KeywordStmt::Select) || !state.cursor_rounded_eq(Token![all]) {
+        return Err(QueryError::QExecStreamUnsupported);
+    }
+    state.cursor_ahead();
+    let mut state = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    let _session = session::enter(cstate.session());
+    _callgs(global, &mut state, |g, mut select: SelectAllStatement| {
+        if select.sample.is_some() | select.arrow {
+            return Err(QueryError::QExecStreamUnsupported);
+        }
+        select.offset = select.offset.saturating_add(from);
+        select.limit = select.limit.saturating_sub(from).min(stream.chunk_rows());
+        dml::select_all_resp(g, select)
+    })
+}
+
+/// Check that a blob can be written into its target, before any of it is received
+pub fn dispatch_blob_begin(
+    global: &Global,
+    cstate: &ClientLocalState,
+    target: &BlobTarget,
+) -> QueryResult<()> {
+    let mut spelled = None;
+    let entity = frame_entity(global, cstate, target.entity(), &mut spelled)?;
+    dml::check_blob_target(global, entity, target.field())
+}
+
+/// Write an uploaded blob into its target
+pub fn dispatch_blob_write(
+    global: &Global,
+    cstate: &mut ClientLocalState,
+    target: &BlobTarget,
+    data: &[u8],
+) -> QueryResult<Response> {
+    let mut spelled = None;
+    let entity = frame_entity(global, cstate, target.entity(), &mut spelled)?;
+    quota::admit(global, cstate.username(), entity, Access::Write)?;
+    dml::write_blob(global, entity, target.key(), target.field(), data)?;
+    cstate.set_rows_affected(1);
+    Ok(Response::Empty)
+}
+
+/// Read a chunk of a blob, responding with the bytes read (or a null if the field is null)
+pub async fn dispatch_blob_read(
+    global: &Global,
+    cstate: &ClientLocalState,
+    target: &BlobTarget,
+    offset: u64,
+    len: u64,
+) -> QueryResult<Response> {
+    let data = {
+        let mut spelled = None;
+        let entity = frame_entity(global, cstate, target.entity(), &mut spelled)?;
+        dml::read_blob(global, entity, target.key(), target.field(), offset, len)?
+    };
+    let data = match data {
+        // a tiered blob is fetched on a blocking task
+        Some(reference) if fractal::tier::get().map_or(false, |tier| tier.is_ref(&reference)) => {
+            let read =
+                tokio::task::spawn_blocking(move || dml::read_tiered_blob(&reference, offset, len));
+            Some(read.await.map_err(|_| QueryError::SysServerError)??)
+        }
+        data => data,
+    };
+    Ok(match data {
+        Some(data) => Response::Serialized {
+            ty: ResponseType::Binary,
+            size: data.len(),
+            data,
+        },
+        None => Response::Null,
+    })
+}
+
+/// Swap the references to tiered blobs in a response for the blobs (see `fractal::tier`), fetching them on a blocking
+/// task. This runs once the statement has released its locks. Since we can resume on another thread, a blob that
+/// can't be fetched fails with its detail rather than leaving it on a thread
+pub async fn resolve_tiered(r: Response) -> Result<Response, ErrorDetail> {
+    match r {
+        Response::Serialized { ty, size, data }
+            if (ty != ResponseType::Binary) && dml::has_tiered(&data) =>
+        {
+            let data = tokio::task::spawn_blocking(move || {
+                ErrorDetail::clear();
+                dml::resolve_tiered(data).map_err(ErrorDetail::take)
+            })
+            .await
+            .map_err(|_| ErrorDetail::new(QueryError::SysServerError))??;
+            Ok(Response::Serialized { ty, size, data })
+        }
+        r => Ok(r),
+    }
+}
+
+/// Resolve the entity of a frame that names it without a statement, as either `space.model` or just the model (in
+/// the current space). If names are case insensitive and the entity isn't spelled like it was created, its spelling
+/// is put in `spelled`
+fn frame_entity<'a>(
+    global: &Global,
+    cstate: &'a ClientLocalState,
+    entity: &'a str,
+    spelled: &'a mut Option<EntityID>,
+) -> QueryResult<EntityIDRef<'a>> {
+    let entity = match entity.split_once('.') {
+        Some((space, model)) => EntityIDRef::new(space, model),
+        None => EntityIDRef::new(cstate.get_cs().ok_or(QueryError::QLExpectedEntity)?, entity),
+    };
+    match names::resolve_entity(global.state().namespace(), entity) {
+        Some(entity) => {
+            let entity = spelled.insert(entity);
+            Ok(EntityIDRef::new(entity.space(), entity.entity()))
+        }
+        None => Ok(entity),
+    }
+}
+
+/// Parse the statement, noting when parsing ended (for the profiler)
+#[inline(always)]
+fn parse<A: ASTNode<'static>>(state: &mut State<'static, InplaceData>) -> QueryResult<A> {
+    let r = A::parse_from_state_hardened(state);
+    profile::mark_parsed();
+    r
+}
+
 fn _callgs_map<A: ASTNode<'static> + core::fmt::Debug, T>(
     g: &Global,
     state: &mut State<'static, InplaceData>,
     f: impl FnOnce(&Global, A) -> Result<T, QueryError>,
     map: impl FnOnce(T) -> Response,
 ) -> QueryResult<Response> {
-    let cs = ASTNode::parse_from_state_hardened(state)?;
+    let cs = parse(state)?;
     Ok(map(f(&g, cs)?))
 }
 
@@ -78,7 +366,7 @@ fn _callgs<A: ASTNode<'static> + core::fmt::Debug, T>(
     state: &mut State<'static, InplaceData>,
     f: impl FnOnce(&Global, A) -> Result<T, QueryError>,
 ) -> QueryResult<T> {
-    let cs = ASTNode::parse_from_state_hardened(state)?;
+    let cs = parse(state)?;
     f(&g, cs)
 }
 
@@ -89,10 +377,95 @@ fn _callgcs<A: ASTNode<'static> + core::fmt::Debug, T>(
     state: &mut State<'static, InplaceData>,
     f: impl FnOnce(&Global, &ClientLocalState, A) -> Result<T, QueryError>,
 ) -> QueryResult<T> {
-    let a = ASTNode::parse_from_state_hardened(state)?;
+    let a = parse(state)?;
     f(&g, cstate, a)
 }
 
+/// Run a read, serving it from (and then adding it to) the model's result cache if the model has one. Reads that
+/// call `now()` or that aren't otherwise idempotent always run. A read served from the cache still counts against
+/// the quotas
+#[inline(always)]
+fn _callgcs_cached<A: ASTNode<'static> + core::fmt::Debug>(
+    g: &Global,
+    cstate: &ClientLocalState,
+    state: &mut State<'static, InplaceData>,
+    kind: &str,
+    entity: impl FnOnce(&A) -> EntityIDRef<'static>,
+    idempotent: impl FnOnce(&A) -> bool,
+    f: impl FnOnce(&Global, A) -> QueryResult<Response>,
+) -> QueryResult<Response> {
+    let tokens = state.current();
+    let a = parse(state)?;
+    let entity = entity(&a);
+    quota::admit(g, cstate.username(), entity, Access::Read)?;
+    let calls_now = tokens
+        .windows(2)
+        .any(|w| w[0].ident_eq("now") & (w[1] == Token::Symbol(Symbol::TtOpenParen)));
+    if calls_now | !idempotent(&a) {
+        return f(g, a);
+    }
+    let now = os::get_epoch_time_secs();
+    // the tokens hold the values of the parameters, and the kind tells apart statements with the same tail
+    let fingerprint = format!("{kind}:{tokens:?}");
+    let lookup = g.state().with_model(entity, |mdl| {
+        Ok(mdl.result_cache().map(|cache| {
+            let generation = CacheGeneration::of(mdl);
+            (cache.get(&fingerprint, generation, now), generation)
+        }))
+    })?;
+    match lookup {
+        None => f(g, a),
+        Some((Some(hit), _)) => Ok(hit),
+        Some((None, generation)) => {
+            let r = f(g, a)?;
+            // the model might have been dropped in the meantime, in which case there's nothing to cache into
+            let _ = g.state().namespace().with_model(entity, |mdl| {
+                if let Some(cache) = mdl.result_cache() {
+                    cache.put(mdl, fingerprint.into_boxed_str(), generation, now, &r);
+                }
+                Ok(())
+            });
+            Ok(r)
+        }
+    }
+}
+
+/// Run a statement that inserts, updates or deletes rows, noting how many rows it affected (unless it responds with
+/// the LSN of the change instead)
+#[inline(always)]
+fn _callgcs_write<A: ASTNode<'static> + core::fmt::Debug>(
+    g: &Global,
+    cstate: &mut ClientLocalState,
+    state: &mut State<'static, InplaceData>,
+    entity: impl FnOnce(&A) -> EntityIDRef<'static>,
+    with_lsn: impl FnOnce(&A) -> bool,
+    f: impl FnOnce(&Global, A) -> QueryResult<Response>,
+) -> QueryResult<Response> {
+    let a = parse(state)?;
+    quota::admit(g, cstate.username(), entity(&a), Access::Write)?;
+    let with_lsn = with_lsn(&a);
+    let r = f(g, a)?;
+    let affected = match &r {
+        _ if with_lsn => None,
+        Response::Empty
+        | Response::Serialized {
+            ty: ResponseType::Row,
+            ..
+        } => Some(1),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size,
+            ..
+        } => Some(*size as u64),
+        Response::UInt64(rows) => Some(*rows),
+        _ => None,
+    };
+    if let Some(affected) = affected {
+        cstate.set_rows_affected(affected);
+    }
+    Ok(r)
+}
+
 #[inline(always)]
 fn translate_ddl_result(x: Option<bool>) -> Response {
     match x {
@@ -111,14 +484,19 @@ async fn run_blocking_stmt(
         // all the actions here need root permission (but we do an exception for sysctl which allows status to be called by anyone)
         return Err(QueryError::SysPermissionDenied);
     }
-    state.ensure_minimum_for_blocking_stmt()?;
+    let sysctl = stmt == KeywordStmt::Sysctl;
+    // a sysctl command can be a single word (like `sysctl freeze`); the sysctl parser checks the length itself
+    if !(sysctl & state.not_exhausted()) {
+        state.ensure_minimum_for_blocking_stmt()?;
+    }
     /*
         IMPORTANT: DDL queries will NOT pick up the currently set space. instead EVERY DDL query must manually fully specify the entity that
         they want to manipulate. this prevents a whole set of exciting errors like dropping a model with the same model name from another space
     */
     state.unset_space();
-    let (a, b) = (&state.current()[0], &state.current()[1]);
-    let sysctl = stmt == KeywordStmt::Sysctl;
+    let a = &state.current()[0];
+    // only a sysctl can have a single token, and it never looks at `b`
+    let b = state.current().get(1).unwrap_or(a);
     let create = stmt == KeywordStmt::Create;
     let alter = stmt == KeywordStmt::Alter;
     let drop = stmt == KeywordStmt::Drop;
@@ -126,18 +504,24 @@ async fn run_blocking_stmt(
     let last_allow = Token![allow].eq(b);
     let last_if = Token![if].eq(b);
     let c_s = (create & Token![space].eq(a) & (last_id | last_if)) as u8 * 2;
-    let c_m = (create & Token![model].eq(a) & (last_id | last_if)) as u8 * 3;
+    let c_m = create & Token![model].eq(a) & (last_id | last_if);
+    let copy = c_m && CreateModelCopy::is_next(&state);
+    let c_mc = copy as u8 * 11;
+    let c_m = (c_m & !copy) as u8 * 3;
     let a_s = (alter & Token![space].eq(a) & last_id) as u8 * 4;
     let a_m = (alter & Token![model].eq(a) & last_id) as u8 * 5;
     let d_s = (drop & Token![space].eq(a) & (last_id | last_allow | last_if)) as u8 * 6;
     let d_m = (drop & Token![model].eq(a) & (last_id | last_allow | last_if)) as u8 * 7;
-    let fc = sysctl as u8 | c_s | c_m | a_s | a_m | d_s | d_m;
+    let c_i = (create & Token![index].eq(a) & (last_id | last_if)) as u8 * 8;
+    let d_i = (drop & Token![index].eq(a) & (last_id | last_if)) as u8 * 9;
+    let c_v = (create & a.ident_eq("view") & (last_id | last_if)) as u8 * 10;
+    let fc = sysctl as u8 | c_s | c_m | a_s | a_m | d_s | d_m | c_i | d_i | c_v | c_mc;
     state.cursor_ahead_if(!sysctl);
     static BLK_EXEC: [fn(
         Global,
         &ClientLocalState,
         &mut State<'static, InplaceData>,
-    ) -> QueryResult<Response>; 8] = [
+    ) -> QueryResult<Response>; 12] = [
         |_, _, _| Err(QueryError::QLUnknownStatement),
         blocking_exec_sysctl,
         |g, _, t| {
@@ -171,19 +555,74 @@ async fn run_blocking_stmt(
                 translate_ddl_result,
             )
         },
+        |g, c, t| _callgcs(&g, c, t, create_index),
+        |g, _, t| {
+            _callgs_map(
+                &g,
+                t,
+                ModelData::transactional_exec_drop_index,
+                translate_ddl_result,
+            )
+        },
+        |g, _, t| {
+            _callgs_map(
+                &g,
+                t,
+                ModelData::transactional_exec_create_view,
+                translate_ddl_result,
+            )
+        },
+        |g, _, t| {
+            _callgs_map(
+                &g,
+                t,
+                ModelData::transactional_exec_create_copy,
+                translate_ddl_result,
+            )
+        },
     ];
-    let r = unsafe {
+    unsafe {
         // UNSAFE(@ohsayan): the only await is within this block
         let c_glob = global.clone();
         let static_cstate: &'static ClientLocalState = core::mem::transmute(cstate);
         let static_state: &'static mut State<'static, InplaceData> =
             core::mem::transmute(&mut state);
-        tokio::task::spawn_blocking(move || {
-            BLK_EXEC[fc as usize](c_glob, static_cstate, static_state)
-        })
-        .await
-    };
-    r.unwrap()
+        spawn_blocking_carry(move || BLK_EXEC[fc as usize](c_glob, static_cstate, static_state))
+            .await
+    }
+}
+
+/// Run `f` on a blocking task, carrying the error detail, notices and parse time that it leaves behind over to this
+/// thread
+async fn spawn_blocking_carry<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    let (r, detail, notices, parsed) = tokio::task::spawn_blocking(move || {
+        ErrorDetail::clear();
+        Notice::clear();
+        profile::clear_parsed();
+        let r = f();
+        (
+            r,
+            ErrorDetail::take_any(),
+            Notice::take_all(),
+            profile::take_parsed(),
+        )
+    })
+    .await
+    .unwrap();
+    ErrorDetail::restore(detail);
+    Notice::restore(notices);
+    profile::restore_parsed(parsed);
+    r
+}
+
+fn create_index(g: &Global, cstate: &ClientLocalState, stmt: CreateIndex) -> QueryResult<Response> {
+    if stmt.background {
+        // respond with the id of the job that builds the index
+        ModelData::transactional_exec_create_index_in_background(g, cstate.username(), stmt)
+            .map(|job| job.map_or(Response::Bool(false), Response::UInt64))
+    } else {
+        ModelData::transactional_exec_create_index(g, stmt).map(translate_ddl_result)
+    }
 }
 
 fn blocking_exec_sysctl(
@@ -191,8 +630,8 @@ fn blocking_exec_sysctl(
     cstate: &ClientLocalState,
     state: &mut State<'static, InplaceData>,
 ) -> QueryResult<Response> {
-    let r = ASTNode::parse_from_state_hardened(state)?;
-    super::dcl::exec(g, cstate, r).map(|_| Response::Empty)
+    let r = parse(state)?;
+    super::dcl::exec(g, cstate, r)
 }
 
 /*
@@ -204,7 +643,7 @@ fn cstate_use(
     cstate: &mut ClientLocalState,
     state: &mut State<'static, InplaceData>,
 ) -> QueryResult<Response> {
-    let use_c = Use::parse_from_state_hardened(state)?;
+    let use_c: Use = parse(state)?;
     match use_c {
         Use::Null => cstate.unset_cs(),
         Use::Space(new_space) => {
@@ -239,6 +678,344 @@ fn cstate_use(
     Ok(Response::Empty)
 }
 
+/// Override a setting for the rest of the session (see [`session`])
+fn run_set(cstate: &mut ClientLocalState, state: State<'_, InplaceData>) -> QueryResult<Response> {
+    let mut state: State<'static, InplaceData> = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    let set: SessionSet = parse(&mut state)?;
+    cstate.session_mut().set(&set)?;
+    Ok(Response::Empty)
+}
+
+/// Describe how a `SELECT` or `SELECT ALL` would be executed, without running it
+fn run_explain(global: &Global, mut state: State<'_, InplaceData>) -> QueryResult<Response> {
+    if state.try_statement()? != KeywordStmt::Select {
+        return Err(QueryError::QLUnknownStatement);
+    }
+    let all = state.cursor_rounded_eq(Token![all]);
+    state.cursor_ahead_if(all);
+    let mut state = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    if all {
+        _callgs(global, &mut state, dml::explain_select_all_resp)
+    } else {
+        _callgs(global, &mut state, dml::explain_select_resp)
+    }
+}
+
+/// Run a bounded breadth-first expansion over an adjacency list field
+fn run_traverse(
+    global: &Global,
+    cstate: &ClientLocalState,
+    state: State<'_, InplaceData>,
+) -> QueryResult<Response> {
+    let mut state = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    _callgs(global, &mut state, |g, traverse: TraverseStatement| {
+        quota::admit(g, cstate.username(), traverse.entity(), Access::Read)?;
+        dml::traverse_resp(g, traverse)
+    })
+}
+
+/// Run an `EVAL`, responding with the outcome of its condition (and noting if its action changed a row)
+fn run_eval(
+    global: &Global,
+    cstate: &mut ClientLocalState,
+    state: State<'_, InplaceData>,
+) -> QueryResult<Response> {
+    let mut state: State<'static, InplaceData> = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    let eval: EvalStatement = parse(&mut state)?;
+    quota::admit(global, cstate.username(), eval.entity(), Access::Write)?;
+    let _session = session::enter(cstate.session());
+    let (outcome, changed) = dml::eval(global, eval)?;
+    cstate.set_rows_affected(changed);
+    Ok(Response::Bool(outcome))
+}
+
+/// The maximum number of blocking tasks that the lookups of a `FETCH` are spread across
+const FETCH_MAX_CONCURRENCY: usize = 4;
+
+/// Run the point lookups of a `FETCH` concurrently, returning one row per lookup (in the order requested). The fetch
+/// counts as a read on every model that it looks up rows in
+async fn run_fetch(
+    global: &Global,
+    cstate: &ClientLocalState,
+    state: State<'_, InplaceData>,
+) -> QueryResult<Response> {
+    let mut state: State<'static, InplaceData> = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    let fetch: FetchStatement = parse(&mut state)?;
+    let items = fetch.items();
+    let mut entities: Vec<_> = items.iter().map(|(entity, _)| *entity).collect();
+    entities.sort();
+    entities.dedup();
+    for entity in entities {
+        quota::admit(global, cstate.username(), entity, Access::Read)?;
+    }
+    let chunk_size = items.len().div_ceil(FETCH_MAX_CONCURRENCY);
+    let mut tasks = Vec::with_capacity(FETCH_MAX_CONCURRENCY);
+    for chunk in items.chunks(chunk_size) {
+        let g = global.clone();
+        // this future can be dropped (say, on shutdown) while the tasks still run, so they can't borrow the statement
+        let chunk: Vec<(Box<str>, Box<str>, Datacell)> = chunk
+            .iter()
+            .map(|(entity, key)| {
+                (
+                    entity.space().into(),
+                    entity.entity().into(),
+                    Datacell::from(key.clone()),
+                )
+            })
+            .collect();
+        tasks.push(tokio::task::spawn_blocking(move || {
+            let (entities, keys): (Vec<_>, Vec<_>) = chunk
+                .into_iter()
+                .map(|(space, model, key)| ((space, model), key))
+                .unzip();
+            let items: Vec<_> = entities
+                .iter()
+                .map(|(space, model)| EntityIDRef::new(space, model))
+                .zip(keys)
+                .collect();
+            dml::fetch(&g, &items)
+        }));
+    }
+    let mut data = Vec::new();
+    for task in tasks {
+        data.extend(task.await.map_err(|_| QueryError::SysServerError)?);
+    }
+    Ok(Response::Serialized {
+        ty: ResponseType::MultiRow,
+        size: items.len(),
+        data,
+    })
+}
+
+/// Run a foreign scan on a blocking task. This needs root since it reads from the local filesystem
+async fn run_select_file(
+    global: &Global,
+    cstate: &ClientLocalState,
+    state: State<'_, InplaceData>,
+) -> QueryResult<Response> {
+    if !cstate.is_root() {
+        return Err(QueryError::SysPermissionDenied);
+    }
+    let mut state: State<'static, InplaceData> = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    let select: SelectFileStatement = parse(&mut state)?;
+    let g = global.clone();
+    // NB: the task is awaited below, before the statement's tokens are dropped
+    spawn_blocking_carry(move || dml::select_file_resp(&g, select)).await
+}
+
+/// Create or drop one of the current user's stored statements. These are kept in the GNS, so this runs on a blocking
+/// task
+async fn run_stored_statement_ddl(
+    global: &Global,
+    cstate: &ClientLocalState,
+    state: State<'_, InplaceData>,
+) -> QueryResult<Response> {
+    let mut state: State<'static, InplaceData> = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    let create = Token![create].eq(state.read());
+    state.cursor_ahead_by(2);
+    let g = global.clone();
+    let username: Box<str> = cstate.username().into();
+    // NB: the tasks are awaited below, before the statement's tokens are dropped
+    if create {
+        let stmt: CreateStatement = parse(&mut state)?;
+        spawn_blocking_carry(move || dcl::create_statement(&g, &username, stmt)).await?;
+    } else {
+        let stmt: DropStatement = parse(&mut state)?;
+        spawn_blocking_carry(move || dcl::drop_statement(&g, &username, stmt)).await?;
+    }
+    Ok(Response::Empty)
+}
+
+/// Run one of the current user's stored statements, binding the arguments to its placeholders (in order). The body
+/// is lexed again with the arguments as its parameters, so it runs exactly like it would if the client sent it
+async fn run_execute(
+    global: &Global,
+    cstate: &mut ClientLocalState,
+    state: State<'_, InplaceData>,
+) -> QueryResult<Response> {
+    let mut state: State<'static, InplaceData> = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
+        core::mem::transmute(state)
+    };
+    let execute: ExecuteStatement = parse(&mut state)?;
+    let Some(stored) = global
+        .state()
+        .namespace()
+        .sys_db()
+        .statement(cstate.username(), execute.name())
+    else {
+        return Err(QueryError::QExecObjectNotFound);
+    };
+    if stored.params() != execute.args().len() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let mut params = vec![];
+    for arg in execute.args() {
+        lex::encode_param(&mut params, arg.as_ref());
+    }
+    let _spellings;
+    let mut tokens = SecureLexer::new_with_segments(stored.body().as_bytes(), &params).lex()?;
+    _spellings = names::resolve(global.state().namespace(), cstate.get_cs(), &mut tokens);
+    let mut state = State::new_inplace(&tokens);
+    state.set_space_maybe(unsafe {
+        // UNSAFE(@ohsayan): exclusively used within this scope
+        core::mem::transmute(cstate.get_cs())
+    });
+    let stmt = state.try_statement()?;
+    if (stmt < KeywordStmt::Insert)
+        | ((stmt == KeywordStmt::Select) && SelectFileStatement::is_next(&state))
+    {
+        // only DML can be stored, but we check again since this will run whatever it's given
+        return Err(QueryError::QLUnknownStatement);
+    }
+    match fractal::upstream::get() {
+        Some(upstream) => {
+            let body = stored.body().as_bytes();
+            run_nb_upstream(global, cstate, state, stmt, upstream, body, &params).await
+        }
+        None => run_nb(global, cstate, state, stmt),
+    }
+}
+
+/// Run a statement that doesn't block, on a model that might cache the upstream (see `core::model::upstream`): a
+/// point select of a stale row fetches it first, and an insert, update or delete (the query and parameters that the
+/// client sent) is forwarded
+async fn run_nb_upstream(
+    global: &Global,
+    cstate: &mut ClientLocalState,
+    state: State<'_, InplaceData>,
+    stmt: KeywordStmt,
+    upstream: &'static Upstream,
+    query: &[u8],
+    params: &[u8],
+) -> QueryResult<Response> {
+    // a copy is parsed to find the model (and the row), since the statement itself is parsed when it runs
+    let mut copy: State<'static, InplaceData> = unsafe {
+        // UNSAFE(@ohsayan): this is a lifetime issue with the token handle; the copy is dropped before the tokens
+        core::mem::transmute(State::new_inplace(state.current()))
+    };
+    copy.set_space_maybe(unsafe {
+        // UNSAFE(@ohsayan): exclusively used within this scope
+        core::mem::transmute(cstate.get_cs())
+    });
+    let entity = match stmt {
+        KeywordStmt::Select
+            if !copy.cursor_rounded_eq(Token![all]) && !Aggregate::is_next(&copy) =>
+        {
+            if let Ok(mut select) = SelectStatement::parse_from_state_hardened(&mut copy) {
+                let entity = select.entity();
+                if let Some(fetch) = dml::upstream_fetch(global, entity, select.clauses_mut())? {
+                    upstream_read(global, upstream, entity, fetch).await?;
+                }
+            }
+            return run_nb(global, cstate, state, stmt);
+        }
+        KeywordStmt::Insert => {
+            InsertStatement::parse_from_state_hardened(&mut copy).map(|s| s.entity())
+        }
+        KeywordStmt::Update => {
+            UpdateStatement::parse_from_state_hardened(&mut copy).map(|s| s.entity())
+        }
+        KeywordStmt::Delete => {
+            DeleteStatement::parse_from_state_hardened(&mut copy).map(|s| s.entity())
+        }
+        _ => return run_nb(global, cstate, state, stmt),
+    };
+    // if it doesn't parse (or the model doesn't exist), running it locally fails just the same
+    let Ok(entity) = entity else {
+        return run_nb(global, cstate, state, stmt);
+    };
+    let Ok(Some(writes)) = dml::upstream_writes(global, entity) else {
+        return run_nb(global, cstate, state, stmt);
+    };
+    let space = cstate.get_cs().map(Box::<str>::from);
+    match writes {
+        UpstreamWrites::Through => {
+            let (f_query, f_params) = (query.to_vec(), params.to_vec());
+            let forwarded =
+                spawn_blocking_carry(move || upstream.run(space.as_deref(), &f_query, &f_params))
+                    .await;
+            let upstream_response = match forwarded {
+                Ok(Ok(r)) => r,
+                Ok(Err(code)) => return Err(QueryError::SysUpstreamError.with_detail("code", code)),
+                Err(_) => return Err(QueryError::SysUpstreamError),
+            };
+            match run_nb(global, cstate, state, stmt) {
+                Ok(r) => Ok(r),
+                Err(_) => {
+                    // the upstream took it, so it's the local copy that's off (say, an update of a row that was never
+                    // fetched)
+                    ErrorDetail::clear();
+                    dml::upstream_forget(global, entity);
+                    Ok(upstream_response)
+                }
+            }
+        }
+        UpstreamWrites::Behind => {
+            if !upstream.can_queue() {
+                return Err(
+                    QueryError::SysUpstreamError.with_detail("queue", fractal::upstream::QUEUE_MAX)
+                );
+            }
+            let r = run_nb(global, cstate, state, stmt)?;
+            upstream.queue(space.as_deref(), query, params);
+            Ok(r)
+        }
+    }
+}
+
+/// Fetch a row from the upstream into the model's local copy. If the upstream can't be reached, the local copy of the
+/// row is served instead (if there is one)
+async fn upstream_read(
+    global: &Global,
+    upstream: &'static Upstream,
+    entity: EntityIDRef<'_>,
+    fetch: dml::UpstreamFetch,
+) -> QueryResult<()> {
+    let (f_query, f_params) = (fetch.query.clone(), fetch.params.clone());
+    let fetched =
+        spawn_blocking_carry(move || upstream.run(None, f_query.as_bytes(), &f_params)).await;
+    match fetched {
+        Ok(Ok(r)) => match fractal::upstream::decode_row(&r) {
+            Some(row) => dml::upstream_refresh(global, entity, fetch, Some(row)),
+            None => Err(QueryError::SysUpstreamError),
+        },
+        Ok(Err(code)) if code == QueryError::QExecDmlRowNotFound.value_u8() as u16 => {
+            dml::upstream_refresh(global, entity, fetch, None)
+        }
+        Ok(Err(code)) => Err(QueryError::SysUpstreamError.with_detail("code", code)),
+        Err(_) if fetch.cached() => {
+            Notice::raise(
+                NoticeCode::UpstreamUnavailable,
+                "the upstream couldn't be reached, so the row was served from the local copy",
+            );
+            Ok(())
+        }
+        Err(_) => Err(QueryError::SysUpstreamError),
+    }
+}
+
 fn run_nb(
     global: &Global,
     cstate: &mut ClientLocalState,
@@ -246,25 +1023,55 @@ fn run_nb(
     stmt: KeywordStmt,
 ) -> QueryResult<Response> {
     let stmt_c = stmt.value_u8() - KeywordStmt::Use.value_u8();
+    let _session = session::enter(cstate.session());
     static F: [fn(
         &Global,
         &mut ClientLocalState,
         &mut State<'static, InplaceData>,
-    ) -> QueryResult<Response>; 9] = [
+    ) -> QueryResult<Response>; 10] = [
         cstate_use, // use
         |g, c, s| _callgcs(g, c, s, ddl_misc::inspect),
-        |_, _, _| Err(QueryError::QLUnknownStatement), // describe
-        |g, _, s| _callgs(g, s, dml::insert_resp),
-        |g, _, s| _callgs(g, s, dml::select_resp),
-        |g, _, s| _callgs(g, s, dml::update_resp),
-        |g, _, s| _callgs(g, s, dml::delete_resp),
-        |_, _, _| Err(QueryError::QLUnknownStatement), // exists
-        |g, _, s| _callgs(g, s, dml::select_all_resp),
+        |g, _, s| _callgs(g, s, ddl_misc::describe),
+        |g, c, s| {
+            let (entity, with_lsn) = (InsertStatement::entity, InsertStatement::with_lsn);
+            _callgcs_write(g, c, s, entity, with_lsn, dml::insert_resp)
+        },
+        |g, c, s| {
+            let entity = SelectStatement::entity;
+            _callgcs_cached(g, c, s, "sel", entity, |_| true, dml::select_resp)
+        },
+        |g, c, s| {
+            let (entity, with_lsn) = (UpdateStatement::entity, UpdateStatement::with_lsn);
+            _callgcs_write(g, c, s, entity, with_lsn, dml::update_resp)
+        },
+        |g, c, s| {
+            let (entity, with_lsn) = (DeleteStatement::entity, DeleteStatement::with_lsn);
+            _callgcs_write(g, c, s, entity, with_lsn, dml::delete_resp)
+        },
+        |g, c, s| {
+            _callgcs(g, c, s, |g, c, exists: ExistsStatement| {
+                quota::admit(g, c.username(), exists.entity(), Access::Read)?;
+                dml::exists_resp(g, exists)
+            })
+        },
+        |g, c, s| {
+            let entity = |s: &SelectAllStatement<'static>| s.entity;
+            let idempotent = |s: &SelectAllStatement| s.sample.is_none();
+            _callgcs_cached(g, c, s, "selall", entity, idempotent, dml::select_all_resp)
+        },
+        |g, c, s| {
+            let entity = SelectAggregateStatement::entity;
+            _callgcs_cached(g, c, s, "agg", entity, |_| true, dml::select_aggregate_resp)
+        },
     ];
     {
         let n_offset_adjust = (stmt == KeywordStmt::Select) & state.cursor_rounded_eq(Token![all]);
         state.cursor_ahead_if(n_offset_adjust);
-        let corrected_offset = (n_offset_adjust as u8 * 8) | (stmt_c * (!n_offset_adjust as u8));
+        let aggregate =
+            (stmt == KeywordStmt::Select) & !n_offset_adjust & Aggregate::is_next(&state);
+        let corrected_offset = (n_offset_adjust as u8 * 8)
+            | (aggregate as u8 * 9)
+            | (stmt_c * (!(n_offset_adjust | aggregate) as u8));
         let mut state = unsafe {
             // UNSAFE(@ohsayan): this is a lifetime issue with the token handle
             core::mem::transmute(state)
@@ -0,0 +1,123 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    freezes
+    ---
+    `sysctl freeze [space <space>]` makes the instance (or a space) reject writes until it is unfrozen; reads keep
+    working. writes are rejected at dispatch, before anything is changed, so once a freeze returns (it also writes out
+    everything that is pending for the frozen models) the data directory doesn't change under an external snapshot.
+    freezes only live in memory, so a restart lifts them. a snapshot (see `core::snapshot`) freezes the instance with a
+    hold of its own while it copies the journals, so an unfreeze doesn't lift its freeze (and it doesn't lift one that
+    was made with `sysctl freeze`)
+*/
+
+use {
+    crate::{
+        engine::error::{QueryError, QueryResult},
+        util::compiler,
+    },
+    parking_lot::RwLock,
+    std::{
+        collections::BTreeSet,
+        sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+#[derive(Debug, Default)]
+pub struct Freeze {
+    instance: AtomicBool,
+    /// the number of holds (see [`Freeze::hold`])
+    holds: AtomicUsize,
+    spaces: RwLock<BTreeSet<Box<str>>>,
+}
+
+/// Keeps the instance frozen until it is dropped
+#[derive(Debug)]
+pub struct FreezeHold<'a>(&'a Freeze);
+
+impl Drop for FreezeHold<'_> {
+    fn drop(&mut self) {
+        self.0.holds.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Freeze {
+    pub fn freeze_instance(&self) {
+        self.instance.store(true, Ordering::Release)
+    }
+    pub fn unfreeze_instance(&self) {
+        self.instance.store(false, Ordering::Release)
+    }
+    pub fn freeze_space(&self, space: &str) {
+        self.spaces.write().insert(space.into());
+    }
+    pub fn unfreeze_space(&self, space: &str) {
+        self.spaces.write().remove(space);
+    }
+    /// Freeze the instance until the returned hold is dropped, whatever is frozen or unfrozen in the meantime
+    pub fn hold(&self) -> FreezeHold<'_> {
+        self.holds.fetch_add(1, Ordering::AcqRel);
+        FreezeHold(self)
+    }
+    pub fn is_instance_frozen(&self) -> bool {
+        self.instance.load(Ordering::Acquire) | (self.holds.load(Ordering::Acquire) != 0)
+    }
+    pub fn is_frozen(&self) -> bool {
+        self.is_instance_frozen() || !self.spaces.read().is_empty()
+    }
+    /// Fails if the instance is frozen
+    pub fn check_instance(&self) -> QueryResult<()> {
+        if compiler::likely(!self.is_instance_frozen()) {
+            Ok(())
+        } else {
+            compiler::cold_rerr(QueryError::SysFrozen)
+        }
+    }
+    /// Fails if the instance or the given space is frozen
+    pub fn check(&self, space: &str) -> QueryResult<()> {
+        self.check_instance()?;
+        if compiler::likely(!self.spaces.read().contains(space)) {
+            Ok(())
+        } else {
+            compiler::cold_rerr(QueryError::SysFrozen)
+        }
+    }
+    /// Returns what is frozen as a JSON object
+    pub fn describe(&self) -> String {
+        let spaces: Vec<String> = self
+            .spaces
+            .read()
+            .iter()
+            .map(|space| format!("\"{space}\""))
+            .collect();
+        format!(
+            "{{\"instance\":{},\"spaces\":[{}]}}",
+            self.is_instance_frozen(),
+            spaces.join(",")
+        )
+    }
+}
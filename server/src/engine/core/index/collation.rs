@@ -0,0 +1,210 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    collations
+    ---
+    A collation decides how the strings in a secondary index are compared: the string components of an index key are
+    collated before they're encoded (see [`crate::engine::idx::tuple::TupleKey`]), so both the order of the entries and
+    which values a lookup finds follow the collation.
+
+    NOTE(@ohsayan): we don't ship the Unicode normalization tables, so `nfc` only composes the Latin letters (the Latin-1
+    Supplement and Latin Extended-A/B blocks) that are written as a base letter followed by combining diacritics, which
+    is what NFC does for Latin text. Other scripts are left as they are
+*/
+
+use std::borrow::Cow;
+
+/// How the strings in a secondary index are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, sky_macros::EnumMethods)]
+#[repr(u8)]
+pub enum Collation {
+    /// Compare the bytes of the strings
+    #[default]
+    Binary = 0,
+    /// Compare the lowercased strings
+    NoCase = 1,
+    /// Compare the NFC normalized strings, so that a letter with a combining diacritic matches its precomposed form
+    Nfc = 2,
+}
+
+impl Collation {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Binary => "binary",
+            Self::NoCase => "nocase",
+            Self::Nfc => "nfc",
+        }
+    }
+    /// Returns the collation stored as `v` (see [`Self::value_u8`])
+    pub const fn try_from_raw(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Binary),
+            1 => Some(Self::NoCase),
+            2 => Some(Self::Nfc),
+            _ => None,
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "binary" => Some(Self::Binary),
+            "nocase" => Some(Self::NoCase),
+            "nfc" => Some(Self::Nfc),
+            _ => None,
+        }
+    }
+    /// Returns the string that is compared in place of `s`
+    pub fn collate<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        match self {
+            Self::NoCase if s.chars().any(char::is_uppercase) => Cow::Owned(s.to_lowercase()),
+            Self::Nfc if s.chars().any(is_combining_diacritic) => Cow::Owned(compose(s)),
+            _ => Cow::Borrowed(s),
+        }
+    }
+}
+
+fn is_combining_diacritic(c: char) -> bool {
+    ('\u{300}'..='\u{36f}').contains(&c)
+}
+
+/// Compose every base letter and combining diacritic that has a precomposed form. Since a precomposed letter can
+/// itself be a base letter (`Ü` + macron is `Ǖ`), diacritics are composed one at a time
+fn compose(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut last = None;
+    for c in s.chars() {
+        match last.and_then(|base| compose_pair(base, c)) {
+            Some(composed) => {
+                ret.pop();
+                ret.push(composed);
+                last = Some(composed);
+            }
+            None => {
+                ret.push(c);
+                last = Some(c);
+            }
+        }
+    }
+    ret
+}
+
+fn compose_pair(base: char, mark: char) -> Option<char> {
+    COMPOSITIONS
+        .binary_search_by(|&(b, m, _)| (b, m).cmp(&(base, mark)))
+        .ok()
+        .map(|i| COMPOSITIONS[i].2)
+}
+
+/// `(base, combining diacritic, precomposed)`, sorted by base and then diacritic (generated from the canonical
+/// decompositions in the Unicode Character Database)
+#[rustfmt::skip]
+const COMPOSITIONS: &[(char, char, char)] = &[
+    ('A', '\u{300}', 'À'), ('A', '\u{301}', 'Á'), ('A', '\u{302}', 'Â'), ('A', '\u{303}', 'Ã'), ('A', '\u{304}', 'Ā'),
+    ('A', '\u{306}', 'Ă'), ('A', '\u{307}', 'Ȧ'), ('A', '\u{308}', 'Ä'), ('A', '\u{30a}', 'Å'), ('A', '\u{30c}', 'Ǎ'),
+    ('A', '\u{30f}', 'Ȁ'), ('A', '\u{311}', 'Ȃ'), ('A', '\u{328}', 'Ą'), ('C', '\u{301}', 'Ć'), ('C', '\u{302}', 'Ĉ'),
+    ('C', '\u{307}', 'Ċ'), ('C', '\u{30c}', 'Č'), ('C', '\u{327}', 'Ç'), ('D', '\u{30c}', 'Ď'), ('E', '\u{300}', 'È'),
+    ('E', '\u{301}', 'É'), ('E', '\u{302}', 'Ê'), ('E', '\u{304}', 'Ē'), ('E', '\u{306}', 'Ĕ'), ('E', '\u{307}', 'Ė'),
+    ('E', '\u{308}', 'Ë'), ('E', '\u{30c}', 'Ě'), ('E', '\u{30f}', 'Ȅ'), ('E', '\u{311}', 'Ȇ'), ('E', '\u{327}', 'Ȩ'),
+    ('E', '\u{328}', 'Ę'), ('G', '\u{301}', 'Ǵ'), ('G', '\u{302}', 'Ĝ'), ('G', '\u{306}', 'Ğ'), ('G', '\u{307}', 'Ġ'),
+    ('G', '\u{30c}', 'Ǧ'), ('G', '\u{327}', 'Ģ'), ('H', '\u{302}', 'Ĥ'), ('H', '\u{30c}', 'Ȟ'), ('I', '\u{300}', 'Ì'),
+    ('I', '\u{301}', 'Í'), ('I', '\u{302}', 'Î'), ('I', '\u{303}', 'Ĩ'), ('I', '\u{304}', 'Ī'), ('I', '\u{306}', 'Ĭ'),
+    ('I', '\u{307}', 'İ'), ('I', '\u{308}', 'Ï'), ('I', '\u{30c}', 'Ǐ'), ('I', '\u{30f}', 'Ȉ'), ('I', '\u{311}', 'Ȋ'),
+    ('I', '\u{328}', 'Į'), ('J', '\u{302}', 'Ĵ'), ('K', '\u{30c}', 'Ǩ'), ('K', '\u{327}', 'Ķ'), ('L', '\u{301}', 'Ĺ'),
+    ('L', '\u{30c}', 'Ľ'), ('L', '\u{327}', 'Ļ'), ('N', '\u{300}', 'Ǹ'), ('N', '\u{301}', 'Ń'), ('N', '\u{303}', 'Ñ'),
+    ('N', '\u{30c}', 'Ň'), ('N', '\u{327}', 'Ņ'), ('O', '\u{300}', 'Ò'), ('O', '\u{301}', 'Ó'), ('O', '\u{302}', 'Ô'),
+    ('O', '\u{303}', 'Õ'), ('O', '\u{304}', 'Ō'), ('O', '\u{306}', 'Ŏ'), ('O', '\u{307}', 'Ȯ'), ('O', '\u{308}', 'Ö'),
+    ('O', '\u{30b}', 'Ő'), ('O', '\u{30c}', 'Ǒ'), ('O', '\u{30f}', 'Ȍ'), ('O', '\u{311}', 'Ȏ'), ('O', '\u{31b}', 'Ơ'),
+    ('O', '\u{328}', 'Ǫ'), ('R', '\u{301}', 'Ŕ'), ('R', '\u{30c}', 'Ř'), ('R', '\u{30f}', 'Ȑ'), ('R', '\u{311}', 'Ȓ'),
+    ('R', '\u{327}', 'Ŗ'), ('S', '\u{301}', 'Ś'), ('S', '\u{302}', 'Ŝ'), ('S', '\u{30c}', 'Š'), ('S', '\u{326}', 'Ș'),
+    ('S', '\u{327}', 'Ş'), ('T', '\u{30c}', 'Ť'), ('T', '\u{326}', 'Ț'), ('T', '\u{327}', 'Ţ'), ('U', '\u{300}', 'Ù'),
+    ('U', '\u{301}', 'Ú'), ('U', '\u{302}', 'Û'), ('U', '\u{303}', 'Ũ'), ('U', '\u{304}', 'Ū'), ('U', '\u{306}', 'Ŭ'),
+    ('U', '\u{308}', 'Ü'), ('U', '\u{30a}', 'Ů'), ('U', '\u{30b}', 'Ű'), ('U', '\u{30c}', 'Ǔ'), ('U', '\u{30f}', 'Ȕ'),
+    ('U', '\u{311}', 'Ȗ'), ('U', '\u{31b}', 'Ư'), ('U', '\u{328}', 'Ų'), ('W', '\u{302}', 'Ŵ'), ('Y', '\u{301}', 'Ý'),
+    ('Y', '\u{302}', 'Ŷ'), ('Y', '\u{304}', 'Ȳ'), ('Y', '\u{308}', 'Ÿ'), ('Z', '\u{301}', 'Ź'), ('Z', '\u{307}', 'Ż'),
+    ('Z', '\u{30c}', 'Ž'), ('a', '\u{300}', 'à'), ('a', '\u{301}', 'á'), ('a', '\u{302}', 'â'), ('a', '\u{303}', 'ã'),
+    ('a', '\u{304}', 'ā'), ('a', '\u{306}', 'ă'), ('a', '\u{307}', 'ȧ'), ('a', '\u{308}', 'ä'), ('a', '\u{30a}', 'å'),
+    ('a', '\u{30c}', 'ǎ'), ('a', '\u{30f}', 'ȁ'), ('a', '\u{311}', 'ȃ'), ('a', '\u{328}', 'ą'), ('c', '\u{301}', 'ć'),
+    ('c', '\u{302}', 'ĉ'), ('c', '\u{307}', 'ċ'), ('c', '\u{30c}', 'č'), ('c', '\u{327}', 'ç'), ('d', '\u{30c}', 'ď'),
+    ('e', '\u{300}', 'è'), ('e', '\u{301}', 'é'), ('e', '\u{302}', 'ê'), ('e', '\u{304}', 'ē'), ('e', '\u{306}', 'ĕ'),
+    ('e', '\u{307}', 'ė'), ('e', '\u{308}', 'ë'), ('e', '\u{30c}', 'ě'), ('e', '\u{30f}', 'ȅ'), ('e', '\u{311}', 'ȇ'),
+    ('e', '\u{327}', 'ȩ'), ('e', '\u{328}', 'ę'), ('g', '\u{301}', 'ǵ'), ('g', '\u{302}', 'ĝ'), ('g', '\u{306}', 'ğ'),
+    ('g', '\u{307}', 'ġ'), ('g', '\u{30c}', 'ǧ'), ('g', '\u{327}', 'ģ'), ('h', '\u{302}', 'ĥ'), ('h', '\u{30c}', 'ȟ'),
+    ('i', '\u{300}', 'ì'), ('i', '\u{301}', 'í'), ('i', '\u{302}', 'î'), ('i', '\u{303}', 'ĩ'), ('i', '\u{304}', 'ī'),
+    ('i', '\u{306}', 'ĭ'), ('i', '\u{308}', 'ï'), ('i', '\u{30c}', 'ǐ'), ('i', '\u{30f}', 'ȉ'), ('i', '\u{311}', 'ȋ'),
+    ('i', '\u{328}', 'į'), ('j', '\u{302}', 'ĵ'), ('j', '\u{30c}', 'ǰ'), ('k', '\u{30c}', 'ǩ'), ('k', '\u{327}', 'ķ'),
+    ('l', '\u{301}', 'ĺ'), ('l', '\u{30c}', 'ľ'), ('l', '\u{327}', 'ļ'), ('n', '\u{300}', 'ǹ'), ('n', '\u{301}', 'ń'),
+    ('n', '\u{303}', 'ñ'), ('n', '\u{30c}', 'ň'), ('n', '\u{327}', 'ņ'), ('o', '\u{300}', 'ò'), ('o', '\u{301}', 'ó'),
+    ('o', '\u{302}', 'ô'), ('o', '\u{303}', 'õ'), ('o', '\u{304}', 'ō'), ('o', '\u{306}', 'ŏ'), ('o', '\u{307}', 'ȯ'),
+    ('o', '\u{308}', 'ö'), ('o', '\u{30b}', 'ő'), ('o', '\u{30c}', 'ǒ'), ('o', '\u{30f}', 'ȍ'), ('o', '\u{311}', 'ȏ'),
+    ('o', '\u{31b}', 'ơ'), ('o', '\u{328}', 'ǫ'), ('r', '\u{301}', 'ŕ'), ('r', '\u{30c}', 'ř'), ('r', '\u{30f}', 'ȑ'),
+    ('r', '\u{311}', 'ȓ'), ('r', '\u{327}', 'ŗ'), ('s', '\u{301}', 'ś'), ('s', '\u{302}', 'ŝ'), ('s', '\u{30c}', 'š'),
+    ('s', '\u{326}', 'ș'), ('s', '\u{327}', 'ş'), ('t', '\u{30c}', 'ť'), ('t', '\u{326}', 'ț'), ('t', '\u{327}', 'ţ'),
+    ('u', '\u{300}', 'ù'), ('u', '\u{301}', 'ú'), ('u', '\u{302}', 'û'), ('u', '\u{303}', 'ũ'), ('u', '\u{304}', 'ū'),
+    ('u', '\u{306}', 'ŭ'), ('u', '\u{308}', 'ü'), ('u', '\u{30a}', 'ů'), ('u', '\u{30b}', 'ű'), ('u', '\u{30c}', 'ǔ'),
+    ('u', '\u{30f}', 'ȕ'), ('u', '\u{311}', 'ȗ'), ('u', '\u{31b}', 'ư'), ('u', '\u{328}', 'ų'), ('w', '\u{302}', 'ŵ'),
+    ('y', '\u{301}', 'ý'), ('y', '\u{302}', 'ŷ'), ('y', '\u{304}', 'ȳ'), ('y', '\u{308}', 'ÿ'), ('z', '\u{301}', 'ź'),
+    ('z', '\u{307}', 'ż'), ('z', '\u{30c}', 'ž'), ('Ä', '\u{304}', 'Ǟ'), ('Å', '\u{301}', 'Ǻ'), ('Æ', '\u{301}', 'Ǽ'),
+    ('Æ', '\u{304}', 'Ǣ'), ('Õ', '\u{304}', 'Ȭ'), ('Ö', '\u{304}', 'Ȫ'), ('Ø', '\u{301}', 'Ǿ'), ('Ü', '\u{300}', 'Ǜ'),
+    ('Ü', '\u{301}', 'Ǘ'), ('Ü', '\u{304}', 'Ǖ'), ('Ü', '\u{30c}', 'Ǚ'), ('ä', '\u{304}', 'ǟ'), ('å', '\u{301}', 'ǻ'),
+    ('æ', '\u{301}', 'ǽ'), ('æ', '\u{304}', 'ǣ'), ('õ', '\u{304}', 'ȭ'), ('ö', '\u{304}', 'ȫ'), ('ø', '\u{301}', 'ǿ'),
+    ('ü', '\u{300}', 'ǜ'), ('ü', '\u{301}', 'ǘ'), ('ü', '\u{304}', 'ǖ'), ('ü', '\u{30c}', 'ǚ'), ('Ʒ', '\u{30c}', 'Ǯ'),
+    ('Ǫ', '\u{304}', 'Ǭ'), ('ǫ', '\u{304}', 'ǭ'), ('Ȧ', '\u{304}', 'Ǡ'), ('ȧ', '\u{304}', 'ǡ'), ('Ȯ', '\u{304}', 'Ȱ'),
+    ('ȯ', '\u{304}', 'ȱ'), ('ʒ', '\u{30c}', 'ǯ'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::Collation;
+
+    #[test]
+    fn nocase() {
+        assert_eq!(Collation::NoCase.collate("Sayan"), "sayan");
+        assert_eq!(Collation::NoCase.collate("ÉCOLE"), "école");
+        assert_eq!(Collation::Binary.collate("Sayan"), "Sayan");
+    }
+
+    #[test]
+    fn nfc() {
+        assert_eq!(Collation::Nfc.collate("e\u{301}cole"), "école");
+        assert_eq!(Collation::Nfc.collate("U\u{308}\u{304}"), "Ǖ");
+        // nothing to compose
+        assert_eq!(Collation::Nfc.collate("école"), "école");
+        assert_eq!(Collation::Nfc.collate("\u{301}x"), "\u{301}x");
+        assert_eq!(Collation::Binary.collate("e\u{301}"), "e\u{301}");
+    }
+
+    #[test]
+    fn names() {
+        for collation in [Collation::Binary, Collation::NoCase, Collation::Nfc] {
+            assert_eq!(Collation::from_name(collation.name()), Some(collation));
+            assert_eq!(
+                Collation::try_from_raw(collation.value_u8()),
+                Some(collation)
+            );
+        }
+        assert_eq!(Collation::from_name("latin1"), None);
+    }
+}
@@ -33,10 +33,11 @@ use {
             lit::Lit,
             tag::{DataTag, TagUnique},
         },
-        idx::meta::Comparable,
+        idx::meta::{hash::HasherRawFx, Comparable},
         mem::{self, DwordNN, DwordQN, SpecialPaddedWord, WordIO, ZERO_BLOCK},
     },
     core::{
+        cmp::Ordering,
         fmt,
         hash::{Hash, Hasher},
         mem::ManuallyDrop,
@@ -52,7 +53,7 @@ pub struct PrimaryIndexKey {
 impl Clone for PrimaryIndexKey {
     fn clone(&self) -> Self {
         match self.tag {
-            TagUnique::SignedInt | TagUnique::UnsignedInt => {
+            TagUnique::Bin | TagUnique::Str => {
                 let (qw, nw) = self.data.dwordqn_load_qw_nw();
                 unsafe {
                     let slice = slice::from_raw_parts(nw as *const u8, qw as _);
@@ -63,7 +64,7 @@ impl Clone for PrimaryIndexKey {
                     }
                 }
             }
-            TagUnique::Bin | TagUnique::Str => Self {
+            TagUnique::SignedInt | TagUnique::UnsignedInt => Self {
                 tag: self.tag,
                 data: unsafe { core::mem::transmute_copy(&self.data) },
             },
@@ -76,6 +77,21 @@ impl PrimaryIndexKey {
     pub fn tag(&self) -> TagUnique {
         self.tag
     }
+    /// Returns the partition (out of the given number of partitions) that this key belongs to. The hash function is
+    /// unseeded, so a key is always mapped to the same partition (and hence the same batch file) across restarts
+    pub fn partition(&self, partitions: usize) -> usize {
+        let mut hasher = HasherRawFx::<u64>::new();
+        hasher.write_u8(self.tag.value_u8());
+        match self.tag {
+            TagUnique::Bin | TagUnique::Str => hasher.write(self.virtual_block()),
+            _ => hasher.write_u64(unsafe {
+                // UNSAFE(@ohsayan): +tagck
+                self.read_uint()
+            }),
+        }
+        // use the high bits since they're better mixed
+        ((hasher.finish() as u128 * partitions as u128) >> 64) as usize
+    }
 }
 
 impl PrimaryIndexKey {
@@ -121,10 +137,21 @@ impl PrimaryIndexKey {
 }
 
 impl PrimaryIndexKey {
-    #[cfg(test)]
     pub fn try_from_dc(dc: Datacell) -> Option<Self> {
         Self::check(&dc).then(|| unsafe { Self::new_from_dc(dc) })
     }
+    /// Returns an owned key with a copy of the cell's data, if the cell is an eligible candidate key
+    pub fn try_clone_from_dc(dc: &Datacell) -> Option<Self> {
+        Self::check(dc).then(|| unsafe {
+            // UNSAFE(@ohsayan): the borrowed key is never dropped; we only return an (owned) clone of it
+            let (a, b) = dc.as_raw().dwordqn_load_qw_nw();
+            let borrowed = ManuallyDrop::new(Self::new(
+                dc.tag().tag_unique(),
+                SpecialPaddedWord::new(a, b),
+            ));
+            PrimaryIndexKey::clone(&borrowed)
+        })
+    }
     /// ## Safety
     ///
     /// Make sure that the [`Datacell`] is an eligible candidate key (ensuring uniqueness constraints + allocation correctness).
@@ -174,6 +201,26 @@ impl PrimaryIndexKey {
             },
         }
     }
+    /// Returns a key that borrows the data of the given literal. Useful for looking up keys in indexes that can't
+    /// compare against a [`Lit`] directly
+    ///
+    /// ## Safety
+    ///
+    /// The literal must be an eligible candidate key and the returned key must not outlive the literal. The key
+    /// MUST NOT be dropped (hence the [`ManuallyDrop`])
+    pub unsafe fn probe_lit(lit: &Lit) -> ManuallyDrop<Self> {
+        let tag = lit.kind().tag_unique();
+        debug_assert!(tag.is_unique());
+        ManuallyDrop::new(match tag {
+            TagUnique::UnsignedInt | TagUnique::SignedInt => {
+                Self::new_from_qw(tag, lit.data().dwordnn_load_qw())
+            }
+            _ => {
+                let vdata = lit.__vdata();
+                Self::new_from_dual(tag, vdata.len() as u64, vdata.as_ptr() as usize)
+            }
+        })
+    }
     pub unsafe fn raw_clone(&self) -> Self {
         Self::new(self.tag, {
             let (qw, nw) = self.data.dwordqn_load_qw_nw();
@@ -234,9 +281,33 @@ impl PartialEq for PrimaryIndexKey {
 
 impl Eq for PrimaryIndexKey {}
 
+impl Ord for PrimaryIndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // keys of a model always have the same tag, but we still order by tag first to keep this total
+        self.tag.cmp(&other.tag).then_with(|| unsafe {
+            // UNSAFE(@ohsayan): tags verified
+            match self.tag {
+                TagUnique::UnsignedInt => self.read_uint().cmp(&other.read_uint()),
+                TagUnique::SignedInt => self.read_sint().cmp(&other.read_sint()),
+                TagUnique::Bin | TagUnique::Str => self.virtual_block().cmp(other.virtual_block()),
+                TagUnique::Illegal => unreachable!(),
+            }
+        })
+    }
+}
+
+impl PartialOrd for PrimaryIndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Hash for PrimaryIndexKey {
     fn hash<H: Hasher>(&self, hasher: &mut H) {
+        // must hash exactly like `Lit`, since a key is looked up with a literal
         self.tag.hash(hasher);
+        // the quad holds the value of an int key (and the length of a bin or str key)
+        self.data.dwordnn_load_qw().hash(hasher);
         self.virtual_block().hash(hasher);
     }
 }
@@ -285,6 +356,42 @@ impl fmt::Debug for PrimaryIndexKey {
     }
 }
 
+/// A primary key that borrows its data from a [`Lit`] that it owns. Used to look up and range scan ordered indexes
+pub struct PrimaryIndexKeyProbe<'a> {
+    key: ManuallyDrop<PrimaryIndexKey>,
+    _lit: Lit<'a>,
+}
+
+impl<'a> PrimaryIndexKeyProbe<'a> {
+    /// Returns [`None`] if the literal can't be a primary key
+    pub fn new(lit: Lit<'a>) -> Option<Self> {
+        if lit.kind().tag_unique().is_unique() {
+            Some(Self {
+                key: unsafe {
+                    // UNSAFE(@ohsayan): the lit is a candidate key and we hold it; moving it doesn't move the data
+                    PrimaryIndexKey::probe_lit(&lit)
+                },
+                _lit: lit,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> core::ops::Deref for PrimaryIndexKeyProbe<'a> {
+    type Target = PrimaryIndexKey;
+    fn deref(&self) -> &Self::Target {
+        &self.key
+    }
+}
+
+impl<'a> core::borrow::Borrow<PrimaryIndexKey> for PrimaryIndexKeyProbe<'a> {
+    fn borrow(&self) -> &PrimaryIndexKey {
+        &self.key
+    }
+}
+
 #[test]
 fn gh_issue_test_325_same_type_collapse() {
     assert_ne!(
@@ -344,6 +451,30 @@ fn check_pk_eq_hash() {
     }
 }
 
+#[test]
+fn check_pk_clone() {
+    let data = [
+        Datacell::from(100),
+        Datacell::from(-100),
+        Datacell::from("binary".as_bytes()),
+        Datacell::from("string"),
+    ];
+    for datum in data {
+        let pk1 = PrimaryIndexKey::try_from_dc(datum).unwrap();
+        let pk2 = pk1.clone();
+        // the clone must own its data
+        drop(pk1.clone());
+        assert_eq!(pk1, pk2);
+        drop(pk1);
+        assert_eq!(pk2, pk2.clone());
+    }
+    for datum in [Datacell::from(-100), Datacell::from("string")] {
+        let pk = PrimaryIndexKey::try_clone_from_dc(&datum).unwrap();
+        drop(datum);
+        assert_eq!(pk, pk.clone());
+    }
+}
+
 #[test]
 fn check_pk_lit_eq_hash() {
     let state = test_utils::randomstate();
@@ -395,3 +526,81 @@ fn empty_slice() {
     assert_eq!(pk2, pk2_);
     drop((pk2, pk2_));
 }
+
+#[test]
+fn check_pk_ord() {
+    let ord = |a: Datacell, b: Datacell| {
+        PrimaryIndexKey::try_from_dc(a)
+            .unwrap()
+            .cmp(&PrimaryIndexKey::try_from_dc(b).unwrap())
+    };
+    assert_eq!(
+        ord(
+            Datacell::new_uint_default(2),
+            Datacell::new_uint_default(10)
+        ),
+        Ordering::Less
+    );
+    assert_eq!(ord(Datacell::from(-10), Datacell::from(-2)), Ordering::Less);
+    assert_eq!(
+        ord(Datacell::from("b"), Datacell::from("ab")),
+        Ordering::Greater
+    );
+    assert_eq!(
+        ord(Datacell::from("ab"), Datacell::from("abc")),
+        Ordering::Less
+    );
+    assert_eq!(
+        ord(
+            Datacell::from("abc".as_bytes()),
+            Datacell::from("abc".as_bytes())
+        ),
+        Ordering::Equal
+    );
+}
+
+#[test]
+fn check_pk_probe_lit() {
+    let data = [
+        Lit::new_uint(100),
+        Lit::new_sint(-100),
+        Lit::new_bin(b"binary bro"),
+        Lit::new_str("string bro"),
+    ];
+    for lit in data {
+        let pk = PrimaryIndexKey::try_from_dc(Datacell::from(lit.clone())).unwrap();
+        let probe = unsafe { PrimaryIndexKey::probe_lit(&lit) };
+        assert_eq!(&pk, &*probe);
+        assert_eq!(pk.cmp(&probe), Ordering::Equal);
+        let probe = PrimaryIndexKeyProbe::new(lit).unwrap();
+        assert_eq!(&pk, &*probe);
+    }
+    assert!(PrimaryIndexKeyProbe::new(Lit::new_bool(true)).is_none());
+}
+
+#[test]
+fn check_pk_hash() {
+    fn hash(h: &impl Hash) -> u64 {
+        let mut hasher = HasherRawFx::<u64>::new();
+        h.hash(&mut hasher);
+        hasher.finish()
+    }
+    let data = [
+        Lit::new_uint(100),
+        Lit::new_sint(-100),
+        Lit::new_bin(b"binary bro"),
+        Lit::new_str("string bro"),
+    ];
+    for lit in data {
+        // a key is looked up with a literal, so both have to hash the same
+        let pk = PrimaryIndexKey::try_from_dc(Datacell::from(lit.clone())).unwrap();
+        assert_eq!(hash(&pk), hash(&lit));
+    }
+    // int keys have no virtual block, but must still hash by value
+    let pk = |lit: Lit| PrimaryIndexKey::try_from_dc(Datacell::from(lit)).unwrap();
+    assert_ne!(hash(&pk(Lit::new_uint(100))), hash(&pk(Lit::new_uint(101))));
+    assert_ne!(
+        hash(&pk(Lit::new_sint(-100))),
+        hash(&pk(Lit::new_sint(-101)))
+    );
+}
@@ -24,32 +24,97 @@
  *
 */
 
+mod collation;
 mod key;
 mod row;
+mod secondary;
 
-use crate::engine::{
-    data::lit::Lit,
-    idx::{IndexBaseSpec, IndexMTRaw, MTIndex},
-    sync::atm::Guard,
+use {
+    crate::engine::{
+        data::lit::Lit,
+        idx::{
+            meta::hash::{HasherDynState, HasherKind},
+            mtbt::IterRange,
+            IndexBaseSpec, IndexMTBTree, IndexMTRaw, MTIndex, MTIndexExt,
+        },
+        sync::atm::Guard,
+    },
+    std::ops::Bound,
 };
 
+#[cfg(test)]
+pub use secondary::BUILD_RUN;
 pub use {
-    key::PrimaryIndexKey,
+    collation::Collation,
+    key::{PrimaryIndexKey, PrimaryIndexKeyProbe},
     row::{DcFieldIndex, Row, RowData},
+    secondary::SecondaryIndex,
 };
 
-pub type RowDataLck = parking_lot::RwLock<RowData>;
+pub type RowDataLck = row::RowLock;
 
+/// The data structure backing a primary index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PrimaryIndexKind {
+    /// A concurrent hash trie. Fastest for point lookups, but unordered
+    #[default]
+    Hash,
+    /// A B-tree. Keeps keys ordered, so range and prefix scans on the primary key are efficient
+    BTree,
+}
+
+impl PrimaryIndexKind {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Hash => "hash",
+            Self::BTree => "btree",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "hash" => Some(Self::Hash),
+            "btree" => Some(Self::BTree),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PrimaryIndexData {
+    Hash(IndexMTRaw<row::Row>),
+    BTree(IndexMTBTree<row::Row>),
+}
+
+impl PrimaryIndexData {
+    fn count(&self) -> usize {
+        match self {
+            Self::Hash(idx) => idx.mt_len(),
+            Self::BTree(idx) => idx.mt_len(),
+        }
+    }
+}
+
+/// The primary index of a model. The rows of a model can be spread across several partitions (each with its own
+/// index) by the hash of their primary key (see [`PrimaryIndexKey::partition`]); only hash indexes can be partitioned
 #[derive(Debug)]
 pub struct PrimaryIndex {
-    data: IndexMTRaw<row::Row>,
+    parts: Box<[PrimaryIndexData]>,
     latch: IndexLatch,
 }
 
 impl PrimaryIndex {
-    pub fn new_empty() -> Self {
+    /// Create a new primary index with the given number of partitions. The hasher is ignored by ordered indexes
+    pub fn new_empty_with(kind: PrimaryIndexKind, hasher: HasherKind, partitions: usize) -> Self {
+        debug_assert!(partitions != 0 && (partitions == 1 || kind == PrimaryIndexKind::Hash));
         Self {
-            data: IndexMTRaw::idx_init(),
+            parts: (0..partitions)
+                .map(|_| match kind {
+                    PrimaryIndexKind::Hash => PrimaryIndexData::Hash(
+                        IndexMTRaw::idx_init_with_hasher(HasherDynState::new(hasher)),
+                    ),
+                    PrimaryIndexKind::BTree => PrimaryIndexData::BTree(IndexMTBTree::idx_init()),
+                })
+                .collect(),
             latch: IndexLatch::new(),
         }
     }
@@ -59,14 +124,200 @@ impl PrimaryIndex {
     pub fn acquire_exclusive(&self) -> IndexLatchHandleExclusive {
         self.latch.gl_handle_exclusive()
     }
+    /// Returns the number of partitions
+    pub fn partitions(&self) -> usize {
+        self.parts.len()
+    }
+    fn part(&self, key: &PrimaryIndexKey) -> &PrimaryIndexData {
+        match self.parts.len() {
+            1 => &self.parts[0],
+            n => &self.parts[key.partition(n)],
+        }
+    }
     pub fn select<'a, 'v, 't: 'v, 'g: 't>(&'t self, key: Lit<'a>, g: &'g Guard) -> Option<&'v Row> {
-        self.data.mt_get_element(&key, g)
+        let probe = PrimaryIndexKeyProbe::new(key)?;
+        self.select_key(&probe, g)
+    }
+    pub fn select_key<'v, 't: 'v, 'g: 't>(
+        &'t self,
+        key: &PrimaryIndexKey,
+        g: &'g Guard,
+    ) -> Option<&'v Row> {
+        match self.part(key) {
+            PrimaryIndexData::Hash(idx) => idx.mt_get_element(key, g),
+            PrimaryIndexData::BTree(idx) => idx.mt_get_element(key, g),
+        }
     }
-    pub fn __raw_index(&self) -> &IndexMTRaw<row::Row> {
-        &self.data
+    /// Returns true if the row was inserted; returns false if a row with the same key already exists
+    pub fn insert(&self, row: Row, g: &Guard) -> bool {
+        match self.part(row.d_key()) {
+            PrimaryIndexData::Hash(idx) => idx.mt_insert(row, g),
+            PrimaryIndexData::BTree(idx) => idx.mt_insert(row, g),
+        }
+    }
+    pub fn delete_key(&self, key: &PrimaryIndexKey, g: &Guard) -> bool {
+        match self.part(key) {
+            PrimaryIndexData::Hash(idx) => idx.mt_delete(key, g),
+            PrimaryIndexData::BTree(idx) => idx.mt_delete(key, g),
+        }
+    }
+    pub fn delete_return_entry<'a, 'v, 't: 'v, 'g: 't>(
+        &'t self,
+        key: Lit<'a>,
+        g: &'g Guard,
+    ) -> Option<&'v Row> {
+        let probe = PrimaryIndexKeyProbe::new(key)?;
+        self.delete_return_entry_key(&probe, g)
+    }
+    pub fn delete_return_entry_key<'v, 't: 'v, 'g: 't>(
+        &'t self,
+        key: &PrimaryIndexKey,
+        g: &'g Guard,
+    ) -> Option<&'v Row> {
+        match self.part(key) {
+            PrimaryIndexData::Hash(idx) => idx.mt_delete_return_entry(key, g),
+            PrimaryIndexData::BTree(idx) => idx.mt_delete_return_entry(key, g),
+        }
+    }
+    /// Returns an iterator over all rows. Rows are returned in key order if the index is ordered, and in hash order
+    /// (partition by partition) otherwise, which is arbitrary, but stable for the lifetime of the index since the
+    /// hasher never changes
+    pub fn iter<'v, 't: 'v, 'g: 't>(
+        &'t self,
+        g: &'g Guard,
+    ) -> PrimaryIndexIter<'static, 't, 'g, 'v> {
+        self.range_keys(Bound::Unbounded, Bound::Unbounded, g)
+    }
+    /// Returns an iterator over the rows in the given partition
+    pub fn iter_partition<'v, 't: 'v, 'g: 't>(
+        &'t self,
+        partition: usize,
+        g: &'g Guard,
+    ) -> PrimaryIndexIter<'static, 't, 'g, 'v> {
+        Self::iter_parts(
+            &self.parts[partition..partition + 1],
+            Bound::Unbounded,
+            Bound::Unbounded,
+            g,
+        )
+    }
+    /// Returns an iterator over the rows whose keys are in the given range. Ordered indexes only visit the rows in
+    /// the range (in key order) while hash indexes have to scan (and filter) all rows
+    ///
+    /// Returns [`None`] if any of the bounds can't be a primary key
+    pub fn range<'a, 'v, 't: 'v, 'g: 't>(
+        &'t self,
+        lo: Bound<Lit<'a>>,
+        hi: Bound<Lit<'a>>,
+        g: &'g Guard,
+    ) -> Option<PrimaryIndexIter<'a, 't, 'g, 'v>> {
+        fn probe(b: Bound<Lit>) -> Option<Bound<PrimaryIndexKeyProbe>> {
+            Some(match b {
+                Bound::Included(lit) => Bound::Included(PrimaryIndexKeyProbe::new(lit)?),
+                Bound::Excluded(lit) => Bound::Excluded(PrimaryIndexKeyProbe::new(lit)?),
+                Bound::Unbounded => Bound::Unbounded,
+            })
+        }
+        Some(self.range_keys(probe(lo)?, probe(hi)?, g))
+    }
+    fn range_keys<'a, 'v, 't: 'v, 'g: 't>(
+        &'t self,
+        lo: Bound<PrimaryIndexKeyProbe<'a>>,
+        hi: Bound<PrimaryIndexKeyProbe<'a>>,
+        g: &'g Guard,
+    ) -> PrimaryIndexIter<'a, 't, 'g, 'v> {
+        Self::iter_parts(&self.parts, lo, hi, g)
+    }
+    fn iter_parts<'a, 'v, 't: 'v, 'g: 't>(
+        parts: &'t [PrimaryIndexData],
+        lo: Bound<PrimaryIndexKeyProbe<'a>>,
+        hi: Bound<PrimaryIndexKeyProbe<'a>>,
+        g: &'g Guard,
+    ) -> PrimaryIndexIter<'a, 't, 'g, 'v> {
+        match parts {
+            [PrimaryIndexData::BTree(idx)] => PrimaryIndexIter::BTree(idx.mt_range(lo, hi, g)),
+            parts => PrimaryIndexIter::Hash {
+                parts: parts.iter(),
+                it: None,
+                g,
+                lo,
+                hi,
+            },
+        }
     }
     pub fn count(&self) -> usize {
-        self.data.mt_len()
+        self.parts.iter().map(PrimaryIndexData::count).sum()
+    }
+    /// Returns the number of rows in the given partition
+    pub fn count_partition(&self, partition: usize) -> usize {
+        self.parts[partition].count()
+    }
+}
+
+pub enum PrimaryIndexIter<'a, 't, 'g, 'v>
+where
+    'g: 't + 'v,
+    't: 'v,
+{
+    Hash {
+        parts: std::slice::Iter<'t, PrimaryIndexData>,
+        it: Option<
+            <IndexMTRaw<Row> as MTIndexExt<Row, PrimaryIndexKey, RowDataLck>>::IterEntry<
+                't,
+                'g,
+                'v,
+            >,
+        >,
+        g: &'g Guard,
+        lo: Bound<PrimaryIndexKeyProbe<'a>>,
+        hi: Bound<PrimaryIndexKeyProbe<'a>>,
+    },
+    BTree(IterRange<'t, 'g, 'v, Row, PrimaryIndexKeyProbe<'a>>),
+}
+
+impl<'a, 't, 'g, 'v> Iterator for PrimaryIndexIter<'a, 't, 'g, 'v>
+where
+    'g: 't + 'v,
+    't: 'v,
+{
+    type Item = &'v Row;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Hash {
+                parts,
+                it,
+                g,
+                lo,
+                hi,
+            } => loop {
+                if let Some(it) = it {
+                    let row = it.find(|row| {
+                        let k: &PrimaryIndexKey = row.d_key();
+                        let lo_ok = match lo {
+                            Bound::Included(lo) => k >= &**lo,
+                            Bound::Excluded(lo) => k > &**lo,
+                            Bound::Unbounded => true,
+                        };
+                        let hi_ok = match hi {
+                            Bound::Included(hi) => k <= &**hi,
+                            Bound::Excluded(hi) => k < &**hi,
+                            Bound::Unbounded => true,
+                        };
+                        lo_ok & hi_ok
+                    });
+                    if row.is_some() {
+                        return row;
+                    }
+                }
+                match parts.next()? {
+                    PrimaryIndexData::Hash(idx) => *it = Some(idx.mt_iter_entry(*g)),
+                    PrimaryIndexData::BTree(_) => {
+                        unreachable!("ordered indexes are never partitioned")
+                    }
+                }
+            },
+            Self::BTree(it) => it.next(),
+        }
     }
 }
 
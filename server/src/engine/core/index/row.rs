@@ -30,22 +30,45 @@ use {
         engine::{
             core::model::{DeltaState, DeltaVersion, SchemaDeltaKind},
             data::cell::Datacell,
+            error::{QueryError, QueryResult},
             idx::{meta::hash::HasherNativeFx, mtchm::meta::TreeElement, IndexST, STIndex},
             mem::RawStr,
+            storage::overflow::{OverflowSlot, OverflowStore},
             sync::smart::RawRC,
         },
         util::compiler,
     },
     parking_lot::{RwLock, RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard},
-    std::mem::ManuallyDrop,
+    std::{
+        mem::ManuallyDrop,
+        ops::Deref,
+        sync::{
+            atomic::{AtomicU8, Ordering},
+            Arc,
+        },
+        time::Duration,
+    },
 };
 
 pub type DcFieldIndex = IndexST<RawStr, Datacell, HasherNativeFx>;
 
+/// set whenever the row is accessed (and cleared by every overflow sweep)
+const ROW_REFERENCED: u8 = 1 << 0;
+/// set while the values of the row are in the overflow store
+const ROW_SPILLED: u8 = 1 << 1;
+
 #[derive(Debug)]
 pub struct Row {
     __pk: ManuallyDrop<PrimaryIndexKey>,
-    __rc: RawRC<RwLock<RowData>>,
+    __rc: RawRC<RowLock>,
+}
+
+#[derive(Debug)]
+/// The lock on the data of a row. Locking it reads the values of the row back from the overflow store first if they
+/// were moved there, which fails if they can't be read
+pub struct RowLock {
+    data: RwLock<RowData>,
+    state: AtomicU8,
 }
 
 #[derive(Debug, PartialEq)]
@@ -53,6 +76,17 @@ pub struct RowData {
     fields: DcFieldIndex,
     txn_revised_data: DeltaVersion,
     txn_revised_schema_version: DeltaVersion,
+    /// the time (in seconds since the UNIX epoch) at which this row was soft deleted
+    tombstone: Option<u64>,
+    /// the values of this row, if they were moved to the overflow store
+    spill: Option<Box<Spill>>,
+}
+
+/// The values of a row that were moved to the overflow store, along with the fields that they belong to
+#[derive(Debug, PartialEq)]
+struct Spill {
+    fields: Box<[RawStr]>,
+    slot: OverflowSlot,
 }
 
 impl RowData {
@@ -68,13 +102,23 @@ impl RowData {
     pub fn get_txn_revised(&self) -> DeltaVersion {
         self.txn_revised_data
     }
+    pub fn tombstone(&self) -> Option<u64> {
+        self.tombstone
+    }
+    /// Returns true if this row was soft deleted
+    pub fn is_tombstoned(&self) -> bool {
+        self.tombstone.is_some()
+    }
+    pub fn set_tombstone(&mut self, tombstone: Option<u64>) {
+        self.tombstone = tombstone;
+    }
 }
 
 impl TreeElement for Row {
     type IKey = PrimaryIndexKey;
     type Key = PrimaryIndexKey;
     type IValue = DcFieldIndex;
-    type Value = RwLock<RowData>;
+    type Value = RowLock;
     type VEx1 = DeltaVersion;
     type VEx2 = DeltaVersion;
     fn key(&self) -> &Self::Key {
@@ -112,24 +156,49 @@ impl Row {
             __pk: ManuallyDrop::new(pk),
             __rc: unsafe {
                 // UNSAFE(@ohsayan): we free this up later
-                RawRC::new(RwLock::new(RowData {
-                    fields: data,
-                    txn_revised_schema_version: schema_version,
-                    txn_revised_data,
-                }))
+                RawRC::new(RowLock {
+                    data: RwLock::new(RowData {
+                        fields: data,
+                        txn_revised_schema_version: schema_version,
+                        txn_revised_data,
+                        tombstone: None,
+                        spill: None,
+                    }),
+                    state: AtomicU8::new(ROW_REFERENCED),
+                })
             },
         }
     }
     pub fn d_key(&self) -> &PrimaryIndexKey {
         &self.__pk
     }
-    pub fn d_data(&self) -> &RwLock<RowData> {
+    /// Returns the lock on the data of this row (see [`RowLock`])
+    pub fn d_data(&self) -> &RowLock {
         self.__rc.data()
     }
+    /// Ask the CPU to start loading the data of this row into its cache, returning false if there's no way to on this
+    /// target. Unlike [`Self::d_data`], this doesn't touch the row
+    pub fn prefetch(&self) -> bool {
+        #[cfg(target_arch = "x86_64")]
+        {
+            use core::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+            let cell: *const RowLock = self.__rc.data();
+            unsafe {
+                // UNSAFE(@ohsayan): a prefetch is only a hint, and the cell is valid anyway since we hold the row
+                _mm_prefetch::<_MM_HINT_T0>(cell as *const i8);
+            }
+            true
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            false
+        }
+    }
     #[cfg(test)]
     pub fn cloned_data(&self) -> Vec<(Box<str>, Datacell)> {
         self.d_data()
             .read()
+            .unwrap()
             .fields()
             .st_iter_kv()
             .map(|(id, data)| (id.as_str().to_owned().into_boxed_str(), data.clone()))
@@ -137,17 +206,158 @@ impl Row {
     }
 }
 
+/*
+    overflow
+*/
+
+impl Row {
+    /// Clear the referenced bit, returning true if the row was accessed since the last sweep. A spilled row is never
+    /// referenced (until it's read back)
+    pub fn overflow_sweep(&self) -> bool {
+        let cell = self.__rc.data();
+        cell.state.fetch_and(!ROW_REFERENCED, Ordering::AcqRel) & ROW_REFERENCED != 0
+    }
+    /// Returns true if the values of this row are in the overflow store
+    pub fn is_spilled(&self) -> bool {
+        self.__rc.data().state.load(Ordering::Acquire) & ROW_SPILLED != 0
+    }
+    /// Move the values of this row to the overflow store, returning the size of the slot that they were written to.
+    /// Nothing is moved if the row is locked, was accessed since the last sweep or is soft deleted
+    pub fn overflow_spill(&self, store: &Arc<OverflowStore>) -> Option<u64> {
+        let cell = self.__rc.data();
+        if cell.state.load(Ordering::Acquire) != 0 {
+            return None;
+        }
+        let mut data = cell.data.try_write()?;
+        if data.is_tombstoned() | (data.fields.st_len() == 0) {
+            return None;
+        }
+        let slot = match store.write_cells(data.fields.st_iter_kv().map(|(_, dc)| dc)) {
+            Ok(slot) => slot,
+            Err(e) => {
+                warn!("overflow: failed to spill row: {e}");
+                return None;
+            }
+        };
+        // if the row was accessed while we were writing it out, it's hot again (and the slot is released)
+        if cell
+            .state
+            .compare_exchange(0, ROW_SPILLED, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return None;
+        }
+        let size = slot.size();
+        let fields = data
+            .fields
+            .st_iter_kv()
+            .map(|(id, _)| unsafe {
+                // UNSAFE(@ohsayan): the field names are held by the model, just like the ones in the index
+                id.clone()
+            })
+            .collect();
+        data.fields.st_clear();
+        data.spill = Some(Box::new(Spill { fields, slot }));
+        Some(size)
+    }
+}
+
+impl RowLock {
+    pub fn read(&self) -> QueryResult<RwLockReadGuard<'_, RowData>> {
+        self.acquire(|data| Some(data.read())).map(Option::unwrap)
+    }
+    pub fn write(&self) -> QueryResult<RwLockWriteGuard<'_, RowData>> {
+        self.acquire(|data| Some(data.write())).map(Option::unwrap)
+    }
+    pub fn upgradable_read(&self) -> QueryResult<RwLockUpgradableReadGuard<'_, RowData>> {
+        self.acquire(|data| Some(data.upgradable_read()))
+            .map(Option::unwrap)
+    }
+    pub fn try_write(&self) -> QueryResult<Option<RwLockWriteGuard<'_, RowData>>> {
+        self.acquire(RwLock::try_write)
+    }
+    pub fn try_write_for(
+        &self,
+        timeout: Duration,
+    ) -> QueryResult<Option<RwLockWriteGuard<'_, RowData>>> {
+        self.acquire(|data| data.try_write_for(timeout))
+    }
+    pub fn try_upgradable_read(
+        &self,
+    ) -> QueryResult<Option<RwLockUpgradableReadGuard<'_, RowData>>> {
+        self.acquire(RwLock::try_upgradable_read)
+    }
+    pub fn try_upgradable_read_for(
+        &self,
+        timeout: Duration,
+    ) -> QueryResult<Option<RwLockUpgradableReadGuard<'_, RowData>>> {
+        self.acquire(|data| data.try_upgradable_read_for(timeout))
+    }
+    /// Touch the row and lock it. The values of the row are only checked once it's locked, since they can be moved
+    /// out between the two (the lock is what keeps them in)
+    fn acquire<'a, G: Deref<Target = RowData>>(
+        &'a self,
+        lock: impl Fn(&'a RwLock<RowData>) -> Option<G>,
+    ) -> QueryResult<Option<G>> {
+        loop {
+            if compiler::unlikely(self.state.load(Ordering::Relaxed) != ROW_REFERENCED) {
+                self.touch()?;
+            }
+            match lock(&self.data) {
+                Some(guard) if compiler::unlikely(guard.spill.is_some()) => drop(guard),
+                guard => return Ok(guard),
+            }
+        }
+    }
+    #[cold]
+    #[inline(never)]
+    fn touch(&self) -> QueryResult<()> {
+        if self.state.fetch_or(ROW_REFERENCED, Ordering::AcqRel) & ROW_SPILLED == 0 {
+            return Ok(());
+        }
+        let mut data = self.data.write();
+        if let Some(spill) = data.spill.as_ref() {
+            let cells = match spill.slot.read_cells(spill.fields.len()) {
+                Ok(cells) => cells,
+                Err(e) => {
+                    // the values aren't anywhere else, so leave them where they are (maybe the next read works)
+                    error!("overflow: failed to read row back: {e}");
+                    spill.slot.report_failed_read();
+                    return Err(QueryError::SysServerError);
+                }
+            };
+            // the slot is released once the spill is dropped
+            let Spill { fields, .. } = *data.spill.take().unwrap();
+            for (id, dc) in fields.into_vec().into_iter().zip(cells) {
+                data.fields.st_insert(id, dc);
+            }
+        }
+        self.state.fetch_and(!ROW_SPILLED, Ordering::Release);
+        Ok(())
+    }
+}
+
 impl Row {
     /// Only apply deltas if a certain condition is met
     pub fn resolve_schema_deltas_and_freeze_if<'g>(
         &'g self,
         delta_state: &DeltaState,
         iff: impl Fn(&RowData) -> bool,
-    ) -> RwLockReadGuard<'g, RowData> {
-        let rwl_ug = self.d_data().upgradable_read();
+    ) -> QueryResult<RwLockReadGuard<'g, RowData>> {
+        let rwl_ug = self.d_data().upgradable_read()?;
         if !iff(&rwl_ug) {
-            return RwLockUpgradableReadGuard::downgrade(rwl_ug);
+            return Ok(RwLockUpgradableReadGuard::downgrade(rwl_ug));
         }
+        Ok(Self::resolve_schema_deltas_and_freeze_locked(
+            rwl_ug,
+            delta_state,
+        ))
+    }
+    /// Apply deltas to a row that was already locked with an upgradable read lock
+    pub fn resolve_schema_deltas_and_freeze_locked<'g>(
+        rwl_ug: RwLockUpgradableReadGuard<'g, RowData>,
+        delta_state: &DeltaState,
+    ) -> RwLockReadGuard<'g, RowData> {
         let current_version = delta_state.schema_current_version();
         if compiler::likely(current_version <= rwl_ug.txn_revised_schema_version) {
             return RwLockUpgradableReadGuard::downgrade(rwl_ug);
@@ -172,7 +382,7 @@ impl Row {
                     wl.fields.st_delete(f);
                 }
             }
-            max_delta = *delta_id;
+            max_delta = delta_id.step();
         }
         // we've revised upto the most most recent delta version (that we saw at this point)
         wl.txn_revised_schema_version = max_delta;
@@ -181,7 +391,7 @@ impl Row {
     pub fn resolve_schema_deltas_and_freeze<'g>(
         &'g self,
         delta_state: &DeltaState,
-    ) -> RwLockReadGuard<'g, RowData> {
+    ) -> QueryResult<RwLockReadGuard<'g, RowData>> {
         self.resolve_schema_deltas_and_freeze_if(delta_state, |_| true)
     }
 }
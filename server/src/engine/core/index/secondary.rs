@@ -0,0 +1,397 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{collation::Collation, key::PrimaryIndexKey, row::DcFieldIndex},
+    crate::{
+        engine::{
+            core::{
+                lock,
+                model::{predicate::Predicate, ModelData},
+            },
+            data::{
+                cell::Datacell,
+                tag::{DataTag, FullTag, TagClass},
+            },
+            fractal::jobs::CancelToken,
+            idx::{tuple::TupleKey, STIndex},
+            ql::{dml::RelationalExpr, lex::Ident},
+            sync::atm::cpin,
+        },
+        util::os,
+    },
+    parking_lot::RwLock,
+    std::{
+        collections::{BTreeMap, HashMap},
+        sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+/// Builds report their progress and check if they were cancelled once every so many rows
+pub const BUILD_RUN: usize = 1024;
+
+/// How much an index has been used since the server started (the counters aren't persisted)
+#[derive(Debug, Default)]
+struct IndexUsage {
+    /// the number of lookups
+    reads: AtomicU64,
+    /// the number of entries added or removed by DML
+    writes: AtomicU64,
+    /// when the last lookup happened, in seconds since the epoch (zero if never)
+    last_read: AtomicU64,
+}
+
+/// A secondary index maps the values of one or more (non-primary) fields to the primary keys of the rows that hold
+/// them.
+///
+/// The values of a row's indexed fields are encoded as a [`TupleKey`], so the entries of a composite index
+/// (`create index ... (field, ...)`) are ordered by the first field, then by the second and so on. A lookup only needs
+/// the values of a prefix of the fields, since the entries that match a prefix are contiguous. A row is only indexed if
+/// its first field isn't null (the other fields can be null, since a lookup on a shorter prefix still has to find it).
+///
+/// Only the definition is persisted (in the GNS); the data is rebuilt from the primary index when the model is loaded.
+/// Until the build completes, the index is not used to answer queries (but is still maintained by DML).
+///
+/// A partial index only holds the rows that satisfy its predicate (`create index ... where <clauses>`), so it can
+/// only answer queries whose where clause implies the predicate.
+///
+/// A covering index (`create index ... include (<fields>)`) also stores the values of the included fields in each
+/// entry, so that a query that only reads the key, the looked up fields and the included fields can be answered from
+/// the index alone.
+///
+/// The string components of a key are compared using the index's [`Collation`] (`create index ... with { collation:
+/// 'nocase' }`), which decides the order of the entries and which values a lookup finds. A row still only matches a
+/// lookup if its values are equal to the looked up values
+#[derive(Debug)]
+pub struct SecondaryIndex {
+    fields: Box<[Box<str>]>,
+    include: Box<[Box<str>]>,
+    filter: Option<Predicate>,
+    collation: Collation,
+    ready: AtomicBool,
+    progress: AtomicUsize,
+    usage: IndexUsage,
+    /// the keys for each tuple are kept ordered so that lookups return rows in a stable (index, then primary key) order.
+    /// each key maps to the values of the included fields (in the order they were declared in)
+    data: RwLock<BTreeMap<TupleKey, BTreeMap<PrimaryIndexKey, Box<[Datacell]>>>>,
+}
+
+impl SecondaryIndex {
+    /// the collation that strings are compared with (see [`Collation`])
+    pub const KEY_COLLATION: &'static str = "collation";
+    /// Create a new (empty) index on the given fields that needs to be built before use
+    pub fn new_building(
+        fields: Box<[Box<str>]>,
+        include: Box<[Box<str>]>,
+        filter: Option<Predicate>,
+        collation: Collation,
+    ) -> Self {
+        debug_assert!(!fields.is_empty());
+        Self {
+            fields,
+            include,
+            filter,
+            collation,
+            ready: AtomicBool::new(false),
+            progress: AtomicUsize::new(0),
+            usage: IndexUsage::default(),
+            data: RwLock::new(BTreeMap::new()),
+        }
+    }
+    /// Returns the indexed fields, in order
+    pub fn fields(&self) -> &[Box<str>] {
+        &self.fields
+    }
+    /// Returns the first indexed field
+    pub fn field(&self) -> &str {
+        &self.fields[0]
+    }
+    /// Returns true if the given field is one of the indexed fields
+    pub fn indexes(&self, field: &str) -> bool {
+        self.fields.iter().any(|f| f.as_ref() == field)
+    }
+    /// Returns the fields that a covering index stores along with each key
+    pub fn include(&self) -> &[Box<str>] {
+        &self.include
+    }
+    /// Returns the position of the given field among the included fields
+    pub fn included_position(&self, field: &str) -> Option<usize> {
+        self.include.iter().position(|f| f.as_ref() == field)
+    }
+    /// Returns true if the value of the given field (of a row found by a lookup on the first `prefix` fields) can be
+    /// read from the index alone
+    pub fn covers(&self, mdl: &ModelData, field: &str, prefix: usize) -> bool {
+        (field == mdl.p_key())
+            | self.fields[..prefix].iter().any(|f| f.as_ref() == field)
+            | self.included_position(field).is_some()
+    }
+    /// Returns the predicate of a partial index
+    pub fn filter(&self) -> Option<&Predicate> {
+        self.filter.as_ref()
+    }
+    /// Returns the collation that the string components of a key are compared with
+    pub fn collation(&self) -> Collation {
+        self.collation
+    }
+    /// Returns true if the index can be used for a query with the given where clause
+    pub fn applies_to(&self, clauses: &HashMap<Ident, RelationalExpr>) -> bool {
+        self.filter
+            .as_ref()
+            .map_or(true, |filter| filter.implied_by(clauses))
+    }
+    /// Returns true if the index has been fully built
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+    /// Returns the number of rows indexed so far by the current build
+    pub fn progress(&self) -> usize {
+        self.progress.load(Ordering::Relaxed)
+    }
+    /// Returns the number of rows in the index
+    pub fn entries(&self) -> usize {
+        self.data.read().values().map(BTreeMap::len).sum()
+    }
+    /// Returns the number of lookups since the server started
+    pub fn reads(&self) -> u64 {
+        self.usage.reads.load(Ordering::Relaxed)
+    }
+    /// Returns the number of entries that DML added or removed since the server started
+    pub fn writes(&self) -> u64 {
+        self.usage.writes.load(Ordering::Relaxed)
+    }
+    /// Returns when the index was last used for a lookup (in seconds since the epoch), if it was used since the server
+    /// started
+    pub fn last_read(&self) -> Option<u64> {
+        match self.usage.last_read.load(Ordering::Relaxed) {
+            0 => None,
+            t => Some(t),
+        }
+    }
+    /// Returns true if a field of the given type can be indexed
+    pub fn can_index(tag: FullTag) -> bool {
+        tag.tag_unique().is_unique()
+    }
+    /// Returns the key of a row, using `value` to get the value of each field (or [`None`] if the first field is null)
+    fn key_of<'d>(&self, value: impl Fn(&str) -> Option<&'d Datacell>) -> Option<TupleKey> {
+        let mut key = TupleKey::default();
+        for (i, field) in self.fields.iter().enumerate() {
+            match value(field).filter(|dc| !dc.is_null()) {
+                Some(dc) => match dc.tag().tag_class() {
+                    TagClass::UnsignedInt => key.push_uint(dc.uint()),
+                    TagClass::SignedInt => key.push_sint(dc.sint()),
+                    TagClass::Str => key.push_bytes(self.collation.collate(dc.str()).as_bytes()),
+                    TagClass::Bin => key.push_bytes(dc.bin()),
+                    _ => return None,
+                },
+                None if i == 0 => return None,
+                None => key.push_null(),
+            }
+        }
+        Some(key)
+    }
+    /// Returns the key prefix for the given values of the first fields
+    fn prefix_of(&self, values: &[PrimaryIndexKey]) -> TupleKey {
+        let mut key = TupleKey::default();
+        for value in values {
+            if let Some(u) = value.uint() {
+                key.push_uint(u);
+            } else if let Some(i) = value.sint() {
+                key.push_sint(i);
+            } else if let Some(s) = value.str() {
+                key.push_bytes(self.collation.collate(s).as_bytes());
+            } else if let Some(b) = value.bin() {
+                key.push_bytes(b);
+            }
+        }
+        key
+    }
+    /// Index the given row (unless it doesn't satisfy the predicate)
+    pub fn insert_row(&self, mdl: &ModelData, pk: &PrimaryIndexKey, fields: &DcFieldIndex) {
+        if self.index_row(mdl, pk, fields) {
+            self.usage.writes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    /// Index the given row (unless it doesn't satisfy the predicate), returning true if it was indexed
+    fn index_row(&self, mdl: &ModelData, pk: &PrimaryIndexKey, fields: &DcFieldIndex) -> bool {
+        if !self
+            .filter
+            .as_ref()
+            .map_or(true, |filter| filter.matches(mdl, pk, fields))
+        {
+            return false;
+        }
+        let Some(k) = self.key_of(|field| fields.st_get(field)) else {
+            return false;
+        };
+        let included = self
+            .include
+            .iter()
+            .map(|field| {
+                fields
+                    .st_get(field.as_ref())
+                    .cloned()
+                    .unwrap_or_else(Datacell::null)
+            })
+            .collect();
+        self.data
+            .write()
+            .entry(k)
+            .or_default()
+            .insert(pk.clone(), included);
+        true
+    }
+    /// Remove the given row from the index
+    pub fn remove_row(&self, pk: &PrimaryIndexKey, fields: &DcFieldIndex) {
+        if let Some(k) = self.key_of(|field| fields.st_get(field)) {
+            self.remove_key(pk, &k)
+        }
+    }
+    /// The given row was updated, where `old` holds the prior values of the fields that changed. The row is only
+    /// indexed again if an indexed field (or an included field, or a field that the predicate uses) changed
+    pub fn update_row(
+        &self,
+        mdl: &ModelData,
+        pk: &PrimaryIndexKey,
+        old: &[(&str, Datacell)],
+        fields: &DcFieldIndex,
+    ) {
+        let old_value = |field: &str| {
+            old.iter()
+                .find(|(changed, _)| *changed == field)
+                .map(|(_, dc)| dc)
+        };
+        let changed = |list: &[Box<str>]| list.iter().any(|field| old_value(field).is_some());
+        let filter_changed = self.filter.as_ref().map_or(false, |filter| {
+            filter.fields().any(|field| old_value(field).is_some())
+        });
+        if !changed(&self.fields) & !changed(&self.include) & !filter_changed {
+            return;
+        }
+        // the row may or may not have been indexed before, but removing a missing entry does nothing
+        if let Some(k) = self.key_of(|field| old_value(field).or_else(|| fields.st_get(field))) {
+            self.remove_key(pk, &k);
+        }
+        self.insert_row(mdl, pk, fields);
+    }
+    fn remove_key(&self, pk: &PrimaryIndexKey, k: &TupleKey) {
+        let mut data = self.data.write();
+        if let Some(pks) = data.get_mut(k) {
+            if pks.remove(pk).is_some() {
+                self.usage.writes.fetch_add(1, Ordering::Relaxed);
+            }
+            if pks.is_empty() {
+                data.remove(k);
+            }
+        }
+    }
+    fn record_read(&self) {
+        self.usage.reads.fetch_add(1, Ordering::Relaxed);
+        self.usage
+            .last_read
+            .store(os::get_epoch_time_secs(), Ordering::Relaxed);
+    }
+    /// Returns the primary keys of all rows where the first fields have the given values, in index order: ordered by
+    /// the remaining fields (as compared by the collation), and then by primary key.
+    ///
+    /// NOTE(@ohsayan): an entry can go stale if a row is deleted while it is being inserted or updated; the caller
+    /// must verify the row against the values
+    pub fn lookup(&self, values: &[PrimaryIndexKey]) -> Vec<PrimaryIndexKey> {
+        self.record_read();
+        let prefix = self.prefix_of(values);
+        self.data
+            .read()
+            .range(&prefix..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .flat_map(|(_, pks)| pks.keys().cloned())
+            .collect()
+    }
+    /// Returns the primary keys of all rows where the first fields have the given values, along with the values of
+    /// the included fields (see [`Self::lookup`] for the order, and why entries may be stale)
+    pub fn lookup_covered(
+        &self,
+        values: &[PrimaryIndexKey],
+    ) -> Vec<(PrimaryIndexKey, Box<[Datacell]>)> {
+        self.record_read();
+        let prefix = self.prefix_of(values);
+        self.data
+            .read()
+            .range(&prefix..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .flat_map(|(_, pks)| {
+                pks.iter()
+                    .map(|(pk, included)| (pk.clone(), included.clone()))
+            })
+            .collect()
+    }
+    /// Returns true if the first fields of the given row have the given values
+    pub fn row_matches(&self, fields: &DcFieldIndex, values: &[PrimaryIndexKey]) -> bool {
+        self.fields.iter().zip(values).all(|(field, value)| {
+            fields
+                .st_get(field.as_ref())
+                .filter(|dc| !dc.is_null())
+                .and_then(PrimaryIndexKey::try_clone_from_dc)
+                .map_or(false, |k| &k == value)
+        })
+    }
+    /// Build the index from all rows in the model. Inserts and deletes are blocked while the index is built
+    pub fn build(&self, mdl: &ModelData) {
+        let _ = self.build_until(mdl, &CancelToken::default(), |_| {});
+    }
+    /// Build the index (see [`Self::build`]), calling `progress` with the number of rows indexed so far after every
+    /// [`BUILD_RUN`] rows. If the token is cancelled (or a row can't be read), the build stops and the index is left
+    /// empty (and not ready). Returns true if the build completed
+    pub fn build_until(
+        &self,
+        mdl: &ModelData,
+        token: &CancelToken,
+        mut progress: impl FnMut(usize),
+    ) -> bool {
+        let idx = mdl.primary_index();
+        let _latch = idx.acquire_exclusive();
+        let g = cpin();
+        self.ready.store(false, Ordering::Release);
+        self.data.write().clear();
+        self.progress.store(0, Ordering::Relaxed);
+        for row in idx.iter(&g) {
+            // hold the row lock so that a concurrent update can't slip in between
+            let Ok(data) = lock::read(mdl, row) else {
+                self.data.write().clear();
+                return false;
+            };
+            self.index_row(mdl, row.d_key(), data.fields());
+            let rows = self.progress.fetch_add(1, Ordering::Relaxed) + 1;
+            if rows % BUILD_RUN == 0 {
+                progress(rows);
+                if token.is_cancelled() {
+                    self.data.write().clear();
+                    return false;
+                }
+            }
+        }
+        self.ready.store(true, Ordering::Release);
+        true
+    }
+}
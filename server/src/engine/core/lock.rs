@@ -0,0 +1,457 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    row locks
+    ---
+    every row has its own RW lock. most statements only hold a row's lock for a moment (while a scan reads the row),
+    but some hold it for longer: updates and deletes lock each row that they change for writing, and statements
+    that read several rows as of the same point in time (such as `fetch`) lock all of them for reading until they're
+    done. every row lock outside the storage engine is taken through here, which registers them while they're held
+    or waited for, so that `sysctl list locks` can show who holds a row and who is waiting on it. rows are identified
+    by their model and a hash of their key (keys are never reported). the storage engine (which writes rows out and
+    restores them) locks rows directly since it can't give up on a row
+
+    a statement waits at most `system.lock_wait_timeout` milliseconds for one of these locks (for as long as it takes
+    if that's not set), and never past the statement's own timeout (see `core::session`), before it fails with
+    `SysLockWaitTimeout`. every wait (that is, every lock that wasn't free right away) is counted, along with how
+    long it took, in buckets by powers of ten.
+*/
+
+use {
+    crate::engine::{
+        core::{
+            index::{PrimaryIndexKey, Row, RowData},
+            model::ModelData,
+            session,
+        },
+        data::uuid::Uuid,
+        error::{QueryError, QueryResult},
+        fractal::GlobalInstanceLike,
+        storage::encode_str,
+    },
+    parking_lot::{RwLockReadGuard, RwLockWriteGuard},
+    std::{
+        collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+        fmt::Write,
+        hash::{Hash, Hasher},
+        ops::{Deref, DerefMut},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// The upper bounds (in microseconds) of the buckets that waits are counted in. Longer waits go in a last bucket
+const WAIT_BUCKETS_US: [u64; 4] = [1_000, 10_000, 100_000, 1_000_000];
+const WAIT_BUCKET_NAMES: [&str; 5] = ["1ms", "10ms", "100ms", "1s", "over_1s"];
+
+/// The longest that a statement waits for a row lock, in milliseconds (0 waits for as long as it takes)
+static WAIT_TIMEOUT_MS: AtomicU64 = AtomicU64::new(0);
+static LOCKS: RowLocks = RowLocks::new();
+
+/// Set the longest that a statement waits for a row lock, in milliseconds (0 waits for as long as it takes)
+pub fn set_wait_timeout(ms: u64) {
+    WAIT_TIMEOUT_MS.store(ms, Ordering::Relaxed)
+}
+
+fn wait_timeout() -> Option<Duration> {
+    let timeout = match WAIT_TIMEOUT_MS.load(Ordering::Relaxed) {
+        0 => None,
+        ms => Some(Duration::from_millis(ms)),
+    };
+    // a statement with a timeout doesn't wait past it
+    match session::deadline() {
+        Some((deadline, _)) => {
+            let left = deadline.saturating_duration_since(Instant::now());
+            Some(timeout.map_or(left, |timeout| timeout.min(left)))
+        }
+        None => timeout,
+    }
+}
+
+/// Lock a row for writing
+pub fn write<'g>(
+    mdl: &ModelData,
+    row: &'g Row,
+) -> QueryResult<Held<'static, RwLockWriteGuard<'g, RowData>>> {
+    let data = row.d_data();
+    LOCKS.acquire(
+        mdl.get_uuid(),
+        key_hash(row.d_key()),
+        LockMode::Write,
+        wait_timeout(),
+        || data.try_write(),
+        |timeout| match timeout {
+            Some(timeout) => data.try_write_for(timeout),
+            None => data.write().map(Some),
+        },
+    )
+}
+
+/// Lock a row for reading, applying any schema changes that it's missing first (see
+/// [`Row::resolve_schema_deltas_and_freeze`])
+pub fn read<'g>(
+    mdl: &ModelData,
+    row: &'g Row,
+) -> QueryResult<Held<'static, RwLockReadGuard<'g, RowData>>> {
+    let data = row.d_data();
+    let held = LOCKS.acquire(
+        mdl.get_uuid(),
+        key_hash(row.d_key()),
+        LockMode::Read,
+        wait_timeout(),
+        || data.try_upgradable_read(),
+        |timeout| match timeout {
+            Some(timeout) => data.try_upgradable_read_for(timeout),
+            None => data.upgradable_read().map(Some),
+        },
+    )?;
+    Ok(held.map(|rwl_ug| Row::resolve_schema_deltas_and_freeze_locked(rwl_ug, mdl.delta_state())))
+}
+
+/// Returns the row locks that are held or waited for, along with statistics about how long statements waited for
+/// them, as a JSON object
+pub fn describe(global: &impl GlobalInstanceLike) -> String {
+    let models: HashMap<Uuid, String> = global
+        .state()
+        .namespace()
+        .idx_models()
+        .read()
+        .iter()
+        .map(|(entity, model)| {
+            (
+                model.data().get_uuid(),
+                format!("{}.{}", entity.space(), entity.entity()),
+            )
+        })
+        .collect();
+    LOCKS.describe(
+        Instant::now(),
+        WAIT_TIMEOUT_MS.load(Ordering::Relaxed),
+        |uuid| {
+            models
+                .get(uuid)
+                .cloned()
+                .unwrap_or_else(|| uuid.to_string())
+        },
+    )
+}
+
+fn key_hash(key: &PrimaryIndexKey) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LockMode {
+    Read,
+    Write,
+}
+
+impl LockMode {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+}
+
+/// A row lock that is held. It stays registered until it's released
+pub struct Held<'a, G> {
+    guard: G,
+    _registration: Registration<'a>,
+}
+
+impl<'a, G> Held<'a, G> {
+    fn map<H>(self, f: impl FnOnce(G) -> H) -> Held<'a, H> {
+        Held {
+            guard: f(self.guard),
+            _registration: self._registration,
+        }
+    }
+}
+
+impl<'a, G: Deref> Deref for Held<'a, G> {
+    type Target = G::Target;
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a, G: DerefMut> DerefMut for Held<'a, G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+/// Removes a lock from the registry when dropped
+struct Registration<'a> {
+    locks: &'a RowLocks,
+    id: u64,
+}
+
+impl<'a> Drop for Registration<'a> {
+    fn drop(&mut self) {
+        self.locks.entries.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LockEntry {
+    model: Uuid,
+    key_hash: u64,
+    waiting: bool,
+    mode: LockMode,
+    /// when the lock was acquired (or when the wait started)
+    since: Instant,
+}
+
+struct RowLocks {
+    next_id: AtomicU64,
+    entries: Mutex<BTreeMap<u64, LockEntry>>,
+    waits: AtomicU64,
+    timeouts: AtomicU64,
+    wait_total_us: AtomicU64,
+    wait_max_us: AtomicU64,
+    wait_buckets: [AtomicU64; 5],
+}
+
+impl RowLocks {
+    const fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(0),
+            entries: Mutex::new(BTreeMap::new()),
+            waits: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            wait_total_us: AtomicU64::new(0),
+            wait_max_us: AtomicU64::new(0),
+            wait_buckets: [
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+                AtomicU64::new(0),
+            ],
+        }
+    }
+    fn register(
+        &self,
+        model: Uuid,
+        key_hash: u64,
+        mode: LockMode,
+        waiting: bool,
+    ) -> Registration<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.entries.lock().unwrap().insert(
+            id,
+            LockEntry {
+                model,
+                key_hash,
+                waiting,
+                mode,
+                since: Instant::now(),
+            },
+        );
+        Registration { locks: self, id }
+    }
+    /// Take a lock with `try_now`, and if it isn't free, wait for it (for at most `timeout`) with `wait`
+    fn acquire<G>(
+        &self,
+        model: Uuid,
+        key_hash: u64,
+        mode: LockMode,
+        timeout: Option<Duration>,
+        try_now: impl FnOnce() -> QueryResult<Option<G>>,
+        wait: impl FnOnce(Option<Duration>) -> QueryResult<Option<G>>,
+    ) -> QueryResult<Held<'_, G>> {
+        let guard = match try_now()? {
+            Some(guard) => guard,
+            None => {
+                let _waiting = self.register(model, key_hash, mode, true);
+                let started = Instant::now();
+                let guard = wait(timeout)?;
+                self.record_wait(started.elapsed(), guard.is_none());
+                match guard {
+                    Some(guard) => guard,
+                    None => {
+                        return Err(QueryError::SysLockWaitTimeout
+                            .with_detail("timeout_ms", timeout.unwrap_or_default().as_millis()))
+                    }
+                }
+            }
+        };
+        Ok(Held {
+            guard,
+            _registration: self.register(model, key_hash, mode, false),
+        })
+    }
+    fn record_wait(&self, waited: Duration, timed_out: bool) {
+        let us = waited.as_micros() as u64;
+        self.waits.fetch_add(1, Ordering::Relaxed);
+        if timed_out {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+        }
+        self.wait_total_us.fetch_add(us, Ordering::Relaxed);
+        self.wait_max_us.fetch_max(us, Ordering::Relaxed);
+        let bucket = WAIT_BUCKETS_US
+            .iter()
+            .position(|max| us <= *max)
+            .unwrap_or(WAIT_BUCKETS_US.len());
+        self.wait_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+    fn describe(
+        &self,
+        now: Instant,
+        timeout_ms: u64,
+        model_name: impl Fn(&Uuid) -> String,
+    ) -> String {
+        let mut entries: Vec<LockEntry> = self.entries.lock().unwrap().values().cloned().collect();
+        entries.sort_by_key(|entry| (entry.model, entry.key_hash, entry.since));
+        let mut locks = String::new();
+        let mut rest = &entries[..];
+        while let Some(first) = rest.first() {
+            let same_row = rest
+                .iter()
+                .take_while(|entry| {
+                    (entry.model == first.model) & (entry.key_hash == first.key_hash)
+                })
+                .count();
+            let (row, tail) = rest.split_at(same_row);
+            rest = tail;
+            if !locks.is_empty() {
+                locks.push(',');
+            }
+            locks.push_str("{\"model\":");
+            encode_str(&mut locks, &model_name(&row[0].model));
+            let _ = write!(locks, ",\"key_hash\":\"{:016x}\"", row[0].key_hash);
+            for (list, waiting, time) in [
+                ("holders", false, "held_us"),
+                ("waiters", true, "waiting_us"),
+            ] {
+                let _ = write!(locks, ",\"{list}\":[");
+                for (i, entry) in row
+                    .iter()
+                    .filter(|entry| entry.waiting == waiting)
+                    .enumerate()
+                {
+                    if i != 0 {
+                        locks.push(',');
+                    }
+                    let _ = write!(
+                        locks,
+                        "{{\"mode\":\"{}\",\"{time}\":{}}}",
+                        entry.mode.name(),
+                        now.saturating_duration_since(entry.since).as_micros()
+                    );
+                }
+                locks.push(']');
+            }
+            locks.push('}');
+        }
+        let mut buckets = String::new();
+        for (i, (name, count)) in WAIT_BUCKET_NAMES.iter().zip(&self.wait_buckets).enumerate() {
+            if i != 0 {
+                buckets.push(',');
+            }
+            let _ = write!(buckets, "\"{name}\":{}", count.load(Ordering::Relaxed));
+        }
+        format!(
+            "{{\"wait_timeout_ms\":{},\"waits\":{{\"count\":{},\"timeouts\":{},\"total_us\":{},\"max_us\":{},\"buckets\":{{{buckets}}}}},\"locks\":[{locks}]}}",
+            match timeout_ms {
+                0 => "null".to_owned(),
+                ms => ms.to_string(),
+            },
+            self.waits.load(Ordering::Relaxed),
+            self.timeouts.load(Ordering::Relaxed),
+            self.wait_total_us.load(Ordering::Relaxed),
+            self.wait_max_us.load(Ordering::Relaxed),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, parking_lot::RwLock, std::thread};
+
+    #[test]
+    fn holders_waiters_and_timeouts() {
+        let locks = RowLocks::new();
+        let (model, row) = (Uuid::new(), RwLock::new(()));
+        let lock = |timeout| {
+            locks.acquire(
+                model,
+                0xcafe,
+                LockMode::Write,
+                timeout,
+                || Ok(row.try_write()),
+                |timeout| match timeout {
+                    Some(timeout) => Ok(row.try_write_for(timeout)),
+                    None => Ok(Some(row.write())),
+                },
+            )
+        };
+        let describe = || locks.describe(Instant::now(), 10, |_| "myspace.mymodel".into());
+        // a free lock is taken right away
+        let held = lock(Some(Duration::from_millis(10))).unwrap();
+        let report = describe();
+        assert!(report.starts_with(
+            r#"{"wait_timeout_ms":10,"waits":{"count":0,"timeouts":0,"total_us":0,"max_us":0,"buckets":{"1ms":0,"10ms":0,"100ms":0,"1s":0,"over_1s":0}},"locks":[{"model":"myspace.mymodel","key_hash":"000000000000cafe","holders":[{"mode":"write","held_us":"#
+        ));
+        assert!(report.ends_with(r#"}],"waiters":[]}]}"#));
+        // but a held lock is only waited on until the timeout
+        assert_eq!(
+            lock(Some(Duration::from_millis(10))).err(),
+            Some(QueryError::SysLockWaitTimeout)
+        );
+        assert!(describe().starts_with(r#"{"wait_timeout_ms":10,"waits":{"count":1,"timeouts":1,"#));
+        // while a statement waits, it's listed as a waiter
+        thread::scope(|s| {
+            let waiter = s.spawn(|| lock(None).is_ok());
+            while !describe().contains(r#""waiters":[{"mode":"write","waiting_us":"#) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            drop(held);
+            assert!(waiter.join().unwrap());
+        });
+        let report = describe();
+        assert!(report.starts_with(r#"{"wait_timeout_ms":10,"waits":{"count":2,"timeouts":1,"#));
+        assert!(report.ends_with(r#""locks":[]}"#));
+        let buckets: u64 = locks
+            .wait_buckets
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .sum();
+        assert_eq!(buckets, 2);
+        assert!(locks
+            .describe(Instant::now(), 0, |_| String::new())
+            .starts_with(r#"{"wait_timeout_ms":null,"#));
+    }
+}
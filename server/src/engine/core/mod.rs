@@ -24,13 +24,24 @@
  *
 */
 
+pub(in crate::engine) mod catalog;
 pub(in crate::engine) mod dcl;
 mod ddl_misc;
 pub(in crate::engine) mod dml;
 pub(in crate::engine) mod exec;
+mod freeze;
 pub(in crate::engine) mod index;
+pub(in crate::engine) mod lock;
 pub(in crate::engine) mod model;
+pub(in crate::engine) mod names;
+pub(in crate::engine) mod notice;
+pub(in crate::engine) mod profile;
 pub(in crate::engine) mod query_meta;
+pub(in crate::engine) mod quota;
+pub(in crate::engine) mod record;
+pub(in crate::engine) mod relocate;
+pub(in crate::engine) mod session;
+pub(in crate::engine) mod snapshot;
 pub(in crate::engine) mod space;
 pub(in crate::engine) mod system_db;
 // util
@@ -40,25 +51,30 @@ mod util;
 pub(super) mod tests;
 
 // re-exports
-pub use self::util::{EntityID, EntityIDRef};
+pub use self::{
+    freeze::Freeze,
+    snapshot::Snapshots,
+    util::{EntityID, EntityIDRef},
+};
 
 // imports
 use {
     self::{
         dml::QueryExecMeta,
-        model::{Model, ModelData},
+        model::{props::Durability, DeltaVersion, Model, ModelData},
     },
     crate::{
         engine::{
             core::space::Space,
             error::{QueryError, QueryResult},
-            fractal::{FractalGNSDriver, GlobalInstanceLike},
+            fractal::{jobs::Jobs, FractalGNSDriver, GlobalInstanceLike, ModelUniqueID},
             idx::IndexST,
+            storage::driver::StorageDriver,
         },
         util::compiler,
     },
-    parking_lot::RwLock,
-    std::collections::HashMap,
+    parking_lot::{Mutex, RwLock},
+    std::{collections::HashMap, time::Duration},
 };
 
 /// Use this for now since it substitutes for a file lock (and those syscalls are expensive),
@@ -68,19 +84,59 @@ type RWLIdx<K, V> = RwLock<IndexST<K, V>>;
 #[derive(Debug)]
 pub struct GlobalNS {
     data: GNSData,
+    storage: Box<dyn StorageDriver>,
     driver: FractalGNSDriver,
+    freeze: Freeze,
+    snapshots: Snapshots,
+    jobs: Jobs,
 }
 
 impl GlobalNS {
-    pub fn new(data: GNSData, driver: FractalGNSDriver) -> Self {
-        Self { data, driver }
+    pub fn new(
+        data: GNSData,
+        storage: Box<dyn StorageDriver>,
+        driver: FractalGNSDriver,
+        jobs: Jobs,
+    ) -> Self {
+        Self {
+            data,
+            storage,
+            driver,
+            freeze: Freeze::default(),
+            snapshots: Snapshots::default(),
+            jobs,
+        }
     }
     pub fn namespace(&self) -> &GNSData {
         &self.data
     }
+    /// Returns the storage driver of this instance
+    pub fn storage(&self) -> &dyn StorageDriver {
+        &*self.storage
+    }
     pub fn gns_driver(&self) -> &FractalGNSDriver {
         &self.driver
     }
+    pub fn freeze(&self) -> &Freeze {
+        &self.freeze
+    }
+    pub fn snapshots(&self) -> &Snapshots {
+        &self.snapshots
+    }
+    pub fn jobs(&self) -> &Jobs {
+        &self.jobs
+    }
+    /// Run `f` with the model like [`GNSData::with_model`] does, except that a model in the `sys` space is built from
+    /// the live state (see [`catalog`]). Use this for reads only
+    pub fn with_model<'a, T, F>(&self, entity: EntityIDRef<'a>, f: F) -> QueryResult<T>
+    where
+        F: FnOnce(&ModelData) -> QueryResult<T>,
+    {
+        match catalog::build(self, entity) {
+            Some(model) => f(&model),
+            None => self.data.with_model(entity, f),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -88,6 +144,9 @@ pub struct GNSData {
     idx_mdl: RWLIdx<EntityID, Model>,
     idx: RWLIdx<Box<str>, Space>,
     sys_db: system_db::SystemDatabase,
+    /// the directories of the spaces whose drops were replayed from the journal. the directory is deleted in the
+    /// background after the drop is committed, so it might still be around if the server went down in between
+    dropped_dirs: Mutex<Vec<String>>,
 }
 
 impl GNSData {
@@ -96,6 +155,7 @@ impl GNSData {
             idx_mdl: RWLIdx::default(),
             idx: RWLIdx::default(),
             sys_db: system_db::SystemDatabase::empty(),
+            dropped_dirs: Mutex::new(vec![]),
         }
     }
     pub fn ddl_with_all_mut<T>(
@@ -119,10 +179,10 @@ impl GNSData {
         f: impl FnOnce(&mut Space) -> QueryResult<T>,
     ) -> QueryResult<T> {
         let mut spaces = self.idx.write();
-        let Some(space) = spaces.get_mut(space) else {
-            return Err(QueryError::QExecObjectNotFound);
+        let Some(space_ref) = spaces.get_mut(space) else {
+            return Err(QueryError::QExecObjectNotFound.with_detail("entity", space));
         };
-        f(space)
+        f(space_ref)
     }
     pub fn with_model_space_mut_for_ddl<'a, T, F>(
         &self,
@@ -134,7 +194,7 @@ impl GNSData {
     {
         let mut mdl_idx = self.idx_mdl.write();
         let Some(model) = mdl_idx.get_mut(&entity) else {
-            return Err(QueryError::QExecObjectNotFound);
+            return Err(QueryError::QExecObjectNotFound.with_detail("entity", entity));
         };
         let space_read = self.idx.read();
         let space = space_read.get(entity.space()).unwrap();
@@ -146,7 +206,7 @@ impl GNSData {
     {
         let mdl_idx = self.idx_mdl.read();
         let Some(model) = mdl_idx.get(&entity) else {
-            return Err(QueryError::QExecObjectNotFound);
+            return Err(QueryError::QExecObjectNotFound.with_detail("entity", entity));
         };
         f(model.data())
     }
@@ -169,31 +229,113 @@ impl GNSData {
     pub fn sys_db(&self) -> &system_db::SystemDatabase {
         &self.sys_db
     }
+    /// Record the directory of a space whose drop was replayed, so that it can be cleaned up once recovery is done
+    pub fn note_dropped_dir(&self, dir: String) {
+        self.dropped_dirs.lock().push(dir)
+    }
+    /// Returns (and forgets) the directories recorded with [`Self::note_dropped_dir`]
+    pub fn take_dropped_dirs(&self) -> Vec<String> {
+        core::mem::take(&mut *self.dropped_dirs.lock())
+    }
+}
+
+/// Returns the backlog and the webhook lag of the model, if it exists (see [`crate::engine::fractal::throttle`])
+fn write_signals(global: &impl GlobalInstanceLike, entity: EntityIDRef) -> Option<(u64, u64)> {
+    let mdl_idx = global.state().namespace().idx_mdl.read();
+    let model = mdl_idx.get(&entity)?;
+    Some((
+        model.data().delta_state().pending_data_deltas() as u64,
+        model.data().webhook_lag(),
+    ))
+}
+
+/// Returns how long a write to the model has to wait before it runs, if its changes (or webhooks) fell behind (see
+/// [`crate::engine::fractal::throttle`])
+pub fn write_delay(global: &impl GlobalInstanceLike, entity: EntityIDRef) -> Option<Duration> {
+    if !global.write_throttle().is_enabled() {
+        return None;
+    }
+    let (backlog, lag) = write_signals(global, entity)?;
+    global.write_throttle().delay(backlog, lag)
+}
+
+/// Reject a write to the model if its changes (or webhooks) fell too far behind
+fn throttle_write(global: &impl GlobalInstanceLike, entity: EntityIDRef) -> QueryResult<()> {
+    // the write fails once it looks the model up if it doesn't exist
+    match write_signals(global, entity) {
+        Some((backlog, lag)) => global.write_throttle().admit(backlog, lag),
+        None => Ok(()),
+    }
 }
 
 pub(self) fn with_model_for_data_update<'a, F>(
     global: &impl GlobalInstanceLike,
     entity: EntityIDRef<'a>,
     f: F,
-) -> QueryResult<()>
+) -> QueryResult<Option<DeltaVersion>>
 where
     F: FnOnce(&ModelData) -> QueryResult<QueryExecMeta>,
 {
+    with_model_for_data_write(global, entity, |model| f(model.data()))
+}
+
+/// Like [`with_model_for_data_update`], but for writes that also need the model's driver
+pub(self) fn with_model_for_data_write<'a, F>(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef<'a>,
+    f: F,
+) -> QueryResult<Option<DeltaVersion>>
+where
+    F: FnOnce(&Model) -> QueryResult<QueryExecMeta>,
+{
+    // a delayed write already waited (see `write_delay`), so this only rejects writes to a model that's too far behind
+    if global.write_throttle().is_enabled() {
+        throttle_write(global, entity)?;
+    }
     let mdl_idx = global.state().namespace().idx_mdl.read();
     let Some(model) = mdl_idx.get(&entity) else {
-        return Err(QueryError::QExecObjectNotFound);
+        return Err(QueryError::QExecObjectNotFound.with_detail("entity", entity));
     };
-    if compiler::likely(model.driver().status().is_healthy()) {
-        let r = f(model.data())?;
-        model::DeltaState::guard_delta_overflow(
-            global,
-            entity.space(),
-            entity.entity(),
-            model.data(),
-            r,
-        );
-        Ok(())
+    // a view is only changed through the model that it is derived from
+    if model.data().props().view().is_some() {
+        return Err(QueryError::QExecDmlViewIsReadOnly);
+    }
+    global.state().freeze().check(entity.space())?;
+    global.health().check_writable()?;
+    // while the model's changes can't be written to disk, the flush policy decides if we take any more
+    if compiler::likely(model.driver().status().is_healthy())
+        || global.flush_policy().admit_write(model.data())
+    {
+        let r = f(model);
+        if let Some(columns) = model.data().columns() {
+            columns.sync(model.data());
+        }
+        if let Some(maintained) = model.data().maintained() {
+            maintained.sync(model.data());
+        }
+        if !model.data().views().is_empty() {
+            dml::refresh_views(global, &mdl_idx, entity.space(), model.data());
+        }
+        let r = r?;
+        let version = r.version();
+        // the session can override the durability of the model
+        let durability = session::durability().unwrap_or(model.data().props().durability());
+        if durability == Durability::Strict {
+            // the changes to a strict model are on disk before the statement returns
+            let mdl_id =
+                ModelUniqueID::new(entity.space(), entity.entity(), model.data().get_uuid());
+            model.driver().flush(global, mdl_id, model.data())?;
+        } else {
+            model::DeltaState::guard_delta_overflow(
+                global,
+                entity.space(),
+                entity.entity(),
+                model.data(),
+                r,
+            );
+        }
+        Ok(version)
     } else {
-        compiler::cold_call(|| Err(QueryError::SysServerError))
+        compiler::cold_call(|| Err(QueryError::SysStorageDegraded))
     }
 }
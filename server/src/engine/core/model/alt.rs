@@ -25,10 +25,10 @@
 */
 
 use {
-    super::{Field, Layer, ModelData},
+    super::{check::FieldChecks, Field, FieldProps, Layer, ModelData},
     crate::{
         engine::{
-            core::EntityIDRef,
+            core::{lock, names, EntityIDRef},
             data::{
                 tag::{DataTag, TagClass},
                 DictEntryGeneric,
@@ -43,6 +43,7 @@ use {
                 },
                 lex::Ident,
             },
+            sync::atm::cpin,
             txn::{gns, ModelIDRef},
         },
         util,
@@ -93,6 +94,10 @@ impl<'a> AlterPlan<'a> {
         mdl: &ModelData,
         AlterModel { model, kind }: AlterModel<'a>,
     ) -> QueryResult<AlterPlan<'a>> {
+        // a view has the shape of its `select`, so it can't be altered
+        if mdl.props().view().is_some() {
+            return Err(QueryError::QExecDdlModelAlterIllegal);
+        }
         let mut no_lock = true;
         let mut okay = true;
         let action = match kind {
@@ -106,7 +111,15 @@ impl<'a> AlterPlan<'a> {
                     let not_pk = mdl.not_pk(id);
                     let exists = !no_field(mdl, id.as_str());
                     not_found = !exists;
-                    not_pk & exists
+                    // an indexed field (or the source of a computed field, or a field used by a view) can't be removed
+                    // until the index (or computed field, or view) is dropped. neither can a field that a maintained
+                    // aggregate uses
+                    not_pk
+                        & exists
+                        & !mdl.is_indexed(id.as_str())
+                        & !mdl.is_computed_source(id.as_str())
+                        & !mdl.views().uses_field(id.as_str())
+                        & !mdl.is_aggregated(id.as_str())
                 }) {
                     can_ignore!(AlterAction::Remove(r))
                 } else if not_found {
@@ -127,8 +140,16 @@ impl<'a> AlterPlan<'a> {
                     okay &= no_field(mdl, &field_name) & mdl.not_pk(&field_name);
                     let is_nullable = check_nullable(&mut props)?;
                     let layers = Field::parse_layers(layers, is_nullable)?;
+                    // existing rows would have no value for a computed field
+                    okay &= !layers.is_computed();
                     okay &= add.st_insert(field_name.as_str().into(), layers);
                 }
+                // with case insensitive names, no two of the new fields can only differ in case (they are matched
+                // against the existing fields when the statement is lexed)
+                okay &= !(names::case_insensitive()
+                    && names::any_differ_in_case(
+                        add.stseq_ord_key().map(|field: &Box<str>| &**field),
+                    ));
                 can_ignore!(AlterAction::Add(add))
             }
             AlterKind::Update(updated_fields) => {
@@ -144,6 +165,15 @@ impl<'a> AlterPlan<'a> {
                     } = updated_fields.next().unwrap();
                     // enforce pk
                     mdl.guard_pk(&field_name)?;
+                    // indexed fields (and sources of computed fields, and fields used by views or by maintained
+                    // aggregates) can't be changed
+                    if mdl.is_indexed(&field_name)
+                        | mdl.is_computed_source(&field_name)
+                        | mdl.views().uses_field(&field_name)
+                        | mdl.is_aggregated(&field_name)
+                    {
+                        return Err(QueryError::QExecDdlModelAlterIllegal);
+                    }
                     // get the current field
                     let Some(current_field) = mdl.fields().st_get(field_name.as_str()) else {
                         return Err(QueryError::QExecUnknownField);
@@ -201,6 +231,8 @@ impl<'a> AlterPlan<'a> {
         let mut okay = true;
         let mut new_field = current.clone();
         new_field.nullable = nullable;
+        let mut new_checks: Option<FieldChecks> = None;
+        let mut new_overflow = None;
         let mut zipped_layers = layers
             .into_iter()
             .rev()
@@ -210,8 +242,21 @@ impl<'a> AlterPlan<'a> {
         while (zipped_layers.len() != 0) & okay {
             let ((LayerSpec { ty, props }, current_layer), new_layer) =
                 zipped_layers.next().unwrap();
+            if new_checks.is_none() {
+                // checks are set on the outermost layer (but a field can't be made computed or generated, or be given a
+                // default)
+                let props =
+                    FieldProps::try_new(props).ok_or(QueryError::QExecDdlInvalidProperties)?;
+                okay &= props.computed().is_none()
+                    & props.keygen().is_none()
+                    & props.literal_default().is_none()
+                    & props.decimal().is_none();
+                new_overflow = Some(props.overflow());
+                new_checks = Some(props.into_checks());
+            } else {
+                okay &= props.is_empty();
+            }
             // actually parse the new layer
-            okay &= props.is_empty();
             let Some(new_parsed_layer) = Layer::get_layer(&ty) else {
                 return Err(QueryError::QExecDdlInvalidTypeDefinition);
             };
@@ -235,6 +280,20 @@ impl<'a> AlterPlan<'a> {
             }
             *new_layer = new_parsed_layer;
         }
+        // if the type is given, the checks and the overflow policy are replaced (otherwise they're left as is)
+        if let Some(new_checks) = new_checks {
+            if !new_checks.applies_to(new_field.layers[0].tag.tag_class()) {
+                return Err(QueryError::QExecDdlInvalidProperties);
+            }
+            deltasize += new_field.props.set_checks(new_checks) as usize;
+        }
+        if let Some(new_overflow) = new_overflow {
+            let class = new_field.layers[0].tag.tag_class();
+            if !new_overflow.map_or(true, |policy| policy.applies_to(class)) {
+                return Err(QueryError::QExecDdlInvalidProperties);
+            }
+            deltasize += new_field.props.set_overflow(new_overflow) as usize;
+        }
         *super_nlck &= no_lock;
         *super_okay &= okay;
         if okay {
@@ -246,6 +305,28 @@ impl<'a> AlterPlan<'a> {
 }
 
 impl ModelData {
+    /// Validate all the rows in the model against the checks of the given (updated) fields
+    fn check_existing_data(&self, updated: &IndexST<Box<str>, Field>) -> QueryResult<()> {
+        let checked: Vec<(&Box<str>, &Field)> = updated
+            .st_iter_kv()
+            .filter(|(_, field)| !field.props().checks().is_empty())
+            .collect();
+        if checked.is_empty() {
+            return Ok(());
+        }
+        let idx = self.primary_index();
+        let _latch = idx.acquire_exclusive();
+        let g = cpin();
+        for row in idx.iter(&g) {
+            let data = lock::read(self, row)?;
+            for (field_id, field) in checked.iter() {
+                if let Some(dc) = data.fields().st_get(field_id.as_ref()) {
+                    field.check(field_id.as_ref(), dc)?;
+                }
+            }
+        }
+        Ok(())
+    }
     pub fn transactional_exec_alter<G: GlobalInstanceLike>(
         global: &G,
         alter: AlterModel,
@@ -255,6 +336,7 @@ impl ModelData {
             .state()
             .namespace()
             .with_model_space_mut_for_ddl(alter.model, |space, model| {
+                global.state().freeze().check(space_name)?;
                 // prepare plan
                 let plan = AlterPlan::fdeltas(model, alter)?;
                 // we have a legal plan; acquire exclusive if we need it
@@ -305,6 +387,8 @@ impl ModelData {
                         });
                     }
                     AlterAction::Update(updated) => {
+                        // the existing data must pass the new checks
+                        Self::check_existing_data(model, &updated)?;
                         // prepare txn
                         let txn = gns::model::AlterModelUpdateTxn::new(
                             ModelIDRef::new_ref(&space_name, space, &model_name, model),
@@ -0,0 +1,217 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    backfills
+    ---
+    a field that is added with `alter model ... add` is null in the rows that existed before it, which isn't a value
+    that a field that can't be null would otherwise hold. `sysctl backfill model` fills in the default of every such
+    field (that has one) in those rows, so that they read like rows that were inserted after the field was added. the
+    model is walked one partition at a time: the keys of the rows that have something to fill in are collected, and
+    the rows are then updated in batches of BACKFILL_RUN (each of which is an update like any other: it is journaled,
+    reindexed and let through the write throttle and freezes). DDL and writes only wait for a batch, not the whole
+    backfill, and a cancelled backfill leaves the rows it already filled in
+*/
+
+use {
+    crate::engine::{
+        core::{dml, index::PrimaryIndexKey, lock, EntityIDRef},
+        error::{QueryError, QueryResult},
+        fractal::{
+            jobs::{self, Job, JobKind, JobStatus},
+            GlobalInstanceLike, WorkClass,
+        },
+        idx::{STIndex, STIndexSeq},
+        sync::atm::cpin,
+    },
+    std::time::Instant,
+};
+
+/// Rows are filled in in batches of this size
+const BACKFILL_RUN: usize = 1024;
+
+/// The result of a backfill (see [`backfill_model`])
+#[derive(Debug, PartialEq)]
+pub struct BackfillReport {
+    /// the fields that were backfilled
+    fields: Vec<Box<str>>,
+    /// the rows that were walked
+    rows: u64,
+    /// the rows that had a field filled in
+    filled: u64,
+}
+
+impl BackfillReport {
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+    pub fn filled(&self) -> u64 {
+        self.filled
+    }
+    /// Returns the report as a JSON object
+    pub fn describe(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| format!("\"{field}\""))
+            .collect();
+        format!(
+            "{{\"fields\":[{}],\"rows\":{},\"filled\":{}}}",
+            fields.join(","),
+            self.rows,
+            self.filled
+        )
+    }
+}
+
+/// Fill in the default of every field that can't be null in the rows where it is null (see the module docs). Fails if
+/// the model has no such field. Since this is a full scan, it is rejected while the server is under memory pressure
+pub fn backfill_model(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+) -> QueryResult<BackfillReport> {
+    backfill(global, entity, None).map(|report| report.unwrap())
+}
+
+/// Start a job (owned by the given user) that backfills the model (see [`backfill_model`]) and return its id. The
+/// job's result is the report
+pub fn backfill_model_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    entity: EntityIDRef,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    let (space, model): (Box<str>, Box<str>) = (entity.space().into(), entity.entity().into());
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::BackfillModel,
+        format!("{space}.{model}").into_boxed_str(),
+        move |global, job| {
+            let entity = EntityIDRef::new(&space, &model);
+            match backfill(global, entity, Some(job)) {
+                Ok(Some(report)) => JobStatus::Completed(Some(report.describe())),
+                Ok(None) => JobStatus::Cancelled,
+                Err(e) => JobStatus::failed(e),
+            }
+        },
+    ))
+}
+
+/// Returns [`None`] if the job (if any) was cancelled
+fn backfill(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    job: Option<&Job>,
+) -> QueryResult<Option<BackfillReport>> {
+    global.admit_expensive_query()?;
+    let (fields, partitions) = {
+        let models = global.state().namespace().idx_models().read();
+        let Some(model) = models.get(&entity) else {
+            return Err(QueryError::QExecObjectNotFound);
+        };
+        let mdl = model.data();
+        let fields: Vec<Box<str>> = mdl
+            .fields()
+            .stseq_ord_kv()
+            .filter(|(_, field)| field.backfills())
+            .map(|(field_id, _)| field_id.as_str().into())
+            .collect();
+        if let Some(job) = job {
+            job.set_total(mdl.primary_index().count() as u64);
+        }
+        (fields, mdl.primary_index().partitions())
+    };
+    if fields.is_empty() {
+        return Err(QueryError::QExecDdlInvalidProperties);
+    }
+    let mut report = BackfillReport {
+        fields,
+        rows: 0,
+        filled: 0,
+    };
+    for partition in 0..partitions {
+        let keys = scan_partition(global, entity, partition, &mut report)?;
+        let mut pending = keys.len() as u64;
+        for batch in keys.chunks(BACKFILL_RUN) {
+            let started_at = Instant::now();
+            report.filled += dml::backfill_rows(global, entity, batch)?;
+            pending -= batch.len() as u64;
+            if let Some(job) = job {
+                job.set_done(report.rows - pending);
+                if job.token().is_cancelled() {
+                    return Ok(None);
+                }
+                global.scheduler().throttle(
+                    WorkClass::Backfill,
+                    batch.len() as u64,
+                    started_at.elapsed(),
+                );
+            }
+        }
+    }
+    if let Some(job) = job {
+        job.set_done(report.rows);
+    }
+    Ok(Some(report))
+}
+
+/// Returns the keys of the rows in the partition with a backfilled field that is null
+fn scan_partition(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    partition: usize,
+    report: &mut BackfillReport,
+) -> QueryResult<Vec<PrimaryIndexKey>> {
+    let models = global.state().namespace().idx_models().read();
+    let Some(model) = models.get(&entity) else {
+        return Err(QueryError::QExecObjectNotFound);
+    };
+    let mdl = model.data();
+    let index = mdl.primary_index();
+    if partition >= index.partitions() {
+        // the model was dropped and created again (with fewer partitions) since we started
+        return Ok(vec![]);
+    }
+    let g = cpin();
+    let mut keys = vec![];
+    for row in index.iter_partition(partition, &g) {
+        let data = lock::read(mdl, row)?;
+        report.rows += 1;
+        if data.is_tombstoned() {
+            continue;
+        }
+        let null = report.fields.iter().any(|field| {
+            data.fields()
+                .st_get(&**field)
+                .map_or(false, |data| data.is_null())
+        });
+        if null {
+            keys.push(row.d_key().clone());
+        }
+    }
+    Ok(keys)
+}
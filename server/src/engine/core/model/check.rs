@@ -0,0 +1,271 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::engine::{
+        core::util,
+        data::{cell::Datacell, tag::TagClass, DictEntryGeneric, DictGeneric},
+        error::{QueryError, QueryResult},
+        storage::encode_str,
+    },
+    regex::Regex,
+    std::{cmp::Ordering, fmt},
+};
+
+/// A bound used by the `min` and `max` checks
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CheckBound {
+    Int(i128),
+    Float(f64),
+}
+
+impl CheckBound {
+    fn from_dc(dc: &Datacell) -> Option<Self> {
+        match dc.kind() {
+            TagClass::UnsignedInt => Some(Self::Int(dc.uint() as i128)),
+            TagClass::SignedInt => Some(Self::Int(dc.sint() as i128)),
+            TagClass::Float => Some(Self::Float(dc.float())),
+            _ => None,
+        }
+    }
+    fn to_dc(&self) -> Datacell {
+        match *self {
+            Self::Int(i) if i >= 0 => Datacell::new_uint_default(i as u64),
+            Self::Int(i) => Datacell::new_sint_default(i as i64),
+            Self::Float(f) => Datacell::new_float_default(f),
+        }
+    }
+    fn as_f64(&self) -> f64 {
+        match *self {
+            Self::Int(i) => i as f64,
+            Self::Float(f) => f,
+        }
+    }
+    /// Compare a numeric value against this bound. Returns [`None`] if the value is not a number (or is NaN)
+    fn cmp_dc(&self, dc: &Datacell) -> Option<Ordering> {
+        let v = match dc.kind() {
+            TagClass::UnsignedInt => Self::Int(dc.uint() as i128),
+            TagClass::SignedInt => Self::Int(dc.sint() as i128),
+            TagClass::Float => Self::Float(dc.float()),
+            _ => return None,
+        };
+        match (v, *self) {
+            (Self::Int(a), Self::Int(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+}
+
+impl fmt::Display for CheckBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Float(fl) => write!(f, "{fl}"),
+        }
+    }
+}
+
+/// The pattern used by a `regex` check. The whole value must match the pattern
+#[derive(Debug, Clone)]
+pub struct CheckRegex {
+    pattern: Box<str>,
+    compiled: Regex,
+}
+
+impl PartialEq for CheckRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl CheckRegex {
+    fn new(pattern: &str) -> Option<Self> {
+        Some(Self {
+            pattern: pattern.into(),
+            compiled: util::compile_regex(pattern, true)?,
+        })
+    }
+}
+
+/// Declarative checks on the values of a field (set using the `min`, `max`, `maxlen` and `regex` properties). Nulls
+/// are never checked
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct FieldChecks {
+    min: Option<CheckBound>,
+    max: Option<CheckBound>,
+    maxlen: Option<u64>,
+    regex: Option<CheckRegex>,
+}
+
+impl FieldChecks {
+    /// smallest allowed value (numeric fields)
+    pub const KEY_MIN: &'static str = "min";
+    /// largest allowed value (numeric fields)
+    pub const KEY_MAX: &'static str = "max";
+    /// maximum length (characters for strings, bytes for binary and elements for lists)
+    pub const KEY_MAXLEN: &'static str = "maxlen";
+    /// a pattern that values must match (string fields)
+    pub const KEY_REGEX: &'static str = "regex";
+    /// Attempt to set the given property. Returns [`None`] if the value is illegal and `Some(false)` if this is not a
+    /// check property
+    pub(super) fn try_set(&mut self, key: &str, value: &DictEntryGeneric) -> Option<bool> {
+        let DictEntryGeneric::Data(d) = value else {
+            return match key {
+                Self::KEY_MIN | Self::KEY_MAX | Self::KEY_MAXLEN | Self::KEY_REGEX => None,
+                _ => Some(false),
+            };
+        };
+        match key {
+            Self::KEY_MIN => self.min = Some(CheckBound::from_dc(d)?),
+            Self::KEY_MAX => self.max = Some(CheckBound::from_dc(d)?),
+            Self::KEY_MAXLEN => self.maxlen = Some(d.try_uint()?),
+            Self::KEY_REGEX => self.regex = Some(CheckRegex::new(d.try_str()?)?),
+            _ => return Some(false),
+        }
+        Some(true)
+    }
+    /// Returns the number of checks that are set
+    pub fn len(&self) -> usize {
+        self.min.is_some() as usize
+            + self.max.is_some() as usize
+            + self.maxlen.is_some() as usize
+            + self.regex.is_some() as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn min(&self) -> Option<CheckBound> {
+        self.min
+    }
+    pub fn max(&self) -> Option<CheckBound> {
+        self.max
+    }
+    pub fn maxlen(&self) -> Option<u64> {
+        self.maxlen
+    }
+    /// Returns true if these checks can be used on a field of the given class
+    pub fn applies_to(&self, class: TagClass) -> bool {
+        let numeric = matches!(
+            class,
+            TagClass::UnsignedInt | TagClass::SignedInt | TagClass::Float
+        );
+        let sized = matches!(class, TagClass::Str | TagClass::Bin | TagClass::List);
+        let bounds_okay = match (self.min, self.max) {
+            (Some(min), Some(max)) => min.as_f64() <= max.as_f64(),
+            _ => true,
+        };
+        ((self.min.is_none() & self.max.is_none()) | numeric)
+            & (self.maxlen.is_none() | sized)
+            & (self.regex.is_none() | (class == TagClass::Str))
+            & bounds_okay
+    }
+    /// Validate a (type checked) value. On failure, the check that failed and its limit are added to the error's
+    /// detail
+    pub fn validate(&self, dc: &Datacell) -> QueryResult<()> {
+        if dc.is_null() {
+            return Ok(());
+        }
+        if let Some(min) = self.min {
+            if !matches!(min.cmp_dc(dc), Some(Ordering::Equal | Ordering::Greater)) {
+                return Err(QueryError::QExecDmlCheckMinViolated
+                    .with_detail("constraint", Self::KEY_MIN)
+                    .with_detail("limit", min));
+            }
+        }
+        if let Some(max) = self.max {
+            if !matches!(max.cmp_dc(dc), Some(Ordering::Equal | Ordering::Less)) {
+                return Err(QueryError::QExecDmlCheckMaxViolated
+                    .with_detail("constraint", Self::KEY_MAX)
+                    .with_detail("limit", max));
+            }
+        }
+        if let Some(maxlen) = self.maxlen {
+            let len = match dc.kind() {
+                TagClass::Str => dc.str().chars().count(),
+                TagClass::Bin => dc.bin().len(),
+                TagClass::List => dc.list().read().len(),
+                _ => 0,
+            };
+            if len as u64 > maxlen {
+                return Err(QueryError::QExecDmlCheckMaxLenViolated
+                    .with_detail("constraint", Self::KEY_MAXLEN)
+                    .with_detail("limit", maxlen));
+            }
+        }
+        if let Some(regex) = self.regex.as_ref() {
+            if !dc
+                .try_str()
+                .map(|s| regex.compiled.is_match(s))
+                .unwrap_or(false)
+            {
+                return Err(QueryError::QExecDmlCheckRegexViolated
+                    .with_detail("constraint", Self::KEY_REGEX)
+                    .with_detail("limit", &regex.pattern));
+            }
+        }
+        Ok(())
+    }
+    /// Returns the checks that are set as members of a JSON object
+    pub(super) fn describe_members(&self) -> Vec<String> {
+        let mut members = vec![];
+        if let Some(min) = self.min {
+            members.push(format!("\"{}\":{min}", Self::KEY_MIN));
+        }
+        if let Some(max) = self.max {
+            members.push(format!("\"{}\":{max}", Self::KEY_MAX));
+        }
+        if let Some(maxlen) = self.maxlen {
+            members.push(format!("\"{}\":{maxlen}", Self::KEY_MAXLEN));
+        }
+        if let Some(regex) = self.regex.as_ref() {
+            let mut member = format!("\"{}\":", Self::KEY_REGEX);
+            encode_str(&mut member, &regex.pattern);
+            members.push(member);
+        }
+        members
+    }
+    /// Add the checks to the given (raw) properties
+    pub(super) fn write_raw(&self, raw: &mut DictGeneric) {
+        if let Some(min) = self.min {
+            raw.insert(Self::KEY_MIN.into(), DictEntryGeneric::Data(min.to_dc()));
+        }
+        if let Some(max) = self.max {
+            raw.insert(Self::KEY_MAX.into(), DictEntryGeneric::Data(max.to_dc()));
+        }
+        if let Some(maxlen) = self.maxlen {
+            raw.insert(
+                Self::KEY_MAXLEN.into(),
+                DictEntryGeneric::Data(Datacell::new_uint_default(maxlen)),
+            );
+        }
+        if let Some(regex) = self.regex.as_ref() {
+            raw.insert(
+                Self::KEY_REGEX.into(),
+                DictEntryGeneric::Data(Datacell::new_str(regex.pattern.clone())),
+            );
+        }
+    }
+}
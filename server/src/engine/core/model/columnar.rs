@@ -0,0 +1,225 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    columnar layout
+    ---
+    models created with `analytic: true` also keep the values of their live rows column-major in memory: every row
+    has a slot, and every field (other than the primary key, which is kept as the key of the slot) has a vector of
+    values indexed by slot. the columns are built from the primary index when they're first read (and again after a
+    restart or a schema change). from then on, the DML layer records the keys of the rows that a query changes and
+    syncs their slots with the primary index once the query is done: a row that is no longer live frees its slot,
+    which is then reused by the next new row. aggregates without a where clause only read the columns that they use,
+    and `select all` without a where clause (on a hash indexed model) materializes the rows that it returns from the
+    columns. the primary index remains the source of truth, so the columns are never persisted
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{
+        core::{
+            index::{DcFieldIndex, PrimaryIndexKey},
+            lock,
+        },
+        data::cell::Datacell,
+        error::QueryError,
+        idx::{STIndex, STIndexSeq},
+        sync::atm::{cpin, Guard},
+    },
+    parking_lot::{Mutex, RwLock, RwLockWriteGuard},
+    std::{
+        collections::HashMap,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// The values of the live rows of a model, by field and slot
+#[derive(Debug, Default)]
+pub struct Columns {
+    /// the schema version that the columns were built at
+    schema: u64,
+    slots: HashMap<PrimaryIndexKey, usize>,
+    /// the key of the row in each slot ([`None`] if the slot is free)
+    keys: Vec<Option<PrimaryIndexKey>>,
+    free: Vec<usize>,
+    fields: HashMap<Box<str>, Vec<Datacell>>,
+}
+
+impl Columns {
+    fn build(mdl: &ModelData, g: &Guard) -> Self {
+        let mut columns = Self {
+            schema: mdl.delta_state().schema_current_version().value_u64(),
+            fields: mdl
+                .fields()
+                .stseq_ord_key()
+                .filter(|field| field.as_str() != mdl.p_key())
+                .map(|field| (field.as_str().into(), vec![]))
+                .collect(),
+            ..Default::default()
+        };
+        for row in mdl.primary_index().iter(g) {
+            // a row that can't be read is left out (see the model's overflow report)
+            let Ok(data) = lock::read(mdl, row) else {
+                continue;
+            };
+            if !data.is_tombstoned() {
+                columns.set(row.d_key(), Some(data.fields()));
+            }
+        }
+        columns
+    }
+    /// Returns the number of live rows
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+    /// Returns the occupied slots (in slot order) along with the keys of their rows
+    pub fn rows(&self) -> impl Iterator<Item = (usize, &PrimaryIndexKey)> {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, key)| Some((slot, key.as_ref()?)))
+    }
+    /// Returns the value of a field (other than the primary key) in the given slot
+    pub fn cell(&self, field: &str, slot: usize) -> Option<&Datacell> {
+        self.fields.get(field).map(|column| &column[slot])
+    }
+    /// Put the values of the row with the given key in its slot (allocating one if it doesn't have one), or free its
+    /// slot if the row is no longer live
+    fn set(&mut self, key: &PrimaryIndexKey, fields: Option<&DcFieldIndex>) {
+        let Some(fields) = fields else {
+            if let Some(slot) = self.slots.remove(key) {
+                self.keys[slot] = None;
+                self.fields
+                    .values_mut()
+                    .for_each(|column| column[slot] = Datacell::null());
+                self.free.push(slot);
+            }
+            return;
+        };
+        let slot = match self.slots.get(key) {
+            Some(&slot) => slot,
+            None => {
+                let slot = self.free.pop().unwrap_or_else(|| {
+                    self.keys.push(None);
+                    self.fields
+                        .values_mut()
+                        .for_each(|column| column.push(Datacell::null()));
+                    self.keys.len() - 1
+                });
+                self.keys[slot] = Some(key.clone());
+                self.slots.insert(key.clone(), slot);
+                slot
+            }
+        };
+        for (field, column) in self.fields.iter_mut() {
+            column[slot] = fields
+                .st_get(&**field)
+                .cloned()
+                .unwrap_or_else(Datacell::null);
+        }
+    }
+}
+
+/// The column-major copy of the rows of an analytic model (see the module docs)
+#[derive(Debug, Default)]
+pub struct ColumnStore {
+    /// set once the columns are built (and until a schema change). changes are only recorded while this is set
+    built: AtomicBool,
+    pending: Mutex<Vec<PrimaryIndexKey>>,
+    columns: RwLock<Columns>,
+}
+
+impl ColumnStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn is_current(&self, mdl: &ModelData, columns: &Columns) -> bool {
+        self.built.load(Ordering::Acquire)
+            & (columns.schema == mdl.delta_state().schema_current_version().value_u64())
+    }
+    /// Record a change to the row with the given key, so that its slot is synced once the query is done
+    pub(in crate::engine::core) fn touch(&self, key: &PrimaryIndexKey) {
+        if self.built.load(Ordering::Acquire) {
+            self.pending.lock().push(key.clone());
+        }
+    }
+    /// Sync the slots of the rows that have changed with the primary index. Must not be called with a row locked
+    pub(in crate::engine::core) fn sync(&self, mdl: &ModelData) {
+        let keys = core::mem::take(&mut *self.pending.lock());
+        if keys.is_empty() {
+            return;
+        }
+        // syncs are serialized (and the rows are read with the columns locked) so that a slot never goes back in time
+        let mut columns = self.columns.write();
+        if !self.is_current(mdl, &columns) {
+            // the columns are rebuilt when they're next read
+            self.built.store(false, Ordering::Release);
+            *columns = Columns::default();
+            return;
+        }
+        let g = cpin();
+        for key in keys {
+            match mdl.primary_index().select_key(&key, &g) {
+                Some(row) => match lock::read(mdl, row) {
+                    Ok(data) => columns.set(&key, (!data.is_tombstoned()).then(|| data.fields())),
+                    // the row is synced again next time
+                    Err(QueryError::SysLockWaitTimeout) => self.pending.lock().push(key),
+                    Err(_) => columns.set(&key, None),
+                },
+                None => columns.set(&key, None),
+            }
+        }
+    }
+    /// Run `f` with the columns, building them first if they haven't been built (or are out of date)
+    pub fn with_columns<T>(&self, mdl: &ModelData, f: impl FnOnce(&Columns) -> T) -> T {
+        {
+            let columns = self.columns.read();
+            if self.is_current(mdl, &columns) {
+                return f(&columns);
+            }
+        }
+        let g = cpin();
+        // no rows can change while the columns are built
+        let _idx_latch = mdl.primary_index().acquire_exclusive();
+        let mut columns = self.columns.write();
+        if !self.is_current(mdl, &columns) {
+            *columns = Columns::build(mdl, &g);
+            self.pending.lock().clear();
+            self.built.store(true, Ordering::Release);
+        }
+        f(&RwLockWriteGuard::downgrade(columns))
+    }
+    /// Returns whether the columns are built, the number of live rows and the number of slots as a JSON object
+    pub fn describe(&self) -> String {
+        let columns = self.columns.read();
+        format!(
+            "{{\"built\":{},\"rows\":{},\"slots\":{}}}",
+            self.built.load(Ordering::Acquire),
+            columns.len(),
+            columns.keys.len()
+        )
+    }
+}
@@ -0,0 +1,130 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::data::cell::Datacell;
+
+/// A function that can be used in a computed field's expression
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ComputedFn {
+    Lower,
+    Upper,
+    Trim,
+}
+
+impl ComputedFn {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "lower" => Some(Self::Lower),
+            "upper" => Some(Self::Upper),
+            "trim" => Some(Self::Trim),
+            _ => None,
+        }
+    }
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+            Self::Trim => "trim",
+        }
+    }
+    fn apply(&self, s: &str) -> String {
+        match self {
+            Self::Lower => s.to_lowercase(),
+            Self::Upper => s.to_uppercase(),
+            Self::Trim => s.trim().to_owned(),
+        }
+    }
+}
+
+/// The expression of a computed field, for example `lower(trim(username))`. The value is computed from the source field
+/// whenever a row is inserted or the source field is updated, and is then stored like any other value (which means that
+/// it can be used in a `where` clause or be indexed)
+#[derive(Debug, PartialEq, Clone)]
+pub struct ComputedExpr {
+    source: Box<str>,
+    /// functions, applied innermost first
+    fns: Vec<ComputedFn>,
+}
+
+impl ComputedExpr {
+    /// the maximum number of nested function calls
+    pub const MAX_DEPTH: usize = 8;
+    pub fn parse(expr: &str) -> Option<Self> {
+        let mut fns = Vec::new();
+        let mut expr = expr.trim();
+        while let Some(body) = expr.strip_suffix(')') {
+            let (name, inner) = body.split_once('(')?;
+            fns.push(ComputedFn::from_name(name.trim())?);
+            if fns.len() > Self::MAX_DEPTH {
+                return None;
+            }
+            expr = inner.trim();
+        }
+        let mut chars = expr.chars();
+        let is_ident = chars
+            .next()
+            .map(|c| c.is_ascii_alphabetic() | (c == '_'))
+            .unwrap_or(false)
+            & chars.all(|c| c.is_ascii_alphanumeric() | (c == '_'));
+        if !is_ident {
+            return None;
+        }
+        fns.reverse();
+        Some(Self {
+            source: expr.into(),
+            fns,
+        })
+    }
+    /// Returns the field that this expression is computed from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    /// Evaluate the expression using the value of the source field. A null source (or one of an unexpected type)
+    /// produces a null
+    pub fn eval(&self, source: Option<&str>) -> Datacell {
+        match source {
+            Some(s) => {
+                let mut value = s.to_owned();
+                for f in self.fns.iter() {
+                    value = f.apply(&value);
+                }
+                Datacell::new_str(value.into_boxed_str())
+            }
+            None => Datacell::null(),
+        }
+    }
+    /// Returns the canonical form of this expression
+    pub fn describe(&self) -> String {
+        let mut ret = String::new();
+        for f in self.fns.iter().rev() {
+            ret.push_str(f.name());
+            ret.push('(');
+        }
+        ret.push_str(&self.source);
+        ret.push_str(&")".repeat(self.fns.len()));
+        ret
+    }
+}
@@ -0,0 +1,322 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    model copies
+    ---
+    `sysctl copy model` pulls a model from another running instance over the native protocol (with the connections of
+    `fractal::upstream`). the schema is read with `describe model`, and the model is created here with the same fields:
+    their types, which of them can be null and the primary key, but not their properties (nor the model's). the rows
+    are then paged through with `select all ... limit ... offset ...`, which returns them in a stable order, and
+    inserted in bulk. the source shouldn't change while it's copied: rows that are inserted or deleted in the meantime
+    can be skipped or repeated, and a repeated row fails the copy. a copy that fails (or is cancelled) leaves the model
+    with the rows copied so far
+*/
+
+use {
+    super::{ModelData, LUT},
+    crate::engine::{
+        core::{dml, EntityIDRef},
+        data::{tag::TagClass, DictEntryGeneric, DictGeneric},
+        error::{QueryError, QueryResult},
+        fractal::{
+            self,
+            jobs::{self, Job, JobKind, JobStatus},
+            upstream::Connection,
+            GlobalInstanceLike,
+        },
+        net::protocol::{Response, ResponseType},
+        ql::{
+            ast::{traits::ASTNode, State},
+            ddl::crt::CreateModel,
+            lex::{quote_ident, SecureLexer},
+        },
+    },
+    serde::Deserialize,
+};
+
+/// The number of rows fetched from the source at a time
+const COPY_PAGE: u64 = 1024;
+
+/// The schema of a model, as described by `describe model` (see [`ModelData::describe_schema`])
+#[derive(Debug, Deserialize)]
+struct SourceSchema {
+    fields: Vec<SourceField>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceField {
+    name: String,
+    primary: bool,
+    nullable: bool,
+    layers: Vec<SourceLayer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceLayer {
+    #[serde(rename = "type")]
+    ty: String,
+}
+
+/// The instance that a model is copied from
+struct CopySource {
+    host: Box<str>,
+    port: u16,
+    username: String,
+    password: String,
+}
+
+impl CopySource {
+    /// Returns the source at `from` (a `host:port`). Without credentials, those of the upstream (if any) are used
+    fn new(from: &str, credentials: Option<DictGeneric>) -> QueryResult<Self> {
+        let Some((host, Ok(port))) = from
+            .rsplit_once(':')
+            .map(|(host, port)| (host, port.parse::<u16>()))
+        else {
+            return Err(QueryError::QLInvalidSyntax);
+        };
+        let (username, password) = match credentials {
+            Some(mut credentials) => {
+                let username = take_str(&mut credentials, "username")?;
+                let password = take_str(&mut credentials, "password")?;
+                if !credentials.is_empty() {
+                    return Err(QueryError::QExecDdlInvalidProperties);
+                }
+                (username, password)
+            }
+            None => match fractal::upstream::get() {
+                Some(upstream) => {
+                    let (username, password) = upstream.credentials();
+                    (username.to_owned(), password.to_owned())
+                }
+                None => return Err(QueryError::QExecDdlInvalidProperties),
+            },
+        };
+        if host.is_empty() {
+            return Err(QueryError::QLInvalidSyntax);
+        }
+        Ok(Self {
+            host: host.into(),
+            port,
+            username,
+            password,
+        })
+    }
+}
+
+fn take_str(dict: &mut DictGeneric, key: &str) -> QueryResult<String> {
+    match dict.remove(key) {
+        Some(DictEntryGeneric::Data(d)) if d.kind() == TagClass::Str => {
+            Ok(unsafe { d.into_str().unwrap_unchecked() })
+        }
+        _ => Err(QueryError::QExecDdlInvalidProperties),
+    }
+}
+
+/// Copy a model (its schema and rows) from another instance, returning the number of rows copied. The model must not
+/// exist here
+pub fn copy_model(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    from: &str,
+    credentials: Option<DictGeneric>,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    let source = CopySource::new(from, credentials)?;
+    // only a job can be cancelled
+    copy(global, entity, &source, None).map(Option::unwrap_or_default)
+}
+
+/// Copy a model from another instance in the background, returning the id of the job
+pub fn copy_model_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    entity: EntityIDRef,
+    from: &str,
+    credentials: Option<DictGeneric>,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    let source = CopySource::new(from, credentials)?;
+    let (space, model): (Box<str>, Box<str>) = (entity.space().into(), entity.entity().into());
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::CopyModel,
+        format!("{space}.{model}").into_boxed_str(),
+        move |global, job| {
+            let entity = EntityIDRef::new(&space, &model);
+            match copy(global, entity, &source, Some(job)) {
+                Ok(Some(rows)) => JobStatus::Completed(Some(format!("{{\"rows\":{rows}}}"))),
+                Ok(None) => JobStatus::Cancelled,
+                Err(e) => JobStatus::failed(e),
+            }
+        },
+    ))
+}
+
+/// Returns [`None`] if the job (if any) was cancelled
+fn copy(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    source: &CopySource,
+    job: Option<&Job>,
+) -> QueryResult<Option<u64>> {
+    let mut con = Connection::connect(
+        &source.host,
+        source.port,
+        &source.username,
+        &source.password,
+    )
+    .map_err(|_| QueryError::SysUpstreamError)?;
+    let (space, model) = (quote_ident(entity.space()), quote_ident(entity.entity()));
+    let schema = match self::run(&mut con, &format!("describe model {space}.{model}"))? {
+        Response::Serialized {
+            ty: ResponseType::String,
+            data,
+            ..
+        } => String::from_utf8(data).map_err(|_| QueryError::SysUpstreamError)?,
+        _ => return Err(QueryError::SysUpstreamError),
+    };
+    let ddl = create_model_ddl(entity, &schema)?;
+    let tokens = SecureLexer::new_with_segments(ddl.as_bytes(), &[]).lex()?;
+    // skip over `create model`
+    let stmt = CreateModel::parse_from_state_hardened(&mut State::new_inplace(&tokens[2..]))?;
+    ModelData::transactional_exec_create(global, stmt)?;
+    let mut copied = 0;
+    loop {
+        if job.map_or(false, |job| job.token().is_cancelled()) {
+            return Ok(None);
+        }
+        let page = self::run(
+            &mut con,
+            &format!("select all * from {space}.{model} limit {COPY_PAGE} offset {copied}"),
+        )?;
+        let Some(rows) = fractal::upstream::decode_rows(&page) else {
+            return Err(QueryError::SysUpstreamError);
+        };
+        let count = rows.len() as u64;
+        if count != 0 {
+            dml::bulk_insert(global, entity, rows)?;
+        }
+        copied += count;
+        if let Some(job) = job {
+            job.set_done(copied);
+        }
+        if count < COPY_PAGE {
+            return Ok(Some(copied));
+        }
+    }
+}
+
+/// Run a query on the source
+fn run(con: &mut Connection, query: &str) -> QueryResult<Response> {
+    match con.query(query.as_bytes(), &[]) {
+        Ok(Ok(r)) => Ok(r),
+        Ok(Err(code)) => Err(QueryError::SysUpstreamError.with_detail("code", code)),
+        Err(_) => Err(QueryError::SysUpstreamError),
+    }
+}
+
+/// Returns the `create model` statement for a model with the schema that `describe model` returned for it on the
+/// source. Only the fields' names, types (which must be ones that we know) and nullability are used
+fn create_model_ddl(entity: EntityIDRef, schema: &str) -> QueryResult<String> {
+    // the description is JSON, which is also YAML
+    let schema: SourceSchema =
+        serde_yaml::from_str(schema).map_err(|_| QueryError::SysUpstreamError)?;
+    let mut fields = vec![];
+    for field in schema.fields {
+        let valid_name = !field.name.is_empty()
+            && field
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() | (c == '_'));
+        let valid_layers = !field.layers.is_empty()
+            && field
+                .layers
+                .iter()
+                .all(|layer| LUT.iter().any(|(ty, _)| *ty == layer.ty));
+        if !(valid_name & valid_layers) {
+            return Err(QueryError::QExecDdlModelBadDefinition.with_detail("field", field.name));
+        }
+        let mut decl = String::new();
+        if field.primary {
+            decl.push_str("primary ");
+        } else if field.nullable {
+            decl.push_str("null ");
+        }
+        decl.push_str(&quote_ident(&field.name));
+        decl.push_str(": ");
+        // every layer but the last is a list of the next
+        let (last, lists) = field.layers.split_last().unwrap();
+        for list in lists {
+            decl.push_str(&list.ty);
+            decl.push_str(" { type: ");
+        }
+        decl.push_str(&last.ty);
+        decl.push_str(&" }".repeat(lists.len()));
+        fields.push(decl);
+    }
+    Ok(format!(
+        "create model {}.{}({})",
+        quote_ident(entity.space()),
+        quote_ident(entity.entity()),
+        fields.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::create_model_ddl, crate::engine::core::EntityIDRef};
+
+    #[test]
+    fn ddl_from_schema() {
+        let schema = "{\"primary_key\":\"username\",\"fields\":[\
+            {\"name\":\"username\",\"primary\":true,\"nullable\":false,\"layers\":[{\"type\":\"string\",\"selector\":12,\"class\":5,\"unique\":4}],\"properties\":{}},\
+            {\"name\":\"email\",\"primary\":false,\"nullable\":true,\"layers\":[{\"type\":\"string\",\"selector\":12,\"class\":5,\"unique\":4}],\"properties\":{\"maxlen\":64}},\
+            {\"name\":\"scores\",\"primary\":false,\"nullable\":false,\"layers\":[{\"type\":\"list\",\"selector\":13,\"class\":6,\"unique\":null},{\"type\":\"list\",\"selector\":13,\"class\":6,\"unique\":null},{\"type\":\"uint8\",\"selector\":1,\"class\":1,\"unique\":1}],\"properties\":{}}]}";
+        assert_eq!(
+            create_model_ddl(EntityIDRef::new("myspace", "mymodel"), schema).unwrap(),
+            "create model myspace.mymodel(primary username: string, null email: string, \
+            scores: list { type: list { type: uint8 } })"
+        );
+        // names that are keywords are quoted
+        let schema = "{\"fields\":[{\"name\":\"type\",\"primary\":true,\"nullable\":false,\"layers\":[{\"type\":\"string\"}]}]}";
+        assert_eq!(
+            create_model_ddl(EntityIDRef::new("myspace", "select"), schema).unwrap(),
+            "create model myspace.`select`(primary `type`: string)"
+        );
+        // types that we don't know (or anything else in their place) are rejected
+        for bad in [
+            "{\"fields\":[{\"name\":\"a\",\"primary\":true,\"nullable\":false,\"layers\":[{\"type\":\"uuid\"}]}]}",
+            "{\"fields\":[{\"name\":\"a\",\"primary\":true,\"nullable\":false,\"layers\":[{\"type\":\"string { computed: 1 }\"}]}]}",
+            "{\"fields\":[{\"name\":\"a b\",\"primary\":true,\"nullable\":false,\"layers\":[{\"type\":\"string\"}]}]}",
+            "{\"fields\":[{\"name\":\"a\",\"primary\":true,\"nullable\":false,\"layers\":[]}]}",
+            "not a schema",
+        ] {
+            assert!(create_model_ddl(EntityIDRef::new("myspace", "mymodel"), bad).is_err());
+        }
+    }
+}
@@ -0,0 +1,262 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    decimals
+    ---
+    a field declared as `decimal { precision: <digits>, scale: <digits> }` holds exact fixed-point numbers (such as
+    amounts of money) with at most `precision` digits, `scale` of them after the point (the precision is 18 and the
+    scale is 2, unless set otherwise). there's no separate type: a decimal is a signed integer holding the value times
+    10^scale (with a scale of 2, 12.34 is held as 1234), and the field is a `sint64` with the `precision` and `scale`
+    properties. so decimals are stored, compared, sorted and indexed exactly, just like any other integer.
+
+    - `insert` and `update ... set f = v` take a string (`"12.34"`), an integer or a float. a value with more digits
+    after the point than the scale, or more digits than the precision, is rejected (never rounded). a float is read as
+    the shortest decimal that converts back into it, so `0.1` is 0.1
+    - `update ... set f += v` and `set f -= v` add and subtract exactly. the other operators are rejected
+    - a `where` comparison on the field takes the same values (`where price >= "9.99"`)
+    - the field is returned (and read by functions) as the integer that it holds. `fixed(f, scale)` formats an integer
+    with `scale` digits after the point, so `fixed(price, 2)` returns `"12.34"`. the result of a function assigned to
+    the field is also taken as that integer
+*/
+
+use crate::engine::{
+    core::model::Numeric,
+    data::{
+        cell::Datacell,
+        lit::Lit,
+        tag::{DataTag, TagClass},
+    },
+};
+
+/// The definition of a decimal field (see the module docs)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Decimal {
+    precision: u8,
+    scale: u8,
+}
+
+impl Decimal {
+    /// the name used in place of a type to declare a decimal
+    pub const TYPE: &'static str = "decimal";
+    /// the most digits that a sint64 always has room for
+    pub const MAX_PRECISION: u64 = 18;
+    /// the precision, unless set otherwise
+    pub const DEFAULT_PRECISION: u64 = 18;
+    /// the scale, unless set otherwise
+    pub const DEFAULT_SCALE: u64 = 2;
+    /// Returns [`None`] if the precision is zero or above [`Self::MAX_PRECISION`], or if the scale is above the
+    /// precision
+    pub fn new(precision: u64, scale: u64) -> Option<Self> {
+        ((precision != 0) & (precision <= Self::MAX_PRECISION) & (scale <= precision)).then_some(
+            Self {
+                precision: precision as u8,
+                scale: scale as u8,
+            },
+        )
+    }
+    /// Returns the number of digits
+    pub fn precision(&self) -> u64 {
+        self.precision as u64
+    }
+    /// Returns the number of digits after the point
+    pub fn scale(&self) -> u64 {
+        self.scale as u64
+    }
+    /// Returns true if the held integer has at most `precision` digits
+    pub fn fits(&self, held: i64) -> bool {
+        held.unsigned_abs() < 10u64.pow(self.precision as u32)
+    }
+    /// Returns the integer held for a decimal string (an optional sign, then digits with at most one point), or
+    /// [`None`] if it isn't one or it doesn't fit
+    pub fn parse(&self, s: &str) -> Option<i64> {
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (int.is_empty() & frac.is_empty()) | !is_digits(int) | !is_digits(frac) {
+            return None;
+        }
+        // trailing zeros don't need room
+        let frac = frac.trim_end_matches('0');
+        if frac.len() > self.scale as usize {
+            return None;
+        }
+        let padding = self.scale as usize - frac.len();
+        let mut held: i64 = 0;
+        for b in int
+            .bytes()
+            .chain(frac.bytes())
+            .chain(core::iter::repeat(b'0').take(padding))
+        {
+            held = held.checked_mul(10)?.checked_add((b - b'0') as i64)?;
+        }
+        let held = if negative { -held } else { held };
+        self.fits(held).then_some(held)
+    }
+    /// Returns the integer held for a number, or [`None`] if it doesn't fit
+    fn from_num(&self, n: Numeric) -> Option<i64> {
+        let int = |int: i128| {
+            let held = int.checked_mul(10i128.pow(self.scale as u32))?;
+            i64::try_from(held).ok().filter(|held| self.fits(*held))
+        };
+        match n {
+            Numeric::UInt(u) => int(u as i128),
+            Numeric::SInt(s) => int(s as i128),
+            // a float is displayed as the shortest decimal that converts back into it (and never with an exponent)
+            Numeric::Float(f) if f.is_finite() => self.parse(&f.to_string()),
+            Numeric::Float(_) => None,
+        }
+    }
+    /// Replace a value given to an insert with the integer held for it. Returns false if the value isn't a decimal
+    /// that fits (a null is left as it is)
+    pub fn prepare_insert(&self, data: &mut Datacell) -> bool {
+        if data.is_null() {
+            return true;
+        }
+        let held = match data.kind() {
+            TagClass::Str => data.try_str().and_then(|s| self.parse(s)),
+            TagClass::UnsignedInt => data
+                .try_uint()
+                .and_then(|u| self.from_num(Numeric::UInt(u))),
+            TagClass::SignedInt => data
+                .try_sint()
+                .and_then(|s| self.from_num(Numeric::SInt(s))),
+            TagClass::Float => data
+                .try_float()
+                .and_then(|f| self.from_num(Numeric::Float(f))),
+            _ => None,
+        };
+        match held {
+            Some(held) => {
+                *data = Datacell::new_sint_default(held);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Returns the integer held for a literal (the value of an assignment or a comparison), or [`None`] if the literal
+    /// isn't a decimal that fits
+    pub fn held_lit(&self, lit: &Lit) -> Option<Lit<'static>> {
+        let held = match lit.kind().tag_class() {
+            TagClass::Str => self.parse(lit.try_str()?),
+            TagClass::UnsignedInt => self.from_num(Numeric::UInt(lit.try_uint()?)),
+            TagClass::SignedInt => self.from_num(Numeric::SInt(lit.try_sint()?)),
+            TagClass::Float => self.from_num(Numeric::Float(lit.try_float()?)),
+            _ => None,
+        };
+        held.map(Lit::new_sint)
+    }
+}
+
+/// Format an integer as a decimal with `scale` digits after the point (`fixed(1234, 2)` is `"12.34"`). Returns
+/// [`None`] if the scale is above [`Decimal::MAX_PRECISION`]
+pub fn fixed(int: i128, scale: u64) -> Option<String> {
+    if scale > Decimal::MAX_PRECISION {
+        return None;
+    }
+    let scale = scale as usize;
+    let digits = format!("{:0>width$}", int.unsigned_abs(), width = scale + 1);
+    let (whole, frac) = digits.split_at(digits.len() - scale);
+    let sign = if int < 0 { "-" } else { "" };
+    Some(match scale {
+        0 => format!("{sign}{whole}"),
+        _ => format!("{sign}{whole}.{frac}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{fixed, Decimal},
+        crate::engine::data::{cell::Datacell, lit::Lit},
+    };
+
+    #[test]
+    fn parse() {
+        let money = Decimal::new(6, 2).unwrap();
+        assert_eq!(money.parse("12.34"), Some(1234));
+        assert_eq!(money.parse("-0.5"), Some(-50));
+        assert_eq!(money.parse("+7"), Some(700));
+        assert_eq!(money.parse("7."), Some(700));
+        assert_eq!(money.parse(".25"), Some(25));
+        assert_eq!(money.parse("1.500"), Some(150));
+        assert_eq!(money.parse("9999.99"), Some(999_999));
+        // too many digits after the point, too many digits or not a decimal at all
+        for bad in ["1.005", "10000", "", ".", "-", "1.2.3", "1e3", "12a", " 1"] {
+            assert_eq!(money.parse(bad), None, "{bad}");
+        }
+        // the largest precision can't overflow
+        let wide = Decimal::new(Decimal::MAX_PRECISION, 0).unwrap();
+        assert_eq!(
+            wide.parse("999999999999999999"),
+            Some(999_999_999_999_999_999)
+        );
+        assert_eq!(wide.parse("9999999999999999999"), None);
+        assert!(Decimal::new(0, 0).is_none());
+        assert!(Decimal::new(19, 2).is_none());
+        assert!(Decimal::new(2, 3).is_none());
+    }
+
+    #[test]
+    fn prepare_insert() {
+        let money = Decimal::new(18, 2).unwrap();
+        for (mut value, held) in [
+            (Datacell::new_str("19.99".into()), 1999),
+            (Datacell::new_uint_default(5), 500),
+            (Datacell::new_sint_default(-5), -500),
+            (Datacell::new_float_default(0.1), 10),
+            (Datacell::new_float_default(1.25), 125),
+        ] {
+            assert!(money.prepare_insert(&mut value));
+            assert_eq!(value, Datacell::new_sint_default(held));
+        }
+        // a float that needs more digits than the scale is rejected, not rounded
+        assert!(!money.prepare_insert(&mut Datacell::new_float_default(0.125)));
+        assert!(!money.prepare_insert(&mut Datacell::new_float_default(f64::NAN)));
+        assert!(!money.prepare_insert(&mut Datacell::new_bool(true)));
+        let mut null = Datacell::null();
+        assert!(money.prepare_insert(&mut null));
+        assert!(null.is_null());
+        assert_eq!(
+            money.held_lit(&Lit::new_str("1.5")),
+            Some(Lit::new_sint(150))
+        );
+        assert_eq!(money.held_lit(&Lit::new_str("1.555")), None);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(fixed(1234, 2).unwrap(), "12.34");
+        assert_eq!(fixed(-5, 2).unwrap(), "-0.05");
+        assert_eq!(fixed(0, 3).unwrap(), "0.000");
+        assert_eq!(fixed(42, 0).unwrap(), "42");
+        assert!(fixed(1, 19).is_none());
+    }
+}
@@ -33,6 +33,7 @@ use {
         sync::atm::Guard,
         sync::queue::Queue,
     },
+    crate::util::os,
     std::{
         collections::btree_map::{BTreeMap, Range},
         sync::atomic::{AtomicU64, AtomicUsize, Ordering},
@@ -47,24 +48,59 @@ pub struct DeltaState {
     schema_deltas: BTreeMap<DeltaVersion, SchemaDeltaPart>,
     // data
     data_current_version: AtomicU64,
-    data_deltas: Queue<DataDelta>,
+    /// the number of data deltas appended so far. unlike the version (which is taken before a change is made), this
+    /// is only bumped once the change is visible
+    data_generation: AtomicU64,
+    data_deltas: Box<[PartitionDeltas]>,
     data_deltas_size: AtomicUsize,
+    /// the number of times the deltas hit the cache capacity, forcing a write
+    data_stalls: AtomicU64,
+    /// the number of deltas after which they're written out as a batch (picked by the flusher)
+    data_batch_size: AtomicUsize,
+    /// when (epoch milliseconds) the data deltas were last handed over to the flusher
+    data_taken_at: AtomicU64,
+}
+
+/// The data deltas of a single partition of the primary index
+#[derive(Debug)]
+struct PartitionDeltas {
+    queue: Queue<DataDelta>,
+    /// the number of deltas that haven't been handed over to the flusher yet
+    pending: AtomicUsize,
+    /// the number of deltas that have been handed over to the flusher but haven't been written yet
+    taken: AtomicUsize,
+}
+
+impl PartitionDeltas {
+    fn new() -> Self {
+        Self {
+            queue: Queue::new(),
+            pending: AtomicUsize::new(0),
+            taken: AtomicUsize::new(0),
+        }
+    }
 }
 
 impl DeltaState {
-    /// A new, fully resolved delta state with version counters set to 0
-    pub fn new_resolved() -> Self {
+    /// A new, fully resolved delta state with version counters set to 0 and a delta queue for each partition
+    pub fn new_resolved(partitions: usize) -> Self {
         Self {
             schema_current_version: 0,
             schema_deltas: BTreeMap::new(),
             data_current_version: AtomicU64::new(0),
-            data_deltas: Queue::new(),
+            data_generation: AtomicU64::new(0),
+            data_deltas: (0..partitions).map(|_| PartitionDeltas::new()).collect(),
             data_deltas_size: AtomicUsize::new(0),
+            data_stalls: AtomicU64::new(0),
+            data_batch_size: AtomicUsize::new(usize::MAX),
+            data_taken_at: AtomicU64::new(os::get_epoch_time_ms()),
         }
     }
+    /// Bump the data version to at least the given version. Each partition is restored separately, so the highest
+    /// version wins
     pub fn __set_delta_version(&self, version: DeltaVersion) {
         self.data_current_version
-            .store(version.value_u64(), Ordering::Relaxed)
+            .fetch_max(version.value_u64(), Ordering::Relaxed);
     }
 }
 
@@ -93,30 +129,76 @@ impl DeltaState {
         self.append_new_data_delta(DataDelta::new(data_version, row, kind), g)
     }
     pub fn append_new_data_delta(&self, delta: DataDelta, g: &Guard) -> usize {
-        self.data_deltas.blocking_enqueue(delta, g);
+        let partition = self.partition(&delta);
+        partition.queue.blocking_enqueue(delta, g);
+        partition.pending.fetch_add(1, Ordering::Release);
+        self.data_generation.fetch_add(1, Ordering::Release);
         self.data_deltas_size.fetch_add(1, Ordering::Release) + 1
     }
+    /// Put back a delta that was handed over to the flusher, but couldn't be written. It isn't counted again
+    pub fn requeue_data_delta(&self, delta: DataDelta, g: &Guard) {
+        self.partition(&delta).queue.blocking_enqueue(delta, g);
+    }
     pub fn create_new_data_delta_version(&self) -> DeltaVersion {
         DeltaVersion(self.__data_delta_step())
     }
+    /// Returns the version that the next data delta will get
+    pub fn data_current_version(&self) -> DeltaVersion {
+        DeltaVersion(self.data_current_version.load(Ordering::Acquire))
+    }
+    /// Returns the number of data deltas appended so far. This changes with every change to the rows (once it's
+    /// visible)
+    pub fn data_generation(&self) -> u64 {
+        self.data_generation.load(Ordering::Acquire)
+    }
+    /// Returns the number of data deltas that are yet to be written (including the ones handed over to the flusher)
+    pub fn pending_data_deltas(&self) -> usize {
+        self.data_deltas
+            .iter()
+            .map(|partition| {
+                partition.pending.load(Ordering::Acquire) + partition.taken.load(Ordering::Acquire)
+            })
+            .sum()
+    }
+    /// Returns the number of times the data deltas hit the cache capacity (which forces a write right away)
+    pub fn data_stalls(&self) -> u64 {
+        self.data_stalls.load(Ordering::Acquire)
+    }
+    /// Returns the number of data deltas after which they should be written out as a batch. Until the first batch
+    /// is written, only the cache capacity applies
+    pub fn data_batch_size(&self) -> usize {
+        self.data_batch_size.load(Ordering::Relaxed)
+    }
+    /// Returns the number of milliseconds since the data deltas were last handed over to the flusher
+    pub fn ms_since_taken(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.data_taken_at.load(Ordering::Acquire))
+    }
 }
 
 impl DeltaState {
     fn __data_delta_step(&self) -> u64 {
         self.data_current_version.fetch_add(1, Ordering::AcqRel)
     }
-    pub fn __data_delta_dequeue(&self, g: &Guard) -> Option<DataDelta> {
-        self.data_deltas.blocking_try_dequeue(g)
+    fn partition(&self, delta: &DataDelta) -> &PartitionDeltas {
+        match self.data_deltas.len() {
+            1 => &self.data_deltas[0],
+            n => &self.data_deltas[delta.row().d_key().partition(n)],
+        }
+    }
+    pub fn __data_delta_dequeue(&self, partition: usize, g: &Guard) -> Option<DataDelta> {
+        self.data_deltas[partition].queue.blocking_try_dequeue(g)
     }
 }
 
 // schema
 impl DeltaState {
+    /// Returns the deltas that a row revised up to `current_version` is missing. A row is revised up to the version
+    /// that the next delta gets, so that delta is included
     pub fn resolve_iter_since(
         &self,
         current_version: DeltaVersion,
     ) -> Range<DeltaVersion, SchemaDeltaPart> {
-        self.schema_deltas.range(current_version.step()..)
+        self.schema_deltas.range(current_version..)
     }
     pub fn schema_current_version(&self) -> DeltaVersion {
         DeltaVersion(self.schema_current_version)
@@ -144,8 +226,54 @@ impl DeltaState {
 
 // fractal
 impl DeltaState {
+    /// Hand over all pending deltas (of every partition) to the flusher, returning the number of deltas handed over
     pub fn __fractal_take_full_from_data_delta(&self, _token: FractalToken) -> usize {
-        self.data_deltas_size.swap(0, Ordering::AcqRel)
+        self.data_deltas_size.swap(0, Ordering::AcqRel);
+        self.data_taken_at
+            .store(os::get_epoch_time_ms(), Ordering::Release);
+        self.data_deltas
+            .iter()
+            .map(|partition| {
+                let pending = partition.pending.swap(0, Ordering::AcqRel);
+                partition.taken.fetch_add(pending, Ordering::AcqRel);
+                pending
+            })
+            .sum()
+    }
+    /// Record that the data deltas hit the cache capacity
+    pub fn __fractal_record_stall(&self, _token: FractalToken) {
+        self.data_stalls.fetch_add(1, Ordering::AcqRel);
+    }
+    /// Set the number of data deltas after which they should be written out as a batch
+    pub fn __fractal_set_batch_size(&self, size: usize, _token: FractalToken) {
+        self.data_batch_size.store(size, Ordering::Relaxed);
+    }
+    /// Returns the number of partitions (each of which is written to its own batch file)
+    pub fn __fractal_partitions(&self) -> usize {
+        self.data_deltas.len()
+    }
+    /// Claim the deltas of the partition that were handed over to the flusher (and are yet to be written)
+    pub fn __fractal_take_partition(&self, partition: usize, _token: FractalToken) -> usize {
+        self.data_deltas[partition].taken.swap(0, Ordering::AcqRel)
+    }
+    /// Return the deltas of the partition that couldn't be written, so that they're picked up by the next flush
+    pub fn __fractal_untake_partition(&self, partition: usize, count: usize, _token: FractalToken) {
+        self.data_deltas[partition]
+            .taken
+            .fetch_add(count, Ordering::AcqRel);
+    }
+    /// Hand the deltas of the partition that were handed over to the flusher back as pending, so that they're counted
+    /// (and picked up) by the next flush. Returns the number of deltas handed back
+    pub fn __fractal_hand_back_partition(&self, partition: usize, _token: FractalToken) -> usize {
+        let partition_ = &self.data_deltas[partition];
+        let taken = partition_.taken.swap(0, Ordering::AcqRel);
+        partition_.pending.fetch_add(taken, Ordering::AcqRel);
+        self.data_deltas_size.fetch_add(taken, Ordering::AcqRel);
+        taken
+    }
+    #[cfg(test)]
+    pub fn __fractal_data_delta_size(&self) -> usize {
+        self.data_deltas_size.load(Ordering::Acquire)
     }
 }
 
@@ -158,7 +286,7 @@ impl DeltaVersion {
     pub const fn __new(v: u64) -> Self {
         Self(v)
     }
-    fn step(&self) -> Self {
+    pub fn step(&self) -> Self {
         Self(self.0 + 1)
     }
     pub const fn value_u64(&self) -> u64 {
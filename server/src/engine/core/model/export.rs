@@ -0,0 +1,174 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    consistent exports
+    ---
+    `sysctl export models <model>, ... into <dir>` writes the rows of every model to `<dir>/<space>.<model>.jsonl` (in
+    the format of `sky-inspect rows`), all as of the same cut, so that the files can be loaded together downstream.
+    the cut is taken by holding the models for writing (like a freeze waits for the writes in flight), which holds
+    back new writes only while the pending changes of the models are written out and their journals are copied. the
+    copies are then decoded with writes let through again.
+
+    every journal has lsns of its own, so the cut is reported as the last lsn of every model's journals (one for each
+    partition): the export of a model has exactly the events up to it, and no write is in one export but missing from
+    another. soft deleted rows are exported with their tombstone, and views can't be exported since they aren't
+    persisted
+*/
+
+use {
+    crate::engine::{
+        core::EntityIDRef,
+        error::{QueryError, QueryResult},
+        fractal::{
+            error::Error,
+            jobs::{self, JobKind, JobStatus},
+            GlobalInstanceLike, ModelUniqueID,
+        },
+        storage::{
+            encode_str,
+            safe_interfaces::{paths_v1, FileSystem},
+            JournalExport, PersistedRows,
+        },
+    },
+    parking_lot::RwLockWriteGuard,
+    std::fmt::Write,
+};
+
+/// Export the rows of the models into `dir` as of a single cut, returning the cut and the files that were written as
+/// JSON lines (one for each model)
+pub fn export_models(
+    global: &impl GlobalInstanceLike,
+    entities: &[EntityIDRef],
+    dir: &str,
+) -> QueryResult<String> {
+    global.admit_expensive_query()?;
+    export(global, entities, dir)
+}
+
+/// Export models in the background, returning the id of the job. The job's result is the report
+pub fn export_models_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    entities: &[EntityIDRef],
+    dir: &str,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    let entities: Vec<(Box<str>, Box<str>)> = entities
+        .iter()
+        .map(|entity| (entity.space().into(), entity.entity().into()))
+        .collect();
+    let name: Vec<String> = entities
+        .iter()
+        .map(|(space, model)| format!("{space}.{model}"))
+        .collect();
+    let dir: Box<str> = dir.into();
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::ExportModels,
+        name.join(",").into_boxed_str(),
+        move |global, _| {
+            let entities: Vec<EntityIDRef> = entities
+                .iter()
+                .map(|(space, model)| EntityIDRef::new(space, model))
+                .collect();
+            match export(global, &entities, &dir) {
+                Ok(report) => JobStatus::Completed(Some(report)),
+                Err(e) => JobStatus::failed(e),
+            }
+        },
+    ))
+}
+
+fn export(
+    global: &impl GlobalInstanceLike,
+    entities: &[EntityIDRef],
+    dir: &str,
+) -> QueryResult<String> {
+    let spaces = global.state().namespace().idx().read();
+    // take the cut: holding the models for writing waits for the writes in flight and holds back new ones
+    let models = global.state().namespace().idx_models().write();
+    let mut cut = Vec::with_capacity(entities.len());
+    for entity in entities {
+        let (Some(space), Some(model)) = (spaces.get(entity.space()), models.get(entity)) else {
+            return Err(QueryError::QExecObjectNotFound.with_detail("entity", entity));
+        };
+        if model.data().props().view().is_some() {
+            return Err(QueryError::QExecDdlInvalidProperties.with_detail("entity", entity));
+        }
+        model.driver().flush(
+            global,
+            ModelUniqueID::new(entity.space(), entity.entity(), model.data().get_uuid()),
+            model.data(),
+        )?;
+        let journals = (0..model.data().primary_index().partitions())
+            .map(|partition| {
+                let journal_path = paths_v1::model_partition_path(
+                    entity.space(),
+                    space.get_uuid(),
+                    entity.entity(),
+                    model.data().get_uuid(),
+                    partition,
+                );
+                JournalExport::snapshot(model, partition, &journal_path)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        cut.push((entity, journals));
+    }
+    // let writes through again, but keep the models from being dropped while their copies are decoded
+    let models = RwLockWriteGuard::downgrade(models);
+    FileSystem::create_dir_all(dir).map_err(Error::from)?;
+    let mut report = String::new();
+    for (entity, journals) in cut {
+        let model = models.get(entity).unwrap();
+        let mut rows = PersistedRows::new();
+        let mut lsns = vec![];
+        for journal in journals {
+            journal.replay(&mut rows)?;
+            lsns.push(
+                rows.take_last_lsn()
+                    .map_or_else(|| "null".into(), |lsn| lsn.to_string()),
+            );
+        }
+        let path = format!("{dir}/{}.{}.jsonl", entity.space(), entity.entity());
+        rows.write_rows(model, &path)?;
+        report.push_str("{\"model\":");
+        encode_str(
+            &mut report,
+            &format!("{}.{}", entity.space(), entity.entity()),
+        );
+        report.push_str(",\"path\":");
+        encode_str(&mut report, &path);
+        let _ = writeln!(
+            report,
+            ",\"lsns\":[{}],\"rows\":{}}}",
+            lsns.join(","),
+            rows.remaining().0
+        );
+    }
+    Ok(report)
+}
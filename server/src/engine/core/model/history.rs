@@ -0,0 +1,125 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    crate::engine::{
+        core::index::{DcFieldIndex, PrimaryIndexKey},
+        data::cell::Datacell,
+        idx::STIndex,
+    },
+    parking_lot::Mutex,
+    std::collections::{HashMap, VecDeque},
+};
+
+/// The fields of a prior version of a row
+pub type RowSnapshot = Box<[(Box<str>, Datacell)]>;
+
+#[derive(Debug)]
+struct RowVersion {
+    /// the time (in seconds since the UNIX epoch) at which this version was replaced
+    until: u64,
+    /// the fields, or [`None`] if the row didn't exist
+    fields: Option<RowSnapshot>,
+}
+
+/// The version of a row that was current at some point in time
+#[derive(Debug, PartialEq)]
+pub enum HistoricRow {
+    /// the row hasn't changed since
+    Current,
+    /// the row didn't exist
+    Missing,
+    /// the row has changed since, and these were its fields
+    Version(RowSnapshot),
+}
+
+/// The prior versions of the rows of a model that keeps history. Whenever a row is inserted, updated or deleted, the
+/// version that it replaced is appended to the row's log along with the time at which it was replaced.
+///
+/// The log is only kept in memory, so history starts afresh when the server is restarted
+#[derive(Debug, Default)]
+pub struct RowHistory {
+    log: Mutex<HashMap<PrimaryIndexKey, VecDeque<RowVersion>>>,
+}
+
+impl RowHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Take a snapshot of the given fields (to be recorded once the change is committed)
+    pub fn snapshot(fields: &DcFieldIndex) -> RowSnapshot {
+        fields
+            .st_iter_kv()
+            .map(|(field, dc)| (Box::from(field.as_str()), dc.clone()))
+            .collect()
+    }
+    /// Record that the row with the given key was changed at `now`. `fields` is the version that was replaced, or
+    /// [`None`] if the row didn't exist
+    pub fn record(&self, key: &PrimaryIndexKey, fields: Option<RowSnapshot>, now: u64) {
+        let mut log = self.log.lock();
+        let versions = log.entry(key.clone()).or_default();
+        // keep the log ordered, even if the clock goes backwards
+        let until = versions.back().map_or(now, |last| last.until.max(now));
+        versions.push_back(RowVersion { until, fields });
+    }
+    /// Returns the version of the row that was current at the end of the second `at`
+    pub fn version_at(&self, key: &PrimaryIndexKey, at: u64) -> HistoricRow {
+        let log = self.log.lock();
+        match log
+            .get(key)
+            .and_then(|versions| versions.iter().find(|version| version.until > at))
+        {
+            Some(RowVersion {
+                fields: Some(fields),
+                ..
+            }) => HistoricRow::Version(fields.clone()),
+            Some(RowVersion { fields: None, .. }) => HistoricRow::Missing,
+            None => HistoricRow::Current,
+        }
+    }
+    /// Discard all versions that were replaced at or before `before`. Returns the number of versions that were
+    /// discarded
+    pub fn prune(&self, before: u64) -> usize {
+        let mut log = self.log.lock();
+        let mut pruned = 0;
+        log.retain(|_, versions| {
+            while versions
+                .front()
+                .map_or(false, |version| version.until <= before)
+            {
+                versions.pop_front();
+                pruned += 1;
+            }
+            !versions.is_empty()
+        });
+        pruned
+    }
+    #[cfg(test)]
+    /// Returns the number of versions in the log
+    pub fn version_count(&self) -> usize {
+        self.log.lock().values().map(VecDeque::len).sum()
+    }
+}
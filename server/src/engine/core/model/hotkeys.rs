@@ -0,0 +1,265 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    hot keys
+    ---
+    one in every `SAMPLE_EVERY` point accesses of a model (reads and writes of a row by its primary key) is counted in
+    a count-min sketch of the model's keys: `DEPTH` rows of `WIDTH` counters, where each row indexes its counters with
+    a different hash of the key. a key's count is estimated as the smallest of its counters, which can only overcount
+    (when other keys share every one of its counters). the `TOP_MAX` keys with the largest estimates are kept on the
+    side so that they can be reported with `sysctl report hotkeys`.
+
+    once `DECAY_AFTER` samples have been counted, every counter (and estimate) is halved, so that the report favours
+    keys that are hot now over keys that were hot a long time ago. the sketch is only allocated once the model's first
+    access is sampled. the hot keys are saved when the server shuts down and counted again when it starts (see
+    `warmup`), but the rest of the sketch is only kept in memory
+*/
+
+use {
+    crate::engine::{
+        core::index::PrimaryIndexKey,
+        fractal::GlobalInstanceLike,
+        storage::{encode_key, encode_str},
+    },
+    parking_lot::Mutex,
+    std::{
+        collections::hash_map::DefaultHasher,
+        fmt::Write,
+        hash::{Hash, Hasher},
+        sync::atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// One in this many point accesses is sampled
+pub const SAMPLE_EVERY: u64 = 16;
+/// The number of rows of counters (and hence of hashes of each key)
+const DEPTH: usize = 4;
+/// The number of counters in a row
+const WIDTH: usize = 1024;
+/// The number of hot keys that are kept
+const TOP_MAX: usize = 16;
+/// The counters are halved once this many samples have been counted
+const DECAY_AFTER: u64 = 1 << 16;
+
+#[derive(Debug, Default)]
+/// Estimates how often each key of a model is accessed (see the module docs)
+pub struct HotKeys {
+    accesses: AtomicU64,
+    sketch: Mutex<Option<Box<Sketch>>>,
+}
+
+impl HotKeys {
+    /// Note that the row with this key was read or written
+    pub fn record(&self, key: &PrimaryIndexKey) {
+        if self.accesses.fetch_add(1, Ordering::Relaxed) % SAMPLE_EVERY != 0 {
+            return;
+        }
+        self.sketch
+            .lock()
+            .get_or_insert_with(|| Box::new(Sketch::new()))
+            .add(key, 1);
+    }
+    /// Count the hot keys of the last run (as returned by [`Self::top`]) again, so that they're still hot until other
+    /// keys take over. This doesn't count as accesses
+    pub fn seed(&self, keys: &[(PrimaryIndexKey, u64)]) {
+        let mut sketch = self.sketch.lock();
+        let sketch = sketch.get_or_insert_with(|| Box::new(Sketch::new()));
+        for (key, estimate) in keys {
+            let samples = u32::try_from(estimate / SAMPLE_EVERY).unwrap_or(u32::MAX);
+            if samples != 0 {
+                sketch.add(key, samples);
+            }
+        }
+    }
+    /// Returns the number of point accesses since the server started
+    pub fn accesses(&self) -> u64 {
+        self.accesses.load(Ordering::Relaxed)
+    }
+    /// Returns the hottest keys (hottest first) along with the estimated number of times they were accessed
+    pub fn top(&self) -> Vec<(PrimaryIndexKey, u64)> {
+        let sketch = self.sketch.lock();
+        let mut top: Vec<_> = sketch
+            .iter()
+            .flat_map(|sketch| sketch.top.iter())
+            .map(|(key, estimate)| (key.clone(), *estimate as u64 * SAMPLE_EVERY))
+            .collect();
+        top.sort_by(|(k1, e1), (k2, e2)| e2.cmp(e1).then_with(|| k1.cmp(k2)));
+        top
+    }
+}
+
+#[derive(Debug)]
+struct Sketch {
+    counters: Box<[[u32; WIDTH]; DEPTH]>,
+    samples: u64,
+    top: Vec<(PrimaryIndexKey, u32)>,
+}
+
+impl Sketch {
+    fn new() -> Self {
+        Self {
+            counters: Box::new([[0; WIDTH]; DEPTH]),
+            samples: 0,
+            top: Vec::with_capacity(TOP_MAX),
+        }
+    }
+    fn slot(row: usize, key: &PrimaryIndexKey) -> usize {
+        let mut hasher = DefaultHasher::new();
+        row.hash(&mut hasher);
+        key.hash(&mut hasher);
+        hasher.finish() as usize % WIDTH
+    }
+    /// Count `samples` samples of the key
+    fn add(&mut self, key: &PrimaryIndexKey, samples: u32) {
+        let mut estimate = u32::MAX;
+        for (row, counters) in self.counters.iter_mut().enumerate() {
+            let counter = &mut counters[Self::slot(row, key)];
+            *counter = counter.saturating_add(samples);
+            estimate = estimate.min(*counter);
+        }
+        let top_len = self.top.len();
+        match self.top.iter_mut().find(|(k, _)| k == key) {
+            Some((_, e)) => *e = estimate,
+            None if top_len < TOP_MAX => self.top.push((key.clone(), estimate)),
+            None => {
+                // replace the coldest of the hot keys if this key is now hotter
+                let coldest = self.top.iter_mut().min_by_key(|(_, e)| *e).unwrap();
+                if coldest.1 < estimate {
+                    *coldest = (key.clone(), estimate);
+                }
+            }
+        }
+        let before = self.samples;
+        self.samples += samples as u64;
+        if self.samples / DECAY_AFTER != before / DECAY_AFTER {
+            self.counters
+                .iter_mut()
+                .flat_map(|counters| counters.iter_mut())
+                .for_each(|counter| *counter /= 2);
+            self.top.iter_mut().for_each(|(_, e)| *e /= 2);
+            self.top.retain(|(_, e)| *e != 0);
+        }
+    }
+}
+
+/// Returns the hottest keys of every model as a JSON array, with the number of point accesses (reads and writes of a
+/// row by its primary key) since the server started and the estimated number of accesses of each hot key
+pub fn describe_hot_keys(global: &impl GlobalInstanceLike) -> String {
+    let mut out = String::from("[");
+    let models = global.state().namespace().idx_models().read();
+    for (entity, model) in models.iter() {
+        if out.len() != 1 {
+            out.push(',');
+        }
+        let hot_keys = model.data().hot_keys();
+        out.push_str("{\"model\":");
+        encode_str(&mut out, &format!("{}.{}", entity.space(), entity.entity()));
+        let _ = write!(
+            out,
+            ",\"accesses\":{},\"sample_every\":{SAMPLE_EVERY},\"keys\":[",
+            hot_keys.accesses()
+        );
+        for (i, (key, estimate)) in hot_keys.top().into_iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            out.push_str("{\"key\":");
+            encode_key(&mut out, &key);
+            let _ = write!(out, ",\"estimate\":{estimate}}}");
+        }
+        out.push_str("]}");
+    }
+    out.push(']');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::engine::data::cell::Datacell};
+
+    #[test]
+    fn hottest_keys_first() {
+        let hot_keys = HotKeys::default();
+        let (hot, warm) = (
+            PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(1)).unwrap(),
+            PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(2)).unwrap(),
+        );
+        // only one in every `SAMPLE_EVERY` accesses is counted
+        for _ in 0..SAMPLE_EVERY * 4 {
+            hot_keys.record(&hot);
+        }
+        for _ in 0..SAMPLE_EVERY {
+            hot_keys.record(&warm);
+        }
+        assert_eq!(hot_keys.accesses(), SAMPLE_EVERY * 5);
+        assert_eq!(
+            hot_keys.top(),
+            vec![(hot.clone(), SAMPLE_EVERY * 4), (warm, SAMPLE_EVERY)]
+        );
+    }
+
+    #[test]
+    fn coldest_key_is_replaced() {
+        let key = |k| PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(k)).unwrap();
+        let mut sketch = Sketch::new();
+        for k in 0..TOP_MAX as u64 {
+            sketch.add(&key(k), 1);
+        }
+        // a key that's only as hot as the others doesn't make it in
+        sketch.add(&key(100), 1);
+        assert!(!sketch.top.iter().any(|(k, _)| *k == key(100)));
+        // but a hotter one does
+        sketch.add(&key(100), 1);
+        assert_eq!(sketch.top.len(), TOP_MAX);
+        assert!(sketch.top.iter().any(|(k, e)| (*k == key(100)) & (*e == 2)));
+    }
+
+    #[test]
+    fn decay() {
+        let key = PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(1)).unwrap();
+        let mut sketch = Sketch::new();
+        for _ in 0..DECAY_AFTER {
+            sketch.add(&key, 1);
+        }
+        assert_eq!(sketch.top, vec![(key, (DECAY_AFTER / 2) as u32)]);
+    }
+
+    #[test]
+    fn seeded_keys_stay_hot() {
+        let key = |k| PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(k)).unwrap();
+        let hot_keys = HotKeys::default();
+        // estimates that round down to no samples aren't counted
+        hot_keys.seed(&[(key(1), SAMPLE_EVERY * 3), (key(2), SAMPLE_EVERY - 1)]);
+        assert_eq!(hot_keys.accesses(), 0);
+        assert_eq!(hot_keys.top(), vec![(key(1), SAMPLE_EVERY * 3)]);
+        // and they add up with the accesses of this run
+        for _ in 0..SAMPLE_EVERY {
+            hot_keys.record(&key(1));
+        }
+        assert_eq!(hot_keys.top(), vec![(key(1), SAMPLE_EVERY * 4)]);
+    }
+}
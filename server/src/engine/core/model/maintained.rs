@@ -0,0 +1,383 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    maintained aggregates
+    ---
+    a model can declare aggregates that it maintains, using the `maintain` property: a map of names to definitions,
+    each of which is either `count(*) group by field` or `sum(field) group by field`. for every grouping field, the
+    model keeps the number of rows in each group along with the sums of the summed fields. the totals are built from
+    the primary index when they're first read (and again after a restart). from then on, the DML layer records the
+    keys of the rows that a query changes and applies the changes to the totals once the query is done: the values
+    that a row last contributed are kept so that they can be taken out of the totals when it changes (or is no longer
+    live), and a group goes away with its last row. `select count(*), sum(field) ... group by field` without a where
+    clause is then answered from the totals (without a scan). the primary index remains the source of truth, so the
+    totals are never persisted
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{
+        core::{
+            dml::{CellKey, Sum},
+            index::{DcFieldIndex, PrimaryIndexKey},
+            lock,
+        },
+        data::cell::Datacell,
+        error::QueryError,
+        idx::STIndex,
+        ql::{dml::sel::Aggregate, lex::Ident},
+        sync::atm::{cpin, Guard},
+    },
+    parking_lot::{Mutex, RwLock, RwLockWriteGuard},
+    std::{
+        collections::HashMap,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// A maintained aggregate (the value of an entry of the `maintain` property)
+#[derive(Debug, PartialEq, Clone)]
+pub struct MaintainedDef {
+    /// the summed field ([`None`] for `count(*)`)
+    sum: Option<Box<str>>,
+    group_by: Box<str>,
+}
+
+impl MaintainedDef {
+    /// Parse a definition: `count(*) group by field` or `sum(field) group by field`
+    pub(super) fn parse(def: &str) -> Option<Self> {
+        fn field(name: &str) -> Option<Box<str>> {
+            let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() | (c == '_'))
+                & name.chars().all(|c| c.is_ascii_alphanumeric() | (c == '_'));
+            valid.then(|| name.into())
+        }
+        let mut tokens = def.split_ascii_whitespace();
+        let sum = match tokens.next()? {
+            "count(*)" => None,
+            func => Some(field(func.strip_prefix("sum(")?.strip_suffix(')')?)?),
+        };
+        if (tokens.next()? != "group") | (tokens.next()? != "by") {
+            return None;
+        }
+        let group_by = field(tokens.next()?)?;
+        tokens.next().is_none().then_some(Self { sum, group_by })
+    }
+    /// Returns the summed field (if this is a sum)
+    pub fn sum(&self) -> Option<&str> {
+        self.sum.as_deref()
+    }
+    pub fn group_by(&self) -> &str {
+        &self.group_by
+    }
+    /// Returns true if this aggregate uses the given field
+    pub fn uses_field(&self, field: &str) -> bool {
+        (self.group_by.as_ref() == field) | (self.sum() == Some(field))
+    }
+    /// Returns the (normalized) definition
+    pub fn describe(&self) -> String {
+        match self.sum() {
+            Some(field) => format!("sum({field}) group by {}", self.group_by),
+            None => format!("count(*) group by {}", self.group_by),
+        }
+    }
+}
+
+/// The number of rows in a group and the sums of the summed fields over them
+#[derive(Debug)]
+pub struct GroupTotals {
+    count: u64,
+    sums: Box<[Sum]>,
+}
+
+impl GroupTotals {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// The totals of every group of a grouping field
+#[derive(Debug)]
+pub struct Grouping {
+    field: Box<str>,
+    sums: Box<[Box<str>]>,
+    index: HashMap<CellKey, usize>,
+    groups: Vec<(Datacell, GroupTotals)>,
+}
+
+impl Grouping {
+    fn new(field: Box<str>, sums: Box<[Box<str>]>) -> Self {
+        Self {
+            field,
+            sums,
+            index: HashMap::new(),
+            groups: Vec::new(),
+        }
+    }
+    /// Returns the groups along with their totals
+    pub fn groups(&self) -> impl Iterator<Item = (&Datacell, &GroupTotals)> {
+        self.groups.iter().map(|(group, totals)| (group, totals))
+    }
+    /// Returns the sum of the given (summed) field over a group
+    pub fn sum(&self, totals: &GroupTotals, field: &str) -> Sum {
+        let slot = self.sums.iter().position(|sum| sum.as_ref() == field);
+        totals.sums[slot.unwrap()].clone()
+    }
+    /// The number of values (the grouping field followed by the summed fields) that a row contributes
+    fn width(&self) -> usize {
+        1 + self.sums.len()
+    }
+    /// Add (or take out) the values that a row contributes
+    fn apply(&mut self, values: &[Datacell], add: bool) {
+        let (group, sums) = values.split_first().unwrap();
+        let slot = match self.index.get(&CellKey(group.clone())) {
+            Some(&slot) => slot,
+            None if add => {
+                self.index.insert(CellKey(group.clone()), self.groups.len());
+                self.groups.push((
+                    group.clone(),
+                    GroupTotals {
+                        count: 0,
+                        sums: vec![Sum::default(); sums.len()].into_boxed_slice(),
+                    },
+                ));
+                self.groups.len() - 1
+            }
+            None => return,
+        };
+        let totals = &mut self.groups[slot].1;
+        // nulls are never summed
+        for (sum, value) in totals
+            .sums
+            .iter_mut()
+            .zip(sums)
+            .filter(|(_, v)| !v.is_null())
+        {
+            if add {
+                sum.add(value);
+            } else {
+                sum.remove(value);
+            }
+        }
+        if add {
+            totals.count += 1;
+            return;
+        }
+        totals.count -= 1;
+        if totals.count == 0 {
+            self.index.remove(&CellKey(group.clone()));
+            self.groups.swap_remove(slot);
+            if let Some((moved, _)) = self.groups.get(slot) {
+                self.index.insert(CellKey(moved.clone()), slot);
+            }
+        }
+    }
+}
+
+/// The totals of every grouping field, along with the values that each live row contributes to them
+#[derive(Debug, Default)]
+struct Totals {
+    groupings: Vec<Grouping>,
+    rows: HashMap<PrimaryIndexKey, Box<[Datacell]>>,
+}
+
+impl Totals {
+    fn new(defs: &[(Box<str>, MaintainedDef)]) -> Self {
+        let mut groupings: Vec<Grouping> = Vec::new();
+        for (_, def) in defs {
+            let slot = match groupings
+                .iter()
+                .position(|grouping| grouping.field.as_ref() == def.group_by())
+            {
+                Some(slot) => slot,
+                None => {
+                    groupings.push(Grouping::new(def.group_by.clone(), Box::default()));
+                    groupings.len() - 1
+                }
+            };
+            let grouping = &mut groupings[slot];
+            if let Some(sum) = def.sum() {
+                if !grouping.sums.iter().any(|s| s.as_ref() == sum) {
+                    let mut sums = grouping.sums.to_vec();
+                    sums.push(sum.into());
+                    grouping.sums = sums.into_boxed_slice();
+                }
+            }
+        }
+        Self {
+            groupings,
+            rows: HashMap::new(),
+        }
+    }
+    fn build(mdl: &ModelData, g: &Guard) -> Self {
+        let mut totals = Self::new(mdl.props().maintain());
+        for row in mdl.primary_index().iter(g) {
+            // a row that can't be read is left out (see the model's overflow report)
+            let Ok(data) = lock::read(mdl, row) else {
+                continue;
+            };
+            if !data.is_tombstoned() {
+                totals.set(row.d_key(), Some(data.fields()));
+            }
+        }
+        totals
+    }
+    /// Take the values that the row with the given key last contributed out of the totals, and add its current
+    /// values (if it's still live)
+    fn set(&mut self, key: &PrimaryIndexKey, fields: Option<&DcFieldIndex>) {
+        if let Some(values) = self.rows.remove(key) {
+            self.apply(&values, false);
+        }
+        let Some(fields) = fields else {
+            return;
+        };
+        let values: Box<[Datacell]> = self
+            .groupings
+            .iter()
+            .flat_map(|grouping| core::iter::once(&grouping.field).chain(grouping.sums.iter()))
+            .map(|field| {
+                fields
+                    .st_get(&**field)
+                    .cloned()
+                    .unwrap_or_else(Datacell::null)
+            })
+            .collect();
+        self.apply(&values, true);
+        self.rows.insert(key.clone(), values);
+    }
+    fn apply(&mut self, mut values: &[Datacell], add: bool) {
+        for grouping in self.groupings.iter_mut() {
+            let (row, rest) = values.split_at(grouping.width());
+            grouping.apply(row, add);
+            values = rest;
+        }
+    }
+}
+
+/// The aggregates maintained by a model (see the module docs)
+#[derive(Debug, Default)]
+pub struct MaintainedAggregates {
+    /// set once the totals are built. changes are only recorded while this is set
+    built: AtomicBool,
+    pending: Mutex<Vec<PrimaryIndexKey>>,
+    totals: RwLock<Totals>,
+}
+
+impl MaintainedAggregates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Record a change to the row with the given key, so that the totals are updated once the query is done
+    pub(in crate::engine::core) fn touch(&self, key: &PrimaryIndexKey) {
+        if self.built.load(Ordering::Acquire) {
+            self.pending.lock().push(key.clone());
+        }
+    }
+    /// Apply the changes to the rows that have changed to the totals. Must not be called with a row locked
+    pub(in crate::engine::core) fn sync(&self, mdl: &ModelData) {
+        let keys = core::mem::take(&mut *self.pending.lock());
+        if keys.is_empty() {
+            return;
+        }
+        // syncs are serialized (and the rows are read with the totals locked) so that a row is never counted twice
+        let mut totals = self.totals.write();
+        let g = cpin();
+        for key in keys {
+            match mdl.primary_index().select_key(&key, &g) {
+                Some(row) => match lock::read(mdl, row) {
+                    Ok(data) => totals.set(&key, (!data.is_tombstoned()).then(|| data.fields())),
+                    // the row is synced again next time
+                    Err(QueryError::SysLockWaitTimeout) => self.pending.lock().push(key),
+                    Err(_) => totals.set(&key, None),
+                },
+                None => totals.set(&key, None),
+            }
+        }
+    }
+    /// Returns true if every aggregate is a `count(*)` or a `sum` that is maintained for the given grouping field
+    pub fn can_answer(mdl: &ModelData, group_by: Option<Ident>, aggregates: &[Aggregate]) -> bool {
+        let Some(group_by) = group_by else {
+            return false;
+        };
+        let maintained = |sum: Option<&str>| {
+            mdl.props()
+                .maintain()
+                .iter()
+                .any(|(_, def)| (def.group_by() == group_by.as_str()) & (def.sum() == sum))
+        };
+        aggregates.iter().all(|aggregate| match aggregate {
+            Aggregate::Count => maintained(None),
+            Aggregate::Sum(field) => maintained(Some(field.as_str())),
+            Aggregate::CountDistinct(_) | Aggregate::TopK(..) => false,
+        })
+    }
+    /// Run `f` with the totals of the given grouping field, building the totals first if they haven't been built
+    pub fn with_grouping<T>(
+        &self,
+        mdl: &ModelData,
+        group_by: &str,
+        f: impl FnOnce(&Grouping) -> T,
+    ) -> T {
+        let grouping = |totals: &Totals| {
+            let slot = totals
+                .groupings
+                .iter()
+                .position(|grouping| grouping.field.as_ref() == group_by);
+            f(&totals.groupings[slot.unwrap()])
+        };
+        {
+            let totals = self.totals.read();
+            if self.built.load(Ordering::Acquire) {
+                return grouping(&totals);
+            }
+        }
+        let g = cpin();
+        // no rows can change while the totals are built
+        let _idx_latch = mdl.primary_index().acquire_exclusive();
+        let mut totals = self.totals.write();
+        if !self.built.load(Ordering::Acquire) {
+            *totals = Totals::build(mdl, &g);
+            self.pending.lock().clear();
+            self.built.store(true, Ordering::Release);
+        }
+        grouping(&RwLockWriteGuard::downgrade(totals))
+    }
+    /// Returns whether the totals are built, the number of rows that they're computed over and the number of groups
+    /// as a JSON object
+    pub fn describe(&self) -> String {
+        let totals = self.totals.read();
+        format!(
+            "{{\"built\":{},\"rows\":{},\"groups\":{}}}",
+            self.built.load(Ordering::Acquire),
+            totals.rows.len(),
+            totals
+                .groupings
+                .iter()
+                .map(|grouping| grouping.groups.len())
+                .sum::<usize>()
+        )
+    }
+}
@@ -25,34 +25,93 @@
 */
 
 pub(super) mod alt;
+mod backfill;
+pub(in crate::engine) mod check;
+pub(in crate::engine) mod columnar;
+pub(in crate::engine) mod computed;
+mod copy;
+pub(in crate::engine) mod decimal;
 pub(in crate::engine) mod delta;
+mod export;
+pub(in crate::engine) mod history;
+pub(in crate::engine) mod hotkeys;
+pub(in crate::engine) mod maintained;
+pub(in crate::engine) mod overflow;
+pub(in crate::engine) mod predicate;
+pub(in crate::engine) mod props;
+mod restore;
+pub(in crate::engine) mod result_cache;
+mod secondary;
+pub(in crate::engine) mod stats;
+pub(in crate::engine) mod upstream;
+mod verify;
+mod view;
+pub(in crate::engine) mod warmup;
+pub(in crate::engine) mod webhook;
+pub(in crate::engine) mod window;
 
 use {
-    super::index::PrimaryIndex,
+    self::{
+        columnar::ColumnStore, computed::ComputedExpr, decimal::Decimal, history::RowHistory,
+        hotkeys::HotKeys, maintained::MaintainedAggregates, overflow::ModelOverflow,
+        predicate::Predicate, result_cache::ResultCache, stats::FieldStats,
+        upstream::UpstreamState, webhook::Webhook, window::WindowCounter,
+    },
+    super::{
+        dml::{self, keygen::KeyGen},
+        index::{Collation, DcFieldIndex, PrimaryIndex, PrimaryIndexKey, SecondaryIndex},
+        names,
+        quota::Quota,
+        space::Space,
+    },
     crate::engine::{
         data::{
             cell::Datacell,
+            dict,
+            lit::Lit,
             tag::{DataTag, FloatSpec, FullTag, SIntSpec, TagClass, TagSelector, UIntSpec},
             uuid::Uuid,
+            DictEntryGeneric, DictGeneric,
         },
         error::{QueryError, QueryResult},
-        fractal::{FractalModelDriver, GenericTask, GlobalInstanceLike, Task},
+        fractal::{FractalModelDriver, GlobalInstanceLike},
         idx::{self, IndexBaseSpec, IndexSTSeqCns, STIndex, STIndexSeq},
         mem::{RawStr, VInline},
-        ql::ddl::{
-            crt::CreateModel,
-            drop::DropModel,
-            syn::{FieldSpec, LayerSpec},
+        ql::{
+            ddl::{
+                crt::{CreateModel, CreateModelCopy},
+                drop::DropModel,
+                syn::{FieldSpec, LayerSpec},
+            },
+            dml::RelationalExpr,
+            lex::{quote_ident, Ident},
         },
         txn::{gns, ModelIDRef, SpaceIDRef},
     },
-    std::collections::hash_map::{Entry, HashMap},
+    std::{
+        collections::hash_map::{Entry, HashMap},
+        sync::Arc,
+    },
 };
 
 pub(in crate::engine::core) use self::delta::{DeltaState, DeltaVersion, SchemaDeltaKind};
+pub(in crate::engine) use self::{
+    backfill::{backfill_model, backfill_model_in_background},
+    copy::{copy_model, copy_model_in_background},
+    export::{export_models, export_models_in_background},
+    hotkeys::describe_hot_keys,
+    props::{FieldProps, ModelProps},
+    restore::{restore_model, restore_model_in_background},
+    secondary::{build_pending_indexes, describe_indexes},
+    verify::{verify_model, verify_model_in_background},
+    view::{link_views, ModelViews},
+    webhook::{create_webhook, describe_webhooks, drop_webhook},
+};
 
 use super::util::{EntityID, EntityIDRef};
 type Fields = IndexSTSeqCns<RawStr, Field>;
+type SecondaryIndexes = IndexSTSeqCns<Box<str>, SecondaryIndex>;
+type Webhooks = IndexSTSeqCns<Box<str>, Arc<Webhook>>;
 
 #[derive(Debug)]
 pub struct Model {
@@ -76,6 +135,30 @@ impl Model {
     pub fn into_driver(self) -> FractalModelDriver {
         self.driver
     }
+    pub fn into_data(self) -> ModelData {
+        self.data
+    }
+    /// Returns the state of the model's writes as a JSON object: the deltas waiting to be written, the time of the
+    /// last write that succeeded, how often the deltas hit the cache capacity, how many writes failed and the batch
+    /// size picked by the flusher (with the throughput and batch write time it was picked from)
+    pub fn describe_flush(&self) -> String {
+        let stats = self.driver.flush_stats();
+        let batch = stats.batch_sizing().map_or_else(
+            || "null".to_owned(),
+            |(size, throughput, write_us)| {
+                format!("{{\"size\":{size},\"throughput\":{throughput},\"write_us\":{write_us}}}")
+            },
+        );
+        format!(
+            "{{\"pending\":{},\"last_persist\":{},\"stalls\":{},\"failures\":{},\"batch\":{batch}}}",
+            self.data.delta_state().pending_data_deltas(),
+            stats
+                .last_persist()
+                .map_or_else(|| "null".to_owned(), |t| t.to_string()),
+            self.data.delta_state().data_stalls(),
+            stats.failures()
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -88,6 +171,19 @@ pub struct ModelData {
     delta: DeltaState,
     private: ModelPrivate,
     decl: String,
+    props: ModelProps,
+    secondary: SecondaryIndexes,
+    history: Option<RowHistory>,
+    stats: Option<FieldStats>,
+    result_cache: Option<ResultCache>,
+    columns: Option<ColumnStore>,
+    maintained: Option<MaintainedAggregates>,
+    upstream: Option<UpstreamState>,
+    overflow: Option<ModelOverflow>,
+    views: ModelViews,
+    webhooks: Webhooks,
+    hot_keys: HotKeys,
+    quota: Quota,
 }
 
 #[cfg(test)]
@@ -97,6 +193,29 @@ impl PartialEq for ModelData {
             && self.p_key == m.p_key
             && self.p_tag == m.p_tag
             && self.fields == m.fields
+            && self.props == m.props
+            && self.quota == m.quota
+            && self
+                .secondary
+                .stseq_ord_kv()
+                .map(|(name, idx)| {
+                    (
+                        name,
+                        idx.fields(),
+                        idx.include(),
+                        idx.filter(),
+                        idx.collation(),
+                    )
+                })
+                .eq(m.secondary.stseq_ord_kv().map(|(name, idx)| {
+                    (
+                        name,
+                        idx.fields(),
+                        idx.include(),
+                        idx.filter(),
+                        idx.collation(),
+                    )
+                }))
     }
 }
 
@@ -132,6 +251,227 @@ impl ModelData {
     pub fn fields(&self) -> &Fields {
         &self.fields
     }
+    pub fn props(&self) -> &ModelProps {
+        &self.props
+    }
+    /// Returns true if the model's flush triggers (`flush_every_n_deltas` and `flush_every_ms`) call for the given
+    /// number of pending changes to be written right away
+    pub fn flush_due(&self, pending: usize, now_ms: u64) -> bool {
+        let n_due = self
+            .props
+            .flush_every_n_deltas()
+            .is_some_and(|n| pending >= n);
+        let ms_due = self
+            .props
+            .flush_every_ms()
+            .is_some_and(|ms| self.delta.ms_since_taken(now_ms) >= ms);
+        (pending != 0) & (n_due | ms_due)
+    }
+    pub fn secondary_indexes(&self) -> &SecondaryIndexes {
+        &self.secondary
+    }
+    /// Returns the log of prior row versions, if this model keeps history
+    pub fn history(&self) -> Option<&RowHistory> {
+        self.history.as_ref()
+    }
+    /// Returns the histograms of the numeric fields, if this model keeps them
+    pub fn stats(&self) -> Option<&FieldStats> {
+        self.stats.as_ref()
+    }
+    /// Returns the cached responses of reads, if this model caches them
+    pub fn result_cache(&self) -> Option<&ResultCache> {
+        self.result_cache.as_ref()
+    }
+    /// Returns the column-major copy of the rows, if this model is analytic
+    pub fn columns(&self) -> Option<&ColumnStore> {
+        self.columns.as_ref()
+    }
+    /// Returns the maintained aggregates, if this model maintains any
+    pub fn maintained(&self) -> Option<&MaintainedAggregates> {
+        self.maintained.as_ref()
+    }
+    /// Returns the rows fetched from the upstream, if this model caches one
+    pub fn upstream(&self) -> Option<&UpstreamState> {
+        self.upstream.as_ref()
+    }
+    /// Returns the overflow state, if this model moves cold rows to disk
+    pub fn overflow(&self) -> Option<&ModelOverflow> {
+        self.overflow.as_ref()
+    }
+    /// Returns the views derived from this model
+    pub fn views(&self) -> &ModelViews {
+        &self.views
+    }
+    /// Returns the webhooks that this model's changes are delivered to
+    pub fn webhooks(&self) -> &Webhooks {
+        &self.webhooks
+    }
+    /// Returns how far (in journal bytes) the webhook that is the furthest behind is
+    pub fn webhook_lag(&self) -> u64 {
+        self.webhooks
+            .stseq_ord_value()
+            .map(|webhook| webhook.lag())
+            .max()
+            .unwrap_or(0)
+    }
+    /// Returns the estimates of how often each key is accessed
+    pub fn hot_keys(&self) -> &HotKeys {
+        &self.hot_keys
+    }
+    /// Add a new secondary index on the given fields, storing the included fields along with each key (and only holding
+    /// the rows that satisfy the predicate, if any) and comparing strings with the given collation. The index must be
+    /// built before it can be used
+    pub fn add_secondary_index(
+        &mut self,
+        name: Box<str>,
+        fields: Box<[Box<str>]>,
+        include: Box<[Box<str>]>,
+        filter: Option<Predicate>,
+        collation: Collation,
+    ) -> bool {
+        self.secondary.st_insert(
+            name,
+            SecondaryIndex::new_building(fields, include, filter, collation),
+        )
+    }
+    pub fn remove_secondary_index(&mut self, name: &str) -> bool {
+        self.secondary.st_delete(name)
+    }
+    pub fn add_webhook(&mut self, name: Box<str>, webhook: Arc<Webhook>) -> bool {
+        self.webhooks.st_insert(name, webhook)
+    }
+    pub fn remove_webhook(&mut self, name: &str) -> bool {
+        self.webhooks.st_delete(name)
+    }
+    /// Returns the limits on the statements run on this model every second
+    pub fn quota(&self) -> &Quota {
+        &self.quota
+    }
+    pub fn set_quota(&mut self, quota: Quota) {
+        self.quota = quota;
+    }
+    /// Returns the number of computed fields in this model
+    pub fn computed_field_count(&self) -> usize {
+        self.fields
+            .stseq_ord_value()
+            .filter(|field| field.is_computed())
+            .count()
+    }
+    /// Returns true if any computed field is computed from the given field
+    pub fn is_computed_source(&self, field: &str) -> bool {
+        self.fields
+            .stseq_ord_value()
+            .filter_map(Field::computed)
+            .any(|expr| expr.source() == field)
+    }
+    /// Returns true if the field has one or more secondary indexes (or a covering index stores it, or the predicate of a
+    /// partial index uses it)
+    pub fn is_indexed(&self, field: &str) -> bool {
+        self.secondary.stseq_ord_value().any(|idx| {
+            idx.indexes(field)
+                | idx.include().iter().any(|f| f.as_ref() == field)
+                | idx
+                    .filter()
+                    .map_or(false, |f| f.fields().any(|f| f == field))
+        })
+    }
+    /// Returns true if a maintained aggregate uses the given field
+    pub fn is_aggregated(&self, field: &str) -> bool {
+        self.props
+            .maintain()
+            .iter()
+            .any(|(_, def)| def.uses_field(field))
+    }
+    /// Returns true if the values of the given field can be moved to the blob tier (see `fractal::tier`): a binary field
+    /// (other than the primary key or a computed field) that no index, view, computed field or maintained aggregate
+    /// uses, since they would see the references
+    pub fn is_tierable(&self, field: &str) -> bool {
+        self.fields.st_get(field).map_or(false, |f| {
+            (f.layers()[0].tag().tag_class() == TagClass::Bin)
+                & (field != self.p_key())
+                & !f.is_computed()
+                & !self.is_indexed(field)
+                & !self.is_computed_source(field)
+                & !self.views.uses_field(field)
+                & !self.is_aggregated(field)
+        })
+    }
+    /// Returns a secondary index that can be used for a query with the given where clause, along with the values of
+    /// the longest prefix of its fields that the where clause has equality clauses for. An index with a longer prefix
+    /// is preferred (and then one that is ready for use). A partial index can only be used if the where clause implies
+    /// its predicate, and only indexes for which `usable` returns true are considered
+    pub fn secondary_index_for(
+        &self,
+        clauses: &HashMap<Ident, RelationalExpr>,
+        usable: impl Fn(&SecondaryIndex) -> bool,
+    ) -> Option<(&str, &SecondaryIndex, Vec<PrimaryIndexKey>)> {
+        let mut ret: Option<(&str, &SecondaryIndex, Vec<PrimaryIndexKey>)> = None;
+        for (name, idx) in self.secondary.stseq_ord_kv() {
+            if !idx.applies_to(clauses) | !usable(idx) {
+                continue;
+            }
+            let values: Vec<_> = idx
+                .fields()
+                .iter()
+                .map_while(|field| self.equality_value(field, clauses))
+                .collect();
+            let better = match &ret {
+                _ if values.is_empty() => false,
+                None => true,
+                Some((_, best, best_values)) => {
+                    (values.len() > best_values.len())
+                        | ((values.len() == best_values.len()) & idx.is_ready() & !best.is_ready())
+                }
+            };
+            if better {
+                ret = Some((name.as_ref(), idx, values));
+            }
+        }
+        ret
+    }
+    /// Returns the value of an equality clause on the given field (if the value has the field's type)
+    fn equality_value(
+        &self,
+        field: &str,
+        clauses: &HashMap<Ident, RelationalExpr>,
+    ) -> Option<PrimaryIndexKey> {
+        let clause = clauses.get(field.as_bytes())?;
+        let field_tag = self.fields.st_get(field)?.layers()[0].tag();
+        if !clause.filter_hint_none() | (clause.rhs().kind().tag_unique() != field_tag.tag_unique())
+        {
+            return None;
+        }
+        PrimaryIndexKey::try_from_dc(Datacell::from(clause.rhs()))
+    }
+    pub(in crate::engine::core) fn sidx_insert_row(
+        &self,
+        pk: &PrimaryIndexKey,
+        fields: &DcFieldIndex,
+    ) {
+        self.secondary
+            .stseq_ord_value()
+            .for_each(|idx| idx.insert_row(self, pk, fields))
+    }
+    pub(in crate::engine::core) fn sidx_remove_row(
+        &self,
+        pk: &PrimaryIndexKey,
+        fields: &DcFieldIndex,
+    ) {
+        self.secondary
+            .stseq_ord_value()
+            .for_each(|idx| idx.remove_row(pk, fields))
+    }
+    /// Sync the secondary indexes with an updated row, where `old` holds the prior values of the fields that changed
+    pub(in crate::engine::core) fn sidx_update_row(
+        &self,
+        pk: &PrimaryIndexKey,
+        old: &[(&str, Datacell)],
+        fields: &DcFieldIndex,
+    ) {
+        self.secondary
+            .stseq_ord_value()
+            .for_each(|idx| idx.update_row(self, pk, old, fields))
+    }
     pub fn model_mutator<'a>(&'a mut self) -> ModelMutator<'a> {
         ModelMutator { model: self }
     }
@@ -141,6 +481,49 @@ impl ModelData {
     pub fn describe(&self) -> &str {
         &self.decl
     }
+    /// Returns the schema as a JSON object for tools that generate typed bindings. Fields are listed in the order
+    /// that they were declared in, along with their layers (outermost first). A layer is identified by the ids of its
+    /// tag selector, class and unique tag, which are stable (see [`crate::engine::data::tag`]); the unique tag is null
+    /// for types that can't be a primary key
+    pub fn describe_schema(&self) -> String {
+        let fields: Vec<String> = self
+            .fields()
+            .stseq_ord_kv()
+            .map(|(field_name, field_decl)| {
+                let layers: Vec<String> = field_decl
+                    .layers()
+                    .iter()
+                    .map(|layer| {
+                        let tag = layer.tag();
+                        let unique = tag.tag_unique();
+                        format!(
+                            "{{\"type\":\"{}\",\"selector\":{},\"class\":{},\"unique\":{}}}",
+                            LUT[tag.tag_selector().value_word()].0,
+                            tag.tag_selector().value_u8(),
+                            tag.tag_class().value_u8(),
+                            if unique.is_unique() {
+                                unique.value_u8().to_string()
+                            } else {
+                                "null".into()
+                            }
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"name\":\"{field_name}\",\"primary\":{},\"nullable\":{},\"layers\":[{}],\"properties\":{}}}",
+                    self.is_pk(field_name),
+                    field_decl.is_nullable(),
+                    layers.join(","),
+                    field_decl.props().describe()
+                )
+            })
+            .collect();
+        format!(
+            "{{\"primary_key\":\"{}\",\"fields\":[{}]}}",
+            self.p_key(),
+            fields.join(",")
+        )
+    }
     fn redescribe(&self) -> String {
         let mut ret = format!("{{");
         let mut it = self.fields().stseq_ord_kv().peekable();
@@ -153,10 +536,14 @@ impl ModelData {
             } else {
                 ret.push('!');
             }
-            ret.push_str(&field_name);
+            ret.push_str(&quote_ident(&field_name));
             ret.push(':');
             // TODO(@ohsayan): it's all lists right now, so this is okay but fix it later
-            if field_decl.layers().len() == 1 {
+            if field_decl.window().is_some() {
+                ret.push_str(WindowCounter::TYPE);
+            } else if field_decl.decimal().is_some() {
+                ret.push_str(Decimal::TYPE);
+            } else if field_decl.layers().len() == 1 {
                 ret.push_str(field_decl.layers()[0].tag().tag_selector().name_str());
             } else {
                 ret.push_str(&"[".repeat(field_decl.layers().len() - 1));
@@ -184,25 +571,53 @@ impl ModelData {
         p_tag: FullTag,
         fields: Fields,
         private: ModelPrivate,
+        props: ModelProps,
     ) -> Self {
         let mut slf = Self {
             uuid,
             p_key,
             p_tag,
             fields,
-            data: PrimaryIndex::new_empty(),
-            delta: DeltaState::new_resolved(),
+            data: PrimaryIndex::new_empty_with(props.index(), props.hasher(), props.partitions()),
+            delta: DeltaState::new_resolved(props.partitions()),
             private,
             decl: String::new(),
+            history: props.history().then(RowHistory::new),
+            stats: props.histograms().then(FieldStats::new),
+            result_cache: props
+                .result_cache()
+                .then(|| ResultCache::new(props.result_cache_ttl())),
+            columns: props.analytic().then(ColumnStore::new),
+            maintained: (!props.maintain().is_empty()).then(MaintainedAggregates::new),
+            upstream: props
+                .upstream()
+                .then(|| UpstreamState::new(props.upstream_ttl(), props.upstream_writes())),
+            overflow: props.overflow().then(ModelOverflow::default),
+            props,
+            secondary: IndexSTSeqCns::idx_init(),
+            views: ModelViews::default(),
+            webhooks: IndexSTSeqCns::idx_init(),
+            hot_keys: HotKeys::default(),
+            quota: Quota::default(),
         };
         slf.sync_decl();
         slf
     }
+    #[cfg(test)]
     pub fn new_restore(
         uuid: Uuid,
         p_key: Box<str>,
         p_tag: FullTag,
         decl_fields: IndexSTSeqCns<Box<str>, Field>,
+    ) -> Self {
+        Self::new_restore_with_props(uuid, p_key, p_tag, decl_fields, ModelProps::default())
+    }
+    pub fn new_restore_with_props(
+        uuid: Uuid,
+        p_key: Box<str>,
+        p_tag: FullTag,
+        decl_fields: IndexSTSeqCns<Box<str>, Field>,
+        props: ModelProps,
     ) -> Self {
         let mut private = ModelPrivate::empty();
         let p_key = unsafe {
@@ -224,7 +639,7 @@ impl ModelData {
             .for_each(|(field_key, field)| {
                 fields.st_insert(field_key, field);
             });
-        Self::new_with_private(uuid, p_key, p_tag, fields, private)
+        Self::new_with_private(uuid, p_key, p_tag, fields, private, props)
     }
     pub fn process_create(
         CreateModel {
@@ -235,7 +650,13 @@ impl ModelData {
         }: CreateModel,
     ) -> QueryResult<Self> {
         let mut private = ModelPrivate::empty();
-        let mut okay = props.is_empty() & !fields.is_empty();
+        let props = ModelProps::try_new(dict::rflatten_metadata(props))
+            .ok_or(QueryError::QExecDdlModelBadDefinition)?;
+        // views can only be created with `create view`
+        if props.view().is_some() {
+            return Err(QueryError::QExecDdlModelBadDefinition);
+        }
+        let mut okay = !fields.is_empty();
         // validate fields
         let mut field_spec = fields.into_iter();
         let mut fields = Fields::idx_init_cap(field_spec.len());
@@ -264,24 +685,73 @@ impl ModelData {
             okay &= fields.st_insert(this_field_ptr, layer);
         }
         okay &= pk_cnt <= 1;
+        // with case insensitive names, no two fields can only differ in case
+        okay &= !(names::case_insensitive()
+            && names::any_differ_in_case(fields.stseq_ord_key().map(|field| field.as_str())));
         if okay {
             let last_pk = last_pk.unwrap_or(unsafe {
                 // UNSAFE(@ohsayan): once again, all of this is allocated
                 fields.stseq_ord_key().next().unwrap().clone()
             });
             let tag = fields.st_get(&last_pk).unwrap().layers()[0].tag;
-            if tag.tag_unique().is_unique() {
+            if tag.tag_unique().is_unique()
+                & Self::computed_fields_okay(&fields, &last_pk)
+                & Self::maintained_okay(&fields, &last_pk, &props)
+            {
                 return Ok(Self::new_with_private(
                     Uuid::new(),
                     last_pk,
                     tag,
                     fields,
                     private,
+                    props,
                 ));
             }
         }
         Err(QueryError::QExecDdlModelBadDefinition)
     }
+    /// A maintained aggregate must group by a (non-primary) scalar field, and can only sum a (non-primary) numeric
+    /// field
+    fn maintained_okay(fields: &Fields, pk: &RawStr, props: &ModelProps) -> bool {
+        let scalar = |name: &str| match fields.st_get(name) {
+            Some(f) if (name != pk.as_str()) & (f.layers().len() == 1) => {
+                Some(f.layers()[0].tag().tag_class())
+            }
+            _ => None,
+        };
+        props.maintain().iter().all(|(_, def)| {
+            let group_okay = scalar(def.group_by()).is_some();
+            let sum_okay = def.sum().map_or(true, |field| {
+                matches!(
+                    scalar(field),
+                    Some(TagClass::UnsignedInt | TagClass::SignedInt | TagClass::Float)
+                )
+            });
+            group_okay & sum_okay
+        })
+    }
+    /// Computed fields must be (non-primary) string fields, computed from a string field that is not computed itself.
+    /// If the source can be null, so must the computed field
+    fn computed_fields_okay(fields: &Fields, pk: &RawStr) -> bool {
+        fn is_str(f: &Field) -> bool {
+            (f.layers().len() == 1) & (f.layers()[0].tag().tag_class() == TagClass::Str)
+        }
+        fields.stseq_ord_kv().all(|(field_id, field)| {
+            let Some(expr) = field.computed() else {
+                return true;
+            };
+            match fields.st_get(expr.source()) {
+                Some(source) => {
+                    (field_id.as_str() != pk.as_str())
+                        & is_str(field)
+                        & is_str(source)
+                        & !source.is_computed()
+                        & (field.is_nullable() | !source.is_nullable())
+                }
+                None => false,
+            }
+        })
+    }
 }
 
 impl ModelData {
@@ -296,6 +766,7 @@ impl ModelData {
             .state()
             .namespace()
             .ddl_with_space_mut(&space_name, |space| {
+                global.state().freeze().check(space_name)?;
                 // TODO(@ohsayan): be extra cautious with post-transactional tasks (memck)
                 if space.models().contains(model_name) {
                     if if_nx {
@@ -317,20 +788,19 @@ impl ModelData {
                     space.get_uuid(),
                     &model_name,
                     model.get_uuid(),
+                    model.primary_index().partitions(),
                 )?;
                 // commit txn
                 global.state().gns_driver().driver_context(
                     global,
                     |drv| drv.commit_event(txn),
                     || {
-                        global.taskmgr_post_standard_priority(Task::new(
-                            GenericTask::delete_model_dir(
-                                &space_name,
-                                space.get_uuid(),
-                                &model_name,
-                                model.get_uuid(),
-                            ),
-                        ))
+                        global.purge_model_driver(
+                            &space_name,
+                            space.get_uuid(),
+                            &model_name,
+                            model.get_uuid(),
+                        )
                     },
                 )?;
                 // update global state
@@ -346,6 +816,135 @@ impl ModelData {
                 }
             })
     }
+    pub fn transactional_exec_create_copy<G: GlobalInstanceLike>(
+        global: &G,
+        CreateModelCopy {
+            model_name,
+            source,
+            with_data,
+            if_not_exists,
+        }: CreateModelCopy,
+    ) -> QueryResult<Option<bool>> {
+        let (space_name, model_name) = (model_name.space(), model_name.entity());
+        global
+            .state()
+            .namespace()
+            .ddl_with_space_mut(&space_name, |space| {
+                global.state().freeze().check(space_name)?;
+                if space.models().contains(model_name) {
+                    if if_not_exists {
+                        return Ok(Some(false));
+                    } else {
+                        return Err(QueryError::QExecDdlObjectAlreadyExists);
+                    }
+                }
+                // get exclusive lock on models; no rows can change until the copy is ready
+                let mut models_idx = global.state().namespace().idx_models().write();
+                let Some(source) = models_idx.get(&source) else {
+                    return Err(QueryError::QExecObjectNotFound.with_detail("entity", source));
+                };
+                let source = source.data();
+                let (model, mdl_driver) =
+                    Self::create_like(global, space_name, space, model_name, source)?;
+                if with_data {
+                    dml::copy_rows(&model, source)?;
+                }
+                // update global state
+                let _ = space.models_mut().insert(model_name.into());
+                let _ = models_idx.insert(
+                    EntityID::new(&space_name, &model_name),
+                    Model::new(model, mdl_driver),
+                );
+                if if_not_exists {
+                    Ok(Some(true))
+                } else {
+                    Ok(None)
+                }
+            })
+    }
+    /// Create a model with the rows that a model of a snapshot would be restored with (see
+    /// [`dml::restore_rows`]), returning the number of rows
+    pub fn transactional_exec_restore<G: GlobalInstanceLike>(
+        global: &G,
+        entity: EntityIDRef,
+        source: &ModelData,
+        rows: impl Iterator<Item = (PrimaryIndexKey, Vec<Datacell>)>,
+    ) -> QueryResult<u64> {
+        let (space_name, model_name) = (entity.space(), entity.entity());
+        global
+            .state()
+            .namespace()
+            .ddl_with_space_mut(space_name, |space| {
+                global.state().freeze().check(space_name)?;
+                if space.models().contains(model_name) {
+                    return Err(QueryError::QExecDdlObjectAlreadyExists);
+                }
+                // get exclusive lock on models; the model is only visible once all its rows are in
+                let mut models_idx = global.state().namespace().idx_models().write();
+                let (model, mdl_driver) =
+                    Self::create_like(global, space_name, space, model_name, source)?;
+                let restored = dml::restore_rows(&model, rows);
+                // update global state
+                let _ = space.models_mut().insert(model_name.into());
+                let _ = models_idx.insert(
+                    EntityID::new(space_name, model_name),
+                    Model::new(model, mdl_driver),
+                );
+                Ok(restored)
+            })
+    }
+    /// Create a model with the same fields, primary key and properties as `source` (which is a model of this or any
+    /// other instance), committing it to the GNS and initializing its driver. The model isn't made visible
+    fn create_like<G: GlobalInstanceLike>(
+        global: &G,
+        space_name: &str,
+        space: &Space,
+        model_name: &str,
+        source: &ModelData,
+    ) -> QueryResult<(Self, FractalModelDriver)> {
+        // a view is only ever created with `create view`
+        if source.props().view().is_some() {
+            return Err(QueryError::QExecDdlModelBadDefinition);
+        }
+        let props = ModelProps::try_new(source.props().raw().clone())
+            .ok_or(QueryError::QExecDdlModelBadDefinition)?;
+        let model = Self::new_restore_with_props(
+            Uuid::new(),
+            source.p_key().into(),
+            source.p_tag(),
+            source
+                .fields()
+                .stseq_ord_kv()
+                .map(|(name, field)| (Box::<str>::from(name.as_str()), field.clone()))
+                .collect(),
+            props,
+        );
+        // prepare txn
+        let txn =
+            gns::model::CreateModelTxn::new(SpaceIDRef::new(space_name, space), model_name, &model);
+        // attempt to initialize driver
+        let mdl_driver = global.initialize_model_driver(
+            space_name,
+            space.get_uuid(),
+            model_name,
+            model.get_uuid(),
+            model.primary_index().partitions(),
+        )?;
+        // commit txn
+        global.state().gns_driver().driver_context(
+            global,
+            |drv| drv.commit_event(txn),
+            || {
+                global.purge_model_driver(
+                    space_name,
+                    space.get_uuid(),
+                    model_name,
+                    model.get_uuid(),
+                )
+            },
+        )?;
+        Ok((model, mdl_driver))
+    }
     pub fn transactional_exec_drop<G: GlobalInstanceLike>(
         global: &G,
         stmt: DropModel,
@@ -355,6 +954,7 @@ impl ModelData {
             .state()
             .namespace()
             .ddl_with_space_mut(&space_name, |space| {
+                global.state().freeze().check(space_name)?;
                 if !space.models().contains(model_name) {
                     if stmt.if_exists {
                         return Ok(Some(false));
@@ -373,6 +973,12 @@ impl ModelData {
                     // nope, we can't drop this
                     return Err(QueryError::QExecDdlNotEmpty);
                 }
+                // the views derived from this model must be dropped first (even with force)
+                if !model.data.views().is_empty() {
+                    return Err(QueryError::QExecDdlNotEmpty);
+                }
+                let view_of: Option<Box<str>> =
+                    model.data.props().view().map(|v| v.source().into());
                 // okay this is looking good for us
                 // prepare txn
                 let txn = gns::model::DropModelTxn::new(ModelIDRef::new(
@@ -391,6 +997,10 @@ impl ModelData {
                     |drv| drv.commit_event(txn),
                     || {},
                 )?;
+                if model.driver().status().is_iffy() {
+                    // this driver had a fault, but it's being purged anyway
+                    global.health().report_removal_of_faulty_source();
+                }
                 // request cleanup
                 global.purge_model_driver(
                     space_name,
@@ -399,6 +1009,13 @@ impl ModelData {
                     model.data().get_uuid(),
                 );
                 // update global state
+                if let Some(source) = view_of {
+                    if let Some(source) =
+                        models_idx.get_mut(&EntityIDRef::new(&space_name, &source))
+                    {
+                        source.data.views.unlink(model_name);
+                    }
+                }
                 let _ = models_idx.remove(&EntityIDRef::new(&space_name, &model_name));
                 let _ = space.models_mut().remove(model_name);
                 if stmt.if_exists {
@@ -546,11 +1163,19 @@ pub static TY_LIST: &str = LUT[13].0;
 pub struct Field {
     layers: VInline<1, Layer>,
     nullable: bool,
+    props: FieldProps,
 }
 
 impl Field {
     pub fn new(layers: VInline<1, Layer>, nullable: bool) -> Self {
-        Self { layers, nullable }
+        Self::new_with_props(layers, nullable, FieldProps::default())
+    }
+    pub fn new_with_props(layers: VInline<1, Layer>, nullable: bool, props: FieldProps) -> Self {
+        Self {
+            layers,
+            nullable,
+            props,
+        }
     }
     pub fn is_nullable(&self) -> bool {
         self.nullable
@@ -558,14 +1183,96 @@ impl Field {
     pub fn layers(&self) -> &[Layer] {
         &self.layers
     }
-    pub fn parse_layers(spec: Vec<LayerSpec>, nullable: bool) -> QueryResult<Self> {
+    /// Returns the type of this field as it would be declared (such as `list { type: string }`)
+    pub fn type_name(&self) -> String {
+        if let Some(decimal) = self.decimal() {
+            return format!(
+                "{} {{ {}: {}, {}: {} }}",
+                Decimal::TYPE,
+                FieldProps::KEY_PRECISION,
+                decimal.precision(),
+                FieldProps::KEY_SCALE,
+                decimal.scale()
+            );
+        }
+        let name = |layer: &Layer| LUT[layer.tag().tag_selector().value_word()].0;
+        let mut layers = self.layers.iter().rev();
+        let innermost = layers.next().map_or("", name).to_owned();
+        layers.fold(innermost, |inner, layer| {
+            format!("{} {{ type: {inner} }}", name(layer))
+        })
+    }
+    pub fn props(&self) -> &FieldProps {
+        &self.props
+    }
+    /// Returns the expression for this field if it is computed
+    pub fn computed(&self) -> Option<&ComputedExpr> {
+        self.props.computed()
+    }
+    pub fn is_computed(&self) -> bool {
+        self.computed().is_some()
+    }
+    /// Returns the definition of the window counter, if this field is one
+    pub fn window(&self) -> Option<&WindowCounter> {
+        self.props.window()
+    }
+    /// Returns the definition of the decimal, if this field is one
+    pub fn decimal(&self) -> Option<&Decimal> {
+        self.props.decimal()
+    }
+    /// Returns the generator for this field's value when an insert leaves it out, if it has one
+    pub fn keygen(&self) -> Option<KeyGen> {
+        self.props.keygen()
+    }
+    /// Returns the value that fills in this field when an insert leaves it out (its literal default, or a newly
+    /// generated key), if it has one
+    pub fn default_value(&self) -> Option<Datacell> {
+        match self.props.literal_default() {
+            Some(default) => Some(default.clone()),
+            None => self.keygen().map(|keygen| keygen.generate()),
+        }
+    }
+    /// Returns true if `sysctl backfill model` fills this field in with its default, in the rows where it is null.
+    /// Only fields that can't be null and have a default are backfilled
+    pub fn backfills(&self) -> bool {
+        (self.props.literal_default().is_some() | self.keygen().is_some()) & !self.nullable
+    }
+    /// Validate a (type checked) value against this field's checks. `name` is the name of this field (which is added
+    /// to the error's detail)
+    pub fn check(&self, name: &str, data: &Datacell) -> QueryResult<()> {
+        self.props
+            .checks()
+            .validate(data)
+            .map_err(|e| e.with_detail("field", name))?;
+        // a decimal can leave its precision when it's added to
+        match (self.decimal(), data.try_sint()) {
+            (Some(decimal), Some(held)) if !decimal.fits(held) => {
+                Err(QueryError::QExecDmlValidationError.with_detail("field", name))
+            }
+            _ => Ok(()),
+        }
+    }
+    pub fn parse_layers(mut spec: Vec<LayerSpec>, nullable: bool) -> QueryResult<Self> {
+        if (spec.len() == 1) && (spec[0].ty.as_str() == WindowCounter::TYPE) {
+            return Self::parse_window_counter(spec.pop().unwrap().props, nullable);
+        }
+        if (spec.len() == 1) && (spec[0].ty.as_str() == Decimal::TYPE) {
+            return Self::parse_decimal(spec.pop().unwrap().props, nullable);
+        }
         let mut layers = spec.into_iter().rev();
         let mut okay = true;
         let mut fin = false;
         let mut layerview = VInline::new();
+        let mut field_props = FieldProps::default();
         while (layers.len() != 0) & okay & !fin {
             let LayerSpec { ty, props } = layers.next().unwrap();
-            okay &= props.is_empty(); // FIXME(@ohsayan): you know what to do here
+            if layerview.is_empty() {
+                // field properties are set on the outermost layer
+                field_props =
+                    FieldProps::try_new(props).ok_or(QueryError::QExecDdlInvalidProperties)?;
+            } else {
+                okay &= props.is_empty();
+            }
             match Layer::get_layer(&ty) {
                 Some(l) => {
                     fin = l.tag.tag_selector() != TagSelector::List;
@@ -576,14 +1283,80 @@ impl Field {
         }
         okay &= fin & (layers.len() == 0);
         if okay {
-            Ok(Self {
+            // a window is only set by declaring a window counter, and generated keys are plain strings
+            let generated_key_okay = field_props.keygen().is_none()
+                | ((layerview.len() == 1)
+                    & (layerview[0].tag().tag_class() == TagClass::Str)
+                    & field_props.computed().is_none()
+                    & field_props.literal_default().is_none());
+            // a literal default is a scalar
+            let default_okay = field_props.literal_default().is_none()
+                | ((layerview.len() == 1) & field_props.computed().is_none());
+            // only numeric scalars can overflow
+            let overflow_okay = field_props.overflow().map_or(true, |policy| {
+                policy.applies_to(layerview[0].tag().tag_class())
+            });
+            if !field_props
+                .checks()
+                .applies_to(layerview[0].tag().tag_class())
+                | field_props.window().is_some()
+                | field_props.decimal().is_some()
+                | !generated_key_okay
+                | !default_okay
+                | !overflow_okay
+            {
+                return Err(QueryError::QExecDdlInvalidProperties);
+            }
+            let field = Self {
                 layers: layerview,
                 nullable,
-            })
+                props: field_props,
+            };
+            // and it has to be a value that an insert could set the field to
+            if let Some(mut default) = field.props.literal_default().cloned() {
+                if !field.vt_data_fpath(&mut default) || field.check("", &default).is_err() {
+                    return Err(QueryError::QExecDdlInvalidProperties);
+                }
+            }
+            Ok(field)
         } else {
             Err(QueryError::QExecDdlInvalidTypeDefinition)
         }
     }
+    /// A window counter is stored as a list of unsigned integers, and only takes the `window` property
+    fn parse_window_counter(mut props: DictGeneric, nullable: bool) -> QueryResult<Self> {
+        props
+            .entry(FieldProps::KEY_WINDOW.into())
+            .or_insert_with(|| {
+                DictEntryGeneric::Data(Datacell::new_uint_default(WindowCounter::DEFAULT_WINDOW))
+            });
+        let props = FieldProps::try_new(props).ok_or(QueryError::QExecDdlInvalidProperties)?;
+        if props.len() != 1 {
+            return Err(QueryError::QExecDdlInvalidProperties);
+        }
+        let mut layers = VInline::new();
+        layers.push(Layer::list());
+        layers.push(Layer::uint64());
+        Ok(Self::new_with_props(layers, nullable, props))
+    }
+    /// A decimal is stored as a signed integer, and only takes the `precision` and `scale` properties
+    fn parse_decimal(mut props: DictGeneric, nullable: bool) -> QueryResult<Self> {
+        for (key, default) in [
+            (FieldProps::KEY_PRECISION, Decimal::DEFAULT_PRECISION),
+            (FieldProps::KEY_SCALE, Decimal::DEFAULT_SCALE),
+        ] {
+            props
+                .entry(key.into())
+                .or_insert_with(|| DictEntryGeneric::Data(Datacell::new_uint_default(default)));
+        }
+        let props = FieldProps::try_new(props).ok_or(QueryError::QExecDdlInvalidProperties)?;
+        if props.len() != 2 {
+            return Err(QueryError::QExecDdlInvalidProperties);
+        }
+        let mut layers = VInline::new();
+        layers.push(Layer::sint64());
+        Ok(Self::new_with_props(layers, nullable, props))
+    }
     #[inline(always)]
     fn compute_index(&self, dc: &Datacell) -> usize {
         if {
@@ -596,6 +1369,41 @@ impl Field {
             dc.kind().value_word()
         }
     }
+    /// Convert a numeric value of another kind into this field's kind, if it fits without losing any information (see
+    /// [`props::NumericCoercion::Lenient`]). Any other value is left as is (to be rejected by validation, if it
+    /// doesn't match). Only scalar fields are coerced
+    pub fn coerce_numeric(&self, data: &mut Datacell) {
+        if (self.layers.len() != 1) | data.is_null() {
+            return;
+        }
+        let value = match data.kind() {
+            TagClass::UnsignedInt => data.try_uint().map(Numeric::UInt),
+            TagClass::SignedInt => data.try_sint().map(Numeric::SInt),
+            TagClass::Float => data.try_float().map(Numeric::Float),
+            _ => None,
+        };
+        match value.and_then(|v| v.coerce(self.layers[0].tag.tag_class())) {
+            Some(Numeric::UInt(u)) => *data = Datacell::new_uint_default(u),
+            Some(Numeric::SInt(s)) => *data = Datacell::new_sint_default(s),
+            Some(Numeric::Float(f)) => *data = Datacell::new_float_default(f),
+            None => {}
+        }
+    }
+    /// Same as [`Self::coerce_numeric`], but for a literal (the right hand side of an assignment)
+    pub fn coerce_numeric_lit<'a>(&self, lit: Lit<'a>) -> Lit<'a> {
+        let value = match lit.kind().tag_class() {
+            TagClass::UnsignedInt => lit.try_uint().map(Numeric::UInt),
+            TagClass::SignedInt => lit.try_sint().map(Numeric::SInt),
+            TagClass::Float => lit.try_float().map(Numeric::Float),
+            _ => None,
+        };
+        match value.and_then(|v| v.coerce(self.layers[0].tag.tag_class())) {
+            Some(Numeric::UInt(u)) => Lit::new_uint(u),
+            Some(Numeric::SInt(s)) => Lit::new_sint(s),
+            Some(Numeric::Float(f)) => Lit::new_float(f),
+            None => lit,
+        }
+    }
     pub fn vt_data_fpath(&self, data: &mut Datacell) -> bool {
         if (self.layers.len() == 1) | (data.is_null()) {
             layertrace("fpath");
@@ -634,6 +1442,107 @@ impl Field {
             _ => false,
         }
     }
+    /// If the value is of the wrong type for this field (as opposed to, say, being out of the field's bounds), returns
+    /// the type that was expected and the one that was received. For lists, this is the first element that doesn't
+    /// match
+    pub fn type_mismatch(&self, data: &Datacell) -> Option<(&'static str, &'static str)> {
+        if data.is_null() {
+            return None;
+        }
+        Self::r_type_mismatch(self.layers(), data)
+    }
+    fn r_type_mismatch(layers: &[Layer], data: &Datacell) -> Option<(&'static str, &'static str)> {
+        let layer = layers[0];
+        match (layer.tag().tag_class(), data.kind()) {
+            (TagClass::List, TagClass::List) => data
+                .try_list()?
+                .read()
+                .iter()
+                .find_map(|item| Self::r_type_mismatch(&layers[1..], item)),
+            (tag_a, tag_b) if tag_a == tag_b => None,
+            _ => Some((tag_name(layer.tag()), tag_name(data.tag()))),
+        }
+    }
+    /// Returns false if this (validated) value has a float that a `float32` layer would round (see
+    /// [`props::NumericCoercion::Exact`])
+    pub fn is_exact(&self, data: &Datacell) -> bool {
+        data.is_null() || Self::r_is_exact(self.layers(), data)
+    }
+    fn r_is_exact(layers: &[Layer], data: &Datacell) -> bool {
+        match (layers[0].tag().tag_selector(), data.kind()) {
+            (TagSelector::Float32, TagClass::Float) => {
+                data.try_float().map_or(true, is_exact_float32)
+            }
+            (TagSelector::List, TagClass::List) => data.try_list().map_or(true, |list| {
+                list.read()
+                    .iter()
+                    .all(|item| Self::r_is_exact(&layers[1..], item))
+            }),
+            _ => true,
+        }
+    }
+    /// Same as [`Self::is_exact`], but for a literal (the right hand side of an assignment)
+    pub fn is_exact_lit(&self, lit: &Lit) -> bool {
+        // the last layer is the one that an element added to a list is checked against
+        match (
+            self.layers[self.layers.len() - 1].tag().tag_selector(),
+            lit.try_float(),
+        ) {
+            (TagSelector::Float32, Some(f)) => is_exact_float32(f),
+            _ => true,
+        }
+    }
+}
+
+/// The name of the type with this tag (as used in a model's definition)
+pub fn tag_name(tag: FullTag) -> &'static str {
+    LUT[tag.tag_selector().value_word()].0
+}
+
+fn is_exact_float32(f: f64) -> bool {
+    f.is_nan() | (f as f32 as f64 == f)
+}
+
+/// A numeric value that's being coerced into a field of another kind (or cast into another type by an expression)
+#[derive(Debug, Clone, Copy)]
+pub enum Numeric {
+    UInt(u64),
+    SInt(i64),
+    Float(f64),
+}
+
+impl Numeric {
+    /// integers up to this magnitude are exactly representable as a float64
+    const MAX_EXACT_FLOAT: u64 = 1 << f64::MANTISSA_DIGITS;
+    pub const fn class(&self) -> TagClass {
+        match self {
+            Self::UInt(_) => TagClass::UnsignedInt,
+            Self::SInt(_) => TagClass::SignedInt,
+            Self::Float(_) => TagClass::Float,
+        }
+    }
+    /// Convert into the given class, unless that loses information. The field's own bounds (for example, those of a
+    /// `sint8`) are checked later by validation
+    pub fn coerce(self, to: TagClass) -> Option<Self> {
+        match (self, to) {
+            (Self::UInt(u), TagClass::SignedInt) => i64::try_from(u).ok().map(Self::SInt),
+            (Self::UInt(u), TagClass::Float) => {
+                (u <= Self::MAX_EXACT_FLOAT).then_some(Self::Float(u as f64))
+            }
+            (Self::SInt(s), TagClass::UnsignedInt) => u64::try_from(s).ok().map(Self::UInt),
+            (Self::SInt(s), TagClass::Float) => {
+                (s.unsigned_abs() <= Self::MAX_EXACT_FLOAT).then_some(Self::Float(s as f64))
+            }
+            // NaN and the infinities have a NaN fractional part
+            (Self::Float(f), TagClass::UnsignedInt) if f.fract() == 0.0 => {
+                ((f >= 0.0) & (f < u64::MAX as f64)).then_some(Self::UInt(f as u64))
+            }
+            (Self::Float(f), TagClass::SignedInt) if f.fract() == 0.0 => {
+                ((f >= i64::MIN as f64) & (f < i64::MAX as f64)).then_some(Self::SInt(f as i64))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -0,0 +1,145 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    overflow
+    ---
+    a model with `overflow` enabled can hold more rows than fit in memory. whenever the server uses more memory than
+    the high watermark, the general executor's maintenance moves the values of the model's cold rows to its overflow
+    file (see [`crate::engine::storage::overflow`]) until roughly enough was moved to get back under the low
+    watermark. a row is read back the next time that it's accessed, so nothing changes for queries other than that
+    the first access of a cold row has to go to disk.
+
+    cold rows are picked with a CLOCK: every sweep clears the referenced bit of every row, and a row is only moved
+    if it wasn't accessed since the previous sweep (that is, for at least one maintenance interval). the keys (and any
+    secondary indexes) always stay in memory. rows are still loaded into memory on startup, and are moved out again by
+    the first maintenance run that finds the server over the watermark
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{
+        core::index::PrimaryIndexKey, fractal::jobs::CancelToken, storage::overflow::OverflowStore,
+        sync,
+    },
+    parking_lot::Mutex,
+    std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// The most rows that are moved by a single maintenance run
+const SPILL_MAX: usize = 1 << 16;
+
+#[derive(Debug, Default)]
+/// The overflow state of a model (see the module docs)
+pub struct ModelOverflow {
+    store: Mutex<Option<Arc<OverflowStore>>>,
+    spilled: AtomicU64,
+    sweeps: AtomicU64,
+}
+
+impl ModelOverflow {
+    /// Sweep the rows of the model, moving the values of cold rows to the overflow store until about `target` bytes
+    /// were moved (nothing is moved if `target` is 0). Returns the number of rows that were moved
+    pub fn maintain(&self, model: &ModelData, target: u64, token: &CancelToken) -> usize {
+        let g = sync::atm::cpin();
+        let idx = model.primary_index();
+        // collect the cold rows first since we can't write them out while the scan holds the index latch
+        let candidates: Vec<PrimaryIndexKey> = {
+            let _latch = idx.acquire_exclusive();
+            let mut candidates = vec![];
+            for row in idx.iter(&g) {
+                let cold = !row.overflow_sweep() & !row.is_spilled();
+                if cold & (target != 0) & (candidates.len() < SPILL_MAX) {
+                    candidates.push(row.d_key().clone());
+                }
+            }
+            candidates
+        };
+        self.sweeps.fetch_add(1, Ordering::Relaxed);
+        if candidates.is_empty() {
+            return 0;
+        }
+        let Some(store) = self.store(model) else {
+            return 0;
+        };
+        if store.failed_reads() != 0 {
+            // rows couldn't be read back from the store, so don't put any more in it
+            return 0;
+        }
+        let (mut moved, mut moved_bytes) = (0, 0);
+        for key in candidates {
+            if token.is_cancelled() | (moved_bytes >= target) {
+                break;
+            }
+            // the row might have been accessed (or removed) since the scan, in which case it's left alone
+            if let Some(size) = idx
+                .select_key(&key, &g)
+                .and_then(|row| row.overflow_spill(&store))
+            {
+                moved += 1;
+                moved_bytes += size;
+            }
+        }
+        self.spilled.fetch_add(moved as u64, Ordering::Relaxed);
+        moved
+    }
+    /// Returns the overflow store, creating it if this is the first time that rows are moved
+    fn store(&self, model: &ModelData) -> Option<Arc<OverflowStore>> {
+        let mut store = self.store.lock();
+        if store.is_none() {
+            match OverflowStore::create(model.get_uuid()) {
+                Ok(new) => *store = Some(new),
+                Err(e) => {
+                    warn!("overflow: failed to create store: {e}. will try again later");
+                    return None;
+                }
+            }
+        }
+        store.clone()
+    }
+    /// Returns the state of the overflow store as a JSON object: the rows (and bytes) currently in it, the size of the
+    /// file, the number of rows that couldn't be read back (no more rows are moved once one couldn't be) and the
+    /// number of sweeps and moved rows so far
+    pub fn describe(&self) -> String {
+        let (rows, bytes, file, failed) =
+            self.store.lock().as_ref().map_or((0, 0, 0, 0), |store| {
+                (
+                    store.live_slots(),
+                    store.live_bytes(),
+                    store.file_size(),
+                    store.failed_reads(),
+                )
+            });
+        format!(
+            "{{\"rows\":{rows},\"bytes\":{bytes},\"file_size\":{file},\"failed_reads\":{failed},\"sweeps\":{},\"spilled\":{}}}",
+            self.sweeps.load(Ordering::Relaxed),
+            self.spilled.load(Ordering::Relaxed)
+        )
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    row predicates
+    ---
+    a predicate is a conjunction of comparisons against the fields of a row, for example `age >= 18 and status =
+    'active'`. views use one to pick the rows of their source model, and partial indexes use one to pick the rows that
+    they hold. it's stored as a flat list of `field, operator, value` triples (see `Predicate::from_raw`)
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{
+        core::index::{DcFieldIndex, PrimaryIndexKey},
+        data::{
+            cell::{Datacell, VirtualDatacell},
+            tag::TagClass,
+        },
+        error::{QueryError, QueryResult},
+        idx::STIndex,
+        ql::{dml::RelationalExpr, lex::Ident},
+    },
+    std::{cmp::Ordering, collections::HashMap},
+};
+
+/// A comparison operator used in a predicate
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum PredicateOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl PredicateOp {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "=" => Some(Self::Eq),
+            "!=" => Some(Self::Ne),
+            ">" => Some(Self::Gt),
+            ">=" => Some(Self::Ge),
+            "<" => Some(Self::Lt),
+            "<=" => Some(Self::Le),
+            _ => None,
+        }
+    }
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+        }
+    }
+    fn check(&self, ord: Ordering) -> bool {
+        match self {
+            Self::Eq => ord.is_eq(),
+            Self::Ne => ord.is_ne(),
+            Self::Gt => ord.is_gt(),
+            Self::Ge => ord.is_ge(),
+            Self::Lt => ord.is_lt(),
+            Self::Le => ord.is_le(),
+        }
+    }
+}
+
+/// A comparison that a field of a row must satisfy, for example `age >= 18`
+#[derive(Debug, PartialEq, Clone)]
+struct PredicateClause {
+    field: Box<str>,
+    op: PredicateOp,
+    value: Datacell,
+}
+
+impl PredicateClause {
+    /// Compare a value against this clause. A null (or a value of another type) never matches
+    fn eval(&self, dc: &Datacell) -> bool {
+        if dc.is_null() {
+            return false;
+        }
+        let int = |dc: &Datacell| {
+            dc.try_uint()
+                .map(|u| u as i128)
+                .or_else(|| dc.try_sint().map(|s| s as i128))
+        };
+        let v = &self.value;
+        let ord = match dc.kind() {
+            TagClass::Bool => dc.try_bool().zip(v.try_bool()).map(|(a, b)| a.cmp(&b)),
+            TagClass::UnsignedInt | TagClass::SignedInt => {
+                int(dc).zip(int(v)).map(|(a, b)| a.cmp(&b))
+            }
+            TagClass::Float => dc
+                .try_float()
+                .zip(v.try_float())
+                .and_then(|(a, b)| a.partial_cmp(&b)),
+            TagClass::Bin => dc.try_bin().zip(v.try_bin()).map(|(a, b)| a.cmp(b)),
+            TagClass::Str => dc.try_str().zip(v.try_str()).map(|(a, b)| a.cmp(b)),
+            TagClass::List => None,
+        };
+        ord.map_or(false, |ord| self.op.check(ord))
+    }
+}
+
+/// The clauses that a row must all satisfy (see the module docs). An empty predicate matches every row
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Predicate {
+    clauses: Vec<PredicateClause>,
+}
+
+impl Predicate {
+    /// Decode a predicate from a flat list of `field, operator, value` triples. Returns [`None`] if the list is
+    /// malformed (or a value is null or a list)
+    pub fn from_raw(raw: &Datacell) -> Option<Self> {
+        let raw = raw.try_list()?.read();
+        if raw.len() % 3 != 0 {
+            return None;
+        }
+        let mut clauses = Vec::with_capacity(raw.len() / 3);
+        for clause in raw.chunks(3) {
+            if clause[2].is_null() | (clause[2].kind() == TagClass::List) {
+                return None;
+            }
+            clauses.push(PredicateClause {
+                field: clause[0].try_str()?.into(),
+                op: PredicateOp::from_name(clause[1].try_str()?)?,
+                value: clause[2].clone(),
+            });
+        }
+        Some(Self { clauses })
+    }
+    /// Encode the predicate as a flat list of `field, operator, value` triples (see [`Self::from_raw`])
+    pub fn to_raw(&self) -> Datacell {
+        let mut raw = Vec::with_capacity(self.clauses.len() * 3);
+        for clause in self.clauses.iter() {
+            raw.push(Datacell::new_str(clause.field.as_ref().into()));
+            raw.push(Datacell::new_str(clause.op.name().into()));
+            raw.push(clause.value.clone());
+        }
+        Datacell::new_list(raw)
+    }
+    /// Encode the clauses of a where clause on the given model as a flat list of triples. The list can still fail to
+    /// decode (with [`Self::from_raw`]) if it has a clause that can't be stored, such as a `matches` clause
+    pub fn raw_from_where(
+        mdl: &ModelData,
+        clauses: &HashMap<Ident, RelationalExpr>,
+    ) -> QueryResult<Vec<Datacell>> {
+        let mut raw = Vec::new();
+        for (field, expr) in clauses.iter() {
+            if !mdl.fields().st_contains(field.as_str()) {
+                return Err(QueryError::QExecUnknownField);
+            }
+            if expr.lhs_expr().is_some() {
+                // functions can't be stored in a predicate
+                return Err(QueryError::QExecDdlModelBadDefinition);
+            }
+            for (op, value) in expr.comparisons() {
+                raw.push(Datacell::new_str(field.as_str().into()));
+                raw.push(Datacell::new_str(op.into()));
+                raw.push(Datacell::from(value));
+            }
+        }
+        Ok(raw)
+    }
+    /// Resolve a predicate from a where clause on the given model (see [`Self::raw_from_where`])
+    pub fn from_where(
+        mdl: &ModelData,
+        clauses: &HashMap<Ident, RelationalExpr>,
+    ) -> QueryResult<Self> {
+        Self::from_raw(&Datacell::new_list(Self::raw_from_where(mdl, clauses)?))
+            .ok_or(QueryError::QExecDdlModelBadDefinition)
+    }
+    /// Returns the fields that the predicate uses
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.clauses.iter().map(|clause| clause.field.as_ref())
+    }
+    /// Returns true if the row of the given model with the given key and fields satisfies the predicate
+    pub fn matches(&self, mdl: &ModelData, key: &PrimaryIndexKey, fields: &DcFieldIndex) -> bool {
+        self.clauses.iter().all(|clause| {
+            if clause.field.as_ref() == mdl.p_key() {
+                clause.eval(&VirtualDatacell::new_pk(key, mdl.p_tag()))
+            } else {
+                fields
+                    .st_get(clause.field.as_ref())
+                    .map_or(false, |dc| clause.eval(dc))
+            }
+        })
+    }
+    /// Returns true if every row that satisfies the where clause satisfies the predicate too. This is conservative: a
+    /// clause of the predicate is only implied by an equality on its field with a value that satisfies it, or by the
+    /// very same comparison
+    pub fn implied_by(&self, clauses: &HashMap<Ident, RelationalExpr>) -> bool {
+        self.clauses.iter().all(|clause| {
+            let Some(expr) = clauses.get(clause.field.as_bytes()) else {
+                return false;
+            };
+            expr.lhs_expr().is_none()
+                && expr.comparisons().any(|(op, value)| {
+                    let value = Datacell::from(value);
+                    match PredicateOp::from_name(op) {
+                        Some(PredicateOp::Eq) => clause.eval(&value),
+                        Some(op) => (op == clause.op) & (value == clause.value),
+                        None => false,
+                    }
+                })
+        })
+    }
+}
@@ -0,0 +1,836 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{
+        check::FieldChecks, computed::ComputedExpr, decimal::Decimal, maintained::MaintainedDef,
+        upstream::UpstreamWrites, view::ViewDef, window::WindowCounter,
+    },
+    crate::engine::{
+        core::{dml::keygen::KeyGen, index::PrimaryIndexKind},
+        data::{cell::Datacell, tag::TagClass, DictEntryGeneric, DictGeneric},
+        idx::meta::hash::HasherKind,
+        storage::encode_str,
+    },
+};
+
+/// Validated properties of a model (set using `with { ... }` in `create model`)
+#[derive(Debug, PartialEq)]
+pub struct ModelProps {
+    raw: DictGeneric,
+    index: PrimaryIndexKind,
+    hasher: HasherKind,
+    partitions: usize,
+    soft_delete: bool,
+    tombstone_retention: u64,
+    history: bool,
+    history_retention: u64,
+    histograms: bool,
+    result_cache: bool,
+    result_cache_ttl: u64,
+    analytic: bool,
+    maintain: Vec<(Box<str>, MaintainedDef)>,
+    coercion: NumericCoercion,
+    view: Option<ViewDef>,
+    upstream: bool,
+    upstream_ttl: u64,
+    upstream_writes: UpstreamWrites,
+    flush_every_n_deltas: Option<usize>,
+    flush_every_ms: Option<u64>,
+    durability: Durability,
+    overflow: bool,
+}
+
+impl Default for ModelProps {
+    fn default() -> Self {
+        Self {
+            raw: DictGeneric::new(),
+            index: PrimaryIndexKind::default(),
+            hasher: HasherKind::default(),
+            partitions: 1,
+            soft_delete: false,
+            tombstone_retention: Self::DEFAULT_TOMBSTONE_RETENTION,
+            history: false,
+            history_retention: Self::DEFAULT_HISTORY_RETENTION,
+            histograms: false,
+            result_cache: false,
+            result_cache_ttl: Self::DEFAULT_RESULT_CACHE_TTL,
+            analytic: false,
+            maintain: Vec::new(),
+            coercion: NumericCoercion::Strict,
+            view: None,
+            upstream: false,
+            upstream_ttl: Self::DEFAULT_UPSTREAM_TTL,
+            upstream_writes: UpstreamWrites::Through,
+            flush_every_n_deltas: None,
+            flush_every_ms: None,
+            durability: Durability::Relaxed,
+            overflow: false,
+        }
+    }
+}
+
+impl ModelProps {
+    /// the data structure backing the primary index
+    pub const KEY_INDEX: &'static str = "primary_index";
+    /// the hash function used by the primary index
+    pub const KEY_HASHER: &'static str = "hasher";
+    /// the number of partitions that the rows are spread across (by the hash of their primary key)
+    pub const KEY_PARTITIONS: &'static str = "partitions";
+    /// the maximum number of partitions (each partition has its own batch file)
+    pub const MAX_PARTITIONS: u64 = 64;
+    /// if set, deleted rows are only marked with a tombstone (and are purged once the retention period is over)
+    pub const KEY_SOFT_DELETE: &'static str = "soft_delete";
+    /// the number of seconds for which soft deleted rows are retained
+    pub const KEY_TOMBSTONE_RETENTION: &'static str = "tombstone_retention";
+    /// tombstones are retained for a week, unless set otherwise
+    pub const DEFAULT_TOMBSTONE_RETENTION: u64 = 7 * 24 * 60 * 60;
+    /// if set, prior versions of rows are retained and can be queried using `select ... as of <timestamp>`
+    pub const KEY_HISTORY: &'static str = "history";
+    /// the number of seconds for which prior versions of rows are retained
+    pub const KEY_HISTORY_RETENTION: &'static str = "history_retention";
+    /// prior versions are retained for a day, unless set otherwise
+    pub const DEFAULT_HISTORY_RETENTION: u64 = 24 * 60 * 60;
+    /// if set, histograms are kept for the numeric fields (see [`super::stats`])
+    pub const KEY_HISTOGRAMS: &'static str = "histograms";
+    /// if set, the responses of reads are cached (see [`super::result_cache`])
+    pub const KEY_RESULT_CACHE: &'static str = "result_cache";
+    /// the number of seconds for which a cached response is served
+    pub const KEY_RESULT_CACHE_TTL: &'static str = "result_cache_ttl";
+    /// cached responses are served for 30 seconds, unless set otherwise
+    pub const DEFAULT_RESULT_CACHE_TTL: u64 = 30;
+    /// if set, the values of the rows are also kept column-major in memory (see [`super::columnar`])
+    pub const KEY_ANALYTIC: &'static str = "analytic";
+    /// the aggregates that are maintained on every change, by name (see [`super::maintained`])
+    pub const KEY_MAINTAIN: &'static str = "maintain";
+    /// how numeric values are stored into numeric fields of a different kind (see [`NumericCoercion`])
+    pub const KEY_COERCION: &'static str = "coercion";
+    /// the model that a view is derived from (set by `create view`)
+    pub const KEY_VIEW_OF: &'static str = "view_of";
+    /// the clauses that the rows of the source model must satisfy to be in a view (set by `create view`)
+    pub const KEY_VIEW_FILTER: &'static str = "view_filter";
+    /// if set, the model caches the model with the same name on the upstream instance (see [`super::upstream`])
+    pub const KEY_UPSTREAM: &'static str = "upstream";
+    /// the number of seconds for which a row fetched from the upstream is served before it's fetched again
+    pub const KEY_UPSTREAM_TTL: &'static str = "upstream_ttl";
+    /// rows are fetched again after a minute, unless set otherwise
+    pub const DEFAULT_UPSTREAM_TTL: u64 = 60;
+    /// how writes are forwarded to the upstream (see [`UpstreamWrites`])
+    pub const KEY_UPSTREAM_WRITES: &'static str = "upstream_writes";
+    /// if set, the changes are written to disk as soon as this many are pending
+    pub const KEY_FLUSH_EVERY_N_DELTAS: &'static str = "flush_every_n_deltas";
+    /// if set, pending changes are written to disk at least this often (in milliseconds)
+    pub const KEY_FLUSH_EVERY_MS: &'static str = "flush_every_ms";
+    /// the smallest flush interval (the flusher checks for due models this often)
+    pub const MIN_FLUSH_EVERY_MS: u64 = 50;
+    /// how soon changes are written to disk (see [`Durability`])
+    pub const KEY_DURABILITY: &'static str = "durability";
+    /// if set, cold rows are moved to disk when the server runs low on memory (see [`super::overflow`])
+    pub const KEY_OVERFLOW: &'static str = "overflow";
+    /// Validate and resolve the given (flattened) properties. Returns [`None`] if any property is unknown or has an
+    /// illegal value
+    pub fn try_new(raw: DictGeneric) -> Option<Self> {
+        let mut slf = Self::default();
+        for (key, value) in raw.iter() {
+            match (key.as_ref(), value) {
+                (Self::KEY_INDEX, DictEntryGeneric::Data(d)) => {
+                    slf.index = PrimaryIndexKind::from_name(d.try_str()?)?;
+                }
+                (Self::KEY_HASHER, DictEntryGeneric::Data(d)) => {
+                    slf.hasher = HasherKind::from_name(d.try_str()?)?;
+                }
+                (Self::KEY_PARTITIONS, DictEntryGeneric::Data(d)) => {
+                    let partitions = d.try_uint()?;
+                    if partitions == 0 || partitions > Self::MAX_PARTITIONS {
+                        return None;
+                    }
+                    slf.partitions = partitions as usize;
+                }
+                (Self::KEY_SOFT_DELETE, DictEntryGeneric::Data(d)) => {
+                    slf.soft_delete = d.try_bool()?;
+                }
+                (Self::KEY_TOMBSTONE_RETENTION, DictEntryGeneric::Data(d)) => {
+                    slf.tombstone_retention = d.try_uint()?;
+                }
+                (Self::KEY_HISTORY, DictEntryGeneric::Data(d)) => {
+                    slf.history = d.try_bool()?;
+                }
+                (Self::KEY_HISTORY_RETENTION, DictEntryGeneric::Data(d)) => {
+                    slf.history_retention = d.try_uint()?;
+                }
+                (Self::KEY_HISTOGRAMS, DictEntryGeneric::Data(d)) => {
+                    slf.histograms = d.try_bool()?;
+                }
+                (Self::KEY_RESULT_CACHE, DictEntryGeneric::Data(d)) => {
+                    slf.result_cache = d.try_bool()?;
+                }
+                (Self::KEY_RESULT_CACHE_TTL, DictEntryGeneric::Data(d)) => {
+                    let ttl = d.try_uint()?;
+                    if ttl == 0 {
+                        return None;
+                    }
+                    slf.result_cache_ttl = ttl;
+                }
+                (Self::KEY_ANALYTIC, DictEntryGeneric::Data(d)) => {
+                    slf.analytic = d.try_bool()?;
+                }
+                (Self::KEY_MAINTAIN, DictEntryGeneric::Map(defs)) => {
+                    for (name, def) in defs.iter() {
+                        let DictEntryGeneric::Data(def) = def else {
+                            return None;
+                        };
+                        let def = MaintainedDef::parse(def.try_str()?)?;
+                        // the same aggregate is only maintained once
+                        if slf.maintain.iter().any(|(_, d)| *d == def) {
+                            return None;
+                        }
+                        slf.maintain.push((name.clone(), def));
+                    }
+                    if slf.maintain.is_empty() {
+                        return None;
+                    }
+                    slf.maintain.sort_by(|(a, _), (b, _)| a.cmp(b));
+                }
+                (Self::KEY_COERCION, DictEntryGeneric::Data(d)) => {
+                    slf.coercion = NumericCoercion::from_name(d.try_str()?)?;
+                }
+                (Self::KEY_VIEW_OF, DictEntryGeneric::Data(d)) => {
+                    let filter = match raw.get(Self::KEY_VIEW_FILTER) {
+                        Some(DictEntryGeneric::Data(filter)) => Some(filter),
+                        Some(DictEntryGeneric::Map(_)) => return None,
+                        None => None,
+                    };
+                    slf.view = Some(ViewDef::new(d.try_str()?, filter)?);
+                }
+                (Self::KEY_VIEW_FILTER, DictEntryGeneric::Data(_)) => {}
+                (Self::KEY_UPSTREAM, DictEntryGeneric::Data(d)) => {
+                    slf.upstream = d.try_bool()?;
+                }
+                (Self::KEY_UPSTREAM_TTL, DictEntryGeneric::Data(d)) => {
+                    let ttl = d.try_uint()?;
+                    if ttl == 0 {
+                        return None;
+                    }
+                    slf.upstream_ttl = ttl;
+                }
+                (Self::KEY_UPSTREAM_WRITES, DictEntryGeneric::Data(d)) => {
+                    slf.upstream_writes = UpstreamWrites::from_name(d.try_str()?)?;
+                }
+                (Self::KEY_FLUSH_EVERY_N_DELTAS, DictEntryGeneric::Data(d)) => {
+                    let n = d.try_uint()?;
+                    if n == 0 {
+                        return None;
+                    }
+                    slf.flush_every_n_deltas = Some(n as usize);
+                }
+                (Self::KEY_FLUSH_EVERY_MS, DictEntryGeneric::Data(d)) => {
+                    let ms = d.try_uint()?;
+                    if ms < Self::MIN_FLUSH_EVERY_MS {
+                        return None;
+                    }
+                    slf.flush_every_ms = Some(ms);
+                }
+                (Self::KEY_DURABILITY, DictEntryGeneric::Data(d)) => {
+                    slf.durability = Durability::from_name(d.try_str()?)?;
+                }
+                (Self::KEY_OVERFLOW, DictEntryGeneric::Data(d)) => {
+                    slf.overflow = d.try_bool()?;
+                }
+                _ => return None,
+            }
+        }
+        if slf.view.is_none() && raw.contains_key(Self::KEY_VIEW_FILTER) {
+            return None;
+        }
+        // a hasher only makes sense for a hash index
+        if slf.index != PrimaryIndexKind::Hash && raw.contains_key(Self::KEY_HASHER) {
+            return None;
+        }
+        // an ordered index can't be partitioned by hash without losing its order
+        if slf.index != PrimaryIndexKind::Hash && slf.partitions != 1 {
+            return None;
+        }
+        // similarly, a retention period only makes sense with soft deletes
+        if !slf.soft_delete && raw.contains_key(Self::KEY_TOMBSTONE_RETENTION) {
+            return None;
+        }
+        if !slf.history && raw.contains_key(Self::KEY_HISTORY_RETENTION) {
+            return None;
+        }
+        if !slf.result_cache && raw.contains_key(Self::KEY_RESULT_CACHE_TTL) {
+            return None;
+        }
+        if !slf.upstream
+            && (raw.contains_key(Self::KEY_UPSTREAM_TTL)
+                | raw.contains_key(Self::KEY_UPSTREAM_WRITES))
+        {
+            return None;
+        }
+        // a cached row is replaced outright whenever it's fetched, which tombstones, history and views can't follow
+        if slf.upstream && (slf.soft_delete | slf.history | slf.view.is_some()) {
+            return None;
+        }
+        // every change to a strict model is written right away, so there's nothing left to trigger
+        if (slf.durability == Durability::Strict)
+            & (slf.flush_every_n_deltas.is_some() | slf.flush_every_ms.is_some())
+        {
+            return None;
+        }
+        // the column-major copy and the histograms would keep (or read back) every row
+        if slf.overflow & (slf.analytic | slf.histograms) {
+            return None;
+        }
+        slf.raw = raw;
+        Some(slf)
+    }
+    pub fn raw(&self) -> &DictGeneric {
+        &self.raw
+    }
+    pub fn index(&self) -> PrimaryIndexKind {
+        self.index
+    }
+    pub fn hasher(&self) -> HasherKind {
+        self.hasher
+    }
+    /// Returns the number of partitions. This is fixed once the model is created
+    pub fn partitions(&self) -> usize {
+        self.partitions
+    }
+    pub fn soft_delete(&self) -> bool {
+        self.soft_delete
+    }
+    /// Returns the number of seconds for which soft deleted rows are retained
+    pub fn tombstone_retention(&self) -> u64 {
+        self.tombstone_retention
+    }
+    /// Returns true if prior versions of rows are retained
+    pub fn history(&self) -> bool {
+        self.history
+    }
+    /// Returns the number of seconds for which prior versions of rows are retained
+    pub fn history_retention(&self) -> u64 {
+        self.history_retention
+    }
+    /// Returns true if histograms are kept for the numeric fields
+    pub fn histograms(&self) -> bool {
+        self.histograms
+    }
+    /// Returns true if the responses of reads are cached
+    pub fn result_cache(&self) -> bool {
+        self.result_cache
+    }
+    /// Returns the number of seconds for which a cached response is served
+    pub fn result_cache_ttl(&self) -> u64 {
+        self.result_cache_ttl
+    }
+    /// Returns true if the values of the rows are also kept column-major
+    pub fn analytic(&self) -> bool {
+        self.analytic
+    }
+    /// Returns the maintained aggregates (ordered by name)
+    pub fn maintain(&self) -> &[(Box<str>, MaintainedDef)] {
+        &self.maintain
+    }
+    /// Returns how numeric values are coerced into the numeric fields of this model
+    pub fn coercion(&self) -> NumericCoercion {
+        self.coercion
+    }
+    /// Returns the definition of the view, if this model is a view
+    pub fn view(&self) -> Option<&ViewDef> {
+        self.view.as_ref()
+    }
+    /// Returns true if this model caches the upstream
+    pub fn upstream(&self) -> bool {
+        self.upstream
+    }
+    /// Returns the number of seconds for which a row fetched from the upstream is served
+    pub fn upstream_ttl(&self) -> u64 {
+        self.upstream_ttl
+    }
+    /// Returns how writes are forwarded to the upstream
+    pub fn upstream_writes(&self) -> UpstreamWrites {
+        self.upstream_writes
+    }
+    /// Returns the number of pending changes that are written to disk right away, if set
+    pub fn flush_every_n_deltas(&self) -> Option<usize> {
+        self.flush_every_n_deltas
+    }
+    /// Returns the longest time (in milliseconds) that changes are left pending, if set
+    pub fn flush_every_ms(&self) -> Option<u64> {
+        self.flush_every_ms
+    }
+    pub fn durability(&self) -> Durability {
+        self.durability
+    }
+    /// Returns true if cold rows are moved to disk when the server runs low on memory
+    pub fn overflow(&self) -> bool {
+        self.overflow
+    }
+    /// Returns the resolved properties as a JSON object
+    pub fn describe(&self) -> String {
+        let mut ret = self.describe_index();
+        let mut extend = |enabled, key, retention_key, retention| {
+            if enabled {
+                ret.pop();
+                ret.push_str(&format!(
+                    ",\"{}\":true,\"{}\":{}}}",
+                    key, retention_key, retention
+                ));
+            }
+        };
+        extend(
+            self.soft_delete,
+            Self::KEY_SOFT_DELETE,
+            Self::KEY_TOMBSTONE_RETENTION,
+            self.tombstone_retention,
+        );
+        extend(
+            self.history,
+            Self::KEY_HISTORY,
+            Self::KEY_HISTORY_RETENTION,
+            self.history_retention,
+        );
+        extend(
+            self.result_cache,
+            Self::KEY_RESULT_CACHE,
+            Self::KEY_RESULT_CACHE_TTL,
+            self.result_cache_ttl,
+        );
+        if self.histograms {
+            ret.pop();
+            ret.push_str(&format!(",\"{}\":true}}", Self::KEY_HISTOGRAMS));
+        }
+        if self.analytic {
+            ret.pop();
+            ret.push_str(&format!(",\"{}\":true}}", Self::KEY_ANALYTIC));
+        }
+        if !self.maintain.is_empty() {
+            ret.pop();
+            ret.push_str(&format!(",\"{}\":{{", Self::KEY_MAINTAIN));
+            let defs: Vec<String> = self
+                .maintain
+                .iter()
+                .map(|(name, def)| format!("\"{}\":\"{}\"", name, def.describe()))
+                .collect();
+            ret.push_str(&defs.join(","));
+            ret.push_str("}}");
+        }
+        if self.coercion != NumericCoercion::Strict {
+            ret.pop();
+            ret.push_str(&format!(
+                ",\"{}\":\"{}\"}}",
+                Self::KEY_COERCION,
+                self.coercion.name()
+            ));
+        }
+        if let Some(view) = self.view.as_ref() {
+            ret.pop();
+            ret.push_str(&format!(
+                ",\"{}\":\"{}\"}}",
+                Self::KEY_VIEW_OF,
+                view.source()
+            ));
+        }
+        if self.upstream {
+            ret.pop();
+            ret.push_str(&format!(
+                ",\"{}\":true,\"{}\":{},\"{}\":\"{}\"}}",
+                Self::KEY_UPSTREAM,
+                Self::KEY_UPSTREAM_TTL,
+                self.upstream_ttl,
+                Self::KEY_UPSTREAM_WRITES,
+                self.upstream_writes.name()
+            ));
+        }
+        if let Some(n) = self.flush_every_n_deltas {
+            ret.pop();
+            ret.push_str(&format!(",\"{}\":{}}}", Self::KEY_FLUSH_EVERY_N_DELTAS, n));
+        }
+        if let Some(ms) = self.flush_every_ms {
+            ret.pop();
+            ret.push_str(&format!(",\"{}\":{}}}", Self::KEY_FLUSH_EVERY_MS, ms));
+        }
+        if self.durability != Durability::Relaxed {
+            ret.pop();
+            ret.push_str(&format!(
+                ",\"{}\":\"{}\"}}",
+                Self::KEY_DURABILITY,
+                self.durability.name()
+            ));
+        }
+        if self.overflow {
+            ret.pop();
+            ret.push_str(&format!(",\"{}\":true}}", Self::KEY_OVERFLOW));
+        }
+        ret
+    }
+    fn describe_index(&self) -> String {
+        match self.index {
+            PrimaryIndexKind::Hash if self.partitions != 1 => format!(
+                "{{\"{}\":\"{}\",\"{}\":\"{}\",\"{}\":{}}}",
+                Self::KEY_INDEX,
+                self.index.name(),
+                Self::KEY_HASHER,
+                self.hasher.name(),
+                Self::KEY_PARTITIONS,
+                self.partitions
+            ),
+            PrimaryIndexKind::Hash => format!(
+                "{{\"{}\":\"{}\",\"{}\":\"{}\"}}",
+                Self::KEY_INDEX,
+                self.index.name(),
+                Self::KEY_HASHER,
+                self.hasher.name()
+            ),
+            PrimaryIndexKind::BTree => {
+                format!("{{\"{}\":\"{}\"}}", Self::KEY_INDEX, self.index.name())
+            }
+        }
+    }
+}
+
+/// How a numeric value is stored into a numeric field of a different kind (for example, an unsigned integer literal
+/// into a signed integer field)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NumericCoercion {
+    /// the kinds must match exactly: anything else is a validation error
+    Strict,
+    /// the value is converted, as long as it fits the field without losing any information: `10` can be stored into
+    /// a `sint8` field and `2.0` into a `uint8` field, but `-1` can't be stored into a `uint8` field, nor `2.5` into
+    /// any integer field
+    Lenient,
+    /// like [`Self::Strict`], but a value is also rejected if the field can't hold it exactly: `0.1` can't be stored
+    /// into a `float32` field since it would be rounded when read back as a `float32`
+    Exact,
+}
+
+impl NumericCoercion {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Strict => "strict",
+            Self::Lenient => "lenient",
+            Self::Exact => "exact",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "strict" => Some(Self::Strict),
+            "lenient" => Some(Self::Lenient),
+            "exact" => Some(Self::Exact),
+            _ => None,
+        }
+    }
+}
+
+/// How soon the changes to a model are written to disk
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Durability {
+    /// changes are written in batches by the flusher (as the cache fills up, on the model's flush triggers or on
+    /// the flusher's schedule)
+    Relaxed,
+    /// every change is written to disk before the statement that made it returns
+    Strict,
+}
+
+impl Durability {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Relaxed => "relaxed",
+            Self::Strict => "strict",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "relaxed" => Some(Self::Relaxed),
+            "strict" => Some(Self::Strict),
+            _ => None,
+        }
+    }
+}
+
+/// What happens when `+=`, `-=`, `*=` or `/=` takes a numeric field out of its bounds (for example, `x -= 1` when a
+/// `uint8` field is 0). A plain assignment that's out of bounds is always an error
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OverflowPolicy {
+    /// the update fails
+    Error,
+    /// the field is set to the bound that was crossed (0 in the example)
+    Saturate,
+    /// the field wraps around, keeping the low bits of the result (255 in the example). Floats can't wrap
+    Wrap,
+}
+
+impl OverflowPolicy {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Saturate => "saturate",
+            Self::Wrap => "wrap",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Self::Error),
+            "saturate" => Some(Self::Saturate),
+            "wrap" => Some(Self::Wrap),
+            _ => None,
+        }
+    }
+    /// Returns true if a field of this class can have this policy (only scalar numeric fields can)
+    pub fn applies_to(&self, class: TagClass) -> bool {
+        match class {
+            TagClass::UnsignedInt | TagClass::SignedInt => true,
+            TagClass::Float => *self != Self::Wrap,
+            _ => false,
+        }
+    }
+}
+
+/// Validated properties of a field (set on the field's type, for example `name: string { computed: "lower(id)" }`)
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct FieldProps {
+    computed: Option<ComputedExpr>,
+    window: Option<WindowCounter>,
+    decimal: Option<Decimal>,
+    default: Option<Datacell>,
+    generated: Option<KeyGen>,
+    overflow: Option<OverflowPolicy>,
+    checks: FieldChecks,
+}
+
+impl FieldProps {
+    /// an expression over another field, used to compute this field's value
+    pub const KEY_COMPUTED: &'static str = "computed";
+    /// the length of the window (in seconds) of a window counter (see [`super::window`])
+    pub const KEY_WINDOW: &'static str = "window";
+    /// the number of digits of a decimal (see [`super::decimal`])
+    pub const KEY_PRECISION: &'static str = "precision";
+    /// the number of digits after the point of a decimal
+    pub const KEY_SCALE: &'static str = "scale";
+    /// a literal that fills in this field when an insert leaves it out (a quoted string is always a literal)
+    pub const KEY_DEFAULT: &'static str = "default";
+    /// a key generator (such as `uuid7`) that fills in this field when an insert leaves it out
+    pub const KEY_GENERATED: &'static str = "generated";
+    /// what a compound assignment does when it takes a numeric field out of its bounds (see [`OverflowPolicy`])
+    pub const KEY_OVERFLOW: &'static str = "overflow";
+    /// Validate and resolve the given properties. Returns [`None`] if any property is unknown or has an illegal value
+    pub fn try_new(raw: DictGeneric) -> Option<Self> {
+        let mut slf = Self::default();
+        let (mut precision, mut scale) = (None, None);
+        for (key, value) in raw.iter() {
+            match (key.as_ref(), value) {
+                (Self::KEY_COMPUTED, DictEntryGeneric::Data(d)) => {
+                    slf.computed = Some(ComputedExpr::parse(d.try_str()?)?);
+                }
+                (Self::KEY_WINDOW, DictEntryGeneric::Data(d)) => {
+                    slf.window = Some(WindowCounter::new(d.try_uint()?)?);
+                }
+                (Self::KEY_DEFAULT, DictEntryGeneric::Data(d)) => {
+                    let scalar = matches!(
+                        d.kind(),
+                        TagClass::Bool
+                            | TagClass::UnsignedInt
+                            | TagClass::SignedInt
+                            | TagClass::Float
+                            | TagClass::Str
+                    );
+                    if d.is_null() | !scalar {
+                        return None;
+                    }
+                    slf.default = Some(d.clone());
+                }
+                (Self::KEY_GENERATED, DictEntryGeneric::Data(d)) => {
+                    slf.generated = Some(KeyGen::from_name(d.try_str()?)?);
+                }
+                (Self::KEY_OVERFLOW, DictEntryGeneric::Data(d)) => {
+                    slf.overflow = Some(OverflowPolicy::from_name(d.try_str()?)?);
+                }
+                (Self::KEY_PRECISION, DictEntryGeneric::Data(d)) => precision = Some(d.try_uint()?),
+                (Self::KEY_SCALE, DictEntryGeneric::Data(d)) => scale = Some(d.try_uint()?),
+                (key, value) => {
+                    if !slf.checks.try_set(key, value)? {
+                        return None;
+                    }
+                }
+            }
+        }
+        if precision.is_some() | scale.is_some() {
+            slf.decimal = Some(Decimal::new(
+                precision.unwrap_or(Decimal::DEFAULT_PRECISION),
+                scale.unwrap_or(Decimal::DEFAULT_SCALE),
+            )?);
+        }
+        Some(slf)
+    }
+    pub fn computed(&self) -> Option<&ComputedExpr> {
+        self.computed.as_ref()
+    }
+    /// Returns the definition of the window counter, if this is one
+    pub fn window(&self) -> Option<&WindowCounter> {
+        self.window.as_ref()
+    }
+    /// Returns the definition of the decimal, if this is one
+    pub fn decimal(&self) -> Option<&Decimal> {
+        self.decimal.as_ref()
+    }
+    /// Returns the literal default of this field, if it has one
+    pub fn literal_default(&self) -> Option<&Datacell> {
+        self.default.as_ref()
+    }
+    /// Returns the generator for the value of this field, if it has one
+    pub fn keygen(&self) -> Option<KeyGen> {
+        self.generated
+    }
+    /// Returns the overflow policy, if one was set (otherwise, overflowing is an error)
+    pub fn overflow(&self) -> Option<OverflowPolicy> {
+        self.overflow
+    }
+    /// Replace the overflow policy, returning true if it changed
+    pub fn set_overflow(&mut self, overflow: Option<OverflowPolicy>) -> bool {
+        let changed = self.overflow != overflow;
+        self.overflow = overflow;
+        changed
+    }
+    pub fn checks(&self) -> &FieldChecks {
+        &self.checks
+    }
+    pub fn into_checks(self) -> FieldChecks {
+        self.checks
+    }
+    /// Replace the checks, returning true if they changed
+    pub fn set_checks(&mut self, checks: FieldChecks) -> bool {
+        let changed = self.checks != checks;
+        self.checks = checks;
+        changed
+    }
+    /// Returns the number of properties that are set (a decimal sets two: its precision and scale)
+    pub fn len(&self) -> usize {
+        self.computed.is_some() as usize
+            + self.window.is_some() as usize
+            + 2 * self.decimal.is_some() as usize
+            + self.default.is_some() as usize
+            + self.generated.is_some() as usize
+            + self.overflow.is_some() as usize
+            + self.checks.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Returns the properties that are set as a JSON object
+    pub fn describe(&self) -> String {
+        let mut members = vec![];
+        if let Some(computed) = self.computed.as_ref() {
+            let mut member = format!("\"{}\":", Self::KEY_COMPUTED);
+            encode_str(&mut member, &computed.describe());
+            members.push(member);
+        }
+        if let Some(window) = self.window.as_ref() {
+            members.push(format!("\"{}\":{}", Self::KEY_WINDOW, window.window()));
+        }
+        if let Some(decimal) = self.decimal.as_ref() {
+            members.push(format!(
+                "\"{}\":{},\"{}\":{}",
+                Self::KEY_PRECISION,
+                decimal.precision(),
+                Self::KEY_SCALE,
+                decimal.scale()
+            ));
+        }
+        if let Some(default) = self.default.as_ref() {
+            let mut member = format!("\"{}\":", Self::KEY_DEFAULT);
+            match default.kind() {
+                TagClass::Str => encode_str(&mut member, default.str()),
+                TagClass::Bool => member.push_str(&default.bool().to_string()),
+                TagClass::UnsignedInt => member.push_str(&default.uint().to_string()),
+                TagClass::SignedInt => member.push_str(&default.sint().to_string()),
+                _ => member.push_str(&default.float().to_string()),
+            }
+            members.push(member);
+        }
+        if let Some(generated) = self.generated {
+            members.push(format!(
+                "\"{}\":\"{}\"",
+                Self::KEY_GENERATED,
+                generated.name()
+            ));
+        }
+        if let Some(overflow) = self.overflow {
+            members.push(format!(
+                "\"{}\":\"{}\"",
+                Self::KEY_OVERFLOW,
+                overflow.name()
+            ));
+        }
+        members.extend(self.checks.describe_members());
+        format!("{{{}}}", members.join(","))
+    }
+    /// Returns the properties as a generic dictionary (for persistence)
+    pub fn to_raw(&self) -> DictGeneric {
+        let mut raw = DictGeneric::new();
+        if let Some(computed) = self.computed.as_ref() {
+            raw.insert(
+                Self::KEY_COMPUTED.into(),
+                DictEntryGeneric::Data(Datacell::new_str(computed.describe().into_boxed_str())),
+            );
+        }
+        if let Some(window) = self.window.as_ref() {
+            raw.insert(
+                Self::KEY_WINDOW.into(),
+                DictEntryGeneric::Data(Datacell::new_uint_default(window.window())),
+            );
+        }
+        if let Some(decimal) = self.decimal.as_ref() {
+            raw.insert(
+                Self::KEY_PRECISION.into(),
+                DictEntryGeneric::Data(Datacell::new_uint_default(decimal.precision())),
+            );
+            raw.insert(
+                Self::KEY_SCALE.into(),
+                DictEntryGeneric::Data(Datacell::new_uint_default(decimal.scale())),
+            );
+        }
+        if let Some(default) = self.default.as_ref() {
+            raw.insert(
+                Self::KEY_DEFAULT.into(),
+                DictEntryGeneric::Data(default.clone()),
+            );
+        }
+        if let Some(generated) = self.generated {
+            raw.insert(
+                Self::KEY_GENERATED.into(),
+                DictEntryGeneric::Data(Datacell::new_str(generated.name().into())),
+            );
+        }
+        if let Some(overflow) = self.overflow {
+            raw.insert(
+                Self::KEY_OVERFLOW.into(),
+                DictEntryGeneric::Data(Datacell::new_str(overflow.name().into())),
+            );
+        }
+        self.checks.write_raw(&mut raw);
+        raw
+    }
+}
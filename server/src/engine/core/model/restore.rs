@@ -0,0 +1,119 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    selective restores
+    ---
+    `sysctl restore model <model> from snapshot <path> as <new model>` brings back a single model from a snapshot (a
+    copy of the data directory, taken while the instance or the model's space was frozen) into the running instance
+    under a new name, so that recovering one model doesn't take rolling back the whole instance. the snapshot is read
+    like `sky-inspect` reads a data directory: its global journal is decoded to find the model, and the model's
+    journals are replayed (in place and without ever writing to them) to get the rows that it would be restored with.
+    the new model is then created with the same fields, primary key and properties, and the rows are inserted like a
+    copy's are, so they're written out by the flusher in batches like any other insert. soft deleted rows and
+    secondary indexes aren't restored.
+
+    the new model only becomes visible once all of its rows are in. the snapshot must be a copy: reading the journals
+    of a data directory that a server is writing to can see a half written batch. a snapshot taken with `sysctl create
+    snapshot` is one, and it can't be removed while a restore reads from it
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{
+        core::EntityIDRef,
+        error::{QueryError, QueryResult},
+        fractal::{
+            jobs::{self, JobKind, JobStatus},
+            GlobalInstanceLike,
+        },
+        storage::{
+            safe_interfaces::{paths_v1, FileSystem},
+            Inspector,
+        },
+    },
+};
+
+/// Restore a model from the snapshot at `snapshot` as `target`, returning the number of rows restored. The target
+/// must not exist
+pub fn restore_model(
+    global: &impl GlobalInstanceLike,
+    source: EntityIDRef,
+    snapshot: &str,
+    target: EntityIDRef,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    restore(global, source, snapshot, target)
+}
+
+/// Restore a model from a snapshot in the background, returning the id of the job
+pub fn restore_model_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    source: EntityIDRef,
+    snapshot: &str,
+    target: EntityIDRef,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    let (source_space, source_model): (Box<str>, Box<str>) =
+        (source.space().into(), source.entity().into());
+    let (space, model): (Box<str>, Box<str>) = (target.space().into(), target.entity().into());
+    let snapshot: Box<str> = snapshot.into();
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::RestoreModel,
+        format!("{space}.{model}").into_boxed_str(),
+        move |global, _| {
+            let source = EntityIDRef::new(&source_space, &source_model);
+            match restore(global, source, &snapshot, EntityIDRef::new(&space, &model)) {
+                Ok(rows) => JobStatus::Completed(Some(format!("{{\"rows\":{rows}}}"))),
+                Err(e) => JobStatus::failed(e),
+            }
+        },
+    ))
+}
+
+fn restore(
+    global: &impl GlobalInstanceLike,
+    source: EntityIDRef,
+    snapshot: &str,
+    target: EntityIDRef,
+) -> QueryResult<u64> {
+    // a snapshot taken by the server isn't removed while it is read (see `core::snapshot`)
+    let _restoring = global.state().snapshots().restoring(snapshot);
+    if FileSystem::file_len(&paths_v1::in_copy(snapshot, &paths_v1::gns_path())).is_err() {
+        return Err(QueryError::QExecObjectNotFound.with_detail("snapshot", snapshot));
+    }
+    let inspector = Inspector::load_copy(snapshot)?;
+    if !inspector.has_model(source) {
+        return Err(QueryError::QExecObjectNotFound.with_detail("entity", source));
+    }
+    let entity = format!("{}.{}", source.space(), source.entity());
+    inspector.with_rows(&entity, |model, rows| {
+        ModelData::transactional_exec_restore(global, target, model.data(), rows.into_rows())
+    })?
+}
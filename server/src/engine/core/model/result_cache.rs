@@ -0,0 +1,189 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    result cache
+    ---
+    models created with `result_cache: true` keep the responses of recent reads (`select`, `select all` and
+    aggregates) in memory. an entry is keyed by the statement's fingerprint, which is built from its tokens and hence
+    includes the values of its parameters, and it's only served while the model is at the generation that it was
+    computed at. the generation is made up of the model's identity, its schema version and the number of data changes
+    that have been made visible, so any write (or schema change) makes every entry stale. entries also expire after
+    `result_cache_ttl` seconds, and the responses held by a model never take more than MAX_BYTES (the oldest entries
+    are evicted first). the cache is only kept in memory
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{data::uuid::Uuid, net::protocol::Response},
+    parking_lot::Mutex,
+    std::collections::HashMap,
+};
+
+/// The maximum number of bytes (of fingerprints and responses) held by a model's cache
+pub const MAX_BYTES: usize = 4 * 1024 * 1024;
+/// Responses (along with their fingerprints) larger than this are never cached
+pub const MAX_ENTRY_BYTES: usize = 256 * 1024;
+
+/// The state of a model that a cached response was computed at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CacheGeneration {
+    uuid: Uuid,
+    schema: u64,
+    data: u64,
+}
+
+impl CacheGeneration {
+    /// Returns the current generation of the model
+    pub fn of(mdl: &ModelData) -> Self {
+        Self {
+            uuid: mdl.get_uuid(),
+            schema: mdl.delta_state().schema_current_version().value_u64(),
+            data: mdl.delta_state().data_generation(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    generation: CacheGeneration,
+    /// the time (in seconds since the epoch) at which the response was computed
+    created_at: u64,
+    response: Response,
+    /// the size of the fingerprint and the response
+    size: usize,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<Box<str>, Entry>,
+    bytes: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl CacheState {
+    fn remove(&mut self, fingerprint: &str) {
+        if let Some(entry) = self.entries.remove(fingerprint) {
+            self.bytes -= entry.size;
+        }
+    }
+}
+
+/// The cached responses of a model that has them enabled (see the module docs)
+#[derive(Debug)]
+pub struct ResultCache {
+    ttl: u64,
+    state: Mutex<CacheState>,
+}
+
+impl ResultCache {
+    pub fn new(ttl: u64) -> Self {
+        Self {
+            ttl,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+    /// Returns the cached response for the given fingerprint, if there is one that was computed at the given
+    /// generation and hasn't expired
+    pub fn get(
+        &self,
+        fingerprint: &str,
+        generation: CacheGeneration,
+        now: u64,
+    ) -> Option<Response> {
+        let mut state = self.state.lock();
+        let response = state
+            .entries
+            .get(fingerprint)
+            .filter(|entry| (entry.generation == generation) & (now < entry.created_at + self.ttl))
+            .map(|entry| entry.response.clone());
+        match response {
+            Some(_) => state.hits += 1,
+            None => state.misses += 1,
+        }
+        response
+    }
+    /// Cache a response that was computed at the given generation. Nothing is cached if the model has changed since
+    /// (as the response might not reflect the change) or if the response is too large. Entries that are stale are
+    /// dropped, and then the oldest entries are evicted until the response fits
+    pub fn put(
+        &self,
+        mdl: &ModelData,
+        fingerprint: Box<str>,
+        generation: CacheGeneration,
+        now: u64,
+        response: &Response,
+    ) {
+        let size = fingerprint.len()
+            + match response {
+                Response::Serialized { data, .. } => data.len(),
+                _ => 0,
+            };
+        if (size > MAX_ENTRY_BYTES) | (CacheGeneration::of(mdl) != generation) {
+            return;
+        }
+        let mut state = self.state.lock();
+        state.remove(&fingerprint);
+        let ttl = self.ttl;
+        state
+            .entries
+            .retain(|_, entry| (entry.generation == generation) & (now < entry.created_at + ttl));
+        state.bytes = state.entries.values().map(|entry| entry.size).sum();
+        while state.bytes + size > MAX_BYTES {
+            let Some(oldest) = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.created_at)
+                .map(|(fingerprint, _)| fingerprint.clone())
+            else {
+                break;
+            };
+            state.remove(&oldest);
+        }
+        state.bytes += size;
+        state.entries.insert(
+            fingerprint,
+            Entry {
+                generation,
+                created_at: now,
+                response: response.clone(),
+                size,
+            },
+        );
+    }
+    /// Returns the number of entries, the bytes that they take and the hits and misses so far as a JSON object
+    pub fn describe(&self) -> String {
+        let state = self.state.lock();
+        format!(
+            "{{\"entries\":{},\"bytes\":{},\"hits\":{},\"misses\":{}}}",
+            state.entries.len(),
+            state.bytes,
+            state.hits,
+            state.misses
+        )
+    }
+}
@@ -0,0 +1,438 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{predicate::Predicate, ModelData},
+    crate::engine::{
+        core::{
+            index::{Collation, SecondaryIndex},
+            EntityIDRef,
+        },
+        data::{
+            tag::{DataTag, TagClass},
+            DictEntryGeneric, DictGeneric,
+        },
+        error::{QueryError, QueryResult},
+        fractal::{
+            jobs::{self, Job, JobKind, JobStatus},
+            GlobalInstanceLike, WorkClass,
+        },
+        idx::{STIndex, STIndexSeq},
+        mem,
+        ql::ddl::{crt::CreateIndex, drop::DropIndex},
+        storage::encode_str,
+        txn::{gns, ModelIDRef},
+    },
+    std::{fmt::Write, time::Instant},
+};
+
+impl ModelData {
+    pub fn transactional_exec_create_index<G: GlobalInstanceLike>(
+        global: &G,
+        stmt: CreateIndex,
+    ) -> QueryResult<Option<bool>> {
+        // NB: we hold the model lock, so no DML can run while we build
+        Self::define_index(global, stmt, |model, idx| idx.build(model))
+    }
+    /// Create an index like [`Self::transactional_exec_create_index`], but build it in a background job (owned by
+    /// the given user) whose id is returned. Until the job is done, the index is maintained by DML but isn't used to
+    /// answer queries. Returns [`None`] if the index already exists (with `if not exists`)
+    pub fn transactional_exec_create_index_in_background<G: GlobalInstanceLike>(
+        global: &G,
+        owner: &str,
+        stmt: CreateIndex,
+    ) -> QueryResult<Option<u64>> {
+        let (space_name, model_name, index_name): (Box<str>, Box<str>, Box<str>) = (
+            stmt.model_name.space().into(),
+            stmt.model_name.entity().into(),
+            stmt.index_name.as_str().into(),
+        );
+        if Self::define_index(global, stmt, |_, _| {})? == Some(false) {
+            return Ok(None);
+        }
+        Ok(Some(jobs::start(
+            global,
+            Some(owner),
+            JobKind::BuildIndex,
+            format!("{space_name}.{model_name}.{index_name}").into_boxed_str(),
+            move |global, job| build_index_job(global, job, &space_name, &model_name, &index_name),
+        )))
+    }
+    /// Commit the index definition and add the index to the model, then call `build` (with the model lock held)
+    fn define_index<G: GlobalInstanceLike>(
+        global: &G,
+        CreateIndex {
+            index_name,
+            model_name: entity,
+            fields,
+            include,
+            props,
+            predicate,
+            if_not_exists,
+            background: _,
+        }: CreateIndex,
+        build: impl FnOnce(&ModelData, &SecondaryIndex),
+    ) -> QueryResult<Option<bool>> {
+        let (space_name, model_name) = (entity.space(), entity.entity());
+        global
+            .state()
+            .namespace()
+            .with_model_space_mut_for_ddl(entity, |space, model| {
+                global.state().freeze().check(space_name)?;
+                if model.secondary_indexes().st_contains(index_name.as_str()) {
+                    if if_not_exists {
+                        return Ok(Some(false));
+                    } else {
+                        return Err(QueryError::QExecDdlObjectAlreadyExists);
+                    }
+                }
+                // we only index scalar, non-primary fields that can be compared for equality (each only once)
+                let mut indexed: Vec<Box<str>> = Vec::with_capacity(fields.len());
+                for f in fields.iter() {
+                    let Some(field_def) = model.fields().st_get(f.as_str()) else {
+                        return Err(QueryError::QExecUnknownField);
+                    };
+                    if model.is_pk(f.as_str())
+                        | (field_def.layers().len() != 1)
+                        | !SecondaryIndex::can_index(field_def.layers()[0].tag())
+                        | indexed.iter().any(|i| i.as_ref() == f.as_str())
+                    {
+                        return Err(QueryError::QExecDdlModelBadDefinition);
+                    }
+                    indexed.push(f.as_str().into());
+                }
+                let indexed = indexed.into_boxed_slice();
+                // a collation only makes sense if a string field is indexed
+                let collation = Self::index_collation(&props)?;
+                let has_str_field = indexed.iter().any(|f| {
+                    model.fields().st_get(f.as_ref()).unwrap().layers()[0]
+                        .tag()
+                        .tag_class()
+                        == TagClass::Str
+                });
+                if (collation != Collation::Binary) & !has_str_field {
+                    return Err(QueryError::QExecDdlModelBadDefinition);
+                }
+                // a covering index can store any other (non-primary) field, but only once
+                let mut included: Vec<Box<str>> = Vec::with_capacity(include.len());
+                for f in include.iter() {
+                    if !model.fields().st_contains(f.as_str()) {
+                        return Err(QueryError::QExecUnknownField);
+                    }
+                    if model.is_pk(f.as_str())
+                        | indexed.iter().any(|i| i.as_ref() == f.as_str())
+                        | included.iter().any(|i| i.as_ref() == f.as_str())
+                    {
+                        return Err(QueryError::QExecDdlModelBadDefinition);
+                    }
+                    included.push(f.as_str().into());
+                }
+                let included = included.into_boxed_slice();
+                // a partial index only holds the rows that satisfy its predicate
+                let filter = match predicate {
+                    Some(mut predicate) => {
+                        Some(Predicate::from_where(model, predicate.clauses_mut())?)
+                    }
+                    None => None,
+                };
+                let raw_filter = filter.as_ref().map(Predicate::to_raw);
+                // prepare txn
+                let txn = gns::model::CreateIndexTxn::new(
+                    ModelIDRef::new_ref(&space_name, space, &model_name, model),
+                    index_name.as_str(),
+                    &indexed,
+                    &included,
+                    raw_filter.as_ref(),
+                    collation,
+                );
+                // commit txn
+                global.state().gns_driver().driver_context(
+                    global,
+                    |drv| drv.commit_event(txn),
+                    || {},
+                )?;
+                // update global state
+                let _ = model.add_secondary_index(
+                    index_name.as_str().into(),
+                    indexed,
+                    included,
+                    filter,
+                    collation,
+                );
+                build(
+                    model,
+                    model
+                        .secondary_indexes()
+                        .st_get(index_name.as_str())
+                        .unwrap(),
+                );
+                if if_not_exists {
+                    Ok(Some(true))
+                } else {
+                    Ok(None)
+                }
+            })
+    }
+    /// Resolve the properties of an index. The only property is the collation (`binary`, unless set otherwise)
+    fn index_collation(props: &DictGeneric) -> QueryResult<Collation> {
+        let mut collation = Collation::Binary;
+        for (key, value) in props.iter() {
+            match (key.as_ref(), value) {
+                (SecondaryIndex::KEY_COLLATION, DictEntryGeneric::Data(d)) => {
+                    collation = d
+                        .try_str()
+                        .and_then(Collation::from_name)
+                        .ok_or(QueryError::QExecDdlInvalidProperties)?;
+                }
+                _ => return Err(QueryError::QExecDdlInvalidProperties),
+            }
+        }
+        Ok(collation)
+    }
+    pub fn transactional_exec_drop_index<G: GlobalInstanceLike>(
+        global: &G,
+        DropIndex {
+            index_name,
+            entity,
+            if_exists,
+        }: DropIndex,
+    ) -> QueryResult<Option<bool>> {
+        let (space_name, model_name) = (entity.space(), entity.entity());
+        global
+            .state()
+            .namespace()
+            .with_model_space_mut_for_ddl(entity, |space, model| {
+                global.state().freeze().check(space_name)?;
+                if !model.secondary_indexes().st_contains(index_name.as_str()) {
+                    if if_exists {
+                        return Ok(Some(false));
+                    } else {
+                        return Err(QueryError::QExecObjectNotFound);
+                    }
+                }
+                // prepare txn
+                let txn = gns::model::DropIndexTxn::new(
+                    ModelIDRef::new_ref(&space_name, space, &model_name, model),
+                    index_name.as_str(),
+                );
+                // commit txn
+                global.state().gns_driver().driver_context(
+                    global,
+                    |drv| drv.commit_event(txn),
+                    || {},
+                )?;
+                // update global state
+                let _ = model.remove_secondary_index(index_name.as_str());
+                if if_exists {
+                    Ok(Some(true))
+                } else {
+                    Ok(None)
+                }
+            })
+    }
+}
+
+/// Build an index that was created with `in background`. A cancelled build drops the index
+fn build_index_job<G: GlobalInstanceLike>(
+    global: &G,
+    job: &Job,
+    space_name: &str,
+    model_name: &str,
+    index_name: &str,
+) -> JobStatus {
+    // index entries are allocated here, so use the huge page arena (if enabled)
+    mem::arena::bind_current_thread();
+    let entity = EntityIDRef::new(space_name, model_name);
+    {
+        // the model (or the index) could have been dropped in the meantime
+        let models = global.state().namespace().idx_models().read();
+        let Some((mdl, idx)) = models.get(&entity).and_then(|mdl| {
+            mdl.data()
+                .secondary_indexes()
+                .st_get(index_name)
+                .map(|idx| (mdl.data(), idx))
+        }) else {
+            return JobStatus::failed(QueryError::QExecObjectNotFound);
+        };
+        job.set_total(mdl.primary_index().count() as u64);
+        let started_at = Instant::now();
+        let built = idx.build_until(mdl, job.token(), |rows| job.set_done(rows as u64));
+        job.set_done(idx.progress() as u64);
+        // writes to the model are blocked while the index is built, so it is never held back
+        global.scheduler().record(
+            WorkClass::Rebuild,
+            idx.progress() as u64,
+            started_at.elapsed(),
+        );
+        if built {
+            return JobStatus::Completed(None);
+        }
+    }
+    match ModelData::transactional_exec_drop_index(
+        global,
+        DropIndex::new(index_name.into(), entity, true),
+    ) {
+        Ok(_) => JobStatus::Cancelled,
+        Err(e) => JobStatus::failed(e),
+    }
+}
+
+/// Build all secondary indexes that are not ready yet, using up to `workers` threads. The build is run as a job, so
+/// it can be followed with `sysctl list jobs` and cancelled (which leaves the indexes that weren't built yet to be
+/// built on the next boot).
+///
+/// This is called once all the model data has been loaded on boot. The model index is only held for reads, so
+/// DML can continue while the indexes are built (the indexes are not used to answer queries until ready)
+pub fn build_pending_indexes<G: GlobalInstanceLike + Sync>(global: &G, workers: usize) {
+    let pending: Vec<(Box<str>, Box<str>, Box<str>)> = global
+        .state()
+        .namespace()
+        .idx_models()
+        .read()
+        .iter()
+        .flat_map(|(mdl_id, mdl)| {
+            mdl.data()
+                .secondary_indexes()
+                .stseq_ord_kv()
+                .filter(|(_, idx)| !idx.is_ready())
+                .map(|(idx_name, _)| {
+                    (
+                        mdl_id.space().into(),
+                        mdl_id.entity().into(),
+                        idx_name.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if pending.is_empty() {
+        return;
+    }
+    info!(
+        "building {} secondary index(es) with {} worker(s)",
+        pending.len(),
+        workers.min(pending.len())
+    );
+    let status = jobs::run(
+        global,
+        JobKind::RebuildIndexes,
+        "*".into(),
+        |global, job| build_pending(global, job, &pending, workers),
+    );
+    match status {
+        JobStatus::Cancelled => {
+            warn!("stopped building secondary indexes (the build was cancelled)")
+        }
+        _ => info!("finished building secondary indexes"),
+    }
+}
+
+fn build_pending<G: GlobalInstanceLike + Sync>(
+    global: &G,
+    job: &Job,
+    pending: &[(Box<str>, Box<str>, Box<str>)],
+    workers: usize,
+) -> JobStatus {
+    job.set_total(pending.len() as u64);
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let built = std::sync::atomic::AtomicU64::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..workers.max(1).min(pending.len()) {
+            s.spawn(|| {
+                // index entries are allocated here, so use the huge page arena (if enabled)
+                mem::arena::bind_current_thread();
+                while !job.token().is_cancelled() {
+                    let i = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    let Some((space_name, model_name, idx_name)) = pending.get(i) else {
+                        break;
+                    };
+                    let (started_at, mut rows) = (Instant::now(), 0);
+                    // the model (or the index) could have been dropped in the meantime
+                    let models = global.state().namespace().idx_models().read();
+                    if let Some(mdl) = models.get(&EntityIDRef::new(space_name, model_name)) {
+                        if let Some(idx) = mdl.data().secondary_indexes().st_get(idx_name.as_ref())
+                        {
+                            if !idx.build_until(mdl.data(), job.token(), |_| {}) {
+                                break;
+                            }
+                            rows = idx.progress() as u64;
+                        }
+                    }
+                    drop(models);
+                    job.set_done(built.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1);
+                    // the model index isn't held anymore, so the next index can wait if queries are running
+                    global
+                        .scheduler()
+                        .throttle(WorkClass::Rebuild, rows, started_at.elapsed());
+                }
+            });
+        }
+    });
+    if job.token().is_cancelled() {
+        JobStatus::Cancelled
+    } else {
+        JobStatus::Completed(None)
+    }
+}
+
+/// Describe the secondary indexes of all models as a JSON array, with their size and how much they've been used since
+/// the server started. A (built) index that hasn't served a single lookup is marked as unused, since it only adds to
+/// the cost of writes and is a candidate for removal
+pub fn describe_indexes(global: &impl GlobalInstanceLike) -> String {
+    let mut out = String::from("[");
+    let models = global.state().namespace().idx_models().read();
+    for (entity, model) in models.iter() {
+        for (name, idx) in model.data().secondary_indexes().stseq_ord_kv() {
+            if out.len() != 1 {
+                out.push(',');
+            }
+            out.push_str("{\"model\":");
+            encode_str(&mut out, &format!("{}.{}", entity.space(), entity.entity()));
+            out.push_str(",\"name\":");
+            encode_str(&mut out, name);
+            out.push_str(",\"fields\":[");
+            for (i, field) in idx.fields().iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                encode_str(&mut out, field);
+            }
+            let _ = write!(
+                out,
+                "],\"ready\":{},\"entries\":{},\"reads\":{},\"writes\":{},\"last_read\":{},\"unused\":{}}}",
+                idx.is_ready(),
+                idx.entries(),
+                idx.reads(),
+                idx.writes(),
+                idx.last_read()
+                    .map_or_else(|| "null".into(), |t| t.to_string()),
+                idx.is_ready() & (idx.reads() == 0),
+            );
+        }
+    }
+    out.push(']');
+    out
+}
@@ -0,0 +1,312 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    field statistics
+    ---
+    models created with `histograms: true` keep an equi-depth histogram for every numeric (scalar) field, including
+    the primary key. a histogram is built from a sample of at most SAMPLE_MAX rows (every nth row) by the maintenance
+    task, which refreshes it once the number of rows has changed by more than a tenth or once it's REFRESH_AFTER
+    seconds old. histograms are only estimates: they're kept in memory (and hence rebuilt after a restart) and never
+    block writes
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{
+        core::{index::PrimaryIndexKey, lock},
+        data::{
+            cell::Datacell,
+            tag::{DataTag, TagClass},
+        },
+        idx::{STIndex, STIndexSeq},
+        sync::atm::cpin,
+    },
+    parking_lot::RwLock,
+    std::{collections::HashMap, fmt::Write, ops::Bound},
+};
+
+/// The (maximum) number of buckets in a histogram
+pub const BUCKETS: usize = 32;
+/// The maximum number of rows that are sampled to build the histograms
+const SAMPLE_MAX: usize = 4096;
+/// Histograms older than this (in seconds) are refreshed, even if the number of rows hasn't changed much
+const REFRESH_AFTER: u64 = 10 * 60;
+
+/// An equi-depth histogram of the values of a numeric field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// the bucket boundaries, in ascending order: the first is the smallest sampled value and the last is the largest.
+    /// every bucket holds about the same number of sampled values
+    bounds: Vec<f64>,
+    /// the number of sampled values that were not null
+    sampled: usize,
+    /// the number of sampled values that were null
+    nulls: usize,
+    /// the number of distinct values in the sample
+    distinct: usize,
+}
+
+impl Histogram {
+    /// Build a histogram from the sampled values. Returns [`None`] if no value was sampled
+    pub fn from_sample(mut sample: Vec<f64>, nulls: usize) -> Option<Self> {
+        if sample.is_empty() {
+            return None;
+        }
+        sample.sort_by(f64::total_cmp);
+        let buckets = BUCKETS.min(sample.len());
+        let bounds = (0..=buckets)
+            .map(|i| sample[i * (sample.len() - 1) / buckets])
+            .collect();
+        let distinct = 1 + sample.windows(2).filter(|w| w[0] != w[1]).count();
+        Some(Self {
+            bounds,
+            sampled: sample.len(),
+            nulls,
+            distinct,
+        })
+    }
+    fn min(&self) -> f64 {
+        self.bounds[0]
+    }
+    fn max(&self) -> f64 {
+        self.bounds[self.bounds.len() - 1]
+    }
+    /// The share of rows that are not null
+    fn not_null(&self) -> f64 {
+        self.sampled as f64 / (self.sampled + self.nulls) as f64
+    }
+    /// Estimate the share of rows whose value is equal to `v`
+    pub fn eq_selectivity(&self, v: f64) -> f64 {
+        if (v < self.min()) | (v > self.max()) {
+            return 0.0;
+        }
+        self.not_null() / self.distinct as f64
+    }
+    /// Estimate the share of rows whose value is in the given range. Values are assumed to be spread evenly within
+    /// a bucket
+    pub fn range_selectivity(&self, lo: Bound<f64>, hi: Bound<f64>) -> f64 {
+        let (lo, lo_incl) = match lo {
+            Bound::Included(lo) => (lo, true),
+            Bound::Excluded(lo) => (lo, false),
+            Bound::Unbounded => (f64::NEG_INFINITY, true),
+        };
+        let (hi, hi_incl) = match hi {
+            Bound::Included(hi) => (hi, true),
+            Bound::Excluded(hi) => (hi, false),
+            Bound::Unbounded => (f64::INFINITY, true),
+        };
+        let in_range =
+            |v: f64| ((v > lo) | (lo_incl & (v == lo))) & ((v < hi) | (hi_incl & (v == hi)));
+        let buckets = self.bounds.len() - 1;
+        let covered: f64 = self
+            .bounds
+            .windows(2)
+            .map(|bucket| {
+                let (start, end) = (bucket[0], bucket[1]);
+                if start == end {
+                    // all the values in this bucket are the same
+                    return in_range(start) as u8 as f64;
+                }
+                let overlap = end.min(hi) - start.max(lo);
+                (overlap / (end - start)).clamp(0.0, 1.0)
+            })
+            .sum();
+        covered / buckets as f64 * self.not_null()
+    }
+    /// Returns the histogram as a JSON object
+    pub fn describe(&self) -> String {
+        let mut ret = format!(
+            "{{\"sampled\":{},\"nulls\":{},\"distinct\":{},\"bounds\":[",
+            self.sampled, self.nulls, self.distinct
+        );
+        for (i, bound) in self.bounds.iter().enumerate() {
+            if i != 0 {
+                ret.push(',');
+            }
+            let _ = write!(ret, "{bound}");
+        }
+        ret.push_str("]}");
+        ret
+    }
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    histograms: HashMap<Box<str>, Histogram>,
+    /// the number of rows when the histograms were built
+    rows: usize,
+    /// the time (in seconds since the epoch) at which the histograms were built, or zero if they never were
+    built_at: u64,
+}
+
+/// The histograms of a model that has them enabled (see the module docs)
+#[derive(Debug, Default)]
+pub struct FieldStats {
+    snapshot: RwLock<Snapshot>,
+}
+
+impl FieldStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the histogram of the given field, if there is one
+    pub fn histogram(&self, field: &str) -> Option<Histogram> {
+        self.snapshot.read().histograms.get(field).cloned()
+    }
+    /// Returns true if the histograms should be rebuilt, given the current number of rows
+    pub fn is_stale(&self, rows: usize, now: u64) -> bool {
+        let snapshot = self.snapshot.read();
+        (snapshot.built_at == 0)
+            | (rows.abs_diff(snapshot.rows) > snapshot.rows / 10)
+            | (now.saturating_sub(snapshot.built_at) >= REFRESH_AFTER)
+    }
+    /// Rebuild the histograms from a sample of the model's rows. Soft deleted rows are left out
+    pub fn refresh(&self, mdl: &ModelData, now: u64) {
+        let fields: Vec<&str> = mdl
+            .fields()
+            .stseq_ord_kv()
+            .filter(|(_, field)| {
+                (field.layers().len() == 1)
+                    & matches!(
+                        field.layers()[0].tag().tag_class(),
+                        TagClass::UnsignedInt | TagClass::SignedInt | TagClass::Float
+                    )
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        let mut samples: Vec<(Vec<f64>, usize)> = vec![(vec![], 0); fields.len()];
+        let rows = mdl.primary_index().count();
+        let every = rows / SAMPLE_MAX + 1;
+        let g = cpin();
+        for row in mdl.primary_index().iter(&g).step_by(every) {
+            let Ok(data) = lock::read(mdl, row) else {
+                continue;
+            };
+            if data.is_tombstoned() {
+                continue;
+            }
+            for (field, (sample, nulls)) in fields.iter().zip(samples.iter_mut()) {
+                let value = if *field == mdl.p_key() {
+                    key_value(row.d_key())
+                } else {
+                    data.fields().st_get(*field).and_then(cell_value)
+                };
+                match value {
+                    Some(v) => sample.push(v),
+                    None => *nulls += 1,
+                }
+            }
+        }
+        let histograms = fields
+            .into_iter()
+            .zip(samples)
+            .filter_map(|(field, (sample, nulls))| {
+                Histogram::from_sample(sample, nulls).map(|h| (Box::from(field), h))
+            })
+            .collect();
+        *self.snapshot.write() = Snapshot {
+            histograms,
+            rows,
+            built_at: now,
+        };
+    }
+    /// Returns the histograms as a JSON object (by field), along with the number of rows and the time at which they
+    /// were built
+    pub fn describe(&self) -> String {
+        let snapshot = self.snapshot.read();
+        let mut fields: Vec<_> = snapshot.histograms.iter().collect();
+        fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut ret = format!(
+            "{{\"rows\":{},\"built_at\":{},\"fields\":{{",
+            snapshot.rows, snapshot.built_at
+        );
+        for (i, (field, histogram)) in fields.into_iter().enumerate() {
+            if i != 0 {
+                ret.push(',');
+            }
+            let _ = write!(ret, "\"{field}\":{}", histogram.describe());
+        }
+        ret.push_str("}}");
+        ret
+    }
+}
+
+fn key_value(key: &PrimaryIndexKey) -> Option<f64> {
+    key.uint()
+        .map(|v| v as f64)
+        .or_else(|| key.sint().map(|v| v as f64))
+}
+
+fn cell_value(dc: &Datacell) -> Option<f64> {
+    if dc.is_null() {
+        return None;
+    }
+    match dc.kind() {
+        TagClass::UnsignedInt => dc.try_uint().map(|v| v as f64),
+        TagClass::SignedInt => dc.try_sint().map(|v| v as f64),
+        TagClass::Float => dc.try_float().filter(|v| v.is_finite()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::Histogram, std::ops::Bound};
+
+    #[test]
+    fn uniform_selectivity() {
+        let h = Histogram::from_sample((0..1000).map(|v| v as f64).collect(), 0).unwrap();
+        let close = |a: f64, b: f64| (a - b).abs() < 0.01;
+        assert!(close(
+            h.range_selectivity(Bound::Included(0.0), Bound::Excluded(250.0)),
+            0.25
+        ));
+        assert!(close(
+            h.range_selectivity(Bound::Excluded(900.0), Bound::Unbounded),
+            0.1
+        ));
+        assert_eq!(
+            h.range_selectivity(Bound::Included(2000.0), Bound::Unbounded),
+            0.0
+        );
+        assert!(close(h.eq_selectivity(10.0), 0.001));
+        assert_eq!(h.eq_selectivity(-1.0), 0.0);
+    }
+
+    #[test]
+    fn skewed_and_null_selectivity() {
+        // half of the rows are null and 90% of the rest are zero
+        let sample = (0..100)
+            .map(|v| if v < 90 { 0.0 } else { v as f64 })
+            .collect();
+        let h = Histogram::from_sample(sample, 100).unwrap();
+        let zeros = h.range_selectivity(Bound::Included(0.0), Bound::Included(0.0));
+        assert!((0.4..=0.5).contains(&zeros), "{zeros}");
+        assert!(h.range_selectivity(Bound::Included(1.0), Bound::Unbounded) < 0.1);
+        assert!(Histogram::from_sample(vec![], 10).is_none());
+    }
+}
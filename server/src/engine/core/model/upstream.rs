@@ -0,0 +1,146 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    upstream caching
+    ---
+    models created with `upstream: true` cache the model with the same name (and the same fields) on the upstream
+    instance (see `fractal::upstream`). a point `select` of a row that wasn't fetched in the last `upstream_ttl`
+    seconds fetches it from the upstream first, and a row that the upstream doesn't have is remembered as missing for
+    just as long, so while the upstream is reachable a point read is never staler than the ttl. if it can't be
+    reached, the local copy is served (with a notice) and a row that was never fetched can't be read at all.
+
+    inserts, updates and deletes are forwarded exactly as the client sent them:
+    - `upstream_writes: "through"` (the default) forwards a write first and only applies it locally once the upstream
+    took it. if the upstream rejects it, so do we. if it then fails locally (say, an update of a row that was never
+    fetched), every row is considered stale so that the next reads fetch them again
+    - `upstream_writes: "behind"` applies a write locally and queues it for the upstream, so writes don't wait on the
+    upstream (nor fail when it's down). a write that the upstream later rejects is logged and dropped, so the local
+    copy can diverge until the rows are fetched again
+
+    other reads (scans, aggregates, fetch) only see what's cached locally, and writes that don't go through a
+    statement (bulk inserts, blob uploads and eval) are rejected since they couldn't be forwarded. statements that
+    call `now()` or generate values are evaluated separately on both instances. if no upstream is configured, such
+    a model is just a local model
+*/
+
+use {crate::engine::core::index::PrimaryIndexKey, parking_lot::Mutex, std::collections::HashMap};
+
+/// The most rows whose fetch times are tracked. Once there are more, the ones that are stale anyway are forgotten
+/// (and then all of them, if that isn't enough)
+const MAX_TRACKED: usize = 1 << 20;
+
+/// How the writes to a model that caches an upstream are forwarded (see the module docs)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UpstreamWrites {
+    /// forwarded before they're applied locally, and rejected if the upstream rejects them
+    Through,
+    /// applied locally, and forwarded in the background
+    Behind,
+}
+
+impl UpstreamWrites {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Through => "through",
+            Self::Behind => "behind",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "through" => Some(Self::Through),
+            "behind" => Some(Self::Behind),
+            _ => None,
+        }
+    }
+}
+
+/// The rows of a model that were fetched from the upstream, and when (see the module docs)
+#[derive(Debug)]
+pub struct UpstreamState {
+    ttl: u64,
+    writes: UpstreamWrites,
+    /// the time (in seconds since the epoch) at which each row was last fetched
+    fetched: Mutex<HashMap<PrimaryIndexKey, u64>>,
+}
+
+impl UpstreamState {
+    pub fn new(ttl: u64, writes: UpstreamWrites) -> Self {
+        Self {
+            ttl,
+            writes,
+            fetched: Mutex::new(HashMap::new()),
+        }
+    }
+    pub fn writes(&self) -> UpstreamWrites {
+        self.writes
+    }
+    /// Returns true if the row (or the fact that the upstream doesn't have it) was fetched within the ttl
+    pub fn is_fresh(&self, key: &PrimaryIndexKey, now: u64) -> bool {
+        self.fetched
+            .lock()
+            .get(key)
+            .map_or(false, |fetched_at| now < fetched_at + self.ttl)
+    }
+    /// Note that the row was just fetched from the upstream
+    pub fn mark_fetched(&self, key: PrimaryIndexKey, now: u64) {
+        let mut fetched = self.fetched.lock();
+        if fetched.len() >= MAX_TRACKED && !fetched.contains_key(&key) {
+            let ttl = self.ttl;
+            fetched.retain(|_, fetched_at| now < *fetched_at + ttl);
+            if fetched.len() >= MAX_TRACKED {
+                fetched.clear();
+            }
+        }
+        fetched.insert(key, now);
+    }
+    /// Consider every row stale, so that they're fetched again when they're next read
+    pub fn forget_all(&self) {
+        self.fetched.lock().clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{UpstreamState, UpstreamWrites},
+        crate::engine::{core::index::PrimaryIndexKey, data::cell::Datacell},
+    };
+
+    #[test]
+    fn freshness() {
+        let state = UpstreamState::new(10, UpstreamWrites::Through);
+        let key = PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(1)).unwrap();
+        assert!(!state.is_fresh(&key, 100));
+        state.mark_fetched(key.clone(), 100);
+        assert!(state.is_fresh(&key, 109));
+        assert!(!state.is_fresh(&key, 110));
+        state.mark_fetched(key.clone(), 105);
+        assert!(state.is_fresh(&key, 110));
+        state.forget_all();
+        assert!(!state.is_fresh(&key, 106));
+    }
+}
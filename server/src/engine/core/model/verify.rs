@@ -0,0 +1,355 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    model verification
+    ---
+    a verification walks every row in the primary index (checking that it is well formed and lives in the right
+    partition) and cross-checks it against the rows that a restore would load from the model's journals. the journals
+    are replayed from a copy, so writes are never held up; however, a cross-check is only conclusive if no writes
+    were in flight while the model was verified (otherwise the report says so)
+*/
+
+use {
+    super::ModelData,
+    crate::engine::{
+        core::{index::DcFieldIndex, lock, EntityIDRef},
+        data::tag::DataTag,
+        error::{QueryError, QueryResult},
+        fractal::{
+            jobs::{self, CancelToken, Job, JobKind, JobStatus},
+            GlobalInstanceLike, Scheduler, WorkClass,
+        },
+        idx::{STIndex, STIndexSeq},
+        storage::{row_checksum, safe_interfaces::paths_v1, JournalExport, PersistedRows},
+        sync::atm::cpin,
+    },
+    std::time::Instant,
+};
+
+/// Rows are verified in runs of this size, after which we yield to other threads (and a background verification is
+/// held back if queries are running)
+const VERIFY_RUN: usize = 1024;
+
+/// The result of verifying a model (see [`verify_model`])
+#[derive(Debug, PartialEq)]
+pub struct VerifyReport {
+    rows: usize,
+    partitions: usize,
+    /// rows with a field that doesn't exist or a value that doesn't match the field's type
+    invalid_rows: usize,
+    /// rows in a partition that their key doesn't map to
+    misplaced_rows: usize,
+    checksum: u64,
+    persisted_batches: u64,
+    persisted_events: u64,
+    persisted_rows: usize,
+    persisted_checksum: u64,
+    /// rows in memory that aren't persisted
+    missing_rows: usize,
+    /// rows in memory that differ from their persisted version
+    stale_rows: usize,
+    /// persisted rows that aren't in memory
+    unexpected_rows: usize,
+    /// set if writes were in flight, in which case the persisted rows can't be compared
+    in_flight: bool,
+    rebuilt_indexes: Vec<Box<str>>,
+}
+
+impl VerifyReport {
+    /// Returns true if the rows are well formed and match the persisted rows. Returns [`None`] if the rows are well
+    /// formed, but writes were in flight
+    pub fn consistent(&self) -> Option<bool> {
+        if (self.invalid_rows != 0) | (self.misplaced_rows != 0) {
+            return Some(false);
+        }
+        if self.in_flight {
+            return None;
+        }
+        Some(
+            (self.missing_rows == 0)
+                & (self.stale_rows == 0)
+                & (self.unexpected_rows == 0)
+                & (self.checksum == self.persisted_checksum),
+        )
+    }
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+    pub fn invalid_rows(&self) -> usize {
+        self.invalid_rows
+    }
+    pub fn stale_rows(&self) -> usize {
+        self.stale_rows
+    }
+    pub fn rebuilt_indexes(&self) -> &[Box<str>] {
+        &self.rebuilt_indexes
+    }
+    /// Returns the report as a JSON object
+    pub fn describe(&self) -> String {
+        let consistent = match self.consistent() {
+            Some(consistent) => consistent.to_string(),
+            None => "null".into(),
+        };
+        let rebuilt: Vec<String> = self
+            .rebuilt_indexes
+            .iter()
+            .map(|idx| format!("\"{idx}\""))
+            .collect();
+        format!(
+            "{{\"consistent\":{consistent},\"rows\":{},\"partitions\":{},\"invalid_rows\":{},\"misplaced_rows\":{},\"checksum\":\"{:016x}\",\"persisted\":{{\"batches\":{},\"events\":{},\"rows\":{},\"checksum\":\"{:016x}\",\"missing\":{},\"stale\":{},\"unexpected\":{}}},\"in_flight\":{},\"rebuilt_indexes\":[{}]}}",
+            self.rows,
+            self.partitions,
+            self.invalid_rows,
+            self.misplaced_rows,
+            self.checksum,
+            self.persisted_batches,
+            self.persisted_events,
+            self.persisted_rows,
+            self.persisted_checksum,
+            self.missing_rows,
+            self.stale_rows,
+            self.unexpected_rows,
+            self.in_flight,
+            rebuilt.join(",")
+        )
+    }
+}
+
+/// Verify the model, optionally rebuilding its secondary indexes once done. Writes to the model are not blocked (except
+/// while an index is rebuilt), but DDL is. Since this is a full scan, it is rejected while the server is under
+/// memory pressure
+pub fn verify_model(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    rebuild: bool,
+) -> QueryResult<VerifyReport> {
+    verify(global, entity, rebuild, None).map(|report| report.unwrap())
+}
+
+/// Start a job (owned by the given user) that verifies the model (see [`verify_model`]) and return its id. The job's
+/// result is the report
+pub fn verify_model_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    entity: EntityIDRef,
+    rebuild: bool,
+) -> QueryResult<u64> {
+    global.admit_expensive_query()?;
+    let (space, model): (Box<str>, Box<str>) = (entity.space().into(), entity.entity().into());
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::VerifyModel,
+        format!("{space}.{model}").into_boxed_str(),
+        move |global, job| {
+            let entity = EntityIDRef::new(&space, &model);
+            match verify(global, entity, rebuild, Some(job)) {
+                Ok(Some(report)) => JobStatus::Completed(Some(report.describe())),
+                Ok(None) => JobStatus::Cancelled,
+                Err(e) => JobStatus::failed(e),
+            }
+        },
+    ))
+}
+
+/// Returns [`None`] if the job (if any) was cancelled
+fn verify(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    rebuild: bool,
+    job: Option<&Job>,
+) -> QueryResult<Option<VerifyReport>> {
+    global.admit_expensive_query()?;
+    let spaces = global.state().namespace().idx().read();
+    let models = global.state().namespace().idx_models().read();
+    let (Some(space), Some(model)) = (spaces.get(entity.space()), models.get(&entity)) else {
+        return Err(QueryError::QExecObjectNotFound);
+    };
+    let mdl = model.data();
+    if let Some(job) = job {
+        // every row is walked, and then indexed again by every index that is rebuilt
+        let passes = 1 + rebuild as usize * mdl.secondary_indexes().st_len();
+        job.set_total((mdl.primary_index().count() * passes) as u64);
+    }
+    let version = mdl.delta_state().data_current_version();
+    let in_flight = mdl.delta_state().pending_data_deltas() != 0;
+    // replay the journals
+    let mut persisted = PersistedRows::new();
+    for partition in 0..mdl.primary_index().partitions() {
+        let journal_path = paths_v1::model_partition_path(
+            entity.space(),
+            space.get_uuid(),
+            entity.entity(),
+            mdl.get_uuid(),
+            partition,
+        );
+        JournalExport::snapshot(model, partition, &journal_path)?.replay(&mut persisted)?;
+    }
+    // walk the index
+    let Some(mut report) = walk_rows(mdl, &mut persisted, job, global.scheduler()) else {
+        return Ok(None);
+    };
+    let (unexpected_rows, unexpected_checksum) = persisted.remaining();
+    report.unexpected_rows = unexpected_rows;
+    report.persisted_rows += unexpected_rows;
+    report.persisted_checksum = report.persisted_checksum.wrapping_add(unexpected_checksum);
+    report.persisted_batches = persisted.batches();
+    report.persisted_events = persisted.events();
+    report.in_flight |= in_flight | (mdl.delta_state().data_current_version() != version);
+    if rebuild {
+        let token = job.map_or_else(CancelToken::default, |job| job.token().clone());
+        let mut done = report.rows as u64;
+        for (name, idx) in mdl.secondary_indexes().stseq_ord_kv() {
+            let started_at = Instant::now();
+            // a cancelled rebuild leaves the index to be built again on boot
+            let built = idx.build_until(mdl, &token, |rows| {
+                if let Some(job) = job {
+                    job.set_done(done + rows as u64)
+                }
+            });
+            if !built {
+                return Ok(None);
+            }
+            done += idx.progress() as u64;
+            report.rebuilt_indexes.push(name.clone());
+            if job.is_some() {
+                // writes are let through again, so the next index can wait if queries are running
+                global.scheduler().throttle(
+                    WorkClass::Rebuild,
+                    idx.progress() as u64,
+                    started_at.elapsed(),
+                );
+            }
+        }
+    }
+    Ok(Some(report))
+}
+
+fn walk_rows(
+    mdl: &ModelData,
+    persisted: &mut PersistedRows,
+    job: Option<&Job>,
+    sched: &Scheduler,
+) -> Option<VerifyReport> {
+    let index = mdl.primary_index();
+    let partitions = index.partitions();
+    let mut report = VerifyReport {
+        rows: 0,
+        partitions,
+        invalid_rows: 0,
+        misplaced_rows: 0,
+        checksum: 0,
+        persisted_batches: 0,
+        persisted_events: 0,
+        persisted_rows: 0,
+        persisted_checksum: 0,
+        missing_rows: 0,
+        stale_rows: 0,
+        unexpected_rows: 0,
+        in_flight: false,
+        rebuilt_indexes: vec![],
+    };
+    let mut run_started_at = Instant::now();
+    for partition in 0..partitions {
+        let g = cpin();
+        for row in index.iter_partition(partition, &g) {
+            let pk = row.d_key();
+            report.misplaced_rows +=
+                ((partitions != 1) && (pk.partition(partitions) != partition)) as usize;
+            let data = match lock::read(mdl, row) {
+                Ok(data) => data,
+                // the row is being written to, so the cross-check isn't conclusive
+                Err(QueryError::SysLockWaitTimeout) => {
+                    report.in_flight = true;
+                    continue;
+                }
+                // the values of the row couldn't be read back from the overflow store
+                Err(_) => {
+                    report.invalid_rows += 1;
+                    continue;
+                }
+            };
+            report.invalid_rows += !((pk.tag() == mdl.p_tag().tag_unique())
+                && row_is_valid(mdl, data.fields())) as usize;
+            let checksum = row_checksum(
+                pk,
+                data.get_txn_revised().value_u64(),
+                data.tombstone(),
+                mdl.fields()
+                    .stseq_ord_key()
+                    .filter(|field| field.as_str() != mdl.p_key())
+                    .map(|field| data.fields().st_get(field.as_str())),
+            );
+            report.checksum = report.checksum.wrapping_add(checksum);
+            match persisted.take_checksum(pk) {
+                Some(persisted_checksum) => {
+                    report.persisted_rows += 1;
+                    report.persisted_checksum =
+                        report.persisted_checksum.wrapping_add(persisted_checksum);
+                    report.stale_rows += (persisted_checksum != checksum) as usize;
+                }
+                None => report.missing_rows += 1,
+            }
+            report.rows += 1;
+            if report.rows % VERIFY_RUN == 0 {
+                if let Some(job) = job {
+                    job.set_done(report.rows as u64);
+                    if job.token().is_cancelled() {
+                        return None;
+                    }
+                    sched.throttle(
+                        WorkClass::Scrub,
+                        VERIFY_RUN as u64,
+                        run_started_at.elapsed(),
+                    );
+                    run_started_at = Instant::now();
+                }
+                // this is a background job; let writers through
+                std::thread::yield_now();
+            }
+        }
+    }
+    if job.is_some() {
+        sched.record(
+            WorkClass::Scrub,
+            (report.rows % VERIFY_RUN) as u64,
+            run_started_at.elapsed(),
+        );
+    }
+    Some(report)
+}
+
+/// Returns true if every field in the row is a (non primary key) field of the model and holds a value of the
+/// field's type
+fn row_is_valid(mdl: &ModelData, fields: &DcFieldIndex) -> bool {
+    fields
+        .st_iter_kv()
+        .all(|(name, value)| match mdl.fields().st_get(name.as_str()) {
+            Some(field) if name.as_str() != mdl.p_key() => field.vt_data_fpath(&mut value.clone()),
+            _ => false,
+        })
+}
@@ -0,0 +1,329 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{predicate::Predicate, Field, FieldProps, Model, ModelData, ModelProps},
+    crate::engine::{
+        core::{
+            dml,
+            index::{DcFieldIndex, PrimaryIndexKey},
+            EntityID, EntityIDRef,
+        },
+        data::uuid::Uuid,
+        data::{cell::Datacell, DictEntryGeneric, DictGeneric},
+        error::{QueryError, QueryResult},
+        fractal::GlobalInstanceLike,
+        idx::{IndexBaseSpec, IndexSTSeqCns, STIndex, STIndexSeq},
+        ql::ddl::crt::CreateView,
+        txn::{gns, SpaceIDRef},
+    },
+    parking_lot::Mutex,
+    std::collections::HashMap,
+};
+
+/// The definition of a view (set by `create view`): the model that the view is derived from and the clauses that the
+/// rows of that model must satisfy to be in the view
+#[derive(Debug, PartialEq, Clone)]
+pub struct ViewDef {
+    source: Box<str>,
+    filter: Predicate,
+}
+
+impl ViewDef {
+    /// Resolve a view definition from the `view_of` and `view_filter` properties. The filter is a flat list of
+    /// `field, operator, value` triples
+    pub(super) fn new(source: &str, filter: Option<&Datacell>) -> Option<Self> {
+        let filter = match filter {
+            Some(filter) => Predicate::from_raw(filter)?,
+            None => Predicate::default(),
+        };
+        Some(Self {
+            source: source.into(),
+            filter,
+        })
+    }
+    /// Returns the name of the model that this view is derived from (in the same space)
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    /// Returns the fields of the source model that the filter uses
+    fn filter_fields(&self) -> impl Iterator<Item = &str> {
+        self.filter.fields()
+    }
+    /// Returns true if the row of the source model with the given key and fields belongs in the view
+    pub fn matches(
+        &self,
+        source: &ModelData,
+        key: &PrimaryIndexKey,
+        fields: &DcFieldIndex,
+    ) -> bool {
+        self.filter.matches(source, key, fields)
+    }
+}
+
+/// A view derived from a model, along with the fields of the model that it uses
+#[derive(Debug)]
+pub struct ViewLink {
+    name: Box<str>,
+    fields: Box<[Box<str>]>,
+}
+
+impl ViewLink {
+    fn new(name: &str, view: &ModelData) -> Self {
+        let def = view.props().view().unwrap();
+        let fields = view
+            .fields()
+            .stseq_ord_key()
+            .map(|field| field.as_str())
+            .chain(def.filter_fields())
+            .map(Into::into)
+            .collect();
+        Self {
+            name: name.into(),
+            fields,
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The views derived from a model, and the keys of the rows that have changed since the views were last refreshed
+#[derive(Debug, Default)]
+pub struct ModelViews {
+    links: Vec<ViewLink>,
+    pending: Mutex<Vec<PrimaryIndexKey>>,
+}
+
+impl ModelViews {
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+    pub fn links(&self) -> &[ViewLink] {
+        &self.links
+    }
+    /// Returns true if any view uses the given field (in its fields or its filter)
+    pub fn uses_field(&self, field: &str) -> bool {
+        self.links
+            .iter()
+            .any(|link| link.fields.iter().any(|f| f.as_ref() == field))
+    }
+    fn link(&mut self, link: ViewLink) {
+        self.links.push(link);
+    }
+    pub(in crate::engine::core) fn unlink(&mut self, name: &str) {
+        self.links.retain(|link| link.name() != name)
+    }
+    /// Record a change to the row with the given key, so that the views are refreshed once the query is done
+    pub(in crate::engine::core) fn touch(&self, key: &PrimaryIndexKey) {
+        if !self.links.is_empty() {
+            self.pending.lock().push(key.clone());
+        }
+    }
+    pub(in crate::engine::core) fn take_pending(&self) -> Vec<PrimaryIndexKey> {
+        core::mem::take(&mut *self.pending.lock())
+    }
+}
+
+/// Link all views to the models that they are derived from. This is needed once all models have been restored
+pub fn link_views(models: &mut HashMap<EntityID, Model>) {
+    let views: Vec<(EntityID, Box<str>, ViewLink)> = models
+        .iter()
+        .filter_map(|(id, model)| {
+            let def = model.data().props().view()?;
+            Some((
+                EntityID::new(id.space(), id.entity()),
+                def.source().into(),
+                ViewLink::new(id.entity(), model.data()),
+            ))
+        })
+        .collect();
+    for (view_id, source, link) in views {
+        if let Some(model) = models.get_mut(&EntityIDRef::new(view_id.space(), &source)) {
+            model.data_mut().views.link(link);
+        }
+    }
+}
+
+impl ModelData {
+    /// Create the model that backs a view: the selected fields (always including the primary key) of the source
+    /// model, with the source model's primary index
+    fn new_view(
+        source: &ModelData,
+        select: &mut crate::engine::ql::dml::sel::SelectStatement,
+    ) -> QueryResult<Self> {
+        if select.as_of().is_some()
+            | select.with_deleted()
+            | select.take_keys().is_some()
+            | select.take_projection().is_some()
+        {
+            return Err(QueryError::QExecDdlModelBadDefinition);
+        }
+        let field_copy = |name: &str| {
+            source
+                .fields()
+                .st_get(name)
+                .map(|field| {
+                    Field::new_with_props(
+                        field.layers.clone(),
+                        field.is_nullable(),
+                        FieldProps::default(),
+                    )
+                })
+                .ok_or(QueryError::QExecUnknownField)
+        };
+        let mut fields = IndexSTSeqCns::idx_init();
+        fields.st_insert(
+            Box::<str>::from(source.p_key()),
+            field_copy(source.p_key())?,
+        );
+        if select.is_wildcard() {
+            for name in source.fields().stseq_ord_key() {
+                fields.st_insert(Box::<str>::from(name.as_str()), field_copy(name.as_str())?);
+            }
+        } else {
+            for name in select.fields() {
+                fields.st_insert(Box::<str>::from(name.as_str()), field_copy(name.as_str())?);
+            }
+        }
+        let filter = Predicate::raw_from_where(source, select.clauses_mut().clauses_mut())?;
+        let mut props = DictGeneric::new();
+        let mut set = |key: &str, dc| {
+            props.insert(key.into(), DictEntryGeneric::Data(dc));
+        };
+        set(
+            ModelProps::KEY_VIEW_OF,
+            Datacell::new_str(select.entity().entity().into()),
+        );
+        if !filter.is_empty() {
+            set(ModelProps::KEY_VIEW_FILTER, Datacell::new_list(filter));
+        }
+        set(
+            ModelProps::KEY_INDEX,
+            Datacell::new_str(source.props().index().name().into()),
+        );
+        if source.props().raw().contains_key(ModelProps::KEY_HASHER) {
+            set(
+                ModelProps::KEY_HASHER,
+                Datacell::new_str(source.props().hasher().name().into()),
+            );
+        }
+        // a `matches` clause is not supported in views
+        let props = ModelProps::try_new(props).ok_or(QueryError::QExecDdlModelBadDefinition)?;
+        Ok(Self::new_restore_with_props(
+            Uuid::new(),
+            source.p_key().into(),
+            source.p_tag(),
+            fields,
+            props,
+        ))
+    }
+    pub fn transactional_exec_create_view<G: GlobalInstanceLike>(
+        global: &G,
+        CreateView {
+            view_name,
+            mut select,
+            if_not_exists,
+        }: CreateView,
+    ) -> QueryResult<Option<bool>> {
+        let (space_name, view_name) = (view_name.space(), view_name.entity());
+        // a view is derived from a model in the same space
+        if select.entity().space() != space_name {
+            return Err(QueryError::QExecDdlModelBadDefinition);
+        }
+        let source_name = select.entity().entity();
+        global
+            .state()
+            .namespace()
+            .ddl_with_space_mut(&space_name, |space| {
+                global.state().freeze().check(space_name)?;
+                if space.models().contains(view_name) {
+                    if if_not_exists {
+                        return Ok(Some(false));
+                    } else {
+                        return Err(QueryError::QExecDdlObjectAlreadyExists);
+                    }
+                }
+                // get exclusive lock on models; no rows can change until the view is ready
+                let mut models_idx = global.state().namespace().idx_models().write();
+                let Some(source) = models_idx.get(&EntityIDRef::new(&space_name, &source_name))
+                else {
+                    return Err(QueryError::QExecObjectNotFound);
+                };
+                // views of views are not supported
+                if source.data().props().view().is_some() {
+                    return Err(QueryError::QExecDdlModelBadDefinition);
+                }
+                let view = Self::new_view(source.data(), &mut select)?;
+                // prepare txn
+                let txn = gns::model::CreateModelTxn::new(
+                    SpaceIDRef::new(&space_name, &space),
+                    &view_name,
+                    &view,
+                );
+                // attempt to initialize driver
+                let mdl_driver = global.initialize_model_driver(
+                    &space_name,
+                    space.get_uuid(),
+                    &view_name,
+                    view.get_uuid(),
+                    view.primary_index().partitions(),
+                )?;
+                // commit txn
+                global.state().gns_driver().driver_context(
+                    global,
+                    |drv| drv.commit_event(txn),
+                    || {
+                        global.purge_model_driver(
+                            &space_name,
+                            space.get_uuid(),
+                            &view_name,
+                            view.get_uuid(),
+                        )
+                    },
+                )?;
+                // fill the view with the rows of the source model
+                dml::fill_view(&view, source.data());
+                // update global state
+                models_idx
+                    .get_mut(&EntityIDRef::new(&space_name, &source_name))
+                    .unwrap()
+                    .data_mut()
+                    .views
+                    .link(ViewLink::new(&view_name, &view));
+                let _ = space.models_mut().insert(view_name.into());
+                let _ = models_idx.insert(
+                    EntityID::new(&space_name, &view_name),
+                    Model::new(view, mdl_driver),
+                );
+                if if_not_exists {
+                    Ok(Some(true))
+                } else {
+                    Ok(None)
+                }
+            })
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    warmup
+    ---
+    after a restart, every read goes to the cold path until the working set is touched again: upstream rows have to
+    be fetched (their freshness isn't persisted), tiered blobs have to come back from the bucket and rows have to have
+    their schema deltas resolved. to avoid that, the hot keys of every model (see `hotkeys`) are saved to the warmup
+    file when the server shuts down. on boot, they're loaded back into the sketches (so that the access history
+    survives the restart) and, if `system.warmup` is enabled, the hottest rows are touched before the server reports
+    that it's ready. warming is bounded (by WARM_MAX rows and WARM_DEADLINE) and a row that can't be warmed is
+    skipped, since the server serves it just the same (only slower)
+*/
+
+use {
+    crate::engine::{
+        core::{dml, index::PrimaryIndexKey, lock, EntityIDRef},
+        data::tag::TagClass,
+        error::{ErrorKind, QueryError, StorageError},
+        fractal::{self, tier, GlobalInstanceLike},
+        idx::STIndex,
+        storage::WarmupFile,
+        sync, RuntimeResult,
+    },
+    std::time::{Duration, Instant},
+};
+
+/// The most rows that are warmed on boot
+const WARM_MAX: usize = 4096;
+/// Warming stops after this long, even if there are rows left
+const WARM_DEADLINE: Duration = Duration::from_secs(60);
+
+/// The hot keys of a model, hottest first, with the estimated number of accesses of each
+#[derive(Debug, PartialEq)]
+pub struct HotModel {
+    pub space: Box<str>,
+    pub model: Box<str>,
+    pub keys: Vec<(PrimaryIndexKey, u64)>,
+}
+
+impl HotModel {
+    /// The estimated number of accesses of all the hot keys of the model
+    pub fn accesses(&self) -> u64 {
+        self.keys.iter().map(|(_, estimate)| estimate).sum()
+    }
+}
+
+/// Returns the hot keys of every model, hottest model first
+pub fn collect(global: &impl GlobalInstanceLike) -> Vec<HotModel> {
+    let models = global.state().namespace().idx_models().read();
+    let mut hot: Vec<HotModel> = models
+        .iter()
+        .map(|(entity, model)| HotModel {
+            space: entity.space().into(),
+            model: entity.entity().into(),
+            keys: model.data().hot_keys().top(),
+        })
+        .filter(|model| !model.keys.is_empty())
+        .collect();
+    hot.sort_by(|a, b| b.accesses().cmp(&a.accesses()));
+    hot
+}
+
+/// Save the hot keys of every model to the warmup file at `path`
+pub fn save(global: &impl GlobalInstanceLike, path: &str) -> RuntimeResult<()> {
+    WarmupFile::write(path, &collect(global))
+}
+
+/// Load the hot keys in the warmup file at `path` (if there is one). A file that is corrupted (or from another
+/// version) is ignored, since warming isn't worth refusing to boot over
+pub fn load(path: &str) -> RuntimeResult<Vec<HotModel>> {
+    WarmupFile::read(path).or_else(|e| match e.kind() {
+        ErrorKind::Storage(
+            StorageError::WarmupFileCorrupted | StorageError::HeaderDecodeVersionMismatch,
+        ) => {
+            warn!("ignoring the warmup file {path}, so no rows will be warmed: {e}");
+            Ok(vec![])
+        }
+        _ => Err(e),
+    })
+}
+
+/// Load the saved hot keys back into the sketches of their models. Models that were dropped since are skipped
+pub fn seed(global: &impl GlobalInstanceLike, models: &[HotModel]) {
+    for hot in models {
+        let entity = EntityIDRef::new(&hot.space, &hot.model);
+        let _ = global.state().namespace().with_model(entity, |mdl| {
+            mdl.hot_keys().seed(&hot.keys);
+            Ok(())
+        });
+    }
+}
+
+/// Warm the rows of the saved hot keys, hottest first, returning the number of rows that were warmed. This blocks
+/// (on the upstream and the bucket), so it should be run on a blocking thread
+pub fn warm(global: &impl GlobalInstanceLike, models: &[HotModel]) -> usize {
+    let deadline = Instant::now() + WARM_DEADLINE;
+    let mut keys: Vec<(EntityIDRef, &PrimaryIndexKey, u64)> = models
+        .iter()
+        .flat_map(|hot| {
+            let entity = EntityIDRef::new(&hot.space, &hot.model);
+            hot.keys.iter().map(move |(key, e)| (entity, key, *e))
+        })
+        .collect();
+    keys.sort_by(|a, b| b.2.cmp(&a.2));
+    let mut warmed = 0;
+    for (entity, key, _) in keys.into_iter().take(WARM_MAX) {
+        if Instant::now() >= deadline {
+            warn!("warmup: stopped after {WARM_DEADLINE:?} with {warmed} rows warmed");
+            break;
+        }
+        if warm_row(global, entity, key) {
+            warmed += 1;
+        }
+    }
+    warmed
+}
+
+/// Warm a single row, returning true if it exists
+fn warm_row(global: &impl GlobalInstanceLike, entity: EntityIDRef, key: &PrimaryIndexKey) -> bool {
+    if let (Some(upstream), Ok(Some(fetch))) = (
+        fractal::upstream::get(),
+        dml::upstream_fetch_key(global, entity, key),
+    ) {
+        let _ = match upstream.run(None, fetch.query.as_bytes(), &fetch.params) {
+            Ok(Ok(r)) => match fractal::upstream::decode_row(&r) {
+                Some(row) => dml::upstream_refresh(global, entity, fetch, Some(row)),
+                None => Ok(()),
+            },
+            Ok(Err(code)) if code == QueryError::QExecDmlRowNotFound.value_u8() as u16 => {
+                dml::upstream_refresh(global, entity, fetch, None)
+            }
+            // the local copy (if any) is still warmed below
+            _ => Ok(()),
+        };
+    }
+    let refs = global.state().namespace().with_model(entity, |mdl| {
+        let g = sync::atm::cpin();
+        let Some(row) = mdl.primary_index().select_key(key, &g) else {
+            return Ok(None);
+        };
+        let data = lock::read(mdl, row)?;
+        if data.is_tombstoned() {
+            return Ok(None);
+        }
+        let tier = tier::get();
+        let refs: Vec<Box<[u8]>> = data
+            .fields()
+            .st_iter_value()
+            .filter(|dc| !dc.is_null() && dc.kind() == TagClass::Bin)
+            .filter(|dc| tier.map_or(false, |t| t.is_ref(dc.bin())))
+            .map(|dc| dc.bin().into())
+            .collect();
+        Ok(Some(refs))
+    });
+    match refs {
+        Ok(Some(refs)) => {
+            // fetched outside the model, since the bucket might be slow
+            for r in refs {
+                let _ = tier::resolve(&r);
+            }
+            true
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,549 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    change event webhooks
+    ---
+    `sysctl create webhook <name> on <model> with { url: ..., secret: ..., batch: ... }` POSTs the changes committed
+    to the model's journal to the url (see `fractal::webhook` for the transport), in the order they were committed:
+
+    - only changes made after the webhook was created are delivered, and only once they've been written to disk.
+    the model's pending deltas are flushed when the webhook is created, so nothing that was written before that is
+    ever sent
+    - up to `batch` events (100 by default) are sent in a request, but the events of a journal batch are never split
+    across requests. a request is retried until it's acknowledged, so events are delivered at least once
+    - the LSN of the last batch that was delivered is kept in a cursor file in the model's directory, so deliveries
+    resume where they left off after a restart
+    - only models with a single partition can have webhooks, since every partition has its own journal (and sequence
+    of LSNs)
+
+    a webhook with a `kafka[s]://[<user>@]<host>:<port>[,<host>:<port>...]/<topic>[?sasl=<mechanism>]` url publishes
+    every event as a record to the topic instead (see `fractal::kafka`), with the same batching, cursor and retries.
+    kafka sinks aren't signed: their secret is the SASL password of the user instead
+
+    webhooks are kept in the GNS like secondary indexes are, but aren't part of GNS exports since their endpoints
+    (and secrets) belong to this instance. they're dropped along with their model
+*/
+
+use {
+    super::Model,
+    crate::engine::{
+        core::{EntityID, EntityIDRef},
+        data::{tag::TagClass, uuid::Uuid, DictEntryGeneric, DictGeneric},
+        error::{QueryError, QueryResult},
+        fractal::{
+            kafka::KafkaEndpoint,
+            webhook::{self, WebhookEndpoint},
+            GlobalInstanceLike, ModelUniqueID,
+        },
+        idx::{STIndex, STIndexSeq},
+        storage::{
+            encode_str,
+            safe_interfaces::{paths_v1, FileSystem},
+            JournalExport, WebhookCursor,
+        },
+        txn::{gns, ModelIDRef},
+    },
+    parking_lot::Mutex,
+    std::{
+        fmt::Write,
+        io,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// The most events sent in a request, unless the webhook says otherwise
+const BATCH_DEFAULT: u64 = 100;
+/// The most events that a webhook can ask for in a request
+const BATCH_MAX: u64 = 10_000;
+/// The longest we wait before retrying a failed request
+const BACKOFF_MAX: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+/// A webhook that a model's changes are delivered to
+pub struct Webhook {
+    endpoint: Endpoint,
+    url: Box<str>,
+    /// empty if requests aren't signed (or the SASL password, for a kafka sink)
+    secret: Box<str>,
+    batch: u64,
+    delivery: Mutex<Delivery>,
+    /// the journal bytes written since the webhook last delivered everything (see [`Webhook::lag`])
+    lag: AtomicU64,
+}
+
+#[derive(Debug)]
+/// Where a webhook's events go
+enum Endpoint {
+    Http(WebhookEndpoint),
+    Kafka(KafkaEndpoint),
+}
+
+#[derive(Debug, Default)]
+/// How far the deliveries of a webhook got. Only the LSN is kept on disk
+struct Delivery {
+    /// set once the LSN was read from the cursor file
+    loaded: bool,
+    /// the LSN of the last batch that was delivered
+    lsn: Option<u64>,
+    /// the length of the journal when everything in it was last delivered
+    journal_len: u64,
+    /// the length of the journal when the webhook last caught up (or was first seen since the server started)
+    caught_up: Option<u64>,
+    /// the events delivered since the server started
+    delivered: u64,
+    /// the requests that failed in a row
+    failures: u32,
+    retry_at: Option<Instant>,
+    last_error: Option<String>,
+}
+
+impl Webhook {
+    /// Returns a webhook for the given url, or [`None`] if the url or batch size is invalid (or a kafka sink's
+    /// brokers can't be connected to as configured)
+    pub fn new(url: &str, secret: &str, batch: u64) -> Option<Self> {
+        let endpoint = if KafkaEndpoint::is_kafka(url) {
+            Endpoint::Kafka(KafkaEndpoint::parse(url, secret)?)
+        } else {
+            Endpoint::Http(WebhookEndpoint::parse(url)?)
+        };
+        if !(1..=BATCH_MAX).contains(&batch) {
+            return None;
+        }
+        Some(Self {
+            endpoint,
+            url: url.into(),
+            secret: secret.into(),
+            batch,
+            delivery: Mutex::new(Delivery::default()),
+            lag: AtomicU64::new(0),
+        })
+    }
+    /// Returns how far (in journal bytes) the webhook was behind the last time deliveries were checked. Writes to a
+    /// model whose webhooks fall too far behind are throttled (see [`crate::engine::fractal::throttle`])
+    pub fn lag(&self) -> u64 {
+        self.lag.load(Ordering::Relaxed)
+    }
+    /// Send the events (JSON lines) of the given model to the endpoint
+    fn send(&self, name: &str, model: &str, events: &[&str]) -> io::Result<()> {
+        match &self.endpoint {
+            Endpoint::Http(endpoint) => {
+                let mut body = String::from("{\"webhook\":");
+                encode_str(&mut body, name);
+                body.push_str(",\"model\":");
+                encode_str(&mut body, model);
+                body.push_str(",\"events\":[");
+                body.push_str(&events.join(","));
+                body.push_str("]}");
+                webhook::post(endpoint, &body, &self.secret)
+            }
+            Endpoint::Kafka(endpoint) => endpoint.produce(events),
+        }
+    }
+}
+
+/// Create a webhook on the given model (see the module docs)
+pub fn create_webhook(
+    global: &impl GlobalInstanceLike,
+    name: &str,
+    entity: EntityIDRef,
+    mut props: DictGeneric,
+) -> QueryResult<()> {
+    let url = take_str(&mut props, "url")?.ok_or(QueryError::QExecDdlInvalidProperties)?;
+    let secret = take_str(&mut props, "secret")?.unwrap_or_default();
+    let batch = match props.remove("batch") {
+        None => BATCH_DEFAULT,
+        Some(DictEntryGeneric::Data(d)) if d.kind() == TagClass::UnsignedInt => d.uint(),
+        Some(_) => return Err(QueryError::QExecDdlInvalidProperties),
+    };
+    if !props.is_empty() {
+        return Err(QueryError::QExecDdlInvalidProperties);
+    }
+    let Some(webhook) = Webhook::new(&url, &secret, batch) else {
+        return Err(QueryError::QExecDdlInvalidProperties);
+    };
+    let (space_name, model_name) = (entity.space(), entity.entity());
+    let mut models = global.state().namespace().idx_models().write();
+    let Some(model) = models.get_mut(&entity) else {
+        return Err(QueryError::QExecObjectNotFound);
+    };
+    let spaces = global.state().namespace().idx().read();
+    let space = spaces.get(space_name).unwrap();
+    global.state().freeze().check(space_name)?;
+    if model.data().webhooks().st_contains(name) {
+        return Err(QueryError::QExecDdlObjectAlreadyExists);
+    }
+    if model.data().primary_index().partitions() != 1 {
+        return Err(QueryError::QExecDdlInvalidProperties);
+    }
+    // we hold the model index, so no DML can run until the webhook is in place
+    let model_uuid = model.data().get_uuid();
+    model.driver().flush(
+        global,
+        ModelUniqueID::new(space_name, model_name, model_uuid),
+        model.data(),
+    )?;
+    let journal_path = paths_v1::model_path(space_name, space.get_uuid(), model_name, model_uuid);
+    let cursor_path = WebhookCursor::path(
+        &paths_v1::model_dir(space_name, space.get_uuid(), model_name, model_uuid),
+        name,
+    );
+    let lsn = JournalExport::snapshot(model, 0, &journal_path)?
+        .summarize()?
+        .last_lsn();
+    match lsn {
+        Some(lsn) => WebhookCursor::write(&cursor_path, lsn)?,
+        // a webhook with the same name could have left its cursor behind
+        None => WebhookCursor::remove(&cursor_path)?,
+    }
+    let txn = gns::model::CreateWebhookTxn::new(
+        ModelIDRef::new_ref(space_name, space, model_name, model.data()),
+        name,
+        &url,
+        &secret,
+        batch,
+    );
+    let gns_driver = global.state().gns_driver();
+    if let Err(e) = gns_driver.driver_context(global, |drv| drv.commit_event(txn), || {}) {
+        let _ = WebhookCursor::remove(&cursor_path);
+        return Err(e);
+    }
+    {
+        let mut delivery = webhook.delivery.lock();
+        delivery.loaded = true;
+        delivery.lsn = lsn;
+    }
+    let _ = model.data_mut().add_webhook(name.into(), Arc::new(webhook));
+    Ok(())
+}
+
+/// Drop a webhook from the given model. Changes that weren't delivered yet are never delivered
+pub fn drop_webhook(
+    global: &impl GlobalInstanceLike,
+    name: &str,
+    entity: EntityIDRef,
+) -> QueryResult<()> {
+    let (space_name, model_name) = (entity.space(), entity.entity());
+    global
+        .state()
+        .namespace()
+        .with_model_space_mut_for_ddl(entity, |space, model| {
+            global.state().freeze().check(space_name)?;
+            if !model.webhooks().st_contains(name) {
+                return Err(QueryError::QExecObjectNotFound);
+            }
+            // prepare txn
+            let txn = gns::model::DropWebhookTxn::new(
+                ModelIDRef::new_ref(space_name, space, model_name, model),
+                name,
+            );
+            // commit txn
+            global.state().gns_driver().driver_context(
+                global,
+                |drv| drv.commit_event(txn),
+                || {},
+            )?;
+            // update global state
+            let _ = model.remove_webhook(name);
+            let cursor_path = WebhookCursor::path(
+                &paths_v1::model_dir(space_name, space.get_uuid(), model_name, model.get_uuid()),
+                name,
+            );
+            if let Err(e) = WebhookCursor::remove(&cursor_path) {
+                // the self-check will report it
+                warn!("failed to remove the cursor of dropped webhook `{name}`: {e}");
+            }
+            Ok(())
+        })
+}
+
+/// Returns every webhook (and how far its deliveries got) as a JSON array. Secrets are never included
+pub fn describe_webhooks(global: &impl GlobalInstanceLike) -> String {
+    let mut out = String::from("[");
+    let models = global.state().namespace().idx_models().read();
+    for (entity, model) in models.iter() {
+        for (name, webhook) in model.data().webhooks().stseq_ord_kv() {
+            if out.len() != 1 {
+                out.push(',');
+            }
+            let delivery = webhook.delivery.lock();
+            out.push_str("{\"model\":");
+            encode_str(&mut out, &format!("{}.{}", entity.space(), entity.entity()));
+            out.push_str(",\"name\":");
+            encode_str(&mut out, name);
+            out.push_str(",\"url\":");
+            encode_str(&mut out, &webhook.url);
+            let _ = write!(
+                out,
+                ",\"batch\":{},\"signed\":{},\"lsn\":{},\"delivered\":{},\"failures\":{},\"lag\":{},\"last_error\":",
+                webhook.batch,
+                !webhook.secret.is_empty() & matches!(webhook.endpoint, Endpoint::Http(_)),
+                delivery
+                    .lsn
+                    .map_or_else(|| "null".into(), |lsn| lsn.to_string()),
+                delivery.delivered,
+                delivery.failures,
+                webhook.lag(),
+            );
+            match &delivery.last_error {
+                Some(e) => encode_str(&mut out, e),
+                None => out.push_str("null"),
+            }
+            out.push('}');
+        }
+    }
+    out.push(']');
+    out
+}
+
+/// The webhooks of a model that have something to deliver
+struct Pending {
+    entity: EntityID,
+    model_uuid: Uuid,
+    model_dir: String,
+    journal_len: u64,
+    webhooks: Vec<(Box<str>, Arc<Webhook>)>,
+    export: JournalExport,
+}
+
+/// Deliver the changes written since the last delivery to every webhook that isn't waiting to retry a request
+pub fn deliver(global: &impl GlobalInstanceLike) {
+    let pending = {
+        let models = global.state().namespace().idx_models().read();
+        let spaces = global.state().namespace().idx().read();
+        models
+            .iter()
+            .filter(|(_, model)| model.data().webhooks().st_len() != 0)
+            .filter_map(|(entity, model)| {
+                let space = spaces.get(entity.space())?;
+                prepare(entity, space.get_uuid(), model)
+            })
+            .collect::<Vec<_>>()
+    };
+    for Pending {
+        entity,
+        model_uuid,
+        model_dir,
+        journal_len,
+        webhooks,
+        export,
+    } in pending
+    {
+        let since = webhooks
+            .iter()
+            .map(|(_, webhook)| webhook.delivery.lock().lsn)
+            .min()
+            .flatten();
+        let batches = match export.decode_batches(since) {
+            Ok(batches) => batches,
+            Err(e) => {
+                warn!(
+                    "webhooks on {}.{}: failed to read the journal: {e}",
+                    entity.space(),
+                    entity.entity()
+                );
+                continue;
+            }
+        };
+        for (name, webhook) in webhooks {
+            let target = Target {
+                entity: &entity,
+                model_uuid,
+                cursor_path: WebhookCursor::path(&model_dir, &name),
+                name: &name,
+                webhook: &webhook,
+            };
+            if target.deliver(global, &batches) {
+                let mut delivery = webhook.delivery.lock();
+                delivery.journal_len = journal_len;
+                delivery.caught_up = Some(journal_len);
+                webhook.lag.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Find the webhooks of the model that are due, and copy its journal if any of them has something to deliver
+fn prepare(entity: &EntityID, space_uuid: Uuid, model: &Model) -> Option<Pending> {
+    if model.data().primary_index().partitions() != 1 {
+        return None;
+    }
+    let model_uuid = model.data().get_uuid();
+    let model_dir = paths_v1::model_dir(entity.space(), space_uuid, entity.entity(), model_uuid);
+    let journal_path =
+        paths_v1::model_path(entity.space(), space_uuid, entity.entity(), model_uuid);
+    let journal_len = FileSystem::file_len(&journal_path).ok()?;
+    let now = Instant::now();
+    let mut webhooks = vec![];
+    for (name, webhook) in model.data().webhooks().stseq_ord_kv() {
+        let mut delivery = webhook.delivery.lock();
+        // a journal that was compacted since is caught up to from where it is now
+        let caught_up = match delivery.caught_up {
+            Some(len) if len <= journal_len => len,
+            _ => *delivery.caught_up.insert(journal_len),
+        };
+        webhook
+            .lag
+            .store(journal_len - caught_up, Ordering::Relaxed);
+        if delivery.retry_at.is_some_and(|at| at > now) {
+            continue;
+        }
+        if !delivery.loaded {
+            match WebhookCursor::read(&WebhookCursor::path(&model_dir, name)) {
+                Ok(lsn) => {
+                    delivery.loaded = true;
+                    delivery.lsn = lsn;
+                }
+                Err(e) => {
+                    record_failure(entity, name, &mut delivery, e.to_string());
+                    continue;
+                }
+            }
+        }
+        // skip it if nothing was written since it delivered everything
+        if delivery.journal_len != journal_len {
+            webhooks.push((name.clone(), webhook.clone()));
+        }
+    }
+    if webhooks.is_empty() {
+        return None;
+    }
+    let export = match JournalExport::snapshot(model, 0, &journal_path) {
+        Ok(export) => export,
+        Err(e) => {
+            warn!(
+                "webhooks on {}.{}: failed to copy the journal: {e}",
+                entity.space(),
+                entity.entity()
+            );
+            return None;
+        }
+    };
+    Some(Pending {
+        entity: EntityID::new(entity.space(), entity.entity()),
+        model_uuid,
+        model_dir,
+        journal_len,
+        webhooks,
+        export,
+    })
+}
+
+/// A webhook that batches are delivered to
+struct Target<'a> {
+    entity: &'a EntityID,
+    model_uuid: Uuid,
+    cursor_path: String,
+    name: &'a str,
+    webhook: &'a Arc<Webhook>,
+}
+
+impl<'a> Target<'a> {
+    /// Deliver the batches that the webhook doesn't have yet, in requests of up to `batch` events (but never
+    /// splitting a batch). Returns true if everything was delivered
+    fn deliver(&self, global: &impl GlobalInstanceLike, batches: &[(u64, String)]) -> bool {
+        let model = format!("{}.{}", self.entity.space(), self.entity.entity());
+        let since = self.webhook.delivery.lock().lsn;
+        let mut batches = batches
+            .iter()
+            .filter(|(lsn, _)| since.map_or(true, |since| *lsn > since))
+            .peekable();
+        while batches.peek().is_some() {
+            let (mut events, mut last_lsn) = (vec![], 0);
+            while let Some((lsn, lines)) = batches.peek() {
+                let count = lines.lines().count() as u64;
+                if !events.is_empty() & (events.len() as u64 + count > self.webhook.batch) {
+                    break;
+                }
+                events.extend(lines.lines());
+                last_lsn = *lsn;
+                batches.next();
+            }
+            if let Err(e) = self.webhook.send(self.name, &model, &events) {
+                self.fail(&mut self.webhook.delivery.lock(), e.to_string());
+                return false;
+            }
+            // the webhook (or its model) could have been dropped (or even created again) while we were sending
+            let models = global.state().namespace().idx_models().read();
+            let is_current = models
+                .get(&EntityIDRef::new(self.entity.space(), self.entity.entity()))
+                .filter(|model| model.data().get_uuid() == self.model_uuid)
+                .and_then(|model| model.data().webhooks().st_get(self.name))
+                .is_some_and(|webhook| Arc::ptr_eq(webhook, self.webhook));
+            if !is_current {
+                return false;
+            }
+            let mut delivery = self.webhook.delivery.lock();
+            if let Err(e) = WebhookCursor::write(&self.cursor_path, last_lsn) {
+                // the events will be delivered again
+                self.fail(&mut delivery, e.to_string());
+                return false;
+            }
+            if delivery.failures != 0 {
+                info!(
+                    "webhook `{}` on {model}: delivering again after {} failed request(s)",
+                    self.name, delivery.failures
+                );
+            }
+            delivery.lsn = Some(last_lsn);
+            delivery.delivered += events.len() as u64;
+            delivery.failures = 0;
+            delivery.retry_at = None;
+            delivery.last_error = None;
+        }
+        true
+    }
+    fn fail(&self, delivery: &mut Delivery, error: String) {
+        record_failure(self.entity, self.name, delivery, error)
+    }
+}
+
+/// Record a failed delivery, backing off exponentially before the next attempt
+fn record_failure(entity: &EntityID, name: &str, delivery: &mut Delivery, error: String) {
+    delivery.failures = delivery.failures.saturating_add(1);
+    let backoff = Duration::from_secs(1 << (delivery.failures - 1).min(16)).min(BACKOFF_MAX);
+    delivery.retry_at = Some(Instant::now() + backoff);
+    if delivery.failures == 1 {
+        warn!(
+            "webhook `{name}` on {}.{}: delivery failed: `{error}`. will try again later",
+            entity.space(),
+            entity.entity()
+        );
+    }
+    delivery.last_error = Some(error);
+}
+
+fn take_str(props: &mut DictGeneric, key: &str) -> QueryResult<Option<String>> {
+    match props.remove(key) {
+        None => Ok(None),
+        Some(DictEntryGeneric::Data(d)) if d.kind() == TagClass::Str => Ok(Some(d.str().into())),
+        Some(_) => Err(QueryError::QExecDdlInvalidProperties),
+    }
+}
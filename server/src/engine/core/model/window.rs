@@ -0,0 +1,182 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    window counters
+    ---
+    a field declared as `window_counter { window: <seconds> }` (the window is a minute, unless set otherwise) counts
+    events over a sliding window. there's no separate type: the counter is a list of unsigned integers (and the field
+    is a `list { type: uint64 }` with the `window` property), holding the width of a bucket in seconds followed by a
+    `start, count` pair for every bucket that has events in it, oldest first. the window is split into at most
+    BUCKETS buckets, and recording events drops the buckets that have left the window before merging the events into
+    the current bucket, so a counter never holds more than BUCKETS + 1 buckets, however many events it records.
+
+    - `insert` takes the number of events to record (`0` for none)
+    - `update ... set f += n` records `n` events, and `update ... set f = n` starts over with `n` events
+    - `window_count(f, seconds)` returns the number of events in the last `seconds` (at most the window). it's only
+    as precise as the buckets: a bucket is counted if any part of it falls in the last `seconds`
+*/
+
+use crate::engine::data::cell::Datacell;
+
+/// The definition of a window counter field (see the module docs)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WindowCounter {
+    window: u64,
+}
+
+impl WindowCounter {
+    /// the name used in place of a type to declare a window counter
+    pub const TYPE: &'static str = "window_counter";
+    /// the maximum number of buckets that the window is split into
+    pub const BUCKETS: u64 = 60;
+    /// the window is a minute, unless set otherwise
+    pub const DEFAULT_WINDOW: u64 = 60;
+    /// Returns [`None`] if the window is empty
+    pub fn new(window: u64) -> Option<Self> {
+        (window != 0).then_some(Self { window })
+    }
+    /// Returns the length of the window, in seconds
+    pub fn window(&self) -> u64 {
+        self.window
+    }
+    /// Returns the width of a bucket, in seconds
+    fn width(&self) -> u64 {
+        (self.window + Self::BUCKETS - 1) / Self::BUCKETS
+    }
+    /// Returns a counter with `n` events recorded at `now`
+    pub fn start(&self, n: u64, now: u64) -> Datacell {
+        self.record(&Datacell::null(), n, now)
+    }
+    /// Replace the number of events given to an insert with a counter holding them. Returns false if the value isn't
+    /// a number of events (a null is left as it is)
+    pub fn prepare_insert(&self, data: &mut Datacell, now: u64) -> bool {
+        if data.is_null() {
+            return true;
+        }
+        match data.try_uint() {
+            Some(n) => {
+                *data = self.start(n, now);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Returns the given counter (which can be null) with `n` more events recorded at `now`
+    pub fn record(&self, counter: &Datacell, n: u64, now: u64) -> Datacell {
+        let width = self.width();
+        let mut buckets = match read(counter) {
+            Some((w, buckets)) if w == width => buckets,
+            _ => vec![],
+        };
+        let since = now.saturating_sub(self.window);
+        buckets.retain(|(start, _)| start + width > since);
+        let current = now - now % width;
+        match buckets.last_mut() {
+            Some((start, count)) if *start == current => *count = count.saturating_add(n),
+            _ if n != 0 => buckets.push((current, n)),
+            _ => {}
+        }
+        let mut ring = Vec::with_capacity(1 + buckets.len() * 2);
+        ring.push(Datacell::new_uint_default(width));
+        for (start, count) in buckets {
+            ring.push(Datacell::new_uint_default(start));
+            ring.push(Datacell::new_uint_default(count));
+        }
+        Datacell::new_list(ring)
+    }
+    /// Returns the number of events that the given counter recorded in the last `secs` seconds (before `now`)
+    pub fn count(counter: &Datacell, secs: u64, now: u64) -> u64 {
+        let Some((width, buckets)) = read(counter) else {
+            return 0;
+        };
+        let since = now.saturating_sub(secs);
+        buckets
+            .into_iter()
+            .filter(|(start, _)| start + width > since)
+            .fold(0u64, |total, (_, count)| total.saturating_add(count))
+    }
+}
+
+/// Returns the width of the buckets and the buckets of a counter, or [`None`] if it isn't one
+fn read(counter: &Datacell) -> Option<(u64, Vec<(u64, u64)>)> {
+    if counter.is_null() {
+        return None;
+    }
+    let ring = counter.try_list()?.read();
+    let (width, buckets) = ring.split_first()?;
+    let buckets = buckets
+        .chunks_exact(2)
+        .map(|bucket| Some((bucket[0].try_uint()?, bucket[1].try_uint()?)))
+        .collect::<Option<Vec<_>>>()?;
+    Some((width.try_uint()?, buckets))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::WindowCounter, crate::engine::data::cell::Datacell};
+
+    #[test]
+    fn record_and_count() {
+        let counter = WindowCounter::new(60).unwrap();
+        let mut ring = counter.start(2, 1000);
+        ring = counter.record(&ring, 3, 1000);
+        ring = counter.record(&ring, 1, 1030);
+        assert_eq!(WindowCounter::count(&ring, 60, 1030), 6);
+        assert_eq!(WindowCounter::count(&ring, 10, 1030), 1);
+        // the first bucket leaves the window
+        assert_eq!(WindowCounter::count(&ring, 60, 1061), 1);
+        ring = counter.record(&ring, 0, 1061);
+        assert_eq!(ring.list().read().len(), 3);
+        assert_eq!(WindowCounter::count(&Datacell::null(), 60, 1061), 0);
+    }
+
+    #[test]
+    fn bounded() {
+        let counter = WindowCounter::new(3600).unwrap();
+        let mut ring = Datacell::null();
+        for now in 0..10_000 {
+            ring = counter.record(&ring, 1, now);
+        }
+        assert!(ring.list().read().len() <= 1 + 2 * (WindowCounter::BUCKETS as usize + 1));
+        // a count is up to a bucket's worth of events over
+        assert_eq!(WindowCounter::count(&ring, 3600, 9_999), 3640);
+        assert_eq!(WindowCounter::count(&ring, 60, 9_999), 100);
+    }
+
+    #[test]
+    fn prepare_insert() {
+        let counter = WindowCounter::new(60).unwrap();
+        let mut data = Datacell::new_uint_default(4);
+        assert!(counter.prepare_insert(&mut data, 1000));
+        assert_eq!(WindowCounter::count(&data, 60, 1000), 4);
+        let mut data = Datacell::null();
+        assert!(counter.prepare_insert(&mut data, 1000));
+        assert!(data.is_null());
+        assert!(!counter.prepare_insert(&mut Datacell::new_str("a".into()), 1000));
+        assert!(WindowCounter::new(0).is_none());
+    }
+}
@@ -0,0 +1,295 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    case insensitive names
+    ---
+    with `system.case_insensitive_names` set, the names of spaces, models and fields are matched without regard to
+    (ascii) case, but keep the case that they were created with. rather than changing how every index compares
+    names, the identifiers of a statement are rewritten to the spelling of the names that they refer to as soon as
+    the statement is lexed:
+    - `space.model` is matched against the spaces, and then against the models of the space
+    - any other identifier is matched against the models of the current space, and then against the fields of the
+      models that the statement names and the spaces. types (which come right after a `:`) and functions (which come
+      right before a `(`) are left alone
+    an exact match always wins, so names that were created before the option was set and only differ in case can
+    still be used with their exact spelling. since a name that only differs in case from an existing one is
+    rewritten to it, creating it fails as a duplicate. a new model can't have two fields that only differ in case
+    either, and neither can the fields that an alter adds
+*/
+
+use {
+    crate::engine::{
+        core::{space::Space, EntityID, EntityIDRef, GNSData},
+        idx::STIndexSeq,
+        ql::lex::{Ident, Symbol, Token},
+    },
+    std::{
+        collections::HashMap,
+        mem,
+        sync::atomic::{AtomicBool, Ordering},
+    },
+};
+
+/// If set, names are matched without regard to case
+static CASE_INSENSITIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set if names are matched without regard to case
+pub fn set_case_insensitive(case_insensitive: bool) {
+    CASE_INSENSITIVE.store(case_insensitive, Ordering::Relaxed)
+}
+
+/// Returns true if names are matched without regard to case
+pub fn case_insensitive() -> bool {
+    CASE_INSENSITIVE.load(Ordering::Relaxed)
+}
+
+/// The spellings that the identifiers of a statement were rewritten to. The tokens point into them, so they must be
+/// kept for as long as the tokens are
+#[derive(Debug, Default)]
+pub struct Spellings(Vec<Box<str>>);
+
+impl Spellings {
+    /// Returns an identifier for the given spelling that lives as long as the tokens do
+    fn ident<'a>(&mut self, spelling: &str) -> Ident<'a> {
+        let spelling: Box<str> = spelling.into();
+        let ident = unsafe {
+            // UNSAFE(@ohsayan): the spelling doesn't move with the box, and is kept for as long as the tokens are
+            Ident::new(mem::transmute::<&[u8], &'a [u8]>(spelling.as_bytes()))
+        };
+        self.0.push(spelling);
+        ident
+    }
+}
+
+/// Rewrite the identifiers of a lexed statement to the spelling of the names that they refer to (if names are case
+/// insensitive). `cs` is the current space
+pub fn resolve<'a>(gns: &GNSData, cs: Option<&str>, tokens: &mut [Token<'a>]) -> Spellings {
+    if case_insensitive() {
+        resolve_names(gns, cs, tokens)
+    } else {
+        Spellings::default()
+    }
+}
+
+/// Returns the spelling of an entity, if names are case insensitive and it isn't spelled like it was created
+pub fn resolve_entity(gns: &GNSData, entity: EntityIDRef) -> Option<EntityID> {
+    if !case_insensitive() {
+        return None;
+    }
+    let spaces = gns.idx().read();
+    let (space_name, space) = pick_space(&spaces, entity.space())?;
+    let model = pick(space.models().iter().map(|m| &**m), entity.entity())?;
+    ((space_name != entity.space()) | (model != entity.entity()))
+        .then(|| EntityID::new(space_name, model))
+}
+
+/// Returns true if any two of the names only differ in case
+pub fn any_differ_in_case<'n>(names: impl Iterator<Item = &'n str>) -> bool {
+    let mut seen: Vec<&str> = Vec::new();
+    for name in names {
+        if seen.iter().any(|seen| seen.eq_ignore_ascii_case(name)) {
+            return true;
+        }
+        seen.push(name);
+    }
+    false
+}
+
+pub(super) fn resolve_names<'a>(
+    gns: &GNSData,
+    cs: Option<&str>,
+    tokens: &mut [Token<'a>],
+) -> Spellings {
+    let spaces = gns.idx().read();
+    let models = gns.idx_models().read();
+    let current = cs.and_then(|cs| pick_space(&spaces, cs));
+    let mut spellings = Spellings::default();
+    let mut resolved = vec![false; tokens.len()];
+    let mut named = Vec::new();
+    let mut rewrite = |token: &mut Token<'a>, typed: &str, spelling: &str| {
+        if typed != spelling {
+            *token = Token::Ident(spellings.ident(spelling));
+        }
+    };
+    // first the entities
+    let mut i = 0;
+    while i < tokens.len() {
+        let Token::Ident(typed) = tokens[i] else {
+            i += 1;
+            continue;
+        };
+        match tokens.get(i + 1..i + 3) {
+            Some([dot, Token::Ident(model)]) if *dot == Token::Symbol(Symbol::SymPeriod) => {
+                let model = *model;
+                if let Some((space_name, space)) = pick_space(&spaces, typed.as_str()) {
+                    rewrite(&mut tokens[i], typed.as_str(), space_name);
+                    if let Some(model_name) =
+                        pick(space.models().iter().map(|m| &**m), model.as_str())
+                    {
+                        rewrite(&mut tokens[i + 2], model.as_str(), model_name);
+                        named.push(EntityIDRef::new(space_name, model_name));
+                    }
+                }
+                resolved[i] = true;
+                resolved[i + 2] = true;
+                i += 3;
+                continue;
+            }
+            _ => {}
+        }
+        if let Some((space_name, space)) = current {
+            if let Some(model_name) = pick(space.models().iter().map(|m| &**m), typed.as_str()) {
+                rewrite(&mut tokens[i], typed.as_str(), model_name);
+                named.push(EntityIDRef::new(space_name, model_name));
+                resolved[i] = true;
+            }
+        }
+        i += 1;
+    }
+    // then the fields of the named models, and the spaces
+    let fields: Vec<&str> = named
+        .iter()
+        .filter_map(|entity| models.get(entity))
+        .flat_map(|model| model.data().fields().stseq_ord_key())
+        .map(|field| field.as_str())
+        .collect();
+    for i in 0..tokens.len() {
+        let Token::Ident(typed) = tokens[i] else {
+            continue;
+        };
+        let is_type = (i != 0) && (tokens[i - 1] == Token::Symbol(Symbol::SymColon));
+        let is_function = tokens.get(i + 1) == Some(&Token::Symbol(Symbol::TtOpenParen));
+        if resolved[i] | is_type | is_function {
+            continue;
+        }
+        let spelling = pick(fields.iter().copied(), typed.as_str())
+            .or_else(|| pick_space(&spaces, typed.as_str()).map(|(name, _)| name));
+        if let Some(spelling) = spelling {
+            rewrite(&mut tokens[i], typed.as_str(), spelling);
+        }
+    }
+    spellings
+}
+
+/// Returns the space with the given name (or else, the first one whose name only differs in case)
+fn pick_space<'s>(
+    spaces: &'s HashMap<Box<str>, Space>,
+    typed: &str,
+) -> Option<(&'s str, &'s Space)> {
+    match spaces.get_key_value(typed) {
+        Some((name, space)) => Some((&**name, space)),
+        None => spaces
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(typed))
+            .map(|(name, space)| (&**name, space)),
+    }
+}
+
+/// Returns the name that is spelled like the typed one (or else, the first one that only differs in case)
+fn pick<'n>(names: impl Iterator<Item = &'n str> + Clone, typed: &str) -> Option<&'n str> {
+    names.clone().find(|name| *name == typed).or_else(|| {
+        names
+            .into_iter()
+            .find(|name| name.eq_ignore_ascii_case(typed))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{any_differ_in_case, resolve_names},
+        crate::engine::{
+            core::{model::ModelData, space::Space},
+            fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+            ql::{ast::parse_ast_node_full, tests::lex_insecure},
+        },
+    };
+
+    #[test]
+    fn resolve_spellings() {
+        let global = TestGlobal::new_with_driver_id_instant_update("names_resolve_spellings");
+        global
+            .state()
+            .namespace()
+            .idx()
+            .write()
+            .insert("MySpace".into(), Space::new_auto_all().into());
+        let create = lex_insecure(
+            b"create model MySpace.Users(UserName: string, Age: uint8, Upper: string, String: binary)",
+        )
+        .unwrap();
+        ModelData::transactional_exec_create(&global, parse_ast_node_full(&create[2..]).unwrap())
+            .unwrap();
+        let check = |cs: Option<&str>, query: &str, expected: &str| {
+            let mut tokens = lex_insecure(query.as_bytes()).unwrap();
+            let _spellings = resolve_names(global.state().namespace(), cs, &mut tokens);
+            assert_eq!(
+                tokens,
+                lex_insecure(expected.as_bytes()).unwrap(),
+                "{query}"
+            );
+        };
+        check(
+            None,
+            "select username, age from myspace.users where username = 'sayan'",
+            "select UserName, Age from MySpace.Users where UserName = 'sayan'",
+        );
+        check(
+            Some("myspace"),
+            "insert into users { username: 'sayan', age: 30 }",
+            "insert into Users { UserName: 'sayan', Age: 30 }",
+        );
+        check(None, "use myspace", "use MySpace");
+        // types and functions are left alone, as are names that aren't known
+        check(
+            None,
+            "alter model myspace.users add bio { type: string }",
+            "alter model MySpace.Users add bio { type: string }",
+        );
+        check(
+            None,
+            "select upper(username), upper from myspace.users where city = 'London'",
+            "select upper(UserName), Upper from MySpace.Users where city = 'London'",
+        );
+        // fields are only matched against the models that the statement names
+        check(
+            None,
+            "select username from otherspace.users",
+            "select username from otherspace.users",
+        );
+    }
+
+    #[test]
+    fn differ_in_case() {
+        assert!(any_differ_in_case(
+            ["username", "age", "UserName"].into_iter()
+        ));
+        assert!(!any_differ_in_case(
+            ["username", "age", "user_name"].into_iter()
+        ));
+    }
+}
@@ -0,0 +1,101 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    notices
+    ---
+    a notice is a non-fatal message about a statement that succeeded (for example, that an update without a where
+    clause changed every row). like error details (see `ErrorDetail`), notices are kept on the thread that runs the
+    statement: they're cleared before it runs and taken right after, on the same thread. clients that asked for them
+    in the handshake get them along with the response, and they are also kept with the stats for `sysctl report last`.
+    a background job that ends raises its notice on the thread that runs the next statement of the user who started it
+*/
+
+/// The most notices that are kept for a statement (any more are dropped)
+const MAX_NOTICES: usize = 16;
+
+local! {
+    static NOTICES: Vec<Notice> = Vec::new();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, sky_macros::EnumMethods)]
+#[repr(u8)]
+/// What a [`Notice`] is about. Like error codes, these are stable
+pub enum NoticeCode {
+    /// an update without a where clause (run with `force`) changed rows
+    UnfilteredMutation = 0,
+    /// an update or delete stopped at its limit, so more rows might match
+    LimitReached = 1,
+    /// a job that the user started ended (see [`crate::engine::fractal::jobs`])
+    JobFinished = 2,
+    /// the upstream of a model that caches one couldn't be reached, so a row was served from the local copy (see
+    /// [`crate::engine::fractal::upstream`])
+    UpstreamUnavailable = 3,
+    /// the statement used more of a limit than its soft threshold, so it will start failing if usage keeps growing
+    /// (see [`crate::engine::fractal::soft_limit`])
+    SoftLimitReached = 4,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    code: NoticeCode,
+    message: Box<str>,
+}
+
+impl Notice {
+    pub fn new(code: NoticeCode, message: impl Into<Box<str>>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+    pub fn code(&self) -> NoticeCode {
+        self.code
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    /// Record a notice for the statement that is running on this thread
+    pub fn raise(code: NoticeCode, message: impl Into<Box<str>>) {
+        local_mut!(NOTICES, |notices| {
+            if notices.len() < MAX_NOTICES {
+                notices.push(Self::new(code, message));
+            }
+        })
+    }
+    /// Clear the notices recorded on this thread
+    pub fn clear() {
+        local_mut!(NOTICES, |notices| notices.clear())
+    }
+    /// Take the notices recorded on this thread
+    pub fn take_all() -> Vec<Self> {
+        local_mut!(NOTICES, |notices| core::mem::take(notices))
+    }
+    /// Replace the notices recorded on this thread (to carry them over from another thread)
+    pub fn restore(notices: Vec<Self>) {
+        local_mut!(NOTICES, |n| *n = notices)
+    }
+}
@@ -0,0 +1,373 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    profiler
+    ---
+    one in every `SAMPLE_EVERY` statements is sampled: we time how long it spends being lexed, parsed, executed and
+    serialized (written out to the connection's buffer), and add that to the statement's fingerprint (the statement
+    with every literal replaced by `?`). a statement that isn't sampled only costs an atomic increment.
+
+    the times are wall-clock times of the thread running each phase. lexing, parsing and serializing never wait, so
+    for them this is the CPU time used; executing a statement can wait for locks (and DDL for the disk), which is
+    counted too. parsing happens wherever the statement is executed (which can be a blocking task), so like error
+    details, the time at which parsing ended is kept on that thread and carried over.
+
+    samples are added up in windows of `WINDOW`. `sysctl report profile` reports the current window, along with the
+    window before it (if the current window started right when that one ended)
+*/
+
+use {
+    crate::{
+        engine::{ql::lex::Token, storage::encode_str},
+        util::os,
+    },
+    std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+        time::{Duration, Instant},
+    },
+};
+
+/// One in this many statements is sampled
+pub const SAMPLE_EVERY: u64 = 16;
+/// The length of a window
+const WINDOW: Duration = Duration::from_secs(60);
+/// The most fingerprints kept in a window (samples of any other fingerprint are only counted)
+const FINGERPRINTS_MAX: usize = 1024;
+/// Fingerprints longer than this are cut short
+const FINGERPRINT_LEN_MAX: usize = 512;
+/// The phases, in the order that a statement goes through them
+const PHASES: [&str; 4] = ["lex", "parse", "execute", "serialize"];
+
+static PROFILER: Profiler = Profiler {
+    statements: AtomicU64::new(0),
+    windows: Mutex::new(Windows {
+        current: None,
+        previous: None,
+    }),
+};
+
+local! {
+    static PARSED: Option<Instant> = None;
+}
+
+/// Clear the time at which the statement running on this thread was parsed
+pub fn clear_parsed() {
+    local_mut!(PARSED, |parsed| *parsed = None)
+}
+
+/// Note that the statement running on this thread has been parsed (whether or not it parsed)
+pub fn mark_parsed() {
+    local_mut!(PARSED, |parsed| *parsed = Some(Instant::now()))
+}
+
+/// Take the time at which the statement running on this thread was parsed (to carry it over to another thread with
+/// [`restore_parsed`])
+pub fn take_parsed() -> Option<Instant> {
+    local_mut!(PARSED, |parsed| parsed.take())
+}
+
+/// Replace the time at which the statement running on this thread was parsed
+pub fn restore_parsed(at: Option<Instant>) {
+    local_mut!(PARSED, |parsed| *parsed = at)
+}
+
+#[derive(Debug, PartialEq)]
+/// A statement that is being sampled
+pub struct Sample {
+    fingerprint: Option<Box<str>>,
+    /// the time at which the last phase ended
+    last: Instant,
+    nanos: [u64; 4],
+}
+
+impl Sample {
+    /// Returns a sample if this statement is to be sampled
+    pub fn start() -> Option<Self> {
+        if PROFILER.statements.fetch_add(1, Ordering::Relaxed) % SAMPLE_EVERY != 0 {
+            return None;
+        }
+        Some(Self {
+            fingerprint: None,
+            last: Instant::now(),
+            nanos: [0; 4],
+        })
+    }
+    fn phase_ended(&mut self, phase: usize, at: Instant) {
+        self.nanos[phase] += at.saturating_duration_since(self.last).as_nanos() as u64;
+        self.last = at;
+    }
+    /// Note that the statement has been lexed into `tokens`
+    pub fn lexed(&mut self, tokens: &[Token]) {
+        self.phase_ended(0, Instant::now());
+        self.fingerprint = Some(fingerprint(tokens));
+    }
+    /// Note that the statement has been executed
+    pub fn executed(&mut self) {
+        let now = Instant::now();
+        if let Some(parsed) = take_parsed().filter(|parsed| *parsed >= self.last) {
+            self.phase_ended(1, parsed.min(now));
+        }
+        self.phase_ended(2, now);
+    }
+    /// Note that the response has been serialized, adding the sample to the current window. A statement that
+    /// couldn't be lexed has no fingerprint, so its sample is dropped
+    pub fn serialized(mut self) {
+        self.phase_ended(3, Instant::now());
+        if let Some(fingerprint) = self.fingerprint.take() {
+            PROFILER.record(fingerprint, self.nanos);
+        }
+    }
+}
+
+/// Returns the fingerprint of a statement: its tokens, with every literal replaced by `?`
+pub fn fingerprint(tokens: &[Token]) -> Box<str> {
+    let mut fp = String::new();
+    let mut space = false;
+    for token in tokens {
+        match token {
+            Token::Symbol(s) => {
+                let c = s.as_char();
+                if space & !".,)]}".contains(c) {
+                    fp.push(' ');
+                }
+                fp.push(c);
+                space = !".([{".contains(c);
+            }
+            token => {
+                if space {
+                    fp.push(' ');
+                }
+                fp.push_str(match token {
+                    Token::Keyword(kw) => kw.as_str(),
+                    Token::Ident(id) => id.as_str(),
+                    _ => "?",
+                });
+                space = true;
+            }
+        }
+        if fp.len() >= FINGERPRINT_LEN_MAX {
+            let mut end = FINGERPRINT_LEN_MAX;
+            while !fp.is_char_boundary(end) {
+                end -= 1;
+            }
+            fp.truncate(end);
+            fp.push_str("...");
+            break;
+        }
+    }
+    fp.into_boxed_str()
+}
+
+/// Returns the profile as a JSON object
+pub fn describe() -> String {
+    PROFILER.describe(Instant::now())
+}
+
+struct Profiler {
+    /// every statement (sampled or not)
+    statements: AtomicU64,
+    windows: Mutex<Windows>,
+}
+
+struct Windows {
+    current: Option<Window>,
+    previous: Option<Window>,
+}
+
+struct Window {
+    started: Instant,
+    /// when the window started, in seconds since the epoch
+    start: u64,
+    /// the value of the statement counter when the window started
+    statements_before: u64,
+    /// the number of statements run in the window (only set once the window is over)
+    statements: Option<u64>,
+    fingerprints: HashMap<Box<str>, PhaseTimes>,
+    /// samples of fingerprints that weren't kept because there were too many
+    untracked: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct PhaseTimes {
+    samples: u64,
+    nanos: [u64; 4],
+}
+
+impl PhaseTimes {
+    fn total(&self) -> u64 {
+        self.nanos.iter().sum()
+    }
+}
+
+impl Profiler {
+    fn record(&self, fingerprint: Box<str>, nanos: [u64; 4]) {
+        let mut windows = self.windows.lock().unwrap();
+        let window = self.rotate(&mut windows, Instant::now());
+        if (window.fingerprints.len() >= FINGERPRINTS_MAX)
+            & !window.fingerprints.contains_key(&fingerprint)
+        {
+            window.untracked += 1;
+            return;
+        }
+        let times = window.fingerprints.entry(fingerprint).or_default();
+        times.samples += 1;
+        for (total, phase) in times.nanos.iter_mut().zip(nanos) {
+            *total += phase;
+        }
+    }
+    /// Start a new window if the current one is over, returning the current window
+    fn rotate<'a>(&self, windows: &'a mut Windows, now: Instant) -> &'a mut Window {
+        let elapsed = windows
+            .current
+            .as_ref()
+            .map(|current| now.saturating_duration_since(current.started));
+        if elapsed.map_or(false, |elapsed| elapsed >= WINDOW) {
+            let current = windows.current.take().unwrap();
+            // the window before the current one is only kept if it ended right before the current one started
+            windows.previous = match elapsed.unwrap() < WINDOW * 2 {
+                true => Some(current.close(self.statements.load(Ordering::Relaxed))),
+                false => None,
+            };
+        }
+        windows.current.get_or_insert_with(|| Window {
+            started: now,
+            start: os::get_epoch_time_secs(),
+            statements_before: self.statements.load(Ordering::Relaxed),
+            statements: None,
+            fingerprints: HashMap::new(),
+            untracked: 0,
+        })
+    }
+    fn describe(&self, now: Instant) -> String {
+        let mut windows = self.windows.lock().unwrap();
+        self.rotate(&mut windows, now);
+        let statements = self.statements.load(Ordering::Relaxed);
+        let windows = [windows.previous.as_ref(), windows.current.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|window| window.describe(now, statements))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            "{{\"sample_every\":{SAMPLE_EVERY},\"window_secs\":{},\"windows\":[{windows}]}}",
+            WINDOW.as_secs()
+        )
+    }
+}
+
+impl Window {
+    fn close(mut self, statements: u64) -> Self {
+        self.statements = Some(statements - self.statements_before);
+        self
+    }
+    fn describe(&self, now: Instant, statements: u64) -> String {
+        let mut fingerprints: Vec<_> = self.fingerprints.iter().collect();
+        fingerprints.sort_by(|(_, a), (_, b)| b.total().cmp(&a.total()));
+        let mut out = String::new();
+        for (i, (fingerprint, times)) in fingerprints.into_iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            out.push_str("{\"fingerprint\":");
+            encode_str(&mut out, fingerprint);
+            out.push_str(&format!(",\"samples\":{}", times.samples));
+            for (phase, nanos) in PHASES.iter().zip(times.nanos) {
+                out.push_str(&format!(",\"{phase}_us\":{}", nanos / 1000));
+            }
+            out.push_str(&format!(",\"total_us\":{}}}", times.total() / 1000));
+        }
+        let secs = match self.statements {
+            Some(_) => WINDOW.as_secs(),
+            None => now.saturating_duration_since(self.started).as_secs(),
+        };
+        format!(
+            "{{\"start\":{},\"secs\":{secs},\"complete\":{},\"statements\":{},\"untracked_samples\":{},\"fingerprints\":[{out}]}}",
+            self.start,
+            self.statements.is_some(),
+            self.statements
+                .unwrap_or(statements - self.statements_before),
+            self.untracked,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::engine::ql::tests::lex_insecure};
+
+    #[test]
+    fn fingerprint_replaces_literals() {
+        let tokens =
+            lex_insecure(b"select * from myspace.mymodel where username = 'sayan' and age = 25")
+                .unwrap();
+        assert_eq!(
+            &*fingerprint(&tokens),
+            "select * from myspace.mymodel where username = ? and age = ?"
+        );
+        let tokens = lex_insecure(b"insert into myspace.mymodel('sayan', 25, [1, 2])").unwrap();
+        assert_eq!(
+            &*fingerprint(&tokens),
+            "insert into myspace.mymodel (?, ?, [?, ?])"
+        );
+    }
+
+    #[test]
+    fn windows() {
+        let profiler = Profiler {
+            statements: AtomicU64::new(0),
+            windows: Mutex::new(Windows {
+                current: None,
+                previous: None,
+            }),
+        };
+        profiler.statements.store(10, Ordering::Relaxed);
+        profiler.record("select ?".into(), [1000, 2000, 3000, 4000]);
+        profiler.record("select ?".into(), [1000, 2000, 3000, 4000]);
+        let mut windows = profiler.windows.lock().unwrap();
+        let current = windows.current.as_ref().unwrap();
+        let started = current.started;
+        assert_eq!(
+            current.fingerprints["select ?"],
+            PhaseTimes {
+                samples: 2,
+                nanos: [2000, 4000, 6000, 8000]
+            }
+        );
+        // the next window keeps the one before it
+        profiler.statements.store(30, Ordering::Relaxed);
+        profiler.rotate(&mut windows, started + WINDOW);
+        assert_eq!(windows.previous.as_ref().unwrap().statements, Some(20));
+        assert!(windows.current.as_ref().unwrap().fingerprints.is_empty());
+        // but a window that ended long before the current one started is dropped
+        profiler.rotate(&mut windows, started + WINDOW * 4);
+        assert!(windows.previous.is_none());
+    }
+}
@@ -0,0 +1,355 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+/*
+    statement quotas
+    ---
+    a user and a model can each be given a quota: the number of reads (selects, exists, fetches and traversals) and
+    writes (inserts, updates, deletes, evals and blob writes) that can be run every second, with `sysctl alter quota
+    user <name> with { reads: <n>, writes: <n> }` (or `model <model>` in place of `user <name>`). a rate that is left
+    out or is 0 doesn't limit its statements, so `with {}` lifts the quota. a fetch is checked once for every model
+    that it looks up rows in. every rate is a token bucket that holds up to a second's worth of statements, so short
+    bursts go through but a client that stays over the rate is held to it.
+
+    a statement is checked against the quota of the user that runs it and then against that of the model that it runs
+    on, before it runs (statements run with `execute` included). a statement past either of them is rejected with
+    `SysQuotaExceeded` and told how long to wait (`retry_after_ms`) for the bucket to have room again; a statement that
+    the model's quota rejects still counts against the user's. quotas are kept in the global journal (and in schema
+    exports), are dropped along with their user or model, and start out with full buckets on startup. the quotas that
+    are set and how many statements each of them rejected is what `sysctl report quotas` shows
+*/
+
+use {
+    super::EntityIDRef,
+    crate::engine::{
+        data::{tag::TagClass, DictEntryGeneric, DictGeneric},
+        error::{QueryError, QueryResult},
+        fractal::GlobalInstanceLike,
+        txn::{gns::model::AlterModelQuotaTxn, ModelIDRef},
+    },
+    parking_lot::Mutex,
+    std::{
+        fmt,
+        sync::atomic::{AtomicU64, Ordering},
+        time::{Duration, Instant},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// What a statement does, as far as quotas are concerned
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// the statements that can be admitted right away
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// A limit on the number of statements that can be run every second
+#[derive(Debug)]
+pub struct RateLimit {
+    rate: u64,
+    bucket: Mutex<Bucket>,
+    rejected: AtomicU64,
+}
+
+impl RateLimit {
+    /// Create a limit of `rate` statements per second. A rate of 0 doesn't limit statements
+    pub fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            bucket: Mutex::new(Bucket {
+                tokens: rate as f64,
+                refilled_at: Instant::now(),
+            }),
+            rejected: AtomicU64::new(0),
+        }
+    }
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+    /// Returns the number of statements that were rejected
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+    /// Take a statement from the bucket at `now`, returning how long the caller has to wait for room if it's empty
+    pub fn take(&self, now: Instant) -> Result<(), Duration> {
+        if self.rate == 0 {
+            return Ok(());
+        }
+        let rate = self.rate as f64;
+        let mut bucket = self.bucket.lock();
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(rate);
+        bucket.refilled_at = bucket.refilled_at.max(now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+}
+
+/// The limits on the reads and writes of a user or model (see the module docs)
+#[derive(Debug)]
+pub struct Quota {
+    reads: RateLimit,
+    writes: RateLimit,
+}
+
+impl Default for Quota {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl PartialEq for Quota {
+    fn eq(&self, other: &Self) -> bool {
+        (self.reads() == other.reads()) & (self.writes() == other.writes())
+    }
+}
+
+impl Quota {
+    /// the reads that can be run every second
+    pub const KEY_READS: &'static str = "reads";
+    /// the writes that can be run every second
+    pub const KEY_WRITES: &'static str = "writes";
+    pub fn new(reads: u64, writes: u64) -> Self {
+        Self {
+            reads: RateLimit::new(reads),
+            writes: RateLimit::new(writes),
+        }
+    }
+    /// Resolve the properties given to `sysctl alter quota`
+    pub fn try_from_props(mut props: DictGeneric) -> QueryResult<Self> {
+        let mut rate = |key| match props.remove(key) {
+            None => Ok(0),
+            Some(DictEntryGeneric::Data(d)) if d.kind() == TagClass::UnsignedInt => Ok(d.uint()),
+            Some(_) => Err(QueryError::QExecDdlInvalidProperties),
+        };
+        let (reads, writes) = (rate(Self::KEY_READS)?, rate(Self::KEY_WRITES)?);
+        if !props.is_empty() {
+            return Err(QueryError::QExecDdlInvalidProperties);
+        }
+        Ok(Self::new(reads, writes))
+    }
+    pub fn reads(&self) -> u64 {
+        self.reads.rate()
+    }
+    pub fn writes(&self) -> u64 {
+        self.writes.rate()
+    }
+    /// Returns true if neither reads nor writes are limited
+    pub fn is_unlimited(&self) -> bool {
+        (self.reads() == 0) & (self.writes() == 0)
+    }
+    /// Admit a statement that does the given access at `now`, returning how long to wait for room if it's past the
+    /// quota
+    pub fn admit(&self, access: Access, now: Instant) -> Result<(), Duration> {
+        match access {
+            Access::Read => self.reads.take(now),
+            Access::Write => self.writes.take(now),
+        }
+    }
+    /// Returns the rates and the number of statements rejected as a JSON object
+    pub fn describe(&self) -> String {
+        format!(
+            "{{\"reads\":{},\"writes\":{},\"rejected_reads\":{},\"rejected_writes\":{}}}",
+            self.reads(),
+            self.writes(),
+            self.reads.rejected(),
+            self.writes.rejected()
+        )
+    }
+}
+
+fn exceeded(
+    kind: &'static str,
+    name: impl fmt::Display,
+    access: Access,
+    wait: Duration,
+) -> QueryError {
+    QueryError::SysQuotaExceeded
+        .with_detail(kind, name)
+        .with_detail("access", access.name())
+        .with_detail("retry_after_ms", wait.as_millis().max(1))
+}
+
+/// Admit a statement that the user runs on the model, or reject it if it's past the quota of either
+pub fn admit(
+    global: &impl GlobalInstanceLike,
+    username: &str,
+    entity: EntityIDRef,
+    access: Access,
+) -> QueryResult<()> {
+    let now = Instant::now();
+    let users = global.state().namespace().sys_db().users().read();
+    if let Some(Err(wait)) = users
+        .get(username)
+        .map(|user| user.quota().admit(access, now))
+    {
+        return Err(exceeded("user", username, access, wait));
+    }
+    drop(users);
+    let models = global.state().namespace().idx_models().read();
+    if let Some(Err(wait)) = models
+        .get(&entity)
+        .map(|model| model.data().quota().admit(access, now))
+    {
+        return Err(exceeded("model", entity, access, wait));
+    }
+    Ok(())
+}
+
+/// Set the quota of a user (`sysctl alter quota user ...`)
+pub fn alter_user_quota(
+    global: &impl GlobalInstanceLike,
+    username: &str,
+    props: DictGeneric,
+) -> QueryResult<()> {
+    let quota = Quota::try_from_props(props)?;
+    global
+        .state()
+        .namespace()
+        .sys_db()
+        .alter_quota(global, username, quota)
+}
+
+/// Set the quota of a model (`sysctl alter quota model ...`)
+pub fn alter_model_quota(
+    global: &impl GlobalInstanceLike,
+    entity: EntityIDRef,
+    props: DictGeneric,
+) -> QueryResult<()> {
+    let quota = Quota::try_from_props(props)?;
+    global
+        .state()
+        .namespace()
+        .with_model_space_mut_for_ddl(entity, |space, model| {
+            global.state().freeze().check(entity.space())?;
+            let txn = AlterModelQuotaTxn::new(
+                ModelIDRef::new_ref(entity.space(), space, entity.entity(), model),
+                quota.reads(),
+                quota.writes(),
+            );
+            global.state().gns_driver().driver_context(
+                global,
+                |drv| drv.commit_event(txn),
+                || {},
+            )?;
+            model.set_quota(quota);
+            Ok(())
+        })
+}
+
+/// Returns the quotas of the users and models that have one (and how many statements each rejected) as a JSON object
+pub fn describe(global: &impl GlobalInstanceLike) -> String {
+    let mut users: Vec<String> = global
+        .state()
+        .namespace()
+        .sys_db()
+        .users()
+        .read()
+        .iter()
+        .filter(|(_, user)| !user.quota().is_unlimited())
+        .map(|(name, user)| format!("\"{name}\":{}", user.quota().describe()))
+        .collect();
+    let mut models: Vec<String> = global
+        .state()
+        .namespace()
+        .idx_models()
+        .read()
+        .iter()
+        .filter(|(_, model)| !model.data().quota().is_unlimited())
+        .map(|(entity, model)| {
+            format!(
+                "\"{}.{}\":{}",
+                entity.space(),
+                entity.entity(),
+                model.data().quota().describe()
+            )
+        })
+        .collect();
+    users.sort();
+    models.sort();
+    format!(
+        "{{\"users\":{{{}}},\"models\":{{{}}}}}",
+        users.join(","),
+        models.join(",")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Access, Quota, RateLimit},
+        std::time::{Duration, Instant},
+    };
+
+    #[test]
+    fn bucket_refills() {
+        let limit = RateLimit::new(10);
+        let start = Instant::now();
+        for _ in 0..10 {
+            assert_eq!(limit.take(start), Ok(()));
+        }
+        assert_eq!(limit.take(start), Err(Duration::from_millis(100)));
+        assert_eq!(limit.rejected(), 1);
+        // a statement's worth of room after 100ms, but never more than a second's worth
+        assert_eq!(limit.take(start + Duration::from_millis(100)), Ok(()));
+        let later = start + Duration::from_secs(60);
+        for _ in 0..10 {
+            assert_eq!(limit.take(later), Ok(()));
+        }
+        assert!(limit.take(later).is_err());
+    }
+
+    #[test]
+    fn reads_and_writes_are_separate() {
+        let quota = Quota::new(1, 0);
+        let now = Instant::now();
+        assert_eq!(quota.admit(Access::Read, now), Ok(()));
+        assert!(quota.admit(Access::Read, now).is_err());
+        for _ in 0..100 {
+            assert_eq!(quota.admit(Access::Write, now), Ok(()));
+        }
+        assert!(Quota::default().is_unlimited());
+    }
+}
@@ -0,0 +1,284 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    query recorder
+    ---
+    while a recording is running, every statement that lexes is appended to the recording's file, along with its
+    parameters (as the client encoded them) so that `sky-bench --replay` can run the same workload against another
+    instance. all integers are little endian:
+
+    [magic:8B][version:8B]
+    [at:8B][connection:8B][query size:8B][query][params size:8B][params] (for every statement)
+
+    `at` is the number of microseconds since the recording started and `connection` tells statements run on different
+    connections apart. a statement is recorded when it lexes, so statements that fail to parse or execute are recorded
+    too (the replay will fail them just the same). sysctl statements are never recorded: they are administrative and
+    can carry passwords. bulk inserts, blob uploads and stream chunks aren't statements, so they aren't recorded either.
+
+    an anonymized recording replaces every string and binary parameter with hex digits of the same length that are
+    derived from a hash of the value (with keys that are only good for this recording), so equal values stay equal
+    and lookups by key still hit the rows inserted before them. numbers, booleans and nulls are kept as they are.
+
+    if the file can't be written to, the recording is stopped (statements never fail because of a recording)
+*/
+
+use {
+    crate::engine::{
+        error::{QueryError, QueryResult},
+        ql::lex::{Keyword, KeywordStmt, Token},
+    },
+    std::{
+        borrow::Cow,
+        collections::hash_map::RandomState,
+        fs::{File, OpenOptions},
+        hash::{BuildHasher, Hash, Hasher},
+        io::{BufWriter, Write},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
+        time::Instant,
+    },
+};
+
+/// The magic at the start of every recording
+pub const MAGIC: [u8; 8] = *b"SKYQLOG\0";
+/// The version of the recording format
+pub const VERSION: u64 = 1;
+
+static RECORDER: Recorder = Recorder {
+    running: AtomicBool::new(false),
+    recording: Mutex::new(None),
+};
+
+struct Recorder {
+    /// set while there is a recording (so that statements don't need to take the lock otherwise)
+    running: AtomicBool,
+    recording: Mutex<Option<Recording>>,
+}
+
+struct Recording {
+    file: BufWriter<File>,
+    started: Instant,
+    statements: u64,
+    /// the keys used to anonymize parameters, if the recording is anonymized
+    anonymize: Option<RandomState>,
+}
+
+/// Start recording statements into a new file at `path` (an existing file is never overwritten)
+pub fn start(path: &str, anonymized: bool) -> QueryResult<()> {
+    let mut recording = RECORDER.recording.lock().unwrap();
+    if recording.is_some() {
+        return Err(QueryError::QExecDdlObjectAlreadyExists.with_detail("recording", "running"));
+    }
+    let mut file = BufWriter::new(OpenOptions::new().write(true).create_new(true).open(path)?);
+    file.write_all(&MAGIC)?;
+    file.write_all(&VERSION.to_le_bytes())?;
+    file.flush()?;
+    *recording = Some(Recording {
+        file,
+        started: Instant::now(),
+        statements: 0,
+        anonymize: anonymized.then(RandomState::new),
+    });
+    RECORDER.running.store(true, Ordering::Release);
+    info!("started recording statements into {path}");
+    Ok(())
+}
+
+/// Stop the running recording, returning the number of statements that it has
+pub fn stop() -> QueryResult<u64> {
+    let mut recording = RECORDER.recording.lock().unwrap();
+    let Some(mut stopped) = recording.take() else {
+        return Err(QueryError::QExecObjectNotFound.with_detail("recording", "none"));
+    };
+    RECORDER.running.store(false, Ordering::Release);
+    stopped.file.flush()?;
+    stopped.file.get_ref().sync_all()?;
+    info!("stopped recording after {} statements", stopped.statements);
+    Ok(stopped.statements)
+}
+
+/// Record a statement (lexed into `tokens`) that was run on the given connection, if there is a recording
+pub fn statement(connection: u64, tokens: &[Token], query: &[u8], params: &[u8]) {
+    if !RECORDER.running.load(Ordering::Acquire)
+        || matches!(
+            tokens.first(),
+            Some(Token::Keyword(Keyword::Statement(KeywordStmt::Sysctl)))
+        )
+    {
+        return;
+    }
+    let mut recording = RECORDER.recording.lock().unwrap();
+    let Some(current) = recording.as_mut() else {
+        return;
+    };
+    let mut entry = Vec::with_capacity(sizeof!(u64, 4) + query.len() + params.len());
+    entry.extend((current.started.elapsed().as_micros() as u64).to_le_bytes());
+    entry.extend(connection.to_le_bytes());
+    entry.extend((query.len() as u64).to_le_bytes());
+    entry.extend(query);
+    let params = match current.anonymize.as_ref() {
+        Some(keys) => Cow::Owned(anonymize(keys, params)),
+        None => Cow::Borrowed(params),
+    };
+    entry.extend((params.len() as u64).to_le_bytes());
+    entry.extend(params.iter());
+    match current.file.write_all(&entry) {
+        Ok(()) => current.statements += 1,
+        Err(e) => {
+            warn!("stopped recording statements because the recording couldn't be written to: {e}");
+            *recording = None;
+            RECORDER.running.store(false, Ordering::Release);
+        }
+    }
+}
+
+/// Returns the parameters (encoded just like a client encodes them), with the payload of every string and binary
+/// replaced by hex digits (of the same length) derived from its hash. The parameters have already been lexed, so
+/// they are well formed; if they somehow aren't, whatever is left is dropped
+fn anonymize(keys: &RandomState, mut params: &[u8]) -> Vec<u8> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut anonymized = Vec::with_capacity(params.len());
+    while let Some((&tag, rest)) = params.split_first() {
+        anonymized.push(tag);
+        params = rest;
+        match tag {
+            // null
+            0 => {}
+            // bool
+            1 => match params.split_first() {
+                Some((&b, rest)) => {
+                    anonymized.push(b);
+                    params = rest;
+                }
+                None => break,
+            },
+            // uint, sint and float
+            2..=4 => match params.iter().position(|b| *b == b'\n') {
+                Some(end) => {
+                    anonymized.extend(&params[..=end]);
+                    params = &params[end + 1..];
+                }
+                None => break,
+            },
+            // bin and str
+            5 | 6 => {
+                let Some(end) = params.iter().position(|b| *b == b'\n') else {
+                    break;
+                };
+                let Some(len) = core::str::from_utf8(&params[..end])
+                    .ok()
+                    .and_then(|len| len.parse::<usize>().ok())
+                    .filter(|len| params.len() - end - 1 >= *len)
+                else {
+                    break;
+                };
+                anonymized.extend(&params[..=end]);
+                let value = &params[end + 1..end + 1 + len];
+                // a new hash for every 16 digits
+                for i in (0..len).step_by(16) {
+                    let mut hasher = keys.build_hasher();
+                    (value, i).hash(&mut hasher);
+                    let hash = hasher.finish();
+                    anonymized.extend(
+                        (0..16.min(len - i)).map(|d| HEX[((hash >> (d * 4)) & 0xF) as usize]),
+                    );
+                }
+                params = &params[end + 1 + len..];
+            }
+            _ => break,
+        }
+    }
+    anonymized
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::anonymize,
+        crate::engine::{data::lit::Lit, ql::lex::encode_param},
+        std::collections::hash_map::RandomState,
+    };
+
+    fn encode(params: &[Option<Lit>]) -> Vec<u8> {
+        let mut buf = vec![];
+        for param in params {
+            encode_param(&mut buf, param.as_ref());
+        }
+        buf
+    }
+
+    #[test]
+    fn anonymize_params() {
+        let keys = RandomState::new();
+        let secret = "a secret that is longer than sixteen bytes";
+        let params = encode(&[
+            Some(Lit::new_str(secret)),
+            Some(Lit::new_uint(100)),
+            None,
+            Some(Lit::new_bool(true)),
+            Some(Lit::new_bin(b"\xff\x00")),
+            Some(Lit::new_str(secret)),
+        ]);
+        let anonymized = anonymize(&keys, &params);
+        // everything but the payloads is kept
+        assert_eq!(anonymized.len(), params.len());
+        let expected_shape = encode(&[
+            Some(Lit::new_str(&"x".repeat(secret.len()))),
+            Some(Lit::new_uint(100)),
+            None,
+            Some(Lit::new_bool(true)),
+            Some(Lit::new_bin(b"xx")),
+            Some(Lit::new_str(&"x".repeat(secret.len()))),
+        ]);
+        let payload = |b: u8| b == b'x';
+        for (a, e) in anonymized.iter().zip(expected_shape.iter()) {
+            if payload(*e) {
+                assert!(a.is_ascii_hexdigit());
+            } else {
+                assert_eq!(a, e);
+            }
+        }
+        // the secret is gone, but both copies of it are anonymized the same way
+        let anonymized = String::from_utf8(anonymized).unwrap();
+        assert!(!anonymized.contains(secret));
+        let start = format!("\x06{}\n", secret.len()).len();
+        let first = &anonymized[start..start + secret.len()];
+        assert!(anonymized[start + secret.len()..].contains(first));
+        // and the same keys always anonymize the same way
+        assert_eq!(anonymize(&keys, &params), anonymize(&keys, &params));
+    }
+
+    #[test]
+    fn anonymize_truncated_params() {
+        let keys = RandomState::new();
+        let params = encode(&[Some(Lit::new_uint(1)), Some(Lit::new_str("hello"))]);
+        let anonymized = anonymize(&keys, &params[..params.len() - 1]);
+        assert_eq!(anonymized, b"\x021\n\x06");
+    }
+}
@@ -0,0 +1,295 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    data relocation
+    ---
+    `sysctl relocate data <path>` moves the files on the batch volume (the data directory, the quarantine and the
+    snapshots) to a new volume without stopping the server. the files are copied while serving, in blocks that are
+    throttled like any other maintenance (see `fractal::sched`), and in passes until a pass has little left to copy.
+    then, at the quiesce point (no statement can change a model and no batch can be written), what is left is copied,
+    every copy is checked against its file with a checksum, the batch volume is switched over and the journal of every
+    model partition is reopened on its copy, from where it was. the old directories are only removed once the switch
+    is done, and a marker is left in their place, so that a server that is restarted with the old volume refuses to
+    start: set `system.batch_volume` to the new volume before the next restart.
+
+    if anything fails before the switch (or the job is cancelled), nothing changes and the copies are removed. the
+    global journal is on the journal volume, so it isn't moved, and neither are the overflow files of running models,
+    which are thrown away on restart. a relocation never runs while a snapshot is being taken (see `core::snapshot`)
+*/
+
+use {
+    crate::engine::{
+        core::EntityIDRef,
+        error::{QueryError, QueryResult},
+        fractal::{
+            jobs::{self, Job, JobKind, JobStatus},
+            sched::WorkClass,
+            GlobalInstanceLike,
+        },
+        storage::{driver::ModelJournal, encode_str, safe_interfaces::paths_v1, DataRelocation},
+        RuntimeResult,
+    },
+    std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Instant,
+    },
+};
+
+/// The most that is copied at a time, between which the relocation is throttled
+const COPY_BLOCK: u64 = 1 << 20;
+/// A pass that copies less than this is the last one before the quiesce point
+const QUIESCE_AT: u64 = 16 << 20;
+/// The most passes before the quiesce point (for files that grow faster than they can be copied)
+const MAX_PASSES: usize = 16;
+
+/// Set while a relocation is running
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if a relocation is running
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
+
+/// Move the files on the batch volume to the volume at `to`, returning a report
+pub fn relocate_data(global: &impl GlobalInstanceLike, to: &str) -> QueryResult<String> {
+    relocate(global, to, None).map(|report| report.unwrap())
+}
+
+/// Move the files on the batch volume in the background, returning the id of the job. The job's result is the report
+pub fn relocate_data_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    to: &str,
+) -> QueryResult<u64> {
+    // a relocation that can't be done is refused right away
+    check(global, to)?;
+    let to: Box<str> = to.into();
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::RelocateData,
+        to.clone(),
+        move |global, job| match relocate(global, &to, Some(job)) {
+            Ok(Some(report)) => JobStatus::Completed(Some(report)),
+            Ok(None) => JobStatus::Cancelled,
+            Err(e) => JobStatus::failed(e),
+        },
+    ))
+}
+
+fn check(global: &impl GlobalInstanceLike, to: &str) -> QueryResult<DataRelocation> {
+    let Some(data_dir) = global.state().storage().data_dir() else {
+        return Err(QueryError::QExecDdlInvalidProperties.with_detail("storage", "memory"));
+    };
+    let relocation = DataRelocation::new(to);
+    // the new volume can't be (or be in) the current one
+    let copy = relocation.copy_path(&data_dir);
+    if to.is_empty() | (copy == data_dir) | copy.starts_with(&format!("{data_dir}/")) {
+        return Err(QueryError::QExecDdlInvalidProperties.with_detail("path", to));
+    }
+    if relocation.target_has_data() {
+        return Err(QueryError::QExecDdlNotEmpty.with_detail("path", to));
+    }
+    Ok(relocation)
+}
+
+/// Returns [`None`] if the job (if any) was cancelled
+fn relocate(
+    global: &impl GlobalInstanceLike,
+    to: &str,
+    job: Option<&Job>,
+) -> QueryResult<Option<String>> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(QueryError::QExecDdlObjectAlreadyExists.with_detail("relocation", "running"));
+    }
+    // a snapshot checks for a relocation the same way, so one of them always sees the other
+    if global.state().snapshots().is_running() {
+        RUNNING.store(false, Ordering::Release);
+        return Err(QueryError::QExecDdlObjectAlreadyExists.with_detail("snapshot", "running"));
+    }
+    let r = check(global, to).and_then(|relocation| {
+        let r = run(global, &relocation, job);
+        if !matches!(r, Ok(Some(_))) {
+            if let Err(e) = relocation.abort() {
+                warn!(
+                    "relocate: failed to remove the copies in {}: {e}",
+                    relocation.new_volume()
+                );
+            }
+        }
+        r
+    });
+    RUNNING.store(false, Ordering::Release);
+    r
+}
+
+fn run(
+    global: &impl GlobalInstanceLike,
+    relocation: &DataRelocation,
+    job: Option<&Job>,
+) -> QueryResult<Option<String>> {
+    info!(
+        "relocate: moving the data directory from {} to {}",
+        relocation.old_volume(),
+        relocation.new_volume()
+    );
+    let (mut copied, mut passes) = (0, 0);
+    loop {
+        let files = relocation.files()?;
+        if let Some(job) = job {
+            job.set_total(files.iter().map(|(_, len)| len).sum());
+        }
+        let mut pass = 0;
+        for (path, _) in &files {
+            loop {
+                let started_at = Instant::now();
+                let n = relocation.sync(path, COPY_BLOCK)?;
+                if n == 0 {
+                    break;
+                }
+                pass += n;
+                if let Some(job) = job {
+                    job.set_done(copied + pass);
+                    if job.token().is_cancelled() {
+                        return Ok(None);
+                    }
+                    global.scheduler().throttle(
+                        WorkClass::Relocate,
+                        n.div_ceil(1024),
+                        started_at.elapsed(),
+                    );
+                } else {
+                    global.scheduler().record(
+                        WorkClass::Relocate,
+                        n.div_ceil(1024),
+                        started_at.elapsed(),
+                    );
+                }
+            }
+        }
+        copied += pass;
+        passes += 1;
+        if (pass < QUIESCE_AT) | (passes == MAX_PASSES) {
+            break;
+        }
+    }
+    let files = switch_over(global, relocation, &mut copied)?;
+    info!(
+        "relocate: moved {files} file(s) to {} after {passes} pass(es)",
+        relocation.new_volume()
+    );
+    if let Err(e) = relocation.finish() {
+        warn!(
+            "relocate: failed to clean up {}: {e}. remove its data directory once the batch volume is set to {}",
+            relocation.old_volume(),
+            relocation.new_volume()
+        );
+    }
+    let mut report = String::from("{\"from\":");
+    encode_str(&mut report, relocation.old_volume());
+    report.push_str(",\"to\":");
+    encode_str(&mut report, relocation.new_volume());
+    report.push_str(&format!(
+        ",\"files\":{files},\"bytes\":{copied},\"passes\":{passes}}}"
+    ));
+    Ok(Some(report))
+}
+
+/// Copy what is left at the quiesce point, check every copy and switch over to the new volume, returning the number
+/// of files that were moved
+fn switch_over(
+    global: &impl GlobalInstanceLike,
+    relocation: &DataRelocation,
+    copied: &mut u64,
+) -> QueryResult<usize> {
+    // holding the models for writing waits for the writes in flight and holds back new ones (and DDL), and holding
+    // every batch driver holds back the flusher
+    let spaces = global.state().namespace().idx().read();
+    let models = global.state().namespace().idx_models().write();
+    let mut journals = vec![];
+    for (space_name, space) in spaces.iter() {
+        for model_name in space.models() {
+            let Some(model) = models.get(&EntityIDRef::new(space_name, model_name)) else {
+                continue;
+            };
+            for partition in 0..model.driver().partitions() {
+                journals.push((
+                    paths_v1::model_partition_path(
+                        space_name,
+                        space.get_uuid(),
+                        model_name,
+                        model.data().get_uuid(),
+                        partition,
+                    ),
+                    model.driver().batch_driver(partition).lock(),
+                ));
+            }
+        }
+    }
+    let files = relocation.files()?;
+    for (path, _) in &files {
+        loop {
+            match relocation.sync(path, COPY_BLOCK)? {
+                0 => break,
+                n => *copied += n,
+            }
+        }
+        // a file that was rewritten in place is copied again
+        if !relocation.verify(path)? {
+            *copied += relocation.resync(path)?;
+            if !relocation.verify(path)? {
+                return Err(QueryError::SysServerError.with_detail("file", path));
+            }
+        }
+    }
+    relocation.prune(&files)?;
+    paths_v1::relocate_batches(relocation.new_volume());
+    let mut reopened = 0;
+    let r: RuntimeResult<()> = journals.iter_mut().try_for_each(|(path, journal)| {
+        reopen(journal, &relocation.copy_path(path))?;
+        reopened += 1;
+        Ok(())
+    });
+    if let Err(e) = r {
+        // go back to the old volume, along with the journals that were already switched
+        paths_v1::relocate_batches(relocation.old_volume());
+        for (path, journal) in &mut journals[..reopened] {
+            if let Err(e) = reopen(journal, path) {
+                error!("relocate: failed to reopen {path}: {e}");
+            }
+        }
+        return Err(e.into());
+    }
+    Ok(files.len())
+}
+
+fn reopen(journal: &mut Option<Box<dyn ModelJournal>>, path: &str) -> RuntimeResult<()> {
+    match journal {
+        Some(journal) => journal.relocate(path),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,302 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    sessions
+    ---
+    a connection can override some settings for the rest of its session with `set <setting> = <value>` (and go back
+    to what the server uses with `set <setting> = default`):
+    - `durability` (`relaxed` or `strict`) is used instead of the durability of every model that the session writes
+      to. any session can ask for `strict`, but only if `system.relaxed_sessions` is set can a session ask for
+      `relaxed` (which trades the durability of strict models for speed, say while loading a batch)
+    - `isolation` (`read_committed` or `snapshot`). by default, a statement reads every row as it is when it gets to
+      the row. under `snapshot`, a select of several keys locks all of its rows before it reads any of them (just like
+      `fetch`), so they're read as of the same point in time. scans still read every row as they get to it
+    - `statement_timeout` (in milliseconds) fails a statement that runs for longer with `QExecStatementTimeout`. it's
+      checked as the statement reads rows, and it also bounds how long the statement waits for row locks. it can't be
+      longer than `system.statement_timeout`, which applies to every statement (if it's set)
+    - `bulk_load` (`true` or `false`) loads the session's bulk inserts instead of inserting them row by row: the rows
+      skip the deltas and are written out as one batch for each partition before they're added to the index (see
+      `dml::bulk_load`). it's meant for import tools filling up a model
+
+    the settings are kept with the connection, and are put on the thread that runs a statement of the session for as
+    long as it runs (like notices), so that the executor can look them up without having them passed along
+*/
+
+use {
+    super::model::props::Durability,
+    crate::engine::{
+        error::{QueryError, QueryResult},
+        ql::ddl::{SessionSet, SetValue},
+    },
+    std::{
+        sync::atomic::{AtomicBool, AtomicU64, Ordering},
+        time::{Duration, Instant},
+    },
+};
+
+/// The longest that any statement can run, in milliseconds (0 doesn't limit statements)
+static STATEMENT_TIMEOUT_MS: AtomicU64 = AtomicU64::new(0);
+/// If set, sessions can ask for relaxed durability
+static RELAXED_SESSIONS: AtomicBool = AtomicBool::new(false);
+
+local! {
+    static CURRENT: Option<Current> = None;
+}
+
+/// Set the longest that any statement can run (in milliseconds, 0 doesn't limit statements), and if sessions can ask
+/// for relaxed durability
+pub fn set_policy(statement_timeout: u64, relaxed_sessions: bool) {
+    STATEMENT_TIMEOUT_MS.store(statement_timeout, Ordering::Relaxed);
+    RELAXED_SESSIONS.store(relaxed_sessions, Ordering::Relaxed);
+}
+
+/// How the rows read by a statement relate to each other
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Isolation {
+    /// every row is read as it is when the statement gets to it
+    #[default]
+    ReadCommitted,
+    /// the rows of a select of several keys are read as of the same point in time
+    Snapshot,
+}
+
+impl Isolation {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ReadCommitted => "read_committed",
+            Self::Snapshot => "snapshot",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "read_committed" => Some(Self::ReadCommitted),
+            "snapshot" => Some(Self::Snapshot),
+            _ => None,
+        }
+    }
+}
+
+/// The settings that a session has overridden
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct SessionSettings {
+    durability: Option<Durability>,
+    isolation: Isolation,
+    /// in milliseconds (0 doesn't limit the session's statements)
+    statement_timeout: Option<u64>,
+    bulk_load: bool,
+}
+
+impl SessionSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn durability(&self) -> Option<Durability> {
+        self.durability
+    }
+    pub fn isolation(&self) -> Isolation {
+        self.isolation
+    }
+    /// Returns the longest that a statement of the session can run, in milliseconds
+    pub fn statement_timeout(&self) -> Option<u64> {
+        match self.statement_timeout {
+            Some(0) => None,
+            Some(ms) => Some(ms),
+            None => match STATEMENT_TIMEOUT_MS.load(Ordering::Relaxed) {
+                0 => None,
+                ms => Some(ms),
+            },
+        }
+    }
+    /// Returns true if the session's bulk inserts are loaded (instead of inserted row by row)
+    pub fn bulk_load(&self) -> bool {
+        self.bulk_load
+    }
+    /// Override a setting (or go back to what the server uses), if the server allows it
+    pub fn set(&mut self, set: &SessionSet) -> QueryResult<()> {
+        let setting = set.setting().as_str().to_ascii_lowercase();
+        let invalid = || QueryError::QLInvalidSyntax.with_detail("setting", &setting);
+        match (setting.as_str(), set.value()) {
+            ("durability", SetValue::Default) => self.durability = None,
+            ("durability", SetValue::Name(name)) => {
+                let durability = Durability::from_name(name.as_str()).ok_or_else(invalid)?;
+                if (durability == Durability::Relaxed) & !RELAXED_SESSIONS.load(Ordering::Relaxed) {
+                    return Err(QueryError::SysPermissionDenied.with_detail("setting", &setting));
+                }
+                self.durability = Some(durability);
+            }
+            ("isolation", SetValue::Default) => self.isolation = Isolation::ReadCommitted,
+            ("isolation", SetValue::Name(name)) => {
+                self.isolation = Isolation::from_name(name.as_str()).ok_or_else(invalid)?;
+            }
+            ("statement_timeout", SetValue::Default) => self.statement_timeout = None,
+            ("statement_timeout", SetValue::Lit(lit)) => {
+                let ms = lit.try_uint().ok_or_else(invalid)?;
+                // a session can only shorten the server's timeout
+                let max = STATEMENT_TIMEOUT_MS.load(Ordering::Relaxed);
+                if (max != 0) & ((ms == 0) | (ms > max)) {
+                    return Err(QueryError::SysPermissionDenied.with_detail("max_ms", max));
+                }
+                self.statement_timeout = Some(ms);
+            }
+            ("bulk_load", SetValue::Default) => self.bulk_load = false,
+            ("bulk_load", SetValue::Lit(lit)) => {
+                self.bulk_load = lit.try_bool().ok_or_else(invalid)?;
+            }
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+}
+
+/// The settings of the statement that is running on this thread
+struct Current {
+    durability: Option<Durability>,
+    isolation: Isolation,
+    /// when the statement times out, and its timeout (in milliseconds)
+    deadline: Option<(Instant, u64)>,
+}
+
+/// Keeps the settings of a session on this thread until it's dropped (see [`enter`])
+pub struct SessionGuard(());
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        local_mut!(CURRENT, |current| *current = None)
+    }
+}
+
+/// Use the settings of a session for the statement that is about to run on this thread (until the guard is dropped).
+/// The statement's timeout starts now
+pub fn enter(settings: &SessionSettings) -> SessionGuard {
+    let deadline = settings
+        .statement_timeout()
+        .map(|ms| (Instant::now() + Duration::from_millis(ms), ms));
+    local_mut!(CURRENT, |current| {
+        *current = Some(Current {
+            durability: settings.durability,
+            isolation: settings.isolation,
+            deadline,
+        })
+    });
+    SessionGuard(())
+}
+
+/// Returns the durability that the statement running on this thread asked for, if it overrides the model's
+pub fn durability() -> Option<Durability> {
+    local_ref!(CURRENT, |current| current
+        .as_ref()
+        .and_then(|c| c.durability))
+}
+
+/// Returns the isolation of the statement running on this thread
+pub fn isolation() -> Isolation {
+    local_ref!(CURRENT, |current| current
+        .as_ref()
+        .map_or(Isolation::ReadCommitted, |c| c.isolation))
+}
+
+/// Returns when the statement running on this thread times out (and its timeout in milliseconds), if it has a timeout
+pub fn deadline() -> Option<(Instant, u64)> {
+    local_ref!(CURRENT, |current| current.as_ref().and_then(|c| c.deadline))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{set_policy, Isolation, SessionSettings},
+        crate::engine::{
+            core::model::props::Durability,
+            data::lit::Lit,
+            error::{QueryError, QueryResult},
+            ql::ddl::{SessionSet, SetValue},
+        },
+    };
+
+    fn set(settings: &mut SessionSettings, setting: &str, value: SetValue) -> QueryResult<()> {
+        settings.set(&SessionSet::new(setting.into(), value))
+    }
+
+    fn set_name(settings: &mut SessionSettings, setting: &str, name: &str) -> QueryResult<()> {
+        set(settings, setting, SetValue::Name(name.into()))
+    }
+
+    fn set_timeout(settings: &mut SessionSettings, timeout: Lit) -> QueryResult<()> {
+        set(settings, "statement_timeout", SetValue::Lit(timeout))
+    }
+
+    #[test]
+    fn settings() {
+        let mut settings = SessionSettings::new();
+        // relaxing durability is up to the server
+        assert_eq!(
+            set_name(&mut settings, "durability", "relaxed"),
+            Err(QueryError::SysPermissionDenied)
+        );
+        set_name(&mut settings, "durability", "strict").unwrap();
+        assert_eq!(settings.durability(), Some(Durability::Strict));
+        set_policy(0, true);
+        set_name(&mut settings, "durability", "relaxed").unwrap();
+        set_policy(0, false);
+        assert_eq!(settings.durability(), Some(Durability::Relaxed));
+        set(&mut settings, "durability", SetValue::Default).unwrap();
+        assert_eq!(settings.durability(), None);
+        // isolation
+        set_name(&mut settings, "ISOLATION", "snapshot").unwrap();
+        assert_eq!(settings.isolation(), Isolation::Snapshot);
+        assert_eq!(
+            set_name(&mut settings, "isolation", "serializable"),
+            Err(QueryError::QLInvalidSyntax)
+        );
+        // timeouts
+        set_timeout(&mut settings, Lit::new_uint(100)).unwrap();
+        assert_eq!(settings.statement_timeout(), Some(100));
+        set_timeout(&mut settings, Lit::new_uint(0)).unwrap();
+        assert_eq!(settings.statement_timeout(), None);
+        assert_eq!(
+            set_timeout(&mut settings, Lit::new_str("1s")),
+            Err(QueryError::QLInvalidSyntax)
+        );
+        // bulk loads
+        set(
+            &mut settings,
+            "bulk_load",
+            SetValue::Lit(Lit::new_bool(true)),
+        )
+        .unwrap();
+        assert!(settings.bulk_load());
+        assert_eq!(
+            set(&mut settings, "bulk_load", SetValue::Lit(Lit::new_uint(1))),
+            Err(QueryError::QLInvalidSyntax)
+        );
+        set(&mut settings, "bulk_load", SetValue::Default).unwrap();
+        assert!(!settings.bulk_load());
+        assert_eq!(
+            set_name(&mut settings, "timezone", "utc"),
+            Err(QueryError::QLInvalidSyntax)
+        );
+    }
+}
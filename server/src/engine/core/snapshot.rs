@@ -0,0 +1,511 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    snapshots
+    ---
+    `sysctl create snapshot <name>` copies the global journal and the batch file of every model partition into a
+    directory of its own in the snapshots directory of the batch volume (see `storage::SnapshotFiles` for the layout),
+    so that a model can be brought back from it with `sysctl restore model ... from snapshot` without any external
+    tooling. the instance is frozen while the snapshot is taken (see [`Freeze::hold`]): the writes that got past the
+    freeze are waited for, everything that is pending is written out, and then every journal is read while holding its
+    driver's lock (so that a copy never ends with a partially written batch) and written out after it. the freeze is
+    lifted once the journals are copied, and the snapshot's manifest is written last. a snapshot that fails (or whose
+    job is cancelled) is removed.
+
+    `sysctl create snapshot <name> every '<interval>' keep <n>` schedules snapshots instead. every interval, a
+    snapshot named `<name>_<time>` is taken as a system job (see `fractal::snapshot`), and then all but the newest `n`
+    complete snapshots of the schedule are removed. schedules are kept in the snapshots directory, so they survive
+    restarts, and when a schedule is due is worked out from the newest of its snapshots.
+
+    a snapshot that a restore is reading from is never removed, by hand or by a schedule: restores register the
+    snapshot that they read (see [`Snapshots::restoring`]) and a removal is refused (or, for a schedule, put off to
+    its next run) while it is registered. restores are matched by the snapshot's path as `sysctl list snapshots` shows
+    it. only one snapshot is taken at a time, and never while the data is being relocated (see `core::relocate`)
+
+    [`Freeze::hold`]: super::Freeze::hold
+*/
+
+use {
+    crate::{
+        engine::{
+            core::{relocate, EntityIDRef},
+            error::{QueryError, QueryResult},
+            fractal::{
+                error::Error,
+                jobs::{self, Job, JobKind, JobStatus},
+                GlobalInstanceLike, ModelUniqueID,
+            },
+            storage::{
+                encode_str,
+                safe_interfaces::{paths_v1, FileSystem},
+                SnapshotEntry, SnapshotFiles, SnapshotManifest, SnapshotSchedule,
+            },
+            RuntimeResult,
+        },
+        util::os,
+    },
+    parking_lot::Mutex,
+    std::sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The shortest interval that snapshots can be scheduled at (in seconds)
+pub const MIN_INTERVAL: u64 = 60;
+
+#[derive(Debug, Default)]
+pub struct Snapshots {
+    /// set while a snapshot is being taken
+    running: AtomicBool,
+    /// every schedule along with the time (in seconds since the epoch) at which it is due
+    schedules: Mutex<Vec<(SnapshotSchedule, u64)>>,
+    /// the snapshots that restores are reading from
+    restoring: Mutex<Vec<Box<str>>>,
+}
+
+/// Keeps a snapshot from being removed until it is dropped
+#[derive(Debug)]
+pub struct Restoring<'a> {
+    snapshots: &'a Snapshots,
+    path: Box<str>,
+}
+
+impl Drop for Restoring<'_> {
+    fn drop(&mut self) {
+        let mut restoring = self.snapshots.restoring.lock();
+        if let Some(i) = restoring.iter().position(|path| *path == self.path) {
+            restoring.swap_remove(i);
+        }
+    }
+}
+
+impl Snapshots {
+    /// Returns true if a snapshot is being taken
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+    /// Register a restore from the snapshot at `path`, so that the snapshot isn't removed while it is read
+    pub fn restoring(&self, path: &str) -> Restoring<'_> {
+        let path: Box<str> = normalize(path).into();
+        self.restoring.lock().push(path.clone());
+        Restoring {
+            snapshots: self,
+            path,
+        }
+    }
+    /// Load the schedules from the snapshots directory
+    pub fn load_schedules(&self) -> RuntimeResult<()> {
+        let schedules = SnapshotFiles::read_schedules(&paths_v1::snapshot_schedules_path())?;
+        let snapshots = SnapshotFiles::list(&paths_v1::snapshots_dir())?;
+        *self.schedules.lock() = schedules
+            .into_iter()
+            .map(|schedule| {
+                let due_at = due_at(&schedule, &snapshots);
+                (schedule, due_at)
+            })
+            .collect();
+        Ok(())
+    }
+    fn persist(schedules: &[(SnapshotSchedule, u64)]) -> RuntimeResult<()> {
+        let schedules: Vec<SnapshotSchedule> = schedules.iter().map(|(s, _)| s.clone()).collect();
+        SnapshotFiles::write_schedules(&paths_v1::snapshot_schedules_path(), &schedules)
+    }
+}
+
+/// Take a snapshot named `name`, returning a report
+pub fn create_snapshot(global: &impl GlobalInstanceLike, name: &str) -> QueryResult<String> {
+    take(global, name, None, None).map(|report| report.unwrap())
+}
+
+/// Take a snapshot named `name` in the background, returning the id of the job. The job's result is the report
+pub fn create_snapshot_in_background<G: GlobalInstanceLike>(
+    global: &G,
+    owner: &str,
+    name: &str,
+) -> QueryResult<u64> {
+    // a snapshot that can't be taken is refused right away
+    check(global, name)?;
+    let name: Box<str> = name.into();
+    Ok(jobs::start(
+        global,
+        Some(owner),
+        JobKind::Snapshot,
+        name.clone(),
+        move |global, job| match take(global, &name, None, Some(job)) {
+            Ok(Some(report)) => JobStatus::Completed(Some(report)),
+            Ok(None) => JobStatus::Cancelled,
+            Err(e) => JobStatus::failed(e),
+        },
+    ))
+}
+
+/// Take a snapshot named `<name>_<time>` every `every` seconds, keeping the newest `keep` of them
+pub fn schedule_snapshots(
+    global: &impl GlobalInstanceLike,
+    name: &str,
+    every: u64,
+    keep: u64,
+) -> QueryResult<()> {
+    if global.state().storage().data_dir().is_none() {
+        return Err(QueryError::QExecDdlInvalidProperties.with_detail("storage", "memory"));
+    }
+    if every < MIN_INTERVAL {
+        return Err(QueryError::QExecDdlInvalidProperties.with_detail("every", every));
+    }
+    if keep == 0 {
+        return Err(QueryError::QExecDdlInvalidProperties.with_detail("keep", keep));
+    }
+    let mut schedules = global.state().snapshots().schedules.lock();
+    if schedules.iter().any(|(s, _)| &*s.name == name) {
+        return Err(QueryError::QExecDdlObjectAlreadyExists.with_detail("schedule", name));
+    }
+    let schedule = SnapshotSchedule {
+        name: name.into(),
+        every,
+        keep,
+    };
+    let due_at = due_at(&schedule, &SnapshotFiles::list(&paths_v1::snapshots_dir())?);
+    schedules.push((schedule, due_at));
+    if let Err(e) = Snapshots::persist(&schedules) {
+        schedules.pop();
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Remove a schedule. The snapshots that it took are kept
+pub fn drop_schedule(global: &impl GlobalInstanceLike, name: &str) -> QueryResult<()> {
+    let mut schedules = global.state().snapshots().schedules.lock();
+    let Some(i) = schedules.iter().position(|(s, _)| &*s.name == name) else {
+        return Err(QueryError::QExecObjectNotFound.with_detail("schedule", name));
+    };
+    let removed = schedules.remove(i);
+    if let Err(e) = Snapshots::persist(&schedules) {
+        schedules.insert(i, removed);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Remove a snapshot. A snapshot that is being restored from is never removed, and neither is an incomplete one
+/// while a snapshot is being taken
+pub fn drop_snapshot(global: &impl GlobalInstanceLike, name: &str) -> QueryResult<()> {
+    let path = paths_v1::snapshot_path(name);
+    if FileSystem::list_files(&path).is_err() {
+        return Err(QueryError::QExecObjectNotFound.with_detail("snapshot", name));
+    }
+    if global.state().snapshots().is_running() && SnapshotFiles::read_manifest(&path)?.is_none() {
+        return Err(QueryError::QExecDdlNotEmpty.with_detail("snapshot", "running"));
+    }
+    remove(global, name)
+}
+
+/// Returns every snapshot and schedule as a JSON object
+pub fn describe(global: &impl GlobalInstanceLike) -> QueryResult<String> {
+    let snapshots = global.state().snapshots();
+    let entries = SnapshotFiles::list(&paths_v1::snapshots_dir())?;
+    let restoring = snapshots.restoring.lock().clone();
+    let mut ret = String::from("{\"snapshots\":[");
+    for (i, entry) in entries.iter().enumerate() {
+        if i != 0 {
+            ret.push(',');
+        }
+        let path = paths_v1::snapshot_path(&entry.name);
+        ret.push_str("{\"name\":");
+        encode_str(&mut ret, &entry.name);
+        ret.push_str(",\"path\":");
+        encode_str(&mut ret, &path);
+        ret.push_str(&format!(
+            ",\"complete\":{},\"bytes\":{}",
+            entry.manifest.is_some(),
+            entry.bytes
+        ));
+        match &entry.manifest {
+            Some(manifest) => {
+                ret.push_str(&format!(
+                    ",\"created_at\":{},\"files\":{},\"schedule\":",
+                    manifest.created_at, manifest.files
+                ));
+                match &manifest.schedule {
+                    Some(schedule) => encode_str(&mut ret, schedule),
+                    None => ret.push_str("null"),
+                }
+            }
+            None => ret.push_str(",\"created_at\":null,\"files\":null,\"schedule\":null"),
+        }
+        ret.push_str(&format!(
+            ",\"restoring\":{}}}",
+            restoring.iter().any(|p| **p == *normalize(&path))
+        ));
+    }
+    ret.push_str("],\"schedules\":[");
+    for (i, (schedule, due_at)) in snapshots.schedules.lock().iter().enumerate() {
+        if i != 0 {
+            ret.push(',');
+        }
+        ret.push_str("{\"name\":");
+        encode_str(&mut ret, &schedule.name);
+        ret.push_str(&format!(
+            ",\"every\":{},\"keep\":{},\"due_at\":{due_at}}}",
+            schedule.every, schedule.keep
+        ));
+    }
+    ret.push_str("]}");
+    Ok(ret)
+}
+
+/// Take a snapshot for every schedule that is due, and then remove the snapshots of the schedule that expired
+pub fn run_schedules<G: GlobalInstanceLike>(global: &G) {
+    let now = os::get_epoch_time_secs();
+    // a schedule is due again an interval later, even if its snapshot fails
+    let due: Vec<SnapshotSchedule> = global
+        .state()
+        .snapshots()
+        .schedules
+        .lock()
+        .iter_mut()
+        .filter(|(_, due_at)| *due_at <= now)
+        .map(|(schedule, due_at)| {
+            *due_at = now + schedule.every;
+            schedule.clone()
+        })
+        .collect();
+    for schedule in due {
+        let name = format!("{}_{now}", schedule.name);
+        let status = jobs::run(
+            global,
+            JobKind::Snapshot,
+            name.clone().into_boxed_str(),
+            |global, job| match take(global, &name, Some(&*schedule.name), Some(job)) {
+                Ok(Some(report)) => JobStatus::Completed(Some(report)),
+                Ok(None) => JobStatus::Cancelled,
+                Err(e) => JobStatus::failed(e),
+            },
+        );
+        if let JobStatus::Failed(e) = status {
+            warn!(
+                "snapshot: schedule {} failed to take {name} (error {e})",
+                schedule.name
+            );
+        }
+        retain(global, &schedule);
+    }
+}
+
+/// Remove all but the newest snapshots of a schedule. A snapshot that is being restored from is kept until the
+/// schedule runs again
+fn retain(global: &impl GlobalInstanceLike, schedule: &SnapshotSchedule) {
+    let mut taken: Vec<(u64, Box<str>)> = match SnapshotFiles::list(&paths_v1::snapshots_dir()) {
+        Ok(entries) => entries
+            .into_iter()
+            .filter_map(|entry| {
+                let manifest = entry.manifest?;
+                (manifest.schedule.as_deref() == Some(&*schedule.name))
+                    .then_some((manifest.created_at, entry.name))
+            })
+            .collect(),
+        Err(e) => {
+            warn!(
+                "snapshot: failed to list the snapshots of {}: {e}",
+                schedule.name
+            );
+            return;
+        }
+    };
+    taken.sort_by(|a, b| b.cmp(a));
+    for (_, name) in taken.iter().skip(schedule.keep as usize) {
+        match remove(global, name) {
+            Ok(()) => info!("snapshot: removed {name}, which expired"),
+            Err(e) => warn!("snapshot: kept {name}, which expired ({e})"),
+        }
+    }
+}
+
+/// Remove a snapshot unless a restore is reading from it
+fn remove(global: &impl GlobalInstanceLike, name: &str) -> QueryResult<()> {
+    let path = paths_v1::snapshot_path(name);
+    // held while the snapshot is removed, so that a restore that starts in the meantime finds it gone
+    let restoring = global.state().snapshots().restoring.lock();
+    if restoring.iter().any(|p| **p == *normalize(&path)) {
+        return Err(QueryError::QExecDdlNotEmpty.with_detail("restoring", name));
+    }
+    SnapshotFiles::remove(&path)?;
+    Ok(())
+}
+
+fn check(global: &impl GlobalInstanceLike, name: &str) -> QueryResult<String> {
+    if global.state().storage().data_dir().is_none() {
+        return Err(QueryError::QExecDdlInvalidProperties.with_detail("storage", "memory"));
+    }
+    let path = paths_v1::snapshot_path(name);
+    if FileSystem::list_files(&path).is_ok() {
+        return Err(QueryError::QExecDdlObjectAlreadyExists.with_detail("snapshot", name));
+    }
+    Ok(path)
+}
+
+/// Returns [`None`] if the job (if any) was cancelled
+fn take(
+    global: &impl GlobalInstanceLike,
+    name: &str,
+    schedule: Option<&str>,
+    job: Option<&Job>,
+) -> QueryResult<Option<String>> {
+    let snapshots = global.state().snapshots();
+    if snapshots.running.swap(true, Ordering::SeqCst) {
+        return Err(QueryError::QExecDdlObjectAlreadyExists.with_detail("snapshot", "running"));
+    }
+    // a relocation checks for a snapshot the same way, so one of them always sees the other
+    let r = if relocate::is_running() {
+        Err(QueryError::QExecDdlObjectAlreadyExists.with_detail("relocation", "running"))
+    } else {
+        check(global, name).and_then(|path| {
+            let r = copy(global, &path, schedule, job);
+            if !matches!(r, Ok(Some(_))) {
+                if let Err(e) = SnapshotFiles::remove(&path) {
+                    warn!("snapshot: failed to remove the incomplete snapshot {path}: {e}");
+                }
+            }
+            r
+        })
+    };
+    snapshots.running.store(false, Ordering::Release);
+    r.map(|manifest| {
+        manifest.map(|manifest| {
+            let mut report = String::from("{\"name\":");
+            encode_str(&mut report, name);
+            report.push_str(",\"path\":");
+            encode_str(&mut report, &paths_v1::snapshot_path(name));
+            report.push_str(&format!(
+                ",\"files\":{},\"bytes\":{}}}",
+                manifest.files, manifest.bytes
+            ));
+            report
+        })
+    })
+}
+
+fn copy(
+    global: &impl GlobalInstanceLike,
+    path: &str,
+    schedule: Option<&str>,
+    job: Option<&Job>,
+) -> QueryResult<Option<SnapshotManifest>> {
+    FileSystem::create_dir_all(path).map_err(Error::from)?;
+    let (mut bytes, mut files) = (0, 0);
+    let mut write = |journal: &str, contents: Vec<u8>| -> RuntimeResult<()> {
+        bytes += contents.len() as u64;
+        files += 1;
+        SnapshotFiles::write_journal(path, journal, &contents)
+    };
+    {
+        let _freeze = global.state().freeze().hold();
+        // writes that got past the check before the freeze hold the models for reading, so wait for them to finish
+        drop(global.state().namespace().idx_models().write());
+        // DDL is held back by the freeze, so what is flushed here is what is copied
+        let spaces = global.state().namespace().idx().read();
+        let models = global.state().namespace().idx_models().read();
+        for (id, model) in models.iter() {
+            model.driver().flush(
+                global,
+                ModelUniqueID::new(id.space(), id.entity(), model.data().get_uuid()),
+                model.data(),
+            )?;
+        }
+        if let Some(job) = job {
+            job.set_total(
+                1 + models
+                    .values()
+                    .map(|model| model.driver().partitions() as u64)
+                    .sum::<u64>(),
+            );
+        }
+        let gns = paths_v1::gns_path();
+        let contents = {
+            let _driver = global.state().gns_driver().lock_journal();
+            FileSystem::read(&gns).map_err(Error::from)?
+        };
+        write(&gns, contents)?;
+        let mut done = 1;
+        for (space_name, space) in spaces.iter() {
+            for model_name in space.models() {
+                let Some(model) = models.get(&EntityIDRef::new(space_name, model_name)) else {
+                    continue;
+                };
+                for partition in 0..model.driver().partitions() {
+                    if let Some(job) = job {
+                        job.set_done(done);
+                        if job.token().is_cancelled() {
+                            return Ok(None);
+                        }
+                    }
+                    let journal = paths_v1::model_partition_path(
+                        space_name,
+                        space.get_uuid(),
+                        model_name,
+                        model.data().get_uuid(),
+                        partition,
+                    );
+                    let contents = {
+                        let _driver = model.driver().batch_driver(partition).lock();
+                        FileSystem::read(&journal).map_err(Error::from)?
+                    };
+                    write(&journal, contents)?;
+                    done += 1;
+                }
+            }
+        }
+        if let Some(job) = job {
+            job.set_done(done);
+        }
+    }
+    let manifest = SnapshotManifest {
+        created_at: os::get_epoch_time_secs(),
+        bytes,
+        files,
+        schedule: schedule.map(Into::into),
+    };
+    SnapshotFiles::write_manifest(path, &manifest)?;
+    Ok(Some(manifest))
+}
+
+/// Returns when the schedule is due: an interval after its newest snapshot, or right away if it has none
+fn due_at(schedule: &SnapshotSchedule, snapshots: &[SnapshotEntry]) -> u64 {
+    snapshots
+        .iter()
+        .filter_map(|entry| entry.manifest.as_ref())
+        .filter(|manifest| manifest.schedule.as_deref() == Some(&*schedule.name))
+        .map(|manifest| manifest.created_at + schedule.every)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Paths are compared without a leading `./` or a trailing `/`
+fn normalize(mut path: &str) -> &str {
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    path.trim_end_matches('/')
+}
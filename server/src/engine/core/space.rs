@@ -25,11 +25,11 @@
 */
 
 use {
-    super::EntityIDRef,
+    super::{catalog, EntityIDRef},
     crate::engine::{
         data::{dict, uuid::Uuid, DictEntryGeneric, DictGeneric},
         error::{QueryError, QueryResult},
-        fractal::{GenericTask, GlobalInstanceLike, Task},
+        fractal::GlobalInstanceLike,
         idx::STIndex,
         ql::ddl::{alt::AlterSpace, crt::CreateSpace, drop::DropSpace},
         txn::{self, SpaceIDRef},
@@ -60,7 +60,6 @@ impl Space {
             props,
         }
     }
-    #[cfg(test)]
     pub fn new_auto_all() -> Self {
         Self::new_auto(Default::default(), Default::default())
     }
@@ -155,7 +154,10 @@ impl Space {
         } = Self::process_create(space)?;
         // lock the global namespace
         global.state().namespace().ddl_with_spaces_write(|spaces| {
-            if spaces.st_contains(&space_name) {
+            // checked under the lock so that nothing changes after a freeze returns
+            global.state().freeze().check_instance()?;
+            // the catalog is in the `sys` space, which always exists
+            if spaces.st_contains(&space_name) | (space_name.as_ref() == catalog::SYS_SPACE) {
                 if if_not_exists {
                     return Ok(Some(false));
                 } else {
@@ -171,12 +173,7 @@ impl Space {
             global.state().gns_driver().driver_context(
                 global,
                 |drv| drv.commit_event(txn),
-                || {
-                    global.taskmgr_post_standard_priority(Task::new(GenericTask::delete_space_dir(
-                        &space_name,
-                        space.get_uuid(),
-                    )))
-                },
+                || global.purge_space(&space_name, space.get_uuid()),
             )?;
             // update global state
             let _ = spaces.st_insert(space_name, space);
@@ -199,6 +196,7 @@ impl Space {
             .state()
             .namespace()
             .ddl_with_space_mut(&space_name, |space| {
+                global.state().freeze().check(&space_name)?;
                 match updated_props.get(Self::KEY_ENV) {
                     Some(DictEntryGeneric::Map(_)) if updated_props.len() == 1 => {}
                     Some(DictEntryGeneric::Data(l)) if updated_props.len() == 1 && l.is_null() => {}
@@ -245,14 +243,16 @@ impl Space {
                 .state()
                 .namespace()
                 .ddl_with_all_mut(|spaces, models| {
-                    let Some(space) = spaces.remove(space_name.as_str()) else {
+                    global.state().freeze().check(&space_name)?;
+                    let Some(space) = spaces.get(space_name.as_str()) else {
                         if if_exists {
                             return Ok(Some(false));
                         } else {
                             return Err(QueryError::QExecObjectNotFound);
                         }
                     };
-                    // commit drop
+                    // nothing changes until the drop is committed: if the commit fails, the space and all its models
+                    // are left as they were. once it's committed, the models are gone on restore as well
                     // prepare txn
                     let txn =
                         txn::gns::space::DropSpaceTxn::new(SpaceIDRef::new(&space_name, &space));
@@ -262,16 +262,13 @@ impl Space {
                         |drv| drv.commit_event(txn),
                         || {},
                     )?;
-                    // request cleanup
-                    global.taskmgr_post_standard_priority(Task::new(
-                        GenericTask::delete_space_dir(&space_name, space.get_uuid()),
-                    ));
-                    for model in space.models.into_iter() {
-                        let e: EntityIDRef<'static> = unsafe {
-                            // UNSAFE(@ohsayan): I want to try what the borrow checker has been trying
-                            core::mem::transmute(EntityIDRef::new(space_name.as_str(), &model))
+                    let space = spaces.remove(space_name.as_str()).unwrap();
+                    for model in space.models.iter() {
+                        let Some(mdl) =
+                            models.remove(&EntityIDRef::new(space_name.as_str(), model))
+                        else {
+                            continue;
                         };
-                        let mdl = models.st_delete_return(&e).unwrap();
                         // no need to purge model drive since the dir itself is deleted. our work here is to just
                         // remove this from the linked models from the model ns. but we should update the global state
                         if mdl.driver().status().is_iffy() {
@@ -279,7 +276,9 @@ impl Space {
                             global.health().report_removal_of_faulty_source();
                         }
                     }
-                    let _ = spaces.st_delete(space_name.as_str());
+                    // request cleanup (only once the models are gone, so that nothing is written to the directory
+                    // after it's deleted). if we go down before this runs, it's finished on restore
+                    global.purge_space(&space_name, space.get_uuid());
                     if if_exists {
                         Ok(Some(true))
                     } else {
@@ -288,6 +287,7 @@ impl Space {
                 })
         } else {
             global.state().namespace().ddl_with_spaces_write(|spaces| {
+                global.state().freeze().check(&space_name)?;
                 let Some(space) = spaces.get(space_name.as_str()) else {
                     if if_exists {
                         return Ok(Some(false));
@@ -309,10 +309,7 @@ impl Space {
                     || {},
                 )?;
                 // request cleanup
-                global.taskmgr_post_standard_priority(Task::new(GenericTask::delete_space_dir(
-                    &space_name,
-                    space.get_uuid(),
-                )));
+                global.purge_space(&space_name, space.get_uuid());
                 let _ = spaces.st_delete(space_name.as_str());
                 if if_exists {
                     Ok(Some(true))
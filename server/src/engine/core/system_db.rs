@@ -25,13 +25,16 @@
 */
 
 use {
-    super::RWLIdx,
+    super::{quota::Quota, RWLIdx},
     crate::engine::{
         error::{QueryError, QueryResult},
         fractal::GlobalInstanceLike,
-        txn::gns::sysctl::{AlterUserTxn, CreateUserTxn, DropUserTxn},
+        txn::gns::sysctl::{
+            AlterUserQuotaTxn, AlterUserTxn, CreateStatementTxn, CreateUserTxn, DropStatementTxn,
+            DropUserTxn,
+        },
     },
-    std::collections::hash_map::Entry,
+    std::collections::{hash_map::Entry, HashMap},
 };
 
 #[derive(Debug)]
@@ -42,17 +45,51 @@ pub struct SystemDatabase {
 #[derive(Debug, PartialEq)]
 pub struct User {
     phash: Box<[u8]>,
+    statements: HashMap<Box<str>, StoredStatement>,
+    quota: Quota,
 }
 
 impl User {
     pub fn new(password_hash: Box<[u8]>) -> Self {
         Self {
             phash: password_hash,
+            statements: HashMap::new(),
+            quota: Quota::default(),
         }
     }
     pub fn hash(&self) -> &[u8] {
         &self.phash
     }
+    /// Returns the statements that this user has stored (by name)
+    pub fn statements(&self) -> &HashMap<Box<str>, StoredStatement> {
+        &self.statements
+    }
+    /// Returns the limits on the statements that this user runs every second
+    pub fn quota(&self) -> &Quota {
+        &self.quota
+    }
+}
+
+/// A statement stored with `CREATE STATEMENT`, that can be run by its owner with `EXECUTE`
+#[derive(Debug, PartialEq, Clone)]
+pub struct StoredStatement {
+    body: Box<str>,
+    params: usize,
+}
+
+impl StoredStatement {
+    pub fn new(body: Box<str>) -> Self {
+        // the body is rebuilt from its tokens (and has no literals), so every `?` is a placeholder
+        let params = body.bytes().filter(|b| *b == b'?').count();
+        Self { body, params }
+    }
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+    /// Returns the number of placeholders in the body
+    pub fn params(&self) -> usize {
+        self.params
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -79,6 +116,13 @@ impl SystemDatabase {
     pub fn users(&self) -> &RWLIdx<Box<str>, User> {
         &self.users
     }
+    /// Returns the statement stored by the given user under this name, if there is one
+    pub fn statement(&self, username: &str, name: &str) -> Option<StoredStatement> {
+        self.users
+            .read()
+            .get(username)
+            .and_then(|user| user.statements.get(name).cloned())
+    }
     pub fn verify_user(&self, username: &str, password: &[u8]) -> VerifyUser {
         self.users
             .read()
@@ -120,6 +164,33 @@ impl SystemDatabase {
             None => false,
         }
     }
+    pub fn __raw_create_statement(&self, username: &str, name: Box<str>, body: Box<str>) -> bool {
+        match self.users.write().get_mut(username) {
+            Some(user) => match user.statements.entry(name) {
+                Entry::Vacant(ve) => {
+                    ve.insert(StoredStatement::new(body));
+                    true
+                }
+                Entry::Occupied(_) => false,
+            },
+            None => false,
+        }
+    }
+    pub fn __raw_drop_statement(&self, username: &str, name: &str) -> bool {
+        self.users
+            .write()
+            .get_mut(username)
+            .map_or(false, |user| user.statements.remove(name).is_some())
+    }
+    pub fn __raw_alter_quota(&self, username: &str, reads: u64, writes: u64) -> bool {
+        match self.users.write().get_mut(username) {
+            Some(user) => {
+                user.quota = Quota::new(reads, writes);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 impl SystemDatabase {
@@ -175,4 +246,73 @@ impl SystemDatabase {
         let _ = users.remove(username);
         Ok(())
     }
+    pub fn create_statement(
+        &self,
+        global: &impl GlobalInstanceLike,
+        username: &str,
+        name: &str,
+        body: String,
+    ) -> QueryResult<()> {
+        let mut users = self.users.write();
+        let Some(user) = users.get_mut(username) else {
+            return Err(QueryError::SysAuthError);
+        };
+        if user.statements.contains_key(name) {
+            return Err(QueryError::QExecDdlObjectAlreadyExists);
+        }
+        global.state().gns_driver().driver_context(
+            global,
+            |drv| drv.commit_event(CreateStatementTxn::new(username, name, &body)),
+            || {},
+        )?;
+        user.statements
+            .insert(name.into(), StoredStatement::new(body.into_boxed_str()));
+        Ok(())
+    }
+    pub fn drop_statement(
+        &self,
+        global: &impl GlobalInstanceLike,
+        username: &str,
+        name: &str,
+    ) -> QueryResult<()> {
+        let mut users = self.users.write();
+        let Some(user) = users.get_mut(username) else {
+            return Err(QueryError::SysAuthError);
+        };
+        if !user.statements.contains_key(name) {
+            return Err(QueryError::QExecObjectNotFound);
+        }
+        global.state().gns_driver().driver_context(
+            global,
+            |drv| drv.commit_event(DropStatementTxn::new(username, name)),
+            || {},
+        )?;
+        let _ = user.statements.remove(name);
+        Ok(())
+    }
+    /// Set the quota of a user (`sysctl alter quota user ...`)
+    pub fn alter_quota(
+        &self,
+        global: &impl GlobalInstanceLike,
+        username: &str,
+        quota: Quota,
+    ) -> QueryResult<()> {
+        let mut users = self.users.write();
+        let Some(user) = users.get_mut(username) else {
+            return Err(QueryError::SysAuthError);
+        };
+        global.state().gns_driver().driver_context(
+            global,
+            |drv| {
+                drv.commit_event(AlterUserQuotaTxn::new(
+                    username,
+                    quota.reads(),
+                    quota.writes(),
+                ))
+            },
+            || {},
+        )?;
+        user.quota = quota;
+        Ok(())
+    }
 }
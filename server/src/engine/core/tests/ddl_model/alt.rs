@@ -76,8 +76,9 @@ fn exec_plan(
 mod plan {
     use crate::{
         engine::{
-            core::model::{self, alt::AlterAction, Field, Layer},
+            core::model::{self, alt::AlterAction, props::OverflowPolicy, Field, Layer},
             error::QueryError,
+            idx::STIndex,
         },
         vecfuse,
     };
@@ -180,6 +181,53 @@ mod plan {
         );
     }
     #[test]
+    fn illegal_remove_or_update_computed_source() {
+        for alter in [
+            "alter model myspace.mymodel remove email",
+            "alter model myspace.mymodel update email { type: string, nullable: true }",
+        ] {
+            assert_eq!(
+                super::with_plan(
+                    "create model myspace.mymodel(username: string, email: string, email_lc: string { computed: \"lower(email)\" })",
+                    alter,
+                    |_| {}
+                )
+                .unwrap_err(),
+                QueryError::QExecDdlModelAlterIllegal
+            );
+        }
+    }
+    #[test]
+    fn illegal_remove_or_update_aggregated() {
+        for alter in [
+            "alter model myspace.mymodel remove city",
+            "alter model myspace.mymodel remove age",
+            "alter model myspace.mymodel update age { type: uint16 }",
+        ] {
+            assert_eq!(
+                super::with_plan(
+                    "create model myspace.mymodel(username: string, city: string, age: uint8) with { maintain: { age_by_city: \"sum(age) group by city\" } }",
+                    alter,
+                    |_| {}
+                )
+                .unwrap_err(),
+                QueryError::QExecDdlModelAlterIllegal
+            );
+        }
+    }
+    #[test]
+    fn illegal_add_computed() {
+        assert_eq!(
+            super::with_plan(
+                "create model myspace.mymodel(username: string, email: string)",
+                "alter model myspace.mymodel add email_lc { type: string { computed: \"lower(email)\" } }",
+                |_| {}
+            )
+            .unwrap_err(),
+            QueryError::QExecDdlModelAlterIllegal
+        );
+    }
+    #[test]
     fn illegal_add_pk() {
         assert_eq!(
             super::with_plan(
@@ -216,6 +264,32 @@ mod plan {
         );
     }
     #[test]
+    fn update_overflow() {
+        super::plan(
+            "create model myspace.mymodel(username: string, visits: uint8)",
+            "alter model myspace.mymodel update visits { type: uint8 { overflow: \"saturate\" } }",
+            |plan| {
+                let AlterAction::Update(fields) = plan.action else {
+                    panic!("expected an update")
+                };
+                assert_eq!(
+                    fields.st_get("visits").unwrap().props().overflow(),
+                    Some(OverflowPolicy::Saturate)
+                );
+            },
+        );
+        // floats can't wrap
+        assert_eq!(
+            super::with_plan(
+                "create model myspace.mymodel(username: string, score: float64)",
+                "alter model myspace.mymodel update score { type: float64 { overflow: \"wrap\" } }",
+                |_| {}
+            )
+            .unwrap_err(),
+            QueryError::QExecDdlInvalidProperties
+        );
+    }
+    #[test]
     fn illegal_update_nx() {
         assert_eq!(
             super::with_plan(
@@ -431,6 +505,142 @@ mod exec {
         .unwrap();
     }
     #[test]
+    fn update_checks_existing_data() {
+        use crate::engine::{
+            core::{dml, model::ModelData, EntityIDRef},
+            fractal::GlobalInstanceLike,
+            ql::{ast::parse_ast_node_full, tests::lex_insecure},
+        };
+        let global = TestGlobal::new_with_driver_id_instant_update("update_checks_existing_data");
+        super::exec_create(
+            &global,
+            "create model myspace.mymodel(username: string, age: uint8)",
+            true,
+        )
+        .unwrap();
+        for insert in [
+            "insert into myspace.mymodel('sayan', 21)",
+            "insert into myspace.mymodel('robot', 12)",
+        ] {
+            let tok = lex_insecure(insert.as_bytes()).unwrap();
+            dml::insert(&global, parse_ast_node_full(&tok[1..]).unwrap()).unwrap();
+        }
+        let alter = |query: &str| {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            ModelData::transactional_exec_alter(&global, parse_ast_node_full(&tok[2..]).unwrap())
+        };
+        // one of the rows violates the check, so the alter fails
+        assert_eq!(
+            alter("alter model myspace.mymodel update age { type: uint8 { min: 13 } }")
+                .unwrap_err(),
+            QueryError::QExecDmlCheckMinViolated
+        );
+        alter("alter model myspace.mymodel update age { type: uint8 { min: 12, max: 100 } }")
+            .unwrap();
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                assert_eq!(
+                    mdl.fields().st_get("age").unwrap().props().checks().len(),
+                    2
+                );
+                Ok(())
+            })
+            .unwrap();
+        // the checks are dropped if the type is given without them
+        alter("alter model myspace.mymodel update age { type: uint8 }").unwrap();
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                assert!(mdl
+                    .fields()
+                    .st_get("age")
+                    .unwrap()
+                    .props()
+                    .checks()
+                    .is_empty());
+                Ok(())
+            })
+            .unwrap();
+    }
+    #[test]
+    fn backfill_added_field() {
+        use crate::engine::{
+            core::{
+                dml,
+                model::{backfill_model, backfill_model_in_background, ModelData},
+                EntityIDRef,
+            },
+            data::cell::Datacell,
+            fractal::{jobs::JobStatus, GlobalInstanceLike},
+            ql::{ast::parse_ast_node_full, tests::lex_insecure},
+            sync::atm::cpin,
+        };
+        let global = TestGlobal::new_with_driver_id_instant_update("backfill_added_field");
+        super::exec_create(
+            &global,
+            "create model myspace.mymodel(username: string, age: uint8)",
+            true,
+        )
+        .unwrap();
+        for insert in [
+            "insert into myspace.mymodel('sayan', 21)",
+            "insert into myspace.mymodel('robot', 12)",
+            "insert into myspace.mymodel('user', 30)",
+        ] {
+            let tok = lex_insecure(insert.as_bytes()).unwrap();
+            dml::insert(&global, parse_ast_node_full(&tok[1..]).unwrap()).unwrap();
+        }
+        let entity = EntityIDRef::new("myspace", "mymodel");
+        // no field has a default
+        assert_eq!(
+            backfill_model(&global, entity).unwrap_err(),
+            QueryError::QExecDdlInvalidProperties
+        );
+        assert_eq!(
+            backfill_model(&global, EntityIDRef::new("myspace", "nomodel")).unwrap_err(),
+            QueryError::QExecObjectNotFound
+        );
+        let tok = lex_insecure(
+            b"alter model myspace.mymodel add (token { type: string { generated: \"ulid\" } }, note { type: string { generated: \"uuid4\" }, nullable: true })",
+        )
+        .unwrap();
+        ModelData::transactional_exec_alter(&global, parse_ast_node_full(&tok[2..]).unwrap())
+            .unwrap();
+        // the test global runs jobs right away
+        let id = backfill_model_in_background(&global, "root", entity).unwrap();
+        let job = global.state().jobs().get(id).unwrap();
+        assert_eq!(
+            job.status(),
+            JobStatus::Completed(Some(
+                "{\"fields\":[\"token\"],\"rows\":3,\"filled\":3}".into()
+            ))
+        );
+        assert_eq!(job.done(), 3);
+        // a field that can be null is left as it is
+        global
+            .state()
+            .namespace()
+            .with_model(entity, |mdl| {
+                let g = cpin();
+                for row in mdl.primary_index().iter(&g) {
+                    let data = row
+                        .resolve_schema_deltas_and_freeze(mdl.delta_state())
+                        .unwrap();
+                    let token = data.fields().st_get("token").unwrap();
+                    assert_eq!(token.try_str().unwrap().len(), 26);
+                    assert!(data.fields().st_get("note").map_or(true, Datacell::is_null));
+                }
+                Ok(())
+            })
+            .unwrap();
+        // there's nothing left to fill in
+        let report = backfill_model(&global, entity).unwrap();
+        assert_eq!((report.rows(), report.filled()), (3, 0));
+    }
+    #[test]
     fn failing_alter_nullable_switch_need_lock() {
         let global = TestGlobal::new_with_driver_id("failing_alter_nullable_switch_need_lock");
         assert_eq!(
@@ -28,10 +28,10 @@ mod validation {
     use {
         super::super::create,
         crate::engine::{
-            core::model::{DeltaVersion, Field, Layer},
+            core::model::{decimal::Decimal, props::OverflowPolicy, DeltaVersion, Field, Layer},
             data::tag::{DataTag, FullTag},
             error::QueryError,
-            idx::STIndexSeq,
+            idx::{STIndex, STIndexSeq},
         },
     };
 
@@ -110,6 +110,611 @@ mod validation {
     );
     }
 
+    #[test]
+    fn computed_field() {
+        let model = create("create model myspace.mymodel(primary username: string, username_lc: string { computed: \"lower(username)\" })").unwrap();
+        let field = model.fields().st_get("username_lc").unwrap();
+        assert_eq!(field.computed().unwrap().source(), "username");
+        assert_eq!(field.computed().unwrap().describe(), "lower(username)");
+        // unknown function or bad expression
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, username_lc: string { computed: \"reverse(username)\" })").unwrap_err(),
+            QueryError::QExecDdlInvalidProperties
+        );
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, username_lc: string { computed: \"lower(username\" })").unwrap_err(),
+            QueryError::QExecDdlInvalidProperties
+        );
+        for bad_model in [
+            // unknown source
+            "create model myspace.mymodel(primary username: string, email_lc: string { computed: \"lower(email)\" })",
+            // not a string
+            "create model myspace.mymodel(primary username: string, followers: uint64, f: string { computed: \"lower(followers)\" })",
+            "create model myspace.mymodel(primary username: string, username_lc: binary { computed: \"lower(username)\" })",
+            // computed from a computed field
+            "create model myspace.mymodel(primary username: string, a: string { computed: \"lower(username)\" }, b: string { computed: \"upper(a)\" })",
+            // nullable source, but not nullable computed field
+            "create model myspace.mymodel(primary username: string, null email: string, email_lc: string { computed: \"lower(email)\" })",
+            // primary key
+            "create model myspace.mymodel(username: string, primary username_lc: string { computed: \"lower(username)\" })",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition
+            );
+        }
+    }
+
+    #[test]
+    fn window_counter_field() {
+        let model = create("create model myspace.mymodel(primary username: string, hits: window_counter, null daily: window_counter { window: 86400 })").unwrap();
+        let window = |field| {
+            model
+                .fields()
+                .st_get(field)
+                .unwrap()
+                .window()
+                .unwrap()
+                .window()
+        };
+        assert_eq!(window("hits"), 60);
+        assert_eq!(window("daily"), 86400);
+        // stored as a list of unsigned integers
+        assert_eq!(
+            model.fields().st_get("hits").unwrap().layers(),
+            [Layer::list(), Layer::uint64()]
+        );
+        assert_eq!(
+            model.describe(),
+            "{*username:String,!hits:window_counter,?daily:window_counter}"
+        );
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, hits: window_counter { window: 0 })",
+            "create model myspace.mymodel(primary username: string, hits: window_counter { window: 'a' })",
+            "create model myspace.mymodel(primary username: string, hits: window_counter { maxlen: 10 })",
+            // a window is only set by declaring a window counter
+            "create model myspace.mymodel(primary username: string, hits: list { type: uint64, window: 60 })",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlInvalidProperties,
+                "{bad_model}"
+            );
+        }
+    }
+
+    #[test]
+    fn decimal_field() {
+        let model = create("create model myspace.mymodel(primary username: string, balance: decimal, null price: decimal { precision: 6, scale: 3 })").unwrap();
+        let decimal = |field| *model.fields().st_get(field).unwrap().decimal().unwrap();
+        assert_eq!(decimal("balance"), Decimal::new(18, 2).unwrap());
+        assert_eq!(decimal("price"), Decimal::new(6, 3).unwrap());
+        // stored as a signed integer
+        assert_eq!(
+            model.fields().st_get("price").unwrap().layers(),
+            [Layer::sint64()]
+        );
+        assert_eq!(
+            model.describe(),
+            "{*username:String,!balance:decimal,?price:decimal}"
+        );
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, price: decimal { precision: 0 })",
+            "create model myspace.mymodel(primary username: string, price: decimal { precision: 19 })",
+            "create model myspace.mymodel(primary username: string, price: decimal { precision: 4, scale: 5 })",
+            "create model myspace.mymodel(primary username: string, price: decimal { scale: 'a' })",
+            "create model myspace.mymodel(primary username: string, price: decimal { maxlen: 10 })",
+            // a precision is only set by declaring a decimal
+            "create model myspace.mymodel(primary username: string, price: sint64 { precision: 6 })",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlInvalidProperties,
+                "{bad_model}"
+            );
+        }
+    }
+
+    #[test]
+    fn overflow_field() {
+        let model = create("create model myspace.mymodel(primary username: string, visits: uint8 { overflow: \"saturate\" }, seq: sint16 { overflow: \"wrap\" }, score: float32 { overflow: \"saturate\" }, balance: sint64)").unwrap();
+        let overflow = |field| model.fields().st_get(field).unwrap().props().overflow();
+        assert_eq!(overflow("visits"), Some(OverflowPolicy::Saturate));
+        assert_eq!(overflow("seq"), Some(OverflowPolicy::Wrap));
+        assert_eq!(overflow("score"), Some(OverflowPolicy::Saturate));
+        assert_eq!(overflow("balance"), None);
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, visits: uint8 { overflow: \"clamp\" })",
+            // floats can't wrap
+            "create model myspace.mymodel(primary username: string, score: float64 { overflow: \"wrap\" })",
+            // only numbers overflow
+            "create model myspace.mymodel(primary username: string, bio: string { overflow: \"saturate\" })",
+            "create model myspace.mymodel(primary username: string, balance: decimal { overflow: \"saturate\" })",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlInvalidProperties,
+                "{bad_model}"
+            );
+        }
+    }
+
+    #[test]
+    fn generated_field() {
+        let model = create("create model myspace.mymodel(primary id: string { generated: \"uuid7\" }, null token: string { generated: \"ulid\" })").unwrap();
+        let keygen = |field| model.fields().st_get(field).unwrap().keygen().unwrap();
+        assert_eq!(keygen("id").name(), "uuid7");
+        assert_eq!(keygen("token").name(), "ulid");
+        for bad_model in [
+            // unknown generator
+            "create model myspace.mymodel(primary id: string { generated: \"uuid1\" })",
+            "create model myspace.mymodel(primary id: string { generated: \"uuid7()\" })",
+            // generated keys are strings
+            "create model myspace.mymodel(primary id: uint64 { generated: \"uuid4\" })",
+            "create model myspace.mymodel(primary id: string, tags: list { type: string, generated: \"uuid4\" })",
+            // a field is either generated or has a literal default
+            "create model myspace.mymodel(primary id: string { generated: \"uuid4\", default: \"x\" })",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlInvalidProperties,
+                "{bad_model}"
+            );
+        }
+    }
+
+    #[test]
+    fn literal_default() {
+        let model = create("create model myspace.mymodel(primary id: string, status: string { default: \"uuid7()\" }, age: uint8 { default: 18 })").unwrap();
+        let field = |name| model.fields().st_get(name).unwrap();
+        // a quoted call is a literal, not a generator
+        assert_eq!(field("status").keygen(), None);
+        assert_eq!(
+            field("status").props().literal_default().unwrap().str(),
+            "uuid7()"
+        );
+        assert_eq!(field("age").default_value().unwrap().uint(), 18);
+        for bad_model in [
+            // the default doesn't fit the field
+            "create model myspace.mymodel(primary id: string, age: uint8 { default: 300 })",
+            "create model myspace.mymodel(primary id: string, age: uint8 { default: \"18\" })",
+            "create model myspace.mymodel(primary id: string, name: string { maxlen: 2, default: \"sayan\" })",
+            "create model myspace.mymodel(primary id: string, age: uint8 { default: null })",
+            "create model myspace.mymodel(primary id: string, tags: list { type: string, default: \"x\" })",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlInvalidProperties,
+                "{bad_model}"
+            );
+        }
+    }
+
+    #[test]
+    fn field_checks() {
+        let model = create("create model myspace.mymodel(primary username: string { maxlen: 16, regex: \"[a-z0-9_]+\" }, age: uint8 { min: 13, max: 120 }, tags: list { type: string, maxlen: 4 })").unwrap();
+        let checks = |field| model.fields().st_get(field).unwrap().props().checks().len();
+        assert_eq!(checks("username"), 2);
+        assert_eq!(checks("age"), 2);
+        assert_eq!(checks("tags"), 1);
+        for bad_model in [
+            // unknown property
+            "create model myspace.mymodel(primary username: string { minlen: 1 })",
+            // checks that don't apply to the type
+            "create model myspace.mymodel(primary username: string { min: 1 })",
+            "create model myspace.mymodel(primary username: string, age: uint8 { maxlen: 1 })",
+            "create model myspace.mymodel(primary username: string, pass: binary { regex: \"a\" })",
+            // bad values
+            "create model myspace.mymodel(primary username: string { maxlen: -1 })",
+            "create model myspace.mymodel(primary username: string { regex: \"(\" })",
+            "create model myspace.mymodel(primary username: string, age: uint8 { min: 10, max: 1 })",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlInvalidProperties
+            );
+        }
+        // props on an inner layer
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, tags: list { type: string { maxlen: 4 } })").unwrap_err(),
+            QueryError::QExecDdlInvalidTypeDefinition
+        );
+    }
+
+    #[test]
+    fn quoted_field_names() {
+        let model = create("create model myspace.mymodel(primary `type`: string, `select`: uint8)")
+            .unwrap();
+        assert!(model.fields().st_contains("type"));
+        assert_eq!(model.p_key(), "type");
+        assert_eq!(model.describe(), "{*`type`:String,!`select`:UInt8}");
+    }
+
+    #[test]
+    fn describe_schema() {
+        let model = create("create model myspace.mymodel(primary username: string { maxlen: 16, regex: \"[a-z0-9_]+\" }, null age: uint8 { min: 13 }, username_lc: string { computed: \"lower(username)\" }, scores: list { type: float64 })").unwrap();
+        assert_eq!(
+            model.describe_schema(),
+            concat!(
+                "{\"primary_key\":\"username\",\"fields\":[",
+                "{\"name\":\"username\",\"primary\":true,\"nullable\":false,\"layers\":[{\"type\":\"string\",\"selector\":12,\"class\":5,\"unique\":3}],\"properties\":{\"maxlen\":16,\"regex\":\"[a-z0-9_]+\"}},",
+                "{\"name\":\"age\",\"primary\":false,\"nullable\":true,\"layers\":[{\"type\":\"uint8\",\"selector\":1,\"class\":1,\"unique\":0}],\"properties\":{\"min\":13}},",
+                "{\"name\":\"username_lc\",\"primary\":false,\"nullable\":false,\"layers\":[{\"type\":\"string\",\"selector\":12,\"class\":5,\"unique\":3}],\"properties\":{\"computed\":\"lower(username)\"}},",
+                "{\"name\":\"scores\",\"primary\":false,\"nullable\":false,\"layers\":[{\"type\":\"list\",\"selector\":13,\"class\":6,\"unique\":null},{\"type\":\"float64\",\"selector\":10,\"class\":3,\"unique\":null}],\"properties\":{}}",
+                "]}"
+            )
+        );
+    }
+
+    #[test]
+    fn hasher_prop() {
+        use crate::engine::idx::meta::hash::HasherKind;
+        let model =
+            create("create model myspace.mymodel(primary username: string, password: binary)")
+                .unwrap();
+        assert_eq!(model.props().hasher(), HasherKind::Sip);
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { hasher: \"fxhash\" }").unwrap();
+        assert_eq!(model.props().hasher(), HasherKind::Fx);
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { hasher: \"siphash\" }").unwrap();
+        assert_eq!(model.props().hasher(), HasherKind::Sip);
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, password: binary) with { hasher: \"md5\" }").unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, password: binary) with { hasher: 1 }").unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+    }
+
+    #[test]
+    fn index_prop() {
+        use crate::engine::core::index::PrimaryIndexKind;
+        let model =
+            create("create model myspace.mymodel(primary username: string, password: binary)")
+                .unwrap();
+        assert_eq!(model.props().index(), PrimaryIndexKind::Hash);
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { primary_index: \"btree\" }").unwrap();
+        assert_eq!(model.props().index(), PrimaryIndexKind::BTree);
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, password: binary) with { primary_index: \"skiplist\" }").unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+        // a hasher is meaningless for an ordered index
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, password: binary) with { primary_index: \"btree\", hasher: \"fxhash\" }").unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+    }
+
+    #[test]
+    fn partitions_prop() {
+        let model =
+            create("create model myspace.mymodel(primary username: string, password: binary)")
+                .unwrap();
+        assert_eq!(model.props().partitions(), 1);
+        assert_eq!(model.primary_index().partitions(), 1);
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { partitions: 8 }").unwrap();
+        assert_eq!(model.props().partitions(), 8);
+        assert_eq!(model.primary_index().partitions(), 8);
+        assert_eq!(model.delta_state().__fractal_partitions(), 8);
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, password: binary) with { partitions: 0 }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { partitions: 65 }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { partitions: \"4\" }",
+            // an ordered index can't be partitioned
+            "create model myspace.mymodel(primary username: string, password: binary) with { primary_index: \"btree\", partitions: 4 }",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition,
+                "{bad_model}"
+            );
+        }
+    }
+
+    #[test]
+    fn soft_delete_prop() {
+        use crate::engine::core::model::props::ModelProps;
+        let model =
+            create("create model myspace.mymodel(primary username: string, password: binary)")
+                .unwrap();
+        assert!(!model.props().soft_delete());
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { soft_delete: true }").unwrap();
+        assert!(model.props().soft_delete());
+        assert_eq!(
+            model.props().tombstone_retention(),
+            ModelProps::DEFAULT_TOMBSTONE_RETENTION
+        );
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { soft_delete: true, tombstone_retention: 3600 }").unwrap();
+        assert_eq!(model.props().tombstone_retention(), 3600);
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, password: binary) with { soft_delete: \"yes\" }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { soft_delete: true, tombstone_retention: -1 }",
+            // a retention period is meaningless without soft deletes
+            "create model myspace.mymodel(primary username: string, password: binary) with { tombstone_retention: 3600 }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { soft_delete: false, tombstone_retention: 3600 }",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition
+            );
+        }
+    }
+
+    #[test]
+    fn history_prop() {
+        use crate::engine::core::model::props::ModelProps;
+        let model =
+            create("create model myspace.mymodel(primary username: string, password: binary)")
+                .unwrap();
+        assert!(!model.props().history());
+        assert!(model.history().is_none());
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { history: true }").unwrap();
+        assert!(model.props().history());
+        assert!(model.history().is_some());
+        assert_eq!(
+            model.props().history_retention(),
+            ModelProps::DEFAULT_HISTORY_RETENTION
+        );
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { history: true, history_retention: 3600 }").unwrap();
+        assert_eq!(model.props().history_retention(), 3600);
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, password: binary) with { history: 1 }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { history: true, history_retention: \"1h\" }",
+            // a retention period is meaningless without history
+            "create model myspace.mymodel(primary username: string, password: binary) with { history_retention: 3600 }",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition
+            );
+        }
+    }
+
+    #[test]
+    fn result_cache_prop() {
+        use crate::engine::core::model::props::ModelProps;
+        let model =
+            create("create model myspace.mymodel(primary username: string, password: binary)")
+                .unwrap();
+        assert!(!model.props().result_cache());
+        assert!(model.result_cache().is_none());
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { result_cache: true }").unwrap();
+        assert!(model.result_cache().is_some());
+        assert_eq!(
+            model.props().result_cache_ttl(),
+            ModelProps::DEFAULT_RESULT_CACHE_TTL
+        );
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { result_cache: true, result_cache_ttl: 5 }").unwrap();
+        assert_eq!(model.props().result_cache_ttl(), 5);
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, password: binary) with { result_cache: 1 }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { result_cache: true, result_cache_ttl: 0 }",
+            // a ttl is meaningless without the cache
+            "create model myspace.mymodel(primary username: string, password: binary) with { result_cache_ttl: 5 }",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition
+            );
+        }
+    }
+
+    #[test]
+    fn upstream_prop() {
+        use crate::engine::core::model::{props::ModelProps, upstream::UpstreamWrites};
+        let model =
+            create("create model myspace.mymodel(primary username: string, password: binary)")
+                .unwrap();
+        assert!(!model.props().upstream());
+        assert!(model.upstream().is_none());
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { upstream: true }").unwrap();
+        assert_eq!(model.upstream().unwrap().writes(), UpstreamWrites::Through);
+        assert_eq!(
+            model.props().upstream_ttl(),
+            ModelProps::DEFAULT_UPSTREAM_TTL
+        );
+        let model = create("create model myspace.mymodel(primary username: string, password: binary) with { upstream: true, upstream_ttl: 5, upstream_writes: \"behind\" }").unwrap();
+        assert_eq!(model.props().upstream_ttl(), 5);
+        assert_eq!(model.upstream().unwrap().writes(), UpstreamWrites::Behind);
+        for bad_model in [
+            "create model myspace.mymodel(primary username: string, password: binary) with { upstream: true, upstream_ttl: 0 }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { upstream: true, upstream_writes: \"around\" }",
+            // these are meaningless without the upstream
+            "create model myspace.mymodel(primary username: string, password: binary) with { upstream_ttl: 5 }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { upstream_writes: \"behind\" }",
+            // fetched rows replace the cached ones outright
+            "create model myspace.mymodel(primary username: string, password: binary) with { upstream: true, soft_delete: true }",
+            "create model myspace.mymodel(primary username: string, password: binary) with { upstream: true, history: true }",
+        ] {
+            assert_eq!(
+                create(bad_model).unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition
+            );
+        }
+    }
+
+    #[test]
+    fn histograms_prop() {
+        let model =
+            create("create model myspace.mymodel(primary username: string, age: uint8)").unwrap();
+        assert!(!model.props().histograms());
+        assert!(model.stats().is_none());
+        let model = create(
+            "create model myspace.mymodel(primary username: string, age: uint8) with { histograms: true }",
+        )
+        .unwrap();
+        assert!(model.props().histograms());
+        assert!(model.stats().is_some());
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, age: uint8) with { histograms: 1 }")
+                .unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+    }
+
+    #[test]
+    fn analytic_prop() {
+        let model =
+            create("create model myspace.mymodel(primary username: string, age: uint8)").unwrap();
+        assert!(!model.props().analytic());
+        assert!(model.columns().is_none());
+        let model = create(
+            "create model myspace.mymodel(primary username: string, age: uint8) with { analytic: true }",
+        )
+        .unwrap();
+        assert!(model.props().analytic());
+        assert!(model.columns().is_some());
+        assert_eq!(
+            model.props().describe(),
+            "{\"primary_index\":\"hash\",\"hasher\":\"siphash\",\"analytic\":true}"
+        );
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, age: uint8) with { analytic: 1 }")
+                .unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+    }
+
+    #[test]
+    fn maintain_prop() {
+        let model =
+            create("create model myspace.mymodel(primary username: string, age: uint8)").unwrap();
+        assert!(model.props().maintain().is_empty());
+        assert!(model.maintained().is_none());
+        let model = create(
+            "create model myspace.mymodel(primary username: string, city: string, age: sint16) with { maintain: { \
+                users_by_city: \"count(*) group by city\", age_by_city: \"sum(age)  group by city\" } }",
+        )
+        .unwrap();
+        assert!(model.maintained().is_some());
+        assert_eq!(
+            model.props().describe(),
+            "{\"primary_index\":\"hash\",\"hasher\":\"siphash\",\"maintain\":{\"age_by_city\":\"sum(age) group by city\",\"users_by_city\":\"count(*) group by city\"}}"
+        );
+        for maintain in [
+            // not a definition
+            "{ a: 1 }",
+            "{ a: \"count(city) group by city\" }",
+            "{ a: \"sum(age)\" }",
+            "{ a: \"sum(age) group by city, age\" }",
+            "{}",
+            // the same aggregate twice
+            "{ a: \"count(*) group by city\", b: \"count(*) group by city\" }",
+            // unknown fields
+            "{ a: \"count(*) group by pincode\" }",
+            "{ a: \"sum(score) group by city\" }",
+            // only numbers can be summed
+            "{ a: \"sum(city) group by age\" }",
+            // the primary key and lists can't be used
+            "{ a: \"count(*) group by username\" }",
+            "{ a: \"count(*) group by tags\" }",
+        ] {
+            assert_eq!(
+                create(&format!(
+                    "create model myspace.mymodel(primary username: string, city: string, age: uint8, tags: list {{ type: string }}) with {{ maintain: {maintain} }}"
+                ))
+                .unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition,
+                "{maintain}"
+            );
+        }
+    }
+
+    #[test]
+    fn coercion_prop() {
+        use crate::engine::core::model::props::NumericCoercion;
+        let model =
+            create("create model myspace.mymodel(primary username: string, age: uint8)").unwrap();
+        assert_eq!(model.props().coercion(), NumericCoercion::Strict);
+        let model = create(
+            "create model myspace.mymodel(primary username: string, age: uint8) with { coercion: 'lenient' }",
+        )
+        .unwrap();
+        assert_eq!(model.props().coercion(), NumericCoercion::Lenient);
+        assert_eq!(
+            create("create model myspace.mymodel(primary username: string, age: uint8) with { coercion: 'loose' }")
+                .unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+    }
+
+    #[test]
+    fn flush_props() {
+        use crate::engine::core::model::props::Durability;
+        let model =
+            create("create model myspace.mymodel(primary username: string, age: uint8)").unwrap();
+        assert_eq!(model.props().flush_every_n_deltas(), None);
+        assert_eq!(model.props().flush_every_ms(), None);
+        assert_eq!(model.props().durability(), Durability::Relaxed);
+        let model = create(
+            "create model myspace.mymodel(primary username: string, age: uint8) with { flush_every_n_deltas: 100, flush_every_ms: 250 }",
+        )
+        .unwrap();
+        assert_eq!(model.props().flush_every_n_deltas(), Some(100));
+        assert_eq!(model.props().flush_every_ms(), Some(250));
+        assert_eq!(
+            model.props().describe(),
+            "{\"primary_index\":\"hash\",\"hasher\":\"siphash\",\"flush_every_n_deltas\":100,\"flush_every_ms\":250}"
+        );
+        let model = create(
+            "create model myspace.mymodel(primary username: string, age: uint8) with { durability: 'strict' }",
+        )
+        .unwrap();
+        assert_eq!(model.props().durability(), Durability::Strict);
+        for props in [
+            "{ flush_every_n_deltas: 0 }",
+            // shorter than the flusher checks
+            "{ flush_every_ms: 10 }",
+            "{ durability: 'eventual' }",
+            // every change to a strict model is written right away
+            "{ durability: 'strict', flush_every_n_deltas: 10 }",
+            "{ durability: 'strict', flush_every_ms: 100 }",
+        ] {
+            assert_eq!(
+                create(&format!(
+                    "create model myspace.mymodel(primary username: string, age: uint8) with {props}"
+                ))
+                .unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition,
+                "{props}"
+            );
+        }
+    }
+
+    #[test]
+    fn overflow_props() {
+        let model =
+            create("create model myspace.mymodel(primary username: string, age: uint8)").unwrap();
+        assert!(!model.props().overflow());
+        assert!(model.overflow().is_none());
+        let model = create(
+            "create model myspace.mymodel(primary username: string, age: uint8) with { overflow: true }",
+        )
+        .unwrap();
+        assert!(model.props().overflow());
+        assert!(model.overflow().is_some());
+        assert_eq!(
+            model.props().describe(),
+            "{\"primary_index\":\"hash\",\"hasher\":\"siphash\",\"overflow\":true}"
+        );
+        // both would keep (or read back) every row
+        for props in [
+            "{ overflow: true, analytic: true }",
+            "{ overflow: true, histograms: true }",
+        ] {
+            assert_eq!(
+                create(&format!(
+                    "create model myspace.mymodel(primary username: string, age: uint8) with {props}"
+                ))
+                .unwrap_err(),
+                QueryError::QExecDdlModelBadDefinition,
+                "{props}"
+            );
+        }
+    }
+
     #[test]
     fn illegal_pk() {
         assert_eq!(
@@ -134,12 +739,21 @@ mod validation {
 mod exec {
     use crate::engine::{
         core::{
+            ddl_misc, dml,
             model::{DeltaVersion, Field, Layer},
-            tests::ddl_model::{exec_create_new_space, with_model},
+            tests::ddl_model::{exec_create_copy, exec_create_new_space, with_model},
+            EntityIDRef,
+        },
+        data::{
+            cell::Datacell,
+            tag::{DataTag, FullTag},
         },
-        data::tag::{DataTag, FullTag},
-        fractal::test_utils::TestGlobal,
-        idx::STIndexSeq,
+        error::QueryError,
+        fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+        idx::{STIndex, STIndexSeq},
+        net::protocol::{Response, ResponseType},
+        ql::{ast::parse_ast_node_full, tests::lex_insecure},
+        sync,
     };
 
     const SPACE: &str = "myspace";
@@ -179,4 +793,164 @@ mod exec {
             );
         });
     }
+
+    fn fields_of(global: &TestGlobal, space: &str, model: &str) -> Vec<(String, Field)> {
+        let mut fields = vec![];
+        with_model(global, space, model, |model| {
+            fields = model
+                .fields()
+                .stseq_ord_kv()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect();
+        });
+        fields
+    }
+
+    fn rows_of(global: &TestGlobal, space: &str, model: &str) -> Vec<(String, u64)> {
+        let g = sync::atm::cpin();
+        let mut rows = vec![];
+        with_model(global, space, model, |model| {
+            rows = model
+                .primary_index()
+                .iter(&g)
+                .map(|row| {
+                    let data = row.d_data().read().unwrap();
+                    (
+                        row.d_key().str().unwrap().to_string(),
+                        data.fields().st_get("age").unwrap().uint(),
+                    )
+                })
+                .collect();
+        });
+        rows.sort();
+        rows
+    }
+
+    #[test]
+    fn copy() {
+        let global = TestGlobal::new_with_driver_id_instant_update("exec_create_copy");
+        exec_create_new_space(
+            &global,
+            "create model myspace.users(username: string, age: uint8) with { soft_delete: true }",
+        )
+        .unwrap();
+        global.state().namespace().create_empty_test_space("other");
+        dml::bulk_insert(
+            &global,
+            EntityIDRef::new(SPACE, "users"),
+            ["sayan", "elder", "kid"]
+                .into_iter()
+                .zip([22u64, 80, 12])
+                .map(|(name, age)| {
+                    vec![
+                        Datacell::new_str(name.into()),
+                        Datacell::new_uint_default(age),
+                    ]
+                })
+                .collect(),
+        )
+        .unwrap();
+        let tok = lex_insecure(b"delete from myspace.users where username = 'kid'").unwrap();
+        dml::delete(&global, parse_ast_node_full(&tok[1..]).unwrap()).unwrap();
+        // schema only
+        assert_eq!(
+            exec_create_copy(
+                &global,
+                "create model myspace.staging as copy of myspace.users"
+            )
+            .unwrap(),
+            None
+        );
+        assert_eq!(
+            fields_of(&global, SPACE, "staging"),
+            fields_of(&global, SPACE, "users")
+        );
+        assert!(rows_of(&global, SPACE, "staging").is_empty());
+        with_model(&global, SPACE, "staging", |model| {
+            assert!(model.props().soft_delete());
+            assert_eq!(
+                model.delta_state().schema_current_version(),
+                DeltaVersion::genesis()
+            );
+        });
+        // with data, across spaces; soft deleted rows are left out
+        assert_eq!(
+            exec_create_copy(
+                &global,
+                "create model other.users as copy of myspace.users with data"
+            )
+            .unwrap(),
+            None
+        );
+        assert_eq!(
+            rows_of(&global, "other", "users"),
+            [("elder".to_string(), 80), ("sayan".to_string(), 22)]
+        );
+        // the copy is independent of the source
+        dml::bulk_insert(
+            &global,
+            EntityIDRef::new("other", "users"),
+            vec![vec![
+                Datacell::new_str("new".into()),
+                Datacell::new_uint_default(1),
+            ]],
+        )
+        .unwrap();
+        assert_eq!(rows_of(&global, "other", "users").len(), 3);
+        with_model(&global, SPACE, "users", |model| {
+            assert_eq!(model.primary_index().count(), 3)
+        });
+        // errors
+        assert_eq!(
+            exec_create_copy(
+                &global,
+                "create model myspace.staging as copy of myspace.users"
+            )
+            .unwrap_err(),
+            QueryError::QExecDdlObjectAlreadyExists
+        );
+        assert_eq!(
+            exec_create_copy(
+                &global,
+                "create model if not exists myspace.staging as copy of myspace.users"
+            )
+            .unwrap(),
+            Some(false)
+        );
+        assert_eq!(
+            exec_create_copy(
+                &global,
+                "create model myspace.nothing as copy of myspace.missing"
+            )
+            .unwrap_err(),
+            QueryError::QExecObjectNotFound
+        );
+    }
+
+    #[test]
+    fn describe() {
+        let global = TestGlobal::new_with_driver_id("exec_describe");
+        exec_create_new_space(
+            &global,
+            "create model myspace.mymodel(username: string, null password: binary)",
+        )
+        .unwrap();
+        let describe = |query: &str| {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            ddl_misc::describe(&global, parse_ast_node_full(&tok[1..]).unwrap())
+        };
+        let schema = "{\"primary_key\":\"username\",\"fields\":[{\"name\":\"username\",\"primary\":true,\"nullable\":false,\"layers\":[{\"type\":\"string\",\"selector\":12,\"class\":5,\"unique\":3}],\"properties\":{}},{\"name\":\"password\",\"primary\":false,\"nullable\":true,\"layers\":[{\"type\":\"binary\",\"selector\":11,\"class\":4,\"unique\":2}],\"properties\":{}}]}";
+        assert_eq!(
+            describe("describe model myspace.mymodel").unwrap(),
+            Response::Serialized {
+                ty: ResponseType::String,
+                size: schema.len(),
+                data: schema.as_bytes().to_vec(),
+            }
+        );
+        assert_eq!(
+            describe("describe model myspace.othermodel").unwrap_err(),
+            QueryError::QExecObjectNotFound
+        );
+    }
 }
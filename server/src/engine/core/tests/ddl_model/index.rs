@@ -0,0 +1,422 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::{
+    core::{
+        dml,
+        index::{Collation, PrimaryIndexKey, BUILD_RUN},
+        model::{verify_model_in_background, ModelData},
+        tests::ddl_model::{exec_create_index, exec_create_new_space, exec_drop_index},
+        EntityIDRef,
+    },
+    data::cell::Datacell,
+    error::QueryError,
+    fractal::{
+        jobs::{CancelToken, JobStatus},
+        test_utils::TestGlobal,
+        GlobalInstanceLike,
+    },
+    idx::STIndex,
+    ql::{ast::parse_ast_node_full, tests::lex_insecure},
+};
+
+#[test]
+fn create_and_drop_index() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_create_and_drop_index");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string)",
+    )
+    .unwrap();
+    assert_eq!(
+        exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap(),
+        None
+    );
+    assert_eq!(
+        exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap_err(),
+        QueryError::QExecDdlObjectAlreadyExists
+    );
+    assert_eq!(
+        exec_create_index(
+            &global,
+            "create index if not exists bycity on myspace.mymodel(city)"
+        )
+        .unwrap(),
+        Some(false)
+    );
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        let idx = model.secondary_indexes().st_get("bycity").unwrap();
+        assert_eq!(idx.field(), "city");
+        assert!(idx.is_ready());
+    });
+    assert_eq!(
+        exec_drop_index(&global, "drop index bycity on myspace.mymodel").unwrap(),
+        None
+    );
+    assert_eq!(
+        exec_drop_index(&global, "drop index bycity on myspace.mymodel").unwrap_err(),
+        QueryError::QExecObjectNotFound
+    );
+    assert_eq!(
+        exec_drop_index(&global, "drop index if exists bycity on myspace.mymodel").unwrap(),
+        Some(false)
+    );
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        assert_eq!(model.secondary_indexes().st_len(), 0);
+    });
+}
+
+#[test]
+fn create_index_bad_field() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_create_index_bad_field");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, tags: list { type: string }, score: float64)",
+    )
+    .unwrap();
+    assert_eq!(
+        exec_create_index(&global, "create index idx on myspace.mymodel(city)").unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+    for field in ["username", "tags", "score"] {
+        assert_eq!(
+            exec_create_index(
+                &global,
+                &format!("create index idx on myspace.mymodel({field})")
+            )
+            .unwrap_err(),
+            QueryError::QExecDdlModelBadDefinition
+        );
+    }
+}
+
+#[test]
+fn alter_indexed_field() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_alter_indexed_field");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string)",
+    )
+    .unwrap();
+    exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap();
+    let tok = lex_insecure(b"alter model myspace.mymodel remove city").unwrap();
+    let alter = parse_ast_node_full(&tok[2..]).unwrap();
+    assert_eq!(
+        ModelData::transactional_exec_alter(&global, alter).unwrap_err(),
+        QueryError::QExecDdlModelAlterIllegal
+    );
+}
+
+#[test]
+fn create_partial_index() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_create_partial_index");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, status: string)",
+    )
+    .unwrap();
+    for (query, error) in [
+        (
+            "create index idx on myspace.mymodel(city) where state = 'active'",
+            QueryError::QExecUnknownField,
+        ),
+        (
+            "create index idx on myspace.mymodel(city) where status matches '^a'",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+        (
+            "create index idx on myspace.mymodel(city) where lower(status) = 'active'",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+    ] {
+        assert_eq!(exec_create_index(&global, query).unwrap_err(), error);
+    }
+    exec_create_index(
+        &global,
+        "create index activebycity on myspace.mymodel(city) where status = 'active'",
+    )
+    .unwrap();
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        let idx = model.secondary_indexes().st_get("activebycity").unwrap();
+        assert_eq!(idx.field(), "city");
+        assert!(idx.filter().is_some());
+        assert!(model.is_indexed("status"));
+    });
+    // the predicate depends on status, so it can't be removed
+    let tok = lex_insecure(b"alter model myspace.mymodel remove status").unwrap();
+    let alter = parse_ast_node_full(&tok[2..]).unwrap();
+    assert_eq!(
+        ModelData::transactional_exec_alter(&global, alter).unwrap_err(),
+        QueryError::QExecDdlModelAlterIllegal
+    );
+}
+
+#[test]
+fn create_covering_index() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_create_covering_index");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8)",
+    )
+    .unwrap();
+    for (query, error) in [
+        (
+            "create index idx on myspace.mymodel(city) include (email)",
+            QueryError::QExecUnknownField,
+        ),
+        (
+            "create index idx on myspace.mymodel(city) include (username)",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+        (
+            "create index idx on myspace.mymodel(city) include (city)",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+        (
+            "create index idx on myspace.mymodel(city) include (age, age)",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+    ] {
+        assert_eq!(exec_create_index(&global, query).unwrap_err(), error);
+    }
+    exec_create_index(
+        &global,
+        "create index bycity on myspace.mymodel(city) include (age)",
+    )
+    .unwrap();
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        let idx = model.secondary_indexes().st_get("bycity").unwrap();
+        assert_eq!(idx.field(), "city");
+        assert_eq!(idx.include(), [Box::<str>::from("age")]);
+        assert!(model.is_indexed("age"));
+    });
+    // the index stores age, so it can't be removed
+    let tok = lex_insecure(b"alter model myspace.mymodel remove age").unwrap();
+    let alter = parse_ast_node_full(&tok[2..]).unwrap();
+    assert_eq!(
+        ModelData::transactional_exec_alter(&global, alter).unwrap_err(),
+        QueryError::QExecDdlModelAlterIllegal
+    );
+}
+
+#[test]
+fn create_composite_index() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_create_composite_index");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8)",
+    )
+    .unwrap();
+    for (query, error) in [
+        (
+            "create index idx on myspace.mymodel(city, email)",
+            QueryError::QExecUnknownField,
+        ),
+        (
+            "create index idx on myspace.mymodel(city, username)",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+        (
+            "create index idx on myspace.mymodel(city, city)",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+        (
+            "create index idx on myspace.mymodel(city, age) include (age)",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+    ] {
+        assert_eq!(exec_create_index(&global, query).unwrap_err(), error);
+    }
+    exec_create_index(
+        &global,
+        "create index bycityage on myspace.mymodel(city, age)",
+    )
+    .unwrap();
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        let idx = model.secondary_indexes().st_get("bycityage").unwrap();
+        assert_eq!(idx.field(), "city");
+        assert_eq!(
+            idx.fields(),
+            [Box::<str>::from("city"), Box::<str>::from("age")]
+        );
+        assert!(model.is_indexed("age"));
+    });
+    let tok = lex_insecure(b"alter model myspace.mymodel remove age").unwrap();
+    let alter = parse_ast_node_full(&tok[2..]).unwrap();
+    assert_eq!(
+        ModelData::transactional_exec_alter(&global, alter).unwrap_err(),
+        QueryError::QExecDdlModelAlterIllegal
+    );
+}
+
+#[test]
+fn create_collated_index() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_create_collated_index");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8)",
+    )
+    .unwrap();
+    for (query, error) in [
+        (
+            "create index idx on myspace.mymodel(city) with { collation: 'latin1' }",
+            QueryError::QExecDdlInvalidProperties,
+        ),
+        (
+            "create index idx on myspace.mymodel(city) with { unique: true }",
+            QueryError::QExecDdlInvalidProperties,
+        ),
+        (
+            "create index idx on myspace.mymodel(age) with { collation: 'nocase' }",
+            QueryError::QExecDdlModelBadDefinition,
+        ),
+    ] {
+        assert_eq!(exec_create_index(&global, query).unwrap_err(), error);
+    }
+    exec_create_index(
+        &global,
+        "create index bycity on myspace.mymodel(city, age) with { collation: 'nfc' }",
+    )
+    .unwrap();
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        let idx = model.secondary_indexes().st_get("bycity").unwrap();
+        assert_eq!(idx.collation(), Collation::Nfc);
+    });
+}
+
+#[test]
+fn create_index_in_background() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("ddl_model_create_index_in_background");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string)",
+    )
+    .unwrap();
+    let rows = BUILD_RUN * 2 + 1;
+    dml::bulk_insert(
+        &global,
+        EntityIDRef::new("myspace", "mymodel"),
+        (0..rows)
+            .map(|i| {
+                vec![
+                    Datacell::new_str(format!("user{i}").into()),
+                    Datacell::new_str(if i % 2 == 0 { "ccu" } else { "blr" }.into()),
+                ]
+            })
+            .collect(),
+    )
+    .unwrap();
+    let create_index = |query: &str| {
+        let tok = lex_insecure(query.as_bytes()).unwrap();
+        ModelData::transactional_exec_create_index_in_background(
+            &global,
+            "root",
+            parse_ast_node_full(&tok[2..]).unwrap(),
+        )
+    };
+    // the test global runs jobs right away
+    let id = create_index("create index bycity on myspace.mymodel(city) in background")
+        .unwrap()
+        .unwrap();
+    let job = global.state().jobs().get(id).unwrap();
+    assert_eq!(job.status(), JobStatus::Completed(None));
+    assert_eq!(job.done(), rows as u64);
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        assert!(model
+            .secondary_indexes()
+            .st_get("bycity")
+            .unwrap()
+            .is_ready());
+    });
+    assert_eq!(
+        create_index("create index if not exists bycity on myspace.mymodel(city) in background")
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        create_index("create index bycity on myspace.mymodel(city) in background").unwrap_err(),
+        QueryError::QExecDdlObjectAlreadyExists
+    );
+    // verify
+    let id = verify_model_in_background(
+        &global,
+        "root",
+        EntityIDRef::new("myspace", "mymodel"),
+        true,
+    )
+    .unwrap();
+    let JobStatus::Completed(Some(report)) = global.state().jobs().get(id).unwrap().status() else {
+        panic!("verification didn't complete")
+    };
+    assert!(report.contains("\"rebuilt_indexes\":[\"bycity\"]"));
+    // jobs that are done can't be cancelled
+    assert_eq!(global.state().jobs().cancel(id), Ok(false));
+    assert_eq!(
+        global.state().jobs().cancel(id + 1).unwrap_err(),
+        QueryError::QExecObjectNotFound
+    );
+    let jobs = global.state().jobs().describe();
+    assert!(jobs
+        .starts_with("[{\"id\":1,\"kind\":\"build index\",\"target\":\"myspace.mymodel.bycity\""));
+    assert_eq!(jobs.matches("\"status\":\"completed\"").count(), 2);
+}
+
+#[test]
+fn index_build_stopped() {
+    let global = TestGlobal::new_with_driver_id_instant_update("ddl_model_index_build_stopped");
+    exec_create_new_space(
+        &global,
+        "create model myspace.mymodel(username: string, city: string)",
+    )
+    .unwrap();
+    exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap();
+    dml::bulk_insert(
+        &global,
+        EntityIDRef::new("myspace", "mymodel"),
+        (0..BUILD_RUN + 1)
+            .map(|i| {
+                vec![
+                    Datacell::new_str(format!("user{i}").into()),
+                    Datacell::new_str("ccu".into()),
+                ]
+            })
+            .collect(),
+    )
+    .unwrap();
+    super::with_model(&global, "myspace", "mymodel", |model| {
+        let idx = model.secondary_indexes().st_get("bycity").unwrap();
+        let token = CancelToken::default();
+        token.cancel();
+        let mut checked = vec![];
+        assert!(!idx.build_until(model, &token, |rows| checked.push(rows)));
+        assert_eq!(checked, [BUILD_RUN]);
+        assert!(!idx.is_ready());
+        assert!(idx
+            .lookup(&[PrimaryIndexKey::try_from_dc(Datacell::new_str("ccu".into())).unwrap()])
+            .is_empty());
+    });
+}
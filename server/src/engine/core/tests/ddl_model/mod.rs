@@ -26,13 +26,21 @@
 
 mod alt;
 mod crt;
+mod index;
 mod layer;
 
 use crate::engine::{
     core::{model::ModelData, EntityIDRef},
     error::QueryResult,
     fractal::GlobalInstanceLike,
-    ql::{ast::parse_ast_node_full, ddl::crt::CreateModel, tests::lex_insecure},
+    ql::{
+        ast::parse_ast_node_full,
+        ddl::{
+            crt::{CreateModel, CreateModelCopy},
+            drop::DropIndex,
+        },
+        tests::lex_insecure,
+    },
 };
 
 fn create(s: &str) -> QueryResult<ModelData> {
@@ -65,11 +73,38 @@ pub fn exec_create_new_space(
     exec_create(global, create_stmt, true).map(|_| ())
 }
 
+pub fn exec_create_copy(
+    global: &impl GlobalInstanceLike,
+    create_stmt: &str,
+) -> QueryResult<Option<bool>> {
+    let tok = lex_insecure(create_stmt.as_bytes()).unwrap();
+    let create_copy = parse_ast_node_full::<CreateModelCopy>(&tok[2..]).unwrap();
+    ModelData::transactional_exec_create_copy(global, create_copy)
+}
+
+pub fn exec_create_index(
+    global: &impl GlobalInstanceLike,
+    create_stmt: &str,
+) -> QueryResult<Option<bool>> {
+    let tok = lex_insecure(create_stmt.as_bytes()).unwrap();
+    let create_index = parse_ast_node_full(&tok[2..]).unwrap();
+    ModelData::transactional_exec_create_index(global, create_index)
+}
+
+pub fn exec_drop_index(
+    global: &impl GlobalInstanceLike,
+    drop_stmt: &str,
+) -> QueryResult<Option<bool>> {
+    let tok = lex_insecure(drop_stmt.as_bytes()).unwrap();
+    let drop_index = parse_ast_node_full::<DropIndex>(&tok[2..]).unwrap();
+    ModelData::transactional_exec_drop_index(global, drop_index)
+}
+
 fn with_model(
     global: &impl GlobalInstanceLike,
     space_id: &str,
     model_name: &str,
-    f: impl Fn(&ModelData),
+    f: impl FnOnce(&ModelData),
 ) {
     let models = global.state().namespace().idx_models().read();
     let model = models.get(&EntityIDRef::new(space_id, model_name)).unwrap();
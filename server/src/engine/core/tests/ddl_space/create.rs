@@ -89,3 +89,12 @@ fn exec_create_space_with_random_property() {
         QueryError::QExecDdlInvalidProperties
     );
 }
+
+#[test]
+fn exec_create_space_sys() {
+    let global = TestGlobal::new_with_driver_id("exec_create_space_sys");
+    assert_eq!(
+        super::exec_create(&global, "create space sys", |_| {}).unwrap_err(),
+        QueryError::QExecDdlObjectAlreadyExists
+    );
+}
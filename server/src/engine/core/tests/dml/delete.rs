@@ -24,7 +24,15 @@
  *
 */
 
-use crate::engine::{error::QueryError, fractal::test_utils::TestGlobal};
+use crate::{
+    engine::{
+        core::{dml, tests::ddl_model::exec_create_index, EntityIDRef},
+        error::QueryError,
+        fractal::{jobs::CancelToken, test_utils::TestGlobal, GlobalInstanceLike},
+        net::protocol::{Response, ResponseType},
+    },
+    util::os,
+};
 
 #[test]
 fn simple_delete() {
@@ -54,3 +62,443 @@ fn delete_nonexisting() {
         QueryError::QExecDmlRowNotFound
     );
 }
+
+#[test]
+fn delete_filtered_with_limit() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_filtered_with_limit");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('a', 10)",
+            "insert into myspace.mymodel('b', 20)",
+            "insert into myspace.mymodel('c', 30)",
+            "insert into myspace.mymodel('d', 40)",
+            "insert into myspace.mymodel('e', 50)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let delete = "delete from myspace.mymodel where age < 35 limit 2";
+    assert_eq!(super::exec_delete_only(&global, delete).unwrap(), Some(2));
+    assert_eq!(super::exec_delete_only(&global, delete).unwrap(), Some(1));
+    assert_eq!(super::exec_delete_only(&global, delete).unwrap(), Some(0));
+    let mut remaining = super::exec_select_all_only(
+        &global,
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    remaining.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+    assert_eq!(remaining, vec![intovec!["d"], intovec!["e"]]);
+}
+
+#[test]
+fn delete_filtered_multiple_clauses() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_delete_filtered_multiple_clauses");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 20)",
+            "insert into myspace.mymodel('robot', 'paris', 30)",
+            "insert into myspace.mymodel('hgwells', 'london', 40)",
+            "insert into myspace.mymodel('orwell', 'london', 50)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap();
+    assert_eq!(
+        super::exec_delete_only(
+            &global,
+            "delete from myspace.mymodel where city = 'london' and age > 30"
+        )
+        .unwrap(),
+        Some(2)
+    );
+    let mut remaining = super::exec_select_all_only(
+        &global,
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    remaining.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+    assert_eq!(remaining, vec![intovec!["robot"], intovec!["sayan"]]);
+    // the index no longer has the deleted rows
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where city = 'london' limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["sayan"]]
+    );
+}
+
+#[test]
+fn delete_filtered_matches() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_filtered_matches");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, null email: string)",
+        &[
+            "insert into myspace.mymodel('sayan', 'sayan@example.com')",
+            "insert into myspace.mymodel('robot', 'robot@spam.net')",
+            "insert into myspace.mymodel('hgwells', 'hgwells@spam.net')",
+            "insert into myspace.mymodel('orwell', null)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_delete_only(
+            &global,
+            "delete from myspace.mymodel where email matches '@spam[.]net$'"
+        )
+        .unwrap(),
+        Some(2)
+    );
+    let mut remaining = super::exec_select_all_only(
+        &global,
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    remaining.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+    assert_eq!(remaining, vec![intovec!["orwell"], intovec!["sayan"]]);
+    assert_eq!(
+        super::exec_delete_only(
+            &global,
+            "delete from myspace.mymodel where email matches '('"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlIllegalPattern
+    );
+}
+
+#[test]
+fn delete_filtered_unknown_field() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_filtered_unknown_field");
+    assert_eq!(
+        super::exec_delete(
+            &global,
+            "create model myspace.mymodel(username: string, password: string)",
+            None,
+            "delete from myspace.mymodel where email = 'sayan@example.com' limit 10",
+            "sayan",
+        )
+        .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+}
+
+#[test]
+fn delete_returning() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_returning");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('a', 10)",
+            "insert into myspace.mymodel('b', 20)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where username = 'a' returning age"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::Row,
+            size: 1,
+            data: b"\x0210\n".to_vec(),
+        }
+    );
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where age > 15 returning *"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"2\n\x0D1\nb\x0220\n".to_vec(),
+        }
+    );
+    // nothing left to delete
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where age > 15 returning *"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 0,
+            data: vec![],
+        }
+    );
+}
+
+#[test]
+fn delete_with_lsn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_with_lsn");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('a', 10)",
+            "insert into myspace.mymodel('b', 20)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where username = 'a' with lsn"
+        )
+        .unwrap(),
+        Response::UInt64(2)
+    );
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where age > 50 with lsn"
+        )
+        .unwrap(),
+        Response::Null
+    );
+}
+
+#[test]
+fn delete_with_summary() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_with_summary");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('a', 10)",
+            "insert into myspace.mymodel('b', 20)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where username = 'a' with keys"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::String,
+            size: 82,
+            data: br#"{"matched":1,"modified":0,"inserted":0,"deleted":1,"keys":["a"],"truncated":false}"#
+                .to_vec(),
+        }
+    );
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where age > 50 with summary"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::String,
+            size: 51,
+            data: br#"{"matched":0,"modified":0,"inserted":0,"deleted":0}"#.to_vec(),
+        }
+    );
+}
+
+#[test]
+fn delete_returning_memory_limit() {
+    let mut global =
+        TestGlobal::new_with_driver_id_instant_update("dml_delete_returning_memory_limit");
+    global.set_query_memory_limit(4096);
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, bio: string)",
+    )
+    .unwrap();
+    for i in 0..10 {
+        super::exec_insert_only(
+            &global,
+            &format!(
+                "insert into myspace.mymodel('user{i}', '{}')",
+                "x".repeat(1000)
+            ),
+        )
+        .unwrap();
+    }
+    // the limit is checked before any row is removed
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "delete from myspace.mymodel where bio matches 'x' returning *"
+        )
+        .unwrap_err(),
+        QueryError::QExecQueryMemoryLimitExceeded
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel limit 100"
+        )
+        .unwrap()
+        .len(),
+        10
+    );
+    // without returning the rows, only the keys are held
+    assert_eq!(
+        super::exec_delete_only(&global, "delete from myspace.mymodel where bio matches 'x'")
+            .unwrap(),
+        Some(10)
+    );
+}
+
+#[test]
+fn soft_delete() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_soft_delete");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, password: string) with { soft_delete: true }",
+    )
+    .unwrap();
+    for user in ["sayan", "ohsayan"] {
+        super::exec_insert_only(
+            &global,
+            &format!("insert into myspace.mymodel('{user}', 'pass123')"),
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        super::exec_delete_only(
+            &global,
+            "delete from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        None
+    );
+    // the row is hidden
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select * from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+    assert_eq!(
+        super::exec_select_all_only(&global, "select all username from myspace.mymodel limit 10")
+            .unwrap(),
+        vec![intovec!["ohsayan"]]
+    );
+    // .. unless asked for
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select * from myspace.mymodel where username = 'sayan' with deleted"
+        )
+        .unwrap(),
+        intovec!["sayan", "pass123"]
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel limit 10 with deleted"
+        )
+        .unwrap()
+        .len(),
+        2
+    );
+    // but it can't be deleted or updated again
+    assert_eq!(
+        super::exec_delete_only(
+            &global,
+            "delete from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set password = 'pass321' where username = 'sayan'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+    assert_eq!(
+        super::exec_delete_only(
+            &global,
+            "delete from myspace.mymodel where password = 'pass123'"
+        )
+        .unwrap(),
+        Some(1)
+    );
+    // inserting the same key replaces the tombstoned row
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', 'pass321')").unwrap();
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select * from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec!["sayan", "pass321"]
+    );
+}
+
+#[test]
+fn soft_delete_purge() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_delete_soft_delete_purge");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, password: string) with { soft_delete: true, tombstone_retention: 60 }",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', 'pass123')").unwrap();
+    super::exec_delete_only(
+        &global,
+        "delete from myspace.mymodel where username = 'sayan'",
+    )
+    .unwrap();
+    let purge_until = |now, token: &CancelToken| {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                Ok(dml::purge_tombstones(mdl, now, token))
+            })
+            .unwrap()
+    };
+    let purge = |now| purge_until(now, &CancelToken::default());
+    // still within the retention period
+    let now = os::get_epoch_time_secs();
+    assert_eq!(purge(now), 0);
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel limit 10 with deleted"
+        )
+        .unwrap(),
+        vec![intovec!["sayan"]]
+    );
+    // a cancelled sweep leaves it for the next one
+    let cancelled = CancelToken::default();
+    cancelled.cancel();
+    assert_eq!(purge_until(now + 60, &cancelled), 0);
+    // and now it's gone for good
+    assert_eq!(purge(now + 60), 1);
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select * from myspace.mymodel where username = 'sayan' with deleted"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+}
@@ -0,0 +1,124 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::{
+    core::dml,
+    error::{QueryError, QueryResult},
+    fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+    ql::{ast::parse_ast_node_full, tests::lex_insecure},
+};
+
+fn exec_eval(global: &impl GlobalInstanceLike, eval: &str) -> QueryResult<(bool, u64)> {
+    let tok = lex_insecure(eval.as_bytes()).unwrap();
+    dml::eval(global, parse_ast_node_full(&tok[1..]).unwrap())
+}
+
+fn logins(global: &impl GlobalInstanceLike, username: &str) -> QueryResult<u64> {
+    super::exec_select_only(
+        global,
+        &format!("select logins from myspace.users where username = '{username}'"),
+    )
+    .map(|row| row[0].uint())
+}
+
+#[test]
+fn upsert() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_eval_upsert");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, logins: uint64)",
+    )
+    .unwrap();
+    let upsert = "eval { if exists from myspace.users where username = 'sayan' then update myspace.users set logins += 1 where username = 'sayan' else insert into myspace.users('sayan', 1) }";
+    assert_eq!(exec_eval(&global, upsert).unwrap(), (false, 1));
+    assert_eq!(logins(&global, "sayan").unwrap(), 1);
+    assert_eq!(exec_eval(&global, upsert).unwrap(), (true, 1));
+    assert_eq!(exec_eval(&global, upsert).unwrap(), (true, 1));
+    assert_eq!(logins(&global, "sayan").unwrap(), 3);
+}
+
+#[test]
+fn insert_or_delete_once() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_eval_insert_or_delete_once");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, logins: uint64)",
+    )
+    .unwrap();
+    let insert = "eval { if not exists from myspace.users where username = 'sayan' then insert into myspace.users('sayan', 0) }";
+    assert_eq!(exec_eval(&global, insert).unwrap(), (true, 1));
+    // nothing to do the second time
+    assert_eq!(exec_eval(&global, insert).unwrap(), (false, 0));
+    let delete = "eval { if exists from myspace.users where username = 'sayan' then delete from myspace.users where username = 'sayan' }";
+    assert_eq!(exec_eval(&global, delete).unwrap(), (true, 1));
+    assert_eq!(
+        logins(&global, "sayan").unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+    assert_eq!(exec_eval(&global, delete).unwrap(), (false, 0));
+}
+
+#[test]
+fn eval_errors() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_eval_errors");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, logins: uint64)",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.users('sayan', 0)").unwrap();
+    for (eval, error) in [
+        // the condition and the actions look up a single primary key
+        (
+            "eval { if exists from myspace.users where logins = 0 then delete from myspace.users where username = 'sayan' }",
+            QueryError::QExecDmlWhereHasUnindexedColumn,
+        ),
+        (
+            "eval { if exists from myspace.users where username = 'sayan' then update myspace.users set logins += 1 where logins = 0 }",
+            QueryError::QExecDmlWhereHasUnindexedColumn,
+        ),
+        (
+            "eval { if exists from myspace.users where username = 'sayan' then insert into myspace.users('sayan', 1) }",
+            QueryError::QExecDmlDuplicate,
+        ),
+        (
+            "eval { if exists from myspace.users where username = 'sayan' then update myspace.users set logins += 'a' where username = 'sayan' }",
+            QueryError::QExecDmlValidationError,
+        ),
+    ] {
+        assert_eq!(exec_eval(&global, eval).unwrap_err(), error, "{eval}");
+    }
+    // a failed action doesn't change anything
+    assert_eq!(logins(&global, "sayan").unwrap(), 0);
+    assert_eq!(
+        exec_eval(
+            &global,
+            "eval { if exists from myspace.nothere where username = 'sayan' then delete from myspace.nothere where username = 'sayan' }"
+        )
+        .unwrap_err(),
+        QueryError::QExecObjectNotFound
+    );
+}
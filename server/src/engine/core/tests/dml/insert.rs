@@ -24,7 +24,16 @@
  *
 */
 
-use crate::engine::{data::cell::Datacell, error::QueryError, fractal::test_utils::TestGlobal};
+use crate::engine::{
+    core::{dml, tests::ddl_model::exec_create_index, EntityIDRef},
+    data::{cell::Datacell, lit::Lit},
+    error::{ErrorDetail, QueryError},
+    fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+    idx::STIndex,
+    net::protocol::{Response, ResponseType},
+    storage::driver::MemoryDriver,
+    sync::atm::cpin,
+};
 
 #[derive(sky_macros::Wrapper, Debug)]
 struct Tuple(Vec<(Box<str>, Datacell)>);
@@ -86,3 +95,734 @@ fn insert_duplicate() {
         QueryError::QExecDmlDuplicate
     );
 }
+
+#[test]
+fn insert_returning() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_returning");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, followers: uint64)",
+        &[],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "insert into myspace.mymodel('sayan', 100) returning followers, username"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::Row,
+            size: 2,
+            data: b"\x05100\n\x0D5\nsayan".to_vec(),
+        }
+    );
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "insert into myspace.mymodel('robot', 0) returning email"
+        )
+        .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+}
+
+#[test]
+fn insert_with_lsn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_with_lsn");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, followers: uint64)",
+        &[],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "insert into myspace.mymodel('sayan', 100) with lsn"
+        )
+        .unwrap(),
+        Response::UInt64(0)
+    );
+    assert_eq!(
+        super::exec_dml_resp(&global, "insert into myspace.mymodel('robot', 0) with lsn").unwrap(),
+        Response::UInt64(1)
+    );
+}
+
+#[test]
+fn insert_with_summary() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_with_summary");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, followers: uint64)",
+        &[],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "insert into myspace.mymodel('sayan', 100) with keys"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::String,
+            size: 86,
+            data: br#"{"matched":0,"modified":0,"inserted":1,"deleted":0,"keys":["sayan"],"truncated":false}"#
+                .to_vec(),
+        }
+    );
+}
+
+#[test]
+fn insert_generated() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_generated");
+    super::exec_insert(
+        &global,
+        "create model myspace.mymodel(username: string, token: string { generated: \"ulid\" }, session: string)",
+        "insert into myspace.mymodel { username: 'sayan', session: uuid4() }",
+        "sayan",
+        |row| {
+            let row = row.cloned_data();
+            let token = row.iter().find(|(f, _)| f.as_ref() == "token").unwrap();
+            let session = row.iter().find(|(f, _)| f.as_ref() == "session").unwrap();
+            assert_eq!(token.1.str().len(), 26);
+            assert_eq!(session.1.str().len(), 36);
+        },
+    )
+    .unwrap();
+    // a provided value is used as is
+    super::exec_insert_core(
+        &global,
+        "insert into myspace.mymodel { username: 'robot', token: 'abc', session: 'def' }",
+        "robot",
+        |row| {
+            assert_veceq_transposed!(
+                row.cloned_data(),
+                Tuple(pairvec!(("token", "abc"), ("session", "def")))
+            );
+        },
+    )
+    .unwrap();
+    // fields without a generator must still be provided
+    assert_eq!(
+        super::exec_insert_only(
+            &global,
+            "insert into myspace.mymodel { username: 'human', token: 'abc' }"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+}
+
+#[test]
+fn insert_literal_default() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_literal_default");
+    // a quoted call is just a string
+    super::exec_insert(
+        &global,
+        "create model myspace.mymodel(username: string, status: string { default: \"uuid7()\" }, visits: uint64 { default: 0 })",
+        "insert into myspace.mymodel { username: 'sayan' }",
+        "sayan",
+        |row| {
+            assert_veceq_transposed!(
+                row.cloned_data(),
+                Tuple(pairvec!(("status", "uuid7()"), ("visits", 0_u64)))
+            );
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn insert_computed() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_computed");
+    super::exec_insert(
+        &global,
+        "create model myspace.mymodel(username: string, email: string, email_lc: string { computed: \"lower(trim(email))\" })",
+        "insert into myspace.mymodel('sayan', ' Sayan@Example.com ')",
+        "sayan",
+        |row| {
+            assert_veceq_transposed!(
+                row.cloned_data(),
+                Tuple(pairvec!(
+                    ("email", " Sayan@Example.com "),
+                    ("email_lc", "sayan@example.com")
+                ))
+            );
+        },
+    )
+    .unwrap();
+    super::exec_insert_core(
+        &global,
+        "insert into myspace.mymodel { username: 'robot', email: 'ROBOT@example.com' }",
+        "robot",
+        |row| {
+            assert_veceq_transposed!(
+                row.cloned_data(),
+                Tuple(pairvec!(
+                    ("email", "ROBOT@example.com"),
+                    ("email_lc", "robot@example.com")
+                ))
+            );
+        },
+    )
+    .unwrap();
+    // computed fields can't be provided
+    assert_eq!(
+        super::exec_insert_only(
+            &global,
+            "insert into myspace.mymodel('human', 'human@example.com', 'human@example.com')"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+    assert_eq!(
+        super::exec_insert_only(
+            &global,
+            "insert into myspace.mymodel { username: 'human', email: 'a@b.c', email_lc: 'a@b.c' }"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+    // and the stored value can be indexed and used to filter
+    exec_create_index(&global, "create index byemail on myspace.mymodel(email_lc)").unwrap();
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where email_lc = 'robot@example.com' limit 10"
+        )
+        .unwrap(),
+        vec![intovec!["robot"]]
+    );
+}
+
+#[test]
+fn insert_checks() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_checks");
+    super::exec_insert(
+        &global,
+        "create model myspace.mymodel(username: string { maxlen: 8, regex: \"[a-z]+\" }, age: uint8 { min: 1, max: 120 }, tags: list { type: string, maxlen: 2 })",
+        "insert into myspace.mymodel('sayan', 1, ['a', 'b'])",
+        "sayan",
+        |_| {},
+    )
+    .unwrap();
+    for (insert, error) in [
+        (
+            "insert into myspace.mymodel('robot', 0, [])",
+            QueryError::QExecDmlCheckMinViolated,
+        ),
+        (
+            "insert into myspace.mymodel('robot', 121, [])",
+            QueryError::QExecDmlCheckMaxViolated,
+        ),
+        (
+            "insert into myspace.mymodel('verylongname', 1, [])",
+            QueryError::QExecDmlCheckMaxLenViolated,
+        ),
+        (
+            "insert into myspace.mymodel('robot', 1, ['a', 'b', 'c'])",
+            QueryError::QExecDmlCheckMaxLenViolated,
+        ),
+        (
+            "insert into myspace.mymodel('robot2', 1, [])",
+            QueryError::QExecDmlCheckRegexViolated,
+        ),
+        (
+            "insert into myspace.mymodel { username: 'Robot', age: 1, tags: [] }",
+            QueryError::QExecDmlCheckRegexViolated,
+        ),
+    ] {
+        assert_eq!(
+            super::exec_insert_only(&global, insert).unwrap_err(),
+            error,
+            "{insert}"
+        );
+    }
+}
+
+#[test]
+fn insert_error_details() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_error_details");
+    super::exec_insert(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8 { min: 1, max: 120 })",
+        "insert into myspace.mymodel('sayan', 1)",
+        "sayan",
+        |_| {},
+    )
+    .unwrap();
+    for (insert, detail) in [
+        (
+            "insert into myspace.mymodel('robot', 121)",
+            vec![("constraint", "max"), ("limit", "120"), ("field", "age")],
+        ),
+        (
+            "insert into myspace.mymodel('sayan', 2)",
+            vec![("constraint", "primary_key"), ("field", "username")],
+        ),
+        (
+            "insert into myspace.nomodel('sayan', 2)",
+            vec![("entity", "myspace.nomodel")],
+        ),
+    ] {
+        let e = super::exec_insert_only(&global, insert).unwrap_err();
+        let taken = ErrorDetail::take(e);
+        let entries: Vec<(&str, &str)> = taken
+            .entries()
+            .iter()
+            .map(|(k, v)| (*k, v.as_ref()))
+            .collect();
+        assert_eq!(entries, detail, "{insert}");
+    }
+}
+
+#[test]
+fn bulk_insert() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_bulk_insert");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+    )
+    .unwrap();
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    let row = |username: &str, age: u64| {
+        vec![
+            Datacell::new_str(username.into()),
+            Datacell::new_uint_default(age),
+        ]
+    };
+    assert_eq!(
+        dml::bulk_insert(&global, entity, vec![row("sayan", 21), row("robot", 30)]),
+        Ok(2)
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select age from myspace.mymodel where username = 'robot'"
+        )
+        .unwrap(),
+        intovec![30_u64]
+    );
+    let details = |e: QueryError| -> Vec<(&'static str, Box<str>)> {
+        ErrorDetail::take(e)
+            .entries()
+            .iter()
+            .map(|(k, v)| (*k, v.clone()))
+            .collect()
+    };
+    // every row is validated first, so nothing is inserted if any row is bad
+    let e = dml::bulk_insert(
+        &global,
+        entity,
+        vec![row("alpha", 1), vec![Datacell::new_str("beta".into())]],
+    )
+    .unwrap_err();
+    assert_eq!(e, QueryError::QExecDmlValidationError);
+    assert_eq!(details(e), [("row", "1".into())]);
+    // but rows are inserted until one of them has a key that's taken
+    let e = dml::bulk_insert(
+        &global,
+        entity,
+        vec![row("gamma", 1), row("sayan", 2), row("delta", 3)],
+    )
+    .unwrap_err();
+    assert_eq!(e, QueryError::QExecDmlDuplicate);
+    assert_eq!(
+        details(e),
+        [
+            ("constraint", "primary_key".into()),
+            ("field", "username".into()),
+            ("row", "1".into())
+        ]
+    );
+    for (username, exists) in [("alpha", false), ("gamma", true), ("delta", false)] {
+        assert_eq!(
+            super::exec_select_only(
+                &global,
+                &format!("select age from myspace.mymodel where username = '{username}'")
+            )
+            .is_ok(),
+            exists,
+            "{username}"
+        );
+    }
+}
+
+#[test]
+fn bulk_load() {
+    let driver = MemoryDriver::new();
+    let global = TestGlobal::new_with_memory_driver("dml_insert_bulk_load", &driver);
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8) with { partitions: 4 }",
+    )
+    .unwrap();
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    let row = |username: &str, age: u64| {
+        vec![
+            Datacell::new_str(username.into()),
+            Datacell::new_uint_default(age),
+        ]
+    };
+    super::_exec_only_insert(&global, "insert into myspace.mymodel('sayan', 21)", |_| {}).unwrap();
+    let rows: Vec<_> = (0..100).map(|i| row(&format!("user{i}"), i)).collect();
+    assert_eq!(dml::bulk_load(&global, entity, rows), Ok(100));
+    // the insert before the load is written first, and then the rows of each partition go out as one batch
+    assert_eq!(driver.batches(), 1 + 4);
+    assert_eq!(driver.changes(), 101);
+    global
+        .state()
+        .namespace()
+        .with_model(entity, |mdl| {
+            assert_eq!(mdl.delta_state().pending_data_deltas(), 0);
+            assert_eq!(mdl.primary_index().count(), 101);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select age from myspace.mymodel where username = 'user42'"
+        )
+        .unwrap(),
+        intovec![42_u64]
+    );
+    // a key that's taken (or that's used twice) rejects every row
+    for rows in [
+        vec![row("alpha", 1), row("sayan", 2)],
+        vec![row("alpha", 1), row("alpha", 2)],
+    ] {
+        assert_eq!(
+            dml::bulk_load(&global, entity, rows).unwrap_err(),
+            QueryError::QExecDmlDuplicate
+        );
+    }
+    assert_eq!(
+        dml::bulk_load(
+            &global,
+            entity,
+            vec![vec![Datacell::new_str("beta".into())]]
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+    assert!(super::exec_select_only(
+        &global,
+        "select age from myspace.mymodel where username = 'alpha'"
+    )
+    .is_err());
+    assert_eq!(driver.changes(), 101);
+    // nothing is loaded if the rows can't be written
+    driver.set_failing(true);
+    assert_eq!(
+        dml::bulk_load(&global, entity, vec![row("alpha", 1)]).unwrap_err(),
+        QueryError::SysServerError
+    );
+    driver.set_failing(false);
+    assert!(super::exec_select_only(
+        &global,
+        "select age from myspace.mymodel where username = 'alpha'"
+    )
+    .is_err());
+}
+
+#[test]
+fn bulk_load_partial() {
+    let driver = MemoryDriver::new();
+    let global = TestGlobal::new_with_memory_driver("dml_insert_bulk_load_partial", &driver);
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8) with { partitions: 4 }",
+    )
+    .unwrap();
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    let rows: Vec<_> = (0..100)
+        .map(|i| {
+            vec![
+                Datacell::new_str(format!("user{i}").into_boxed_str()),
+                Datacell::new_uint_default(i),
+            ]
+        })
+        .collect();
+    // the disk fills up after two of the four batches
+    driver.set_failing_after(2);
+    let e = dml::bulk_load(&global, entity, rows).unwrap_err();
+    assert_eq!(e, QueryError::SysServerError);
+    let taken = ErrorDetail::take(e);
+    let loaded: u64 = taken
+        .entries()
+        .iter()
+        .find(|(k, _)| *k == "loaded")
+        .unwrap()
+        .1
+        .parse()
+        .unwrap();
+    // only the rows of the batches that were written are loaded
+    assert_eq!(driver.batches(), 2);
+    assert_eq!(driver.changes(), loaded);
+    assert!((loaded != 0) & (loaded < 100));
+    global
+        .state()
+        .namespace()
+        .with_model(entity, |mdl| {
+            assert_eq!(mdl.primary_index().count() as u64, loaded);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn insert_numeric_coercion() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_numeric_coercion");
+    // strict (the default): the kinds must match
+    super::exec_insert(
+        &global,
+        "create model myspace.strict(username: string, delta: sint8, score: float32)",
+        "insert into myspace.strict('sayan', -1, 1.5)",
+        "sayan",
+        |_| {},
+    )
+    .unwrap();
+    for insert in [
+        "insert into myspace.strict('robot', 1, 1.5)",
+        "insert into myspace.strict('robot', -1, 1)",
+    ] {
+        assert_eq!(
+            super::exec_insert_only(&global, insert).unwrap_err(),
+            QueryError::QExecDmlValidationError,
+            "{insert}"
+        );
+    }
+    // lenient: converted if it fits
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.lenient(username: string, delta: sint8, visits: uint8, score: float32) with { coercion: 'lenient' }",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.lenient('sayan', 1, 2.0, 3)").unwrap();
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select delta, visits, score from myspace.lenient where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![1_i64, 2_u64, 3.0_f64]
+    );
+    // but bounds are still checked and nothing is ever truncated
+    for insert in [
+        "insert into myspace.lenient('robot', 128, 0, 0)",
+        "insert into myspace.lenient('robot', 0, -1, 0)",
+        "insert into myspace.lenient('robot', 0, 2.5, 0)",
+        "insert into myspace.lenient('robot', 0, 256.0, 0)",
+    ] {
+        assert_eq!(
+            super::exec_insert_only(&global, insert).unwrap_err(),
+            QueryError::QExecDmlValidationError,
+            "{insert}"
+        );
+    }
+}
+
+#[test]
+fn insert_type_mismatch_detail() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_insert_type_mismatch_detail");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, tags: list { type: string }, age: uint8, score: float32)",
+    )
+    .unwrap();
+    let details = |insert: &str| -> Vec<(&'static str, Box<str>)> {
+        let e = super::exec_insert_only(&global, insert).unwrap_err();
+        assert_eq!(e, QueryError::QExecDmlValidationError, "{insert}");
+        ErrorDetail::take(e)
+            .entries()
+            .iter()
+            .map(|(k, v)| (*k, v.clone()))
+            .collect()
+    };
+    // every element of a list is a parameter of its own
+    assert_eq!(
+        details("insert into myspace.mymodel('sayan', ['a', 'b'], -1, 1.5)"),
+        [
+            ("field", "age".into()),
+            ("parameter", "3".into()),
+            ("expected", "uint8".into()),
+            ("received", "sint64".into())
+        ]
+    );
+    assert_eq!(
+        details("insert into myspace.mymodel('sayan', [1, 2], 1, 1.5)"),
+        [
+            ("field", "tags".into()),
+            ("parameter", "1".into()),
+            ("expected", "string".into()),
+            ("received", "uint64".into())
+        ]
+    );
+    // the order of a map isn't kept, so only the field is named
+    assert_eq!(
+        details(
+            "insert into myspace.mymodel { username: 'sayan', tags: [], age: 1, score: 'high' }"
+        ),
+        [
+            ("field", "score".into()),
+            ("expected", "float32".into()),
+            ("received", "string".into())
+        ]
+    );
+    // bounds aren't type mismatches
+    assert_eq!(
+        details("insert into myspace.mymodel('sayan', [], 256, 1.5)"),
+        []
+    );
+    // exact: a float32 field can't be given a float that it would round
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.exact(username: string, score: float32, ratio: float64) with { coercion: 'exact' }",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.exact('sayan', 1.5, 0.1)").unwrap();
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', [], 1, 0.1)").unwrap();
+    assert_eq!(
+        details("insert into myspace.exact('robot', 0.1, 0.1)"),
+        [
+            ("field", "score".into()),
+            ("parameter", "1".into()),
+            ("expected", "float32".into()),
+            ("received", "float64".into())
+        ]
+    );
+}
+
+#[test]
+fn import_rdb() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_import_rdb");
+    const PATH: &str = "dml_import_rdb.rdb";
+    let mut rdb = b"REDIS0011\xFE\x00".to_vec();
+    rdb.extend(b"\x00\x08greeting\x05hello");
+    rdb.extend(b"\x04\x06user:1\x02\x04name\x05sayan\x03age\x0220");
+    rdb.extend(b"\x04\x06user:2\x01\x03age\x0230");
+    rdb.extend(b"\x01\x05queue\x02\x01a\x01b");
+    // a set, which is skipped
+    rdb.extend(b"\x02\x04tags\x01\x01x");
+    rdb.extend(b"\xFF\x00\x00\x00\x00\x00\x00\x00\x00");
+    std::fs::write(PATH, &rdb).unwrap();
+    super::_exec_only_create_space_model(&global, "create model myspace.kv(k: string, v: string)")
+        .unwrap();
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, null name: string, age: uint8)",
+    )
+    .unwrap();
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.queues(name: string, items: list { type: string })",
+    )
+    .unwrap();
+    let mapping = into_dict!(
+        "strings" => Lit::new_str("myspace.kv"),
+        "hashes" => Lit::new_str("myspace.users"),
+        "lists" => Lit::new_str("myspace.queues"),
+    );
+    assert_eq!(
+        dml::import_rdb(&global, PATH, mapping).unwrap(),
+        "{\"imported\":4,\"skipped\":1}"
+    );
+    assert_eq!(
+        super::exec_select_only(&global, "select v from myspace.kv where k = 'greeting'").unwrap(),
+        intovec!["hello"]
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select name, age from myspace.users where username = 'user:2'"
+        )
+        .unwrap(),
+        intovec![Datacell::null(), 30_u64]
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select items from myspace.queues where name = 'queue'"
+        )
+        .unwrap(),
+        intovec![Datacell::new_list(intovec!["a", "b"])]
+    );
+    // the keys are taken now
+    let mapping = into_dict!("strings" => Lit::new_str("myspace.kv"));
+    assert_eq!(
+        dml::import_rdb(&global, PATH, mapping).unwrap_err(),
+        QueryError::QExecDmlDuplicate
+    );
+    // a string can't be imported into a model with more than one value
+    let mapping = into_dict!("strings" => Lit::new_str("myspace.users"));
+    assert_eq!(
+        dml::import_rdb(&global, PATH, mapping).unwrap_err(),
+        QueryError::QExecDdlModelBadDefinition
+    );
+    std::fs::remove_file(PATH).unwrap();
+}
+
+#[test]
+fn generate_rows() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_generate_rows");
+    let model = |name: &str| {
+        format!(
+            "create model myspace.{name}(id: uint8, name: string {{ maxlen: 4 }}, age: uint16 {{ min: 10, max: 20 }}, null score: float64 {{ min: 1, max: 2 }}, tags: list {{ type: string }}, token: string {{ generated: \"ulid\" }})"
+        )
+    };
+    super::_exec_only_create_space_model(&global, &model("first")).unwrap();
+    super::_exec_only_create_space_model(&global, &model("second")).unwrap();
+    let (first, second) = (
+        EntityIDRef::new("myspace", "first"),
+        EntityIDRef::new("myspace", "second"),
+    );
+    assert_eq!(
+        dml::generate_rows(&global, first, 100, Some(7)),
+        Ok((100, 7))
+    );
+    assert_eq!(
+        dml::generate_rows(&global, second, 100, Some(7)),
+        Ok((100, 7))
+    );
+    let rows = |entity| {
+        global
+            .state()
+            .namespace()
+            .with_model(entity, |mdl| {
+                let g = cpin();
+                let mut rows: Vec<_> = mdl
+                    .primary_index()
+                    .iter(&g)
+                    .map(|row| {
+                        let data = row.d_data().read().unwrap();
+                        let field = |name: &str| data.fields().st_get(name).unwrap().clone();
+                        let (name, age, score) = (field("name"), field("age"), field("score"));
+                        assert!(name.str().chars().count() <= 4);
+                        assert!((10..=20).contains(&age.uint()));
+                        assert!(score.is_null() || (1.0..=2.0).contains(&score.float()));
+                        assert!(field("tags").list().read().len() <= 16);
+                        assert_eq!(field("token").str().len(), 26);
+                        (row.d_key().uint().unwrap(), name, age, score)
+                    })
+                    .collect();
+                rows.sort_by_key(|(id, ..)| *id);
+                Ok(rows)
+            })
+            .unwrap()
+    };
+    // the same seed generates the same rows (except for generated keys)
+    assert_eq!(rows(first), rows(second));
+    // there are only 256 keys to go around
+    assert_eq!(
+        dml::generate_rows(&global, first, 200, None).unwrap_err(),
+        QueryError::QExecDmlDuplicate
+    );
+    let count = rows(first).len();
+    assert!((100..=256).contains(&count));
+}
@@ -25,15 +25,19 @@
 */
 
 mod delete;
+mod eval;
 mod insert;
 mod select;
+mod traverse;
 mod update;
+mod view;
 
 use crate::engine::{
     core::{dml, index::Row, model::ModelData, space::Space, EntityIDRef},
     data::{cell::Datacell, lit::Lit},
     error::QueryResult,
     fractal::GlobalInstanceLike,
+    net::protocol::Response,
     ql::{
         ast::parse_ast_node_full,
         dml::{del::DeleteStatement, ins::InsertStatement},
@@ -108,7 +112,7 @@ fn _exec_only_select(global: &impl GlobalInstanceLike, select: &str) -> QueryRes
     Ok(r)
 }
 
-fn _exec_only_update(global: &impl GlobalInstanceLike, update: &str) -> QueryResult<()> {
+fn _exec_only_update(global: &impl GlobalInstanceLike, update: &str) -> QueryResult<Option<u64>> {
     let lex_upd = lex_insecure(update.as_bytes()).unwrap();
     let update = parse_ast_node_full(&lex_upd[1..]).unwrap();
     dml::update(global, update)
@@ -140,6 +144,17 @@ pub(self) fn exec_insert_only(global: &impl GlobalInstanceLike, insert: &str) ->
     _exec_only_insert(global, insert, |_| {})
 }
 
+/// Run an `insert`, `update` or `delete` and return the response sent to the client
+pub(self) fn exec_dml_resp(global: &impl GlobalInstanceLike, query: &str) -> QueryResult<Response> {
+    let tok = lex_insecure(query.as_bytes()).unwrap();
+    match query.split_whitespace().next() {
+        Some("insert") => dml::insert_resp(global, parse_ast_node_full(&tok[1..]).unwrap()),
+        Some("update") => dml::update_resp(global, parse_ast_node_full(&tok[1..]).unwrap()),
+        Some("delete") => dml::delete_resp(global, parse_ast_node_full(&tok[1..]).unwrap()),
+        _ => panic!("not a dml query: {query}"),
+    }
+}
+
 pub(self) fn exec_delete(
     global: &impl GlobalInstanceLike,
     model: &str,
@@ -154,6 +169,15 @@ pub(self) fn exec_delete(
     _exec_delete_only(global, delete, key)
 }
 
+pub(self) fn exec_delete_only(
+    global: &impl GlobalInstanceLike,
+    delete: &str,
+) -> QueryResult<Option<u64>> {
+    let lex_del = lex_insecure(delete.as_bytes()).unwrap();
+    let delete = parse_ast_node_full::<DeleteStatement>(&lex_del[1..]).unwrap();
+    dml::delete(global, delete)
+}
+
 pub(self) fn exec_select(
     global: &impl GlobalInstanceLike,
     model: &str,
@@ -175,10 +199,24 @@ pub(self) fn exec_select_all(
     for insert in inserts {
         _exec_only_insert(global, insert, |_| {})?;
     }
+    exec_select_all_only(global, select)
+}
+
+pub(self) fn exec_select_all_only(
+    global: &impl GlobalInstanceLike,
+    select: &str,
+) -> QueryResult<Vec<Vec<Datacell>>> {
+    exec_select_all_with_total_only(global, select).map(|(rows, _)| rows)
+}
+
+pub(self) fn exec_select_all_with_total_only(
+    global: &impl GlobalInstanceLike,
+    select: &str,
+) -> QueryResult<(Vec<Vec<Datacell>>, Option<dml::Total>)> {
     let lex_sel = lex_insecure(select.as_bytes()).unwrap();
     let select = parse_ast_node_full(&lex_sel[2..]).unwrap();
     let mut r: Vec<Vec<Datacell>> = Vec::new();
-    dml::select_all(
+    let (_, total) = dml::select_all_with_total(
         global,
         select,
         &mut r,
@@ -188,7 +226,19 @@ pub(self) fn exec_select_all(
             _ => rows.push(vec![dc.clone()]),
         },
     )?;
-    Ok(r)
+    Ok((r, total))
+}
+
+pub(self) fn exec_explain_select_all(
+    global: &impl GlobalInstanceLike,
+    explain: &str,
+) -> QueryResult<String> {
+    let lex_explain = lex_insecure(explain.as_bytes()).unwrap();
+    let select = parse_ast_node_full(&lex_explain[3..]).unwrap();
+    match dml::explain_select_all_resp(global, select)? {
+        Response::Serialized { data, .. } => Ok(String::from_utf8(data).unwrap()),
+        _ => panic!("expected a serialized response"),
+    }
 }
 
 pub(self) fn exec_select_only(
@@ -198,6 +248,11 @@ pub(self) fn exec_select_only(
     _exec_only_select(global, select)
 }
 
+pub(self) fn exec_exists_only(global: &impl GlobalInstanceLike, exists: &str) -> QueryResult<bool> {
+    let lex_exists = lex_insecure(exists.as_bytes()).unwrap();
+    dml::exists(global, parse_ast_node_full(&lex_exists[1..]).unwrap())
+}
+
 pub(self) fn exec_update(
     global: &impl GlobalInstanceLike,
     model: &str,
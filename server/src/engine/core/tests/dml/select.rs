@@ -25,7 +25,33 @@
 */
 
 use {
-    crate::engine::{data::cell::Datacell, error::QueryError, fractal::test_utils::TestGlobal},
+    crate::{
+        engine::{
+            core::{
+                dml,
+                index::{Collation, PrimaryIndexKey},
+                model::{
+                    describe_hot_keys, describe_indexes, result_cache::CacheGeneration, ModelData,
+                },
+                notice::{Notice, NoticeCode},
+                session::{self, SessionSettings},
+                tests::ddl_model::{exec_create_index, exec_drop_index},
+                EntityIDRef,
+            },
+            data::{cell::Datacell, lit::Lit},
+            error::{QueryError, QueryResult},
+            fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+            net::protocol::{Response, ResponseType},
+            ql::{
+                ast::parse_ast_node_full,
+                ddl::{SessionSet, SetValue},
+                dml::sel::FetchStatement,
+                lex::{encode_param, Token},
+                tests::{lex_insecure, lex_secure},
+            },
+        },
+        util::os,
+    },
     std::collections::HashMap,
 };
 
@@ -165,3 +191,2423 @@ fn select_all_onefield() {
     assert_eq!(ret.get("hgwells").unwrap(), &intovec![]);
     assert_eq!(ret.get("orwell").unwrap(), &intovec![]);
 }
+
+#[test]
+fn select_all_ordered_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_ordered_index");
+    let ret = super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, password: string) with { primary_index: 'btree' }",
+        &[
+            "insert into myspace.mymodel('sayan', 'password123')",
+            "insert into myspace.mymodel('robot', 'robot123')",
+            "insert into myspace.mymodel('douglas', 'galaxy123')",
+            "insert into myspace.mymodel('hgwells', 'timemachine')",
+            "insert into myspace.mymodel('orwell', '1984')",
+        ],
+        "select all username from myspace.mymodel LIMIT 100",
+    )
+    .unwrap();
+    assert_eq!(
+        ret,
+        vec![
+            intovec!["douglas"],
+            intovec!["hgwells"],
+            intovec!["orwell"],
+            intovec!["robot"],
+            intovec!["sayan"],
+        ]
+    );
+}
+
+#[test]
+fn select_all_paginated() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_paginated");
+    for (model, index) in [("mymodel", "hash"), ("mymodel2", "btree")] {
+        super::_exec_only_create_space_model(
+            &global,
+            &format!(
+                "create model myspace.{model}(username: string, city: string) with {{ primary_index: '{index}' }}"
+            ),
+        )
+        .unwrap();
+        for i in 0..25 {
+            let city = ["london", "paris"][i % 2];
+            super::exec_insert_only(
+                &global,
+                &format!("insert into myspace.{model}('user{i:02}', '{city}')"),
+            )
+            .unwrap();
+        }
+    }
+    exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap();
+    // a full scan and a secondary index lookup
+    for (select, count) in [
+        ("select all username from myspace.mymodel", 25),
+        ("select all username from myspace.mymodel2", 25),
+        (
+            "select all username from myspace.mymodel where city = 'london'",
+            13,
+        ),
+    ] {
+        let all = super::exec_select_all_only(&global, &format!("{select} limit 100")).unwrap();
+        assert_eq!(all.len(), count);
+        // pages are disjoint and together return all rows in the same order as a single scan
+        let mut paged = vec![];
+        for page in 0.. {
+            let rows = super::exec_select_all_only(
+                &global,
+                &format!("{select} limit 10 offset {}", page * 10),
+            )
+            .unwrap();
+            if rows.is_empty() {
+                break;
+            }
+            paged.extend(rows);
+        }
+        assert_eq!(paged, all);
+    }
+    // ordered indexes return rows in key order and a lookup on all fields of a secondary index returns rows in primary
+    // key order
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel2 limit 2 offset 10"
+        )
+        .unwrap(),
+        vec![intovec!["user10"], intovec!["user11"]]
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where city = 'paris' limit 3 offset 1"
+        )
+        .unwrap(),
+        vec![intovec!["user03"], intovec!["user05"], intovec!["user07"]]
+    );
+}
+
+#[test]
+fn select_all_approx_float() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_all_approx_float");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, ratio: float64) with { primary_index: 'btree' }",
+    )
+    .unwrap();
+    for insert in [
+        // computed by the client as 0.1 + 0.2
+        "insert into myspace.mymodel('a', 0.30000000000000004)",
+        "insert into myspace.mymodel('b', 0.295)",
+        "insert into myspace.mymodel('c', 0.7)",
+    ] {
+        super::_exec_only_insert(&global, insert, |_| {}).unwrap();
+    }
+    // ratio isn't indexed, so the rows are counted with a filter
+    let count = |query: &str| exec_select_aggregate(&global, query).unwrap();
+    assert_eq!(
+        count("select count(*) from myspace.mymodel where ratio = 0.3"),
+        b"\x050\n"
+    );
+    assert_eq!(
+        count("select count(*) from myspace.mymodel where ratio ~= 0.3"),
+        b"\x051\n"
+    );
+    assert_eq!(
+        count("select count(*) from myspace.mymodel where ratio ~= 0.3 within 0.01"),
+        b"\x052\n"
+    );
+}
+
+/// Lex `query` with its `params` encoded just like a client would send them
+fn with_params<T>(query: &str, params: &[Lit], f: impl FnOnce(&[Token]) -> T) -> T {
+    let mut src = query.as_bytes().to_vec();
+    for param in params {
+        encode_param(&mut src, Some(param));
+    }
+    let tokens = lex_secure(&src, query.len()).unwrap();
+    f(&tokens)
+}
+
+#[test]
+fn binary_primary_key() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_binary_primary_key");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(k: binary, v: string)",
+        &[],
+        "select all k from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    // neither of these keys is valid UTF-8, and they only differ after the invalid bytes
+    let (k1, k2): (&[u8], &[u8]) = (b"\xff\x00\xfeone", b"\xff\x00\xfetwo");
+    for (key, value) in [(k1, "first"), (k2, "second")] {
+        with_params(
+            "insert into myspace.mymodel(?, ?)",
+            &[Lit::new_bin(key), Lit::new_string(value.into())],
+            |tok| dml::insert(&global, parse_ast_node_full(&tok[1..]).unwrap()),
+        )
+        .unwrap();
+    }
+    let select = |key: &[u8]| {
+        with_params(
+            "select * from myspace.mymodel where k = ?",
+            &[Lit::new_bin(key)],
+            |tok| {
+                let mut r = vec![];
+                dml::select_custom(&global, parse_ast_node_full(&tok[1..]).unwrap(), |dc| {
+                    r.push(dc.clone())
+                })
+                .map(|_| r)
+            },
+        )
+    };
+    assert_eq!(select(k1).unwrap(), intovec![k1.to_vec(), "first"]);
+    assert_eq!(select(k2).unwrap(), intovec![k2.to_vec(), "second"]);
+    with_params(
+        "delete from myspace.mymodel where k = ?",
+        &[Lit::new_bin(k1)],
+        |tok| dml::delete(&global, parse_ast_node_full(&tok[1..]).unwrap()),
+    )
+    .unwrap();
+    assert_eq!(select(k1).unwrap_err(), QueryError::QExecDmlRowNotFound);
+    assert_eq!(select(k2).unwrap(), intovec![k2.to_vec(), "second"]);
+}
+
+#[test]
+fn select_all_range_ordered_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_range_ordered_index");
+    let ret = super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, password: string) with { primary_index: 'btree' }",
+        &[
+            "insert into myspace.mymodel('sayan', 'password123')",
+            "insert into myspace.mymodel('robot', 'robot123')",
+            "insert into myspace.mymodel('douglas', 'galaxy123')",
+            "insert into myspace.mymodel('hgwells', 'timemachine')",
+            "insert into myspace.mymodel('orwell', '1984')",
+        ],
+        "select all username from myspace.mymodel where username between 'e' and 'robot' limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        ret,
+        vec![intovec!["hgwells"], intovec!["orwell"], intovec!["robot"]]
+    );
+    let ret = super::exec_select_all(
+        &global,
+        "create model myspace.mymodel2(username: string, password: string) with { primary_index: 'btree' }",
+        &[
+            "insert into myspace.mymodel2('sayan', 'password123')",
+            "insert into myspace.mymodel2('robot', 'robot123')",
+            "insert into myspace.mymodel2('hgwells', 'timemachine')",
+            "insert into myspace.mymodel2('orwell', '1984')",
+        ],
+        "select all username from myspace.mymodel2 where username > 'hgwells' and username < 'sayan' limit 1",
+    )
+    .unwrap();
+    assert_eq!(ret, vec![intovec!["orwell"]]);
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where username >= 'e' limit 10"
+        )
+        .unwrap(),
+        r#"{"plan":"range_scan","index":"btree","warnings":[]}"#
+    );
+}
+
+#[test]
+fn select_all_range_hash_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_range_hash_index");
+    let mut ret = super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, password: string)",
+        &[
+            "insert into myspace.mymodel('sayan', 'password123')",
+            "insert into myspace.mymodel('robot', 'robot123')",
+            "insert into myspace.mymodel('douglas', 'galaxy123')",
+            "insert into myspace.mymodel('hgwells', 'timemachine')",
+            "insert into myspace.mymodel('orwell', '1984')",
+        ],
+        "select all username from myspace.mymodel where username >= 'hgwells' and username < 'sayan' limit 100",
+    )
+    .unwrap();
+    ret.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+    assert_eq!(
+        ret,
+        vec![intovec!["hgwells"], intovec!["orwell"], intovec!["robot"]]
+    );
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where username between 'a' and 'b' limit 10"
+        )
+        .unwrap(),
+        r#"{"plan":"filtered_full_scan","index":"hash","warnings":["no ordered index on the primary key; the range filter falls back to a full scan"]}"#
+    );
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel limit 10"
+        )
+        .unwrap(),
+        r#"{"plan":"full_scan","index":"hash","warnings":[]}"#
+    );
+}
+
+#[test]
+fn select_all_range_unindexed_column() {
+    let global = TestGlobal::new_with_driver_id_instant_update(
+        "dml_select_select_all_range_unindexed_column",
+    );
+    assert_eq!(
+        super::exec_select_all(
+            &global,
+            "create model myspace.mymodel(username: string, password: string)",
+            &["insert into myspace.mymodel('sayan', 'password123')"],
+            "select all username from myspace.mymodel where password >= 'a' limit 100",
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlWhereHasUnindexedColumn
+    );
+    assert_eq!(
+        super::exec_select_all(
+            &global,
+            "create model myspace.mymodel2(username: string, password: string)",
+            &[],
+            "select all username from myspace.mymodel2 where username >= 100 limit 100",
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlWhereHasUnindexedColumn
+    );
+}
+
+#[test]
+fn select_all_matches() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_matches");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 20)",
+            "insert into myspace.mymodel('robot', 'paris', 30)",
+            "insert into myspace.mymodel('hgwells', 'london', 40)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let select_sorted = |select| {
+        let mut ret = super::exec_select_all_only(&global, select).unwrap();
+        ret.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+        ret
+    };
+    assert_eq!(
+        select_sorted("select all username from myspace.mymodel where city matches 'on' limit 100"),
+        vec![intovec!["hgwells"], intovec!["sayan"]]
+    );
+    // the filter also applies on top of a key range
+    assert_eq!(
+        select_sorted(
+            "select all username from myspace.mymodel where username = 'sayan' and city matches '^l' limit 100"
+        ),
+        vec![intovec!["sayan"]]
+    );
+    assert_eq!(
+        select_sorted(
+            "select all username from myspace.mymodel where username matches '^[rs]' limit 100"
+        ),
+        vec![intovec!["robot"], intovec!["sayan"]]
+    );
+    // not a string
+    assert_eq!(
+        select_sorted("select all username from myspace.mymodel where age matches '2' limit 100"),
+        Vec::<Vec<Datacell>>::new()
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where email matches 'a' limit 100"
+        )
+        .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+    // bad or oversized patterns
+    let long_pattern = "a".repeat(300);
+    for pattern in ["(abc", "a{1000}{1000}", long_pattern.as_str()] {
+        assert_eq!(
+            super::exec_select_all_only(
+                &global,
+                &format!(
+                    "select all username from myspace.mymodel where city matches '{pattern}' limit 100"
+                )
+            )
+            .unwrap_err(),
+            QueryError::QExecDmlIllegalPattern
+        );
+    }
+}
+
+#[test]
+fn select_all_sample() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_sample");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(id: uint64, even: bool) with { primary_index: 'btree' }",
+        &["insert into myspace.mymodel(0, true)"],
+        "select all id from myspace.mymodel limit 1000",
+    )
+    .unwrap();
+    for id in 1..500u64 {
+        super::exec_insert_only(
+            &global,
+            &format!("insert into myspace.mymodel({id}, {})", id % 2 == 0),
+        )
+        .unwrap();
+    }
+    let ids = |select: &str| -> Vec<u64> {
+        super::exec_select_all_only(&global, select)
+            .unwrap()
+            .into_iter()
+            .map(|row| row[0].uint())
+            .collect()
+    };
+    // distinct matching rows, in key order
+    let sample = ids("select all id from myspace.mymodel where id >= 100 limit 1000 sample 50");
+    assert_eq!(sample.len(), 50);
+    assert!(sample.windows(2).all(|w| w[0] < w[1]));
+    assert!(sample.iter().all(|id| *id >= 100));
+    // the limit caps the sample
+    assert_eq!(
+        ids("select all id from myspace.mymodel limit 10 sample 50").len(),
+        10
+    );
+    // there are fewer rows than the sample size
+    assert_eq!(
+        ids("select all id from myspace.mymodel where id < 20 limit 1000 sample 50"),
+        (0..20).collect::<Vec<_>>()
+    );
+    assert!(ids("select all id from myspace.mymodel limit 1000 sample 0").is_empty());
+    assert_eq!(
+        ids("select all id from myspace.mymodel limit 1000 sample 100 percent").len(),
+        500
+    );
+    let sample = ids("select all id from myspace.mymodel limit 1000 sample 50 percent");
+    assert!((150..350).contains(&sample.len()), "{}", sample.len());
+    // repeated samples differ
+    let first = ids("select all id from myspace.mymodel limit 1000 sample 20");
+    assert!(
+        (0..10).any(|_| ids("select all id from myspace.mymodel limit 1000 sample 20") != first)
+    );
+}
+
+#[test]
+fn select_all_secondary_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_secondary_index");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 20)",
+            "insert into myspace.mymodel('robot', 'paris', 30)",
+            "insert into myspace.mymodel('hgwells', 'london', 40)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap();
+    let select_sorted = |select| {
+        let mut ret = super::exec_select_all_only(&global, select).unwrap();
+        ret.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+        ret
+    };
+    assert_eq!(
+        select_sorted("select all username from myspace.mymodel where city = 'london' limit 100"),
+        vec![intovec!["hgwells"], intovec!["sayan"]]
+    );
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where city = 'london' limit 100"
+        )
+        .unwrap(),
+        r#"{"plan":"index_lookup","index":"bycity","warnings":[]}"#
+    );
+    // the index is maintained by DML
+    super::exec_insert_only(
+        &global,
+        "insert into myspace.mymodel('orwell', 'london', 50)",
+    )
+    .unwrap();
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set city = 'paris' where username = 'sayan'",
+    )
+    .unwrap();
+    super::_exec_delete_only(
+        &global,
+        "delete from myspace.mymodel where username = 'hgwells'",
+        "hgwells",
+    )
+    .unwrap();
+    assert_eq!(
+        select_sorted("select all username from myspace.mymodel where city = 'london' limit 100"),
+        vec![intovec!["orwell"]]
+    );
+    assert_eq!(
+        select_sorted("select all username from myspace.mymodel where city = 'paris' limit 100"),
+        vec![intovec!["robot"], intovec!["sayan"]]
+    );
+    // no index on this field
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where age = 50 limit 100"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlWhereHasUnindexedColumn
+    );
+}
+
+#[test]
+fn select_all_partial_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_partial_index");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, status: string)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 'active')",
+            "insert into myspace.mymodel('robot', 'london', 'banned')",
+            "insert into myspace.mymodel('hgwells', 'paris', 'active')",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    exec_create_index(
+        &global,
+        "create index activebycity on myspace.mymodel(city) where status = 'active'",
+    )
+    .unwrap();
+    let select_sorted = |select| {
+        let mut ret = super::exec_select_all_only(&global, select).unwrap();
+        ret.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+        ret
+    };
+    assert_eq!(
+        select_sorted(
+            "select all username from myspace.mymodel where city = 'london' and status = 'active' limit 100"
+        ),
+        vec![intovec!["sayan"]]
+    );
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where city = 'london' and status = 'active' limit 100"
+        )
+        .unwrap(),
+        r#"{"plan":"index_lookup","index":"activebycity","partial":true,"warnings":[]}"#
+    );
+    // the where clause doesn't imply the predicate, so the index can't be used
+    for select in [
+        "select all username from myspace.mymodel where city = 'london' limit 100",
+        "select all username from myspace.mymodel where city = 'london' and status = 'banned' limit 100",
+    ] {
+        assert_eq!(
+            super::exec_select_all_only(&global, select).unwrap_err(),
+            QueryError::QExecDmlWhereHasUnindexedColumn
+        );
+    }
+    // rows move in and out of the index as they start or stop satisfying the predicate
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set status = 'active' where username = 'robot'",
+    )
+    .unwrap();
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set status = 'banned' where username = 'sayan'",
+    )
+    .unwrap();
+    super::exec_insert_only(
+        &global,
+        "insert into myspace.mymodel('orwell', 'london', 'banned')",
+    )
+    .unwrap();
+    assert_eq!(
+        select_sorted(
+            "select all username from myspace.mymodel where city = 'london' and status = 'active' limit 100"
+        ),
+        vec![intovec!["robot"]]
+    );
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set city = 'paris' where username = 'robot'",
+    )
+    .unwrap();
+    assert_eq!(
+        select_sorted(
+            "select all username from myspace.mymodel where city = 'paris' and status = 'active' limit 100"
+        ),
+        vec![intovec!["hgwells"], intovec!["robot"]]
+    );
+    assert!(select_sorted(
+        "select all username from myspace.mymodel where city = 'london' and status = 'active' limit 100"
+    )
+    .is_empty());
+}
+
+#[test]
+fn select_all_composite_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_composite_index");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, tenant_id: uint64, created_at: uint64)",
+        &[
+            "insert into myspace.mymodel('a', 1, 300)",
+            "insert into myspace.mymodel('b', 1, 100)",
+            "insert into myspace.mymodel('c', 2, 100)",
+            "insert into myspace.mymodel('d', 1, 200)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    exec_create_index(
+        &global,
+        "create index bytenant on myspace.mymodel(tenant_id)",
+    )
+    .unwrap();
+    exec_create_index(
+        &global,
+        "create index bytenantcreated on myspace.mymodel(tenant_id, created_at)",
+    )
+    .unwrap();
+    let explain = |select: &str| {
+        super::exec_explain_select_all(&global, &format!("explain {select}")).unwrap()
+    };
+    // the index with the longest usable prefix is picked
+    assert_eq!(
+        explain("select all * from myspace.mymodel where tenant_id = 1 limit 100"),
+        r#"{"plan":"index_lookup","index":"bytenant","warnings":[]}"#
+    );
+    assert_eq!(
+        explain(
+            "select all * from myspace.mymodel where tenant_id = 1 and created_at = 100 limit 100"
+        ),
+        r#"{"plan":"index_only_lookup","index":"bytenantcreated","prefix":2,"warnings":[]}"#
+    );
+    // the remaining indexed fields can be filtered on (and rows are returned in their order)
+    assert_eq!(
+        explain(
+            "select all * from myspace.mymodel where tenant_id = 1 and created_at > 150 limit 100"
+        ),
+        r#"{"plan":"index_lookup","index":"bytenantcreated","prefix":1,"warnings":[]}"#
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where tenant_id = 1 limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["a"], intovec!["b"], intovec!["d"]]
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where tenant_id = 1 and created_at = 100 limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["b"]]
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where tenant_id = 1 and created_at > 150 limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["d"], intovec!["a"]]
+    );
+    // created_at isn't a prefix of any index
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where created_at = 100 limit 100"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlWhereHasUnindexedColumn
+    );
+    // updating the second field moves the row within the index
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set created_at = 100 where username = 'd'",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where tenant_id = 1 and created_at = 100 limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["b"], intovec!["d"]]
+    );
+}
+
+#[test]
+fn select_all_collated_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_collated_index");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, tenant_id: uint64, name: string)",
+        &[
+            "insert into myspace.mymodel('u1', 1, 'bob')",
+            "insert into myspace.mymodel('u2', 1, 'Alice')",
+            "insert into myspace.mymodel('u3', 1, 'carol')",
+            "insert into myspace.mymodel('u4', 1, 'Bob')",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let create_index = |collation: &str| {
+        exec_create_index(
+            &global,
+            &format!(
+                "create index bytenantname on myspace.mymodel(tenant_id, name) with {{ collation: '{collation}' }}"
+            ),
+        )
+        .unwrap();
+    };
+    let select = |select: &str| super::exec_select_all_only(&global, select).unwrap();
+    let explain = |select: &str| {
+        super::exec_explain_select_all(&global, &format!("explain {select}")).unwrap()
+    };
+    // rows are returned in the order of the names, as compared by the collation
+    create_index("binary");
+    assert_eq!(
+        select("select all username from myspace.mymodel where tenant_id = 1 limit 100"),
+        vec![
+            intovec!["u2"],
+            intovec!["u4"],
+            intovec!["u1"],
+            intovec!["u3"]
+        ]
+    );
+    assert_eq!(
+        explain(
+            "select all username from myspace.mymodel where tenant_id = 1 and name = 'bob' limit 100"
+        ),
+        r#"{"plan":"index_only_lookup","index":"bytenantname","prefix":2,"warnings":[]}"#
+    );
+    exec_drop_index(&global, "drop index bytenantname on myspace.mymodel").unwrap();
+    create_index("nocase");
+    assert_eq!(
+        select("select all username from myspace.mymodel where tenant_id = 1 limit 100"),
+        vec![
+            intovec!["u2"],
+            intovec!["u1"],
+            intovec!["u4"],
+            intovec!["u3"]
+        ]
+    );
+    // but rows only match if their values are equal, so the rows have to be read to tell the spellings apart
+    assert_eq!(
+        select(
+            "select all username from myspace.mymodel where tenant_id = 1 and name = 'bob' limit 100"
+        ),
+        vec![intovec!["u1"]]
+    );
+    assert!(select(
+        "select all username from myspace.mymodel where tenant_id = 1 and name = 'BOB' limit 100"
+    )
+    .is_empty());
+    assert_eq!(
+        explain(
+            "select all username from myspace.mymodel where tenant_id = 1 and name = 'bob' limit 100"
+        ),
+        r#"{"plan":"index_lookup","index":"bytenantname","prefix":2,"warnings":[]}"#
+    );
+}
+
+#[test]
+fn select_all_index_usage() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_index_usage");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 20)",
+            "insert into myspace.mymodel('robot', 'paris', 30)",
+            "insert into myspace.mymodel('hgwells', 'london', 40)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    exec_create_index(&global, "create index bycity on myspace.mymodel(city)").unwrap();
+    exec_create_index(&global, "create index byage on myspace.mymodel(age)").unwrap();
+    // building an index doesn't count as a write
+    assert_eq!(
+        describe_indexes(&global),
+        concat!(
+            r#"[{"model":"myspace.mymodel","name":"bycity","fields":["city"],"ready":true,"entries":3,"reads":0,"writes":0,"last_read":null,"unused":true},"#,
+            r#"{"model":"myspace.mymodel","name":"byage","fields":["age"],"ready":true,"entries":3,"reads":0,"writes":0,"last_read":null,"unused":true}]"#
+        )
+    );
+    super::exec_insert_only(&global, "insert into myspace.mymodel('kant', 'berlin', 50)").unwrap();
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where city = 'london' limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["hgwells"], intovec!["sayan"]]
+    );
+    let report = describe_indexes(&global);
+    let (bycity, byage) = report.split_once("},{").unwrap();
+    assert!(bycity.starts_with(
+        r#"[{"model":"myspace.mymodel","name":"bycity","fields":["city"],"ready":true,"entries":4,"reads":1,"writes":1,"last_read":"#
+    ));
+    assert!(bycity.ends_with(r#","unused":false"#));
+    assert!(!bycity.contains("null"));
+    assert_eq!(
+        byage,
+        r#""model":"myspace.mymodel","name":"byage","fields":["age"],"ready":true,"entries":4,"reads":0,"writes":1,"last_read":null,"unused":true}]"#
+    );
+}
+
+#[test]
+fn select_hot_keys() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_hot_keys");
+    super::exec_select(
+        &global,
+        "create model myspace.mymodel(username: string, password: string)",
+        "insert into myspace.mymodel('sayan', 'pass123')",
+        "select * from myspace.mymodel where username = 'sayan'",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.mymodel('robot', 'beep')").unwrap();
+    // one in every 16 accesses is sampled
+    for _ in 1..32 {
+        super::exec_select_only(
+            &global,
+            "select * from myspace.mymodel where username = 'sayan'",
+        )
+        .unwrap();
+    }
+    for _ in 0..16 {
+        super::exec_select_only(
+            &global,
+            "select * from myspace.mymodel where username = 'robot'",
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        describe_hot_keys(&global),
+        r#"[{"model":"myspace.mymodel","accesses":48,"sample_every":16,"keys":[{"key":"sayan","estimate":32},{"key":"robot","estimate":16}]}]"#
+    );
+}
+
+#[test]
+fn select_all_covering_index() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_covering_index");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8, bio: string)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 20, 'hello')",
+            "insert into myspace.mymodel('robot', 'paris', 30, 'beep')",
+            "insert into myspace.mymodel('hgwells', 'london', 40, 'time')",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    exec_create_index(
+        &global,
+        "create index bycity on myspace.mymodel(city) include (age)",
+    )
+    .unwrap();
+    let explain = |select: &str| {
+        super::exec_explain_select_all(&global, &format!("explain {select}")).unwrap()
+    };
+    // everything that's read is in the index
+    for select in [
+        "select all username, city, age from myspace.mymodel where city = 'london' limit 100",
+        "select all username from myspace.mymodel where city = 'london' and username matches '^h' limit 100",
+    ] {
+        assert_eq!(
+            explain(select),
+            r#"{"plan":"index_only_lookup","index":"bycity","warnings":[]}"#
+        );
+    }
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username, city, age from myspace.mymodel where city = 'london' limit 100"
+        )
+        .unwrap(),
+        vec![
+            intovec!["hgwells", "london", 40u64],
+            intovec!["sayan", "london", 20u64]
+        ]
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where city = 'london' and username matches '^h' limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["hgwells"]]
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where city = 'london' limit 1 offset 1"
+        )
+        .unwrap(),
+        vec![intovec!["sayan"]]
+    );
+    // bio isn't in the index, so the rows are read
+    for select in [
+        "select all * from myspace.mymodel where city = 'london' limit 100",
+        "select all username, bio from myspace.mymodel where city = 'london' limit 100",
+        "select all username from myspace.mymodel where city = 'london' and bio matches '^t' limit 100",
+    ] {
+        assert_eq!(
+            explain(select),
+            r#"{"plan":"index_lookup","index":"bycity","warnings":[]}"#
+        );
+    }
+    // the included fields are maintained by DML
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set age += 5 where username = 'sayan'",
+    )
+    .unwrap();
+    super::_exec_delete_only(
+        &global,
+        "delete from myspace.mymodel where username = 'hgwells'",
+        "hgwells",
+    )
+    .unwrap();
+    super::exec_insert_only(
+        &global,
+        "insert into myspace.mymodel('orwell', 'london', 50, 'farm')",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username, age from myspace.mymodel where city = 'london' limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["orwell", 50u64], intovec!["sayan", 25u64]]
+    );
+}
+
+#[test]
+fn select_all_with_total() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_with_total");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(id: uint64, name: string) with { primary_index: 'btree', soft_delete: true }",
+        &["insert into myspace.mymodel(0, 'sayan')"],
+        "select all id from myspace.mymodel limit 1000",
+    )
+    .unwrap();
+    for id in 1..500u64 {
+        super::exec_insert_only(
+            &global,
+            &format!("insert into myspace.mymodel({id}, 'sayan')"),
+        )
+        .unwrap();
+    }
+    super::exec_delete_only(&global, "delete from myspace.mymodel where id = 0").unwrap();
+    let page = |select: &str| {
+        let (rows, total) = super::exec_select_all_with_total_only(&global, select).unwrap();
+        let ids: Vec<u64> = rows.into_iter().map(|row| row[0].uint()).collect();
+        (ids, total.map(|total| (total.rows(), total.exact())))
+    };
+    // the page along with the number of matching rows
+    assert_eq!(
+        page("select all id from myspace.mymodel where id >= 100 limit 5 offset 10 with total"),
+        ((110..115).collect(), Some((400, true)))
+    );
+    // soft deleted rows are only counted if they're selected
+    assert_eq!(
+        page("select all id from myspace.mymodel limit 2 with total"),
+        (vec![1, 2], Some((499, true)))
+    );
+    assert_eq!(
+        page("select all id from myspace.mymodel limit 2 with deleted with total"),
+        (vec![0, 1], Some((500, true)))
+    );
+    // past the last page
+    assert_eq!(
+        page("select all id from myspace.mymodel limit 10 offset 1000 with total"),
+        (vec![], Some((499, true)))
+    );
+    assert_eq!(
+        page("select all id from myspace.mymodel limit 2"),
+        (vec![1, 2], None)
+    );
+}
+
+#[test]
+fn select_all_histogram_estimate() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_histogram_estimate");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(id: uint64, age: uint8) with { primary_index: 'btree', histograms: true }",
+        &["insert into myspace.mymodel(0, 0)"],
+        "select all id from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    for id in 1..100 {
+        super::exec_insert_only(
+            &global,
+            &format!("insert into myspace.mymodel({id}, {})", id % 4),
+        )
+        .unwrap();
+    }
+    exec_create_index(&global, "create index byage on myspace.mymodel(age)").unwrap();
+    // not built yet, so there's no estimate
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where age = 1 limit 100"
+        )
+        .unwrap(),
+        r#"{"plan":"index_only_lookup","index":"byage","warnings":[]}"#
+    );
+    {
+        let models = global.state().namespace().idx_models().read();
+        let mdl = models
+            .get(&EntityIDRef::new("myspace", "mymodel"))
+            .unwrap()
+            .data();
+        let stats = mdl.stats().unwrap();
+        assert!(stats.is_stale(100, 1));
+        stats.refresh(mdl, 1);
+        assert!(!stats.is_stale(105, 2));
+        assert!(stats.is_stale(111, 2));
+    }
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where age = 1 limit 100"
+        )
+        .unwrap(),
+        r#"{"plan":"index_only_lookup","index":"byage","estimate":{"selectivity":0.25,"rows":25},"warnings":[]}"#
+    );
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where id >= 0 limit 100"
+        )
+        .unwrap(),
+        r#"{"plan":"range_scan","index":"btree","estimate":{"selectivity":1,"rows":100},"warnings":[]}"#
+    );
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where id > 1000 limit 100"
+        )
+        .unwrap(),
+        r#"{"plan":"range_scan","index":"btree","estimate":{"selectivity":0,"rows":0},"warnings":[]}"#
+    );
+}
+
+#[test]
+fn select_all_secondary_index_building() {
+    let global = TestGlobal::new_with_driver_id_instant_update(
+        "dml_select_select_all_secondary_index_building",
+    );
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london')",
+            "insert into myspace.mymodel('robot', 'paris')",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    // as if restored on boot, but not built yet
+    assert!(global
+        .state()
+        .namespace()
+        .idx_models()
+        .write()
+        .get_mut(&EntityIDRef::new("myspace", "mymodel"))
+        .unwrap()
+        .data_mut()
+        .add_secondary_index(
+            "bycity".into(),
+            Box::new(["city".into()]),
+            Box::default(),
+            None,
+            Collation::Binary
+        ));
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel where city = 'paris' limit 100"
+        )
+        .unwrap(),
+        vec![intovec!["robot"]]
+    );
+    assert_eq!(
+        super::exec_explain_select_all(
+            &global,
+            "explain select all * from myspace.mymodel where city = 'paris' limit 100"
+        )
+        .unwrap(),
+        r#"{"plan":"filtered_full_scan","index":"bycity","warnings":["the secondary index is still being built; the filter falls back to a full scan"]}"#
+    );
+}
+
+#[test]
+fn select_keys() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_keys");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('sayan', 20)",
+            "insert into myspace.mymodel('robot', 30)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let select = |query: &str| {
+        let tok = lex_insecure(query.as_bytes()).unwrap();
+        dml::select_resp(&global, parse_ast_node_full(&tok[1..]).unwrap())
+    };
+    // rows are returned in request order, with missing keys as nulls
+    assert_eq!(
+        select("select age from myspace.mymodel where username in ('robot', 'orwell', 'sayan')")
+            .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 3,
+            data: b"1\n\x0230\n1\n\x001\n\x0220\n".to_vec(),
+        }
+    );
+    assert_eq!(
+        select("select * from myspace.mymodel where username in ('orwell', 'sayan') skip missing")
+            .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"2\n\x0D5\nsayan\x0220\n".to_vec(),
+        }
+    );
+    // only the primary key can be used
+    assert_eq!(
+        select("select * from myspace.mymodel where age in (20, 30)").unwrap_err(),
+        QueryError::QExecDmlWhereHasUnindexedColumn
+    );
+    let tok =
+        lex_insecure(b"explain select * from myspace.mymodel where username in ('sayan', 'robot')")
+            .unwrap();
+    match dml::explain_select_resp(&global, parse_ast_node_full(&tok[2..]).unwrap()).unwrap() {
+        Response::Serialized { data, .. } => assert_eq!(
+            String::from_utf8(data).unwrap(),
+            r#"{"plan":"multi_point_lookup","index":"hash","warnings":[]}"#
+        ),
+        _ => panic!("expected a serialized response"),
+    }
+}
+
+#[test]
+fn select_keys_in_sessions() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_keys_in_sessions");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('sayan', 20)",
+            "insert into myspace.mymodel('robot', 30)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let select = |query: &str| {
+        let tok = lex_insecure(query.as_bytes()).unwrap();
+        dml::select_resp(&global, parse_ast_node_full(&tok[1..]).unwrap())
+    };
+    let mut settings = SessionSettings::new();
+    let snapshot = SessionSet::new("isolation".into(), SetValue::Name("snapshot".into()));
+    settings.set(&snapshot).unwrap();
+    {
+        // the rows are locked together (a key that is repeated is locked once)
+        let _session = session::enter(&settings);
+        assert_eq!(
+            select(
+                "select age from myspace.mymodel where username in ('robot', 'orwell', 'robot')"
+            )
+            .unwrap(),
+            Response::Serialized {
+                ty: ResponseType::MultiRow,
+                size: 3,
+                data: b"1\n\x0230\n1\n\x001\n\x0230\n".to_vec(),
+            }
+        );
+    }
+    // and unlocked once the statement is done
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set age = 31 where username = 'robot'",
+    )
+    .unwrap();
+    let timeout = SessionSet::new("statement_timeout".into(), SetValue::Lit(Lit::new_uint(1)));
+    settings.set(&timeout).unwrap();
+    let _session = session::enter(&settings);
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    assert_eq!(
+        select("select age from myspace.mymodel where username in ('sayan', 'robot')").unwrap_err(),
+        QueryError::QExecStatementTimeout
+    );
+}
+
+/// The lookups of the fetch, with owned keys (like the tasks that run them get)
+fn fetch_items<'a>(fetch: &FetchStatement<'a>) -> Vec<(EntityIDRef<'a>, Datacell)> {
+    fetch
+        .items()
+        .iter()
+        .map(|(entity, key)| (*entity, Datacell::from(key.clone())))
+        .collect()
+}
+
+#[test]
+fn fetch() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_fetch");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, age: uint8)",
+    )
+    .unwrap();
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.posts(id: uint64, title: string)",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.users('sayan', 20)").unwrap();
+    super::exec_insert_only(&global, "insert into myspace.posts(10, 'hello')").unwrap();
+    let tok = lex_insecure(
+        b"fetch myspace.users('sayan'), myspace.posts(10), myspace.users('robot'), \
+        myspace.posts('10'), myspace.comments(1)",
+    )
+    .unwrap();
+    let fetch: FetchStatement = parse_ast_node_full(&tok[1..]).unwrap();
+    // one row per lookup, in request order, with the status as the first column
+    assert_eq!(
+        dml::fetch(&global, &fetch_items(&fetch)),
+        [
+            &b"3\n\x050\n\x0D5\nsayan\x0220\n"[..],
+            b"3\n\x050\n\x0510\n\x0D5\nhello",
+            b"1\n\x05111\n",
+            b"1\n\x05110\n",
+            b"1\n\x05100\n",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn fetch_across_spaces() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_fetch_across_spaces");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, age: uint8)",
+    )
+    .unwrap();
+    global
+        .state()
+        .namespace()
+        .create_empty_test_space("otherspace");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model otherspace.posts(id: uint64, title: string)",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.users('sayan', 20)").unwrap();
+    super::exec_insert_only(&global, "insert into otherspace.posts(10, 'hello')").unwrap();
+    // the rows of users have a pending schema change, which is applied when they're locked
+    let tok = lex_insecure(b"alter model myspace.users add bio { type: string, nullable: true }")
+        .unwrap();
+    ModelData::transactional_exec_alter(&global, parse_ast_node_full(&tok[2..]).unwrap()).unwrap();
+    // rows are locked in (space, model, key) order, and a row that is looked up twice is locked once
+    let tok = lex_insecure(
+        b"fetch otherspace.posts(10), myspace.users('sayan'), otherspace.posts(10), myspace.users('sayan')",
+    )
+    .unwrap();
+    let fetch: FetchStatement = parse_ast_node_full(&tok[1..]).unwrap();
+    let user = &b"4\n\x050\n\x0D5\nsayan\x0220\n\x00"[..];
+    let post = &b"3\n\x050\n\x0510\n\x0D5\nhello"[..];
+    assert_eq!(
+        dml::fetch(&global, &fetch_items(&fetch)),
+        [post, user, post, user].concat()
+    );
+}
+
+#[test]
+fn select_memory_limit() {
+    let mut global = TestGlobal::new_with_driver_id_instant_update("dml_select_memory_limit");
+    global.set_query_memory_limit(4096);
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, bio: string)",
+    )
+    .unwrap();
+    for i in 0..10 {
+        super::exec_insert_only(
+            &global,
+            &format!(
+                "insert into myspace.mymodel('user{i}', '{}')",
+                "x".repeat(1000)
+            ),
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        super::exec_select_all_only(&global, "select all * from myspace.mymodel limit 100")
+            .unwrap_err(),
+        QueryError::QExecQueryMemoryLimitExceeded
+    );
+    // smaller results are fine
+    assert_eq!(
+        super::exec_select_all_only(&global, "select all * from myspace.mymodel limit 2")
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel limit 100"
+        )
+        .unwrap()
+        .len(),
+        10
+    );
+    let select = |query: &str| {
+        let tok = lex_insecure(query.as_bytes()).unwrap();
+        dml::select_resp(&global, parse_ast_node_full(&tok[1..]).unwrap())
+    };
+    assert_eq!(
+        select("select * from myspace.mymodel where username in ('user0', 'user1', 'user2', 'user3', 'user4')")
+            .unwrap_err(),
+        QueryError::QExecQueryMemoryLimitExceeded
+    );
+    assert!(select("select * from myspace.mymodel where username in ('user0', 'user1')").is_ok());
+}
+
+#[test]
+fn select_soft_limits() {
+    let mut global = TestGlobal::new_with_driver_id_instant_update("dml_select_soft_limits");
+    global.set_query_memory_limit(4096);
+    global.set_memory_watermarks(1000, 500);
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, bio: string)",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', 'hello')").unwrap();
+    super::exec_insert_only(
+        &global,
+        &format!(
+            "insert into myspace.mymodel('robot', '{}')",
+            "x".repeat(3500)
+        ),
+    )
+    .unwrap();
+    let codes = || -> Vec<NoticeCode> { Notice::take_all().iter().map(Notice::code).collect() };
+    let select_all = |name: &str| {
+        super::exec_select_all_only(
+            &global,
+            &format!("select all * from myspace.mymodel where username = '{name}' limit 1"),
+        )
+    };
+    Notice::clear();
+    select_all("sayan").unwrap();
+    assert!(codes().is_empty());
+    // past 80% of the query memory limit, but still within it
+    select_all("robot").unwrap();
+    assert_eq!(codes(), [NoticeCode::SoftLimitReached]);
+    // past 80% of the high watermark, but still below it
+    global.set_memory_usage(850);
+    select_all("sayan").unwrap();
+    assert_eq!(codes(), [NoticeCode::SoftLimitReached]);
+}
+
+#[test]
+fn select_all_memory_pressure() {
+    let mut global = TestGlobal::new_with_driver_id_instant_update("dml_select_memory_pressure");
+    global.set_memory_watermarks(1000, 500);
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint8)",
+        &[
+            "insert into myspace.mymodel('sayan', 20)",
+            "insert into myspace.mymodel('robot', 30)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let select_all = || {
+        super::exec_select_all_only(
+            &global,
+            "select all username from myspace.mymodel limit 100",
+        )
+    };
+    global.set_memory_usage(1000);
+    assert_eq!(select_all().unwrap_err(), QueryError::SysMemoryPressure);
+    assert_eq!(
+        super::exec_delete_only(&global, "delete from myspace.mymodel where age > 10").unwrap_err(),
+        QueryError::SysMemoryPressure
+    );
+    // point lookups and point mutations are still admitted
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select username from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec!["sayan"]
+    );
+    super::exec_insert_only(&global, "insert into myspace.mymodel('hgwells', 40)").unwrap();
+    // expensive queries are only admitted again once usage drops to the low watermark
+    global.set_memory_usage(700);
+    assert_eq!(select_all().unwrap_err(), QueryError::SysMemoryPressure);
+    assert!(global.memory_watermark().under_pressure());
+    assert_eq!(global.memory_watermark().rejected(), 3);
+    global.set_memory_usage(500);
+    assert_eq!(select_all().unwrap().len(), 3);
+    global.set_memory_usage(700);
+    assert_eq!(select_all().unwrap().len(), 3);
+    assert!(!global.memory_watermark().under_pressure());
+}
+
+#[test]
+fn select_as_of() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_as_of");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint64) with { history: true, history_retention: 3600 }",
+    )
+    .unwrap();
+    let now = os::get_epoch_time_secs();
+    // pretend that the row was created 100s ago and deleted 50s ago
+    global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+            let history = mdl.history().unwrap();
+            let key = PrimaryIndexKey::try_from_dc(Datacell::from("sayan")).unwrap();
+            history.record(&key, None, now - 100);
+            history.record(
+                &key,
+                Some(Box::new([("age".into(), Datacell::new_uint_default(20))])),
+                now - 50,
+            );
+            Ok(())
+        })
+        .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', 30)").unwrap();
+    let select_as_of = |at: u64| {
+        super::exec_select_only(
+            &global,
+            &format!("select * from myspace.mymodel where username = 'sayan' as of {at}"),
+        )
+    };
+    assert_eq!(
+        select_as_of(now - 101).unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+    assert_eq!(select_as_of(now - 60).unwrap(), intovec!["sayan", 20u64]);
+    assert_eq!(
+        select_as_of(now - 10).unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+    assert_eq!(select_as_of(now + 60).unwrap(), intovec!["sayan", 30u64]);
+    // multi-get
+    let query = format!(
+        "select age from myspace.mymodel where username in ('sayan', 'robot') as of {}",
+        now - 60
+    );
+    let tok = lex_insecure(query.as_bytes()).unwrap();
+    assert_eq!(
+        dml::select_resp(&global, parse_ast_node_full(&tok[1..]).unwrap()).unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 2,
+            data: b"1\n\x0520\n1\n\x00".to_vec(),
+        }
+    );
+    // outside the retention window
+    assert_eq!(
+        select_as_of(now - 3601).unwrap_err(),
+        QueryError::QExecDmlHistoryUnavailable
+    );
+}
+
+#[test]
+fn select_as_of_records_changes() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_select_as_of_records_changes");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint64) with { history: true }",
+    )
+    .unwrap();
+    let before = os::get_epoch_time_secs() - 1;
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', 20)").unwrap();
+    super::exec_dml_resp(
+        &global,
+        "update myspace.mymodel set age += 1 where username = 'sayan'",
+    )
+    .unwrap();
+    super::exec_delete_only(
+        &global,
+        "delete from myspace.mymodel where username = 'sayan'",
+    )
+    .unwrap();
+    let history_len = || {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                Ok(mdl.history().unwrap().version_count())
+            })
+            .unwrap()
+    };
+    // the insert, the update and the delete
+    assert_eq!(history_len(), 3);
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            &format!("select * from myspace.mymodel where username = 'sayan' as of {before}")
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlRowNotFound
+    );
+    // versions are discarded once they're outside the retention window
+    global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+            Ok(mdl.history().unwrap().prune(before + 1 + 24 * 60 * 60))
+        })
+        .unwrap();
+    assert_eq!(history_len(), 0);
+}
+
+#[test]
+fn result_cache_invalidated_by_writes() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_result_cache");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, age: uint64) with { result_cache: true, result_cache_ttl: 10 }",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', 20)").unwrap();
+    let now = os::get_epoch_time_secs();
+    let with_cache = |f: &dyn Fn(&ModelData, CacheGeneration)| {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                Ok(f(mdl, CacheGeneration::of(mdl)))
+            })
+            .unwrap()
+    };
+    let generation = global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+            Ok(CacheGeneration::of(mdl))
+        })
+        .unwrap();
+    with_cache(&|mdl, current| {
+        let cache = mdl.result_cache().unwrap();
+        assert_eq!(cache.get("q", current, now), None);
+        cache.put(mdl, "q".into(), current, now, &Response::Bool(true));
+        assert_eq!(cache.get("q", current, now), Some(Response::Bool(true)));
+        // entries expire after the ttl
+        assert_eq!(cache.get("q", current, now + 10), None);
+    });
+    super::exec_insert_only(&global, "insert into myspace.mymodel('robot', 30)").unwrap();
+    with_cache(&|mdl, current| {
+        let cache = mdl.result_cache().unwrap();
+        assert_ne!(generation, current);
+        assert_eq!(cache.get("q", current, now), None);
+        // a response computed before the write is never cached
+        cache.put(mdl, "q".into(), generation, now, &Response::Bool(false));
+        assert_eq!(cache.get("q", current, now), None);
+        cache.put(mdl, "q".into(), current, now, &Response::Bool(false));
+        assert_eq!(cache.get("q", current, now), Some(Response::Bool(false)));
+        assert_eq!(
+            cache.describe(),
+            "{\"entries\":1,\"bytes\":1,\"hits\":2,\"misses\":4}"
+        );
+    });
+}
+
+#[test]
+fn select_as_of_without_history() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_as_of_without_history");
+    assert_eq!(
+        super::exec_select(
+            &global,
+            "create model myspace.mymodel(username: string, password: string)",
+            "insert into myspace.mymodel('sayan', 'pass123')",
+            &format!(
+                "select * from myspace.mymodel where username = 'sayan' as of {}",
+                os::get_epoch_time_secs()
+            ),
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlHistoryUnavailable
+    );
+}
+
+fn exec_select_aggregate(global: &impl GlobalInstanceLike, query: &str) -> QueryResult<Vec<u8>> {
+    let tok = lex_insecure(query.as_bytes()).unwrap();
+    let select = parse_ast_node_full(&tok[1..]).unwrap();
+    match dml::select_aggregate_resp(global, select)? {
+        Response::Serialized {
+            ty: ResponseType::Row,
+            data,
+            ..
+        } => Ok(data),
+        _ => panic!("expected a row"),
+    }
+}
+
+#[test]
+fn select_aggregate() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_aggregate");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, city: string, age: uint8)",
+    )
+    .unwrap();
+    let cities = [("ccu", 50), ("blr", 30), ("del", 15), ("bom", 5)];
+    let mut i = 0;
+    for (city, count) in cities {
+        for _ in 0..count {
+            super::_exec_only_insert(
+                &global,
+                &format!(
+                    "insert into myspace.users('user{i}', '{city}', {})",
+                    i % 100
+                ),
+                |_| {},
+            )
+            .unwrap();
+            i += 1;
+        }
+    }
+    assert_eq!(
+        exec_select_aggregate(
+            &global,
+            "select count(distinct city), topk(city, 2) from myspace.users"
+        )
+        .unwrap(),
+        b"\x054\n\x0e2\n\x0e2\n\x0d3\nccu\x0550\n\x0e2\n\x0d3\nblr\x0530\n"
+    );
+    // with a filter (the last 20 users are from del and bom)
+    assert_eq!(
+        exec_select_aggregate(
+            &global,
+            "select count(distinct city), topk(city, 5) from myspace.users where age >= 80"
+        )
+        .unwrap(),
+        b"\x052\n\x0e2\n\x0e2\n\x0d3\ndel\x0515\n\x0e2\n\x0d3\nbom\x055\n"
+    );
+    // the primary key can be aggregated too
+    assert_eq!(
+        exec_select_aggregate(
+            &global,
+            "select count(distinct username) from myspace.users"
+        )
+        .unwrap(),
+        b"\x05100\n"
+    );
+    assert_eq!(
+        exec_select_aggregate(&global, "select count(distinct pincode) from myspace.users")
+            .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+}
+
+#[test]
+fn select_aggregate_group_by() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_aggregate_group_by");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, city: string, age: uint8)",
+    )
+    .unwrap();
+    for (username, city, age) in [
+        ("sayan", "ccu", 20),
+        ("elana", "blr", 30),
+        ("sneha", "ccu", 25),
+        ("john", "del", 10),
+        ("jane", "blr", 30),
+    ] {
+        super::_exec_only_insert(
+            &global,
+            &format!("insert into myspace.users('{username}', '{city}', {age})"),
+            |_| {},
+        )
+        .unwrap();
+    }
+    let tok = lex_insecure(
+        b"select count(*), count(distinct age) from myspace.users where age > 18 group by city",
+    )
+    .unwrap();
+    let select = parse_ast_node_full(&tok[1..]).unwrap();
+    let Response::Serialized { ty, size, data } =
+        dml::select_aggregate_resp(&global, select).unwrap()
+    else {
+        panic!("expected rows")
+    };
+    assert_eq!(ty, ResponseType::MultiRow);
+    assert_eq!(size, 2);
+    // groups are returned in the order they were first seen, which depends on the index
+    let ccu = b"3\n\x0d3\nccu\x052\n\x052\n".as_slice();
+    let blr = b"3\n\x0d3\nblr\x052\n\x051\n".as_slice();
+    assert!(data == [ccu, blr].concat() || data == [blr, ccu].concat());
+    // no rows matched
+    let tok =
+        lex_insecure(b"select count(*) from myspace.users where age > 100 group by city").unwrap();
+    let select = parse_ast_node_full(&tok[1..]).unwrap();
+    assert_eq!(
+        dml::select_aggregate_resp(&global, select).unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 0,
+            data: vec![]
+        }
+    );
+    // without a group, there's always a row
+    assert_eq!(
+        exec_select_aggregate(
+            &global,
+            "select count(*) from myspace.users where age > 100"
+        )
+        .unwrap(),
+        b"\x050\n"
+    );
+    let tok = lex_insecure(b"select count(*) from myspace.users group by pincode").unwrap();
+    let select = parse_ast_node_full(&tok[1..]).unwrap();
+    assert_eq!(
+        dml::select_aggregate_resp(&global, select).unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+}
+
+#[test]
+fn select_aggregate_having() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_aggregate_having");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, city: string, age: uint8)",
+    )
+    .unwrap();
+    for (username, city, age) in [
+        ("sayan", "ccu", 20),
+        ("elana", "blr", 30),
+        ("sneha", "ccu", 25),
+        ("john", "del", 10),
+        ("jane", "blr", 30),
+        ("joe", "ccu", 40),
+    ] {
+        super::_exec_only_insert(
+            &global,
+            &format!("insert into myspace.users('{username}', '{city}', {age})"),
+            |_| {},
+        )
+        .unwrap();
+    }
+    let exec = |query: &str| {
+        let tok = lex_insecure(query.as_bytes()).unwrap();
+        let select = parse_ast_node_full(&tok[1..]).unwrap();
+        dml::select_aggregate_resp(&global, select).unwrap()
+    };
+    // only ccu has more than two users
+    assert_eq!(
+        exec("select count(*) from myspace.users group by city having count(*) > 2"),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"2\n\x0d3\nccu\x053\n".to_vec()
+        }
+    );
+    // the aggregate in the having clause doesn't have to be selected
+    assert_eq!(
+        exec(
+            "select count(*) from myspace.users group by city having count(distinct age) = 1 and count(*) >= 2"
+        ),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"2\n\x0d3\nblr\x052\n".to_vec()
+        }
+    );
+    assert_eq!(
+        exec("select count(*) from myspace.users group by city having count(*) between 4 and 10"),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 0,
+            data: vec![]
+        }
+    );
+}
+
+#[test]
+fn select_aggregate_group_by_memory_limit() {
+    let mut global =
+        TestGlobal::new_with_driver_id_instant_update("dml_select_aggregate_group_by_memory_limit");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, city: string)",
+    )
+    .unwrap();
+    for i in 0..8 {
+        super::_exec_only_insert(
+            &global,
+            &format!("insert into myspace.users('user{i}', 'city{i}')"),
+            |_| {},
+        )
+        .unwrap();
+    }
+    // each group needs a HyperLogLog sketch, so eight of them don't fit
+    global.set_query_memory_limit(64 * 1024);
+    let tok =
+        lex_insecure(b"select count(distinct username) from myspace.users group by city").unwrap();
+    let select = parse_ast_node_full(&tok[1..]).unwrap();
+    assert_eq!(
+        dml::select_aggregate_resp(&global, select).unwrap_err(),
+        QueryError::QExecQueryMemoryLimitExceeded
+    );
+}
+
+#[test]
+fn select_aggregate_count_distinct_estimate() {
+    let global = TestGlobal::new_with_driver_id_instant_update(
+        "dml_select_aggregate_count_distinct_estimate",
+    );
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.events(id: uint64, user: string)",
+    )
+    .unwrap();
+    const USERS: u64 = 4_000;
+    for id in 0..USERS * 2 {
+        super::_exec_only_insert(
+            &global,
+            &format!("insert into myspace.events({id}, 'user{}')", id % USERS),
+            |_| {},
+        )
+        .unwrap();
+    }
+    let resp =
+        exec_select_aggregate(&global, "select count(distinct user) from myspace.events").unwrap();
+    let estimate: u64 = std::str::from_utf8(&resp[1..resp.len() - 1])
+        .unwrap()
+        .parse()
+        .unwrap();
+    // the standard error is about 0.8%, so this is well within bounds
+    assert!(estimate.abs_diff(USERS) < USERS / 25, "{estimate}");
+}
+
+fn exec_select_file(global: &impl GlobalInstanceLike, query: &str) -> QueryResult<Response> {
+    let tok = lex_insecure(query.as_bytes()).unwrap();
+    let select = parse_ast_node_full(&tok[1..]).unwrap();
+    dml::select_file_resp(global, select)
+}
+
+#[test]
+fn select_file() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_file");
+    const PATH: &str = "dml_select_file.csv";
+    std::fs::write(
+        PATH,
+        "name,city,age\r\nsayan,ccu,20\r\n\"doe, jane\",blr,\r\n\r\n\"the \"\"dude\"\"\",\"ccu\",30\r\n",
+    )
+    .unwrap();
+    const SCHEMA: &str =
+        "(format csv, header, schema (name: string, city: string, null age: uint8))";
+    assert_eq!(
+        exec_select_file(&global, &format!("select * from file '{PATH}' {SCHEMA}")).unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 3,
+            data: b"3\n\x0d5\nsayan\x0d3\nccu\x0220\n\
+                3\n\x0d9\ndoe, jane\x0d3\nblr\x00\
+                3\n\x0d10\nthe \"dude\"\x0d3\nccu\x0230\n"
+                .to_vec()
+        }
+    );
+    assert_eq!(
+        exec_select_file(
+            &global,
+            &format!("select name from file '{PATH}' {SCHEMA} where age >= 20 limit 1")
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"1\n\x0d5\nsayan".to_vec()
+        }
+    );
+    assert_eq!(
+        exec_select_file(
+            &global,
+            &format!(
+                "select count(*), count(distinct age) from file '{PATH}' {SCHEMA} group by city"
+            )
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 2,
+            data: b"3\n\x0d3\nccu\x052\n\x052\n3\n\x0d3\nblr\x051\n\x050\n".to_vec()
+        }
+    );
+    assert_eq!(
+        exec_select_file(
+            &global,
+            &format!("select pincode from file '{PATH}' {SCHEMA}")
+        )
+        .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+    // the header doesn't match the schema
+    assert_eq!(
+        exec_select_file(
+            &global,
+            &format!("select * from file '{PATH}' (format csv, schema (name: string, city: string, age: uint8))")
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+    std::fs::remove_file(PATH).unwrap();
+    assert_eq!(
+        exec_select_file(&global, &format!("select * from file '{PATH}' {SCHEMA}")).unwrap_err(),
+        QueryError::QExecObjectNotFound
+    );
+}
+
+#[test]
+fn select_all_datetime_expr() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_all_datetime_expr");
+    let now = os::get_epoch_time_secs();
+    let day = 24 * 60 * 60;
+    let inserts: Vec<String> = [("sayan", now - day), ("robot", now - 10 * day)]
+        .into_iter()
+        .map(|(username, t)| format!("insert into myspace.mymodel({t}, '{username}')"))
+        .collect();
+    let inserts: Vec<&str> = inserts.iter().map(String::as_str).collect();
+    let ret = super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(last_seen: uint64, username: string) with { primary_index: 'btree' }",
+        &inserts,
+        "select all username from myspace.mymodel where last_seen >= now() - 7d limit 10",
+    )
+    .unwrap();
+    assert_eq!(ret, vec![intovec!["sayan"]]);
+    let ret = super::exec_select_all_only(
+        &global,
+        "select all last_seen, username from myspace.mymodel where last_seen < now() - 1 week limit 10",
+    )
+    .unwrap();
+    assert_eq!(ret, vec![intovec![now - 10 * day, "robot"]]);
+}
+
+#[test]
+fn select_string_fn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_string_fn");
+    assert_eq!(
+        super::exec_select(
+            &global,
+            "create model myspace.mymodel(username: string, name: string, null nick: string, age: uint8)",
+            "insert into myspace.mymodel('sayan', '  Sayan Nandan ', null, 30)",
+            "select username, upper(trim(name)), len(name), substr(trim(name), 7), concat(username, '@', lower(trim(name))), lower(nick) from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![
+            "sayan",
+            "SAYAN NANDAN",
+            15_u64,
+            "Nandan",
+            "sayan@sayan nandan",
+            Datacell::null()
+        ]
+    );
+    for (select, error) in [
+        (
+            "select upper(missing) from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecUnknownField,
+        ),
+        (
+            "select upper(age) from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+        (
+            "select substr(name, 'a') from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+    ] {
+        assert_eq!(
+            super::exec_select_only(&global, select).unwrap_err(),
+            error,
+            "{select}"
+        );
+    }
+}
+
+#[test]
+fn select_numeric_fn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_numeric_fn");
+    assert_eq!(
+        super::exec_select(
+            &global,
+            "create model myspace.mymodel(username: string, score: sint64, ratio: float64, code: string)",
+            "insert into myspace.mymodel('sayan', -42, 1.25, '300')",
+            "select abs(score), max(score, 0), min(score, 0, -100), round(ratio, 1), round(ratio), cast(code as uint16), cast(score as string), cast(round(ratio) as uint8) from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![42_i64, 0_i64, -100_i64, 1.3_f64, 1.0_f64, 300_u64, "-42", 1_u64]
+    );
+    for (select, error) in [
+        (
+            "select abs(username) from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+        (
+            "select round(ratio, -1) from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+        // the casts below type check, but the values don't fit
+        (
+            "select cast(code as uint8) from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+        (
+            "select cast(score as uint64) from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+        (
+            "select cast(ratio as sint64) from myspace.mymodel where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+    ] {
+        assert_eq!(
+            super::exec_select_only(&global, select).unwrap_err(),
+            error,
+            "{select}"
+        );
+    }
+}
+
+#[test]
+fn select_all_string_fn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_all_string_fn");
+    let ret = super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string) with { primary_index: 'btree' }",
+        &[
+            "insert into myspace.mymodel('sayan', 'London')",
+            "insert into myspace.mymodel('robot', 'paris')",
+            "insert into myspace.mymodel('hgwells', 'LONDON')",
+        ],
+        "select all username, upper(city) from myspace.mymodel where lower(city) = 'london' limit 10",
+    )
+    .unwrap();
+    assert_eq!(
+        ret,
+        vec![intovec!["hgwells", "LONDON"], intovec!["sayan", "LONDON"]]
+    );
+    // a string function can be combined with a key range
+    let ret = super::exec_select_all_only(
+        &global,
+        "select all len(username) from myspace.mymodel where username > 'i' and len(city) = 5 limit 10",
+    )
+    .unwrap();
+    assert_eq!(ret, vec![intovec![5_u64]]);
+}
+
+#[test]
+fn exists() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_exists");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, age: uint8) with { soft_delete: true }",
+    )
+    .unwrap();
+    assert!(!super::exec_exists_only(&global, "exists from myspace.mymodel").unwrap());
+    for insert in [
+        "insert into myspace.mymodel('sayan', 'London', 30)",
+        "insert into myspace.mymodel('robot', 'Paris', 5)",
+    ] {
+        super::exec_insert_only(&global, insert).unwrap();
+    }
+    for (exists, expected) in [
+        ("exists from myspace.mymodel", true),
+        // point lookups
+        ("exists from myspace.mymodel where username = 'sayan'", true),
+        (
+            "exists from myspace.mymodel where username = 'hgwells'",
+            false,
+        ),
+        // filters, on any field
+        (
+            "exists from myspace.mymodel where city = 'Paris' and age < 10",
+            true,
+        ),
+        (
+            "exists from myspace.mymodel where city = 'Paris' and age > 10",
+            false,
+        ),
+        (
+            "exists from myspace.mymodel where username = 'sayan' and age > 30",
+            false,
+        ),
+        (
+            "exists from myspace.mymodel where lower(city) = 'london'",
+            true,
+        ),
+    ] {
+        assert_eq!(
+            super::exec_exists_only(&global, exists).unwrap(),
+            expected,
+            "{exists}"
+        );
+    }
+    // soft deleted rows don't exist
+    super::exec_delete_only(
+        &global,
+        "delete from myspace.mymodel where username = 'robot'",
+    )
+    .unwrap();
+    for exists in [
+        "exists from myspace.mymodel where username = 'robot'",
+        "exists from myspace.mymodel where city = 'Paris'",
+    ] {
+        assert!(
+            !super::exec_exists_only(&global, exists).unwrap(),
+            "{exists}"
+        );
+    }
+    assert_eq!(
+        super::exec_exists_only(&global, "exists from myspace.mymodel where missing = 1")
+            .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+}
+
+#[test]
+fn analytic_model_reads_columns() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_analytic_model");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, city: string, age: uint8) with { analytic: true }",
+    )
+    .unwrap();
+    for (username, city, age) in [
+        ("sayan", "ccu", 20),
+        ("elana", "blr", 30),
+        ("sneha", "ccu", 25),
+    ] {
+        super::exec_insert_only(
+            &global,
+            &format!("insert into myspace.users('{username}', '{city}', {age})"),
+        )
+        .unwrap();
+    }
+    let describe_columns = || {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "users"), |mdl| {
+                Ok(mdl.columns().unwrap().describe())
+            })
+            .unwrap()
+    };
+    // the columns are only built once they're read
+    assert_eq!(
+        describe_columns(),
+        "{\"built\":false,\"rows\":0,\"slots\":0}"
+    );
+    assert_eq!(
+        exec_select_aggregate(
+            &global,
+            "select count(*), count(distinct city) from myspace.users"
+        )
+        .unwrap(),
+        b"\x053\n\x052\n"
+    );
+    assert_eq!(
+        describe_columns(),
+        "{\"built\":true,\"rows\":3,\"slots\":3}"
+    );
+    // changes are synced once the query is done, and a freed slot is reused
+    super::_exec_only_update(
+        &global,
+        "update myspace.users set age += 1 where username = 'sayan'",
+    )
+    .unwrap();
+    super::exec_delete_only(
+        &global,
+        "delete from myspace.users where username = 'elana'",
+    )
+    .unwrap();
+    assert_eq!(
+        describe_columns(),
+        "{\"built\":true,\"rows\":2,\"slots\":3}"
+    );
+    super::exec_insert_only(&global, "insert into myspace.users('john', 'del', 10)").unwrap();
+    assert_eq!(
+        describe_columns(),
+        "{\"built\":true,\"rows\":3,\"slots\":3}"
+    );
+    let ret: HashMap<String, Vec<Datacell>> =
+        super::exec_select_all_only(&global, "select all * from myspace.users limit 100")
+            .unwrap()
+            .into_iter()
+            .map(|mut d| (d.remove(0).into_str().unwrap(), d))
+            .collect();
+    assert_eq!(ret.len(), 3);
+    assert_eq!(ret.get("sayan").unwrap(), &intovec!["ccu", 21u64]);
+    assert_eq!(ret.get("sneha").unwrap(), &intovec!["ccu", 25u64]);
+    assert_eq!(ret.get("john").unwrap(), &intovec!["del", 10u64]);
+    assert_eq!(
+        exec_select_aggregate(&global, "select count(*) from myspace.users where age > 20")
+            .unwrap(),
+        b"\x052\n"
+    );
+}
+
+#[test]
+fn maintained_aggregates() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_maintained_aggregates");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, city: string, age: uint8) with { maintain: { \
+            users_by_city: \"count(*) group by city\", age_by_city: \"sum(age) group by city\" } }",
+    )
+    .unwrap();
+    for (username, city, age) in [
+        ("sayan", "ccu", 20),
+        ("elana", "blr", 30),
+        ("sneha", "ccu", 25),
+    ] {
+        super::exec_insert_only(
+            &global,
+            &format!("insert into myspace.users('{username}', '{city}', {age})"),
+        )
+        .unwrap();
+    }
+    let describe_totals = || {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "users"), |mdl| {
+                Ok(mdl.maintained().unwrap().describe())
+            })
+            .unwrap()
+    };
+    let exec = |query: &str| {
+        let tok = lex_insecure(query.as_bytes()).unwrap();
+        let select = parse_ast_node_full(&tok[1..]).unwrap();
+        dml::select_aggregate_resp(&global, select).unwrap()
+    };
+    // the totals are only built once they're read
+    assert_eq!(
+        describe_totals(),
+        "{\"built\":false,\"rows\":0,\"groups\":0}"
+    );
+    assert_eq!(
+        exec("select count(*), sum(age) from myspace.users group by city having count(*) > 1"),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"3\n\x0d3\nccu\x052\n\x0545\n".to_vec()
+        }
+    );
+    assert_eq!(
+        describe_totals(),
+        "{\"built\":true,\"rows\":3,\"groups\":2}"
+    );
+    // changes are applied once the query is done, and a group goes away with its last row
+    super::_exec_only_update(
+        &global,
+        "update myspace.users set age += 1 where username = 'sayan'",
+    )
+    .unwrap();
+    super::exec_delete_only(
+        &global,
+        "delete from myspace.users where username = 'elana'",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.users('john', 'ccu', 10)").unwrap();
+    assert_eq!(
+        describe_totals(),
+        "{\"built\":true,\"rows\":3,\"groups\":1}"
+    );
+    assert_eq!(
+        exec("select sum(age), count(*) from myspace.users group by city"),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"3\n\x0d3\nccu\x0556\n\x053\n".to_vec()
+        }
+    );
+    // a row that moves to another group is taken out of its old group
+    super::_exec_only_update(
+        &global,
+        "update myspace.users set city = 'del' where username = 'sneha'",
+    )
+    .unwrap();
+    assert_eq!(
+        exec("select sum(age) from myspace.users group by city having count(*) = 2"),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"2\n\x0d3\nccu\x0531\n".to_vec()
+        }
+    );
+    // anything else is still computed with a scan
+    assert_eq!(
+        exec_select_aggregate(&global, "select sum(age) from myspace.users where age > 20")
+            .unwrap(),
+        b"\x0546\n"
+    );
+}
+
+#[test]
+fn select_all_arrow() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_select_all_arrow");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, null age: uint8)",
+    )
+    .unwrap();
+    for insert in [
+        "insert into myspace.users('sayan', 30)",
+        "insert into myspace.users('robot', null)",
+    ] {
+        super::_exec_only_insert(&global, insert, |_| {}).unwrap();
+    }
+    let tok = lex_insecure(b"select all * from myspace.users limit 10 format arrow").unwrap();
+    let select = parse_ast_node_full(&tok[2..]).unwrap();
+    let Response::Serialized { ty, size, data } = dml::select_all_resp(&global, select).unwrap()
+    else {
+        panic!("expected a stream")
+    };
+    assert_eq!(ty, ResponseType::Binary);
+    assert_eq!(size, data.len());
+    // read a field of a flatbuffers table
+    let u32_at = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as usize;
+    let field_at = |table: usize, id: usize| {
+        let vtable =
+            table - i32::from_le_bytes(data[table..table + 4].try_into().unwrap()) as usize;
+        let slot = vtable + 4 + 2 * id;
+        table + u16::from_le_bytes(data[slot..slot + 2].try_into().unwrap()) as usize
+    };
+    let (mut at, mut messages) = (0, vec![]);
+    loop {
+        // every message starts with a continuation marker and the size of its metadata
+        assert_eq!(u32_at(at), u32::MAX as usize);
+        let metadata_size = u32_at(at + 4);
+        at += 8;
+        if metadata_size == 0 {
+            break;
+        }
+        assert_eq!(metadata_size % 8, 0);
+        let message = at + u32_at(at);
+        let header_type = data[field_at(message, 1)];
+        let header = field_at(message, 2);
+        let header = header + u32_at(header);
+        let body = field_at(message, 3);
+        let body_size = u64::from_le_bytes(data[body..body + 8].try_into().unwrap()) as usize;
+        assert_eq!(body_size % 8, 0);
+        at += metadata_size;
+        if header_type == 3 {
+            // a record batch starts with the number of its rows
+            let rows = field_at(header, 0);
+            assert_eq!(
+                u64::from_le_bytes(data[rows..rows + 8].try_into().unwrap()),
+                2
+            );
+            let body = &data[at..at + body_size];
+            assert!(body.windows(5).any(|w| w == b"sayan"));
+            assert!(body.windows(5).any(|w| w == b"robot"));
+        }
+        messages.push(header_type);
+        at += body_size;
+    }
+    assert_eq!(at, data.len());
+    // the schema, and then a single batch
+    assert_eq!(messages, [1, 3]);
+}
+
+#[test]
+fn select_from_system_catalog() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_select_from_system_catalog");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, tags: list { type: string }, null nick: string)",
+    )
+    .unwrap();
+    super::_exec_only_insert(
+        &global,
+        "insert into myspace.mymodel('sayan', ['admin'], null)",
+        |_| (),
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select primary_key, fields, rows from sys.models where name = 'myspace.mymodel'"
+        )
+        .unwrap(),
+        intovec!["username", 3u64, 1u64]
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select field_type, nullable, is_primary from sys.fields where name = 'myspace.mymodel.tags'"
+        )
+        .unwrap(),
+        intovec!["list { type: string }", false, false]
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select nullable from sys.fields where name = 'myspace.mymodel.nick'"
+        )
+        .unwrap(),
+        intovec![true]
+    );
+    // catalog models can't be written to
+    assert_eq!(
+        super::_exec_only_insert(
+            &global,
+            "insert into sys.spaces('myspace', 'uuid', 0)",
+            |_| ()
+        )
+        .unwrap_err(),
+        QueryError::QExecObjectNotFound
+    );
+}
@@ -0,0 +1,167 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::{
+    core::dml,
+    data::cell::Datacell,
+    error::{QueryError, QueryResult},
+    fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+    net::protocol::Response,
+    ql::{ast::parse_ast_node_full, tests::lex_insecure},
+};
+
+fn exec_traverse(global: &impl GlobalInstanceLike, traverse: &str) -> QueryResult<Vec<u8>> {
+    let tok = lex_insecure(traverse.as_bytes()).unwrap();
+    match dml::traverse_resp(global, parse_ast_node_full(&tok[1..]).unwrap())? {
+        Response::Serialized { data, .. } => Ok(data),
+        _ => panic!("expected a serialized response"),
+    }
+}
+
+/// Encode a row of the graph, as returned by a traversal
+fn row(depth: u64, id: u64, follows: &[u64]) -> Vec<u8> {
+    let mut data = b"3\n".to_vec();
+    dml::encode_cell(&mut data, &Datacell::new_uint_default(depth));
+    dml::encode_cell(&mut data, &Datacell::new_uint_default(id));
+    dml::encode_cell(
+        &mut data,
+        &Datacell::new_list(
+            follows
+                .iter()
+                .map(|id| Datacell::new_uint_default(*id))
+                .collect(),
+        ),
+    );
+    data
+}
+
+#[test]
+fn traverse() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_traverse");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.graph(id: uint64, follows: list { type: uint64 })",
+    )
+    .unwrap();
+    // 99 doesn't exist, and 2 links back to 1
+    for insert in [
+        "insert into myspace.graph(1, [2, 3])",
+        "insert into myspace.graph(2, [4, 99, 1])",
+        "insert into myspace.graph(3, [4])",
+        "insert into myspace.graph(4, [5])",
+        "insert into myspace.graph(5, [])",
+    ] {
+        super::exec_insert_only(&global, insert).unwrap();
+    }
+    assert_eq!(
+        exec_traverse(
+            &global,
+            "traverse myspace.graph(1) via follows depth 2 limit 100"
+        )
+        .unwrap(),
+        [
+            row(0, 1, &[2, 3]),
+            row(1, 2, &[4, 99, 1]),
+            row(1, 3, &[4]),
+            row(2, 4, &[5]),
+        ]
+        .concat()
+    );
+    assert_eq!(
+        exec_traverse(
+            &global,
+            "traverse myspace.graph(1) via follows depth 10 limit 100 fanout 1"
+        )
+        .unwrap(),
+        [
+            row(0, 1, &[2, 3]),
+            row(1, 2, &[4, 99, 1]),
+            row(2, 4, &[5]),
+            row(3, 5, &[]),
+        ]
+        .concat()
+    );
+    assert_eq!(
+        exec_traverse(
+            &global,
+            "traverse myspace.graph(1) via follows depth 10 limit 2"
+        )
+        .unwrap(),
+        [row(0, 1, &[2, 3]), row(1, 2, &[4, 99, 1])].concat()
+    );
+    assert_eq!(
+        exec_traverse(
+            &global,
+            "traverse myspace.graph(5) via follows depth 0 limit 10"
+        )
+        .unwrap(),
+        row(0, 5, &[])
+    );
+}
+
+#[test]
+fn traverse_bad() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_traverse_bad");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.graph(id: uint64, name: string, follows: list { type: uint64 }, tags: list { type: string })",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.graph(1, 'a', [], [])").unwrap();
+    for (traverse, error) in [
+        (
+            "traverse myspace.graph(2) via follows depth 1 limit 10",
+            QueryError::QExecDmlRowNotFound,
+        ),
+        (
+            "traverse myspace.graph('1') via follows depth 1 limit 10",
+            QueryError::QExecDmlWhereHasUnindexedColumn,
+        ),
+        (
+            "traverse myspace.graph(1) via friends depth 1 limit 10",
+            QueryError::QExecUnknownField,
+        ),
+        // not a list of keys
+        (
+            "traverse myspace.graph(1) via name depth 1 limit 10",
+            QueryError::QExecDmlValidationError,
+        ),
+        (
+            "traverse myspace.graph(1) via tags depth 1 limit 10",
+            QueryError::QExecDmlValidationError,
+        ),
+        (
+            "traverse myspace.nodes(1) via follows depth 1 limit 10",
+            QueryError::QExecObjectNotFound,
+        ),
+    ] {
+        assert_eq!(
+            exec_traverse(&global, traverse).unwrap_err(),
+            error,
+            "{traverse}"
+        );
+    }
+}
@@ -25,7 +25,17 @@
 */
 
 use crate::engine::{
-    core::dml, data::cell::Datacell, error::QueryError, fractal::test_utils::TestGlobal,
+    core::{
+        dml, lock,
+        notice::{Notice, NoticeCode},
+        session::{self, SessionSettings},
+        EntityIDRef,
+    },
+    data::{cell::Datacell, lit::Lit},
+    error::{ErrorDetail, QueryError},
+    fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+    net::protocol::{Response, ResponseType},
+    ql::ddl::{SessionSet, SetValue},
 };
 
 #[test]
@@ -146,7 +156,7 @@ fn fail_typedef_violation() {
     );
     assert_eq!(
         dml::update_flow_trace(),
-        ["sametag;nonnull", "unknown_reason;exitmainloop", "rollback"]
+        ["sametag;nonnull", "badtag", "rollback"]
     );
     // verify integrity
     assert_eq!(
@@ -158,3 +168,891 @@ fn fail_typedef_violation() {
         intovec!["sayan", "pass123", 1u64]
     );
 }
+
+#[test]
+fn filtered_multiple_rows() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_filtered_multiple_rows");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, followers: uint64)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 10)",
+            "insert into myspace.mymodel('robot', 'paris', 20)",
+            "insert into myspace.mymodel('hgwells', 'london', 30)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set followers += 100 where city = 'london'"
+        )
+        .unwrap(),
+        Some(2)
+    );
+    let mut rows = super::exec_select_all_only(
+        &global,
+        "select all username, followers from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    rows.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+    assert_eq!(
+        rows,
+        vec![
+            intovec!["hgwells", 130_u64],
+            intovec!["robot", 20_u64],
+            intovec!["sayan", 110_u64]
+        ]
+    );
+    // a point update doesn't return a count
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set followers = 0 where username = 'robot'"
+        )
+        .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn filtered_multiple_rows_fail_together() {
+    let global = TestGlobal::new_with_driver_id_instant_update(
+        "dml_update_filtered_multiple_rows_fail_together",
+    );
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, visits: uint8)",
+        &[
+            "insert into myspace.mymodel('hgwells', 'london', 1)",
+            "insert into myspace.mymodel('sayan', 'london', 250)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    // the first row is fine, but the second one overflows
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set visits += 10 where city = 'london'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+    let mut rows = super::exec_select_all_only(
+        &global,
+        "select all username, visits from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    rows.sort_by(|a, b| a[0].str().cmp(b[0].str()));
+    assert_eq!(
+        rows,
+        vec![intovec!["hgwells", 1_u64], intovec!["sayan", 250_u64]]
+    );
+}
+
+#[test]
+fn unfiltered_needs_limit_or_force() {
+    let global =
+        TestGlobal::new_with_driver_id_instant_update("dml_update_unfiltered_needs_limit_or_force");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, followers: uint64)",
+        &[
+            "insert into myspace.mymodel('sayan', 10)",
+            "insert into myspace.mymodel('robot', 20)",
+            "insert into myspace.mymodel('hgwells', 30)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::_exec_only_update(&global, "update myspace.mymodel set followers = 0").unwrap_err(),
+        QueryError::QExecDmlUnfilteredMutation
+    );
+    assert_eq!(
+        super::_exec_only_update(&global, "update myspace.mymodel set followers = 0 limit 2")
+            .unwrap(),
+        Some(2)
+    );
+    assert_eq!(
+        super::_exec_only_update(&global, "update myspace.mymodel set followers += 1 force")
+            .unwrap(),
+        Some(3)
+    );
+}
+
+#[test]
+fn unfiltered_memory_limit() {
+    let mut global =
+        TestGlobal::new_with_driver_id_instant_update("dml_update_unfiltered_memory_limit");
+    global.set_query_memory_limit(4096);
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, bio: string, visits: uint64)",
+    )
+    .unwrap();
+    for i in 0..10 {
+        super::exec_insert_only(
+            &global,
+            &format!(
+                "insert into myspace.mymodel('user{i}', '{}', 0)",
+                "x".repeat(1000)
+            ),
+        )
+        .unwrap();
+    }
+    // the old values are held until every row is changed
+    assert_eq!(
+        super::_exec_only_update(&global, "update myspace.mymodel set bio = 'y' force")
+            .unwrap_err(),
+        QueryError::QExecQueryMemoryLimitExceeded
+    );
+    let rows = super::exec_select_all_only(
+        &global,
+        "select all username from myspace.mymodel where bio = 'y' limit 100",
+    )
+    .unwrap();
+    assert!(rows.is_empty());
+    // small changes are fine
+    assert_eq!(
+        super::_exec_only_update(&global, "update myspace.mymodel set visits += 1 force").unwrap(),
+        Some(10)
+    );
+}
+
+#[test]
+fn unfiltered_and_limited_notices() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_notices");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, followers: uint64)",
+        &[
+            "insert into myspace.mymodel('sayan', 10)",
+            "insert into myspace.mymodel('robot', 20)",
+            "insert into myspace.mymodel('hgwells', 30)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let codes = || -> Vec<NoticeCode> { Notice::take_all().iter().map(Notice::code).collect() };
+    Notice::clear();
+    super::_exec_only_update(&global, "update myspace.mymodel set followers += 1 force").unwrap();
+    assert_eq!(codes(), [NoticeCode::UnfilteredMutation]);
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set followers += 1 where followers > 0 limit 2",
+    )
+    .unwrap();
+    assert_eq!(codes(), [NoticeCode::LimitReached]);
+    // the limit wasn't reached
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set followers += 1 where followers > 0 limit 5",
+    )
+    .unwrap();
+    assert!(codes().is_empty());
+}
+
+#[test]
+fn update_returning() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_returning");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, followers: uint64)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 10)",
+            "insert into myspace.mymodel('robot', 'paris', 20)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    // point update: a single row with the new values
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "update myspace.mymodel set followers += 5 where username = 'sayan' returning *"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::Row,
+            size: 3,
+            data: b"\x0D5\nsayan\x0D6\nlondon\x0515\n".to_vec(),
+        }
+    );
+    // filtered update: all matching rows
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "update myspace.mymodel set followers = 0 where city = 'paris' returning username"
+        )
+        .unwrap(),
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size: 1,
+            data: b"1\n\x0D5\nrobot".to_vec(),
+        }
+    );
+}
+
+#[test]
+fn update_with_lsn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_with_lsn");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, followers: uint64)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 10)",
+            "insert into myspace.mymodel('robot', 'paris', 20)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "update myspace.mymodel set followers += 5 where username = 'sayan' with lsn"
+        )
+        .unwrap(),
+        Response::UInt64(2)
+    );
+    // the last change of a filtered update
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "update myspace.mymodel set followers = 0 where followers > 0 with lsn"
+        )
+        .unwrap(),
+        Response::UInt64(4)
+    );
+    // nothing changed
+    assert_eq!(
+        super::exec_dml_resp(
+            &global,
+            "update myspace.mymodel set followers = 0 where city = 'tokyo' with lsn"
+        )
+        .unwrap(),
+        Response::Null
+    );
+}
+
+#[test]
+fn update_with_summary() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_with_summary");
+    super::exec_select_all(
+        &global,
+        "create model myspace.mymodel(username: string, city: string, followers: uint64)",
+        &[
+            "insert into myspace.mymodel('sayan', 'london', 10)",
+            "insert into myspace.mymodel('robot', 'paris', 20)",
+        ],
+        "select all username from myspace.mymodel limit 100",
+    )
+    .unwrap();
+    let summary = |query| match super::exec_dml_resp(&global, query).unwrap() {
+        Response::Serialized {
+            ty: ResponseType::String,
+            data,
+            ..
+        } => String::from_utf8(data).unwrap(),
+        _ => panic!("expected a summary"),
+    };
+    // assigning the current value matches the row but doesn't modify it
+    assert_eq!(
+        summary("update myspace.mymodel set city = 'london' where username = 'sayan' with summary"),
+        r#"{"matched":1,"modified":0,"inserted":0,"deleted":0}"#
+    );
+    assert_eq!(
+        summary("update myspace.mymodel set followers = 0 where city = 'paris' with keys"),
+        r#"{"matched":1,"modified":1,"inserted":0,"deleted":0,"keys":["robot"],"truncated":false}"#
+    );
+    assert_eq!(
+        summary("update myspace.mymodel set followers = 0 where city = 'tokyo' with keys"),
+        r#"{"matched":0,"modified":0,"inserted":0,"deleted":0,"keys":[],"truncated":false}"#
+    );
+}
+
+#[test]
+fn update_computed() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_computed");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, null email: string, null email_lc: string { computed: \"lower(email)\" })",
+            "insert into myspace.mymodel('sayan', 'Sayan@Example.com')",
+            "update myspace.mymodel set email = 'SN@Example.com' where username = 'sayan'",
+            "select * from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec!["sayan", "SN@Example.com", "sn@example.com"]
+    );
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set email_lc = 'lol@example.com' where username = 'sayan'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+    assert_eq!(
+        dml::update_flow_trace(),
+        ["sametag;nonnull", "computed", "rollback"]
+    );
+}
+
+#[test]
+fn update_checks() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_checks");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, email: string { regex: \"[^@]+@[^@]+\" }, followers: uint64 { max: 1000 }, tags: list { type: string, maxlen: 1 })",
+            "insert into myspace.mymodel('sayan', 'sayan@example.com', 0, [])",
+            "update myspace.mymodel set followers += 1000, tags += 'rust' where username = 'sayan'",
+            "select followers from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![1000_u64]
+    );
+    for (update, error) in [
+        (
+            "update myspace.mymodel set followers += 1 where username = 'sayan'",
+            QueryError::QExecDmlCheckMaxViolated,
+        ),
+        (
+            "update myspace.mymodel set email = 'sayan', followers = 0 where username = 'sayan'",
+            QueryError::QExecDmlCheckRegexViolated,
+        ),
+        (
+            "update myspace.mymodel set tags += 'db' where username = 'sayan'",
+            QueryError::QExecDmlCheckMaxLenViolated,
+        ),
+    ] {
+        assert_eq!(
+            super::_exec_only_update(&global, update).unwrap_err(),
+            error,
+            "{update}"
+        );
+    }
+    // nothing was changed
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select * from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![
+            "sayan",
+            "sayan@example.com",
+            1000_u64,
+            Datacell::new_list(intovec!["rust"])
+        ]
+    );
+}
+
+#[test]
+fn update_numeric_coercion() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_numeric_coercion");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, delta: sint8, score: float64) with { coercion: 'lenient' }",
+            "insert into myspace.mymodel('sayan', -1, 0.5)",
+            "update myspace.mymodel set delta += 2, score += 1 where username = 'sayan'",
+            "select delta, score from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![1_i64, 1.5_f64]
+    );
+    for update in [
+        "update myspace.mymodel set delta = 128 where username = 'sayan'",
+        "update myspace.mymodel set delta = 1.5 where username = 'sayan'",
+    ] {
+        assert_eq!(
+            super::_exec_only_update(&global, update).unwrap_err(),
+            QueryError::QExecDmlValidationError,
+            "{update}"
+        );
+    }
+}
+
+#[test]
+fn update_type_mismatch_detail() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_type_mismatch_detail");
+    super::exec_insert(
+        &global,
+        "create model myspace.mymodel(username: string, tags: list { type: string }, score: float32) with { coercion: 'exact' }",
+        "insert into myspace.mymodel('sayan', [], 1.5)",
+        "sayan",
+        |_| {},
+    )
+    .unwrap();
+    for (update, detail) in [
+        (
+            "update myspace.mymodel set score = 2.5, tags += 1 where username = 'sayan'",
+            [
+                ("field", "tags"),
+                ("parameter", "1"),
+                ("expected", "string"),
+                ("received", "uint64"),
+            ],
+        ),
+        (
+            "update myspace.mymodel set tags += 'a', score = 'high' where username = 'sayan'",
+            [
+                ("field", "score"),
+                ("parameter", "1"),
+                ("expected", "float32"),
+                ("received", "string"),
+            ],
+        ),
+        (
+            "update myspace.mymodel set score = 0.1 where username = 'sayan'",
+            [
+                ("field", "score"),
+                ("parameter", "0"),
+                ("expected", "float32"),
+                ("received", "float64"),
+            ],
+        ),
+    ] {
+        let e = super::_exec_only_update(&global, update).unwrap_err();
+        assert_eq!(e, QueryError::QExecDmlValidationError, "{update}");
+        let taken = ErrorDetail::take(e);
+        let entries: Vec<(&str, &str)> = taken
+            .entries()
+            .iter()
+            .map(|(k, v)| (*k, v.as_ref()))
+            .collect();
+        assert_eq!(entries, detail, "{update}");
+    }
+    // nothing was changed
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select tags, score from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![Datacell::new_list(vec![]), 1.5_f64]
+    );
+}
+
+#[test]
+fn update_string_fn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_string_fn");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, name: string, null nick: string, name_len: uint64)",
+            "insert into myspace.mymodel('sayan', ' Sayan ', null, 0)",
+            "update myspace.mymodel set name = upper(trim(name)), name_len = len(name), nick = lower(nick) where username = 'sayan'",
+            "select name, name_len, nick from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        // every function sees the row as it was before the update
+        intovec!["SAYAN", 7_u64, Datacell::null()]
+    );
+    for (update, error) in [
+        (
+            "update myspace.mymodel set name = upper(missing) where username = 'sayan'",
+            QueryError::QExecUnknownField,
+        ),
+        (
+            "update myspace.mymodel set name = upper(name_len) where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+        (
+            "update myspace.mymodel set name_len = upper(name) where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+        // nick is null, but name isn't nullable
+        (
+            "update myspace.mymodel set name = lower(nick) where username = 'sayan'",
+            QueryError::QExecDmlValidationError,
+        ),
+    ] {
+        assert_eq!(
+            super::_exec_only_update(&global, update).unwrap_err(),
+            error,
+            "{update}"
+        );
+    }
+    // a filter on a string function
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set nick = concat(lower(name), '_', username) where lower(name) = 'sayan'"
+        )
+        .unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select nick from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec!["sayan_sayan"]
+    );
+}
+
+#[test]
+fn update_numeric_fn() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_numeric_fn");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, score: uint64, level: uint8, delta: sint64)",
+            "insert into myspace.mymodel('sayan', 250, 0, -7)",
+            "update myspace.mymodel set level = cast(score as uint8), score = min(score, 100), delta = abs(delta) where username = 'sayan'",
+            "select score, level, delta from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![100_u64, 250_u64, 7_i64]
+    );
+    // 300 doesn't fit into a uint8, and nothing is changed
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set score = 300 where username = 'sayan'",
+    )
+    .unwrap();
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set delta = 0, level = cast(score as uint8) where username = 'sayan'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select level, delta from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![250_u64, 7_i64]
+    );
+    // a row for which a cast fails doesn't match a filter
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set level = 1 where cast(score as uint8) > 0"
+        )
+        .unwrap(),
+        Some(0)
+    );
+}
+
+#[test]
+fn window_counter() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_window_counter");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, hits: window_counter { window: 3600 })",
+            "insert into myspace.mymodel('sayan', 2)",
+            "update myspace.mymodel set hits += 3 where username = 'sayan'",
+            "select window_count(hits, 60), window_count(hits, 3600) from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![5_u64, 5_u64]
+    );
+    assert_eq!(dml::update_flow_trace(), ["window"]);
+    // start over
+    super::_exec_only_update(
+        &global,
+        "update myspace.mymodel set hits = 1 where username = 'sayan'",
+    )
+    .unwrap();
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select window_count(hits, 60) from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![1_u64]
+    );
+    // events can only be recorded
+    for bad_update in [
+        "update myspace.mymodel set hits -= 1 where username = 'sayan'",
+        "update myspace.mymodel set hits += 'a' where username = 'sayan'",
+    ] {
+        assert_eq!(
+            super::_exec_only_update(&global, bad_update).unwrap_err(),
+            QueryError::QExecDmlValidationError
+        );
+    }
+    // a rate limit
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set hits += 1 where window_count(hits, 60) < 2"
+        )
+        .unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set hits += 1 where window_count(hits, 60) < 2"
+        )
+        .unwrap(),
+        Some(0)
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select window_count(username, 60) from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDmlValidationError
+    );
+}
+
+#[test]
+fn decimal() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_decimal");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, balance: decimal { precision: 6, scale: 2 })",
+            "insert into myspace.mymodel('sayan', '12.34')",
+            "update myspace.mymodel set balance += '0.66' where username = 'sayan'",
+            "select balance, fixed(balance, 2) from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![1300_i64, "13.00"]
+    );
+    assert_eq!(dml::update_flow_trace(), ["sametag;nonnull"]);
+    // compared exactly
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set balance -= 0.1 where balance >= '13.00'"
+        )
+        .unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select fixed(balance, 2) from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec!["12.90"]
+    );
+    for bad_update in [
+        // too many digits after the point
+        "update myspace.mymodel set balance = '1.001' where username = 'sayan'",
+        // too many digits
+        "update myspace.mymodel set balance += '9999.99' where username = 'sayan'",
+        "update myspace.mymodel set balance *= 2 where username = 'sayan'",
+        "update myspace.mymodel set balance = 'a' where username = 'sayan'",
+    ] {
+        assert_eq!(
+            super::_exec_only_update(&global, bad_update).unwrap_err(),
+            QueryError::QExecDmlValidationError,
+            "{bad_update}"
+        );
+    }
+}
+
+#[test]
+fn overflow() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_overflow");
+    assert_eq!(
+        super::exec_update(
+            &global,
+            "create model myspace.mymodel(username: string, visits: uint8 { overflow: 'saturate' }, seq: sint8 { overflow: 'wrap' }, plain: uint8)",
+            "insert into myspace.mymodel('sayan', 250, -127, 0)",
+            "update myspace.mymodel set visits += 300, seq += -2 where username = 'sayan'",
+            "select visits, seq from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![255_u64, 127_i64]
+    );
+    assert_eq!(
+        dml::update_flow_trace(),
+        [
+            "sametag;overflow",
+            "sametag;nonnull",
+            "sametag;overflow",
+            "sametag;nonnull"
+        ]
+    );
+    assert_eq!(
+        super::_exec_only_update(
+            &global,
+            "update myspace.mymodel set visits -= 256 where username = 'sayan'"
+        )
+        .unwrap(),
+        None
+    );
+    assert_eq!(
+        super::exec_select_only(
+            &global,
+            "select visits from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec![0_u64]
+    );
+    for bad_update in [
+        // no policy
+        "update myspace.mymodel set plain -= 1 where username = 'sayan'",
+        // a plain assignment is still checked
+        "update myspace.mymodel set visits = 256 where username = 'sayan'",
+    ] {
+        assert_eq!(
+            super::_exec_only_update(&global, bad_update).unwrap_err(),
+            QueryError::QExecDmlValidationError,
+            "{bad_update}"
+        );
+    }
+}
+
+#[test]
+fn blob() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_blob");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, null avatar: binary, bio: string)",
+    )
+    .unwrap();
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    let sayan = Datacell::new_str("sayan".into());
+    dml::bulk_insert(
+        &global,
+        entity,
+        vec![vec![
+            sayan.clone(),
+            Datacell::null(),
+            Datacell::new_str("".into()),
+        ]],
+    )
+    .unwrap();
+    // a null blob reads as null
+    assert_eq!(
+        dml::read_blob(&global, entity, &sayan, "avatar", 0, 16),
+        Ok(None)
+    );
+    let avatar: Vec<u8> = (0..=255).collect();
+    dml::check_blob_target(&global, entity, "avatar").unwrap();
+    dml::write_blob(&global, entity, &sayan, "avatar", &avatar).unwrap();
+    assert_eq!(
+        dml::read_blob(&global, entity, &sayan, "avatar", 0, 16),
+        Ok(Some(avatar[..16].to_vec()))
+    );
+    assert_eq!(
+        dml::read_blob(&global, entity, &sayan, "avatar", 250, 16),
+        Ok(Some(avatar[250..].to_vec()))
+    );
+    assert_eq!(
+        dml::read_blob(&global, entity, &sayan, "avatar", 1024, 16),
+        Ok(Some(vec![]))
+    );
+    // only binary fields (other than the primary key) can hold blobs
+    for field in ["bio", "username"] {
+        assert_eq!(
+            dml::check_blob_target(&global, entity, field),
+            Err(QueryError::QExecDmlValidationError)
+        );
+    }
+    assert_eq!(
+        dml::check_blob_target(&global, entity, "picture"),
+        Err(QueryError::QExecUnknownField)
+    );
+    assert_eq!(
+        dml::write_blob(
+            &global,
+            entity,
+            &Datacell::new_str("robot".into()),
+            "avatar",
+            b"beep"
+        ),
+        Err(QueryError::QExecDmlRowNotFound)
+    );
+}
+
+#[test]
+fn row_locks_are_listed() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_row_locks_are_listed");
+    super::exec_update(
+        &global,
+        "create model myspace.mymodel(username: string, followers: uint64)",
+        "insert into myspace.mymodel('sayan', 0)",
+        "update myspace.mymodel set followers += 1 where username = 'sayan'",
+        "select * from myspace.mymodel where username = 'sayan'",
+    )
+    .unwrap();
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    let listed = || lock::describe(&global).contains(r#""model":"myspace.mymodel","key_hash":""#);
+    // the update released its lock when it was done
+    assert!(!listed());
+    let row = super::_exec_only_read_key_and_then(&global, entity, "sayan", |row| row).unwrap();
+    let held = global
+        .state()
+        .namespace()
+        .with_model(entity, |mdl| lock::write(mdl, &row))
+        .unwrap();
+    assert!(listed());
+    drop(held);
+    assert!(!listed());
+}
+
+#[test]
+fn row_locks_time_out() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_update_row_locks_time_out");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.mymodel(username: string, followers: uint64)",
+    )
+    .unwrap();
+    super::exec_insert_only(&global, "insert into myspace.mymodel('sayan', 0)").unwrap();
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    let row = super::_exec_only_read_key_and_then(&global, entity, "sayan", |row| row).unwrap();
+    let held = global
+        .state()
+        .namespace()
+        .with_model(entity, |mdl| lock::write(mdl, &row))
+        .unwrap();
+    // a statement only waits for the row until its timeout
+    let mut settings = SessionSettings::new();
+    settings
+        .set(&SessionSet::new(
+            "statement_timeout".into(),
+            SetValue::Lit(Lit::new_uint(50)),
+        ))
+        .unwrap();
+    {
+        let _session = session::enter(&settings);
+        assert_eq!(
+            super::_exec_only_select(
+                &global,
+                "select * from myspace.mymodel where username = 'sayan'"
+            ),
+            Err(QueryError::SysLockWaitTimeout)
+        );
+    }
+    {
+        let _session = session::enter(&settings);
+        assert_eq!(
+            super::_exec_delete_only(
+                &global,
+                "delete from myspace.mymodel where username = 'sayan'",
+                "sayan"
+            ),
+            Err(QueryError::SysLockWaitTimeout)
+        );
+    }
+    // and the row is left as it was
+    drop(held);
+    assert_eq!(
+        super::_exec_only_select(
+            &global,
+            "select * from myspace.mymodel where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec!["sayan", 0_u64]
+    );
+}
@@ -0,0 +1,207 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::engine::{
+    core::{model::ModelData, EntityIDRef},
+    error::{QueryError, QueryResult},
+    fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+    ql::{
+        ast::parse_ast_node_full,
+        ddl::{crt::CreateView, drop::DropModel},
+        tests::lex_insecure,
+    },
+};
+
+fn exec_create_view(global: &impl GlobalInstanceLike, create: &str) -> QueryResult<Option<bool>> {
+    let tok = lex_insecure(create.as_bytes()).unwrap();
+    let create = parse_ast_node_full::<CreateView>(&tok[2..]).unwrap();
+    ModelData::transactional_exec_create_view(global, create)
+}
+
+fn exec_drop(global: &impl GlobalInstanceLike, drop: &str) -> QueryResult<Option<bool>> {
+    let tok = lex_insecure(drop.as_bytes()).unwrap();
+    let drop = parse_ast_node_full::<DropModel>(&tok[2..]).unwrap();
+    ModelData::transactional_exec_drop(global, drop)
+}
+
+fn count_rows(global: &impl GlobalInstanceLike, model: &str) -> usize {
+    global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", model), |mdl| {
+            Ok(mdl.primary_index().count())
+        })
+        .unwrap()
+}
+
+#[test]
+fn view_maintained_on_write() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_view_maintained_on_write");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, age: uint8, city: string)",
+    )
+    .unwrap();
+    super::_exec_only_insert(
+        &global,
+        "insert into myspace.users('sayan', 22, 'ccu')",
+        |_| (),
+    )
+    .unwrap();
+    super::_exec_only_insert(
+        &global,
+        "insert into myspace.users('kid', 12, 'ccu')",
+        |_| (),
+    )
+    .unwrap();
+    assert_eq!(
+        exec_create_view(
+            &global,
+            "create view myspace.adults as select age from myspace.users where age >= 18"
+        )
+        .unwrap(),
+        None
+    );
+    // existing rows are in the view, and the primary key is always included
+    assert_eq!(count_rows(&global, "adults"), 1);
+    assert_eq!(
+        super::_exec_only_select(
+            &global,
+            "select username from myspace.adults where username = 'sayan'"
+        )
+        .unwrap(),
+        intovec!["sayan"]
+    );
+    assert_eq!(
+        super::_exec_only_select(
+            &global,
+            "select city from myspace.adults where username = 'sayan'"
+        )
+        .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+    // insert
+    super::_exec_only_insert(
+        &global,
+        "insert into myspace.users('elder', 80, 'blr')",
+        |_| (),
+    )
+    .unwrap();
+    assert_eq!(count_rows(&global, "adults"), 2);
+    // update into the view
+    super::_exec_only_update(
+        &global,
+        "update myspace.users set age = 18 where username = 'kid'",
+    )
+    .unwrap();
+    assert_eq!(count_rows(&global, "adults"), 3);
+    // update out of the view
+    super::_exec_only_update(
+        &global,
+        "update myspace.users set age = 17 where username = 'kid'",
+    )
+    .unwrap();
+    assert_eq!(count_rows(&global, "adults"), 2);
+    // delete
+    super::_exec_delete_only(
+        &global,
+        "delete from myspace.users where username = 'elder'",
+        "elder",
+    )
+    .unwrap();
+    assert_eq!(count_rows(&global, "adults"), 1);
+    // the view can only be changed through the model
+    assert_eq!(
+        super::_exec_only_insert(&global, "insert into myspace.adults('new', 40)", |_| ())
+            .unwrap_err(),
+        QueryError::QExecDmlViewIsReadOnly
+    );
+    // the model can't be dropped while it has views (not even with force)
+    assert_eq!(
+        exec_drop(&global, "drop model allow not empty myspace.users").unwrap_err(),
+        QueryError::QExecDdlNotEmpty
+    );
+    assert_eq!(
+        exec_drop(&global, "drop model allow not empty myspace.adults").unwrap(),
+        None
+    );
+    assert_eq!(
+        exec_drop(&global, "drop model allow not empty myspace.users").unwrap(),
+        None
+    );
+}
+
+#[test]
+fn view_bad_definition() {
+    let global = TestGlobal::new_with_driver_id_instant_update("dml_view_bad_definition");
+    super::_exec_only_create_space_model(
+        &global,
+        "create model myspace.users(username: string, age: uint8)",
+    )
+    .unwrap();
+    assert_eq!(
+        exec_create_view(
+            &global,
+            "create view myspace.v as select * from myspace.nope"
+        )
+        .unwrap_err(),
+        QueryError::QExecObjectNotFound
+    );
+    assert_eq!(
+        exec_create_view(
+            &global,
+            "create view myspace.v as select city from myspace.users"
+        )
+        .unwrap_err(),
+        QueryError::QExecUnknownField
+    );
+    assert_eq!(
+        exec_create_view(
+            &global,
+            "create view myspace.v as select * from myspace.users where username matches 'a*'"
+        )
+        .unwrap_err(),
+        QueryError::QExecDdlModelBadDefinition
+    );
+    assert_eq!(
+        exec_create_view(
+            &global,
+            "create view myspace.users as select * from myspace.users"
+        )
+        .unwrap_err(),
+        QueryError::QExecDdlObjectAlreadyExists
+    );
+    exec_create_view(
+        &global,
+        "create view myspace.v as select * from myspace.users",
+    )
+    .unwrap();
+    // views of views are not supported
+    assert_eq!(
+        exec_create_view(&global, "create view myspace.w as select * from myspace.v").unwrap_err(),
+        QueryError::QExecDdlModelBadDefinition
+    );
+}
@@ -26,9 +26,42 @@
 
 use {
     crate::engine::mem::unsafe_apis,
-    std::{borrow::Borrow, fmt, hash::Hash, marker::PhantomData, mem::ManuallyDrop, slice, str},
+    regex::{Regex, RegexBuilder},
+    std::{
+        borrow::Borrow, cmp::Ordering, fmt, hash::Hash, marker::PhantomData, mem::ManuallyDrop,
+        slice, str,
+    },
 };
 
+/// the maximum length of a user supplied regex pattern
+pub const REGEX_MAX_PATTERN_LEN: usize = 256;
+/// the maximum size of a compiled regex (and of its lazy DFA cache)
+const REGEX_MAX_COMPILED_SIZE: usize = 1 << 20;
+/// the maximum nesting depth of a regex
+const REGEX_MAX_NEST: u32 = 32;
+
+/// Compile a user supplied regex pattern (if `anchored`, the whole input must match). Returns [`None`] if the pattern
+/// is illegal or exceeds the limits.
+///
+/// NOTE(@ohsayan): the regex engine never backtracks (matching is linear in the size of the input), so the limits only
+/// need to guard the size and nesting of the compiled pattern against hostile patterns like `(a{1000}){1000}`
+pub fn compile_regex(pattern: &str, anchored: bool) -> Option<Regex> {
+    if pattern.len() > REGEX_MAX_PATTERN_LEN {
+        return None;
+    }
+    let pattern = if anchored {
+        format!("^(?:{pattern})$")
+    } else {
+        pattern.to_owned()
+    };
+    RegexBuilder::new(&pattern)
+        .size_limit(REGEX_MAX_COMPILED_SIZE)
+        .dfa_size_limit(REGEX_MAX_COMPILED_SIZE)
+        .nest_limit(REGEX_MAX_NEST)
+        .build()
+        .ok()
+}
+
 pub struct EntityID {
     sp: *mut u8,
     sl: usize,
@@ -115,6 +148,10 @@ impl<'a> EntityIDRef<'a> {
     }
 }
 
+// UNSAFE(@ohsayan): this is just a pair of borrowed strs
+unsafe impl<'a> Send for EntityIDRef<'a> {}
+unsafe impl<'a> Sync for EntityIDRef<'a> {}
+
 impl<'a> PartialEq for EntityIDRef<'a> {
     fn eq(&self, other: &Self) -> bool {
         self.space() == other.space() && self.entity() == other.entity()
@@ -123,6 +160,19 @@ impl<'a> PartialEq for EntityIDRef<'a> {
 
 impl<'a> Eq for EntityIDRef<'a> {}
 
+impl<'a> PartialOrd for EntityIDRef<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a> Ord for EntityIDRef<'a> {
+    /// Entities are ordered by space, and then by name
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.space(), self.entity()).cmp(&(other.space(), other.entity()))
+    }
+}
+
 impl<'a> Hash for EntityIDRef<'a> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.space().hash(state);
@@ -139,6 +189,12 @@ impl<'a> fmt::Debug for EntityIDRef<'a> {
     }
 }
 
+impl<'a> fmt::Display for EntityIDRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.space(), self.entity())
+    }
+}
+
 impl<'a> Borrow<EntityIDRef<'a>> for EntityID {
     fn borrow(&self) -> &EntityIDRef<'a> {
         unsafe { core::mem::transmute(self) }
@@ -0,0 +1,271 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    crash reports
+    ---
+    when a thread panics, the panic is logged as usual and a crash report is then written to the crash directory (as
+    `crash-<unix millis>-<pid>.json`), with the log saying where it went. the hook runs before the process aborts too,
+    so a panic that aborts gets a report all the same. a report has the version, a hash of the configuration, the
+    panic and where it happened, the phase that the server was in, the statements that were run last (oldest first),
+    the models that are loaded and the memory stats.
+
+    reports are meant to be attached to bug reports, so they carry no data: the statements are kept as fingerprints
+    (with every literal replaced by `?`, like the profiler's), models only by name and row count, and the configuration
+    only as a hash that leaves credentials out. the hook never waits on a lock: whatever is locked (perhaps by the
+    thread that panicked) is left out of the report
+*/
+
+use {
+    crate::engine::{
+        config::Configuration,
+        core::profile,
+        fractal::{Global, GlobalInstanceLike},
+        mem,
+        net::probe,
+        ql::lex::Token,
+        storage::encode_str,
+    },
+    std::{
+        any::Any,
+        fs, io, panic,
+        path::{Path, PathBuf},
+        process,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, OnceLock, PoisonError, TryLockError,
+        },
+        thread,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// The directory that reports are written to, unless set otherwise
+const DEFAULT_DIR: &str = "crash";
+/// The number of statements that are kept for reports
+const RECENT_MAX: usize = 32;
+
+static REPORTER: OnceLock<Reporter> = OnceLock::new();
+static GLOBAL: OnceLock<Global> = OnceLock::new();
+/// the statements that were run last, as (sequence number, fingerprint). a statement goes into the slot of its sequence
+/// number so that connections rarely wait on each other
+static RECENT: [Mutex<Option<(u64, Box<str>)>>; RECENT_MAX] =
+    [const { Mutex::new(None) }; RECENT_MAX];
+static NEXT: AtomicU64 = AtomicU64::new(0);
+
+struct Reporter {
+    dir: PathBuf,
+    config: u64,
+}
+
+/// Write a crash report whenever a thread panics. Statements are only kept once this is called
+pub fn install(config: &Configuration) {
+    let reporter = Reporter {
+        dir: config
+            .system
+            .crash_dir
+            .as_deref()
+            .unwrap_or(DEFAULT_DIR)
+            .into(),
+        config: config.fingerprint(),
+    };
+    if REPORTER.set(reporter).is_err() {
+        return;
+    }
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default(info);
+        let Some(reporter) = REPORTER.get() else {
+            return;
+        };
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()));
+        let report = describe(
+            reporter.config,
+            message(info.payload()),
+            location.as_deref(),
+            GLOBAL.get(),
+        );
+        match write(&reporter.dir, &report) {
+            Ok(path) => error!("a crash report was written to {}", path.display()),
+            Err(e) => error!(
+                "failed to write a crash report to {}: {e}",
+                reporter.dir.display()
+            ),
+        }
+    }));
+}
+
+/// Add the models of this instance to crash reports (once the data has been loaded)
+pub fn set_global(global: &Global) {
+    let _ = GLOBAL.set(global.clone());
+}
+
+/// Note that a statement was lexed into `tokens`
+pub fn record_statement(tokens: &[Token]) {
+    if REPORTER.get().is_none() {
+        return;
+    }
+    keep_statement(profile::fingerprint(tokens))
+}
+
+fn keep_statement(fingerprint: Box<str>) {
+    let seq = NEXT.fetch_add(1, Ordering::Relaxed);
+    *RECENT[seq as usize % RECENT_MAX]
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some((seq, fingerprint));
+}
+
+/// Returns the message that a thread panicked with
+fn message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(msg) => msg,
+        None => payload
+            .downcast_ref::<String>()
+            .map_or("<unknown>", String::as_str),
+    }
+}
+
+/// Returns the statements that were run last, oldest first (skipping the ones that are locked)
+fn recent_statements() -> Vec<(u64, Box<str>)> {
+    let mut statements: Vec<_> = RECENT
+        .iter()
+        .filter_map(|slot| match slot.try_lock() {
+            Ok(stmt) => stmt.clone(),
+            Err(TryLockError::Poisoned(e)) => e.into_inner().clone(),
+            Err(TryLockError::WouldBlock) => None,
+        })
+        .collect();
+    statements.sort_unstable_by_key(|(seq, _)| *seq);
+    statements
+}
+
+/// Returns the models as a JSON array, or `null` if they're locked
+fn describe_models(global: &Global) -> String {
+    let Some(models) = global.state().namespace().idx_models().try_read() else {
+        return "null".into();
+    };
+    let models: Vec<String> = models
+        .iter()
+        .map(|(id, model)| {
+            let mut name = String::new();
+            encode_str(&mut name, &format!("{}.{}", id.space(), id.entity()));
+            format!(
+                "{{\"model\":{name},\"rows\":{}}}",
+                model.data().primary_index().count()
+            )
+        })
+        .collect();
+    format!("[{}]", models.join(","))
+}
+
+/// Returns the crash report as a JSON object
+fn describe(config: u64, message: &str, location: Option<&str>, global: Option<&Global>) -> String {
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let mut out = format!(
+        "{{\"version\":\"{}\",\"at\":{at},\"config\":\"{config:016x}\",\"thread\":",
+        libsky::VERSION
+    );
+    encode_str(&mut out, thread::current().name().unwrap_or("<unnamed>"));
+    out.push_str(",\"message\":");
+    encode_str(&mut out, message);
+    out.push_str(",\"location\":");
+    match location {
+        Some(location) => encode_str(&mut out, location),
+        None => out.push_str("null"),
+    }
+    out.push_str(&format!(
+        ",\"phase\":\"{}\",\"statements\":[",
+        probe::phase().name()
+    ));
+    for (i, (_, statement)) in recent_statements().iter().enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        encode_str(&mut out, statement);
+    }
+    out.push_str(&format!(
+        "],\"models\":{},\"memory\":{}}}",
+        global.map_or_else(|| "null".into(), describe_models),
+        mem::allocator::describe()
+    ));
+    out
+}
+
+/// Write the report to a new file in `dir`, returning its path
+fn write(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let path = dir.join(format!("crash-{at}-{}.json", process::id()));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{describe, keep_statement, message, RECENT_MAX},
+        crate::engine::{core::profile, ql::tests::lex_insecure},
+        std::panic,
+    };
+
+    #[test]
+    fn report_redacts_statements() {
+        let tokens =
+            lex_insecure(b"select * from myspace.mymodel where username = 'sayan'").unwrap();
+        keep_statement(profile::fingerprint(&tokens));
+        let tokens =
+            lex_insecure(b"sysctl alter user sayan with { password: 'mypass123' }").unwrap();
+        keep_statement(profile::fingerprint(&tokens));
+        let report = describe(0xfeed, "boom", Some("src/engine/crash.rs:1"), None);
+        assert!(report.starts_with("{\"version\":"));
+        assert!(report.contains("\"config\":\"000000000000feed\""));
+        assert!(report.contains("\"message\":\"boom\",\"location\":\"src/engine/crash.rs:1\""));
+        assert!(report.contains(
+            "\"select * from myspace.mymodel where username = ?\",\"sysctl alter user sayan with {password : ?}\"]"
+        ));
+        assert!(!report.contains("mypass123") & !report.contains("'sayan'"));
+        assert!(report.contains("\"models\":null,\"memory\":{"));
+        // only the last statements are kept
+        for _ in 0..RECENT_MAX {
+            keep_statement("select ?".into());
+        }
+        assert!(!describe(0, "boom", None, None).contains("password"));
+    }
+
+    #[test]
+    fn panic_message() {
+        let payload = panic::catch_unwind(|| panic!("static")).unwrap_err();
+        assert_eq!(message(&*payload), "static");
+        let payload = panic::catch_unwind(|| panic!("formatted {}", 1)).unwrap_err();
+        assert_eq!(message(&*payload), "formatted 1");
+    }
+}
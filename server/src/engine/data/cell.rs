@@ -476,7 +476,6 @@ impl Drop for Datacell {
     }
 }
 
-#[cfg(test)]
 impl Clone for Datacell {
     fn clone(&self) -> Self {
         let data = match self.kind() {
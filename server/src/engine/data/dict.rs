@@ -35,8 +35,7 @@ use {
 /// A generic dictionary built from scratch from syntactical elements
 pub type DictGeneric = HashMap<Box<str>, DictEntryGeneric>;
 
-#[derive(Debug, PartialEq)]
-#[cfg_attr(test, derive(Clone))]
+#[derive(Debug, PartialEq, Clone)]
 /// A generic dict entry: either a literal or a recursive dictionary
 pub enum DictEntryGeneric {
     /// A literal
@@ -308,7 +308,9 @@ impl<'a> fmt::Debug for Lit<'a> {
 
 impl<'a> Hash for Lit<'a> {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        // must hash exactly like `PrimaryIndexKey`
         self.tag.tag_unique().hash(state);
+        self.word.dwordqn_load_qw_nw().0.hash(state);
         self.__vdata().hash(state);
     }
 }
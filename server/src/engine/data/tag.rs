@@ -33,6 +33,11 @@ macro_rules! strid {
     }
 }
 
+/*
+    the values of the class, selector and unique tags are shown to clients (by `describe model`), so they must never
+    change
+*/
+
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, sky_macros::EnumMethods)]
 pub enum TagClass {
@@ -211,7 +216,18 @@ impl UIntSpec {
         Self(f)
     }
     pub fn check(&self, v: u64) -> bool {
-        v <= Self::LIM_MAX[self.0.tag_selector().value_word() - 1]
+        v <= self.max()
+    }
+    fn max(&self) -> u64 {
+        Self::LIM_MAX[self.0.tag_selector().value_word() - 1]
+    }
+    /// Returns the value of this kind that's closest to `v`
+    pub fn saturate(&self, v: i128) -> u64 {
+        v.clamp(0, self.max() as i128) as u64
+    }
+    /// Returns the low bits of `v` that this kind holds
+    pub fn wrap(&self, v: u64) -> u64 {
+        v & self.max()
     }
 }
 
@@ -236,6 +252,17 @@ impl SIntSpec {
         let tag = self.0.tag_selector().value_word() - 5;
         (i >= Self::LIM_MIN[tag]) & (i <= Self::LIM_MAX[tag])
     }
+    /// Returns the value of this kind that's closest to `i`
+    pub fn saturate(&self, i: i128) -> i64 {
+        let tag = self.0.tag_selector().value_word() - 5;
+        i.clamp(Self::LIM_MIN[tag] as i128, Self::LIM_MAX[tag] as i128) as i64
+    }
+    /// Returns the low bits of `i` that this kind holds (sign extended)
+    pub fn wrap(&self, i: i64) -> i64 {
+        let tag = self.0.tag_selector().value_word() - 5;
+        let unused = Self::LIM_MAX[tag].leading_zeros() - 1;
+        (i << unused) >> unused
+    }
 }
 
 impl From<SIntSpec> for FullTag {
@@ -259,6 +286,11 @@ impl FloatSpec {
         let tag = self.0.tag_selector().value_word() - 9;
         (f >= Self::LIM_MIN[tag]) & (f <= Self::LIM_MAX[tag])
     }
+    /// Returns the value of this kind that's closest to `f` (NaN stays NaN)
+    pub fn saturate(&self, f: f64) -> f64 {
+        let tag = self.0.tag_selector().value_word() - 9;
+        f.clamp(Self::LIM_MIN[tag], Self::LIM_MAX[tag])
+    }
 }
 
 impl From<FloatSpec> for FullTag {
@@ -48,6 +48,27 @@ pub enum QueryError {
     /// insufficient permissions error
     SysPermissionDenied = 5,
     SysNetworkSystemIllegalClientPacket = 6,
+    /// the server is using too much memory to run an expensive query right now
+    SysMemoryPressure = 7,
+    /// the model's changes can't be written to disk right now, so it doesn't take any more writes
+    SysStorageDegraded = 8,
+    /// the disk is full, so no writes are taken until space is freed
+    SysDiskFull = 9,
+    /// the instance or space was frozen with `sysctl freeze`, so it doesn't take any writes until it's unfrozen
+    SysFrozen = 10,
+    /// another instance (the upstream of a model that caches one, or the source of a copy) couldn't be reached, or
+    /// rejected the query
+    SysUpstreamError = 11,
+    /// a row lock couldn't be acquired within the configured lock wait timeout (see `sysctl list locks`)
+    SysLockWaitTimeout = 12,
+    /// the model's changes (or its webhooks) are too far behind to take more writes right now. the error says how
+    /// long to wait before retrying (see `sysctl report status`)
+    SysWriteThrottled = 13,
+    /// the user (or the model) has run more statements than its quota allows every second. the error says how long to
+    /// wait before retrying (see `sysctl report quotas`)
+    SysQuotaExceeded = 14,
+    /// a tiered blob that the response holds couldn't be fetched from its bucket (see `--blob-tier`)
+    SysBlobUnavailable = 15,
     // QL
     /// something like an integer that randomly has a character to attached to it like `1234q`
     LexInvalidInput = 25,
@@ -95,6 +116,39 @@ pub enum QueryError {
     QExecDmlRowNotFound = 111,
     /// this query needs a lock for execution, but that wasn't explicitly allowed anywhere
     QExecNeedLock = 112,
+    /// this query would mutate every row in the model, but neither a limit nor `force` was given
+    QExecDmlUnfilteredMutation = 113,
+    /// a value is smaller than the `min` set for its field
+    QExecDmlCheckMinViolated = 114,
+    /// a value is larger than the `max` set for its field
+    QExecDmlCheckMaxViolated = 115,
+    /// a value is longer than the `maxlen` set for its field
+    QExecDmlCheckMaxLenViolated = 116,
+    /// a value does not match the `regex` set for its field
+    QExecDmlCheckRegexViolated = 117,
+    /// a regex pattern is invalid or exceeds the size limits
+    QExecDmlIllegalPattern = 118,
+    /// the query needs more memory (for its results or other intermediate state) than the configured limit
+    QExecQueryMemoryLimitExceeded = 119,
+    /// the model doesn't keep history, or the requested time is outside its retention window
+    QExecDmlHistoryUnavailable = 120,
+    /// the model is a view, which can only be changed through the model that it is derived from
+    QExecDmlViewIsReadOnly = 121,
+    /// the file of a foreign scan is in a format that can't be read (only `csv` files can be scanned)
+    QExecFileFormatUnsupported = 122,
+    /// the response to the statement is larger than the configured limit (the query should be paginated)
+    QExecResponseTooLarge = 123,
+    /// the connection has been sent more bytes than its quota allows in the current window
+    QExecResponseQuotaExceeded = 124,
+    /// the statement can't be streamed (only a `select all` that isn't sampled or exported as Arrow can be resumed)
+    QExecStreamUnsupported = 125,
+    /// the resume token of a stream is malformed, or was issued for another statement
+    QExecStreamBadToken = 126,
+    /// the statement ran for longer than its timeout (see `set statement_timeout`)
+    QExecStatementTimeout = 127,
+    /// the statement filters on (or calls a function with) a binary field whose values can be moved to the blob tier,
+    /// which it would see the references of (or exports it as Arrow)
+    QExecDmlTieredField = 128,
 }
 
 direct_from! {
@@ -114,6 +168,157 @@ impl From<super::fractal::error::Error> for QueryError {
     }
 }
 
+impl QueryError {
+    /// Returns the category of this error
+    pub const fn category(&self) -> ErrorCategory {
+        match self {
+            Self::SysServerError
+            | Self::SysOutOfMemory
+            | Self::SysUnknownError
+            | Self::SysTransactionalError
+            | Self::SysMemoryPressure
+            | Self::SysStorageDegraded
+            | Self::SysDiskFull
+            | Self::SysFrozen
+            | Self::SysUpstreamError
+            | Self::SysLockWaitTimeout
+            | Self::SysWriteThrottled
+            | Self::SysQuotaExceeded
+            | Self::SysBlobUnavailable => ErrorCategory::System,
+            Self::SysAuthError | Self::SysPermissionDenied => ErrorCategory::Auth,
+            Self::SysNetworkSystemIllegalClientPacket => ErrorCategory::Protocol,
+            Self::LexInvalidInput
+            | Self::LexUnexpectedByte
+            | Self::QLUnexpectedEndOfStatement
+            | Self::QLInvalidSyntax
+            | Self::QLInvalidCollectionSyntax
+            | Self::QLInvalidTypeDefinitionSyntax
+            | Self::QLExpectedEntity
+            | Self::QLExpectedStatement
+            | Self::QLUnknownStatement => ErrorCategory::Syntax,
+            Self::QExecObjectNotFound | Self::QExecDmlRowNotFound => ErrorCategory::NotFound,
+            Self::QExecUnknownField
+            | Self::QExecDdlInvalidProperties
+            | Self::QExecDdlObjectAlreadyExists
+            | Self::QExecDdlNotEmpty
+            | Self::QExecDdlInvalidTypeDefinition
+            | Self::QExecDdlModelBadDefinition
+            | Self::QExecDdlModelAlterIllegal => ErrorCategory::Schema,
+            Self::QExecDmlDuplicate
+            | Self::QExecDmlValidationError
+            | Self::QExecDmlCheckMinViolated
+            | Self::QExecDmlCheckMaxViolated
+            | Self::QExecDmlCheckMaxLenViolated
+            | Self::QExecDmlCheckRegexViolated => ErrorCategory::Constraint,
+            Self::QExecDmlWhereHasUnindexedColumn
+            | Self::QExecFileFormatUnsupported
+            | Self::QExecNeedLock
+            | Self::QExecDmlUnfilteredMutation
+            | Self::QExecDmlIllegalPattern
+            | Self::QExecQueryMemoryLimitExceeded
+            | Self::QExecDmlHistoryUnavailable
+            | Self::QExecDmlViewIsReadOnly
+            | Self::QExecResponseTooLarge
+            | Self::QExecResponseQuotaExceeded
+            | Self::QExecStreamUnsupported
+            | Self::QExecStreamBadToken
+            | Self::QExecStatementTimeout
+            | Self::QExecDmlTieredField => ErrorCategory::Query,
+        }
+    }
+    /// Record a detail about this error (for example, the field that a value was rejected for). The detail is kept
+    /// on the current thread (see [`ErrorDetail`]); only the first value recorded for a key is kept
+    pub fn with_detail(self, key: &'static str, value: impl fmt::Display) -> Self {
+        local_mut!(ERROR_DETAIL, |detail| {
+            if !matches!(detail, Some(d) if d.error == self) {
+                *detail = Some(ErrorDetail::new(self));
+            }
+            let detail = detail.as_mut().unwrap();
+            if !detail.entries.iter().any(|(k, _)| *k == key) {
+                detail
+                    .entries
+                    .push((key, value.to_string().into_boxed_str()));
+            }
+        });
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, sky_macros::EnumMethods)]
+#[repr(u8)]
+/// The class of a [`QueryError`], which lets clients handle errors without knowing every code
+pub enum ErrorCategory {
+    /// the server couldn't run the query (I/O errors, running out of memory and such)
+    System = 0,
+    /// the client isn't allowed to do this
+    Auth = 1,
+    /// the client sent a malformed packet
+    Protocol = 2,
+    /// the query couldn't be parsed
+    Syntax = 3,
+    /// a space, model or row that the query needs doesn't exist
+    NotFound = 4,
+    /// the query doesn't fit the schema, or is an illegal schema change
+    Schema = 5,
+    /// the data was rejected by the model (a duplicate key, a type mismatch or a field check)
+    Constraint = 6,
+    /// the query is valid but can't be run as written
+    Query = 7,
+}
+
+local! {
+    static ERROR_DETAIL: Option<ErrorDetail> = None;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Details about why a query failed, as `key: value` pairs (see [`QueryError::with_detail`]). Details only ever hold
+/// what the query or the schema already told the client (like a field name or the limit set by a check).
+///
+/// Errors are passed around as plain codes, so the detail is kept on the side, on the thread that ran the query, and
+/// is only sent to clients that asked for it in the handshake. It must be cleared before a statement runs (so that a
+/// detail left behind by an earlier statement isn't mistaken for that of this one) and taken right after, on the same
+/// thread
+pub struct ErrorDetail {
+    error: QueryError,
+    entries: Vec<(&'static str, Box<str>)>,
+}
+
+impl ErrorDetail {
+    /// A detail with no entries
+    pub fn new(error: QueryError) -> Self {
+        Self {
+            error,
+            entries: vec![],
+        }
+    }
+    pub fn error(&self) -> QueryError {
+        self.error
+    }
+    pub fn entries(&self) -> &[(&'static str, Box<str>)] {
+        &self.entries
+    }
+    /// Clear the detail recorded on this thread
+    pub fn clear() {
+        local_mut!(ERROR_DETAIL, |detail| *detail = None)
+    }
+    /// Take the detail recorded on this thread for `error`. If none was recorded, a detail with no entries is returned
+    pub fn take(error: QueryError) -> Self {
+        match Self::take_any() {
+            Some(detail) if detail.error == error => detail,
+            _ => Self::new(error),
+        }
+    }
+    /// Take the detail recorded on this thread (whatever error it is for), to carry it over to another thread with
+    /// [`Self::restore`]
+    pub fn take_any() -> Option<Self> {
+        local_mut!(ERROR_DETAIL, |detail| detail.take())
+    }
+    /// Replace the detail recorded on this thread
+    pub fn restore(detail: Option<Self>) {
+        local_mut!(ERROR_DETAIL, |d| *d = detail)
+    }
+}
+
 macro_rules! enumerate_err {
     ($(#[$attr:meta])* $vis:vis enum $errname:ident { $($(#[$varattr:meta])* $variant:ident = $errstring:expr),* $(,)? }) => {
         $(#[$attr])*
@@ -221,5 +426,22 @@ enumerate_err! {
         RawJournalCorrupted = "journal-corrupted",
         RawJournalInvalidEvent = "journal-invalid-event-order",
         RawJournalRuntimeCriticalLwtHBFail = "journal-lwt-heartbeat-failed",
+        // exports
+        /// a schema export is corrupted (or isn't a schema export at all)
+        GNSExportCorrupted = "gns-export-corrupted",
+        /// the jobs file is corrupted
+        JobsFileCorrupted = "jobs-file-corrupted",
+        /// the cursor file of a webhook is corrupted
+        WebhookCursorCorrupted = "webhook-cursor-corrupted",
+        /// the warmup file is corrupted
+        WarmupFileCorrupted = "warmup-file-corrupted",
+        /// the manifest of a snapshot (or the snapshot schedules) is corrupted
+        SnapshotFileCorrupted = "snapshot-file-corrupted",
+        // overflow
+        /// the values of a row couldn't be read back from the overflow store of its model
+        OverflowReadFailed = "overflow-read-failed",
+        // legacy
+        /// the data directory of a 0.7 install (that is being migrated) is corrupted
+        LegacyDataCorrupted = "legacy-data-corrupted",
     }
 }
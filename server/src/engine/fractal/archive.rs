@@ -0,0 +1,186 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    journal archiving
+    ---
+    if an archive directory is configured, the committed part of every journal (the global journal and the batch file
+    of every model partition) is copied into it by a background task that wakes up every ARCHIVE_INTERVAL, so that an
+    external pipeline can ship the journals off the host (or recover from them, see `sky-inspect recover`) without
+    stopping the server. only what was appended since the last pass is copied (see [`JournalArchive`]), and what is
+    read of a journal is read while holding its driver's lock, so that a copy never ends with a partially written
+    batch. journals are copied outside the lock.
+
+    the lag (the bytes committed to journals that weren't archived when the last pass started) and the time of the
+    last pass are shown by `sysctl report storage`. a journal that couldn't be archived is retried by the next pass
+*/
+
+use {
+    super::Global,
+    crate::{
+        engine::{
+            core::EntityIDRef,
+            fractal::GlobalInstanceLike,
+            storage::{encode_str, safe_interfaces::paths_v1, ArchivePending, JournalArchive},
+            RuntimeResult,
+        },
+        util::os,
+    },
+    std::{
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            OnceLock,
+        },
+        time::Duration,
+    },
+    tokio::time,
+};
+
+/// How often the journals are archived
+const ARCHIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+static ARCHIVE: OnceLock<Archive> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct Archive {
+    dir: Box<str>,
+    /// the bytes committed to journals that weren't archived when the last pass started
+    lag: AtomicU64,
+    /// the time (in seconds since the epoch) at which the last pass ended, or zero if there wasn't one yet
+    last_pass: AtomicU64,
+    /// the number of journals that couldn't be archived
+    failures: AtomicU64,
+}
+
+/// Start archiving journals to the given directory in the background
+pub fn start(global: Global, dir: &str) {
+    if ARCHIVE.set(Archive::new(dir)).is_err() {
+        return;
+    }
+    tokio::spawn(async move {
+        loop {
+            time::sleep(ARCHIVE_INTERVAL).await;
+            let global = global.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                if let Some(archive) = get() {
+                    archive.pass(&global);
+                }
+            })
+            .await;
+        }
+    });
+}
+
+/// Returns the archive, if archiving is enabled
+pub fn get() -> Option<&'static Archive> {
+    ARCHIVE.get()
+}
+
+impl Archive {
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: dir.into(),
+            lag: AtomicU64::new(0),
+            last_pass: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+    /// Archive what was committed to every journal since the last pass
+    pub fn pass(&self, global: &impl GlobalInstanceLike) {
+        let mut lag = 0;
+        let gns = JournalArchive::new(&self.dir, &paths_v1::gns_path());
+        let pending = {
+            let _driver = global.state().gns_driver().txn_driver.lock();
+            gns.pending()
+        };
+        lag += self.archive(&gns, pending);
+        let spaces = global.state().namespace().idx().read();
+        let models = global.state().namespace().idx_models().read();
+        for (space_name, space) in spaces.iter() {
+            for model_name in space.models() {
+                let Some(model) = models.get(&EntityIDRef::new(space_name, model_name)) else {
+                    continue;
+                };
+                for partition in 0..model.data().primary_index().partitions() {
+                    let journal = JournalArchive::new(
+                        &self.dir,
+                        &paths_v1::model_partition_path(
+                            space_name,
+                            space.get_uuid(),
+                            model_name,
+                            model.data().get_uuid(),
+                            partition,
+                        ),
+                    );
+                    let pending = {
+                        let _driver = model.driver().batch_driver(partition).lock();
+                        journal.pending()
+                    };
+                    lag += self.archive(&journal, pending);
+                }
+            }
+        }
+        self.lag.store(lag, Ordering::Relaxed);
+        self.last_pass
+            .store(os::get_epoch_time_secs(), Ordering::Relaxed);
+    }
+    /// Archive what was read of a journal, returning the number of bytes that weren't archived before
+    fn archive(
+        &self,
+        journal: &JournalArchive,
+        pending: RuntimeResult<Option<ArchivePending>>,
+    ) -> u64 {
+        let mut len = 0;
+        let r = pending.and_then(|pending| match pending {
+            Some(pending) => {
+                len = pending.len();
+                journal.archive(pending)
+            }
+            None => Ok(()),
+        });
+        if let Err(e) = r {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            warn!("archive: failed to archive {}: {e}", journal.copy_path());
+        }
+        len
+    }
+    /// Returns the bytes committed to journals that weren't archived when the last pass started
+    pub fn lag(&self) -> u64 {
+        self.lag.load(Ordering::Relaxed)
+    }
+    /// Returns the directory, lag, time of the last pass and the number of failures as a JSON object
+    pub fn describe(&self) -> String {
+        let mut ret = String::from("{\"dir\":");
+        encode_str(&mut ret, &self.dir);
+        ret.push_str(&format!(
+            ",\"lag\":{},\"last_pass\":{},\"failures\":{}}}",
+            self.lag(),
+            self.last_pass.load(Ordering::Relaxed),
+            self.failures.load(Ordering::Relaxed)
+        ));
+        ret
+    }
+}
@@ -25,27 +25,46 @@
 */
 
 use {
-    super::{util, GlobalInstanceLike},
+    super::{util, FractalToken, GlobalInstanceLike, ModelUniqueID},
     crate::{
         engine::{
+            core::{index::Row, model::ModelData, session},
             error::{QueryError, QueryResult, RuntimeResult},
-            fractal::{CriticalTask, Task},
-            storage::{GNSDriver, ModelDriver},
+            fractal::{error::Error, CriticalTask, Task},
+            storage::{
+                driver::{GNSJournal, ModelJournal},
+                safe_interfaces::{LoadedRows, StdModelBatch},
+                BatchStats,
+            },
         },
-        util::compiler,
+        util::{compiler, os},
+    },
+    parking_lot::{Mutex, MutexGuard},
+    std::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::{Duration, Instant},
     },
-    parking_lot::Mutex,
 };
 
+/// The longest that a change should wait to be written while a model is under load
+const BATCH_PERSIST_INTERVAL_MAX: Duration = Duration::from_secs(1);
+/// Batches are spaced at least this many times the time it takes to write one apart (so that writing batches takes
+/// up at most about a tenth of the time)
+const BATCH_WRITE_TIME_FACTOR: f64 = 10.0;
+/// The smallest batch size that is picked (fewer changes are left for the background flush)
+const BATCH_SIZE_MIN: usize = 1024;
+/// The weight of the newest observation in the moving averages
+const BATCH_EWMA_WEIGHT: f64 = 0.25;
+
 /// GNS driver
 #[derive(Debug)]
 pub struct FractalGNSDriver {
     status: util::Status,
-    pub(super) txn_driver: Mutex<GNSDriver>,
+    pub(super) txn_driver: Mutex<Box<dyn GNSJournal>>,
 }
 
 impl FractalGNSDriver {
-    pub fn new(txn_driver: GNSDriver) -> Self {
+    pub fn new(txn_driver: Box<dyn GNSJournal>) -> Self {
         Self {
             status: util::Status::new_okay(),
             txn_driver: Mutex::new(txn_driver),
@@ -54,21 +73,31 @@ impl FractalGNSDriver {
     pub(super) fn status(&self) -> &util::Status {
         &self.status
     }
+    /// Returns the journal, locked so that nothing is committed to it until the guard is dropped
+    pub fn lock_journal(&self) -> MutexGuard<'_, Box<dyn GNSJournal>> {
+        self.txn_driver.lock()
+    }
     pub fn driver_context<T>(
         &self,
         g: &impl GlobalInstanceLike,
-        f: impl Fn(&mut GNSDriver) -> RuntimeResult<T>,
+        f: impl Fn(&mut dyn GNSJournal) -> RuntimeResult<T>,
         on_failure: impl Fn(),
     ) -> QueryResult<T> {
+        if let Err(e) = g.health().check_writable() {
+            // undo anything that was done in anticipation of the commit
+            on_failure();
+            return Err(e);
+        }
         if self.status.is_iffy() {
             return Err(QueryError::SysServerError);
         }
         let mut txn_driver = self.txn_driver.lock();
-        match f(&mut txn_driver) {
+        match f(&mut **txn_driver) {
             Ok(v) => Ok(v),
             Err(e) => compiler::cold_call(|| {
                 self.status.set_iffy();
                 g.health().report_fault();
+                g.health().report_write_failure(&e);
                 on_failure();
                 g.taskmgr_post_high_priority(Task::new(CriticalTask::CheckGNSDriver));
                 error!("GNS driver failed with: {e}");
@@ -78,43 +107,327 @@ impl FractalGNSDriver {
     }
 }
 
-/// Model driver
+/// Model driver. Each partition of the model has its own batch driver, so partitions can be flushed independently
 #[derive(Debug)]
 #[must_use]
 pub struct FractalModelDriver {
     status: util::Status,
-    batch_driver: Mutex<Option<ModelDriver>>,
+    batch_drivers: Box<[Mutex<Option<Box<dyn ModelJournal>>>]>,
+    flush_stats: FlushStats,
+}
+
+/// Stats about the batch writes of a model
+#[derive(Debug)]
+pub struct FlushStats {
+    /// the time (in seconds since the epoch) of the last write that succeeded, or zero if there wasn't one yet
+    last_persist: AtomicU64,
+    /// the number of writes that failed
+    failures: AtomicU64,
+    sizer: Mutex<BatchSizer>,
+}
+
+impl FlushStats {
+    fn new() -> Self {
+        Self {
+            last_persist: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            sizer: Mutex::new(BatchSizer::new()),
+        }
+    }
+    /// Returns the time (in seconds since the epoch) of the last write that succeeded, if there was one
+    pub fn last_persist(&self) -> Option<u64> {
+        match self.last_persist.load(Ordering::Acquire) {
+            0 => None,
+            t => Some(t),
+        }
+    }
+    pub fn failures(&self) -> u64 {
+        self.failures.load(Ordering::Acquire)
+    }
+    /// Returns the batch size that was picked last, the write throughput (changes per second) and the time it takes
+    /// to write a batch (in microseconds), if a batch was written yet
+    pub fn batch_sizing(&self) -> Option<(usize, u64, u64)> {
+        let sizer = self.sizer.lock();
+        sizer.size.map(|size| {
+            (
+                size,
+                sizer.throughput as u64,
+                (sizer.write_time * 1_000_000.0) as u64,
+            )
+        })
+    }
+}
+
+/// Picks the number of changes after which a model's changes are written out as a batch (rather than waiting for the
+/// background flush), from the model's recent write throughput and how long its batches take to write (which is
+/// mostly the time it takes to sync them).
+///
+/// Under load, batches are written so that a change waits at most [`BATCH_PERSIST_INTERVAL_MAX`] to be written,
+/// unless writing a batch is slow, in which case batches are spaced out so that writing them doesn't take up more
+/// than about a tenth of the time. The cache capacity still applies on top of this
+#[derive(Debug)]
+pub(super) struct BatchSizer {
+    /// the time at which the last batch was written
+    last_write: Instant,
+    /// the moving average of changes written per second
+    throughput: f64,
+    /// the moving average of the time it takes to write a batch, in seconds
+    write_time: f64,
+    /// the batch size that was picked, if a batch was written yet
+    size: Option<usize>,
+}
+
+impl BatchSizer {
+    pub(super) fn new() -> Self {
+        Self {
+            last_write: Instant::now(),
+            throughput: 0.0,
+            write_time: 0.0,
+            size: None,
+        }
+    }
+    /// Note that `changes` changes were written in a batch (that took `write_time` to write), `since_last` after the
+    /// batch before it was written. Returns the batch size to use from now on
+    pub(super) fn observe(
+        &mut self,
+        changes: usize,
+        since_last: Duration,
+        write_time: Duration,
+    ) -> usize {
+        let throughput = changes as f64 / since_last.as_secs_f64().max(f64::EPSILON);
+        let write_time = write_time.as_secs_f64();
+        (self.throughput, self.write_time) = match self.size {
+            None => (throughput, write_time),
+            Some(_) => (
+                ewma(self.throughput, throughput),
+                ewma(self.write_time, write_time),
+            ),
+        };
+        let interval = (self.write_time * BATCH_WRITE_TIME_FACTOR)
+            .max(BATCH_PERSIST_INTERVAL_MAX.as_secs_f64());
+        let size = ((self.throughput * interval) as usize).max(BATCH_SIZE_MIN);
+        self.size = Some(size);
+        size
+    }
+    fn observe_now(&mut self, changes: usize, write_time: Duration) -> usize {
+        let now = Instant::now();
+        let since_last = now.saturating_duration_since(self.last_write);
+        self.last_write = now;
+        self.observe(changes, since_last, write_time)
+    }
+}
+
+fn ewma(average: f64, observed: f64) -> f64 {
+    average + BATCH_EWMA_WEIGHT * (observed - average)
 }
 
 impl FractalModelDriver {
-    pub const fn uninitialized() -> Self {
+    pub fn uninitialized(partitions: usize) -> Self {
         Self {
             status: util::Status::new_okay(),
-            batch_driver: Mutex::new(None),
+            batch_drivers: (0..partitions).map(|_| Mutex::new(None)).collect(),
+            flush_stats: FlushStats::new(),
         }
     }
-    pub fn initialize_model_driver(&self, driver: ModelDriver) {
-        let mut drv = self.batch_driver.lock();
-        if drv.is_none() {
-            *drv = Some(driver);
-        } else {
-            panic!("driver already initialized")
+    /// Initialize the batch drivers (one for each partition, in order)
+    pub fn initialize_model_drivers(&self, drivers: Vec<Box<dyn ModelJournal>>) {
+        assert_eq!(drivers.len(), self.batch_drivers.len());
+        for (drv, driver) in self.batch_drivers.iter().zip(drivers) {
+            let mut drv = drv.lock();
+            if drv.is_none() {
+                *drv = Some(driver);
+            } else {
+                panic!("driver already initialized")
+            }
         }
     }
-    pub(in crate::engine::fractal) fn init(batch_driver: ModelDriver) -> Self {
+    pub(in crate::engine::fractal) fn init(batch_drivers: Vec<Box<dyn ModelJournal>>) -> Self {
         Self {
             status: util::Status::new_okay(),
-            batch_driver: Mutex::new(Some(batch_driver)),
+            batch_drivers: batch_drivers
+                .into_iter()
+                .map(|drv| Mutex::new(Some(drv)))
+                .collect(),
+            flush_stats: FlushStats::new(),
         }
     }
     pub fn status(&self) -> &util::Status {
         &self.status
     }
-    /// Returns a reference to the batch persist driver
-    pub fn batch_driver(&self) -> &Mutex<Option<ModelDriver>> {
-        &self.batch_driver
+    pub fn flush_stats(&self) -> &FlushStats {
+        &self.flush_stats
+    }
+    /// Returns a reference to the batch persist driver of the given partition
+    pub fn batch_driver(&self, partition: usize) -> &Mutex<Option<Box<dyn ModelJournal>>> {
+        &self.batch_drivers[partition]
+    }
+    /// Returns the number of batch drivers (one for each partition)
+    pub fn partitions(&self) -> usize {
+        self.batch_drivers.len()
+    }
+    /// Write the deltas that were handed over to the flusher, as one batch for each partition. If a batch can't be
+    /// written, the deltas of the partition that weren't written are handed back so that the next attempt picks
+    /// them up, and the stats of the failed batch are returned
+    pub fn commit_taken_deltas(&self, model: &ModelData) -> Result<(), (Error, BatchStats)> {
+        self.commit_taken_deltas_by(model, None).map(|_| ())
+    }
+    /// Like [`Self::commit_taken_deltas`], but gives up on waiting for a partition's driver (which another flush might
+    /// be holding) at the `deadline`. Returns false if it gave up, handing the deltas of the partitions that weren't
+    /// written back as pending (for the flusher)
+    fn commit_taken_deltas_by(
+        &self,
+        model: &ModelData,
+        deadline: Option<Instant>,
+    ) -> Result<bool, (Error, BatchStats)> {
+        let (mut written, mut write_time) = (0, Duration::ZERO);
+        let mut finished = true;
+        for (partition, drv) in self.batch_drivers.iter().enumerate() {
+            // the deltas are claimed under the lock so that anyone holding the lock can rely on the pending count
+            let mut drv = match deadline {
+                Some(deadline) => match drv.try_lock_until(deadline) {
+                    Some(drv) => drv,
+                    None => {
+                        // the flusher only looks at the pending deltas, so hand the rest back
+                        for partition in partition..self.batch_drivers.len() {
+                            model
+                                .delta_state()
+                                .__fractal_hand_back_partition(partition, FractalToken::new());
+                        }
+                        finished = false;
+                        break;
+                    }
+                },
+                None => drv.lock(),
+            };
+            let expected = model
+                .delta_state()
+                .__fractal_take_partition(partition, FractalToken::new());
+            if expected == 0 {
+                continue;
+            }
+            let batch_stats = BatchStats::new();
+            let start = Instant::now();
+            if let Err(e) = drv.as_mut().unwrap().commit_batch(
+                StdModelBatch::new(model, partition, expected),
+                batch_stats.clone(),
+            ) {
+                let batch_stats = BatchStats::into_inner(batch_stats);
+                model.delta_state().__fractal_untake_partition(
+                    partition,
+                    expected - batch_stats.get_actual(),
+                    FractalToken::new(),
+                );
+                self.flush_stats.failures.fetch_add(1, Ordering::AcqRel);
+                return Err((e, batch_stats));
+            }
+            write_time += start.elapsed();
+            written += expected;
+        }
+        if written != 0 {
+            self.flush_stats
+                .last_persist
+                .store(os::get_epoch_time_secs(), Ordering::Release);
+            let batch_size = self
+                .flush_stats
+                .sizer
+                .lock()
+                .observe_now(written, write_time);
+            model
+                .delta_state()
+                .__fractal_set_batch_size(batch_size, FractalToken::new());
+        }
+        Ok(finished)
+    }
+    /// Hand over all pending deltas to the flusher and write them right away. If the write fails, the driver is
+    /// marked as dirty (and recovered in the background) and the deltas are left for the next flush. A statement with
+    /// a timeout (see [`session`]) doesn't wait past it for the driver, and fails with `QExecStatementTimeout` (the
+    /// deltas that weren't written are then left for the flusher)
+    pub fn flush(
+        &self,
+        g: &impl GlobalInstanceLike,
+        mdl_id: ModelUniqueID,
+        model: &ModelData,
+    ) -> QueryResult<()> {
+        if self.status.is_iffy() {
+            return Err(QueryError::SysServerError);
+        }
+        model
+            .delta_state()
+            .__fractal_take_full_from_data_delta(FractalToken::new());
+        let deadline = session::deadline();
+        match self.commit_taken_deltas_by(model, deadline.map(|(deadline, _)| deadline)) {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                let (_, timeout_ms) = deadline.unwrap();
+                Err(QueryError::QExecStatementTimeout.with_detail("timeout_ms", timeout_ms))
+            }
+            Err((e, _)) => compiler::cold_call(|| {
+                error!("failed to flush model {mdl_id} with {e}");
+                self.report_persist_failure(g, mdl_id, &e);
+                Err(QueryError::SysServerError)
+            }),
+        }
+    }
+    /// Write rows that were loaded in bulk (and that have no deltas) as a single batch of the given partition. The
+    /// pending deltas have to be flushed first, so that the rows come after them in the journal. If the write fails,
+    /// the driver is marked as dirty (just like when a batch of deltas can't be written)
+    pub fn commit_loaded(
+        &self,
+        g: &impl GlobalInstanceLike,
+        mdl_id: ModelUniqueID,
+        model: &ModelData,
+        partition: usize,
+        rows: &[Row],
+    ) -> QueryResult<()> {
+        if self.status.is_iffy() {
+            return Err(QueryError::SysServerError);
+        }
+        let r = self.batch_drivers[partition]
+            .lock()
+            .as_mut()
+            .unwrap()
+            .commit_loaded(LoadedRows::new(model, rows));
+        match r {
+            Ok(()) => {
+                self.flush_stats
+                    .last_persist
+                    .store(os::get_epoch_time_secs(), Ordering::Release);
+                Ok(())
+            }
+            Err(e) => compiler::cold_call(|| {
+                error!("failed to load rows into model {mdl_id} with {e}");
+                self.flush_stats.failures.fetch_add(1, Ordering::AcqRel);
+                self.report_persist_failure(g, mdl_id, &e);
+                Err(QueryError::SysServerError)
+            }),
+        }
+    }
+    /// Mark the driver as dirty because a batch couldn't be written, and have it recovered in the background. Until
+    /// then, the instance is degraded and writes to the model are handled as set by the
+    /// [`FlushPolicy`](super::FlushPolicy)
+    pub(super) fn report_persist_failure(
+        &self,
+        g: &impl GlobalInstanceLike,
+        mdl_id: ModelUniqueID,
+        e: &Error,
+    ) {
+        g.health().report_write_failure(e);
+        if self.status.change(false) {
+            g.health().report_degraded();
+            g.taskmgr_post_high_priority(Task::new(CriticalTask::TryModelAutorecoverLWT(mdl_id)));
+        }
+    }
+    /// The driver was recovered, so batches can be written again
+    pub(super) fn report_persist_recovery(&self, g: &impl GlobalInstanceLike) {
+        g.health().report_write_success();
+        if self.status.change(true) {
+            g.health().report_undegraded();
+        }
     }
     pub fn close(self) -> RuntimeResult<()> {
-        ModelDriver::close_driver(&mut self.batch_driver.into_inner().unwrap())
+        for drv in self.batch_drivers.into_vec() {
+            drv.into_inner().unwrap().close()?;
+        }
+        Ok(())
     }
 }
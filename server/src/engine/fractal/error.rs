@@ -55,6 +55,10 @@ impl Error {
     pub fn kind(&self) -> &ErrorKind {
         &self.kind
     }
+    /// Returns true if this error was caused by the disk being full
+    pub fn is_disk_full(&self) -> bool {
+        matches!(&self.kind, ErrorKind::IoError(e) if e.is_disk_full())
+    }
     /// Replace the origin in self
     pub fn add_origin(self, origin: Subsystem) -> Self {
         Self::_new(self.kind, Some(origin), self.dmsg)
@@ -0,0 +1,479 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    jobs
+    ---
+    work that takes too long to be done while a client waits is run as a job: an index build or a model verification
+    started with `in background` (on a thread of its own) and the rebuild of secondary indexes on boot. a statement
+    that starts a job only does what has to be done under the DDL lock and responds with the job's id. every job has:
+    - a record, which is what `sysctl list jobs` shows: its kind and target, who started it (system jobs have no
+    owner), how far along it is (in units of work, like rows, out of the total that was known when it started) and how
+    it ended. records are persisted to the jobs file (see [`JobsFile`]) whenever a job starts or ends, so they outlive
+    a restart. a job that was still running when the server went down comes back as interrupted; its work isn't
+    resumed, but whatever it left behind is redone on boot (like an index build) or was never visible
+    - a cancellation token (see [`CancelToken`]), which the loops doing its work check every few units.
+    `sysctl cancel job <id>` cancels the token and the job undoes what it did (a cancelled index build drops the index)
+    - a notice (see [`Notice`]) for its owner once it ends, which is sent along with the response to their next
+    statement
+    only the last MAX_FINISHED records of jobs that are done are kept
+*/
+
+use {
+    crate::{
+        engine::{
+            core::notice::{Notice, NoticeCode},
+            error::{ErrorKind, QueryError, QueryResult, StorageError},
+            fractal::GlobalInstanceLike,
+            storage::{encode_str, JobsFile},
+            RuntimeResult,
+        },
+        util::os,
+    },
+    parking_lot::Mutex,
+    std::sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// The number of records of jobs that are done that are kept around for `sysctl list jobs`
+pub const MAX_FINISHED: usize = 64;
+
+/// A flag that long running loops check to find out if they should stop. Clones share the flag
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release)
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, sky_macros::EnumMethods)]
+#[repr(u8)]
+/// What a job does. Like error codes, these are stable since they are persisted
+pub enum JobKind {
+    /// `create index ... in background`
+    BuildIndex = 0,
+    /// `sysctl verify model ... in background`
+    VerifyModel = 1,
+    /// the rebuild of secondary indexes on boot
+    RebuildIndexes = 2,
+    /// `sysctl copy model ...`
+    CopyModel = 3,
+    /// `sysctl import rdb ...`
+    ImportRdb = 4,
+    /// `sysctl create snapshot ...` (and every snapshot that a schedule takes)
+    Snapshot = 5,
+    /// `sysctl restore model ...`
+    RestoreModel = 6,
+    /// `sysctl export models ...`
+    ExportModels = 7,
+    /// `sysctl relocate data ...`
+    RelocateData = 8,
+    /// `sysctl backfill model ...`
+    BackfillModel = 9,
+}
+
+impl JobKind {
+    pub fn try_from_u8(v: u8) -> Option<Self> {
+        Some(match v {
+            0 => Self::BuildIndex,
+            1 => Self::VerifyModel,
+            2 => Self::RebuildIndexes,
+            3 => Self::CopyModel,
+            4 => Self::ImportRdb,
+            5 => Self::Snapshot,
+            6 => Self::RestoreModel,
+            7 => Self::ExportModels,
+            8 => Self::RelocateData,
+            9 => Self::BackfillModel,
+            _ => return None,
+        })
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BuildIndex => "build index",
+            Self::VerifyModel => "verify model",
+            Self::RebuildIndexes => "rebuild indexes",
+            Self::CopyModel => "copy model",
+            Self::ImportRdb => "import rdb",
+            Self::Snapshot => "snapshot",
+            Self::RestoreModel => "restore model",
+            Self::ExportModels => "export models",
+            Self::RelocateData => "relocate data",
+            Self::BackfillModel => "backfill model",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    Running,
+    /// the job is done, with an optional result (as JSON)
+    Completed(Option<String>),
+    /// the job failed with the given error code
+    Failed(u8),
+    Cancelled,
+    /// the server went down while the job was running
+    Interrupted,
+}
+
+impl JobStatus {
+    pub fn failed(e: QueryError) -> Self {
+        Self::Failed(e.value_u8())
+    }
+    fn is_running(&self) -> bool {
+        matches!(self, Self::Running)
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed(_) => "completed",
+            Self::Failed(_) => "failed",
+            Self::Cancelled => "cancelled",
+            Self::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// A snapshot of a job, which is what is persisted and listed
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobRecord {
+    pub id: u64,
+    pub kind: JobKind,
+    /// the user that started the job (if it wasn't started by the system)
+    pub owner: Option<Box<str>>,
+    /// what the job works on (like `space.model.index`)
+    pub target: Box<str>,
+    /// the time (in seconds since the epoch) at which the job was started
+    pub started_at: u64,
+    /// the time (in seconds since the epoch) at which the job ended
+    pub finished_at: Option<u64>,
+    /// the units of work done so far
+    pub done: u64,
+    /// the units of work that the job was expected to do (or zero if that isn't known)
+    pub total: u64,
+    pub status: JobStatus,
+}
+
+impl JobRecord {
+    /// Returns how far along the job is, as a percentage
+    pub fn percent(&self) -> u64 {
+        match self.status {
+            JobStatus::Completed(_) => 100,
+            _ if self.total == 0 => 0,
+            // rows can be added while the job runs
+            _ => (self.done.saturating_mul(100) / self.total).min(100),
+        }
+    }
+    fn describe(&self, out: &mut String) {
+        out.push_str(&format!("{{\"id\":{},\"kind\":", self.id));
+        encode_str(out, self.kind.name());
+        out.push_str(",\"target\":");
+        encode_str(out, &self.target);
+        out.push_str(",\"owner\":");
+        match &self.owner {
+            Some(owner) => encode_str(out, owner),
+            None => out.push_str("null"),
+        }
+        out.push_str(&format!(
+            ",\"started_at\":{},\"finished_at\":",
+            self.started_at
+        ));
+        match self.finished_at {
+            Some(at) => out.push_str(&at.to_string()),
+            None => out.push_str("null"),
+        }
+        out.push_str(&format!(
+            ",\"done\":{},\"total\":{},\"progress\":{},",
+            self.done,
+            self.total,
+            self.percent()
+        ));
+        match &self.status {
+            JobStatus::Running => out.push_str("\"status\":\"running\""),
+            JobStatus::Completed(result) => {
+                out.push_str("\"status\":\"completed\",\"result\":");
+                out.push_str(result.as_deref().unwrap_or("null"))
+            }
+            JobStatus::Failed(e) => out.push_str(&format!("\"status\":\"failed\",\"error\":{e}")),
+            JobStatus::Cancelled => out.push_str("\"status\":\"cancelled\""),
+            JobStatus::Interrupted => out.push_str("\"status\":\"interrupted\""),
+        }
+        out.push('}');
+    }
+}
+
+#[derive(Debug)]
+pub struct Job {
+    id: u64,
+    kind: JobKind,
+    owner: Option<Box<str>>,
+    target: Box<str>,
+    started_at: u64,
+    done: AtomicU64,
+    total: AtomicU64,
+    token: CancelToken,
+    /// the status and the time at which the job ended
+    state: Mutex<(JobStatus, Option<u64>)>,
+}
+
+impl Job {
+    fn from_record(r: JobRecord) -> Self {
+        Self {
+            id: r.id,
+            kind: r.kind,
+            owner: r.owner,
+            target: r.target,
+            started_at: r.started_at,
+            done: AtomicU64::new(r.done),
+            total: AtomicU64::new(r.total),
+            token: CancelToken::default(),
+            state: Mutex::new((r.status, r.finished_at)),
+        }
+    }
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+    pub fn status(&self) -> JobStatus {
+        self.state.lock().0.clone()
+    }
+    /// Returns the units of work done so far
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+    pub fn set_done(&self, done: u64) {
+        self.done.store(done, Ordering::Relaxed)
+    }
+    pub fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed)
+    }
+    /// Returns the token that the loops doing the job's work should check
+    pub fn token(&self) -> &CancelToken {
+        &self.token
+    }
+    fn is_running(&self) -> bool {
+        self.state.lock().0.is_running()
+    }
+    pub fn record(&self) -> JobRecord {
+        let (status, finished_at) = self.state.lock().clone();
+        JobRecord {
+            id: self.id,
+            kind: self.kind,
+            owner: self.owner.clone(),
+            target: self.target.clone(),
+            started_at: self.started_at,
+            finished_at,
+            done: self.done(),
+            total: self.total.load(Ordering::Relaxed),
+            status,
+        }
+    }
+}
+
+/// The jobs of this instance (see the module docs)
+#[derive(Debug, Default)]
+pub struct Jobs {
+    /// the jobs file (if the records are persisted)
+    path: Option<Box<str>>,
+    next_id: AtomicU64,
+    jobs: Mutex<Vec<Arc<Job>>>,
+    /// notices for the owners of jobs that ended, which are yet to be sent
+    notices: Mutex<Vec<(Box<str>, Notice)>>,
+}
+
+impl Jobs {
+    /// Load the records in the jobs file at `path` (if there is one), marking the jobs that were still running as
+    /// interrupted. A file that is corrupted (or from another version) is ignored and replaced once a job starts, since
+    /// records aren't worth refusing to boot over
+    pub fn load(path: &str) -> RuntimeResult<Self> {
+        let records = JobsFile::read(path).or_else(|e| match e.kind() {
+            ErrorKind::Storage(
+                StorageError::JobsFileCorrupted | StorageError::HeaderDecodeVersionMismatch,
+            ) => {
+                warn!("ignoring the jobs file {path}, so past jobs won't be listed: {e}");
+                Ok(vec![])
+            }
+            _ => Err(e),
+        })?;
+        let next_id = records.iter().map(|r| r.id).max().unwrap_or(0);
+        let jobs = records
+            .into_iter()
+            .map(|mut r| {
+                if r.status.is_running() {
+                    r.status = JobStatus::Interrupted;
+                }
+                Arc::new(Job::from_record(r))
+            })
+            .collect();
+        Ok(Self {
+            path: Some(path.into()),
+            next_id: AtomicU64::new(next_id),
+            jobs: Mutex::new(jobs),
+            notices: Mutex::new(vec![]),
+        })
+    }
+    pub fn get(&self, id: u64) -> Option<Arc<Job>> {
+        self.jobs.lock().iter().find(|job| job.id == id).cloned()
+    }
+    /// Ask a job to stop. Returns false if the job is already done
+    pub fn cancel(&self, id: u64) -> QueryResult<bool> {
+        let Some(job) = self.get(id) else {
+            return Err(QueryError::QExecObjectNotFound.with_detail("job", id));
+        };
+        if !job.is_running() {
+            return Ok(false);
+        }
+        job.token.cancel();
+        Ok(true)
+    }
+    /// Returns all records (oldest first)
+    pub fn records(&self) -> Vec<JobRecord> {
+        self.jobs.lock().iter().map(|job| job.record()).collect()
+    }
+    /// Returns all records (oldest first) as a JSON array
+    pub fn describe(&self) -> String {
+        let mut out = String::from("[");
+        for (i, job) in self.jobs.lock().iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            job.record().describe(&mut out);
+        }
+        out.push(']');
+        out
+    }
+    /// Raise the notices for the jobs of the given user that ended since their last statement
+    pub fn raise_notices(&self, username: &str) {
+        let mut notices = self.notices.lock();
+        if notices.is_empty() {
+            return;
+        }
+        notices.retain(|(owner, notice)| {
+            let mine = owner.as_ref() == username;
+            if mine {
+                Notice::raise(notice.code(), notice.message());
+            }
+            !mine
+        })
+    }
+    fn register(&self, owner: Option<&str>, kind: JobKind, target: Box<str>) -> Arc<Job> {
+        let job = Arc::new(Job::from_record(JobRecord {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            kind,
+            owner: owner.map(Into::into),
+            target,
+            started_at: os::get_epoch_time_secs(),
+            finished_at: None,
+            done: 0,
+            total: 0,
+            status: JobStatus::Running,
+        }));
+        let mut jobs = self.jobs.lock();
+        // forget the oldest jobs that are done
+        let mut finished = jobs.iter().filter(|job| !job.is_running()).count();
+        jobs.retain(|job| {
+            let forget = (finished > MAX_FINISHED) && !job.is_running();
+            finished -= forget as usize;
+            !forget
+        });
+        jobs.push(job.clone());
+        self.persist(&jobs);
+        job
+    }
+    fn finish(&self, job: &Job, status: JobStatus) {
+        let message = match &status {
+            JobStatus::Completed(_) => "completed".to_owned(),
+            JobStatus::Failed(e) => format!("failed with error {e}"),
+            _ => "was cancelled".to_owned(),
+        };
+        *job.state.lock() = (status, Some(os::get_epoch_time_secs()));
+        if let Some(owner) = &job.owner {
+            let mut notices = self.notices.lock();
+            // an owner that never runs another statement shouldn't make these pile up
+            if notices.len() >= MAX_FINISHED {
+                notices.remove(0);
+            }
+            notices.push((
+                owner.clone(),
+                Notice::new(
+                    NoticeCode::JobFinished,
+                    format!(
+                        "job {} ({} {}) {message}",
+                        job.id,
+                        job.kind.name(),
+                        job.target
+                    ),
+                ),
+            ));
+        }
+        self.persist(&self.jobs.lock());
+    }
+    /// Write out the records of the given jobs. The records are only informational, so a failure is just logged
+    fn persist(&self, jobs: &[Arc<Job>]) {
+        if let Some(path) = &self.path {
+            let records: Vec<JobRecord> = jobs.iter().map(|job| job.record()).collect();
+            if let Err(e) = JobsFile::write(path, &records) {
+                warn!("failed to write the jobs file {path}: {e}");
+            }
+        }
+    }
+}
+
+/// Start a job that runs `f` in the background and return its id. The job ends with whatever `f` returns
+pub fn start<G: GlobalInstanceLike>(
+    global: &G,
+    owner: Option<&str>,
+    kind: JobKind,
+    target: Box<str>,
+    f: impl FnOnce(&G, &Job) -> JobStatus + Send + 'static,
+) -> u64 {
+    let job = global.state().jobs().register(owner, kind, target);
+    let id = job.id;
+    global.spawn_job(Box::new(move |global| {
+        let status = f(global, &job);
+        global.state().jobs().finish(&job, status);
+    }));
+    id
+}
+
+/// Run `f` as a job on this thread (for work that is already in the background), returning what it returned
+pub fn run<G: GlobalInstanceLike>(
+    global: &G,
+    kind: JobKind,
+    target: Box<str>,
+    f: impl FnOnce(&G, &Job) -> JobStatus,
+) -> JobStatus {
+    let job = global.state().jobs().register(None, kind, target);
+    let status = f(global, &job);
+    global.state().jobs().finish(&job, status.clone());
+    status
+}
@@ -0,0 +1,1129 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    kafka sinks
+    ---
+    a webhook whose url is `kafka[s]://[<user>@]<host>:<port>[,<host>:<port>...]/<topic>[?sasl=<mechanism>]`
+    publishes its events to the topic instead of POSTing them. this is a minimal producer that only speaks as much of
+    the Kafka protocol as it needs:
+
+    - `kafkas` brokers are connected to over TLS, and verified against the system's certificate authorities
+    - a url with a user authenticates every connection with SASL, using the webhook's secret as the password. the
+    mechanism is `scram-sha-512` (the default), `scram-sha-256` or `plain`, and `plain` is only allowed over TLS since
+    it sends the password as is. a user without a password (or the other way round) is rejected when the webhook is
+    created (or loaded)
+    - every event is keyed by its primary key (the JSON value of its `key`), and goes to the partition that Kafka's
+    default partitioner would pick for that key (murmur2), so that the events of a row stay in the order they were
+    committed
+    - the leaders of the topic's partitions are found with a `Metadata` (v4) request to the first broker that
+    answers, and are used until a request fails. topics aren't created automatically
+    - events are published with a `Produce` (v3) request to every leader, holding an uncompressed record batch (v2)
+    for every partition it leads, and wait for all in-sync replicas (`acks: -1`). if any request fails (or is
+    rejected) all the events are retried like any other webhook request, so events are published at least once
+*/
+
+use {
+    crc::{Crc, CRC_32_ISCSI},
+    openssl::{
+        base64,
+        hash::{self, MessageDigest},
+        memcmp, pkcs5,
+        pkey::PKey,
+        rand::rand_bytes,
+        sign::Signer,
+        ssl::{SslConnector, SslMethod},
+    },
+    parking_lot::Mutex,
+    std::{
+        io::{self, Read, Write},
+        net::{TcpStream, ToSocketAddrs},
+        time::Duration,
+    },
+};
+
+/// CRC-32C, which record batches are checksummed with
+const CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+/// How long we wait to connect, and for every read or write (the broker waits up to this long for replicas, too)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The largest response that we accept
+const RESPONSE_MAX: usize = 1 << 20;
+const CLIENT_ID: &str = "skyd";
+const API_PRODUCE: i16 = 0;
+const API_METADATA: i16 = 3;
+const API_SASL_HANDSHAKE: i16 = 17;
+const API_SASL_AUTHENTICATE: i16 = 36;
+/// The longest topic name that Kafka accepts
+const TOPIC_MAX: usize = 249;
+/// The fewest SCRAM iterations that we accept from a broker (and the fewest that Kafka allows)
+const SCRAM_MIN_ITERATIONS: usize = 4096;
+
+#[derive(Debug)]
+/// The brokers and topic that a webhook publishes to
+pub struct KafkaEndpoint {
+    tls: bool,
+    sasl: Option<Sasl>,
+    brokers: Vec<(Box<str>, u16)>,
+    topic: Box<str>,
+    /// the leaders of the topic's partitions (by partition), once found
+    leaders: Mutex<Option<Vec<(Box<str>, u16)>>>,
+}
+
+#[derive(Debug, PartialEq)]
+/// The credentials that connections are authenticated with
+struct Sasl {
+    mechanism: Mechanism,
+    user: Box<str>,
+    password: Box<str>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Mechanism {
+    Plain,
+    ScramSha256,
+    ScramSha512,
+}
+
+impl Mechanism {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "plain" => Some(Self::Plain),
+            "scram-sha-256" => Some(Self::ScramSha256),
+            "scram-sha-512" => Some(Self::ScramSha512),
+            _ => None,
+        }
+    }
+    /// The name that brokers know the mechanism by
+    fn name(self) -> &'static str {
+        match self {
+            Self::Plain => "PLAIN",
+            Self::ScramSha256 => "SCRAM-SHA-256",
+            Self::ScramSha512 => "SCRAM-SHA-512",
+        }
+    }
+}
+
+impl KafkaEndpoint {
+    pub const SCHEME: &'static str = "kafka://";
+    pub const SCHEME_TLS: &'static str = "kafkas://";
+    /// Returns true if the url is meant for a kafka sink
+    pub fn is_kafka(url: &str) -> bool {
+        url.starts_with(Self::SCHEME) | url.starts_with(Self::SCHEME_TLS)
+    }
+    /// Parse a `kafka[s]://[<user>@]<host>:<port>[,<host>:<port>...]/<topic>[?sasl=<mechanism>]` URL, with the
+    /// password of the user (empty if there's no user)
+    pub fn parse(url: &str, password: &str) -> Option<Self> {
+        if !url.bytes().all(|b| b.is_ascii_graphic()) {
+            return None;
+        }
+        let (tls, rest) = if let Some(rest) = url.strip_prefix(Self::SCHEME) {
+            (false, rest)
+        } else if let Some(rest) = url.strip_prefix(Self::SCHEME_TLS) {
+            (true, rest)
+        } else {
+            return None;
+        };
+        let (rest, mechanism) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(Mechanism::parse(query.strip_prefix("sasl=")?)?)),
+            None => (rest, None),
+        };
+        let (user, rest) = match rest.rsplit_once('@') {
+            Some((user, rest)) => (Some(user), rest),
+            None => (None, rest),
+        };
+        let sasl = match user {
+            // the password is the webhook's secret, so it never ends up in the url
+            Some(user) if !user.is_empty() & !user.contains(':') & !password.is_empty() => {
+                let mechanism = mechanism.unwrap_or(Mechanism::ScramSha512);
+                if (mechanism == Mechanism::Plain) & !tls {
+                    return None;
+                }
+                Some(Sasl {
+                    mechanism,
+                    user: user.into(),
+                    password: password.into(),
+                })
+            }
+            None if mechanism.is_none() & password.is_empty() => None,
+            _ => return None,
+        };
+        let (brokers, topic) = rest.split_once('/')?;
+        // the characters that Kafka allows in topic names
+        let valid_topic = topic
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() | matches!(b, b'.' | b'_' | b'-'));
+        if topic.is_empty() | (topic.len() > TOPIC_MAX) | !valid_topic {
+            return None;
+        }
+        let brokers = brokers
+            .split(',')
+            .map(|broker| {
+                let (host, port) = broker.rsplit_once(':')?;
+                let port = port.parse().ok().filter(|port| *port != 0)?;
+                if host.is_empty() | host.contains(['@', '[', ']']) {
+                    return None;
+                }
+                Some((host.into(), port))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            tls,
+            sasl,
+            brokers,
+            topic: topic.into(),
+            leaders: Mutex::new(None),
+        })
+    }
+    /// Publish the events (JSON lines) to the topic, in a record batch for every partition
+    pub fn produce(&self, events: &[&str]) -> io::Result<()> {
+        let leaders = self.leaders.lock().clone();
+        let leaders = match leaders {
+            Some(leaders) => leaders,
+            None => {
+                let leaders = self.find_leaders()?;
+                *self.leaders.lock() = Some(leaders.clone());
+                leaders
+            }
+        };
+        let r = self.produce_to(&leaders, events);
+        if r.is_err() {
+            // the leaders could have moved (or partitions were added)
+            *self.leaders.lock() = None;
+        }
+        r
+    }
+    /// Ask the brokers (in order, until one answers) for the leaders of the topic's partitions
+    fn find_leaders(&self) -> io::Result<Vec<(Box<str>, u16)>> {
+        let mut body = Encoder::default();
+        body.i32(1);
+        body.str(&self.topic);
+        body.i8(0); // allow_auto_topic_creation
+        let mut last_error = None;
+        for (host, port) in &self.brokers {
+            match self
+                .connect(host, *port)
+                .and_then(|mut connection| connection.request(API_METADATA, 4, &body.0))
+            {
+                Ok(response) => return leaders_of(&response, &self.topic),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap())
+    }
+    fn produce_to(&self, leaders: &[(Box<str>, u16)], events: &[&str]) -> io::Result<()> {
+        // the records of every partition, in the order they were committed
+        let mut partitions = vec![vec![]; leaders.len()];
+        for &event in events {
+            let key = event_key(event);
+            let partition = key.map_or(0, |key| partition_of(key.as_bytes(), leaders.len()));
+            partitions[partition].push((key, event));
+        }
+        // the partitions that every leader is sent
+        let mut requests: Vec<(&(Box<str>, u16), Vec<usize>)> = vec![];
+        for (partition, _) in partitions.iter().enumerate().filter(|(_, r)| !r.is_empty()) {
+            let leader = &leaders[partition];
+            match requests.iter_mut().find(|(l, _)| *l == leader) {
+                Some((_, led)) => led.push(partition),
+                None => requests.push((leader, vec![partition])),
+            }
+        }
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        for ((host, port), led) in requests {
+            let mut body = Encoder::default();
+            body.i16(-1); // transactional_id
+            body.i16(-1); // acks (all in-sync replicas)
+            body.i32(REQUEST_TIMEOUT.as_millis() as i32);
+            body.i32(1);
+            body.str(&self.topic);
+            body.i32(led.len() as i32);
+            for &partition in &led {
+                let batch = encode_batch(&partitions[partition], timestamp);
+                body.i32(partition as i32);
+                body.i32(batch.len() as i32);
+                body.raw(&batch);
+            }
+            let response = self
+                .connect(host, *port)?
+                .request(API_PRODUCE, 3, &body.0)?;
+            check_produced(&response, &self.topic, &led)?;
+        }
+        Ok(())
+    }
+    /// Connect to the broker (over TLS, if the endpoint wants it) and authenticate
+    fn connect(&self, host: &str, port: u16) -> io::Result<Connection> {
+        let addr = (host, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "kafka broker not found"))?;
+        let stream = TcpStream::connect_timeout(&addr, REQUEST_TIMEOUT)?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+        let stream: Box<dyn Stream> = if self.tls {
+            let connector = SslConnector::builder(SslMethod::tls())
+                .map_err(ssl_error)?
+                .build();
+            Box::new(connector.connect(host, stream).map_err(ssl_error)?)
+        } else {
+            Box::new(stream)
+        };
+        let mut connection = Connection {
+            stream,
+            correlation_id: 0,
+        };
+        if let Some(sasl) = &self.sasl {
+            connection.authenticate(sasl)?;
+        }
+        Ok(connection)
+    }
+}
+
+/// A stream that requests can be sent over (plain TCP, or TLS)
+trait Stream: Read + Write {}
+impl<S: Read + Write> Stream for S {}
+
+/// A connection to a broker
+struct Connection {
+    stream: Box<dyn Stream>,
+    correlation_id: i32,
+}
+
+impl Connection {
+    /// Send a request to the broker, returning the response (without its header)
+    fn request(&mut self, api_key: i16, api_version: i16, body: &[u8]) -> io::Result<Vec<u8>> {
+        self.correlation_id += 1;
+        let mut request = Encoder::default();
+        request.i32(0); // the size, set below
+        request.i16(api_key);
+        request.i16(api_version);
+        request.i32(self.correlation_id);
+        request.str(CLIENT_ID);
+        request.raw(body);
+        let size = (request.0.len() - 4) as i32;
+        request.0[..4].copy_from_slice(&size.to_be_bytes());
+        self.stream.write_all(&request.0)?;
+        self.stream.flush()?;
+        let mut size = [0u8; 4];
+        self.stream.read_exact(&mut size)?;
+        let size = i32::from_be_bytes(size);
+        if (size < 4) | (size as usize > RESPONSE_MAX) {
+            return Err(invalid_response());
+        }
+        let mut response = vec![0; size as usize];
+        self.stream.read_exact(&mut response)?;
+        if Decoder::new(&response).i32()? != self.correlation_id {
+            return Err(invalid_response());
+        }
+        response.drain(..4);
+        Ok(response)
+    }
+    /// Authenticate with a `SaslHandshake` (v1) request, followed by `SaslAuthenticate` (v0) requests
+    fn authenticate(&mut self, sasl: &Sasl) -> io::Result<()> {
+        let mut body = Encoder::default();
+        body.str(sasl.mechanism.name());
+        let response = self.request(API_SASL_HANDSHAKE, 1, &body.0)?;
+        // the broker doesn't have the mechanism enabled if this fails
+        kafka_error(Decoder::new(&response).i16()?)?;
+        let digest = match sasl.mechanism {
+            Mechanism::Plain => {
+                let message = format!("\0{}\0{}", sasl.user, sasl.password);
+                self.sasl_authenticate(message.as_bytes())?;
+                return Ok(());
+            }
+            Mechanism::ScramSha256 => MessageDigest::sha256(),
+            Mechanism::ScramSha512 => MessageDigest::sha512(),
+        };
+        let mut nonce = [0u8; 24];
+        rand_bytes(&mut nonce).map_err(ssl_error)?;
+        let mut scram = Scram::new(digest, &sasl.user, base64::encode_block(&nonce));
+        let server_first = self.sasl_authenticate(scram.client_first().as_bytes())?;
+        let client_final = scram.client_final(&sasl.password, &server_first)?;
+        let server_final = self.sasl_authenticate(client_final.as_bytes())?;
+        scram.verify(&server_final)
+    }
+    /// Send a message of the SASL exchange, returning the broker's reply
+    fn sasl_authenticate(&mut self, message: &[u8]) -> io::Result<String> {
+        let mut body = Encoder::default();
+        body.bytes(message);
+        let response = self.request(API_SASL_AUTHENTICATE, 0, &body.0)?;
+        let mut d = Decoder::new(&response);
+        let error = d.i16()?;
+        let error_message = d.nullable_str()?;
+        if error != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "kafka authentication failed with error code {error}: {}",
+                    error_message.unwrap_or_default()
+                ),
+            ));
+        }
+        String::from_utf8(d.bytes()?).map_err(|_| invalid_response())
+    }
+}
+
+/// The client side of a SCRAM exchange (RFC 5802, without channel binding)
+struct Scram {
+    digest: MessageDigest,
+    nonce: String,
+    client_first_bare: String,
+    /// the signature that the broker has to send back, once the proof was sent
+    server_signature: Vec<u8>,
+}
+
+impl Scram {
+    fn new(digest: MessageDigest, user: &str, nonce: String) -> Self {
+        let user = user.replace('=', "=3D").replace(',', "=2C");
+        Self {
+            digest,
+            client_first_bare: format!("n={user},r={nonce}"),
+            nonce,
+            server_signature: vec![],
+        }
+    }
+    fn client_first(&self) -> String {
+        format!("n,,{}", self.client_first_bare)
+    }
+    /// Returns the final message (with the proof) for the broker's first message
+    fn client_final(&mut self, password: &str, server_first: &str) -> io::Result<String> {
+        let mut attributes = server_first.split(',');
+        let mut attribute = |name: &str| attributes.next().and_then(|a| a.strip_prefix(name));
+        let (Some(nonce), Some(salt), Some(iterations)) =
+            (attribute("r="), attribute("s="), attribute("i="))
+        else {
+            return Err(scram_error());
+        };
+        let salt = base64::decode_block(salt).map_err(|_| scram_error())?;
+        let iterations = iterations
+            .parse()
+            .ok()
+            .filter(|i| *i >= SCRAM_MIN_ITERATIONS)
+            .ok_or_else(scram_error)?;
+        // the broker's nonce extends ours
+        if !nonce.starts_with(&self.nonce) | (nonce.len() == self.nonce.len()) {
+            return Err(scram_error());
+        }
+        let without_proof = format!("c=biws,r={nonce}");
+        let auth_message = format!("{},{server_first},{without_proof}", self.client_first_bare);
+        let mut salted = vec![0; self.digest.size()];
+        pkcs5::pbkdf2_hmac(
+            password.as_bytes(),
+            &salt,
+            iterations,
+            self.digest,
+            &mut salted,
+        )
+        .map_err(ssl_error)?;
+        let client_key = hmac(self.digest, &salted, b"Client Key");
+        let stored_key = hash::hash(self.digest, &client_key).map_err(ssl_error)?;
+        let client_signature = hmac(self.digest, &stored_key, auth_message.as_bytes());
+        let proof: Vec<u8> = client_key
+            .iter()
+            .zip(&client_signature)
+            .map(|(k, s)| k ^ s)
+            .collect();
+        let server_key = hmac(self.digest, &salted, b"Server Key");
+        self.server_signature = hmac(self.digest, &server_key, auth_message.as_bytes());
+        Ok(format!(
+            "{without_proof},p={}",
+            base64::encode_block(&proof)
+        ))
+    }
+    /// Check that the broker's final message proves that it knows the password, too
+    fn verify(&self, server_final: &str) -> io::Result<()> {
+        match server_final
+            .strip_prefix("v=")
+            .and_then(|signature| base64::decode_block(signature).ok())
+        {
+            Some(signature)
+                if (signature.len() == self.server_signature.len())
+                    && memcmp::eq(&signature, &self.server_signature) =>
+            {
+                Ok(())
+            }
+            _ => Err(scram_error()),
+        }
+    }
+}
+
+fn hmac(digest: MessageDigest, key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = PKey::hmac(key).unwrap();
+    let mut signer = Signer::new(digest, &key).unwrap();
+    signer.sign_oneshot_to_vec(data).unwrap()
+}
+
+/// Returns the key of an event: the JSON value of its `key` (an integer or a string)
+fn event_key(event: &str) -> Option<&str> {
+    let (_, rest) = event.split_once(",\"key\":")?;
+    let len = if let Some(string) = rest.strip_prefix('"') {
+        let mut escaped = false;
+        let end = string.find(|c| {
+            let end = (c == '"') & !escaped;
+            escaped = (c == '\\') & !escaped;
+            end
+        })?;
+        end + 2
+    } else {
+        rest.find([',', '}'])?
+    };
+    Some(&rest[..len]).filter(|key| !key.is_empty())
+}
+
+/// Returns the partition for the key, like Kafka's default partitioner does
+fn partition_of(key: &[u8], partitions: usize) -> usize {
+    (murmur2(key) & 0x7fffffff) as usize % partitions
+}
+
+/// The 32-bit murmur2 hash that Kafka partitions keys with
+fn murmur2(data: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+    let mut h = 0x9747b28c ^ data.len() as u32;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes(chunk.try_into().unwrap()).wrapping_mul(M);
+        k ^= k >> 24;
+        h = h.wrapping_mul(M) ^ k.wrapping_mul(M);
+    }
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        for (i, b) in rest.iter().enumerate() {
+            h ^= (*b as u32) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> 13;
+    h = h.wrapping_mul(M);
+    h ^ (h >> 15)
+}
+
+/// Returns the leaders of the topic's partitions (by partition) from a `Metadata` (v4) response
+fn leaders_of(response: &[u8], topic: &str) -> io::Result<Vec<(Box<str>, u16)>> {
+    let mut d = Decoder::new(response);
+    d.i32()?; // throttle_time_ms
+    let mut brokers = vec![];
+    for _ in 0..d.array_len()? {
+        let node_id = d.i32()?;
+        let host = d.str()?;
+        let port = d.i32()?;
+        d.nullable_str()?; // rack
+        brokers.push((node_id, host, port));
+    }
+    d.nullable_str()?; // cluster_id
+    d.i32()?; // controller_id
+    let mut found = None;
+    for _ in 0..d.array_len()? {
+        let error = d.i16()?;
+        let name = d.str()?;
+        d.i8()?; // is_internal
+        let mut partitions = vec![];
+        for _ in 0..d.array_len()? {
+            let partition_error = d.i16()?;
+            let index = d.i32()?;
+            let leader_id = d.i32()?;
+            d.i32_array()?; // replica_nodes
+            d.i32_array()?; // isr_nodes
+            partitions.push((index, partition_error, leader_id));
+        }
+        if name == topic {
+            found = Some(kafka_error(error).map(|_| partitions));
+        }
+    }
+    let mut partitions = found.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("kafka topic `{topic}` not found"),
+        )
+    })??;
+    if partitions.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("kafka topic `{topic}` has no partitions"),
+        ));
+    }
+    partitions.sort_unstable_by_key(|(index, _, _)| *index);
+    partitions
+        .into_iter()
+        .enumerate()
+        .map(|(partition, (index, error, leader_id))| {
+            if index != partition as i32 {
+                return Err(invalid_response());
+            }
+            kafka_error(error)?;
+            brokers
+                .iter()
+                .find(|(node_id, _, _)| *node_id == leader_id)
+                .and_then(|(_, host, port)| {
+                    Some((host.as_str().into(), u16::try_from(*port).ok()?))
+                })
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("partition {partition} of kafka topic `{topic}` has no leader"),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Check a `Produce` (v3) response for errors, and that every partition that was sent was acknowledged
+fn check_produced(response: &[u8], topic: &str, partitions: &[usize]) -> io::Result<()> {
+    let mut d = Decoder::new(response);
+    let mut produced = 0;
+    for _ in 0..d.array_len()? {
+        let name = d.str()?;
+        for _ in 0..d.array_len()? {
+            let index = d.i32()?;
+            let error = d.i16()?;
+            d.i64()?; // base_offset
+            d.i64()?; // log_append_time_ms
+            kafka_error(error)?;
+            if (name == topic) & partitions.contains(&(index as usize)) {
+                produced += 1;
+            }
+        }
+    }
+    if produced == partitions.len() {
+        Ok(())
+    } else {
+        Err(invalid_response())
+    }
+}
+
+fn kafka_error(code: i16) -> io::Result<()> {
+    match code {
+        0 => Ok(()),
+        code => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("kafka responded with error code {code}"),
+        )),
+    }
+}
+
+fn invalid_response() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "kafka broker sent an invalid response",
+    )
+}
+
+fn scram_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "kafka broker sent an invalid SCRAM message",
+    )
+}
+
+fn ssl_error(e: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// Encode a record batch (v2, uncompressed) with a record for every (key, event)
+fn encode_batch(records: &[(Option<&str>, &str)], timestamp: i64) -> Vec<u8> {
+    let mut encoded = Encoder::default();
+    for (i, (key, event)) in records.iter().enumerate() {
+        let mut record = Encoder::default();
+        record.i8(0); // attributes
+        record.varint(0); // timestamp delta
+        record.varint(i as i64); // offset delta
+        match key {
+            Some(key) => {
+                record.varint(key.len() as i64);
+                record.raw(key.as_bytes());
+            }
+            None => record.varint(-1),
+        }
+        record.varint(event.len() as i64);
+        record.raw(event.as_bytes());
+        record.varint(0); // headers
+        encoded.varint(record.0.len() as i64);
+        encoded.raw(&record.0);
+    }
+    // the CRC covers everything from the attributes on
+    let mut tail = Encoder::default();
+    tail.i16(0); // attributes
+    tail.i32(records.len() as i32 - 1); // last offset delta
+    tail.i64(timestamp); // base timestamp
+    tail.i64(timestamp); // max timestamp
+    tail.i64(-1); // producer id
+    tail.i16(-1); // producer epoch
+    tail.i32(-1); // base sequence
+    tail.i32(records.len() as i32);
+    tail.raw(&encoded.0);
+    let mut batch = Encoder::default();
+    batch.i64(0); // base offset
+    batch.i32((4 + 1 + 4 + tail.0.len()) as i32); // batch length (from the leader epoch on)
+    batch.i32(-1); // partition leader epoch
+    batch.i8(2); // magic
+    batch.0.extend(CRC32C.checksum(&tail.0).to_be_bytes());
+    batch.raw(&tail.0);
+    batch.0
+}
+
+#[derive(Default)]
+/// Big-endian encoding of the protocol's types
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn raw(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+    fn i8(&mut self, v: i8) {
+        self.0.extend(v.to_be_bytes());
+    }
+    fn i16(&mut self, v: i16) {
+        self.0.extend(v.to_be_bytes());
+    }
+    fn i32(&mut self, v: i32) {
+        self.0.extend(v.to_be_bytes());
+    }
+    fn i64(&mut self, v: i64) {
+        self.0.extend(v.to_be_bytes());
+    }
+    fn str(&mut self, s: &str) {
+        self.i16(s.len() as i16);
+        self.raw(s.as_bytes());
+    }
+    fn bytes(&mut self, b: &[u8]) {
+        self.i32(b.len() as i32);
+        self.raw(b);
+    }
+    /// A zigzag encoded varint (or varlong)
+    fn varint(&mut self, v: i64) {
+        let mut v = ((v << 1) ^ (v >> 63)) as u64;
+        while v >= 0x80 {
+            self.0.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        self.0.push(v as u8);
+    }
+}
+
+/// Big-endian decoding of the protocol's types, failing if the response is cut short
+struct Decoder<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+    fn take<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        if self.buf.len() < N {
+            return Err(invalid_response());
+        }
+        let (head, rest) = self.buf.split_at(N);
+        self.buf = rest;
+        Ok(head.try_into().unwrap())
+    }
+    fn i8(&mut self) -> io::Result<i8> {
+        self.take().map(i8::from_be_bytes)
+    }
+    fn i16(&mut self) -> io::Result<i16> {
+        self.take().map(i16::from_be_bytes)
+    }
+    fn i32(&mut self) -> io::Result<i32> {
+        self.take().map(i32::from_be_bytes)
+    }
+    fn i64(&mut self) -> io::Result<i64> {
+        self.take().map(i64::from_be_bytes)
+    }
+    /// The length of an array
+    fn array_len(&mut self) -> io::Result<usize> {
+        match self.i32()? {
+            // a null array is an empty one
+            -1 => Ok(0),
+            len if len < 0 => Err(invalid_response()),
+            len => Ok(len as usize),
+        }
+    }
+    fn slice(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.buf.len() < len {
+            return Err(invalid_response());
+        }
+        let (s, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(s)
+    }
+    fn nullable_str(&mut self) -> io::Result<Option<String>> {
+        let len = self.i16()?;
+        if len < 0 {
+            return Ok(None);
+        }
+        String::from_utf8(self.slice(len as usize)?.to_vec())
+            .map(Some)
+            .map_err(|_| invalid_response())
+    }
+    fn str(&mut self) -> io::Result<String> {
+        self.nullable_str()?.ok_or_else(invalid_response)
+    }
+    /// Bytes (null ones are empty)
+    fn bytes(&mut self) -> io::Result<Vec<u8>> {
+        let len = self.array_len()?;
+        self.slice(len).map(<[u8]>::to_vec)
+    }
+    fn i32_array(&mut self) -> io::Result<()> {
+        for _ in 0..self.array_len()? {
+            self.i32()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{
+            encode_batch, event_key, murmur2, partition_of, Decoder, Encoder, KafkaEndpoint,
+            Mechanism, Sasl, Scram, CRC32C,
+        },
+        openssl::hash::MessageDigest,
+        std::{
+            io::{Read, Write},
+            net::TcpListener,
+            sync::mpsc,
+            thread,
+        },
+    };
+
+    #[test]
+    fn endpoints() {
+        let endpoint =
+            KafkaEndpoint::parse("kafka://localhost:9092,10.0.0.2:9093/sky.changes_v1", "")
+                .unwrap();
+        assert_eq!(
+            endpoint.brokers,
+            vec![("localhost".into(), 9092), ("10.0.0.2".into(), 9093)]
+        );
+        assert_eq!(&*endpoint.topic, "sky.changes_v1");
+        assert!(!endpoint.tls & endpoint.sasl.is_none());
+        // SCRAM-SHA-512 unless the url says otherwise
+        let endpoint = KafkaEndpoint::parse("kafka://sky@localhost:9092/changes", "pw").unwrap();
+        assert_eq!(
+            endpoint.sasl,
+            Some(Sasl {
+                mechanism: Mechanism::ScramSha512,
+                user: "sky".into(),
+                password: "pw".into()
+            })
+        );
+        let endpoint =
+            KafkaEndpoint::parse("kafkas://sky@localhost:9093/changes?sasl=plain", "pw").unwrap();
+        assert!(endpoint.tls);
+        assert_eq!(endpoint.sasl.unwrap().mechanism, Mechanism::Plain);
+        let endpoint = KafkaEndpoint::parse(
+            "kafka://sky@example.com@localhost:9092/changes?sasl=scram-sha-256",
+            "pw",
+        )
+        .unwrap();
+        let sasl = endpoint.sasl.unwrap();
+        assert_eq!(
+            (sasl.mechanism, &*sasl.user),
+            (Mechanism::ScramSha256, "sky@example.com")
+        );
+        for (url, password) in [
+            ("kafka://localhost:9092", ""),
+            ("kafka://localhost:9092/", ""),
+            ("kafka://localhost/changes", ""),
+            ("kafka://localhost:0/changes", ""),
+            ("kafka://:9092/changes", ""),
+            ("kafka://localhost:9092,/changes", ""),
+            ("kafka://localhost:9092/sky/changes", ""),
+            ("kafka://localhost:9092/sky changes", ""),
+            ("http://localhost:9092/changes", ""),
+            // a password needs a user, and the other way round
+            ("kafka://localhost:9092/changes", "pw"),
+            ("kafka://sky@localhost:9092/changes", ""),
+            ("kafka://@localhost:9092/changes", "pw"),
+            // the password only goes in the secret
+            ("kafka://sky:pw@localhost:9092/changes", "pw"),
+            // unknown (or unauthenticated) mechanisms
+            ("kafka://sky@localhost:9092/changes?sasl=gssapi", "pw"),
+            ("kafka://sky@localhost:9092/changes?mechanism=plain", "pw"),
+            ("kafka://localhost:9092/changes?sasl=plain", ""),
+            // the password would be sent in the clear
+            ("kafka://sky@localhost:9092/changes?sasl=plain", "pw"),
+        ] {
+            assert!(KafkaEndpoint::parse(url, password).is_none(), "{url}");
+        }
+    }
+
+    #[test]
+    fn varints() {
+        let mut e = Encoder::default();
+        for v in [0, -1, 1, 150, -65] {
+            e.varint(v);
+        }
+        assert_eq!(e.0, [0x00, 0x01, 0x02, 0xac, 0x02, 0x81, 0x01]);
+        // the check value of CRC-32C
+        assert_eq!(CRC32C.checksum(b"123456789"), 0xe3069283);
+    }
+
+    #[test]
+    fn partitions() {
+        // the values that Kafka's own tests check
+        for (key, hash) in [
+            ("21", -973932308),
+            ("foobar", -790332482),
+            ("a-little-bit-long-string", -985981536),
+            ("a-little-bit-longer-string", -1486304829),
+            (
+                "lkjh234lh9fiuh90y23oiuhsafujhadof229phr9h19h89h8",
+                -58897971,
+            ),
+            ("abc", 479470107),
+        ] {
+            assert_eq!(murmur2(key.as_bytes()) as i32, hash, "{key}");
+        }
+        assert_eq!(partition_of(b"1", 2), 1);
+        assert_eq!(partition_of(b"2", 2), 0);
+        assert_eq!(
+            event_key(r#"{"v":1,"lsn":4,"op":"update","key":"say\"an\\","row":{}}"#),
+            Some(r#""say\"an\\""#)
+        );
+        assert_eq!(event_key(r#"{"lsn":4,"key":-12}"#), Some("-12"));
+        assert_eq!(event_key(r#"{"lsn":4,"key":"a,}"}"#), Some(r#""a,}""#));
+        assert_eq!(event_key(r#"{"lsn":4}"#), None);
+        assert_eq!(event_key(r#"{"lsn":4,"key":"a"#), None);
+    }
+
+    #[test]
+    fn scram() {
+        // the example exchange of RFC 7677
+        let mut scram = Scram::new(
+            MessageDigest::sha256(),
+            "user",
+            "rOprNGfwEbeRWgbNEkqO".into(),
+        );
+        assert_eq!(scram.client_first(), "n,,n=user,r=rOprNGfwEbeRWgbNEkqO");
+        let server_first = "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096";
+        assert_eq!(
+            scram.client_final("pencil", server_first).unwrap(),
+            "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ="
+        );
+        scram
+            .verify("v=6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")
+            .unwrap();
+        assert!(scram
+            .verify("v=7rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=")
+            .is_err());
+        assert!(scram.verify("e=invalid-proof").is_err());
+        // the broker has to extend our nonce, and can't ask for too few iterations
+        for server_first in [
+            "r=rOprNGfwEbeRWgbNEkqO,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+            "r=other%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096",
+            "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=1",
+            "r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,i=4096",
+        ] {
+            assert!(scram.client_final("pencil", server_first).is_err());
+        }
+        // user names are escaped
+        let scram = Scram::new(MessageDigest::sha512(), "a=b,c", "n".into());
+        assert_eq!(scram.client_first(), "n,,n=a=3Db=2Cc,r=n");
+    }
+
+    fn varint(d: &mut Decoder) -> i64 {
+        let (mut v, mut shift) = (0u64, 0);
+        loop {
+            let [b] = d.take::<1>().unwrap();
+            v |= ((b & 0x7f) as u64) << shift;
+            shift += 7;
+            if b & 0x80 == 0 {
+                break;
+            }
+        }
+        (v >> 1) as i64 ^ -((v & 1) as i64)
+    }
+
+    fn nullable_string(d: &mut Decoder) -> Option<String> {
+        match varint(d) {
+            -1 => None,
+            len => Some(String::from_utf8(d.slice(len as usize).unwrap().to_vec()).unwrap()),
+        }
+    }
+
+    /// Decode a record batch, returning the keys and values of its records
+    fn decode_batch(batch: &[u8]) -> Vec<(Option<String>, String)> {
+        let mut d = Decoder::new(batch);
+        assert_eq!(d.i64().unwrap(), 0);
+        assert_eq!(d.i32().unwrap() as usize, batch.len() - 12);
+        assert_eq!(d.i32().unwrap(), -1);
+        assert_eq!(d.i8().unwrap(), 2);
+        assert_eq!(d.i32().unwrap() as u32, CRC32C.checksum(&batch[21..]));
+        assert_eq!(d.i16().unwrap(), 0);
+        let last_offset_delta = d.i32().unwrap();
+        let timestamp = d.i64().unwrap();
+        assert_eq!(d.i64().unwrap(), timestamp);
+        assert_eq!(
+            (d.i64().unwrap(), d.i16().unwrap(), d.i32().unwrap()),
+            (-1, -1, -1)
+        );
+        let count = d.i32().unwrap();
+        assert_eq!(last_offset_delta, count - 1);
+        let records = (0..count)
+            .map(|i| {
+                let len = varint(&mut d) as usize;
+                let rest = d.buf.len();
+                assert_eq!(d.i8().unwrap(), 0);
+                assert_eq!(varint(&mut d), 0);
+                assert_eq!(varint(&mut d), i as i64);
+                let key = nullable_string(&mut d);
+                let value = nullable_string(&mut d).unwrap();
+                assert_eq!(varint(&mut d), 0);
+                assert_eq!(rest - d.buf.len(), len);
+                (key, value)
+            })
+            .collect();
+        assert!(d.buf.is_empty());
+        records
+    }
+
+    #[test]
+    fn record_batch() {
+        let records = [
+            (Some("1"), "{\"lsn\":1,\"key\":1}"),
+            (None, ""),
+            (Some("\"a\""), "{\"lsn\":1,\"op\":\"delete\",\"key\":\"a\"}"),
+        ];
+        let expected: Vec<_> = records
+            .iter()
+            .map(|(key, value)| (key.map(String::from), value.to_string()))
+            .collect();
+        assert_eq!(
+            decode_batch(&encode_batch(&records, 1_700_000_000_000)),
+            expected
+        );
+    }
+
+    /// The records of every partition in a produce request
+    type Produced = Vec<(i32, Vec<(Option<String>, String)>)>;
+
+    /// A broker that leads both partitions of `changes`, sending the records produced to it
+    fn broker(connections: usize) -> (u16, mpsc::Receiver<Produced>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let mut stream = stream.unwrap();
+                let mut size = [0u8; 4];
+                stream.read_exact(&mut size).unwrap();
+                let mut request = vec![0; i32::from_be_bytes(size) as usize];
+                stream.read_exact(&mut request).unwrap();
+                let mut d = Decoder::new(&request);
+                let api_key = d.i16().unwrap();
+                d.i16().unwrap();
+                let correlation_id = d.i32().unwrap();
+                assert_eq!(d.str().unwrap(), "skyd");
+                let mut response = Encoder::default();
+                response.i32(correlation_id);
+                if api_key == 3 {
+                    response.i32(0);
+                    response.i32(1);
+                    response.i32(7);
+                    response.str("127.0.0.1");
+                    response.i32(port as i32);
+                    response.i16(-1);
+                    response.i16(-1);
+                    response.i32(7);
+                    response.i32(1);
+                    response.i16(0);
+                    response.str("changes");
+                    response.i8(0);
+                    response.i32(2);
+                    for partition in [1, 0] {
+                        response.i16(0);
+                        response.i32(partition);
+                        response.i32(7);
+                        response.i32(-1);
+                        response.i32(-1);
+                    }
+                } else {
+                    assert_eq!(d.i16().unwrap(), -1);
+                    assert_eq!(d.i16().unwrap(), -1);
+                    d.i32().unwrap();
+                    assert_eq!(d.array_len().unwrap(), 1);
+                    assert_eq!(d.str().unwrap(), "changes");
+                    let mut produced = vec![];
+                    for _ in 0..d.array_len().unwrap() {
+                        let partition = d.i32().unwrap();
+                        let len = d.i32().unwrap() as usize;
+                        produced.push((partition, decode_batch(d.slice(len).unwrap())));
+                    }
+                    response.i32(1);
+                    response.str("changes");
+                    response.i32(produced.len() as i32);
+                    for (partition, _) in &produced {
+                        response.i32(*partition);
+                        response.i16(0);
+                        response.i64(0);
+                        response.i64(-1);
+                    }
+                    response.i32(0);
+                    tx.send(produced).unwrap();
+                }
+                stream
+                    .write_all(&(response.0.len() as i32).to_be_bytes())
+                    .unwrap();
+                stream.write_all(&response.0).unwrap();
+            }
+        });
+        (port, rx)
+    }
+
+    fn records(records: &[(&str, &str)]) -> Vec<(Option<String>, String)> {
+        records
+            .iter()
+            .map(|(key, value)| (Some(key.to_string()), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn produce() {
+        let (port, rx) = broker(4);
+        let endpoint =
+            KafkaEndpoint::parse(&format!("kafka://127.0.0.1:{port}/changes"), "").unwrap();
+        let (a, b, c) = (
+            r#"{"lsn":1,"key":1}"#,
+            r#"{"lsn":1,"key":2}"#,
+            r#"{"lsn":2,"key":1}"#,
+        );
+        // the events of a key go to the same partition, in order
+        endpoint.produce(&[a, b, c]).unwrap();
+        assert_eq!(
+            rx.recv().unwrap(),
+            [
+                (0, records(&[("2", b)])),
+                (1, records(&[("1", a), ("1", c)]))
+            ]
+        );
+        // the leaders are remembered, and only the partitions with events are sent
+        endpoint.produce(&[c]).unwrap();
+        assert_eq!(rx.recv().unwrap(), [(1, records(&[("1", c)]))]);
+        // unknown topics are reported
+        let endpoint =
+            KafkaEndpoint::parse(&format!("kafka://127.0.0.1:{port}/other"), "").unwrap();
+        assert!(endpoint.produce(&["{\"key\":1}"]).is_err());
+    }
+}
@@ -29,22 +29,18 @@ use {
     crate::{
         engine::{
             core::{
-                model::{delta::DataDelta, ModelData},
+                dml,
+                model::{delta::DataDelta, props::ModelProps, Model, ModelData},
                 EntityIDRef,
             },
-            data::uuid::Uuid,
             error::ErrorKind,
-            fractal::GlobalInstanceLike,
-            storage::{
-                safe_interfaces::{paths_v1, StdModelBatch},
-                BatchStats,
-            },
+            fractal::{jobs::CancelToken, trace::Span, GlobalInstanceLike, WorkClass},
+            storage::{safe_interfaces::FileSystem, BatchStats},
         },
         util::os,
     },
-    std::{path::PathBuf, time::Duration},
+    std::time::{Duration, Instant},
     tokio::{
-        fs,
         sync::{
             broadcast,
             mpsc::{UnboundedReceiver, UnboundedSender},
@@ -56,6 +52,7 @@ use {
 pub const GENERAL_EXECUTOR_WINDOW: u64 = 5 * 60;
 const TASK_THRESHOLD: usize = 10;
 const TASK_FAILURE_SLEEP_DURATION: u64 = 30;
+const TASK_FAILURE_MAX_SLEEP_DURATION: u64 = 5 * 60;
 
 /// A task for the [`FractalMgr`] to perform
 #[derive(Debug)]
@@ -77,36 +74,36 @@ impl<T> Task<T> {
     pub fn into_task(self) -> T {
         self.task
     }
+    #[cfg(test)]
+    pub fn task(&self) -> &T {
+        &self.task
+    }
+    /// Returns how long the executor should wait before running this task. Only tasks that are being retried
+    /// (and hence had their threshold adjusted) are delayed, and every retry waits twice as long as the one before
+    /// it (up to a limit)
+    pub(super) fn backoff(&self) -> Option<Duration> {
+        match TASK_THRESHOLD.saturating_sub(self.threshold) {
+            0 => None,
+            retries => Some(Duration::from_secs(
+                (TASK_FAILURE_SLEEP_DURATION << (retries - 1)).min(TASK_FAILURE_MAX_SLEEP_DURATION),
+            )),
+        }
+    }
     async fn sleep(&self) {
-        if self.threshold != TASK_THRESHOLD {
-            tokio::time::sleep(Duration::from_secs(TASK_FAILURE_SLEEP_DURATION)).await
+        if let Some(dur) = self.backoff() {
+            tokio::time::sleep(dur).await
         }
     }
 }
 
 /// A general task
+#[derive(Debug)]
 pub enum GenericTask {
     #[allow(unused)]
     /// Delete a single file
-    DeleteFile(PathBuf),
+    DeleteFile(Box<str>),
     /// Delete a directory (and all its children)
-    DeleteDirAll(PathBuf),
-}
-
-impl GenericTask {
-    pub fn delete_model_dir(
-        space_name: &str,
-        space_uuid: Uuid,
-        model_name: &str,
-        model_uuid: Uuid,
-    ) -> Self {
-        Self::DeleteDirAll(
-            paths_v1::model_dir(space_name, space_uuid, model_name, model_uuid).into(),
-        )
-    }
-    pub fn delete_space_dir(space_name: &str, space_uuid: Uuid) -> Self {
-        Self::DeleteDirAll(paths_v1::space_dir(space_name, space_uuid).into())
-    }
+    DeleteDirAll(Box<str>),
 }
 
 /// A critical task
@@ -124,6 +121,8 @@ pub(super) struct FractalMgr {
     hp_dispatcher: UnboundedSender<Task<CriticalTask>>,
     general_dispatcher: UnboundedSender<Task<GenericTask>>,
     runtime_stats: FractalRTStat,
+    /// cancelled once we're shutting down, so that sweeps don't hold up the last flush
+    shutdown: CancelToken,
 }
 
 pub(super) struct FractalRTStat {
@@ -160,8 +159,13 @@ impl FractalMgr {
             hp_dispatcher,
             general_dispatcher,
             runtime_stats: FractalRTStat::init(model_count),
+            shutdown: CancelToken::default(),
         }
     }
+    /// Stop any sweep that is running (and skip the ones to come), so that only the flushes are left
+    pub(super) fn begin_shutdown(&self) {
+        self.shutdown.cancel()
+    }
     pub fn get_rt_stat(&self) -> &FractalRTStat {
         &self.runtime_stats
     }
@@ -286,14 +290,14 @@ impl FractalMgr {
                     info!("fhp: finishing pending tasks");
                     while let Ok(task) = receiver.try_recv() {
                         let global = global.clone();
-                        tokio::task::spawn_blocking(move || self.hp_executor(global, task)).await.unwrap()
+                        tokio::task::spawn_blocking(move || self.hp_executor(&global, task)).await.unwrap()
                     }
                     info!("fhp: exited executor service");
                     break;
                 }
             };
             let global = global.clone();
-            tokio::task::spawn_blocking(move || self.hp_executor(global, task))
+            tokio::task::spawn_blocking(move || self.hp_executor(&global, task))
                 .await
                 .unwrap()
         }
@@ -314,31 +318,29 @@ impl FractalMgr {
             ))
             .unwrap()
     }
-    fn hp_executor(
-        &'static self,
-        global: super::Global,
+    pub(super) fn hp_executor(
+        &self,
+        global: &impl GlobalInstanceLike,
         Task { threshold, task }: Task<CriticalTask>,
     ) {
         // TODO(@ohsayan): check threshold and update hooks
         match task {
             CriticalTask::CheckGNSDriver => {
                 info!("trying to autorecover GNS driver");
-                match global
-                    .state()
-                    .gns_driver()
-                    .txn_driver
-                    .lock()
-                    .__lwt_heartbeat()
-                {
+                match global.state().gns_driver().txn_driver.lock().recover() {
                     Ok(()) => {
                         info!("GNS driver has been successfully auto-recovered");
                         global.state().gns_driver().status().set_okay();
                         global.health().report_recovery();
+                        global.health().report_write_success();
                     }
                     Err(e) => {
                         error!("failed to autorecover GNS driver with error `{e}`. will try again");
                         self.hp_dispatcher
-                            .send(Task::new(CriticalTask::CheckGNSDriver))
+                            .send(Task::with_threshold(
+                                CriticalTask::CheckGNSDriver,
+                                Self::adjust_threshold(threshold),
+                            ))
                             .unwrap();
                     }
                 }
@@ -353,18 +355,26 @@ impl FractalMgr {
                     .get(&EntityIDRef::new(mdl_id.space(), mdl_id.model()))
                 {
                     Some(mdl) if mdl.data().get_uuid() == mdl_id.uuid() => {
-                        let mut drv = mdl.driver().batch_driver().lock();
-                        let drv = drv.as_mut().unwrap();
-                        match drv.__lwt_heartbeat() {
+                        let heartbeat = (0..mdl.driver().partitions()).try_for_each(|partition| {
+                            mdl.driver()
+                                .batch_driver(partition)
+                                .lock()
+                                .as_mut()
+                                .unwrap()
+                                .recover()
+                        });
+                        match heartbeat {
                             Ok(()) => {
-                                mdl.driver().status().set_okay();
-                                global.health().report_recovery();
+                                mdl.driver().report_persist_recovery(global);
                                 info!("model driver for {mdl_id} has been successfully auto-recovered");
                             }
                             Err(e) => {
                                 error!("failed to autorecover {mdl_id} with {e}. will try again");
                                 self.hp_dispatcher
-                                    .send(Task::new(CriticalTask::TryModelAutorecoverLWT(mdl_id)))
+                                    .send(Task::with_threshold(
+                                        CriticalTask::TryModelAutorecoverLWT(mdl_id),
+                                        Self::adjust_threshold(threshold),
+                                    ))
                                     .unwrap()
                             }
                         }
@@ -385,7 +395,9 @@ impl FractalMgr {
                         return;
                     }
                 };
+                let started_at = Instant::now();
                 match self.try_write_model_data_batch(
+                    global,
                     ModelUniqueIDRef::from(&model_id),
                     mdl.data(),
                     observed_size,
@@ -393,7 +405,13 @@ impl FractalMgr {
                 ) {
                     Ok(()) => {
                         if observed_size != 0 {
-                            info!("fhp: completed maintenance task for {model_id}, synced={observed_size}")
+                            info!("fhp: completed maintenance task for {model_id}, synced={observed_size}");
+                            // the model's buffer is full, so this is never held back
+                            global.scheduler().record(
+                                WorkClass::Flush,
+                                observed_size as u64,
+                                started_at.elapsed(),
+                            );
                         }
                     }
                     Err((err, stats)) => {
@@ -425,21 +443,34 @@ impl FractalMgr {
         rs_window: u64,
     ) {
         let dur = std::time::Duration::from_secs(rs_window);
+        // the maintenance window starts over after every task (but not after the flush ticks)
+        let maintenance = tokio::time::sleep(dur);
+        tokio::pin!(maintenance);
+        // models with a flush interval are checked as often as the smallest interval allows
+        let mut flush_tick =
+            tokio::time::interval(Duration::from_millis(ModelProps::MIN_FLUSH_EVERY_MS));
+        flush_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
         loop {
             tokio::select! {
                 _ = sigterm.recv() => {
                     info!("flp: finishing any pending maintenance tasks");
+                    self.begin_shutdown();
                     let global = global.clone();
-                    tokio::task::spawn_blocking(|| self.general_executor(global)).await.unwrap();
+                    tokio::task::spawn_blocking(move || self.general_executor(&global)).await.unwrap();
                     info!("flp: exited executor service");
                     break;
                 },
-                _ = tokio::time::sleep(dur) => {
+                _ = &mut maintenance => {
                     let global = global.clone();
-                    tokio::task::spawn_blocking(|| self.general_executor(global)).await.unwrap()
+                    tokio::task::spawn_blocking(move || self.general_executor(&global)).await.unwrap();
+                    maintenance.as_mut().reset(tokio::time::Instant::now() + dur);
+                }
+                _ = flush_tick.tick() => {
+                    let global = global.clone();
+                    tokio::task::spawn_blocking(move || self.flush_due_models(&global)).await.unwrap()
                 }
                 task = lpq.recv() => {
-                    let Task { threshold, task } = match task {
+                    let task = match task {
                         Some(t) => {
                             t.sleep().await;
                             t
@@ -449,67 +480,211 @@ impl FractalMgr {
                             break;
                         }
                     };
-                    // TODO(@ohsayan): threshold
-                    match task {
-                        GenericTask::DeleteFile(f) => {
-                            if let Err(_) = fs::remove_file(&f).await {
-                                self.general_dispatcher.send(
-                                    Task::with_threshold(GenericTask::DeleteFile(f), Self::adjust_threshold(threshold))
-                                ).unwrap();
-                            }
-                        }
-                        GenericTask::DeleteDirAll(dir) => {
-                            if let Err(_) = fs::remove_dir_all(&dir).await {
-                                self.general_dispatcher.send(
-                                    Task::with_threshold(GenericTask::DeleteDirAll(dir), Self::adjust_threshold(threshold))
-                                ).unwrap();
-                            }
-                        }
-                    }
+                    tokio::task::spawn_blocking(move || self.lp_executor(task)).await.unwrap();
+                    maintenance.as_mut().reset(tokio::time::Instant::now() + dur);
                 }
             }
         }
     }
-    fn general_executor(&'static self, global: super::Global) {
-        for (model_id, model) in global.state().namespace().idx_models().read().iter() {
-            let observed_len = model
-                .data()
-                .delta_state()
-                .__fractal_take_full_from_data_delta(super::FractalToken::new());
-            match self.try_write_model_data_batch(
-                ModelUniqueIDRef::new(model_id.space(), model_id.entity(), model.data().get_uuid()),
-                model.data(),
-                observed_len,
-                model.driver(),
-            ) {
-                Ok(()) => {
-                    if observed_len != 0 {
-                        info!(
-                            "flp: completed maintenance task for {}.{}, synced={observed_len}",
-                            model_id.space(),
-                            model_id.entity()
-                        )
+    pub(super) fn lp_executor(&self, Task { threshold, task }: Task<GenericTask>) {
+        // TODO(@ohsayan): threshold
+        match task {
+            GenericTask::DeleteFile(f) => {
+                if let Err(_) = FileSystem::remove_file(&f) {
+                    self.general_dispatcher
+                        .send(Task::with_threshold(
+                            GenericTask::DeleteFile(f),
+                            Self::adjust_threshold(threshold),
+                        ))
+                        .unwrap();
+                }
+            }
+            GenericTask::DeleteDirAll(dir) => {
+                if let Err(_) = FileSystem::remove_dir_all(&dir) {
+                    self.general_dispatcher
+                        .send(Task::with_threshold(
+                            GenericTask::DeleteDirAll(dir),
+                            Self::adjust_threshold(threshold),
+                        ))
+                        .unwrap();
+                }
+            }
+        }
+    }
+    pub(super) fn general_executor(&self, global: &impl GlobalInstanceLike) {
+        let now = os::get_epoch_time_secs();
+        let model_ids: Vec<ModelUniqueID> = global
+            .state()
+            .namespace()
+            .idx_models()
+            .read()
+            .iter()
+            .map(|(model_id, model)| {
+                ModelUniqueID::new(model_id.space(), model_id.entity(), model.data().get_uuid())
+            })
+            .collect();
+        for model_id in model_ids {
+            let started_at = Instant::now();
+            let synced = {
+                let models = global.state().namespace().idx_models().read();
+                match models.get(&EntityIDRef::new(model_id.space(), model_id.model())) {
+                    Some(model) if model.data().get_uuid() == model_id.uuid() => {
+                        self.general_executor_model(global, &model_id, model, now)
                     }
+                    // the model was dropped in the meantime
+                    Some(_) | None => continue,
                 }
-                Err((e, stats)) => {
+            };
+            // the model index isn't held anymore, so the next model can wait if queries are running (unless we're
+            // shutting down)
+            if synced != 0 {
+                let busy = started_at.elapsed();
+                if self.shutdown.is_cancelled() {
+                    global
+                        .scheduler()
+                        .record(WorkClass::Flush, synced as u64, busy)
+                } else {
+                    global
+                        .scheduler()
+                        .throttle(WorkClass::Flush, synced as u64, busy)
+                }
+            }
+        }
+    }
+    /// Run the general executor's maintenance for a model, returning the number of changes that were synced
+    fn general_executor_model(
+        &self,
+        global: &impl GlobalInstanceLike,
+        model_id: &ModelUniqueID,
+        model: &Model,
+        now: u64,
+    ) -> usize {
+        // purge any expired tombstones first, so that the removals are synced right away
+        let purged = dml::purge_tombstones(model.data(), now, &self.shutdown);
+        if purged != 0 {
+            info!(
+                "flp: purged {purged} soft deleted rows from {}.{}",
+                model_id.space(),
+                model_id.model()
+            )
+        }
+        // move large binary values to the blob tier (if enabled), so that the references are synced right away
+        if !self.shutdown.is_cancelled() {
+            let tiered = dml::tier_blobs(model.data(), &self.shutdown);
+            if tiered != 0 {
+                info!(
+                    "flp: moved {tiered} blobs from {}.{} to the blob tier",
+                    model_id.space(),
+                    model_id.model()
+                )
+            }
+        }
+        // and discard row versions that are outside the history retention window
+        if let Some(history) = model.data().history() {
+            history.prune(now.saturating_sub(model.data().props().history_retention()));
+        }
+        // and rebuild the histograms if enough has changed since (a full scan that can wait if we're shutting down)
+        if let Some(stats) = model.data().stats() {
+            if stats.is_stale(model.data().primary_index().count(), now)
+                & !self.shutdown.is_cancelled()
+            {
+                stats.refresh(model.data(), now);
+            }
+        }
+        let synced = self.flush_model(global, model_id, model);
+        if synced != 0 {
+            info!(
+                "flp: completed maintenance task for {}.{}, synced={synced}",
+                model_id.space(),
+                model_id.model()
+            )
+        }
+        // finally, move cold rows to disk if we're running low on memory (the sweep runs either way)
+        if let Some(overflow) = model.data().overflow() {
+            if !self.shutdown.is_cancelled() {
+                let target = Self::overflow_target(global);
+                let moved = overflow.maintain(model.data(), target, &self.shutdown);
+                if moved != 0 {
                     info!(
-                        "flp: failed to sync data for {}.{} with erro `{e}`. promoting to higher priority",
-                        model_id.space(), model_id.entity(),
-                    );
-                    // this failure is *not* good, so we want to promote this to a critical task
-                    self.re_enqueue_model_sync(
-                        ModelUniqueID::new(
-                            model_id.space(),
-                            model_id.entity(),
-                            model.data().get_uuid(),
-                        ),
-                        observed_len,
-                        stats,
-                        TASK_THRESHOLD,
+                        "flp: moved {moved} rows from {}.{} to the overflow store",
+                        model_id.space(),
+                        model_id.model()
                     )
                 }
             }
         }
+        synced
+    }
+    /// Returns the number of bytes that an overflow model should move to disk: once the server uses more memory
+    /// than the high watermark, enough to get back under the low watermark
+    fn overflow_target(global: &impl GlobalInstanceLike) -> u64 {
+        let watermark = global.memory_watermark();
+        if !watermark.is_enabled() {
+            return 0;
+        }
+        let usage = global.get_memory_usage();
+        if usage < watermark.high() {
+            return 0;
+        }
+        usage.saturating_sub(watermark.low())
+    }
+    /// Write the changes of the models whose flush interval (`flush_every_ms`) is up
+    fn flush_due_models(&self, global: &impl GlobalInstanceLike) {
+        let now_ms = os::get_epoch_time_ms();
+        let models = global.state().namespace().idx_models().read();
+        for (model_id, model) in models.iter() {
+            if model.data().props().flush_every_ms().is_none()
+                || !model
+                    .data()
+                    .flush_due(model.data().delta_state().pending_data_deltas(), now_ms)
+            {
+                continue;
+            }
+            let model_id =
+                ModelUniqueID::new(model_id.space(), model_id.entity(), model.data().get_uuid());
+            let started_at = Instant::now();
+            let synced = self.flush_model(global, &model_id, model);
+            if synced != 0 {
+                // the model asked for this, so it's never held back
+                global
+                    .scheduler()
+                    .record(WorkClass::Flush, synced as u64, started_at.elapsed());
+            }
+        }
+    }
+    /// Write the pending changes of a model, returning the number of changes that were synced
+    fn flush_model(
+        &self,
+        global: &impl GlobalInstanceLike,
+        model_id: &ModelUniqueID,
+        model: &Model,
+    ) -> usize {
+        if model.driver().status().is_iffy() {
+            // a write is already being retried (with a backoff) for this model
+            return 0;
+        }
+        let observed_len = model
+            .data()
+            .delta_state()
+            .__fractal_take_full_from_data_delta(super::FractalToken::new());
+        match self.try_write_model_data_batch(
+            global,
+            ModelUniqueIDRef::from(model_id),
+            model.data(),
+            observed_len,
+            model.driver(),
+        ) {
+            Ok(()) => observed_len,
+            Err((e, stats)) => {
+                info!(
+                    "flp: failed to sync data for {}.{} with erro `{e}`. promoting to higher priority",
+                    model_id.space(), model_id.model(),
+                );
+                // this failure is *not* good, so we want to promote this to a critical task
+                self.re_enqueue_model_sync(model_id.clone(), observed_len, stats, TASK_THRESHOLD);
+                0
+            }
+        }
     }
 }
 
@@ -519,7 +694,8 @@ impl FractalMgr {
     ///
     /// The zero check is essential
     fn try_write_model_data_batch(
-        &'static self,
+        &self,
+        global: &impl GlobalInstanceLike,
         mdl_id: ModelUniqueIDRef,
         model: &ModelData,
         observed_size: usize,
@@ -538,23 +714,20 @@ impl FractalMgr {
             // no changes, all good
             return Ok(());
         }
-        // try flushing the batch
-        let batch_stats = BatchStats::new();
-        let mut mdl_driver = mdl_driver_.batch_driver().lock();
-        let batch_driver = mdl_driver.as_mut().unwrap();
-        batch_driver
-            .commit_with_ctx(
-                StdModelBatch::new(model, observed_size),
-                batch_stats.clone(),
-            )
-            .map_err(|e| {
-                mdl_driver_.status().set_iffy();
-                self.hp_dispatcher
-                    .send(Task::new(CriticalTask::TryModelAutorecoverLWT(
-                        mdl_id.into(),
-                    )))
-                    .unwrap();
-                (e, BatchStats::into_inner(batch_stats))
+        let mut span = Span::root("skyd.flush");
+        if let Some(span) = span.as_mut() {
+            span.set_str("skyd.model", format!("{}.{}", mdl_id.space, mdl_id.model));
+            span.set_int("skyd.changes", observed_size as u64);
+        }
+        // try flushing the batches (one for each partition)
+        mdl_driver_
+            .commit_taken_deltas(model)
+            .map_err(|(e, batch_stats)| {
+                if let Some(span) = span.as_mut() {
+                    span.set_error(e.to_string());
+                }
+                mdl_driver_.report_persist_failure(global, mdl_id.into(), &e);
+                (e, batch_stats)
             })
     }
 }
@@ -26,32 +26,53 @@
 
 use {
     super::{
+        config::{ConfigFlushFailure, ConfigSystem},
         core::{dml::QueryExecMeta, model::ModelData, GlobalNS},
         data::uuid::Uuid,
-        storage::{
-            safe_interfaces::{paths_v1, FileSystem},
-            GNSDriver, ModelDriver,
-        },
+        error::{QueryError, QueryResult},
+        net::protocol::ResponseLimits,
+    },
+    crate::{
+        engine::error::RuntimeResult,
+        util::{compiler, os},
     },
-    crate::{engine::error::RuntimeResult, util::compiler},
     std::{
         fmt,
         mem::MaybeUninit,
-        sync::atomic::{AtomicUsize, Ordering},
+        sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     },
     tokio::sync::mpsc::unbounded_channel,
 };
 
+pub mod archive;
 pub mod context;
 mod drivers;
 pub mod error;
+pub mod jobs;
+pub mod kafka;
 mod mgr;
+pub mod sched;
+pub mod shardmap;
+#[cfg(test)]
+pub mod sim;
+pub mod snapshot;
+pub mod soft_limit;
 #[cfg(test)]
 pub mod test_utils;
+#[cfg(test)]
+mod tests;
+pub mod throttle;
+pub mod tier;
+pub mod trace;
+pub mod upstream;
 mod util;
+pub mod webhook;
 pub use {
     drivers::{FractalGNSDriver, FractalModelDriver},
     mgr::{CriticalTask, GenericTask, Task, GENERAL_EXECUTOR_WINDOW},
+    sched::{Scheduler, WorkClass},
+    soft_limit::{Limit, SoftLimit},
+    throttle::WriteThrottle,
     util::FractalToken,
 };
 
@@ -71,13 +92,14 @@ pub struct GlobalStateStart {
 /// ## Safety
 ///
 /// Must be called iff this is the only thread calling it
-pub unsafe fn load_and_enable_all(gns: GlobalNS) -> GlobalStateStart {
+pub unsafe fn load_and_enable_all(gns: GlobalNS, system: &ConfigSystem) -> GlobalStateStart {
     let model_cnt_on_boot = gns.namespace().idx_models().read().len();
     let (hp_sender, hp_recv) = unbounded_channel();
     let (lp_sender, lp_recv) = unbounded_channel();
     let global_state = GlobalState::new(
         gns,
         mgr::FractalMgr::new(hp_sender, lp_sender, model_cnt_on_boot),
+        system,
     );
     *Global::__gref_raw() = MaybeUninit::new(global_state);
     let token = Global::new();
@@ -93,15 +115,42 @@ pub unsafe fn load_and_enable_all(gns: GlobalNS) -> GlobalStateStart {
 
 pub struct GlobalHealth {
     faults: AtomicUsize,
+    /// the number of models whose changes can't be written to disk right now
+    degraded: AtomicUsize,
+    /// set once a write fails because the disk is full, and cleared once a write goes through again
+    disk_full: AtomicBool,
 }
 
 impl GlobalHealth {
     pub fn status_okay(&self) -> bool {
         self.faults.load(Ordering::Acquire) == 0
     }
+    /// Returns true if the changes of any model can't be written to disk right now. Such models are retried in the
+    /// background, and writes to them are handled as set by the [`FlushPolicy`]
+    pub fn is_degraded(&self) -> bool {
+        self.degraded_models() != 0
+    }
+    pub fn degraded_models(&self) -> usize {
+        self.degraded.load(Ordering::Acquire)
+    }
+    /// Returns true if the disk is full. Until space is freed (and a write goes through again), the server is
+    /// read-only
+    pub fn is_disk_full(&self) -> bool {
+        self.disk_full.load(Ordering::Acquire)
+    }
+    /// Reject writes while the disk is full
+    pub fn check_writable(&self) -> QueryResult<()> {
+        if compiler::likely(!self.is_disk_full()) {
+            Ok(())
+        } else {
+            compiler::cold_rerr(QueryError::SysDiskFull)
+        }
+    }
     const fn new() -> Self {
         Self {
             faults: AtomicUsize::new(0),
+            degraded: AtomicUsize::new(0),
+            disk_full: AtomicBool::new(false),
         }
     }
     fn report_fault(&self) {
@@ -110,8 +159,109 @@ impl GlobalHealth {
     fn report_recovery(&self) {
         self.faults.fetch_sub(1, Ordering::Release);
     }
+    fn report_degraded(&self) {
+        self.degraded.fetch_add(1, Ordering::Release);
+    }
+    fn report_undegraded(&self) {
+        self.degraded.fetch_sub(1, Ordering::Release);
+    }
+    /// Check if a write failed because the disk is full, and if so, make the server read-only
+    fn report_write_failure(&self, e: &error::Error) {
+        if e.is_disk_full() && !self.disk_full.swap(true, Ordering::AcqRel) {
+            warn!("the disk is full. no writes will be taken until space is freed");
+        }
+    }
+    /// A write went through, so if the disk was full, space has been freed
+    fn report_write_success(&self) {
+        if self.disk_full.swap(false, Ordering::AcqRel) {
+            info!("disk space is available again. taking writes");
+        }
+    }
+    /// A model whose changes couldn't be written to disk was dropped
     pub fn report_removal_of_faulty_source(&self) {
-        self.report_recovery()
+        self.report_undegraded()
+    }
+}
+
+/// What happens to writes to a model while its changes can't be written to disk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlushPolicy {
+    on_failure: ConfigFlushFailure,
+    cap: usize,
+}
+
+impl FlushPolicy {
+    pub const fn new(on_failure: ConfigFlushFailure, cap: usize) -> Self {
+        Self { on_failure, cap }
+    }
+    /// Returns true if a write to a model whose changes can't be written to disk right now can go ahead
+    pub fn admit_write(&self, model: &ModelData) -> bool {
+        match self.on_failure {
+            ConfigFlushFailure::Block => false,
+            ConfigFlushFailure::Accumulate => model.delta_state().pending_data_deltas() < self.cap,
+        }
+    }
+}
+
+/// Process-level memory admission control. Once the memory used by the process reaches the high watermark, expensive
+/// queries (such as scans) are rejected until it drops back to the low watermark. Cheap queries (such as point lookups)
+/// are always admitted
+pub struct MemoryWatermark {
+    high: u64,
+    low: u64,
+    pressure: AtomicBool,
+    /// the memory usage seen by the last admission check
+    last_usage: AtomicU64,
+    /// the number of queries rejected so far
+    rejected: AtomicU64,
+}
+
+impl MemoryWatermark {
+    /// Create a new watermark. A high watermark of 0 disables admission control
+    pub const fn new(high: u64, low: u64) -> Self {
+        Self {
+            high,
+            low,
+            pressure: AtomicBool::new(false),
+            last_usage: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.high != 0
+    }
+    pub fn high(&self) -> u64 {
+        self.high
+    }
+    pub fn low(&self) -> u64 {
+        self.low
+    }
+    /// Returns true if expensive queries are currently being rejected
+    pub fn under_pressure(&self) -> bool {
+        self.pressure.load(Ordering::Acquire)
+    }
+    pub fn last_usage(&self) -> u64 {
+        self.last_usage.load(Ordering::Relaxed)
+    }
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+    /// Update the state with the current memory usage, and returns true if an expensive query can run
+    fn admit(&self, usage: u64) -> bool {
+        self.last_usage.store(usage, Ordering::Relaxed);
+        let pressure = if usage >= self.high {
+            true
+        } else if usage <= self.low {
+            false
+        } else {
+            // between the watermarks, so stay in the current state
+            self.under_pressure()
+        };
+        self.pressure.store(pressure, Ordering::Release);
+        if pressure {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+        }
+        !pressure
     }
 }
 
@@ -120,31 +270,93 @@ pub trait GlobalInstanceLike {
     // stat
     fn health(&self) -> &GlobalHealth;
     fn get_max_delta_size(&self) -> usize;
+    /// Returns the maximum (approximate) memory in bytes that a single query can use for its intermediate state
+    fn get_query_memory_limit(&self) -> usize;
+    /// Returns the largest value (in bytes) that a client can upload in chunks
+    fn get_blob_max_size(&self) -> u64;
+    /// Returns the limits on the responses that are sent to clients
+    fn get_response_limits(&self) -> ResponseLimits;
+    /// Returns the share of every limit past which usage is warned about
+    fn get_soft_limit(&self) -> SoftLimit;
+    fn memory_watermark(&self) -> &MemoryWatermark;
+    /// Returns the scheduler that admits maintenance (see [`sched`])
+    fn scheduler(&self) -> &Scheduler;
+    fn flush_policy(&self) -> &FlushPolicy;
+    /// Returns the throttle that holds back writes to models that fall behind (see [`throttle`])
+    fn write_throttle(&self) -> &WriteThrottle;
+    /// Returns true if shards were configured to be NUMA aware
+    fn is_numa_aware(&self) -> bool;
+    /// Returns the memory currently used by the process
+    fn get_memory_usage(&self) -> u64 {
+        os::process_memory_in_bytes()
+    }
+    /// Check if an expensive query (such as a scan) can run now, given the memory used by the process
+    fn admit_expensive_query(&self) -> QueryResult<()> {
+        let watermark = self.memory_watermark();
+        if !watermark.is_enabled() {
+            return Ok(());
+        }
+        let usage = self.get_memory_usage();
+        if !watermark.admit(usage) {
+            return compiler::cold_rerr(QueryError::SysMemoryPressure);
+        }
+        if let Some(soft) = self.get_soft_limit().threshold(watermark.high()) {
+            if usage >= soft {
+                Limit::Memory.warn(usage, watermark.high());
+            }
+        }
+        Ok(())
+    }
     // global namespace
     fn state(&self) -> &GlobalNS;
     fn initialize_space(&self, space_name: &str, space_uuid: Uuid) -> RuntimeResult<()> {
-        e!(FileSystem::create_dir_all(&paths_v1::space_dir(
-            space_name, space_uuid
-        )))
+        self.state().storage().create_space(space_name, space_uuid)
     }
     // model drivers
+    /// Set up the model's storage along with a batch driver for each of its partitions
     fn initialize_model_driver(
         &self,
         space_name: &str,
         space_uuid: Uuid,
         model_name: &str,
         model_uuid: Uuid,
-    ) -> RuntimeResult<FractalModelDriver>;
+        partitions: usize,
+    ) -> RuntimeResult<FractalModelDriver> {
+        let journals = self
+            .state()
+            .storage()
+            .create_model(space_name, space_uuid, model_name, model_uuid, partitions)?;
+        Ok(FractalModelDriver::init(journals))
+    }
+    /// Delete the model's storage (in the background)
     fn purge_model_driver(
         &self,
         space_name: &str,
         space_uuid: Uuid,
         model_name: &str,
         model_uuid: Uuid,
-    );
+    ) {
+        if let Some(dir) = self
+            .state()
+            .storage()
+            .model_dir(space_name, space_uuid, model_name, model_uuid)
+        {
+            self.taskmgr_post_standard_priority(Task::new(GenericTask::DeleteDirAll(dir.into())))
+        }
+    }
+    /// Delete the space's storage (in the background)
+    fn purge_space(&self, space_name: &str, space_uuid: Uuid) {
+        if let Some(dir) = self.state().storage().space_dir(space_name, space_uuid) {
+            self.taskmgr_post_standard_priority(Task::new(GenericTask::DeleteDirAll(dir.into())))
+        }
+    }
     // taskmgr
     fn taskmgr_post_high_priority(&self, task: Task<CriticalTask>);
     fn taskmgr_post_standard_priority(&self, task: Task<GenericTask>);
+    /// Run a background job (see [`jobs`])
+    fn spawn_job(&self, job: Box<dyn FnOnce(&Self) + Send>)
+    where
+        Self: Sized;
     // default impls
     #[inline(always)]
     fn request_batch_resolve_if_cache_full(
@@ -158,10 +370,19 @@ pub trait GlobalInstanceLike {
         let r_tolerated_change = hint.delta_hint() >= self.get_max_delta_size();
         let r_percent_change = (hint.delta_hint() >= ((model.primary_index().count() / 100) * 5))
             & (r_tolerated_change);
-        if compiler::unlikely(r_tolerated_change | r_percent_change) {
+        // the flusher picks a batch size from the model's throughput, so that changes don't wait too long under load
+        let r_batch_full = hint.delta_hint() >= model.delta_state().data_batch_size();
+        // and the model can ask for its changes to be written sooner
+        let r_flush_due = model.flush_due(hint.delta_hint(), os::get_epoch_time_ms());
+        if compiler::unlikely(r_tolerated_change | r_percent_change | r_batch_full | r_flush_due) {
             // do not inline this path as we expect sufficient memory to be present and/or the background service
             // to pick this up
             compiler::cold_call(|| {
+                if r_tolerated_change {
+                    model
+                        .delta_state()
+                        .__fractal_record_stall(FractalToken::new());
+                }
                 let obtained_delta_size = model
                     .delta_state()
                     .__fractal_take_full_from_data_delta(FractalToken::new());
@@ -193,39 +414,42 @@ impl GlobalInstanceLike for Global {
     fn taskmgr_post_standard_priority(&self, task: Task<GenericTask>) {
         self._post_standard_priority_task(task)
     }
+    fn spawn_job(&self, job: Box<dyn FnOnce(&Self) + Send>) {
+        let global = self.clone();
+        std::thread::spawn(move || job(&global));
+    }
     // stat
     fn get_max_delta_size(&self) -> usize {
         self._get_max_delta_size()
     }
-    // model
-    fn purge_model_driver(
-        &self,
-        space_name: &str,
-        space_uuid: Uuid,
-        model_name: &str,
-        model_uuid: Uuid,
-    ) {
-        self.taskmgr_post_standard_priority(Task::new(GenericTask::delete_model_dir(
-            space_name, space_uuid, model_name, model_uuid,
-        )));
+    fn get_query_memory_limit(&self) -> usize {
+        self.get_state().query_memory_limit
     }
-    fn initialize_model_driver(
-        &self,
-        space_name: &str,
-        space_uuid: Uuid,
-        model_name: &str,
-        model_uuid: Uuid,
-    ) -> RuntimeResult<FractalModelDriver> {
-        // create dir
-        FileSystem::create_dir(&paths_v1::model_dir(
-            space_name, space_uuid, model_name, model_uuid,
-        ))?;
-        // init driver
-        let driver = ModelDriver::create_model_driver(&paths_v1::model_path(
-            space_name, space_uuid, model_name, model_uuid,
-        ))?;
-        Ok(FractalModelDriver::init(driver))
+    fn get_blob_max_size(&self) -> u64 {
+        self.get_state().blob_max_size
+    }
+    fn get_response_limits(&self) -> ResponseLimits {
+        self.get_state().response_limits
     }
+    fn get_soft_limit(&self) -> SoftLimit {
+        self.get_state().soft_limit
+    }
+    fn memory_watermark(&self) -> &MemoryWatermark {
+        &self.get_state().memory_watermark
+    }
+    fn scheduler(&self) -> &Scheduler {
+        &self.get_state().scheduler
+    }
+    fn flush_policy(&self) -> &FlushPolicy {
+        &self.get_state().flush_policy
+    }
+    fn write_throttle(&self) -> &WriteThrottle {
+        &self.get_state().write_throttle
+    }
+    fn is_numa_aware(&self) -> bool {
+        self.get_state().numa_aware
+    }
+    // model
 }
 
 #[derive(Debug, Clone)]
@@ -272,8 +496,7 @@ impl Global {
     pub unsafe fn unload_all(self) {
         // TODO(@ohsayan): handle errors
         let GlobalState { gns, .. } = Self::__gref_raw().assume_init_read();
-        let mut gns_driver = gns.gns_driver().txn_driver.lock();
-        GNSDriver::close_driver(&mut gns_driver).unwrap();
+        gns.gns_driver().txn_driver.lock().close().unwrap();
         for mdl in gns
             .namespace()
             .idx_models()
@@ -295,14 +518,43 @@ struct GlobalState {
     gns: GlobalNS,
     task_mgr: mgr::FractalMgr,
     health: GlobalHealth,
+    query_memory_limit: usize,
+    blob_max_size: u64,
+    response_limits: ResponseLimits,
+    soft_limit: SoftLimit,
+    memory_watermark: MemoryWatermark,
+    scheduler: Scheduler,
+    flush_policy: FlushPolicy,
+    write_throttle: WriteThrottle,
+    numa_aware: bool,
 }
 
 impl GlobalState {
-    fn new(gns: GlobalNS, task_mgr: mgr::FractalMgr) -> Self {
+    fn new(gns: GlobalNS, task_mgr: mgr::FractalMgr, system: &ConfigSystem) -> Self {
         Self {
             gns,
             task_mgr,
             health: GlobalHealth::new(),
+            query_memory_limit: usize::try_from(system.query_memory_limit).unwrap_or(usize::MAX),
+            blob_max_size: system.blob_max_size,
+            response_limits: ResponseLimits::new(
+                system.max_response_size,
+                system.response_quota,
+                system.response_quota_window,
+            )
+            .with_soft_limit(SoftLimit::new(system.soft_limit)),
+            soft_limit: SoftLimit::new(system.soft_limit),
+            memory_watermark: MemoryWatermark::new(
+                system.memory_high_watermark,
+                system.memory_low_watermark,
+            ),
+            scheduler: Scheduler::new(system.maintenance_rate),
+            flush_policy: FlushPolicy::new(
+                system.flush_failure,
+                usize::try_from(system.flush_failure_cap).unwrap_or(usize::MAX),
+            ),
+            write_throttle: WriteThrottle::new(system.throttle_backlog, system.throttle_lag),
+            numa_aware: system.numa_aware,
         }
     }
     pub(self) fn fractal_mgr(&self) -> &mgr::FractalMgr {
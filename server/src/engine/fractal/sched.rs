@@ -0,0 +1,237 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    scheduler
+    ---
+    work is split into classes: foreground work (the statements that clients run) and maintenance (batches written by
+    the flusher, rows checked by background verifications, rows indexed by index builds, data copied by relocations
+    and rows filled in by backfills). maintenance is metered in units (changes for flushes, KiB for relocations and
+    rows otherwise) and while any foreground statement is running, it is only admitted at `system.maintenance_rate`
+    units per second by a token bucket. the bucket holds up to a second's worth of units and maintenance that runs out
+    waits for it to refill (never for more than MAX_WAIT at a time), so that a long scan or a large flush is spread out
+    instead of competing with queries. when nothing is running in the foreground, maintenance runs at full speed. a
+    rate of 0 disables throttling. maintenance is only held back where it doesn't block writes: the flusher between
+    models, background verifications between runs of rows, index rebuilds between indexes, relocations between the
+    blocks they copy and backfills between batches. flushes of a model whose buffer is full (holding them back would
+    only hold on to more memory) and `create index ... in background` (which blocks writes to the model until the
+    index is built) are accounted for, but never held back. every class records the units of work it did, the time it
+    was busy and the time it was held back, which is what `sysctl report scheduler` shows
+*/
+
+use {
+    parking_lot::Mutex,
+    std::{
+        sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+        time::{Duration, Instant},
+    },
+};
+
+/// The longest that maintenance is held back at a time
+pub const MAX_WAIT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// What a unit of work is done for
+pub enum WorkClass {
+    /// statements run by clients
+    Foreground = 0,
+    /// batches written by the flusher
+    Flush = 1,
+    /// rows checked by background verifications
+    Scrub = 2,
+    /// rows indexed by index builds and rebuilds
+    Rebuild = 3,
+    /// KiB copied by data relocations
+    Relocate = 4,
+    /// rows filled in by backfills
+    Backfill = 5,
+}
+
+impl WorkClass {
+    const ALL: [Self; 6] = [
+        Self::Foreground,
+        Self::Flush,
+        Self::Scrub,
+        Self::Rebuild,
+        Self::Relocate,
+        Self::Backfill,
+    ];
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Foreground => "foreground",
+            Self::Flush => "flush",
+            Self::Scrub => "scrub",
+            Self::Rebuild => "rebuild",
+            Self::Relocate => "relocate",
+            Self::Backfill => "backfill",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ClassStats {
+    units: AtomicU64,
+    /// the time (in microseconds) spent doing the work
+    busy: AtomicU64,
+    /// the time (in microseconds) that the work was held back for
+    throttled: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// the units of maintenance that can be admitted right away (negative if the last admission overdrew the bucket)
+    units: f64,
+    refilled_at: Instant,
+}
+
+/// Admits maintenance so that it doesn't starve foreground work (see the module docs)
+#[derive(Debug)]
+pub struct Scheduler {
+    rate: u64,
+    bucket: Mutex<Bucket>,
+    /// the number of foreground statements that are running
+    foreground: AtomicUsize,
+    classes: [ClassStats; 6],
+    started_at: Instant,
+}
+
+impl Scheduler {
+    /// Create a new scheduler that admits `rate` units of maintenance per second while foreground work is running. A
+    /// rate of 0 disables throttling
+    pub fn new(rate: u64) -> Self {
+        let now = Instant::now();
+        Self {
+            rate,
+            bucket: Mutex::new(Bucket {
+                units: rate as f64,
+                refilled_at: now,
+            }),
+            foreground: AtomicUsize::new(0),
+            classes: Default::default(),
+            started_at: now,
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.rate != 0
+    }
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+    /// Returns the number of foreground statements that are running
+    pub fn foreground_in_flight(&self) -> usize {
+        self.foreground.load(Ordering::Acquire)
+    }
+    /// Mark a foreground statement as running until the returned guard is dropped (which records the time it took)
+    pub fn foreground(&self) -> ForegroundGuard<'_> {
+        self.foreground.fetch_add(1, Ordering::AcqRel);
+        ForegroundGuard {
+            sched: self,
+            started_at: Instant::now(),
+        }
+    }
+    /// Record units of work done for the class, along with the time it was busy doing it
+    pub fn record(&self, class: WorkClass, units: u64, busy: Duration) {
+        let stats = &self.classes[class as usize];
+        stats.units.fetch_add(units, Ordering::Relaxed);
+        stats
+            .busy
+            .fetch_add(busy.as_micros() as u64, Ordering::Relaxed);
+    }
+    /// Record units of maintenance (see [`Self::record`]) and then hold the calling thread back until the bucket has
+    /// made room for them
+    pub fn throttle(&self, class: WorkClass, units: u64, busy: Duration) {
+        self.record(class, units, busy);
+        let wait = self.take(units, Instant::now());
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+            self.classes[class as usize]
+                .throttled
+                .fetch_add(wait.as_micros() as u64, Ordering::Relaxed);
+        }
+    }
+    /// Take units of maintenance from the bucket at `now`, returning how long the caller has to wait for them
+    pub fn take(&self, units: u64, now: Instant) -> Duration {
+        if !self.is_enabled() {
+            return Duration::ZERO;
+        }
+        let rate = self.rate as f64;
+        let mut bucket = self.bucket.lock();
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.units = (bucket.units + elapsed.as_secs_f64() * rate).min(rate);
+        bucket.refilled_at = now;
+        if self.foreground_in_flight() == 0 {
+            return Duration::ZERO;
+        }
+        // anything owed beyond what the bucket refills in MAX_WAIT is forgiven
+        bucket.units = (bucket.units - units as f64).max(-rate * MAX_WAIT.as_secs_f64());
+        if bucket.units >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.units / rate)
+        }
+    }
+    /// Returns the rate, the number of foreground statements that are running and the units, busy time, throttled
+    /// time and utilization (the busy time as a percentage of the time the scheduler has been up, which can go above
+    /// 100 when work runs on many threads) of every class as a JSON object
+    pub fn describe(&self) -> String {
+        let uptime = self.started_at.elapsed().as_micros().max(1) as u64;
+        let classes: Vec<String> = WorkClass::ALL
+            .iter()
+            .map(|class| {
+                let stats = &self.classes[*class as usize];
+                let busy = stats.busy.load(Ordering::Relaxed);
+                format!(
+                    "\"{}\":{{\"units\":{},\"busy_ms\":{},\"throttled_ms\":{},\"utilization\":{}}}",
+                    class.name(),
+                    stats.units.load(Ordering::Relaxed),
+                    busy / 1000,
+                    stats.throttled.load(Ordering::Relaxed) / 1000,
+                    busy * 100 / uptime
+                )
+            })
+            .collect();
+        format!(
+            "{{\"rate\":{},\"foreground_in_flight\":{},\"classes\":{{{}}}}}",
+            self.rate,
+            self.foreground_in_flight(),
+            classes.join(",")
+        )
+    }
+}
+
+/// Returned by [`Scheduler::foreground`]
+pub struct ForegroundGuard<'a> {
+    sched: &'a Scheduler,
+    started_at: Instant,
+}
+
+impl<'a> Drop for ForegroundGuard<'a> {
+    fn drop(&mut self) {
+        self.sched.foreground.fetch_sub(1, Ordering::AcqRel);
+        self.sched
+            .record(WorkClass::Foreground, 1, self.started_at.elapsed());
+    }
+}
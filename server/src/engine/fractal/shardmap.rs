@@ -0,0 +1,129 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    shard map
+    ---
+    a static map of the independent instances that clients shard their keys across, registered by the operator with
+    `shardmap` and served (to any user) by `sysctl report shardmap` so that drivers can fetch it once and cache it.
+    the server itself never routes anything: a key belongs to the node that owns its slot, which is the CRC-64/XZ of
+    the key modulo the number of slots. strings and binaries are hashed as their bytes and integers as their 8 byte
+    little-endian encoding. the version is a checksum of the map, so drivers can tell that a map they cached is out of
+    date (it changes whenever the map does)
+*/
+
+use {
+    super::super::{config::ConfigShardMap, storage::encode_str},
+    crc::{Crc, CRC_64_XZ},
+    std::sync::OnceLock,
+};
+
+const CRC64: Crc<u64> = Crc::<u64>::new(&CRC_64_XZ);
+
+static SHARDMAP: OnceLock<ConfigShardMap> = OnceLock::new();
+
+/// Register the shard map. It can't be changed while the server is running
+pub fn set(shardmap: &ConfigShardMap) {
+    let _ = SHARDMAP.set(shardmap.clone());
+}
+
+/// Returns the shard map as a JSON object (with no nodes if none is registered)
+pub fn describe() -> String {
+    self::describe_map(SHARDMAP.get())
+}
+
+fn describe_map(shardmap: Option<&ConfigShardMap>) -> String {
+    let mut nodes = String::from("[");
+    for (i, node) in shardmap.iter().flat_map(|m| m.nodes.iter()).enumerate() {
+        if i != 0 {
+            nodes.push(',');
+        }
+        nodes.push_str("{\"name\":");
+        encode_str(&mut nodes, &node.name);
+        nodes.push_str(",\"host\":");
+        encode_str(&mut nodes, node.endpoint.host());
+        nodes.push_str(&format!(",\"port\":{},\"ranges\":[", node.endpoint.port()));
+        for (j, (start, end)) in node.ranges.iter().enumerate() {
+            if j != 0 {
+                nodes.push(',');
+            }
+            nodes.push_str(&format!("[{start},{end}]"));
+        }
+        nodes.push_str("]}");
+    }
+    nodes.push(']');
+    let version = match shardmap {
+        Some(_) => CRC64.checksum(nodes.as_bytes()),
+        None => 0,
+    };
+    format!(
+        "{{\"version\":{version},\"hash\":\"crc64-xz\",\"slots\":{},\"nodes\":{nodes}}}",
+        ConfigShardMap::SLOTS
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::describe_map,
+        crate::engine::config::{ConfigEndpointTcp, ConfigShardMap, ConfigShardNode},
+    };
+
+    #[test]
+    fn describe() {
+        assert_eq!(
+            describe_map(None),
+            "{\"version\":0,\"hash\":\"crc64-xz\",\"slots\":65536,\"nodes\":[]}"
+        );
+        let mut map = ConfigShardMap {
+            nodes: vec![
+                ConfigShardNode {
+                    name: "a".into(),
+                    endpoint: ConfigEndpointTcp::new("10.0.0.1".into(), 2003),
+                    ranges: vec![(0, 32767)],
+                },
+                ConfigShardNode {
+                    name: "b".into(),
+                    endpoint: ConfigEndpointTcp::new("10.0.0.2".into(), 2003),
+                    ranges: vec![(32768, 65535)],
+                },
+            ],
+        };
+        let described = describe_map(Some(&map));
+        assert!(described.ends_with(
+            ",\"hash\":\"crc64-xz\",\"slots\":65536,\"nodes\":[\
+            {\"name\":\"a\",\"host\":\"10.0.0.1\",\"port\":2003,\"ranges\":[[0,32767]]},\
+            {\"name\":\"b\",\"host\":\"10.0.0.2\",\"port\":2003,\"ranges\":[[32768,65535]]}]}"
+        ));
+        assert_eq!(described, describe_map(Some(&map)));
+        // moving a slot changes the version
+        map.nodes[0].ranges = vec![(0, 32766)];
+        map.nodes[1].ranges = vec![(32767, 65535)];
+        let moved = describe_map(Some(&map));
+        let version = |d: &str| d.split(',').next().unwrap().to_owned();
+        assert_ne!(version(&described), version(&moved));
+    }
+}
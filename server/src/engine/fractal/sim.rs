@@ -0,0 +1,242 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! A deterministic simulation of the fractal task manager.
+//!
+//! The background services started by [`FractalMgr::start_all`] are driven by tokio timers which makes it hard to
+//! test anything that depends on scheduling. The [`Simulator`] runs the very same executors against a virtual clock:
+//! time only moves when [`Simulator::advance`] is called, and the queues can be inspected between steps.
+//!
+//! The simulation follows the semantics of the real services:
+//! - both executors are serial, and a task that is being retried holds up its queue for the failure backoff
+//! - the general executor flushes all models once a full window elapses without it having run any task
+//! - on shutdown, all pending high priority tasks are run (without backoff) and then a final flush is made
+//! - the flush ticks for models with a flush interval (`flush_every_ms`) aren't simulated, since the intervals follow
+//!   the wall clock
+
+use {
+    super::{mgr::FractalMgr, CriticalTask, GenericTask, GlobalInstanceLike, Task},
+    std::{collections::VecDeque, time::Duration},
+    tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver},
+};
+
+/// The maximum number of tasks that can run at a single instant of virtual time. If we go past this, an executor is
+/// (most likely) re-enqueueing a task that keeps failing without any backoff
+const MAX_TASKS_PER_INSTANT: usize = 1024;
+
+/// A serial executor queue
+struct SimQueue<T> {
+    rx: UnboundedReceiver<Task<T>>,
+    queue: VecDeque<Task<T>>,
+    /// the time at which the task at the head of the queue will run, if the executor has picked it up
+    head_run_at: Option<Duration>,
+}
+
+impl<T> SimQueue<T> {
+    fn new(rx: UnboundedReceiver<Task<T>>) -> Self {
+        Self {
+            rx,
+            queue: VecDeque::new(),
+            head_run_at: None,
+        }
+    }
+    /// Move any tasks that were posted to the queue
+    fn receive(&mut self) {
+        while let Ok(task) = self.rx.try_recv() {
+            self.queue.push_back(task);
+        }
+    }
+    /// If the executor is idle, pick up the next task. Returns true if a task was picked up
+    fn pick_up(&mut self, now: Duration) -> bool {
+        match (self.head_run_at, self.queue.front()) {
+            (None, Some(task)) => {
+                self.head_run_at = Some(now + task.backoff().unwrap_or_default());
+                true
+            }
+            _ => false,
+        }
+    }
+    fn pop_due(&mut self, now: Duration) -> Option<Task<T>> {
+        match self.head_run_at {
+            Some(at) if at <= now => {
+                self.head_run_at = None;
+                self.queue.pop_front()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A simulated task manager with a virtual clock
+pub struct Simulator {
+    mgr: FractalMgr,
+    hp: SimQueue<CriticalTask>,
+    lp: SimQueue<GenericTask>,
+    window: Duration,
+    now: Duration,
+    next_flush: Duration,
+    flush_count: usize,
+    task_count: usize,
+}
+
+impl Simulator {
+    /// Create a new simulator that flushes all models every `window` (of virtual time)
+    pub fn new(window: Duration, model_count: usize) -> Self {
+        let (hp_sender, hp_recv) = unbounded_channel();
+        let (lp_sender, lp_recv) = unbounded_channel();
+        Self {
+            mgr: FractalMgr::new(hp_sender, lp_sender, model_count),
+            hp: SimQueue::new(hp_recv),
+            lp: SimQueue::new(lp_recv),
+            window,
+            now: Duration::ZERO,
+            next_flush: window,
+            flush_count: 0,
+            task_count: 0,
+        }
+    }
+    /// Add a high priority task to the queue
+    pub fn post_high_priority(&self, task: Task<CriticalTask>) {
+        self.mgr.post_high_priority(task)
+    }
+    /// Add a low priority task to the queue
+    pub fn post_low_priority(&self, task: Task<GenericTask>) {
+        self.mgr.post_low_priority(task)
+    }
+    /// Advance the virtual clock by the given duration, running every task and flush that falls due (in order)
+    pub fn advance(&mut self, global: &impl GlobalInstanceLike, by: Duration) {
+        let target = self.now + by;
+        let mut ran_at_instant = 0;
+        loop {
+            self.hp.receive();
+            self.lp.receive();
+            self.hp.pick_up(self.now);
+            if self.lp.pick_up(self.now) {
+                // the flush timer is reset every time the general executor picks up a task
+                self.next_flush = self.lp.head_run_at.unwrap() + self.window;
+            }
+            let next = [
+                self.hp.head_run_at,
+                self.lp.head_run_at,
+                Some(self.next_flush),
+            ]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap();
+            if next > target {
+                break;
+            }
+            if next == self.now {
+                ran_at_instant += 1;
+                assert!(
+                    ran_at_instant <= MAX_TASKS_PER_INSTANT,
+                    "simulation is not making progress at {:?}",
+                    self.now
+                );
+            } else {
+                self.now = next;
+                ran_at_instant = 0;
+            }
+            // hp and lp run concurrently in the real system, but here we always prefer hp to stay deterministic
+            if let Some(task) = self.hp.pop_due(self.now) {
+                self.task_count += 1;
+                self.mgr.hp_executor(global, task);
+            } else if let Some(task) = self.lp.pop_due(self.now) {
+                self.task_count += 1;
+                self.mgr.lp_executor(task);
+            } else {
+                self.flush(global);
+            }
+        }
+        self.now = target;
+    }
+    /// Advance the virtual clock till the next scheduled flush (and run it)
+    pub fn advance_to_next_flush(&mut self, global: &impl GlobalInstanceLike) {
+        let flush_count = self.flush_count;
+        while self.flush_count == flush_count {
+            let by = self.next_flush_in();
+            self.advance(global, by);
+        }
+    }
+    /// Simulate a termination signal
+    pub fn shutdown(&mut self, global: &impl GlobalInstanceLike) {
+        let mut ran = 0;
+        loop {
+            self.hp.receive();
+            match self.hp.queue.pop_front() {
+                Some(task) => {
+                    ran += 1;
+                    assert!(
+                        ran <= MAX_TASKS_PER_INSTANT,
+                        "simulation is not making progress at shutdown"
+                    );
+                    self.task_count += 1;
+                    self.mgr.hp_executor(global, task)
+                }
+                None => break,
+            }
+        }
+        self.hp.head_run_at = None;
+        self.mgr.begin_shutdown();
+        self.flush(global);
+    }
+    fn flush(&mut self, global: &impl GlobalInstanceLike) {
+        self.mgr.general_executor(global);
+        self.flush_count += 1;
+        self.next_flush = self.now + self.window;
+    }
+}
+
+// inspection
+impl Simulator {
+    /// Returns the current virtual time, measured from when the simulator was created
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+    /// Returns the time left until the next scheduled flush
+    pub fn next_flush_in(&self) -> Duration {
+        self.next_flush.saturating_sub(self.now)
+    }
+    /// Returns the number of flushes that were run
+    pub fn flush_count(&self) -> usize {
+        self.flush_count
+    }
+    /// Returns the number of tasks (of either priority) that were run
+    pub fn task_count(&self) -> usize {
+        self.task_count
+    }
+    /// Returns the high priority tasks that are yet to run, in order
+    pub fn queued_high_priority(&mut self) -> Vec<&CriticalTask> {
+        self.hp.receive();
+        self.hp.queue.iter().map(Task::task).collect()
+    }
+    /// Returns the low priority tasks that are yet to run, in order
+    pub fn queued_low_priority(&mut self) -> Vec<&GenericTask> {
+        self.lp.receive();
+        self.lp.queue.iter().map(Task::task).collect()
+    }
+}
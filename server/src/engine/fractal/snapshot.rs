@@ -0,0 +1,60 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    snapshot schedules
+    ---
+    every SCHEDULE_TICK, the snapshot schedules that are due (see `core::snapshot`) take their snapshots, one after
+    the other and each as a job of its own, and then remove the snapshots of theirs that expired
+*/
+
+use {
+    super::Global,
+    crate::engine::{core::snapshot, fractal::GlobalInstanceLike},
+    std::time::Duration,
+    tokio::time,
+};
+
+/// How often the schedules are checked
+const SCHEDULE_TICK: Duration = Duration::from_secs(30);
+
+/// Load the snapshot schedules and run them in the background. There is nothing to snapshot if the storage doesn't
+/// keep any files
+pub fn start(global: Global) {
+    if global.state().storage().data_dir().is_none() {
+        return;
+    }
+    if let Err(e) = global.state().snapshots().load_schedules() {
+        warn!("snapshot: failed to load the snapshot schedules: {e}");
+    }
+    tokio::spawn(async move {
+        loop {
+            time::sleep(SCHEDULE_TICK).await;
+            let global = global.clone();
+            let _ = tokio::task::spawn_blocking(move || snapshot::run_schedules(&global)).await;
+        }
+    });
+}
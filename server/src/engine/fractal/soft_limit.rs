@@ -0,0 +1,148 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    soft limits
+    ---
+    every limit that can fail a statement (the query memory limit, the largest response, the response quota and the
+    memory high watermark) also has a soft threshold: a share of the limit (`system.soft_limit`, 80% by default)
+    past which the statement still succeeds, but is warned about. the statement gets a notice, and the number of
+    warnings for every limit is counted so that `sysctl report status` can tell operators that they're running
+    close to a limit before requests start failing
+*/
+
+use {
+    crate::engine::core::notice::{Notice, NoticeCode},
+    std::sync::atomic::{AtomicU64, Ordering},
+};
+
+static WARNINGS: [AtomicU64; 4] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The share of a limit (in percent) past which usage is warned about. A share of 0 never warns
+pub struct SoftLimit {
+    percent: u8,
+}
+
+impl SoftLimit {
+    pub const fn new(percent: u8) -> Self {
+        Self { percent }
+    }
+    /// Returns the soft threshold for the given limit, unless either of them is disabled (a limit of 0)
+    pub fn threshold(self, limit: u64) -> Option<u64> {
+        ((self.percent != 0) & (limit != 0))
+            .then(|| (limit as u128 * self.percent as u128 / 100) as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// A limit that has a soft threshold
+pub enum Limit {
+    /// the memory that a single query can use (`system.query_memory_limit`)
+    QueryMemory = 0,
+    /// the largest response (`system.max_response_size`)
+    ResponseSize = 1,
+    /// the bytes that a connection can be sent in a window (`system.response_quota`)
+    ResponseQuota = 2,
+    /// the memory used by the process (`system.memory_high_watermark`)
+    Memory = 3,
+}
+
+impl Limit {
+    const ALL: [Self; 4] = [
+        Self::QueryMemory,
+        Self::ResponseSize,
+        Self::ResponseQuota,
+        Self::Memory,
+    ];
+    fn name(self) -> &'static str {
+        match self {
+            Self::QueryMemory => "query_memory",
+            Self::ResponseSize => "response_size",
+            Self::ResponseQuota => "response_quota",
+            Self::Memory => "memory",
+        }
+    }
+    fn describe(self) -> &'static str {
+        match self {
+            Self::QueryMemory => "the query's memory",
+            Self::ResponseSize => "the response",
+            Self::ResponseQuota => "the connection's response quota",
+            Self::Memory => "the server's memory",
+        }
+    }
+    /// Warn the statement running on this thread that `used` bytes of the limit (of `limit` bytes) are in use
+    pub fn warn(self, used: u64, limit: u64) {
+        WARNINGS[self as usize].fetch_add(1, Ordering::Relaxed);
+        Notice::raise(
+            NoticeCode::SoftLimitReached,
+            format!(
+                "{} is at {}% of its limit ({used} of {limit} bytes)",
+                self.describe(),
+                used as u128 * 100 / limit.max(1) as u128
+            ),
+        );
+    }
+    /// Returns the number of warnings for this limit since the server started
+    pub fn warnings(self) -> u64 {
+        WARNINGS[self as usize].load(Ordering::Relaxed)
+    }
+}
+
+/// Returns the number of warnings for every limit as a JSON object, or [`None`] if there haven't been any
+pub fn describe() -> Option<String> {
+    if Limit::ALL.iter().all(|limit| limit.warnings() == 0) {
+        return None;
+    }
+    let warnings: Vec<String> = Limit::ALL
+        .iter()
+        .map(|limit| format!("\"{}\":{}", limit.name(), limit.warnings()))
+        .collect();
+    Some(format!("{{{}}}", warnings.join(",")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SoftLimit;
+
+    #[test]
+    fn threshold() {
+        assert_eq!(SoftLimit::new(80).threshold(1000), Some(800));
+        // doesn't overflow
+        assert_eq!(
+            SoftLimit::new(80).threshold(u64::MAX),
+            Some(14_757_395_258_967_641_292)
+        );
+        assert_eq!(SoftLimit::new(0).threshold(1000), None);
+        assert_eq!(SoftLimit::new(80).threshold(0), None);
+    }
+}
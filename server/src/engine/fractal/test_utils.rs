@@ -26,20 +26,27 @@
 
 use {
     super::{
-        drivers::FractalGNSDriver, CriticalTask, FractalModelDriver, GenericTask, GlobalHealth,
-        GlobalInstanceLike, Task,
+        drivers::FractalGNSDriver, jobs::Jobs, sim::Simulator, CriticalTask, FlushPolicy,
+        GenericTask, GlobalHealth, GlobalInstanceLike, MemoryWatermark, Scheduler, SoftLimit, Task,
+        WriteThrottle,
     },
     crate::engine::{
+        config::{ConfigFlushFailure, ConfigSystem},
         core::{EntityIDRef, GNSData, GlobalNS},
-        data::uuid::Uuid,
         error::ErrorKind,
+        net::protocol::ResponseLimits,
         storage::{
-            safe_interfaces::{paths_v1, FileSystem, StdModelBatch},
-            BatchStats, GNSDriver, ModelDriver,
+            driver::{model_journals, MemoryDriver, SDSSDriver},
+            safe_interfaces::{finish_drops, paths_v1},
+            GNSDriver, ModelDriver,
         },
         RuntimeResult,
     },
-    parking_lot::RwLock,
+    parking_lot::{Mutex, MutexGuard, RwLock},
+    std::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    },
 };
 
 /// A `test` mode global implementation
@@ -47,7 +54,17 @@ pub struct TestGlobal {
     gns: GlobalNS,
     lp_queue: RwLock<Vec<Task<GenericTask>>>,
     max_delta_size: usize,
+    query_memory_limit: usize,
+    blob_max_size: u64,
+    response_limits: ResponseLimits,
+    soft_limit: SoftLimit,
+    memory_watermark: MemoryWatermark,
+    scheduler: Scheduler,
+    memory_usage: AtomicU64,
+    flush_policy: FlushPolicy,
+    write_throttle: WriteThrottle,
     health: GlobalHealth,
+    sim: Option<Mutex<Simulator>>,
 }
 
 impl TestGlobal {
@@ -56,12 +73,44 @@ impl TestGlobal {
             gns,
             lp_queue: RwLock::default(),
             max_delta_size: usize::MAX,
+            query_memory_limit: usize::MAX,
+            blob_max_size: ConfigSystem::DEFAULT_BLOB_MAX_SIZE,
+            response_limits: ResponseLimits::new(0, 0, ConfigSystem::DEFAULT_RESPONSE_QUOTA_WINDOW),
+            soft_limit: SoftLimit::new(ConfigSystem::DEFAULT_SOFT_LIMIT),
+            memory_watermark: MemoryWatermark::new(0, 0),
+            scheduler: Scheduler::new(0),
+            memory_usage: AtomicU64::new(0),
+            flush_policy: FlushPolicy::new(ConfigFlushFailure::Block, 0),
+            write_throttle: WriteThrottle::new(0, 0),
             health: GlobalHealth::new(),
+            sim: None,
         }
     }
     pub fn set_max_data_pressure(&mut self, max_data_pressure: usize) {
         self.max_delta_size = max_data_pressure;
     }
+    pub fn set_query_memory_limit(&mut self, query_memory_limit: usize) {
+        self.query_memory_limit = query_memory_limit;
+    }
+    pub fn set_blob_max_size(&mut self, blob_max_size: u64) {
+        self.blob_max_size = blob_max_size;
+    }
+    pub fn set_memory_watermarks(&mut self, high: u64, low: u64) {
+        self.memory_watermark = MemoryWatermark::new(high, low);
+    }
+    pub fn set_maintenance_rate(&mut self, rate: u64) {
+        self.scheduler = Scheduler::new(rate);
+    }
+    pub fn set_flush_policy(&mut self, flush_policy: FlushPolicy) {
+        self.flush_policy = flush_policy;
+    }
+    pub fn set_write_throttle(&mut self, backlog: u64, lag: u64) {
+        self.write_throttle = WriteThrottle::new(backlog, lag);
+    }
+    /// Set the memory usage reported for the process
+    pub fn set_memory_usage(&self, usage: u64) {
+        self.memory_usage.store(usage, Ordering::Relaxed)
+    }
     /// Normally, model drivers are not loaded on startup because of shared global state. Calling this will attempt to load
     /// all model drivers
     fn load_model_drivers(&self) -> RuntimeResult<()> {
@@ -69,16 +118,18 @@ impl TestGlobal {
         for (model_name, model) in self.gns.namespace().idx_models().read().iter() {
             let model_data = model.data();
             let space_uuid = space_idx.get(model_name.space()).unwrap().get_uuid();
-            let driver = ModelDriver::open_model_driver(
-                model_data,
-                &paths_v1::model_path(
+            let drivers = ModelDriver::open_model_drivers(model_data, |partition| {
+                paths_v1::model_partition_path(
                     model_name.space(),
                     space_uuid,
                     model_name.entity(),
                     model_data.get_uuid(),
-                ),
-            )?;
-            model.driver().initialize_model_driver(driver);
+                    partition,
+                )
+            })?;
+            model
+                .driver()
+                .initialize_model_drivers(model_journals(drivers));
         }
         Ok(())
     }
@@ -90,6 +141,34 @@ impl TestGlobal {
         me.set_max_data_pressure(1);
         me
     }
+    /// Create a global whose tasks are run by a [`Simulator`] (with the given flush window) instead of being run
+    /// (or queued) immediately
+    pub fn new_with_driver_id_simulated(log_name: &str, window: Duration) -> Self {
+        let mut me = Self::new_with_driver_id(log_name);
+        let model_count = me.gns.namespace().idx_models().read().len();
+        me.sim = Some(Mutex::new(Simulator::new(window, model_count)));
+        me
+    }
+    /// Returns the simulator
+    ///
+    /// ## Panics
+    ///
+    /// This will panic if the global is not simulated
+    pub fn simulator(&self) -> MutexGuard<'_, Simulator> {
+        self.sim.as_ref().expect("global is not simulated").lock()
+    }
+    /// Advance the simulator's clock by the given duration
+    pub fn sim_advance(&self, by: Duration) {
+        self.simulator().advance(self, by)
+    }
+    /// Advance the simulator's clock till the next flush has run
+    pub fn sim_advance_to_next_flush(&self) {
+        self.simulator().advance_to_next_flush(self)
+    }
+    /// Simulate a termination signal
+    pub fn sim_shutdown(&self) {
+        self.simulator().shutdown(self)
+    }
     pub fn new_with_driver_id(log_name: &str) -> Self {
         let data = GNSData::empty();
         let driver = match GNSDriver::create_gns_with_name(log_name) {
@@ -105,10 +184,28 @@ impl TestGlobal {
             },
         }
         .unwrap();
-        let me = Self::new(GlobalNS::new(data, FractalGNSDriver::new(driver)));
+        finish_drops(&data).unwrap();
+        // like the GNS, job records are kept in a file of their own for every test global
+        let jobs = Jobs::load(&format!("{log_name}-jobs.db")).unwrap();
+        let me = Self::new(GlobalNS::new(
+            data,
+            Box::new(SDSSDriver),
+            FractalGNSDriver::new(Box::new(driver)),
+            jobs,
+        ));
         me.load_model_drivers().unwrap();
         me
     }
+    /// Create a global that keeps its data with the given [`MemoryDriver`] (so it always starts empty)
+    pub fn new_with_memory_driver(log_name: &str, driver: &MemoryDriver) -> Self {
+        let jobs = Jobs::load(&format!("{log_name}-jobs.db")).unwrap();
+        Self::new(GlobalNS::new(
+            GNSData::empty(),
+            Box::new(driver.clone()),
+            FractalGNSDriver::new(driver.gns_journal()),
+            jobs,
+        ))
+    }
 }
 
 impl GlobalInstanceLike for TestGlobal {
@@ -119,17 +216,18 @@ impl GlobalInstanceLike for TestGlobal {
         &self.gns
     }
     fn taskmgr_post_high_priority(&self, task: Task<CriticalTask>) {
+        if let Some(sim) = self.sim.as_ref() {
+            return sim.lock().post_high_priority(task);
+        }
         match task.into_task() {
-            CriticalTask::WriteBatch(mdl_id, count) => {
+            CriticalTask::WriteBatch(mdl_id, _) => {
                 let models = self.gns.namespace().idx_models().read();
                 let mdl = models
                     .get(&EntityIDRef::new(mdl_id.space(), mdl_id.model()))
                     .unwrap();
-                let mut mdl_driver = mdl.driver().batch_driver().lock();
-                mdl_driver
-                    .as_mut()
-                    .unwrap()
-                    .commit_with_ctx(StdModelBatch::new(mdl.data(), count), BatchStats::new())
+                mdl.driver()
+                    .commit_taken_deltas(mdl.data())
+                    .map_err(|(e, _)| e)
                     .unwrap()
             }
             CriticalTask::TryModelAutorecoverLWT(_) => {}
@@ -137,57 +235,63 @@ impl GlobalInstanceLike for TestGlobal {
         }
     }
     fn taskmgr_post_standard_priority(&self, task: Task<GenericTask>) {
+        if let Some(sim) = self.sim.as_ref() {
+            return sim.lock().post_low_priority(task);
+        }
         self.lp_queue.write().push(task)
     }
+    fn spawn_job(&self, job: Box<dyn FnOnce(&Self) + Send>) {
+        // jobs run to completion before the statement that started them returns
+        job(self)
+    }
     fn get_max_delta_size(&self) -> usize {
         self.max_delta_size
     }
-    fn purge_model_driver(
-        &self,
-        space_name: &str,
-        space_uuid: Uuid,
-        model_name: &str,
-        model_uuid: Uuid,
-    ) {
-        self.taskmgr_post_standard_priority(Task::new(GenericTask::delete_model_dir(
-            space_name, space_uuid, model_name, model_uuid,
-        )));
-    }
-    fn initialize_model_driver(
-        &self,
-        space_name: &str,
-        space_uuid: Uuid,
-        model_name: &str,
-        model_uuid: Uuid,
-    ) -> crate::engine::error::RuntimeResult<FractalModelDriver> {
-        // create model dir
-        FileSystem::create_dir_all(&paths_v1::model_dir(
-            space_name, space_uuid, model_name, model_uuid,
-        ))?;
-        let driver = ModelDriver::create_model_driver(&paths_v1::model_path(
-            space_name, space_uuid, model_name, model_uuid,
-        ))?;
-        Ok(super::drivers::FractalModelDriver::init(driver))
+    fn get_query_memory_limit(&self) -> usize {
+        self.query_memory_limit
+    }
+    fn get_blob_max_size(&self) -> u64 {
+        self.blob_max_size
+    }
+    fn get_response_limits(&self) -> ResponseLimits {
+        self.response_limits
+    }
+    fn get_soft_limit(&self) -> SoftLimit {
+        self.soft_limit
+    }
+    fn memory_watermark(&self) -> &MemoryWatermark {
+        &self.memory_watermark
+    }
+    fn scheduler(&self) -> &Scheduler {
+        &self.scheduler
+    }
+    fn flush_policy(&self) -> &FlushPolicy {
+        &self.flush_policy
+    }
+    fn write_throttle(&self) -> &WriteThrottle {
+        &self.write_throttle
+    }
+    fn is_numa_aware(&self) -> bool {
+        false
+    }
+    fn get_memory_usage(&self) -> u64 {
+        self.memory_usage.load(Ordering::Relaxed)
     }
 }
 
 impl Drop for TestGlobal {
     fn drop(&mut self) {
-        let mut txn_driver = self.gns.gns_driver().txn_driver.lock();
-        GNSDriver::close_driver(&mut txn_driver).unwrap();
+        self.gns.gns_driver().txn_driver.lock().close().unwrap();
         for (_, model) in self.gns.namespace().idx_models().write().drain() {
             let delta_count = model
                 .data()
                 .delta_state()
                 .__fractal_take_full_from_data_delta(super::FractalToken::new());
             if delta_count != 0 {
-                let mut drv = model.driver().batch_driver().lock();
-                drv.as_mut()
-                    .unwrap()
-                    .commit_with_ctx(
-                        StdModelBatch::new(model.data(), delta_count),
-                        BatchStats::new(),
-                    )
+                model
+                    .driver()
+                    .commit_taken_deltas(model.data())
+                    .map_err(|(e, _)| e)
                     .unwrap();
             }
             model.into_driver().close().unwrap();
@@ -0,0 +1,907 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use {
+    super::{
+        drivers::BatchSizer,
+        error::Error,
+        jobs::{self, CancelToken, JobKind, JobStatus, Jobs},
+        sched::{Scheduler, WorkClass, MAX_WAIT},
+        test_utils::TestGlobal,
+        CriticalTask, FlushPolicy, GenericTask, GlobalInstanceLike, ModelUniqueID, Task,
+    },
+    crate::engine::{
+        config::ConfigFlushFailure,
+        core::{
+            dml,
+            model::ModelData,
+            notice::{Notice, NoticeCode},
+            session::{self, SessionSettings},
+            space::Space,
+            write_delay, EntityIDRef,
+        },
+        data::{cell::Datacell, lit::Lit},
+        error::{ErrorKind, QueryError, QueryResult},
+        idx::STIndex,
+        ql::{
+            ast::parse_ast_node_full,
+            ddl::{
+                crt::{CreateModel, CreateSpace},
+                drop::DropModel,
+                SessionSet, SetValue,
+            },
+            dml::{del::DeleteStatement, ins::InsertStatement},
+            tests::lex_insecure,
+        },
+        storage::{driver::MemoryDriver, safe_interfaces::FileSystem},
+        sync,
+    },
+    crate::util::os,
+    std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::{Duration, Instant},
+    },
+};
+
+const WINDOW: Duration = Duration::from_secs(300);
+const RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+fn create_space_and_model(global: &TestGlobal) {
+    create_space_and_model_with(global, "")
+}
+
+fn create_space_and_model_with(global: &TestGlobal, props: &str) {
+    let tokens = lex_insecure(b"create space myspace").unwrap();
+    let create_space: CreateSpace = parse_ast_node_full(&tokens[2..]).unwrap();
+    Space::transactional_exec_create(global, create_space).unwrap();
+    let query = format!("create model myspace.mymodel(username: string, password: string) {props}");
+    let tokens = lex_insecure(query.as_bytes()).unwrap();
+    let create_model: CreateModel = parse_ast_node_full(&tokens[2..]).unwrap();
+    ModelData::transactional_exec_create(global, create_model).unwrap();
+}
+
+fn insert(global: &TestGlobal, username: &str) {
+    try_insert(global, username).unwrap()
+}
+
+fn try_insert(global: &TestGlobal, username: &str) -> QueryResult<()> {
+    let query = format!("insert into myspace.mymodel('{username}', 'password')");
+    let tokens = lex_insecure(query.as_bytes()).unwrap();
+    let insert: InsertStatement = parse_ast_node_full(&tokens[1..]).unwrap();
+    dml::insert(global, insert)
+}
+
+fn delete(global: &TestGlobal, username: &str) {
+    let query = format!("delete from myspace.mymodel where username = '{username}'");
+    let tokens = lex_insecure(query.as_bytes()).unwrap();
+    let delete: DeleteStatement = parse_ast_node_full(&tokens[1..]).unwrap();
+    dml::delete(global, delete).unwrap();
+}
+
+fn row_count(global: &TestGlobal) -> usize {
+    global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+            Ok(mdl.primary_index().count())
+        })
+        .unwrap()
+}
+
+fn pending_deltas(global: &TestGlobal) -> usize {
+    global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+            Ok(mdl.delta_state().__fractal_data_delta_size())
+        })
+        .unwrap()
+}
+
+/// Have the model's driver report that a batch couldn't be written
+fn fail_persist(global: &TestGlobal, e: &Error) {
+    let models = global.state().namespace().idx_models().read();
+    let mdl = models.get(&EntityIDRef::new("myspace", "mymodel")).unwrap();
+    mdl.driver().report_persist_failure(
+        global,
+        ModelUniqueID::new("myspace", "mymodel", mdl.data().get_uuid()),
+        e,
+    );
+}
+
+/// Have the model's driver report that it was recovered
+fn recover_persist(global: &TestGlobal) {
+    let models = global.state().namespace().idx_models().read();
+    let mdl = models.get(&EntityIDRef::new("myspace", "mymodel")).unwrap();
+    mdl.driver().report_persist_recovery(global);
+}
+
+#[test]
+fn sim_flush_on_window() {
+    let global = TestGlobal::new_with_driver_id_simulated("sim_flush_on_window", WINDOW);
+    create_space_and_model(&global);
+    for i in 0..10 {
+        insert(&global, &format!("user{i}"));
+    }
+    assert_eq!(pending_deltas(&global), 10);
+    // nothing happens until the window elapses
+    global.sim_advance(WINDOW - Duration::from_secs(1));
+    assert_eq!(pending_deltas(&global), 10);
+    assert_eq!(global.simulator().flush_count(), 0);
+    global.sim_advance(Duration::from_secs(1));
+    assert_eq!(pending_deltas(&global), 0);
+    assert_eq!(global.simulator().flush_count(), 1);
+    assert_eq!(global.simulator().next_flush_in(), WINDOW);
+    // and then on every window
+    insert(&global, "user10");
+    global.sim_advance_to_next_flush();
+    assert_eq!(pending_deltas(&global), 0);
+    assert_eq!(global.simulator().now(), WINDOW * 2);
+    assert_eq!(global.simulator().flush_count(), 2);
+}
+
+#[test]
+fn sim_write_batch_when_cache_full() {
+    let mut global =
+        TestGlobal::new_with_driver_id_simulated("sim_write_batch_when_cache_full", WINDOW);
+    global.set_max_data_pressure(1);
+    create_space_and_model(&global);
+    insert(&global, "sayan");
+    // the batch write is queued, but not run until the clock moves
+    assert!(matches!(
+        global.simulator().queued_high_priority().as_slice(),
+        [CriticalTask::WriteBatch(mdl_id, 1)] if mdl_id.model() == "mymodel"
+    ));
+    assert_eq!(global.simulator().task_count(), 0);
+    global.sim_advance(Duration::ZERO);
+    assert!(global.simulator().queued_high_priority().is_empty());
+    assert_eq!(global.simulator().task_count(), 1);
+    assert_eq!(global.simulator().flush_count(), 0);
+}
+
+#[test]
+fn sim_write_batch_on_flush_triggers() {
+    let global = TestGlobal::new_with_driver_id_simulated("sim_write_batch_on_triggers", WINDOW);
+    create_space_and_model_with(
+        &global,
+        "with { flush_every_n_deltas: 3, flush_every_ms: 1000 }",
+    );
+    insert(&global, "user0");
+    insert(&global, "user1");
+    assert!(global.simulator().queued_high_priority().is_empty());
+    insert(&global, "user2");
+    assert!(matches!(
+        global.simulator().queued_high_priority().as_slice(),
+        [CriticalTask::WriteBatch(mdl_id, 3)] if mdl_id.model() == "mymodel"
+    ));
+    global.sim_advance(Duration::ZERO);
+    assert!(global.simulator().queued_high_priority().is_empty());
+    assert_eq!(pending_deltas(&global), 0);
+    // the interval is counted from the last time the changes were handed over
+    global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+            let now_ms = os::get_epoch_time_ms();
+            assert!(!mdl.flush_due(1, now_ms));
+            assert!(mdl.flush_due(1, now_ms + 1000));
+            // with nothing pending, there's nothing to write
+            assert!(!mdl.flush_due(0, now_ms + 1000));
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn strict_durability_writes_before_returning() {
+    let global = TestGlobal::new_with_driver_id_simulated("strict_durability", WINDOW);
+    create_space_and_model_with(&global, "with { durability: 'strict' }");
+    insert(&global, "sayan");
+    {
+        let models = global.state().namespace().idx_models().read();
+        let mdl = models.get(&EntityIDRef::new("myspace", "mymodel")).unwrap();
+        assert_eq!(mdl.data().delta_state().pending_data_deltas(), 0);
+        assert!(mdl.driver().flush_stats().last_persist().is_some());
+    }
+    // without going through the flusher
+    assert!(global.simulator().queued_high_priority().is_empty());
+    assert_eq!(global.simulator().task_count(), 0);
+    assert_eq!(global.simulator().flush_count(), 0);
+}
+
+#[test]
+fn session_durability_overrides_the_model() {
+    let global = TestGlobal::new_with_driver_id_simulated("session_durability", WINDOW);
+    create_space_and_model(&global);
+    let mut settings = SessionSettings::new();
+    settings
+        .set(&SessionSet::new(
+            "durability".into(),
+            SetValue::Name("strict".into()),
+        ))
+        .unwrap();
+    {
+        let _session = session::enter(&settings);
+        insert(&global, "sayan");
+    }
+    {
+        let models = global.state().namespace().idx_models().read();
+        let mdl = models.get(&EntityIDRef::new("myspace", "mymodel")).unwrap();
+        assert_eq!(mdl.data().delta_state().pending_data_deltas(), 0);
+        assert!(mdl.driver().flush_stats().last_persist().is_some());
+    }
+    // outside of the session, the model's own durability applies again
+    insert(&global, "robot");
+    assert_eq!(pending_deltas(&global), 1);
+}
+
+#[test]
+fn strict_flush_waits_until_the_statement_times_out() {
+    let global = TestGlobal::new_with_driver_id_simulated("strict_flush_timeout", WINDOW);
+    create_space_and_model_with(&global, "with { durability: 'strict' }");
+    let mut settings = SessionSettings::new();
+    settings
+        .set(&SessionSet::new(
+            "statement_timeout".into(),
+            SetValue::Lit(Lit::new_uint(50)),
+        ))
+        .unwrap();
+    {
+        // another flush is holding the drivers
+        let models = global.state().namespace().idx_models().read();
+        let mdl = models.get(&EntityIDRef::new("myspace", "mymodel")).unwrap();
+        let _drivers: Vec<_> = (0..mdl.driver().partitions())
+            .map(|partition| mdl.driver().batch_driver(partition).lock())
+            .collect();
+        let _session = session::enter(&settings);
+        let start = Instant::now();
+        assert_eq!(
+            try_insert(&global, "sayan").unwrap_err(),
+            QueryError::QExecStatementTimeout
+        );
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(mdl.driver().flush_stats().last_persist().is_none());
+    }
+    // the change was made, and is written by the next flush
+    assert_eq!(row_count(&global), 1);
+    global.sim_advance_to_next_flush();
+    let models = global.state().namespace().idx_models().read();
+    let mdl = models.get(&EntityIDRef::new("myspace", "mymodel")).unwrap();
+    assert_eq!(mdl.data().delta_state().pending_data_deltas(), 0);
+    assert!(mdl.driver().flush_stats().last_persist().is_some());
+}
+
+#[test]
+fn sim_lp_retry_backoff_resets_flush() {
+    let global = TestGlobal::new_with_driver_id_simulated("sim_lp_retry_backoff", WINDOW);
+    global.sim_advance(Duration::from_secs(100));
+    // this directory doesn't exist yet, so the first attempt fails and the task is retried after a backoff
+    global.taskmgr_post_standard_priority(Task::new(GenericTask::DeleteDirAll(
+        "sim_lp_retry_backoff_dir".into(),
+    )));
+    global.sim_advance(Duration::ZERO);
+    assert!(matches!(
+        global.simulator().queued_low_priority().as_slice(),
+        [GenericTask::DeleteDirAll(dir)] if dir.as_ref() == "sim_lp_retry_backoff_dir"
+    ));
+    // the flush is pushed back since the executor is busy with the retry
+    assert_eq!(global.simulator().next_flush_in(), RETRY_BACKOFF + WINDOW);
+    FileSystem::create_dir_all("sim_lp_retry_backoff_dir").unwrap();
+    global.sim_advance(RETRY_BACKOFF - Duration::from_secs(1));
+    assert_eq!(global.simulator().queued_low_priority().len(), 1);
+    global.sim_advance(Duration::from_secs(1));
+    assert!(global.simulator().queued_low_priority().is_empty());
+    assert_eq!(global.simulator().task_count(), 2);
+    assert!(FileSystem::remove_dir("sim_lp_retry_backoff_dir").is_err());
+    global.sim_advance_to_next_flush();
+    assert_eq!(
+        global.simulator().now(),
+        Duration::from_secs(100) + RETRY_BACKOFF + WINDOW
+    );
+}
+
+#[test]
+fn sim_shutdown_runs_pending() {
+    let mut global = TestGlobal::new_with_driver_id_simulated("sim_shutdown_runs_pending", WINDOW);
+    create_space_and_model(&global);
+    insert(&global, "sayan");
+    global.set_max_data_pressure(1);
+    insert(&global, "ohsayan");
+    assert_eq!(global.simulator().queued_high_priority().len(), 1);
+    global.sim_shutdown();
+    assert!(global.simulator().queued_high_priority().is_empty());
+    assert_eq!(global.simulator().flush_count(), 1);
+    assert_eq!(global.simulator().now(), Duration::ZERO);
+    assert_eq!(pending_deltas(&global), 0);
+}
+
+#[test]
+fn sim_purge_tombstones_on_flush() {
+    let global = TestGlobal::new_with_driver_id_simulated("sim_purge_tombstones_on_flush", WINDOW);
+    create_space_and_model_with(
+        &global,
+        "with { soft_delete: true, tombstone_retention: 0 }",
+    );
+    insert(&global, "sayan");
+    insert(&global, "ohsayan");
+    delete(&global, "sayan");
+    // the row is only marked deleted ..
+    assert_eq!(row_count(&global), 2);
+    // .. until the next flush purges it
+    global.sim_advance_to_next_flush();
+    assert_eq!(row_count(&global), 1);
+    assert_eq!(pending_deltas(&global), 0);
+}
+
+#[test]
+fn sim_shutdown_skips_sweeps() {
+    let global = TestGlobal::new_with_driver_id_simulated("sim_shutdown_skips_sweeps", WINDOW);
+    create_space_and_model_with(
+        &global,
+        "with { soft_delete: true, tombstone_retention: 0 }",
+    );
+    insert(&global, "sayan");
+    delete(&global, "sayan");
+    // the changes are flushed, but the tombstone is left for a sweep after the restart
+    global.sim_shutdown();
+    assert_eq!(row_count(&global), 1);
+    assert_eq!(pending_deltas(&global), 0);
+}
+
+#[test]
+fn sim_flush_stats_and_forced_flush() {
+    let mut global = TestGlobal::new_with_driver_id_simulated("sim_flush_stats", WINDOW);
+    create_space_and_model(&global);
+    insert(&global, "sayan");
+    global.set_max_data_pressure(1);
+    insert(&global, "ohsayan");
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    {
+        let models = global.state().namespace().idx_models().read();
+        let mdl = models.get(&entity).unwrap();
+        assert_eq!(mdl.data().delta_state().data_stalls(), 1);
+        assert_eq!(mdl.driver().flush_stats().last_persist(), None);
+        assert_eq!(mdl.data().delta_state().pending_data_deltas(), 2);
+        // flushing doesn't wait for the queued batch write
+        mdl.driver()
+            .flush(
+                &global,
+                ModelUniqueID::new("myspace", "mymodel", mdl.data().get_uuid()),
+                mdl.data(),
+            )
+            .unwrap();
+        assert_eq!(mdl.data().delta_state().pending_data_deltas(), 0);
+        assert!(mdl.driver().flush_stats().last_persist().is_some());
+        assert_eq!(mdl.driver().flush_stats().failures(), 0);
+    }
+    // and the queued batch write has nothing left to do
+    global.sim_advance(Duration::ZERO);
+    assert!(global.simulator().queued_high_priority().is_empty());
+}
+
+#[test]
+fn sim_retry_backoff_is_exponential() {
+    let global = TestGlobal::new_with_driver_id_simulated("sim_retry_backoff_exp", WINDOW);
+    // this directory never exists, so every attempt fails
+    global.taskmgr_post_standard_priority(Task::new(GenericTask::DeleteDirAll(
+        "sim_retry_backoff_exp_dir".into(),
+    )));
+    global.sim_advance(Duration::ZERO);
+    assert_eq!(global.simulator().task_count(), 1);
+    global.sim_advance(RETRY_BACKOFF);
+    assert_eq!(global.simulator().task_count(), 2);
+    // the next retry waits twice as long
+    global.sim_advance(RETRY_BACKOFF * 2 - Duration::from_secs(1));
+    assert_eq!(global.simulator().task_count(), 2);
+    global.sim_advance(Duration::from_secs(1));
+    assert_eq!(global.simulator().task_count(), 3);
+    // and the backoff never goes past the limit
+    for _ in 0..10 {
+        global.sim_advance(Duration::from_secs(5 * 60));
+    }
+    assert_eq!(global.simulator().task_count(), 13);
+}
+
+#[test]
+fn flush_failure_policy() {
+    let mut global = TestGlobal::new_with_driver_id("flush_failure_policy");
+    create_space_and_model(&global);
+    let error = Error::from(ErrorKind::Other("write failed".into()));
+    fail_persist(&global, &error);
+    // reporting the same failure twice doesn't count twice
+    fail_persist(&global, &error);
+    assert_eq!(global.health().degraded_models(), 1);
+    // writes are blocked by default
+    assert_eq!(
+        try_insert(&global, "sayan").unwrap_err(),
+        QueryError::SysStorageDegraded
+    );
+    // but can pile up to the cap
+    global.set_flush_policy(FlushPolicy::new(ConfigFlushFailure::Accumulate, 2));
+    insert(&global, "sayan");
+    insert(&global, "ohsayan");
+    assert_eq!(
+        try_insert(&global, "sky").unwrap_err(),
+        QueryError::SysStorageDegraded
+    );
+    recover_persist(&global);
+    assert!(!global.health().is_degraded());
+    insert(&global, "sky");
+    assert_eq!(row_count(&global), 3);
+}
+
+#[test]
+#[cfg(unix)]
+fn disk_full_is_read_only() {
+    let global = TestGlobal::new_with_driver_id("disk_full_is_read_only");
+    create_space_and_model(&global);
+    insert(&global, "sayan");
+    fail_persist(
+        &global,
+        &Error::from(std::io::Error::from_raw_os_error(libc::ENOSPC)),
+    );
+    assert!(global.health().is_disk_full());
+    // no writes at all, not even to other models or the schema
+    assert_eq!(
+        try_insert(&global, "ohsayan").unwrap_err(),
+        QueryError::SysDiskFull
+    );
+    let tokens = lex_insecure(b"create space otherspace").unwrap();
+    let create_space: CreateSpace = parse_ast_node_full(&tokens[2..]).unwrap();
+    assert_eq!(
+        Space::transactional_exec_create(&global, create_space).unwrap_err(),
+        QueryError::SysDiskFull
+    );
+    // but reads still work
+    assert_eq!(row_count(&global), 1);
+    // once space is freed, the driver recovers and we take writes again
+    recover_persist(&global);
+    assert!(!global.health().is_disk_full());
+    insert(&global, "ohsayan");
+}
+
+#[test]
+fn freeze_rejects_writes() {
+    let global = TestGlobal::new_with_driver_id("freeze_rejects_writes");
+    create_space_and_model(&global);
+    insert(&global, "sayan");
+    let freeze = global.state().freeze();
+    freeze.freeze_space("myspace");
+    assert_eq!(
+        try_insert(&global, "ohsayan").unwrap_err(),
+        QueryError::SysFrozen
+    );
+    let tokens = lex_insecure(b"drop model myspace.mymodel").unwrap();
+    let drop_model: DropModel = parse_ast_node_full(&tokens[2..]).unwrap();
+    assert_eq!(
+        ModelData::transactional_exec_drop(&global, drop_model).unwrap_err(),
+        QueryError::SysFrozen
+    );
+    // other spaces can still be created
+    let tokens = lex_insecure(b"create space otherspace").unwrap();
+    let create_space: CreateSpace = parse_ast_node_full(&tokens[2..]).unwrap();
+    Space::transactional_exec_create(&global, create_space).unwrap();
+    // but not once the whole instance is frozen
+    freeze.freeze_instance();
+    let tokens = lex_insecure(b"create space thirdspace").unwrap();
+    let create_space: CreateSpace = parse_ast_node_full(&tokens[2..]).unwrap();
+    assert_eq!(
+        Space::transactional_exec_create(&global, create_space).unwrap_err(),
+        QueryError::SysFrozen
+    );
+    assert_eq!(
+        freeze.describe(),
+        "{\"instance\":true,\"spaces\":[\"myspace\"]}"
+    );
+    // reads still work
+    assert_eq!(row_count(&global), 1);
+    freeze.unfreeze_instance();
+    freeze.unfreeze_space("myspace");
+    assert!(!freeze.is_frozen());
+    insert(&global, "ohsayan");
+    assert_eq!(row_count(&global), 2);
+}
+
+#[test]
+fn sim_write_throttle() {
+    let mut global = TestGlobal::new_with_driver_id_simulated("sim_write_throttle", WINDOW);
+    global.set_write_throttle(4, 0);
+    create_space_and_model(&global);
+    let entity = EntityIDRef::new("myspace", "mymodel");
+    for i in 0..4 {
+        assert_eq!(write_delay(&global, entity), None);
+        insert(&global, &format!("user{i}"));
+    }
+    assert_eq!(global.write_throttle().delayed(), 0);
+    // past the threshold, writes are delayed (but still go through)
+    assert_eq!(write_delay(&global, entity), None);
+    insert(&global, "user4");
+    for i in 5..8 {
+        assert!(write_delay(&global, entity).is_some());
+        insert(&global, &format!("user{i}"));
+    }
+    assert_eq!(global.write_throttle().delayed(), 3);
+    assert!(global.write_throttle().delayed_for() >= Duration::from_millis(150));
+    // and at twice the threshold, they're rejected
+    assert_eq!(
+        try_insert(&global, "user8").unwrap_err(),
+        QueryError::SysWriteThrottled
+    );
+    assert_eq!(global.write_throttle().rejected(), 1);
+    assert_eq!(row_count(&global), 8);
+    // once the changes are written, writes go through again
+    global.sim_advance_to_next_flush();
+    assert_eq!(write_delay(&global, entity), None);
+    insert(&global, "user8");
+    assert_eq!(global.write_throttle().delayed(), 3);
+    assert_eq!(row_count(&global), 9);
+}
+
+#[test]
+fn memory_driver() {
+    let driver = MemoryDriver::new();
+    let global = TestGlobal::new_with_memory_driver("memory_driver", &driver);
+    create_space_and_model(&global);
+    // one event for the space and one for the model
+    assert_eq!(driver.events(), 2);
+    insert(&global, "sayan");
+    insert(&global, "ohsayan");
+    delete(&global, "sayan");
+    let flush = || {
+        let models = global.state().namespace().idx_models().read();
+        let mdl = models.get(&EntityIDRef::new("myspace", "mymodel")).unwrap();
+        mdl.driver().flush(
+            &global,
+            ModelUniqueID::new("myspace", "mymodel", mdl.data().get_uuid()),
+            mdl.data(),
+        )
+    };
+    let pending = || {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                Ok(mdl.delta_state().pending_data_deltas())
+            })
+            .unwrap()
+    };
+    flush().unwrap();
+    assert_eq!(driver.changes(), 3);
+    assert_eq!(pending(), 0);
+    // a batch that can't be written leaves its changes with the model
+    driver.set_failing(true);
+    insert(&global, "sky");
+    assert_eq!(flush().unwrap_err(), QueryError::SysServerError);
+    assert_eq!(pending(), 1);
+    // and the schema can't be changed either
+    let tokens = lex_insecure(b"drop model allow not empty myspace.mymodel").unwrap();
+    let drop_model: DropModel = parse_ast_node_full(&tokens[2..]).unwrap();
+    assert_eq!(
+        ModelData::transactional_exec_drop(&global, drop_model).unwrap_err(),
+        QueryError::SysServerError
+    );
+    assert_eq!(row_count(&global), 2);
+    // once the driver is back, the changes are written by the next flush
+    driver.set_failing(false);
+    recover_persist(&global);
+    flush().unwrap();
+    assert_eq!(driver.changes(), 4);
+    assert_eq!(driver.events(), 2);
+}
+
+#[test]
+fn sim_overflow_moves_cold_rows() {
+    let mut global =
+        TestGlobal::new_with_driver_id_simulated("sim_overflow_moves_cold_rows", WINDOW);
+    global.set_memory_watermarks(1000, 500);
+    create_space_and_model_with(&global, "with { overflow: true }");
+    for i in 0..10 {
+        insert(&global, &format!("user{i}"));
+    }
+    // returns the number of spilled rows, after reading back the rows with the given keys
+    let touch = |keys: &[&str]| {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                let g = sync::atm::cpin();
+                let rows: Vec<_> = mdl.primary_index().iter(&g).collect();
+                for row in rows.iter() {
+                    if keys.contains(&row.d_key().str().unwrap()) {
+                        assert_eq!(
+                            row.cloned_data(),
+                            [(
+                                "password".to_owned().into_boxed_str(),
+                                Datacell::new_str("password".into())
+                            )]
+                        );
+                    }
+                }
+                Ok(rows.iter().filter(|row| row.is_spilled()).count())
+            })
+            .unwrap()
+    };
+    let describe = || {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+                Ok(mdl.overflow().unwrap().describe())
+            })
+            .unwrap()
+    };
+    // we aren't running low on memory, so the rows are only swept
+    global.sim_advance_to_next_flush();
+    assert_eq!(touch(&[]), 0);
+    assert_eq!(
+        describe(),
+        "{\"rows\":0,\"bytes\":0,\"file_size\":0,\"failed_reads\":0,\"sweeps\":1,\"spilled\":0}"
+    );
+    // now we are, so every row that wasn't accessed since the sweep is moved
+    global.set_memory_usage(10_000);
+    assert_eq!(touch(&["user0"]), 0);
+    global.sim_advance_to_next_flush();
+    assert_eq!(touch(&[]), 9);
+    assert!(describe().starts_with("{\"rows\":9,"));
+    // rows are read back as they're accessed
+    assert_eq!(touch(&["user1", "user2"]), 7);
+    assert!(describe().starts_with("{\"rows\":7,"));
+    let keys: Vec<String> = (0..10).map(|i| format!("user{i}")).collect();
+    let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+    assert_eq!(touch(&keys), 0);
+    // and the file is emptied once nothing in it is live anymore
+    assert!(describe().starts_with("{\"rows\":0,\"bytes\":0,\"file_size\":0,"));
+}
+
+#[test]
+fn sim_overflow_spills_while_rows_are_read() {
+    const ROWS: usize = 100;
+    const SPILLS: usize = 200;
+    let global =
+        TestGlobal::new_with_driver_id_simulated("sim_overflow_spills_while_rows_are_read", WINDOW);
+    create_space_and_model_with(&global, "with { overflow: true }");
+    for i in 0..ROWS {
+        insert(&global, &format!("user{i}"));
+    }
+    let with_model = |f: &dyn Fn(&ModelData)| {
+        global
+            .state()
+            .namespace()
+            .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| Ok(f(mdl)))
+            .unwrap()
+    };
+    /// A model that is shared with the reader threads, like the server shares it through the global
+    struct SharedModel<'a>(&'a ModelData);
+    unsafe impl<'a> Sync for SharedModel<'a> {}
+    impl<'a> SharedModel<'a> {
+        fn get(&self) -> &'a ModelData {
+            self.0
+        }
+    }
+    let moved = global
+        .state()
+        .namespace()
+        .with_model(EntityIDRef::new("myspace", "mymodel"), |mdl| {
+            let shared = SharedModel(mdl);
+            let done = AtomicBool::new(false);
+            Ok(std::thread::scope(|s| {
+                for t in 0..4 {
+                    let (shared, done) = (&shared, &done);
+                    s.spawn(move || {
+                        let mut i = t;
+                        while !done.load(Ordering::Acquire) {
+                            // only one row is read at a time, so that the others go cold
+                            let g = sync::atm::cpin();
+                            let row = shared.get().primary_index().iter(&g).nth(i % ROWS).unwrap();
+                            assert_eq!(
+                                row.cloned_data(),
+                                [(
+                                    "password".to_owned().into_boxed_str(),
+                                    Datacell::new_str("password".into())
+                                )],
+                                "row {} was read while it was being moved",
+                                row.d_key().str().unwrap()
+                            );
+                            i += 7;
+                        }
+                    });
+                }
+                let token = CancelToken::default();
+                let moved = (0..SPILLS)
+                    .map(|_| mdl.overflow().unwrap().maintain(mdl, u64::MAX, &token))
+                    .sum::<usize>();
+                done.store(true, Ordering::Release);
+                moved
+            }))
+        })
+        .unwrap();
+    assert_ne!(moved, 0);
+    // every row can still be read once the dust settles
+    with_model(&|mdl| {
+        let g = sync::atm::cpin();
+        for row in mdl.primary_index().iter(&g) {
+            assert_eq!(row.d_data().read().unwrap().fields().st_len(), 1);
+            assert!(!row.is_spilled());
+        }
+    });
+}
+
+#[test]
+fn batch_size_follows_throughput() {
+    let mut sizer = BatchSizer::new();
+    // a second's worth of changes
+    assert_eq!(
+        sizer.observe(10_000, Duration::from_secs(1), Duration::from_millis(5)),
+        10_000
+    );
+    // the throughput is averaged
+    assert_eq!(
+        sizer.observe(30_000, Duration::from_secs(1), Duration::from_millis(5)),
+        15_000
+    );
+    // slow writes space batches out
+    let mut sizer = BatchSizer::new();
+    assert_eq!(
+        sizer.observe(1_000, Duration::from_secs(1), Duration::from_millis(200)),
+        2_000
+    );
+    // and a trickle of changes is left to the background flush
+    let mut sizer = BatchSizer::new();
+    assert_eq!(
+        sizer.observe(10, Duration::from_secs(1), Duration::from_millis(1)),
+        1024
+    );
+}
+
+#[test]
+fn jobs_are_persisted() {
+    let global = TestGlobal::new_with_driver_id("fractal_jobs_are_persisted");
+    const JOBS_FILE: &str = "fractal_jobs_are_persisted-jobs.db";
+    let id = jobs::start(
+        &global,
+        Some("sayan"),
+        JobKind::VerifyModel,
+        "myspace.mymodel".into(),
+        |global, job| {
+            job.set_total(4);
+            job.set_done(2);
+            assert_eq!(job.record().percent(), 50);
+            // if we went down now, the job would be listed as interrupted after the restart
+            let restarted = Jobs::load(JOBS_FILE).unwrap();
+            assert_eq!(
+                restarted.get(job.id()).unwrap().status(),
+                JobStatus::Interrupted
+            );
+            assert_eq!(
+                global.state().jobs().get(job.id()).unwrap().status(),
+                JobStatus::Running
+            );
+            JobStatus::Completed(Some("{\"consistent\":true}".into()))
+        },
+    );
+    let cancelled = jobs::start(
+        &global,
+        Some("sayan"),
+        JobKind::BuildIndex,
+        "myspace.mymodel.bycity".into(),
+        |global, job| {
+            assert_eq!(global.state().jobs().cancel(job.id()), Ok(true));
+            assert!(job.token().is_cancelled());
+            JobStatus::Cancelled
+        },
+    );
+    assert_eq!(cancelled, id + 1);
+    // the owner hears about both jobs with their next statement (and nobody else does)
+    Notice::clear();
+    global.state().jobs().raise_notices("root");
+    assert!(Notice::take_all().is_empty());
+    global.state().jobs().raise_notices("sayan");
+    let notices = Notice::take_all();
+    assert_eq!(
+        notices
+            .iter()
+            .map(|n| (n.code(), n.message()))
+            .collect::<Vec<_>>(),
+        [
+            (
+                NoticeCode::JobFinished,
+                "job 1 (verify model myspace.mymodel) completed"
+            ),
+            (
+                NoticeCode::JobFinished,
+                "job 2 (build index myspace.mymodel.bycity) was cancelled"
+            ),
+        ]
+    );
+    global.state().jobs().raise_notices("sayan");
+    assert!(Notice::take_all().is_empty());
+    // the records outlive a restart
+    let restarted = Jobs::load(JOBS_FILE).unwrap();
+    assert_eq!(restarted.describe(), global.state().jobs().describe());
+    let record = restarted.get(id).unwrap().record();
+    assert_eq!(record.owner.as_deref(), Some("sayan"));
+    assert_eq!((record.done, record.total, record.percent()), (2, 4, 100));
+    assert_eq!(
+        record.status,
+        JobStatus::Completed(Some("{\"consistent\":true}".into()))
+    );
+    assert_eq!(
+        restarted.get(cancelled).unwrap().status(),
+        JobStatus::Cancelled
+    );
+}
+
+#[test]
+fn maintenance_is_throttled_while_queries_run() {
+    let sched = Scheduler::new(1000);
+    let now = Instant::now();
+    // nothing is running in the foreground, so maintenance runs at full speed
+    assert_eq!(sched.take(5000, now), Duration::ZERO);
+    let query = sched.foreground();
+    assert_eq!(sched.foreground_in_flight(), 1);
+    // the bucket holds a second's worth of work
+    assert_eq!(sched.take(1000, now), Duration::ZERO);
+    assert_eq!(sched.take(500, now), Duration::from_millis(500));
+    // and refills at the rate
+    assert_eq!(
+        sched.take(500, now + Duration::from_secs(1)),
+        Duration::ZERO
+    );
+    // no one waits for longer than MAX_WAIT
+    assert_eq!(sched.take(10_000, now + Duration::from_secs(1)), MAX_WAIT);
+    drop(query);
+    assert_eq!(sched.foreground_in_flight(), 0);
+    assert_eq!(
+        sched.take(10_000, now + Duration::from_secs(1)),
+        Duration::ZERO
+    );
+    // a rate of 0 never holds maintenance back
+    let sched = Scheduler::new(0);
+    let _query = sched.foreground();
+    assert_eq!(sched.take(u64::MAX, now), Duration::ZERO);
+}
+
+#[test]
+fn sim_maintenance_is_accounted() {
+    let mut global =
+        TestGlobal::new_with_driver_id_simulated("sim_maintenance_is_accounted", WINDOW);
+    global.set_maintenance_rate(1_000_000);
+    create_space_and_model(&global);
+    for i in 0..10 {
+        insert(&global, &format!("user{i}"));
+    }
+    global
+        .scheduler()
+        .record(WorkClass::Foreground, 10, Duration::from_millis(20));
+    global.sim_advance(WINDOW);
+    assert_eq!(global.simulator().flush_count(), 1);
+    let report = global.scheduler().describe();
+    assert!(report.starts_with("{\"rate\":1000000,\"foreground_in_flight\":0,"));
+    assert!(report.contains("\"foreground\":{\"units\":10,\"busy_ms\":20,\"throttled_ms\":0,"));
+    assert!(report.contains("\"flush\":{\"units\":10,"));
+    assert!(report
+        .contains("\"scrub\":{\"units\":0,\"busy_ms\":0,\"throttled_ms\":0,\"utilization\":0}"));
+}
@@ -0,0 +1,185 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    write throttling
+    ---
+    writes to a model are throttled when it falls behind: either its changes pile up faster than they're written to
+    disk (the backlog, `system.throttle_backlog` changes), or its webhooks fall behind the journal (the lag,
+    `system.throttle_lag` bytes). the throttling is progressive, so that a model under sustained overload slows down
+    its writers instead of running out of memory (or leaving its webhooks further and further behind):
+
+    - until a signal is past its threshold, writes aren't held back at all
+    - past the threshold, every write is delayed, by up to DELAY_MAX as the signal grows to twice the threshold
+    - at twice the threshold, writes are rejected with `SysWriteThrottled` and a hint to retry after RETRY_AFTER
+
+    the worse of the two signals decides, and a threshold of 0 disables its signal. reads are never throttled. how
+    many writes were delayed (and for how long) and rejected is what `sysctl report status` shows.
+
+    a statement waits out its delay in the async dispatch, before it runs (see `core::exec`), so that it doesn't hold
+    up a worker thread (or the model). writes that aren't statements (like bulk inserts and blob uploads) are only
+    ever rejected
+*/
+
+use {
+    crate::engine::error::{QueryError, QueryResult},
+    std::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    },
+};
+
+/// The longest that a write is delayed (right before writes are rejected)
+pub const DELAY_MAX: Duration = Duration::from_millis(100);
+/// How long a client whose write was rejected is asked to wait before retrying
+pub const RETRY_AFTER: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// What happens to a write
+pub enum Throttle {
+    /// the write goes ahead right away
+    None,
+    /// the write goes ahead after waiting for this long
+    Delay(Duration),
+    /// the write is rejected
+    Reject,
+}
+
+/// Throttles writes to models that fall behind
+pub struct WriteThrottle {
+    backlog: u64,
+    lag: u64,
+    delayed: AtomicU64,
+    /// the time (in microseconds) that writes were delayed for
+    delayed_us: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl WriteThrottle {
+    /// Create a new throttle for the given backlog (in changes) and lag (in journal bytes). A threshold of 0 disables
+    /// its signal
+    pub const fn new(backlog: u64, lag: u64) -> Self {
+        Self {
+            backlog,
+            lag,
+            delayed: AtomicU64::new(0),
+            delayed_us: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        (self.backlog != 0) | (self.lag != 0)
+    }
+    pub fn backlog_threshold(&self) -> u64 {
+        self.backlog
+    }
+    pub fn lag_threshold(&self) -> u64 {
+        self.lag
+    }
+    pub fn delayed(&self) -> u64 {
+        self.delayed.load(Ordering::Relaxed)
+    }
+    pub fn delayed_for(&self) -> Duration {
+        Duration::from_micros(self.delayed_us.load(Ordering::Relaxed))
+    }
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+    /// Returns what happens to a write to a model with the given backlog and lag
+    pub fn check(&self, backlog: u64, lag: u64) -> Throttle {
+        // the delay grows with how far past its threshold a signal is, as a share of the threshold
+        let delay = [(backlog, self.backlog), (lag, self.lag)]
+            .into_iter()
+            .filter(|&(value, threshold)| (threshold != 0) & (value > threshold))
+            .map(|(value, threshold)| {
+                (value - threshold) as u128 * DELAY_MAX.as_nanos() / threshold as u128
+            })
+            .max();
+        match delay {
+            None => Throttle::None,
+            Some(delay) if delay >= DELAY_MAX.as_nanos() => Throttle::Reject,
+            Some(delay) => Throttle::Delay(Duration::from_nanos(delay as u64)),
+        }
+    }
+    /// Returns how long a write to a model with the given backlog and lag has to wait before it runs (as decided by
+    /// [`Self::check`]), counting it as delayed
+    pub fn delay(&self, backlog: u64, lag: u64) -> Option<Duration> {
+        match self.check(backlog, lag) {
+            Throttle::Delay(delay) => {
+                self.delayed.fetch_add(1, Ordering::Relaxed);
+                self.delayed_us
+                    .fetch_add(delay.as_micros() as u64, Ordering::Relaxed);
+                Some(delay)
+            }
+            Throttle::None | Throttle::Reject => None,
+        }
+    }
+    /// Reject a write to a model with the given backlog and lag if it's too far behind (as decided by
+    /// [`Self::check`]). A write that only has to be delayed goes ahead, since it waited with [`Self::delay`]
+    pub fn admit(&self, backlog: u64, lag: u64) -> QueryResult<()> {
+        match self.check(backlog, lag) {
+            Throttle::None | Throttle::Delay(_) => Ok(()),
+            Throttle::Reject => {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                Err(QueryError::SysWriteThrottled
+                    .with_detail("backlog", backlog)
+                    .with_detail("lag", lag)
+                    .with_detail("retry_after_secs", RETRY_AFTER.as_secs()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Throttle, WriteThrottle, DELAY_MAX};
+
+    #[test]
+    fn progressive() {
+        let throttle = WriteThrottle::new(100, 1000);
+        assert_eq!(throttle.check(100, 1000), Throttle::None);
+        assert_eq!(throttle.check(150, 0), Throttle::Delay(DELAY_MAX / 2));
+        // the worse signal decides
+        assert_eq!(throttle.check(125, 1500), Throttle::Delay(DELAY_MAX / 2));
+        assert_eq!(
+            throttle.check(199, 1000),
+            Throttle::Delay(DELAY_MAX / 100 * 99)
+        );
+        assert_eq!(throttle.check(200, 0), Throttle::Reject);
+        assert_eq!(throttle.check(0, 5000), Throttle::Reject);
+    }
+
+    #[test]
+    fn disabled() {
+        let throttle = WriteThrottle::new(0, 10);
+        assert!(throttle.is_enabled());
+        assert_eq!(throttle.check(u64::MAX, 10), Throttle::None);
+        assert_eq!(throttle.check(u64::MAX, 20), Throttle::Reject);
+        let throttle = WriteThrottle::new(0, 0);
+        assert!(!throttle.is_enabled());
+        assert_eq!(throttle.check(u64::MAX, u64::MAX), Throttle::None);
+    }
+}
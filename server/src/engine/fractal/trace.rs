@@ -0,0 +1,539 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    tracing
+    ---
+    when an OTLP collector is configured, spans are recorded for:
+    - `skyd.connection`: a connection, from the handshake (`skyd.handshake`) till it is closed
+    - `skyd.statement`: a statement, with its phases as children: `skyd.parse` (lexing; the AST is parsed as the
+    statement runs, so that is part of the next phase), `skyd.execute` and `skyd.respond` (encoding and writing the
+    response). a statement is a trace of its own, linked to the trace of its connection
+    - `skyd.flush`: a flush of a model's changes to disk
+
+    whether a trace is recorded is decided when its root span is started, for the given percentage of traces. if
+    tracing is disabled (or a trace isn't sampled) nothing is allocated. finished spans are buffered and exported
+    in the background to the collector's `/v1/traces` endpoint, using OTLP/HTTP with the JSON encoding (plain HTTP
+    only). spans are dropped (and counted) if the buffer is full or the collector fails to accept them; they are
+    never retried, since tracing must never hold up the server
+*/
+
+use {
+    super::super::{config::ConfigEndpointTcp, data::uuid::Uuid, storage::encode_str},
+    parking_lot::Mutex,
+    std::{
+        fmt::Write,
+        io, mem,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            OnceLock,
+        },
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        time,
+    },
+};
+
+/// The most spans that are held till the next export
+const BUFFER_MAX: usize = 16384;
+/// How often finished spans are exported
+const EXPORT_INTERVAL: Duration = Duration::from_secs(5);
+/// How long the collector has to accept an export
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+/// The most we'll read of the collector's response (we only need the status line)
+const RESPONSE_MAX: usize = 1024;
+
+static TRACER: OnceLock<Tracer> = OnceLock::new();
+
+struct Tracer {
+    endpoint: ConfigEndpointTcp,
+    /// the percentage of traces that are recorded
+    sample_rate: u64,
+    /// the number of root spans that were started
+    roots: AtomicU64,
+    /// spans that have finished, waiting to be exported
+    finished: Mutex<Vec<FinishedSpan>>,
+    /// spans dropped since the last export, because the buffer was full
+    dropped: AtomicU64,
+}
+
+impl Tracer {
+    /// Returns true if the next trace should be recorded. Traces are picked evenly, so that exactly `sample_rate`
+    /// out of every 100 traces are recorded
+    fn sample(&self) -> bool {
+        let n = self.roots.fetch_add(1, Ordering::Relaxed) % 100;
+        (n * self.sample_rate) / 100 != ((n + 1) * self.sample_rate) / 100
+    }
+    fn push(&self, span: FinishedSpan) {
+        let mut finished = self.finished.lock();
+        if finished.len() < BUFFER_MAX {
+            finished.push(span);
+        } else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    /// Export every span that has finished
+    async fn export(&self) -> io::Result<usize> {
+        let spans = mem::take(&mut *self.finished.lock());
+        let dropped = self.dropped.swap(0, Ordering::Relaxed);
+        if dropped != 0 {
+            warn!("trace: dropped {dropped} span(s) because too many were waiting to be exported");
+        }
+        if spans.is_empty() {
+            return Ok(0);
+        }
+        let body = encode_spans(&spans);
+        match time::timeout(EXPORT_TIMEOUT, post(&self.endpoint, &body)).await {
+            Ok(r) => r.map(|_| spans.len()),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "collector took too long to respond",
+            )),
+        }
+    }
+}
+
+/// Start exporting spans to the given collector, recording `sample_rate` percent of all traces. Spans are exported
+/// in the background until the runtime is shut down (call [`finish`] before that, to export what's left)
+///
+/// WARN: Must be in [`tokio::runtime::Runtime`] context!
+pub fn start(endpoint: &ConfigEndpointTcp, sample_rate: u8) {
+    let tracer = Tracer {
+        endpoint: endpoint.clone(),
+        sample_rate: sample_rate as u64,
+        roots: AtomicU64::new(0),
+        finished: Mutex::new(vec![]),
+        dropped: AtomicU64::new(0),
+    };
+    if TRACER.set(tracer).is_err() {
+        return;
+    }
+    tokio::spawn(async {
+        let tracer = TRACER.get().unwrap();
+        let mut failing = false;
+        loop {
+            time::sleep(EXPORT_INTERVAL).await;
+            match tracer.export().await {
+                Ok(_) if failing => {
+                    failing = false;
+                    info!("trace: exporting spans again");
+                }
+                Ok(_) => {}
+                Err(e) if !failing => {
+                    failing = true;
+                    warn!(
+                        "trace: failed to export spans to `{}:{}`: `{e}`",
+                        tracer.endpoint.host(),
+                        tracer.endpoint.port()
+                    );
+                }
+                Err(_) => {}
+            }
+        }
+    });
+}
+
+/// Export the spans that have finished (if tracing is enabled), so that none are lost on shutdown
+pub async fn finish() {
+    if let Some(tracer) = TRACER.get() {
+        match tracer.export().await {
+            Ok(0) => {}
+            Ok(n) => info!("trace: exported {n} remaining span(s)"),
+            Err(e) => warn!("trace: failed to export remaining spans: `{e}`"),
+        }
+    }
+}
+
+/// Returns the current time, in nanoseconds since the epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+fn new_trace_id() -> u128 {
+    u128::from_le_bytes(Uuid::new().to_le_bytes())
+}
+
+fn new_span_id() -> u64 {
+    // a span id can't be zero
+    (new_trace_id() as u64) | 1
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The value of a span attribute
+pub enum AttributeValue {
+    Str(String),
+    Int(u64),
+}
+
+#[derive(Debug, PartialEq)]
+/// A span that is being recorded. The span ends (and is queued for export) when it is dropped
+pub struct Span {
+    trace_id: u128,
+    span_id: u64,
+    parent_id: Option<u64>,
+    /// a span in another trace that this span follows from
+    link: Option<(u128, u64)>,
+    name: &'static str,
+    start: u64,
+    attributes: Vec<(&'static str, AttributeValue)>,
+    error: Option<String>,
+}
+
+impl Span {
+    /// Start a new trace, returning its root span. [`None`] is returned if tracing is disabled or the trace wasn't
+    /// sampled
+    pub fn root(name: &'static str) -> Option<Self> {
+        let tracer = TRACER.get()?;
+        if !tracer.sample() {
+            return None;
+        }
+        Some(Self {
+            trace_id: new_trace_id(),
+            span_id: new_span_id(),
+            parent_id: None,
+            link: None,
+            name,
+            start: now(),
+            attributes: vec![],
+            error: None,
+        })
+    }
+    /// Returns the time at which this span started
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+    /// Record a child of this span that has already finished
+    pub fn record_child(&self, name: &'static str, start: u64, end: u64) {
+        push(FinishedSpan {
+            trace_id: self.trace_id,
+            span_id: new_span_id(),
+            parent_id: Some(self.span_id),
+            link: None,
+            name,
+            start,
+            end,
+            attributes: vec![],
+            error: None,
+        })
+    }
+    /// Note that this span follows from `other` (which is in another trace)
+    pub fn link_to(&mut self, other: &Span) {
+        self.link = Some((other.trace_id, other.span_id));
+    }
+    pub fn set_str(&mut self, key: &'static str, value: impl Into<String>) {
+        self.attributes
+            .push((key, AttributeValue::Str(value.into())));
+    }
+    pub fn set_int(&mut self, key: &'static str, value: u64) {
+        self.attributes.push((key, AttributeValue::Int(value)));
+    }
+    /// Mark this span as failed
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        self.error = Some(message.into());
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        push(FinishedSpan {
+            trace_id: self.trace_id,
+            span_id: self.span_id,
+            parent_id: self.parent_id,
+            link: self.link,
+            name: self.name,
+            start: self.start,
+            end: now(),
+            attributes: mem::take(&mut self.attributes),
+            error: self.error.take(),
+        })
+    }
+}
+
+fn push(span: FinishedSpan) {
+    // the tracer is always set if there's a span
+    if let Some(tracer) = TRACER.get() {
+        tracer.push(span);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// The trace of a statement: the statement's span, with a child for every phase that it has been through
+pub struct StatementTrace {
+    span: Span,
+    /// the time at which the last phase ended
+    last: u64,
+    parsed: bool,
+}
+
+impl StatementTrace {
+    /// Start tracing a statement (if the trace is sampled), linking it to the trace of its connection
+    pub fn start(connection: Option<&Span>) -> Option<Self> {
+        let mut span = Span::root("skyd.statement")?;
+        if let Some(connection) = connection {
+            span.link_to(connection);
+        }
+        let last = span.start();
+        Some(Self {
+            span,
+            last,
+            parsed: false,
+        })
+    }
+    fn phase(&mut self, name: &'static str) {
+        let now = now();
+        self.span.record_child(name, self.last, now);
+        self.last = now;
+    }
+    /// The client tagged the statement with `tag`
+    pub fn tagged(&mut self, tag: &str) {
+        self.span.set_str("skyd.tag", tag)
+    }
+    /// The statement was lexed
+    pub fn parsed(&mut self) {
+        self.parsed = true;
+        self.phase("skyd.parse")
+    }
+    /// The statement has run (if it failed while it was being parsed, that's where the time went)
+    pub fn executed(&mut self, error: Option<String>) {
+        if self.parsed {
+            self.phase("skyd.execute")
+        } else {
+            self.phase("skyd.parse")
+        }
+        if let Some(error) = error {
+            self.span.set_error(error);
+        }
+    }
+    /// The response was written. The statement's span ends here
+    pub fn responded(mut self, bytes_in: usize, bytes_out: usize, rows_returned: Option<u64>) {
+        self.phase("skyd.respond");
+        self.span.set_int("skyd.bytes_in", bytes_in as u64);
+        self.span.set_int("skyd.bytes_out", bytes_out as u64);
+        if let Some(rows) = rows_returned {
+            self.span.set_int("skyd.rows_returned", rows);
+        }
+    }
+}
+
+struct FinishedSpan {
+    trace_id: u128,
+    span_id: u64,
+    parent_id: Option<u64>,
+    link: Option<(u128, u64)>,
+    name: &'static str,
+    start: u64,
+    end: u64,
+    attributes: Vec<(&'static str, AttributeValue)>,
+    error: Option<String>,
+}
+
+/*
+    export
+*/
+
+/// Encode the spans as an OTLP `ExportTraceServiceRequest`, in the JSON encoding
+fn encode_spans(spans: &[FinishedSpan]) -> String {
+    let mut out = String::from(
+        "{\"resourceSpans\":[{\"resource\":{\"attributes\":[\
+        {\"key\":\"service.name\",\"value\":{\"stringValue\":\"skyd\"}}]},\
+        \"scopeSpans\":[{\"scope\":{\"name\":\"skyd\",\"version\":",
+    );
+    encode_str(&mut out, libsky::VERSION);
+    out.push_str("},\"spans\":[");
+    for (i, span) in spans.iter().enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        let _ = write!(
+            out,
+            "{{\"traceId\":\"{:032x}\",\"spanId\":\"{:016x}\",",
+            span.trace_id, span.span_id
+        );
+        if let Some(parent_id) = span.parent_id {
+            let _ = write!(out, "\"parentSpanId\":\"{parent_id:016x}\",");
+        }
+        out.push_str("\"name\":");
+        encode_str(&mut out, span.name);
+        // statements and connections are handled for a client (SERVER); everything else is INTERNAL
+        let kind = if span.parent_id.is_none() && span.name != "skyd.flush" {
+            2
+        } else {
+            1
+        };
+        let _ = write!(
+            out,
+            ",\"kind\":{kind},\"startTimeUnixNano\":\"{}\",\"endTimeUnixNano\":\"{}\",\"attributes\":[",
+            span.start, span.end
+        );
+        for (i, (key, value)) in span.attributes.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            out.push_str("{\"key\":");
+            encode_str(&mut out, key);
+            out.push_str(",\"value\":");
+            match value {
+                AttributeValue::Str(s) => {
+                    out.push_str("{\"stringValue\":");
+                    encode_str(&mut out, s);
+                    out.push('}');
+                }
+                AttributeValue::Int(n) => {
+                    let _ = write!(out, "{{\"intValue\":\"{n}\"}}");
+                }
+            }
+            out.push('}');
+        }
+        out.push(']');
+        if let Some((trace_id, span_id)) = span.link {
+            let _ = write!(
+                out,
+                ",\"links\":[{{\"traceId\":\"{trace_id:032x}\",\"spanId\":\"{span_id:016x}\"}}]"
+            );
+        }
+        if let Some(message) = &span.error {
+            // STATUS_CODE_ERROR (the status is left unset otherwise)
+            out.push_str(",\"status\":{\"code\":2,\"message\":");
+            encode_str(&mut out, message);
+            out.push('}');
+        }
+        out.push('}');
+    }
+    out.push_str("]}]}]}");
+    out
+}
+
+/// Send the body to the collector
+async fn post(endpoint: &ConfigEndpointTcp, body: &str) -> io::Result<()> {
+    let mut stream = TcpStream::connect((endpoint.host(), endpoint.port())).await?;
+    let request = format!(
+        "POST /v1/traces HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        endpoint.host(),
+        endpoint.port(),
+        body.len()
+    );
+    stream.write_all(request.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    let mut buf = [0u8; RESPONSE_MAX];
+    let mut len = 0;
+    // read until we have the status line
+    while !buf[..len].contains(&b'\n') && len < RESPONSE_MAX {
+        match stream.read(&mut buf[len..]).await? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    let status = parse_status_line(&buf[..len]);
+    match status {
+        Some(200..=299) => Ok(()),
+        Some(status) => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("collector responded with status {status}"),
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "collector sent an invalid response",
+        )),
+    }
+}
+
+/// Returns the status code from the status line of a response
+pub(super) fn parse_status_line(response: &[u8]) -> Option<u16> {
+    let line = response.split(|b| *b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?;
+    let mut parts = line.split(' ');
+    if !parts.next()?.starts_with("HTTP/1.") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_spans, parse_status_line, AttributeValue, FinishedSpan};
+
+    #[test]
+    fn status_line() {
+        assert_eq!(parse_status_line(b"HTTP/1.1 200 OK\r\n"), Some(200));
+        assert_eq!(
+            parse_status_line(b"HTTP/1.0 503 Service Unavailable\r\n"),
+            Some(503)
+        );
+        assert_eq!(parse_status_line(b"SSH-2.0-OpenSSH\r\n"), None);
+        assert_eq!(parse_status_line(b""), None);
+    }
+
+    #[test]
+    fn encode() {
+        let spans = [
+            FinishedSpan {
+                trace_id: 0xab,
+                span_id: 1,
+                parent_id: None,
+                link: Some((0xcd, 3)),
+                name: "skyd.statement",
+                start: 100,
+                end: 300,
+                attributes: vec![
+                    ("skyd.bytes_in", AttributeValue::Int(12)),
+                    ("skyd.user", AttributeValue::Str("\"root\"".into())),
+                ],
+                error: Some("QExecDdlObjectNotFound".into()),
+            },
+            FinishedSpan {
+                trace_id: 0xab,
+                span_id: 2,
+                parent_id: Some(1),
+                link: None,
+                name: "skyd.parse",
+                start: 100,
+                end: 150,
+                attributes: vec![],
+                error: None,
+            },
+        ];
+        let json = encode_spans(&spans);
+        assert!(json.starts_with(
+            r#"{"resourceSpans":[{"resource":{"attributes":[{"key":"service.name","value":{"stringValue":"skyd"}}]},"scopeSpans":[{"scope":{"name":"skyd","version":"#
+        ));
+        assert!(json.ends_with(concat!(
+            r#""spans":["#,
+            r#"{"traceId":"000000000000000000000000000000ab","spanId":"0000000000000001","name":"skyd.statement","kind":2,"#,
+            r#""startTimeUnixNano":"100","endTimeUnixNano":"300","attributes":[{"key":"skyd.bytes_in","value":{"intValue":"12"}},"#,
+            r#"{"key":"skyd.user","value":{"stringValue":"\"root\""}}],"#,
+            r#""links":[{"traceId":"000000000000000000000000000000cd","spanId":"0000000000000003"}],"#,
+            r#""status":{"code":2,"message":"QExecDdlObjectNotFound"}},"#,
+            r#"{"traceId":"000000000000000000000000000000ab","spanId":"0000000000000002","parentSpanId":"0000000000000001","#,
+            r#""name":"skyd.parse","kind":1,"startTimeUnixNano":"100","endTimeUnixNano":"150","attributes":[]}"#,
+            r#"]}]}]}"#
+        )));
+    }
+}
@@ -0,0 +1,570 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    upstream
+    ---
+    the client that models with `upstream: true` fetch rows from, and forward writes to (see `core::model::upstream`).
+    it speaks Skyhash/2.0 with the original protocol version, so a rejected query is answered with just its error
+    code. like the blob tier's requests, the connections are blocking (so queries are sent from a blocking task) and
+    they're kept in a small pool, remembering the space they're in so that `use` is only sent when it changes. a
+    connection that was idle for a while is dropped rather than reused, since the upstream might have closed it.
+
+    writes that are forwarded behind are queued and sent in order, every second, by a background task. a write that
+    couldn't be sent because the upstream is unreachable stays at the head of the queue, while a write that the
+    upstream rejected is logged and dropped. the queue is only kept in memory.
+
+    the same connections are used to pull models from other instances (see `sysctl copy model`)
+*/
+
+use {
+    super::super::{
+        config::ConfigUpstream,
+        data::cell::Datacell,
+        net::protocol::{Response, ResponseType},
+    },
+    parking_lot::Mutex,
+    std::{
+        collections::VecDeque,
+        io::{self, Read, Write},
+        net::{TcpStream, ToSocketAddrs},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            OnceLock,
+        },
+        time::{Duration, Instant},
+    },
+    tokio::time,
+};
+
+/// How long a query to the upstream can wait to connect, and for each read or write
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The most idle connections that are kept
+const POOL_MAX: usize = 4;
+/// Connections that were idle for longer than this aren't reused
+const POOL_IDLE_MAX: Duration = Duration::from_secs(30);
+/// The largest response that is read
+const RESPONSE_MAX: usize = 64 * 1024 * 1024;
+/// The most writes that can wait to be forwarded behind
+pub const QUEUE_MAX: usize = 10_000;
+/// How often the queued writes are forwarded
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+static UPSTREAM: OnceLock<Upstream> = OnceLock::new();
+
+pub struct Upstream {
+    config: ConfigUpstream,
+    pool: Mutex<Vec<Connection>>,
+    queue: Mutex<VecDeque<Forward>>,
+    /// held while the queue is being flushed, so that the writes are sent in order
+    flushing: Mutex<()>,
+    failing: AtomicBool,
+}
+
+/// A write that is waiting to be forwarded
+struct Forward {
+    space: Option<Box<str>>,
+    query: Box<[u8]>,
+    params: Box<[u8]>,
+}
+
+/// Start using the given upstream, forwarding queued writes in the background until the runtime is shut down (call
+/// [`finish`] before that, to forward what's left)
+///
+/// WARN: Must be in [`tokio::runtime::Runtime`] context!
+pub fn start(config: &ConfigUpstream) {
+    let upstream = Upstream {
+        config: config.clone(),
+        pool: Mutex::new(vec![]),
+        queue: Mutex::new(VecDeque::new()),
+        flushing: Mutex::new(()),
+        failing: AtomicBool::new(false),
+    };
+    if UPSTREAM.set(upstream).is_err() {
+        return;
+    }
+    tokio::spawn(async {
+        loop {
+            time::sleep(FLUSH_INTERVAL).await;
+            let _ = tokio::task::spawn_blocking(|| UPSTREAM.get().unwrap().flush()).await;
+        }
+    });
+}
+
+/// Forward the writes that are still queued (if there is an upstream), so that they aren't lost on shutdown
+pub async fn finish() {
+    let Some(upstream) = UPSTREAM.get() else {
+        return;
+    };
+    let _ = tokio::task::spawn_blocking(|| upstream.flush()).await;
+    let left = upstream.queue.lock().len();
+    if left != 0 {
+        warn!("upstream: {left} write(s) couldn't be forwarded before shutdown");
+    }
+}
+
+/// Returns the upstream, if one is configured
+pub fn get() -> Option<&'static Upstream> {
+    UPSTREAM.get()
+}
+
+impl Upstream {
+    /// Run a query on the upstream (after switching to the given space, if one is given). The outer error is
+    /// returned if the upstream couldn't be reached (or sent something that isn't a response), and the inner one if
+    /// it rejected the query (with the error code that it responded with)
+    pub fn run(
+        &self,
+        space: Option<&str>,
+        query: &[u8],
+        params: &[u8],
+    ) -> io::Result<Result<Response, u16>> {
+        let mut con = self.connection()?;
+        if let Some(space) = space {
+            if con.space.as_deref() != Some(space) {
+                if let Err(code) = con.query(format!("use {space}").as_bytes(), &[])? {
+                    self.release(con);
+                    return Ok(Err(code));
+                }
+                con.space = Some(space.into());
+            }
+        }
+        let r = con.query(query, params)?;
+        self.release(con);
+        Ok(r)
+    }
+    /// Returns true if another write can be queued
+    pub fn can_queue(&self) -> bool {
+        self.queue.lock().len() < QUEUE_MAX
+    }
+    /// Queue a write to be forwarded in the background (see [`Self::can_queue`])
+    pub fn queue(&self, space: Option<&str>, query: &[u8], params: &[u8]) {
+        self.queue.lock().push_back(Forward {
+            space: space.map(Into::into),
+            query: query.into(),
+            params: params.into(),
+        })
+    }
+    /// Forward the queued writes, stopping at the first one that couldn't be sent
+    fn flush(&self) {
+        let _flushing = self.flushing.lock();
+        loop {
+            let Some(forward) = self.queue.lock().pop_front() else {
+                return;
+            };
+            match self.run(forward.space.as_deref(), &forward.query, &forward.params) {
+                Ok(r) => {
+                    if self.failing.swap(false, Ordering::Relaxed) {
+                        info!("upstream: forwarding writes again");
+                    }
+                    if let Err(code) = r {
+                        warn!(
+                            "upstream: dropped a write that the upstream rejected (error code {code}): `{}`",
+                            String::from_utf8_lossy(&forward.query)
+                        );
+                    }
+                }
+                Err(e) => {
+                    if !self.failing.swap(true, Ordering::Relaxed) {
+                        warn!("upstream: failed to forward writes: `{e}`. will try again later");
+                    }
+                    self.queue.lock().push_front(forward);
+                    return;
+                }
+            }
+        }
+    }
+    fn connection(&self) -> io::Result<Connection> {
+        {
+            let mut pool = self.pool.lock();
+            while let Some(con) = pool.pop() {
+                if con.idle_since.elapsed() < POOL_IDLE_MAX {
+                    return Ok(con);
+                }
+            }
+        }
+        let config = &self.config;
+        Connection::connect(
+            config.endpoint.host(),
+            config.endpoint.port(),
+            &config.username,
+            &config.password,
+        )
+    }
+    /// Returns the username and password used to connect to the upstream
+    pub fn credentials(&self) -> (&str, &str) {
+        (&self.config.username, &self.config.password)
+    }
+    fn release(&self, mut con: Connection) {
+        let mut pool = self.pool.lock();
+        if pool.len() < POOL_MAX {
+            con.idle_since = Instant::now();
+            pool.push(con);
+        }
+    }
+}
+
+/// A blocking connection to another instance
+pub struct Connection {
+    stream: TcpStream,
+    /// the space that the connection is in (if we switched it to one)
+    space: Option<Box<str>>,
+    idle_since: Instant,
+}
+
+impl Connection {
+    /// Connect to the instance at the given host and port, authenticating with the given credentials
+    pub fn connect(host: &str, port: u16, username: &str, password: &str) -> io::Result<Self> {
+        let addr = (host, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host not found"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, REQUEST_TIMEOUT)?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+        stream.write_all(&encode_handshake(username, password))?;
+        let mut response = [0u8; 4];
+        stream.read_exact(&mut response)?;
+        match response {
+            [b'H', 0, 0, 0] => Ok(Self {
+                stream,
+                space: None,
+                idle_since: Instant::now(),
+            }),
+            [b'H', 0, 1, e] => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("the instance rejected the handshake (error code {e})"),
+            )),
+            _ => Err(io::ErrorKind::InvalidData.into()),
+        }
+    }
+    /// Run a query, returning the response or the error code that the instance responded with (see [`Upstream::run`])
+    pub fn query(&mut self, query: &[u8], params: &[u8]) -> io::Result<Result<Response, u16>> {
+        self.stream.write_all(&encode_query(query, params))?;
+        let mut buf = vec![];
+        let mut chunk = vec![0u8; 64 * 1024];
+        loop {
+            match decode_response(&buf) {
+                Ok((r, size)) if size == buf.len() => return Ok(r),
+                Err(Decode::Incomplete) => {}
+                // we never pipeline queries, so there can't be anything after the response
+                Ok(_) | Err(Decode::Invalid) => return Err(io::ErrorKind::InvalidData.into()),
+            }
+            let n = self.stream.read(&mut chunk)?;
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            if buf.len() + n > RESPONSE_MAX {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "response is too large",
+                ));
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+
+/// Encode a handshake (with the original versions and modes) that authenticates with a password
+fn encode_handshake(username: &str, password: &str) -> Vec<u8> {
+    let mut hs = b"H\0\0\0\0\0".to_vec();
+    hs.extend_from_slice(format!("{}\n{}\n", username.len(), password.len()).as_bytes());
+    hs.extend_from_slice(username.as_bytes());
+    hs.extend_from_slice(password.as_bytes());
+    hs
+}
+
+/// Encode a simple query: `S<packet size>\n<query size>\n<query><params>`
+fn encode_query(query: &[u8], params: &[u8]) -> Vec<u8> {
+    let q_window = format!("{}\n", query.len());
+    let packet_size = q_window.len() + query.len() + params.len();
+    let mut packet = format!("S{packet_size}\n{q_window}").into_bytes();
+    packet.extend_from_slice(query);
+    packet.extend_from_slice(params);
+    packet
+}
+
+#[derive(Debug, PartialEq)]
+enum Decode {
+    /// more bytes are needed
+    Incomplete,
+    /// the bytes aren't a response that we know
+    Invalid,
+}
+
+type DecodeResult<T> = Result<T, Decode>;
+
+/// Decode a response, returning it along with its size. Rows (and multiple rows) are kept as they were encoded, so
+/// that they can be passed on as is (see [`decode_row`])
+fn decode_response(buf: &[u8]) -> DecodeResult<(Result<Response, u16>, usize)> {
+    let mut cursor = Cursor { buf, pos: 0 };
+    let ty = cursor.byte()?;
+    let r = match ty {
+        _ if ty == ResponseType::Empty.value_u8() => Ok(Response::Empty),
+        _ if ty == ResponseType::Null.value_u8() => Ok(Response::Null),
+        _ if ty == ResponseType::Bool.value_u8() => Ok(Response::Bool(cursor.byte()? != 0)),
+        _ if ty == ResponseType::UInt64.value_u8() => Ok(Response::UInt64(cursor.uint()?)),
+        _ if ty == ResponseType::Error.value_u8() => {
+            Err(u16::from_le_bytes([cursor.byte()?, cursor.byte()?]))
+        }
+        _ if (ty == ResponseType::Row.value_u8()) | (ty == ResponseType::MultiRow.value_u8()) => {
+            let size = cursor.uint()?;
+            let start = cursor.pos;
+            if ty == ResponseType::Row.value_u8() {
+                cursor.cells(size)?;
+            } else {
+                for _ in 0..size {
+                    let columns = cursor.uint()?;
+                    cursor.cells(columns)?;
+                }
+            }
+            Ok(Response::Serialized {
+                ty: if ty == ResponseType::Row.value_u8() {
+                    ResponseType::Row
+                } else {
+                    ResponseType::MultiRow
+                },
+                size: size as usize,
+                data: buf[start..cursor.pos].to_vec(),
+            })
+        }
+        _ if (ty == ResponseType::String.value_u8()) | (ty == ResponseType::Binary.value_u8()) => {
+            let size = cursor.uint()?;
+            Ok(Response::Serialized {
+                ty: if ty == ResponseType::String.value_u8() {
+                    ResponseType::String
+                } else {
+                    ResponseType::Binary
+                },
+                size: size as usize,
+                data: cursor.take(size)?.to_vec(),
+            })
+        }
+        _ => return Err(Decode::Invalid),
+    };
+    Ok((r, cursor.pos))
+}
+
+/// Decode the cells of a row response, returning [`None`] if the response isn't a row
+pub fn decode_row(response: &Response) -> Option<Vec<Datacell>> {
+    match response {
+        Response::Serialized {
+            ty: ResponseType::Row,
+            size,
+            data,
+        } => {
+            let mut cursor = Cursor { buf: data, pos: 0 };
+            let cells = cursor.cells(*size as u64).ok()?;
+            (cursor.pos == data.len()).then_some(cells)
+        }
+        _ => None,
+    }
+}
+
+/// Decode the rows of a multiple row response, returning [`None`] if the response isn't one
+pub fn decode_rows(response: &Response) -> Option<Vec<Vec<Datacell>>> {
+    match response {
+        Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size,
+            data,
+        } => {
+            let mut cursor = Cursor { buf: data, pos: 0 };
+            let mut rows = Vec::with_capacity(*size);
+            for _ in 0..*size {
+                let columns = cursor.uint().ok()?;
+                rows.push(cursor.cells(columns).ok()?);
+            }
+            (cursor.pos == data.len()).then_some(rows)
+        }
+        _ => None,
+    }
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn byte(&mut self) -> DecodeResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn take(&mut self, len: u64) -> DecodeResult<&'a [u8]> {
+        let len = usize::try_from(len).map_err(|_| Decode::Invalid)?;
+        if len > self.buf.len() - self.pos {
+            return Err(Decode::Incomplete);
+        }
+        self.pos += len;
+        Ok(&self.buf[self.pos - len..self.pos])
+    }
+    /// Returns the bytes up to the next newline (skipping over it)
+    fn line(&mut self) -> DecodeResult<&'a str> {
+        let Some(len) = self.buf[self.pos..].iter().position(|b| *b == b'\n') else {
+            return Err(Decode::Incomplete);
+        };
+        let line = &self.buf[self.pos..self.pos + len];
+        self.pos += len + 1;
+        core::str::from_utf8(line).map_err(|_| Decode::Invalid)
+    }
+    fn uint(&mut self) -> DecodeResult<u64> {
+        self.line()?.parse().map_err(|_| Decode::Invalid)
+    }
+    fn cells(&mut self, count: u64) -> DecodeResult<Vec<Datacell>> {
+        let mut cells = vec![];
+        for _ in 0..count {
+            cells.push(self.cell()?);
+        }
+        Ok(cells)
+    }
+    /// Decode a cell: its tag (the tag selector, plus one) and then its value, or just a zero for a null
+    fn cell(&mut self) -> DecodeResult<Datacell> {
+        fn parse_err<E>(_: E) -> Decode {
+            Decode::Invalid
+        }
+        Ok(match self.byte()? {
+            0 => Datacell::null(),
+            1 => Datacell::new_bool(self.byte()? != 0),
+            2..=5 => Datacell::new_uint_default(self.line()?.parse().map_err(parse_err)?),
+            6..=9 => Datacell::new_sint_default(self.line()?.parse().map_err(parse_err)?),
+            10 | 11 => Datacell::new_float_default(self.line()?.parse().map_err(parse_err)?),
+            12 => {
+                let len = self.uint()?;
+                Datacell::new_bin(self.take(len)?.into())
+            }
+            13 => {
+                let len = self.uint()?;
+                let s = core::str::from_utf8(self.take(len)?).map_err(|_| Decode::Invalid)?;
+                Datacell::new_str(s.into())
+            }
+            14 => {
+                let len = self.uint()?;
+                Datacell::new_list(self.cells(len)?)
+            }
+            _ => return Err(Decode::Invalid),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{decode_response, decode_row, decode_rows, encode_handshake, encode_query, Decode},
+        crate::engine::{
+            core::dml,
+            data::cell::Datacell,
+            net::protocol::{Response, ResponseType},
+        },
+    };
+
+    #[test]
+    fn frames() {
+        assert_eq!(
+            encode_handshake("sayan", "pass1234"),
+            b"H\0\0\0\0\05\n8\nsayanpass1234"
+        );
+        assert_eq!(
+            encode_query(
+                b"select * from myspace.mymodel where username = ?",
+                b"\x06sayan"
+            ),
+            b"S57\n48\nselect * from myspace.mymodel where username = ?\x06sayan"
+        );
+    }
+
+    #[test]
+    fn responses() {
+        assert_eq!(decode_response(b"\x12"), Ok((Ok(Response::Empty), 1)));
+        assert_eq!(
+            decode_response(b"\x01\x01"),
+            Ok((Ok(Response::Bool(true)), 2))
+        );
+        assert_eq!(
+            decode_response(b"\x0512\n"),
+            Ok((Ok(Response::UInt64(12)), 4))
+        );
+        assert_eq!(decode_response(b"\x10\x6f\x00"), Ok((Err(111), 3)));
+        assert_eq!(decode_response(b"\x0512"), Err(Decode::Incomplete));
+        assert_eq!(decode_response(b"\x10\x6f"), Err(Decode::Incomplete));
+        assert_eq!(decode_response(b"\x05ab\n"), Err(Decode::Invalid));
+        assert_eq!(decode_response(b"\x7f"), Err(Decode::Invalid));
+    }
+
+    #[test]
+    fn rows() {
+        let cells = vec![
+            Datacell::new_str("sayan".into()),
+            Datacell::new_uint_default(18),
+            Datacell::new_sint_default(-5),
+            Datacell::new_float_default(1.5),
+            Datacell::new_bool(true),
+            Datacell::new_bin(b"\n\n".to_vec().into_boxed_slice()),
+            Datacell::null(),
+            Datacell::new_list(vec![Datacell::new_uint_default(1)]),
+        ];
+        let mut data = vec![];
+        for cell in cells.iter() {
+            dml::encode_cell(&mut data, cell);
+        }
+        let mut encoded = format!("\x11{}\n", cells.len()).into_bytes();
+        encoded.extend_from_slice(&data);
+        let expected = Response::Serialized {
+            ty: ResponseType::Row,
+            size: cells.len(),
+            data: data.clone(),
+        };
+        assert_eq!(
+            decode_response(&encoded),
+            Ok((Ok(expected.clone()), encoded.len()))
+        );
+        assert_eq!(decode_row(&expected).unwrap(), cells);
+        // every prefix is incomplete
+        for len in 0..encoded.len() {
+            assert_eq!(decode_response(&encoded[..len]), Err(Decode::Incomplete));
+        }
+        // two rows
+        let mut encoded = b"\x132\n".to_vec();
+        for _ in 0..2 {
+            encoded.extend_from_slice(format!("{}\n", cells.len()).as_bytes());
+            encoded.extend_from_slice(&data);
+        }
+        let (r, size) = decode_response(&encoded).unwrap();
+        assert_eq!(size, encoded.len());
+        assert!(matches!(
+            r,
+            Ok(Response::Serialized {
+                ty: ResponseType::MultiRow,
+                size: 2,
+                ..
+            })
+        ));
+        assert_eq!(
+            decode_rows(&r.unwrap()).unwrap(),
+            vec![cells.clone(), cells]
+        );
+        assert_eq!(decode_rows(&expected), None);
+    }
+}
@@ -61,6 +61,10 @@ impl Status {
     pub(super) fn set_iffy(&self) {
         self._set(false)
     }
+    /// Set the status, returning true if it changed
+    pub(super) fn change(&self, okay: bool) -> bool {
+        self.okay.swap(okay, Ordering::AcqRel) != okay
+    }
     fn _set(&self, v: bool) {
         self.okay.store(v, Ordering::Release)
     }
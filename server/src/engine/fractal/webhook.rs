@@ -0,0 +1,258 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    webhook delivery
+    ---
+    changes committed to a model's journal are POSTed to the model's webhooks (see `core::model::webhook`) by a
+    background task that wakes up every second. every request carries a JSON object with the webhook's name, the
+    model and the events (in the same format as `sysctl export changes`):
+
+    {"webhook":"<name>","model":"<space>.<model>","events":[<event>,...]}
+
+    - a webhook with a secret signs the body with HMAC-SHA256, and sends the signature as
+    `X-Skytable-Signature: sha256=<hex>` so that the receiver can check that the request came from us
+    - any 2xx response acknowledges the events. other responses (and failed requests) are retried with exponential
+    backoff (up to 5 minutes), so events are delivered at least once and in the order they were committed
+    - `https` endpoints are verified against the system's certificate authorities
+*/
+
+use {
+    super::{trace, Global},
+    crate::engine::core::model::webhook,
+    openssl::ssl::{SslConnector, SslMethod},
+    std::{
+        fmt::Write as _,
+        io::{self, Read, Write},
+        net::{TcpStream, ToSocketAddrs},
+        time::Duration,
+    },
+    tokio::time,
+};
+
+/// How often the journals are checked for changes to deliver
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(1);
+/// How long we wait to connect, and for every read or write
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The most bytes of the response that we read (we only need the status line)
+const RESPONSE_MAX: usize = 1024;
+/// The header with the signature of the body (for webhooks with a secret)
+pub const SIGNATURE_HEADER: &str = "X-Skytable-Signature";
+
+/// Start delivering changes to webhooks in the background
+pub fn start(global: Global) {
+    tokio::spawn(async move {
+        loop {
+            time::sleep(DELIVERY_INTERVAL).await;
+            let global = global.clone();
+            let _ = tokio::task::spawn_blocking(move || webhook::deliver(&global)).await;
+        }
+    });
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// Where the requests of a webhook are sent
+pub struct WebhookEndpoint {
+    tls: bool,
+    host: Box<str>,
+    port: u16,
+    path: Box<str>,
+}
+
+impl WebhookEndpoint {
+    /// Parse a `http://host[:port][/path]` or `https://host[:port][/path]` URL
+    pub fn parse(url: &str) -> Option<Self> {
+        // the url ends up in the request line and headers, so don't let it break out of them
+        if url.is_empty() | !url.bytes().all(|b| b.is_ascii_graphic()) {
+            return None;
+        }
+        let (tls, rest) = if let Some(rest) = url.strip_prefix("http://") {
+            (false, rest)
+        } else if let Some(rest) = url.strip_prefix("https://") {
+            (true, rest)
+        } else {
+            return None;
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().ok().filter(|port| *port != 0)?),
+            None => (authority, if tls { 443 } else { 80 }),
+        };
+        if host.is_empty() | host.contains(['@', '[', ']']) {
+            return None;
+        }
+        Some(Self {
+            tls,
+            host: host.into(),
+            port,
+            path: path.into(),
+        })
+    }
+}
+
+/// POST the body to the endpoint, failing unless the response has a 2xx status
+pub fn post(endpoint: &WebhookEndpoint, body: &str, secret: &str) -> io::Result<()> {
+    let request = encode_request(endpoint, body, secret);
+    let addr = (&*endpoint.host, endpoint.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "webhook host not found"))?;
+    let stream = TcpStream::connect_timeout(&addr, REQUEST_TIMEOUT)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    if endpoint.tls {
+        let tls_error = |e: String| io::Error::new(io::ErrorKind::Other, e);
+        let connector = SslConnector::builder(SslMethod::tls())
+            .map_err(|e| tls_error(e.to_string()))?
+            .build();
+        let stream = connector
+            .connect(&endpoint.host, stream)
+            .map_err(|e| tls_error(e.to_string()))?;
+        exchange(stream, &request)
+    } else {
+        exchange(stream, &request)
+    }
+}
+
+/// Send the request, and check the status of the response
+fn exchange(mut stream: impl Read + Write, request: &[u8]) -> io::Result<()> {
+    stream.write_all(request)?;
+    stream.flush()?;
+    let mut buf = [0u8; RESPONSE_MAX];
+    let mut len = 0;
+    // read until we have the status line
+    while !buf[..len].contains(&b'\n') && len < RESPONSE_MAX {
+        match stream.read(&mut buf[len..])? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    match trace::parse_status_line(&buf[..len]) {
+        Some(200..=299) => Ok(()),
+        Some(status) => Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("webhook responded with status {status}"),
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "webhook sent an invalid response",
+        )),
+    }
+}
+
+/// Returns the signature of the body, as sent in the [`SIGNATURE_HEADER`]
+pub fn signature(secret: &str, body: &str) -> String {
+    format!(
+        "sha256={}",
+        super::tier::hex(&super::tier::hmac(secret.as_bytes(), body.as_bytes()))
+    )
+}
+
+fn encode_request(endpoint: &WebhookEndpoint, body: &str, secret: &str) -> Vec<u8> {
+    let mut request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+        endpoint.path,
+        endpoint.host,
+        endpoint.port,
+        body.len()
+    );
+    if !secret.is_empty() {
+        let _ = write!(
+            request,
+            "{SIGNATURE_HEADER}: {}\r\n",
+            signature(secret, body)
+        );
+    }
+    request.push_str("Connection: close\r\n\r\n");
+    let mut request = request.into_bytes();
+    request.extend_from_slice(body.as_bytes());
+    request
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_request, signature, WebhookEndpoint};
+
+    #[test]
+    fn endpoints() {
+        assert_eq!(
+            WebhookEndpoint::parse("http://localhost:8080/hooks/sky?x=1").unwrap(),
+            WebhookEndpoint {
+                tls: false,
+                host: "localhost".into(),
+                port: 8080,
+                path: "/hooks/sky?x=1".into(),
+            }
+        );
+        assert_eq!(
+            WebhookEndpoint::parse("https://example.com").unwrap(),
+            WebhookEndpoint {
+                tls: true,
+                host: "example.com".into(),
+                port: 443,
+                path: "/".into(),
+            }
+        );
+        for url in [
+            "",
+            "ftp://example.com/",
+            "http://",
+            "http://:80/",
+            "http://example.com:0/",
+            "http://example.com:http/",
+            "http://user@example.com/",
+            "http://example.com/a b",
+            "http://example.com/\r\nX-Injected: 1",
+        ] {
+            assert_eq!(WebhookEndpoint::parse(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn signed_request() {
+        let endpoint = WebhookEndpoint::parse("http://localhost:8080/hook").unwrap();
+        let body = "{\"events\":[]}";
+        let request = String::from_utf8(encode_request(&endpoint, body, "")).unwrap();
+        assert_eq!(
+            request,
+            "POST /hook HTTP/1.1\r\nHost: localhost:8080\r\nContent-Type: application/json\r\nContent-Length: 13\r\nConnection: close\r\n\r\n{\"events\":[]}"
+        );
+        // RFC 4231, test case 2
+        assert_eq!(
+            signature("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let request = String::from_utf8(encode_request(&endpoint, body, "Jefe")).unwrap();
+        assert!(request.contains(&format!(
+            "\r\nX-Skytable-Signature: {}\r\n",
+            signature("Jefe", body)
+        )));
+        assert!(request.ends_with("\r\n\r\n{\"events\":[]}"));
+    }
+}
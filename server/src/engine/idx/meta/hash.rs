@@ -24,7 +24,11 @@
  *
 */
 
-use std::hash::{BuildHasher, Hasher};
+use std::{
+    collections::hash_map::{DefaultHasher, RandomState},
+    hash::{BuildHasher, Hasher},
+    sync::OnceLock,
+};
 
 pub type HasherNativeFx = HasherRawFx<usize>;
 
@@ -189,6 +193,9 @@ impl<T: HashWord> HasherRawFx<T> {
     pub const fn new() -> Self {
         Self(T::STATE)
     }
+    pub const fn with_state(state: T) -> Self {
+        Self(state)
+    }
 }
 
 impl<T: HashWord> Hasher for HasherRawFx<T> {
@@ -237,3 +244,111 @@ impl<T: HashWord> Default for HasherRawFx<T> {
         Self::new()
     }
 }
+
+/*
+    runtime selectable hasher
+*/
+
+/// The hash function used by an index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HasherKind {
+    /// SipHash-1-3 with randomly generated keys. This is resistant to hash flooding and is hence the default
+    #[default]
+    Sip,
+    /// [`HasherRawFx`] seeded with a per-process random state. This is considerably faster, but should only be used for
+    /// trusted workloads since collisions can be engineered by anyone who can observe the hash function
+    Fx,
+}
+
+impl HasherKind {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Sip => "siphash",
+            Self::Fx => "fxhash",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "siphash" => Some(Self::Sip),
+            "fxhash" => Some(Self::Fx),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the random seed for this process. The seed is generated once (on first use) and is then shared by all
+/// seeded hashers for the lifetime of the process
+pub fn process_seed() -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    *SEED.get_or_init(|| {
+        // the keys of a RandomState are derived from the system's random source
+        RandomState::new().build_hasher().finish()
+    })
+}
+
+/// A [`BuildHasher`] for which the hash function is chosen at runtime (see [`HasherKind`])
+#[derive(Debug, Clone)]
+pub enum HasherDynState {
+    Sip(RandomState),
+    Fx(u64),
+}
+
+impl HasherDynState {
+    pub fn new(kind: HasherKind) -> Self {
+        match kind {
+            HasherKind::Sip => Self::Sip(RandomState::new()),
+            HasherKind::Fx => Self::Fx(process_seed()),
+        }
+    }
+}
+
+impl Default for HasherDynState {
+    fn default() -> Self {
+        Self::new(HasherKind::default())
+    }
+}
+
+impl BuildHasher for HasherDynState {
+    type Hasher = HasherDyn;
+    fn build_hasher(&self) -> Self::Hasher {
+        match self {
+            Self::Sip(rs) => HasherDyn::Sip(rs.build_hasher()),
+            Self::Fx(seed) => HasherDyn::Fx(HasherRawFx::with_state(*seed)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum HasherDyn {
+    Sip(DefaultHasher),
+    Fx(HasherRawFx<u64>),
+}
+
+macro_rules! dyn_hasher_dispatch {
+    ($($f:ident($ty:ty)),* $(,)?) => {
+        $(fn $f(&mut self, i: $ty) {
+            match self {
+                Self::Sip(h) => h.$f(i),
+                Self::Fx(h) => h.$f(i),
+            }
+        })*
+    };
+}
+
+impl Hasher for HasherDyn {
+    fn finish(&self) -> u64 {
+        match self {
+            Self::Sip(h) => h.finish(),
+            Self::Fx(h) => h.finish(),
+        }
+    }
+    dyn_hasher_dispatch!(
+        write(&[u8]),
+        write_u8(u8),
+        write_u16(u16),
+        write_u32(u32),
+        write_u64(u64),
+        write_u128(u128),
+        write_usize(usize),
+    );
+}
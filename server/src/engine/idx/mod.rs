@@ -27,11 +27,13 @@
 #![deny(unreachable_patterns)]
 
 pub mod meta;
+pub mod mtbt;
 pub mod mtchm;
 mod stdhm;
 mod stord;
 #[cfg(test)]
 mod tests;
+pub mod tuple;
 
 use {
     self::meta::Comparable,
@@ -48,6 +50,7 @@ pub type IndexSTSeqCns<K, V> = stord::IndexSTSeqDll<K, V, stord::config::Conserv
 #[cfg(test)]
 pub type IndexSTSeqLib<K, V> = stord::IndexSTSeqDll<K, V, stord::config::LiberalConfig<K, V>>;
 pub type IndexMTRaw<E> = mtchm::imp::Raw<E, mtchm::meta::DefConfig>;
+pub type IndexMTBTree<E> = mtbt::RawBTree<E>;
 pub type IndexST<K, V, S = std::collections::hash_map::RandomState> =
     std::collections::hash_map::HashMap<K, V, S>;
 
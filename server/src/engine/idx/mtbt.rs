@@ -0,0 +1,307 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! An ordered multi-threaded index, built on a B-tree.
+//!
+//! Readers take a shared latch on the tree only for the duration of a lookup (or while fetching the next chunk of a
+//! scan); elements themselves live on the heap and are reclaimed through the epoch GC so that references handed out
+//! to readers remain valid for as long as their [`Guard`] is held.
+
+#[cfg(debug_assertions)]
+use super::DummyMetrics;
+use {
+    super::{mtchm::meta::TreeElement, IndexBaseSpec},
+    crate::engine::sync::atm::{Guard, Shared},
+    core::{borrow::Borrow, cmp::Ordering, fmt, marker::PhantomData, ops::Bound, ptr::NonNull},
+    parking_lot::RwLock,
+    std::collections::BTreeMap,
+};
+
+/// The number of elements fetched from the tree (under a single shared latch) when scanning
+const SCAN_CHUNK: usize = 128;
+
+/// A pointer to a key. All comparisons are done on the pointee
+struct KeyPtr<K>(NonNull<K>);
+
+impl<K> KeyPtr<K> {
+    fn new(k: &K) -> Self {
+        Self(NonNull::from(k))
+    }
+    fn key(&self) -> &K {
+        unsafe {
+            // UNSAFE(@ohsayan): either points to a live element or to a probe that outlives this ptr
+            self.0.as_ref()
+        }
+    }
+}
+
+impl<K: Ord> PartialEq for KeyPtr<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<K: Ord> Eq for KeyPtr<K> {}
+
+impl<K: Ord> PartialOrd for KeyPtr<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> Ord for KeyPtr<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(other.key())
+    }
+}
+
+fn bound_ptr<K, B: Borrow<K>>(b: &Bound<B>) -> Bound<KeyPtr<K>> {
+    match b {
+        Bound::Included(k) => Bound::Included(KeyPtr::new(k.borrow())),
+        Bound::Excluded(k) => Bound::Excluded(KeyPtr::new(k.borrow())),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Returns true if the range is non-empty. [`BTreeMap::range`] panics on inverted ranges, so we check first
+fn range_is_valid<K: Ord>(lo: &Bound<KeyPtr<K>>, hi: &Bound<KeyPtr<K>>) -> bool {
+    match (lo, hi) {
+        (Bound::Included(a) | Bound::Excluded(a), Bound::Included(b) | Bound::Excluded(b)) => {
+            match a.cmp(b) {
+                Ordering::Less => true,
+                Ordering::Equal => matches!((lo, hi), (Bound::Included(_), Bound::Included(_))),
+                Ordering::Greater => false,
+            }
+        }
+        _ => true,
+    }
+}
+
+pub struct RawBTree<E: TreeElement> {
+    d: RwLock<BTreeMap<KeyPtr<E::Key>, NonNull<E>>>,
+}
+
+// UNSAFE(@ohsayan): the pointers are uniquely owned by the tree and are only shared behind the latch/epoch guard
+unsafe impl<E: TreeElement + Send + Sync> Send for RawBTree<E> {}
+unsafe impl<E: TreeElement + Send + Sync> Sync for RawBTree<E> {}
+
+impl<E: TreeElement> IndexBaseSpec for RawBTree<E>
+where
+    E::Key: Ord,
+{
+    const PREALLOC: bool = false;
+    #[cfg(debug_assertions)]
+    type Metrics = DummyMetrics;
+    fn idx_init() -> Self {
+        Self {
+            d: RwLock::new(BTreeMap::new()),
+        }
+    }
+    fn idx_init_with(s: Self) -> Self {
+        s
+    }
+    #[cfg(debug_assertions)]
+    fn idx_metrics(&self) -> &Self::Metrics {
+        &DummyMetrics
+    }
+}
+
+impl<E: TreeElement> RawBTree<E>
+where
+    E::Key: Ord,
+{
+    fn alloc(e: E) -> NonNull<E> {
+        NonNull::from(Box::leak(Box::new(e)))
+    }
+    unsafe fn read<'v>(e: NonNull<E>) -> &'v E {
+        // UNSAFE(@ohsayan): elements are only freed through the GC (or when the tree itself is dropped)
+        &*e.as_ptr()
+    }
+    unsafe fn retire(e: NonNull<E>, g: &Guard) {
+        // UNSAFE(@ohsayan): the element was unlinked under the exclusive latch, so no new reader can find it
+        g.defer_destroy(Shared::from(e.as_ptr() as *const E))
+    }
+    pub fn mt_len(&self) -> usize {
+        self.d.read().len()
+    }
+    /// Returns true if the entry was inserted; returns false if the key already exists
+    pub fn mt_insert(&self, e: E, _: &Guard) -> bool {
+        let e = Self::alloc(e);
+        let k = KeyPtr::new(unsafe { Self::read(e) }.key());
+        let mut d = self.d.write();
+        if d.contains_key(&k) {
+            drop(d);
+            drop(unsafe {
+                // UNSAFE(@ohsayan): never shared
+                Box::from_raw(e.as_ptr())
+            });
+            false
+        } else {
+            d.insert(k, e);
+            true
+        }
+    }
+    pub fn mt_get_element<'t, 'g, 'v>(&'t self, key: &E::Key, _: &'g Guard) -> Option<&'v E>
+    where
+        't: 'v,
+        'g: 't + 'v,
+    {
+        self.d
+            .read()
+            .get(&KeyPtr::new(key))
+            .map(|e| unsafe { Self::read(*e) })
+    }
+    pub fn mt_delete(&self, key: &E::Key, g: &Guard) -> bool {
+        let e = self.d.write().remove(&KeyPtr::new(key));
+        e.map(|e| unsafe { Self::retire(e, g) }).is_some()
+    }
+    pub fn mt_delete_return_entry<'t, 'g, 'v>(&'t self, key: &E::Key, g: &'g Guard) -> Option<&'v E>
+    where
+        't: 'v,
+        'g: 't + 'v,
+    {
+        let e = self.d.write().remove(&KeyPtr::new(key))?;
+        unsafe {
+            let r = Self::read(e);
+            Self::retire(e, g);
+            Some(r)
+        }
+    }
+    /// Returns an iterator over all the elements within the given bounds, in key order.
+    ///
+    /// The iterator does not hold the latch; it fetches elements in chunks and resumes after the last key it has seen,
+    /// so concurrent writes may (or may not) be observed
+    pub fn mt_range<'t, 'g, 'v, B: Borrow<E::Key>>(
+        &'t self,
+        lo: Bound<B>,
+        hi: Bound<B>,
+        g: &'g Guard,
+    ) -> IterRange<'t, 'g, 'v, E, B>
+    where
+        't: 'v,
+        'g: 't + 'v,
+    {
+        IterRange {
+            t: self,
+            _g: g,
+            cursor: Cursor::Start(lo),
+            hi,
+            buf: Vec::new(),
+            pos: 0,
+            exhausted: false,
+            _m: PhantomData,
+        }
+    }
+}
+
+impl<E: TreeElement> Drop for RawBTree<E> {
+    fn drop(&mut self) {
+        core::mem::take(self.d.get_mut())
+            .into_values()
+            .for_each(|e| unsafe {
+                // UNSAFE(@ohsayan): sole live owner
+                drop(Box::from_raw(e.as_ptr()))
+            })
+    }
+}
+
+impl<E: TreeElement> fmt::Debug for RawBTree<E>
+where
+    E::Key: fmt::Debug + Ord,
+    E::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = self.d.read();
+        f.debug_map()
+            .entries(d.values().map(|e| {
+                let e = unsafe { Self::read(*e) };
+                (e.key(), e.val())
+            }))
+            .finish()
+    }
+}
+
+enum Cursor<'v, K, B> {
+    Start(Bound<B>),
+    After(&'v K),
+}
+
+pub struct IterRange<'t, 'g, 'v, E: TreeElement, B> {
+    t: &'t RawBTree<E>,
+    _g: &'g Guard,
+    cursor: Cursor<'v, E::Key, B>,
+    hi: Bound<B>,
+    buf: Vec<NonNull<E>>,
+    pos: usize,
+    exhausted: bool,
+    _m: PhantomData<&'v E>,
+}
+
+impl<'t, 'g, 'v, E: TreeElement, B: Borrow<E::Key>> IterRange<'t, 'g, 'v, E, B>
+where
+    E::Key: Ord,
+{
+    fn refill(&mut self) {
+        self.buf.clear();
+        self.pos = 0;
+        let lo = match &self.cursor {
+            Cursor::Start(lo) => bound_ptr(lo),
+            Cursor::After(k) => Bound::Excluded(KeyPtr::new(*k)),
+        };
+        let hi = bound_ptr(&self.hi);
+        if !range_is_valid(&lo, &hi) {
+            self.exhausted = true;
+            return;
+        }
+        let d = self.t.d.read();
+        self.buf
+            .extend(d.range((lo, hi)).take(SCAN_CHUNK).map(|(_, e)| *e));
+        drop(d);
+        self.exhausted = self.buf.len() < SCAN_CHUNK;
+        if let Some(last) = self.buf.last() {
+            // the element may get removed once we release the latch, but the guard keeps it alive
+            self.cursor = Cursor::After(unsafe { RawBTree::read(*last) }.key());
+        }
+    }
+}
+
+impl<'t, 'g, 'v, E: TreeElement, B: Borrow<E::Key>> Iterator for IterRange<'t, 'g, 'v, E, B>
+where
+    E::Key: Ord,
+{
+    type Item = &'v E;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos == self.buf.len() {
+            if self.exhausted {
+                return None;
+            }
+            self.refill();
+        }
+        let e = self.buf.get(self.pos)?;
+        self.pos += 1;
+        Some(unsafe { RawBTree::read(*e) })
+    }
+}
@@ -65,6 +65,13 @@ impl<E, C: Config> IndexBaseSpec for Raw<E, C> {
     }
 }
 
+impl<E, C: Config> Raw<E, C> {
+    /// Initialize an empty index that uses the provided hasher state
+    pub fn idx_init_with_hasher(h: C::HState) -> Self {
+        Self::_new(h)
+    }
+}
+
 impl<E: TreeElement, C: Config> MTIndexExt<E, E::Key, E::Value> for Raw<E, C> {
     type IterEntry<'t, 'g, 'v> = super::iter::IterEntry<'t, 'g, 'v, E, C>
     where
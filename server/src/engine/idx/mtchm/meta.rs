@@ -26,14 +26,17 @@
 
 use {
     crate::engine::{
-        idx::{meta::AsHasher, AsKey, AsKeyClone, AsValue, AsValueClone},
+        idx::{
+            meta::{hash::HasherDynState, AsHasher},
+            AsKey, AsKeyClone, AsValue, AsValueClone,
+        },
         mem::VInline,
     },
-    std::{collections::hash_map::RandomState, sync::Arc},
+    std::sync::Arc,
 };
 
 const LNODE_STACK: usize = 1;
-pub type DefConfig = Config2B<RandomState>;
+pub type DefConfig = Config2B<HasherDynState>;
 pub type LNode<T> = VInline<LNODE_STACK, T>;
 
 pub trait PreConfig: Sized + 'static {
@@ -28,7 +28,6 @@
 use super::DummyMetrics;
 use {
     super::{AsKey, AsValue, AsValueClone, IndexBaseSpec, STIndex},
-    crate::engine::mem::StatelessLen,
     std::{
         borrow::Borrow,
         collections::{
@@ -216,9 +215,3 @@ where
         self.values()
     }
 }
-
-impl<K, V, S> StatelessLen for StdMap<K, V, S> {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
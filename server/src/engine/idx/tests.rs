@@ -185,3 +185,195 @@ mod idx_st_seq_dll {
             });
     }
 }
+
+mod hasher {
+    use super::meta::hash::{HasherDynState, HasherKind, HasherRawFx};
+    use std::hash::{BuildHasher, Hasher};
+
+    #[test]
+    fn dyn_state_seeded() {
+        fn hash(s: &HasherDynState, v: &str) -> u64 {
+            let mut h = s.build_hasher();
+            h.write(v.as_bytes());
+            h.finish()
+        }
+        // fx: all states in this process share the seed
+        let (a, b) = (
+            HasherDynState::new(HasherKind::Fx),
+            HasherDynState::new(HasherKind::Fx),
+        );
+        assert_eq!(hash(&a, "sayan"), hash(&b, "sayan"));
+        // fx: seeded state must differ from the unseeded one (well, unless we're spectacularly unlucky)
+        let mut unseeded = HasherRawFx::<u64>::new();
+        unseeded.write("sayan".as_bytes());
+        assert_ne!(hash(&a, "sayan"), unseeded.finish());
+        // sip: each state is keyed independently
+        let (a, b) = (
+            HasherDynState::new(HasherKind::Sip),
+            HasherDynState::new(HasherKind::Sip),
+        );
+        assert_ne!(hash(&a, "sayan"), hash(&b, "sayan"));
+    }
+}
+
+mod idx_mt_btree {
+    use {
+        super::{mtbt::RawBTree, IndexBaseSpec},
+        crate::engine::sync::atm::cpin,
+        std::ops::Bound,
+    };
+
+    type Index = RawBTree<(u64, String)>;
+
+    // spans several scan chunks
+    const SPAM_CNT: u64 = 1000;
+
+    fn mkidx() -> Index {
+        let idx = Index::idx_init();
+        let g = cpin();
+        // insert in reverse so that we know the order comes from the tree
+        for i in (0..SPAM_CNT).rev() {
+            assert!(idx.mt_insert((i, i.to_string()), &g));
+        }
+        idx
+    }
+
+    #[test]
+    fn insert_get_delete() {
+        let idx = mkidx();
+        let g = cpin();
+        assert_eq!(idx.mt_len(), SPAM_CNT as usize);
+        assert!(!idx.mt_insert((10, "ten".into()), &g));
+        assert_eq!(idx.mt_get_element(&10, &g).unwrap().1, "10");
+        assert!(idx.mt_delete(&10, &g));
+        assert!(!idx.mt_delete(&10, &g));
+        assert!(idx.mt_get_element(&10, &g).is_none());
+        assert_eq!(idx.mt_delete_return_entry(&11, &g).unwrap().1, "11");
+        assert_eq!(idx.mt_len(), SPAM_CNT as usize - 2);
+    }
+
+    #[test]
+    fn ordered_scan() {
+        let idx = mkidx();
+        let g = cpin();
+        let keys: Vec<u64> = idx
+            .mt_range(Bound::<u64>::Unbounded, Bound::Unbounded, &g)
+            .map(|(k, _)| *k)
+            .collect();
+        assert_eq!(keys, (0..SPAM_CNT).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn range_scan() {
+        let idx = mkidx();
+        let g = cpin();
+        let range = |lo, hi| -> Vec<u64> { idx.mt_range(lo, hi, &g).map(|(k, _)| *k).collect() };
+        assert_eq!(
+            range(Bound::Included(100), Bound::Included(400)),
+            (100..=400).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            range(Bound::Excluded(100), Bound::Excluded(400)),
+            (101..400).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            range(Bound::Included(990), Bound::Unbounded),
+            (990..SPAM_CNT).collect::<Vec<_>>()
+        );
+        // empty and inverted ranges must not panic
+        assert!(range(Bound::Excluded(5), Bound::Excluded(5)).is_empty());
+        assert!(range(Bound::Included(500), Bound::Included(10)).is_empty());
+        assert_eq!(range(Bound::Included(5), Bound::Included(5)), vec![5]);
+    }
+
+    #[test]
+    fn delete_while_scanning() {
+        let idx = mkidx();
+        let g = cpin();
+        let mut cnt = 0;
+        for (k, v) in idx.mt_range(Bound::<u64>::Unbounded, Bound::Unbounded, &g) {
+            // the element stays alive (because of the guard) even though it was removed
+            assert!(idx.mt_delete(k, &g));
+            assert_eq!(v, &k.to_string());
+            cnt += 1;
+        }
+        assert_eq!(cnt, SPAM_CNT);
+        assert_eq!(idx.mt_len(), 0);
+    }
+}
+
+mod tuple_key {
+    use super::tuple::TupleKey;
+
+    fn key(f: impl FnOnce(&mut TupleKey)) -> TupleKey {
+        let mut k = TupleKey::default();
+        f(&mut k);
+        k
+    }
+
+    #[test]
+    fn order_preserving() {
+        let uints: Vec<_> = [0, 1, 255, 256, u64::MAX]
+            .into_iter()
+            .map(|u| key(|k| k.push_uint(u)))
+            .collect();
+        assert!(uints.windows(2).all(|w| w[0] < w[1]));
+        let sints: Vec<_> = [i64::MIN, -256, -1, 0, 1, i64::MAX]
+            .into_iter()
+            .map(|i| key(|k| k.push_sint(i)))
+            .collect();
+        assert!(sints.windows(2).all(|w| w[0] < w[1]));
+        let strs: Vec<_> = [
+            &b""[..],
+            b"\x00",
+            b"\x00\x00",
+            b"\x00a",
+            b"a",
+            b"a\x00",
+            b"ab",
+            b"b",
+        ]
+        .into_iter()
+        .map(|s| key(|k| k.push_bytes(s)))
+        .collect();
+        assert!(strs.windows(2).all(|w| w[0] < w[1]));
+        // null sorts first
+        assert!(key(|k| k.push_null()) < key(|k| k.push_uint(0)));
+    }
+
+    #[test]
+    fn component_order() {
+        // the first component decides, no matter what follows
+        let a = key(|k| {
+            k.push_bytes(b"a");
+            k.push_uint(u64::MAX);
+        });
+        let ab = key(|k| {
+            k.push_bytes(b"ab");
+            k.push_uint(0);
+        });
+        assert!(a < ab);
+        let a_null = key(|k| {
+            k.push_bytes(b"a");
+            k.push_null();
+        });
+        assert!(a_null < a);
+    }
+
+    #[test]
+    fn prefix_match() {
+        let prefix = key(|k| k.push_bytes(b"tenant"));
+        let full = key(|k| {
+            k.push_bytes(b"tenant");
+            k.push_uint(10);
+        });
+        assert!(full.starts_with(&prefix));
+        // a longer string isn't a match, even though its bytes start with the same string
+        let other = key(|k| {
+            k.push_bytes(b"tenant2");
+            k.push_uint(10);
+        });
+        assert!(!other.starts_with(&prefix));
+        assert!(!prefix.starts_with(&full));
+    }
+}
@@ -0,0 +1,76 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    tuple keys
+    ---
+    A tuple key is an order preserving, byte encoded tuple of scalars: comparing two encoded tuples byte by byte gives
+    the same result as comparing the tuples component by component (as long as the components at each position are
+    of the same type). Every component is self delimiting, so the encoding of a tuple is a prefix of the encoding of
+    every tuple that extends it, and a prefix match is just a byte prefix match.
+
+    Each component is a type byte followed by its data:
+    - null: `0x00` (sorts before any value)
+    - unsigned integers: `0x01` + the big endian value
+    - signed integers: `0x02` + the big endian value with the sign bit flipped
+    - strings and binaries: `0x03` + the bytes, with every `0x00` escaped as `0x00 0xFF`, terminated by `0x00 0x01`
+*/
+
+const TYPE_NULL: u8 = 0x00;
+const TYPE_UINT: u8 = 0x01;
+const TYPE_SINT: u8 = 0x02;
+const TYPE_BYTES: u8 = 0x03;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TupleKey(Vec<u8>);
+
+impl TupleKey {
+    pub fn push_null(&mut self) {
+        self.0.push(TYPE_NULL);
+    }
+    pub fn push_uint(&mut self, u: u64) {
+        self.0.push(TYPE_UINT);
+        self.0.extend(u.to_be_bytes());
+    }
+    pub fn push_sint(&mut self, i: i64) {
+        self.0.push(TYPE_SINT);
+        self.0.extend(((i as u64) ^ (1 << 63)).to_be_bytes());
+    }
+    pub fn push_bytes(&mut self, b: &[u8]) {
+        self.0.push(TYPE_BYTES);
+        for byte in b {
+            self.0.push(*byte);
+            if *byte == 0 {
+                self.0.push(0xFF);
+            }
+        }
+        self.0.extend([0x00, 0x01]);
+    }
+    /// Returns true if this tuple starts with all the components of the given tuple
+    pub fn starts_with(&self, prefix: &Self) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+}
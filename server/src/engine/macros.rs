@@ -203,6 +203,12 @@ macro_rules! Token {
     (space) => {
         __kw_misc!(Space)
     };
+    (index) => {
+        __kw_misc!(Index)
+    };
+    (on) => {
+        __kw_misc!(On)
+    };
     (primary) => {
         __kw_misc!(Primary)
     };
@@ -248,6 +254,9 @@ macro_rules! Token {
     (into) => {
         __kw_misc!(Into)
     };
+    (in) => {
+        __kw_misc!(In)
+    };
     (where) => {
         __kw_misc!(Where)
     };
@@ -260,9 +269,15 @@ macro_rules! Token {
     (as) => {
         __kw_misc!(As)
     };
+    (of) => {
+        __kw_misc!(Of)
+    };
     (by) => {
         __kw_misc!(By)
     };
+    (group) => {
+        __kw_misc!(Group)
+    };
     (asc) => {
         __kw_misc!(Asc)
     };
@@ -320,6 +335,9 @@ macro_rules! Token {
     (not) => {
         __kw_misc!(Not)
     };
+    (else) => {
+        __kw_misc!(Else)
+    };
     (return) => {
         __kw_misc!(Return)
     };
@@ -329,6 +347,12 @@ macro_rules! Token {
     (all) => {
         __kw_misc!(All)
     };
+    (default) => {
+        __kw_misc!(Default)
+    };
+    (batch) => {
+        __kw_misc!(Batch)
+    };
     (exists) => {
         __kw_stmt!(Exists)
     };
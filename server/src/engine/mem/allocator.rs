@@ -0,0 +1,186 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*!
+    # Allocator statistics
+
+    The global allocator is chosen at build time using the `jemalloc` (default) and `mimalloc` features (or neither,
+    for the system allocator). This module reads the allocator's own statistics so that fragmentation (memory that
+    the allocator holds on to but that isn't used by live allocations) can be observed on long running instances.
+*/
+
+/// The name of the global allocator in use
+pub const ALLOCATOR: &str = if cfg!(all(
+    feature = "jemalloc",
+    not(target_env = "msvc"),
+    not(miri)
+)) {
+    "jemalloc"
+} else if cfg!(all(feature = "mimalloc", not(miri))) {
+    "mimalloc"
+} else {
+    "system"
+};
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+/// A snapshot of the global allocator's statistics. All values are in bytes; values that the allocator does not
+/// track are `None`
+pub struct AllocatorStats {
+    /// bytes held by live allocations
+    pub allocated: Option<u64>,
+    /// bytes in pages that contain live allocations
+    pub active: Option<u64>,
+    /// bytes in physically resident pages mapped by the allocator
+    pub resident: Option<u64>,
+    /// bytes mapped by the allocator
+    pub mapped: Option<u64>,
+    /// bytes that were unmapped but are retained in the allocator's address space for reuse
+    pub retained: Option<u64>,
+}
+
+impl AllocatorStats {
+    /// Returns the ratio of active to allocated memory (`1.0` means no fragmentation), if the allocator tracks both
+    pub fn fragmentation(&self) -> Option<f64> {
+        match (self.active, self.allocated) {
+            (Some(active), Some(allocated)) if allocated != 0 => {
+                Some(active as f64 / allocated as f64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the current statistics for the global allocator
+pub fn stats() -> AllocatorStats {
+    imp::stats()
+}
+
+/// Returns the global allocator's statistics along with the resident set size of the process, as a JSON object. Stats
+/// that the allocator does not track are `null`
+pub fn describe() -> String {
+    fn json(v: Option<impl ToString>) -> String {
+        v.map_or_else(|| "null".into(), |v| v.to_string())
+    }
+    let stats = stats();
+    format!(
+        "{{\"allocator\":\"{}\",\"allocated\":{},\"active\":{},\"resident\":{},\"mapped\":{},\"retained\":{},\"fragmentation\":{},\"process_resident\":{}}}",
+        ALLOCATOR,
+        json(stats.allocated),
+        json(stats.active),
+        json(stats.resident),
+        json(stats.mapped),
+        json(stats.retained),
+        json(stats.fragmentation().map(|f| format!("{f:.3}"))),
+        crate::util::os::process_memory_in_bytes()
+    )
+}
+
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc"), not(miri)))]
+mod imp {
+    use {
+        super::AllocatorStats,
+        jemalloc_sys::mallctl,
+        std::{mem, ptr},
+    };
+
+    pub(super) fn stats() -> AllocatorStats {
+        // stats are cached by jemalloc and only refreshed when the epoch is advanced
+        let mut epoch: u64 = 1;
+        unsafe {
+            // UNSAFE(@ohsayan): the buffer is correctly sized for the (u64) epoch
+            mallctl(
+                b"epoch\0".as_ptr() as _,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut epoch as *mut _ as _,
+                mem::size_of::<u64>(),
+            );
+        }
+        AllocatorStats {
+            allocated: read(b"stats.allocated\0"),
+            active: read(b"stats.active\0"),
+            resident: read(b"stats.resident\0"),
+            mapped: read(b"stats.mapped\0"),
+            retained: read(b"stats.retained\0"),
+        }
+    }
+
+    fn read(name: &[u8]) -> Option<u64> {
+        let mut value: usize = 0;
+        let mut len = mem::size_of::<usize>();
+        let ret = unsafe {
+            // UNSAFE(@ohsayan): the name is nul terminated and all of the stats we read are `size_t`s
+            mallctl(
+                name.as_ptr() as _,
+                &mut value as *mut _ as _,
+                &mut len,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        (ret == 0).then_some(value as u64)
+    }
+}
+
+#[cfg(all(feature = "mimalloc", not(feature = "jemalloc"), not(miri)))]
+mod imp {
+    use {super::AllocatorStats, std::ptr};
+
+    pub(super) fn stats() -> AllocatorStats {
+        let (mut rss, mut commit) = (0usize, 0usize);
+        unsafe {
+            // UNSAFE(@ohsayan): all the out pointers that we don't need are allowed to be null
+            libmimalloc_sys::mi_process_info(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut rss,
+                ptr::null_mut(),
+                &mut commit,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+        }
+        // mimalloc doesn't (cheaply) track live bytes, so fragmentation isn't available
+        AllocatorStats {
+            resident: Some(rss as u64),
+            mapped: Some(commit as u64),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(not(any(
+    all(feature = "jemalloc", not(target_env = "msvc"), not(miri)),
+    all(feature = "mimalloc", not(miri))
+)))]
+mod imp {
+    use super::AllocatorStats;
+
+    pub(super) fn stats() -> AllocatorStats {
+        AllocatorStats::default()
+    }
+}
@@ -0,0 +1,343 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*!
+    # Allocator arenas
+
+    All index and row memory comes from the global allocator (jemalloc, by default). When jemalloc is in use, this
+    module lets threads allocate from arenas that we create ourselves:
+    - dedicated per-thread arenas (used by NUMA aware shards), so that a thread never reuses pages that a thread on
+    another node touched first
+    - huge page backed arenas, whose extents are mapped using huge pages to reduce TLB pressure for large datasets.
+    Threads are bound to the shared huge page arena as they start (see [`bind_current_thread`])
+
+    Huge page extents are never purged (only unmapped when jemalloc releases a whole extent), since returning parts of
+    a huge page to the OS would split it anyway.
+*/
+
+use {
+    crate::engine::config::ConfigHugePages,
+    std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering},
+};
+
+const NO_ARENA: u32 = u32::MAX;
+/// The arena that threads are bound to when huge pages are enabled
+static SHARED_ARENA: AtomicU32 = AtomicU32::new(NO_ARENA);
+/// The (effective) huge pages mode
+static HUGE_PAGES: AtomicU8 = AtomicU8::new(HUGE_PAGES_OFF);
+/// Number of times an explicit huge page mapping failed and we fell back to transparent huge pages
+static HUGE_PAGE_FALLBACKS: AtomicU64 = AtomicU64::new(0);
+
+const HUGE_PAGES_OFF: u8 = 0;
+const HUGE_PAGES_TRANSPARENT: u8 = 1;
+const HUGE_PAGES_EXPLICIT: u8 = 2;
+
+/// Returns the huge pages mode that is in effect
+pub fn huge_pages() -> ConfigHugePages {
+    match HUGE_PAGES.load(Ordering::Acquire) {
+        HUGE_PAGES_TRANSPARENT => ConfigHugePages::Transparent,
+        HUGE_PAGES_EXPLICIT => ConfigHugePages::Explicit,
+        _ => ConfigHugePages::Off,
+    }
+}
+
+/// Returns the number of times we couldn't map explicit huge pages (and used transparent huge pages instead)
+pub fn huge_page_fallbacks() -> u64 {
+    HUGE_PAGE_FALLBACKS.load(Ordering::Relaxed)
+}
+
+/// Enable huge pages (in the requested mode, if the system supports it) and create the shared huge page arena,
+/// returning the mode that is actually in effect.
+///
+/// WARN: Must be called once during startup, before any other threads are started
+pub fn enable_huge_pages(requested: ConfigHugePages) -> ConfigHugePages {
+    let mode = match requested {
+        ConfigHugePages::Off => return ConfigHugePages::Off,
+        ConfigHugePages::Explicit if imp::explicit_huge_pages_available() => HUGE_PAGES_EXPLICIT,
+        ConfigHugePages::Explicit => {
+            warn!("no explicit huge pages are reserved (see `vm.nr_hugepages`). falling back to transparent huge pages");
+            HUGE_PAGES_TRANSPARENT
+        }
+        ConfigHugePages::Transparent => HUGE_PAGES_TRANSPARENT,
+    };
+    if !imp::transparent_huge_pages_available() {
+        warn!("huge pages are not available on this system. using regular pages");
+        return ConfigHugePages::Off;
+    }
+    HUGE_PAGES.store(mode, Ordering::Release);
+    match imp::create_arena(true) {
+        Some(arena) => SHARED_ARENA.store(arena, Ordering::Release),
+        None => {
+            HUGE_PAGES.store(HUGE_PAGES_OFF, Ordering::Release);
+            warn!("failed to create a huge page arena. using regular pages");
+        }
+    }
+    huge_pages()
+}
+
+/// Bind the calling thread to the shared huge page arena (if huge pages are enabled)
+pub fn bind_current_thread() {
+    let arena = SHARED_ARENA.load(Ordering::Acquire);
+    if arena != NO_ARENA {
+        imp::bind_arena(arena);
+    }
+}
+
+/// Give the calling thread its own arena (backed by huge pages, if enabled). Returns false if the arena couldn't be
+/// created
+pub fn use_dedicated_arena() -> bool {
+    let huge = HUGE_PAGES.load(Ordering::Acquire) != HUGE_PAGES_OFF;
+    imp::create_arena(huge).map_or(false, imp::bind_arena)
+}
+
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc"), not(miri)))]
+mod imp {
+    use {
+        super::{HUGE_PAGES, HUGE_PAGES_EXPLICIT, HUGE_PAGE_FALLBACKS},
+        jemalloc_sys::{extent_hooks_t, mallctl},
+        libc::{c_uint, c_void},
+        std::{
+            mem, ptr,
+            sync::{atomic::Ordering, OnceLock},
+        },
+    };
+
+    /// The hooks for huge page arenas. jemalloc needs a stable `*mut` to these, so we leak them once
+    static HUGE_PAGE_HOOKS: OnceLock<usize> = OnceLock::new();
+
+    pub(super) fn create_arena(huge: bool) -> Option<c_uint> {
+        let mut arena: c_uint = 0;
+        let mut len = mem::size_of::<c_uint>();
+        let mut hooks = if huge {
+            let hooks = huge_page_hooks()?;
+            *HUGE_PAGE_HOOKS.get_or_init(|| Box::into_raw(Box::new(hooks)) as usize)
+                as *mut extent_hooks_t
+        } else {
+            ptr::null_mut()
+        };
+        let (new, new_len) = if hooks.is_null() {
+            (ptr::null_mut(), 0)
+        } else {
+            (
+                &mut hooks as *mut _ as *mut c_void,
+                mem::size_of::<*mut extent_hooks_t>(),
+            )
+        };
+        let ret = unsafe {
+            // UNSAFE(@ohsayan): the buffers are correctly sized for an (unsigned) arena index and a hooks pointer
+            mallctl(
+                b"arenas.create\0".as_ptr() as _,
+                &mut arena as *mut _ as _,
+                &mut len,
+                new,
+                new_len,
+            )
+        };
+        (ret == 0).then_some(arena)
+    }
+
+    pub(super) fn bind_arena(mut arena: c_uint) -> bool {
+        unsafe {
+            // UNSAFE(@ohsayan): the buffer is correctly sized for an (unsigned) arena index
+            mallctl(
+                b"thread.arena\0".as_ptr() as _,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut arena as *mut _ as _,
+                mem::size_of::<c_uint>(),
+            ) == 0
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn huge_page_hooks() -> Option<extent_hooks_t> {
+        Some(extent_hooks_t {
+            alloc: Some(huge_extent_alloc),
+            dalloc: Some(huge_extent_dalloc),
+            destroy: Some(huge_extent_destroy),
+            // no commit, decommit, purge, split or merge: jemalloc opts out of these for our extents
+            ..Default::default()
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn huge_page_hooks() -> Option<extent_hooks_t> {
+        None
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) fn transparent_huge_pages_available() -> bool {
+        // `never` means that the kernel will ignore `MADV_HUGEPAGE`
+        std::fs::read_to_string("/sys/kernel/mm/transparent_hugepage/enabled")
+            .map_or(false, |thp| !thp.contains("[never]"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub(super) fn transparent_huge_pages_available() -> bool {
+        false
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) fn explicit_huge_pages_available() -> bool {
+        std::fs::read_to_string("/proc/meminfo").map_or(false, |meminfo| {
+            meminfo
+                .lines()
+                .find_map(|line| line.strip_prefix("HugePages_Free:"))
+                .and_then(|free| free.trim().parse::<u64>().ok())
+                .map_or(false, |free| free != 0)
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub(super) fn explicit_huge_pages_available() -> bool {
+        false
+    }
+
+    /*
+        extent hooks. these are called by jemalloc (possibly with its locks held), so they must never allocate or log
+    */
+
+    #[cfg(target_os = "linux")]
+    const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+    /// Extents are mapped in multiples of the huge page size (the same length is computed when unmapping)
+    #[cfg(target_os = "linux")]
+    fn map_len(size: usize) -> usize {
+        (size + HUGE_PAGE_SIZE - 1) & !(HUGE_PAGE_SIZE - 1)
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe fn map_aligned(len: usize, alignment: usize) -> *mut c_void {
+        // overallocate, then unmap the unaligned head and the tail
+        let ptr = libc::mmap(
+            ptr::null_mut(),
+            len + alignment,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if ptr == libc::MAP_FAILED {
+            return ptr::null_mut();
+        }
+        let head = (alignment - (ptr as usize & (alignment - 1))) & (alignment - 1);
+        if head != 0 {
+            libc::munmap(ptr, head);
+        }
+        let aligned = (ptr as *mut u8).add(head);
+        let tail = alignment - head;
+        if tail != 0 {
+            libc::munmap(aligned.add(len) as _, tail);
+        }
+        aligned as _
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe extern "C" fn huge_extent_alloc(
+        _: *mut extent_hooks_t,
+        new_addr: *mut c_void,
+        size: usize,
+        alignment: usize,
+        zero: *mut bool,
+        commit: *mut bool,
+        _: c_uint,
+    ) -> *mut c_void {
+        if !new_addr.is_null() {
+            // we can't place an extent at a given address; jemalloc will ask for a new extent instead
+            return ptr::null_mut();
+        }
+        let len = map_len(size);
+        let alignment = alignment.max(HUGE_PAGE_SIZE);
+        let mut ptr = libc::MAP_FAILED;
+        if (HUGE_PAGES.load(Ordering::Relaxed) == HUGE_PAGES_EXPLICIT)
+            & (alignment == HUGE_PAGE_SIZE)
+        {
+            // explicit huge pages are always aligned to the huge page size
+            ptr = libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_HUGETLB,
+                -1,
+                0,
+            );
+            if ptr == libc::MAP_FAILED {
+                // the reserved pool is exhausted
+                HUGE_PAGE_FALLBACKS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        if ptr == libc::MAP_FAILED {
+            ptr = map_aligned(len, alignment);
+            if ptr.is_null() {
+                return ptr;
+            }
+            libc::madvise(ptr, len, libc::MADV_HUGEPAGE);
+        }
+        // fresh anonymous mappings are zeroed and committed
+        *zero = true;
+        *commit = true;
+        ptr
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe extern "C" fn huge_extent_dalloc(
+        _: *mut extent_hooks_t,
+        addr: *mut c_void,
+        size: usize,
+        _: bool,
+        _: c_uint,
+    ) -> bool {
+        // jemalloc expects false on success
+        libc::munmap(addr, map_len(size)) != 0
+    }
+
+    #[cfg(target_os = "linux")]
+    unsafe extern "C" fn huge_extent_destroy(
+        _: *mut extent_hooks_t,
+        addr: *mut c_void,
+        size: usize,
+        _: bool,
+        _: c_uint,
+    ) {
+        libc::munmap(addr, map_len(size));
+    }
+}
+
+#[cfg(not(all(feature = "jemalloc", not(target_env = "msvc"), not(miri))))]
+mod imp {
+    pub(super) fn create_arena(_: bool) -> Option<u32> {
+        None
+    }
+    pub(super) fn bind_arena(_: u32) -> bool {
+        false
+    }
+    pub(super) fn transparent_huge_pages_available() -> bool {
+        false
+    }
+    pub(super) fn explicit_huge_pages_available() -> bool {
+        false
+    }
+}
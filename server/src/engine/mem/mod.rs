@@ -24,109 +24,33 @@
  *
 */
 
+/*
+    the raw data structures (`UArray`, `VInline`, `FixedVec`, the scanners, words and raw slices) live in the `no_std`
+    `sky_mem` crate so that tools which read data files can use them without the server, and are re-exported here.
+    what's left is tied to the server: `allocator` and `arena` set up the global allocator and huge pages from the
+    config, and `AStr` is built from QL identifiers. `engine::idx` isn't moved: the concurrent indexes pin through
+    crossbeam's default collector (which needs std) in `sync::atm`, and `IndexST` is std's `HashMap`
+*/
+
+pub mod allocator;
+pub mod arena;
 mod astr;
-pub mod fixed_vec;
-mod ll;
-mod numbuf;
-mod rawslice;
-pub mod scanner;
 mod stackop;
-mod uarray;
-pub mod unsafe_apis;
-mod vinline;
-mod word;
 // test
 #[cfg(test)]
 mod tests;
 // re-exports
 pub use {
     astr::AStr,
-    ll::CachePadded,
-    numbuf::IntegerRepr,
-    rawslice::RawStr,
-    scanner::BufferedScanner,
-    uarray::UArray,
-    vinline::VInline,
-    word::{DwordNN, DwordQN, WordIO, ZERO_BLOCK},
+    sky_mem::{
+        fixed_vec, scanner, unsafe_apis, BufferedScanner, CachePadded, DwordNN, DwordQN,
+        IntegerRepr, NativeQword, RawStr, SpecialPaddedWord, StatelessLen, UArray, VInline, WordIO,
+        ZERO_BLOCK,
+    },
 };
 
-/// Native double pointer width (note, native != arch native, but host native)
-pub struct NativeDword([usize; 2]);
-/// Native triple pointer width (note, native != arch native, but host native)
-pub struct NativeTword([usize; 3]);
-/// Native quad pointer width (note, native != arch native, but host native)
-pub struct NativeQword([usize; 4]);
-/// A special word with a special bit pattern padded (with a quad)
-///
-/// **WARNING**: DO NOT EXPECT this to have the same bit pattern as that of native word sizes. It's called "special" FOR A REASON
-pub struct SpecialPaddedWord {
-    a: u64,
-    b: usize,
-}
-
-impl SpecialPaddedWord {
-    pub const unsafe fn new(a: u64, b: usize) -> Self {
-        Self { a, b }
-    }
-    pub fn new_quad(a: u64) -> Self {
-        Self {
-            a,
-            b: ZERO_BLOCK.as_ptr() as usize,
-        }
-    }
-}
-
-pub trait StatelessLen {
-    fn stateless_len(&self) -> usize;
-    fn stateless_empty(&self) -> bool {
-        self.stateless_len() == 0
-    }
-}
-
-impl<T> StatelessLen for Vec<T> {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
-
-impl<T> StatelessLen for Box<[T]> {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
-
-impl StatelessLen for String {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
-
-impl StatelessLen for str {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
-
-impl<T> StatelessLen for [T] {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
-
-impl<const N: usize, T> StatelessLen for VInline<N, T> {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
-
 impl<const N: usize> StatelessLen for AStr<N> {
     fn stateless_len(&self) -> usize {
         self.len()
     }
 }
-
-impl<const N: usize, T> StatelessLen for UArray<N, T> {
-    fn stateless_len(&self) -> usize {
-        self.len()
-    }
-}
@@ -27,10 +27,13 @@
 #[macro_use]
 mod macros;
 pub mod config;
+mod config_check;
 mod core;
+mod crash;
 mod data;
 mod error;
 mod fractal;
+pub mod fuzz;
 mod idx;
 mod mem;
 mod net;
@@ -42,16 +45,21 @@ mod txn;
 #[cfg(test)]
 mod tests;
 // re-export
-pub use error::RuntimeResult;
+pub use {error::RuntimeResult, storage::bench};
 
 use crate::engine::storage::SELoaded;
 
 use {
     self::{
-        config::{ConfigEndpoint, ConfigEndpointTls, ConfigMode, Configuration},
+        config::{
+            ConfigEndpoint, ConfigEndpointTls, ConfigMode, ConfigRuntime, ConfigStorage,
+            Configuration,
+        },
+        core::model::warmup,
         fractal::context::{self, Subsystem},
+        storage::safe_interfaces::paths_v1,
     },
-    crate::util::os::TerminationSignal,
+    crate::util::os::{self, TerminationSignal},
     tokio::sync::broadcast,
 };
 
@@ -59,6 +67,26 @@ pub(super) fn set_context_init(msg: &'static str) {
     context::set(Subsystem::Init, msg)
 }
 
+/// Enable huge pages for index and row memory (if configured), for the calling thread and all threads that call
+/// [`bind_thread_arena`] when they start
+///
+/// WARN: Must be called before any other threads are started
+pub fn init_huge_pages(config: &mut Configuration) {
+    let requested = config.system.huge_pages;
+    config.system.huge_pages = mem::arena::enable_huge_pages(requested);
+    match config.system.huge_pages {
+        config::ConfigHugePages::Off => {}
+        config::ConfigHugePages::Transparent => info!("using transparent huge pages"),
+        config::ConfigHugePages::Explicit => info!("using explicit huge pages"),
+    }
+    mem::arena::bind_current_thread();
+}
+
+/// Bind the calling thread to the shared (huge page) arena, if huge pages are enabled
+pub fn bind_thread_arena() {
+    mem::arena::bind_current_thread()
+}
+
 /// Initialize all drivers, load all data
 ///
 /// WARN: Must be in [`tokio::runtime::Runtime`] context!
@@ -69,17 +97,56 @@ pub fn load_all(
     if config.mode == ConfigMode::Dev {
         warn!("running in dev mode");
     }
+    crash::install(&config);
     info!("starting storage engine");
     context::set_origin(Subsystem::Storage);
     let SELoaded { gns } = storage::load(&config)?;
     info!("storage engine ready. initializing system");
     let global = unsafe {
         // UNSAFE(@ohsayan): the only call we ever make
-        fractal::load_and_enable_all(gns)
+        fractal::load_and_enable_all(gns, &config.system)
     };
+    crash::set_global(&global.global);
+    // rebuild secondary indexes in the background; until an index is ready, queries fall back to a scan
+    let sidx_global = global.global.clone();
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    std::thread::Builder::new()
+        .name("sidx-rebuild".into())
+        .spawn(move || {
+            mem::arena::bind_current_thread();
+            core::model::build_pending_indexes(&sidx_global, workers)
+        })?;
     Ok((config, global))
 }
 
+/// Start the probe listener (if one was configured), so that liveness can be probed while data is recovered
+///
+/// WARN: Must be in [`tokio::runtime::Runtime`] context!
+pub async fn start_probes(config: &Configuration) -> RuntimeResult<()> {
+    if let Some(ep) = &config.system.probe_endpoint {
+        net::probe::start(ep).await?;
+        info!("serving probes on http@{}:{}", ep.host(), ep.port());
+    }
+    Ok(())
+}
+
+/// Run the protocol test kit (`skyd testkit`) with the given arguments
+pub fn run_testkit(args: Vec<String>) -> RuntimeResult<()> {
+    net::protocol::testkit::run(args)
+}
+
+/// Run the offline data inspector (`sky-inspect`) with the given arguments
+pub fn run_inspect(args: Vec<String>) -> RuntimeResult<()> {
+    storage::run_inspect(args)
+}
+
+/// Check the configuration (`skyd --check-config`) and print the findings. Returns true if none of them is an error
+pub fn run_config_check() -> bool {
+    let report = config_check::run();
+    println!("{}", report.describe());
+    report.passed()
+}
+
 enum EndpointListeners {
     Insecure(net::Listener),
     Secure {
@@ -122,16 +189,120 @@ pub async fn start(
 ) -> RuntimeResult<()> {
     // create our system-wide channel
     let (signal, _) = broadcast::channel::<()>(1);
+    if let Some(ep) = &system.otlp_endpoint {
+        fractal::trace::start(ep, system.trace_sample_rate);
+        info!(
+            "exporting {}% of traces to http@{}:{}",
+            system.trace_sample_rate,
+            ep.host(),
+            ep.port()
+        );
+    }
+    if let Some(tier) = &system.blob_tier {
+        fractal::tier::start(tier);
+        info!(
+            "moving binary values larger than {} bytes to bucket `{}` at http@{}:{}",
+            tier.threshold,
+            tier.bucket,
+            tier.endpoint.host(),
+            tier.endpoint.port()
+        );
+    }
+    if let Some(upstream) = &system.upstream {
+        fractal::upstream::start(upstream);
+        info!(
+            "caching models of the upstream at tcp@{}:{}",
+            upstream.endpoint.host(),
+            upstream.endpoint.port()
+        );
+    }
+    if let Some(shardmap) = &system.shardmap {
+        fractal::shardmap::set(shardmap);
+        info!(
+            "serving a shard map of {} node(s) to drivers",
+            shardmap.nodes.len()
+        );
+    }
+    if system.lock_wait_timeout != 0 {
+        core::lock::set_wait_timeout(system.lock_wait_timeout);
+        info!(
+            "statements wait at most {}ms for a row lock",
+            system.lock_wait_timeout
+        );
+    }
+    core::session::set_policy(system.statement_timeout, system.relaxed_sessions);
+    if system.statement_timeout != 0 {
+        info!("statements run for at most {}ms", system.statement_timeout);
+    }
+    if system.relaxed_sessions {
+        info!("sessions can relax the durability of the models that they write to");
+    }
+    core::names::set_case_insensitive(system.case_insensitive_names);
+    if system.case_insensitive_names {
+        info!("the names of spaces, models and fields are case insensitive");
+    }
+    core::dml::prefetch::set_lookahead(system.scan_prefetch);
+    if system.scan_prefetch == 0 {
+        info!("scans will not prefetch rows");
+    }
+    if system.max_response_size != 0 {
+        info!(
+            "statements can return at most {} bytes",
+            system.max_response_size
+        );
+    }
+    if system.response_quota != 0 {
+        info!(
+            "connections can be sent at most {} bytes every {}s",
+            system.response_quota, system.response_quota_window
+        );
+    }
+    fractal::webhook::start(global.clone());
+    fractal::snapshot::start(global.clone());
     // start our services
     context::set_dmsg("starting fractal engine");
     let fractal_handle = boot.boot(&signal, system.reliability_system_window);
+    // bring back the access history of the last run (and warm its rows, if asked to)
+    let persistent = system.storage == ConfigStorage::Sdss;
+    if persistent {
+        context::set_dmsg("loading access history");
+        let hot = warmup::load(&paths_v1::warmup_path())?;
+        warmup::seed(&global, &hot);
+        if system.warmup && !hot.is_empty() {
+            net::probe::set_warming();
+            context::set_dmsg("warming hot rows");
+            let g = global.clone();
+            let warmed = tokio::task::spawn_blocking(move || warmup::warm(&g, &hot))
+                .await
+                .unwrap_or(0);
+            info!("warmed {warmed} hot row(s) of the last run");
+        }
+        if let Some(dir) = &system.archive_dir {
+            fractal::archive::start(global.clone(), dir);
+            info!("archiving journals to {dir}");
+        }
+    }
+    // start our shards (if any)
+    context::set(Subsystem::Network, "initializing runtime");
+    let shards = match system.runtime {
+        ConfigRuntime::MultiThreaded => None,
+        ConfigRuntime::Sharded => {
+            let count = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let shards = net::Shards::start(count, system.numa_aware)?;
+            info!("serving connections using {} shards", shards.count());
+            Some(shards)
+        }
+    };
+    let spawner = shards
+        .as_ref()
+        .map_or(net::ConnectionSpawner::Runtime, net::Shards::spawner);
     // create our server
-    context::set(Subsystem::Network, "initializing endpoints");
+    context::set_dmsg("initializing endpoints");
     let str;
     let mut endpoint_handles = match &endpoints {
         ConfigEndpoint::Secure(ConfigEndpointTls { tcp, .. }) | ConfigEndpoint::Insecure(tcp) => {
             let listener =
-                net::Listener::new(tcp.host(), tcp.port(), global.clone(), signal.clone()).await?;
+                net::Listener::new_cfg(tcp, global.clone(), spawner, signal.clone()).await?;
             if let ConfigEndpoint::Secure(s) = endpoints {
                 context::set_dmsg("initializing TLS");
                 let acceptor = net::Listener::init_tls(s.cert(), s.private_key(), s.pkey_pass())?;
@@ -146,10 +317,16 @@ pub async fn start(
             }
         }
         ConfigEndpoint::Multi(insecure_ep, secure_ep) => {
-            let tcp_listener =
-                net::Listener::new_cfg(insecure_ep, global.clone(), signal.clone()).await?;
+            let tcp_listener = net::Listener::new_cfg(
+                insecure_ep,
+                global.clone(),
+                spawner.clone(),
+                signal.clone(),
+            )
+            .await?;
             let tls_listener =
-                net::Listener::new_cfg(secure_ep.tcp(), global.clone(), signal.clone()).await?;
+                net::Listener::new_cfg(secure_ep.tcp(), global.clone(), spawner, signal.clone())
+                    .await?;
             context::set_dmsg("initializing TLS");
             let acceptor = net::Listener::init_tls(
                 secure_ep.cert(),
@@ -171,14 +348,23 @@ pub async fn start(
         }
     };
     info!("{str}");
+    // all data was recovered before we got here, so we're actually ready
+    os::notify_ready(&str);
+    net::probe::set_serving(&global);
     tokio::select! {
         _ = endpoint_handles.listen() => {}
         _ = termsig => {
             info!("received terminate signal. waiting for inflight tasks to complete ...");
         }
     }
+    os::notify_stopping();
+    net::probe::set_stopping();
     drop(signal);
     endpoint_handles.finish().await;
+    if let Some(shards) = shards {
+        info!("stopping shards ...");
+        let _ = tokio::task::spawn_blocking(move || shards.shutdown()).await;
+    }
     info!("waiting for fractal engine to exit ...");
     let (hp_handle, lp_handle) = tokio::join!(fractal_handle.hp_handle, fractal_handle.lp_handle);
     match (hp_handle, lp_handle) {
@@ -189,6 +375,13 @@ pub async fn start(
         (_, Err(e)) => error!("error while terminating flp-executor: {e}"),
         _ => {}
     }
+    if persistent {
+        if let Err(e) = warmup::save(&global, &paths_v1::warmup_path()) {
+            warn!("failed to save the access history, so no rows will be warmed on the next start: {e}");
+        }
+    }
+    fractal::upstream::finish().await;
+    fractal::trace::finish().await;
     Ok(())
 }
 
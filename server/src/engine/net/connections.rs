@@ -0,0 +1,77 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    connection registry
+    ---
+    every client connection is registered once its handshake succeeds and stays registered until it is closed, so
+    that the instance can list who is connected (see `sys.connections` in [`crate::engine::core::catalog`])
+*/
+
+use {crate::util::os, parking_lot::Mutex, std::collections::BTreeMap};
+
+static CONNECTIONS: Mutex<BTreeMap<u64, Connection>> = parking_lot::const_mutex(BTreeMap::new());
+
+#[derive(Debug, Clone, PartialEq)]
+/// A connection that is open
+pub struct Connection {
+    pub id: u64,
+    pub username: Box<str>,
+    pub root: bool,
+    /// the time (in seconds since the epoch) at which the connection was established
+    pub connected_at: u64,
+    /// the tag of the last tagged query that the connection sent, if any
+    pub last_tag: Option<Box<str>>,
+}
+
+pub fn register(id: u64, username: &str, root: bool) {
+    CONNECTIONS.lock().insert(
+        id,
+        Connection {
+            id,
+            username: username.into(),
+            root,
+            connected_at: os::get_epoch_time_secs(),
+            last_tag: None,
+        },
+    );
+}
+
+pub fn unregister(id: u64) {
+    CONNECTIONS.lock().remove(&id);
+}
+
+/// Note that the connection sent a query tagged with `tag`
+pub fn set_tag(id: u64, tag: &str) {
+    if let Some(connection) = CONNECTIONS.lock().get_mut(&id) {
+        connection.last_tag = Some(tag.into());
+    }
+}
+
+/// Returns the open connections, ordered by id
+pub fn list() -> Vec<Connection> {
+    CONNECTIONS.lock().values().cloned().collect()
+}
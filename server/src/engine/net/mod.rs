@@ -24,12 +24,22 @@
  *
 */
 
+pub mod connections;
+pub mod probe;
 pub mod protocol;
+mod shard;
+
+pub use shard::{ConnectionSpawner, Shards};
 
 use {
-    crate::engine::{
-        config::ConfigEndpointTcp, error::RuntimeResult, fractal::error::ErrorContext,
-        fractal::Global,
+    crate::{
+        engine::{
+            config::{ConfigEndpointTcp, ConfigKeepalive},
+            error::RuntimeResult,
+            fractal::error::ErrorContext,
+            fractal::{trace::Span, Global},
+        },
+        util::os,
     },
     bytes::BytesMut,
     openssl::{
@@ -60,6 +70,7 @@ enum QueryLoopResult {
     Fin,
     Rst,
     HSFailed,
+    IdleTimeout,
 }
 
 /*
@@ -100,6 +111,7 @@ pub struct ConnectionHandler<S> {
     socket: BufWriter<S>,
     buffer: BytesMut,
     global: Global,
+    idle_timeout: Option<Duration>,
     sig_terminate: broadcast::Receiver<()>,
     _sig_inflight_complete: mpsc::Sender<()>,
 }
@@ -108,6 +120,7 @@ impl<S: Socket> ConnectionHandler<S> {
     pub fn new(
         socket: S,
         global: Global,
+        idle_timeout: Option<Duration>,
         term_sig: broadcast::Receiver<()>,
         _inflight_complete: mpsc::Sender<()>,
     ) -> Self {
@@ -115,6 +128,7 @@ impl<S: Socket> ConnectionHandler<S> {
             socket: BufWriter::with_capacity(BUF_WRITE_CAP, socket),
             buffer: BytesMut::with_capacity(BUF_READ_CAP),
             global,
+            idle_timeout,
             sig_terminate: term_sig,
             _sig_inflight_complete: _inflight_complete,
         }
@@ -124,17 +138,40 @@ impl<S: Socket> ConnectionHandler<S> {
             socket,
             buffer,
             global,
+            idle_timeout,
             ..
         } = self;
+        let mut span = Span::root("skyd.connection");
+        let close = |span: &mut Option<Span>, how: &'static str| {
+            if let Some(span) = span.as_mut() {
+                span.set_str("skyd.close", how);
+            }
+        };
         loop {
             tokio::select! {
-                ret = protocol::query_loop(socket, buffer, global) => {
+                ret = protocol::query_loop(socket, buffer, global, *idle_timeout, &mut span) => {
                     socket.flush().await?;
                     match ret {
-                        Ok(QueryLoopResult::Fin) => return Ok(()),
-                        Ok(QueryLoopResult::Rst) => error!("connection reset while talking to client"),
-                        Ok(QueryLoopResult::HSFailed) => error!("failed to handshake with client"),
+                        Ok(QueryLoopResult::Fin) => {
+                            close(&mut span, "fin");
+                            return Ok(())
+                        }
+                        Ok(QueryLoopResult::Rst) => {
+                            close(&mut span, "rst");
+                            error!("connection reset while talking to client")
+                        }
+                        Ok(QueryLoopResult::HSFailed) => {
+                            close(&mut span, "handshake_failed");
+                            error!("failed to handshake with client")
+                        }
+                        Ok(QueryLoopResult::IdleTimeout) => {
+                            close(&mut span, "idle_timeout");
+                            info!("closed idle client connection")
+                        }
                         Err(e) => {
+                            if let Some(span) = span.as_mut() {
+                                span.set_error(e.to_string());
+                            }
                             error!("error while handling connection: {e}");
                             return Err(e);
                         }
@@ -142,6 +179,7 @@ impl<S: Socket> ConnectionHandler<S> {
                     return Ok(())
                 },
                 _ = self.sig_terminate.recv() => {
+                    close(&mut span, "shutdown");
                     return Ok(());
                 }
             }
@@ -153,6 +191,8 @@ impl<S: Socket> ConnectionHandler<S> {
 pub struct Listener {
     global: Global,
     listener: TcpListener,
+    keepalive: ConfigKeepalive,
+    spawner: ConnectionSpawner,
     sig_shutdown: broadcast::Sender<()>,
     sig_inflight: mpsc::Sender<()>,
     sig_inflight_wait: mpsc::Receiver<()>,
@@ -162,23 +202,39 @@ impl Listener {
     pub async fn new_cfg(
         tcp: &ConfigEndpointTcp,
         global: Global,
+        spawner: ConnectionSpawner,
         sig_shutdown: broadcast::Sender<()>,
     ) -> RuntimeResult<Self> {
-        Self::new(tcp.host(), tcp.port(), global, sig_shutdown).await
+        Self::new(
+            tcp.host(),
+            tcp.port(),
+            tcp.keepalive(),
+            global,
+            spawner,
+            sig_shutdown,
+        )
+        .await
     }
     pub async fn new(
         host: &str,
         port: u16,
+        keepalive: ConfigKeepalive,
         global: Global,
+        spawner: ConnectionSpawner,
         sig_shutdown: broadcast::Sender<()>,
     ) -> RuntimeResult<Self> {
         let (sig_inflight, sig_inflight_wait) = mpsc::channel(1);
         let listener = TcpListener::bind((host, port))
             .await
             .set_dmsg(format!("failed to bind to port `{host}:{port}`"))?;
+        if keepalive.probe_after.is_some() & !os::supports_tcp_keepalive() {
+            warn!("TCP keepalive isn't supported on this platform, so it won't be used for `{host}:{port}`");
+        }
         Ok(Self {
             global,
             listener,
+            keepalive,
+            spawner,
             sig_shutdown,
             sig_inflight,
             sig_inflight_wait,
@@ -195,11 +251,20 @@ impl Listener {
         drop(sig_inflight); // could be that we are the only ones holding this lol
         let _ = sig_inflight_wait.recv().await; // wait
     }
+    fn idle_timeout(&self) -> Option<Duration> {
+        self.keepalive.idle_timeout.map(Duration::from_secs)
+    }
     async fn accept(&mut self) -> IoResult<(TcpStream, SocketAddr)> {
         let backoff = NetBackoff::new();
         loop {
             match self.listener.accept().await {
-                Ok(s) => return Ok(s),
+                Ok((stream, addr)) => {
+                    if let Some(probe_after) = self.keepalive.probe_after {
+                        // not fatal; the connection just won't be probed
+                        let _ = os::set_tcp_keepalive(&stream, Duration::from_secs(probe_after));
+                    }
+                    return Ok((stream, addr));
+                }
                 Err(e) => {
                     if backoff.should_disconnect() {
                         // that's enough of your crappy connection dear sir
@@ -224,13 +289,18 @@ impl Listener {
                     continue;
                 }
             };
-            let mut handler = ConnectionHandler::new(
-                stream,
-                self.global.clone(),
-                self.sig_shutdown.subscribe(),
-                self.sig_inflight.clone(),
-            );
-            tokio::spawn(async move {
+            let global = self.global.clone();
+            let idle_timeout = self.idle_timeout();
+            let sig_terminate = self.sig_shutdown.subscribe();
+            let sig_inflight = self.sig_inflight.clone();
+            self.spawner.spawn(stream, move |stream| async move {
+                let mut handler = ConnectionHandler::new(
+                    stream,
+                    global,
+                    idle_timeout,
+                    sig_terminate,
+                    sig_inflight,
+                );
                 if let Err(e) = handler.run().await {
                     warn!("error handling client connection: `{e}`");
                 }
@@ -264,11 +334,9 @@ impl Listener {
             let stream = async {
                 let (stream, _) = self.accept().await?;
                 let ssl = Ssl::new(acceptor.context())?;
-                let mut stream = SslStream::new(ssl, stream)?;
-                Pin::new(&mut stream).accept().await?;
-                RuntimeResult::Ok(stream)
+                RuntimeResult::Ok((stream, ssl))
             };
-            let stream = match stream.await {
+            let (stream, ssl) = match stream.await {
                 Ok(s) => s,
                 Err(e) => {
                     /*
@@ -278,13 +346,34 @@ impl Listener {
                     continue;
                 }
             };
-            let mut handler = ConnectionHandler::new(
-                stream,
-                self.global.clone(),
-                self.sig_shutdown.subscribe(),
-                self.sig_inflight.clone(),
-            );
-            tokio::spawn(async move {
+            let global = self.global.clone();
+            let idle_timeout = self.idle_timeout();
+            let sig_terminate = self.sig_shutdown.subscribe();
+            let sig_inflight = self.sig_inflight.clone();
+            // the handshake is done by the connection's own task (on its shard, if sharded)
+            self.spawner.spawn(stream, move |stream| async move {
+                let stream = async {
+                    let mut stream = SslStream::new(ssl, stream)?;
+                    Pin::new(&mut stream).accept().await?;
+                    RuntimeResult::Ok(stream)
+                };
+                let stream = match stream.await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        /*
+                            SECURITY: Once again, ignore this error
+                        */
+                        warn!("failed to accept connection on TLS socket: `{e}`");
+                        return;
+                    }
+                };
+                let mut handler = ConnectionHandler::new(
+                    stream,
+                    global,
+                    idle_timeout,
+                    sig_terminate,
+                    sig_inflight,
+                );
                 if let Err(e) = handler.run().await {
                     warn!("error handling client TLS connection: `{e}`");
                 }
@@ -0,0 +1,304 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    probes
+    ---
+    the probe listener is a (very) small HTTP/1.1 server that answers liveness (`/healthz`) and readiness (`/readyz`)
+    checks, for orchestrators such as k8s. it is bound before any data is recovered so that a long recovery isn't
+    mistaken for a hung process. every response closes the connection
+*/
+
+use {
+    crate::engine::{
+        config::ConfigEndpointTcp,
+        error::RuntimeResult,
+        fractal::{error::ErrorContext, Global, GlobalInstanceLike},
+    },
+    std::{
+        sync::{
+            atomic::{AtomicU8, Ordering},
+            OnceLock,
+        },
+        time::Duration,
+    },
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        time,
+    },
+};
+
+/// The most we'll read of a request (we only need the request line)
+const REQUEST_MAX: usize = 1024;
+/// How long a client has to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+static PHASE: AtomicU8 = AtomicU8::new(Phase::Recovering as u8);
+static GLOBAL: OnceLock<Global> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+/// The lifecycle phase of the server
+pub enum Phase {
+    /// data is being recovered, or the endpoints aren't bound yet
+    Recovering = 0,
+    /// the hottest rows of the last run are being warmed (see `model::warmup`)
+    Warming = 1,
+    /// the endpoints are accepting connections
+    Serving = 2,
+    /// the server is shutting down
+    Stopping = 3,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Recovering => "recovering",
+            Self::Warming => "warming",
+            Self::Serving => "serving",
+            Self::Stopping => "stopping",
+        }
+    }
+}
+
+/// Returns the current phase of the server
+pub fn phase() -> Phase {
+    match PHASE.load(Ordering::Acquire) {
+        0 => Phase::Recovering,
+        1 => Phase::Warming,
+        2 => Phase::Serving,
+        _ => Phase::Stopping,
+    }
+}
+
+/// Mark the server as warming its caches (the data has been recovered)
+pub fn set_warming() {
+    PHASE.store(Phase::Warming as u8, Ordering::Release);
+}
+
+/// Mark the server as accepting connections
+pub fn set_serving(global: &Global) {
+    let _ = GLOBAL.set(global.clone());
+    PHASE.store(Phase::Serving as u8, Ordering::Release);
+}
+
+/// Mark the server as shutting down
+pub fn set_stopping() {
+    PHASE.store(Phase::Stopping as u8, Ordering::Release);
+}
+
+#[derive(Debug, PartialEq)]
+/// The readiness of the server to serve queries
+pub struct Readiness {
+    reasons: Vec<&'static str>,
+}
+
+impl Readiness {
+    /// Check if the server is ready. The server is ready if it is serving connections, no storage faults are
+    /// outstanding and expensive queries aren't being rejected due to memory pressure
+    pub fn check(phase: Phase, global: Option<&impl GlobalInstanceLike>) -> Self {
+        let mut reasons = vec![];
+        match phase {
+            Phase::Recovering => reasons.push("recovering"),
+            Phase::Warming => reasons.push("warming"),
+            Phase::Serving => {}
+            Phase::Stopping => reasons.push("stopping"),
+        }
+        if let Some(global) = global {
+            if !global.health().status_okay() {
+                reasons.push("storage_fault");
+            }
+            if global.health().is_degraded() {
+                reasons.push("storage_degraded");
+            }
+            if global.health().is_disk_full() {
+                reasons.push("disk_full");
+            }
+            if global.memory_watermark().under_pressure() {
+                reasons.push("memory_pressure");
+            }
+        }
+        Self { reasons }
+    }
+    pub fn is_ready(&self) -> bool {
+        self.reasons.is_empty()
+    }
+    pub fn reasons(&self) -> &[&'static str] {
+        &self.reasons
+    }
+    /// Returns the readiness as a JSON object
+    pub fn describe(&self) -> String {
+        let reasons: Vec<String> = self.reasons.iter().map(|r| format!("\"{r}\"")).collect();
+        format!(
+            "{{\"ready\":{},\"reasons\":[{}]}}",
+            self.is_ready(),
+            reasons.join(",")
+        )
+    }
+}
+
+/// Returns the liveness of the process as a JSON object. If we can answer, we're alive
+pub fn describe_liveness() -> String {
+    format!("{{\"alive\":true,\"phase\":\"{}\"}}", phase().name())
+}
+
+/// Bind the probe listener and serve probes in the background, until the runtime is shut down
+pub async fn start(ep: &ConfigEndpointTcp) -> RuntimeResult<()> {
+    let listener = TcpListener::bind((ep.host(), ep.port()))
+        .await
+        .set_dmsg(format!(
+            "failed to bind probe listener to `{}:{}`",
+            ep.host(),
+            ep.port()
+        ))?;
+    tokio::spawn(async move {
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("failed to accept connection on probe listener: `{e}`");
+                    continue;
+                }
+            };
+            tokio::spawn(async move {
+                if let Err(e) = serve(stream).await {
+                    warn!("error handling probe: `{e}`");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+async fn serve(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut buf = [0u8; REQUEST_MAX];
+    let mut len = 0;
+    // read until we have the request line
+    while !buf[..len].contains(&b'\n') && len < REQUEST_MAX {
+        match time::timeout(REQUEST_TIMEOUT, stream.read(&mut buf[len..])).await {
+            Ok(Ok(0)) | Err(_) => return Ok(()),
+            Ok(Ok(n)) => len += n,
+            Ok(Err(e)) => return Err(e),
+        }
+    }
+    let (status, body) = respond(&buf[..len]);
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Returns the status line and body for the given request
+fn respond(request: &[u8]) -> (&'static str, String) {
+    let Some((method, path)) = parse_request_line(request) else {
+        return ("400 Bad Request", "{}".into());
+    };
+    if method != "GET" {
+        return ("405 Method Not Allowed", "{}".into());
+    }
+    match path {
+        "/healthz" => ("200 OK", describe_liveness()),
+        "/readyz" => {
+            let readiness = Readiness::check(phase(), GLOBAL.get());
+            let status = if readiness.is_ready() {
+                "200 OK"
+            } else {
+                "503 Service Unavailable"
+            };
+            (status, readiness.describe())
+        }
+        _ => ("404 Not Found", "{}".into()),
+    }
+}
+
+/// Returns the method and path (without the query string) from the request line
+fn parse_request_line(request: &[u8]) -> Option<(&str, &str)> {
+    let line = request.split(|b| *b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?.trim_end_matches('\r');
+    let mut parts = line.split(' ');
+    let (method, target, version) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || !version.starts_with("HTTP/1.") {
+        return None;
+    }
+    let path = target.split('?').next()?;
+    Some((method, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{parse_request_line, respond, Phase, Readiness},
+        crate::engine::fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+    };
+
+    #[test]
+    fn request_line() {
+        assert_eq!(
+            parse_request_line(b"GET /readyz HTTP/1.1\r\nHost: sky\r\n\r\n"),
+            Some(("GET", "/readyz"))
+        );
+        assert_eq!(
+            parse_request_line(b"GET /healthz?verbose=1 HTTP/1.0\r\n"),
+            Some(("GET", "/healthz"))
+        );
+        assert_eq!(parse_request_line(b"GET /healthz\r\n"), None);
+        assert_eq!(parse_request_line(b"\xff\xfe"), None);
+    }
+
+    #[test]
+    fn routes() {
+        assert_eq!(respond(b"GET /healthz HTTP/1.1\r\n").0, "200 OK");
+        assert_eq!(
+            respond(b"POST /healthz HTTP/1.1\r\n").0,
+            "405 Method Not Allowed"
+        );
+        assert_eq!(respond(b"GET /metrics HTTP/1.1\r\n").0, "404 Not Found");
+        assert_eq!(respond(b"garbage").0, "400 Bad Request");
+    }
+
+    #[test]
+    fn readiness() {
+        let mut global = TestGlobal::new_with_driver_id_instant_update("readiness_probe");
+        let ready = Readiness::check(Phase::Serving, Some(&global));
+        assert!(ready.is_ready());
+        assert_eq!(ready.describe(), "{\"ready\":true,\"reasons\":[]}");
+        let not_ready = Readiness::check(Phase::Recovering, None::<&TestGlobal>);
+        assert_eq!(not_ready.reasons(), ["recovering"]);
+        let warming = Readiness::check(Phase::Warming, None::<&TestGlobal>);
+        assert_eq!(warming.reasons(), ["warming"]);
+        global.set_memory_watermarks(1000, 500);
+        global.set_memory_usage(1000);
+        global.admit_expensive_query().unwrap_err();
+        let pressure = Readiness::check(Phase::Stopping, Some(&global));
+        assert_eq!(
+            pressure.describe(),
+            "{\"ready\":false,\"reasons\":[\"stopping\",\"memory_pressure\"]}"
+        );
+    }
+}
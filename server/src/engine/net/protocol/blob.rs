@@ -0,0 +1,161 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    blob uploads
+    ---
+    the uploads that a connection has begun (see the blob frames in `exchange`). the bytes of an upload are spooled to
+    a file in the temporary directory as they're received, and are only read back (to be written into the target)
+    once the upload is committed. an upload that is aborted, or still open when the connection closes, is removed
+    along with its file
+*/
+
+use {
+    super::exchange::BlobTarget,
+    crate::engine::error::{QueryError, QueryResult},
+    std::{
+        collections::HashMap,
+        path::PathBuf,
+        sync::atomic::{AtomicU64, Ordering},
+    },
+    tokio::{
+        fs::{self, File},
+        io::AsyncWriteExt,
+    },
+};
+
+/// upload ids are unique across connections, so that they can also name the spool files
+static NEXT_UPLOAD_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The uploads that a connection has open
+pub(super) struct BlobUploads {
+    uploads: HashMap<u64, BlobUpload>,
+}
+
+struct BlobUpload {
+    target: BlobTarget,
+    size: u64,
+    received: u64,
+    // NB: the spool is dropped (and closed) before its path, which removes the file
+    spool: File,
+    path: SpoolPath,
+}
+
+struct SpoolPath(PathBuf);
+
+impl Drop for SpoolPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+impl BlobUploads {
+    /// the maximum number of uploads that a connection can have open at once
+    pub const MAX_OPEN: usize = 4;
+    pub fn new() -> Self {
+        Self {
+            uploads: HashMap::new(),
+        }
+    }
+    /// Begin an upload of `size` bytes (at most `max_size`) into the target, returning the id of the upload. The
+    /// target should have been checked already
+    pub async fn begin(
+        &mut self,
+        target: BlobTarget,
+        size: u64,
+        max_size: u64,
+    ) -> QueryResult<u64> {
+        if size > max_size {
+            return Err(QueryError::QExecDmlValidationError.with_detail("limit", max_size));
+        }
+        if self.uploads.len() >= Self::MAX_OPEN {
+            return Err(QueryError::QExecDmlValidationError.with_detail("limit", Self::MAX_OPEN));
+        }
+        let id = NEXT_UPLOAD_ID.fetch_add(1, Ordering::Relaxed);
+        let path =
+            SpoolPath(std::env::temp_dir().join(format!("skyd-blob-{}-{id}", std::process::id())));
+        let spool = File::create(&path.0)
+            .await
+            .map_err(|_| QueryError::SysServerError)?;
+        self.uploads.insert(
+            id,
+            BlobUpload {
+                target,
+                size,
+                received: 0,
+                spool,
+                path,
+            },
+        );
+        Ok(id)
+    }
+    /// Add a chunk to an upload, returning the number of bytes received so far. The upload is dropped if the chunk
+    /// can't be spooled
+    pub async fn append(&mut self, id: u64, data: &[u8]) -> QueryResult<u64> {
+        let upload = self.get_mut(id)?;
+        let received = upload.received.saturating_add(data.len() as u64);
+        if received > upload.size {
+            return Err(QueryError::QExecDmlValidationError.with_detail("limit", upload.size));
+        }
+        if upload.spool.write_all(data).await.is_err() {
+            self.uploads.remove(&id);
+            return Err(QueryError::SysServerError);
+        }
+        upload.received = received;
+        Ok(received)
+    }
+    /// Close an upload that has received all its bytes, returning its target and its bytes
+    pub async fn commit(&mut self, id: u64) -> QueryResult<(BlobTarget, Vec<u8>)> {
+        let upload = self.get_mut(id)?;
+        if upload.received != upload.size {
+            return Err(
+                QueryError::QExecDmlValidationError.with_detail("received", upload.received)
+            );
+        }
+        let mut upload = self.uploads.remove(&id).unwrap();
+        upload
+            .spool
+            .flush()
+            .await
+            .map_err(|_| QueryError::SysServerError)?;
+        let data = fs::read(&upload.path.0)
+            .await
+            .map_err(|_| QueryError::SysServerError)?;
+        Ok((upload.target, data))
+    }
+    /// Drop an upload
+    pub fn abort(&mut self, id: u64) -> QueryResult<()> {
+        match self.uploads.remove(&id) {
+            Some(_) => Ok(()),
+            None => Err(QueryError::QExecObjectNotFound.with_detail("upload", id)),
+        }
+    }
+    fn get_mut(&mut self, id: u64) -> QueryResult<&mut BlobUpload> {
+        self.uploads
+            .get_mut(&id)
+            .ok_or_else(|| QueryError::QExecObjectNotFound.with_detail("upload", id))
+    }
+}
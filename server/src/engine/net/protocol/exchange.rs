@@ -24,7 +24,14 @@
  *
 */
 
-use {super::AccumlatorStatus, crate::engine::mem::BufferedScanner};
+use {
+    super::AccumlatorStatus,
+    crate::engine::{
+        data::cell::Datacell,
+        error::{QueryError, QueryResult},
+        mem::BufferedScanner,
+    },
+};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Resume(usize);
@@ -93,6 +100,446 @@ impl<'a> SQuery<'a> {
     }
 }
 
+/*
+    batch
+    ---
+    a batch carries several independent statements in one frame:
+
+    B<packet size>\n<statement count>\n<mode: u8>(<statement size>\n<q window>\n<query><params>)*
+
+    each statement is laid out like a simple query (without the `S`). the statements are run one after the other, and
+    each gets its own result; nothing is rolled back if one of them fails
+*/
+
+#[derive(Debug, PartialEq)]
+pub struct SBatch<'a> {
+    payload: &'a [u8],
+    count: usize,
+}
+
+impl<'a> SBatch<'a> {
+    /// run every statement, even if some of them fail
+    pub const MODE_RUN_ALL: u8 = 0;
+    /// stop at the first statement that fails
+    pub const MODE_STOP_ON_ERROR: u8 = 1;
+    pub(super) fn new(payload: &'a [u8], count: usize) -> Self {
+        Self { payload, count }
+    }
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// Split the batch into its statements, returning them along with whether we should stop at the first error.
+    /// Returns [`None`] if the batch is malformed
+    pub fn split(&self) -> Option<(bool, Vec<SQuery<'a>>)> {
+        let mut scanner = BufferedScanner::new(self.payload);
+        let stop_on_error = match scanner.try_next_byte()? {
+            Self::MODE_RUN_ALL => false,
+            Self::MODE_STOP_ON_ERROR => true,
+            _ => return None,
+        };
+        // every statement takes atleast a few bytes, so the rest of the packet bounds the allocation
+        let mut statements = Vec::with_capacity(self.count.min(scanner.remaining()));
+        for _ in 0..self.count {
+            let size = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+            let start = scanner.cursor();
+            let q_window = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+            let df_size = usize::try_from(size)
+                .ok()?
+                .checked_sub(scanner.cursor() - start)?;
+            if q_window > df_size as u64 {
+                return None;
+            }
+            let payload = scanner.try_next_variable_block(df_size)?;
+            statements.push(SQuery::new(payload, q_window as usize));
+        }
+        // the statements must account for the whole packet
+        scanner.eof().then_some((stop_on_error, statements))
+    }
+}
+
+/*
+    bulk insert
+    ---
+    a bulk insert carries rows for a single model, without any statement to parse:
+
+    I<packet size>\n<row count>\n<entity size>\n<entity><column count>\n(<cell>)*
+
+    the entity is either `space.model` or just the model (in the current space). each row has a cell for each of the
+    model's fields, in the order that they were declared in (leaving out computed fields), and the rows are laid out
+    one after the other. a cell is a tag followed by its value:
+    - 0: null
+    - 1: bool (a byte, 0 or 1)
+    - 2, 3, 4: uint, sint and float (8 bytes, little endian)
+    - 5, 6: binary and string (<size>\n<bytes>)
+
+    a session that ran `set bulk_load = true` has its bulk inserts loaded instead: the rows skip the deltas and are
+    written out as one batch for each partition of the model before they're added to it
+*/
+
+#[derive(Debug, PartialEq)]
+pub struct SBulkInsert<'a> {
+    payload: &'a [u8],
+    rows: usize,
+}
+
+/// The rows of a bulk insert, decoded
+#[derive(Debug, PartialEq)]
+pub struct BulkInsert<'a> {
+    entity: &'a str,
+    rows: Vec<Vec<Datacell>>,
+}
+
+impl<'a> BulkInsert<'a> {
+    /// Returns the entity, either as `space.model` or as just the model
+    pub fn entity(&self) -> &'a str {
+        self.entity
+    }
+    pub fn into_rows(self) -> Vec<Vec<Datacell>> {
+        self.rows
+    }
+}
+
+impl<'a> SBulkInsert<'a> {
+    pub const TAG_NULL: u8 = 0;
+    pub const TAG_BOOL: u8 = 1;
+    pub const TAG_UINT: u8 = 2;
+    pub const TAG_SINT: u8 = 3;
+    pub const TAG_FLOAT: u8 = 4;
+    pub const TAG_BIN: u8 = 5;
+    pub const TAG_STR: u8 = 6;
+    pub(super) fn new(payload: &'a [u8], rows: usize) -> Self {
+        Self { payload, rows }
+    }
+    pub fn row_count(&self) -> usize {
+        self.rows
+    }
+    /// Decode the entity and the rows. Returns [`None`] if the bulk insert is malformed
+    pub fn decode(&self) -> Option<BulkInsert<'a>> {
+        let mut scanner = BufferedScanner::new(self.payload);
+        let entity_size = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+        let entity = scanner.try_next_variable_block(usize::try_from(entity_size).ok()?)?;
+        let entity = core::str::from_utf8(entity).ok()?;
+        let columns = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+        // every cell takes atleast a byte, so the rest of the packet bounds the allocations below
+        if (columns == 0) | (columns > scanner.remaining() as u64) {
+            return None;
+        }
+        let columns = columns as usize;
+        let mut rows = Vec::with_capacity(self.rows.min(scanner.remaining() / columns));
+        for _ in 0..self.rows {
+            let mut row = Vec::with_capacity(columns.min(scanner.remaining()));
+            for _ in 0..columns {
+                row.push(Self::decode_cell(&mut scanner)?);
+            }
+            rows.push(row);
+        }
+        // the rows must account for the whole packet
+        scanner.eof().then_some(BulkInsert { entity, rows })
+    }
+    fn decode_cell(scanner: &mut BufferedScanner<'a>) -> Option<Datacell> {
+        fn word(scanner: &mut BufferedScanner) -> Option<[u8; 8]> {
+            scanner.try_next_variable_block(8)?.try_into().ok()
+        }
+        fn block<'a>(scanner: &mut BufferedScanner<'a>) -> Option<&'a [u8]> {
+            let size = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+            scanner.try_next_variable_block(usize::try_from(size).ok()?)
+        }
+        let cell = match scanner.try_next_byte()? {
+            Self::TAG_NULL => Datacell::null(),
+            Self::TAG_BOOL => match scanner.try_next_byte()? {
+                b @ (0 | 1) => Datacell::new_bool(b == 1),
+                _ => return None,
+            },
+            Self::TAG_UINT => Datacell::new_uint_default(u64::from_le_bytes(word(scanner)?)),
+            Self::TAG_SINT => Datacell::new_sint_default(i64::from_le_bytes(word(scanner)?)),
+            Self::TAG_FLOAT => Datacell::new_float_default(f64::from_le_bytes(word(scanner)?)),
+            Self::TAG_BIN => Datacell::new_bin(block(scanner)?.into()),
+            Self::TAG_STR => Datacell::new_str(core::str::from_utf8(block(scanner)?).ok()?.into()),
+            _ => return None,
+        };
+        Some(cell)
+    }
+}
+
+/*
+    blobs
+    ---
+    a value that is too large to send in a single packet is moved in chunks, with blob frames:
+
+    X<packet size>\n<verb>\n<payload>
+
+    where the payload depends on the verb:
+    - 0 (begin): <target><blob size>\n. starts an upload of `blob size` bytes and responds with the id of the upload
+    - 1 (append): <upload id>\n<bytes>. adds the bytes to the upload and responds with the bytes received so far
+    - 2 (commit): <upload id>\n. once all the bytes were received, writes the upload into its target
+    - 3 (abort): <upload id>\n. drops the upload
+    - 4 (read): <target><offset>\n<length>\n. responds with up to `length` bytes of the target, from `offset` on
+
+    a target is a binary field of a row: <entity size>\n<entity><key><field size>\n<field>, where the entity is like
+    that of a bulk insert and the key is a cell holding the row's primary key. an upload is spooled to a temporary file
+    (instead of being held in memory) until it's committed, and belongs to the connection that began it
+*/
+
+#[derive(Debug, PartialEq)]
+pub struct SBlob<'a> {
+    payload: &'a [u8],
+    verb: u64,
+}
+
+/// The field of a row that a blob is written into or read from
+#[derive(Debug, PartialEq)]
+pub struct BlobTarget {
+    entity: Box<str>,
+    key: Datacell,
+    field: Box<str>,
+}
+
+impl BlobTarget {
+    /// Returns the entity, either as `space.model` or as just the model
+    pub fn entity(&self) -> &str {
+        &self.entity
+    }
+    pub fn key(&self) -> &Datacell {
+        &self.key
+    }
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+}
+
+/// A blob frame, decoded
+#[derive(Debug, PartialEq)]
+pub enum BlobRequest<'a> {
+    Begin {
+        target: BlobTarget,
+        size: u64,
+    },
+    Append {
+        id: u64,
+        data: &'a [u8],
+    },
+    Commit {
+        id: u64,
+    },
+    Abort {
+        id: u64,
+    },
+    Read {
+        target: BlobTarget,
+        offset: u64,
+        len: u64,
+    },
+}
+
+impl<'a> SBlob<'a> {
+    pub const VERB_BEGIN: u64 = 0;
+    pub const VERB_APPEND: u64 = 1;
+    pub const VERB_COMMIT: u64 = 2;
+    pub const VERB_ABORT: u64 = 3;
+    pub const VERB_READ: u64 = 4;
+    pub(super) fn new(payload: &'a [u8], verb: u64) -> Self {
+        Self { payload, verb }
+    }
+    /// Decode the request. Returns [`None`] if the verb is unknown or the payload is malformed
+    pub fn decode(&self) -> Option<BlobRequest<'a>> {
+        fn int(scanner: &mut BufferedScanner) -> Option<u64> {
+            scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()
+        }
+        let mut scanner = BufferedScanner::new(self.payload);
+        let scanner = &mut scanner;
+        let request = match self.verb {
+            Self::VERB_BEGIN => BlobRequest::Begin {
+                target: Self::decode_target(scanner)?,
+                size: int(scanner)?,
+            },
+            Self::VERB_APPEND => BlobRequest::Append {
+                id: int(scanner)?,
+                data: scanner.try_next_variable_block(scanner.remaining())?,
+            },
+            Self::VERB_COMMIT => BlobRequest::Commit { id: int(scanner)? },
+            Self::VERB_ABORT => BlobRequest::Abort { id: int(scanner)? },
+            Self::VERB_READ => BlobRequest::Read {
+                target: Self::decode_target(scanner)?,
+                offset: int(scanner)?,
+                len: int(scanner)?,
+            },
+            _ => return None,
+        };
+        // the request must account for the whole packet
+        scanner.eof().then_some(request)
+    }
+    fn decode_target(scanner: &mut BufferedScanner<'a>) -> Option<BlobTarget> {
+        fn string<'a>(scanner: &mut BufferedScanner<'a>) -> Option<&'a str> {
+            let size = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+            core::str::from_utf8(scanner.try_next_variable_block(usize::try_from(size).ok()?)?).ok()
+        }
+        let entity = string(scanner)?.into();
+        let key = SBulkInsert::decode_cell(scanner)?;
+        let field = string(scanner)?.into();
+        Some(BlobTarget { entity, key, field })
+    }
+}
+
+/*
+    streams
+    ---
+    a stream returns the rows of a `select all` in chunks, so that a large result doesn't have to be sent (or held) at
+    once:
+
+    R<packet size>\n<chunk rows>\n<token size>\n<token><q window>\n<query><params>
+
+    the response is a sequence of chunks, each of which is `0x17 <token size>\n<token>` followed by the response for
+    at most `chunk rows` rows (encoded just like the response to the `select all` itself). the last chunk has an empty
+    token, and a chunk that fails is always the last one. every chunk is scanned on its own (skipping the rows that
+    were already sent) so nothing is held between chunks, and a client that disconnects mid stream leaves nothing
+    behind.
+
+    the token of a chunk names the rows that come after it. a client that lost its connection can send the same
+    statement again with the token of the last chunk that it received in full, and the stream continues from there
+    (the token is empty when a stream is started). like paginating with `limit` and `offset`, no rows are skipped or
+    repeated as long as no rows were inserted or deleted in between. a token is `<rows sent>.<check>`, where the
+    check is a checksum of the statement (with its parameters) so that a token can't be used to resume another
+    statement
+*/
+
+const STREAM_CHECK: crc::Crc<u32> = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
+
+#[derive(Debug, PartialEq)]
+pub struct SStream<'a> {
+    payload: &'a [u8],
+    chunk_rows: u64,
+}
+
+/// A stream frame, decoded
+#[derive(Debug, PartialEq)]
+pub struct StreamRequest<'a> {
+    query: SQuery<'a>,
+    chunk_rows: u64,
+    token: &'a [u8],
+}
+
+impl<'a> StreamRequest<'a> {
+    pub fn query(&self) -> &SQuery<'a> {
+        &self.query
+    }
+    /// Returns the most rows that are sent in a chunk
+    pub fn chunk_rows(&self) -> u64 {
+        self.chunk_rows
+    }
+    /// Returns the number of rows that were already sent, as given by the token (0 if the stream is being started).
+    /// Fails if the token is malformed or was issued for another statement
+    pub fn resume_from(&self) -> QueryResult<u64> {
+        if self.token.is_empty() {
+            return Ok(0);
+        }
+        core::str::from_utf8(self.token)
+            .ok()
+            .and_then(|token| token.split_once('.'))
+            .and_then(|(rows, check)| {
+                let rows = rows.parse::<u64>().ok()?;
+                let check = u32::from_str_radix(check, 16).ok()?;
+                (check == self.check()).then_some(rows)
+            })
+            .ok_or(QueryError::QExecStreamBadToken)
+    }
+    /// Returns the token for the rows that come after the first `rows_sent` rows
+    pub fn token(&self, rows_sent: u64) -> String {
+        format!("{rows_sent}.{:08x}", self.check())
+    }
+    fn check(&self) -> u32 {
+        STREAM_CHECK.checksum(self.query.payload())
+    }
+}
+
+impl<'a> SStream<'a> {
+    pub(super) fn new(payload: &'a [u8], chunk_rows: u64) -> Self {
+        Self {
+            payload,
+            chunk_rows,
+        }
+    }
+    /// Decode the request. Returns [`None`] if the payload is malformed (or a chunk can't have any rows)
+    pub fn decode(&self) -> Option<StreamRequest<'a>> {
+        if self.chunk_rows == 0 {
+            return None;
+        }
+        let mut scanner = BufferedScanner::new(self.payload);
+        let token_size = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+        let token = scanner.try_next_variable_block(usize::try_from(token_size).ok()?)?;
+        let q_window = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+        if q_window > scanner.remaining() as u64 {
+            return None;
+        }
+        let payload = scanner.try_next_variable_block(scanner.remaining())?;
+        Some(StreamRequest {
+            query: SQuery::new(payload, q_window as usize),
+            chunk_rows: self.chunk_rows,
+            token,
+        })
+    }
+}
+
+/*
+    tagged queries
+    ---
+    a tagged query is a simple query that carries an opaque tag (a trace id, for example), which the server hands back
+    so that the client can correlate the query with what happened elsewhere:
+
+    T<packet size>\n<tag size>\n<tag><q window>\n<query><params>
+
+    the response is `0x18 <tag size>\n<tag>` followed by the response to the query (encoded just like the response to
+    a simple query). the tag is also kept with the statement's stats (see `sysctl report last`), added to its trace
+    and shown as the connection's last tag in `sys.connections`. a tag is a (non empty) string of at most 256 bytes
+*/
+
+#[derive(Debug, PartialEq)]
+pub struct STagged<'a> {
+    payload: &'a [u8],
+    tag_size: usize,
+}
+
+/// A tagged query, decoded
+#[derive(Debug, PartialEq)]
+pub struct TaggedQuery<'a> {
+    tag: &'a str,
+    query: SQuery<'a>,
+}
+
+impl<'a> TaggedQuery<'a> {
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+    pub fn into_query(self) -> SQuery<'a> {
+        self.query
+    }
+}
+
+impl<'a> STagged<'a> {
+    pub const MAX_TAG_SIZE: usize = 256;
+    pub(super) fn new(payload: &'a [u8], tag_size: usize) -> Self {
+        Self { payload, tag_size }
+    }
+    /// Decode the tag and the query. Returns [`None`] if the tag is empty, too large or not a string, or if the
+    /// query is malformed
+    pub fn decode(&self) -> Option<TaggedQuery<'a>> {
+        if (self.tag_size == 0) | (self.tag_size > Self::MAX_TAG_SIZE) {
+            return None;
+        }
+        let mut scanner = BufferedScanner::new(self.payload);
+        let tag = scanner.try_next_variable_block(self.tag_size)?;
+        let tag = core::str::from_utf8(tag).ok()?;
+        let q_window = scanner.try_next_ascii_u64_lf_separated_or_restore_cursor()?;
+        if q_window > scanner.remaining() as u64 {
+            return None;
+        }
+        let payload = scanner.try_next_variable_block(scanner.remaining())?;
+        Some(TaggedQuery {
+            tag,
+            query: SQuery::new(payload, q_window as usize),
+        })
+    }
+}
+
 /*
     utils
 */
@@ -111,12 +558,26 @@ impl Default for QExchangeStateInternal {
     }
 }
 
+/// The kind of frame that the client is sending
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(super) enum QExchangeFrame {
+    Simple,
+    Batch,
+    BulkInsert,
+    Blob,
+    Stream,
+    Tagged,
+}
+
 #[derive(Debug, PartialEq)]
 pub(super) struct QExchangeState {
     state: QExchangeStateInternal,
     target: usize,
     md_packet_size: u64,
+    /// the q window for a simple query, the statement count for a batch, the row count for a bulk insert, the verb
+    /// of a blob frame, the chunk rows of a stream or the tag size of a tagged query
     md_q_window: u64,
+    frame: QExchangeFrame,
 }
 
 impl Default for QExchangeState {
@@ -130,6 +591,18 @@ impl Default for QExchangeState {
 pub(super) enum QExchangeResult<'a> {
     /// We completed the exchange and yielded a [`SQuery`]
     SQCompleted(SQuery<'a>),
+    /// We completed the exchange and yielded a [`SBatch`]
+    BatchCompleted(SBatch<'a>),
+    /// We completed the exchange and yielded a [`SBulkInsert`]
+    BulkInsertCompleted(SBulkInsert<'a>),
+    /// We completed the exchange and yielded a [`SBlob`]
+    BlobCompleted(SBlob<'a>),
+    /// We completed the exchange and yielded a [`SStream`]
+    StreamCompleted(SStream<'a>),
+    /// We completed the exchange and yielded a [`STagged`]
+    TaggedCompleted(STagged<'a>),
+    /// The client sent a ping
+    Ping,
     /// We're changing states
     ChangeState(QExchangeState),
     /// We hit an error and need to terminate this exchange
@@ -148,6 +621,7 @@ impl QExchangeState {
             target,
             md_packet_size,
             md_q_window,
+            frame: QExchangeFrame::Simple,
         }
     }
     #[cfg(test)]
@@ -167,10 +641,19 @@ impl QExchangeState {
         Self::_new(QExchangeStateInternal::Initial, Self::MIN_READ, 0, 0)
     }
     pub fn has_reached_target(&self, new_buffer: &[u8]) -> bool {
-        new_buffer.len() >= self.target
+        // a ping is a single byte, so it never makes the minimum read
+        (new_buffer.len() >= self.target) | self.is_ping(new_buffer)
+    }
+    fn is_ping(&self, buffer: &[u8]) -> bool {
+        (self.state == QExchangeStateInternal::Initial) & (buffer.first() == Some(&b'P'))
     }
     fn resume<'a>(self, scanner: &mut BufferedScanner<'a>) -> QExchangeResult<'a> {
-        debug_assert!(scanner.has_left(Self::MIN_READ));
+        // only the first read is guaranteed to be this large; a resume might have just one new byte
+        debug_assert!(
+            (self.state != QExchangeStateInternal::Initial)
+                | scanner.has_left(Self::MIN_READ)
+                | self.is_ping(scanner.current_buffer())
+        );
         match self.state {
             QExchangeStateInternal::Initial => self.start_initial(scanner),
             QExchangeStateInternal::PendingMeta1 => self.resume_at_md1(scanner),
@@ -178,10 +661,18 @@ impl QExchangeState {
             QExchangeStateInternal::PendingData => self.resume_data(scanner),
         }
     }
-    fn start_initial<'a>(self, scanner: &mut BufferedScanner<'a>) -> QExchangeResult<'a> {
-        if unsafe { scanner.next_byte() } != b'S' {
-            // has to be a simple query!
-            return QExchangeResult::Error;
+    fn start_initial<'a>(mut self, scanner: &mut BufferedScanner<'a>) -> QExchangeResult<'a> {
+        match unsafe { scanner.next_byte() } {
+            b'S' => {}
+            b'B' => self.frame = QExchangeFrame::Batch,
+            b'I' => self.frame = QExchangeFrame::BulkInsert,
+            b'X' => self.frame = QExchangeFrame::Blob,
+            b'R' => self.frame = QExchangeFrame::Stream,
+            b'T' => self.frame = QExchangeFrame::Tagged,
+            // a ping is just the one byte (and pipelining isn't supported)
+            b'P' if scanner.eof() => return QExchangeResult::Ping,
+            // has to be a simple query, a batch, a bulk insert, a blob frame, a stream, a tagged query or a ping!
+            _ => return QExchangeResult::Error,
         }
         self.resume_at_md1(scanner)
     }
@@ -199,7 +690,13 @@ impl QExchangeState {
             Err(()) => return QExchangeResult::Error,
         };
         self.md_packet_size = packet_size;
-        self.target = scanner.cursor() + packet_size as usize;
+        self.target = match usize::try_from(packet_size)
+            .ok()
+            .and_then(|packet_size| scanner.cursor().checked_add(packet_size))
+        {
+            Some(target) => target,
+            None => return QExchangeResult::Error,
+        };
         // hand over control to md2
         self.resume_at_md2(scanner)
     }
@@ -218,13 +715,43 @@ impl QExchangeState {
         self.resume_data(scanner)
     }
     fn resume_data<'a>(mut self, scanner: &mut BufferedScanner<'a>) -> QExchangeResult<'a> {
-        let df_size = self.target - scanner.cursor();
+        // the packet size must cover the q window metadata, and the q window must fit in the dataframe (the verb of a
+        // blob frame and the chunk rows of a stream aren't sizes)
+        let df_size = match self.target.checked_sub(scanner.cursor()) {
+            Some(df_size)
+                if matches!(self.frame, QExchangeFrame::Blob | QExchangeFrame::Stream)
+                    | (self.md_q_window <= df_size as u64) =>
+            {
+                df_size
+            }
+            _ => return QExchangeResult::Error,
+        };
+        if scanner.remaining() > df_size {
+            // more than what the client told us it would send (and pipelining isn't supported)
+            return QExchangeResult::Error;
+        }
         if scanner.remaining() == df_size {
-            unsafe {
-                QExchangeResult::SQCompleted(SQuery::new(
-                    scanner.next_chunk_variable(df_size),
-                    self.md_q_window as usize,
-                ))
+            let payload = unsafe { scanner.next_chunk_variable(df_size) };
+            let md_q_window = self.md_q_window as usize;
+            match self.frame {
+                QExchangeFrame::Simple => {
+                    QExchangeResult::SQCompleted(SQuery::new(payload, md_q_window))
+                }
+                QExchangeFrame::Batch => {
+                    QExchangeResult::BatchCompleted(SBatch::new(payload, md_q_window))
+                }
+                QExchangeFrame::BulkInsert => {
+                    QExchangeResult::BulkInsertCompleted(SBulkInsert::new(payload, md_q_window))
+                }
+                QExchangeFrame::Blob => {
+                    QExchangeResult::BlobCompleted(SBlob::new(payload, self.md_q_window))
+                }
+                QExchangeFrame::Stream => {
+                    QExchangeResult::StreamCompleted(SStream::new(payload, self.md_q_window))
+                }
+                QExchangeFrame::Tagged => {
+                    QExchangeResult::TaggedCompleted(STagged::new(payload, md_q_window))
+                }
             }
         } else {
             self.state = QExchangeStateInternal::PendingData;
@@ -0,0 +1,160 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    entry points for the fuzz targets (in `fuzz/`), and the drivers that the fuzz tests share with them. the drivers
+    resume the decoders just like the connection loop does, reading the input in pieces of the sizes that they're
+    told to
+*/
+
+use {
+    super::{
+        exchange::{self, QExchangeResult, QExchangeState, Resume},
+        handshake::{CHandshake, HandshakeResult, HandshakeState, ProtocolError},
+    },
+    crate::engine::{mem::BufferedScanner, ql::lex::SecureLexer},
+};
+
+/// Feed the input to the handshake decoder. The first byte of `data` sets the read sizes and the rest is the input
+pub fn handshake(data: &[u8]) {
+    if let Some((&seed, src)) = data.split_first() {
+        drive_handshake(src, read_sizes(seed));
+    }
+}
+
+/// Feed the input to the exchange decoder (and the decoded queries to the lexer). The first byte of `data` sets the
+/// read sizes and the rest is the input
+pub fn exchange(data: &[u8]) {
+    if let Some((&seed, src)) = data.split_first() {
+        drive_exchange(src, read_sizes(seed));
+    }
+}
+
+/// Read everything at once for a seed of zero, and at most `seed` bytes at a time otherwise
+fn read_sizes(seed: u8) -> impl FnMut(usize) -> usize {
+    move |remaining| match seed {
+        0 => remaining,
+        seed => remaining.min(seed as usize),
+    }
+}
+
+/// Drive the handshake decoder like the connection loop does. `next_read` is given the number of bytes that are left
+/// and returns how many of them are read next (at least one). Returns `None` if we ran out of input
+pub(super) fn drive_handshake(
+    src: &[u8],
+    mut next_read: impl FnMut(usize) -> usize,
+) -> Option<Result<(), ProtocolError>> {
+    let mut expected = CHandshake::INITIAL_READ;
+    let mut state = HandshakeState::default();
+    let (mut cursor, mut read) = (0, 0);
+    loop {
+        if read == src.len() {
+            return None;
+        }
+        read += next_read(src.len() - read);
+        let buf = &src[..read];
+        if buf.len() < expected {
+            continue;
+        }
+        let mut scanner = unsafe {
+            // UNSAFE(@ohsayan): the cursor is where the decoder stopped in a previous (shorter) read of the buffer
+            BufferedScanner::new_with_cursor(buf, cursor)
+        };
+        match CHandshake::resume_with(&mut scanner, state) {
+            HandshakeResult::Completed(hs) => {
+                assert!(hs.hs_auth().username().len() + hs.hs_auth().password().len() <= read);
+                return Some(Ok(()));
+            }
+            HandshakeResult::ChangeState { new_state, expect } => {
+                expected = expect;
+                state = new_state;
+                cursor = scanner.cursor();
+            }
+            HandshakeResult::Error(e) => return Some(Err(e)),
+        }
+    }
+}
+
+/// Drive the exchange decoder like the query loop does. `next_read` works like it does for [`drive_handshake`].
+/// Returns `None` if we ran out of input
+pub(super) fn drive_exchange(
+    src: &[u8],
+    mut next_read: impl FnMut(usize) -> usize,
+) -> Option<bool> {
+    let mut state = QExchangeState::default();
+    let mut cursor = Resume::default();
+    let mut read = 0;
+    loop {
+        if read == src.len() {
+            return None;
+        }
+        read += next_read(src.len() - read);
+        let buf = &src[..read];
+        if !state.has_reached_target(buf) {
+            continue;
+        }
+        let result = unsafe {
+            // UNSAFE(@ohsayan): the cursor and state are from a previous (shorter) read of the buffer
+            exchange::resume(buf, cursor, state)
+        };
+        match result {
+            (_, QExchangeResult::SQCompleted(sq)) => {
+                let _ = SecureLexer::new_with_segments(sq.query(), sq.params()).lex();
+                return Some(true);
+            }
+            (_, QExchangeResult::BatchCompleted(batch)) => {
+                let Some((_, statements)) = batch.split() else {
+                    return Some(false);
+                };
+                for sq in statements {
+                    let _ = SecureLexer::new_with_segments(sq.query(), sq.params()).lex();
+                }
+                return Some(true);
+            }
+            (_, QExchangeResult::BulkInsertCompleted(bulk)) => {
+                return Some(bulk.decode().is_some())
+            }
+            (_, QExchangeResult::BlobCompleted(blob)) => return Some(blob.decode().is_some()),
+            (_, QExchangeResult::StreamCompleted(stream)) => {
+                return Some(stream.decode().is_some())
+            }
+            (_, QExchangeResult::TaggedCompleted(tagged)) => {
+                let Some(tagged) = tagged.decode() else {
+                    return Some(false);
+                };
+                let sq = tagged.into_query();
+                let _ = SecureLexer::new_with_segments(sq.query(), sq.params()).lex();
+                return Some(true);
+            }
+            (_, QExchangeResult::Ping) => return Some(true),
+            (new_cursor, QExchangeResult::ChangeState(new_state)) => {
+                cursor = new_cursor;
+                state = new_state;
+            }
+            (_, QExchangeResult::Error) => return Some(false),
+        }
+    }
+}
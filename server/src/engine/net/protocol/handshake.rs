@@ -68,6 +68,91 @@ pub enum HandshakeVersion {
 pub enum ProtocolVersion {
     /// Skyhash/2.0 protocol
     Original = 0,
+    /// Skyhash/2.0 protocol, with a set of [`Capabilities`] sent right after the static block
+    Capabilities = 1,
+}
+
+impl ProtocolVersion {
+    unsafe fn from_raw(v: u8) -> Self {
+        core::mem::transmute(v)
+    }
+}
+
+/// The protocol extensions that a client asked for in the handshake, sent as a bitset (an LF-terminated integer)
+/// with [`ProtocolVersion::Capabilities`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Capabilities(u64);
+
+impl Capabilities {
+    /// no extensions (what [`ProtocolVersion::Original`] gets)
+    pub const NONE: Self = Self(0);
+    /// error responses carry a category and details
+    pub const ERROR_DETAIL: Self = Self(1 << 0);
+    /// successful responses can carry notices
+    pub const NOTICES: Self = Self(1 << 1);
+    /// the client can send batches of statements
+    pub const BATCHES: Self = Self(1 << 2);
+    /// the client can ping the server
+    pub const PING: Self = Self(1 << 3);
+    /// the client can send bulk inserts
+    pub const BULK_INSERT: Self = Self(1 << 4);
+    /// the client can move blobs in chunks
+    pub const BLOBS: Self = Self(1 << 5);
+    /// the client can stream the rows of a `select all` in chunks
+    pub const STREAMS: Self = Self(1 << 6);
+    /// the client can tag its queries
+    pub const TAGS: Self = Self(1 << 7);
+    /// every capability this server knows about
+    const ALL: u64 = (1 << 8) - 1;
+    /// Returns the capabilities in `v`, or [`None`] if it has a bit that isn't a known capability
+    fn from_raw(v: u64) -> Option<Self> {
+        (v & !Self::ALL == 0).then_some(Self(v))
+    }
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+    /// Returns true if error responses should carry a category and details
+    pub fn sends_error_detail(&self) -> bool {
+        self.contains(Self::ERROR_DETAIL)
+    }
+    /// Returns true if successful responses should carry notices
+    pub fn sends_notices(&self) -> bool {
+        self.contains(Self::NOTICES)
+    }
+    /// Returns true if the client can send batches of statements
+    pub fn accepts_batches(&self) -> bool {
+        self.contains(Self::BATCHES)
+    }
+    /// Returns true if the client can ping the server
+    pub fn accepts_ping(&self) -> bool {
+        self.contains(Self::PING)
+    }
+    /// Returns true if the client can send bulk inserts
+    pub fn accepts_bulk_inserts(&self) -> bool {
+        self.contains(Self::BULK_INSERT)
+    }
+    /// Returns true if the client can send blob frames
+    pub fn accepts_blobs(&self) -> bool {
+        self.contains(Self::BLOBS)
+    }
+    /// Returns true if the client can send streams
+    pub fn accepts_streams(&self) -> bool {
+        self.contains(Self::STREAMS)
+    }
+    /// Returns true if the client can send tagged queries
+    pub fn accepts_tags(&self) -> bool {
+        self.contains(Self::TAGS)
+    }
+}
+
+impl core::ops::BitOr for Capabilities {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, sky_macros::EnumMethods)]
@@ -116,6 +201,8 @@ pub enum HandshakeState {
     Initial,
     /// we just processed the static block
     StaticBlock(CHandshakeStatic),
+    /// we just processed the static block, and the client's capabilities come next
+    ExpectingCapabilities(CHandshakeStatic),
     /// Expecting some more auth meta
     ExpectingMetaForVariableBlock {
         /// static block
@@ -147,6 +234,8 @@ pub struct CHandshakeStatic {
     hs_version: HandshakeVersion,
     /// protocol version
     protocol: ProtocolVersion,
+    /// the capabilities that the client asked for
+    capabilities: Capabilities,
     /// exchange mode
     exchange_mode: DataExchangeMode,
     /// query mode
@@ -166,6 +255,7 @@ impl CHandshakeStatic {
         Self {
             hs_version,
             protocol,
+            capabilities: Capabilities::NONE,
             exchange_mode,
             query_mode,
             auth_mode,
@@ -174,9 +264,18 @@ impl CHandshakeStatic {
     pub fn hs_version(&self) -> HandshakeVersion {
         self.hs_version
     }
+    pub const fn with_capabilities(self, capabilities: Capabilities) -> Self {
+        Self {
+            capabilities,
+            ..self
+        }
+    }
     pub fn protocol(&self) -> ProtocolVersion {
         self.protocol
     }
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
     pub fn exchange_mode(&self) -> DataExchangeMode {
         self.exchange_mode
     }
@@ -250,6 +349,10 @@ impl<'a> CHandshake<'a> {
             HandshakeState::StaticBlock(static_block) => {
                 Self::resume_at_auth_metadata1(scanner, static_block)
             }
+            // buffered static block, but not the capabilities
+            HandshakeState::ExpectingCapabilities(static_block) => {
+                Self::resume_at_capabilities(scanner, static_block)
+            }
             // buffered some auth meta
             HandshakeState::ExpectingMetaForVariableBlock { static_hs, uname_l } => {
                 Self::resume_at_auth_metadata2(scanner, static_hs, uname_l)
@@ -308,20 +411,26 @@ impl<'a> CHandshake<'a> {
                 ProtocolError::RejectQueryMode,
                 ProtocolError::RejectAuth,
             ];
+            // report the first invalid field (several of them might be invalid)
+            let invalid = [
+                invalid_first_byte,
+                invalid_hs_version,
+                invalid_proto_version,
+                invalid_exchange_mode,
+                invalid_query_mode,
+                invalid_auth_mode,
+            ];
             return HandshakeResult::Error(
-                ERROR[((invalid_first_byte as u8 * 1)
-                    | (invalid_hs_version as u8 * 2)
-                    | (invalid_proto_version as u8 * 3)
-                    | (invalid_exchange_mode as u8 * 4)
-                    | (invalid_query_mode as u8 * 5)
-                    | (invalid_auth_mode as u8) * 6) as usize
-                    - 1usize],
+                ERROR[invalid.iter().position(|invalid| *invalid).unwrap_or(0)],
             );
         }
         // init header
         let static_header = CHandshakeStatic::new(
             HandshakeVersion::Original,
-            ProtocolVersion::Original,
+            unsafe {
+                // UNSAFE(@ohsayan): already checked
+                ProtocolVersion::from_raw(buf[2])
+            },
             DataExchangeMode::QueryTime,
             QueryMode::Bql1,
             unsafe {
@@ -329,8 +438,37 @@ impl<'a> CHandshake<'a> {
                 AuthMode::from_raw(buf[5])
             },
         );
-        // check if we have auth data
-        Self::resume_at_auth_metadata1(scanner, static_header)
+        match static_header.protocol {
+            ProtocolVersion::Original => Self::resume_at_auth_metadata1(scanner, static_header),
+            ProtocolVersion::Capabilities => Self::resume_at_capabilities(scanner, static_header),
+        }
+    }
+    /// Resume parsing at the capabilities (which come before the auth metadata)
+    fn resume_at_capabilities(
+        scanner: &mut BufferedScanner<'a>,
+        static_header: CHandshakeStatic,
+    ) -> HandshakeResult<'a> {
+        let capabilities =
+            match scanner.try_next_ascii_u64_lf_separated_with_result_or_restore_cursor() {
+                ScannerDecodeResult::NeedMore => {
+                    return HandshakeResult::ChangeState {
+                        new_state: HandshakeState::ExpectingCapabilities(static_header),
+                        expect: 4, // the bitset (at most three digits) and its LF
+                    };
+                }
+                ScannerDecodeResult::Value(v) => v,
+                ScannerDecodeResult::Error => {
+                    return HandshakeResult::Error(ProtocolError::CorruptedHSPacket)
+                }
+            };
+        match Capabilities::from_raw(capabilities) {
+            Some(capabilities) => Self::resume_at_auth_metadata1(
+                scanner,
+                static_header.with_capabilities(capabilities),
+            ),
+            // we don't know what the client wants
+            None => HandshakeResult::Error(ProtocolError::RejectProtocol),
+        }
     }
     fn resume_at_variable_block_payload(
         scanner: &mut BufferedScanner<'a>,
@@ -338,7 +476,10 @@ impl<'a> CHandshake<'a> {
         uname_l: usize,
         pwd_l: usize,
     ) -> HandshakeResult<'a> {
-        if scanner.has_left(uname_l + pwd_l) {
+        let Some(payload_l) = uname_l.checked_add(pwd_l) else {
+            return HandshakeResult::Error(ProtocolError::CorruptedHSPacket);
+        };
+        if scanner.has_left(payload_l) {
             // we're done here
             return unsafe {
                 // UNSAFE(@ohsayan): we just checked buffered size
@@ -353,7 +494,7 @@ impl<'a> CHandshake<'a> {
                 uname_l,
                 pwd_l,
             },
-            expect: (uname_l + pwd_l),
+            expect: payload_l,
         }
     }
 }
@@ -405,7 +546,7 @@ impl<'a> CHandshake<'a> {
                 // newline missing (or maybe there's more?)
                 return HandshakeResult::ChangeState {
                     new_state: HandshakeState::ExpectingMetaForVariableBlock { static_hs, uname_l },
-                    expect: uname_l + 2, // space for username + password len
+                    expect: uname_l.saturating_add(2), // space for username + password len
                 };
             }
             ScannerDecodeResult::Error => {
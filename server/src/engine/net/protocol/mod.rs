@@ -34,38 +34,59 @@
  * without any integer payload is equivalent to a zero value. we allow this because it's easier to specify formally
  * as states
  * - Handshake parameter versions: We currently only evaluate values for the version "original" (shipped with
- * Skytable 0.8.0)
+ * Skytable 0.8.0), except for the protocol version, where `1` means that a bitset of the protocol extensions that the
+ * client wants (error details, notices, batches and so on) follows the static block
  * - FIXME(@ohsayan) Optimistic retry without timeout: Our current algorithm does not apply a timeout to receive data
  * and optimistically retries infinitely until the target block size is received
 */
 
+mod blob;
 mod exchange;
+pub mod fuzz;
 mod handshake;
+mod quota;
+pub mod testkit;
 #[cfg(test)]
 mod tests;
 
 // re-export
-pub use exchange::SQuery;
+pub use exchange::{BlobTarget, BulkInsert, SQuery, StreamRequest, TaggedQuery};
+pub use quota::ResponseLimits;
 
 use crate::engine::core::system_db::VerifyUser;
 
 use {
     self::{
-        exchange::{QExchangeResult, QExchangeState},
+        blob::BlobUploads,
+        exchange::{BlobRequest, QExchangeResult, QExchangeState},
         handshake::{
-            AuthMode, CHandshake, DataExchangeMode, HandshakeResult, HandshakeState,
-            HandshakeVersion, ProtocolError, ProtocolVersion, QueryMode,
+            AuthMode, CHandshake, Capabilities, DataExchangeMode, HandshakeResult, HandshakeState,
+            HandshakeVersion, ProtocolError, QueryMode,
         },
+        quota::ResponseQuota,
     },
     super::{IoResult, QueryLoopResult, Socket},
     crate::engine::{
         self,
-        error::QueryError,
-        fractal::{Global, GlobalInstanceLike},
+        core::{notice::Notice, profile::Sample, session::SessionSettings},
+        crash,
+        error::{ErrorDetail, QueryError, QueryResult},
+        fractal::{
+            trace::{self, Span, StatementTrace},
+            Global, GlobalInstanceLike,
+        },
         mem::{BufferedScanner, IntegerRepr},
+        ql::lex::Token,
     },
     bytes::{Buf, BytesMut},
-    tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter},
+    std::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::{Duration, Instant},
+    },
+    tokio::{
+        io::{AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter},
+        time,
+    },
 };
 
 #[repr(u8)]
@@ -92,25 +113,60 @@ pub enum ResponseType {
     Row = 0x11,
     Empty = 0x12,
     MultiRow = 0x13,
+    Notices = 0x14,
+    Batch = 0x15,
+    Pong = 0x16,
+    Chunk = 0x17,
+    Tag = 0x18,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct ClientLocalState {
+    /// tells this connection apart from every other connection since the server started
+    id: u64,
     username: Box<str>,
     root: bool,
     hs: handshake::CHandshakeStatic,
     cs: Option<Box<str>>,
+    /// rows affected by the statement that is running
+    rows_affected: Option<u64>,
+    /// stats for the last statement that was run
+    last: Option<StatementStats>,
+    /// the trace of the statement that is running (if it is being traced)
+    trace: Option<StatementTrace>,
+    /// the profiler's sample of the statement that is running (if it is being sampled)
+    sample: Option<Sample>,
+    /// the tag of the statement that is running (if the client tagged it)
+    tag: Option<Box<str>>,
+    /// the bytes that this connection has been sent in the current quota window
+    quota: ResponseQuota,
+    /// the settings that the session has overridden with `set`
+    session: SessionSettings,
 }
 
 impl ClientLocalState {
     pub fn new(username: Box<str>, root: bool, hs: handshake::CHandshakeStatic) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        super::connections::register(id, &username, root);
         Self {
+            id,
             username,
             root,
             hs,
             cs: None,
+            rows_affected: None,
+            last: None,
+            trace: None,
+            sample: None,
+            tag: None,
+            quota: ResponseQuota::new(),
+            session: SessionSettings::new(),
         }
     }
+    pub fn id(&self) -> u64 {
+        self.id
+    }
     pub fn is_root(&self) -> bool {
         self.root
     }
@@ -126,9 +182,137 @@ impl ClientLocalState {
     pub fn get_cs(&self) -> Option<&str> {
         self.cs.as_deref()
     }
+    pub fn session(&self) -> &SessionSettings {
+        &self.session
+    }
+    pub fn session_mut(&mut self) -> &mut SessionSettings {
+        &mut self.session
+    }
+    /// Returns the protocol extensions that the client asked for in the handshake
+    pub fn capabilities(&self) -> Capabilities {
+        self.hs.capabilities()
+    }
+    /// Note that the statement that is running was tagged with `tag` (which is also kept as the connection's last tag)
+    fn set_tag(&mut self, tag: &str) {
+        super::connections::set_tag(self.id, tag);
+        self.tag = Some(tag.into());
+    }
+    /// Note the number of rows inserted, updated or deleted by the statement that is running
+    pub fn set_rows_affected(&mut self, rows: u64) {
+        self.rows_affected = Some(rows);
+    }
+    /// Note that the statement that is running has been lexed into `tokens` (for crash reports, and if it is being
+    /// traced or sampled)
+    pub fn lexed(&mut self, tokens: &[Token]) {
+        crash::record_statement(tokens);
+        if let Some(trace) = self.trace.as_mut() {
+            trace.parsed();
+        }
+        if let Some(sample) = self.sample.as_mut() {
+            sample.lexed(tokens);
+        }
+    }
+    /// Returns the stats for the last statement that was run on this connection (not including the one that is
+    /// running)
+    pub fn last_statement(&self) -> Option<&StatementStats> {
+        self.last.as_ref()
+    }
+    fn finish_statement(
+        &mut self,
+        exec_time: Duration,
+        rows_returned: Option<u64>,
+        bytes_in: usize,
+        bytes_out: usize,
+        notices: Vec<Notice>,
+    ) {
+        self.last = Some(StatementStats {
+            exec_time,
+            rows_returned,
+            rows_affected: self.rows_affected.take(),
+            bytes_in: bytes_in as u64,
+            bytes_out: bytes_out as u64,
+            notices,
+            tag: self.tag.take(),
+        });
+    }
 }
 
-#[derive(Debug, PartialEq)]
+impl Drop for ClientLocalState {
+    fn drop(&mut self) {
+        super::connections::unregister(self.id)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// What the server knows about a statement that it ran
+pub struct StatementStats {
+    /// time spent executing the statement (this doesn't include network I/O)
+    exec_time: Duration,
+    /// rows returned, if any
+    rows_returned: Option<u64>,
+    /// rows inserted, updated or deleted, if any
+    rows_affected: Option<u64>,
+    /// size of the query packet
+    bytes_in: u64,
+    /// size of the response
+    bytes_out: u64,
+    /// notices raised by the statement
+    notices: Vec<Notice>,
+    /// the tag that the client sent with the statement, if any
+    tag: Option<Box<str>>,
+}
+
+impl StatementStats {
+    pub fn exec_time(&self) -> Duration {
+        self.exec_time
+    }
+    pub fn rows_returned(&self) -> Option<u64> {
+        self.rows_returned
+    }
+    pub fn rows_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+    pub fn notices(&self) -> &[Notice] {
+        &self.notices
+    }
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+    /// Returns the stats as a JSON object
+    pub fn describe(&self) -> String {
+        fn json(v: Option<u64>) -> String {
+            v.map_or_else(|| "null".into(), |v| v.to_string())
+        }
+        let mut notices = String::from("[");
+        for (i, notice) in self.notices.iter().enumerate() {
+            if i != 0 {
+                notices.push(',');
+            }
+            notices.push_str(&format!(
+                "{{\"code\":{},\"message\":",
+                notice.code().value_u8()
+            ));
+            engine::storage::encode_str(&mut notices, notice.message());
+            notices.push('}');
+        }
+        notices.push(']');
+        let mut tag = String::new();
+        match self.tag.as_deref() {
+            Some(t) => engine::storage::encode_str(&mut tag, t),
+            None => tag.push_str("null"),
+        }
+        format!(
+            "{{\"exec_us\":{},\"rows_returned\":{},\"rows_affected\":{},\"bytes_in\":{},\"bytes_out\":{},\"notices\":{notices},\"tag\":{tag}}}",
+            self.exec_time.as_micros(),
+            json(self.rows_returned),
+            json(self.rows_affected),
+            self.bytes_in,
+            self.bytes_out
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Response {
     Empty,
     Null,
@@ -138,15 +322,26 @@ pub enum Response {
         data: Vec<u8>,
     },
     Bool(bool),
+    UInt64(u64),
 }
 
 pub(super) async fn query_loop<S: Socket>(
     con: &mut BufWriter<S>,
     buf: &mut BytesMut,
     global: &Global,
+    idle_timeout: Option<Duration>,
+    span: &mut Option<Span>,
 ) -> IoResult<QueryLoopResult> {
     // handshake
-    let mut client_state = match do_handshake(con, buf, global).await? {
+    let hs_start = trace::now();
+    let hs = do_handshake(con, buf, global).await?;
+    if let Some(span) = span.as_mut() {
+        span.record_child("skyd.handshake", hs_start, trace::now());
+        if let PostHandshake::Okay(ref client_state) = hs {
+            span.set_str("skyd.user", client_state.username());
+        }
+    }
+    let mut client_state = match hs {
         PostHandshake::Okay(hs) => hs,
         PostHandshake::ConnectionClosedFin => return Ok(QueryLoopResult::Fin),
         PostHandshake::ConnectionClosedRst => return Ok(QueryLoopResult::Rst),
@@ -162,8 +357,19 @@ pub(super) async fn query_loop<S: Socket>(
     con.flush().await?;
     let mut state = QExchangeState::default();
     let mut cursor = Default::default();
+    let mut blobs = BlobUploads::new();
     loop {
-        if con.read_buf(buf).await? == 0 {
+        let read = match idle_timeout {
+            // a connection is only idle if it's between statements
+            Some(idle_timeout) if buf.is_empty() => {
+                match time::timeout(idle_timeout, con.read_buf(buf)).await {
+                    Ok(read) => read?,
+                    Err(_) => return Ok(QueryLoopResult::IdleTimeout),
+                }
+            }
+            _ => con.read_buf(buf).await?,
+        };
+        if read == 0 {
             if buf.is_empty() {
                 return Ok(QueryLoopResult::Fin);
             } else {
@@ -174,59 +380,581 @@ pub(super) async fn query_loop<S: Socket>(
             // we haven't buffered sufficient bytes; keep working
             continue;
         }
-        let sq = match unsafe {
+        match unsafe {
             // UNSAFE(@ohsayan): as the resume cursor is private, we can't access this anyways
             exchange::resume(buf, cursor, state)
         } {
-            (_, QExchangeResult::SQCompleted(sq)) => sq,
+            (_, QExchangeResult::SQCompleted(sq)) => {
+                // now execute query
+                let bytes_in = buf.len();
+                run_statement(con, global, &mut client_state, sq, bytes_in, span.as_ref()).await?;
+            }
+            (_, QExchangeResult::BatchCompleted(batch))
+                if client_state.capabilities().accepts_batches() =>
+            {
+                match batch.split() {
+                    Some((stop_on_error, statements)) => {
+                        run_batch(
+                            con,
+                            global,
+                            &mut client_state,
+                            stop_on_error,
+                            statements,
+                            span.as_ref(),
+                        )
+                        .await?
+                    }
+                    None => write_illegal_packet(con, &client_state).await?,
+                }
+            }
+            (_, QExchangeResult::BulkInsertCompleted(bulk))
+                if client_state.capabilities().accepts_bulk_inserts() =>
+            {
+                match bulk.decode() {
+                    Some(bulk) => {
+                        let bytes_in = buf.len();
+                        run_bulk_insert(
+                            con,
+                            global,
+                            &mut client_state,
+                            bulk,
+                            bytes_in,
+                            span.as_ref(),
+                        )
+                        .await?;
+                    }
+                    None => write_illegal_packet(con, &client_state).await?,
+                }
+            }
+            (_, QExchangeResult::BlobCompleted(blob))
+                if client_state.capabilities().accepts_blobs() =>
+            {
+                match blob.decode() {
+                    Some(request) => {
+                        let bytes_in = buf.len();
+                        run_blob(
+                            con,
+                            global,
+                            &mut client_state,
+                            &mut blobs,
+                            request,
+                            bytes_in,
+                            span.as_ref(),
+                        )
+                        .await?;
+                    }
+                    None => write_illegal_packet(con, &client_state).await?,
+                }
+            }
+            (_, QExchangeResult::StreamCompleted(stream))
+                if client_state.capabilities().accepts_streams() =>
+            {
+                match stream.decode() {
+                    Some(stream) => {
+                        let bytes_in = buf.len();
+                        run_stream(
+                            con,
+                            global,
+                            &mut client_state,
+                            stream,
+                            bytes_in,
+                            span.as_ref(),
+                        )
+                        .await?;
+                    }
+                    None => write_illegal_packet(con, &client_state).await?,
+                }
+            }
+            (_, QExchangeResult::TaggedCompleted(tagged))
+                if client_state.capabilities().accepts_tags() =>
+            {
+                match tagged.decode() {
+                    Some(tagged) => {
+                        let bytes_in = buf.len();
+                        run_tagged(
+                            con,
+                            global,
+                            &mut client_state,
+                            tagged,
+                            bytes_in,
+                            span.as_ref(),
+                        )
+                        .await?;
+                    }
+                    None => write_illegal_packet(con, &client_state).await?,
+                }
+            }
+            (_, QExchangeResult::Ping) if client_state.capabilities().accepts_ping() => {
+                con.write_u8(ResponseType::Pong.value_u8()).await?;
+            }
             (new_cursor, QExchangeResult::ChangeState(new_state)) => {
                 cursor = new_cursor;
                 state = new_state;
                 continue;
             }
-            (_, QExchangeResult::Error) => {
+            (
+                _,
+                QExchangeResult::BatchCompleted(_)
+                | QExchangeResult::BulkInsertCompleted(_)
+                | QExchangeResult::BlobCompleted(_)
+                | QExchangeResult::StreamCompleted(_)
+                | QExchangeResult::TaggedCompleted(_)
+                | QExchangeResult::Ping
+                | QExchangeResult::Error,
+            ) => {
                 // respond with error
-                let [a, b] = (QueryError::SysNetworkSystemIllegalClientPacket.value_u8() as u16)
-                    .to_le_bytes();
-                con.write_all(&[ResponseType::Error.value_u8(), a, b])
-                    .await?;
-                con.flush().await?;
-                // reset buffer, cursor and state
-                buf.clear();
-                cursor = Default::default();
-                state = QExchangeState::default();
-                continue;
+                write_illegal_packet(con, &client_state).await?;
             }
-        };
-        // now execute query
-        match engine::core::exec::dispatch_to_executor(global, &mut client_state, sq).await {
-            Ok(Response::Empty) => {
-                con.write_all(&[ResponseType::Empty.value_u8()]).await?;
+        }
+        con.flush().await?;
+        // reset buffer, cursor and state
+        buf.clear();
+        cursor = Default::default();
+        state = QExchangeState::default();
+    }
+}
+
+/// Run a statement and write its response, returning true if it succeeded
+async fn run_statement<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    global: &Global,
+    client_state: &mut ClientLocalState,
+    sq: SQuery<'_>,
+    bytes_in: usize,
+    connection: Option<&Span>,
+) -> IoResult<bool> {
+    client_state.trace = StatementTrace::start(connection);
+    client_state.sample = Sample::start();
+    if let (Some(trace), Some(tag)) = (client_state.trace.as_mut(), client_state.tag.as_deref()) {
+        trace.tagged(tag);
+    }
+    let exec_start = Instant::now();
+    let foreground = global.scheduler().foreground();
+    let r = engine::core::exec::dispatch_to_executor(global, client_state, sq).await;
+    drop(foreground);
+    let r = Outcome::take(global, client_state, r);
+    respond(con, global, client_state, r, exec_start.elapsed(), bytes_in).await
+}
+
+/// Run a tagged query (see [`TaggedQuery`]) and write its response, returning true if it succeeded. The tag is
+/// written first (`0x18 <tag size>\n<tag>`), followed by the response to the query just like for a simple query
+async fn run_tagged<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    global: &Global,
+    client_state: &mut ClientLocalState,
+    tagged: TaggedQuery<'_>,
+    bytes_in: usize,
+    connection: Option<&Span>,
+) -> IoResult<bool> {
+    let tag = tagged.tag();
+    let mut irep = IntegerRepr::new();
+    con.write_u8(ResponseType::Tag.value_u8()).await?;
+    con.write_all(irep.as_bytes(tag.len() as u64)).await?;
+    con.write_u8(b'\n').await?;
+    con.write_all(tag.as_bytes()).await?;
+    client_state.set_tag(tag);
+    run_statement(
+        con,
+        global,
+        client_state,
+        tagged.into_query(),
+        bytes_in,
+        connection,
+    )
+    .await
+}
+
+/// Insert the rows of a bulk insert and write the response (the number of rows inserted), returning true if it
+/// succeeded. There's nothing to lex, so bulk inserts are never sampled by the profiler
+async fn run_bulk_insert<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    global: &Global,
+    client_state: &mut ClientLocalState,
+    bulk: BulkInsert<'_>,
+    bytes_in: usize,
+    connection: Option<&Span>,
+) -> IoResult<bool> {
+    client_state.trace = StatementTrace::start(connection);
+    client_state.sample = None;
+    let exec_start = Instant::now();
+    let foreground = global.scheduler().foreground();
+    let r = engine::core::exec::dispatch_bulk_insert(global, client_state, bulk);
+    drop(foreground);
+    let r = Outcome::take(global, client_state, r);
+    respond(con, global, client_state, r, exec_start.elapsed(), bytes_in).await
+}
+
+/// Run a blob frame (see [`BlobRequest`]) and write its response, returning true if it succeeded. Like bulk inserts,
+/// blob frames are never sampled by the profiler
+async fn run_blob<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    global: &Global,
+    client_state: &mut ClientLocalState,
+    uploads: &mut BlobUploads,
+    request: BlobRequest<'_>,
+    bytes_in: usize,
+    connection: Option<&Span>,
+) -> IoResult<bool> {
+    client_state.trace = StatementTrace::start(connection);
+    client_state.sample = None;
+    ErrorDetail::clear();
+    Notice::clear();
+    let exec_start = Instant::now();
+    let foreground = global.scheduler().foreground();
+    let r = match request {
+        BlobRequest::Begin { target, size } => {
+            match engine::core::exec::dispatch_blob_begin(global, client_state, &target) {
+                Ok(()) => uploads
+                    .begin(target, size, global.get_blob_max_size())
+                    .await
+                    .map(Response::UInt64),
+                Err(e) => Err(e),
             }
-            Ok(Response::Serialized { ty, size, data }) => {
-                con.write_u8(ty.value_u8()).await?;
-                let mut irep = IntegerRepr::new();
-                con.write_all(irep.as_bytes(size as u64)).await?;
-                con.write_u8(b'\n').await?;
-                con.write_all(&data).await?;
+        }
+        BlobRequest::Append { id, data } => uploads.append(id, data).await.map(Response::UInt64),
+        BlobRequest::Commit { id } => match uploads.commit(id).await {
+            Ok((target, data)) => {
+                // reading the spool may have moved us to another thread
+                ErrorDetail::clear();
+                Notice::clear();
+                engine::core::exec::dispatch_blob_write(global, client_state, &target, &data)
             }
-            Ok(Response::Bool(b)) => {
-                con.write_all(&[ResponseType::Bool.value_u8(), b as u8])
-                    .await?
+            Err(e) => Err(e),
+        },
+        BlobRequest::Abort { id } => uploads.abort(id).map(|_| Response::Empty),
+        BlobRequest::Read {
+            target,
+            offset,
+            len,
+        } => {
+            engine::core::exec::dispatch_blob_read(global, client_state, &target, offset, len).await
+        }
+    };
+    drop(foreground);
+    let r = Outcome::take(global, client_state, r);
+    respond(con, global, client_state, r, exec_start.elapsed(), bytes_in).await
+}
+
+/// The result of a statement that just ran, along with the detail and notices that it left behind
+struct Outcome {
+    r: QueryResult<Response>,
+    detail: Option<ErrorDetail>,
+    notices: Vec<Notice>,
+}
+
+impl Outcome {
+    /// Take the detail and notices of the statement that just returned `r`. They're kept on the thread that ran the
+    /// statement, so this has to happen before we await again (we could resume on another thread). Both are always
+    /// taken (even if the client doesn't want them) so that nothing is left behind for a statement of another
+    /// connection to pick up. Notices are only kept for statements that succeeded (which is also when the user hears
+    /// about any of their jobs that ended)
+    fn take(global: &Global, client_state: &ClientLocalState, r: QueryResult<Response>) -> Self {
+        let detail = match &r {
+            Ok(_) => {
+                ErrorDetail::clear();
+                None
+            }
+            Err(e) => Some(ErrorDetail::take(*e)),
+        };
+        if r.is_ok() {
+            global.state().jobs().raise_notices(client_state.username());
+        }
+        let notices = Notice::take_all();
+        let notices = if r.is_ok() { notices } else { vec![] };
+        Self { r, detail, notices }
+    }
+}
+
+/// Write the response to a statement that just ran (along with its detail and notices), and record it
+async fn respond<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    global: &Global,
+    client_state: &mut ClientLocalState,
+    outcome: Outcome,
+    exec_time: Duration,
+    bytes_in: usize,
+) -> IoResult<bool> {
+    let Outcome {
+        r,
+        mut detail,
+        mut notices,
+    } = outcome;
+    // the tiered blobs are fetched only now, since the statement has released its locks
+    let r = match r {
+        Ok(r) => engine::core::exec::resolve_tiered(r).await.map_err(|d| {
+            let e = d.error();
+            detail = Some(d);
+            e
+        }),
+        Err(e) => Err(e),
+    };
+    // responses that are too large (or that would put the connection over its quota) are replaced with an error. we
+    // may be on another thread by now, so the check's detail (and warning) are taken right away
+    let limits = global.get_response_limits();
+    let r = match r {
+        Ok(Response::Serialized { ty, size, data }) => {
+            ErrorDetail::clear();
+            Notice::clear();
+            let admitted = client_state
+                .quota
+                .admit(limits, data.len() as u64, Instant::now());
+            notices.extend(Notice::take_all());
+            match admitted {
+                Ok(()) => Ok(Response::Serialized { ty, size, data }),
+                Err(e) => {
+                    detail = Some(ErrorDetail::take(e));
+                    Err(e)
+                }
             }
-            Ok(Response::Null) => con.write_u8(ResponseType::Null.value_u8()).await?,
-            Err(e) => {
+        }
+        r => r,
+    };
+    // the statement succeeded, but its blobs couldn't be fetched or its response was rejected
+    if r.is_err() {
+        notices.clear();
+    }
+    if let Some(trace) = client_state.trace.as_mut() {
+        trace.executed(r.as_ref().err().map(|e| format!("{e:?}")));
+    }
+    if let Some(sample) = client_state.sample.as_mut() {
+        sample.executed();
+    }
+    let rows_returned = match &r {
+        Ok(Response::Serialized {
+            ty: ResponseType::MultiRow,
+            size,
+            ..
+        }) => Some(*size as u64),
+        Ok(Response::Serialized {
+            ty: ResponseType::Row,
+            ..
+        }) => Some(1),
+        _ => None,
+    };
+    let sent_notices: &[Notice] = if client_state.capabilities().sends_notices() {
+        &notices
+    } else {
+        &[]
+    };
+    let detail = detail.filter(|_| client_state.capabilities().sends_error_detail());
+    let okay = r.is_ok();
+    let bytes_out = write_response(con, r, detail, sent_notices).await?;
+    client_state
+        .quota
+        .charge(limits, bytes_out as u64, Instant::now());
+    if let Some(trace) = client_state.trace.take() {
+        trace.responded(bytes_in, bytes_out, rows_returned);
+    }
+    if let Some(sample) = client_state.sample.take() {
+        sample.serialized();
+    }
+    client_state.finish_statement(exec_time, rows_returned, bytes_in, bytes_out, notices);
+    Ok(okay)
+}
+
+/// Run a stream (see [`StreamRequest`]), writing each chunk as soon as it's ready. Every chunk is scanned (and goes
+/// through the response limits) like a statement of its own, so nothing is held while a chunk is being written, and
+/// we're done as soon as the client goes away. Like bulk inserts, streams are never sampled by the profiler
+async fn run_stream<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    global: &Global,
+    client_state: &mut ClientLocalState,
+    stream: StreamRequest<'_>,
+    mut bytes_in: usize,
+    connection: Option<&Span>,
+) -> IoResult<()> {
+    let mut from = stream.resume_from();
+    loop {
+        client_state.trace = StatementTrace::start(connection);
+        client_state.sample = None;
+        ErrorDetail::clear();
+        Notice::clear();
+        let exec_start = Instant::now();
+        let foreground = global.scheduler().foreground();
+        let r = from.and_then(|from| {
+            engine::core::exec::dispatch_stream_chunk(global, client_state, &stream, from)
+        });
+        drop(foreground);
+        // a chunk that isn't full is the last one
+        let rows = match &r {
+            Ok(Response::Serialized { size, .. }) => *size as u64,
+            _ => 0,
+        };
+        let more = r.is_ok() & (rows == stream.chunk_rows());
+        // taken before the chunk header is written, since that awaits
+        let r = Outcome::take(global, client_state, r);
+        from = from.map(|from| from + rows);
+        let token = match from {
+            Ok(from) if more => stream.token(from),
+            _ => String::new(),
+        };
+        let mut irep = IntegerRepr::new();
+        con.write_u8(ResponseType::Chunk.value_u8()).await?;
+        con.write_all(irep.as_bytes(token.len() as u64)).await?;
+        con.write_u8(b'\n').await?;
+        con.write_all(token.as_bytes()).await?;
+        let okay = respond(con, global, client_state, r, exec_start.elapsed(), bytes_in).await?;
+        con.flush().await?;
+        if !(okay & more) {
+            return Ok(());
+        }
+        bytes_in = 0;
+    }
+}
+
+/// Run the statements in a batch one after the other, and write their results as a single response:
+///
+/// ```text
+/// 0x15 <result count>\n (<result>)*
+/// ```
+///
+/// Each result is encoded just like the response to a simple query. If `stop_on_error` is set, we stop at the first
+/// statement that fails (so there can be fewer results than statements)
+async fn run_batch<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    global: &Global,
+    client_state: &mut ClientLocalState,
+    stop_on_error: bool,
+    statements: Vec<SQuery<'_>>,
+    connection: Option<&Span>,
+) -> IoResult<()> {
+    let mut results = vec![];
+    let mut ran = 0u64;
+    for sq in statements {
+        let bytes_in = sq.payload().len();
+        let okay =
+            run_statement(&mut results, global, client_state, sq, bytes_in, connection).await?;
+        ran += 1;
+        if stop_on_error & !okay {
+            break;
+        }
+    }
+    let mut irep = IntegerRepr::new();
+    con.write_u8(ResponseType::Batch.value_u8()).await?;
+    con.write_all(irep.as_bytes(ran)).await?;
+    con.write_u8(b'\n').await?;
+    con.write_all(&results).await
+}
+
+/// Tell the client that we couldn't make sense of what it sent
+async fn write_illegal_packet<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    client_state: &ClientLocalState,
+) -> IoResult<()> {
+    let e = QueryError::SysNetworkSystemIllegalClientPacket;
+    let detail = client_state
+        .capabilities()
+        .sends_error_detail()
+        .then(|| ErrorDetail::new(e));
+    write_response(con, Err(e), detail, &[]).await?;
+    Ok(())
+}
+
+/// Write the response to a query, returning the number of bytes written. Errors are written with their category and
+/// details if `detail` is set, and successful responses are preceded by `notices` (if there are any). Both are only
+/// set if the client asked for them in the handshake
+async fn write_response<W: AsyncWrite + Unpin>(
+    con: &mut W,
+    r: QueryResult<Response>,
+    detail: Option<ErrorDetail>,
+    notices: &[Notice],
+) -> IoResult<usize> {
+    let mut written = 0;
+    if r.is_ok() & !notices.is_empty() {
+        let section = encode_notices(notices);
+        con.write_all(&section).await?;
+        written += section.len();
+    }
+    written += match r {
+        Ok(Response::Empty) => {
+            con.write_all(&[ResponseType::Empty.value_u8()]).await?;
+            1
+        }
+        Ok(Response::Serialized { ty, size, data }) => {
+            con.write_u8(ty.value_u8()).await?;
+            let mut irep = IntegerRepr::new();
+            let size = irep.as_bytes(size as u64);
+            con.write_all(size).await?;
+            con.write_u8(b'\n').await?;
+            con.write_all(&data).await?;
+            size.len() + data.len() + 2
+        }
+        Ok(Response::Bool(b)) => {
+            con.write_all(&[ResponseType::Bool.value_u8(), b as u8])
+                .await?;
+            2
+        }
+        Ok(Response::Null) => {
+            con.write_u8(ResponseType::Null.value_u8()).await?;
+            1
+        }
+        Ok(Response::UInt64(u)) => {
+            con.write_u8(ResponseType::UInt64.value_u8()).await?;
+            let mut irep = IntegerRepr::new();
+            let u = irep.as_bytes(u);
+            con.write_all(u).await?;
+            con.write_u8(b'\n').await?;
+            u.len() + 2
+        }
+        Err(e) => match detail {
+            Some(detail) => {
+                let packet = encode_error_detail(&detail);
+                con.write_all(&packet).await?;
+                packet.len()
+            }
+            None => {
                 let [a, b] = (e.value_u8() as u16).to_le_bytes();
                 con.write_all(&[ResponseType::Error.value_u8(), a, b])
                     .await?;
+                3
             }
-        }
-        con.flush().await?;
-        // reset buffer, cursor and state
-        buf.clear();
-        cursor = Default::default();
-        state = QExchangeState::default();
+        },
+    };
+    Ok(written)
+}
+
+/// Encode the notices for a response, which come right before it:
+///
+/// ```text
+/// 0x14 <count>\n (<code: u8> <message len>\n<message>)*
+/// ```
+fn encode_notices(notices: &[Notice]) -> Vec<u8> {
+    let mut irep = IntegerRepr::new();
+    let mut section = vec![ResponseType::Notices.value_u8()];
+    section.extend_from_slice(irep.as_bytes(notices.len() as u64));
+    section.push(b'\n');
+    for notice in notices {
+        section.push(notice.code().value_u8());
+        section.extend_from_slice(irep.as_bytes(notice.message().len() as u64));
+        section.push(b'\n');
+        section.extend_from_slice(notice.message().as_bytes());
+    }
+    section
+}
+
+/// Encode an error with its category and details:
+///
+/// ```text
+/// 0x10 <code: u16 LE> <category: u8> <entry count>\n (<key len>\n<key><value len>\n<value>)*
+/// ```
+fn encode_error_detail(detail: &ErrorDetail) -> Vec<u8> {
+    fn push_len(packet: &mut Vec<u8>, len: usize) {
+        let mut irep = IntegerRepr::new();
+        packet.extend_from_slice(irep.as_bytes(len as u64));
+        packet.push(b'\n');
+    }
+    let e = detail.error();
+    let mut packet = vec![ResponseType::Error.value_u8()];
+    packet.extend_from_slice(&(e.value_u8() as u16).to_le_bytes());
+    packet.push(e.category().value_u8());
+    push_len(&mut packet, detail.entries().len());
+    for (key, value) in detail.entries() {
+        push_len(&mut packet, key.len());
+        packet.extend_from_slice(key.as_bytes());
+        push_len(&mut packet, value.len());
+        packet.extend_from_slice(value.as_bytes());
     }
+    packet
 }
 
 #[derive(Debug, PartialEq)]
@@ -241,6 +969,32 @@ async fn do_handshake<S: Socket>(
     con: &mut BufWriter<S>,
     buf: &mut BytesMut,
     global: &Global,
+) -> IoResult<PostHandshake> {
+    read_handshake(con, buf, |handshake| {
+        let uname = core::str::from_utf8(handshake.hs_auth().username()).ok()?;
+        match global
+            .state()
+            .namespace()
+            .sys_db()
+            .verify_user(uname, handshake.hs_auth().password())
+        {
+            okay @ (VerifyUser::Okay | VerifyUser::OkayRoot) => Some(ClientLocalState::new(
+                uname.into(),
+                okay.is_root(),
+                handshake.hs_static(),
+            )),
+            VerifyUser::IncorrectPassword | VerifyUser::NotFound => None,
+        }
+    })
+    .await
+}
+
+/// Read the client's handshake and once it is complete, authenticate the client with `auth` (which returns [`None`] if
+/// the client should be rejected)
+async fn read_handshake<S: Socket>(
+    con: &mut BufWriter<S>,
+    buf: &mut BytesMut,
+    auth: impl FnOnce(&CHandshake) -> Option<ClientLocalState>,
 ) -> IoResult<PostHandshake> {
     let mut expected = CHandshake::INITIAL_READ;
     let mut state = HandshakeState::default();
@@ -281,7 +1035,6 @@ async fn do_handshake<S: Socket>(
             handshake.hs_static().hs_version(),
             HandshakeVersion::Original
         );
-        assert_eq!(handshake.hs_static().protocol(), ProtocolVersion::Original);
         assert_eq!(
             handshake.hs_static().exchange_mode(),
             DataExchangeMode::QueryTime
@@ -289,30 +1042,13 @@ async fn do_handshake<S: Socket>(
         assert_eq!(handshake.hs_static().query_mode(), QueryMode::Bql1);
         assert_eq!(handshake.hs_static().auth_mode(), AuthMode::Password);
     }
-    match core::str::from_utf8(handshake.hs_auth().username()) {
-        Ok(uname) => {
-            match global
-                .state()
-                .namespace()
-                .sys_db()
-                .verify_user(uname, handshake.hs_auth().password())
-            {
-                okay @ (VerifyUser::Okay | VerifyUser::OkayRoot) => {
-                    let hs = handshake.hs_static();
-                    let ret = Ok(PostHandshake::Okay(ClientLocalState::new(
-                        uname.into(),
-                        okay.is_root(),
-                        hs,
-                    )));
-                    buf.advance(cursor);
-                    return ret;
-                }
-                VerifyUser::IncorrectPassword | VerifyUser::NotFound => {}
-            }
+    match auth(&handshake) {
+        Some(client_state) => {
+            buf.advance(cursor);
+            Ok(PostHandshake::Okay(client_state))
         }
-        Err(_) => {}
-    };
-    Ok(PostHandshake::Error(ProtocolError::RejectAuth))
+        None => Ok(PostHandshake::Error(ProtocolError::RejectAuth)),
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
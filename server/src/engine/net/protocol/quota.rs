@@ -0,0 +1,145 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    response limits
+    ---
+    the largest response that a single statement can return, and the bytes that a connection can be sent in every
+    window. the window is fixed (and not sliding): it begins with the first response that is counted, and the count
+    is reset once the window has passed. a response that is larger than the soft threshold of the largest response, or
+    that puts the connection past the soft threshold of its quota (once every window), is sent with a warning
+*/
+
+use {
+    crate::engine::{
+        error::{QueryError, QueryResult},
+        fractal::{Limit, SoftLimit},
+    },
+    std::time::{Duration, Instant},
+};
+
+/// what a client is told to do when its responses are too large
+const HINT_PAGINATE: &str = "paginate the query with `limit` and `offset`";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The limits on the responses that are sent to clients (see `system.max_response_size` and `system.response_quota`)
+pub struct ResponseLimits {
+    max_size: u64,
+    quota: u64,
+    window: Duration,
+    soft: SoftLimit,
+}
+
+impl ResponseLimits {
+    /// Create the limits. A `max_size` or `quota` of 0 doesn't limit responses; the `window` is in seconds
+    pub const fn new(max_size: u64, quota: u64, window: u64) -> Self {
+        Self {
+            max_size,
+            quota,
+            window: Duration::from_secs(window),
+            soft: SoftLimit::new(0),
+        }
+    }
+    /// Warn about responses past the given share of the limits
+    pub const fn with_soft_limit(self, soft: SoftLimit) -> Self {
+        Self { soft, ..self }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// The bytes that a connection has been sent in the current window
+pub struct ResponseQuota {
+    window_start: Option<Instant>,
+    sent: u64,
+    /// set once the connection has been warned about its quota in the current window
+    warned: bool,
+}
+
+impl ResponseQuota {
+    pub const fn new() -> Self {
+        Self {
+            window_start: None,
+            sent: 0,
+            warned: false,
+        }
+    }
+    /// Check if a response of `size` bytes can be sent at `now`
+    pub fn admit(&mut self, limits: ResponseLimits, size: u64, now: Instant) -> QueryResult<()> {
+        if limits.max_size != 0 && size > limits.max_size {
+            return Err(QueryError::QExecResponseTooLarge
+                .with_detail("size", size)
+                .with_detail("limit", limits.max_size)
+                .with_detail("hint", HINT_PAGINATE));
+        }
+        if let Some(soft) = limits.soft.threshold(limits.max_size) {
+            if size > soft {
+                Limit::ResponseSize.warn(size, limits.max_size);
+            }
+        }
+        if limits.quota == 0 {
+            return Ok(());
+        }
+        let window_start = self.roll(limits.window, now);
+        if self.sent.saturating_add(size) > limits.quota {
+            let retry_after = limits
+                .window
+                .saturating_sub(now.saturating_duration_since(window_start));
+            return Err(QueryError::QExecResponseQuotaExceeded
+                .with_detail("limit", limits.quota)
+                .with_detail("window_secs", limits.window.as_secs())
+                .with_detail("retry_after_secs", retry_after.as_secs_f64().ceil() as u64)
+                .with_detail("hint", HINT_PAGINATE));
+        }
+        if let Some(soft) = limits.soft.threshold(limits.quota) {
+            let sent = self.sent.saturating_add(size);
+            if !self.warned & (sent > soft) {
+                self.warned = true;
+                Limit::ResponseQuota.warn(sent, limits.quota);
+            }
+        }
+        Ok(())
+    }
+    /// Count the `bytes` that were sent to the connection at `now`
+    pub fn charge(&mut self, limits: ResponseLimits, bytes: u64, now: Instant) {
+        if limits.quota == 0 {
+            return;
+        }
+        self.roll(limits.window, now);
+        self.sent = self.sent.saturating_add(bytes);
+    }
+    /// Begin a new window if the current one has passed (or if there isn't one), returning when the window began
+    fn roll(&mut self, window: Duration, now: Instant) -> Instant {
+        match self.window_start {
+            Some(start) if now.saturating_duration_since(start) < window => start,
+            _ => {
+                self.window_start = Some(now);
+                self.sent = 0;
+                self.warned = false;
+                now
+            }
+        }
+    }
+}
@@ -0,0 +1,797 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    protocol test kit
+    ---
+    `skyd testkit` speaks the server side of Skyhash/2 (with the same handshake and exchange state machines and the
+    same response encoder as the server) but instead of running queries, it answers every query with a scripted
+    response so that driver authors can check their driver against it without running a full server. the first word
+    of the query names the case (see `CASES`, or `skyd testkit --list`); the rest of the query is ignored, except by
+    `echo`. bulk inserts are answered with the number of rows that they carry. blob frames are answered without
+    storing anything: a begin with the upload id `0`, an append with the number of bytes that it carries, a read with
+    an empty value and a commit or an abort with an empty response. a stream is answered with a single (and so last)
+    chunk holding the response of its case, and a tagged query with its tag followed by the response of its case.
+
+    any username and password is accepted, except for the password `reject` which fails the handshake
+*/
+
+use {
+    super::{
+        exchange::{
+            self, BlobRequest, QExchangeResult, QExchangeState, SQuery, StreamRequest, TaggedQuery,
+        },
+        handshake::Capabilities,
+        read_handshake, write_response, ClientLocalState, PostHandshake, Response, ResponseType,
+    },
+    crate::engine::{
+        core::{
+            dml::encode_cell,
+            notice::{Notice, NoticeCode},
+        },
+        data::{
+            cell::Datacell,
+            tag::{FloatSpec, SIntSpec, TagSelector, UIntSpec},
+        },
+        error::{ErrorDetail, ErrorKind, QueryError, QueryResult, RuntimeResult},
+        fractal::error::{Error, ErrorContext},
+        mem::IntegerRepr,
+        net::IoResult,
+        ql::lex::{SecureLexer, Token},
+    },
+    bytes::BytesMut,
+    std::time::Duration,
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt, BufWriter},
+        net::{TcpListener, TcpStream},
+        time,
+    },
+};
+
+const USAGE: &str = "Usage: skyd testkit [--endpoint <host:port>] [--list]";
+const DEFAULT_ENDPOINT: &str = "127.0.0.1:2003";
+const BUF_CAP: usize = 16384;
+/// The password that fails the handshake
+const REJECT_PASSWORD: &[u8] = b"reject";
+/// The size of the string returned by `large.string` (16 MiB)
+const LARGE_STRING_LEN: usize = 16 * 1024 * 1024;
+/// The number of rows returned by `large.multirow`
+const LARGE_MULTIROW_ROWS: u64 = 100_000;
+/// How long to wait between writes when a response is split
+const SPLIT_DELAY: Duration = Duration::from_millis(1);
+
+/// How a response is written to the connection
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Delivery {
+    /// in a single write
+    Whole,
+    /// one byte per write, flushing after each
+    Split,
+    /// only the first half is written, and then the connection is closed
+    Truncated,
+}
+
+struct Case {
+    name: &'static str,
+    /// what the driver should decode
+    expect: &'static str,
+    delivery: Delivery,
+    respond: fn(&SQuery) -> QueryResult<Response>,
+}
+
+static CASES: [Case; 18] = [
+    Case {
+        name: "empty",
+        expect: "an empty response",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(Response::Empty),
+    },
+    Case {
+        name: "null",
+        expect: "null",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(Response::Null),
+    },
+    Case {
+        name: "bool",
+        expect: "true",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(Response::Bool(true)),
+    },
+    Case {
+        name: "uint64",
+        expect: "18446744073709551615",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(Response::UInt64(u64::MAX)),
+    },
+    Case {
+        name: "string",
+        expect: "the string \"skytable ✓\\n\"",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(string("skytable ✓\n".into())),
+    },
+    Case {
+        name: "string.empty",
+        expect: "the empty string",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(string(String::new())),
+    },
+    Case {
+        name: "row",
+        expect: "a row of: true, 255 (uint8), 65535 (uint16), 4294967295 (uint32), 18446744073709551615 (uint64), \
+                 -128 (sint8), -32768 (sint16), -2147483648 (sint32), -9223372036854775808 (sint64), 1.5 (float32), \
+                 -0.1 (float64), the binary [0, 10, 255], the string \"skytable ✓\\n\", the list [\"a\", [\"b\"]] \
+                 and null",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(row(&every_type())),
+    },
+    Case {
+        name: "row.empty",
+        expect: "a row with no columns",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(row(&[])),
+    },
+    Case {
+        name: "multirow",
+        expect: "the rows (1, \"one\"), (2, \"two\") and (3, \"three\")",
+        delivery: Delivery::Whole,
+        respond: |_| {
+            Ok(multirow(["one", "two", "three"].into_iter().zip(1..).map(
+                |(name, id)| vec![Datacell::new_uint_default(id), Datacell::new_str(name.into())],
+            )))
+        },
+    },
+    Case {
+        name: "multirow.empty",
+        expect: "no rows",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(multirow(std::iter::empty())),
+    },
+    Case {
+        name: "error",
+        expect: "the error code 111 (row not found)",
+        delivery: Delivery::Whole,
+        respond: |_| Err(QueryError::QExecDmlRowNotFound),
+    },
+    Case {
+        name: "error.detail",
+        expect: "the error code 115 (check `max` violated). clients that asked for error details also get the \
+                 category 6 (constraint) and the details field = \"age\", constraint = \"max\" and limit = \"120\"",
+        delivery: Delivery::Whole,
+        respond: |_| {
+            Err(QueryError::QExecDmlCheckMaxViolated
+                .with_detail("field", "age")
+                .with_detail("constraint", "max")
+                .with_detail("limit", 120))
+        },
+    },
+    Case {
+        name: "notices",
+        expect: "an empty response. clients that asked for notices get the notices (0, \"first\") and \
+                 (1, \"second ✓\") before it",
+        delivery: Delivery::Whole,
+        respond: |_| {
+            Notice::raise(NoticeCode::UnfilteredMutation, "first");
+            Notice::raise(NoticeCode::LimitReached, "second ✓");
+            Ok(Response::Empty)
+        },
+    },
+    Case {
+        name: "large.string",
+        expect: "a string of 16777216 `a`s",
+        delivery: Delivery::Whole,
+        respond: |_| Ok(string("a".repeat(LARGE_STRING_LEN))),
+    },
+    Case {
+        name: "large.multirow",
+        expect: "100000 rows, where row `n` (from 0) is (n, \"row n\")",
+        delivery: Delivery::Whole,
+        respond: |_| {
+            Ok(multirow((0..LARGE_MULTIROW_ROWS).map(|n| {
+                vec![
+                    Datacell::new_uint_default(n),
+                    Datacell::new_str(format!("row {n}").into_boxed_str()),
+                ]
+            })))
+        },
+    },
+    Case {
+        name: "split",
+        expect: "the same as `row`, but written one byte at a time",
+        delivery: Delivery::Split,
+        respond: |_| Ok(row(&every_type())),
+    },
+    Case {
+        name: "truncated",
+        expect: "an error: the connection is closed after half of the response to `row` is written",
+        delivery: Delivery::Truncated,
+        respond: |_| Ok(row(&every_type())),
+    },
+    Case {
+        name: "echo",
+        expect: "a row with the parameters of the query, as the server decoded them (for example, `echo ?, ?` with \
+                 42 and \"hello\" returns (42, \"hello\"))",
+        delivery: Delivery::Whole,
+        respond: echo,
+    },
+];
+
+/// Run the test kit (`skyd testkit`) with the given arguments. Unless it is only asked to list the cases, this never
+/// returns unless the endpoint can't be bound
+pub fn run(args: Vec<String>) -> RuntimeResult<()> {
+    let mut endpoint = DEFAULT_ENDPOINT.to_owned();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--list" => {
+                for case in CASES.iter() {
+                    println!("{:<16}{}", case.name, case.expect);
+                }
+                return Ok(());
+            }
+            "--endpoint" => match args.next() {
+                Some(ep) => endpoint = ep,
+                None => return Err(usage("`--endpoint` needs a value")),
+            },
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            unknown => return Err(usage(&format!("unknown argument `{unknown}`"))),
+        }
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(listen(&endpoint))
+}
+
+async fn listen(endpoint: &str) -> RuntimeResult<()> {
+    let listener = TcpListener::bind(endpoint)
+        .await
+        .set_dmsg(format!("failed to bind test kit to `{endpoint}`"))?;
+    info!("protocol test kit listening on tcp@{endpoint} (`skyd testkit --list` lists the cases)");
+    loop {
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                warn!("test kit: failed to accept connection: {e}");
+                continue;
+            }
+        };
+        tokio::spawn(async move {
+            if let Err(e) = serve(stream).await {
+                warn!("test kit: error while talking to {peer}: {e}");
+            }
+        });
+    }
+}
+
+fn usage(problem: &str) -> Error {
+    ErrorKind::Other(format!("{problem}. {USAGE}")).into()
+}
+
+async fn serve(stream: TcpStream) -> IoResult<()> {
+    let mut con = BufWriter::with_capacity(BUF_CAP, stream);
+    let mut buf = BytesMut::with_capacity(BUF_CAP);
+    let hs = read_handshake(&mut con, &mut buf, |hs| {
+        (hs.hs_auth().password() != REJECT_PASSWORD)
+            .then(|| ClientLocalState::new("testkit".into(), false, hs.hs_static()))
+    })
+    .await?;
+    let client_state = match hs {
+        PostHandshake::Okay(client_state) => {
+            con.write_all(b"H\x00\x00\x00").await?;
+            client_state
+        }
+        PostHandshake::Error(e) => {
+            con.write_all(&[b'H', 0, 1, e.value_u8()]).await?;
+            return con.flush().await;
+        }
+        PostHandshake::ConnectionClosedFin | PostHandshake::ConnectionClosedRst => return Ok(()),
+    };
+    con.flush().await?;
+    let mut state = QExchangeState::default();
+    let mut cursor = Default::default();
+    loop {
+        if con.read_buf(&mut buf).await? == 0 {
+            return Ok(());
+        }
+        if !state.has_reached_target(&buf) {
+            continue;
+        }
+        let capabilities = client_state.capabilities();
+        let (encoded, delivery) = match unsafe {
+            // UNSAFE(@ohsayan): as the resume cursor is private, we can't access this anyways
+            exchange::resume(&buf, cursor, state)
+        } {
+            (_, QExchangeResult::SQCompleted(sq)) => {
+                let case = find_case(&sq);
+                let delivery = case.map_or(Delivery::Whole, |case| case.delivery);
+                (encode(run_case(case, &sq), capabilities).await?, delivery)
+            }
+            (_, QExchangeResult::BatchCompleted(batch)) if capabilities.accepts_batches() => {
+                let encoded = match batch.split() {
+                    Some((stop_on_error, statements)) => {
+                        encode_batch(stop_on_error, &statements, capabilities).await?
+                    }
+                    None => {
+                        encode(
+                            Err(QueryError::SysNetworkSystemIllegalClientPacket),
+                            capabilities,
+                        )
+                        .await?
+                    }
+                };
+                (encoded, Delivery::Whole)
+            }
+            (_, QExchangeResult::BulkInsertCompleted(bulk))
+                if capabilities.accepts_bulk_inserts() =>
+            {
+                // there's no model to insert into, so we just tell the client how many rows it sent
+                let r = match bulk.decode() {
+                    Some(bulk) => Ok(Response::UInt64(bulk.into_rows().len() as u64)),
+                    None => Err(QueryError::SysNetworkSystemIllegalClientPacket),
+                };
+                (encode(r, capabilities).await?, Delivery::Whole)
+            }
+            (_, QExchangeResult::BlobCompleted(blob)) if capabilities.accepts_blobs() => {
+                let r = match blob.decode() {
+                    Some(BlobRequest::Begin { .. }) => Ok(Response::UInt64(0)),
+                    Some(BlobRequest::Append { data, .. }) => {
+                        Ok(Response::UInt64(data.len() as u64))
+                    }
+                    Some(BlobRequest::Commit { .. } | BlobRequest::Abort { .. }) => {
+                        Ok(Response::Empty)
+                    }
+                    Some(BlobRequest::Read { .. }) => Ok(Response::Serialized {
+                        ty: ResponseType::Binary,
+                        size: 0,
+                        data: vec![],
+                    }),
+                    None => Err(QueryError::SysNetworkSystemIllegalClientPacket),
+                };
+                (encode(r, capabilities).await?, Delivery::Whole)
+            }
+            (_, QExchangeResult::StreamCompleted(stream)) if capabilities.accepts_streams() => {
+                let encoded = match stream.decode() {
+                    Some(stream) => encode_stream(&stream, capabilities).await?,
+                    None => {
+                        encode(
+                            Err(QueryError::SysNetworkSystemIllegalClientPacket),
+                            capabilities,
+                        )
+                        .await?
+                    }
+                };
+                (encoded, Delivery::Whole)
+            }
+            (_, QExchangeResult::TaggedCompleted(tagged)) if capabilities.accepts_tags() => {
+                match tagged.decode() {
+                    Some(tagged) => encode_tagged(tagged, capabilities).await?,
+                    None => (
+                        encode(
+                            Err(QueryError::SysNetworkSystemIllegalClientPacket),
+                            capabilities,
+                        )
+                        .await?,
+                        Delivery::Whole,
+                    ),
+                }
+            }
+            (_, QExchangeResult::Ping) if capabilities.accepts_ping() => {
+                (vec![ResponseType::Pong.value_u8()], Delivery::Whole)
+            }
+            (new_cursor, QExchangeResult::ChangeState(new_state)) => {
+                cursor = new_cursor;
+                state = new_state;
+                continue;
+            }
+            (
+                _,
+                QExchangeResult::BatchCompleted(_)
+                | QExchangeResult::BulkInsertCompleted(_)
+                | QExchangeResult::BlobCompleted(_)
+                | QExchangeResult::StreamCompleted(_)
+                | QExchangeResult::TaggedCompleted(_)
+                | QExchangeResult::Ping
+                | QExchangeResult::Error,
+            ) => (
+                encode(
+                    Err(QueryError::SysNetworkSystemIllegalClientPacket),
+                    capabilities,
+                )
+                .await?,
+                Delivery::Whole,
+            ),
+        };
+        match delivery {
+            Delivery::Whole => con.write_all(&encoded).await?,
+            Delivery::Split => {
+                for byte in encoded {
+                    con.write_u8(byte).await?;
+                    con.flush().await?;
+                    time::sleep(SPLIT_DELAY).await;
+                }
+            }
+            Delivery::Truncated => {
+                con.write_all(&encoded[..encoded.len() / 2]).await?;
+                con.flush().await?;
+                return con.shutdown().await;
+            }
+        }
+        con.flush().await?;
+        // reset buffer, cursor and state
+        buf.clear();
+        cursor = Default::default();
+        state = QExchangeState::default();
+    }
+}
+
+/// Returns the case named by the first word of the query
+fn find_case(sq: &SQuery) -> Option<&'static Case> {
+    let query = core::str::from_utf8(sq.query()).ok()?;
+    let name = query.split_ascii_whitespace().next()?;
+    CASES
+        .iter()
+        .find(|case| case.name.eq_ignore_ascii_case(name))
+}
+
+/// Run the case. The response must be encoded with [`encode`] right after, before awaiting
+fn respond(case: &Case, sq: &SQuery) -> QueryResult<Response> {
+    ErrorDetail::clear();
+    Notice::clear();
+    (case.respond)(sq)
+}
+
+/// Run the case, if the query named one. The response must be encoded with [`encode`] right after, before awaiting
+fn run_case(case: Option<&Case>, sq: &SQuery) -> QueryResult<Response> {
+    match case {
+        Some(case) => respond(case, sq),
+        None => Err(QueryError::QLUnknownStatement),
+    }
+}
+
+/// Encode the responses to the statements in a batch, like the server does. Batched responses are always delivered
+/// whole
+async fn encode_batch(
+    stop_on_error: bool,
+    statements: &[SQuery<'_>],
+    capabilities: Capabilities,
+) -> IoResult<Vec<u8>> {
+    let mut results = vec![];
+    let mut ran = 0u64;
+    for sq in statements {
+        let r = run_case(find_case(sq), sq);
+        let okay = r.is_ok();
+        results.extend(encode(r, capabilities).await?);
+        ran += 1;
+        if stop_on_error & !okay {
+            break;
+        }
+    }
+    let mut irep = IntegerRepr::new();
+    let mut encoded = vec![ResponseType::Batch.value_u8()];
+    encoded.extend_from_slice(irep.as_bytes(ran));
+    encoded.push(b'\n');
+    encoded.extend(results);
+    Ok(encoded)
+}
+
+/// Answer a stream with a single (and so last) chunk, holding the response of its case
+async fn encode_stream(
+    stream: &StreamRequest<'_>,
+    capabilities: Capabilities,
+) -> IoResult<Vec<u8>> {
+    let sq = stream.query();
+    let r = stream
+        .resume_from()
+        .and_then(|_| run_case(find_case(sq), sq));
+    let mut encoded = vec![ResponseType::Chunk.value_u8(), b'0', b'\n'];
+    encoded.extend(encode(r, capabilities).await?);
+    Ok(encoded)
+}
+
+/// Answer a tagged query with its tag, followed by the response of its case (which is delivered like the case says)
+async fn encode_tagged(
+    tagged: TaggedQuery<'_>,
+    capabilities: Capabilities,
+) -> IoResult<(Vec<u8>, Delivery)> {
+    let tag = tagged.tag();
+    let sq = tagged.into_query();
+    let case = find_case(&sq);
+    let delivery = case.map_or(Delivery::Whole, |case| case.delivery);
+    let mut irep = IntegerRepr::new();
+    let mut encoded = vec![ResponseType::Tag.value_u8()];
+    encoded.extend_from_slice(irep.as_bytes(tag.len() as u64));
+    encoded.push(b'\n');
+    encoded.extend_from_slice(tag.as_bytes());
+    encoded.extend(encode(run_case(case, &sq), capabilities).await?);
+    Ok((encoded, delivery))
+}
+
+/// Encode a response, along with the error detail and notices that the case left behind (if the client asked for
+/// them)
+async fn encode(r: QueryResult<Response>, capabilities: Capabilities) -> IoResult<Vec<u8>> {
+    let detail = match &r {
+        Err(e) if capabilities.sends_error_detail() => Some(ErrorDetail::take(*e)),
+        _ => None,
+    };
+    let notices = match &r {
+        Ok(_) if capabilities.sends_notices() => Notice::take_all(),
+        _ => vec![],
+    };
+    let mut encoded = vec![];
+    write_response(&mut encoded, r, detail, &notices).await?;
+    Ok(encoded)
+}
+
+/// Returns the parameters of the query as a row
+fn echo(sq: &SQuery) -> QueryResult<Response> {
+    let tokens = SecureLexer::new_with_segments(sq.query(), sq.params()).lex()?;
+    let cells: Vec<Datacell> = tokens
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Lit(lit) => Some(Datacell::from(lit)),
+            Token![null] => Some(Datacell::null()),
+            _ => None,
+        })
+        .collect();
+    Ok(row(&cells))
+}
+
+/// Returns a cell of every type, at the edges of their ranges where they have one
+fn every_type() -> Vec<Datacell> {
+    unsafe {
+        // UNSAFE(@ohsayan): the tags match the spec
+        vec![
+            Datacell::new_bool(true),
+            Datacell::new_uint(
+                u8::MAX as _,
+                UIntSpec::from_full(TagSelector::UInt8.into_full()),
+            ),
+            Datacell::new_uint(
+                u16::MAX as _,
+                UIntSpec::from_full(TagSelector::UInt16.into_full()),
+            ),
+            Datacell::new_uint(
+                u32::MAX as _,
+                UIntSpec::from_full(TagSelector::UInt32.into_full()),
+            ),
+            Datacell::new_uint_default(u64::MAX),
+            Datacell::new_sint(
+                i8::MIN as _,
+                SIntSpec::from_full(TagSelector::SInt8.into_full()),
+            ),
+            Datacell::new_sint(
+                i16::MIN as _,
+                SIntSpec::from_full(TagSelector::SInt16.into_full()),
+            ),
+            Datacell::new_sint(
+                i32::MIN as _,
+                SIntSpec::from_full(TagSelector::SInt32.into_full()),
+            ),
+            Datacell::new_sint_default(i64::MIN),
+            Datacell::new_float(1.5, FloatSpec::from_full(TagSelector::Float32.into_full())),
+            Datacell::new_float_default(-0.1),
+            Datacell::new_bin(Box::new([0, b'\n', 255])),
+            Datacell::new_str("skytable ✓\n".into()),
+            Datacell::new_list(vec![
+                Datacell::new_str("a".into()),
+                Datacell::new_list(vec![Datacell::new_str("b".into())]),
+            ]),
+            Datacell::null(),
+        ]
+    }
+}
+
+fn string(s: String) -> Response {
+    Response::Serialized {
+        ty: ResponseType::String,
+        size: s.len(),
+        data: s.into_bytes(),
+    }
+}
+
+fn row(cells: &[Datacell]) -> Response {
+    let mut data = vec![];
+    for cell in cells {
+        encode_cell(&mut data, cell);
+    }
+    Response::Serialized {
+        ty: ResponseType::Row,
+        size: cells.len(),
+        data,
+    }
+}
+
+fn multirow(rows: impl Iterator<Item = Vec<Datacell>>) -> Response {
+    let mut data = vec![];
+    let mut size = 0;
+    for row in rows {
+        IntegerRepr::scoped(row.len() as u64, |repr| data.extend(repr));
+        data.push(b'\n');
+        for cell in row.iter() {
+            encode_cell(&mut data, cell);
+        }
+        size += 1;
+    }
+    Response::Serialized {
+        ty: ResponseType::MultiRow,
+        size,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            exchange::SBatch,
+            tests::{create_batch, create_simple_query, create_tagged},
+        },
+        encode, encode_batch, encode_tagged, exchange, find_case, respond, Capabilities,
+        QExchangeResult, QExchangeState, SQuery,
+    };
+
+    fn with_query<T>(packet: &[u8], f: impl FnOnce(&SQuery) -> T) -> T {
+        match unsafe { exchange::resume(packet, Default::default(), QExchangeState::default()) } {
+            (_, QExchangeResult::SQCompleted(sq)) => f(&sq),
+            _ => panic!("bad packet"),
+        }
+    }
+
+    async fn encoded(packet: &[u8]) -> Vec<u8> {
+        encoded_for(packet, Capabilities::NONE).await
+    }
+
+    async fn encoded_for(packet: &[u8], capabilities: Capabilities) -> Vec<u8> {
+        let response = with_query(packet, |sq| respond(find_case(sq).unwrap(), sq));
+        encode(response, capabilities).await.unwrap()
+    }
+
+    #[test]
+    fn cases() {
+        with_query(&create_simple_query("ROW", []), |sq| {
+            assert_eq!(find_case(sq).unwrap().name, "row")
+        });
+        with_query(&create_simple_query("drop model x", []), |sq| {
+            assert!(find_case(sq).is_none())
+        });
+    }
+
+    #[tokio::test]
+    async fn responses() {
+        assert_eq!(
+            encoded(&create_simple_query("uint64", [])).await,
+            b"\x0518446744073709551615\n"
+        );
+        assert_eq!(
+            encoded(&create_simple_query("multirow.empty", [])).await,
+            b"\x130\n"
+        );
+        assert_eq!(
+            encoded(&create_simple_query("error", [])).await,
+            b"\x10\x6f\x00"
+        );
+        assert_eq!(
+            encoded(&create_simple_query("multirow", [])).await[..14],
+            *b"\x133\n2\n\x051\n\x0d3\none"
+        );
+    }
+
+    #[tokio::test]
+    async fn error_detail() {
+        let packet = create_simple_query("error.detail", []);
+        assert_eq!(encoded(&packet).await, b"\x10\x73\x00");
+        assert_eq!(
+            encoded_for(&packet, Capabilities::ERROR_DETAIL).await,
+            b"\x10\x73\x00\x063\n5\nfield3\nage10\nconstraint3\nmax5\nlimit3\n120"
+        );
+        assert_eq!(
+            encoded_for(
+                &create_simple_query("error", []),
+                Capabilities::ERROR_DETAIL
+            )
+            .await,
+            b"\x10\x6f\x00\x040\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn notices() {
+        let packet = create_simple_query("notices", []);
+        assert_eq!(encoded(&packet).await, b"\x12");
+        assert_eq!(
+            encoded_for(&packet, Capabilities::ERROR_DETAIL).await,
+            b"\x12"
+        );
+        assert_eq!(
+            encoded_for(&packet, Capabilities::NOTICES).await,
+            "\x142\n\x005\nfirst\x0110\nsecond ✓\x12".as_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn echo_params() {
+        let packet = create_simple_query("echo ?, ?", ["\x0242\n", "\x065\nhello"]);
+        assert_eq!(encoded(&packet).await, b"\x112\n\x0542\n\x0d5\nhello");
+    }
+
+    #[tokio::test]
+    async fn tagged() {
+        let packet = create_tagged("req-1", "uint64", b"");
+        let tagged = match unsafe {
+            exchange::resume(&packet, Default::default(), QExchangeState::default())
+        } {
+            (_, QExchangeResult::TaggedCompleted(tagged)) => tagged.decode().unwrap(),
+            _ => panic!("bad packet"),
+        };
+        assert_eq!(
+            encode_tagged(tagged, Capabilities::TAGS).await.unwrap().0,
+            b"\x185\nreq-1\x0518446744073709551615\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn batch() {
+        let packet = create_batch(
+            SBatch::MODE_RUN_ALL,
+            &[
+                create_simple_query("uint64", []),
+                create_simple_query("error", []),
+                create_simple_query("multirow.empty", []),
+            ],
+        );
+        let batch = match unsafe {
+            exchange::resume(&packet, Default::default(), QExchangeState::default())
+        } {
+            (_, QExchangeResult::BatchCompleted(batch)) => batch,
+            _ => panic!("bad packet"),
+        };
+        let (_, statements) = batch.split().unwrap();
+        assert_eq!(
+            encode_batch(
+                false,
+                &statements,
+                Capabilities::ERROR_DETAIL | Capabilities::BATCHES
+            )
+            .await
+            .unwrap(),
+            b"\x153\n\x0518446744073709551615\n\x10\x6f\x00\x040\n\x130\n"
+        );
+        assert_eq!(
+            encode_batch(
+                true,
+                &statements,
+                Capabilities::ERROR_DETAIL | Capabilities::BATCHES
+            )
+            .await
+            .unwrap(),
+            b"\x152\n\x0518446744073709551615\n\x10\x6f\x00\x040\n"
+        );
+    }
+}
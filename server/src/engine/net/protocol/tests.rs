@@ -28,24 +28,36 @@ use crate::engine::net::protocol::exchange::Resume;
 
 use {
     super::{
-        exchange::{self, QExchangeResult, QExchangeState},
+        exchange::{
+            self, BlobRequest, QExchangeResult, QExchangeState, SBatch, SBlob, SBulkInsert,
+            STagged, TaggedQuery,
+        },
+        fuzz,
         handshake::ProtocolError,
-        SQuery,
+        quota::ResponseQuota,
+        ResponseLimits, SQuery, StreamRequest,
     },
     crate::{
         engine::{
+            core::notice::{Notice, NoticeCode},
+            data::cell::Datacell,
+            error::{ErrorCategory, ErrorDetail, QueryError},
+            fractal::SoftLimit,
             mem::BufferedScanner,
             net::protocol::{
+                encode_error_detail, encode_notices,
                 handshake::{
-                    AuthMode, CHandshake, CHandshakeAuth, CHandshakeStatic, DataExchangeMode,
-                    HandshakeResult, HandshakeState, HandshakeVersion, ProtocolVersion, QueryMode,
+                    AuthMode, CHandshake, CHandshakeAuth, CHandshakeStatic, Capabilities,
+                    DataExchangeMode, HandshakeResult, HandshakeState, HandshakeVersion,
+                    ProtocolVersion, QueryMode,
                 },
-                scan_int, AccumlatorStatus,
+                scan_int, AccumlatorStatus, ClientLocalState,
             },
         },
         util::test_utils,
     },
     rand::Rng,
+    std::time::{Duration, Instant},
 };
 
 pub(super) fn create_simple_query<const N: usize>(query: &str, params: [&str; N]) -> Vec<u8> {
@@ -70,6 +82,81 @@ pub(super) fn create_simple_query<const N: usize>(query: &str, params: [&str; N]
     buf
 }
 
+pub(super) fn create_batch(mode: u8, statements: &[Vec<u8>]) -> Vec<u8> {
+    // every statement is a simple query without the `S`
+    let mut payload = vec![mode];
+    statements
+        .iter()
+        .for_each(|sq| payload.extend_from_slice(&sq[1..]));
+    let count = statements.len().to_string();
+    let mut buf = vec![b'B'];
+    buf.extend((payload.len() + count.len() + 1).to_string().as_bytes());
+    buf.push(b'\n');
+    buf.extend(count.as_bytes());
+    buf.push(b'\n');
+    buf.extend(payload);
+    buf
+}
+
+pub(super) fn create_bulk_insert(
+    entity: &str,
+    columns: usize,
+    rows: usize,
+    cells: &[u8],
+) -> Vec<u8> {
+    let mut payload = format!("{}\n{entity}{columns}\n", entity.len()).into_bytes();
+    payload.extend(cells);
+    let rows = rows.to_string();
+    let mut buf = vec![b'I'];
+    buf.extend((payload.len() + rows.len() + 1).to_string().as_bytes());
+    buf.push(b'\n');
+    buf.extend(rows.as_bytes());
+    buf.push(b'\n');
+    buf.extend(payload);
+    buf
+}
+
+pub(super) fn create_blob(verb: u64, payload: &[u8]) -> Vec<u8> {
+    let verb = verb.to_string();
+    let mut buf = vec![b'X'];
+    buf.extend((payload.len() + verb.len() + 1).to_string().as_bytes());
+    buf.push(b'\n');
+    buf.extend(verb.as_bytes());
+    buf.push(b'\n');
+    buf.extend(payload);
+    buf
+}
+
+pub(super) fn create_tagged(tag: &str, query: &str, params: &[u8]) -> Vec<u8> {
+    let tag_size = tag.len().to_string();
+    let mut payload = format!("{tag}{}\n{query}", query.len()).into_bytes();
+    payload.extend(params);
+    let mut buf = vec![b'T'];
+    buf.extend((payload.len() + tag_size.len() + 1).to_string().as_bytes());
+    buf.push(b'\n');
+    buf.extend(tag_size.as_bytes());
+    buf.push(b'\n');
+    buf.extend(payload);
+    buf
+}
+
+pub(super) fn create_stream(chunk_rows: u64, token: &str, query: &str, params: &[u8]) -> Vec<u8> {
+    let chunk_rows = chunk_rows.to_string();
+    let mut payload = format!("{}\n{token}{}\n{query}", token.len(), query.len()).into_bytes();
+    payload.extend(params);
+    let mut buf = vec![b'R'];
+    buf.extend(
+        (payload.len() + chunk_rows.len() + 1)
+            .to_string()
+            .as_bytes(),
+    );
+    buf.push(b'\n');
+    buf.extend(chunk_rows.as_bytes());
+    buf.push(b'\n');
+    buf.extend(payload);
+    buf
+}
+
 /*
     client handshake
 */
@@ -186,7 +273,7 @@ fn parse_auth_with_state_updates() {
 
 const HS_BAD_PACKET: [u8; 6] = *b"I\x00\0\0\0\0";
 const HS_BAD_VERSION_HS: [u8; 6] = *b"H\x01\0\0\0\0";
-const HS_BAD_VERSION_PROTO: [u8; 6] = *b"H\0\x01\0\0\0";
+const HS_BAD_VERSION_PROTO: [u8; 6] = *b"H\0\x02\0\0\0";
 const HS_BAD_MODE_XCHG: [u8; 6] = *b"H\0\0\x01\0\0";
 const HS_BAD_MODE_QUERY: [u8; 6] = *b"H\0\0\0\x01\0";
 const HS_BAD_MODE_AUTH: [u8; 6] = *b"H\0\0\0\0\x01";
@@ -257,6 +344,75 @@ fn hs_bad_version_proto() {
     })
 }
 
+#[test]
+fn hs_capabilities() {
+    for (raw, capabilities) in [
+        ("0", Capabilities::NONE),
+        ("1", Capabilities::ERROR_DETAIL),
+        ("3", Capabilities::ERROR_DETAIL | Capabilities::NOTICES),
+        ("12", Capabilities::BATCHES | Capabilities::PING),
+        ("160", Capabilities::BLOBS | Capabilities::TAGS),
+        ("192", Capabilities::STREAMS | Capabilities::TAGS),
+    ] {
+        let packet = format!("H\0\x01\0\0\0{raw}\n5\n8\nsayanpass1234");
+        scan_hs(packet, |hs_result| {
+            let HandshakeResult::Completed(hs) = hs_result else {
+                panic!("handshake failed: {hs_result:?}")
+            };
+            assert_eq!(hs.hs_static().protocol(), ProtocolVersion::Capabilities);
+            assert_eq!(hs.hs_static().capabilities(), capabilities);
+            assert_eq!(hs.hs_auth(), &CHandshakeAuth::new(b"sayan", b"pass1234"));
+        });
+    }
+    // the original protocol gets no extensions
+    scan_hs(FULL_HANDSHAKE_WITH_AUTH, |hs_result| {
+        let HandshakeResult::Completed(hs) = hs_result else {
+            panic!("handshake failed: {hs_result:?}")
+        };
+        assert_eq!(hs.hs_static().capabilities(), Capabilities::NONE);
+    });
+    // a bit that isn't a known capability
+    scan_hs(b"H\0\x01\0\0\0256\n5\n8\nsayanpass1234", |hs_result| {
+        assert_eq!(
+            hs_result,
+            HandshakeResult::Error(ProtocolError::RejectProtocol)
+        )
+    });
+    scan_hs(b"H\0\x01\0\0\0A\n5\n8\nsayanpass1234", |hs_result| {
+        assert_eq!(
+            hs_result,
+            HandshakeResult::Error(ProtocolError::CorruptedHSPacket)
+        )
+    });
+    let capabilities = Capabilities::ERROR_DETAIL | Capabilities::STREAMS;
+    assert!(capabilities.sends_error_detail() & capabilities.accepts_streams());
+    assert!(!capabilities.sends_notices());
+    assert!(!capabilities.accepts_batches());
+    assert!(!capabilities.accepts_ping());
+    assert!(!capabilities.accepts_bulk_inserts());
+    assert!(!capabilities.accepts_blobs());
+    assert!(!capabilities.accepts_tags());
+}
+
+#[test]
+fn parse_capabilities_with_state_updates() {
+    let rounds = run_state_changes_return_rounds(
+        b"H\0\x01\0\0\0129\n5\n8\nsayanpass1234",
+        CHandshake::new(
+            CHandshakeStatic::new(
+                HandshakeVersion::Original,
+                ProtocolVersion::Capabilities,
+                DataExchangeMode::QueryTime,
+                QueryMode::Bql1,
+                AuthMode::Password,
+            )
+            .with_capabilities(Capabilities::ERROR_DETAIL | Capabilities::TAGS),
+            CHandshakeAuth::new(b"sayan", b"pass1234"),
+        ),
+    );
+    assert_eq!(rounds, 4); // r1 = initial read, r2 = capabilities, r3 = lengths, r4 = items
+}
+
 #[test]
 fn hs_bad_exchange_mode() {
     scan_hs(HS_BAD_MODE_XCHG, |hs_result| {
@@ -284,6 +440,32 @@ fn hs_bad_auth_mode() {
     })
 }
 
+#[test]
+fn hs_bad_multiple_static_fields() {
+    // the first invalid field is reported
+    let packets = [
+        (*b"I\x01\0\0\0\x01", ProtocolError::CorruptedHSPacket),
+        (*b"H\x01\0\0\x01\0", ProtocolError::RejectHSVersion),
+        (*b"H\0\x07\x01\0\0", ProtocolError::RejectProtocol),
+        (*b"H\0\0\x01\x01\x01", ProtocolError::RejectExchangeMode),
+    ];
+    for (packet, error) in packets {
+        scan_hs(packet, |hs_result| {
+            assert_eq!(hs_result, HandshakeResult::Error(error))
+        })
+    }
+}
+
+#[test]
+fn hs_bad_packet_overflowing_lengths() {
+    scan_hs(b"H\0\0\0\0\018446744073709551615\n1\n", |hs_result| {
+        assert_eq!(
+            hs_result,
+            HandshakeResult::Error(ProtocolError::CorruptedHSPacket)
+        )
+    })
+}
+
 /*
     QT-DEX/SQ
 */
@@ -473,6 +655,349 @@ fn exchange_bad_segment_metadata() {
     }
 }
 
+#[test]
+fn exchange_bad_segment_sizes() {
+    let exchange_packets = [
+        (b"S4\n1\nabc".as_slice(), "more data than the packet size"),
+        (b"S4\n9\nab", "q window larger than the dataframe"),
+        (b"S1\n1\nab", "packet size smaller than the metadata"),
+        (b"S99999999999999999999\n1\nab", "overflowing packet size"),
+    ];
+    for (packet, description) in exchange_packets {
+        assert_eq!(
+            unsafe { exchange::resume(packet, Resume::test_new(0), Default::default()) }.1,
+            QExchangeResult::Error,
+            "failed for `{description}`"
+        )
+    }
+}
+
+#[test]
+fn exchange_batch() {
+    let packet = create_batch(
+        SBatch::MODE_STOP_ON_ERROR,
+        &[
+            create_simple_query(SQ, ["\x05sayan"]),
+            create_simple_query("sysctl report status", []),
+        ],
+    );
+    let batch = match unsafe { exchange::resume(&packet, Resume::test_new(0), Default::default()) }
+    {
+        (_, QExchangeResult::BatchCompleted(batch)) => batch,
+        r => panic!("expected a batch, got {r:?}"),
+    };
+    assert_eq!(batch.count(), 2);
+    let (stop_on_error, statements) = batch.split().unwrap();
+    assert!(stop_on_error);
+    let statements: Vec<_> = statements
+        .iter()
+        .map(|sq| (sq.query_str(), sq.params_str()))
+        .collect();
+    assert_eq!(
+        statements,
+        [(SQ, "\x05sayan"), ("sysctl report status", "")]
+    );
+    // an empty batch is fine
+    let packet = create_batch(SBatch::MODE_RUN_ALL, &[]);
+    match unsafe { exchange::resume(&packet, Resume::test_new(0), Default::default()) } {
+        (_, QExchangeResult::BatchCompleted(batch)) => {
+            assert_eq!(batch.split(), Some((false, vec![])))
+        }
+        r => panic!("expected a batch, got {r:?}"),
+    }
+}
+
+#[test]
+fn exchange_bad_batch() {
+    let packets = [
+        (b"B9\n1\n\x024\n1\nab".as_slice(), "unknown mode"),
+        (b"B9\n2\n\x004\n1\nab", "fewer statements than the count"),
+        (b"B9\n1\n\x005\n1\nab", "statement larger than the packet"),
+        (b"B10\n1\n\x003\n1\nabc", "bytes after the statements"),
+        (b"B9\n1\n\x004\n3\nab", "q window larger than the statement"),
+    ];
+    for (packet, description) in packets {
+        match unsafe { exchange::resume(packet, Resume::test_new(0), Default::default()) } {
+            (_, QExchangeResult::BatchCompleted(batch)) => {
+                assert_eq!(batch.split(), None, "failed for `{description}`")
+            }
+            r => panic!("expected a batch for `{description}`, got {r:?}"),
+        }
+    }
+}
+
+#[test]
+fn exchange_bulk_insert() {
+    let mut cells = vec![SBulkInsert::TAG_STR];
+    cells.extend(b"5\nsayan");
+    cells.push(SBulkInsert::TAG_UINT);
+    cells.extend(21u64.to_le_bytes());
+    cells.push(SBulkInsert::TAG_STR);
+    cells.extend(b"5\nrobot");
+    cells.push(SBulkInsert::TAG_NULL);
+    let packet = create_bulk_insert("myspace.mymodel", 2, 2, &cells);
+    let bulk = match unsafe { exchange::resume(&packet, Resume::test_new(0), Default::default()) } {
+        (_, QExchangeResult::BulkInsertCompleted(bulk)) => bulk,
+        r => panic!("expected a bulk insert, got {r:?}"),
+    };
+    assert_eq!(bulk.row_count(), 2);
+    let bulk = bulk.decode().unwrap();
+    assert_eq!(bulk.entity(), "myspace.mymodel");
+    assert_eq!(
+        bulk.into_rows(),
+        [
+            vec![
+                Datacell::new_str("sayan".into()),
+                Datacell::new_uint_default(21)
+            ],
+            vec![Datacell::new_str("robot".into()), Datacell::null()],
+        ]
+    );
+}
+
+#[test]
+fn exchange_bad_bulk_insert() {
+    let packets = [
+        (create_bulk_insert("mymodel", 0, 0, b""), "no columns"),
+        (
+            create_bulk_insert("mymodel", 1, 2, b"\x00"),
+            "fewer cells than the rows",
+        ),
+        (
+            create_bulk_insert("mymodel", 1, 1, b"\x00\x00"),
+            "bytes after the rows",
+        ),
+        (create_bulk_insert("mymodel", 1, 1, b"\x07"), "unknown tag"),
+        (create_bulk_insert("mymodel", 1, 1, b"\x01\x02"), "bad bool"),
+        (
+            create_bulk_insert("mymodel", 1, 1, b"\x02\x01\x00"),
+            "truncated uint",
+        ),
+        (
+            create_bulk_insert("mymodel", 1, 1, b"\x065\nab"),
+            "truncated string",
+        ),
+        (
+            create_bulk_insert("mymodel", 1, 1, b"\x062\n\xff\xff"),
+            "string isn't utf8",
+        ),
+    ];
+    for (packet, description) in packets {
+        match unsafe { exchange::resume(&packet, Resume::test_new(0), Default::default()) } {
+            (_, QExchangeResult::BulkInsertCompleted(bulk)) => {
+                assert_eq!(bulk.decode(), None, "failed for `{description}`")
+            }
+            r => panic!("expected a bulk insert for `{description}`, got {r:?}"),
+        }
+    }
+}
+
+fn decode_blob(packet: &[u8]) -> Option<BlobRequest> {
+    match unsafe { exchange::resume(packet, Resume::test_new(0), Default::default()) } {
+        (_, QExchangeResult::BlobCompleted(blob)) => blob.decode(),
+        r => panic!("expected a blob frame, got {r:?}"),
+    }
+}
+
+#[test]
+fn exchange_blob() {
+    let packet = create_blob(
+        SBlob::VERB_BEGIN,
+        b"15\nmyspace.mymodel\x065\nsayan6\navatar1048576\n",
+    );
+    let Some(BlobRequest::Begin { target, size }) = decode_blob(&packet) else {
+        panic!("expected a begin")
+    };
+    assert_eq!(target.entity(), "myspace.mymodel");
+    assert_eq!(target.key(), &Datacell::new_str("sayan".into()));
+    assert_eq!(target.field(), "avatar");
+    assert_eq!(size, 1048576);
+    assert_eq!(
+        decode_blob(&create_blob(SBlob::VERB_APPEND, b"3\n\x00\n\xff")),
+        Some(BlobRequest::Append {
+            id: 3,
+            data: b"\x00\n\xff"
+        })
+    );
+    assert_eq!(
+        decode_blob(&create_blob(SBlob::VERB_COMMIT, b"3\n")),
+        Some(BlobRequest::Commit { id: 3 })
+    );
+    assert_eq!(
+        decode_blob(&create_blob(SBlob::VERB_ABORT, b"3\n")),
+        Some(BlobRequest::Abort { id: 3 })
+    );
+    let packet = create_blob(
+        SBlob::VERB_READ,
+        b"7\nmymodel\x02\x15\0\0\0\0\0\0\x006\navatar1024\n512\n",
+    );
+    let Some(BlobRequest::Read {
+        target,
+        offset,
+        len,
+    }) = decode_blob(&packet)
+    else {
+        panic!("expected a read")
+    };
+    assert_eq!(target.entity(), "mymodel");
+    assert_eq!(target.key(), &Datacell::new_uint_default(21));
+    assert_eq!((offset, len), (1024, 512));
+}
+
+#[test]
+fn exchange_bad_blob() {
+    let packets = [
+        (create_blob(5, b"3\n"), "unknown verb"),
+        (
+            create_blob(SBlob::VERB_BEGIN, b"7\nmymodel\x065\nsayan6\navatar"),
+            "begin without a size",
+        ),
+        (
+            create_blob(SBlob::VERB_BEGIN, b"7\nmymodel\x075\nsayan6\navatar1\n"),
+            "unknown key tag",
+        ),
+        (
+            create_blob(SBlob::VERB_APPEND, b"abc"),
+            "append without an id",
+        ),
+        (
+            create_blob(SBlob::VERB_COMMIT, b"3\n\x00"),
+            "bytes after the commit",
+        ),
+        (
+            create_blob(SBlob::VERB_READ, b"7\nmymodel\x065\nsayan6\navatar0\n"),
+            "read without a length",
+        ),
+    ];
+    for (packet, description) in packets {
+        assert_eq!(decode_blob(&packet), None, "failed for `{description}`");
+    }
+}
+
+fn decode_stream(packet: &[u8]) -> Option<StreamRequest> {
+    match unsafe { exchange::resume(packet, Resume::test_new(0), Default::default()) } {
+        (_, QExchangeResult::StreamCompleted(stream)) => stream.decode(),
+        r => panic!("expected a stream, got {r:?}"),
+    }
+}
+
+#[test]
+fn exchange_stream() {
+    let packet = create_stream(100, "", "select all * from mymodel limit ?", b"\x021000\n");
+    let stream = decode_stream(&packet).unwrap();
+    assert_eq!(
+        stream.query().query_str(),
+        "select all * from mymodel limit ?"
+    );
+    assert_eq!(stream.query().params_str(), "\x021000\n");
+    assert_eq!(stream.chunk_rows(), 100);
+    assert_eq!(stream.resume_from(), Ok(0));
+    // resuming the same statement
+    let token = stream.token(300);
+    let packet = create_stream(
+        100,
+        &token,
+        "select all * from mymodel limit ?",
+        b"\x021000\n",
+    );
+    assert_eq!(decode_stream(&packet).unwrap().resume_from(), Ok(300));
+}
+
+#[test]
+fn exchange_stream_bad_token() {
+    let token = decode_stream(&create_stream(10, "", "select all * from a limit 5", b""))
+        .unwrap()
+        .token(10);
+    for (token, description) in [
+        (token.as_str(), "token of another statement"),
+        ("10", "token without a check"),
+        ("ten.00000000", "token with a bad row count"),
+    ] {
+        let packet = create_stream(10, token, "select all * from b limit 5", b"");
+        assert_eq!(
+            decode_stream(&packet).unwrap().resume_from(),
+            Err(QueryError::QExecStreamBadToken),
+            "failed for `{description}`"
+        );
+    }
+}
+
+#[test]
+fn exchange_bad_stream() {
+    let packets = [
+        (
+            create_stream(0, "", "select all * from mymodel limit 10", b""),
+            "empty chunks",
+        ),
+        (
+            b"R13\n10\n9\nabc3\nabc".to_vec(),
+            "token longer than the packet",
+        ),
+        (b"R10\n10\n0\n9\nabc".to_vec(), "q window past the end"),
+    ];
+    for (packet, description) in packets {
+        assert_eq!(decode_stream(&packet), None, "failed for `{description}`");
+    }
+}
+
+fn decode_tagged(packet: &[u8]) -> Option<TaggedQuery> {
+    match unsafe { exchange::resume(packet, Resume::test_new(0), Default::default()) } {
+        (_, QExchangeResult::TaggedCompleted(tagged)) => tagged.decode(),
+        r => panic!("expected a tagged query, got {r:?}"),
+    }
+}
+
+#[test]
+fn exchange_tagged() {
+    let packet = create_tagged(
+        "4bf92f3577b34da6",
+        "select * from mymodel where username = ?",
+        b"\x065\nsayan",
+    );
+    let tagged = decode_tagged(&packet).unwrap();
+    assert_eq!(tagged.tag(), "4bf92f3577b34da6");
+    let sq = tagged.into_query();
+    assert_eq!(sq.query_str(), "select * from mymodel where username = ?");
+    assert_eq!(sq.params_str(), "\x065\nsayan");
+}
+
+#[test]
+fn exchange_bad_tagged() {
+    let packets = [
+        (create_tagged("", "select * from a", b""), "empty tag"),
+        (
+            create_tagged(
+                &"x".repeat(STagged::MAX_TAG_SIZE + 1),
+                "select * from a",
+                b"",
+            ),
+            "tag too large",
+        ),
+        (b"T7\n2\n\xff\xfe1\na".to_vec(), "tag that isn't a string"),
+        (b"T9\n2\nab9\nabc".to_vec(), "q window past the end"),
+    ];
+    for (packet, description) in packets {
+        assert_eq!(decode_tagged(&packet), None, "failed for `{description}`");
+    }
+}
+
+#[test]
+fn exchange_ping() {
+    // a ping is a single byte, even though that's less than the minimum read for a query
+    let state = QExchangeState::default();
+    assert!(state.has_reached_target(b"P"));
+    assert!(!state.has_reached_target(b"S"));
+    assert_eq!(
+        unsafe { exchange::resume(b"P", Resume::test_new(0), Default::default()) }.1,
+        QExchangeResult::Ping
+    );
+    // pipelining isn't supported
+    assert_eq!(
+        unsafe { exchange::resume(b"PS0\n0\n", Resume::test_new(0), Default::default()) }.1,
+        QExchangeResult::Error
+    );
+}
+
 #[test]
 fn num_accumulate() {
     let x = [
@@ -492,3 +1017,247 @@ fn num_accumulate() {
         assert_eq!(bs.cursor(), cursor);
     }
 }
+
+/*
+    fuzzing
+    ---
+    feed mutated (and entirely random) packets to the handshake and exchange decoders, delivering them in random
+    chunks just like the connection loop would. the decoders must never panic, and a completed query must be safe to
+    split and lex
+*/
+
+const FUZZ_ROUNDS: usize = if cfg!(miri) { 16 } else { 20_000 };
+
+fn fuzz_byte(extra: &[u8], rng: &mut impl Rng) -> u8 {
+    const INTERESTING: &[u8] = b"\0\x01\x02\xFF\n0123456789";
+    if test_utils::random_bool(rng) {
+        *[INTERESTING, extra]
+            .concat()
+            .get(test_utils::random_number(
+                0,
+                INTERESTING.len() + extra.len(),
+                rng,
+            ))
+            .unwrap()
+    } else {
+        rng.gen()
+    }
+}
+
+fn fuzz_input(valid: &[&[u8]], extra: &[u8], rng: &mut impl Rng) -> Vec<u8> {
+    if test_utils::random_number(0, 10, rng) == 0 {
+        let len = test_utils::random_number(0, 64, rng);
+        (0..len).map(|_| fuzz_byte(extra, rng)).collect()
+    } else {
+        let src = valid[test_utils::random_number(0, valid.len(), rng)];
+        test_utils::mutate(src, rng, |rng| fuzz_byte(extra, rng))
+    }
+}
+
+#[test]
+fn fuzz_handshake() {
+    let mut rng = test_utils::rng();
+    let valid: [&[u8]; 2] = [&FULL_HANDSHAKE_WITH_AUTH, b"H\0\0\0\0\0\n\n"];
+    for _ in 0..FUZZ_ROUNDS {
+        let input = fuzz_input(&valid, b"H", &mut rng);
+        fuzz::drive_handshake(&input, |remaining| {
+            test_utils::random_number(1, remaining + 1, &mut rng)
+        });
+    }
+}
+
+#[test]
+fn fuzz_exchange() {
+    let mut rng = test_utils::rng();
+    let valid = [
+        create_simple_query(SQ, ["\x05sayan"]),
+        create_simple_query(
+            "insert into myspace.mymodel(?, ?)",
+            ["\x05sayan", "\x02\x01"],
+        ),
+        create_simple_query("sysctl report status", []),
+        create_batch(
+            SBatch::MODE_STOP_ON_ERROR,
+            &[
+                create_simple_query(SQ, ["\x05sayan"]),
+                create_simple_query("sysctl report status", []),
+            ],
+        ),
+        create_bulk_insert("mymodel", 2, 1, b"\x065\nsayan\x02\x15\0\0\0\0\0\0\0"),
+        create_blob(SBlob::VERB_APPEND, b"3\nsayan"),
+        create_tagged("req-42", SQ, b"\x05sayan"),
+    ];
+    let mut valid: Vec<&[u8]> = valid.iter().map(Vec::as_slice).collect();
+    valid.push(b"P");
+    for _ in 0..FUZZ_ROUNDS {
+        let input = fuzz_input(&valid, b"SBIXRTP?", &mut rng);
+        fuzz::drive_exchange(&input, |remaining| {
+            test_utils::random_number(1, remaining + 1, &mut rng)
+        });
+    }
+}
+
+/*
+    statement stats
+*/
+
+#[test]
+fn statement_stats() {
+    let mut cstate = ClientLocalState::new("sayan".into(), false, STATIC_HANDSHAKE_WITH_AUTH);
+    assert_eq!(cstate.last_statement(), None);
+    cstate.set_rows_affected(3);
+    cstate.finish_statement(Duration::from_micros(420), None, 40, 1, vec![]);
+    let stats = cstate.last_statement().unwrap().clone();
+    assert_eq!(stats.rows_affected(), Some(3));
+    assert_eq!(
+        stats.describe(),
+        "{\"exec_us\":420,\"rows_returned\":null,\"rows_affected\":3,\"bytes_in\":40,\"bytes_out\":1,\"notices\":[],\"tag\":null}"
+    );
+    // rows affected are only counted for the statement that set them
+    cstate.finish_statement(Duration::from_micros(80), Some(10), 32, 512, vec![]);
+    let stats = cstate.last_statement().unwrap();
+    assert_eq!(
+        (stats.rows_returned(), stats.rows_affected()),
+        (Some(10), None)
+    );
+    // a tag is only kept for the statement that it was sent with
+    cstate.set_tag("4bf92f3577b34da6");
+    cstate.finish_statement(Duration::from_micros(10), None, 30, 1, vec![]);
+    let stats = cstate.last_statement().unwrap();
+    assert_eq!(stats.tag(), Some("4bf92f3577b34da6"));
+    assert!(stats.describe().ends_with(",\"tag\":\"4bf92f3577b34da6\"}"));
+    cstate.finish_statement(Duration::from_micros(10), None, 30, 1, vec![]);
+    assert_eq!(cstate.last_statement().unwrap().tag(), None);
+}
+
+/*
+    error detail
+*/
+
+#[test]
+fn error_detail_encoding() {
+    let e = QueryError::QExecDmlDuplicate
+        .with_detail("field", "username")
+        .with_detail("field", "ignored");
+    assert_eq!(e.category(), ErrorCategory::Constraint);
+    let detail = ErrorDetail::take(e);
+    assert_eq!(detail.entries(), &[("field", Box::from("username"))]);
+    assert_eq!(
+        encode_error_detail(&detail),
+        b"\x10\x6c\x00\x061\n5\nfield8\nusername"
+    );
+    // the detail was taken, and a detail for another error is never returned
+    let _ = QueryError::QExecObjectNotFound.with_detail("entity", "myspace.mymodel");
+    assert_eq!(
+        ErrorDetail::take(QueryError::QExecUnknownField),
+        ErrorDetail::new(QueryError::QExecUnknownField)
+    );
+    assert_eq!(ErrorDetail::take_any(), None);
+}
+
+/*
+    notices
+*/
+
+#[test]
+fn notices_encoding_and_stats() {
+    let notices = vec![
+        Notice::new(NoticeCode::LimitReached, "stopped"),
+        Notice::new(NoticeCode::UnfilteredMutation, "said \"hi\""),
+    ];
+    assert_eq!(
+        encode_notices(&notices),
+        b"\x142\n\x017\nstopped\x009\nsaid \"hi\""
+    );
+    let mut cstate = ClientLocalState::new("sayan".into(), false, STATIC_HANDSHAKE_WITH_AUTH);
+    cstate.finish_statement(Duration::from_micros(1), None, 1, 1, notices.clone());
+    let stats = cstate.last_statement().unwrap();
+    assert_eq!(stats.notices(), notices.as_slice());
+    assert!(stats
+        .describe()
+        .ends_with("\"notices\":[{\"code\":1,\"message\":\"stopped\"},{\"code\":0,\"message\":\"said \\\"hi\\\"\"}],\"tag\":null}"));
+}
+
+/*
+    response limits
+*/
+
+#[test]
+fn response_too_large() {
+    let limits = ResponseLimits::new(100, 0, 60);
+    let mut quota = ResponseQuota::new();
+    let now = Instant::now();
+    assert_eq!(quota.admit(limits, 100, now), Ok(()));
+    let e = quota.admit(limits, 101, now).unwrap_err();
+    assert_eq!(e, QueryError::QExecResponseTooLarge);
+    assert_eq!(
+        ErrorDetail::take(e).entries(),
+        &[
+            ("size", Box::from("101")),
+            ("limit", Box::from("100")),
+            (
+                "hint",
+                Box::from("paginate the query with `limit` and `offset`")
+            )
+        ]
+    );
+    // no limit
+    let limits = ResponseLimits::new(0, 0, 60);
+    assert_eq!(quota.admit(limits, u64::MAX, now), Ok(()));
+}
+
+#[test]
+fn response_quota_window() {
+    let limits = ResponseLimits::new(0, 1000, 60);
+    let mut quota = ResponseQuota::new();
+    let start = Instant::now();
+    assert_eq!(quota.admit(limits, 600, start), Ok(()));
+    quota.charge(limits, 600, start);
+    let later = start + Duration::from_secs(20);
+    assert_eq!(quota.admit(limits, 400, later), Ok(()));
+    let e = quota.admit(limits, 401, later).unwrap_err();
+    assert_eq!(e, QueryError::QExecResponseQuotaExceeded);
+    assert_eq!(
+        ErrorDetail::take(e).entries(),
+        &[
+            ("limit", Box::from("1000")),
+            ("window_secs", Box::from("60")),
+            ("retry_after_secs", Box::from("40")),
+            (
+                "hint",
+                Box::from("paginate the query with `limit` and `offset`")
+            )
+        ]
+    );
+    // the count is reset once the window has passed
+    let next_window = start + Duration::from_secs(60);
+    assert_eq!(quota.admit(limits, 1000, next_window), Ok(()));
+}
+
+#[test]
+fn response_soft_limits() {
+    let codes = || -> Vec<NoticeCode> { Notice::take_all().iter().map(Notice::code).collect() };
+    Notice::clear();
+    let limits = ResponseLimits::new(100, 1000, 60).with_soft_limit(SoftLimit::new(80));
+    let mut quota = ResponseQuota::new();
+    let start = Instant::now();
+    assert_eq!(quota.admit(limits, 80, start), Ok(()));
+    assert!(codes().is_empty());
+    // past 80% of the largest response
+    assert_eq!(quota.admit(limits, 81, start), Ok(()));
+    assert_eq!(codes(), [NoticeCode::SoftLimitReached]);
+    // past 80% of the quota, which is only warned about once in every window
+    for _ in 0..8 {
+        quota.charge(limits, 100, start);
+    }
+    assert!(codes().is_empty());
+    assert_eq!(quota.admit(limits, 50, start), Ok(()));
+    assert_eq!(codes(), [NoticeCode::SoftLimitReached]);
+    quota.charge(limits, 50, start);
+    assert_eq!(quota.admit(limits, 50, start), Ok(()));
+    assert!(codes().is_empty());
+    let next_window = start + Duration::from_secs(60);
+    quota.charge(limits, 900, next_window);
+    assert_eq!(quota.admit(limits, 50, next_window), Ok(()));
+    assert_eq!(codes(), [NoticeCode::SoftLimitReached]);
+}
@@ -0,0 +1,209 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Thread-per-core shards
+//!
+//! In the sharded runtime, every connection is handed off to one of several shards. Each shard is a single-threaded
+//! runtime on its own OS thread that is pinned to a core, so a connection (and all the queries it runs) stays on one
+//! core for its whole lifetime instead of being moved around by a work-stealing scheduler.
+//!
+//! With NUMA awareness, each shard additionally gets its own allocator arena and a local memory policy, so the rows
+//! and index nodes that a shard allocates come from the memory node of the core it is pinned to.
+
+use {
+    crate::{
+        engine::{error::RuntimeResult, mem::arena},
+        util::os,
+    },
+    std::{
+        future::Future,
+        io,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc, Arc,
+        },
+        thread::JoinHandle,
+    },
+    tokio::{
+        net::TcpStream,
+        runtime::{Builder, Handle},
+        sync::oneshot,
+    },
+};
+
+/// Decides where the task for a newly accepted connection runs
+#[derive(Clone)]
+pub enum ConnectionSpawner {
+    /// On the runtime that accepted the connection
+    Runtime,
+    /// On the next shard (round-robin)
+    Sharded(Arc<ShardSet>),
+}
+
+impl ConnectionSpawner {
+    /// Spawn the task returned by `f` for the given connection
+    pub fn spawn<F, Fut>(&self, stream: TcpStream, f: F)
+    where
+        F: FnOnce(TcpStream) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        match self {
+            Self::Runtime => {
+                tokio::spawn(f(stream));
+            }
+            Self::Sharded(shards) => {
+                // the socket is registered with the accepting runtime's reactor, so we deregister it here and
+                // register it again with the shard's reactor
+                let stream = match stream.into_std() {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("failed to hand off connection to shard: `{e}`");
+                        return;
+                    }
+                };
+                shards.next().spawn(async move {
+                    match TcpStream::from_std(stream) {
+                        Ok(stream) => f(stream).await,
+                        Err(e) => warn!("failed to hand off connection to shard: `{e}`"),
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// The handles to all running shards
+pub struct ShardSet {
+    handles: Vec<Handle>,
+    next: AtomicUsize,
+}
+
+impl ShardSet {
+    fn next(&self) -> &Handle {
+        let next = self.next.fetch_add(1, Ordering::Relaxed);
+        &self.handles[next % self.handles.len()]
+    }
+}
+
+/// A running shard
+struct ShardThread {
+    stop: oneshot::Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+/// The shards that serve connections in the sharded runtime
+pub struct Shards {
+    set: Arc<ShardSet>,
+    threads: Vec<ShardThread>,
+}
+
+impl Shards {
+    /// Start `count` shards, pinning the `n`th shard to the `n`th available core
+    pub fn start(count: usize, numa_aware: bool) -> RuntimeResult<Self> {
+        let cpus = os::allowed_cpus();
+        let nodes = if numa_aware { os::numa_nodes() } else { vec![] };
+        if numa_aware & nodes.is_empty() {
+            warn!("NUMA topology is not available. shards will not be NUMA aware");
+        }
+        let mut shards_per_node = vec![0usize; nodes.len()];
+        let mut handles = Vec::with_capacity(count);
+        let mut threads = Vec::with_capacity(count);
+        for id in 0..count {
+            let cpu = (!cpus.is_empty()).then(|| cpus[id % cpus.len()]);
+            let node = cpu.and_then(|cpu| nodes.iter().position(|node| node.cpus().contains(&cpu)));
+            if let Some(node) = node {
+                shards_per_node[node] += 1;
+            }
+            let (handle_tx, handle_rx) = mpsc::channel();
+            let (stop, stop_rx) = oneshot::channel::<()>();
+            let thread = std::thread::Builder::new()
+                .name(format!("shard-{id}"))
+                .spawn(move || {
+                    if !cpu.map_or(false, os::pin_current_thread) {
+                        warn!("could not pin shard-{id} to a core");
+                    }
+                    // the policy and the arena must be set up before the runtime allocates anything
+                    if node.is_some() {
+                        if !os::set_local_memory_policy() {
+                            warn!("could not set a local memory policy for shard-{id}");
+                        }
+                        if !arena::use_dedicated_arena() {
+                            warn!("could not create a dedicated allocator arena for shard-{id}");
+                        }
+                    } else {
+                        arena::bind_current_thread();
+                    }
+                    let runtime = match Builder::new_current_thread().enable_all().build() {
+                        Ok(rt) => rt,
+                        Err(e) => {
+                            let _ = handle_tx.send(Err(e));
+                            return;
+                        }
+                    };
+                    let _ = handle_tx.send(Ok(runtime.handle().clone()));
+                    // drive connection tasks until we're asked to stop
+                    runtime.block_on(async move {
+                        let _ = stop_rx.await;
+                    });
+                })?;
+            let handle = handle_rx.recv().unwrap_or_else(|_| {
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("shard-{id} exited during startup"),
+                ))
+            })?;
+            handles.push(handle);
+            threads.push(ShardThread { stop, thread });
+        }
+        for (node, shards) in nodes.iter().zip(shards_per_node) {
+            info!("NUMA node {}: {shards} shards", node.id());
+        }
+        Ok(Self {
+            set: Arc::new(ShardSet {
+                handles,
+                next: AtomicUsize::new(0),
+            }),
+            threads,
+        })
+    }
+    pub fn count(&self) -> usize {
+        self.threads.len()
+    }
+    pub fn spawner(&self) -> ConnectionSpawner {
+        ConnectionSpawner::Sharded(self.set.clone())
+    }
+    /// Stop all shards, cancelling any tasks that are still running on them
+    ///
+    /// WARN: This blocks until all shard threads have exited
+    pub fn shutdown(self) {
+        for ShardThread { stop, thread } in self.threads {
+            let _ = stop.send(());
+            if thread.join().is_err() {
+                error!("a shard thread panicked");
+            }
+        }
+    }
+}
@@ -66,6 +66,15 @@ impl<'a, Qd: QueryData<'a>> State<'a, Qd> {
     fn _entity_signature_match_cs(&self, a: &Token<'a>) -> bool {
         a.is_ident() & self.cs.is_some()
     }
+    /// Returns if we have a full entity (`space.model`) or a model name (with the current space set) at the cursor,
+    /// only looking at the tokens that we actually have
+    fn _entity_signature_match(&self) -> (bool, bool) {
+        let (i, remaining) = (self.cursor(), self.t.len().saturating_sub(self.cursor()));
+        let full = (remaining >= 3)
+            && Self::_entity_signature_match_self_full(&self.t[i], &self.t[i + 1], &self.t[i + 2]);
+        let cs = (remaining >= 1) && self._entity_signature_match_cs(&self.t[i]);
+        (full, cs)
+    }
     unsafe fn _entity_new_from_tokens(&mut self) -> EntityIDRef<'a> {
         let space = self.fw_read().uck_read_ident();
         self.cursor_ahead();
@@ -82,18 +91,16 @@ impl<'a, Qd: QueryData<'a>> State<'a, Qd> {
     pub fn unset_space(&mut self) {
         self.set_space_maybe(None)
     }
+    pub fn get_space(&self) -> Option<&'static str> {
+        self.cs
+    }
     #[cfg(test)]
     pub fn set_space(&mut self, s: &'static str) {
         self.set_space_maybe(Some(s));
     }
     pub fn try_entity_buffered_into_state_uninit(&mut self) -> MaybeInit<EntityIDRef<'a>> {
         let mut ret = MaybeInit::uninit();
-        let self_has_full = Self::_entity_signature_match_self_full(
-            &self.t[self.cursor()],
-            &self.t[self.cursor() + 1],
-            &self.t[self.cursor() + 2],
-        );
-        let self_has_full_cs = self._entity_signature_match_cs(&self.t[self.cursor()]);
+        let (self_has_full, self_has_full_cs) = self._entity_signature_match();
         unsafe {
             if self_has_full {
                 ret = MaybeInit::new(self._entity_new_from_tokens());
@@ -105,12 +112,7 @@ impl<'a, Qd: QueryData<'a>> State<'a, Qd> {
         ret
     }
     pub fn try_entity_ref(&mut self) -> Option<EntityIDRef<'a>> {
-        let self_has_full = Self::_entity_signature_match_self_full(
-            self.offset_current_r(0),
-            self.offset_current_r(1),
-            self.offset_current_r(2),
-        );
-        let self_has_pre_full = self._entity_signature_match_cs(self.offset_current_r(0));
+        let (self_has_full, self_has_pre_full) = self._entity_signature_match();
         if self_has_full {
             unsafe {
                 // UNSAFE(@ohsayan): +branch condition
@@ -308,6 +310,23 @@ impl<'a, Qd: QueryData<'a>> State<'a, Qd> {
     pub(crate) fn cursor_is_ident(&self) -> bool {
         self.read().is_ident()
     }
+    /// Consume a trailing `in background` (the last two tokens), returning true if there was one
+    pub(crate) fn consume_in_background(&mut self) -> bool {
+        let background = (self.remaining() == 2)
+            && Token![in].eq(self.offset_current_r(0))
+            && self.offset_current_r(1).ident_eq("background");
+        self.cursor_ahead_by(background as usize * 2);
+        background
+    }
+    /// Run `f` with only the next `len` tokens visible, so that a nested statement can be parsed as if it were all
+    /// that's left. The cursor is left wherever `f` leaves it
+    pub(crate) fn with_bounded<T>(&mut self, len: usize, f: impl FnOnce(&mut Self) -> T) -> T {
+        let t = self.t;
+        self.t = &t[..core::cmp::min(t.len(), self.i + len)];
+        let r = f(self);
+        self.t = t;
+        r
+    }
     #[inline(always)]
     fn round_cursor_up(&self, up: usize) -> usize {
         core::cmp::min(self.t.len() - 1, self.i + up)
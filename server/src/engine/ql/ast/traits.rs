@@ -104,7 +104,10 @@ pub trait ASTNode<'a>: Sized {
     fn from_insecure_tokens_full(tok: &'a [Token<'a>]) -> QueryResult<Self> {
         let mut state = State::new(tok, InplaceData::new());
         let r = <Self as ASTNode>::test_parse_from_state(&mut state)?;
-        assert!(state.exhausted());
+        // like the hardened parse, leftover tokens are an error
+        if state.not_exhausted() {
+            return Err(QueryError::QLInvalidSyntax);
+        }
         Ok(r)
     }
     #[cfg(test)]
@@ -115,7 +118,10 @@ pub trait ASTNode<'a>: Sized {
         let mut state = State::new(tok, InplaceData::new());
         state.set_space(space_name);
         let r = <Self as ASTNode>::test_parse_from_state(&mut state)?;
-        assert!(state.exhausted());
+        // like the hardened parse, leftover tokens are an error
+        if state.not_exhausted() {
+            return Err(QueryError::QLInvalidSyntax);
+        }
         Ok(r)
     }
     #[cfg(test)]
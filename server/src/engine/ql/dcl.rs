@@ -25,15 +25,23 @@
 */
 
 use crate::engine::{
-    data::DictGeneric,
+    core::EntityIDRef,
+    data::{lit::Lit, DictGeneric},
     error::{QueryError, QueryResult},
     ql::{
         ast::{traits, QueryData, State},
         ddl::syn,
-        lex::Ident,
+        lex::{Ident, Keyword, KeywordStmt, Token},
     },
 };
 
+#[derive(Debug, PartialEq)]
+/// What `sysctl alter quota` sets the quota of
+pub enum QuotaTarget<'a> {
+    User(Ident<'a>),
+    Model(EntityIDRef<'a>),
+}
+
 #[derive(Debug, PartialEq)]
 pub enum SysctlCommand<'a> {
     /// `sysctl create user ...`
@@ -44,11 +52,144 @@ pub enum SysctlCommand<'a> {
     AlterUser(UserDecl<'a>),
     /// `sysctl status`
     ReportStatus,
+    /// `sysctl report memory`
+    ReportMemory,
+    /// `sysctl report storage`
+    ReportStorage,
+    /// `sysctl report live`
+    ReportLive,
+    /// `sysctl report ready`
+    ReportReady,
+    /// `sysctl report last`
+    ReportLast,
+    /// `sysctl report profile`
+    ReportProfile,
+    /// `sysctl report scheduler`
+    ReportScheduler,
+    /// `sysctl report shardmap`
+    ReportShardmap,
+    /// `sysctl report prefetch`
+    ReportPrefetch,
+    /// `sysctl decode journal <model> [since <lsn>]`
+    DecodeJournal {
+        entity: EntityIDRef<'a>,
+        since: Option<u64>,
+    },
+    /// `sysctl verify model <model> [rebuild] [in background]`
+    VerifyModel {
+        entity: EntityIDRef<'a>,
+        rebuild: bool,
+        background: bool,
+    },
+    /// `sysctl backfill model <model> [in background]`
+    BackfillModel {
+        entity: EntityIDRef<'a>,
+        background: bool,
+    },
+    /// `sysctl generate rows <model> count <n> [seed <seed>]`
+    GenerateRows {
+        entity: EntityIDRef<'a>,
+        count: u64,
+        seed: Option<u64>,
+    },
+    /// `sysctl flush model <model>`
+    FlushModel { entity: EntityIDRef<'a> },
+    /// `sysctl copy model <model> from <host:port> [with credentials { ... }] [in background]`
+    CopyModel {
+        entity: EntityIDRef<'a>,
+        from: &'a str,
+        credentials: Option<DictGeneric>,
+        background: bool,
+    },
+    /// `sysctl restore model <model> from snapshot <path> as <model> [in background]`
+    RestoreModel {
+        entity: EntityIDRef<'a>,
+        snapshot: &'a str,
+        target: EntityIDRef<'a>,
+        background: bool,
+    },
+    /// `sysctl freeze [space <space>]`
+    Freeze { space: Option<Ident<'a>> },
+    /// `sysctl unfreeze [space <space>]`
+    Unfreeze { space: Option<Ident<'a>> },
+    /// `sysctl export gns <path>`
+    ExportGns { path: &'a str },
+    /// `sysctl export models <model>, ... into <path> [in background]`
+    ExportModels {
+        entities: Vec<EntityIDRef<'a>>,
+        path: &'a str,
+        background: bool,
+    },
+    /// `sysctl import gns <path>`
+    ImportGns { path: &'a str },
+    /// `sysctl import rdb <path> with { ... } [in background]`
+    ImportRdb {
+        path: &'a str,
+        mapping: DictGeneric,
+        background: bool,
+    },
+    /// `sysctl create webhook <name> on <model> with { url: ..., secret: ..., batch: ... }`
+    CreateWebhook {
+        name: Ident<'a>,
+        entity: EntityIDRef<'a>,
+        props: DictGeneric,
+    },
+    /// `sysctl drop webhook <name> on <model>`
+    DropWebhook {
+        name: Ident<'a>,
+        entity: EntityIDRef<'a>,
+    },
+    /// `sysctl report webhooks`
+    ReportWebhooks,
+    /// `sysctl alter quota (user <name> | model <model>) with { reads: ..., writes: ... }`
+    AlterQuota {
+        target: QuotaTarget<'a>,
+        props: DictGeneric,
+    },
+    /// `sysctl report quotas`
+    ReportQuotas,
+    /// `sysctl report indexes`
+    ReportIndexes,
+    /// `sysctl report hotkeys`
+    ReportHotKeys,
+    /// `sysctl list jobs`
+    ListJobs,
+    /// `sysctl list locks`
+    ListLocks,
+    /// `sysctl cancel job <id>`
+    CancelJob { id: u64 },
+    /// `sysctl record start <path> [anonymized]`
+    RecordStart { path: &'a str, anonymized: bool },
+    /// `sysctl record stop`
+    RecordStop,
+    /// `sysctl relocate data <path> [in background]`
+    RelocateData { path: &'a str, background: bool },
+    /// `sysctl create snapshot <name> [in background]`
+    CreateSnapshot { name: Ident<'a>, background: bool },
+    /// `sysctl create snapshot <name> every '<interval>' keep <n>`
+    ScheduleSnapshots {
+        name: Ident<'a>,
+        every: u64,
+        keep: u64,
+    },
+    /// `sysctl drop snapshot <name>`
+    DropSnapshot { name: Ident<'a> },
+    /// `sysctl drop snapshot schedule <name>`
+    DropSnapshotSchedule { name: Ident<'a> },
+    /// `sysctl list snapshots`
+    ListSnapshots,
 }
 
 impl<'a> SysctlCommand<'a> {
     pub fn needs_root(&self) -> bool {
-        !matches!(self, Self::ReportStatus)
+        !matches!(
+            self,
+            Self::ReportStatus
+                | Self::ReportLive
+                | Self::ReportReady
+                | Self::ReportLast
+                | Self::ReportShardmap
+        )
     }
 }
 
@@ -58,6 +199,12 @@ impl<'a> traits::ASTNode<'a> for SysctlCommand<'a> {
     fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
         state: &mut State<'a, Qd>,
     ) -> QueryResult<Self> {
+        // freezes are the only commands that can be a single word
+        if state.has_remaining(1)
+            && (state.read().ident_eq("freeze") | state.read().ident_eq("unfreeze"))
+        {
+            return parse_freeze(state);
+        }
         if state.remaining() < 2 {
             return Err(QueryError::QLUnexpectedEndOfStatement);
         }
@@ -66,7 +213,83 @@ impl<'a> traits::ASTNode<'a> for SysctlCommand<'a> {
         let create = Token![create].eq(a) & b.ident_eq("user");
         let drop = Token![drop].eq(a) & b.ident_eq("user");
         let status = a.ident_eq("report") & b.ident_eq("status");
-        if !(create | drop | status | alter) {
+        let memory = a.ident_eq("report") & b.ident_eq("memory");
+        let storage = a.ident_eq("report") & b.ident_eq("storage");
+        let live = a.ident_eq("report") & b.ident_eq("live");
+        let ready = a.ident_eq("report") & b.ident_eq("ready");
+        let last = a.ident_eq("report") & b.ident_eq("last");
+        let profile = a.ident_eq("report") & b.ident_eq("profile");
+        let scheduler = a.ident_eq("report") & b.ident_eq("scheduler");
+        let shardmap = a.ident_eq("report") & b.ident_eq("shardmap");
+        let prefetch = a.ident_eq("report") & b.ident_eq("prefetch");
+        let webhooks = a.ident_eq("report") & b.ident_eq("webhooks");
+        let indexes = a.ident_eq("report") & b.ident_eq("indexes");
+        let hotkeys = a.ident_eq("report") & b.ident_eq("hotkeys");
+        let quota = Token![alter].eq(a) & b.ident_eq("quota");
+        let quotas = a.ident_eq("report") & b.ident_eq("quotas");
+        let create_webhook = Token![create].eq(a) & b.ident_eq("webhook");
+        let drop_webhook = Token![drop].eq(a) & b.ident_eq("webhook");
+        let decode = a.ident_eq("decode") & b.ident_eq("journal");
+        let verify = a.ident_eq("verify") & Token![model].eq(b);
+        let backfill = a.ident_eq("backfill") & Token![model].eq(b);
+        let generate = a.ident_eq("generate") & b.ident_eq("rows");
+        let flush = a.ident_eq("flush") & Token![model].eq(b);
+        let copy = a.ident_eq("copy") & Token![model].eq(b);
+        let restore = a.ident_eq("restore") & Token![model].eq(b);
+        let export = a.ident_eq("export") & b.ident_eq("gns");
+        let export_models = a.ident_eq("export") & b.ident_eq("models");
+        let import = a.ident_eq("import") & b.ident_eq("gns");
+        let rdb = a.ident_eq("import") & b.ident_eq("rdb");
+        let jobs = a.ident_eq("list") & b.ident_eq("jobs");
+        let locks = a.ident_eq("list") & b.ident_eq("locks");
+        let cancel = a.ident_eq("cancel") & b.ident_eq("job");
+        let record_start = a.ident_eq("record") & b.ident_eq("start");
+        let record_stop = a.ident_eq("record") & b.ident_eq("stop");
+        let relocate = a.ident_eq("relocate") & b.ident_eq("data");
+        let create_snapshot = Token![create].eq(a) & b.ident_eq("snapshot");
+        let drop_snapshot = Token![drop].eq(a) & b.ident_eq("snapshot");
+        let snapshots = a.ident_eq("list") & b.ident_eq("snapshots");
+        if !(create
+            | drop
+            | status
+            | memory
+            | storage
+            | live
+            | ready
+            | last
+            | profile
+            | scheduler
+            | shardmap
+            | prefetch
+            | webhooks
+            | indexes
+            | hotkeys
+            | quota
+            | quotas
+            | create_webhook
+            | drop_webhook
+            | alter
+            | decode
+            | verify
+            | backfill
+            | generate
+            | flush
+            | copy
+            | restore
+            | export
+            | export_models
+            | import
+            | rdb
+            | jobs
+            | locks
+            | cancel
+            | record_start
+            | record_stop
+            | relocate
+            | create_snapshot
+            | drop_snapshot
+            | snapshots)
+        {
             return Err(QueryError::QLUnknownStatement);
         }
         if create {
@@ -75,12 +298,587 @@ impl<'a> traits::ASTNode<'a> for SysctlCommand<'a> {
             UserDel::parse(state).map(SysctlCommand::DropUser)
         } else if alter {
             UserDecl::parse(state).map(SysctlCommand::AlterUser)
+        } else if memory {
+            Ok(SysctlCommand::ReportMemory)
+        } else if storage {
+            Ok(SysctlCommand::ReportStorage)
+        } else if live {
+            Ok(SysctlCommand::ReportLive)
+        } else if ready {
+            Ok(SysctlCommand::ReportReady)
+        } else if last {
+            Ok(SysctlCommand::ReportLast)
+        } else if profile {
+            Ok(SysctlCommand::ReportProfile)
+        } else if scheduler {
+            Ok(SysctlCommand::ReportScheduler)
+        } else if shardmap {
+            Ok(SysctlCommand::ReportShardmap)
+        } else if prefetch {
+            Ok(SysctlCommand::ReportPrefetch)
+        } else if webhooks {
+            Ok(SysctlCommand::ReportWebhooks)
+        } else if indexes {
+            Ok(SysctlCommand::ReportIndexes)
+        } else if hotkeys {
+            Ok(SysctlCommand::ReportHotKeys)
+        } else if quota {
+            parse_alter_quota(state)
+        } else if quotas {
+            Ok(SysctlCommand::ReportQuotas)
+        } else if create_webhook | drop_webhook {
+            parse_webhook(state, create_webhook)
+        } else if decode {
+            parse_decode_journal(state)
+        } else if verify {
+            parse_verify_model(state)
+        } else if backfill {
+            let entity = state.try_entity_ref_result()?;
+            let background = state.consume_in_background();
+            Ok(SysctlCommand::BackfillModel { entity, background })
+        } else if generate {
+            parse_generate_rows(state)
+        } else if flush {
+            state
+                .try_entity_ref_result()
+                .map(|entity| SysctlCommand::FlushModel { entity })
+        } else if copy {
+            parse_copy_model(state)
+        } else if restore {
+            parse_restore_model(state)
+        } else if export | import {
+            parse_gns_path(state, export)
+        } else if export_models {
+            parse_export_models(state)
+        } else if rdb {
+            parse_import_rdb(state)
+        } else if jobs {
+            Ok(SysctlCommand::ListJobs)
+        } else if locks {
+            Ok(SysctlCommand::ListLocks)
+        } else if cancel {
+            parse_cancel_job(state)
+        } else if record_start {
+            parse_record_start(state)
+        } else if record_stop {
+            Ok(SysctlCommand::RecordStop)
+        } else if relocate {
+            parse_relocate_data(state)
+        } else if create_snapshot | drop_snapshot {
+            parse_snapshot(state, create_snapshot)
+        } else if snapshots {
+            Ok(SysctlCommand::ListSnapshots)
         } else {
             Ok(SysctlCommand::ReportStatus)
         }
     }
 }
 
+fn parse_freeze<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        (freeze | unfreeze) [space <space>]
+        ^cursor
+    */
+    let freeze = state.fw_read().ident_eq("freeze");
+    let mut space = None;
+    if state.not_exhausted() {
+        if state.remaining() < 2 {
+            return Err(QueryError::QLInvalidSyntax);
+        }
+        match (state.fw_read(), state.fw_read()) {
+            (Token![space], Token::Ident(name)) => space = Some(*name),
+            _ => return Err(QueryError::QLInvalidSyntax),
+        }
+    }
+    Ok(if freeze {
+        SysctlCommand::Freeze { space }
+    } else {
+        SysctlCommand::Unfreeze { space }
+    })
+}
+
+fn parse_decode_journal<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [model] since [lsn]
+        ^cursor
+    */
+    let entity = state.try_entity_ref_result()?;
+    let mut since = None;
+    if state.has_remaining(1) && state.read().ident_eq("since") {
+        state.cursor_ahead();
+        state.poison_if_not(state.can_read_lit_rounded());
+        if state.okay() {
+            since = unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.read_cursor_lit_unchecked()
+            }
+            .try_uint();
+            state.poison_if(since.is_none());
+            state.cursor_ahead();
+        }
+    }
+    if state.okay() {
+        Ok(SysctlCommand::DecodeJournal { entity, since })
+    } else {
+        Err(QueryError::QLInvalidSyntax)
+    }
+}
+
+fn parse_generate_rows<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [model] count [n] seed [seed]
+        ^cursor
+    */
+    let entity = state.try_entity_ref_result()?;
+    // a missing keyword is fine (unless it's the count), but a keyword without a valid value isn't
+    let mut read_uint = |keyword: &str| {
+        if !(state.has_remaining(1) && state.read().ident_eq(keyword)) {
+            return Ok(None);
+        }
+        state.cursor_ahead();
+        if !(state.not_exhausted() && state.can_read_lit_rounded()) {
+            return Err(QueryError::QLInvalidSyntax);
+        }
+        let v = unsafe {
+            // UNSAFE(@ohsayan): verified above
+            state.read_cursor_lit_unchecked()
+        }
+        .try_uint();
+        state.cursor_ahead();
+        v.map(Some).ok_or(QueryError::QLInvalidSyntax)
+    };
+    let count = read_uint("count")?.ok_or(QueryError::QLInvalidSyntax)?;
+    let seed = read_uint("seed")?;
+    if state.not_exhausted() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    Ok(SysctlCommand::GenerateRows {
+        entity,
+        count,
+        seed,
+    })
+}
+
+fn parse_copy_model<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [model] from [host:port] with credentials { username: [username], password: [password] } in background
+        ^cursor
+    */
+    let entity = state.try_entity_ref_result()?;
+    if !(state.has_remaining(2) && Token![from].eq(state.read())) {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead();
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let from = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str()
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    let mut credentials = None;
+    if state.has_remaining(3)
+        && Token![with].eq(state.read())
+        && state.offset_current_r(1).ident_eq("credentials")
+        && Token![open {}].eq(state.offset_current_r(2))
+    {
+        state.cursor_ahead_by(2);
+        let Some(dict) = syn::parse_dict(state) else {
+            return Err(QueryError::QLInvalidCollectionSyntax);
+        };
+        credentials = Some(dict);
+    }
+    let background = state.consume_in_background();
+    Ok(SysctlCommand::CopyModel {
+        entity,
+        from,
+        credentials,
+        background,
+    })
+}
+
+fn parse_restore_model<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [model] from snapshot [path] as [model] in background
+        ^cursor
+    */
+    let entity = state.try_entity_ref_result()?;
+    if !(state.has_remaining(3)
+        && Token![from].eq(state.read())
+        && state.offset_current_r(1).ident_eq("snapshot"))
+    {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead_by(2);
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let snapshot = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str()
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    if !(state.has_remaining(2) && Token![as].eq(state.read())) {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead();
+    let target = state.try_entity_ref_result()?;
+    let background = state.consume_in_background();
+    Ok(SysctlCommand::RestoreModel {
+        entity,
+        snapshot,
+        target,
+        background,
+    })
+}
+
+fn parse_export_models<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [model], [model] ... into [path] in background
+        ^cursor
+    */
+    let mut entities = vec![state.try_entity_ref_result()?];
+    while state.has_remaining(1) && Token![,].eq(state.read()) {
+        state.cursor_ahead();
+        entities.push(state.try_entity_ref_result()?);
+    }
+    if !(state.has_remaining(2) && Token![into].eq(state.read())) {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead();
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let path = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str()
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    let background = state.consume_in_background();
+    Ok(SysctlCommand::ExportModels {
+        entities,
+        path,
+        background,
+    })
+}
+
+fn parse_import_rdb<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [path] with { strings: [model], hashes: [model], lists: [model], database: [db] } in background
+        ^cursor
+    */
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let path = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str()
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    if !(state.has_remaining(2)
+        && Token![with].eq(state.read())
+        && Token![open {}].eq(state.offset_current_r(1)))
+    {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead();
+    let Some(mapping) = syn::parse_dict(state) else {
+        return Err(QueryError::QLInvalidCollectionSyntax);
+    };
+    let background = state.consume_in_background();
+    Ok(SysctlCommand::ImportRdb {
+        path,
+        mapping,
+        background,
+    })
+}
+
+fn parse_webhook<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+    create: bool,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [name] on [model] with { url: [url], secret: [secret], batch: [events] }
+        ^cursor
+    */
+    if !(state.has_remaining(3)
+        && state.cursor_is_ident()
+        && Token![on].eq(state.offset_current_r(1)))
+    {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let name = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.fw_read().uck_read_ident()
+    };
+    state.cursor_ahead(); // on
+    let entity = state.try_entity_ref_result()?;
+    if !create {
+        return Ok(SysctlCommand::DropWebhook { name, entity });
+    }
+    if !(state.has_remaining(2)
+        && Token![with].eq(state.read())
+        && Token![open {}].eq(state.offset_current_r(1)))
+    {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead();
+    let Some(props) = syn::parse_dict(state) else {
+        return Err(QueryError::QLInvalidCollectionSyntax);
+    };
+    Ok(SysctlCommand::CreateWebhook {
+        name,
+        entity,
+        props,
+    })
+}
+
+fn parse_alter_quota<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        (user [name] | model [model]) with { reads: [statements], writes: [statements] }
+        ^cursor
+    */
+    if !state.has_remaining(2) {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let target =
+        if state.read().ident_eq("user") && matches!(state.offset_current_r(1), Token::Ident(_)) {
+            state.cursor_ahead();
+            let username = unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.fw_read().uck_read_ident()
+            };
+            QuotaTarget::User(username)
+        } else if Token![model].eq(state.read()) {
+            state.cursor_ahead();
+            QuotaTarget::Model(state.try_entity_ref_result()?)
+        } else {
+            return Err(QueryError::QLInvalidSyntax);
+        };
+    if !(state.has_remaining(2)
+        && Token![with].eq(state.read())
+        && Token![open {}].eq(state.offset_current_r(1)))
+    {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead();
+    let Some(props) = syn::parse_dict(state) else {
+        return Err(QueryError::QLInvalidCollectionSyntax);
+    };
+    Ok(SysctlCommand::AlterQuota { target, props })
+}
+
+fn parse_gns_path<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+    export: bool,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [path]
+        ^cursor
+    */
+    if !(state.can_read_lit_rounded() & (state.remaining() == 1)) {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let path = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str();
+    state.cursor_ahead();
+    match path {
+        Some(path) if export => Ok(SysctlCommand::ExportGns { path }),
+        Some(path) => Ok(SysctlCommand::ImportGns { path }),
+        None => Err(QueryError::QLInvalidSyntax),
+    }
+}
+
+fn parse_verify_model<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [model] rebuild in background
+        ^cursor
+    */
+    let entity = state.try_entity_ref_result()?;
+    let rebuild = state.has_remaining(1) && state.read().ident_eq("rebuild");
+    if rebuild {
+        state.cursor_ahead();
+    }
+    let background = state.consume_in_background();
+    Ok(SysctlCommand::VerifyModel {
+        entity,
+        rebuild,
+        background,
+    })
+}
+
+fn parse_cancel_job<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [id]
+        ^cursor
+    */
+    if !(state.can_read_lit_rounded() & (state.remaining() == 1)) {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let id = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_uint();
+    state.cursor_ahead();
+    match id {
+        Some(id) => Ok(SysctlCommand::CancelJob { id }),
+        None => Err(QueryError::QLInvalidSyntax),
+    }
+}
+
+fn parse_record_start<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [path] anonymized
+        ^cursor
+    */
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let path = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str()
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    let anonymized = state.has_remaining(1) && state.read().ident_eq("anonymized");
+    if anonymized {
+        state.cursor_ahead();
+    }
+    Ok(SysctlCommand::RecordStart { path, anonymized })
+}
+
+fn parse_relocate_data<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        [path] in background
+        ^cursor
+    */
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let path = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str()
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    let background = state.consume_in_background();
+    Ok(SysctlCommand::RelocateData { path, background })
+}
+
+fn parse_snapshot<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+    create: bool,
+) -> QueryResult<SysctlCommand<'a>> {
+    /*
+        create: [name] every [interval] keep [n] | [name] in background
+        drop: schedule [name] | [name]
+        ^cursor
+    */
+    if !create && state.remaining() == 2 && state.read().ident_eq("schedule") {
+        state.cursor_ahead();
+        return match state.fw_read() {
+            Token::Ident(name) => Ok(SysctlCommand::DropSnapshotSchedule { name: *name }),
+            _ => Err(QueryError::QLInvalidSyntax),
+        };
+    }
+    if state.exhausted() {
+        return Err(QueryError::QLUnexpectedEndOfStatement);
+    }
+    let name = match state.fw_read() {
+        Token::Ident(name) => *name,
+        _ => return Err(QueryError::QLInvalidSyntax),
+    };
+    if !create {
+        return Ok(SysctlCommand::DropSnapshot { name });
+    }
+    if !(state.has_remaining(4) && state.read().ident_eq("every")) {
+        let background = state.consume_in_background();
+        return Ok(SysctlCommand::CreateSnapshot { name, background });
+    }
+    state.cursor_ahead();
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let every = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_str()
+    .and_then(parse_interval)
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    if !state.read().ident_eq("keep") {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    state.cursor_ahead();
+    if !state.can_read_lit_rounded() {
+        return Err(QueryError::QLInvalidSyntax);
+    }
+    let keep = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_uint()
+    .ok_or(QueryError::QLInvalidSyntax)?;
+    state.cursor_ahead();
+    Ok(SysctlCommand::ScheduleSnapshots { name, every, keep })
+}
+
+/// Returns the number of seconds in an interval like `90s`, `15m`, `6h` or `1d`
+fn parse_interval(interval: &str) -> Option<u64> {
+    let unit = match interval.as_bytes().last()? {
+        b's' => 1,
+        b'm' => 60,
+        b'h' => 60 * 60,
+        b'd' => 24 * 60 * 60,
+        _ => return None,
+    };
+    let n = &interval[..interval.len() - 1];
+    if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    n.parse::<u64>().ok()?.checked_mul(unit)
+}
+
 fn parse<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<UserMeta<'a>> {
     /*
         [username] with { password: [password], ... }
@@ -171,3 +969,208 @@ impl<'a> UserDel<'a> {
         self.username.as_str()
     }
 }
+
+/*
+    stored statements
+*/
+
+/// The maximum number of arguments that can be passed to a stored statement
+pub const EXECUTE_MAX_ARGS: usize = 64;
+
+#[derive(Debug, PartialEq)]
+/// `create statement <name> as <statement>`: store a DML statement for the current user. The body can't have any
+/// values; every value is a placeholder (`?`) that is bound when the statement is run with `execute`
+pub struct CreateStatement<'a> {
+    name: Ident<'a>,
+    /// the body, rebuilt from its tokens
+    body: String,
+}
+
+impl<'a> CreateStatement<'a> {
+    #[cfg(test)]
+    pub fn new(name: Ident<'a>, body: String) -> Self {
+        Self { name, body }
+    }
+    /// Parse a stored statement's definition
+    ///
+    /// MUSTENDSTREAM: YES
+    pub fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            [name] as [statement]
+            ^cursor
+        */
+        if state.remaining() < 3 {
+            return Err(QueryError::QLUnexpectedEndOfStatement);
+        }
+        if !(state.cursor_has_ident_rounded() & Token![as].eq(&state.current()[1])) {
+            return Err(QueryError::QLInvalidSyntax);
+        }
+        let name = unsafe {
+            // UNSAFE(@ohsayan): +boundck
+            state.fw_read().uck_read_ident()
+        };
+        state.cursor_ahead();
+        if !matches!(
+            state.read(),
+            Token::Keyword(Keyword::Statement(
+                KeywordStmt::Insert
+                    | KeywordStmt::Select
+                    | KeywordStmt::Update
+                    | KeywordStmt::Delete
+                    | KeywordStmt::Exists
+            ))
+        ) {
+            // only DML can be stored
+            return Err(QueryError::QLUnknownStatement);
+        }
+        let mut body = String::new();
+        while state.not_exhausted() {
+            let tok = state.fw_read();
+            if state.can_read_lit_from(tok) {
+                return Err(QueryError::QLInvalidSyntax);
+            }
+            if !body.is_empty() {
+                body.push(' ');
+            }
+            body.push_str(&tok.to_string());
+        }
+        Ok(Self { name, body })
+    }
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+    pub fn into_body(self) -> String {
+        self.body
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// `drop statement <name>`
+pub struct DropStatement<'a> {
+    name: Ident<'a>,
+}
+
+impl<'a> DropStatement<'a> {
+    #[cfg(test)]
+    pub fn new(name: Ident<'a>) -> Self {
+        Self { name }
+    }
+    /// Parse the name of the stored statement to drop
+    ///
+    /// MUSTENDSTREAM: YES
+    pub fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        if state.cursor_has_ident_rounded() & (state.remaining() == 1) {
+            let name = unsafe {
+                // UNSAFE(@ohsayan): +boundck
+                state.fw_read().uck_read_ident()
+            };
+            return Ok(Self { name });
+        }
+        Err(QueryError::QLInvalidSyntax)
+    }
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// `execute <name> [(<arg>, ...)]`: run a stored statement, binding the arguments to its placeholders in order. A
+/// `null` argument is [`None`]
+pub struct ExecuteStatement<'a> {
+    name: Ident<'a>,
+    args: Vec<Option<Lit<'a>>>,
+}
+
+impl<'a> ExecuteStatement<'a> {
+    #[cfg(test)]
+    pub fn new(name: Ident<'a>, args: Vec<Option<Lit<'a>>>) -> Self {
+        Self { name, args }
+    }
+    /// Parse the name of the stored statement to run, along with its arguments
+    ///
+    /// MUSTENDSTREAM: YES
+    pub fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            [name] [( [arg] {, [arg]} )]
+            ^cursor
+        */
+        if state.exhausted() {
+            return Err(QueryError::QLUnexpectedEndOfStatement);
+        }
+        if !state.cursor_has_ident_rounded() {
+            return Err(QueryError::QLInvalidSyntax);
+        }
+        let name = unsafe {
+            // UNSAFE(@ohsayan): +boundck
+            state.fw_read().uck_read_ident()
+        };
+        let mut args = vec![];
+        if state.not_exhausted() {
+            state.poison_if_not(state.cursor_eq(Token![() open]));
+            state.cursor_ahead();
+            let mut close = state.cursor_rounded_eq(Token![() close]);
+            state.cursor_ahead_if(close);
+            while state.not_exhausted() & state.okay() & !close & (args.len() < EXECUTE_MAX_ARGS) {
+                if state.cursor_eq(Token![null]) {
+                    args.push(None);
+                } else {
+                    state.poison_if_not(state.can_read_lit_rounded());
+                    if !state.okay() {
+                        break;
+                    }
+                    args.push(Some(unsafe {
+                        // UNSAFE(@ohsayan): verified above
+                        state.read_cursor_lit_unchecked()
+                    }));
+                }
+                state.cursor_ahead();
+                let comma = state.cursor_rounded_eq(Token![,]);
+                close = state.cursor_rounded_eq(Token![() close]);
+                state.poison_if_not(comma | close);
+                state.cursor_ahead_if(comma | close);
+            }
+            state.poison_if_not(close);
+        }
+        if state.okay() {
+            Ok(Self { name, args })
+        } else {
+            Err(QueryError::QLInvalidSyntax)
+        }
+    }
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+    pub fn args(&self) -> &[Option<Lit<'a>>] {
+        &self.args
+    }
+}
+
+impl<'a> traits::ASTNode<'a> for CreateStatement<'a> {
+    const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+    const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = true;
+    fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+    ) -> QueryResult<Self> {
+        Self::parse(state)
+    }
+}
+
+impl<'a> traits::ASTNode<'a> for DropStatement<'a> {
+    const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+    const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = true;
+    fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+    ) -> QueryResult<Self> {
+        Self::parse(state)
+    }
+}
+
+impl<'a> traits::ASTNode<'a> for ExecuteStatement<'a> {
+    const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+    const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+    fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+    ) -> QueryResult<Self> {
+        Self::parse(state)
+    }
+}
@@ -120,6 +120,9 @@ impl<'a> AlterModel<'a> {
             // FIXME(@ohsayan): bad because no specificity
         }
         let model_name = state.try_entity_ref_result()?;
+        if compiler::unlikely(state.exhausted()) {
+            return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
+        }
         let kind = match state.fw_read() {
             Token![add] => AlterKind::alter_add(state),
             Token![remove] => AlterKind::alter_remove(state),
@@ -33,6 +33,7 @@ use {
             error::{QueryError, QueryResult},
             ql::{
                 ast::{QueryData, State},
+                dml::{sel::SelectStatement, WhereClause},
                 lex::Ident,
             },
         },
@@ -180,9 +181,300 @@ impl<'a> CreateModel<'a> {
     }
 }
 
+#[derive(Debug, PartialEq)]
+/// A copy of a model
+pub struct CreateModelCopy<'a> {
+    /// the name of the new model
+    pub(in crate::engine) model_name: EntityIDRef<'a>,
+    /// the model that is copied
+    pub(in crate::engine) source: EntityIDRef<'a>,
+    /// if set, the rows are copied too
+    pub(in crate::engine) with_data: bool,
+    /// if not exists
+    pub(in crate::engine) if_not_exists: bool,
+}
+
+/*
+    model copy:
+    create model [if not exists] myspace.newmodel as copy of myspace.mymodel [with data]
+*/
+
+impl<'a> CreateModelCopy<'a> {
+    #[cfg(test)]
+    pub fn new(
+        model_name: EntityIDRef<'a>,
+        source: EntityIDRef<'a>,
+        with_data: bool,
+        if_not_exists: bool,
+    ) -> Self {
+        Self {
+            model_name,
+            source,
+            with_data,
+            if_not_exists,
+        }
+    }
+    /// Returns true if the `create model` statement that follows is a copy (and not a definition)
+    pub fn is_next<Qd: QueryData<'a>>(state: &State<'a, Qd>) -> bool {
+        state
+            .current()
+            .windows(2)
+            .any(|w| Token![as].eq(&w[0]) & w[1].ident_eq("copy"))
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        // smallest declaration: `create model newmodel as copy of mymodel` -> >= 5 tokens
+        if compiler::unlikely(state.remaining() < 5) {
+            return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
+        }
+        // if not exists?
+        let if_not_exists = sig_if_not_exists(state);
+        state.cursor_ahead_by(if_not_exists as usize * 3);
+        // model name; ignore errors
+        let model_uninit = state.try_entity_buffered_into_state_uninit();
+        state.poison_if_not(state.cursor_rounded_eq(Token![as]));
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.cursor_has_ident_rounded() && state.read().ident_eq("copy"));
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.cursor_rounded_eq(Token![of]));
+        state.cursor_ahead_if(state.okay());
+        if compiler::unlikely(!state.okay()) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        // source name; ignore errors
+        let source_uninit = state.try_entity_buffered_into_state_uninit();
+        let with_data = state.cursor_rounded_eq(Token![with]);
+        if with_data {
+            state.cursor_ahead();
+            state.poison_if_not(state.cursor_has_ident_rounded() && state.read().ident_eq("data"));
+            state.cursor_ahead_if(state.okay());
+        }
+        if state.okay() {
+            unsafe {
+                Ok(Self {
+                    // UNSAFE(@ohsayan): we verified if `model_name` is initialized through the state
+                    model_name: model_uninit.assume_init(),
+                    // UNSAFE(@ohsayan): we verified if `source` is initialized through the state
+                    source: source_uninit.assume_init(),
+                    with_data,
+                    if_not_exists,
+                })
+            }
+        } else {
+            Err(QueryError::QLInvalidSyntax)
+        }
+    }
+}
+
+/// Parse a parenthesized, non-empty list of fields: `(field, ...)`
+fn parse_field_list<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Vec<Ident<'a>> {
+    let mut fields = Vec::new();
+    state.poison_if_not(state.cursor_rounded_eq(Token![() open]));
+    state.cursor_ahead_if(state.okay());
+    let mut nx_comma = true;
+    while state.okay() && nx_comma && state.cursor_has_ident_rounded() {
+        fields.push(unsafe {
+            // UNSAFE(@ohsayan): verified in loop condition
+            state.fw_read().uck_read_ident()
+        });
+        nx_comma = state.cursor_rounded_eq(Token![,]);
+        state.cursor_ahead_if(nx_comma);
+    }
+    // we can't have an empty list or a trailing comma
+    let nx_close = state.cursor_rounded_eq(Token![() close]);
+    state.poison_if(nx_comma | !nx_close);
+    state.cursor_ahead_if(nx_close);
+    fields
+}
+
+#[derive(Debug, PartialEq)]
+/// A secondary index definition
+pub struct CreateIndex<'a> {
+    /// the index name
+    pub(in crate::engine) index_name: Ident<'a>,
+    /// the model
+    pub(in crate::engine) model_name: EntityIDRef<'a>,
+    /// the indexed fields (a composite index has more than one)
+    pub(in crate::engine) fields: Vec<Ident<'a>>,
+    /// the fields that a covering index stores along with each key
+    pub(in crate::engine) include: Vec<Ident<'a>>,
+    /// properties
+    pub(in crate::engine) props: DictGeneric,
+    /// the predicate of a partial index
+    pub(in crate::engine) predicate: Option<WhereClause<'a>>,
+    /// if not exists
+    pub(in crate::engine) if_not_exists: bool,
+    /// build the index in a background job
+    pub(in crate::engine) background: bool,
+}
+
+/*
+    index definition:
+    create index [if not exists] myindex on myspace.mymodel(field, ...) [include (field, ...)] [with { ... }]
+        [where <clauses>] [in background]
+*/
+
+impl<'a> CreateIndex<'a> {
+    #[cfg(test)]
+    pub fn new(
+        index_name: Ident<'a>,
+        model_name: EntityIDRef<'a>,
+        fields: Vec<Ident<'a>>,
+        if_not_exists: bool,
+    ) -> Self {
+        Self {
+            index_name,
+            model_name,
+            fields,
+            include: Vec::new(),
+            props: DictGeneric::new(),
+            predicate: None,
+            if_not_exists,
+            background: false,
+        }
+    }
+    #[cfg(test)]
+    pub fn in_background(self) -> Self {
+        Self {
+            background: true,
+            ..self
+        }
+    }
+    #[cfg(test)]
+    pub fn with_include(self, include: Vec<Ident<'a>>) -> Self {
+        Self { include, ..self }
+    }
+    #[cfg(test)]
+    pub fn with_props(self, props: DictGeneric) -> Self {
+        Self { props, ..self }
+    }
+    #[cfg(test)]
+    pub fn with_predicate(self, predicate: WhereClause<'a>) -> Self {
+        Self {
+            predicate: Some(predicate),
+            ..self
+        }
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        // smallest declaration: `create index myindex on mymodel(field)` -> >= 6 tokens
+        if compiler::unlikely(state.remaining() < 6) {
+            return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
+        }
+        // if not exists?
+        let if_not_exists = sig_if_not_exists(state);
+        state.cursor_ahead_by(if_not_exists as usize * 3);
+        if compiler::unlikely(state.remaining() < 6) {
+            return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
+        }
+        // index name
+        let index_name = state.fw_read();
+        state.poison_if_not(index_name.is_ident());
+        state.poison_if_not(state.cursor_eq(Token![on]));
+        state.cursor_ahead();
+        // model name; ignore errors
+        let model_uninit = state.try_entity_buffered_into_state_uninit();
+        // fields
+        let fields = parse_field_list(state);
+        // included fields?
+        let mut include = Vec::new();
+        if state.okay() && state.not_exhausted() && state.read().ident_eq("include") {
+            state.cursor_ahead();
+            include = parse_field_list(state);
+        }
+        // properties?
+        let mut props = DictGeneric::new();
+        if state.okay() && state.cursor_rounded_eq(Token![with]) {
+            state.cursor_ahead();
+            syn::rfold_dict(DictFoldState::OB, state, &mut props);
+        }
+        // predicate?
+        let mut predicate = None;
+        if state.cursor_rounded_eq(Token![where]) {
+            state.cursor_ahead();
+            predicate = Some(WhereClause::parse_where(state));
+        }
+        let background = state.consume_in_background();
+        if state.okay() {
+            unsafe {
+                Ok(Self {
+                    // UNSAFE(@ohsayan): checked with `is_ident` above
+                    index_name: index_name.uck_read_ident(),
+                    // UNSAFE(@ohsayan): we verified if `model_name` is initialized through the state
+                    model_name: model_uninit.assume_init(),
+                    fields,
+                    include,
+                    props,
+                    predicate,
+                    if_not_exists,
+                    background,
+                })
+            }
+        } else {
+            Err(QueryError::QLInvalidSyntax)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// A view
+pub struct CreateView<'a> {
+    /// the view name
+    pub(in crate::engine) view_name: EntityIDRef<'a>,
+    /// the query that selects the rows (and fields) of the view
+    pub(in crate::engine) select: SelectStatement<'a>,
+    /// if not exists
+    pub(in crate::engine) if_not_exists: bool,
+}
+
+/*
+    view definition:
+    create view [if not exists] myspace.myview as select <fields> from myspace.mymodel [where <clauses>]
+*/
+
+impl<'a> CreateView<'a> {
+    #[cfg(test)]
+    pub fn new(
+        view_name: EntityIDRef<'a>,
+        select: SelectStatement<'a>,
+        if_not_exists: bool,
+    ) -> Self {
+        Self {
+            view_name,
+            select,
+            if_not_exists,
+        }
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        // smallest declaration: `create view myview as select * from mymodel` -> >= 6 tokens
+        if compiler::unlikely(state.remaining() < 6) {
+            return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
+        }
+        // if not exists?
+        let if_not_exists = sig_if_not_exists(state);
+        state.cursor_ahead_by(if_not_exists as usize * 3);
+        // view name; ignore errors
+        let view_uninit = state.try_entity_buffered_into_state_uninit();
+        state.poison_if_not(state.cursor_rounded_eq(Token![as]));
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.cursor_rounded_eq(Token![select]));
+        state.cursor_ahead_if(state.okay());
+        if compiler::unlikely(!state.okay()) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        let select = SelectStatement::parse_select(state)?;
+        Ok(Self {
+            view_name: unsafe {
+                // UNSAFE(@ohsayan): we verified if `view_name` is initialized through the state
+                view_uninit.assume_init()
+            },
+            select,
+            if_not_exists,
+        })
+    }
+}
+
 mod impls {
     use {
-        super::{CreateModel, CreateSpace},
+        super::{CreateIndex, CreateModel, CreateModelCopy, CreateSpace, CreateView},
         crate::engine::{
             error::QueryResult,
             ql::ast::{traits::ASTNode, QueryData, State},
@@ -206,4 +498,31 @@ mod impls {
             Self::parse(state)
         }
     }
+    impl<'a> ASTNode<'a> for CreateModelCopy<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
+    impl<'a> ASTNode<'a> for CreateIndex<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
+    impl<'a> ASTNode<'a> for CreateView<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
 }
@@ -138,9 +138,46 @@ impl<'a> DropModel<'a> {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub struct DropIndex<'a> {
+    pub(in crate::engine) index_name: Ident<'a>,
+    pub(in crate::engine) entity: EntityIDRef<'a>,
+    pub(in crate::engine) if_exists: bool,
+}
+
+impl<'a> DropIndex<'a> {
+    #[inline(always)]
+    pub fn new(index_name: Ident<'a>, entity: EntityIDRef<'a>, if_exists: bool) -> Self {
+        Self {
+            index_name,
+            entity,
+            if_exists,
+        }
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            drop index [if exists] myindex on myspace.mymodel
+        */
+        let if_exists = check_if_exists(state)?;
+        if state.cursor_is_ident()
+            & Token![on].eq(state.offset_current_r(1))
+            & state.has_remaining(3)
+        {
+            let index_name = unsafe {
+                // UNSAFE(@ohsayan): verified in branch
+                state.fw_read().uck_read_ident()
+            };
+            state.cursor_ahead(); // on
+            let e = state.try_entity_ref_result()?;
+            return Ok(DropIndex::new(index_name, e, if_exists));
+        }
+        Err(QueryError::QLInvalidSyntax)
+    }
+}
+
 mod impls {
     use {
-        super::{DropModel, DropSpace},
+        super::{DropIndex, DropModel, DropSpace},
         crate::engine::{
             error::QueryResult,
             ql::ast::{traits::ASTNode, QueryData, State},
@@ -164,4 +201,13 @@ mod impls {
             Self::parse(state)
         }
     }
+    impl<'a> ASTNode<'a> for DropIndex<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
 }
@@ -37,6 +37,7 @@ use {
     },
     crate::engine::{
         core::EntityIDRef,
+        data::lit::Lit,
         error::{QueryError, QueryResult},
     },
 };
@@ -77,6 +78,77 @@ impl<'a> ASTNode<'a> for Use<'a> {
     }
 }
 
+#[derive(Debug, PartialEq)]
+/// The value of a session setting
+pub enum SetValue<'a> {
+    /// go back to what the server uses
+    Default,
+    Name(Ident<'a>),
+    Lit(Lit<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+/// `set <setting> = <value>`: override a setting for the rest of the session (see `core::session`)
+pub struct SessionSet<'a> {
+    setting: Ident<'a>,
+    value: SetValue<'a>,
+}
+
+impl<'a> SessionSet<'a> {
+    pub fn new(setting: Ident<'a>, value: SetValue<'a>) -> Self {
+        Self { setting, value }
+    }
+    pub fn setting(&self) -> Ident<'a> {
+        self.setting
+    }
+    pub fn value(&self) -> &SetValue<'a> {
+        &self.value
+    }
+}
+
+impl<'a> ASTNode<'a> for SessionSet<'a> {
+    const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+    const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+    fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+    ) -> QueryResult<Self> {
+        /*
+            [setting] = (default | [name] | [lit])
+            ^cursor
+        */
+        if state.remaining() != 3 {
+            return Err(QueryError::QLInvalidSyntax);
+        }
+        let setting = match state.fw_read() {
+            Token::Ident(setting) => *setting,
+            _ => return Err(QueryError::QLInvalidSyntax),
+        };
+        if !state.cursor_eq(Token![=]) {
+            return Err(QueryError::QLInvalidSyntax);
+        }
+        state.cursor_ahead();
+        let value = if state.cursor_eq(Token![default]) {
+            state.cursor_ahead();
+            SetValue::Default
+        } else if state.cursor_has_ident_rounded() {
+            SetValue::Name(unsafe {
+                // UNSAFE(@ohsayan): +boundck
+                state.fw_read().uck_read_ident()
+            })
+        } else if state.can_read_lit_rounded() {
+            let lit = unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.read_cursor_lit_unchecked()
+            };
+            state.cursor_ahead();
+            SetValue::Lit(lit)
+        } else {
+            return Err(QueryError::QLInvalidSyntax);
+        };
+        Ok(Self { setting, value })
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Inspect<'a> {
     Global,
@@ -113,3 +185,25 @@ impl<'a> ASTNode<'a> for Inspect<'a> {
         Ok(me)
     }
 }
+
+#[derive(Debug, PartialEq)]
+/// `describe model <model>`
+pub enum Describe<'a> {
+    Model(EntityIDRef<'a>),
+}
+
+impl<'a> ASTNode<'a> for Describe<'a> {
+    const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+    const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+    fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+    ) -> QueryResult<Self> {
+        if state.exhausted() {
+            return Err(QueryError::QLUnexpectedEndOfStatement);
+        }
+        match state.fw_read() {
+            Token![model] => state.try_entity_ref_result().map(Self::Model),
+            _ => Err(QueryError::QLInvalidSyntax),
+        }
+    }
+}
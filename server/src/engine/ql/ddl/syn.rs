@@ -148,6 +148,16 @@ where
                 // found a key, now expect colon
                 mstate = DictFoldState::COLON;
             }
+            (Token![default], DictFoldState::CB_OR_IDENT) => {
+                // `default` is a keyword, but it's also the key of a field's default
+                key = MaybeInit::new(Ident::new_str("default"));
+                mstate = DictFoldState::COLON;
+            }
+            (Token![batch], DictFoldState::CB_OR_IDENT) => {
+                // same for `batch`, which is the batch size of a webhook
+                key = MaybeInit::new(Ident::new_str("batch"));
+                mstate = DictFoldState::COLON;
+            }
             (Token![:], DictFoldState::COLON) => {
                 // found colon, now lit or ob
                 mstate = DictFoldState::LIT_OR_OB;
@@ -378,6 +388,10 @@ impl<'a> FieldSpec<'a> {
         let is_null = state.cursor_eq(Token![null]);
         state.cursor_ahead_if(is_null);
         state.poison_if(is_primary & is_null);
+        if compiler::unlikely(state.remaining() < 2) {
+            // we still need `ident:`
+            return Err(QueryError::QLUnexpectedEndOfStatement);
+        }
         // parse layers
         // field name
         let field_name = match (state.fw_read(), state.fw_read()) {
@@ -440,6 +454,10 @@ impl<'a> ExpandedField<'a> {
             state.poison_if_not(state.cursor_eq(Token![:]));
             state.cursor_ahead();
             rfold_layers(state, &mut layers);
+            if compiler::unlikely(state.exhausted()) {
+                // we need either a comma or the closing brace
+                return Err(QueryError::QLUnexpectedEndOfStatement);
+            }
             match state.fw_read() {
                 Token![,] => {
                     rfold_dict(DictFoldState::CB_OR_IDENT, state, &mut props);
@@ -27,7 +27,7 @@
 #[cfg(test)]
 use super::WhereClauseCollection;
 use {
-    super::WhereClause,
+    super::{Returning, Summary, WhereClause},
     crate::{
         engine::{
             core::EntityIDRef,
@@ -49,6 +49,10 @@ use {
 pub struct DeleteStatement<'a> {
     pub(super) entity: EntityIDRef<'a>,
     pub(super) wc: WhereClause<'a>,
+    pub(super) limit: Option<u64>,
+    pub(super) returning: Option<Returning<'a>>,
+    pub(super) with_lsn: bool,
+    pub(super) summary: Option<Summary>,
 }
 
 impl<'a> DeleteStatement<'a> {
@@ -58,13 +62,35 @@ impl<'a> DeleteStatement<'a> {
     pub fn clauses_mut(&mut self) -> &mut WhereClause<'a> {
         &mut self.wc
     }
+    /// Returns the maximum number of rows that this statement can remove, if set
+    pub const fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+    pub fn take_returning(&mut self) -> Option<Returning<'a>> {
+        self.returning.take()
+    }
+    /// Returns true if the statement should return the commit sequence number of its last change (`with lsn`)
+    pub const fn with_lsn(&self) -> bool {
+        self.with_lsn
+    }
+    /// Returns what the statement should return about the rows it changed, if it ends with `with summary` or `with keys`
+    pub const fn summary(&self) -> Option<Summary> {
+        self.summary
+    }
 }
 
 impl<'a> DeleteStatement<'a> {
     #[inline(always)]
     #[cfg(test)]
     pub(super) fn new(entity: EntityIDRef<'a>, wc: WhereClause<'a>) -> Self {
-        Self { entity, wc }
+        Self {
+            entity,
+            wc,
+            limit: None,
+            returning: None,
+            with_lsn: false,
+            summary: None,
+        }
     }
     #[inline(always)]
     #[cfg(test)]
@@ -72,12 +98,45 @@ impl<'a> DeleteStatement<'a> {
         Self::new(entity, WhereClause::new(wc))
     }
     #[inline(always)]
+    #[cfg(test)]
+    pub fn new_test_with_limit(
+        entity: EntityIDRef<'a>,
+        wc: WhereClauseCollection<'a>,
+        limit: u64,
+    ) -> Self {
+        Self {
+            limit: Some(limit),
+            ..Self::new_test(entity, wc)
+        }
+    }
+    #[inline(always)]
+    #[cfg(test)]
+    pub fn new_test_returning(
+        entity: EntityIDRef<'a>,
+        wc: WhereClauseCollection<'a>,
+        returning: Returning<'a>,
+    ) -> Self {
+        Self {
+            returning: Some(returning),
+            ..Self::new_test(entity, wc)
+        }
+    }
+    #[inline(always)]
     pub fn parse_delete<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
         /*
             TODO(@ohsayan): Volcano
             smallest tt:
             delete from model where x = 1
                    ^1   ^2    ^3    ^4  ^5
+            with a limit:
+            delete from model where x > 1 limit 100
+            returning the deleted rows:
+            delete from model where x = 1 returning *
+            returning the commit sequence number:
+            delete from model where x = 1 with lsn
+            returning a summary of the deleted rows (and their keys):
+            delete from model where x > 1 with summary
+            delete from model where x > 1 with keys
         */
         if compiler::unlikely(state.remaining() < 5) {
             return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
@@ -90,6 +149,16 @@ impl<'a> DeleteStatement<'a> {
         state.poison_if_not(state.cursor_eq(Token![where]));
         state.cursor_ahead(); // ignore errors
         let wc = WhereClause::parse_where(state);
+        // limit?
+        let limit = super::parse_optional_limit(state);
+        // returning?
+        let returning = Returning::parse_optional(state);
+        // with lsn?
+        let with_lsn = super::parse_with(state, "lsn");
+        // with summary or with keys?
+        let summary = Summary::parse_optional(state);
+        state.poison_if((with_lsn | summary.is_some()) & returning.is_some());
+        state.poison_if(with_lsn & summary.is_some());
         if compiler::likely(state.okay()) {
             Ok(Self {
                 entity: unsafe {
@@ -97,6 +166,10 @@ impl<'a> DeleteStatement<'a> {
                     entity.assume_init()
                 },
                 wc,
+                limit,
+                returning,
+                with_lsn,
+                summary,
             })
         } else {
             compiler::cold_rerr(QueryError::QLInvalidSyntax)
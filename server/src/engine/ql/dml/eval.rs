@@ -0,0 +1,228 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::{
+    engine::{
+        core::EntityIDRef,
+        error::{QueryError, QueryResult},
+        ql::{
+            ast::{traits::ASTNode, QueryData, State},
+            dml::{
+                del::DeleteStatement, ins::InsertStatement, sel::ExistsStatement,
+                upd::UpdateStatement, WhereClause,
+            },
+            lex::{KeywordStmt, Token},
+        },
+    },
+    util::compiler,
+};
+
+/// A check-then-act statement that runs atomically:
+/// `eval { if [not] exists from <model> where <pk> = <key> then <action> [else <action>] }`, where an action is an
+/// insert, or an update or delete of a single primary key, on the same model. Actions can't use `returning`,
+/// `with lsn`, `with summary`, `with keys` or `limit`
+#[derive(Debug, PartialEq)]
+pub struct EvalStatement<'a> {
+    negated: bool,
+    condition: ExistsStatement<'a>,
+    then: EvalAction<'a>,
+    otherwise: Option<EvalAction<'a>>,
+}
+
+/// An action of an [`EvalStatement`]
+#[derive(Debug, PartialEq)]
+pub enum EvalAction<'a> {
+    Insert(InsertStatement<'a>),
+    Update(UpdateStatement<'a>),
+    Delete(DeleteStatement<'a>),
+}
+
+impl<'a> EvalAction<'a> {
+    fn entity(&self) -> EntityIDRef<'a> {
+        match self {
+            Self::Insert(insert) => insert.entity,
+            Self::Update(update) => update.entity,
+            Self::Delete(delete) => delete.entity,
+        }
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        let action = match state.try_statement() {
+            Ok(KeywordStmt::Insert) => {
+                let insert = InsertStatement::parse_from_state_hardened(state)?;
+                let okay = insert.returning.is_none() & !insert.with_lsn & insert.summary.is_none();
+                okay.then_some(Self::Insert(insert))
+            }
+            Ok(KeywordStmt::Update) => {
+                let update = UpdateStatement::parse_from_state_hardened(state)?;
+                let okay = update.returning.is_none()
+                    & !update.with_lsn
+                    & update.summary.is_none()
+                    & update.limit.is_none()
+                    & !update.force;
+                okay.then_some(Self::Update(update))
+            }
+            Ok(KeywordStmt::Delete) => {
+                let delete = DeleteStatement::parse_from_state_hardened(state)?;
+                let okay = delete.returning.is_none()
+                    & !delete.with_lsn
+                    & delete.summary.is_none()
+                    & delete.limit.is_none();
+                okay.then_some(Self::Delete(delete))
+            }
+            _ => None,
+        };
+        action.ok_or(QueryError::QLInvalidSyntax)
+    }
+}
+
+impl<'a> EvalStatement<'a> {
+    #[cfg(test)]
+    pub fn new_test(
+        negated: bool,
+        condition: ExistsStatement<'a>,
+        then: EvalAction<'a>,
+        otherwise: Option<EvalAction<'a>>,
+    ) -> Self {
+        Self {
+            negated,
+            condition,
+            then,
+            otherwise,
+        }
+    }
+    pub fn entity(&self) -> EntityIDRef<'a> {
+        self.condition.entity
+    }
+    /// Returns true if the condition is `if not exists`
+    pub fn negated(&self) -> bool {
+        self.negated
+    }
+    pub fn condition_mut(&mut self) -> &mut WhereClause<'a> {
+        &mut self.condition.clause
+    }
+    /// Returns the action to run for the given outcome of the condition, if there is one
+    pub fn into_action(self, outcome: bool) -> Option<EvalAction<'a>> {
+        if outcome {
+            Some(self.then)
+        } else {
+            self.otherwise
+        }
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            smallest query: eval { if exists from model where k = v then delete from model where k = v }
+        */
+        let tokens = state.current();
+        if compiler::unlikely(
+            (tokens.len() < 13)
+                | (tokens[0] != Token![open {}])
+                | (tokens[tokens.len() - 1] != Token![close {}])
+                | (tokens[1] != Token![if]),
+        ) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        let negated = tokens[2] == Token![not];
+        state.cursor_ahead_by(2 + negated as usize);
+        if compiler::unlikely(state.read() != &Token![exists]) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        state.cursor_ahead();
+        // the actions are delimited by the (outermost) `then`, `else` and the closing brace
+        let body = &state.current()[..state.remaining() - 1];
+        let Some(then_at) = find_outermost(body, |tok| tok.ident_eq("then")) else {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        };
+        let else_at = find_outermost(body, |tok| *tok == Token![else]);
+        if compiler::unlikely(else_at.map_or(false, |else_at| else_at < then_at)) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        let condition = state.with_bounded(then_at, ExistsStatement::parse_from_state_hardened)?;
+        state.cursor_ahead();
+        let then_len = else_at.unwrap_or(body.len()) - then_at - 1;
+        let then = state.with_bounded(then_len, EvalAction::parse)?;
+        let otherwise = match else_at {
+            Some(else_at) => {
+                state.cursor_ahead();
+                let else_len = body.len() - else_at - 1;
+                Some(state.with_bounded(else_len, EvalAction::parse)?)
+            }
+            None => None,
+        };
+        // skip the closing brace
+        state.cursor_ahead();
+        let entity = condition.entity;
+        if compiler::unlikely(
+            (then.entity() != entity)
+                | otherwise
+                    .as_ref()
+                    .map_or(false, |otherwise| otherwise.entity() != entity),
+        ) {
+            // an eval is only atomic within a model
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        Ok(Self {
+            negated,
+            condition,
+            then,
+            otherwise,
+        })
+    }
+}
+
+/// Returns the position of the first token that isn't nested in a collection or a function call and satisfies `f`
+fn find_outermost(tokens: &[Token], f: impl Fn(&Token) -> bool) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, tok) in tokens.iter().enumerate() {
+        match tok {
+            Token![open {}] | Token![() open] | Token![open []] => depth += 1,
+            Token![close {}] | Token![() close] | Token![close []] => {
+                depth = depth.checked_sub(1)?
+            }
+            tok if (depth == 0) && f(tok) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+mod impls {
+    use {
+        super::EvalStatement,
+        crate::engine::{
+            error::QueryResult,
+            ql::ast::{traits::ASTNode, QueryData, State},
+        },
+    };
+    impl<'a> ASTNode<'a> for EvalStatement<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
+}
@@ -27,11 +27,12 @@
 use {
     crate::{
         engine::{
-            core::EntityIDRef,
+            core::{dml::keygen::KeyGen, EntityIDRef},
             data::cell::Datacell,
             error::{QueryError, QueryResult},
             ql::{
                 ast::{QueryData, State},
+                dml::{Returning, Summary},
                 lex::{Ident, Token},
             },
         },
@@ -122,12 +123,16 @@ fn ldfunc(func: Ident<'_>) -> Option<ProducerFn> {
     }
 }
 
-/// ## Panics
-/// - If tt length is less than 1
+/// Parse a list (the cursor must be just past the opening bracket). An unterminated list poisons the state
 pub(super) fn parse_list<'a, Qd: QueryData<'a>>(
     state: &mut State<'a, Qd>,
     list: &mut Vec<Datacell>,
 ) -> Option<TagClass> {
+    if compiler::unlikely(state.exhausted()) {
+        // a nested list that was opened right at the end
+        state.poison();
+        return None;
+    }
     let mut stop = state.cursor_eq(Token![close []]);
     state.cursor_ahead_if(stop);
     let mut overall_dscr = None;
@@ -155,6 +160,10 @@ pub(super) fn parse_list<'a, Qd: QueryData<'a>>(
                 }
                 Datacell::new_list(nested_list)
             }
+            Token::Ident(name) if keygen_call(state, name).is_some() => {
+                let f = keygen_call(state, name).unwrap();
+                handle_keygen_call(state, f)
+            }
             Token![@] if state.cursor_signature_match_fn_arity0_rounded() => match unsafe {
                 // UNSAFE(@ohsayan): Just verified at guard
                 handle_func_sub(state)
@@ -189,7 +198,24 @@ pub(super) fn parse_list<'a, Qd: QueryData<'a>>(
 unsafe fn handle_func_sub<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<Datacell> {
     let func = state.fw_read().uck_read_ident();
     state.cursor_ahead_by(2); // skip tt:paren
-    ldfunc(func).map(move |f| f())
+    ldfunc(func)
+        .map(move |f| f())
+        .or_else(|| KeyGen::from_name(func.as_str()).map(|f| f.generate()))
+}
+
+/// Returns the key generator if the cursor is just past the name of one that is being called without a `@` (like
+/// `uuid7()`)
+fn keygen_call<'a, Qd: QueryData<'a>>(state: &State<'a, Qd>, name: &Ident) -> Option<KeyGen> {
+    let call = state.has_remaining(2)
+        && (Token![() open].eq(state.offset_current_r(0)))
+            & (Token![() close].eq(state.offset_current_r(1)));
+    KeyGen::from_name(name.as_str()).filter(|_| call)
+}
+
+/// Generate a key for a call to a key generator without a `@` (see [`keygen_call`]), skipping the parentheses
+fn handle_keygen_call<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>, f: KeyGen) -> Datacell {
+    state.cursor_ahead_by(2);
+    f.generate()
 }
 
 /// ## Panics
@@ -212,6 +238,10 @@ pub(super) fn parse_data_tuple_syntax<'a, Qd: QueryData<'a>>(
                 data.push(l.into());
             }
             Token![null] => data.push(Datacell::null()),
+            Token::Ident(name) if keygen_call(state, name).is_some() => {
+                let f = keygen_call(state, name).unwrap();
+                data.push(handle_keygen_call(state, f));
+            }
             Token![@] if state.cursor_signature_match_fn_arity0_rounded() => match unsafe {
                 // UNSAFE(@ohsayan): Just verified at guard
                 handle_func_sub(state)
@@ -266,6 +296,11 @@ pub(super) fn parse_data_map_syntax<'a, Qd: QueryData<'a>>(
                 let _ = parse_list(state, &mut l);
                 state.poison_if_not(data.insert(*id, l.into()).is_none());
             }
+            (Token::Ident(id), Token::Ident(name)) if keygen_call(state, name).is_some() => {
+                let f = keygen_call(state, name).unwrap();
+                let value = handle_keygen_call(state, f);
+                state.poison_if_not(data.insert(*id, value).is_none());
+            }
             (Token::Ident(id), Token![@]) if state.cursor_signature_match_fn_arity0_rounded() => {
                 match unsafe {
                     // UNSAFE(@ohsayan): Just verified at guard
@@ -324,17 +359,49 @@ impl<'a> From<HashMap<Ident<'static>, Datacell>> for InsertData<'a> {
 pub struct InsertStatement<'a> {
     pub(super) entity: EntityIDRef<'a>,
     pub(super) data: InsertData<'a>,
+    pub(super) returning: Option<Returning<'a>>,
+    pub(super) with_lsn: bool,
+    pub(super) summary: Option<Summary>,
 }
 
 impl<'a> InsertStatement<'a> {
     #[inline(always)]
     #[cfg(test)]
     pub fn new(entity: EntityIDRef<'a>, data: InsertData<'a>) -> Self {
-        Self { entity, data }
+        Self {
+            entity,
+            data,
+            returning: None,
+            with_lsn: false,
+            summary: None,
+        }
+    }
+    #[inline(always)]
+    #[cfg(test)]
+    pub fn new_returning(
+        entity: EntityIDRef<'a>,
+        data: InsertData<'a>,
+        returning: Returning<'a>,
+    ) -> Self {
+        Self {
+            returning: Some(returning),
+            ..Self::new(entity, data)
+        }
     }
     pub fn entity(&self) -> EntityIDRef<'a> {
         self.entity
     }
+    pub fn take_returning(&mut self) -> Option<Returning<'a>> {
+        self.returning.take()
+    }
+    /// Returns true if the statement should return the commit sequence number of the insert (`with lsn`)
+    pub const fn with_lsn(&self) -> bool {
+        self.with_lsn
+    }
+    /// Returns what the statement should return about the rows it changed, if it ends with `with summary` or `with keys`
+    pub const fn summary(&self) -> Option<Summary> {
+        self.summary
+    }
     pub fn data(self) -> InsertData<'a> {
         self.data
     }
@@ -346,6 +413,13 @@ impl<'a> InsertStatement<'a> {
             smallest:
             insert into model (primarykey)
                    ^1    ^2   ^3      ^4 ^5
+            returning the inserted row:
+            insert into model (primarykey) returning *
+            returning the commit sequence number:
+            insert into model (primarykey) with lsn
+            returning a summary of the inserted row (and its key):
+            insert into model (primarykey) with summary
+            insert into model (primarykey) with keys
         */
         if compiler::unlikely(state.remaining() < 5) {
             return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
@@ -369,6 +443,14 @@ impl<'a> InsertStatement<'a> {
                 state.poison();
             }
         }
+        // returning?
+        let returning = Returning::parse_optional(state);
+        // with lsn?
+        let with_lsn = super::parse_with(state, "lsn");
+        // with summary or with keys?
+        let summary = Summary::parse_optional(state);
+        state.poison_if((with_lsn | summary.is_some()) & returning.is_some());
+        state.poison_if(with_lsn & summary.is_some());
         if state.okay() {
             let data = unsafe {
                 // UNSAFE(@ohsayan): state's flag guarantees correctness (see wildcard branch)
@@ -380,6 +462,9 @@ impl<'a> InsertStatement<'a> {
                     entity.assume_init()
                 },
                 data,
+                returning,
+                with_lsn,
+                summary,
             })
         } else {
             compiler::cold_rerr(QueryError::QLInvalidSyntax)
@@ -30,17 +30,29 @@
 */
 
 pub mod del;
+pub mod eval;
 pub mod ins;
 pub mod sel;
+pub mod traverse;
 pub mod upd;
 
 use {
     super::{
         ast::{QueryData, State},
-        lex::Ident,
+        lex::{Ident, Token},
+    },
+    crate::{
+        engine::{
+            core::dml::expr::{self, ArithOp, ExprFn, RowFn},
+            data::{lit::Lit, tag::TagSelector},
+        },
+        util::compiler,
+    },
+    std::{
+        cmp::Ordering,
+        collections::{hash_map::Entry, HashMap},
+        ops::Bound,
     },
-    crate::{engine::data::lit::Lit, util::compiler},
-    std::collections::HashMap,
 };
 
 #[inline(always)]
@@ -52,6 +64,341 @@ fn u(b: bool) -> u8 {
     Misc
 */
 
+/// Parse an optional `limit <n>` clause. The state is poisoned if the limit is not an unsigned integer
+fn parse_optional_limit<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<u64> {
+    let mut limit = None;
+    if state.cursor_rounded_eq(Token![limit]) {
+        state.cursor_ahead();
+        state.poison_if_not(state.can_read_lit_rounded());
+        if state.okay() {
+            limit = unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.read_cursor_lit_unchecked()
+            }
+            .try_uint();
+            state.poison_if(limit.is_none());
+            state.cursor_ahead();
+        }
+    }
+    limit
+}
+
+/// Parse an optional `as of <timestamp>` clause. The state is poisoned if the timestamp is not an unsigned integer
+fn parse_optional_as_of<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<u64> {
+    let mut as_of = None;
+    if state.has_remaining(2)
+        && state.read().eq(&Token![as])
+        && state.offset_current_r(1).eq(&Token![of])
+    {
+        state.cursor_ahead_by(2);
+        state.poison_if_not(state.can_read_lit_rounded());
+        if state.okay() {
+            as_of = unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.read_cursor_lit_unchecked()
+            }
+            .try_uint();
+            state.poison_if(as_of.is_none());
+            state.cursor_ahead();
+        }
+    }
+    as_of
+}
+
+/// Parse a value: a literal or an expression (see [`expr`]), which is evaluated right away. The state is poisoned if
+/// the expression is invalid, or if it overflows
+fn parse_value<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<Lit<'a>> {
+    parse_value_nested(state, 0)
+}
+
+fn parse_value_nested<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+    depth: usize,
+) -> Option<Lit<'a>> {
+    let mut value = parse_term(state, depth)?;
+    while state.okay() && state.not_exhausted() {
+        let op = match state.read() {
+            Token![+] => ArithOp::Add,
+            Token![-] => ArithOp::Sub,
+            _ => break,
+        };
+        state.cursor_ahead();
+        let rhs = parse_term(state, depth)?;
+        match (value.try_uint(), rhs.try_uint()) {
+            (Some(a), Some(b)) => match op.apply(a, b) {
+                Some(v) => value = Lit::new_uint(v),
+                None => state.poison(),
+            },
+            _ => state.poison(),
+        }
+    }
+    state.okay().then_some(value)
+}
+
+/// Parse a literal (which may be followed by an interval unit) or a function call
+fn parse_term<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>, depth: usize) -> Option<Lit<'a>> {
+    if compiler::unlikely(state.exhausted()) {
+        state.poison();
+        return None;
+    }
+    if let Token::Ident(name) = state.read() {
+        let f = ExprFn::from_name(name.as_str());
+        state.poison_if(f.is_none() | (depth == expr::MAX_DEPTH));
+        state.cursor_ahead();
+        state.poison_if_not(state.cursor_rounded_eq(Token![() open]));
+        state.cursor_ahead_if(state.okay());
+        let f = f.filter(|_| state.okay())?;
+        let arg = if f.takes_arg() {
+            parse_value_nested(state, depth + 1)?.try_uint()
+        } else {
+            Some(0)
+        };
+        state.poison_if(arg.is_none());
+        state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+        state.cursor_ahead_if(state.okay());
+        return arg
+            .filter(|_| state.okay())
+            .map(|t| Lit::new_uint(f.apply(t)));
+    }
+    state.poison_if_not(state.can_read_lit_rounded());
+    if compiler::unlikely(!state.okay()) {
+        return None;
+    }
+    let lit = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    };
+    state.cursor_ahead();
+    let unit = match state.not_exhausted().then(|| state.read()) {
+        Some(Token::Ident(unit)) => expr::interval_unit(unit.as_slice()),
+        _ => None,
+    };
+    match unit {
+        Some(secs) => {
+            state.cursor_ahead();
+            let interval = lit.try_uint().and_then(|n| n.checked_mul(secs));
+            state.poison_if(interval.is_none());
+            interval.map(Lit::new_uint)
+        }
+        None => Some(lit),
+    }
+}
+
+/// An expression that is evaluated for every row: a field, a value, a function or a cast (see [`expr`])
+#[derive(Debug, PartialEq)]
+pub enum RowExpr<'a> {
+    Field(Ident<'a>),
+    Value(Lit<'a>),
+    Call(RowFn, Vec<RowExpr<'a>>),
+    /// `cast(x as <type>)`
+    Cast(Box<RowExpr<'a>>, TagSelector),
+}
+
+impl<'a> RowExpr<'a> {
+    const CAST: &'static str = "cast";
+    /// Returns true if the cursor is at a function or a cast (its name followed by an opening parenthesis)
+    pub fn is_call<Qd: QueryData<'a>>(state: &State<'a, Qd>) -> bool {
+        state.has_remaining(2)
+            && matches!(
+                state.read(),
+                Token::Ident(name)
+                    if (name.as_str() == Self::CAST) | RowFn::from_name(name.as_str()).is_some()
+            )
+            && *state.offset_current_r(1) == Token![() open]
+    }
+    /// Returns the fields that this expression reads, in order (a field can be repeated)
+    pub fn fields(&self) -> Vec<Ident<'a>> {
+        let mut fields = Vec::new();
+        self.visit_fields(&mut fields);
+        fields
+    }
+    fn visit_fields(&self, fields: &mut Vec<Ident<'a>>) {
+        match self {
+            Self::Field(field) => fields.push(*field),
+            Self::Value(_) => {}
+            Self::Call(_, args) => args.iter().for_each(|arg| arg.visit_fields(fields)),
+            Self::Cast(arg, _) => arg.visit_fields(fields),
+        }
+    }
+    /// Parse a function call or a cast. The caller must ensure that the cursor is at one (see [`Self::is_call`])
+    fn parse_call<Qd: QueryData<'a>>(state: &mut State<'a, Qd>, depth: usize) -> Option<Self> {
+        let f = match state.fw_read() {
+            Token::Ident(name) if name.as_str() == Self::CAST => {
+                return Self::parse_cast(state, depth);
+            }
+            Token::Ident(name) => RowFn::from_name(name.as_str()),
+            _ => None,
+        };
+        state.poison_if(f.is_none() | (depth == expr::MAX_DEPTH));
+        state.cursor_ahead_if(state.okay()); // open paren
+        let f = f.filter(|_| state.okay())?;
+        let mut args = Vec::new();
+        let mut nx_comma = true;
+        while state.okay() && nx_comma {
+            args.push(Self::parse_arg(state, depth + 1)?);
+            nx_comma = state.cursor_rounded_eq(Token![,]);
+            state.cursor_ahead_if(nx_comma);
+        }
+        let (min, max) = f.arity();
+        state.poison_if((args.len() < min) | (args.len() > max));
+        state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+        state.cursor_ahead_if(state.okay());
+        state.okay().then_some(Self::Call(f, args))
+    }
+    /// Parse the rest of `cast(x as <type>)`, right after `cast`
+    fn parse_cast<Qd: QueryData<'a>>(state: &mut State<'a, Qd>, depth: usize) -> Option<Self> {
+        state.poison_if(depth == expr::MAX_DEPTH);
+        state.cursor_ahead_if(state.okay()); // open paren
+        if !state.okay() {
+            return None;
+        }
+        let arg = Self::parse_arg(state, depth + 1)?;
+        state.poison_if_not(state.cursor_rounded_eq(Token![as]));
+        state.cursor_ahead_if(state.okay());
+        let to = match (state.okay() && state.not_exhausted()).then(|| state.read()) {
+            Some(Token::Ident(ty)) => expr::cast_target(ty.as_str()),
+            _ => None,
+        };
+        state.poison_if(to.is_none());
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+        state.cursor_ahead_if(state.okay());
+        let to = to.filter(|_| state.okay())?;
+        Some(Self::Cast(Box::new(arg), to))
+    }
+    /// Parse an argument of a function or a cast: another call, a field or a value
+    fn parse_arg<Qd: QueryData<'a>>(state: &mut State<'a, Qd>, depth: usize) -> Option<Self> {
+        if Self::is_call(state) {
+            return Self::parse_call(state, depth);
+        }
+        let is_field = state.cursor_has_ident_rounded()
+            && !(state.has_remaining(2) && *state.offset_current_r(1) == Token![() open]);
+        if is_field {
+            return Some(Self::Field(unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.fw_read().uck_read_ident()
+            }));
+        }
+        parse_value_nested(state, depth).map(Self::Value)
+    }
+}
+
+/// Parse the projection of a select: `*` or a comma separated list of fields and functions, ending right
+/// before `from`. Returns the fields that are read (empty for `*`) and, if any function was used, the
+/// expression for each column. The state is poisoned if the projection is invalid
+fn parse_projection<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> (Vec<Ident<'a>>, bool, Option<Vec<RowExpr<'a>>>) {
+    let is_wildcard = state.cursor_eq(Token![*]);
+    state.cursor_ahead_if(is_wildcard);
+    let mut columns = Vec::new();
+    let mut nx_comma = false;
+    while state.not_exhausted() && state.okay() && !is_wildcard {
+        if RowExpr::is_call(state) {
+            if let Some(call) = RowExpr::parse_call(state, 0) {
+                columns.push(call);
+            }
+        } else {
+            match state.read() {
+                Token::Ident(id) => columns.push(RowExpr::Field(*id)),
+                _ => break,
+            }
+            state.cursor_ahead();
+        }
+        nx_comma = state.cursor_rounded_eq(Token![,]);
+        let nx_from = state.cursor_rounded_eq(Token![from]);
+        state.poison_if_not(nx_comma | nx_from);
+        state.cursor_ahead_if(nx_comma);
+    }
+    // we can't have a trailing comma
+    state.poison_if(nx_comma);
+    state.poison_if_not(is_wildcard | !columns.is_empty());
+    if columns
+        .iter()
+        .any(|column| !matches!(column, RowExpr::Field(_)))
+    {
+        let fields = columns.iter().flat_map(RowExpr::fields).collect();
+        (fields, is_wildcard, Some(columns))
+    } else {
+        let fields = columns
+            .into_iter()
+            .filter_map(|column| match column {
+                RowExpr::Field(field) => Some(field),
+                _ => None,
+            })
+            .collect();
+        (fields, is_wildcard, None)
+    }
+}
+
+/// The projection of a `returning` clause
+#[derive(Debug, PartialEq)]
+pub enum Returning<'a> {
+    /// all fields (`returning *`)
+    Wildcard,
+    /// the given fields, in order (`returning a, b`)
+    Fields(Vec<Ident<'a>>),
+}
+
+impl<'a> Returning<'a> {
+    /// Parse an optional `returning` clause. The state is poisoned if the projection is invalid
+    fn parse_optional<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<Self> {
+        if state.exhausted() || !state.read().ident_eq("returning") {
+            return None;
+        }
+        state.cursor_ahead();
+        if state.cursor_rounded_eq(Token![*]) {
+            state.cursor_ahead();
+            return Some(Self::Wildcard);
+        }
+        let mut fields = Vec::new();
+        let mut nx_comma = true;
+        while state.not_exhausted() && state.okay() && nx_comma {
+            match state.fw_read() {
+                Token::Ident(id) => fields.push(*id),
+                _ => state.poison(),
+            }
+            nx_comma = state.cursor_rounded_eq(Token![,]);
+            state.cursor_ahead_if(nx_comma);
+        }
+        state.poison_if(nx_comma | fields.is_empty());
+        Some(Self::Fields(fields))
+    }
+}
+
+/// Parse an optional `with <option>`, for example `with deleted` (which makes soft deleted rows visible to a
+/// select) or `with lsn` (which makes a mutation return its commit sequence number). Returns true if it was present
+fn parse_with<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>, option: &str) -> bool {
+    let with = state.has_remaining(2)
+        && state.read().eq(&Token![with])
+        && state.offset_current_r(1).ident_eq(option);
+    state.cursor_ahead_by(2 * with as usize);
+    with
+}
+
+/// What a mutation that ends with `with summary` or `with keys` returns (instead of the number of rows or the row it
+/// changed)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Summary {
+    /// the number of rows that were matched, modified, inserted and deleted (`with summary`)
+    Counts,
+    /// the counts, along with the primary keys of the rows (`with keys`)
+    Keys,
+}
+
+impl Summary {
+    /// Parse an optional `with summary` or `with keys`
+    fn parse_optional<'a, Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<Self> {
+        if parse_with(state, "summary") {
+            Some(Self::Counts)
+        } else if parse_with(state, "keys") {
+            Some(Self::Keys)
+        } else {
+            None
+        }
+    }
+}
+
 /*
     Contexts
 */
@@ -61,12 +408,39 @@ pub struct RelationalExpr<'a> {
     pub(super) lhs: Ident<'a>,
     pub(super) rhs: Lit<'a>,
     pub(super) opc: u8,
+    /// the upper bound (and its operator) for range expressions, i.e `x BETWEEN a AND b` or `x >= a AND x <= b`. in
+    /// this case, `rhs` and `opc` hold the lower bound
+    pub(super) rhs_hi: Option<(Lit<'a>, u8)>,
+    /// the function on the left hand side, if any (`lower(x) = 'a'`). in this case, `lhs` is the first field
+    /// that it reads
+    pub(super) lhs_expr: Option<RowExpr<'a>>,
 }
 
 impl<'a> RelationalExpr<'a> {
     #[inline(always)]
     pub(super) fn new(lhs: Ident<'a>, rhs: Lit<'a>, opc: u8) -> RelationalExpr<'a> {
-        Self { lhs, rhs, opc }
+        Self {
+            lhs,
+            rhs,
+            opc,
+            rhs_hi: None,
+            lhs_expr: None,
+        }
+    }
+    #[inline(always)]
+    #[cfg(test)]
+    pub(super) fn new_range(
+        lhs: Ident<'a>,
+        (lo, lo_opc): (Lit<'a>, u8),
+        hi: (Lit<'a>, u8),
+    ) -> RelationalExpr<'a> {
+        Self {
+            lhs,
+            rhs: lo,
+            opc: lo_opc,
+            rhs_hi: Some(hi),
+            lhs_expr: None,
+        }
     }
     pub(super) const OP_EQ: u8 = 1;
     pub(super) const OP_NE: u8 = 2;
@@ -74,12 +448,141 @@ impl<'a> RelationalExpr<'a> {
     pub(super) const OP_GE: u8 = 4;
     pub(super) const OP_LT: u8 = 5;
     pub(super) const OP_LE: u8 = 6;
+    /// `x MATCHES '<regex>'`; the rhs is always a string literal
+    pub(super) const OP_MATCHES: u8 = 7;
+    /// the tolerance of `x ~= v` when it isn't set with `within`
+    pub const DEFAULT_EPSILON: f64 = 1e-6;
     pub fn filter_hint_none(&self) -> bool {
-        self.opc == Self::OP_EQ
+        (self.opc == Self::OP_EQ) & self.rhs_hi.is_none() & self.lhs_expr.is_none()
     }
     pub fn rhs(&self) -> Lit<'a> {
         self.rhs.clone()
     }
+    pub fn lhs(&self) -> Ident<'a> {
+        self.lhs
+    }
+    /// Returns the function on the left hand side, if any
+    pub fn lhs_expr(&self) -> Option<&RowExpr<'a>> {
+        self.lhs_expr.as_ref()
+    }
+    /// Returns the fields that this expression reads
+    pub fn fields(&self) -> Vec<Ident<'a>> {
+        self.lhs_expr
+            .as_ref()
+            .map_or_else(|| vec![self.lhs], RowExpr::fields)
+    }
+    /// Replace the values that the field is compared with (both bounds, for a range). Returns false if `f` rejects one
+    /// of them
+    pub fn map_rhs(&mut self, f: impl Fn(&Lit<'a>) -> Option<Lit<'a>>) -> bool {
+        let Some(rhs) = f(&self.rhs) else {
+            return false;
+        };
+        self.rhs = rhs;
+        match &mut self.rhs_hi {
+            Some((hi, _)) => match f(hi) {
+                Some(new) => {
+                    *hi = new;
+                    true
+                }
+                None => false,
+            },
+            None => true,
+        }
+    }
+    /// Returns the pattern if this is a `MATCHES` expression
+    pub fn matches_pattern(&self) -> Option<&'a str> {
+        if self.opc == Self::OP_MATCHES {
+            self.rhs.try_str()
+        } else {
+            None
+        }
+    }
+    /// Returns the operator (`=`, `!=`, `>`, `>=`, `<`, `<=` or `matches`) and literal of each comparison in this
+    /// expression. A range has two comparisons
+    pub fn comparisons(&self) -> impl Iterator<Item = (&'static str, Lit<'a>)> + '_ {
+        let name = |opc| match opc {
+            Self::OP_EQ => "=",
+            Self::OP_NE => "!=",
+            Self::OP_GT => ">",
+            Self::OP_GE => ">=",
+            Self::OP_LT => "<",
+            Self::OP_LE => "<=",
+            _ => "matches",
+        };
+        core::iter::once((name(self.opc), self.rhs.clone())).chain(
+            self.rhs_hi
+                .as_ref()
+                .map(|(hi, hi_opc)| (name(*hi_opc), hi.clone())),
+        )
+    }
+    /// Evaluate this expression for a value, using a function that compares the value against a literal. A value
+    /// that can't be compared with the literal never matches (and neither does a `MATCHES` expression, which needs the
+    /// compiled pattern)
+    pub fn eval(&self, cmp: impl Fn(&Lit<'a>) -> Option<Ordering>) -> bool {
+        let check = |opc, lit| {
+            cmp(lit).map_or(false, |ord| match opc {
+                Self::OP_EQ => ord.is_eq(),
+                Self::OP_NE => ord.is_ne(),
+                Self::OP_GT => ord.is_gt(),
+                Self::OP_GE => ord.is_ge(),
+                Self::OP_LT => ord.is_lt(),
+                Self::OP_LE => ord.is_le(),
+                _ => false,
+            })
+        };
+        check(self.opc, &self.rhs)
+            & self
+                .rhs_hi
+                .as_ref()
+                .map_or(true, |(hi, hi_opc)| check(*hi_opc, hi))
+    }
+    fn is_lower_bound(opc: u8) -> bool {
+        (opc == Self::OP_GT) | (opc == Self::OP_GE)
+    }
+    fn is_upper_bound(opc: u8) -> bool {
+        (opc == Self::OP_LT) | (opc == Self::OP_LE)
+    }
+    /// Returns the bounds of the range selected by this expression (an `=` is a range with a single value). Returns
+    /// [`None`] if the expression doesn't select a contiguous range of the field's values
+    pub fn bounds(&self) -> Option<(Bound<Lit<'a>>, Bound<Lit<'a>>)> {
+        if self.lhs_expr.is_some() {
+            return None;
+        }
+        let bound = |opc, lit: &Lit<'a>| match opc {
+            Self::OP_EQ | Self::OP_GE | Self::OP_LE => Bound::Included(lit.clone()),
+            _ => Bound::Excluded(lit.clone()),
+        };
+        match (self.opc, &self.rhs_hi) {
+            (Self::OP_EQ, None) => Some((bound(self.opc, &self.rhs), bound(self.opc, &self.rhs))),
+            (opc, None) if Self::is_lower_bound(opc) => {
+                Some((bound(opc, &self.rhs), Bound::Unbounded))
+            }
+            (opc, None) if Self::is_upper_bound(opc) => {
+                Some((Bound::Unbounded, bound(opc, &self.rhs)))
+            }
+            (opc, Some((hi, hi_opc))) => Some((bound(opc, &self.rhs), bound(*hi_opc, hi))),
+            _ => None,
+        }
+    }
+    /// Attempt to merge a complementary bound on the same column into this expression (`x >= a AND x <= b`)
+    fn try_merge_range(&mut self, other: Self) -> bool {
+        let okay = self.rhs_hi.is_none()
+            & other.rhs_hi.is_none()
+            & self.lhs_expr.is_none()
+            & other.lhs_expr.is_none()
+            & ((Self::is_lower_bound(self.opc) & Self::is_upper_bound(other.opc))
+                | (Self::is_upper_bound(self.opc) & Self::is_lower_bound(other.opc)));
+        if okay {
+            if Self::is_lower_bound(self.opc) {
+                self.rhs_hi = Some((other.rhs, other.opc));
+            } else {
+                let hi = core::mem::replace(&mut self.rhs, other.rhs);
+                self.rhs_hi = Some((hi, self.opc));
+                self.opc = other.opc;
+            }
+        }
+        okay
+    }
     #[inline(always)]
     fn parse_operator<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> u8 {
         let tok = state.current();
@@ -99,23 +602,138 @@ impl<'a> RelationalExpr<'a> {
         if compiler::likely(state.remaining() < 3) {
             return compiler::cold_val(None);
         }
+        if RowExpr::is_call(state) {
+            let func = state.read();
+            let lhs = RowExpr::parse_call(state, 0)?;
+            // the clause is keyed by the first field that the function reads
+            let field = lhs.fields().first().copied();
+            state.poison_if(field.is_none());
+            let mut expr = Self::try_parse_rhs(state, func)?;
+            expr.lhs = field?;
+            expr.lhs_expr = Some(lhs);
+            return Some(expr);
+        }
         let ident = state.read();
         state.poison_if_not(ident.is_ident());
         state.cursor_ahead(); // ignore any errors
+        Self::try_parse_rhs(state, ident)
+    }
+    /// Parse the rest of a comparison (the cursor is right after the lhs, which must be an ident)
+    pub(super) fn try_parse_rhs<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+        ident: &'a Token<'a>,
+    ) -> Option<Self> {
+        if compiler::unlikely(state.remaining() < 2) {
+            state.poison();
+            return compiler::cold_val(None);
+        }
+        if state.read().ident_eq("between") {
+            state.cursor_ahead();
+            return Self::try_parse_between(state, ident);
+        }
+        if state.read().ident_eq("matches") {
+            state.cursor_ahead();
+            return Self::try_parse_matches(state, ident);
+        }
+        if (state.read() == &Token![~]) & (state.offset_current_r(1) == &Token![=]) {
+            state.cursor_ahead_by(2);
+            return Self::try_parse_approx(state, ident);
+        }
         let operator = Self::parse_operator(state);
+        if compiler::unlikely(!state.okay()) {
+            return None;
+        }
+        let lit = parse_value(state)?;
+        // UNSAFE(@ohsayan): we checked if `ident` returns `is_ident` and updated state
+        Some(Self::new(unsafe { ident.uck_read_ident() }, lit, operator))
+    }
+    /// Parse the rest of `x MATCHES '<regex>'` (the cursor is right after `MATCHES`)
+    fn try_parse_matches<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+        ident: &'a Token<'a>,
+    ) -> Option<Self> {
+        if compiler::unlikely(state.exhausted()) {
+            state.poison();
+            return compiler::cold_val(None);
+        }
         state.poison_if_not(state.can_read_lit_rounded());
+        if compiler::unlikely(!state.okay()) {
+            return None;
+        }
+        let pattern = unsafe {
+            // UNSAFE(@ohsayan): verified above
+            state.read_cursor_lit_unchecked()
+        };
+        state.cursor_ahead();
+        state.poison_if_not(pattern.try_str().is_some());
         if compiler::likely(state.okay()) {
-            unsafe {
-                // UNSAFE(@ohsayan): we verified this above
-                let lit = state.read_cursor_lit_unchecked();
-                state.cursor_ahead();
-                // UNSAFE(@ohsayan): we checked if `ident` returns `is_ident` and updated state
-                Some(Self::new(ident.uck_read_ident(), lit, operator))
-            }
+            // UNSAFE(@ohsayan): the caller checked that this is an ident
+            Some(Self::new(
+                unsafe { ident.uck_read_ident() },
+                pattern,
+                Self::OP_MATCHES,
+            ))
         } else {
             None
         }
     }
+    /// Parse the rest of `x ~= v [WITHIN epsilon]` (the cursor is right after `~=`). This is the range
+    /// `x BETWEEN v - epsilon AND v + epsilon`, so it can also be answered by an index
+    fn try_parse_approx<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+        ident: &'a Token<'a>,
+    ) -> Option<Self> {
+        let as_float = |lit: Lit| {
+            lit.try_float()
+                .or_else(|| lit.try_uint().map(|u| u as f64))
+                .or_else(|| lit.try_sint().map(|s| s as f64))
+        };
+        let value = as_float(parse_value(state)?);
+        let within = state.not_exhausted() && state.read().ident_eq("within");
+        state.cursor_ahead_if(within);
+        let epsilon = if within {
+            as_float(parse_value(state)?)
+        } else {
+            Some(Self::DEFAULT_EPSILON)
+        };
+        let (Some(value), Some(epsilon)) = (value, epsilon.filter(|e| *e >= 0.0)) else {
+            state.poison();
+            return None;
+        };
+        // UNSAFE(@ohsayan): the caller checked that this is an ident
+        Some(Self {
+            lhs: unsafe { ident.uck_read_ident() },
+            rhs: Lit::new_float(value - epsilon),
+            opc: Self::OP_GE,
+            rhs_hi: Some((Lit::new_float(value + epsilon), Self::OP_LE)),
+            lhs_expr: None,
+        })
+    }
+    /// Parse the rest of `x BETWEEN a AND b` (the cursor is right after `BETWEEN`)
+    fn try_parse_between<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+        ident: &'a Token<'a>,
+    ) -> Option<Self> {
+        if compiler::unlikely(state.remaining() < 3) {
+            state.poison();
+            return compiler::cold_val(None);
+        }
+        let lo = parse_value(state)?;
+        state.poison_if_not(state.cursor_rounded_eq(Token![and]));
+        state.cursor_ahead_if(state.okay());
+        if compiler::unlikely(!state.okay()) {
+            return None;
+        }
+        let hi = parse_value(state)?;
+        // UNSAFE(@ohsayan): the caller checked that this is an ident
+        Some(Self {
+            lhs: unsafe { ident.uck_read_ident() },
+            rhs: lo,
+            opc: Self::OP_GE,
+            rhs_hi: Some((hi, Self::OP_LE)),
+            lhs_expr: None,
+        })
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -141,7 +759,14 @@ impl<'a> WhereClause<'a> {
         let mut has_more = true;
         while has_more && state.not_exhausted() && state.okay() {
             if let Some(expr) = RelationalExpr::try_parse(state) {
-                state.poison_if_not(c.insert(expr.lhs, expr).is_none());
+                match c.entry(expr.lhs) {
+                    Entry::Vacant(v) => {
+                        v.insert(expr);
+                    }
+                    Entry::Occupied(mut o) => {
+                        state.poison_if_not(o.get_mut().try_merge_range(expr))
+                    }
+                }
             }
             has_more = state.cursor_rounded_eq(Token![and]);
             state.cursor_ahead_if(has_more);
@@ -24,16 +24,16 @@
  *
 */
 
-#[cfg(test)]
-use super::WhereClauseCollection;
 use {
-    super::WhereClause,
+    super::{RelationalExpr, RowExpr, WhereClause, WhereClauseCollection},
     crate::{
         engine::{
             core::EntityIDRef,
+            data::lit::Lit,
             error::{QueryError, QueryResult},
             ql::{
                 ast::{QueryData, State},
+                ddl::syn::FieldSpec,
                 lex::{Ident, Token},
             },
         },
@@ -53,8 +53,81 @@ pub struct SelectStatement<'a> {
     pub(super) fields: Vec<Ident<'a>>,
     /// whether a wildcard was passed
     pub(super) wildcard: bool,
+    /// the expression for each column, if the projection uses a function. `fields` then holds all the fields
+    /// that are read
+    pub(super) projection: Option<Vec<RowExpr<'a>>>,
     /// where clause
     pub(super) clause: WhereClause<'a>,
+    /// the keys for a multi-get (`where pk in (...)`). the where clause is empty when set
+    pub(super) keys: Option<SelectKeys<'a>>,
+    /// if set, soft deleted rows are returned as well
+    pub(super) with_deleted: bool,
+    /// if set, the rows are returned as they were at this time (in seconds since the UNIX epoch)
+    pub(super) as_of: Option<u64>,
+}
+
+/// The keys of a multi-get: `where pk in (k1, k2, ...) [skip missing]`
+#[derive(Debug, PartialEq)]
+pub struct SelectKeys<'a> {
+    pub(super) field: Ident<'a>,
+    pub(super) keys: Vec<Lit<'a>>,
+    /// if set, missing keys are left out of the result instead of being returned as a row of nulls
+    pub(super) skip_missing: bool,
+}
+
+impl<'a> SelectKeys<'a> {
+    #[cfg(test)]
+    pub fn new(field: Ident<'a>, keys: Vec<Lit<'a>>, skip_missing: bool) -> Self {
+        Self {
+            field,
+            keys,
+            skip_missing,
+        }
+    }
+    pub fn field(&self) -> Ident<'a> {
+        self.field
+    }
+    pub fn keys(&self) -> &[Lit<'a>] {
+        &self.keys
+    }
+    pub fn skip_missing(&self) -> bool {
+        self.skip_missing
+    }
+    /// Parse `field in (k1, k2, ...) [skip missing]`. The caller must ensure that the cursor is at an ident that is
+    /// followed by `in`
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Self {
+        let field = unsafe {
+            // UNSAFE(@ohsayan): verified by caller
+            state.fw_read().uck_read_ident()
+        };
+        state.cursor_ahead(); // in
+        state.poison_if_not(state.cursor_rounded_eq(Token![() open]));
+        state.cursor_ahead_if(state.okay());
+        let mut keys = Vec::new();
+        let mut nx_comma = true;
+        while state.okay() && nx_comma && state.can_read_lit_rounded() {
+            keys.push(unsafe {
+                // UNSAFE(@ohsayan): verified in loop condition
+                state.read_cursor_lit_unchecked()
+            });
+            state.cursor_ahead();
+            nx_comma = state.cursor_rounded_eq(Token![,]);
+            state.cursor_ahead_if(nx_comma);
+        }
+        // we can't have an empty list or a trailing comma
+        let nx_close = state.cursor_rounded_eq(Token![() close]);
+        state.poison_if(nx_comma | !nx_close);
+        state.cursor_ahead_if(nx_close);
+        let skip_missing = state.has_remaining(2)
+            && state.read().ident_eq("skip")
+            && state.offset_current_r(1).ident_eq("missing");
+        state.cursor_ahead_by(2 * skip_missing as usize);
+        Self {
+            field,
+            keys,
+            skip_missing,
+        }
+    }
 }
 
 impl<'a> SelectStatement<'a> {
@@ -80,7 +153,24 @@ impl<'a> SelectStatement<'a> {
             entity,
             fields,
             wildcard,
+            projection: None,
             clause: WhereClause::new(clauses),
+            keys: None,
+            with_deleted: false,
+            as_of: None,
+        }
+    }
+    #[inline(always)]
+    #[cfg(test)]
+    pub(crate) fn new_test_keys(
+        entity: EntityIDRef<'a>,
+        fields: Vec<Ident<'a>>,
+        wildcard: bool,
+        keys: SelectKeys<'a>,
+    ) -> SelectStatement<'a> {
+        Self {
+            keys: Some(keys),
+            ..Self::new(entity, fields, wildcard, Default::default())
         }
     }
     pub fn entity(&self) -> EntityIDRef<'a> {
@@ -92,6 +182,21 @@ impl<'a> SelectStatement<'a> {
     pub fn is_wildcard(&self) -> bool {
         self.wildcard
     }
+    pub fn fields(&self) -> &[Ident<'a>] {
+        &self.fields
+    }
+    pub fn take_keys(&mut self) -> Option<SelectKeys<'a>> {
+        self.keys.take()
+    }
+    pub fn take_projection(&mut self) -> Option<Vec<RowExpr<'a>>> {
+        self.projection.take()
+    }
+    pub fn with_deleted(&self) -> bool {
+        self.with_deleted
+    }
+    pub fn as_of(&self) -> Option<u64> {
+        self.as_of
+    }
     pub fn into_fields(self) -> Vec<Ident<'a>> {
         self.fields
     }
@@ -104,25 +209,19 @@ impl<'a> SelectStatement<'a> {
             select * from model
                    ^ ^    ^
                    1 2    3
+            multi-get:
+            select * from model where k in (1, 2, 3) [skip missing]
+            with soft deleted rows:
+            select * from model where k = 1 with deleted
+            a prior version:
+            select * from model where k = 1 as of 1700000000
+            with functions:
+            select k, upper(v) from model where k = 1
         */
         if compiler::unlikely(state.remaining() < 3) {
             return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
         }
-        let mut select_fields = Vec::new();
-        let is_wildcard = state.cursor_eq(Token![*]);
-        state.cursor_ahead_if(is_wildcard);
-        while state.not_exhausted() && state.okay() && !is_wildcard {
-            match state.read() {
-                Token::Ident(id) => select_fields.push(*id),
-                _ => break,
-            }
-            state.cursor_ahead();
-            let nx_comma = state.cursor_rounded_eq(Token![,]);
-            let nx_from = state.cursor_rounded_eq(Token![from]);
-            state.poison_if_not(nx_comma | nx_from);
-            state.cursor_ahead_if(nx_comma);
-        }
-        state.poison_if_not(is_wildcard | !select_fields.is_empty());
+        let (select_fields, is_wildcard, projection) = super::parse_projection(state);
         // we should have from + model
         if compiler::unlikely(state.remaining() < 2 || !state.okay()) {
             return compiler::cold_rerr(QueryError::QLInvalidSyntax);
@@ -131,11 +230,22 @@ impl<'a> SelectStatement<'a> {
         state.cursor_ahead(); // ignore errors
         let entity = state.try_entity_buffered_into_state_uninit();
         let mut clauses = <_ as Default>::default();
+        let mut keys = None;
         if state.cursor_rounded_eq(Token![where]) {
             state.cursor_ahead();
-            WhereClause::parse_where_and_append_to(state, &mut clauses);
-            state.poison_if(clauses.is_empty());
+            if state.cursor_has_ident_rounded() & (*state.offset_current_r(1) == Token![in]) {
+                keys = Some(SelectKeys::parse(state));
+            } else {
+                WhereClause::parse_where_and_append_to(state, &mut clauses);
+                state.poison_if(clauses.is_empty());
+            }
         }
+        // a multi-get can't use functions
+        state.poison_if(keys.is_some() & projection.is_some());
+        let as_of = super::parse_optional_as_of(state);
+        let with_deleted = super::parse_with(state, "deleted");
+        // prior versions never include soft deleted rows
+        state.poison_if(as_of.is_some() & with_deleted);
         if compiler::likely(state.okay()) {
             Ok(SelectStatement {
                 entity: unsafe {
@@ -144,7 +254,11 @@ impl<'a> SelectStatement<'a> {
                 },
                 fields: select_fields,
                 wildcard: is_wildcard,
+                projection,
                 clause: WhereClause::new(clauses),
+                keys,
+                with_deleted,
+                as_of,
             })
         } else {
             compiler::cold_rerr(QueryError::QLInvalidSyntax)
@@ -157,7 +271,30 @@ pub struct SelectAllStatement<'a> {
     pub entity: EntityIDRef<'a>,
     pub fields: Vec<Ident<'a>>,
     pub wildcard: bool,
+    /// the expression for each column, if the projection uses a function. `fields` then holds all the fields
+    /// that are read
+    pub projection: Option<Vec<RowExpr<'a>>>,
+    pub clause: WhereClause<'a>,
     pub limit: u64,
+    /// the number of matching rows to skip before returning rows
+    pub offset: u64,
+    /// if set, soft deleted rows are returned as well
+    pub with_deleted: bool,
+    /// if set, a random sample of the matching rows is returned
+    pub sample: Option<SampleSize>,
+    /// if set, the rows are returned as an Arrow IPC stream (`format arrow`)
+    pub arrow: bool,
+    /// if set, the number of matching rows is returned along with the page (`with total`)
+    pub with_total: bool,
+}
+
+/// The size of the random sample taken by `select all ... sample`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SampleSize {
+    /// at most this many rows, picked uniformly (`sample n`)
+    Rows(u64),
+    /// each row is picked with this probability, as a percentage (`sample n percent`)
+    Percent(f64),
 }
 
 impl<'a> SelectAllStatement<'a> {
@@ -168,71 +305,778 @@ impl<'a> SelectAllStatement<'a> {
         wildcard: bool,
         limit: u64,
     ) -> Self {
-        Self::new(entity, fields, wildcard, limit)
+        Self::new(entity, fields, wildcard, Default::default(), limit, 0)
+    }
+    #[cfg(test)]
+    pub fn test_new_where(
+        entity: EntityIDRef<'a>,
+        fields: Vec<Ident<'a>>,
+        wildcard: bool,
+        clauses: WhereClauseCollection<'a>,
+        limit: u64,
+    ) -> Self {
+        Self::new(entity, fields, wildcard, clauses, limit, 0)
     }
-    fn new(entity: EntityIDRef<'a>, fields: Vec<Ident<'a>>, wildcard: bool, limit: u64) -> Self {
+    fn new(
+        entity: EntityIDRef<'a>,
+        fields: Vec<Ident<'a>>,
+        wildcard: bool,
+        clauses: WhereClauseCollection<'a>,
+        limit: u64,
+        offset: u64,
+    ) -> Self {
         Self {
             entity,
             fields,
             wildcard,
+            projection: None,
+            clause: WhereClause::new(clauses),
             limit,
+            offset,
+            with_deleted: false,
+            sample: None,
+            arrow: false,
+            with_total: false,
         }
     }
+    pub fn clauses_mut(&mut self) -> &mut WhereClause<'a> {
+        &mut self.clause
+    }
     fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
         /*
             smallest query: select all * from mymodel limit 10
+            with a filter: select all * from mymodel where k >= 10 limit 10
+            with an offset: select all * from mymodel limit 10 offset 20
+            with soft deleted rows: select all * from mymodel limit 10 with deleted
+            with the number of matching rows: select all * from mymodel limit 10 offset 20 with total
+            with a random sample: select all * from mymodel limit 10 sample 5 (or sample 1.5 percent)
+            as an arrow stream: select all * from mymodel limit 10 format arrow
         */
         if state.remaining() < 5 {
             return Err(QueryError::QLUnexpectedEndOfStatement);
         }
-        let mut select_fields = Vec::new();
-        let is_wildcard = state.cursor_eq(Token![*]);
-        state.cursor_ahead_if(is_wildcard);
-        while state.not_exhausted() && state.okay() && !is_wildcard {
-            match state.read() {
-                Token::Ident(id) => select_fields.push(*id),
-                _ => break,
-            }
-            state.cursor_ahead();
-            let nx_comma = state.cursor_rounded_eq(Token![,]);
-            let nx_from = state.cursor_rounded_eq(Token![from]);
-            state.poison_if_not(nx_comma | nx_from);
-            state.cursor_ahead_if(nx_comma);
-        }
-        state.poison_if_not(is_wildcard | !select_fields.is_empty());
+        let (select_fields, is_wildcard, projection) = super::parse_projection(state);
         if state.remaining() < 4 {
             return Err(QueryError::QLUnexpectedEndOfStatement);
         }
         state.poison_if_not(state.cursor_eq(Token![from]));
         state.cursor_ahead(); // ignore error
         let entity = state.try_entity_buffered_into_state_uninit();
+        let mut clauses = <_ as Default>::default();
+        if state.cursor_rounded_eq(Token![where]) {
+            state.cursor_ahead();
+            WhereClause::parse_where_and_append_to(state, &mut clauses);
+            state.poison_if(clauses.is_empty());
+        }
         state.poison_if_not(state.cursor_rounded_eq(Token![limit]));
         state.cursor_ahead_if(state.okay()); // we did read limit
         state.poison_if(state.exhausted()); // we MUST have the limit
-        if state.okay() {
-            let lit = unsafe { state.fw_read().uck_read_lit() };
-            match lit.try_uint() {
-                Some(limit) => {
+        if state.okay() && state.can_read_lit_rounded() {
+            let lit = unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.read_cursor_lit_unchecked()
+            };
+            state.cursor_ahead();
+            if let Some(limit) = lit.try_uint() {
+                let offset = Self::parse_offset(state);
+                let sample = Self::parse_sample(state);
+                // a sample is taken from all matching rows, so skipping rows is meaningless
+                state.poison_if((offset != 0) & sample.is_some());
+                let with_deleted = super::parse_with(state, "deleted");
+                let with_total = super::parse_with(state, "total");
+                let arrow = state.has_remaining(2)
+                    && state.read().ident_eq("format")
+                    && state.offset_current_r(1).ident_eq("arrow");
+                state.cursor_ahead_by(arrow as usize * 2);
+                // an arrow stream starts with its schema, but the types of computed columns are only known once
+                // they're evaluated
+                state.poison_if(arrow & projection.is_some());
+                // an arrow stream only holds the rows, so it has nowhere to put the total
+                state.poison_if(arrow & with_total);
+                if state.okay() {
                     return unsafe {
                         // UNSAFE(@ohsayan): state guarantees this works
-                        Ok(Self::new(
-                            entity.assume_init(),
-                            select_fields,
-                            is_wildcard,
-                            limit,
-                        ))
+                        Ok(Self {
+                            with_deleted,
+                            sample,
+                            arrow,
+                            with_total,
+                            projection,
+                            ..Self::new(
+                                entity.assume_init(),
+                                select_fields,
+                                is_wildcard,
+                                clauses,
+                                limit,
+                                offset,
+                            )
+                        })
                     };
                 }
-                _ => {}
             }
         }
         Err(QueryError::QLInvalidSyntax)
     }
+    /// Parse an optional `OFFSET n` (right after the limit). Returns 0 if there is no offset
+    fn parse_offset<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> u64 {
+        if !(state.not_exhausted() && state.read().ident_eq("offset")) {
+            return 0;
+        }
+        state.cursor_ahead();
+        state.poison_if_not(state.can_read_lit_rounded());
+        if compiler::unlikely(!state.okay()) {
+            return 0;
+        }
+        let lit = unsafe {
+            // UNSAFE(@ohsayan): verified above
+            state.read_cursor_lit_unchecked()
+        };
+        state.cursor_ahead();
+        let offset = lit.try_uint();
+        state.poison_if(offset.is_none());
+        offset.unwrap_or(0)
+    }
+    /// Parse an optional `SAMPLE n` or `SAMPLE n PERCENT` (right after the offset). A percentage can be fractional
+    /// but must be in `(0, 100]`
+    fn parse_sample<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<SampleSize> {
+        if !(state.okay() && state.not_exhausted() && state.read().ident_eq("sample")) {
+            return None;
+        }
+        state.cursor_ahead();
+        state.poison_if_not(state.can_read_lit_rounded());
+        if compiler::unlikely(!state.okay()) {
+            return None;
+        }
+        let lit = unsafe {
+            // UNSAFE(@ohsayan): verified above
+            state.read_cursor_lit_unchecked()
+        };
+        state.cursor_ahead();
+        let percent = state.not_exhausted() && state.read().ident_eq("percent");
+        state.cursor_ahead_if(percent);
+        let sample = if percent {
+            lit.try_uint()
+                .map(|p| p as f64)
+                .or_else(|| lit.try_float())
+                .filter(|p| (*p > 0.0) & (*p <= 100.0))
+                .map(SampleSize::Percent)
+        } else {
+            lit.try_uint().map(SampleSize::Rows)
+        };
+        state.poison_if(sample.is_none());
+        sample
+    }
+}
+
+/*
+    Impls for fetch
+*/
+
+/// A batch of point lookups, possibly across models: `fetch space.model(key), model(key), ...`
+#[derive(Debug, PartialEq)]
+pub struct FetchStatement<'a> {
+    /// the entity and primary key of each lookup, in the order they were requested
+    pub(super) items: Vec<(EntityIDRef<'a>, Lit<'a>)>,
+}
+
+impl<'a> FetchStatement<'a> {
+    #[cfg(test)]
+    pub fn new_test(items: Vec<(EntityIDRef<'a>, Lit<'a>)>) -> Self {
+        Self { items }
+    }
+    pub fn items(&self) -> &[(EntityIDRef<'a>, Lit<'a>)] {
+        &self.items
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            smallest query: fetch mymodel(1) (with the current space set)
+            multiple models: fetch myspace.users('sayan'), myspace.posts(10)
+        */
+        let mut items = Vec::new();
+        let mut nx_comma = true;
+        while state.okay() && nx_comma && state.not_exhausted() {
+            let entity = state.try_entity_buffered_into_state_uninit();
+            state.poison_if_not(state.cursor_rounded_eq(Token![() open]));
+            state.cursor_ahead_if(state.okay());
+            state.poison_if_not(state.can_read_lit_rounded());
+            if compiler::unlikely(!state.okay()) {
+                break;
+            }
+            unsafe {
+                // UNSAFE(@ohsayan): the entity is verified by `okay` and the lit by the check above
+                items.push((entity.assume_init(), state.read_cursor_lit_unchecked()));
+            }
+            state.cursor_ahead();
+            state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+            state.cursor_ahead_if(state.okay());
+            nx_comma = state.cursor_rounded_eq(Token![,]);
+            state.cursor_ahead_if(nx_comma);
+        }
+        // we can't have an empty list or a trailing comma
+        state.poison_if(nx_comma);
+        if compiler::likely(state.okay()) {
+            Ok(Self { items })
+        } else {
+            compiler::cold_rerr(QueryError::QLInvalidSyntax)
+        }
+    }
+}
+
+/*
+    Impls for aggregates
+*/
+
+/// An aggregate function over a field
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Aggregate<'a> {
+    /// `count(*)`: the number of rows
+    Count,
+    /// `count(distinct field)`: the (approximate) number of distinct values
+    CountDistinct(Ident<'a>),
+    /// `topk(field, k)`: the (approximate) `k` most frequent values, along with their counts
+    TopK(Ident<'a>, u64),
+    /// `sum(field)`: the sum of the (numeric) values
+    Sum(Ident<'a>),
+}
+
+impl<'a> Aggregate<'a> {
+    /// the largest `k` accepted by `topk`
+    pub const TOPK_MAX_K: u64 = 1000;
+    /// Returns the field that the aggregate is computed over (if any)
+    pub fn field(&self) -> Option<Ident<'a>> {
+        match self {
+            Self::Count => None,
+            Self::CountDistinct(field) | Self::TopK(field, _) | Self::Sum(field) => Some(*field),
+        }
+    }
+    /// Returns true if the cursor is at an aggregate function (a name, other than that of a row function or `cast`, followed
+    /// by an opening parenthesis)
+    pub fn is_next<Qd: QueryData<'a>>(state: &State<'a, Qd>) -> bool {
+        state.has_remaining(2)
+            && state.cursor_has_ident_rounded()
+            && *state.offset_current_r(1) == Token![() open]
+            && !RowExpr::is_call(state)
+    }
+    /// Parse a comma separated list of aggregates
+    fn parse_list<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Vec<Self> {
+        let mut aggregates = Vec::new();
+        let mut nx_comma = true;
+        while state.okay() && nx_comma {
+            if let Some(aggregate) = Self::parse(state) {
+                aggregates.push(aggregate);
+            }
+            nx_comma = state.cursor_rounded_eq(Token![,]);
+            state.cursor_ahead_if(nx_comma);
+        }
+        aggregates
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<Self> {
+        /*
+            count(*)
+            count(distinct field)
+            topk(field, k)
+            sum(field)
+        */
+        state.poison_if_not(Self::is_next(state));
+        if compiler::unlikely(!state.okay()) {
+            return None;
+        }
+        let func = state.fw_read();
+        state.cursor_ahead(); // open paren
+        if func.ident_eq("count") && state.cursor_rounded_eq(Token![*]) {
+            state.cursor_ahead();
+            state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+            state.cursor_ahead_if(state.okay());
+            return state.okay().then_some(Self::Count);
+        }
+        let count =
+            func.ident_eq("count") && state.not_exhausted() && state.read().ident_eq("distinct");
+        state.cursor_ahead_if(count);
+        let sum = func.ident_eq("sum");
+        state.poison_if_not(count | sum | func.ident_eq("topk"));
+        state.poison_if_not(state.cursor_has_ident_rounded());
+        if compiler::unlikely(!state.okay()) {
+            return None;
+        }
+        let field = match state.fw_read() {
+            Token::Ident(field) => *field,
+            _ => unreachable!(),
+        };
+        let aggregate = if count {
+            Self::CountDistinct(field)
+        } else if sum {
+            Self::Sum(field)
+        } else {
+            state.poison_if_not(state.cursor_rounded_eq(Token![,]));
+            state.cursor_ahead_if(state.okay());
+            state.poison_if_not(state.can_read_lit_rounded());
+            if compiler::unlikely(!state.okay()) {
+                return None;
+            }
+            let k = unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.read_cursor_lit_unchecked()
+            }
+            .try_uint();
+            state.cursor_ahead();
+            let k = k.unwrap_or(0);
+            state.poison_if((k == 0) | (k > Self::TOPK_MAX_K));
+            Self::TopK(field, k)
+        };
+        state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+        state.cursor_ahead_if(state.okay());
+        state.okay().then_some(aggregate)
+    }
+}
+
+/// Parse an optional `group by field [having ...]` clause. The state is poisoned if the clause is invalid
+fn parse_optional_group_by<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+) -> (Option<Ident<'a>>, Vec<HavingExpr<'a>>) {
+    let mut group_by = None;
+    if state.cursor_rounded_eq(Token![group]) {
+        state.cursor_ahead();
+        state.poison_if_not(state.cursor_rounded_eq(Token![by]));
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.cursor_has_ident_rounded());
+        if state.okay() {
+            group_by = Some(unsafe {
+                // UNSAFE(@ohsayan): verified above
+                state.fw_read().uck_read_ident()
+            });
+        }
+    }
+    let mut having = Vec::new();
+    if group_by.is_some() && state.not_exhausted() && state.read().ident_eq("having") {
+        state.cursor_ahead();
+        let mut has_more = true;
+        while has_more && state.okay() {
+            if let Some(expr) = HavingExpr::parse(state) {
+                having.push(expr);
+            }
+            has_more = state.cursor_rounded_eq(Token![and]);
+            state.cursor_ahead_if(has_more);
+        }
+    }
+    (group_by, having)
+}
+
+/// A `having` predicate: a comparison of an aggregate (other than `topk`) with a literal, such as `count(*) > 10`
+#[derive(Debug, PartialEq)]
+pub struct HavingExpr<'a> {
+    aggregate: Aggregate<'a>,
+    /// the comparison (the lhs is the name of the aggregate function)
+    expr: RelationalExpr<'a>,
+}
+
+impl<'a> HavingExpr<'a> {
+    #[cfg(test)]
+    pub fn new_test(aggregate: Aggregate<'a>, expr: RelationalExpr<'a>) -> Self {
+        Self { aggregate, expr }
+    }
+    pub fn aggregate(&self) -> Aggregate<'a> {
+        self.aggregate
+    }
+    pub fn expr(&self) -> &RelationalExpr<'a> {
+        &self.expr
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> Option<Self> {
+        if compiler::unlikely(state.exhausted()) {
+            state.poison();
+            return None;
+        }
+        let func = state.read();
+        let aggregate = Aggregate::parse(state)?;
+        // a list can't be compared
+        state.poison_if(matches!(aggregate, Aggregate::TopK(..)));
+        let expr = RelationalExpr::try_parse_rhs(state, func)?;
+        state.poison_if(expr.matches_pattern().is_some());
+        state.okay().then_some(Self { aggregate, expr })
+    }
+}
+
+/// An aggregate query: `select count(distinct field), topk(field, k) from model [where ...] [group by field [having
+/// ...]]`. The aggregates are computed over all the rows that match the where clause, and are returned as a single
+/// row. With a `group by`, the aggregates are computed for each distinct value of the grouping field and one row is
+/// returned per group (the value of the grouping field, followed by the aggregates) that satisfies all the `having`
+/// predicates
+#[derive(Debug, PartialEq)]
+pub struct SelectAggregateStatement<'a> {
+    /// the entity
+    pub(super) entity: EntityIDRef<'a>,
+    /// the aggregates, in the order they were requested
+    pub(super) aggregates: Vec<Aggregate<'a>>,
+    /// where clause
+    pub(super) clause: WhereClause<'a>,
+    /// the field to group by
+    pub(super) group_by: Option<Ident<'a>>,
+    /// the predicates that a group must satisfy
+    pub(super) having: Vec<HavingExpr<'a>>,
+}
+
+impl<'a> SelectAggregateStatement<'a> {
+    #[cfg(test)]
+    pub fn new_test(
+        entity: EntityIDRef<'a>,
+        aggregates: Vec<Aggregate<'a>>,
+        clauses: WhereClauseCollection<'a>,
+    ) -> Self {
+        Self {
+            entity,
+            aggregates,
+            clause: WhereClause::new(clauses),
+            group_by: None,
+            having: Vec::new(),
+        }
+    }
+    #[cfg(test)]
+    pub fn with_group_by(mut self, field: Ident<'a>) -> Self {
+        self.group_by = Some(field);
+        self
+    }
+    #[cfg(test)]
+    pub fn with_having(mut self, having: Vec<HavingExpr<'a>>) -> Self {
+        self.having = having;
+        self
+    }
+    pub fn entity(&self) -> EntityIDRef<'a> {
+        self.entity
+    }
+    pub fn aggregates(&self) -> &[Aggregate<'a>] {
+        &self.aggregates
+    }
+    pub fn group_by(&self) -> Option<Ident<'a>> {
+        self.group_by
+    }
+    pub fn having(&self) -> &[HavingExpr<'a>] {
+        &self.having
+    }
+    pub fn clauses_mut(&mut self) -> &mut WhereClause<'a> {
+        &mut self.clause
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            smallest query: select count(distinct field) from model
+        */
+        let aggregates = Aggregate::parse_list(state);
+        // we should have from + model
+        if compiler::unlikely(state.remaining() < 2 || !state.okay()) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        state.poison_if_not(state.cursor_eq(Token![from]));
+        state.cursor_ahead(); // ignore errors
+        let entity = state.try_entity_buffered_into_state_uninit();
+        let mut clauses = <_ as Default>::default();
+        if state.cursor_rounded_eq(Token![where]) {
+            state.cursor_ahead();
+            WhereClause::parse_where_and_append_to(state, &mut clauses);
+            state.poison_if(clauses.is_empty());
+        }
+        let (group_by, having) = parse_optional_group_by(state);
+        if compiler::likely(state.okay()) {
+            Ok(Self {
+                entity: unsafe {
+                    // UNSAFE(@ohsayan): `process_entity` and `okay` assert correctness
+                    entity.assume_init()
+                },
+                aggregates,
+                clause: WhereClause::new(clauses),
+                group_by,
+                having,
+            })
+        } else {
+            compiler::cold_rerr(QueryError::QLInvalidSyntax)
+        }
+    }
+}
+
+/*
+    Impls for exists
+*/
+
+/// An existence check: `exists from <model> [where ...]`. Returns true if at least one (not soft deleted) row matches
+/// the where clause (or, without one, if the model has any row). The where clause can use any field, and the scan
+/// stops at the first matching row
+#[derive(Debug, PartialEq)]
+pub struct ExistsStatement<'a> {
+    /// the entity
+    pub(super) entity: EntityIDRef<'a>,
+    /// where clause
+    pub(super) clause: WhereClause<'a>,
+}
+
+impl<'a> ExistsStatement<'a> {
+    #[cfg(test)]
+    pub fn new_test(entity: EntityIDRef<'a>, clauses: WhereClauseCollection<'a>) -> Self {
+        Self {
+            entity,
+            clause: WhereClause::new(clauses),
+        }
+    }
+    pub fn entity(&self) -> EntityIDRef<'a> {
+        self.entity
+    }
+    pub fn clauses_mut(&mut self) -> &mut WhereClause<'a> {
+        &mut self.clause
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            smallest query: exists from model
+        */
+        if compiler::unlikely(state.remaining() < 2) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        state.poison_if_not(state.cursor_eq(Token![from]));
+        state.cursor_ahead(); // ignore errors
+        let entity = state.try_entity_buffered_into_state_uninit();
+        let mut clauses = <_ as Default>::default();
+        if state.cursor_rounded_eq(Token![where]) {
+            state.cursor_ahead();
+            WhereClause::parse_where_and_append_to(state, &mut clauses);
+            state.poison_if(clauses.is_empty());
+        }
+        if compiler::likely(state.okay()) {
+            Ok(Self {
+                entity: unsafe {
+                    // UNSAFE(@ohsayan): `process_entity` and `okay` assert correctness
+                    entity.assume_init()
+                },
+                clause: WhereClause::new(clauses),
+            })
+        } else {
+            compiler::cold_rerr(QueryError::QLInvalidSyntax)
+        }
+    }
+}
+
+/*
+    Impls for foreign scans
+*/
+
+/// The format of a file read by a foreign scan. Only CSV files can be read: Parquet (or any other format) fails with
+/// [`QueryError::QExecFileFormatUnsupported`], with the format in the detail
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FileFormat {
+    /// comma separated values (RFC 4180), with one record per row
+    Csv,
+}
+
+/// The projection of a foreign scan
+#[derive(Debug, PartialEq)]
+pub enum FileProjection<'a> {
+    /// all fields, in schema order
+    Wildcard,
+    /// the given fields, in order
+    Fields(Vec<Ident<'a>>),
+    /// the given aggregates (optionally grouped)
+    Aggregates(Vec<Aggregate<'a>>),
+}
+
+/// A foreign scan: `select <projection> from file '<path>' (format csv[, header], schema (<fields>)) [where ...]
+/// [group by field [having ...]] [limit n]`. The records of the file are decoded using the schema (which uses the
+/// same syntax as the fields of a model), filtered and aggregated one at a time, so the file is never loaded into
+/// memory or into a model
+#[derive(Debug, PartialEq)]
+pub struct SelectFileStatement<'a> {
+    /// the path to the (local) file
+    pub(super) path: &'a str,
+    pub(super) format: FileFormat,
+    /// if set, the first record is a header and is skipped
+    pub(super) header: bool,
+    /// the fields of each record, in order
+    pub(super) schema: Vec<FieldSpec<'a>>,
+    pub(super) projection: FileProjection<'a>,
+    /// where clause
+    pub(super) clause: WhereClause<'a>,
+    /// the field to group by (only for aggregates)
+    pub(super) group_by: Option<Ident<'a>>,
+    /// the predicates that a group must satisfy
+    pub(super) having: Vec<HavingExpr<'a>>,
+    /// the maximum number of rows returned
+    pub(super) limit: Option<u64>,
+}
+
+impl<'a> SelectFileStatement<'a> {
+    #[cfg(test)]
+    pub fn new_test(
+        path: &'a str,
+        header: bool,
+        schema: Vec<FieldSpec<'a>>,
+        projection: FileProjection<'a>,
+        clauses: WhereClauseCollection<'a>,
+        limit: Option<u64>,
+    ) -> Self {
+        Self {
+            path,
+            format: FileFormat::Csv,
+            header,
+            schema,
+            projection,
+            clause: WhereClause::new(clauses),
+            group_by: None,
+            having: Vec::new(),
+            limit,
+        }
+    }
+    /// Returns true if the rest of a `select` reads from a file (`... from file '<path>' ...`)
+    pub fn is_next<Qd: QueryData<'a>>(state: &State<'a, Qd>) -> bool {
+        state
+            .current()
+            .windows(3)
+            .any(|w| (w[0] == Token![from]) & w[1].ident_eq("file") & matches!(w[2], Token::Lit(_)))
+    }
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+    pub fn format(&self) -> FileFormat {
+        self.format
+    }
+    pub fn header(&self) -> bool {
+        self.header
+    }
+    pub fn take_schema(&mut self) -> Vec<FieldSpec<'a>> {
+        core::mem::take(&mut self.schema)
+    }
+    pub fn projection(&self) -> &FileProjection<'a> {
+        &self.projection
+    }
+    pub fn group_by(&self) -> Option<Ident<'a>> {
+        self.group_by
+    }
+    pub fn having(&self) -> &[HavingExpr<'a>] {
+        &self.having
+    }
+    pub fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+    pub fn clauses_mut(&mut self) -> &mut WhereClause<'a> {
+        &mut self.clause
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            smallest query: select * from file 'data.csv' (format csv, schema (a: string))
+        */
+        let projection = if Aggregate::is_next(state) {
+            FileProjection::Aggregates(Aggregate::parse_list(state))
+        } else if state.cursor_rounded_eq(Token![*]) {
+            state.cursor_ahead();
+            FileProjection::Wildcard
+        } else {
+            let mut fields = Vec::new();
+            let mut nx_comma = true;
+            while state.okay() && nx_comma {
+                state.poison_if_not(state.cursor_has_ident_rounded());
+                if state.okay() {
+                    fields.push(unsafe {
+                        // UNSAFE(@ohsayan): verified above
+                        state.fw_read().uck_read_ident()
+                    });
+                }
+                nx_comma = state.cursor_rounded_eq(Token![,]);
+                state.cursor_ahead_if(nx_comma);
+            }
+            FileProjection::Fields(fields)
+        };
+        // we should have from + file + path + (
+        if compiler::unlikely(state.remaining() < 4 || !state.okay()) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        state.poison_if_not(state.cursor_eq(Token![from]));
+        state.cursor_ahead();
+        state.poison_if_not(state.read().ident_eq("file"));
+        state.cursor_ahead();
+        let path = match state.fw_read() {
+            Token::Lit(lit) => lit.try_str(),
+            _ => None,
+        };
+        state.poison_if(path.is_none());
+        state.poison_if_not(state.cursor_eq(Token![() open]));
+        state.cursor_ahead();
+        // options
+        let (mut format, mut header, mut schema) = (None, false, None);
+        let mut nx_comma = true;
+        while nx_comma && state.loop_tt() {
+            let option = state.fw_read();
+            if option.ident_eq("format") & format.is_none() & state.not_exhausted() {
+                match state.fw_read() {
+                    name if name.ident_eq("csv") => format = Some(FileFormat::Csv),
+                    // a format that we can't read (like parquet, which the server has no reader for)
+                    Token::Ident(name) => {
+                        return compiler::cold_rerr(
+                            QueryError::QExecFileFormatUnsupported
+                                .with_detail("format", name.as_str().to_ascii_lowercase()),
+                        )
+                    }
+                    _ => state.poison(),
+                }
+            } else if option.ident_eq("header") & !header {
+                header = true;
+            } else if option.ident_eq("schema") & schema.is_none() {
+                schema = Some(Self::parse_schema(state)?);
+            } else {
+                state.poison();
+            }
+            nx_comma = state.cursor_rounded_eq(Token![,]);
+            state.cursor_ahead_if(nx_comma);
+        }
+        state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+        state.cursor_ahead_if(state.okay());
+        let mut clauses = <_ as Default>::default();
+        if state.cursor_rounded_eq(Token![where]) {
+            state.cursor_ahead();
+            WhereClause::parse_where_and_append_to(state, &mut clauses);
+            state.poison_if(clauses.is_empty());
+        }
+        let (group_by, having) = parse_optional_group_by(state);
+        state.poison_if(group_by.is_some() & !matches!(projection, FileProjection::Aggregates(_)));
+        let limit = super::parse_optional_limit(state);
+        // aggregates always return every group
+        state.poison_if(limit.is_some() & matches!(projection, FileProjection::Aggregates(_)));
+        match (path, format, schema) {
+            (Some(path), Some(format), Some(schema)) if state.okay() => Ok(Self {
+                path,
+                format,
+                header,
+                schema,
+                projection,
+                clause: WhereClause::new(clauses),
+                group_by,
+                having,
+                limit,
+            }),
+            _ => compiler::cold_rerr(QueryError::QLInvalidSyntax),
+        }
+    }
+    /// Parse the fields of a schema: `(a: string, null b: uint8, ...)`
+    fn parse_schema<Qd: QueryData<'a>>(
+        state: &mut State<'a, Qd>,
+    ) -> QueryResult<Vec<FieldSpec<'a>>> {
+        state.poison_if_not(state.cursor_rounded_eq(Token![() open]));
+        state.cursor_ahead_if(state.okay());
+        let mut fields = Vec::new();
+        let mut stop = false;
+        while state.loop_tt() && !stop {
+            let field = FieldSpec::parse(state)?;
+            // there's no primary key in a file
+            state.poison_if(field.primary);
+            fields.push(field);
+            let nx_close = state.cursor_rounded_eq(Token![() close]);
+            let nx_comma = state.cursor_rounded_eq(Token![,]);
+            state.poison_if_not(nx_close | nx_comma);
+            state.cursor_ahead_if(nx_close | nx_comma);
+            stop = nx_close;
+        }
+        state.poison_if_not(stop);
+        Ok(fields)
+    }
 }
 
 mod impls {
     use {
-        super::{SelectAllStatement, SelectStatement},
+        super::{
+            ExistsStatement, FetchStatement, SelectAggregateStatement, SelectAllStatement,
+            SelectFileStatement, SelectStatement,
+        },
         crate::engine::{
             error::QueryResult,
             ql::ast::{traits::ASTNode, QueryData, State},
@@ -256,4 +1100,40 @@ mod impls {
             Self::parse(state)
         }
     }
+    impl<'a> ASTNode<'a> for FetchStatement<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
+    impl<'a> ASTNode<'a> for SelectAggregateStatement<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
+    impl<'a> ASTNode<'a> for ExistsStatement<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
+    impl<'a> ASTNode<'a> for SelectFileStatement<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
 }
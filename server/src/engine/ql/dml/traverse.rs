@@ -0,0 +1,190 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+use crate::{
+    engine::{
+        core::EntityIDRef,
+        data::lit::Lit,
+        error::{QueryError, QueryResult},
+        ql::{
+            ast::{QueryData, State},
+            lex::{Ident, Token},
+        },
+    },
+    util::compiler,
+};
+
+/// A bounded breadth-first expansion of the rows of a model, starting at a row and following a list field that holds
+/// the keys of other rows of the same model (an adjacency list):
+/// `traverse myspace.mymodel('start') via friends depth 2 limit 100 [fanout 10]`
+#[derive(Debug, PartialEq)]
+pub struct TraverseStatement<'a> {
+    entity: EntityIDRef<'a>,
+    start: Lit<'a>,
+    via: Ident<'a>,
+    depth: u64,
+    limit: u64,
+    fanout: Option<u64>,
+}
+
+impl<'a> TraverseStatement<'a> {
+    /// the deepest that a traversal can go
+    pub const MAX_DEPTH: u64 = 32;
+    #[cfg(test)]
+    pub fn new_test(
+        entity: EntityIDRef<'a>,
+        start: Lit<'a>,
+        via: Ident<'a>,
+        depth: u64,
+        limit: u64,
+        fanout: Option<u64>,
+    ) -> Self {
+        Self {
+            entity,
+            start,
+            via,
+            depth,
+            limit,
+            fanout,
+        }
+    }
+    pub fn entity(&self) -> EntityIDRef<'a> {
+        self.entity
+    }
+    /// Returns the key of the row that the traversal starts at
+    pub fn start(&self) -> &Lit<'a> {
+        &self.start
+    }
+    /// Returns the list field holding the keys of the adjacent rows
+    pub fn via(&self) -> Ident<'a> {
+        self.via
+    }
+    /// Returns the maximum distance (in edges) of a returned row from the start
+    pub fn depth(&self) -> u64 {
+        self.depth
+    }
+    /// Returns the maximum number of rows returned
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+    /// Returns the maximum number of adjacent rows followed from each row, if set
+    pub fn fanout(&self) -> Option<u64> {
+        self.fanout
+    }
+    fn parse<Qd: QueryData<'a>>(state: &mut State<'a, Qd>) -> QueryResult<Self> {
+        /*
+            smallest query: traverse mymodel('a') via friends depth 1 limit 10 (with the current space set)
+            with a fanout limit: traverse myspace.mymodel('a') via friends depth 3 limit 100 fanout 10
+        */
+        let entity = state.try_entity_buffered_into_state_uninit();
+        state.poison_if_not(state.cursor_rounded_eq(Token![() open]));
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.can_read_lit_rounded());
+        if compiler::unlikely(!state.okay()) {
+            return compiler::cold_rerr(QueryError::QLInvalidSyntax);
+        }
+        let start = unsafe {
+            // UNSAFE(@ohsayan): verified above
+            state.read_cursor_lit_unchecked()
+        };
+        state.cursor_ahead();
+        state.poison_if_not(state.cursor_rounded_eq(Token![() close]));
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.okay() && state.not_exhausted() && state.read().ident_eq("via"));
+        state.cursor_ahead_if(state.okay());
+        state.poison_if_not(state.cursor_has_ident_rounded());
+        let via = match state.okay().then(|| state.read()) {
+            Some(Token::Ident(via)) => *via,
+            _ => return compiler::cold_rerr(QueryError::QLInvalidSyntax),
+        };
+        state.cursor_ahead();
+        let depth = parse_uint_option(state, |tok| tok.ident_eq("depth"));
+        state.poison_if(depth.map_or(true, |depth| depth > Self::MAX_DEPTH));
+        let limit = parse_uint_option(state, |tok| *tok == Token![limit]);
+        let fanout = if state.okay() && state.not_exhausted() && state.read().ident_eq("fanout") {
+            let fanout = parse_uint_option(state, |_| true);
+            state.poison_if(fanout == Some(0));
+            fanout
+        } else {
+            None
+        };
+        match (depth, limit) {
+            (Some(depth), Some(limit)) if state.okay() => Ok(Self {
+                entity: unsafe {
+                    // UNSAFE(@ohsayan): verified by `okay`
+                    entity.assume_init()
+                },
+                start,
+                via,
+                depth,
+                limit,
+                fanout,
+            }),
+            _ => compiler::cold_rerr(QueryError::QLInvalidSyntax),
+        }
+    }
+}
+
+/// Parse `<option> <n>` where `n` is an unsigned integer, poisoning the state if the option is missing or the value
+/// is not an unsigned integer
+fn parse_uint_option<'a, Qd: QueryData<'a>>(
+    state: &mut State<'a, Qd>,
+    is_option: impl Fn(&Token) -> bool,
+) -> Option<u64> {
+    state.poison_if_not(state.has_remaining(2) && is_option(state.read()));
+    state.cursor_ahead_if(state.okay());
+    state.poison_if_not(state.can_read_lit_rounded());
+    if compiler::unlikely(!state.okay()) {
+        return None;
+    }
+    let value = unsafe {
+        // UNSAFE(@ohsayan): verified above
+        state.read_cursor_lit_unchecked()
+    }
+    .try_uint();
+    state.cursor_ahead();
+    state.poison_if(value.is_none());
+    value
+}
+
+mod impls {
+    use {
+        super::TraverseStatement,
+        crate::engine::{
+            error::QueryResult,
+            ql::ast::{traits::ASTNode, QueryData, State},
+        },
+    };
+    impl<'a> ASTNode<'a> for TraverseStatement<'a> {
+        const MUST_USE_FULL_TOKEN_RANGE: bool = true;
+        const VERIFIES_FULL_TOKEN_RANGE_USAGE: bool = false;
+        fn __base_impl_parse_from_state<Qd: QueryData<'a>>(
+            state: &mut State<'a, Qd>,
+        ) -> QueryResult<Self> {
+            Self::parse(state)
+        }
+    }
+}
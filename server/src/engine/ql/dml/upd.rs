@@ -25,7 +25,7 @@
 */
 
 use {
-    super::{u, WhereClause},
+    super::{u, Returning, RowExpr, Summary, WhereClause},
     crate::{
         engine::{
             core::{query_meta::AssignmentOperator, EntityIDRef},
@@ -57,8 +57,10 @@ static OPERATOR: [AssignmentOperator; 6] = [
 pub struct AssignmentExpression<'a> {
     /// the LHS ident
     pub lhs: Ident<'a>,
-    /// the RHS lit
+    /// the RHS lit (unused if `rhs_expr` is set)
     pub rhs: Lit<'a>,
+    /// the function on the RHS, if any. it is evaluated for every row that is updated
+    pub rhs_expr: Option<RowExpr<'a>>,
     /// operator
     pub operator_fn: AssignmentOperator,
 }
@@ -68,9 +70,16 @@ impl<'a> AssignmentExpression<'a> {
         Self {
             lhs,
             rhs,
+            rhs_expr: None,
             operator_fn,
         }
     }
+    pub fn new_expr(lhs: Ident<'a>, rhs: RowExpr<'a>, operator_fn: AssignmentOperator) -> Self {
+        Self {
+            rhs_expr: Some(rhs),
+            ..Self::new(lhs, Lit::new_bool(false), operator_fn)
+        }
+    }
     fn parse_and_append_expression<Qd: QueryData<'a>>(
         state: &mut State<'a, Qd>,
         expressions: &mut Vec<Self>,
@@ -104,20 +113,18 @@ impl<'a> AssignmentExpression<'a> {
         let single_assign_okay = operator_code == 1 && !double_assign_okay;
         state.poison_if_not(single_assign_okay | double_assign_okay);
         state.cursor_ahead_if(double_assign_okay);
-        state.poison_if_not(state.can_read_lit_rounded());
-
-        if state.okay() {
-            unsafe {
-                // UNSAFE(@ohsayan): Checked lit, state flag ensures we have ident for lhs
-                let rhs = state.read_cursor_lit_unchecked();
-                state.cursor_ahead();
-                expressions.push(AssignmentExpression::new(
-                    // UNSAFE(@ohsayan): we verified if `lhs` returns `is_ident`
-                    lhs.uck_read_ident(),
-                    rhs,
-                    OPERATOR[operator_code as usize],
-                ))
+        if !state.okay() {
+            return;
+        }
+        // UNSAFE(@ohsayan): we verified if `lhs` returns `is_ident`
+        let lhs = unsafe { lhs.uck_read_ident() };
+        let operator_fn = OPERATOR[operator_code as usize];
+        if RowExpr::is_call(state) {
+            if let Some(rhs) = RowExpr::parse_call(state, 0) {
+                expressions.push(AssignmentExpression::new_expr(lhs, rhs, operator_fn))
             }
+        } else if let Some(rhs) = super::parse_value(state) {
+            expressions.push(AssignmentExpression::new(lhs, rhs, operator_fn))
         }
     }
 }
@@ -127,6 +134,11 @@ pub struct UpdateStatement<'a> {
     pub(super) entity: EntityIDRef<'a>,
     pub(super) expressions: Vec<AssignmentExpression<'a>>,
     pub(super) wc: WhereClause<'a>,
+    pub(super) limit: Option<u64>,
+    pub(super) force: bool,
+    pub(super) returning: Option<Returning<'a>>,
+    pub(super) with_lsn: bool,
+    pub(super) summary: Option<Summary>,
 }
 
 impl<'a> UpdateStatement<'a> {
@@ -139,8 +151,24 @@ impl<'a> UpdateStatement<'a> {
     pub fn clauses_mut(&mut self) -> &mut WhereClause<'a> {
         &mut self.wc
     }
-    pub fn into_expressions(self) -> Vec<AssignmentExpression<'a>> {
-        self.expressions
+    /// Returns the maximum number of rows that this statement can update, if set
+    pub const fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+    /// Returns true if the statement explicitly allows updating all rows (`force`)
+    pub const fn force(&self) -> bool {
+        self.force
+    }
+    pub fn take_returning(&mut self) -> Option<Returning<'a>> {
+        self.returning.take()
+    }
+    /// Returns true if the statement should return the commit sequence number of its last change (`with lsn`)
+    pub const fn with_lsn(&self) -> bool {
+        self.with_lsn
+    }
+    /// Returns what the statement should return about the rows it changed, if it ends with `with summary` or `with keys`
+    pub const fn summary(&self) -> Option<Summary> {
+        self.summary
     }
 }
 
@@ -156,6 +184,39 @@ impl<'a> UpdateStatement<'a> {
             entity,
             expressions,
             wc,
+            limit: None,
+            force: false,
+            returning: None,
+            with_lsn: false,
+            summary: None,
+        }
+    }
+    #[inline(always)]
+    #[cfg(test)]
+    pub fn new_with_options(
+        entity: EntityIDRef<'a>,
+        expressions: Vec<AssignmentExpression<'a>>,
+        wc: WhereClause<'a>,
+        limit: Option<u64>,
+        force: bool,
+    ) -> Self {
+        Self {
+            limit,
+            force,
+            ..Self::new(entity, expressions, wc)
+        }
+    }
+    #[inline(always)]
+    #[cfg(test)]
+    pub fn new_returning(
+        entity: EntityIDRef<'a>,
+        expressions: Vec<AssignmentExpression<'a>>,
+        wc: WhereClause<'a>,
+        returning: Returning<'a>,
+    ) -> Self {
+        Self {
+            returning: Some(returning),
+            ..Self::new(entity, expressions, wc)
         }
     }
     #[inline(always)]
@@ -163,15 +224,24 @@ impl<'a> UpdateStatement<'a> {
         /*
             TODO(@ohsayan): Allow volcanoes
             smallest tt:
-            update model SET x  =  1 where x = 1
-                   ^1    ^2  ^3 ^4 ^5^6    ^7^8^9
+            update model SET x  =  1
+                   ^1    ^2  ^3 ^4 ^5
+            with a filter, limit and force:
+            update model SET x = 1 where x > 1 limit 10 force
+            returning the updated rows:
+            update model SET x = 1 where x = 1 returning x
+            returning the commit sequence number:
+            update model SET x = 1 where x = 1 with lsn
+            returning a summary of the updated rows (and their keys):
+            update model SET x = 1 where x > 1 limit 10 with summary
+            update model SET x = 1 where x > 1 limit 10 with keys
         */
-        if compiler::unlikely(state.remaining() < 9) {
+        if compiler::unlikely(state.remaining() < 5) {
             return compiler::cold_rerr(QueryError::QLUnexpectedEndOfStatement);
         }
         // parse entity
         let entity = state.try_entity_buffered_into_state_uninit();
-        if !(state.has_remaining(6)) {
+        if !(state.has_remaining(2)) {
             unsafe {
                 // UNSAFE(@ohsayan): Obvious from above, max 3 fw
                 impossible!();
@@ -179,21 +249,34 @@ impl<'a> UpdateStatement<'a> {
         }
         state.poison_if_not(state.cursor_eq(Token![set]));
         state.cursor_ahead(); // ignore errors if any
-        let mut nx_where = false;
+        let mut nx_comma = true;
         let mut expressions = Vec::new();
-        while state.not_exhausted() && state.okay() && !nx_where {
+        while state.not_exhausted() && state.okay() && nx_comma {
             AssignmentExpression::parse_and_append_expression(state, &mut expressions);
-            let nx_comma = state.cursor_rounded_eq(Token![,]);
-            nx_where = state.cursor_rounded_eq(Token![where]); // NOTE: volcano
-            state.poison_if_not(nx_comma | nx_where);
+            nx_comma = state.cursor_rounded_eq(Token![,]);
             state.cursor_ahead_if(nx_comma);
         }
-        state.poison_if_not(nx_where);
-        state.cursor_ahead_if(state.okay());
-        // check where clauses
+        state.poison_if(nx_comma | expressions.is_empty());
+        // check where clauses (if any)
         let mut clauses = <_ as Default>::default();
-        WhereClause::parse_where_and_append_to(state, &mut clauses);
-        state.poison_if(clauses.is_empty()); // NOTE: volcano
+        if state.cursor_rounded_eq(Token![where]) {
+            state.cursor_ahead();
+            WhereClause::parse_where_and_append_to(state, &mut clauses);
+            state.poison_if(clauses.is_empty()); // NOTE: volcano
+        }
+        // limit?
+        let limit = super::parse_optional_limit(state);
+        // force?
+        let force = state.not_exhausted() && state.read().ident_eq("force");
+        state.cursor_ahead_if(force);
+        // returning?
+        let returning = Returning::parse_optional(state);
+        // with lsn?
+        let with_lsn = super::parse_with(state, "lsn");
+        // with summary or with keys?
+        let summary = Summary::parse_optional(state);
+        state.poison_if((with_lsn | summary.is_some()) & returning.is_some());
+        state.poison_if(with_lsn & summary.is_some());
         if compiler::likely(state.okay()) {
             Ok(Self {
                 entity: unsafe {
@@ -202,6 +285,11 @@ impl<'a> UpdateStatement<'a> {
                 },
                 expressions,
                 wc: WhereClause::new(clauses),
+                limit,
+                force,
+                returning,
+                with_lsn,
+                summary,
             })
         } else {
             compiler::cold_rerr(QueryError::QLInvalidSyntax)
@@ -0,0 +1,127 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    the entry point for the BlueQL fuzz target (in `fuzz/`), and the parser driver that the fuzz tests share with it.
+    the parsers are driven just like the executor drives them, and nothing is allowed to panic
+*/
+
+use crate::engine::{
+    error::QueryResult,
+    ql::{
+        ast::{traits::ASTNode, InplaceData, State},
+        dcl::SysctlCommand,
+        ddl::{
+            alt::{AlterModel, AlterSpace},
+            crt::{CreateIndex, CreateModel, CreateSpace},
+            drop::{DropIndex, DropModel, DropSpace},
+            Describe, Inspect, Use,
+        },
+        dml::{
+            del::DeleteStatement,
+            ins::InsertStatement,
+            sel::{SelectAllStatement, SelectStatement},
+            upd::UpdateStatement,
+        },
+        lex::{KeywordStmt, SecureLexer, Token},
+    },
+};
+
+/// Lex the input like a query (with its params) from a client and parse it. The first byte of `data` sets where the
+/// rest of it is split into the query and its params
+pub fn blueql(data: &[u8]) {
+    if let Some((&split, src)) = data.split_first() {
+        let (query, params) = src.split_at((split as usize).min(src.len()));
+        if let Ok(tokens) = SecureLexer::new_with_segments(query, params).lex() {
+            let _ = parse_like_exec(&tokens);
+        }
+    }
+}
+
+/// Parse the token stream like the executor would; we don't care about the result, only that we don't panic
+pub(super) fn parse_like_exec<'a>(tokens: &'a [Token<'a>]) -> QueryResult<()> {
+    fn parse<'a, A: ASTNode<'a>>(state: &mut State<'a, InplaceData>) -> QueryResult<()> {
+        A::parse_from_state_hardened(state).map(|_| ())
+    }
+    let mut state = State::new_inplace(tokens);
+    state.set_space_maybe(Some("myspace"));
+    if state.not_exhausted() && state.read().ident_eq("explain") {
+        state.cursor_ahead();
+        if state.try_statement()? != KeywordStmt::Select {
+            return Ok(());
+        }
+        return if state.cursor_rounded_eq(Token![all]) {
+            state.cursor_ahead();
+            parse::<SelectAllStatement>(&mut state)
+        } else {
+            parse::<SelectStatement>(&mut state)
+        };
+    }
+    let stmt = state.try_statement()?;
+    if stmt.is_blocking() {
+        state.ensure_minimum_for_blocking_stmt()?;
+        state.unset_space();
+        let (a, b) = (&state.current()[0], &state.current()[1]);
+        let (space, model, index) = (
+            Token![space].eq(a),
+            Token![model].eq(a),
+            Token![index].eq(a),
+        );
+        let name_like = b.is_ident() | Token![if].eq(b) | Token![allow].eq(b);
+        if stmt == KeywordStmt::Sysctl {
+            return parse::<SysctlCommand>(&mut state);
+        }
+        if !name_like {
+            return Ok(());
+        }
+        state.cursor_ahead();
+        return match (stmt, space, model, index) {
+            (KeywordStmt::Create, true, _, _) => parse::<CreateSpace>(&mut state),
+            (KeywordStmt::Create, _, true, _) => parse::<CreateModel>(&mut state),
+            (KeywordStmt::Create, _, _, true) => parse::<CreateIndex>(&mut state),
+            (KeywordStmt::Alter, true, _, _) => parse::<AlterSpace>(&mut state),
+            (KeywordStmt::Alter, _, true, _) => parse::<AlterModel>(&mut state),
+            (KeywordStmt::Drop, true, _, _) => parse::<DropSpace>(&mut state),
+            (KeywordStmt::Drop, _, true, _) => parse::<DropModel>(&mut state),
+            (KeywordStmt::Drop, _, _, true) => parse::<DropIndex>(&mut state),
+            _ => Ok(()),
+        };
+    }
+    match stmt {
+        KeywordStmt::Use => parse::<Use>(&mut state),
+        KeywordStmt::Inspect => parse::<Inspect>(&mut state),
+        KeywordStmt::Describe => parse::<Describe>(&mut state),
+        KeywordStmt::Insert => parse::<InsertStatement>(&mut state),
+        KeywordStmt::Select if state.cursor_rounded_eq(Token![all]) => {
+            state.cursor_ahead();
+            parse::<SelectAllStatement>(&mut state)
+        }
+        KeywordStmt::Select => parse::<SelectStatement>(&mut state),
+        KeywordStmt::Update => parse::<UpdateStatement>(&mut state),
+        KeywordStmt::Delete => parse::<DeleteStatement>(&mut state),
+        _ => Ok(()),
+    }
+}
@@ -31,11 +31,15 @@ pub use raw::{Ident, Keyword, KeywordMisc, KeywordStmt, Symbol, Token};
 
 use {
     crate::engine::{
-        data::lit::Lit,
+        data::{
+            lit::Lit,
+            tag::{DataTag, TagClass},
+        },
         error::{QueryError, QueryResult},
         mem::BufferedScanner,
     },
     core::slice,
+    std::borrow::Cow,
 };
 
 /*
@@ -110,6 +114,29 @@ impl<'a> Lexer<'a> {
             }),
         }
     }
+    /// Scan an identifier quoted with backticks (like `` `type` ``), which is never a keyword or a boolean. The quoted
+    /// name must itself be a valid identifier, so there's nothing to escape
+    fn scan_quoted_ident(&mut self) {
+        unsafe {
+            // UNSAFE(@ohsayan): we are at the opening backtick, so fw cursor
+            self.token_buffer.incr_cursor();
+        }
+        let starts_ident = self
+            .token_buffer
+            .rounded_cursor_not_eof_matches(|b| b.is_ascii_alphabetic() || *b == b'_');
+        let s = self.scan_ident();
+        if !(starts_ident & self.token_buffer.rounded_cursor_not_eof_equals(b'`')) {
+            return self.set_error(QueryError::LexInvalidInput);
+        }
+        unsafe {
+            // UNSAFE(@ohsayan): not eof; skip the closing backtick
+            self.token_buffer.incr_cursor();
+        }
+        self.push_token(unsafe {
+            // UNSAFE(@ohsayan): scan_ident only returns a valid ident which is always a string
+            Token::Ident(Ident::new(s))
+        })
+    }
     fn scan_byte(&mut self, byte: u8) {
         match Symbol::get(byte) {
             Some(tok) => self.push_token(tok),
@@ -139,9 +166,10 @@ mod insecure_impl {
         super::Lexer,
         crate::{
             engine::{
+                core::dml::expr::interval_unit,
                 data::lit::Lit,
                 error::{QueryError, QueryResult},
-                ql::lex::Token,
+                ql::lex::{Ident, Token},
             },
             util::compiler,
         },
@@ -175,6 +203,8 @@ mod insecure_impl {
                     byte if byte.is_ascii_alphabetic() | (byte == b'_') => {
                         self.l.scan_ident_or_keyword()
                     }
+                    // quoted ident
+                    b'`' => self.l.scan_quoted_ident(),
                     // uint
                     byte if byte.is_ascii_digit() => self.scan_unsigned_integer(),
                     // sint
@@ -281,25 +311,67 @@ mod insecure_impl {
                 Err(_) | Ok(_) => self.l.set_error(QueryError::LexInvalidInput),
             }
         }
+        /// If the integer that was just scanned (starting at `start`) has a fraction (like in `1.5`), scan the
+        /// fraction and push the float
+        fn try_scan_fraction(&mut self, start: usize) -> bool {
+            let buf = &mut self.l.token_buffer;
+            let has_fraction = buf.rounded_cursor_not_eof_equals(b'.')
+                && buf
+                    .inner_buffer()
+                    .get(buf.cursor() + 1)
+                    .map_or(false, u8::is_ascii_digit);
+            if !has_fraction {
+                return false;
+            }
+            unsafe {
+                // UNSAFE(@ohsayan): not eof (we just saw the dot)
+                buf.incr_cursor()
+            }
+            buf.trim_ahead(|b| b.is_ascii_digit());
+            let float = core::str::from_utf8(&buf.inner_buffer()[start..buf.cursor()])
+                .ok()
+                .and_then(|f| f.parse::<f64>().ok());
+            match float {
+                Some(float) if !buf.rounded_cursor_not_eof_matches(u8::is_ascii_alphanumeric) => {
+                    self.l.push_token(Lit::new_float(float))
+                }
+                _ => self.l.set_error(QueryError::LexInvalidInput),
+            }
+            true
+        }
         pub(crate) fn scan_unsigned_integer(&mut self) {
+            let start = self.l.token_buffer.cursor();
             let mut okay = true;
             // extract integer
             let int = self
                 .l
                 .token_buffer
                 .try_next_ascii_u64_stop_at::<false>(&mut okay, |b| b.is_ascii_digit());
+            if okay && self.try_scan_fraction(start) {
+                return;
+            }
             /*
                 see if we ended at a correct byte:
                 iff the integer has an alphanumeric byte at the end is the integer invalid
             */
-            if compiler::unlikely(
-                !okay
-                    | self
-                        .l
-                        .token_buffer
-                        .rounded_cursor_not_eof_matches(u8::is_ascii_alphanumeric),
-            ) {
+            let has_suffix = self
+                .l
+                .token_buffer
+                .rounded_cursor_not_eof_matches(u8::is_ascii_alphanumeric);
+            if compiler::unlikely(!okay) {
                 self.l.set_error(QueryError::LexInvalidInput);
+            } else if compiler::unlikely(has_suffix) {
+                // the only valid suffix is an interval unit, like in `7d`, which is lexed as `7 d`
+                let unit = self.l.scan_ident();
+                if interval_unit(unit).is_some() {
+                    self.l.push_token(Lit::new_uint(int));
+                    self.l.push_token(unsafe {
+                        // UNSAFE(@ohsayan): scan_ident only returns a valid ident which is always a string
+                        Token::Ident(Ident::new(unit))
+                    });
+                } else {
+                    self.l.set_error(QueryError::LexInvalidInput);
+                }
             } else {
                 self.l.push_token(Lit::new_uint(int))
             }
@@ -310,10 +382,14 @@ mod insecure_impl {
                     // UNSAFE(@ohsayan): the cursor was moved ahead, now we're moving it back
                     self.l.token_buffer.decr_cursor()
                 }
+                let start = self.l.token_buffer.cursor();
                 let (okay, int) = self
                     .l
                     .token_buffer
                     .try_next_ascii_i64_stop_at(|b| !b.is_ascii_digit());
+                if okay && self.try_scan_fraction(start) {
+                    return;
+                }
                 if okay
                     & !self
                         .l
@@ -364,6 +440,7 @@ impl<'a> SecureLexer<'a> {
 
 impl<'a> SecureLexer<'a> {
     fn _lex(mut self) -> QueryResult<Vec<Token<'a>>> {
+        let mut param = 0;
         while self.l.no_error() & !self.l.token_buffer.eof() {
             let b = unsafe {
                 // UNSAFE(@ohsayan): loop invariant
@@ -371,6 +448,7 @@ impl<'a> SecureLexer<'a> {
             };
             match b {
                 b if b.is_ascii_alphabetic() | (b == b'_') => self.l.scan_ident_or_keyword(),
+                b'`' => self.l.scan_quoted_ident(),
                 b'?' if !self.param_buffer.eof() => {
                     // skip the param byte
                     unsafe {
@@ -403,6 +481,16 @@ impl<'a> SecureLexer<'a> {
                         // UNSAFE(@ohsayan): our computation above ensures that we're meeting the expected target
                         SCAN_PARAM[final_target](&mut self)
                     }
+                    if let Some(e) = self.l.last_error {
+                        // name the parameter that couldn't be decoded, and the tag it was sent with
+                        let _ = e.with_detail("parameter", param).with_detail(
+                            "received",
+                            PARAM_TAG_NAMES
+                                .get(target_code as usize)
+                                .map_or_else(|| target_code.to_string(), |name| name.to_string()),
+                        );
+                    }
+                    param += 1;
                 }
                 b' ' | b'\t' | b'\n' => self.l.trim_ahead(),
                 sym => self.l.scan_byte(sym),
@@ -415,7 +503,67 @@ impl<'a> SecureLexer<'a> {
     }
 }
 
+/// Returns the name as it must be written in a query: names that would otherwise be lexed as a keyword or a boolean
+/// are quoted with backticks (see [`Lexer::scan_quoted_ident`]), and all others are left as they are
+pub fn quote_ident(name: &str) -> Cow<str> {
+    let reserved = Keyword::get(name.as_bytes()).is_some()
+        | name.eq_ignore_ascii_case("true")
+        | name.eq_ignore_ascii_case("false");
+    if reserved {
+        Cow::Owned(format!("`{name}`"))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Encode a parameter just like a client would, so that it's scanned back into the same literal (see [`SCAN_PARAM`]).
+/// [`None`] is a null
+pub fn encode_param(buf: &mut Vec<u8>, param: Option<&Lit>) {
+    let Some(lit) = param else {
+        buf.push(0);
+        return;
+    };
+    unsafe {
+        // UNSAFE(@ohsayan): we read the value that the tag says it has
+        match lit.kind().tag_class() {
+            TagClass::Bool => buf.extend([1, lit.bool() as u8]),
+            TagClass::UnsignedInt => {
+                buf.push(2);
+                buf.extend(lit.uint().to_string().as_bytes());
+                buf.push(b'\n');
+            }
+            TagClass::SignedInt => {
+                buf.push(3);
+                buf.extend(lit.sint().to_string().as_bytes());
+                buf.push(b'\n');
+            }
+            TagClass::Float => {
+                buf.push(4);
+                buf.extend(lit.float().to_string().as_bytes());
+                buf.push(b'\n');
+            }
+            TagClass::Bin => {
+                buf.push(5);
+                encode_block(buf, lit.bin());
+            }
+            TagClass::Str => {
+                buf.push(6);
+                encode_block(buf, lit.str().as_bytes());
+            }
+            TagClass::List => impossible!(),
+        }
+    }
+}
+
+fn encode_block(buf: &mut Vec<u8>, block: &[u8]) {
+    buf.extend(block.len().to_string().as_bytes());
+    buf.push(b'\n');
+    buf.extend(block);
+}
+
 const SCAN_PARAM_EXPECT: [u8; 8] = [0, 1, 2, 2, 2, 2, 2, 0];
+/// The names of the parameter tags (in the order of [`SCAN_PARAM`])
+const PARAM_TAG_NAMES: [&str; 7] = ["null", "bool", "uint", "sint", "float", "binary", "string"];
 static SCAN_PARAM: [unsafe fn(&mut SecureLexer); 8] = unsafe {
     [
         // null
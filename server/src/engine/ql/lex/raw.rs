@@ -235,6 +235,10 @@ build_lut!(
 );
 
 impl Symbol {
+    /// Returns the character of this symbol
+    pub fn as_char(self) -> char {
+        char::from(SYM_LUT[self as usize].0)
+    }
     pub fn get(k: u8) -> Option<Self> {
         const SYM_MAGIC_A: u8 = b'w';
         const SYM_MAGIC_B: u8 = b'E';
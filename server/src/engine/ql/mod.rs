@@ -30,6 +30,7 @@ pub(super) mod ast;
 pub(super) mod dcl;
 pub(super) mod ddl;
 pub(super) mod dml;
+pub(super) mod fuzz;
 pub(super) mod lex;
 #[cfg(test)]
 pub(in crate::engine) mod tests;
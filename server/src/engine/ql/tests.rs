@@ -38,6 +38,7 @@ use {
 
 mod dcl;
 mod dml_tests;
+mod fuzz;
 mod lexer_tests;
 mod misc;
 mod schema_tests;
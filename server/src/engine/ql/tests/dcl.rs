@@ -24,10 +24,16 @@
  *
 */
 
-use crate::engine::ql::{
-    ast,
-    dcl::{self, SysctlCommand},
-    tests::lex_insecure,
+use crate::engine::{
+    core::EntityIDRef,
+    data::lit::Lit,
+    error::QueryError,
+    ql::{
+        ast,
+        dcl::{self, QuotaTarget, SysctlCommand},
+        lex::Ident,
+        tests::lex_insecure,
+    },
 };
 
 #[test]
@@ -37,6 +43,73 @@ fn report_status_simple() {
     assert_eq!(q, SysctlCommand::ReportStatus)
 }
 
+#[test]
+fn report_memory_simple() {
+    let query = lex_insecure(b"sysctl report memory").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportMemory)
+}
+
+#[test]
+fn report_storage_simple() {
+    let query = lex_insecure(b"sysctl report storage").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportStorage)
+}
+
+#[test]
+fn report_probes() {
+    let query = lex_insecure(b"sysctl report live").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportLive);
+    assert!(!q.needs_root());
+    let query = lex_insecure(b"sysctl report ready").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportReady);
+    assert!(!q.needs_root());
+}
+
+#[test]
+fn report_last() {
+    let query = lex_insecure(b"sysctl report last").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportLast);
+    assert!(!q.needs_root());
+}
+
+#[test]
+fn report_profile() {
+    let query = lex_insecure(b"sysctl report profile").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportProfile);
+    assert!(q.needs_root());
+}
+
+#[test]
+fn report_scheduler() {
+    let query = lex_insecure(b"sysctl report scheduler").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportScheduler);
+    assert!(q.needs_root());
+}
+
+#[test]
+fn report_shardmap() {
+    let query = lex_insecure(b"sysctl report shardmap").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportShardmap);
+    // drivers fetch it as whatever user they connect as
+    assert!(!q.needs_root());
+}
+
+#[test]
+fn report_prefetch() {
+    let query = lex_insecure(b"sysctl report prefetch").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportPrefetch);
+    assert!(q.needs_root());
+}
+
 #[test]
 fn create_user_simple() {
     let query = lex_insecure(b"sysctl create user sayan with { password: 'mypass123' }").unwrap();
@@ -72,3 +145,759 @@ fn delete_user_simple() {
         SysctlCommand::DropUser(dcl::UserDel::new("monster".into()))
     );
 }
+
+#[test]
+fn decode_journal() {
+    let query = lex_insecure(b"sysctl decode journal myspace.mymodel").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::DecodeJournal {
+            entity: EntityIDRef::new("myspace", "mymodel"),
+            since: None
+        }
+    );
+}
+
+#[test]
+fn decode_journal_since() {
+    let query = lex_insecure(b"sysctl decode journal myspace.mymodel since 100").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::DecodeJournal {
+            entity: EntityIDRef::new("myspace", "mymodel"),
+            since: Some(100)
+        }
+    );
+    for query in [
+        "sysctl decode journal myspace.mymodel since",
+        "sysctl decode journal myspace.mymodel since 'hello'",
+        "sysctl decode journal myspace.mymodel since -1",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn verify_model() {
+    for (query, rebuild, background) in [
+        ("sysctl verify model myspace.mymodel", false, false),
+        ("sysctl verify model myspace.mymodel rebuild", true, false),
+        (
+            "sysctl verify model myspace.mymodel in background",
+            false,
+            true,
+        ),
+        (
+            "sysctl verify model myspace.mymodel rebuild in background",
+            true,
+            true,
+        ),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+        assert_eq!(
+            q,
+            SysctlCommand::VerifyModel {
+                entity: EntityIDRef::new("myspace", "mymodel"),
+                rebuild,
+                background
+            }
+        );
+    }
+    for query in [
+        "sysctl verify model",
+        "sysctl verify model myspace.mymodel reindex",
+        "sysctl verify model myspace.mymodel rebuild rebuild",
+        "sysctl verify model myspace.mymodel in background rebuild",
+        "sysctl verify model myspace.mymodel in",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn copy_model() {
+    let query = lex_insecure(b"sysctl copy model myspace.mymodel from 'staging:2003'").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::CopyModel {
+            entity: EntityIDRef::new("myspace", "mymodel"),
+            from: "staging:2003",
+            credentials: None,
+            background: false,
+        }
+    );
+    assert!(q.needs_root());
+    let query = lex_insecure(
+        b"sysctl copy model myspace.mymodel from 'staging:2003' \
+        with credentials { username: 'sayan', password: 'mypass123' } in background",
+    )
+    .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::CopyModel {
+            entity: EntityIDRef::new("myspace", "mymodel"),
+            from: "staging:2003",
+            credentials: Some(
+                into_dict!("username" => lit!("sayan"), "password" => lit!("mypass123"))
+            ),
+            background: true,
+        }
+    );
+    for query in [
+        "sysctl copy model myspace.mymodel",
+        "sysctl copy model myspace.mymodel from",
+        "sysctl copy model myspace.mymodel from staging",
+        "sysctl copy model myspace.mymodel from 'staging:2003' with { username: 'sayan' }",
+        "sysctl copy model myspace.mymodel from 'staging:2003' with credentials",
+        "sysctl copy model myspace.mymodel from 'staging:2003' in",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn restore_model() {
+    let query = lex_insecure(
+        b"sysctl restore model myspace.users from snapshot '/backups/nightly' as myspace.users_restored",
+    )
+    .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::RestoreModel {
+            entity: EntityIDRef::new("myspace", "users"),
+            snapshot: "/backups/nightly",
+            target: EntityIDRef::new("myspace", "users_restored"),
+            background: false,
+        }
+    );
+    assert!(q.needs_root());
+    let query = lex_insecure(
+        b"sysctl restore model myspace.users from snapshot '/backups/nightly' as recovery.users in background",
+    )
+    .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::RestoreModel {
+            entity: EntityIDRef::new("myspace", "users"),
+            snapshot: "/backups/nightly",
+            target: EntityIDRef::new("recovery", "users"),
+            background: true,
+        }
+    );
+    for query in [
+        "sysctl restore model myspace.users",
+        "sysctl restore model myspace.users from '/backups/nightly' as myspace.users2",
+        "sysctl restore model myspace.users from snapshot as myspace.users2",
+        "sysctl restore model myspace.users from snapshot '/backups/nightly'",
+        "sysctl restore model myspace.users from snapshot '/backups/nightly' as",
+        "sysctl restore model myspace.users from snapshot '/backups/nightly' as myspace.users2 in",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn export_models() {
+    let query =
+        lex_insecure(b"sysctl export models myspace.users, myspace.orders into '/exports/daily'")
+            .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::ExportModels {
+            entities: vec![
+                EntityIDRef::new("myspace", "users"),
+                EntityIDRef::new("myspace", "orders")
+            ],
+            path: "/exports/daily",
+            background: false,
+        }
+    );
+    assert!(q.needs_root());
+    let query =
+        lex_insecure(b"sysctl export models myspace.users into '/exports/daily' in background")
+            .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::ExportModels {
+            entities: vec![EntityIDRef::new("myspace", "users")],
+            path: "/exports/daily",
+            background: true,
+        }
+    );
+    for query in [
+        "sysctl export models",
+        "sysctl export models myspace.users",
+        "sysctl export models myspace.users '/exports/daily'",
+        "sysctl export models myspace.users, into '/exports/daily'",
+        "sysctl export models myspace.users into",
+        "sysctl export models myspace.users into '/exports/daily' in",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn import_rdb() {
+    let query = lex_insecure(
+        b"sysctl import rdb '/var/lib/redis/dump.rdb' \
+        with { strings: 'myspace.kv', hashes: 'myspace.users', database: 1 } in background",
+    )
+    .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::ImportRdb {
+            path: "/var/lib/redis/dump.rdb",
+            mapping: into_dict!(
+                "strings" => lit!("myspace.kv"),
+                "hashes" => lit!("myspace.users"),
+                "database" => Lit::new_uint(1)
+            ),
+            background: true,
+        }
+    );
+    assert!(q.needs_root());
+    for query in [
+        "sysctl import rdb",
+        "sysctl import rdb '/var/lib/redis/dump.rdb'",
+        "sysctl import rdb '/var/lib/redis/dump.rdb' with",
+        "sysctl import rdb dump with { strings: 'myspace.kv' }",
+        "sysctl import rdb '/var/lib/redis/dump.rdb' with { strings: 'myspace.kv' } in",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn webhooks() {
+    let query = lex_insecure(
+        b"sysctl create webhook orders on myspace.mymodel \
+        with { url: 'https://example.com/hooks', secret: 'mysecret', batch: 50 }",
+    )
+    .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::CreateWebhook {
+            name: Ident::from("orders"),
+            entity: EntityIDRef::new("myspace", "mymodel"),
+            props: into_dict!(
+                "url" => lit!("https://example.com/hooks"),
+                "secret" => lit!("mysecret"),
+                "batch" => Lit::new_uint(50)
+            ),
+        }
+    );
+    assert!(q.needs_root());
+    let query = lex_insecure(b"sysctl drop webhook orders on myspace.mymodel").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::DropWebhook {
+            name: Ident::from("orders"),
+            entity: EntityIDRef::new("myspace", "mymodel"),
+        }
+    );
+    let query = lex_insecure(b"sysctl report webhooks").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportWebhooks);
+    assert!(q.needs_root());
+    for query in [
+        "sysctl create webhook orders",
+        "sysctl create webhook orders on myspace.mymodel",
+        "sysctl create webhook orders on myspace.mymodel with",
+        "sysctl create webhook on myspace.mymodel with { url: 'http://localhost' }",
+        "sysctl create webhook 'orders' on myspace.mymodel with { url: 'http://localhost' }",
+        "sysctl drop webhook orders",
+        "sysctl drop webhook orders on myspace.mymodel with { url: 'http://localhost' }",
+        "sysctl report webhooks now",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn report_indexes() {
+    let query = lex_insecure(b"sysctl report indexes").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportIndexes);
+    assert!(q.needs_root());
+    for query in ["sysctl report indexes now", "sysctl report index"] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn report_hot_keys() {
+    let query = lex_insecure(b"sysctl report hotkeys").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ReportHotKeys);
+    assert!(q.needs_root());
+    let query = lex_insecure(b"sysctl report hotkeys now").unwrap();
+    assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+}
+
+#[test]
+fn jobs() {
+    let query = lex_insecure(b"sysctl list jobs").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ListJobs);
+    let query = lex_insecure(b"sysctl list locks").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ListLocks);
+    assert!(q.needs_root());
+    let query = lex_insecure(b"sysctl cancel job 12").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::CancelJob { id: 12 });
+    for query in [
+        "sysctl list jobs now",
+        "sysctl list locks now",
+        "sysctl cancel job",
+        "sysctl cancel job -1",
+        "sysctl cancel job 'a'",
+        "sysctl cancel job 1 2",
+        "sysctl cancel jobs 1",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn record() {
+    let query = lex_insecure(b"sysctl record start '/tmp/workload.log'").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::RecordStart {
+            path: "/tmp/workload.log",
+            anonymized: false
+        }
+    );
+    assert!(q.needs_root());
+    let query = lex_insecure(b"sysctl record start '/tmp/workload.log' anonymized").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::RecordStart {
+            path: "/tmp/workload.log",
+            anonymized: true
+        }
+    );
+    let query = lex_insecure(b"sysctl record stop").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::RecordStop);
+    assert!(q.needs_root());
+    for query in [
+        "sysctl record start",
+        "sysctl record start workload",
+        "sysctl record start 1",
+        "sysctl record start '/tmp/workload.log' anonymous",
+        "sysctl record start '/tmp/workload.log' anonymized now",
+        "sysctl record stop now",
+        "sysctl record",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn relocate_data() {
+    let query = lex_insecure(b"sysctl relocate data '/mnt/fast/skytable'").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::RelocateData {
+            path: "/mnt/fast/skytable",
+            background: false
+        }
+    );
+    assert!(q.needs_root());
+    let query = lex_insecure(b"sysctl relocate data '/mnt/fast/skytable' in background").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::RelocateData {
+            path: "/mnt/fast/skytable",
+            background: true
+        }
+    );
+    for query in [
+        "sysctl relocate data",
+        "sysctl relocate data skytable",
+        "sysctl relocate data 1",
+        "sysctl relocate data '/mnt/fast/skytable' in",
+        "sysctl relocate data '/mnt/fast/skytable' now",
+        "sysctl relocate '/mnt/fast/skytable'",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn backfill_model() {
+    for (query, background) in [
+        ("sysctl backfill model myspace.mymodel", false),
+        ("sysctl backfill model myspace.mymodel in background", true),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+        assert_eq!(
+            q,
+            SysctlCommand::BackfillModel {
+                entity: EntityIDRef::new("myspace", "mymodel"),
+                background
+            }
+        );
+        assert!(q.needs_root());
+    }
+    for query in [
+        "sysctl backfill model",
+        "sysctl backfill myspace.mymodel",
+        "sysctl backfill model myspace.mymodel now",
+        "sysctl backfill model myspace.mymodel in",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn generate_rows() {
+    for (query, count, seed) in [
+        (
+            "sysctl generate rows myspace.mymodel count 1000",
+            1000,
+            None,
+        ),
+        (
+            "sysctl generate rows myspace.mymodel count 10 seed 42",
+            10,
+            Some(42),
+        ),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+        assert_eq!(
+            q,
+            SysctlCommand::GenerateRows {
+                entity: EntityIDRef::new("myspace", "mymodel"),
+                count,
+                seed
+            }
+        );
+    }
+    for query in [
+        "sysctl generate rows myspace.mymodel",
+        "sysctl generate rows myspace.mymodel count",
+        "sysctl generate rows myspace.mymodel count 'a'",
+        "sysctl generate rows myspace.mymodel count 10 seed",
+        "sysctl generate rows myspace.mymodel count 10 seed -1",
+        "sysctl generate rows myspace.mymodel seed 1 count 10",
+        "sysctl generate rows myspace.mymodel count 10 now",
+        "sysctl generate myspace.mymodel count 10",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn flush_model() {
+    let query = lex_insecure(b"sysctl flush model myspace.mymodel").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::FlushModel {
+            entity: EntityIDRef::new("myspace", "mymodel")
+        }
+    );
+    for query in [
+        "sysctl flush model",
+        "sysctl flush myspace.mymodel",
+        "sysctl flush model myspace.mymodel now",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn freeze() {
+    for (query, space) in [
+        ("sysctl freeze", None),
+        ("sysctl freeze space myspace", Some(Ident::from("myspace"))),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+        assert_eq!(q, SysctlCommand::Freeze { space });
+    }
+    for (query, space) in [
+        ("sysctl unfreeze", None),
+        (
+            "sysctl unfreeze space myspace",
+            Some(Ident::from("myspace")),
+        ),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+        assert_eq!(q, SysctlCommand::Unfreeze { space });
+    }
+    for query in [
+        "sysctl freeze space",
+        "sysctl freeze myspace",
+        "sysctl freeze model myspace.mymodel",
+        "sysctl unfreeze space myspace now",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn export_import_gns() {
+    let query = lex_insecure(br#"sysctl export gns "backups/schema.gns""#).unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::ExportGns {
+            path: "backups/schema.gns"
+        }
+    );
+    let query = lex_insecure(br#"sysctl import gns "backups/schema.gns""#).unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::ImportGns {
+            path: "backups/schema.gns"
+        }
+    );
+    for query in [
+        "sysctl export gns",
+        "sysctl export gns backups",
+        "sysctl export gns 1234",
+        "sysctl import gns 'a' 'b'",
+        "sysctl import 'backups/schema.gns'",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn create_drop_statement() {
+    let query =
+        lex_insecure(b"create statement by_city as select * from apps.social where city = ?")
+            .unwrap();
+    let q = ast::parse_ast_node_full::<dcl::CreateStatement>(&query[2..]).unwrap();
+    assert_eq!(
+        q,
+        dcl::CreateStatement::new(
+            Ident::from("by_city"),
+            "select * from apps . social where city = ?".into()
+        )
+    );
+    let query = lex_insecure(b"drop statement by_city").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::DropStatement>(&query[2..]).unwrap();
+    assert_eq!(q, dcl::DropStatement::new(Ident::from("by_city")));
+    for (query, error) in [
+        // values must be placeholders
+        (
+            "create statement by_city as select * from apps.social where city = 'london'",
+            QueryError::QLInvalidSyntax,
+        ),
+        // only DML can be stored
+        (
+            "create statement cleanup as drop model apps.social",
+            QueryError::QLUnknownStatement,
+        ),
+        (
+            "create statement by_city select * from apps.social",
+            QueryError::QLInvalidSyntax,
+        ),
+        (
+            "create statement by_city as",
+            QueryError::QLUnexpectedEndOfStatement,
+        ),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert_eq!(
+            ast::parse_ast_node_full::<dcl::CreateStatement>(&query[2..]).unwrap_err(),
+            error
+        );
+    }
+    let query = lex_insecure(b"drop statement by_city by_name").unwrap();
+    assert!(ast::parse_ast_node_full::<dcl::DropStatement>(&query[2..]).is_err());
+}
+
+#[test]
+fn execute_statement() {
+    let query = lex_insecure(b"execute by_city('london', 10, null)").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::ExecuteStatement>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        dcl::ExecuteStatement::new(
+            Ident::from("by_city"),
+            vec![Some(Lit::new_str("london")), Some(Lit::new_uint(10)), None]
+        )
+    );
+    for query in ["execute by_city", "execute by_city()"] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::ExecuteStatement>(&query[1..]).unwrap();
+        assert_eq!(
+            q,
+            dcl::ExecuteStatement::new(Ident::from("by_city"), vec![])
+        );
+    }
+    for query in [
+        "execute",
+        "execute by_city(",
+        "execute by_city('london'",
+        "execute by_city('london' 10)",
+        "execute by_city(,)",
+        "execute by_city 'london'",
+        "execute by_city() 'london'",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::ExecuteStatement>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn alter_quota() {
+    let query =
+        lex_insecure(b"sysctl alter quota user sayan with { reads: 100, writes: 10 }").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::AlterQuota {
+            target: QuotaTarget::User(Ident::from("sayan")),
+            props: into_dict!("reads" => Lit::new_uint(100), "writes" => Lit::new_uint(10)),
+        }
+    );
+    assert!(q.needs_root());
+    let query = lex_insecure(b"sysctl alter quota model myspace.users with {}").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::AlterQuota {
+            target: QuotaTarget::Model(EntityIDRef::new("myspace", "users")),
+            props: into_dict!(),
+        }
+    );
+    let query = lex_insecure(b"sysctl report quotas").unwrap();
+    assert_eq!(
+        ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap(),
+        SysctlCommand::ReportQuotas
+    );
+    for query in [
+        "sysctl alter quota",
+        "sysctl alter quota user",
+        "sysctl alter quota user sayan",
+        "sysctl alter quota user sayan { reads: 100 }",
+        "sysctl alter quota space myspace with { reads: 100 }",
+        "sysctl alter quota model with { reads: 100 }",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
+
+#[test]
+fn snapshots() {
+    for (query, background) in [
+        ("sysctl create snapshot nightly", false),
+        ("sysctl create snapshot nightly in background", true),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+        assert_eq!(
+            q,
+            SysctlCommand::CreateSnapshot {
+                name: Ident::from("nightly"),
+                background
+            }
+        );
+        assert!(q.needs_root());
+    }
+    for (query, every, keep) in [
+        (
+            "sysctl create snapshot hourly every '6h' keep 8",
+            6 * 3600,
+            8,
+        ),
+        ("sysctl create snapshot hourly every '90s' keep 1", 90, 1),
+        (
+            "sysctl create snapshot hourly every '1d' keep 30",
+            86400,
+            30,
+        ),
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+        assert_eq!(
+            q,
+            SysctlCommand::ScheduleSnapshots {
+                name: Ident::from("hourly"),
+                every,
+                keep
+            }
+        );
+    }
+    let query = lex_insecure(b"sysctl drop snapshot nightly").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::DropSnapshot {
+            name: Ident::from("nightly")
+        }
+    );
+    let query = lex_insecure(b"sysctl drop snapshot schedule hourly").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(
+        q,
+        SysctlCommand::DropSnapshotSchedule {
+            name: Ident::from("hourly")
+        }
+    );
+    let query = lex_insecure(b"sysctl list snapshots").unwrap();
+    let q = ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).unwrap();
+    assert_eq!(q, SysctlCommand::ListSnapshots);
+    assert!(q.needs_root());
+    for query in [
+        "sysctl create snapshot",
+        "sysctl create snapshot 'nightly'",
+        "sysctl create snapshot nightly now",
+        "sysctl create snapshot hourly every '6h'",
+        "sysctl create snapshot hourly every '6h' keep",
+        "sysctl create snapshot hourly every 6 keep 8",
+        "sysctl create snapshot hourly every '6w' keep 8",
+        "sysctl create snapshot hourly every 'h' keep 8",
+        "sysctl create snapshot hourly every '6h' keep 8 in background",
+        "sysctl drop snapshot",
+        "sysctl drop snapshot nightly now",
+        "sysctl list snapshots now",
+    ] {
+        let query = lex_insecure(query.as_bytes()).unwrap();
+        assert!(ast::parse_ast_node_full::<dcl::SysctlCommand>(&query[1..]).is_err());
+    }
+}
@@ -317,6 +317,45 @@ mod stmt_insert {
         assert_eq!(e, r);
     }
     #[test]
+    fn insert_with_lsn() {
+        let x = lex_insecure(br#"insert into twitter.users ("sayan") with lsn"#).unwrap();
+        let r = parse_ast_node_full::<InsertStatement>(&x[1..]).unwrap();
+        assert!(r.with_lsn());
+        let x =
+            lex_insecure(br#"insert into twitter.users ("sayan") returning * with lsn"#).unwrap();
+        assert!(parse_ast_node_full::<InsertStatement>(&x[1..]).is_err());
+    }
+    #[test]
+    fn insert_with_summary() {
+        let x = lex_insecure(br#"insert into twitter.users ("sayan") with summary"#).unwrap();
+        let r = parse_ast_node_full::<InsertStatement>(&x[1..]).unwrap();
+        assert_eq!(r.summary(), Some(dml::Summary::Counts));
+        let x = lex_insecure(br#"insert into twitter.users ("sayan") with keys"#).unwrap();
+        let r = parse_ast_node_full::<InsertStatement>(&x[1..]).unwrap();
+        assert_eq!(r.summary(), Some(dml::Summary::Keys));
+        let x = lex_insecure(br#"insert into twitter.users ("sayan") returning * with summary"#)
+            .unwrap();
+        assert!(parse_ast_node_full::<InsertStatement>(&x[1..]).is_err());
+        let x = lex_insecure(br#"insert into twitter.users ("sayan") with lsn with keys"#).unwrap();
+        assert!(parse_ast_node_full::<InsertStatement>(&x[1..]).is_err());
+    }
+    #[test]
+    fn insert_tuple_returning() {
+        let x = lex_insecure(
+            br#"
+                insert into twitter.users ("sayan") returning *
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<InsertStatement>(&x[1..]).unwrap();
+        let e = InsertStatement::new_returning(
+            ("twitter", "users").into(),
+            into_array_nullable!["sayan"].to_vec().into(),
+            dml::Returning::Wildcard,
+        );
+        assert_eq!(e, r);
+    }
+    #[test]
     fn insert_tuple() {
         let x = lex_insecure(
             br#"
@@ -491,16 +530,39 @@ mod stmt_insert {
         );
         assert_eq!(ret, expected);
     }
+    #[test]
+    fn insert_keygen_calls() {
+        let tok = lex_insecure(br#"insert into jotsy.app(uuid7(), "sayan", [ulid(), @uuid4()])"#)
+            .unwrap();
+        let ret = parse_ast_node_full::<InsertStatement>(&tok[1..]).unwrap();
+        let dml::ins::InsertData::Ordered(mut data) = ret.data() else {
+            panic!("expected ordered data")
+        };
+        assert_eq!(data[0].str().len(), 36);
+        assert_eq!(data[1].str(), "sayan");
+        let nested = data.pop().unwrap().into_list().unwrap();
+        assert_eq!(nested[0].str().len(), 26);
+        assert_eq!(nested[1].str().len(), 36);
+    }
 }
 
 mod stmt_select {
     use {
         super::*,
         crate::engine::{
-            data::lit::Lit,
+            core::dml::expr::RowFn,
+            data::{lit::Lit, tag::TagSelector},
+            error::{ErrorDetail, QueryError},
             ql::{
-                ast::{parse_ast_node_full, parse_ast_node_full_with_space},
-                dml::{sel::SelectStatement, RelationalExpr},
+                ast::{parse_ast_node_full, parse_ast_node_full_with_space, State},
+                ddl::syn::{FieldSpec, LayerSpec},
+                dml::{
+                    sel::{
+                        Aggregate, ExistsStatement, FetchStatement, FileProjection, HavingExpr,
+                        SelectAggregateStatement, SelectFileStatement, SelectKeys, SelectStatement,
+                    },
+                    RelationalExpr, RowExpr,
+                },
                 lex::Ident,
             },
         },
@@ -548,6 +610,99 @@ mod stmt_select {
         assert_eq!(r, e);
     }
     #[test]
+    fn select_keys() {
+        let tok = lex_insecure(
+            br#"
+                select field1 from twitter.users where username in ("sayan", "robot")
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        let e = SelectStatement::new_test_keys(
+            ("twitter", "users").into(),
+            [Ident::from("field1")].to_vec(),
+            false,
+            SelectKeys::new(
+                Ident::from("username"),
+                vec![Lit::new_str("sayan"), Lit::new_str("robot")],
+                false,
+            ),
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn select_keys_skip_missing() {
+        let tok = lex_insecure(
+            br#"
+                select * from twitter.users where id in (1, 2, 3) skip missing
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        let e = SelectStatement::new_test_keys(
+            ("twitter", "users").into(),
+            [].to_vec(),
+            true,
+            SelectKeys::new(
+                Ident::from("id"),
+                vec![Lit::new_uint(1), Lit::new_uint(2), Lit::new_uint(3)],
+                true,
+            ),
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn select_with_deleted() {
+        let tok = lex_insecure(b"select * from twitter.users where id = 1 with deleted").unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert!(r.with_deleted());
+        let tok = lex_insecure(
+            b"select * from twitter.users where id in (1, 2) skip missing with deleted",
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert!(r.with_deleted());
+        let tok = lex_insecure(b"select * from twitter.users where id = 1").unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert!(!r.with_deleted());
+    }
+    #[test]
+    fn select_as_of() {
+        let tok =
+            lex_insecure(b"select * from twitter.users where id = 1 as of 1700000000").unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert_eq!(r.as_of(), Some(1700000000));
+        let tok = lex_insecure(b"select * from twitter.users where id in (1, 2) as of 1700000000")
+            .unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert_eq!(r.as_of(), Some(1700000000));
+        let tok = lex_insecure(b"select * from twitter.users where id = 1").unwrap();
+        let r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert_eq!(r.as_of(), None);
+        for query in [
+            "select * from twitter.users where id = 1 as of",
+            "select * from twitter.users where id = 1 as of -1",
+            "select * from twitter.users where id = 1 as of 'yesterday'",
+            // prior versions never include soft deleted rows
+            "select * from twitter.users where id = 1 as of 1700000000 with deleted",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(parse_ast_node_full::<SelectStatement>(&tok[1..]).is_err());
+        }
+    }
+    #[test]
+    fn select_keys_bad() {
+        for query in [
+            "select * from twitter.users where id in ()",
+            "select * from twitter.users where id in (1, 2,)",
+            "select * from twitter.users where id in (1, 2",
+            "select * from twitter.users where id in 1",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(parse_ast_node_full::<SelectStatement>(&tok[1..]).is_err());
+        }
+    }
+    #[test]
     fn select_pro() {
         let tok = lex_insecure(
             br#"
@@ -589,6 +744,455 @@ mod stmt_select {
         );
         assert_eq!(r, e);
     }
+    #[test]
+    fn fetch() {
+        let tok = lex_insecure(
+            br#"
+                fetch twitter.users("sayan"), posts(10), twitter.users("robot")
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full_with_space::<FetchStatement>(&tok[1..], "apps").unwrap();
+        let e = FetchStatement::new_test(vec![
+            (("twitter", "users").into(), Lit::new_str("sayan")),
+            (("apps", "posts").into(), Lit::new_uint(10)),
+            (("twitter", "users").into(), Lit::new_str("robot")),
+        ]);
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn fetch_bad() {
+        for query in [
+            "fetch",
+            "fetch twitter.users",
+            "fetch twitter.users()",
+            "fetch twitter.users('sayan',)",
+            "fetch twitter.users('sayan', 'robot')",
+            "fetch twitter.users('sayan'),",
+            "fetch users('sayan')",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert_eq!(
+                parse_ast_node_full::<FetchStatement>(&tok[1..]).unwrap_err(),
+                QueryError::QLInvalidSyntax,
+                "{query}"
+            );
+        }
+    }
+    #[test]
+    fn exists() {
+        let tok = lex_insecure(b"exists from users").unwrap();
+        let r = parse_ast_node_full_with_space::<ExistsStatement>(&tok[1..], "apps").unwrap();
+        assert_eq!(
+            r,
+            ExistsStatement::new_test(("apps", "users").into(), dict! {})
+        );
+        let tok =
+            lex_insecure(b"exists from apps.users where city = 'London' and age > 18").unwrap();
+        let r = parse_ast_node_full::<ExistsStatement>(&tok[1..]).unwrap();
+        let e = ExistsStatement::new_test(
+            ("apps", "users").into(),
+            dict! {
+                Ident::from("city") => RelationalExpr::new(
+                    Ident::from("city"), Lit::new_str("London"), RelationalExpr::OP_EQ
+                ),
+                Ident::from("age") => RelationalExpr::new(
+                    Ident::from("age"), Lit::new_uint(18), RelationalExpr::OP_GT
+                ),
+            },
+        );
+        assert_eq!(r, e);
+        for query in [
+            "exists apps.users",
+            "exists from",
+            "exists from apps.users where",
+            "exists from apps.users limit 1",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(
+                parse_ast_node_full::<ExistsStatement>(&tok[1..]).is_err(),
+                "{query}"
+            );
+        }
+    }
+    #[test]
+    fn select_aggregate() {
+        let tok = lex_insecure(
+            br#"
+                select count(distinct city), topk(city, 3) from users where age > 18
+            "#,
+        )
+        .unwrap();
+        let r =
+            parse_ast_node_full_with_space::<SelectAggregateStatement>(&tok[1..], "apps").unwrap();
+        let e = SelectAggregateStatement::new_test(
+            ("apps", "users").into(),
+            vec![
+                Aggregate::CountDistinct(Ident::from("city")),
+                Aggregate::TopK(Ident::from("city"), 3),
+            ],
+            dict! {
+                Ident::from("age") => RelationalExpr::new(
+                    Ident::from("age"), Lit::new_uint(18), RelationalExpr::OP_GT
+                ),
+            },
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn select_aggregate_group_by() {
+        let tok = lex_insecure(
+            br#"
+                select count(*), count(distinct username) from apps.users where age > 18 group by city
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<SelectAggregateStatement>(&tok[1..]).unwrap();
+        let e = SelectAggregateStatement::new_test(
+            ("apps", "users").into(),
+            vec![
+                Aggregate::Count,
+                Aggregate::CountDistinct(Ident::from("username")),
+            ],
+            dict! {
+                Ident::from("age") => RelationalExpr::new(
+                    Ident::from("age"), Lit::new_uint(18), RelationalExpr::OP_GT
+                ),
+            },
+        )
+        .with_group_by(Ident::from("city"));
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn select_aggregate_having() {
+        let tok = lex_insecure(
+            br#"
+                select count(distinct username) from apps.users group by city
+                having count(*) > 10 and count(distinct username) between 1 and 5
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<SelectAggregateStatement>(&tok[1..]).unwrap();
+        let e = SelectAggregateStatement::new_test(
+            ("apps", "users").into(),
+            vec![Aggregate::CountDistinct(Ident::from("username"))],
+            dict! {},
+        )
+        .with_group_by(Ident::from("city"))
+        .with_having(vec![
+            HavingExpr::new_test(
+                Aggregate::Count,
+                RelationalExpr::new(
+                    Ident::from("count"),
+                    Lit::new_uint(10),
+                    RelationalExpr::OP_GT,
+                ),
+            ),
+            HavingExpr::new_test(
+                Aggregate::CountDistinct(Ident::from("username")),
+                RelationalExpr::new_range(
+                    Ident::from("count"),
+                    (Lit::new_uint(1), RelationalExpr::OP_GE),
+                    (Lit::new_uint(5), RelationalExpr::OP_LE),
+                ),
+            ),
+        ]);
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn select_aggregate_sum() {
+        let tok = lex_insecure(
+            br#"
+                select count(*), sum(age) from apps.users group by city having sum(age) > 100
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<SelectAggregateStatement>(&tok[1..]).unwrap();
+        let e = SelectAggregateStatement::new_test(
+            ("apps", "users").into(),
+            vec![Aggregate::Count, Aggregate::Sum(Ident::from("age"))],
+            dict! {},
+        )
+        .with_group_by(Ident::from("city"))
+        .with_having(vec![HavingExpr::new_test(
+            Aggregate::Sum(Ident::from("age")),
+            RelationalExpr::new(
+                Ident::from("sum"),
+                Lit::new_uint(100),
+                RelationalExpr::OP_GT,
+            ),
+        )]);
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn select_file() {
+        let tok = lex_insecure(
+            br#"
+                select name, age from file '/tmp/users.csv' (format csv, header, schema (name: string, null age: uint8))
+                where age > 18 limit 10
+            "#,
+        )
+        .unwrap();
+        assert!(SelectFileStatement::is_next(&State::new_inplace(&tok[1..])));
+        let r = parse_ast_node_full::<SelectFileStatement>(&tok[1..]).unwrap();
+        let e = SelectFileStatement::new_test(
+            "/tmp/users.csv",
+            true,
+            vec![
+                FieldSpec::new(
+                    Ident::from("name"),
+                    vec![LayerSpec::new(Ident::from("string"), null_dict! {})],
+                    false,
+                    false,
+                ),
+                FieldSpec::new(
+                    Ident::from("age"),
+                    vec![LayerSpec::new(Ident::from("uint8"), null_dict! {})],
+                    true,
+                    false,
+                ),
+            ],
+            FileProjection::Fields(vec![Ident::from("name"), Ident::from("age")]),
+            dict! {
+                Ident::from("age") => RelationalExpr::new(
+                    Ident::from("age"), Lit::new_uint(18), RelationalExpr::OP_GT
+                ),
+            },
+            Some(10),
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn select_file_aggregate() {
+        let tok = lex_insecure(
+            br#"
+                select count(*) from file 'users.csv' (schema (name: string, city: string), format csv)
+                group by city
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<SelectFileStatement>(&tok[1..]).unwrap();
+        assert_eq!(
+            r.projection(),
+            &FileProjection::Aggregates(vec![Aggregate::Count])
+        );
+        assert_eq!(r.group_by(), Some(Ident::from("city")));
+        assert!(!r.header());
+    }
+    #[test]
+    fn select_file_bad() {
+        for query in [
+            "select * from file 'a.csv'",
+            "select * from file 'a.csv' ()",
+            "select * from file 'a.csv' (format csv)",
+            "select * from file 'a.csv' (schema (a: string))",
+            "select * from file 'a.csv' (format csv, format csv, schema (a: string))",
+            "select * from file 'a.csv' (format csv, schema (primary a: string))",
+            "select * from file 'a.csv' (format csv, schema (a: string)) group by a",
+            "select count(*) from file 'a.csv' (format csv, schema (a: string)) limit 10",
+            "select * from file 1 (format csv, schema (a: string))",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(
+                parse_ast_node_full::<SelectFileStatement>(&tok[1..]).is_err(),
+                "{query}"
+            );
+        }
+        // parquet (or any other format) files can't be read
+        for (query, format) in [
+            (
+                "select * from file 'a.parquet' (format parquet, schema (a: string))",
+                "parquet",
+            ),
+            (
+                "select * from file 'a.json' (format JSON, schema (a: string))",
+                "json",
+            ),
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            let e = parse_ast_node_full::<SelectFileStatement>(&tok[1..]).unwrap_err();
+            assert_eq!(e, QueryError::QExecFileFormatUnsupported, "{query}");
+            assert_eq!(
+                ErrorDetail::take(e).entries(),
+                [("format", Box::from(format))],
+                "{query}"
+            );
+        }
+        // a model called `file`
+        let tok = lex_insecure(b"select all * from file limit 10").unwrap();
+        assert!(!SelectFileStatement::is_next(&State::new_inplace(
+            &tok[1..]
+        )));
+    }
+    #[test]
+    fn select_aggregate_bad() {
+        for query in [
+            "select count(city) from apps.users",
+            "select count(distinct) from apps.users",
+            "select topk(city) from apps.users",
+            "select topk(city, 0) from apps.users",
+            "select topk(city, 1001) from apps.users",
+            "select topk(city, 'a') from apps.users",
+            "select sum(*) from apps.users",
+            "select sum(distinct city) from apps.users",
+            "select avg(city) from apps.users",
+            "select count(distinct city), from apps.users",
+            "select count(distinct city) apps.users",
+            "select count(*, city) from apps.users",
+            "select count(*) from apps.users group city",
+            "select count(*) from apps.users group by",
+            "select count(*) from apps.users group by city, age",
+            "select count(*) from apps.users having count(*) > 10",
+            "select count(*) from apps.users group by city having",
+            "select count(*) from apps.users group by city having count(*) >",
+            "select count(*) from apps.users group by city having count(*) > 1 and",
+            "select count(*) from apps.users group by city having age > 10",
+            "select count(*) from apps.users group by city having topk(city, 1) > 10",
+            "select count(*) from apps.users group by city having count(*) matches 'a'",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert_eq!(
+                parse_ast_node_full::<SelectAggregateStatement>(&tok[1..]).unwrap_err(),
+                QueryError::QLInvalidSyntax,
+                "{query}"
+            );
+        }
+    }
+    #[test]
+    fn select_string_fn() {
+        let tok = lex_insecure(
+            br#"
+                select username, upper(name), substr(trim(name), 2, 3) from apps.users where username = "sayan"
+            "#,
+        )
+        .unwrap();
+        // not an aggregate
+        assert!(!Aggregate::is_next(&State::new_inplace(&tok[3..])));
+        let mut r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert_eq!(
+            r.fields(),
+            [
+                Ident::from("username"),
+                Ident::from("name"),
+                Ident::from("name")
+            ]
+        );
+        assert_eq!(
+            r.take_projection(),
+            Some(vec![
+                RowExpr::Field(Ident::from("username")),
+                RowExpr::Call(RowFn::Upper, vec![RowExpr::Field(Ident::from("name"))]),
+                RowExpr::Call(
+                    RowFn::Substr,
+                    vec![
+                        RowExpr::Call(RowFn::Trim, vec![RowExpr::Field(Ident::from("name"))]),
+                        RowExpr::Value(Lit::new_uint(2)),
+                        RowExpr::Value(Lit::new_uint(3)),
+                    ]
+                ),
+            ])
+        );
+        // without a string function, there's no projection
+        let tok = lex_insecure(b"select username, name from apps.users where username = 'sayan'")
+            .unwrap();
+        let mut r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert_eq!(r.take_projection(), None);
+    }
+    #[test]
+    fn select_string_fn_bad() {
+        for query in [
+            "select lower() from apps.users where username = 'sayan'",
+            "select lower(name, name) from apps.users where username = 'sayan'",
+            "select substr(name) from apps.users where username = 'sayan'",
+            "select substr(name, 1, 2, 3) from apps.users where username = 'sayan'",
+            "select upper(name from apps.users where username = 'sayan'",
+            "select upper(name), from apps.users where username = 'sayan'",
+            "select upper(name) from apps.users where username in ('sayan', 'bob')",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(
+                parse_ast_node_full::<SelectStatement>(&tok[1..]).is_err(),
+                "{query}"
+            );
+        }
+    }
+    #[test]
+    fn select_numeric_fn() {
+        let tok = lex_insecure(
+            b"select max(score, 0), cast(round(ratio, 2) as string), abs(upper(name)) from apps.users where username = 'sayan'",
+        )
+        .unwrap();
+        let mut r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert_eq!(
+            r.fields(),
+            [
+                Ident::from("score"),
+                Ident::from("ratio"),
+                Ident::from("name")
+            ]
+        );
+        // the types are only checked against the model
+        assert_eq!(
+            r.take_projection(),
+            Some(vec![
+                RowExpr::Call(
+                    RowFn::Max,
+                    vec![
+                        RowExpr::Field(Ident::from("score")),
+                        RowExpr::Value(Lit::new_uint(0)),
+                    ]
+                ),
+                RowExpr::Cast(
+                    Box::new(RowExpr::Call(
+                        RowFn::Round,
+                        vec![
+                            RowExpr::Field(Ident::from("ratio")),
+                            RowExpr::Value(Lit::new_uint(2)),
+                        ]
+                    )),
+                    TagSelector::String
+                ),
+                RowExpr::Call(
+                    RowFn::Abs,
+                    vec![RowExpr::Call(
+                        RowFn::Upper,
+                        vec![RowExpr::Field(Ident::from("name"))]
+                    )]
+                ),
+            ])
+        );
+        // a cast alone is a projection too
+        let tok =
+            lex_insecure(b"select cast(score as uint8) from apps.users where username = 'sayan'")
+                .unwrap();
+        let mut r = parse_ast_node_full::<SelectStatement>(&tok[1..]).unwrap();
+        assert_eq!(
+            r.take_projection(),
+            Some(vec![RowExpr::Cast(
+                Box::new(RowExpr::Field(Ident::from("score"))),
+                TagSelector::UInt8
+            )])
+        );
+    }
+    #[test]
+    fn select_numeric_fn_bad() {
+        for query in [
+            "select max(score) from apps.users where username = 'sayan'",
+            "select round(score, 1, 2) from apps.users where username = 'sayan'",
+            "select cast(score) from apps.users where username = 'sayan'",
+            "select cast(score as) from apps.users where username = 'sayan'",
+            "select cast(score as list) from apps.users where username = 'sayan'",
+            "select cast(score as bool) from apps.users where username = 'sayan'",
+            "select cast(score, uint8) from apps.users where username = 'sayan'",
+            "select cast(score as uint8 from apps.users where username = 'sayan'",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(
+                parse_ast_node_full::<SelectStatement>(&tok[1..]).is_err(),
+                "{query}"
+            );
+        }
+    }
 }
 mod expression_tests {
     use {
@@ -669,13 +1273,13 @@ mod update_statement {
     use {
         super::*,
         crate::engine::{
-            core::query_meta::AssignmentOperator,
-            data::lit::Lit,
+            core::{dml::expr::RowFn, query_meta::AssignmentOperator},
+            data::{lit::Lit, tag::TagSelector},
             ql::{
                 ast::{parse_ast_node_full, parse_ast_node_full_with_space},
                 dml::{
                     upd::{AssignmentExpression, UpdateStatement},
-                    RelationalExpr, WhereClause,
+                    RelationalExpr, Returning, RowExpr, Summary, WhereClause,
                 },
                 lex::Ident,
             },
@@ -746,41 +1350,263 @@ mod update_statement {
         );
         assert_eq!(r, e);
     }
-}
-mod delete_stmt {
-    use {
-        super::*,
-        crate::engine::{
-            data::lit::Lit,
-            ql::{
-                ast::{parse_ast_node_full, parse_ast_node_full_with_space},
-                dml::{del::DeleteStatement, RelationalExpr},
-                lex::Ident,
-            },
-        },
-    };
-
     #[test]
-    fn delete_mini() {
+    fn update_string_fn() {
         let tok = lex_insecure(
             br#"
-                delete from users where username = "sayan"
+                update myspace.mymodel set name = upper(name), name_len = len(name) where username = "sayan"
             "#,
         )
         .unwrap();
-        let e = DeleteStatement::new_test(
-            ("apps", "users").into(),
-            dict! {
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        let e = UpdateStatement::new(
+            ("myspace", "mymodel").into(),
+            vec![
+                AssignmentExpression::new_expr(
+                    Ident::from("name"),
+                    RowExpr::Call(RowFn::Upper, vec![RowExpr::Field(Ident::from("name"))]),
+                    AssignmentOperator::Assign,
+                ),
+                AssignmentExpression::new_expr(
+                    Ident::from("name_len"),
+                    RowExpr::Call(RowFn::Len, vec![RowExpr::Field(Ident::from("name"))]),
+                    AssignmentOperator::Assign,
+                ),
+            ],
+            WhereClause::new(dict! {
                 Ident::from("username") => RelationalExpr::new(
                     Ident::from("username"),
                     Lit::new_str("sayan"),
                     RelationalExpr::OP_EQ
                 )
-            },
+            }),
         );
-        assert_eq!(
-            parse_ast_node_full_with_space::<DeleteStatement>(&tok[1..], "apps").unwrap(),
-            e
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn update_numeric_fn() {
+        let tok = lex_insecure(
+            br#"
+                update myspace.mymodel set level = cast(score as uint8), score = min(score, 100) where username = "sayan"
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        let e = UpdateStatement::new(
+            ("myspace", "mymodel").into(),
+            vec![
+                AssignmentExpression::new_expr(
+                    Ident::from("level"),
+                    RowExpr::Cast(
+                        Box::new(RowExpr::Field(Ident::from("score"))),
+                        TagSelector::UInt8,
+                    ),
+                    AssignmentOperator::Assign,
+                ),
+                AssignmentExpression::new_expr(
+                    Ident::from("score"),
+                    RowExpr::Call(
+                        RowFn::Min,
+                        vec![
+                            RowExpr::Field(Ident::from("score")),
+                            RowExpr::Value(Lit::new_uint(100)),
+                        ],
+                    ),
+                    AssignmentOperator::Assign,
+                ),
+            ],
+            WhereClause::new(dict! {
+                Ident::from("username") => RelationalExpr::new(
+                    Ident::from("username"),
+                    Lit::new_str("sayan"),
+                    RelationalExpr::OP_EQ
+                )
+            }),
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn update_datetime_expr() {
+        let tok = lex_insecure(
+            br#"
+                update myspace.mymodel set expires = 1000 + 1d, year = year(1000) where username = "sayan"
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        let e = UpdateStatement::new(
+            ("myspace", "mymodel").into(),
+            vec![
+                AssignmentExpression::new(
+                    Ident::from("expires"),
+                    Lit::new_uint(1000 + 24 * 60 * 60),
+                    AssignmentOperator::Assign,
+                ),
+                AssignmentExpression::new(
+                    Ident::from("year"),
+                    Lit::new_uint(1970),
+                    AssignmentOperator::Assign,
+                ),
+            ],
+            WhereClause::new(dict! {
+                Ident::from("username") => RelationalExpr::new(
+                    Ident::from("username"),
+                    Lit::new_str("sayan"),
+                    RelationalExpr::OP_EQ
+                )
+            }),
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn update_filter_limit_force() {
+        let tok = lex_insecure(
+            br#"
+                update twitter.users SET followers = 0 where verified = false limit 100 force
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        let e = UpdateStatement::new_with_options(
+            ("twitter", "users").into(),
+            vec![AssignmentExpression::new(
+                Ident::from("followers"),
+                Lit::new_uint(0),
+                AssignmentOperator::Assign,
+            )],
+            WhereClause::new(dict! {
+                Ident::from("verified") => RelationalExpr::new(
+                    Ident::from("verified"),
+                    Lit::new_bool(false),
+                    RelationalExpr::OP_EQ
+                )
+            }),
+            Some(100),
+            true,
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn update_unfiltered() {
+        let tok = lex_insecure(b"update twitter.users SET followers = 0 force").unwrap();
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        let e = UpdateStatement::new_with_options(
+            ("twitter", "users").into(),
+            vec![AssignmentExpression::new(
+                Ident::from("followers"),
+                Lit::new_uint(0),
+                AssignmentOperator::Assign,
+            )],
+            WhereClause::new(Default::default()),
+            None,
+            true,
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn update_with_lsn() {
+        let tok = lex_insecure(
+            br#"update twitter.users SET followers += 1 where username = "sayan" with lsn"#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        assert!(r.with_lsn());
+        let tok = lex_insecure(
+            br#"update twitter.users SET followers += 1 where username = "sayan" returning followers with lsn"#,
+        )
+        .unwrap();
+        assert!(parse_ast_node_full::<UpdateStatement>(&tok[1..]).is_err());
+    }
+    #[test]
+    fn update_with_summary() {
+        let tok = lex_insecure(
+            br#"update twitter.users SET followers += 1 where username = "sayan" with keys"#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        assert_eq!(r.summary(), Some(Summary::Keys));
+        let tok = lex_insecure(
+            br#"update twitter.users SET followers += 1 where username = "sayan" returning * with summary"#,
+        )
+        .unwrap();
+        assert!(parse_ast_node_full::<UpdateStatement>(&tok[1..]).is_err());
+    }
+    #[test]
+    fn update_returning() {
+        let tok = lex_insecure(
+            br#"
+                update twitter.users SET followers += 1 where username = "sayan" returning username, followers
+            "#,
+        )
+        .unwrap();
+        let r = parse_ast_node_full::<UpdateStatement>(&tok[1..]).unwrap();
+        let e = UpdateStatement::new_returning(
+            ("twitter", "users").into(),
+            vec![AssignmentExpression::new(
+                Ident::from("followers"),
+                Lit::new_uint(1),
+                AssignmentOperator::AddAssign,
+            )],
+            WhereClause::new(dict! {
+                Ident::from("username") => RelationalExpr::new(
+                    Ident::from("username"),
+                    Lit::new_str("sayan"),
+                    RelationalExpr::OP_EQ
+                )
+            }),
+            Returning::Fields(vec![Ident::from("username"), Ident::from("followers")]),
+        );
+        assert_eq!(r, e);
+    }
+    #[test]
+    fn update_bad_tail() {
+        for query in [
+            "update twitter.users SET followers = 0,",
+            "update twitter.users SET followers = 0 where",
+            "update twitter.users SET followers = 0 limit",
+            "update twitter.users SET followers = 0 returning",
+            "update twitter.users SET followers = 0 returning followers,",
+            "update twitter.users SET followers = 0 returning 1",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(parse_ast_node_full::<UpdateStatement>(&tok[1..]).is_err());
+        }
+    }
+}
+mod delete_stmt {
+    use {
+        super::*,
+        crate::engine::{
+            data::lit::Lit,
+            ql::{
+                ast::{parse_ast_node_full, parse_ast_node_full_with_space},
+                dml::{del::DeleteStatement, RelationalExpr, Returning, Summary},
+                lex::Ident,
+            },
+        },
+    };
+
+    #[test]
+    fn delete_mini() {
+        let tok = lex_insecure(
+            br#"
+                delete from users where username = "sayan"
+            "#,
+        )
+        .unwrap();
+        let e = DeleteStatement::new_test(
+            ("apps", "users").into(),
+            dict! {
+                Ident::from("username") => RelationalExpr::new(
+                    Ident::from("username"),
+                    Lit::new_str("sayan"),
+                    RelationalExpr::OP_EQ
+                )
+            },
+        );
+        assert_eq!(
+            parse_ast_node_full_with_space::<DeleteStatement>(&tok[1..], "apps").unwrap(),
+            e
         );
     }
     #[test]
@@ -806,13 +1632,110 @@ mod delete_stmt {
             e
         );
     }
+    #[test]
+    fn delete_filter_with_limit() {
+        let tok = lex_insecure(
+            br#"
+                delete from twitter.users where followers < 10 and verified = false limit 100
+            "#,
+        )
+        .unwrap();
+        let e = DeleteStatement::new_test_with_limit(
+            ("twitter", "users").into(),
+            dict! {
+                Ident::from("followers") => RelationalExpr::new(
+                    Ident::from("followers"),
+                    Lit::new_uint(10),
+                    RelationalExpr::OP_LT
+                ),
+                Ident::from("verified") => RelationalExpr::new(
+                    Ident::from("verified"),
+                    Lit::new_bool(false),
+                    RelationalExpr::OP_EQ
+                )
+            },
+            100,
+        );
+        assert_eq!(
+            parse_ast_node_full::<DeleteStatement>(&tok[1..]).unwrap(),
+            e
+        );
+    }
+    #[test]
+    fn delete_with_lsn() {
+        let tok =
+            lex_insecure(br#"delete from twitter.users where followers < 10 limit 5 with lsn"#)
+                .unwrap();
+        let r = parse_ast_node_full::<DeleteStatement>(&tok[1..]).unwrap();
+        assert!(r.with_lsn());
+        assert_eq!(r.limit(), Some(5));
+        let tok = lex_insecure(
+            br#"delete from twitter.users where username = "sayan" returning * with lsn"#,
+        )
+        .unwrap();
+        assert!(parse_ast_node_full::<DeleteStatement>(&tok[1..]).is_err());
+    }
+    #[test]
+    fn delete_with_summary() {
+        let tok =
+            lex_insecure(br#"delete from twitter.users where followers < 10 limit 5 with summary"#)
+                .unwrap();
+        let r = parse_ast_node_full::<DeleteStatement>(&tok[1..]).unwrap();
+        assert_eq!(r.summary(), Some(Summary::Counts));
+        assert_eq!(r.limit(), Some(5));
+        let tok = lex_insecure(
+            br#"delete from twitter.users where username = "sayan" with lsn with summary"#,
+        )
+        .unwrap();
+        assert!(parse_ast_node_full::<DeleteStatement>(&tok[1..]).is_err());
+    }
+    #[test]
+    fn delete_returning() {
+        let tok = lex_insecure(
+            br#"
+                delete from twitter.users where username = "sayan" returning *
+            "#,
+        )
+        .unwrap();
+        let e = DeleteStatement::new_test_returning(
+            ("twitter", "users").into(),
+            dict! {
+                Ident::from("username") => RelationalExpr::new(
+                    Ident::from("username"),
+                    Lit::new_str("sayan"),
+                    RelationalExpr::OP_EQ
+                )
+            },
+            Returning::Wildcard,
+        );
+        assert_eq!(
+            parse_ast_node_full::<DeleteStatement>(&tok[1..]).unwrap(),
+            e
+        );
+    }
+    #[test]
+    fn delete_bad_limit() {
+        for query in [
+            "delete from twitter.users where followers < 10 limit",
+            "delete from twitter.users where followers < 10 limit -1",
+            "delete from twitter.users where followers < 10 limit 'ten'",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert!(parse_ast_node_full::<DeleteStatement>(&tok[1..]).is_err());
+        }
+    }
 }
 mod relational_expr {
     use {
         super::*,
         crate::engine::{
-            data::lit::Lit,
-            ql::{ast::parse_ast_node_full, dml::RelationalExpr, lex::Ident},
+            core::dml::expr::RowFn,
+            data::{lit::Lit, tag::TagSelector},
+            ql::{
+                ast::parse_ast_node_full,
+                dml::{RelationalExpr, RowExpr},
+                lex::Ident,
+            },
         },
     };
 
@@ -825,7 +1748,9 @@ mod relational_expr {
             RelationalExpr {
                 rhs: Lit::new_uint(10),
                 lhs: Ident::from("primary_key"),
-                opc: RelationalExpr::OP_EQ
+                opc: RelationalExpr::OP_EQ,
+                rhs_hi: None,
+                lhs_expr: None,
             }
         );
     }
@@ -838,7 +1763,9 @@ mod relational_expr {
             RelationalExpr {
                 rhs: Lit::new_uint(10),
                 lhs: Ident::from("primary_key"),
-                opc: RelationalExpr::OP_NE
+                opc: RelationalExpr::OP_NE,
+                rhs_hi: None,
+                lhs_expr: None,
             }
         );
     }
@@ -851,7 +1778,9 @@ mod relational_expr {
             RelationalExpr {
                 rhs: Lit::new_uint(10),
                 lhs: Ident::from("primary_key"),
-                opc: RelationalExpr::OP_GT
+                opc: RelationalExpr::OP_GT,
+                rhs_hi: None,
+                lhs_expr: None,
             }
         );
     }
@@ -864,7 +1793,9 @@ mod relational_expr {
             RelationalExpr {
                 rhs: Lit::new_uint(10),
                 lhs: Ident::from("primary_key"),
-                opc: RelationalExpr::OP_GE
+                opc: RelationalExpr::OP_GE,
+                rhs_hi: None,
+                lhs_expr: None,
             }
         );
     }
@@ -877,7 +1808,9 @@ mod relational_expr {
             RelationalExpr {
                 rhs: Lit::new_uint(10),
                 lhs: Ident::from("primary_key"),
-                opc: RelationalExpr::OP_LT
+                opc: RelationalExpr::OP_LT,
+                rhs_hi: None,
+                lhs_expr: None,
             }
         );
     }
@@ -894,6 +1827,242 @@ mod relational_expr {
             )
         );
     }
+    #[test]
+    fn expr_between() {
+        let expr = lex_insecure(b"primary_key between 10 and 20").unwrap();
+        let r = parse_ast_node_full::<RelationalExpr>(&expr).unwrap();
+        assert_eq!(
+            r,
+            RelationalExpr::new_range(
+                Ident::from("primary_key"),
+                (Lit::new_uint(10), RelationalExpr::OP_GE),
+                (Lit::new_uint(20), RelationalExpr::OP_LE)
+            )
+        );
+    }
+    #[test]
+    fn expr_between_bad() {
+        for src in [
+            &b"primary_key between 10"[..],
+            b"primary_key between 10 20",
+            b"primary_key between 10 or 20",
+            b"primary_key between and 20",
+        ] {
+            let expr = lex_insecure(src).unwrap();
+            assert!(parse_ast_node_full::<RelationalExpr>(&expr).is_err());
+        }
+    }
+    #[test]
+    fn expr_approx() {
+        for (src, lo, hi) in [
+            (&b"score ~= 2.5 within 0.5"[..], 2.0, 3.0),
+            (b"score ~= 2 within 1", 1.0, 3.0),
+            (
+                b"score ~= 2.5",
+                2.5 - RelationalExpr::DEFAULT_EPSILON,
+                2.5 + RelationalExpr::DEFAULT_EPSILON,
+            ),
+            (b"score ~= 2.5 within 0", 2.5, 2.5),
+        ] {
+            let expr = lex_insecure(src).unwrap();
+            assert_eq!(
+                parse_ast_node_full::<RelationalExpr>(&expr).unwrap(),
+                RelationalExpr::new_range(
+                    Ident::from("score"),
+                    (Lit::new_float(lo), RelationalExpr::OP_GE),
+                    (Lit::new_float(hi), RelationalExpr::OP_LE)
+                ),
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+        for src in [
+            &b"score ~= 'a'"[..],
+            b"score ~= 2.5 within 'a'",
+            b"score ~= 2.5 within",
+            b"score ~ 2.5",
+            b"score ~=",
+        ] {
+            let expr = lex_insecure(src).unwrap();
+            assert!(parse_ast_node_full::<RelationalExpr>(&expr).is_err());
+        }
+    }
+    #[test]
+    fn expr_datetime() {
+        for (src, value) in [
+            (&b"created >= 1000 - 10s"[..], 990),
+            (
+                b"created >= 1000 + 1d - 1h + 2 minutes",
+                1000 + 86400 - 3600 + 120,
+            ),
+            (b"created >= 1 w", 7 * 24 * 60 * 60),
+            // 2024-02-29T13:45:30Z
+            (b"created >= year(1709214330)", 2024),
+            (b"created >= month(1709214330) + day(1709214330)", 2 + 29),
+            (b"created >= hour(1709214330 + 1h)", 14),
+            (b"created >= weekday(1709214330 - 1d)", 3),
+        ] {
+            let expr = lex_insecure(src).unwrap();
+            assert_eq!(
+                parse_ast_node_full::<RelationalExpr>(&expr).unwrap(),
+                RelationalExpr::new(
+                    Ident::from("created"),
+                    Lit::new_uint(value),
+                    RelationalExpr::OP_GE
+                ),
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+        // now() is evaluated when the expression is parsed (the two calls may be a second apart)
+        let expr = lex_insecure(b"created between now() - 7d and now()").unwrap();
+        let r = parse_ast_node_full::<RelationalExpr>(&expr).unwrap();
+        let now = crate::util::os::get_epoch_time_secs();
+        let bounds: Vec<u64> = r
+            .comparisons()
+            .map(|(_, lit)| lit.try_uint().unwrap())
+            .collect();
+        assert!((bounds[1] - bounds[0]).abs_diff(7 * 24 * 60 * 60) <= 1);
+        assert!(now.abs_diff(bounds[1]) <= 1);
+    }
+    #[test]
+    fn expr_datetime_bad() {
+        for src in [
+            &b"created >= 1000 -"[..],
+            b"created >= 10 - 20",
+            b"created >= 'a' + 1",
+            b"created >= -1d",
+            b"created >= now(",
+            b"created >= now(1)",
+            b"created >= year()",
+            b"created >= yesterday()",
+            b"created >= year('2024')",
+            b"created >= 18446744073709551615 w",
+            b"created >= year(year(year(year(year(year(year(year(year(1)))))))))",
+        ] {
+            let expr = match lex_insecure(src) {
+                Ok(expr) => expr,
+                Err(_) => continue,
+            };
+            assert!(
+                parse_ast_node_full::<RelationalExpr>(&expr).is_err(),
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+    }
+    #[test]
+    fn expr_string_fn() {
+        let expr = lex_insecure(b"len(concat(first, ' ', last)) > 10").unwrap();
+        let r = parse_ast_node_full::<RelationalExpr>(&expr).unwrap();
+        assert_eq!(
+            r,
+            RelationalExpr {
+                rhs: Lit::new_uint(10),
+                // keyed by the first field
+                lhs: Ident::from("first"),
+                opc: RelationalExpr::OP_GT,
+                rhs_hi: None,
+                lhs_expr: Some(RowExpr::Call(
+                    RowFn::Len,
+                    vec![RowExpr::Call(
+                        RowFn::Concat,
+                        vec![
+                            RowExpr::Field(Ident::from("first")),
+                            RowExpr::Value(Lit::new_str(" ")),
+                            RowExpr::Field(Ident::from("last")),
+                        ]
+                    )]
+                )),
+            }
+        );
+        assert_eq!(r.fields(), [Ident::from("first"), Ident::from("last")]);
+        assert!(!r.filter_hint_none() & r.bounds().is_none());
+        let expr = lex_insecure(b"lower(username) matches '^s'").unwrap();
+        let r = parse_ast_node_full::<RelationalExpr>(&expr).unwrap();
+        assert_eq!(r.matches_pattern(), Some("^s"));
+        assert_eq!(
+            r.lhs_expr(),
+            Some(&RowExpr::Call(
+                RowFn::Lower,
+                vec![RowExpr::Field(Ident::from("username"))]
+            ))
+        );
+    }
+    #[test]
+    fn expr_string_fn_bad() {
+        for src in [
+            &b"lower() = 'a'"[..],
+            b"lower('A') = 'a'",
+            b"lower(a, b) = 'a'",
+            b"substr(a) = 'a'",
+            b"lower(a = 'a'",
+            b"lower(a) =",
+            b"lower(lower(lower(lower(lower(lower(lower(lower(lower(a))))))))) = 'a'",
+        ] {
+            let expr = lex_insecure(src).unwrap();
+            assert!(
+                parse_ast_node_full::<RelationalExpr>(&expr).is_err(),
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+    }
+    #[test]
+    fn expr_numeric_fn() {
+        let expr = lex_insecure(b"cast(abs(delta) as uint8) <= 100").unwrap();
+        let r = parse_ast_node_full::<RelationalExpr>(&expr).unwrap();
+        assert_eq!(r.lhs(), Ident::from("delta"));
+        assert_eq!(
+            r.lhs_expr(),
+            Some(&RowExpr::Cast(
+                Box::new(RowExpr::Call(
+                    RowFn::Abs,
+                    vec![RowExpr::Field(Ident::from("delta"))]
+                )),
+                TagSelector::UInt8
+            ))
+        );
+        for src in [
+            &b"cast(5 as uint8) = 5"[..],
+            b"cast(a as uint128) = 5",
+            b"min(a) = 5",
+            b"cast(cast(cast(cast(cast(cast(cast(cast(cast(a as uint8) as uint8) as uint8) as uint8) as uint8) as uint8) as uint8) as uint8) as uint8) = 5",
+        ] {
+            let expr = lex_insecure(src).unwrap();
+            assert!(
+                parse_ast_node_full::<RelationalExpr>(&expr).is_err(),
+                "{}",
+                String::from_utf8_lossy(src)
+            );
+        }
+    }
+    #[test]
+    fn expr_matches() {
+        let expr = lex_insecure(b"username matches '^s.*n$'").unwrap();
+        let r = parse_ast_node_full::<RelationalExpr>(&expr).unwrap();
+        assert_eq!(
+            r,
+            RelationalExpr::new(
+                Ident::from("username"),
+                Lit::new_str("^s.*n$"),
+                RelationalExpr::OP_MATCHES
+            )
+        );
+        assert_eq!(r.matches_pattern(), Some("^s.*n$"));
+    }
+    #[test]
+    fn expr_matches_bad() {
+        for src in [
+            &b"username matches"[..],
+            b"username matches 10",
+            b"username matches null",
+            b"username matches = 'a'",
+        ] {
+            let expr = lex_insecure(src).unwrap();
+            assert!(parse_ast_node_full::<RelationalExpr>(&expr).is_err());
+        }
+    }
 }
 mod where_clause {
     use {
@@ -956,14 +2125,50 @@ mod where_clause {
         .unwrap();
         assert!(parse_ast_node_full::<WhereClause>(&tok).is_err());
     }
+    #[test]
+    fn where_range_merge() {
+        let tok = lex_insecure(
+            br#"
+                userid < 200 and userid >= 100
+            "#,
+        )
+        .unwrap();
+        let expected = WhereClause::new(dict! {
+            Ident::from("userid") => RelationalExpr::new_range(
+                Ident::from("userid"),
+                (Lit::new_uint(100), RelationalExpr::OP_GE),
+                (Lit::new_uint(200), RelationalExpr::OP_LT)
+            )
+        });
+        assert_eq!(expected, parse_ast_node_full::<WhereClause>(&tok).unwrap());
+    }
+    #[test]
+    fn where_range_merge_bad() {
+        for src in [
+            &b"userid > 100 and userid >= 200"[..],
+            b"userid between 100 and 200 and userid < 150",
+            b"userid <= 100 and userid != 200",
+        ] {
+            let tok = lex_insecure(src).unwrap();
+            assert!(parse_ast_node_full::<WhereClause>(&tok).is_err());
+        }
+    }
 }
 
 mod select_all {
     use {
         super::lex_insecure,
         crate::engine::{
+            data::lit::Lit,
             error::QueryError,
-            ql::{ast::parse_ast_node_full_with_space, dml::sel::SelectAllStatement},
+            ql::{
+                ast::parse_ast_node_full_with_space,
+                dml::{
+                    sel::{SampleSize, SelectAllStatement},
+                    RelationalExpr,
+                },
+                lex::Ident,
+            },
         },
     };
 
@@ -990,6 +2195,140 @@ mod select_all {
         );
     }
 
+    #[test]
+    fn select_all_offset() {
+        let tok = lex_insecure(b"select all * from mymodel limit 100 offset 200").unwrap();
+        let mut expected =
+            SelectAllStatement::test_new(("myspace", "mymodel").into(), vec![], true, 100);
+        expected.offset = 200;
+        assert_eq!(
+            parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").unwrap(),
+            expected
+        );
+        for src in [
+            &b"select all * from mymodel limit 100 offset"[..],
+            b"select all * from mymodel limit 100 offset 'a'",
+            b"select all * from mymodel limit 100 offset -1",
+            b"select all * from mymodel offset 100",
+        ] {
+            let tok = lex_insecure(src).unwrap();
+            assert!(
+                parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn select_all_with_deleted() {
+        let tok =
+            lex_insecure(b"select all * from mymodel limit 100 offset 200 with deleted").unwrap();
+        let mut expected =
+            SelectAllStatement::test_new(("myspace", "mymodel").into(), vec![], true, 100);
+        expected.offset = 200;
+        expected.with_deleted = true;
+        assert_eq!(
+            parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").unwrap(),
+            expected
+        );
+        // must come after the limit
+        let tok = lex_insecure(b"select all * from mymodel with deleted limit 100").unwrap();
+        assert!(
+            parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").is_err()
+        );
+    }
+
+    #[test]
+    fn select_all_sample() {
+        for (src, sample) in [
+            (
+                &b"select all * from mymodel limit 100 sample 10"[..],
+                SampleSize::Rows(10),
+            ),
+            (
+                b"select all * from mymodel limit 100 sample 10 percent",
+                SampleSize::Percent(10.0),
+            ),
+            (
+                b"select all * from mymodel limit 100 sample 0.5 percent with deleted",
+                SampleSize::Percent(0.5),
+            ),
+        ] {
+            let tok = lex_insecure(src).unwrap();
+            let mut expected =
+                SelectAllStatement::test_new(("myspace", "mymodel").into(), vec![], true, 100);
+            expected.sample = Some(sample);
+            expected.with_deleted = src.ends_with(b"deleted");
+            assert_eq!(
+                parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").unwrap(),
+                expected
+            );
+        }
+        for src in [
+            &b"select all * from mymodel limit 100 sample"[..],
+            b"select all * from mymodel limit 100 sample 'a'",
+            b"select all * from mymodel limit 100 sample -1",
+            b"select all * from mymodel limit 100 sample 0.5",
+            b"select all * from mymodel limit 100 sample 0 percent",
+            b"select all * from mymodel limit 100 sample 101 percent",
+            b"select all * from mymodel limit 100 offset 10 sample 10",
+            b"select all * from mymodel sample 10 limit 100",
+        ] {
+            let tok = lex_insecure(src).unwrap();
+            assert!(
+                parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").is_err()
+            );
+        }
+    }
+    #[test]
+    fn select_all_arrow() {
+        let tok = lex_insecure(b"select all * from mymodel limit 100 format arrow").unwrap();
+        let mut expected =
+            SelectAllStatement::test_new(("myspace", "mymodel").into(), vec![], true, 100);
+        expected.arrow = true;
+        assert_eq!(
+            parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").unwrap(),
+            expected
+        );
+        for src in [
+            &b"select all * from mymodel limit 100 format"[..],
+            b"select all * from mymodel limit 100 format csv",
+            b"select all upper(username) from mymodel limit 100 format arrow",
+        ] {
+            let tok = lex_insecure(src).unwrap();
+            assert!(
+                parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").is_err()
+            );
+        }
+    }
+    #[test]
+    fn select_all_with_total() {
+        for src in [
+            &b"select all * from mymodel limit 100 offset 200 with total"[..],
+            b"select all * from mymodel limit 100 offset 200 with deleted with total",
+        ] {
+            let tok = lex_insecure(src).unwrap();
+            let mut expected =
+                SelectAllStatement::test_new(("myspace", "mymodel").into(), vec![], true, 100);
+            expected.offset = 200;
+            expected.with_deleted = src.ends_with(b"deleted with total");
+            expected.with_total = true;
+            assert_eq!(
+                parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").unwrap(),
+                expected
+            );
+        }
+        for src in [
+            &b"select all * from mymodel with total limit 100"[..],
+            b"select all * from mymodel limit 100 with total with deleted",
+            b"select all * from mymodel limit 100 with total format arrow",
+        ] {
+            let tok = lex_insecure(src).unwrap();
+            assert!(
+                parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").is_err()
+            );
+        }
+    }
+
     #[test]
     fn select_all_missing_limit() {
         let tok = lex_insecure(b"select all * from mymodel").unwrap();
@@ -1003,4 +2342,194 @@ mod select_all {
             QueryError::QLUnexpectedEndOfStatement
         );
     }
+
+    #[test]
+    fn select_all_where() {
+        let tok =
+            lex_insecure(b"select all * from mymodel where username between 'a' and 'n' limit 100")
+                .unwrap();
+        assert_eq!(
+            parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").unwrap(),
+            SelectAllStatement::test_new_where(
+                ("myspace", "mymodel").into(),
+                vec![],
+                true,
+                dict! {
+                    Ident::from("username") => RelationalExpr::new_range(
+                        Ident::from("username"),
+                        (Lit::new_str("a"), RelationalExpr::OP_GE),
+                        (Lit::new_str("n"), RelationalExpr::OP_LE)
+                    )
+                },
+                100
+            )
+        );
+        let tok = lex_insecure(b"select all * from mymodel where limit 100").unwrap();
+        assert!(
+            parse_ast_node_full_with_space::<SelectAllStatement>(&tok[2..], "myspace").is_err()
+        );
+    }
+}
+
+mod traverse {
+    use {
+        super::lex_insecure,
+        crate::engine::{
+            data::lit::Lit,
+            error::QueryError,
+            ql::{
+                ast::{parse_ast_node_full, parse_ast_node_full_with_space},
+                dml::traverse::TraverseStatement,
+                lex::Ident,
+            },
+        },
+    };
+    #[test]
+    fn traverse() {
+        let tok = lex_insecure(b"traverse users('sayan') via follows depth 2 limit 100").unwrap();
+        assert_eq!(
+            parse_ast_node_full_with_space::<TraverseStatement>(&tok[1..], "social").unwrap(),
+            TraverseStatement::new_test(
+                ("social", "users").into(),
+                Lit::new_str("sayan"),
+                Ident::from("follows"),
+                2,
+                100,
+                None
+            )
+        );
+        let tok = lex_insecure(b"traverse social.users(10) via follows depth 0 limit 10 fanout 5")
+            .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<TraverseStatement>(&tok[1..]).unwrap(),
+            TraverseStatement::new_test(
+                ("social", "users").into(),
+                Lit::new_uint(10),
+                Ident::from("follows"),
+                0,
+                10,
+                Some(5)
+            )
+        );
+    }
+    #[test]
+    fn traverse_bad() {
+        for query in [
+            "traverse social.users",
+            "traverse social.users('sayan')",
+            "traverse social.users() via follows depth 2 limit 100",
+            "traverse social.users('sayan') via depth 2 limit 100",
+            "traverse social.users('sayan') follows depth 2 limit 100",
+            "traverse social.users('sayan') via follows limit 100",
+            "traverse social.users('sayan') via follows depth 2",
+            "traverse social.users('sayan') via follows depth -1 limit 100",
+            "traverse social.users('sayan') via follows depth 33 limit 100",
+            "traverse social.users('sayan') via follows depth 2 limit 100 fanout",
+            "traverse social.users('sayan') via follows depth 2 limit 100 fanout 0",
+            "traverse social.users('sayan') via follows limit 100 depth 2",
+            "traverse users('sayan') via follows depth 2 limit 100",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert_eq!(
+                parse_ast_node_full::<TraverseStatement>(&tok[1..]).unwrap_err(),
+                QueryError::QLInvalidSyntax,
+                "{query}"
+            );
+        }
+    }
+}
+
+mod eval {
+    use {
+        super::lex_insecure,
+        crate::engine::{
+            error::QueryError,
+            ql::{
+                ast::{parse_ast_node_full, parse_ast_node_full_with_space},
+                dml::{
+                    del::DeleteStatement,
+                    eval::{EvalAction, EvalStatement},
+                    ins::InsertStatement,
+                    sel::ExistsStatement,
+                    upd::UpdateStatement,
+                },
+            },
+        },
+    };
+    #[test]
+    fn eval() {
+        let exists = lex_insecure(b"exists from social.users where username = 'sayan'").unwrap();
+        let update =
+            lex_insecure(b"update social.users set logins += 1 where username = 'sayan'").unwrap();
+        let insert = lex_insecure(b"insert into social.users('sayan', 1)").unwrap();
+        let tok = lex_insecure(
+            b"eval { if exists from social.users where username = 'sayan' then update social.users set logins += 1 where username = 'sayan' else insert into social.users('sayan', 1) }",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<EvalStatement>(&tok[1..]).unwrap(),
+            EvalStatement::new_test(
+                false,
+                parse_ast_node_full::<ExistsStatement>(&exists[1..]).unwrap(),
+                EvalAction::Update(parse_ast_node_full::<UpdateStatement>(&update[1..]).unwrap()),
+                Some(EvalAction::Insert(
+                    parse_ast_node_full::<InsertStatement>(&insert[1..]).unwrap()
+                ))
+            )
+        );
+        // a map insert has braces of its own
+        let insert =
+            lex_insecure(b"insert into social.users { username: 'sayan', logins: 1 }").unwrap();
+        let tok = lex_insecure(
+            b"eval { if not exists from users where username = 'sayan' then insert into users { username: 'sayan', logins: 1 } }",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_ast_node_full_with_space::<EvalStatement>(&tok[1..], "social").unwrap(),
+            EvalStatement::new_test(
+                true,
+                parse_ast_node_full::<ExistsStatement>(&exists[1..]).unwrap(),
+                EvalAction::Insert(parse_ast_node_full::<InsertStatement>(&insert[1..]).unwrap()),
+                None
+            )
+        );
+        let delete = lex_insecure(b"delete from social.users where username = 'sayan'").unwrap();
+        let tok = lex_insecure(
+            b"eval { if exists from social.users where username = 'sayan' then delete from social.users where username = 'sayan' }",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<EvalStatement>(&tok[1..]).unwrap(),
+            EvalStatement::new_test(
+                false,
+                parse_ast_node_full::<ExistsStatement>(&exists[1..]).unwrap(),
+                EvalAction::Delete(parse_ast_node_full::<DeleteStatement>(&delete[1..]).unwrap()),
+                None
+            )
+        );
+    }
+    #[test]
+    fn eval_bad() {
+        for query in [
+            "eval { if exists from social.users where username = 'sayan' }",
+            "eval if exists from social.users where username = 'sayan' then delete from social.users where username = 'sayan'",
+            "eval { exists from social.users where username = 'sayan' then delete from social.users where username = 'sayan' }",
+            "eval { if exists from social.users where username = 'sayan' delete from social.users where username = 'sayan' }",
+            // only inserts, updates and deletes
+            "eval { if exists from social.users where username = 'sayan' then select * from social.users where username = 'sayan' }",
+            // on the same model
+            "eval { if exists from social.users where username = 'sayan' then delete from social.posts where username = 'sayan' }",
+            "eval { if exists from social.users where username = 'sayan' else delete from social.users where username = 'sayan' then delete from social.users where username = 'sayan' }",
+            "eval { if exists from social.users where username = 'sayan' then delete from social.users where username = 'sayan' else }",
+            "eval { if exists from social.users where username = 'sayan' then update social.users set logins += 1 where username = 'sayan' with lsn }",
+            "eval { if exists from social.users where username = 'sayan' then delete from social.users where username = 'sayan' } delete",
+        ] {
+            let tok = lex_insecure(query.as_bytes()).unwrap();
+            assert_eq!(
+                parse_ast_node_full::<EvalStatement>(&tok[1..]).unwrap_err(),
+                QueryError::QLInvalidSyntax,
+                "{query}"
+            );
+        }
+    }
 }
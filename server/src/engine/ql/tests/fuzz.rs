@@ -0,0 +1,131 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    fuzz the lexers and the parsers with mutated queries (and mutated token streams). the parsers are driven just
+    like the executor drives them (see [`parse_like_exec`]), and nothing is allowed to panic
+*/
+
+use {
+    super::lex_insecure,
+    crate::{
+        engine::ql::{
+            fuzz::parse_like_exec,
+            lex::{InsecureLexer, SecureLexer, Token},
+        },
+        util::test_utils,
+    },
+    rand::Rng,
+};
+
+const FUZZ_ROUNDS: usize = if cfg!(miri) { 16 } else { 500 };
+
+const CORPUS: &[&str] = &[
+    "create space myspace",
+    "create space if not exists myspace with { env: { MY_PROP: 100 } }",
+    "create model myspace.mymodel(primary username: string { maxlen: 64 }, null email: string, tags: list { type: list { type: uint8 } })",
+    "create model myspace.mymodel(username: string, email_lc: string { computed: 'lower(email)' }, email: string) with { primary_index: 'btree' }",
+    "create index bycity on myspace.mymodel(city)",
+    "alter space myspace with { env: { MY_NEW_PROP: 200 } }",
+    "alter model myspace.mymodel add (city { type: string }, age { type: uint8 { min: 13, max: 120 } })",
+    "alter model myspace.mymodel update password { nullable: true }",
+    "alter model myspace.mymodel remove (col1, col2)",
+    "drop space allow not empty myspace",
+    "drop model if exists myspace.mymodel",
+    "drop index bycity on myspace.mymodel",
+    "use myspace",
+    "use $current",
+    "inspect model myspace.mymodel",
+    "inspect global",
+    "describe model myspace.mymodel",
+    "insert into myspace.mymodel('sayan', 'sayan@example.com', 0, ['a', 'b'], null) returning email",
+    "insert into myspace.mymodel { username: 'robot', age: -1, tags: [[1, 2], []], verified: true }",
+    "select * from myspace.mymodel where username = 'sayan'",
+    "select username, email from myspace.mymodel where username in ('sayan', 'robot')",
+    "select all * from myspace.mymodel where age between 10 and 20 and city != 'paris' limit 100",
+    "explain select all * from myspace.mymodel where username >= 'e' limit 10",
+    "update myspace.mymodel set followers += 1000, tags += 'rust' where username = 'sayan' returning followers",
+    "update myspace.mymodel set followers = 0 limit 2",
+    "delete from myspace.mymodel where email matches '@spam[.]net$' limit 10",
+    "delete from myspace.mymodel where username = 'sayan' returning age",
+    "sysctl create user sayan with { password: 'mypass123' }",
+    "sysctl report status",
+];
+
+/// Extra tokens that the mutator can splice into a token stream
+const VOCABULARY: &str = "( ) { } [ ] , : ; . = += -= != < > <= >= ? null true false 0 -1 18446744073709551615 \
+    'str' all primary if not exists allow empty with where and in between limit returning from into set on";
+
+fn fuzz_byte(rng: &mut impl Rng) -> u8 {
+    const INTERESTING: &[u8] = b"(){}[],:;.=?'\"\\ -+0123456789\0\x01\xFF";
+    if test_utils::random_bool(rng) {
+        INTERESTING[test_utils::random_number(0, INTERESTING.len(), rng)]
+    } else {
+        rng.gen()
+    }
+}
+
+#[test]
+fn fuzz_lexers() {
+    let mut rng = test_utils::rng();
+    for query in CORPUS {
+        for _ in 0..FUZZ_ROUNDS {
+            let mutated = test_utils::mutate(query.as_bytes(), &mut rng, fuzz_byte);
+            if let Ok(tokens) = InsecureLexer::lex(&mutated) {
+                let _ = parse_like_exec(&tokens);
+            }
+            // split into a query and its params at a random point
+            let split = test_utils::random_number(0, mutated.len() + 1, &mut rng);
+            let (q, p) = mutated.split_at(split);
+            if let Ok(tokens) = SecureLexer::new_with_segments(q, p).lex() {
+                let _ = parse_like_exec(&tokens);
+            };
+        }
+    }
+}
+
+#[test]
+fn fuzz_parsers() {
+    let mut rng = test_utils::rng();
+    let vocabulary = lex_insecure(VOCABULARY.as_bytes()).unwrap();
+    let corpus: Vec<Vec<Token>> = CORPUS
+        .iter()
+        .map(|query| lex_insecure(query.as_bytes()).unwrap())
+        .collect();
+    // every query in the corpus must be valid, otherwise we'd be fuzzing the error paths only
+    for (query, tokens) in CORPUS.iter().zip(corpus.iter()) {
+        assert!(parse_like_exec(tokens).is_ok(), "failed to parse `{query}`");
+    }
+    let all_tokens: Vec<&Token> = vocabulary.iter().chain(corpus.iter().flatten()).collect();
+    for tokens in corpus.iter() {
+        for _ in 0..FUZZ_ROUNDS * 4 {
+            let mutated = test_utils::mutate(tokens, &mut rng, |rng| {
+                all_tokens[test_utils::random_number(0, all_tokens.len(), rng)].clone()
+            });
+            let _ = parse_like_exec(&mutated);
+        }
+    }
+}
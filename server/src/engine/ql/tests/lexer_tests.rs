@@ -26,10 +26,13 @@
 
 use {
     super::{
-        super::lex::{Ident, Token},
+        super::lex::{encode_param, Ident, Token},
         lex_insecure, lex_secure,
     },
-    crate::engine::{data::lit::Lit, error::QueryError},
+    crate::engine::{
+        data::lit::Lit,
+        error::{ErrorDetail, QueryError},
+    },
 };
 
 macro_rules! v(
@@ -50,6 +53,41 @@ fn lex_ident() {
     );
 }
 
+#[test]
+fn lex_quoted_ident() {
+    // keywords and booleans are idents when quoted
+    let src = v!("select `type`, `true` from `mymodel`");
+    let expected = vec![
+        Token![select],
+        Token::Ident(Ident::from("type")),
+        Token![,],
+        Token::Ident(Ident::from("true")),
+        Token![from],
+        Token::Ident(Ident::from("mymodel")),
+    ];
+    assert_eq!(lex_insecure(&src).unwrap(), expected);
+    assert_eq!(lex_secure(&src, src.len()).unwrap(), expected);
+    // only a complete, valid ident can be quoted
+    for bad in ["`type", "``", "`1type`", "`my field`", "`type\\``"] {
+        assert_eq!(
+            lex_insecure(bad.as_bytes()).unwrap_err(),
+            QueryError::LexInvalidInput
+        );
+        assert_eq!(
+            lex_secure(bad.as_bytes(), bad.len()).unwrap_err(),
+            QueryError::LexInvalidInput
+        );
+    }
+}
+
+#[test]
+fn quote_ident() {
+    use super::super::lex::quote_ident;
+    assert_eq!(quote_ident("username"), "username");
+    assert_eq!(quote_ident("type"), "`type`");
+    assert_eq!(quote_ident("TRUE"), "`TRUE`");
+}
+
 // literals
 #[test]
 fn lex_unsigned_int() {
@@ -60,6 +98,23 @@ fn lex_unsigned_int() {
     );
 }
 #[test]
+fn lex_unsigned_int_interval_suffix() {
+    let (days, minutes) = v!("7d", "30minutes");
+    assert_eq!(
+        lex_insecure(&days).unwrap(),
+        vec![Token::Lit(Lit::new_uint(7)), Token::Ident(Ident::from("d"))]
+    );
+    assert_eq!(
+        lex_insecure(&minutes).unwrap(),
+        vec![
+            Token::Lit(Lit::new_uint(30)),
+            Token::Ident(Ident::from("minutes"))
+        ]
+    );
+    let bad = v!("7x");
+    assert_eq!(lex_insecure(&bad).unwrap_err(), QueryError::LexInvalidInput);
+}
+#[test]
 fn lex_signed_int() {
     let number = v!("-123456");
     assert_eq!(
@@ -240,6 +295,32 @@ fn safe_query_all_literals() {
     );
 }
 
+#[test]
+fn safe_query_encoded_params() {
+    let params = [
+        None,
+        Some(Lit::new_bool(true)),
+        Some(Lit::new_uint(u64::MAX)),
+        Some(Lit::new_sint(i64::MIN)),
+        Some(Lit::new_float(3.141592654)),
+        Some(Lit::new_bin(b"binary\nwithlf")),
+        Some(Lit::new_string("sayan".into())),
+    ];
+    let mut encoded = vec![];
+    for param in &params {
+        encode_param(&mut encoded, param.as_ref());
+    }
+    let (query, query_window) = make_safe_query(b"? ? ? ? ? ? ?", &encoded);
+    let ret = lex_secure(&query, query_window).unwrap();
+    assert_eq!(
+        ret,
+        params
+            .into_iter()
+            .map(|param| param.map_or(Token![null], Token::Lit))
+            .collect::<Vec<_>>()
+    );
+}
+
 const SFQ_NULL: &[u8] = b"\x00";
 const SFQ_BOOL_FALSE: &[u8] = b"\x01\0";
 const SFQ_BOOL_TRUE: &[u8] = b"\x01\x01";
@@ -356,3 +437,27 @@ fn safe_params_shuffled() {
         )
     }
 }
+
+#[test]
+fn safe_query_bad_param_detail() {
+    for (params, detail) in [
+        // the second parameter is an unsigned integer that doesn't end
+        (
+            &b"\x065\nsayan\x021234"[..],
+            [("parameter", "1"), ("received", "uint")],
+        ),
+        // the third parameter has a tag that doesn't exist
+        (b"\x01\x01\x00\x09", [("parameter", "2"), ("received", "9")]),
+    ] {
+        let (query, query_window) = make_safe_query(b"? ? ?", params);
+        let e = lex_secure(&query, query_window).unwrap_err();
+        assert_eq!(e, QueryError::LexInvalidInput);
+        let taken = ErrorDetail::take(e);
+        let entries: Vec<(&str, &str)> = taken
+            .entries()
+            .iter()
+            .map(|(k, v)| (*k, v.as_ref()))
+            .collect();
+        assert_eq!(entries, detail);
+    }
+}
@@ -25,9 +25,12 @@
 */
 
 use super::*;
-use crate::engine::ql::{
-    ast::{traits::ASTNode, State},
-    ddl::{Inspect, Use},
+use crate::engine::{
+    data::lit::Lit,
+    ql::{
+        ast::{traits::ASTNode, State},
+        ddl::{Describe, Inspect, SessionSet, SetValue, Use},
+    },
 };
 
 /*
@@ -84,6 +87,46 @@ fn use_current() {
     );
 }
 
+/*
+    set
+*/
+
+#[test]
+fn set_session() {
+    let t = lex_insecure(b"set durability = relaxed").unwrap();
+    let mut state = State::new_inplace(&t[1..]);
+    assert_eq!(
+        SessionSet::test_parse_from_state(&mut state).unwrap(),
+        SessionSet::new("durability".into(), SetValue::Name("relaxed".into()))
+    );
+    let t = lex_insecure(b"set statement_timeout = 500").unwrap();
+    let mut state = State::new_inplace(&t[1..]);
+    assert_eq!(
+        SessionSet::test_parse_from_state(&mut state).unwrap(),
+        SessionSet::new(
+            "statement_timeout".into(),
+            SetValue::Lit(Lit::new_uint(500))
+        )
+    );
+    let t = lex_insecure(b"set isolation = default").unwrap();
+    let mut state = State::new_inplace(&t[1..]);
+    assert_eq!(
+        SessionSet::test_parse_from_state(&mut state).unwrap(),
+        SessionSet::new("isolation".into(), SetValue::Default)
+    );
+    for query in [
+        "set durability",
+        "set durability =",
+        "set durability relaxed",
+        "set durability = relaxed strict",
+        "set 1 = relaxed",
+    ] {
+        let t = lex_insecure(query.as_bytes()).unwrap();
+        let mut state = State::new_inplace(&t[1..]);
+        assert!(SessionSet::test_parse_from_state(&mut state).is_err());
+    }
+}
+
 #[test]
 fn inspect_global() {
     let t = lex_insecure(b"inspect global").unwrap();
@@ -113,3 +156,16 @@ fn inspect_model() {
         Inspect::Model(("myspace", "mymodel").into())
     );
 }
+
+#[test]
+fn describe_model() {
+    let t = lex_insecure(b"describe model myspace.mymodel").unwrap();
+    let mut state = State::new_inplace(&t[1..]);
+    assert_eq!(
+        Describe::test_parse_from_state(&mut state).unwrap(),
+        Describe::Model(("myspace", "mymodel").into())
+    );
+    let t = lex_insecure(b"describe space myspace").unwrap();
+    let mut state = State::new_inplace(&t[1..]);
+    assert!(Describe::test_parse_from_state(&mut state).is_err());
+}
@@ -1094,7 +1094,11 @@ mod ddl_other_query_tests {
         super::*,
         crate::engine::ql::{
             ast::{parse_ast_node_full, parse_ast_node_full_with_space},
-            ddl::drop::{DropModel, DropSpace},
+            ddl::{
+                crt::{CreateIndex, CreateModelCopy, CreateView},
+                drop::{DropIndex, DropModel, DropSpace},
+            },
+            dml::{sel::SelectStatement, RelationalExpr, WhereClause},
             lex::Ident,
         },
     };
@@ -1150,4 +1154,243 @@ mod ddl_other_query_tests {
             DropModel::new(("apps", "mymodel").into(), true, true)
         );
     }
+    #[test]
+    fn create_index() {
+        let src = lex_insecure(br"create index myidx on mymodel(username)").unwrap();
+        assert_eq!(
+            parse_ast_node_full_with_space::<CreateIndex>(&src[2..], "apps").unwrap(),
+            CreateIndex::new(
+                Ident::from("myidx"),
+                ("apps", "mymodel").into(),
+                vec![Ident::from("username")],
+                false
+            )
+        );
+        let src =
+            lex_insecure(br"create index if not exists myidx on apps.mymodel(username)").unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateIndex>(&src[2..]).unwrap(),
+            CreateIndex::new(
+                Ident::from("myidx"),
+                ("apps", "mymodel").into(),
+                vec![Ident::from("username")],
+                true
+            )
+        );
+        let src =
+            lex_insecure(br"create index myidx on apps.mymodel(username) in background").unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateIndex>(&src[2..]).unwrap(),
+            CreateIndex::new(
+                Ident::from("myidx"),
+                ("apps", "mymodel").into(),
+                vec![Ident::from("username")],
+                false
+            )
+            .in_background()
+        );
+        let src = lex_insecure(
+            br"create index myidx on apps.mymodel(username) where status = 'active' in background",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateIndex>(&src[2..]).unwrap(),
+            CreateIndex::new(
+                Ident::from("myidx"),
+                ("apps", "mymodel").into(),
+                vec![Ident::from("username")],
+                false
+            )
+            .with_predicate(WhereClause::new(dict! {
+                Ident::from("status") => RelationalExpr::new(
+                    Ident::from("status"),
+                    Lit::new_str("active"),
+                    RelationalExpr::OP_EQ
+                )
+            }))
+            .in_background()
+        );
+        let src = lex_insecure(
+            br"create index myidx on apps.mymodel(username) include (email, age) where status = 'active'",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateIndex>(&src[2..]).unwrap(),
+            CreateIndex::new(
+                Ident::from("myidx"),
+                ("apps", "mymodel").into(),
+                vec![Ident::from("username")],
+                false
+            )
+            .with_include(vec![Ident::from("email"), Ident::from("age")])
+            .with_predicate(WhereClause::new(dict! {
+                Ident::from("status") => RelationalExpr::new(
+                    Ident::from("status"),
+                    Lit::new_str("active"),
+                    RelationalExpr::OP_EQ
+                )
+            }))
+        );
+    }
+    #[test]
+    fn create_composite_index() {
+        let src = lex_insecure(
+            br"create index myidx on apps.mymodel(tenant_id, created_at) include (name)",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateIndex>(&src[2..]).unwrap(),
+            CreateIndex::new(
+                Ident::from("myidx"),
+                ("apps", "mymodel").into(),
+                vec![Ident::from("tenant_id"), Ident::from("created_at")],
+                false
+            )
+            .with_include(vec![Ident::from("name")])
+        );
+    }
+    #[test]
+    fn create_index_with_props() {
+        let src = lex_insecure(
+            br"create index byname on apps.mymodel(name) with { collation: 'nocase' } in background",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateIndex>(&src[2..]).unwrap(),
+            CreateIndex::new(
+                Ident::from("byname"),
+                ("apps", "mymodel").into(),
+                vec![Ident::from("name")],
+                false
+            )
+            .with_props(null_dict! {
+                "collation" => Lit::new_string("nocase".into())
+            })
+            .in_background()
+        );
+    }
+    #[test]
+    fn create_index_bad() {
+        for query in [
+            &b"create index myidx on apps.mymodel"[..],
+            b"create index myidx on apps.mymodel()",
+            b"create index myidx on apps.mymodel(username",
+            b"create index myidx apps.mymodel(username)",
+            b"create index myidx on apps.mymodel(username,)",
+            b"create index myidx on apps.mymodel(username password)",
+            b"create index myidx on apps.mymodel(username) in",
+            b"create index myidx on apps.mymodel(username) in foreground",
+            b"create index myidx on apps.mymodel(username) background",
+            b"create index myidx on apps.mymodel(username) where",
+            b"create index myidx on apps.mymodel(username) where status",
+            b"create index myidx on apps.mymodel(username) include",
+            b"create index myidx on apps.mymodel(username) include ()",
+            b"create index myidx on apps.mymodel(username) include (email,)",
+            b"create index myidx on apps.mymodel(username) include (email",
+            b"create index myidx on apps.mymodel(username) include email",
+            b"create index myidx on apps.mymodel(username) with",
+            b"create index myidx on apps.mymodel(username) with { collation: }",
+        ] {
+            let src = lex_insecure(query).unwrap();
+            assert!(parse_ast_node_full::<CreateIndex>(&src[2..]).is_err());
+        }
+    }
+    #[test]
+    fn create_view() {
+        let src = lex_insecure(br"create view apps.adults as select username, age from apps.users")
+            .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateView>(&src[2..]).unwrap(),
+            CreateView::new(
+                ("apps", "adults").into(),
+                SelectStatement::new_test(
+                    ("apps", "users").into(),
+                    vec![Ident::from("username"), Ident::from("age")],
+                    false,
+                    Default::default()
+                ),
+                false
+            )
+        );
+        let src =
+            lex_insecure(br"create view if not exists apps.everyone as select * from apps.users")
+                .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateView>(&src[2..]).unwrap(),
+            CreateView::new(
+                ("apps", "everyone").into(),
+                SelectStatement::new_test(
+                    ("apps", "users").into(),
+                    vec![],
+                    true,
+                    Default::default()
+                ),
+                true
+            )
+        );
+    }
+    #[test]
+    fn create_view_bad() {
+        for query in [
+            &b"create view apps.adults as"[..],
+            b"create view apps.adults select * from apps.users",
+            b"create view apps.adults as * from apps.users",
+            b"create view as select * from apps.users",
+        ] {
+            let src = lex_insecure(query).unwrap();
+            assert!(parse_ast_node_full::<CreateView>(&src[2..]).is_err());
+        }
+    }
+    #[test]
+    fn create_model_copy() {
+        let src = lex_insecure(br"create model apps.staging as copy of apps.users").unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateModelCopy>(&src[2..]).unwrap(),
+            CreateModelCopy::new(
+                ("apps", "staging").into(),
+                ("apps", "users").into(),
+                false,
+                false
+            )
+        );
+        let src =
+            lex_insecure(br"create model if not exists test.users as copy of apps.users with data")
+                .unwrap();
+        assert_eq!(
+            parse_ast_node_full::<CreateModelCopy>(&src[2..]).unwrap(),
+            CreateModelCopy::new(
+                ("test", "users").into(),
+                ("apps", "users").into(),
+                true,
+                true
+            )
+        );
+    }
+    #[test]
+    fn create_model_copy_bad() {
+        for query in [
+            &b"create model apps.staging as copy of"[..],
+            b"create model apps.staging as copy apps.users",
+            b"create model apps.staging copy of apps.users",
+            b"create model apps.staging as copy of apps.users with",
+            b"create model apps.staging as copy of apps.users with rows",
+            b"create model apps.staging as copy of apps.users with data data",
+        ] {
+            let src = lex_insecure(query).unwrap();
+            assert!(parse_ast_node_full::<CreateModelCopy>(&src[2..]).is_err());
+        }
+    }
+    #[test]
+    fn drop_index() {
+        let src = lex_insecure(br"drop index myidx on mymodel").unwrap();
+        assert_eq!(
+            parse_ast_node_full_with_space::<DropIndex>(&src[2..], "apps").unwrap(),
+            DropIndex::new(Ident::from("myidx"), ("apps", "mymodel").into(), false)
+        );
+        let src = lex_insecure(br"drop index if exists myidx on apps.mymodel").unwrap();
+        assert_eq!(
+            parse_ast_node_full::<DropIndex>(&src[2..]).unwrap(),
+            DropIndex::new(Ident::from("myidx"), ("apps", "mymodel").into(), true)
+        );
+    }
 }
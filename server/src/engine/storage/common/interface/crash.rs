@@ -0,0 +1,262 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! # Crash simulation
+//!
+//! A [`Recorder`] traces every write, truncation and sync on a (virtual) file while a workload runs, along with the
+//! states that the workload has committed. [`CrashTest::run`] then rebuilds the file as it could be found on disk
+//! after a crash at every one of these boundaries (and in the middle of every write), runs recovery on it and
+//! validates that:
+//! - recovery never panics
+//! - a recovered state was actually committed, and is not older than the last state that was synced before the crash
+//! - a crash right after a clean shutdown (or any crash that leaves the file exactly as it was after a clean shutdown)
+//! always recovers to the state at that shutdown
+//!
+//! Every crash image is built twice: once assuming that every operation before the crash made it to disk (including
+//! a torn write at the crash point), and once assuming that everything written after the last sync was lost.
+//!
+
+use {
+    super::{
+        fs::{File, FileSystem, FileWrite},
+        vfs::{VFileOp, VirtualFS},
+    },
+    crate::engine::RuntimeResult,
+    std::{
+        fmt,
+        panic::{self, AssertUnwindSafe},
+    },
+};
+
+/// Records a workload on a traced file
+pub struct Recorder<S> {
+    path: Box<str>,
+    history: Vec<(usize, S)>,
+    shutdowns: Vec<usize>,
+}
+
+impl<S> Recorder<S> {
+    /// Start tracing the file at this path (the file must not exist yet)
+    pub fn new(path: &str) -> Self {
+        VirtualFS::instance().write().fs_trace_file(path);
+        Self {
+            path: path.into(),
+            history: vec![],
+            shutdowns: vec![],
+        }
+    }
+    /// The workload committed this state (the commit has returned)
+    pub fn commit(&mut self, state: S) {
+        let position = VirtualFS::instance()
+            .read()
+            .fs_trace_len(&self.path)
+            .unwrap();
+        self.history.push((position, state));
+    }
+    /// The workload shut down cleanly with this state
+    pub fn shutdown(&mut self, state: S) {
+        self.commit(state);
+        self.shutdowns.push(self.history.len() - 1);
+    }
+    /// Stop tracing and prepare the crash test
+    pub fn finish(self) -> CrashTest<S> {
+        assert!(!self.history.is_empty(), "nothing was committed");
+        let ops = VirtualFS::instance()
+            .write()
+            .fs_take_trace(&self.path)
+            .unwrap();
+        CrashTest {
+            path: self.path,
+            ops,
+            history: self.history,
+            shutdowns: self.shutdowns,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CrashPoint {
+    /// number of operations that completed before the crash
+    completed: usize,
+    /// number of bytes of the next write that made it to disk
+    torn: usize,
+    /// if set, everything after the last sync was lost
+    lost_unsynced: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct CrashReport {
+    /// number of simulated crashes
+    pub crashes: usize,
+    /// number of crashes that recovery succeeded for
+    pub recovered: usize,
+}
+
+/// A recorded workload, ready to be crashed
+pub struct CrashTest<S> {
+    path: Box<str>,
+    ops: Vec<VFileOp>,
+    history: Vec<(usize, S)>,
+    shutdowns: Vec<usize>,
+}
+
+impl<S: PartialEq + fmt::Debug> CrashTest<S> {
+    /// Crash the workload at every boundary and run `recover` on each crash image (the path to the image is passed
+    /// to `recover`), panicking if any of the invariants fail
+    pub fn run(&self, recover: impl Fn(&str) -> RuntimeResult<S>) -> CrashReport {
+        let clean_images: Vec<_> = self
+            .shutdowns
+            .iter()
+            .map(|&state| (self.replay(self.history[state].0), &self.history[state].1))
+            .collect();
+        let path = format!("{}.crash", self.path);
+        let mut report = CrashReport::default();
+        for crash in self.crash_points() {
+            let image = self.image(crash);
+            let mut f = File::create(&path).unwrap();
+            f.fwrite_all(&image).unwrap();
+            drop(f);
+            let restored = panic::catch_unwind(AssertUnwindSafe(|| recover(&path)));
+            FileSystem::remove_file(&path).unwrap();
+            let restored =
+                restored.unwrap_or_else(|_| panic!("recovery panicked after a crash at {crash:?}"));
+            let clean_state = self
+                .shutdowns
+                .iter()
+                .map(|&state| &self.history[state])
+                .find(|(position, _)| (*position == crash.completed) & (crash.torn == 0))
+                .map(|(_, state)| state)
+                .or_else(|| {
+                    clean_images
+                        .iter()
+                        .find(|(clean_image, _)| *clean_image == image)
+                        .map(|(_, state)| *state)
+                });
+            report.crashes += 1;
+            match restored {
+                Ok(state) => {
+                    report.recovered += 1;
+                    let (oldest, newest) = self.bounds(crash);
+                    assert!(
+                        self.history[oldest..=newest]
+                            .iter()
+                            .any(|(_, committed)| *committed == state),
+                        "recovered {state:?} after a crash at {crash:?}, but it was never committed or is older than the last synced state",
+                    );
+                    if let Some(clean_state) = clean_state {
+                        assert_eq!(clean_state, &state, "after a crash at {crash:?}");
+                    }
+                }
+                Err(e) => assert!(
+                    clean_state.is_none(),
+                    "failed to recover a cleanly shut down file after a crash at {crash:?}: {e}"
+                ),
+            }
+        }
+        report
+    }
+}
+
+impl<S> CrashTest<S> {
+    fn crash_points(&self) -> Vec<CrashPoint> {
+        let mut points = vec![];
+        for completed in 0..=self.ops.len() {
+            points.push(CrashPoint {
+                completed,
+                torn: 0,
+                lost_unsynced: false,
+            });
+            if self.synced(completed) != completed {
+                points.push(CrashPoint {
+                    completed,
+                    torn: 0,
+                    lost_unsynced: true,
+                });
+            }
+            if let Some(VFileOp::Write { data, .. }) = self.ops.get(completed) {
+                points.extend((1..data.len()).map(|torn| CrashPoint {
+                    completed,
+                    torn,
+                    lost_unsynced: false,
+                }));
+            }
+        }
+        points
+    }
+    /// Returns the number of operations that are durable if the first `completed` operations completed
+    fn synced(&self, completed: usize) -> usize {
+        self.ops[..completed]
+            .iter()
+            .rposition(|op| *op == VFileOp::Sync)
+            .map_or(0, |sync| sync + 1)
+    }
+    /// Returns the range of states (in history) that recovery may restore after this crash
+    fn bounds(&self, crash: CrashPoint) -> (usize, usize) {
+        let synced = self.synced(crash.completed);
+        let in_flight = crash.completed + (crash.torn != 0) as usize;
+        let oldest = self
+            .history
+            .iter()
+            .rposition(|(position, _)| *position <= synced)
+            .unwrap_or(0);
+        let newest = self
+            .history
+            .iter()
+            .position(|(position, _)| *position >= in_flight)
+            .unwrap_or(self.history.len() - 1);
+        (oldest, newest)
+    }
+    /// Build the file as it would be found on disk after this crash
+    fn image(&self, crash: CrashPoint) -> Vec<u8> {
+        if crash.lost_unsynced {
+            return self.replay(self.synced(crash.completed));
+        }
+        let mut data = self.replay(crash.completed);
+        if let Some(VFileOp::Write { pos, data: write }) = self.ops.get(crash.completed) {
+            apply(&mut data, *pos, &write[..crash.torn]);
+        }
+        data
+    }
+    /// Replay the first `completed` operations
+    fn replay(&self, completed: usize) -> Vec<u8> {
+        let mut data = vec![];
+        for op in &self.ops[..completed] {
+            match op {
+                VFileOp::Write { pos, data: write } => apply(&mut data, *pos, write),
+                VFileOp::Truncate(len) => data.resize(*len, 0),
+                VFileOp::Sync => {}
+            }
+        }
+        data
+    }
+}
+
+fn apply(data: &mut Vec<u8>, pos: usize, write: &[u8]) {
+    if pos + write.len() > data.len() {
+        data.resize(pos + write.len(), 0);
+    }
+    data[pos..pos + write.len()].copy_from_slice(write);
+}
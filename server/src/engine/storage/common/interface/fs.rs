@@ -37,6 +37,7 @@ use {
     std::{
         fs as std_fs,
         io::{BufReader, BufWriter, Error, ErrorKind, Read, Seek, SeekFrom, Write},
+        path::Path,
     },
 };
 
@@ -123,6 +124,44 @@ impl FileSystem {
         std_fs::remove_file(path)
     }
     #[inline(always)]
+    pub fn file_len(path: &str) -> IoResult<u64> {
+        #[cfg(test)]
+        {
+            match Self::context() {
+                FSContext::Local => {}
+                FSContext::Virtual => return VirtualFS::instance().read().fs_file_len(path),
+            }
+        }
+        std_fs::metadata(path).map(|md| md.len())
+    }
+    /// Returns every file in the directory (and its subdirectories) along with its size. Paths are relative to the
+    /// given directory
+    pub fn list_files(path: &str) -> IoResult<Vec<(String, u64)>> {
+        #[cfg(test)]
+        {
+            match Self::context() {
+                FSContext::Local => {}
+                FSContext::Virtual => return VirtualFS::instance().read().fs_list_files(path),
+            }
+        }
+        fn list(dir: &Path, prefix: &str, files: &mut Vec<(String, u64)>) -> IoResult<()> {
+            for entry in std_fs::read_dir(dir)? {
+                let entry = entry?;
+                let name = format!("{prefix}{}", entry.file_name().to_string_lossy());
+                let md = entry.metadata()?;
+                if md.is_dir() {
+                    list(&entry.path(), &format!("{name}/"), files)?;
+                } else {
+                    files.push((name, md.len()));
+                }
+            }
+            Ok(())
+        }
+        let mut files = vec![];
+        list(Path::new(path), "", &mut files)?;
+        Ok(files)
+    }
+    #[inline(always)]
     pub fn rename(from: &str, to: &str) -> IoResult<()> {
         #[cfg(test)]
         {
@@ -299,9 +338,7 @@ impl<Lf: FileWrite> FileWrite for AnyFile<Lf> {
     fn fwrite(&mut self, buf: &[u8]) -> IoResult<u64> {
         match self {
             Self::Local(lf) => lf.fwrite(buf),
-            Self::Virtual(vf) => VirtualFS::instance()
-                .read()
-                .with_file_mut(&vf.0, |f| f.fwrite(buf)),
+            Self::Virtual(vf) => vf.fwrite(buf),
         }
     }
 }
@@ -311,9 +348,7 @@ impl<Lf: FileRead> FileRead for AnyFile<Lf> {
     fn fread_exact(&mut self, buf: &mut [u8]) -> IoResult<()> {
         match self {
             Self::Local(lf) => lf.fread_exact(buf),
-            Self::Virtual(vf) => VirtualFS::instance()
-                .read()
-                .with_file_mut(&vf.0, |f| f.fread_exact(buf)),
+            Self::Virtual(vf) => vf.fread_exact(buf),
         }
     }
 }
@@ -323,21 +358,19 @@ impl<Lf: FileWriteExt> FileWriteExt for AnyFile<Lf> {
     fn fsync_all(&mut self) -> IoResult<()> {
         match self {
             Self::Local(lf) => lf.fsync_all(),
-            Self::Virtual(_) => Ok(()),
+            Self::Virtual(vf) => vf.fsync(),
         }
     }
     fn fsync_data(&mut self) -> IoResult<()> {
         match self {
             Self::Local(lf) => lf.fsync_data(),
-            Self::Virtual(_) => Ok(()),
+            Self::Virtual(vf) => vf.fsync(),
         }
     }
     fn f_truncate(&mut self, new_size: u64) -> IoResult<()> {
         match self {
             Self::Local(lf) => lf.f_truncate(new_size),
-            Self::Virtual(vf) => VirtualFS::instance()
-                .read()
-                .with_file_mut(&vf.0, |f| f.truncate(new_size)),
+            Self::Virtual(vf) => vf.truncate(new_size),
         }
     }
 }
@@ -347,25 +380,19 @@ impl<Lf: FileExt> FileExt for AnyFile<Lf> {
     fn f_len(&self) -> IoResult<u64> {
         match self {
             Self::Local(lf) => lf.f_len(),
-            Self::Virtual(vf) => VirtualFS::instance()
-                .read()
-                .with_file(&vf.0, |f| f.length()),
+            Self::Virtual(vf) => vf.length(),
         }
     }
     fn f_cursor(&mut self) -> IoResult<u64> {
         match self {
             Self::Local(lf) => lf.f_cursor(),
-            Self::Virtual(vf) => VirtualFS::instance()
-                .read()
-                .with_file(&vf.0, |f| f.cursor()),
+            Self::Virtual(vf) => vf.cursor(),
         }
     }
     fn f_seek_start(&mut self, offset: u64) -> IoResult<()> {
         match self {
             Self::Local(lf) => lf.f_seek_start(offset),
-            Self::Virtual(vf) => VirtualFS::instance()
-                .read()
-                .with_file_mut(&vf.0, |f| f.seek_from_start(offset)),
+            Self::Virtual(vf) => vf.seek_from_start(offset),
         }
     }
 }
@@ -413,6 +440,30 @@ impl File {
             f: file,
         })
     }
+    /// Open an existing file for reads only (so that it can be read on a read-only volume)
+    pub fn open_read_only(path: &str) -> IoResult<Self> {
+        #[cfg(test)]
+        {
+            match FileSystem::context() {
+                FSContext::Local => {}
+                FSContext::Virtual => {
+                    return VirtualFS::instance()
+                        .write()
+                        .fs_fopen_ro(path)
+                        .map(|f| Self {
+                            f: AnyFile::Virtual(f),
+                        })
+                }
+            }
+        }
+        let file = std_fs::File::open(path)?;
+        Ok(Self {
+            #[cfg(test)]
+            f: AnyFile::Local(file),
+            #[cfg(not(test))]
+            f: file,
+        })
+    }
     pub fn create(path: &str) -> IoResult<Self> {
         #[cfg(test)]
         {
@@ -30,6 +30,8 @@
 //! traits that provide an unified API for all file systems irrespective of their base impl
 //!
 
+#[cfg(test)]
+pub mod crash;
 pub mod fs;
 #[cfg(test)]
 mod vfs;
@@ -30,7 +30,7 @@ use {
     std::{
         collections::{
             hash_map::{Entry, OccupiedEntry},
-            HashMap,
+            HashMap, HashSet,
         },
         io::{Error, ErrorKind},
     },
@@ -54,6 +54,7 @@ type ComponentIter<'a> = std::iter::Take<std::vec::IntoIter<&'a str>>;
 
 pub struct VirtualFS {
     root: HashMap<Box<str>, VNode>,
+    traced: HashSet<Box<str>>,
 }
 
 #[derive(Debug)]
@@ -64,67 +65,80 @@ enum VNode {
 
 #[derive(Debug)]
 pub(super) struct VFile {
-    read: bool,
-    write: bool,
     data: Vec<u8>,
-    pos: usize,
+    trace: Option<Vec<VFileOp>>,
 }
 
-#[derive(Debug, PartialEq)]
-/// Result of opening a file
-/// - Created: newly created file
-/// - Existing: existing file that was reopened
-pub enum FileOpen<CF, EF = CF> {
-    /// new file
-    Created(CF),
-    /// existing file
-    Existing(EF),
+#[derive(Debug, PartialEq, Clone)]
+/// An operation on a traced file (used to simulate crashes)
+pub enum VFileOp {
+    /// some data was written at the given offset
+    Write { pos: usize, data: Box<[u8]> },
+    /// the file was truncated (or extended) to the given length
+    Truncate(usize),
+    /// the file was synced, so all previous operations are durable
+    Sync,
 }
 
 #[derive(Debug)]
-pub struct VFileDescriptor(pub(super) Box<str>);
-
-impl Drop for VFileDescriptor {
-    fn drop(&mut self) {
-        VirtualFS::instance()
-            .write()
-            .with_file_mut(&self.0, |f| {
-                f.pos = 0;
-                f.write = false;
-                f.read = false;
-                Ok(())
-            })
-            .unwrap()
-    }
+/// A handle to a virtual file. Like a real descriptor, every handle has its own cursor and
+/// permissions so that the same file can be opened more than once
+pub struct VFileDescriptor {
+    path: Box<str>,
+    read: bool,
+    write: bool,
+    pos: usize,
 }
 
 /*
     impl
 */
 
-impl VFile {
+impl VFileDescriptor {
+    fn new(path: &str, read: bool, write: bool) -> Self {
+        Self {
+            path: path.into(),
+            read,
+            write,
+            pos: 0,
+        }
+    }
+    fn with_file_mut<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self, &mut VFile) -> IoResult<T>,
+    ) -> IoResult<T> {
+        let path = self.path.clone();
+        VirtualFS::instance()
+            .read()
+            .with_file_mut(&path, |file| f(self, file))
+    }
     pub fn truncate(&mut self, to: u64) -> IoResult<()> {
         if !self.write {
             return Err(Error::new(ErrorKind::PermissionDenied, "Write permission denied").into());
         }
-        if to as usize > self.data.len() {
-            self.data.resize(to as usize, 0);
-        } else {
-            self.data.truncate(to as usize);
-        }
-        if self.pos > self.data.len() {
-            self.pos = self.data.len();
-        }
-        Ok(())
+        self.with_file_mut(|this, file| {
+            if to as usize > file.data.len() {
+                file.data.resize(to as usize, 0);
+            } else {
+                file.data.truncate(to as usize);
+            }
+            if this.pos > file.data.len() {
+                this.pos = file.data.len();
+            }
+            file.record(|| VFileOp::Truncate(to as usize));
+            Ok(())
+        })
     }
     pub fn length(&self) -> IoResult<u64> {
-        Ok(self.data.len() as u64)
+        VirtualFS::instance()
+            .read()
+            .with_file(&self.path, |f| Ok(f.data.len() as u64))
     }
     pub fn cursor(&self) -> IoResult<u64> {
         Ok(self.pos as u64)
     }
     pub fn seek_from_start(&mut self, by: u64) -> IoResult<()> {
-        if by > self.data.len() as u64 {
+        if by > self.length()? {
             return Err(Error::new(ErrorKind::InvalidInput, "Can't seek beyond file's end").into());
         }
         self.pos = by as usize;
@@ -134,38 +148,53 @@ impl VFile {
         if !self.read {
             return Err(Error::new(ErrorKind::PermissionDenied, "Read permission denied").into());
         }
-        let available_bytes = self.current().len();
-        if available_bytes < buf.len() {
-            return Err(Error::from(ErrorKind::UnexpectedEof).into());
-        }
-        buf.copy_from_slice(&self.data[self.pos..self.pos + buf.len()]);
-        self.pos += buf.len();
-        Ok(())
+        self.with_file_mut(|this, file| {
+            let available_bytes = file.data.len().saturating_sub(this.pos);
+            if available_bytes < buf.len() {
+                return Err(Error::from(ErrorKind::UnexpectedEof).into());
+            }
+            buf.copy_from_slice(&file.data[this.pos..this.pos + buf.len()]);
+            this.pos += buf.len();
+            Ok(())
+        })
     }
     pub fn fwrite(&mut self, bytes: &[u8]) -> IoResult<u64> {
         if !self.write {
             return Err(Error::new(ErrorKind::PermissionDenied, "Write permission denied").into());
         }
-        if self.pos + bytes.len() > self.data.len() {
-            self.data.resize(self.pos + bytes.len(), 0);
-        }
-        self.data[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
-        self.pos += bytes.len();
-        Ok(bytes.len() as _)
+        self.with_file_mut(|this, file| {
+            let pos = this.pos;
+            if pos + bytes.len() > file.data.len() {
+                file.data.resize(pos + bytes.len(), 0);
+            }
+            file.data[pos..pos + bytes.len()].copy_from_slice(bytes);
+            file.record(|| VFileOp::Write {
+                pos,
+                data: bytes.into(),
+            });
+            this.pos += bytes.len();
+            Ok(bytes.len() as _)
+        })
+    }
+    pub fn fsync(&mut self) -> IoResult<()> {
+        self.with_file_mut(|_, file| {
+            file.record(|| VFileOp::Sync);
+            Ok(())
+        })
     }
 }
 
 impl VFile {
-    fn new(read: bool, write: bool, data: Vec<u8>, pos: usize) -> Self {
+    fn new(data: Vec<u8>, traced: bool) -> Self {
         Self {
-            read,
-            write,
             data,
-            pos,
+            trace: traced.then(Vec::new),
         }
     }
-    fn current(&self) -> &[u8] {
-        &self.data[self.pos..]
+    fn record(&mut self, op: impl FnOnce() -> VFileOp) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(op())
+        }
     }
 }
 
@@ -212,33 +241,29 @@ impl VirtualFS {
             }
             Entry::Vacant(v) => {
                 // no file exists, we can create this
-                v.insert(VNode::File(RwLock::new(VFile::new(true, true, vec![], 0))));
-                Ok(VFileDescriptor(fpath.into()))
+                v.insert(VNode::File(RwLock::new(VFile::new(
+                    vec![],
+                    self.traced.contains(fpath),
+                ))));
+                Ok(VFileDescriptor::new(fpath, true, true))
             }
         }
     }
     pub fn fs_fopen_rw(&mut self, fpath: &str) -> IoResult<VFileDescriptor> {
-        self.with_file_mut(fpath, |f| {
-            f.read = true;
-            f.write = true;
-            Ok(VFileDescriptor(fpath.into()))
-        })
+        self.with_file(fpath, |_| Ok(VFileDescriptor::new(fpath, true, true)))
+    }
+    pub fn fs_fopen_ro(&mut self, fpath: &str) -> IoResult<VFileDescriptor> {
+        self.with_file(fpath, |_| Ok(VFileDescriptor::new(fpath, true, false)))
     }
     pub fn fs_rename(&mut self, from: &str, to: &str) -> IoResult<()> {
         // get file data
         let data = self.with_file(from, |f| Ok(f.data.clone()))?;
         // create new file
-        let file = self.fs_fopen_or_create_rw(to)?;
-        match file {
-            FileOpen::Created(c) => self.with_file_mut(&c.0, |f| Ok(f.data = data))?,
-            FileOpen::Existing(c) => self.with_file_mut(&c.0, |f| {
-                f.data = data;
-                f.pos = 0;
-                f.read = false;
-                f.write = false;
-                Ok(())
-            })?,
-        }
+        self.fs_create_if_missing(to)?;
+        self.with_file_mut(to, |f| {
+            f.data = data;
+            Ok(())
+        })?;
         // delete old file
         self.fs_remove_file(from)
     }
@@ -282,10 +307,7 @@ impl VirtualFS {
             ahead = &ahead[1..];
             match current.get_mut(this) {
                 Some(VNode::Dir(d)) => {
-                    if ahead.is_empty() {
-                        // hmm, this was the list dir that was to be created, but it already exists
-                        return Err(Error::from(ErrorKind::AlreadyExists).into());
-                    }
+                    // like `std::fs::create_dir_all`, a directory that already exists is fine
                     return create_ahead(ahead, d);
                 }
                 Some(VNode::File(_)) => return err::file_in_dir_path(),
@@ -305,31 +327,67 @@ impl VirtualFS {
     pub fn fs_delete_dir_all(&mut self, fpath: &str) -> IoResult<()> {
         self.dir_delete(fpath, true)
     }
+    pub fn fs_file_len(&self, fpath: &str) -> IoResult<u64> {
+        self.with_file(fpath, |f| Ok(f.data.len() as u64))
+    }
+    pub fn fs_list_files(&self, fpath: &str) -> IoResult<Vec<(String, u64)>> {
+        fn list(prefix: &str, dir: &VDir, files: &mut Vec<(String, u64)>) {
+            for (name, node) in dir {
+                match node {
+                    VNode::Dir(d) => list(&format!("{prefix}{name}/"), d, files),
+                    VNode::File(f) => {
+                        files.push((format!("{prefix}{name}"), f.read().data.len() as u64))
+                    }
+                }
+            }
+        }
+        let (target_dir, components) = util::split_target_and_components(fpath);
+        let dir = match util::find_target_dir(components, &self.root)?.get(target_dir) {
+            Some(VNode::Dir(dir)) => dir,
+            Some(VNode::File(_)) => return err::file_in_dir_path(),
+            None => return err::could_not_find_item(),
+        };
+        let mut files = vec![];
+        list("", dir, &mut files);
+        Ok(files)
+    }
+    /// Trace all operations on the file at this path (the file must not exist yet)
+    pub fn fs_trace_file(&mut self, fpath: &str) {
+        assert!(self.traced.insert(fpath.into()), "file is already traced");
+    }
+    /// Returns the number of operations traced so far on the file at this path
+    pub fn fs_trace_len(&self, fpath: &str) -> IoResult<usize> {
+        self.with_file(fpath, |f| Ok(f.trace.as_ref().map(Vec::len).unwrap_or(0)))
+    }
+    /// Stop tracing the file at this path, returning all the operations that were traced
+    pub fn fs_take_trace(&mut self, fpath: &str) -> IoResult<Vec<VFileOp>> {
+        self.traced.remove(fpath);
+        self.with_file_mut(fpath, |f| Ok(f.trace.take().unwrap_or_default()))
+    }
 }
 
 impl VirtualFS {
     fn new() -> Self {
         Self {
             root: HashMap::new(),
+            traced: HashSet::new(),
         }
     }
-    fn fs_fopen_or_create_rw(&mut self, fpath: &str) -> IoResult<FileOpen<VFileDescriptor>> {
+    fn fs_create_if_missing(&mut self, fpath: &str) -> IoResult<()> {
         // components
         let (target_file, components) = util::split_target_and_components(fpath);
         let target_dir = util::find_target_dir_mut(components, &mut self.root)?;
         match target_dir.entry(target_file.into()) {
             Entry::Occupied(oe) => match oe.get() {
-                VNode::File(f) => {
-                    let mut f = f.write();
-                    f.read = true;
-                    f.write = true;
-                    Ok(FileOpen::Existing(VFileDescriptor(fpath.into())))
-                }
+                VNode::File(_) => Ok(()),
                 VNode::Dir(_) => return err::item_is_not_file(),
             },
             Entry::Vacant(v) => {
-                v.insert(VNode::File(RwLock::new(VFile::new(true, true, vec![], 0))));
-                Ok(FileOpen::Created(VFileDescriptor(fpath.into())))
+                v.insert(VNode::File(RwLock::new(VFile::new(
+                    vec![],
+                    self.traced.contains(fpath),
+                ))));
+                Ok(())
             }
         }
     }
@@ -416,7 +474,7 @@ mod err {
         Err(Error::new(ErrorKind::InvalidInput, "found file in directory path").into())
     }
     pub(super) fn dir_missing_in_path<T>() -> IoResult<T> {
-        Err(Error::new(ErrorKind::InvalidInput, "could not find directory in path").into())
+        Err(Error::new(ErrorKind::NotFound, "could not find directory in path").into())
     }
     pub(super) fn could_not_find_item<T>() -> IoResult<T> {
         Err(Error::new(ErrorKind::NotFound, "could not find item").into())
@@ -31,7 +31,166 @@ pub mod static_meta;
 pub mod versions;
 
 pub mod paths_v1 {
-    use crate::engine::data::uuid::Uuid;
+    /*
+        placement
+        ---
+        files are placed on a volume (a directory) by class: the global journal goes to the journal volume and model
+        batch files (and the copies made of them for exports) go to the batch volume. a volume that isn't configured is
+        the working directory, in which case paths are exactly what they were before volumes existed
+    */
+
+    use {
+        crate::engine::{
+            data::uuid::Uuid,
+            storage::v2::{self, impls::mdl_export::encode_str},
+        },
+        std::{
+            ptr,
+            sync::{
+                atomic::{AtomicPtr, Ordering},
+                OnceLock,
+            },
+        },
+    };
+
+    static PLACEMENT: OnceLock<Placement> = OnceLock::new();
+    /// the placement after the batch volume was switched, if it was
+    static RELOCATED: AtomicPtr<Placement> = AtomicPtr::new(ptr::null_mut());
+
+    #[derive(Debug, Default, PartialEq, Clone)]
+    /// The volume used for each class of files
+    pub struct Placement {
+        journal: Option<Box<str>>,
+        batches: Option<Box<str>>,
+    }
+
+    impl Placement {
+        pub fn new(journal: Option<&str>, batches: Option<&str>) -> Self {
+            fn volume(v: Option<&str>) -> Option<Box<str>> {
+                v.filter(|v| !v.is_empty() && *v != ".").map(Into::into)
+            }
+            Self {
+                journal: volume(journal),
+                batches: volume(batches),
+            }
+        }
+        /// Returns true if all files are in the working directory
+        pub fn is_default(&self) -> bool {
+            self.journal.is_none() & self.batches.is_none()
+        }
+        pub fn journal_volume(&self) -> &str {
+            self.journal.as_deref().unwrap_or(".")
+        }
+        pub fn batch_volume(&self) -> &str {
+            self.batches.as_deref().unwrap_or(".")
+        }
+        /// Returns the volume used for each class of files as a JSON object. Copies of batch files are always placed
+        /// next to them
+        pub fn describe(&self) -> String {
+            let mut ret = String::from("{\"journal\":");
+            encode_str(&mut ret, self.journal_volume());
+            ret.push_str(",\"batches\":");
+            encode_str(&mut ret, self.batch_volume());
+            ret.push_str(",\"snapshots\":");
+            encode_str(&mut ret, self.batch_volume());
+            ret.push('}');
+            ret
+        }
+    }
+
+    /// Set the placement for all files. Returns false if the placement was already set (or used)
+    ///
+    /// WARN: Must be called before any file is opened
+    pub fn set_placement(placement: Placement) -> bool {
+        PLACEMENT.set(placement).is_ok()
+    }
+
+    pub fn placement() -> &'static Placement {
+        let relocated = RELOCATED.load(Ordering::Acquire);
+        if !relocated.is_null() {
+            return unsafe {
+                // UNSAFE(@ohsayan): placements that are switched to are leaked, so they are never freed
+                &*relocated
+            };
+        }
+        PLACEMENT.get_or_init(Placement::default)
+    }
+
+    /// Switch the batch volume to `volume`, keeping the journal volume
+    ///
+    /// WARN: No batch file can be opened (or created) while this is called, and the files that are open must be
+    /// reopened on the new volume
+    pub fn relocate_batches(volume: &str) {
+        let placement = Placement::new(placement().journal.as_deref(), Some(volume));
+        RELOCATED.store(Box::into_raw(Box::new(placement)), Ordering::Release)
+    }
+
+    fn on_volume(volume: Option<&str>, path: String) -> String {
+        match volume {
+            Some(volume) => format!("{volume}/{path}"),
+            None => path,
+        }
+    }
+
+    /// Returns the path that the file at `path` has in a copy of the data directory at `root` (such as a snapshot).
+    /// Copies are laid out like the working directory, whatever the volumes that the files were placed on
+    pub fn in_copy(root: &str, path: &str) -> String {
+        let placement = placement();
+        let path = [placement.journal.as_deref(), placement.batches.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|volume| path.strip_prefix(volume)?.strip_prefix('/'))
+            .unwrap_or(path);
+        format!("{root}/{path}")
+    }
+
+    /// Returns the path of the global journal
+    pub fn gns_path() -> String {
+        on_volume(placement().journal.as_deref(), v2::GNS_PATH.into())
+    }
+    /// Returns the path of the jobs file (see [`v2::impls::jobs_file`])
+    pub fn jobs_path() -> String {
+        on_volume(placement().journal.as_deref(), v2::JOBS_PATH.into())
+    }
+    /// Returns the path of the warmup file (see [`v2::impls::warmup_file`])
+    pub fn warmup_path() -> String {
+        on_volume(placement().journal.as_deref(), v2::WARMUP_PATH.into())
+    }
+    /// Returns the path of the marker that is left on a batch volume once its files were moved off of it (see
+    /// [`v2::impls::relocate`])
+    pub fn relocation_marker(volume: &str) -> String {
+        format!("{volume}/{}", v2::RELOCATION_MARKER)
+    }
+    /// Returns the directory that holds all spaces
+    pub fn data_dir() -> String {
+        on_volume(placement().batches.as_deref(), v2::DATA_DIR.into())
+    }
+    /// Returns the directory that files moved out of the data directory by a repair are placed in
+    pub fn quarantine_dir() -> String {
+        on_volume(placement().batches.as_deref(), "quarantine".into())
+    }
+    /// Returns the directory that snapshots are placed in (see [`v2::impls::snapshot_file`])
+    pub fn snapshots_dir() -> String {
+        on_volume(placement().batches.as_deref(), v2::SNAPSHOTS_DIR.into())
+    }
+    pub fn snapshot_path(name: &str) -> String {
+        format!("{}/{name}", self::snapshots_dir())
+    }
+    /// Returns the path of the manifest of the snapshot at `snapshot`
+    pub fn snapshot_manifest_path(snapshot: &str) -> String {
+        format!("{snapshot}/{}", v2::SNAPSHOT_MANIFEST)
+    }
+    /// Returns the path of the file that holds the snapshot schedules
+    pub fn snapshot_schedules_path() -> String {
+        format!("{}/{}", self::snapshots_dir(), v2::SNAPSHOT_SCHEDULES)
+    }
+    /// Returns the directory that rows moved out of memory are placed in (see [`crate::engine::storage::overflow`])
+    pub fn overflow_dir() -> String {
+        on_volume(placement().batches.as_deref(), "overflow".into())
+    }
+    pub fn overflow_path(model_uuid: Uuid) -> String {
+        format!("{}/{model_uuid}.db", self::overflow_dir())
+    }
     pub fn model_path(
         space_name: &str,
         space_uuid: Uuid,
@@ -43,15 +202,35 @@ pub mod paths_v1 {
             self::model_dir(space_name, space_uuid, model_name, model_uuid)
         )
     }
+    /// Returns the path of the batch file of the given partition. The first partition uses the model's batch file, so
+    /// an unpartitioned model has the same layout as before
+    pub fn model_partition_path(
+        space_name: &str,
+        space_uuid: Uuid,
+        model_name: &str,
+        model_uuid: Uuid,
+        partition: usize,
+    ) -> String {
+        match partition {
+            0 => self::model_path(space_name, space_uuid, model_name, model_uuid),
+            _ => format!(
+                "{}/data.p{partition}.db-btlog",
+                self::model_dir(space_name, space_uuid, model_name, model_uuid)
+            ),
+        }
+    }
     pub fn model_dir(
         space_name: &str,
         space_uuid: Uuid,
         model_name: &str,
         model_uuid: Uuid,
     ) -> String {
-        format!("data/{space_name}-{space_uuid}/mdl_{model_name}-{model_uuid}")
+        format!(
+            "{}/mdl_{model_name}-{model_uuid}",
+            self::space_dir(space_name, space_uuid)
+        )
     }
     pub fn space_dir(space_name: &str, space_uuid: Uuid) -> String {
-        format!("data/{space_name}-{space_uuid}")
+        format!("{}/{space_name}-{space_uuid}", self::data_dir())
     }
 }
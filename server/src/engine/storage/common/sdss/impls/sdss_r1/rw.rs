@@ -71,6 +71,15 @@ impl<S: FileSpecV1> SdssFile<S> {
         let md = S::read_metadata(&mut f, ())?;
         Ok(Self::new(f, md))
     }
+    /// Open an existing SDSS based file for reads only (with no validation arguments)
+    pub fn open_read_only(path: &str) -> RuntimeResult<Self>
+    where
+        S: FileSpecV1<DecodeArgs = ()>,
+    {
+        let mut f = File::open_read_only(path)?;
+        let md = S::read_metadata(&mut f, ())?;
+        Ok(Self::new(f, md))
+    }
     /// Create a new SDSS based file (with no initialization arguments)
     pub fn create(path: &str) -> RuntimeResult<Self>
     where
@@ -532,6 +541,26 @@ impl<
     pub fn fsync(&mut self) -> IoResult<()> {
         self.f_d.fsync_all()
     }
+    /// Continue writing to the file at `path` instead, from the same cursor. The file must be a copy of everything
+    /// that was written so far, so nothing can be left in the buffer
+    pub fn relocate(&mut self, path: &str) -> IoResult<()> {
+        if self.is_dirty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "buffer not flushed. cannot relocate",
+            ));
+        }
+        let mut f_d = File::open(path)?;
+        if f_d.f_len()? != self.t_cursor {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "relocated file is not a copy. cannot relocate",
+            ));
+        }
+        f_d.f_seek_start(self.t_cursor)?;
+        self.f_d = f_d;
+        Ok(())
+    }
 }
 
 impl<
@@ -0,0 +1,227 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    encode/decode entry points for the persist object and map specs, using a narrow (two field) and a wide (mixed
+    types, with field props) model shape. the criterion benchmarks in `benches/` drive these; see CONTRIBUTING.md for
+    how to run them
+*/
+
+use {
+    super::{dec, enc, map, obj, obj::cell::StorageCellTypeID},
+    crate::engine::{
+        core::model::{Field, FieldProps, Layer, ModelData},
+        data::{
+            cell::Datacell,
+            dict::{DictEntryGeneric, DictGeneric},
+            tag::TagSelector,
+            uuid::Uuid,
+        },
+        idx::{IndexBaseSpec, IndexSTSeqCns, STIndex},
+        mem::BufferedScanner,
+    },
+};
+
+const WIDE_MODEL_FIELDS: usize = 24;
+
+/// The model shapes that the benchmarks use
+#[derive(Debug, Clone, Copy)]
+pub enum ModelShape {
+    /// a string primary key and a binary field
+    Narrow,
+    /// an integer primary key and fields of mixed types (some with field props)
+    Wide,
+}
+
+impl ModelShape {
+    pub const ALL: [Self; 2] = [Self::Narrow, Self::Wide];
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Narrow => "narrow",
+            Self::Wide => "wide",
+        }
+    }
+    pub fn model(self) -> Model {
+        Model(self.model_data())
+    }
+    pub(in crate::engine::storage) fn model_data(self) -> ModelData {
+        match self {
+            Self::Narrow => narrow_model(),
+            Self::Wide => wide_model(),
+        }
+    }
+    /// The cells of the `i`th row of a model of this shape (primary key first, then the fields in declared order)
+    pub(in crate::engine::storage) fn row(self, i: usize) -> Vec<Datacell> {
+        match self {
+            Self::Narrow => vec![
+                Datacell::new_str(format!("user-{i}").into_boxed_str()),
+                Datacell::new_bin(format!("password-{i}").into_bytes().into_boxed_slice()),
+            ],
+            Self::Wide => {
+                let mut row = wide_row();
+                row.insert(0, Datacell::new_uint_default(i as u64));
+                row
+            }
+        }
+    }
+}
+
+/// A model (of one of the [`ModelShape`]s)
+pub struct Model(ModelData);
+
+/// A row of the wide model (without its primary key)
+pub struct Row(Vec<Datacell>);
+
+impl Row {
+    pub fn wide() -> Self {
+        Self(wide_row())
+    }
+}
+
+/// A generic dictionary, like the ones that space and model properties are stored in
+pub struct Dict(DictGeneric);
+
+impl Dict {
+    pub fn nested() -> Self {
+        Self(into_dict! {
+            "hasher" => Datacell::new_str("fxhash".into()),
+            "description" => Datacell::new_str("a model for the benchmarks".into()),
+            "limits" => DictEntryGeneric::Map(into_dict!(
+                "max_rows" => Datacell::new_uint_default(1_000_000),
+                "max_row_size" => Datacell::new_uint_default(4096),
+                "nothing" => Datacell::null(),
+            ))
+        })
+    }
+}
+
+fn narrow_model() -> ModelData {
+    ModelData::new_restore(
+        Uuid::new(),
+        "username".into(),
+        TagSelector::String.into_full(),
+        into_dict! {
+            "password" => Field::new([Layer::bin()].into(), false),
+        },
+    )
+}
+
+fn wide_model() -> ModelData {
+    let mut fields = IndexSTSeqCns::<Box<str>, Field>::idx_init();
+    for i in 0..WIDE_MODEL_FIELDS {
+        let field = match i % 4 {
+            0 => Field::new([Layer::str()].into(), i % 8 == 0),
+            1 => Field::new([Layer::uint64()].into(), false),
+            2 => Field::new([Layer::list(), Layer::sint64()].into(), true),
+            _ => Field::new_with_props(
+                [Layer::str()].into(),
+                false,
+                FieldProps::try_new(into_dict! {
+                    "maxlen" => Datacell::new_uint_default(64),
+                    "regex" => Datacell::new_str("[a-z]+".into()),
+                })
+                .unwrap(),
+            ),
+        };
+        fields.st_insert(format!("field_{i}").into_boxed_str(), field);
+    }
+    ModelData::new_restore(
+        Uuid::new(),
+        "user_id".into(),
+        TagSelector::UInt64.into_full(),
+        fields,
+    )
+}
+
+/// A row that looks like one in [`wide_model`]
+fn wide_row() -> Vec<Datacell> {
+    (0..WIDE_MODEL_FIELDS)
+        .map(|i| match i % 4 {
+            1 => Datacell::new_uint_default(i as u64 * 1000),
+            2 => Datacell::new_list(
+                (0..8)
+                    .map(|j| Datacell::new_sint_default(-(j * i as i64)))
+                    .collect(),
+            ),
+            _ => Datacell::new_str(format!("value-of-field-{i}").into_boxed_str()),
+        })
+        .collect()
+}
+
+pub fn encode_model(model: &Model) -> Vec<u8> {
+    enc::full::<obj::ModelLayoutRef>(obj::ModelLayoutRef(&model.0))
+}
+
+pub fn decode_model(encoded: &[u8]) -> impl Sized {
+    dec::full::<obj::ModelLayoutRef>(encoded).unwrap()
+}
+
+pub fn encode_decode_field_map(model: &Model) -> impl Sized {
+    let encoded = enc::full_dict::<map::FieldMapSpec<_>>(model.0.fields());
+    dec::dict_full::<map::FieldMapSpec<IndexSTSeqCns<Box<str>, Field>>>(&encoded).unwrap()
+}
+
+pub fn encode_decode_dict(dict: &Dict) -> impl Sized {
+    let encoded = enc::full_dict::<map::GenericDictSpec>(&dict.0);
+    dec::dict_full::<map::GenericDictSpec>(&encoded).unwrap()
+}
+
+/// Encode the row into `buf` (after clearing it)
+pub fn encode_row(row: &Row, buf: &mut Vec<u8>) {
+    buf.clear();
+    row.0.iter().for_each(|dc| obj::cell::encode(buf, dc));
+}
+
+/// An encoded row (which can only be made by encoding a [`Row`])
+pub struct EncodedRow(Vec<u8>);
+
+impl EncodedRow {
+    pub fn new(row: &Row) -> Self {
+        let mut buf = vec![];
+        encode_row(row, &mut buf);
+        Self(buf)
+    }
+    pub fn size(&self) -> usize {
+        self.0.len()
+    }
+}
+
+pub fn decode_row(encoded: &EncodedRow) -> impl Sized {
+    let mut scanner = BufferedScanner::new(&encoded.0);
+    let mut restored = Vec::with_capacity(WIDE_MODEL_FIELDS);
+    while !scanner.eof() {
+        let tag = scanner
+            .try_next_byte()
+            .map(StorageCellTypeID::try_from_raw)
+            .unwrap()
+            .unwrap();
+        restored.push(unsafe {
+            // UNSAFE(@ohsayan): the row was encoded by us, so the tag matches the element
+            obj::cell::decode_element::<Datacell, BufferedScanner>(&mut scanner, tag).unwrap()
+        });
+    }
+    restored
+}
@@ -0,0 +1,176 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    cell compression
+    ---
+    a byte oriented LZ77 scheme (in the spirit of LZ4) that is fast to decode. the compressed data is a sequence of:
+
+    token (1B): literal count (high nibble), match length - 4 (low nibble); 15 means that more of it follows as bytes
+    of 255 ending with a byte less than 255
+    [literal count extension]
+    literals
+    offset (2B, LE; how far back the match starts)
+    [match length extension]
+
+    the last sequence only has literals (and ends the input). decoding never trusts the input: every offset and length
+    is checked, and the output must be exactly as long as the length it was stored with
+*/
+
+/// Cells shorter than this are never compressed
+pub const COMPRESS_MIN: usize = 256;
+/// Larger cells are first compressed up to this size to check if they compress at all
+const COMPRESS_PROBE: usize = 4096;
+const MATCH_MIN: usize = 4;
+const OFFSET_MAX: usize = u16::MAX as usize;
+const HASH_LOG: u32 = 12;
+
+/// Compress `data` if it is large enough and compresses well (by at least an eighth, counting the 8 bytes that are
+/// needed to store its uncompressed length)
+pub fn try_compress(data: &[u8]) -> Option<Vec<u8>> {
+    if (data.len() < COMPRESS_MIN) | (data.len() >= u32::MAX as usize) {
+        return None;
+    }
+    if (data.len() >= COMPRESS_PROBE * 2)
+        && !compresses_well(COMPRESS_PROBE, compress(&data[..COMPRESS_PROBE]).len())
+    {
+        return None;
+    }
+    let compressed = compress(data);
+    compresses_well(data.len(), compressed.len() + sizeof!(u64)).then_some(compressed)
+}
+
+fn compresses_well(len: usize, compressed_len: usize) -> bool {
+    compressed_len <= len - len / 8
+}
+
+pub fn compress(src: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(src.len() / 2);
+    // the position of the last sequence with the hash (plus one, since zero means that there was none)
+    let mut table = vec![0u32; 1 << HASH_LOG];
+    let (mut anchor, mut i) = (0, 0);
+    while i + MATCH_MIN <= src.len() {
+        let seq = read_u32(src, i);
+        let slot = &mut table[hash(seq)];
+        let candidate = *slot as usize;
+        *slot = (i + 1) as u32;
+        if (candidate != 0) && (i + 1 - candidate <= OFFSET_MAX) {
+            let start = candidate - 1;
+            if read_u32(src, start) == seq {
+                let mut len = MATCH_MIN;
+                while (i + len < src.len()) && (src[start + len] == src[i + len]) {
+                    len += 1;
+                }
+                emit(&mut out, &src[anchor..i], Some((i - start, len)));
+                i += len;
+                anchor = i;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    emit(&mut out, &src[anchor..], None);
+    out
+}
+
+/// Decompress `src`, which must decompress to exactly `len` bytes
+pub fn decompress(src: &[u8], len: usize) -> Option<Vec<u8>> {
+    // every byte of input yields at most 255 bytes of output, so don't trust `len` for the allocation
+    let mut out = Vec::with_capacity(len.min(src.len().saturating_mul(255)));
+    let mut i = 0;
+    loop {
+        let token = *src.get(i)?;
+        i += 1;
+        let literals = read_len(src, &mut i, (token >> 4) as usize)?;
+        let literals = src.get(i..i.checked_add(literals)?)?;
+        if out.len() + literals.len() > len {
+            return None;
+        }
+        out.extend_from_slice(literals);
+        i += literals.len();
+        if i == src.len() {
+            break;
+        }
+        let offset = u16::from_le_bytes([*src.get(i)?, *src.get(i + 1)?]) as usize;
+        i += 2;
+        let match_len = read_len(src, &mut i, (token & 0x0F) as usize)? + MATCH_MIN;
+        if (offset == 0) || (offset > out.len()) || (out.len() + match_len > len) {
+            return None;
+        }
+        // the match can overlap the bytes that it produces
+        let start = out.len() - offset;
+        for j in start..start + match_len {
+            out.push(out[j]);
+        }
+    }
+    (out.len() == len).then_some(out)
+}
+
+fn emit(out: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+    let match_len = matched.map_or(0, |(_, len)| len - MATCH_MIN);
+    out.push(((literals.len().min(15) as u8) << 4) | match_len.min(15) as u8);
+    if literals.len() >= 15 {
+        write_len(out, literals.len() - 15);
+    }
+    out.extend_from_slice(literals);
+    if let Some((offset, _)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_len >= 15 {
+            write_len(out, match_len - 15);
+        }
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, mut len: usize) {
+    while len >= 255 {
+        out.push(255);
+        len -= 255;
+    }
+    out.push(len as u8);
+}
+
+fn read_len(src: &[u8], i: &mut usize, nibble: usize) -> Option<usize> {
+    let mut len = nibble;
+    if nibble == 15 {
+        loop {
+            let b = *src.get(*i)?;
+            *i += 1;
+            len = len.checked_add(b as usize)?;
+            if b != 255 {
+                break;
+            }
+        }
+    }
+    Some(len)
+}
+
+fn read_u32(src: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([src[i], src[i + 1], src[i + 2], src[i + 3]])
+}
+
+fn hash(seq: u32) -> usize {
+    (seq.wrapping_mul(2654435761) >> (32 - HASH_LOG)) as usize
+}
@@ -29,11 +29,13 @@ use {
     crate::{
         engine::{
             core::{
-                model::{Field, Model, ModelData},
+                index::Collation,
+                model::{predicate::Predicate, webhook::Webhook, Field, Model, ModelData},
+                quota::Quota,
                 space::Space,
                 EntityID, EntityIDRef, GNSData,
             },
-            data::uuid::Uuid,
+            data::{cell::Datacell, uuid::Uuid},
             error::{RuntimeResult, StorageError, TransactionError},
             fractal::FractalModelDriver,
             idx::{IndexSTSeqCns, STIndex, STIndexSeq},
@@ -41,8 +43,9 @@ use {
             storage::common_encoding::r1::{self, map, obj, PersistObject},
             txn::{
                 gns::model::{
-                    AlterModelAddTxn, AlterModelRemoveTxn, AlterModelUpdateTxn, CreateModelTxn,
-                    DropModelTxn,
+                    AlterModelAddTxn, AlterModelQuotaTxn, AlterModelRemoveTxn, AlterModelUpdateTxn,
+                    CreateIndexTxn, CreateModelTxn, CreateWebhookTxn, DropIndexTxn, DropModelTxn,
+                    DropWebhookTxn,
                 },
                 ModelIDRef,
             },
@@ -50,6 +53,7 @@ use {
         util::EndianQW,
     },
     core::marker::PhantomData,
+    std::sync::Arc,
 };
 
 pub struct ModelID<'a>(PhantomData<&'a ()>);
@@ -278,10 +282,11 @@ impl<'a> GNSEvent for CreateModelTxn<'a> {
         if space.models().contains(&model_name) {
             return Err(TransactionError::OnRestoreDataConflictAlreadyExists.into());
         }
+        let partitions = model_data.primary_index().partitions();
         if models
             .insert(
                 EntityID::new(&space_id.name, &model_name),
-                Model::new(model_data, FractalModelDriver::uninitialized()),
+                Model::new(model_data, FractalModelDriver::uninitialized(partitions)),
             )
             .is_some()
         {
@@ -607,3 +612,506 @@ impl<'a> GNSEvent for DropModelTxn<'a> {
         })
     }
 }
+
+/*
+    create index
+*/
+
+pub struct CreateIndexTxnMD {
+    model_id_md: ModelIDMD,
+    index_name_l: u64,
+    field_c: u64,
+    include_c: u64,
+    filter_l: u64,
+    collation: Collation,
+}
+#[derive(Debug, PartialEq)]
+pub struct CreateIndexTxnRestorePL {
+    pub(super) model_id: ModelIDRes,
+    pub(super) index_name: Box<str>,
+    pub(super) fields: Box<[Box<str>]>,
+    pub(super) include: Box<[Box<str>]>,
+    pub(super) filter: Option<Datacell>,
+    pub(super) collation: Collation,
+}
+
+/// Encode the names of fields as `([len: u64][name])*` (the count is in the metadata)
+fn encode_field_names(buf: &mut Vec<u8>, fields: &[Box<str>]) {
+    for field in fields {
+        buf.extend(field.len().u64_bytes_le());
+        buf.extend(field.as_bytes());
+    }
+}
+
+/// Decode `count` field names encoded with [`encode_field_names`]
+unsafe fn decode_field_names(s: &mut BufferedScanner, count: u64) -> RuntimeResult<Vec<Box<str>>> {
+    let mut fields = Vec::with_capacity(count.min(s.remaining() as u64) as usize);
+    while (fields.len() as u64 != count) & s.has_left(sizeof!(u64)) {
+        let len = s.next_u64_le() as usize;
+        if !s.has_left(len) {
+            break;
+        }
+        fields.push(r1::dec::utils::decode_string(s, len)?.into_boxed_str());
+    }
+    if fields.len() as u64 != count {
+        return Err(StorageError::InternalDecodeStructureCorruptedPayload.into());
+    }
+    Ok(fields)
+}
+
+/// Encode the predicate of a partial index as a single cell (nothing if the index isn't partial)
+fn encode_index_filter(filter: Option<&Datacell>) -> Vec<u8> {
+    let mut buf = vec![];
+    if let Some(filter) = filter {
+        obj::cell::encode(&mut buf, filter);
+    }
+    buf
+}
+
+/// Decode the predicate of a partial index, which is the single cell that makes up `block`
+unsafe fn decode_index_filter(block: &[u8]) -> RuntimeResult<Datacell> {
+    let mut s = BufferedScanner::new(block);
+    let filter = match s
+        .try_next_byte()
+        .and_then(obj::cell::StorageCellTypeID::try_from_raw)
+    {
+        Some(tag) if s.has_left(obj::cell::StorageCellTypeID::expect_atleast(tag.value_u8())) => {
+            obj::cell::decode_element::<Datacell, BufferedScanner>(&mut s, tag).ok()
+        }
+        _ => None,
+    };
+    match filter {
+        Some(filter) if s.eof() => Ok(filter),
+        _ => Err(StorageError::InternalDecodeStructureIllegalData.into()),
+    }
+}
+
+impl<'a> PersistObject for CreateIndexTxn<'a> {
+    const METADATA_SIZE: usize = <ModelID as PersistObject>::METADATA_SIZE + sizeof!(u64, 4) + 1;
+    type InputType = CreateIndexTxn<'a>;
+    type OutputType = CreateIndexTxnRestorePL;
+    type Metadata = CreateIndexTxnMD;
+    fn pretest_can_dec_object(scanner: &BufferedScanner, md: &Self::Metadata) -> bool {
+        (md.field_c != 0)
+            & scanner.has_left(
+                (md.model_id_md.space_id.space_name_l
+                    + md.model_id_md.model_name_l
+                    + md.index_name_l
+                    + md.filter_l) as usize,
+            )
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::meta_enc(buf, data.model_id());
+        buf.extend(data.index_name().len().u64_bytes_le());
+        buf.extend(data.fields().len().u64_bytes_le());
+        buf.extend(data.include().len().u64_bytes_le());
+        buf.extend(encode_index_filter(data.filter()).len().u64_bytes_le());
+        buf.push(data.collation().value_u8());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        let model_id_md = <ModelID as PersistObject>::meta_dec(scanner)?;
+        Ok(CreateIndexTxnMD {
+            model_id_md,
+            index_name_l: scanner.next_u64_le(),
+            field_c: scanner.next_u64_le(),
+            include_c: scanner.next_u64_le(),
+            filter_l: scanner.next_u64_le(),
+            collation: Collation::try_from_raw(scanner.next_byte())
+                .ok_or(StorageError::InternalDecodeStructureIllegalData)?,
+        })
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::obj_enc(buf, data.model_id());
+        buf.extend(data.index_name().as_bytes());
+        encode_field_names(buf, data.fields());
+        encode_field_names(buf, data.include());
+        buf.extend(encode_index_filter(data.filter()));
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        md: Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let model_id = <ModelID as PersistObject>::obj_dec(s, md.model_id_md)?;
+        let index_name = r1::dec::utils::decode_string(s, md.index_name_l as usize)?;
+        let fields = decode_field_names(s, md.field_c)?;
+        let include = decode_field_names(s, md.include_c)?;
+        if !s.has_left(md.filter_l as usize) {
+            return Err(StorageError::InternalDecodeStructureCorruptedPayload.into());
+        }
+        let filter = match md.filter_l {
+            0 => None,
+            filter_l => Some(decode_index_filter(
+                s.next_chunk_variable(filter_l as usize),
+            )?),
+        };
+        Ok(CreateIndexTxnRestorePL {
+            model_id,
+            index_name: index_name.into_boxed_str(),
+            fields: fields.into_boxed_slice(),
+            include: include.into_boxed_slice(),
+            filter,
+            collation: md.collation,
+        })
+    }
+}
+
+impl<'a> GNSEvent for CreateIndexTxn<'a> {
+    type CommitType = CreateIndexTxn<'a>;
+    type RestoreType = CreateIndexTxnRestorePL;
+    fn update_global_state(
+        CreateIndexTxnRestorePL {
+            model_id,
+            index_name,
+            fields,
+            include,
+            filter,
+            collation,
+        }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        let filter = match filter {
+            Some(filter) => Some(
+                Predicate::from_raw(&filter)
+                    .ok_or(TransactionError::OnRestoreDataConflictMismatch)?,
+            ),
+            None => None,
+        };
+        with_model_mut(gns, &model_id.space_id, &model_id, |model| {
+            let has_fields = fields
+                .iter()
+                .chain(include.iter())
+                .all(|field| model.fields().st_contains(field.as_ref()))
+                & filter
+                    .iter()
+                    .flat_map(Predicate::fields)
+                    .all(|field| model.fields().st_contains(field));
+            // the index is built once all the model data has been loaded
+            if has_fields
+                && model.add_secondary_index(index_name, fields, include, filter, collation)
+            {
+                Ok(())
+            } else {
+                Err(TransactionError::OnRestoreDataConflictMismatch.into())
+            }
+        })
+    }
+}
+
+/*
+    drop index
+*/
+
+pub struct DropIndexTxnMD {
+    model_id_md: ModelIDMD,
+    index_name_l: u64,
+}
+#[derive(Debug, PartialEq)]
+pub struct DropIndexTxnRestorePL {
+    pub(super) model_id: ModelIDRes,
+    pub(super) index_name: Box<str>,
+}
+
+impl<'a> PersistObject for DropIndexTxn<'a> {
+    const METADATA_SIZE: usize = <ModelID as PersistObject>::METADATA_SIZE + sizeof!(u64);
+    type InputType = DropIndexTxn<'a>;
+    type OutputType = DropIndexTxnRestorePL;
+    type Metadata = DropIndexTxnMD;
+    fn pretest_can_dec_object(scanner: &BufferedScanner, md: &Self::Metadata) -> bool {
+        scanner.has_left(
+            (md.model_id_md.space_id.space_name_l + md.model_id_md.model_name_l + md.index_name_l)
+                as usize,
+        )
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::meta_enc(buf, data.model_id());
+        buf.extend(data.index_name().len().u64_bytes_le());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        let model_id_md = <ModelID as PersistObject>::meta_dec(scanner)?;
+        Ok(DropIndexTxnMD {
+            model_id_md,
+            index_name_l: scanner.next_u64_le(),
+        })
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::obj_enc(buf, data.model_id());
+        buf.extend(data.index_name().as_bytes());
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        md: Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let model_id = <ModelID as PersistObject>::obj_dec(s, md.model_id_md)?;
+        let index_name = r1::dec::utils::decode_string(s, md.index_name_l as usize)?;
+        Ok(DropIndexTxnRestorePL {
+            model_id,
+            index_name: index_name.into_boxed_str(),
+        })
+    }
+}
+
+impl<'a> GNSEvent for DropIndexTxn<'a> {
+    type CommitType = DropIndexTxn<'a>;
+    type RestoreType = DropIndexTxnRestorePL;
+    fn update_global_state(
+        DropIndexTxnRestorePL {
+            model_id,
+            index_name,
+        }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        with_model_mut(gns, &model_id.space_id, &model_id, |model| {
+            if model.remove_secondary_index(&index_name) {
+                Ok(())
+            } else {
+                Err(TransactionError::OnRestoreDataMissing.into())
+            }
+        })
+    }
+}
+
+/*
+    create webhook
+*/
+
+pub struct CreateWebhookTxnMD {
+    model_id_md: ModelIDMD,
+    name_l: u64,
+    url_l: u64,
+    secret_l: u64,
+    batch: u64,
+}
+#[derive(Debug, PartialEq)]
+pub struct CreateWebhookTxnRestorePL {
+    pub(super) model_id: ModelIDRes,
+    pub(super) name: Box<str>,
+    pub(super) url: Box<str>,
+    pub(super) secret: Box<str>,
+    pub(super) batch: u64,
+}
+
+impl<'a> PersistObject for CreateWebhookTxn<'a> {
+    const METADATA_SIZE: usize = <ModelID as PersistObject>::METADATA_SIZE + sizeof!(u64, 4);
+    type InputType = CreateWebhookTxn<'a>;
+    type OutputType = CreateWebhookTxnRestorePL;
+    type Metadata = CreateWebhookTxnMD;
+    fn pretest_can_dec_object(scanner: &BufferedScanner, md: &Self::Metadata) -> bool {
+        scanner.has_left(
+            (md.model_id_md.space_id.space_name_l
+                + md.model_id_md.model_name_l
+                + md.name_l
+                + md.url_l
+                + md.secret_l) as usize,
+        )
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::meta_enc(buf, data.model_id());
+        buf.extend(data.name().len().u64_bytes_le());
+        buf.extend(data.url().len().u64_bytes_le());
+        buf.extend(data.secret().len().u64_bytes_le());
+        buf.extend(data.batch().to_le_bytes());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        let model_id_md = <ModelID as PersistObject>::meta_dec(scanner)?;
+        Ok(CreateWebhookTxnMD {
+            model_id_md,
+            name_l: scanner.next_u64_le(),
+            url_l: scanner.next_u64_le(),
+            secret_l: scanner.next_u64_le(),
+            batch: scanner.next_u64_le(),
+        })
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::obj_enc(buf, data.model_id());
+        buf.extend(data.name().as_bytes());
+        buf.extend(data.url().as_bytes());
+        buf.extend(data.secret().as_bytes());
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        md: Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let model_id = <ModelID as PersistObject>::obj_dec(s, md.model_id_md)?;
+        let name = r1::dec::utils::decode_string(s, md.name_l as usize)?;
+        let url = r1::dec::utils::decode_string(s, md.url_l as usize)?;
+        let secret = r1::dec::utils::decode_string(s, md.secret_l as usize)?;
+        Ok(CreateWebhookTxnRestorePL {
+            model_id,
+            name: name.into_boxed_str(),
+            url: url.into_boxed_str(),
+            secret: secret.into_boxed_str(),
+            batch: md.batch,
+        })
+    }
+}
+
+impl<'a> GNSEvent for CreateWebhookTxn<'a> {
+    type CommitType = CreateWebhookTxn<'a>;
+    type RestoreType = CreateWebhookTxnRestorePL;
+    fn update_global_state(
+        CreateWebhookTxnRestorePL {
+            model_id,
+            name,
+            url,
+            secret,
+            batch,
+        }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        with_model_mut(gns, &model_id.space_id, &model_id, |model| {
+            // the delivery cursor is loaded from disk when the webhook is first polled
+            match Webhook::new(&url, &secret, batch) {
+                Some(webhook) => {
+                    if model.add_webhook(name, Arc::new(webhook)) {
+                        Ok(())
+                    } else {
+                        Err(TransactionError::OnRestoreDataConflictMismatch.into())
+                    }
+                }
+                None => Err(TransactionError::OnRestoreDataConflictMismatch.into()),
+            }
+        })
+    }
+}
+
+/*
+    drop webhook
+*/
+
+pub struct DropWebhookTxnMD {
+    model_id_md: ModelIDMD,
+    name_l: u64,
+}
+#[derive(Debug, PartialEq)]
+pub struct DropWebhookTxnRestorePL {
+    pub(super) model_id: ModelIDRes,
+    pub(super) name: Box<str>,
+}
+
+impl<'a> PersistObject for DropWebhookTxn<'a> {
+    const METADATA_SIZE: usize = <ModelID as PersistObject>::METADATA_SIZE + sizeof!(u64);
+    type InputType = DropWebhookTxn<'a>;
+    type OutputType = DropWebhookTxnRestorePL;
+    type Metadata = DropWebhookTxnMD;
+    fn pretest_can_dec_object(scanner: &BufferedScanner, md: &Self::Metadata) -> bool {
+        scanner.has_left(
+            (md.model_id_md.space_id.space_name_l + md.model_id_md.model_name_l + md.name_l)
+                as usize,
+        )
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::meta_enc(buf, data.model_id());
+        buf.extend(data.name().len().u64_bytes_le());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        let model_id_md = <ModelID as PersistObject>::meta_dec(scanner)?;
+        Ok(DropWebhookTxnMD {
+            model_id_md,
+            name_l: scanner.next_u64_le(),
+        })
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::obj_enc(buf, data.model_id());
+        buf.extend(data.name().as_bytes());
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        md: Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let model_id = <ModelID as PersistObject>::obj_dec(s, md.model_id_md)?;
+        let name = r1::dec::utils::decode_string(s, md.name_l as usize)?;
+        Ok(DropWebhookTxnRestorePL {
+            model_id,
+            name: name.into_boxed_str(),
+        })
+    }
+}
+
+impl<'a> GNSEvent for DropWebhookTxn<'a> {
+    type CommitType = DropWebhookTxn<'a>;
+    type RestoreType = DropWebhookTxnRestorePL;
+    fn update_global_state(
+        DropWebhookTxnRestorePL { model_id, name }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        with_model_mut(gns, &model_id.space_id, &model_id, |model| {
+            if model.remove_webhook(&name) {
+                Ok(())
+            } else {
+                Err(TransactionError::OnRestoreDataMissing.into())
+            }
+        })
+    }
+}
+
+/*
+    alter model quota
+*/
+
+pub struct AlterModelQuotaTxnMD {
+    model_id_md: ModelIDMD,
+    reads: u64,
+    writes: u64,
+}
+#[derive(Debug, PartialEq)]
+pub struct AlterModelQuotaTxnRestorePL {
+    pub(super) model_id: ModelIDRes,
+    pub(super) reads: u64,
+    pub(super) writes: u64,
+}
+
+impl<'a> PersistObject for AlterModelQuotaTxn<'a> {
+    const METADATA_SIZE: usize = <ModelID as PersistObject>::METADATA_SIZE + sizeof!(u64, 2);
+    type InputType = AlterModelQuotaTxn<'a>;
+    type OutputType = AlterModelQuotaTxnRestorePL;
+    type Metadata = AlterModelQuotaTxnMD;
+    fn pretest_can_dec_object(scanner: &BufferedScanner, md: &Self::Metadata) -> bool {
+        scanner
+            .has_left((md.model_id_md.space_id.space_name_l + md.model_id_md.model_name_l) as usize)
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::meta_enc(buf, data.model_id());
+        buf.extend(data.reads().to_le_bytes());
+        buf.extend(data.writes().to_le_bytes());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        let model_id_md = <ModelID as PersistObject>::meta_dec(scanner)?;
+        Ok(AlterModelQuotaTxnMD {
+            model_id_md,
+            reads: scanner.next_u64_le(),
+            writes: scanner.next_u64_le(),
+        })
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        <ModelID as PersistObject>::obj_enc(buf, data.model_id());
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        md: Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let model_id = <ModelID as PersistObject>::obj_dec(s, md.model_id_md)?;
+        Ok(AlterModelQuotaTxnRestorePL {
+            model_id,
+            reads: md.reads,
+            writes: md.writes,
+        })
+    }
+}
+
+impl<'a> GNSEvent for AlterModelQuotaTxn<'a> {
+    type CommitType = AlterModelQuotaTxn<'a>;
+    type RestoreType = AlterModelQuotaTxnRestorePL;
+    fn update_global_state(
+        AlterModelQuotaTxnRestorePL {
+            model_id,
+            reads,
+            writes,
+        }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        with_model_mut(gns, &model_id.space_id, &model_id, |model| {
+            model.set_quota(Quota::new(reads, writes));
+            Ok(())
+        })
+    }
+}
@@ -33,7 +33,10 @@ use {
             error::{RuntimeResult, TransactionError},
             idx::STIndex,
             mem::BufferedScanner,
-            storage::common_encoding::r1::{dec, map, obj, PersistObject},
+            storage::{
+                common::paths_v1,
+                common_encoding::r1::{dec, map, obj, PersistObject},
+            },
             txn::gns::space::{AlterSpaceTxn, CreateSpaceTxn, DropSpaceTxn},
         },
         util::EndianQW,
@@ -243,6 +246,8 @@ impl<'a> GNSEvent for DropSpaceTxn<'a> {
                         };
                         let _ = wmodel.st_delete(&id);
                     }
+                    // the directory might have outlived the drop
+                    gns.note_dropped_dir(paths_v1::space_dir(oe.key(), uuid));
                     oe.remove_entry();
                     Ok(())
                 } else {
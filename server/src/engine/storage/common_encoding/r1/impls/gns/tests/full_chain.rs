@@ -331,3 +331,179 @@ fn drop_model() {
         })
     })
 }
+
+#[test]
+fn create_index() {
+    with_variable("create_index_test.global.db-tlog", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            init_space(&global, "myspace", "{}");
+            init_model(
+                &global,
+                "myspace",
+                "mymodel",
+                "username: string, city: string",
+            );
+            let stmt = lex_insecure(b"create index bycity on myspace.mymodel(city)").unwrap();
+            let stmt = parse_ast_node_full(&stmt[2..]).unwrap();
+            ModelData::transactional_exec_create_index(&global, stmt).unwrap();
+        }
+        multirun(|| {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            global
+                .state()
+                .namespace()
+                .with_model(("myspace", "mymodel").into(), |model| {
+                    let idx = model.secondary_indexes().st_get("bycity").unwrap();
+                    assert_eq!(idx.field(), "city");
+                    // the definition is restored, but the index has to be rebuilt
+                    assert!(!idx.is_ready());
+                    idx.build(model);
+                    assert!(idx.is_ready());
+                    Ok(())
+                })
+                .unwrap();
+        })
+    })
+}
+
+#[test]
+fn create_partial_index() {
+    with_variable("create_partial_index_test.global.db-tlog", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            init_space(&global, "myspace", "{}");
+            init_model(
+                &global,
+                "myspace",
+                "mymodel",
+                "username: string, city: string, status: string",
+            );
+            let stmt = lex_insecure(
+                b"create index activebycity on myspace.mymodel(city) where status = 'active'",
+            )
+            .unwrap();
+            let stmt = parse_ast_node_full(&stmt[2..]).unwrap();
+            ModelData::transactional_exec_create_index(&global, stmt).unwrap();
+        }
+        multirun(|| {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            global
+                .state()
+                .namespace()
+                .with_model(("myspace", "mymodel").into(), |model| {
+                    let idx = model.secondary_indexes().st_get("activebycity").unwrap();
+                    assert_eq!(idx.field(), "city");
+                    assert_eq!(
+                        idx.filter().unwrap().fields().collect::<Vec<_>>(),
+                        ["status"]
+                    );
+                    Ok(())
+                })
+                .unwrap();
+        })
+    })
+}
+
+#[test]
+fn create_covering_index() {
+    with_variable("create_covering_index_test.global.db-tlog", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            init_space(&global, "myspace", "{}");
+            init_model(
+                &global,
+                "myspace",
+                "mymodel",
+                "username: string, city: string, age: uint8",
+            );
+            let stmt = lex_insecure(b"create index bycity on myspace.mymodel(city) include (age)")
+                .unwrap();
+            let stmt = parse_ast_node_full(&stmt[2..]).unwrap();
+            ModelData::transactional_exec_create_index(&global, stmt).unwrap();
+        }
+        multirun(|| {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            global
+                .state()
+                .namespace()
+                .with_model(("myspace", "mymodel").into(), |model| {
+                    let idx = model.secondary_indexes().st_get("bycity").unwrap();
+                    assert_eq!(idx.field(), "city");
+                    assert_eq!(idx.include(), [Box::<str>::from("age")]);
+                    assert!(idx.filter().is_none());
+                    Ok(())
+                })
+                .unwrap();
+        })
+    })
+}
+
+#[test]
+fn create_composite_index() {
+    with_variable("create_composite_index_test.global.db-tlog", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            init_space(&global, "myspace", "{}");
+            init_model(
+                &global,
+                "myspace",
+                "mymodel",
+                "username: string, city: string, age: uint8",
+            );
+            let stmt =
+                lex_insecure(b"create index bycityage on myspace.mymodel(city, age)").unwrap();
+            let stmt = parse_ast_node_full(&stmt[2..]).unwrap();
+            ModelData::transactional_exec_create_index(&global, stmt).unwrap();
+        }
+        multirun(|| {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            global
+                .state()
+                .namespace()
+                .with_model(("myspace", "mymodel").into(), |model| {
+                    let idx = model.secondary_indexes().st_get("bycityage").unwrap();
+                    assert_eq!(
+                        idx.fields(),
+                        [Box::<str>::from("city"), Box::<str>::from("age")]
+                    );
+                    assert!(idx.include().is_empty());
+                    Ok(())
+                })
+                .unwrap();
+        })
+    })
+}
+
+#[test]
+fn drop_index() {
+    with_variable("drop_index_test.global.db-tlog", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            init_space(&global, "myspace", "{}");
+            init_model(
+                &global,
+                "myspace",
+                "mymodel",
+                "username: string, city: string",
+            );
+            let stmt = lex_insecure(b"create index bycity on myspace.mymodel(city)").unwrap();
+            let stmt = parse_ast_node_full(&stmt[2..]).unwrap();
+            ModelData::transactional_exec_create_index(&global, stmt).unwrap();
+            let stmt = lex_insecure(b"drop index bycity on myspace.mymodel").unwrap();
+            let stmt = parse_ast_node_full(&stmt[2..]).unwrap();
+            ModelData::transactional_exec_drop_index(&global, stmt).unwrap();
+        }
+        multirun(|| {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            global
+                .state()
+                .namespace()
+                .with_model(("myspace", "mymodel").into(), |model| {
+                    assert_eq!(model.secondary_indexes().st_len(), 0);
+                    Ok(())
+                })
+                .unwrap();
+        })
+    })
+}
@@ -93,16 +93,20 @@ mod model_tests {
         super::{
             model::{
                 AlterModelAddTxnRestorePL, AlterModelRemoveTxnRestorePL,
-                AlterModelUpdateTxnRestorePL, CreateModelTxnRestorePL,
+                AlterModelUpdateTxnRestorePL, CreateIndexTxnRestorePL, CreateModelTxnRestorePL,
+                CreateWebhookTxnRestorePL, DropIndexTxnRestorePL, DropWebhookTxnRestorePL,
             },
             ModelData, Space,
         },
         crate::engine::{
-            core::model::{Field, Layer},
-            data::{tag::TagSelector, uuid::Uuid},
+            core::{
+                index::Collation,
+                model::{Field, Layer},
+            },
+            data::{cell::Datacell, tag::TagSelector, uuid::Uuid},
             txn::gns::model::{
-                AlterModelAddTxn, AlterModelRemoveTxn, AlterModelUpdateTxn, CreateModelTxn,
-                DropModelTxn,
+                AlterModelAddTxn, AlterModelRemoveTxn, AlterModelUpdateTxn, CreateIndexTxn,
+                CreateModelTxn, CreateWebhookTxn, DropIndexTxn, DropModelTxn, DropWebhookTxn,
             },
         },
     };
@@ -250,4 +254,229 @@ mod model_tests {
             decoded
         );
     }
+    #[test]
+    fn create_index() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let fields: [Box<str>; 1] = ["password".into()];
+        let txn = CreateIndexTxn::new(
+            model_id,
+            "bypassword",
+            &fields,
+            &[],
+            None,
+            Collation::Binary,
+        );
+        let encoded = super::enc::full_self(txn);
+        let decoded = super::dec::full::<CreateIndexTxn>(&encoded).unwrap();
+        assert_eq!(
+            CreateIndexTxnRestorePL {
+                model_id: super::ModelIDRes::new(
+                    super::SpaceIDRes::new(space.get_uuid(), "myspace".into()),
+                    "mymodel".into(),
+                    model.get_uuid(),
+                    model.delta_state().schema_current_version().value_u64()
+                ),
+                index_name: "bypassword".into(),
+                fields: Box::new(fields),
+                include: Box::default(),
+                filter: None,
+                collation: Collation::Binary,
+            },
+            decoded
+        );
+    }
+    #[test]
+    fn create_partial_index() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let filter = Datacell::new_list(vec![
+            Datacell::new_str("profile_pic".into()),
+            Datacell::new_str("!=".into()),
+            Datacell::new_bin(b"".to_vec().into_boxed_slice()),
+        ]);
+        let fields: [Box<str>; 1] = ["password".into()];
+        let txn = CreateIndexTxn::new(
+            model_id,
+            "bypassword",
+            &fields,
+            &[],
+            Some(&filter),
+            Collation::Binary,
+        );
+        let encoded = super::enc::full_self(txn);
+        let decoded = super::dec::full::<CreateIndexTxn>(&encoded).unwrap();
+        assert_eq!(decoded.filter, Some(filter));
+        assert_eq!(&*decoded.fields, &fields);
+        // the length of the predicate is in the metadata, so a payload that is missing some of it is rejected
+        assert!(super::dec::full::<CreateIndexTxn>(&encoded[..encoded.len() - 1]).is_err());
+    }
+    #[test]
+    fn create_covering_index() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let fields: [Box<str>; 1] = ["password".into()];
+        let include: [Box<str>; 1] = ["profile_pic".into()];
+        let filter = Datacell::new_list(vec![
+            Datacell::new_str("profile_pic".into()),
+            Datacell::new_str("!=".into()),
+            Datacell::new_bin(b"".to_vec().into_boxed_slice()),
+        ]);
+        for filter in [None, Some(&filter)] {
+            let txn = CreateIndexTxn::new(
+                model_id,
+                "bypassword",
+                &fields,
+                &include,
+                filter,
+                Collation::Binary,
+            );
+            let encoded = super::enc::full_self(txn);
+            let decoded = super::dec::full::<CreateIndexTxn>(&encoded).unwrap();
+            assert_eq!(&*decoded.include, &include);
+            assert_eq!(decoded.filter.as_ref(), filter);
+        }
+    }
+    #[test]
+    fn create_composite_index() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let fields: [Box<str>; 2] = ["password".into(), "profile_pic".into()];
+        let txn = CreateIndexTxn::new(
+            model_id,
+            "bypasswordpic",
+            &fields,
+            &[],
+            None,
+            Collation::Binary,
+        );
+        let encoded = super::enc::full_self(txn);
+        let decoded = super::dec::full::<CreateIndexTxn>(&encoded).unwrap();
+        assert_eq!(&*decoded.fields, &fields);
+        assert!(decoded.include.is_empty());
+        // every field is counted in the metadata, so a payload that is missing one is rejected
+        assert!(super::dec::full::<CreateIndexTxn>(&encoded[..encoded.len() - 1]).is_err());
+    }
+    #[test]
+    fn create_collated_index() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let fields: [Box<str>; 1] = ["password".into()];
+        for collation in [Collation::NoCase, Collation::Nfc] {
+            let txn = CreateIndexTxn::new(model_id, "bypassword", &fields, &[], None, collation);
+            let encoded = super::enc::full_self(txn);
+            let decoded = super::dec::full::<CreateIndexTxn>(&encoded).unwrap();
+            assert_eq!(decoded.collation, collation);
+            assert_eq!(&*decoded.fields, &fields);
+        }
+    }
+    #[test]
+    fn drop_index() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let txn = DropIndexTxn::new(model_id, "bypassword");
+        let encoded = super::enc::full_self(txn);
+        let decoded = super::dec::full::<DropIndexTxn>(&encoded).unwrap();
+        assert_eq!(
+            DropIndexTxnRestorePL {
+                model_id: super::ModelIDRes::new(
+                    super::SpaceIDRes::new(space.get_uuid(), "myspace".into()),
+                    "mymodel".into(),
+                    model.get_uuid(),
+                    model.delta_state().schema_current_version().value_u64()
+                ),
+                index_name: "bypassword".into(),
+            },
+            decoded
+        );
+    }
+    #[test]
+    fn create_webhook() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let txn = CreateWebhookTxn::new(
+            model_id,
+            "orders",
+            "https://example.com/hooks",
+            "mysecret",
+            50,
+        );
+        let encoded = super::enc::full_self(txn);
+        let decoded = super::dec::full::<CreateWebhookTxn>(&encoded).unwrap();
+        assert_eq!(
+            CreateWebhookTxnRestorePL {
+                model_id: super::ModelIDRes::new(
+                    super::SpaceIDRes::new(space.get_uuid(), "myspace".into()),
+                    "mymodel".into(),
+                    model.get_uuid(),
+                    model.delta_state().schema_current_version().value_u64()
+                ),
+                name: "orders".into(),
+                url: "https://example.com/hooks".into(),
+                secret: "mysecret".into(),
+                batch: 50,
+            },
+            decoded
+        );
+    }
+    #[test]
+    fn drop_webhook() {
+        let (space, model) = default_space_model();
+        let model_id = super::ModelIDRef::new(
+            super::SpaceIDRef::new("myspace", &space),
+            "mymodel",
+            model.get_uuid(),
+            model.delta_state().schema_current_version().value_u64(),
+        );
+        let txn = DropWebhookTxn::new(model_id, "orders");
+        let encoded = super::enc::full_self(txn);
+        let decoded = super::dec::full::<DropWebhookTxn>(&encoded).unwrap();
+        assert_eq!(
+            DropWebhookTxnRestorePL {
+                model_id: super::ModelIDRes::new(
+                    super::SpaceIDRes::new(space.get_uuid(), "myspace".into()),
+                    "mymodel".into(),
+                    model.get_uuid(),
+                    model.delta_state().schema_current_version().value_u64()
+                ),
+                name: "orders".into(),
+            },
+            decoded
+        );
+    }
 }
@@ -84,8 +84,8 @@ impl<'a, M: MapStorageSpec> PersistObject for PersistMapImpl<'a, M> {
         MapIndexSizeMD(dict_size): Self::Metadata,
     ) -> RuntimeResult<Self::OutputType> {
         let mut dict = M::RestoredMap::map_new();
-        let decode_pretest_for_entry_meta = M::decode_pretest_for_entry_meta(scanner);
-        while decode_pretest_for_entry_meta & (dict.map_length() != dict_size) {
+        // the pretest has to run for every entry (a truncated map fails here and is rejected below)
+        while (dict.map_length() != dict_size) && M::decode_pretest_for_entry_meta(scanner) {
             let md = unsafe {
                 // UNSAFE(@ohsayan): +pretest
                 M::decode_entry_meta(scanner).ok_or::<StorageError>(
@@ -197,8 +197,11 @@ impl MapStorageSpec for GenericDictSpec {
         scanner.has_left(9)
     }
     fn decode_pretest_for_entry_data(s: &mut BufferedScanner, md: &Self::EntryMetadata) -> bool {
+        // the key is followed by the value
         StorageCellTypeID::is_valid(md.dscr)
-            & s.has_left(StorageCellTypeID::expect_atleast(md.dscr))
+            & s.remaining().checked_sub(md.klen).map_or(false, |left| {
+                left >= StorageCellTypeID::expect_atleast(md.dscr)
+            })
     }
     unsafe fn decode_entry_meta(s: &mut BufferedScanner) -> Option<Self::EntryMetadata> {
         Some(Self::EntryMetadata::decode(s.next_chunk()))
@@ -326,7 +329,7 @@ impl<FM: FieldMapAny> MapStorageSpec for FieldMapSpec<FM> {
     }
     fn encode_entry_meta(buf: &mut VecU8, key: &Self::InMemoryKey, val: &Self::InMemoryVal) {
         buf.extend(key.len().u64_bytes_le());
-        buf.extend(0u64.to_le_bytes()); // TODO(@ohsayan): props
+        buf.extend(val.props().len().u64_bytes_le());
         buf.extend(val.layers().len().u64_bytes_le());
         buf.push(val.is_nullable() as u8);
     }
@@ -337,9 +340,7 @@ impl<FM: FieldMapAny> MapStorageSpec for FieldMapSpec<FM> {
         buf.extend(key.as_bytes());
     }
     fn encode_entry_val(buf: &mut VecU8, val: &Self::InMemoryVal) {
-        for layer in val.layers() {
-            super::obj::LayerRef::default_full_enc(buf, super::obj::LayerRef(layer))
-        }
+        super::obj::FieldRef::obj_enc(buf, val)
     }
     fn decode_pretest_for_entry_meta(scanner: &mut BufferedScanner) -> bool {
         scanner.has_left(sizeof!(u64, 3) + 1)
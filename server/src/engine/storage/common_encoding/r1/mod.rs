@@ -29,6 +29,8 @@
 //! This is revision 1 of high-level interface encoding.
 //!
 
+pub mod benches;
+pub mod compress;
 pub mod impls;
 pub mod map;
 pub mod obj;
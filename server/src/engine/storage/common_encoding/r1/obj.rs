@@ -29,7 +29,7 @@ use {
     crate::{
         engine::{
             core::{
-                model::{Field, Layer, ModelData},
+                model::{Field, FieldProps, Layer, ModelData, ModelProps},
                 space::Space,
             },
             data::{
@@ -51,7 +51,7 @@ use {
 
 pub mod cell {
     use {
-        super::super::{DataSource, VecU8},
+        super::super::{compress, DataSource, VecU8},
         crate::{
             engine::data::{
                 cell::Datacell,
@@ -105,10 +105,31 @@ pub mod cell {
             [0u8, 1, 8, 8][d.min(3) as usize] as usize
         }
     }
+    /// Set in the length of a compressed [`TagClass::Str`] or [`TagClass::Bin`] cell, which is followed by the
+    /// uncompressed length and then the compressed data
+    const COMPRESSED: u64 = 1 << 63;
     pub fn encode(buf: &mut VecU8, dc: &Datacell) {
         buf.push(encode_tag(dc));
         encode_cell(buf, dc)
     }
+    /// Like [`encode`], but a large string or binary cell is compressed if it compresses well. Cells in a list are
+    /// never compressed
+    pub fn encode_compressed(buf: &mut VecU8, dc: &Datacell) {
+        if !dc.is_null() && matches!(dc.tag().tag_class(), TagClass::Str | TagClass::Bin) {
+            let slc = unsafe {
+                // UNSAFE(@ohsayan): +tagck
+                dc.read_bin()
+            };
+            if let Some(compressed) = compress::try_compress(slc) {
+                buf.push(encode_tag(dc));
+                buf.extend((compressed.len() as u64 | COMPRESSED).to_le_bytes());
+                buf.extend(slc.len().u64_bytes_le());
+                buf.extend(compressed);
+                return;
+            }
+        }
+        encode(buf, dc)
+    }
     pub fn encode_tag(dc: &Datacell) -> u8 {
         (dc.tag().tag_selector().value_u8() + 1) * (dc.is_init() as u8)
     }
@@ -208,8 +229,23 @@ pub mod cell {
                 Datacell::new_qw(nx, tag)
             }
             TagClass::Bin | TagClass::Str => {
-                let len = s.read_next_u64_le()? as usize;
-                let block = s.read_next_variable_block(len)?;
+                let len = s.read_next_u64_le()?;
+                let raw_len = match len & COMPRESSED {
+                    0 => None,
+                    _ if !s.has_remaining(sizeof!(u64)) => return Ok(EY::error()?),
+                    _ => Some(s.read_next_u64_le()? as usize),
+                };
+                let len = (len & !COMPRESSED) as usize;
+                if !s.has_remaining(len) {
+                    return Ok(EY::error()?);
+                }
+                let mut block = s.read_next_variable_block(len)?;
+                if let Some(raw_len) = raw_len {
+                    match compress::decompress(&block, raw_len) {
+                        Some(raw) => block = raw,
+                        None => return Ok(EY::error()?),
+                    }
+                }
                 if tag.tag_class() == TagClass::Str {
                     match String::from_utf8(block).map(|s| Datacell::new_str(s.into_boxed_str())) {
                         Ok(s) => s,
@@ -333,7 +369,7 @@ impl<'a> PersistObject for FieldRef<'a> {
     }
     fn meta_enc(buf: &mut VecU8, slf: Self::InputType) {
         // [prop_c][layer_c][null]
-        buf.extend(0u64.to_le_bytes());
+        buf.extend(slf.props().len().u64_bytes_le());
         buf.extend(slf.layers().len().u64_bytes_le());
         buf.push(slf.is_nullable() as u8);
     }
@@ -348,6 +384,13 @@ impl<'a> PersistObject for FieldRef<'a> {
         for layer in slf.layers() {
             LayerRef::default_full_enc(buf, LayerRef(layer));
         }
+        // [props] (only if there are any)
+        if !slf.props().is_empty() {
+            <super::map::PersistMapImpl<super::map::GenericDictSpec> as PersistObject>::obj_enc(
+                buf,
+                &slf.props().to_raw(),
+            )
+        }
     }
     unsafe fn obj_dec(
         scanner: &mut BufferedScanner,
@@ -368,8 +411,21 @@ impl<'a> PersistObject for FieldRef<'a> {
             fin = l.tag().tag_class() != TagClass::List;
             layers.push(l);
         }
-        let field = Field::new(layers, md.null == 1);
-        if (field.layers().len() as u64 == md.layer_c) & (md.null <= 1) & (md.prop_c == 0) & fin {
+        let props = if md.prop_c == 0 {
+            FieldProps::default()
+        } else {
+            let props = <super::map::PersistMapImpl<super::map::GenericDictSpec> as PersistObject>::obj_dec(
+                scanner,
+                super::map::MapIndexSizeMD(md.prop_c as usize),
+            )?;
+            FieldProps::try_new(props).ok_or(StorageError::InternalDecodeStructureIllegalData)?
+        };
+        let field = Field::new_with_props(layers, md.null == 1, props);
+        if (field.layers().len() as u64 == md.layer_c)
+            & (md.null <= 1)
+            & (field.props().len() as u64 == md.prop_c)
+            & fin
+        {
             Ok(field)
         } else {
             Err(StorageError::InternalDecodeStructureCorrupted.into())
@@ -438,7 +494,20 @@ impl<'a> PersistObject for ModelLayoutRef<'a> {
         <super::map::PersistMapImpl<super::map::FieldMapSpec<_>> as PersistObject>::obj_enc(
             buf,
             model_definition.fields(),
-        )
+        );
+        /*
+            NOTE(@ohsayan): model properties were added after the layout was frozen, so they're written as an optional
+            trailing section: [prop count: u64][props]. it is only written if the model has any properties, which means
+            that older layouts (and models without properties) decode exactly as before. this works because the layout
+            is always the last item in its payload
+        */
+        let props = model_definition.props().raw();
+        if !props.is_empty() {
+            buf.extend(props.len().u64_bytes_le());
+            <super::map::PersistMapImpl<super::map::GenericDictSpec> as PersistObject>::obj_enc(
+                buf, props,
+            )
+        }
     }
     unsafe fn obj_dec(
         scanner: &mut BufferedScanner,
@@ -455,11 +524,25 @@ impl<'a> PersistObject for ModelLayoutRef<'a> {
         } else {
             TagSelector::from_raw(md.p_key_tag as u8)
         };
-        Ok(ModelData::new_restore(
+        let props = if scanner.eof() {
+            ModelProps::default()
+        } else {
+            if !scanner.has_left(sizeof!(u64)) {
+                return Err(StorageError::InternalDecodeStructureCorrupted.into());
+            }
+            let prop_c = scanner.next_u64_le() as usize;
+            let props = <super::map::PersistMapImpl<super::map::GenericDictSpec> as PersistObject>::obj_dec(
+                scanner,
+                super::map::MapIndexSizeMD(prop_c),
+            )?;
+            ModelProps::try_new(props).ok_or(StorageError::InternalDecodeStructureIllegalData)?
+        };
+        Ok(ModelData::new_restore_with_props(
             md.model_uuid,
             key.into_boxed_str(),
             ptag.into_full(),
             fieldmap,
+            props,
         ))
     }
 }
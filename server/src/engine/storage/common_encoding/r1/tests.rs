@@ -28,7 +28,7 @@ use {
     super::obj,
     crate::engine::{
         core::{
-            model::{Field, Layer, ModelData},
+            model::{Field, FieldProps, Layer, ModelData, ModelProps},
             space::Space,
         },
         data::{
@@ -37,12 +37,14 @@ use {
             tag::{FloatSpec, SIntSpec, TagSelector, UIntSpec},
             uuid::Uuid,
         },
-        idx::{IndexBaseSpec, IndexSTSeqCns, STIndex, STIndexSeq},
+        idx::{meta::hash::HasherKind, IndexBaseSpec, IndexSTSeqCns, STIndex, STIndexSeq},
         mem::BufferedScanner,
         storage::common_encoding::r1::obj::cell::StorageCellTypeID,
     },
 };
 
+mod roundtrip;
+
 #[test]
 fn dict() {
     let dict: DictGeneric = into_dict! {
@@ -74,6 +76,47 @@ fn field() {
     assert_eq!(field, dec);
 }
 
+#[test]
+fn field_with_props() {
+    let props = FieldProps::try_new(
+        into_dict! { "computed" => Datacell::new_str("lower(username)".into()) },
+    )
+    .unwrap();
+    let field = Field::new_with_props([Layer::str()].into(), true, props);
+    let encoded = super::enc::full::<obj::FieldRef>((&field).into());
+    let dec = super::dec::full::<obj::FieldRef>(&encoded).unwrap();
+    assert_eq!(field, dec);
+    // checks
+    let props = FieldProps::try_new(into_dict! {
+        "min" => Datacell::new_sint_default(-10),
+        "max" => Datacell::new_uint_default(10),
+    })
+    .unwrap();
+    let field = Field::new_with_props([Layer::sint64()].into(), false, props);
+    let encoded = super::enc::full::<obj::FieldRef>((&field).into());
+    let dec = super::dec::full::<obj::FieldRef>(&encoded).unwrap();
+    assert_eq!(field, dec);
+    let props = FieldProps::try_new(into_dict! {
+        "maxlen" => Datacell::new_uint_default(64),
+        "regex" => Datacell::new_str("[a-z]+".into()),
+    })
+    .unwrap();
+    let field = Field::new_with_props([Layer::str()].into(), false, props);
+    let encoded = super::enc::full::<obj::FieldRef>((&field).into());
+    let dec = super::dec::full::<obj::FieldRef>(&encoded).unwrap();
+    assert_eq!(field, dec);
+    // decimals
+    let props = FieldProps::try_new(into_dict! {
+        "precision" => Datacell::new_uint_default(12),
+        "scale" => Datacell::new_uint_default(2),
+    })
+    .unwrap();
+    let field = Field::new_with_props([Layer::sint64()].into(), false, props);
+    let encoded = super::enc::full::<obj::FieldRef>((&field).into());
+    let dec = super::dec::full::<obj::FieldRef>(&encoded).unwrap();
+    assert_eq!(field, dec);
+}
+
 #[test]
 fn fieldmap() {
     let mut fields = IndexSTSeqCns::<Box<str>, Field>::idx_init();
@@ -112,6 +155,28 @@ fn model() {
     assert_eq!(model, dec);
 }
 
+#[test]
+fn model_with_props() {
+    let model = ModelData::new_restore_with_props(
+        Uuid::new(),
+        "username".into(),
+        TagSelector::String.into_full(),
+        into_dict! {
+            "password" => Field::new([Layer::bin()].into(), false),
+            "username_lc" => Field::new_with_props(
+                [Layer::str()].into(),
+                false,
+                FieldProps::try_new(into_dict! { "computed" => Datacell::new_str("lower(username)".into()) }).unwrap(),
+            ),
+        },
+        ModelProps::try_new(into_dict! { "hasher" => Datacell::new_str("fxhash".into()) }).unwrap(),
+    );
+    let enc = super::enc::full::<obj::ModelLayoutRef>(obj::ModelLayoutRef(&model));
+    let dec = super::dec::full::<obj::ModelLayoutRef>(&enc).unwrap();
+    assert_eq!(model, dec);
+    assert_eq!(dec.props().hasher(), HasherKind::Fx);
+}
+
 #[test]
 fn space() {
     let uuid = Uuid::new();
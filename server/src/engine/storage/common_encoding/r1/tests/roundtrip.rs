@@ -0,0 +1,470 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    property based round trip tests. every generated object must:
+    - decode to the same value
+    - re-encode to the same bytes (or to the same length if it contains a dict with more than one entry, since
+    dictionaries are hash ordered)
+    - reject any truncated encoding with an error (instead of panicking or reading past the buffer)
+*/
+
+use {
+    super::super::{compress, dec, enc, map, obj, obj::cell::StorageCellTypeID},
+    crate::engine::{
+        core::{
+            model::{Field, FieldProps, Layer, ModelData, ModelProps},
+            space::Space,
+        },
+        data::{
+            cell::Datacell,
+            dict::{DictEntryGeneric, DictGeneric},
+            tag::{FloatSpec, SIntSpec, TagSelector, UIntSpec},
+            uuid::Uuid,
+        },
+        error::RuntimeResult,
+        idx::{IndexBaseSpec, IndexSTSeqCns, STIndex},
+        mem::BufferedScanner,
+    },
+    proptest::{prelude::*, sample::Index},
+};
+
+const CASES: u32 = if cfg!(miri) { 4 } else { 256 };
+/// number of truncation points tested per case (in addition to the empty input and the input minus its last byte)
+const CUTS: usize = 8;
+
+/*
+    strategies
+*/
+
+fn any_uuid() -> impl Strategy<Value = Uuid> {
+    any::<[u8; 16]>().prop_map(Uuid::from_bytes)
+}
+
+fn any_ident() -> impl Strategy<Value = Box<str>> {
+    "\\PC{0,12}".prop_map(String::into_boxed_str)
+}
+
+fn any_float() -> impl Strategy<Value = f64> {
+    use proptest::num::f64::*;
+    // no NaNs, since they aren't equal to themselves
+    POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE
+}
+
+fn any_scalar() -> impl Strategy<Value = Datacell> {
+    prop_oneof![
+        Just(Datacell::null()),
+        any::<bool>().prop_map(Datacell::new_bool),
+        (0..4usize, any::<u64>()).prop_map(|(width, v)| {
+            let (selector, v) = [
+                (TagSelector::UInt8, v as u8 as u64),
+                (TagSelector::UInt16, v as u16 as u64),
+                (TagSelector::UInt32, v as u32 as u64),
+                (TagSelector::UInt64, v),
+            ][width];
+            Datacell::new_uint(v, unsafe { UIntSpec::from_full(selector.into_full()) })
+        }),
+        (0..4usize, any::<i64>()).prop_map(|(width, v)| {
+            let (selector, v) = [
+                (TagSelector::SInt8, v as i8 as i64),
+                (TagSelector::SInt16, v as i16 as i64),
+                (TagSelector::SInt32, v as i32 as i64),
+                (TagSelector::SInt64, v),
+            ][width];
+            Datacell::new_sint(v, unsafe { SIntSpec::from_full(selector.into_full()) })
+        }),
+        (any::<bool>(), any_float()).prop_map(|(single, v)| {
+            let (selector, v) = if single {
+                (TagSelector::Float32, v as f32 as f64)
+            } else {
+                (TagSelector::Float64, v)
+            };
+            Datacell::new_float(v, unsafe { FloatSpec::from_full(selector.into_full()) })
+        }),
+        prop::collection::vec(any::<u8>(), 0..32)
+            .prop_map(|bin| Datacell::new_bin(bin.into_boxed_slice())),
+        "\\PC{0,32}".prop_map(|s| Datacell::new_str(s.into_boxed_str())),
+    ]
+}
+
+fn any_datacell() -> impl Strategy<Value = Datacell> {
+    any_scalar().prop_recursive(3, 32, 4, |inner| {
+        prop::collection::vec(inner, 0..4).prop_map(Datacell::new_list)
+    })
+}
+
+fn any_dict() -> impl Strategy<Value = DictGeneric> {
+    let entry = any_datacell()
+        .prop_map(DictEntryGeneric::Data)
+        .prop_recursive(3, 32, 4, |inner| {
+            prop::collection::hash_map(any_ident(), inner, 0..4).prop_map(DictEntryGeneric::Map)
+        });
+    prop::collection::hash_map(any_ident(), entry, 0..6)
+}
+
+fn any_field_props() -> impl Strategy<Value = FieldProps> {
+    prop_oneof![
+        Just(into_dict!()),
+        any::<u64>()
+            .prop_map(|maxlen| into_dict! { "maxlen" => Datacell::new_uint_default(maxlen) }),
+        (any::<i64>(), any::<u64>()).prop_map(|(min, max)| into_dict! {
+            "min" => Datacell::new_sint_default(min),
+            "max" => Datacell::new_uint_default(max),
+        }),
+        Just(into_dict! { "regex" => Datacell::new_str("[a-z]+".into()) }),
+        Just(into_dict! { "computed" => Datacell::new_str("lower(username)".into()) }),
+    ]
+    .prop_map(|props| FieldProps::try_new(props).unwrap())
+}
+
+fn any_layers() -> impl Strategy<Value = Vec<Layer>> {
+    let scalar = prop::sample::select(vec![
+        Layer::bool(),
+        Layer::uint8(),
+        Layer::uint16(),
+        Layer::uint32(),
+        Layer::uint64(),
+        Layer::sint8(),
+        Layer::sint16(),
+        Layer::sint32(),
+        Layer::sint64(),
+        Layer::float32(),
+        Layer::float64(),
+        Layer::bin(),
+        Layer::str(),
+    ]);
+    (0..3usize, scalar).prop_map(|(depth, scalar)| {
+        let mut layers = vec![Layer::list(); depth];
+        layers.push(scalar);
+        layers
+    })
+}
+
+fn any_field() -> impl Strategy<Value = Field> {
+    (any_layers(), any::<bool>(), any_field_props()).prop_map(|(layers, null, props)| {
+        Field::new_with_props(layers.into_iter().collect(), null, props)
+    })
+}
+
+fn any_fields() -> impl Strategy<Value = Vec<(Box<str>, Field)>> {
+    prop::collection::hash_map(any_ident(), any_field(), 0..6)
+        .prop_map(|fields| fields.into_iter().collect())
+}
+
+fn any_pk_tag() -> impl Strategy<Value = TagSelector> {
+    prop::sample::select(vec![
+        TagSelector::UInt64,
+        TagSelector::SInt64,
+        TagSelector::Binary,
+        TagSelector::String,
+    ])
+}
+
+fn any_cuts() -> impl Strategy<Value = Vec<Index>> {
+    prop::collection::vec(any::<Index>(), CUTS)
+}
+
+fn any_model_props() -> impl Strategy<Value = DictGeneric> {
+    prop::sample::select(vec![
+        vec![],
+        vec![("primary_index", "btree")],
+        vec![("hasher", "fxhash")],
+        vec![("primary_index", "hash"), ("hasher", "siphash")],
+    ])
+    .prop_map(|props| {
+        props
+            .into_iter()
+            .map(|(k, v)| {
+                (
+                    k.into(),
+                    DictEntryGeneric::Data(Datacell::new_str(v.into())),
+                )
+            })
+            .collect()
+    })
+}
+
+/*
+    helpers
+*/
+
+/// Returns true if the encoding of this dict doesn't depend on the iteration order of its hash maps
+fn dict_is_ordered(dict: &DictGeneric) -> bool {
+    dict.len() <= 1
+        && dict.values().all(|entry| match entry {
+            DictEntryGeneric::Map(map) => dict_is_ordered(map),
+            DictEntryGeneric::Data(_) => true,
+        })
+}
+
+fn field_is_ordered(field: &Field) -> bool {
+    field.props().len() <= 1
+}
+
+fn new_fieldmap(fields: &[(Box<str>, Field)]) -> IndexSTSeqCns<Box<str>, Field> {
+    let mut fieldmap = IndexSTSeqCns::idx_init();
+    for (field_id, field) in fields {
+        assert!(fieldmap.st_insert(field_id.clone(), field.clone()));
+    }
+    fieldmap
+}
+
+/// The strict prefixes of `encoded` to try decoding
+fn truncation_points(encoded: &[u8], cuts: &[Index]) -> Vec<usize> {
+    let mut points = vec![0, encoded.len() - 1];
+    points.extend(cuts.iter().map(|cut| cut.index(encoded.len())));
+    points
+}
+
+/// Encode, decode and re-encode the object, then make sure that truncated encodings are rejected
+fn roundtrip<T: PartialEq + core::fmt::Debug>(
+    orig: &T,
+    ordered: bool,
+    cuts: &[Index],
+    encode: impl Fn(&T) -> Vec<u8>,
+    decode: impl Fn(&[u8]) -> RuntimeResult<T>,
+) {
+    let encoded = encode(orig);
+    let decoded = decode(&encoded).unwrap();
+    assert_eq!(orig, &decoded);
+    let reencoded = encode(&decoded);
+    if ordered {
+        assert_eq!(encoded, reencoded);
+    } else {
+        assert_eq!(encoded.len(), reencoded.len());
+    }
+    for point in truncation_points(&encoded, cuts) {
+        assert!(
+            decode(&encoded[..point]).is_err(),
+            "decoded truncated input ({point} of {} bytes)",
+            encoded.len()
+        );
+    }
+}
+
+fn cell_enc(dc: &Datacell) -> Vec<u8> {
+    let mut encoded = vec![];
+    obj::cell::encode(&mut encoded, dc);
+    encoded
+}
+
+fn cell_enc_compressed(dc: &Datacell) -> Vec<u8> {
+    let mut encoded = vec![];
+    obj::cell::encode_compressed(&mut encoded, dc);
+    encoded
+}
+
+/// Decode a cell, running the same pretests that the dict decoder does
+fn cell_dec(data: &[u8]) -> Option<Datacell> {
+    let mut scanner = BufferedScanner::new(data);
+    let tag = StorageCellTypeID::try_from_raw(scanner.try_next_byte()?)?;
+    if !scanner.has_left(StorageCellTypeID::expect_atleast(tag.value_u8())) {
+        return None;
+    }
+    let dc = unsafe {
+        // UNSAFE(@ohsayan): +pretest
+        obj::cell::decode_element::<Datacell, BufferedScanner>(&mut scanner, tag).ok()?
+    };
+    scanner.eof().then_some(dc)
+}
+
+/*
+    tests
+*/
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn roundtrip_datacell(dc in any_datacell(), cuts in any_cuts()) {
+        let encoded = cell_enc(&dc);
+        let decoded = cell_dec(&encoded).unwrap();
+        prop_assert_eq!(&dc, &decoded);
+        prop_assert_eq!(dc.tag(), decoded.tag());
+        prop_assert_eq!(&encoded, &cell_enc(&decoded));
+        for point in truncation_points(&encoded, &cuts) {
+            prop_assert!(cell_dec(&encoded[..point]).is_none());
+        }
+    }
+
+    #[test]
+    fn roundtrip_compressed_cell(
+        chunk in prop::collection::vec(any::<u8>(), 1..16),
+        repeat in 256..1024usize,
+        is_str in any::<bool>(),
+        cuts in any_cuts(),
+    ) {
+        let data = chunk.repeat(repeat);
+        let dc = if is_str {
+            Datacell::new_str(String::from_utf8_lossy(&data).into())
+        } else {
+            Datacell::new_bin(data.into_boxed_slice())
+        };
+        let encoded = cell_enc_compressed(&dc);
+        prop_assert!(encoded.len() < cell_enc(&dc).len());
+        prop_assert_eq!(&dc, &cell_dec(&encoded).unwrap());
+        for point in truncation_points(&encoded, &cuts) {
+            prop_assert!(cell_dec(&encoded[..point]).is_none());
+        }
+    }
+
+    #[test]
+    fn roundtrip_compress(data in prop::collection::vec(0..4u8, 0..8192)) {
+        let compressed = compress::compress(&data);
+        prop_assert_eq!(compress::decompress(&compressed, data.len()).unwrap(), data.clone());
+        if !data.is_empty() {
+            prop_assert!(compress::decompress(&compressed, data.len() - 1).is_none());
+        }
+        prop_assert!(compress::decompress(&compressed, data.len() + 1).is_none());
+    }
+
+    #[test]
+    fn compressed_cell_rejects_corruption(
+        chunk in prop::collection::vec(any::<u8>(), 1..16),
+        flip in any::<Index>(),
+    ) {
+        let dc = Datacell::new_bin(chunk.repeat(256).into_boxed_slice());
+        let mut encoded = cell_enc_compressed(&dc);
+        // skip the tag and the lengths so that only the compressed data is corrupted
+        let flip = 17 + flip.index(encoded.len() - 17);
+        encoded[flip] ^= 0xFF;
+        // a corrupted literal still decodes, but never to the original
+        prop_assert_ne!(Some(dc), cell_dec(&encoded));
+    }
+
+    #[test]
+    fn incompressible_cell_is_not_compressed(data in prop::collection::vec(any::<u8>(), 256..1024)) {
+        let dc = Datacell::new_bin(data.into_boxed_slice());
+        prop_assert_eq!(cell_enc_compressed(&dc), cell_enc(&dc));
+    }
+
+    #[test]
+    fn roundtrip_dict(dict in any_dict(), cuts in any_cuts()) {
+        roundtrip(
+            &dict,
+            dict_is_ordered(&dict),
+            &cuts,
+            |dict| enc::full_dict::<map::GenericDictSpec>(dict),
+            |data| dec::dict_full::<map::GenericDictSpec>(data),
+        );
+    }
+
+    #[test]
+    fn roundtrip_layer(layers in any_layers(), cuts in any_cuts()) {
+        for layer in layers {
+            roundtrip(
+                &layer,
+                true,
+                &cuts,
+                |layer| enc::full::<obj::LayerRef>(obj::LayerRef(layer)),
+                |data| dec::full::<obj::LayerRef>(data),
+            );
+        }
+    }
+
+    #[test]
+    fn roundtrip_field(field in any_field(), cuts in any_cuts()) {
+        roundtrip(
+            &field,
+            field_is_ordered(&field),
+            &cuts,
+            |field| enc::full::<obj::FieldRef>(field.into()),
+            |data| dec::full::<obj::FieldRef>(data),
+        );
+    }
+
+    #[test]
+    fn roundtrip_fieldmap(fields in any_fields(), cuts in any_cuts()) {
+        let fieldmap = new_fieldmap(&fields);
+        let ordered = fields.iter().all(|(_, field)| field_is_ordered(field));
+        let encoded = enc::full_dict::<map::FieldMapSpec<_>>(&fieldmap);
+        let decoded = dec::dict_full::<map::FieldMapSpec<IndexSTSeqCns<Box<str>, _>>>(&encoded).unwrap();
+        prop_assert_eq!(new_fieldmap(&fields).st_len(), decoded.st_len());
+        for (field_id, field) in &fields {
+            prop_assert_eq!(Some(field), decoded.st_get(field_id));
+        }
+        let reencoded = enc::full_dict::<map::FieldMapSpec<_>>(&decoded);
+        if ordered {
+            prop_assert_eq!(&encoded, &reencoded);
+        } else {
+            prop_assert_eq!(encoded.len(), reencoded.len());
+        }
+        for point in truncation_points(&encoded, &cuts) {
+            prop_assert!(
+                dec::dict_full::<map::FieldMapSpec<IndexSTSeqCns<Box<str>, Field>>>(&encoded[..point]).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn roundtrip_model(
+        uuid in any_uuid(),
+        p_key in any_ident(),
+        p_tag in any_pk_tag(),
+        fields in any_fields(),
+        props in any_model_props(),
+        cuts in any_cuts(),
+    ) {
+        let ordered = (props.len() <= 1) & fields.iter().all(|(_, field)| field_is_ordered(field));
+        let has_props = !props.is_empty();
+        let model = ModelData::new_restore_with_props(
+            uuid,
+            p_key,
+            p_tag.into_full(),
+            new_fieldmap(&fields),
+            ModelProps::try_new(props).unwrap(),
+        );
+        let encoded = enc::full::<obj::ModelLayoutRef>(obj::ModelLayoutRef(&model));
+        let decoded = dec::full::<obj::ModelLayoutRef>(&encoded).unwrap();
+        prop_assert_eq!(&model, &decoded);
+        let reencoded = enc::full::<obj::ModelLayoutRef>(obj::ModelLayoutRef(&decoded));
+        if ordered {
+            prop_assert_eq!(&encoded, &reencoded);
+        } else {
+            prop_assert_eq!(encoded.len(), reencoded.len());
+        }
+        for point in truncation_points(&encoded, &cuts) {
+            match dec::full::<obj::ModelLayoutRef>(&encoded[..point]) {
+                Err(_) => {}
+                // the properties are an optional trailing section, so cutting them off entirely leaves a valid layout
+                Ok(restored) => prop_assert!(has_props & restored.props().raw().is_empty()),
+            }
+        }
+    }
+
+    #[test]
+    fn roundtrip_space(uuid in any_uuid(), props in any_dict(), cuts in any_cuts()) {
+        let space = Space::new_restore_empty(uuid, props);
+        let ordered = dict_is_ordered(space.props());
+        roundtrip(
+            &space,
+            ordered,
+            &cuts,
+            |space| enc::full::<obj::SpaceLayoutRef>(obj::SpaceLayoutRef::from((space, space.props()))),
+            |data| dec::full::<obj::SpaceLayoutRef>(data),
+        );
+    }
+}
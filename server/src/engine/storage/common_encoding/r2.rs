@@ -35,7 +35,10 @@ use {
             core::GNSData,
             error::{StorageError, TransactionError},
             mem::BufferedScanner,
-            txn::gns::sysctl::{AlterUserTxn, CreateUserTxn, DropUserTxn},
+            txn::gns::sysctl::{
+                AlterUserQuotaTxn, AlterUserTxn, CreateStatementTxn, CreateUserTxn,
+                DropStatementTxn, DropUserTxn,
+            },
             RuntimeResult,
         },
         util::EndianQW,
@@ -235,3 +238,212 @@ impl<'a> PersistObject for DropUserTxn<'a> {
         Ok(DropUserPayload(username.into_boxed_str()))
     }
 }
+
+/*
+    create statement txn
+*/
+
+pub struct FullStatementDefinition {
+    username: Box<str>,
+    name: Box<str>,
+    body: Box<str>,
+}
+
+impl<'a> GNSEvent for CreateStatementTxn<'a> {
+    type CommitType = Self;
+    type RestoreType = FullStatementDefinition;
+    fn update_global_state(
+        FullStatementDefinition {
+            username,
+            name,
+            body,
+        }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        if gns.sys_db().__raw_create_statement(&username, name, body) {
+            Ok(())
+        } else {
+            Err(TransactionError::OnRestoreDataConflictAlreadyExists.into())
+        }
+    }
+}
+
+pub struct CreateStatementMetadata {
+    uname_l: u64,
+    name_l: u64,
+    body_l: u64,
+}
+
+impl<'a> PersistObject for CreateStatementTxn<'a> {
+    const METADATA_SIZE: usize = sizeof!(u64, 3);
+    type InputType = Self;
+    type OutputType = FullStatementDefinition;
+    type Metadata = CreateStatementMetadata;
+    fn pretest_can_dec_object(scanner: &BufferedScanner, md: &Self::Metadata) -> bool {
+        scanner.has_left((md.uname_l + md.name_l + md.body_l) as usize)
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        // [username length: 8B][name length: 8B][body length: 8B]
+        buf.extend(data.username().len().u64_bytes_le());
+        buf.extend(data.name().len().u64_bytes_le());
+        buf.extend(data.body().len().u64_bytes_le());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        Ok(CreateStatementMetadata {
+            uname_l: scanner.next_u64_le(),
+            name_l: scanner.next_u64_le(),
+            body_l: scanner.next_u64_le(),
+        })
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        buf.extend(data.username().as_bytes());
+        buf.extend(data.name().as_bytes());
+        buf.extend(data.body().as_bytes());
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        md: Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let username = dec::utils::decode_string(s, md.uname_l as _)?;
+        let name = dec::utils::decode_string(s, md.name_l as _)?;
+        let body = dec::utils::decode_string(s, md.body_l as _)?;
+        Ok(FullStatementDefinition {
+            username: username.into_boxed_str(),
+            name: name.into_boxed_str(),
+            body: body.into_boxed_str(),
+        })
+    }
+}
+
+/*
+    drop statement txn
+*/
+
+pub struct DropStatementPayload {
+    username: Box<str>,
+    name: Box<str>,
+}
+
+impl<'a> GNSEvent for DropStatementTxn<'a> {
+    type CommitType = Self;
+    type RestoreType = DropStatementPayload;
+    fn update_global_state(
+        DropStatementPayload { username, name }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        if gns.sys_db().__raw_drop_statement(&username, &name) {
+            Ok(())
+        } else {
+            Err(TransactionError::OnRestoreDataConflictMismatch.into())
+        }
+    }
+}
+
+impl<'a> PersistObject for DropStatementTxn<'a> {
+    const METADATA_SIZE: usize = sizeof!(u64, 2);
+    type InputType = Self;
+    type OutputType = DropStatementPayload;
+    type Metadata = (u64, u64);
+    fn pretest_can_dec_object(
+        scanner: &BufferedScanner,
+        (uname_l, name_l): &Self::Metadata,
+    ) -> bool {
+        scanner.has_left((uname_l + name_l) as usize)
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        // [username length: 8B][name length: 8B]
+        buf.extend(data.username().len().u64_bytes_le());
+        buf.extend(data.name().len().u64_bytes_le());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        Ok((scanner.next_u64_le(), scanner.next_u64_le()))
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        buf.extend(data.username().as_bytes());
+        buf.extend(data.name().as_bytes());
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        (uname_l, name_l): Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let username = dec::utils::decode_string(s, uname_l as _)?;
+        let name = dec::utils::decode_string(s, name_l as _)?;
+        Ok(DropStatementPayload {
+            username: username.into_boxed_str(),
+            name: name.into_boxed_str(),
+        })
+    }
+}
+
+/*
+    alter user quota txn
+*/
+
+#[derive(Debug, PartialEq)]
+pub struct AlterUserQuotaPayload {
+    username: Box<str>,
+    reads: u64,
+    writes: u64,
+}
+
+pub struct AlterUserQuotaMetadata {
+    uname_l: u64,
+    reads: u64,
+    writes: u64,
+}
+
+impl<'a> GNSEvent for AlterUserQuotaTxn<'a> {
+    type CommitType = Self;
+    type RestoreType = AlterUserQuotaPayload;
+    fn update_global_state(
+        AlterUserQuotaPayload {
+            username,
+            reads,
+            writes,
+        }: Self::RestoreType,
+        gns: &GNSData,
+    ) -> RuntimeResult<()> {
+        if gns.sys_db().__raw_alter_quota(&username, reads, writes) {
+            Ok(())
+        } else {
+            Err(TransactionError::OnRestoreDataMissing.into())
+        }
+    }
+}
+
+impl<'a> PersistObject for AlterUserQuotaTxn<'a> {
+    const METADATA_SIZE: usize = sizeof!(u64, 3);
+    type InputType = Self;
+    type OutputType = AlterUserQuotaPayload;
+    type Metadata = AlterUserQuotaMetadata;
+    fn pretest_can_dec_object(scanner: &BufferedScanner, md: &Self::Metadata) -> bool {
+        scanner.has_left(md.uname_l as usize)
+    }
+    fn meta_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        // [username length: 8B][reads: 8B][writes: 8B]
+        buf.extend(data.username().len().u64_bytes_le());
+        buf.extend(data.reads().to_le_bytes());
+        buf.extend(data.writes().to_le_bytes());
+    }
+    unsafe fn meta_dec(scanner: &mut BufferedScanner) -> RuntimeResult<Self::Metadata> {
+        Ok(AlterUserQuotaMetadata {
+            uname_l: scanner.next_u64_le(),
+            reads: scanner.next_u64_le(),
+            writes: scanner.next_u64_le(),
+        })
+    }
+    fn obj_enc(buf: &mut Vec<u8>, data: Self::InputType) {
+        buf.extend(data.username().as_bytes());
+    }
+    unsafe fn obj_dec(
+        s: &mut BufferedScanner,
+        md: Self::Metadata,
+    ) -> RuntimeResult<Self::OutputType> {
+        let username = dec::utils::decode_string(s, md.uname_l as _)?;
+        Ok(AlterUserQuotaPayload {
+            username: username.into_boxed_str(),
+            reads: md.reads,
+            writes: md.writes,
+        })
+    }
+}
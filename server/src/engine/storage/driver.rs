@@ -0,0 +1,375 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    storage drivers
+    ---
+    The engine never talks to a storage format directly. Everything that it persists goes through a
+    [`StorageDriver`] (which sets up the storage of spaces and models), the [`GNSJournal`] (DDL and user changes)
+    and a [`ModelJournal`] for every partition of a model (batches of data changes). An instance picks its driver
+    with `system.storage`:
+    - SDSS (the default) keeps everything on disk
+    - the memory driver keeps nothing, so an instance that uses it starts empty every time. It is also what the
+    engine is unit-tested against when a test has no use for the files
+*/
+
+use {
+    super::{
+        common::{interface::fs::FileSystem, paths_v1},
+        common_encoding::r1::impls::gns::GNSEvent,
+        v2::impls::mdl_journal::{BatchStats, LoadedRows, StdModelBatch},
+        GNSDriver, ModelDriver,
+    },
+    crate::engine::{
+        data::uuid::Uuid,
+        error::{ErrorKind, RuntimeResult},
+        fractal::error::Error,
+        txn::gns::{GNSTransaction, GNSTransactionCode},
+    },
+    std::{
+        cell::RefCell,
+        fmt,
+        rc::Rc,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Arc,
+        },
+    },
+};
+
+/// A storage backend
+pub trait StorageDriver: fmt::Debug + Send + Sync {
+    /// Set up the storage for a new space
+    fn create_space(&self, space_name: &str, space_uuid: Uuid) -> RuntimeResult<()>;
+    /// Set up the storage for a new model, returning a journal for each of its partitions (in order)
+    fn create_model(
+        &self,
+        space_name: &str,
+        space_uuid: Uuid,
+        model_name: &str,
+        model_uuid: Uuid,
+        partitions: usize,
+    ) -> RuntimeResult<Vec<Box<dyn ModelJournal>>>;
+    /// Returns the directory that holds the data of all spaces, if the driver keeps one
+    fn data_dir(&self) -> Option<String>;
+    /// Returns the directory that holds the space's data, if the driver keeps one (it is deleted in the background
+    /// once the space is dropped)
+    fn space_dir(&self, space_name: &str, space_uuid: Uuid) -> Option<String>;
+    /// Returns the directory that holds the model's data, if the driver keeps one
+    fn model_dir(
+        &self,
+        space_name: &str,
+        space_uuid: Uuid,
+        model_name: &str,
+        model_uuid: Uuid,
+    ) -> Option<String>;
+}
+
+/// The journal of the GNS
+pub trait GNSJournal: fmt::Debug + Send {
+    /// Durably append an (encoded) event
+    fn commit_encoded(&mut self, code: GNSTransactionCode, payload: Vec<u8>) -> RuntimeResult<()>;
+    /// Check if the journal can be written to again, after a commit failed
+    fn recover(&mut self) -> RuntimeResult<()>;
+    fn close(&mut self) -> RuntimeResult<()>;
+}
+
+impl dyn GNSJournal + '_ {
+    /// Durably append an event
+    pub fn commit_event<E: GNSEvent>(&mut self, event: E) -> RuntimeResult<()> {
+        let mut payload = vec![];
+        E::encode_event(event, &mut payload);
+        self.commit_encoded(<E as GNSTransaction>::CODE, payload)
+    }
+}
+
+/// The journal of a partition of a model
+pub trait ModelJournal: fmt::Debug + Send {
+    /// Durably write a batch of changes. If only some of the changes were written, the number written is set in
+    /// `stats` (and the rest are handed back to the model)
+    fn commit_batch(
+        &mut self,
+        batch: StdModelBatch,
+        stats: Rc<RefCell<BatchStats>>,
+    ) -> RuntimeResult<()>;
+    /// Durably write rows that were loaded in bulk (and that have no deltas) as a single batch
+    fn commit_loaded(&mut self, rows: LoadedRows) -> RuntimeResult<()>;
+    /// Check if the journal can be written to again, after a batch couldn't be written
+    fn recover(&mut self) -> RuntimeResult<()>;
+    fn close(&mut self) -> RuntimeResult<()>;
+    /// Continue writing to the copy of the journal at `path`, which must hold everything that was written so far
+    fn relocate(&mut self, path: &str) -> RuntimeResult<()>;
+}
+
+/// Box up the journals of a model
+pub fn model_journals<J: ModelJournal + 'static>(journals: Vec<J>) -> Vec<Box<dyn ModelJournal>> {
+    journals
+        .into_iter()
+        .map(|journal| Box::new(journal) as Box<dyn ModelJournal>)
+        .collect()
+}
+
+/*
+    sdss
+*/
+
+#[derive(Debug)]
+/// The on-disk driver (SDSS)
+pub struct SDSSDriver;
+
+impl StorageDriver for SDSSDriver {
+    fn create_space(&self, space_name: &str, space_uuid: Uuid) -> RuntimeResult<()> {
+        e!(FileSystem::create_dir_all(&paths_v1::space_dir(
+            space_name, space_uuid
+        )))
+    }
+    fn create_model(
+        &self,
+        space_name: &str,
+        space_uuid: Uuid,
+        model_name: &str,
+        model_uuid: Uuid,
+        partitions: usize,
+    ) -> RuntimeResult<Vec<Box<dyn ModelJournal>>> {
+        FileSystem::create_dir_all(&paths_v1::model_dir(
+            space_name, space_uuid, model_name, model_uuid,
+        ))?;
+        let drivers = ModelDriver::create_model_drivers(partitions, |partition| {
+            paths_v1::model_partition_path(
+                space_name, space_uuid, model_name, model_uuid, partition,
+            )
+        })?;
+        Ok(model_journals(drivers))
+    }
+    fn data_dir(&self) -> Option<String> {
+        Some(paths_v1::data_dir())
+    }
+    fn space_dir(&self, space_name: &str, space_uuid: Uuid) -> Option<String> {
+        Some(paths_v1::space_dir(space_name, space_uuid))
+    }
+    fn model_dir(
+        &self,
+        space_name: &str,
+        space_uuid: Uuid,
+        model_name: &str,
+        model_uuid: Uuid,
+    ) -> Option<String> {
+        Some(paths_v1::model_dir(
+            space_name, space_uuid, model_name, model_uuid,
+        ))
+    }
+}
+
+impl GNSJournal for GNSDriver {
+    fn commit_encoded(&mut self, code: GNSTransactionCode, payload: Vec<u8>) -> RuntimeResult<()> {
+        self.commit_event(super::v2::impls::gns_log::EncodedGNSEvent::new(
+            code, payload,
+        ))
+    }
+    fn recover(&mut self) -> RuntimeResult<()> {
+        self.__lwt_heartbeat()
+    }
+    fn close(&mut self) -> RuntimeResult<()> {
+        GNSDriver::close_driver(self)
+    }
+}
+
+impl ModelJournal for ModelDriver {
+    fn commit_batch(
+        &mut self,
+        batch: StdModelBatch,
+        stats: Rc<RefCell<BatchStats>>,
+    ) -> RuntimeResult<()> {
+        self.commit_with_ctx(batch, stats)
+    }
+    fn commit_loaded(&mut self, rows: LoadedRows) -> RuntimeResult<()> {
+        self.commit_with_ctx(rows, BatchStats::new())
+    }
+    fn recover(&mut self) -> RuntimeResult<()> {
+        self.__lwt_heartbeat()
+    }
+    fn close(&mut self) -> RuntimeResult<()> {
+        ModelDriver::close_driver(self)
+    }
+    fn relocate(&mut self, path: &str) -> RuntimeResult<()> {
+        ModelDriver::relocate(self, path)
+    }
+}
+
+/*
+    memory
+*/
+
+#[derive(Debug, Default, Clone)]
+/// A driver that keeps nothing: its journals only count what is committed to them. Clones share the counts
+pub struct MemoryDriver {
+    stats: Arc<MemoryStats>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryStats {
+    events: AtomicU64,
+    batches: AtomicU64,
+    changes: AtomicU64,
+    failing: AtomicBool,
+    /// one more than the number of commits left before every commit fails (zero if there's no such limit)
+    fail_after: AtomicU64,
+}
+
+impl MemoryStats {
+    fn commit(&self) -> RuntimeResult<()> {
+        let over_limit = self
+            .fail_after
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |left| match left {
+                0 | 1 => None,
+                left => Some(left - 1),
+            })
+            .map_or_else(|left| left == 1, |_| false);
+        if self.failing.load(Ordering::Acquire) | over_limit {
+            return Err(Error::from(ErrorKind::Other(
+                "memory driver set to fail".into(),
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl MemoryDriver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns a GNS journal for this driver
+    pub fn gns_journal(&self) -> Box<dyn GNSJournal> {
+        Box::new(MemoryJournal {
+            stats: self.stats.clone(),
+        })
+    }
+    /// Returns the number of GNS events committed
+    pub fn events(&self) -> u64 {
+        self.stats.events.load(Ordering::Acquire)
+    }
+    /// Returns the number of batches written (by all models)
+    pub fn batches(&self) -> u64 {
+        self.stats.batches.load(Ordering::Acquire)
+    }
+    /// Returns the number of changes written in batches (by all models)
+    pub fn changes(&self) -> u64 {
+        self.stats.changes.load(Ordering::Acquire)
+    }
+    /// Have every commit fail (until this is called with `false`), like a disk that went away would
+    pub fn set_failing(&self, failing: bool) {
+        self.stats.failing.store(failing, Ordering::Release)
+    }
+    /// Have every commit after the next `commits` fail, like a disk that fills up would
+    pub fn set_failing_after(&self, commits: u64) {
+        self.stats.fail_after.store(commits + 1, Ordering::Release)
+    }
+}
+
+impl StorageDriver for MemoryDriver {
+    fn create_space(&self, _: &str, _: Uuid) -> RuntimeResult<()> {
+        self.stats.commit()
+    }
+    fn create_model(
+        &self,
+        _: &str,
+        _: Uuid,
+        _: &str,
+        _: Uuid,
+        partitions: usize,
+    ) -> RuntimeResult<Vec<Box<dyn ModelJournal>>> {
+        self.stats.commit()?;
+        Ok((0..partitions)
+            .map(|_| {
+                Box::new(MemoryJournal {
+                    stats: self.stats.clone(),
+                }) as Box<dyn ModelJournal>
+            })
+            .collect())
+    }
+    fn data_dir(&self) -> Option<String> {
+        None
+    }
+    fn space_dir(&self, _: &str, _: Uuid) -> Option<String> {
+        None
+    }
+    fn model_dir(&self, _: &str, _: Uuid, _: &str, _: Uuid) -> Option<String> {
+        None
+    }
+}
+
+#[derive(Debug)]
+struct MemoryJournal {
+    stats: Arc<MemoryStats>,
+}
+
+impl GNSJournal for MemoryJournal {
+    fn commit_encoded(&mut self, _: GNSTransactionCode, _: Vec<u8>) -> RuntimeResult<()> {
+        self.stats.commit()?;
+        self.stats.events.fetch_add(1, Ordering::AcqRel);
+        Ok(())
+    }
+    fn recover(&mut self) -> RuntimeResult<()> {
+        self.stats.commit()
+    }
+    fn close(&mut self) -> RuntimeResult<()> {
+        Ok(())
+    }
+}
+
+impl ModelJournal for MemoryJournal {
+    fn commit_batch(
+        &mut self,
+        batch: StdModelBatch,
+        _: Rc<RefCell<BatchStats>>,
+    ) -> RuntimeResult<()> {
+        // a failed commit takes no changes off the model (which the stats already say)
+        self.stats.commit()?;
+        let changes = batch.discard();
+        self.stats.batches.fetch_add(1, Ordering::AcqRel);
+        self.stats
+            .changes
+            .fetch_add(changes as u64, Ordering::AcqRel);
+        Ok(())
+    }
+    fn commit_loaded(&mut self, rows: LoadedRows) -> RuntimeResult<()> {
+        self.stats.commit()?;
+        self.stats.batches.fetch_add(1, Ordering::AcqRel);
+        self.stats
+            .changes
+            .fetch_add(rows.row_count() as u64, Ordering::AcqRel);
+        Ok(())
+    }
+    fn recover(&mut self) -> RuntimeResult<()> {
+        self.stats.commit()
+    }
+    fn close(&mut self) -> RuntimeResult<()> {
+        Ok(())
+    }
+    fn relocate(&mut self, _: &str) -> RuntimeResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,558 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    0.7 migration
+    ---
+    an install of Skytable 0.7 keeps its data in `data/ks`, in the "KV/coretable" format. when we find one (and no
+    install of our own), every keyspace and table is loaded into a space and model of the same name, and then written
+    out in the current format (just like an upgrade from an older driver). the old data directory is moved into
+    `backups` first. all of its files are written in the native endianness of the host that wrote them, so the
+    endianness is read from the first byte of the preload:
+
+    data/ks/PRELOAD: [endian:1B][keyspace count:8B]([name size:8B][name])*
+    data/ks/<keyspace>/PARTMAP: [endian:1B][table count:8B]([name size:8B][name][volatile:1B][model code:1B])*
+    data/ks/<keyspace>/<table> (a key/value table): [key count:8B]([key size:8B][value size:8B][key][value])*
+    data/ks/<keyspace>/<table> (a key/list table): [key count:8B]([key size:8B][key][list size:8B]([size:8B][item])*)*
+
+    every table becomes a model with two fields, `k` (the primary key) and `v`. their types depend on the table's model
+    code:
+
+    | code | table                        | model                                 |
+    | ---- | ---------------------------- | ------------------------------------- |
+    | 0    | keymap(binstr, binstr)       | (k: binary, v: binary)                |
+    | 1    | keymap(binstr, str)          | (k: binary, v: string)                |
+    | 2    | keymap(str, str)             | (k: string, v: string)                |
+    | 3    | keymap(str, binstr)          | (k: string, v: binary)                |
+    | 4    | keymap(binstr, list<binstr>) | (k: binary, v: list { type: binary }) |
+    | 5    | keymap(binstr, list<str>)    | (k: binary, v: list { type: string }) |
+    | 6    | keymap(str, list<binstr>)    | (k: string, v: list { type: binary }) |
+    | 7    | keymap(str, list<str>)       | (k: string, v: list { type: string }) |
+
+    a keyspace or table named after a keyword or boolean (like the `default` keyspace and table) can't be named in a
+    query, so it is given a trailing underscore (`default_`). volatile tables never had their data written out, so their models
+    start out empty. the `system` keyspace (which holds the 0.7 auth keys) isn't migrated: the root account is created
+    with the configured password, and every other account has to be created again
+*/
+
+use crate::{
+    engine::{
+        config::Configuration,
+        core::{
+            index::{DcFieldIndex, PrimaryIndexKey, Row},
+            model::{Field, Layer, Model, ModelData, ModelProps},
+            space::Space,
+            system_db::SystemDatabase,
+            EntityID, GNSData,
+        },
+        data::{cell::Datacell, uuid::Uuid},
+        error::StorageError,
+        fractal::{error::ErrorContext, FractalModelDriver},
+        idx::{IndexBaseSpec, IndexSTSeqCns, STIndex, STIndexSeq},
+        mem::BufferedScanner,
+        ql::lex::Keyword,
+        storage::common::interface::fs::FileSystem,
+        sync::atm::cpin,
+        RuntimeResult,
+    },
+    util,
+};
+
+/// The root of the keyspaces of a 0.7 install
+pub const KS_ROOT: &str = "data/ks";
+/// The preload of a 0.7 install (that lists its keyspaces)
+pub const PRELOAD_PATH: &str = "data/ks/PRELOAD";
+/// The keyspace that holds the auth keys of a 0.7 install
+const SYSTEM_KEYSPACE: &str = "system";
+const META_SEGMENT_LE: u8 = 0b1000_0000;
+const META_SEGMENT_BE: u8 = 0b1000_0001;
+/// The name of the primary key of a migrated table
+pub const FIELD_KEY: &str = "k";
+/// The name of the value of a migrated table
+pub const FIELD_VALUE: &str = "v";
+
+/// Load the data of a 0.7 install, and then move its data directory out of the way (into `backups`)
+pub fn load_gns_prepare_migration(cfg: &Configuration) -> RuntimeResult<GNSData> {
+    let gns = load_gns(KS_ROOT)?;
+    let password_hash = rcrypt::hash(&cfg.auth.root_key, rcrypt::DEFAULT_COST).unwrap();
+    assert!(gns.sys_db().__raw_create_user(
+        SystemDatabase::ROOT_ACCOUNT.to_owned().into_boxed_str(),
+        password_hash.into_boxed_slice(),
+    ));
+    let backup_dir_path = format!(
+        "backups/{}",
+        util::time_now_with_postfix("before_upgrade_from_v07")
+    );
+    FileSystem::create_dir_all(&backup_dir_path)?;
+    util::os::move_files_recursively("data", &format!("{backup_dir_path}/data"))?;
+    Ok(gns)
+}
+
+/// Load the keyspaces (and their tables) of a 0.7 install in `ks_root` into spaces and models
+pub fn load_gns(ks_root: &str) -> RuntimeResult<GNSData> {
+    let gns = GNSData::empty();
+    let preload = FileSystem::read(&format!("{ks_root}/PRELOAD"))?;
+    let mut preload = LegacyFile::with_meta_segment(&preload)?;
+    let mut keyspaces = vec![];
+    for _ in 0..preload.u64()? {
+        keyspaces.push(preload.name()?);
+    }
+    preload.finish()?;
+    for keyspace in keyspaces {
+        if keyspace == SYSTEM_KEYSPACE {
+            warn!(
+                "the accounts of the 0.7 install will not be migrated and have to be created again"
+            );
+            continue;
+        }
+        load_keyspace(&gns, ks_root, keyspace, preload.big_endian)
+            .inherit_set_dmsg(format!("failed to migrate keyspace `{keyspace}`"))?;
+    }
+    Ok(gns)
+}
+
+fn load_keyspace(
+    gns: &GNSData,
+    ks_root: &str,
+    keyspace: &str,
+    big_endian: bool,
+) -> RuntimeResult<()> {
+    let partmap = FileSystem::read(&format!("{ks_root}/{keyspace}/PARTMAP"))?;
+    let mut partmap = LegacyFile::with_meta_segment(&partmap)?;
+    let space_name = migrated_name(keyspace);
+    let mut space = Space::new_auto_all();
+    for _ in 0..partmap.u64()? {
+        let table = partmap.name()?;
+        let volatile = match partmap.byte()? {
+            0 => false,
+            1 => true,
+            _ => return Err(StorageError::LegacyDataCorrupted.into()),
+        };
+        let kind = TableKind::from_model_code(partmap.byte()?)?;
+        let model = kind.model();
+        if !volatile {
+            let path = format!("{ks_root}/{keyspace}/{table}");
+            kind.load(&model, &path, big_endian)
+                .inherit_set_dmsg(format!("failed to migrate table in `{path}`"))?;
+        }
+        let model_name = migrated_name(table);
+        info!("migrating table `{keyspace}:{table}` into model `{space_name}.{model_name}`");
+        let partitions = model.primary_index().partitions();
+        let _ = gns.idx_models().write().insert(
+            EntityID::new(&space_name, &model_name),
+            Model::new(model, FractalModelDriver::uninitialized(partitions)),
+        );
+        space.models_mut().insert(model_name);
+    }
+    partmap.finish()?;
+    let _ = gns.idx().write().insert(space_name, space);
+    Ok(())
+}
+
+/// The name of the space or model that a keyspace or table is migrated into
+fn migrated_name(name: &str) -> Box<str> {
+    let reserved = Keyword::get(name.as_bytes()).is_some()
+        | name.eq_ignore_ascii_case("true")
+        | name.eq_ignore_ascii_case("false");
+    if reserved {
+        format!("{name}_").into_boxed_str()
+    } else {
+        name.into()
+    }
+}
+
+/// The types of the keys and values of a table (see the module docs)
+struct TableKind {
+    key_str: bool,
+    value_str: bool,
+    list: bool,
+}
+
+impl TableKind {
+    fn from_model_code(code: u8) -> RuntimeResult<Self> {
+        let (key_str, value_str, list) = match code {
+            0 => (false, false, false),
+            1 => (false, true, false),
+            2 => (true, true, false),
+            3 => (true, false, false),
+            4 => (false, false, true),
+            5 => (false, true, true),
+            6 => (true, false, true),
+            7 => (true, true, true),
+            _ => return Err(StorageError::LegacyDataCorrupted.into()),
+        };
+        Ok(Self {
+            key_str,
+            value_str,
+            list,
+        })
+    }
+    fn layer(is_str: bool) -> Layer {
+        if is_str {
+            Layer::str()
+        } else {
+            Layer::bin()
+        }
+    }
+    /// Create the (empty) model that the table is migrated into
+    fn model(&self) -> ModelData {
+        let key = Self::layer(self.key_str);
+        let value = if self.list {
+            [Layer::list(), Self::layer(self.value_str)].into()
+        } else {
+            [Self::layer(self.value_str)].into()
+        };
+        let mut fields = IndexSTSeqCns::idx_init();
+        fields.st_insert(FIELD_KEY.into(), Field::new([key].into(), false));
+        fields.st_insert(FIELD_VALUE.into(), Field::new(value, false));
+        ModelData::new_restore_with_props(
+            Uuid::new(),
+            FIELD_KEY.into(),
+            key.tag(),
+            fields,
+            ModelProps::default(),
+        )
+    }
+    /// Load the rows of the table in `path` into its model. A table that was never written out is empty
+    fn load(&self, model: &ModelData, path: &str, big_endian: bool) -> RuntimeResult<()> {
+        let table = match FileSystem::read(path) {
+            Ok(table) => table,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut table = LegacyFile::new(&table, big_endian);
+        let value_field = model
+            .fields()
+            .stseq_ord_key()
+            .find(|field| field.as_str() == FIELD_VALUE)
+            .unwrap();
+        let ds = model.delta_state();
+        let g = cpin();
+        for _ in 0..table.u64()? {
+            let (key, value) = if self.list {
+                let key = table.block()?;
+                let mut items = vec![];
+                for _ in 0..table.u64()? {
+                    items.push(Self::cell(table.block()?, self.value_str)?);
+                }
+                (key, Datacell::new_list(items))
+            } else {
+                let (key_size, value_size) = (table.u64()?, table.u64()?);
+                let key = table.sized_block(key_size)?;
+                let value = table.sized_block(value_size)?;
+                (key, Self::cell(value, self.value_str)?)
+            };
+            let key = PrimaryIndexKey::try_from_dc(Self::cell(key, self.key_str)?).unwrap();
+            let mut data = DcFieldIndex::idx_init_cap(1);
+            data.st_insert(
+                unsafe {
+                    // UNSAFE(@ohsayan): the row goes with the model that owns the allocation
+                    value_field.clone()
+                },
+                value,
+            );
+            let row = Row::new(
+                key,
+                data,
+                ds.schema_current_version(),
+                ds.create_new_data_delta_version(),
+            );
+            if !model.primary_index().insert(row, &g) {
+                // keys are unique in a table
+                return Err(StorageError::LegacyDataCorrupted.into());
+            }
+        }
+        table.finish()
+    }
+    fn cell(block: &[u8], is_str: bool) -> RuntimeResult<Datacell> {
+        if is_str {
+            match core::str::from_utf8(block) {
+                Ok(s) => Ok(Datacell::new_str(s.into())),
+                Err(_) => Err(StorageError::LegacyDataCorrupted.into()),
+            }
+        } else {
+            Ok(Datacell::new_bin(block.into()))
+        }
+    }
+}
+
+/// A file of a 0.7 install
+struct LegacyFile<'a> {
+    scanner: BufferedScanner<'a>,
+    big_endian: bool,
+}
+
+impl<'a> LegacyFile<'a> {
+    fn new(file: &'a [u8], big_endian: bool) -> Self {
+        Self {
+            scanner: BufferedScanner::new(file),
+            big_endian,
+        }
+    }
+    /// A file that starts with the meta segment (that holds its endianness)
+    fn with_meta_segment(file: &'a [u8]) -> RuntimeResult<Self> {
+        let mut slf = Self::new(file, false);
+        slf.big_endian = match slf.byte()? {
+            META_SEGMENT_LE => false,
+            META_SEGMENT_BE => true,
+            _ => return Err(StorageError::LegacyDataCorrupted.into()),
+        };
+        Ok(slf)
+    }
+    fn byte(&mut self) -> RuntimeResult<u8> {
+        Ok(self
+            .scanner
+            .try_next_byte()
+            .ok_or(StorageError::LegacyDataCorrupted)?)
+    }
+    fn u64(&mut self) -> RuntimeResult<u64> {
+        let Some(block) = self.scanner.try_next_variable_block(sizeof!(u64)) else {
+            return Err(StorageError::LegacyDataCorrupted.into());
+        };
+        let qw: [u8; sizeof!(u64)] = block.try_into().unwrap();
+        Ok(if self.big_endian {
+            u64::from_be_bytes(qw)
+        } else {
+            u64::from_le_bytes(qw)
+        })
+    }
+    fn sized_block(&mut self, size: u64) -> RuntimeResult<&'a [u8]> {
+        Ok(usize::try_from(size)
+            .ok()
+            .and_then(|size| self.scanner.try_next_variable_block(size))
+            .ok_or(StorageError::LegacyDataCorrupted)?)
+    }
+    fn block(&mut self) -> RuntimeResult<&'a [u8]> {
+        let size = self.u64()?;
+        self.sized_block(size)
+    }
+    /// The name of a keyspace or table, which is always an identifier
+    fn name(&mut self) -> RuntimeResult<&'a str> {
+        let name = self.block()?;
+        let okay = !name.is_empty()
+            && !name[0].is_ascii_digit()
+            && name.iter().all(|b| b.is_ascii_alphanumeric() || *b == b'_');
+        if okay {
+            // UNSAFE(@ohsayan): all ASCII
+            Ok(unsafe { core::str::from_utf8_unchecked(name) })
+        } else {
+            Err(StorageError::LegacyDataCorrupted.into())
+        }
+    }
+    fn finish(&self) -> RuntimeResult<()> {
+        if self.scanner.eof() {
+            Ok(())
+        } else {
+            Err(StorageError::LegacyDataCorrupted.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        core::{model::Layer, EntityIDRef, GNSData},
+        data::{cell::Datacell, lit::Lit},
+        error::{ErrorKind, StorageError},
+        fractal::error::Error,
+        idx::STIndex,
+        storage::common::interface::fs::{File, FileSystem, FileWrite},
+        sync::atm::cpin,
+    };
+
+    fn write(path: &str, data: &[u8]) {
+        // some files are written more than once, and create won't replace them
+        let _ = FileSystem::remove_file(path);
+        File::create(path).unwrap().fwrite_all(data).unwrap();
+    }
+
+    fn qw(buf: &mut Vec<u8>, qw: usize) {
+        buf.extend((qw as u64).to_le_bytes());
+    }
+
+    fn block(buf: &mut Vec<u8>, block: &[u8]) {
+        qw(buf, block.len());
+        buf.extend(block);
+    }
+
+    fn preload(keyspaces: &[&str]) -> Vec<u8> {
+        let mut buf = vec![super::META_SEGMENT_LE];
+        qw(&mut buf, keyspaces.len());
+        keyspaces
+            .iter()
+            .for_each(|ks| block(&mut buf, ks.as_bytes()));
+        buf
+    }
+
+    fn partmap(tables: &[(&str, u8, u8)]) -> Vec<u8> {
+        let mut buf = vec![super::META_SEGMENT_LE];
+        qw(&mut buf, tables.len());
+        for (name, volatile, model_code) in tables {
+            block(&mut buf, name.as_bytes());
+            buf.extend([*volatile, *model_code]);
+        }
+        buf
+    }
+
+    fn kv_table(rows: &[(&[u8], &[u8])]) -> Vec<u8> {
+        let mut buf = vec![];
+        qw(&mut buf, rows.len());
+        for (k, v) in rows {
+            qw(&mut buf, k.len());
+            qw(&mut buf, v.len());
+            buf.extend(*k);
+            buf.extend(*v);
+        }
+        buf
+    }
+
+    fn list_table(rows: &[(&[u8], &[&[u8]])]) -> Vec<u8> {
+        let mut buf = vec![];
+        qw(&mut buf, rows.len());
+        for (k, items) in rows {
+            block(&mut buf, k);
+            qw(&mut buf, items.len());
+            items.iter().for_each(|item| block(&mut buf, item));
+        }
+        buf
+    }
+
+    fn value(gns: &GNSData, space: &str, model: &str, key: Lit) -> Option<Datacell> {
+        let models = gns.idx_models().read();
+        let model = models.get(&EntityIDRef::new(space, model)).unwrap().data();
+        let g = cpin();
+        let row = model.primary_index().select(key, &g)?;
+        let value = row
+            .d_data()
+            .read()
+            .unwrap()
+            .fields()
+            .st_get(super::FIELD_VALUE)
+            .cloned();
+        value
+    }
+
+    #[test]
+    fn load_install() {
+        let root = "legacy_load_install/ks";
+        for dir in ["default", "system", "app"] {
+            FileSystem::create_dir_all(&format!("{root}/{dir}")).unwrap();
+        }
+        write(
+            &format!("{root}/PRELOAD"),
+            &preload(&["default", "system", "app"]),
+        );
+        write(
+            &format!("{root}/default/PARTMAP"),
+            &partmap(&[("default", 0, 2)]),
+        );
+        write(
+            &format!("{root}/default/default"),
+            &kv_table(&[(b"sayan", b"pass123"), (b"robot", b"beep")]),
+        );
+        write(
+            &format!("{root}/app/PARTMAP"),
+            &partmap(&[("blobs", 0, 0), ("tags", 0, 7), ("cache", 1, 2)]),
+        );
+        write(
+            &format!("{root}/app/blobs"),
+            &kv_table(&[(b"\xff\x00", b"\x01\x02")]),
+        );
+        write(
+            &format!("{root}/app/tags"),
+            &list_table(&[(b"sayan", &[b"rust", b"db"]), (b"robot", &[])]),
+        );
+        let gns = super::load_gns(root).unwrap();
+        // keyspaces named after a keyword are renamed, and the system keyspace is skipped
+        let mut spaces: Vec<String> = gns.idx().read().keys().map(|s| s.to_string()).collect();
+        spaces.sort();
+        assert_eq!(spaces, ["app", "default_"]);
+        assert_eq!(
+            value(&gns, "default_", "default_", Lit::new_str("sayan")),
+            Some(Datacell::from("pass123"))
+        );
+        assert_eq!(
+            value(&gns, "default_", "default_", Lit::new_str("robot")),
+            Some(Datacell::from("beep"))
+        );
+        assert_eq!(
+            value(&gns, "app", "blobs", Lit::new_bin(b"\xff\x00")),
+            Some(Datacell::from(b"\x01\x02".to_vec()))
+        );
+        assert_eq!(
+            value(&gns, "app", "tags", Lit::new_str("sayan")),
+            Some(Datacell::new_list(vec![
+                Datacell::from("rust"),
+                Datacell::from("db")
+            ]))
+        );
+        assert_eq!(
+            value(&gns, "app", "tags", Lit::new_str("robot")),
+            Some(Datacell::new_list(vec![]))
+        );
+        let models = gns.idx_models().read();
+        let tags = models.get(&EntityIDRef::new("app", "tags")).unwrap().data();
+        assert_eq!(tags.p_key(), super::FIELD_KEY);
+        assert_eq!(
+            tags.fields().st_get(super::FIELD_VALUE).unwrap().layers(),
+            [Layer::list(), Layer::str()]
+        );
+        // the volatile table was never written out
+        let cache = models
+            .get(&EntityIDRef::new("app", "cache"))
+            .unwrap()
+            .data();
+        assert_eq!(cache.primary_index().count(), 0);
+    }
+
+    #[test]
+    fn load_corrupted_install() {
+        let root = "legacy_load_corrupted_install/ks";
+        FileSystem::create_dir_all(&format!("{root}/app")).unwrap();
+        write(&format!("{root}/PRELOAD"), &preload(&["app"]));
+        let corrupted = |e: Error| {
+            assert_eq!(
+                e.kind(),
+                &ErrorKind::Storage(StorageError::LegacyDataCorrupted)
+            );
+        };
+        // unknown model code
+        write(&format!("{root}/app/PARTMAP"), &partmap(&[("users", 0, 8)]));
+        corrupted(super::load_gns(root).unwrap_err());
+        // a str table with a value that isn't UTF-8
+        write(&format!("{root}/app/PARTMAP"), &partmap(&[("users", 0, 2)]));
+        write(
+            &format!("{root}/app/users"),
+            &kv_table(&[(b"sayan", b"\xff")]),
+        );
+        corrupted(super::load_gns(root).unwrap_err());
+        // a truncated table
+        let mut table = kv_table(&[(b"sayan", b"pass123")]);
+        table.pop();
+        write(&format!("{root}/app/users"), &table);
+        corrupted(super::load_gns(root).unwrap_err());
+    }
+}
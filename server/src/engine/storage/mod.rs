@@ -27,30 +27,58 @@
 //! Implementations of the Skytable Disk Storage Subsystem (SDSS)
 
 use {
-    super::{config::Configuration, core::GlobalNS, fractal::context, RuntimeResult},
+    self::common::{interface::fs::FileSystem, paths_v1},
+    self::driver::MemoryDriver,
+    super::{
+        config::{ConfigStorage, Configuration},
+        core::{system_db::SystemDatabase, GNSData, GlobalNS},
+        error::ErrorKind,
+        fractal::{context, error::Error, jobs::Jobs, FractalGNSDriver},
+        RuntimeResult,
+    },
     std::path::Path,
 };
 
 mod common;
 mod common_encoding;
+pub mod driver;
+mod legacy;
+pub mod overflow;
 // driver versions
 pub mod v1;
 pub mod v2;
 
 pub mod safe_interfaces {
+    #[cfg(test)]
+    pub use super::v2::finish_drops;
     pub use super::{
         common::{interface::fs::FileSystem, paths_v1},
-        v2::impls::mdl_journal::StdModelBatch,
+        v2::impls::mdl_journal::{LoadedRows, StdModelBatch},
     };
 }
 
+/// The storage internals that the criterion benchmarks (in `benches/`) measure
+pub mod bench {
+    pub use super::{common_encoding::r1::benches as encoding, v2::impls::benches as journal};
+}
+
 /*
     loader impl
 */
 
 pub use v2::impls::{
+    archive::{ArchivePending, JournalArchive},
+    gns_export::GNSExport,
     gns_log::GNSDriver,
+    inspect::{run as run_inspect, Inspector},
+    jobs_file::JobsFile,
+    mdl_export::{encode_key, encode_str, row_checksum, JournalExport, PersistedRows},
     mdl_journal::{BatchStats, ModelDriver},
+    relocate::DataRelocation,
+    snapshot_file::{SnapshotEntry, SnapshotFiles, SnapshotManifest, SnapshotSchedule},
+    usage::DiskUsage,
+    warmup_file::WarmupFile,
+    webhook_cursor::WebhookCursor,
 };
 
 pub struct SELoaded {
@@ -58,6 +86,14 @@ pub struct SELoaded {
 }
 
 pub fn load(cfg: &Configuration) -> RuntimeResult<SELoaded> {
+    place_files(cfg)?;
+    // the overflow files only hold copies of rows (from the previous run)
+    overflow::reset_dir()?;
+    if cfg.system.storage == ConfigStorage::Memory {
+        warn!("using the memory storage driver. no data is kept once the server stops");
+        context::set_dmsg("creating databases in memory");
+        return initialize_memory(cfg);
+    }
     // first determine if this is a new install, an existing install or if it uses the old driver
     if Path::new(v1::SYSDB_PATH).is_file() {
         warn!("older storage format detected");
@@ -69,7 +105,17 @@ pub fn load(cfg: &Configuration) -> RuntimeResult<SELoaded> {
         context::set_dmsg("upgrading storage-v1 to storage-v2 format");
         return v2::recreate(gns);
     }
-    if !Path::new(v2::GNS_PATH).is_file() {
+    if !Path::new(&paths_v1::gns_path()).is_file() && Path::new(legacy::PRELOAD_PATH).is_file() {
+        warn!("storage format of 0.7 detected");
+        // this is an install of 0.7
+        info!("loading data");
+        context::set_dmsg("loading 0.7 data in compatibility mode");
+        let gns = legacy::load_gns_prepare_migration(cfg)?;
+        info!("loaded data. now upgrading to new storage format");
+        context::set_dmsg("upgrading 0.7 data to storage-v2 format");
+        return v2::recreate(gns);
+    }
+    if !Path::new(&paths_v1::gns_path()).is_file() {
         info!("initializing databases");
         context::set_dmsg("creating databases");
         // this is a new install
@@ -77,6 +123,64 @@ pub fn load(cfg: &Configuration) -> RuntimeResult<SELoaded> {
     } else {
         info!("reinitializing databases");
         context::set_dmsg("loading databases");
-        v2::restore(cfg)
+        let loaded = v2::restore(cfg)?;
+        context::set_dmsg("checking data directory");
+        v2::impls::selfcheck::self_check(loaded.gns.namespace(), cfg.system.repair)?;
+        Ok(loaded)
+    }
+}
+
+/// Start a new install on the [`MemoryDriver`], with just the root account. Job records are still kept in the
+/// data directory
+fn initialize_memory(cfg: &Configuration) -> RuntimeResult<SELoaded> {
+    let driver = MemoryDriver::new();
+    let gns = GNSData::empty();
+    let password_hash = rcrypt::hash(&cfg.auth.root_key, rcrypt::DEFAULT_COST).unwrap();
+    assert!(gns.sys_db().__raw_create_user(
+        SystemDatabase::ROOT_ACCOUNT.to_owned().into_boxed_str(),
+        password_hash.into_boxed_slice(),
+    ));
+    Ok(SELoaded {
+        gns: GlobalNS::new(
+            gns,
+            Box::new(driver.clone()),
+            FractalGNSDriver::new(driver.gns_journal()),
+            Jobs::load(&paths_v1::jobs_path())?,
+        ),
+    })
+}
+
+/// Place files on the configured volumes. An install is never moved between volumes, so if the journal isn't on the
+/// journal volume but is in the working directory, we refuse to start (instead of initializing a new install). The
+/// same goes for a batch volume that the files were relocated off of
+fn place_files(cfg: &Configuration) -> RuntimeResult<()> {
+    let placement = paths_v1::Placement::new(
+        cfg.system.journal_volume.as_deref(),
+        cfg.system.batch_volume.as_deref(),
+    );
+    if let Some(to) = v2::impls::relocate::relocated_to(placement.batch_volume())? {
+        return Err(Error::from(ErrorKind::Other(format!(
+            "the data directory was relocated from {} to {to}. set the batch volume to {to}",
+            placement.batch_volume()
+        ))));
+    }
+    if placement.is_default() {
+        return Ok(());
+    }
+    info!(
+        "placing the journal in {} and batches in {}",
+        placement.journal_volume(),
+        placement.batch_volume()
+    );
+    assert!(paths_v1::set_placement(placement), "placement already set");
+    if !Path::new(&paths_v1::gns_path()).is_file() && Path::new(v2::GNS_PATH).is_file() {
+        return Err(Error::from(ErrorKind::Other(format!(
+            "found an existing install in the working directory. move it to the configured volumes first (journal: {}, batches: {})",
+            paths_v1::placement().journal_volume(),
+            paths_v1::placement().batch_volume()
+        ))));
     }
+    FileSystem::create_dir_all(paths_v1::placement().journal_volume())?;
+    FileSystem::create_dir_all(paths_v1::placement().batch_volume())?;
+    Ok(())
 }
@@ -0,0 +1,237 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    overflow store
+    ---
+    The rows of a model with `overflow` enabled are moved out of memory when the server runs low on it (see
+    [`crate::engine::core::model::overflow`]). The values of such a row are appended to the model's overflow file
+    and are read back the next time that the row is accessed. The file only ever holds a copy of what's already in
+    the model's batch journals, so it is never synced and is cleared every time the server starts.
+
+    Space is not reused: a slot that was read back (or whose row went away) is only counted as dead, and the file
+    is truncated once nothing in it is live anymore.
+*/
+
+use {
+    super::{
+        common::{
+            interface::fs::{File, FileExt, FileRead, FileSystem, FileWrite, FileWriteExt},
+            paths_v1,
+        },
+        common_encoding::r1::obj::cell::{self, StorageCellTypeID},
+    },
+    crate::{
+        engine::{
+            data::{cell::Datacell, uuid::Uuid},
+            mem::BufferedScanner,
+        },
+        IoResult,
+    },
+    parking_lot::Mutex,
+    std::{
+        io::{Error, ErrorKind},
+        mem::ManuallyDrop,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    },
+};
+
+/// Remove the overflow files that were left behind by the previous run
+pub fn reset_dir() -> IoResult<()> {
+    match FileSystem::remove_dir_all(&paths_v1::overflow_dir()) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// The overflow file of a model
+#[derive(Debug)]
+pub struct OverflowStore {
+    path: String,
+    file: Mutex<OverflowFile>,
+    live_slots: AtomicU64,
+    live_bytes: AtomicU64,
+    failed_reads: AtomicU64,
+}
+
+#[derive(Debug)]
+struct OverflowFile {
+    f: ManuallyDrop<File>,
+    end: u64,
+}
+
+impl OverflowStore {
+    /// Create the overflow file of the given model
+    pub fn create(model_uuid: Uuid) -> IoResult<Arc<Self>> {
+        FileSystem::create_dir_all(&paths_v1::overflow_dir())?;
+        let path = paths_v1::overflow_path(model_uuid);
+        let f = File::create(&path)?;
+        Ok(Arc::new(Self {
+            path,
+            file: Mutex::new(OverflowFile {
+                f: ManuallyDrop::new(f),
+                end: 0,
+            }),
+            live_slots: AtomicU64::new(0),
+            live_bytes: AtomicU64::new(0),
+            failed_reads: AtomicU64::new(0),
+        }))
+    }
+    /// Append the given cells, returning the slot that they can be read back from
+    pub fn write_cells<'a>(
+        self: &Arc<Self>,
+        cells: impl Iterator<Item = &'a Datacell>,
+    ) -> IoResult<OverflowSlot> {
+        let mut block = vec![];
+        for dc in cells {
+            cell::encode_compressed(&mut block, dc);
+        }
+        let mut file = self.file.lock();
+        let offset = file.end;
+        file.f.f_seek_start(offset)?;
+        file.f.fwrite_all(&block)?;
+        file.end += block.len() as u64;
+        self.live_slots.fetch_add(1, Ordering::Relaxed);
+        self.live_bytes
+            .fetch_add(block.len() as u64, Ordering::Relaxed);
+        Ok(OverflowSlot {
+            store: self.clone(),
+            offset,
+            len: block.len() as u64,
+        })
+    }
+    /// Returns the number of slots that haven't been read back yet
+    pub fn live_slots(&self) -> u64 {
+        self.live_slots.load(Ordering::Relaxed)
+    }
+    /// Returns the size (in bytes) of the slots that haven't been read back yet
+    pub fn live_bytes(&self) -> u64 {
+        self.live_bytes.load(Ordering::Relaxed)
+    }
+    /// Returns the number of times that a slot couldn't be read back. No more rows should be moved to a store that
+    /// failed a read
+    pub fn failed_reads(&self) -> u64 {
+        self.failed_reads.load(Ordering::Relaxed)
+    }
+    /// Returns the size of the file (including dead slots)
+    pub fn file_size(&self) -> u64 {
+        self.file.lock().end
+    }
+    fn read(&self, offset: u64, len: u64) -> IoResult<Vec<u8>> {
+        let mut block = vec![0; len as usize];
+        let mut file = self.file.lock();
+        file.f.f_seek_start(offset)?;
+        file.f.fread_exact(&mut block)?;
+        Ok(block)
+    }
+    fn release(&self, len: u64) {
+        self.live_bytes.fetch_sub(len, Ordering::Relaxed);
+        if self.live_slots.fetch_sub(1, Ordering::AcqRel) != 1 {
+            return;
+        }
+        // nothing in the file is live anymore, so start over (unless a slot was written in the meantime)
+        let mut file = self.file.lock();
+        if self.live_slots.load(Ordering::Acquire) != 0 {
+            return;
+        }
+        match file.f.f_truncate(0) {
+            Ok(()) => file.end = 0,
+            Err(e) => warn!("failed to truncate overflow file {}: {e}", self.path),
+        }
+    }
+}
+
+impl Drop for OverflowStore {
+    fn drop(&mut self) {
+        unsafe {
+            // UNSAFE(@ohsayan): the file is never used again, and it has to be closed before it's removed
+            ManuallyDrop::drop(&mut self.file.get_mut().f)
+        }
+        if let Err(e) = FileSystem::remove_file(&self.path) {
+            warn!("failed to remove overflow file {}: {e}", self.path)
+        }
+    }
+}
+
+/// The location of a block of cells in an overflow file. The space is released when the slot is dropped
+#[derive(Debug)]
+pub struct OverflowSlot {
+    store: Arc<OverflowStore>,
+    offset: u64,
+    len: u64,
+}
+
+impl OverflowSlot {
+    /// Read back the cells in this slot (there must be exactly `count` of them)
+    pub fn read_cells(&self, count: usize) -> IoResult<Vec<Datacell>> {
+        let block = self.store.read(self.offset, self.len)?;
+        let corrupted = || Error::new(ErrorKind::InvalidData, "corrupted overflow slot");
+        let mut scanner = BufferedScanner::new(&block);
+        let mut cells = Vec::with_capacity(count);
+        for _ in 0..count {
+            let tag = scanner
+                .try_next_byte()
+                .and_then(StorageCellTypeID::try_from_raw)
+                .ok_or_else(corrupted)?;
+            if !scanner.has_left(StorageCellTypeID::expect_atleast(tag.value_u8())) {
+                return Err(corrupted());
+            }
+            let dc = unsafe {
+                // UNSAFE(@ohsayan): +pretest
+                cell::decode_element::<Datacell, BufferedScanner>(&mut scanner, tag)
+            }
+            .map_err(|()| corrupted())?;
+            cells.push(dc);
+        }
+        if !scanner.eof() {
+            return Err(corrupted());
+        }
+        Ok(cells)
+    }
+    /// Returns the size of this slot (in bytes)
+    pub fn size(&self) -> u64 {
+        self.len
+    }
+    /// Note that the cells in this slot couldn't be read back
+    pub fn report_failed_read(&self) {
+        self.store.failed_reads.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl PartialEq for OverflowSlot {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.store, &other.store) & (self.offset == other.offset)
+    }
+}
+
+impl Drop for OverflowSlot {
+    fn drop(&mut self) {
+        self.store.release(self.len)
+    }
+}
@@ -36,7 +36,7 @@ use {
         },
         data::{cell::Datacell, tag::TagUnique},
         error::{RuntimeResult, StorageError},
-        idx::{MTIndex, STIndex, STIndexSeq},
+        idx::{STIndex, STIndexSeq},
         storage::{
             common::interface::fs::File,
             common_encoding::r1::{
@@ -209,14 +209,21 @@ impl DataBatchRestoreDriver {
         // pin model
         let g = unsafe { crossbeam_epoch::unprotected() };
         let mut pending_delete = HashMap::new();
-        let p_index = m.primary_index().__raw_index();
+        let p_index = m.primary_index();
         // scan rows
         for DecodedBatchEvent { txn_id, pk, kind } in events {
             match kind {
                 DecodedBatchEventKind::Insert(new_row) | DecodedBatchEventKind::Update(new_row) => {
                     // this is more like a "newrow"
-                    match p_index.mt_get_element(&pk, &g) {
-                        Some(row) if row.d_data().read().get_txn_revised() > txn_id => {
+                    match p_index.select_key(&pk, &g) {
+                        Some(row)
+                            if row
+                                .d_data()
+                                .read()
+                                .map_err(|_| StorageError::OverflowReadFailed)?
+                                .get_txn_revised()
+                                > txn_id =>
+                        {
                             // skewed
                             // resolve deltas if any
                             let _ = row.resolve_schema_deltas_and_freeze(m.delta_state());
@@ -224,7 +231,7 @@ impl DataBatchRestoreDriver {
                         }
                         Some(_) | None => {
                             // new row (logically)
-                            let _ = p_index.mt_delete(&pk, &g);
+                            let _ = p_index.delete_key(&pk, &g);
                             let mut data = DcFieldIndex::default();
                             for (field_name, new_data) in m
                                 .fields()
@@ -249,7 +256,7 @@ impl DataBatchRestoreDriver {
                             // resolve any deltas
                             let _ = row.resolve_schema_deltas_and_freeze(m.delta_state());
                             // put it back in (lol); blame @ohsayan for this joke
-                            p_index.mt_insert(row, &g);
+                            p_index.insert(row, &g);
                         }
                     }
                 }
@@ -273,14 +280,20 @@ impl DataBatchRestoreDriver {
             }
         }
         for (pk, txn_id) in pending_delete {
-            match p_index.mt_get(&pk, &g) {
+            match p_index.select_key(&pk, &g) {
                 Some(row) => {
-                    if row.read().get_txn_revised() > txn_id {
+                    if row
+                        .d_data()
+                        .read()
+                        .map_err(|_| StorageError::OverflowReadFailed)?
+                        .get_txn_revised()
+                        > txn_id
+                    {
                         // our delete "happened before" this row was inserted
                         continue;
                     }
                     // yup, go ahead and chuck it
-                    let _ = p_index.mt_delete(&pk, &g);
+                    let _ = p_index.delete_key(&pk, &g);
                 }
                 None => {
                     // since we never delete rows until here, this is quite impossible
@@ -0,0 +1,146 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    journal archive
+    ---
+    the archived copy of a journal (see `fractal::archive`). the archive is laid out like a copy of the data directory
+    (see `paths_v1::in_copy`), so it can be used as a snapshot (for instance, with `sky-inspect recover --snapshot`).
+    a copy is only ever appended to, and how much of a journal was archived is the length of its copy, so archiving
+    resumes where it left off after a restart. a journal that is shorter than its copy was rewritten since, so the copy
+    is moved aside (with the time as a suffix) and the journal is archived again from the start
+*/
+
+use {
+    crate::{
+        engine::{
+            storage::common::{
+                interface::fs::{File, FileExt, FileRead, FileSystem, FileWrite, FileWriteExt},
+                paths_v1,
+            },
+            RuntimeResult,
+        },
+        util::os,
+    },
+    std::io::ErrorKind,
+};
+
+/// The part of a journal that isn't archived yet
+#[derive(Debug, PartialEq)]
+pub struct ArchivePending {
+    /// where the bytes go in the copy
+    at: u64,
+    bytes: Vec<u8>,
+    /// set if the journal was rewritten since it was last archived
+    rewritten: bool,
+}
+
+impl ArchivePending {
+    pub fn len(&self) -> u64 {
+        self.bytes.len() as u64
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+/// A journal and its copy in the archive
+#[derive(Debug)]
+pub struct JournalArchive {
+    journal: String,
+    copy: String,
+}
+
+impl JournalArchive {
+    pub fn new(dir: &str, journal_path: &str) -> Self {
+        Self {
+            journal: journal_path.into(),
+            copy: paths_v1::in_copy(dir, journal_path),
+        }
+    }
+    pub fn copy_path(&self) -> &str {
+        &self.copy
+    }
+    /// Read what was appended to the journal since it was last archived, or [`None`] if the journal doesn't exist
+    /// (yet, or anymore). Call this while holding the lock of the journal's driver, so that what is read ends with a
+    /// complete batch (or event)
+    pub fn pending(&self) -> RuntimeResult<Option<ArchivePending>> {
+        let len = match FileSystem::file_len(&self.journal) {
+            Ok(len) => len,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let archived = match FileSystem::file_len(&self.copy) {
+            Ok(archived) => archived,
+            Err(e) if e.kind() == ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        let (at, rewritten) = if len < archived {
+            (0, true)
+        } else {
+            (archived, false)
+        };
+        let mut bytes = vec![0; (len - at) as usize];
+        if !bytes.is_empty() {
+            let mut f = File::open_read_only(&self.journal)?;
+            f.f_seek_start(at)?;
+            f.fread_exact(&mut bytes)?;
+        }
+        Ok(Some(ArchivePending {
+            at,
+            bytes,
+            rewritten,
+        }))
+    }
+    /// Append the pending bytes to the copy. No lock needs to be held
+    pub fn archive(&self, pending: ArchivePending) -> RuntimeResult<()> {
+        if pending.rewritten {
+            FileSystem::rename(
+                &self.copy,
+                &format!("{}.{}", self.copy, os::get_epoch_time_secs()),
+            )?;
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let mut f = if pending.at == 0 {
+            if let Some((parent, _)) = self.copy.rsplit_once('/') {
+                FileSystem::create_dir_all(parent)?;
+            }
+            match File::create(&self.copy) {
+                Ok(f) => f,
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => File::open(&self.copy)?,
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            File::open(&self.copy)?
+        };
+        f.f_seek_start(pending.at)?;
+        f.fwrite_all(&pending.bytes)?;
+        f.fsync_all()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    model journal entry points: writing a batch of rows and recovering (replaying) the journal, for the model shapes
+    of the encoding benchmarks. the criterion benchmarks in `benches/` drive these. the journals are written to the
+    file system, so the numbers include the disk
+*/
+
+use {
+    super::mdl_journal::{BatchStats, FullModel, ModelDriver},
+    crate::engine::{
+        core::{
+            index::{DcFieldIndex, PrimaryIndexKey, Row},
+            model::ModelData,
+        },
+        idx::{IndexBaseSpec, STIndex, STIndexSeq},
+        storage::{common_encoding::r1::benches::ModelShape, safe_interfaces::FileSystem},
+        sync::atm::cpin,
+    },
+};
+
+/// The number of rows in a batch
+pub const BATCH_SIZE: usize = 1000;
+
+/// A model of one of the shapes, with a batch of [`BATCH_SIZE`] rows
+pub struct Batch {
+    shape: ModelShape,
+    model: ModelData,
+}
+
+impl Batch {
+    pub fn new(shape: ModelShape) -> Self {
+        let model = shape.model_data();
+        let ds = model.delta_state();
+        let g = cpin();
+        for i in 0..BATCH_SIZE {
+            let mut cells = shape.row(i);
+            let pk = PrimaryIndexKey::try_from_dc(cells.remove(0)).unwrap();
+            let mut fields = DcFieldIndex::idx_init_cap(model.fields().st_len());
+            let layout = model
+                .fields()
+                .stseq_ord_key()
+                .filter(|field_id| field_id.as_str() != model.p_key());
+            for (field_id, cell) in layout.zip(cells) {
+                fields.st_insert(
+                    unsafe {
+                        // UNSAFE(@ohsayan): the row goes with the model that owns the allocation
+                        field_id.clone()
+                    },
+                    cell,
+                );
+            }
+            let row = Row::new(
+                pk,
+                fields,
+                ds.schema_current_version(),
+                ds.create_new_data_delta_version(),
+            );
+            assert!(model.primary_index().insert(row, &g));
+        }
+        Self { shape, model }
+    }
+}
+
+fn journal_path(path: &str, partition: usize) -> String {
+    format!("{path}.{partition}")
+}
+
+/// Write the batch to new journals (one for every partition) at `path`
+pub fn write(batch: &Batch, path: &str) {
+    let partitions = batch.model.primary_index().partitions();
+    let mut drivers =
+        ModelDriver::create_model_drivers(partitions, |partition| journal_path(path, partition))
+            .unwrap();
+    for (partition, driver) in drivers.iter_mut().enumerate() {
+        driver
+            .commit_with_ctx(FullModel::new(&batch.model, partition), BatchStats::new())
+            .unwrap();
+        ModelDriver::close_driver(driver).unwrap();
+    }
+}
+
+/// Recover the journals that [`write`] wrote into a new model. Returns the number of rows that were restored
+pub fn recover(batch: &Batch, path: &str) -> usize {
+    let model = batch.shape.model_data();
+    let mut drivers =
+        ModelDriver::open_model_drivers(&model, |partition| journal_path(path, partition)).unwrap();
+    drivers
+        .iter_mut()
+        .for_each(|driver| ModelDriver::close_driver(driver).unwrap());
+    model.primary_index().count()
+}
+
+/// Remove the journals that [`write`] wrote
+pub fn remove(batch: &Batch, path: &str) {
+    for partition in 0..batch.model.primary_index().partitions() {
+        FileSystem::remove_file(&journal_path(path, partition)).unwrap();
+    }
+}
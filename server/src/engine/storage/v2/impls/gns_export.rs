@@ -0,0 +1,223 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    schema export
+    ---
+    a standalone package with the schema of an instance (its spaces, its models along with their secondary indexes
+    and quotas, and every user but root, along with the statements that they stored and their quotas) and none of
+    the data, that can be used to bootstrap a new instance:
+
+    [magic:8B][version:8B][event count:8B]
+    [event code:8B][event size:8B][event] (for every event)
+    [checksum:8B]
+
+    the events are encoded exactly like they are in the GNS journal, and the checksum is the CRC64 of everything
+    that comes before it. root is left out because every instance configures its own root password
+*/
+
+use {
+    super::gns_log::GNSEventLog,
+    crate::{
+        engine::{
+            core::{system_db::SystemDatabase, GNSData},
+            error::StorageError,
+            idx::STIndexSeq,
+            mem::BufferedScanner,
+            storage::{
+                common::{
+                    checksum::SCrc64,
+                    interface::fs::{File, FileSystem, FileWrite, FileWriteExt},
+                },
+                common_encoding::r1::impls::gns::GNSEvent,
+                v2::raw::journal::EventLogSpec,
+            },
+            txn::{
+                gns::{
+                    model::{AlterModelQuotaTxn, CreateIndexTxn, CreateModelTxn},
+                    space::CreateSpaceTxn,
+                    sysctl::{AlterUserQuotaTxn, CreateStatementTxn, CreateUserTxn},
+                    GNSTransactionCode,
+                },
+                ModelIDRef, SpaceIDRef,
+            },
+            RuntimeResult,
+        },
+        util::compiler::TaggedEnum,
+    },
+};
+
+/// The version of the export format. This is bumped whenever the layout of the package changes incompatibly
+pub const GNS_EXPORT_FORMAT_VERSION: u64 = 1;
+const GNS_EXPORT_MAGIC: [u8; 8] = *b"SKYGNSEX";
+
+/// A schema package being built (see the module docs for the layout)
+pub struct GNSExport {
+    events: u64,
+    buf: Vec<u8>,
+}
+
+impl GNSExport {
+    /// Encode the schema of the given namespace
+    pub fn encode(gns: &GNSData) -> Vec<u8> {
+        let mut export = Self {
+            events: 0,
+            buf: vec![],
+        };
+        let spaces = gns.idx().read();
+        let models = gns.idx_models().read();
+        for (space_name, space) in spaces.iter() {
+            export.push(CreateSpaceTxn::new(space.props(), space_name, space));
+        }
+        for (id, model) in models.iter() {
+            let space = spaces.get(id.space()).unwrap();
+            let model = model.data();
+            export.push(CreateModelTxn::new(
+                SpaceIDRef::new(id.space(), space),
+                id.entity(),
+                model,
+            ));
+            for (index_name, index) in model.secondary_indexes().stseq_ord_kv() {
+                let filter = index.filter().map(|filter| filter.to_raw());
+                export.push(CreateIndexTxn::new(
+                    ModelIDRef::new_ref(id.space(), space, id.entity(), model),
+                    index_name,
+                    index.fields(),
+                    index.include(),
+                    filter.as_ref(),
+                    index.collation(),
+                ));
+            }
+            let quota = model.quota();
+            if !quota.is_unlimited() {
+                export.push(AlterModelQuotaTxn::new(
+                    ModelIDRef::new_ref(id.space(), space, id.entity(), model),
+                    quota.reads(),
+                    quota.writes(),
+                ));
+            }
+        }
+        for (username, user) in gns.sys_db().users().read().iter() {
+            if username.as_ref() != SystemDatabase::ROOT_ACCOUNT {
+                export.push(CreateUserTxn::new(username, user.hash()));
+                let mut statements: Vec<_> = user.statements().iter().collect();
+                statements.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (name, statement) in statements {
+                    export.push(CreateStatementTxn::new(username, name, statement.body()));
+                }
+                let quota = user.quota();
+                if !quota.is_unlimited() {
+                    export.push(AlterUserQuotaTxn::new(
+                        username,
+                        quota.reads(),
+                        quota.writes(),
+                    ));
+                }
+            }
+        }
+        export.finish()
+    }
+    /// Encode the schema of the given namespace into a new file (an existing file is never overwritten)
+    pub fn write(path: &str, gns: &GNSData) -> RuntimeResult<()> {
+        let package = Self::encode(gns);
+        let mut f = File::create(path)?;
+        f.fwrite_all(&package)?;
+        f.fsync_all()?;
+        Ok(())
+    }
+    /// Decode a package into a new namespace, after verifying its checksum and version. Every event is applied just
+    /// like it would be on restore, so the result is a consistent namespace (with uninitialized model drivers)
+    pub fn decode(package: &[u8]) -> RuntimeResult<GNSData> {
+        if package.len() < sizeof!(u64) {
+            return Err(StorageError::GNSExportCorrupted.into());
+        }
+        let (body, checksum) = package.split_at(package.len() - sizeof!(u64));
+        let mut crc = SCrc64::new();
+        crc.update(body);
+        if Some(crc.finish()) != read_u64(&mut BufferedScanner::new(checksum)) {
+            return Err(StorageError::GNSExportCorrupted.into());
+        }
+        let mut scanner = BufferedScanner::new(body);
+        if scanner.try_next_variable_block(GNS_EXPORT_MAGIC.len()) != Some(&GNS_EXPORT_MAGIC[..]) {
+            return Err(StorageError::GNSExportCorrupted.into());
+        }
+        match read_u64(&mut scanner) {
+            Some(GNS_EXPORT_FORMAT_VERSION) => {}
+            Some(_) => return Err(StorageError::HeaderDecodeVersionMismatch.into()),
+            None => return Err(StorageError::GNSExportCorrupted.into()),
+        }
+        let gns = GNSData::empty();
+        let events = read_u64(&mut scanner).ok_or(StorageError::GNSExportCorrupted)?;
+        for _ in 0..events {
+            let (Some(code), Some(size)) = (read_u64(&mut scanner), read_u64(&mut scanner)) else {
+                return Err(StorageError::GNSExportCorrupted.into());
+            };
+            let code = u8::try_from(code)
+                .ok()
+                .and_then(GNSTransactionCode::try_from_raw)
+                .ok_or(StorageError::GNSExportCorrupted)?;
+            let event = usize::try_from(size)
+                .ok()
+                .and_then(|size| scanner.try_next_variable_block(size))
+                .ok_or(StorageError::GNSExportCorrupted)?;
+            GNSEventLog::DECODE_DISPATCH[code.dscr_u64() as usize](&gns, event.to_vec())?;
+        }
+        if scanner.eof() {
+            Ok(gns)
+        } else {
+            Err(StorageError::GNSExportCorrupted.into())
+        }
+    }
+    /// Read and decode a package (see [`Self::decode`])
+    pub fn read(path: &str) -> RuntimeResult<GNSData> {
+        Self::decode(&FileSystem::read(path)?)
+    }
+    fn push<T: GNSEvent>(&mut self, event: T) {
+        let mut payload = vec![];
+        T::encode_event(event, &mut payload);
+        self.buf.extend(T::CODE.dscr_u64().to_le_bytes());
+        self.buf.extend((payload.len() as u64).to_le_bytes());
+        self.buf.extend(payload);
+        self.events += 1;
+    }
+    fn finish(self) -> Vec<u8> {
+        let mut package = Vec::with_capacity(self.buf.len() + 4 * sizeof!(u64));
+        package.extend(GNS_EXPORT_MAGIC);
+        package.extend(GNS_EXPORT_FORMAT_VERSION.to_le_bytes());
+        package.extend(self.events.to_le_bytes());
+        package.extend(self.buf);
+        let mut crc = SCrc64::new();
+        crc.update(&package);
+        package.extend(crc.finish().to_le_bytes());
+        package
+    }
+}
+
+fn read_u64(scanner: &mut BufferedScanner) -> Option<u64> {
+    scanner
+        .try_next_variable_block(sizeof!(u64))
+        .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+}
@@ -33,16 +33,21 @@ use {
         engine::{
             core::GNSData,
             storage::{
+                common::paths_v1,
                 common_encoding::r1::impls::gns::GNSEvent,
-                v2::raw::journal::{self, EventLogDriver, JournalAdapterEvent},
+                v2::raw::journal::{self, EventLogDriver, JournalAdapterEvent, ReplayAction},
             },
             txn::gns::{
                 model::{
-                    AlterModelAddTxn, AlterModelRemoveTxn, AlterModelUpdateTxn, CreateModelTxn,
-                    DropModelTxn,
+                    AlterModelAddTxn, AlterModelQuotaTxn, AlterModelRemoveTxn, AlterModelUpdateTxn,
+                    CreateIndexTxn, CreateModelTxn, CreateWebhookTxn, DropIndexTxn, DropModelTxn,
+                    DropWebhookTxn,
                 },
                 space::{AlterSpaceTxn, CreateSpaceTxn, DropSpaceTxn},
-                sysctl::{AlterUserTxn, CreateUserTxn, DropUserTxn},
+                sysctl::{
+                    AlterUserQuotaTxn, AlterUserTxn, CreateStatementTxn, CreateUserTxn,
+                    DropStatementTxn, DropUserTxn,
+                },
                 GNSTransaction, GNSTransactionCode,
             },
             RuntimeResult,
@@ -60,19 +65,36 @@ pub type GNSDriver = EventLogDriver<GNSEventLog>;
 pub struct GNSEventLog;
 
 impl GNSDriver {
-    const FILE_PATH: &'static str = "gns.db-tlog";
     pub fn open_gns_with_name(name: &str, gs: &GNSData) -> RuntimeResult<Self> {
         journal::open_journal(name, gs)
     }
     pub fn open_gns(gs: &GNSData) -> RuntimeResult<Self> {
-        Self::open_gns_with_name(Self::FILE_PATH, gs)
+        Self::open_gns_with_name(&paths_v1::gns_path(), gs)
+    }
+    /// Load the event log into the given state without opening it for writes. Returns the number of events
+    pub fn decode_gns_with_name(name: &str, gs: &GNSData) -> RuntimeResult<u64> {
+        Self::decode_gns_with(name, gs, |_| ReplayAction::Apply).map(|(events, _)| events)
+    }
+    /// Load the event log like [`Self::decode_gns_with_name`] does, asking `action` what to do with every event
+    /// (see [`journal::decode_journal_with`]). Returns the number of events applied and the ids of those skipped
+    pub fn decode_gns_with(
+        name: &str,
+        gs: &GNSData,
+        action: impl FnMut(u64) -> ReplayAction,
+    ) -> RuntimeResult<(u64, Vec<u64>)> {
+        let mut events = 0;
+        let skipped =
+            journal::decode_journal_with::<EventLogAdapter<GNSEventLog>>(name, gs, action, |_| {
+                events += 1
+            })?;
+        Ok((events, skipped))
     }
     pub fn create_gns_with_name(name: &str) -> RuntimeResult<Self> {
         journal::create_journal(name)
     }
     /// Create a new event log
     pub fn create_gns() -> RuntimeResult<Self> {
-        Self::create_gns_with_name(Self::FILE_PATH)
+        Self::create_gns_with_name(&paths_v1::gns_path())
     }
 }
 
@@ -100,6 +122,14 @@ impl EventLogSpec for GNSEventLog {
         CreateUserTxn,
         AlterUserTxn,
         DropUserTxn,
+        CreateIndexTxn,
+        DropIndexTxn,
+        CreateStatementTxn,
+        DropStatementTxn,
+        CreateWebhookTxn,
+        DropWebhookTxn,
+        AlterUserQuotaTxn,
+        AlterModelQuotaTxn,
     ];
 }
 
@@ -111,3 +141,24 @@ impl<T: GNSEvent> JournalAdapterEvent<EventLogAdapter<GNSEventLog>> for T {
         T::encode_event(self, b)
     }
 }
+
+/// An event that was already encoded (by a [`GNSJournal`](crate::engine::storage::driver::GNSJournal))
+pub struct EncodedGNSEvent {
+    code: GNSTransactionCode,
+    payload: Vec<u8>,
+}
+
+impl EncodedGNSEvent {
+    pub fn new(code: GNSTransactionCode, payload: Vec<u8>) -> Self {
+        Self { code, payload }
+    }
+}
+
+impl JournalAdapterEvent<EventLogAdapter<GNSEventLog>> for EncodedGNSEvent {
+    fn md(&self) -> u64 {
+        self.code.dscr_u64()
+    }
+    fn write_buffered(self, b: &mut Vec<u8>, _: ()) {
+        b.extend_from_slice(&self.payload)
+    }
+}
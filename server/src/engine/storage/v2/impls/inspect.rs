@@ -0,0 +1,555 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    offline inspection
+    ---
+    `sky-inspect` reads a data directory without running the server, for support and forensics. files are only ever
+    opened for reads and journals are decoded where they are (without the recovery that the server runs on startup),
+    so it is safe to point it at a read-only copy. it must not be run against the data directory of a server that is
+    running, since a batch that is being written would look corrupted. everything is printed as JSON lines:
+
+    - `gns`: the global journal, then every space, model (with its declaration and properties) and user
+    - `journal <space>.<model>`: for every partition of the model, the batches and events in its journal by kind
+    - `rows <space>.<model> [--key <key>] [--limit <n>]`: the rows as they would be restored, ordered by key
+    - `replay [<space>.<model>] [--until <lsn>] [--skip <lsn>]...`: rehearse a recovery in memory. without a model,
+    the global journal is replayed and every model that it leaves behind is listed with its row count; with one, its
+    journals are. replays stop after the event `--until` and leave out (without verifying) every event passed to
+    `--skip`, so a corrupted event can be skipped as long as its end can still be found. journals don't record when
+    an event was written, so replays can only be bounded by lsn (the lsns are in the output of `journal`). a model's
+    partitions have a journal each and hence lsns of their own; use `--partition <n>` to replay only one of them
+    - `recover <space>.<model> [--snapshot <dir>]... [--until <lsn>] [--skip <lsn>]... [--rows]`: recover a model to
+    a point in time. every `--snapshot` is a copy of the data directory (taken while frozen), and every partition
+    starts from the latest snapshot whose journal ends by `--until`. the events that came after it are then replayed
+    from the journal in this directory, up to `--until`. since journals are only ever appended to, a snapshot's
+    journal is the start of the journal that it was copied from, so the events that it covers are read past without
+    being verified (even if they were corrupted here, as long as their end can be found). the exact event that every
+    partition was recovered to is reported, and `--rows` prints the rows that recovery leaves behind like `rows` does
+*/
+
+use {
+    super::{
+        gns_log::GNSDriver,
+        mdl_export::{encode_str, JournalExport, PersistedRows},
+    },
+    crate::engine::{
+        core::{model, model::Model, EntityIDRef, GNSData},
+        error::{ErrorKind, RuntimeResult},
+        fractal::error::Error,
+        idx::STIndexSeq,
+        storage::{
+            common::{interface::fs::FileSystem, paths_v1},
+            v2::raw::journal::ReplayAction,
+        },
+    },
+    std::fmt::Write,
+};
+
+const USAGE: &str = "Usage: sky-inspect [--journal-volume <dir>] [--batch-volume <dir>] \
+    (gns | journal <space>.<model> | rows <space>.<model> [--key <key>] [--limit <n>] \
+    | replay [<space>.<model> [--partition <n>]] [--until <lsn>] [--skip <lsn>]... \
+    | recover <space>.<model> [--snapshot <dir>]... [--until <lsn>] [--skip <lsn>]... [--rows])";
+
+#[derive(Debug, Default)]
+/// The events to replay in a rehearsal: everything up to (and including) `until`, except the events in `skip`
+pub struct ReplayBounds {
+    until: Option<u64>,
+    skip: Vec<u64>,
+}
+
+impl ReplayBounds {
+    pub fn new(until: Option<u64>, skip: Vec<u64>) -> Self {
+        Self { until, skip }
+    }
+    fn action(&self, lsn: u64) -> ReplayAction {
+        if self.until.is_some_and(|until| lsn > until) {
+            ReplayAction::Stop
+        } else if self.skip.contains(&lsn) {
+            ReplayAction::Skip
+        } else {
+            ReplayAction::Apply
+        }
+    }
+}
+
+/// A data directory, loaded for inspection
+pub struct Inspector {
+    gns: GNSData,
+    gns_path: String,
+    gns_events: u64,
+    /// the events of the global journal that were left out
+    gns_skipped: Vec<u64>,
+    /// set if this is a copy of a data directory (see [`paths_v1::in_copy`])
+    root: Option<Box<str>>,
+}
+
+impl Inspector {
+    /// Load the global journal at the given path
+    pub fn load(gns_path: &str) -> RuntimeResult<Self> {
+        Self::load_bounded(gns_path, &ReplayBounds::default())
+    }
+    /// Load the events of the global journal at the given path that are within `bounds`
+    pub fn load_bounded(gns_path: &str, bounds: &ReplayBounds) -> RuntimeResult<Self> {
+        let gns = GNSData::empty();
+        let (gns_events, gns_skipped) =
+            GNSDriver::decode_gns_with(gns_path, &gns, |lsn| bounds.action(lsn))?;
+        model::link_views(&mut gns.idx_models().write());
+        Ok(Self {
+            gns,
+            gns_path: gns_path.into(),
+            gns_events,
+            gns_skipped,
+            root: None,
+        })
+    }
+    /// Load a copy of a data directory (such as a snapshot) at `root`
+    pub fn load_copy(root: &str) -> RuntimeResult<Self> {
+        let mut inspector = Self::load(&paths_v1::in_copy(root, &paths_v1::gns_path()))?;
+        inspector.root = Some(root.into());
+        Ok(inspector)
+    }
+    /// Returns true if the model is in the global journal (views aren't, since they have no journal)
+    pub fn has_model(&self, entity: EntityIDRef) -> bool {
+        self.gns
+            .idx_models()
+            .read()
+            .get(&entity)
+            .is_some_and(|model| model.data().props().view().is_none())
+    }
+    /// Returns the global journal, spaces, models and users
+    pub fn describe_gns(&self) -> String {
+        let mut out = String::from("{\"journal\":");
+        encode_str(&mut out, &self.gns_path);
+        let _ = writeln!(out, ",\"events\":{}}}", self.gns_events);
+        let spaces = self.gns.idx().read();
+        let mut space_names: Vec<_> = spaces.keys().collect();
+        space_names.sort();
+        for name in space_names {
+            let space = &spaces[name];
+            let mut models: Vec<_> = space.models().iter().collect();
+            models.sort();
+            out.push_str("{\"space\":");
+            encode_str(&mut out, name);
+            let _ = write!(out, ",\"uuid\":\"{}\",\"models\":[", space.get_uuid());
+            for (i, model) in models.into_iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                encode_str(&mut out, model);
+            }
+            out.push_str("]}\n");
+        }
+        let models = self.gns.idx_models().read();
+        let mut ids: Vec<_> = models.keys().collect();
+        ids.sort_by(|a, b| (a.space(), a.entity()).cmp(&(b.space(), b.entity())));
+        for id in ids {
+            let mdl = models[id].data();
+            out.push_str("{\"model\":");
+            encode_str(&mut out, &format!("{}.{}", id.space(), id.entity()));
+            let _ = write!(out, ",\"uuid\":\"{}\",\"decl\":", mdl.get_uuid());
+            encode_str(&mut out, mdl.describe());
+            let _ = write!(
+                out,
+                ",\"properties\":{},\"indexes\":[",
+                mdl.props().describe()
+            );
+            for (i, index) in mdl.secondary_indexes().stseq_ord_key().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                encode_str(&mut out, index);
+            }
+            out.push_str("]}\n");
+        }
+        let users = self.gns.sys_db().users().read();
+        let mut users: Vec<_> = users.keys().collect();
+        users.sort();
+        for user in users {
+            out.push_str("{\"user\":");
+            encode_str(&mut out, user);
+            out.push_str("}\n");
+        }
+        out
+    }
+    /// Returns the batches and events in the journal of every partition of the model
+    pub fn describe_journal(&self, entity: &str) -> RuntimeResult<String> {
+        self.with_model(entity, |model, journals| {
+            let mut out = String::new();
+            for (partition, path) in journals.iter().enumerate() {
+                let summary = JournalExport::in_place(model, path).summarize()?;
+                let _ = write!(out, "{{\"partition\":{partition},\"path\":");
+                encode_str(&mut out, path);
+                let _ = writeln!(
+                    out,
+                    ",\"bytes\":{},\"journal\":{}}}",
+                    FileSystem::file_len(path)?,
+                    summary.describe()
+                );
+            }
+            Ok(out)
+        })
+    }
+    /// Returns the rows of the model (or only the row with the given key), as they would be restored
+    pub fn dump_rows(
+        &self,
+        entity: &str,
+        key: Option<&str>,
+        limit: Option<usize>,
+    ) -> RuntimeResult<String> {
+        self.with_rows(entity, |model, rows| rows.encode_rows(model, key, limit))
+    }
+    /// Call `f` with the model and the rows that it would be restored with
+    pub fn with_rows<T>(
+        &self,
+        entity: &str,
+        f: impl FnOnce(&Model, PersistedRows) -> T,
+    ) -> RuntimeResult<T> {
+        self.with_model(entity, |model, journals| {
+            let mut rows = PersistedRows::new();
+            for path in journals {
+                JournalExport::in_place(model, path).replay(&mut rows)?;
+            }
+            Ok(f(model, rows))
+        })
+    }
+    /// Returns the global journal (with the events that were skipped) and every model with the number of rows that
+    /// it would be restored with, or the error that its journals would fail with
+    pub fn describe_replay(&self) -> String {
+        let mut out = String::from("{\"journal\":");
+        encode_str(&mut out, &self.gns_path);
+        let _ = writeln!(
+            out,
+            ",\"events\":{},\"skipped\":{:?}}}",
+            self.gns_events, self.gns_skipped
+        );
+        let mut models: Vec<(String, String)> = self
+            .gns
+            .idx_models()
+            .read()
+            .iter()
+            .filter(|(_, model)| model.data().props().view().is_none())
+            .map(|(id, model)| {
+                (
+                    format!("{}.{}", id.space(), id.entity()),
+                    model.data().describe().into(),
+                )
+            })
+            .collect();
+        models.sort();
+        for (entity, decl) in models {
+            let rows = self.with_rows(&entity, |_, rows| rows.remaining().0);
+            out.push_str("{\"model\":");
+            encode_str(&mut out, &entity);
+            out.push_str(",\"decl\":");
+            encode_str(&mut out, &decl);
+            match rows {
+                Ok(rows) => {
+                    let _ = writeln!(out, ",\"rows\":{rows}}}");
+                }
+                Err(e) => {
+                    out.push_str(",\"error\":");
+                    encode_str(&mut out, &e.to_string());
+                    out.push_str("}\n");
+                }
+            }
+        }
+        out
+    }
+    /// Returns the number of batches and events replayed from the model's journals (or only from the journal of
+    /// `partition`) within `bounds`, and the number of rows that they leave behind
+    pub fn replay_model(
+        &self,
+        entity: &str,
+        partition: Option<usize>,
+        bounds: &ReplayBounds,
+    ) -> RuntimeResult<String> {
+        self.with_model(entity, |model, journals| {
+            if partition.is_some_and(|partition| partition >= journals.len()) {
+                return Err(ErrorKind::Other(format!(
+                    "`{entity}` has {} partition(s)",
+                    journals.len()
+                ))
+                .into());
+            }
+            let mut out = String::new();
+            let mut rows = PersistedRows::new();
+            for (p, path) in journals.iter().enumerate() {
+                if partition.is_some_and(|partition| partition != p) {
+                    continue;
+                }
+                let (batches, events) = (rows.batches(), rows.events());
+                let skipped = JournalExport::in_place(model, path)
+                    .replay_with(&mut rows, |lsn| bounds.action(lsn))?;
+                let _ = writeln!(
+                    out,
+                    "{{\"partition\":{p},\"batches\":{},\"events\":{},\"skipped\":{skipped:?}}}",
+                    rows.batches() - batches,
+                    rows.events() - events
+                );
+            }
+            out.push_str("{\"model\":");
+            encode_str(&mut out, entity);
+            out.push_str(",\"decl\":");
+            encode_str(&mut out, model.data().describe());
+            let _ = writeln!(out, ",\"rows\":{}}}", rows.remaining().0);
+            Ok(out)
+        })
+    }
+    /// Recover the model to the point in time given by `bounds`: for every partition, the latest of the `snapshots`
+    /// whose journal ends within `bounds` is replayed first, and then the events of this directory's journal that
+    /// came after the snapshot (and are within `bounds`). Returns the snapshot that every partition started from and
+    /// the exact event that it was recovered to, followed by the number of rows left behind (and the rows
+    /// themselves if `with_rows` is set)
+    pub fn recover_model(
+        &self,
+        entity: &str,
+        snapshots: &[Inspector],
+        bounds: &ReplayBounds,
+        with_rows: bool,
+    ) -> RuntimeResult<String> {
+        self.with_model(entity, |model, journals| {
+            let mut out = String::new();
+            let mut rows = PersistedRows::new();
+            for (p, path) in journals.iter().enumerate() {
+                // the latest snapshot of this partition that doesn't go past the recovery point
+                let mut base: Option<(&Inspector, String, Option<u64>)> = None;
+                for snapshot in snapshots {
+                    let Some((snapshot_path, lsn)) = snapshot.snapshot_journal(entity, model, p)?
+                    else {
+                        continue;
+                    };
+                    let is_within =
+                        lsn.map_or(true, |lsn| bounds.until.map_or(true, |until| lsn <= until));
+                    if is_within & base.as_ref().map_or(true, |(_, _, base)| lsn > *base) {
+                        base = Some((snapshot, snapshot_path, lsn));
+                    }
+                }
+                let base_lsn = base.as_ref().and_then(|(_, _, lsn)| *lsn);
+                if let Some((_, snapshot_path, _)) = &base {
+                    JournalExport::in_place(model, snapshot_path).replay(&mut rows)?;
+                }
+                // journals are only ever appended to, so the snapshot's journal is where this one starts
+                let batches = rows.batches();
+                let skipped =
+                    JournalExport::in_place(model, path).replay_with(&mut rows, |lsn| {
+                        if base_lsn.is_some_and(|base| lsn <= base) {
+                            ReplayAction::Skip
+                        } else {
+                            bounds.action(lsn)
+                        }
+                    })?;
+                let recovered_lsn = rows.take_last_lsn();
+                let _ = write!(out, "{{\"partition\":{p},\"snapshot\":");
+                match &base {
+                    Some((snapshot, _, _)) => {
+                        encode_str(&mut out, snapshot.root.as_deref().unwrap_or("."))
+                    }
+                    None => out.push_str("null"),
+                }
+                let _ = writeln!(
+                    out,
+                    ",\"snapshot_lsn\":{},\"batches\":{},\"skipped\":{:?},\"recovered_lsn\":{}}}",
+                    encode_lsn(base_lsn),
+                    rows.batches() - batches,
+                    skipped
+                        .into_iter()
+                        .filter(|lsn| base_lsn.map_or(true, |base| *lsn > base))
+                        .collect::<Vec<_>>(),
+                    encode_lsn(recovered_lsn),
+                );
+            }
+            out.push_str("{\"model\":");
+            encode_str(&mut out, entity);
+            out.push_str(",\"decl\":");
+            encode_str(&mut out, model.data().describe());
+            let _ = writeln!(out, ",\"rows\":{}}}", rows.remaining().0);
+            if with_rows {
+                out.push_str(&rows.encode_rows(model, None, None));
+            }
+            Ok(out)
+        })
+    }
+    /// Returns the path of the journal of the model's partition in this copy of a data directory and the event that
+    /// committed its last batch, or [`None`] if the copy doesn't have the same model (with the same partitions)
+    fn snapshot_journal(
+        &self,
+        entity: &str,
+        model: &Model,
+        partition: usize,
+    ) -> RuntimeResult<Option<(String, Option<u64>)>> {
+        let Some((space_name, model_name)) = entity.split_once('.') else {
+            return Ok(None);
+        };
+        if !self.has_model(EntityIDRef::new(space_name, model_name)) {
+            return Ok(None);
+        }
+        self.with_model(entity, |snapshot_model, journals| {
+            let is_same = (snapshot_model.data().get_uuid() == model.data().get_uuid())
+                & (journals.len() == model.data().primary_index().partitions());
+            if !is_same {
+                return Ok(None);
+            }
+            let path = journals[partition].clone();
+            let summary = JournalExport::in_place(snapshot_model, &path).summarize()?;
+            Ok(Some((path, summary.last_lsn())))
+        })
+    }
+    /// Call `f` with the model and the paths of its journals (one for each partition)
+    fn with_model<T>(
+        &self,
+        entity: &str,
+        f: impl FnOnce(&Model, &[String]) -> RuntimeResult<T>,
+    ) -> RuntimeResult<T> {
+        let Some((space_name, model_name)) = entity.split_once('.') else {
+            return Err(usage(&format!(
+                "expected `<space>.<model>`, got `{entity}`"
+            )));
+        };
+        let spaces = self.gns.idx().read();
+        let models = self.gns.idx_models().read();
+        let (Some(space), Some(model)) = (
+            spaces.get(space_name),
+            models.get(&EntityIDRef::new(space_name, model_name)),
+        ) else {
+            return Err(
+                ErrorKind::Other(format!("no model `{entity}` in the global journal")).into(),
+            );
+        };
+        if model.data().props().view().is_some() {
+            return Err(
+                ErrorKind::Other(format!("`{entity}` is a view, so it has no journal")).into(),
+            );
+        }
+        let journals: Vec<String> = (0..model.data().primary_index().partitions())
+            .map(|partition| {
+                let path = paths_v1::model_partition_path(
+                    space_name,
+                    space.get_uuid(),
+                    model_name,
+                    model.data().get_uuid(),
+                    partition,
+                );
+                match self.root.as_deref() {
+                    Some(root) => paths_v1::in_copy(root, &path),
+                    None => path,
+                }
+            })
+            .collect();
+        f(model, &journals)
+    }
+}
+
+/// Run the inspector (`sky-inspect`) with the given arguments
+pub fn run(args: Vec<String>) -> RuntimeResult<()> {
+    let (mut journal_volume, mut batch_volume) = (None, None);
+    let (mut key, mut limit) = (None, None);
+    let (mut until, mut skip, mut partition) = (None, vec![], None);
+    let (mut snapshots, mut with_rows) = (vec![], false);
+    let mut command = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--journal-volume" => journal_volume = Some(value(&mut args, &arg)?),
+            "--batch-volume" => batch_volume = Some(value(&mut args, &arg)?),
+            "--key" => key = Some(value(&mut args, &arg)?),
+            "--limit" => limit = Some(number(&mut args, &arg)?),
+            "--until" => until = Some(number(&mut args, &arg)?),
+            "--skip" => skip.push(number(&mut args, &arg)?),
+            "--partition" => partition = Some(number(&mut args, &arg)?),
+            "--snapshot" => snapshots.push(value(&mut args, &arg)?),
+            "--rows" => with_rows = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            flag if flag.starts_with("--") => {
+                return Err(usage(&format!("unknown argument `{flag}`")))
+            }
+            _ => command.push(arg),
+        }
+    }
+    assert!(
+        paths_v1::set_placement(paths_v1::Placement::new(
+            journal_volume.as_deref(),
+            batch_volume.as_deref(),
+        )),
+        "placement already set"
+    );
+    let command: Vec<&str> = command.iter().map(String::as_str).collect();
+    let inspector = || Inspector::load(&paths_v1::gns_path());
+    let is_bounded = until.is_some() | !skip.is_empty() | partition.is_some();
+    let is_recovery = !snapshots.is_empty() | with_rows;
+    let bounds = ReplayBounds::new(until, skip);
+    let out = match command.as_slice() {
+        ["gns"] if key.is_none() & limit.is_none() & !is_bounded & !is_recovery => {
+            inspector()?.describe_gns()
+        }
+        ["journal", entity] if key.is_none() & limit.is_none() & !is_bounded & !is_recovery => {
+            inspector()?.describe_journal(entity)?
+        }
+        ["rows", entity] if !is_bounded & !is_recovery => {
+            inspector()?.dump_rows(entity, key.as_deref(), limit)?
+        }
+        ["replay"] if key.is_none() & limit.is_none() & partition.is_none() & !is_recovery => {
+            Inspector::load_bounded(&paths_v1::gns_path(), &bounds)?.describe_replay()
+        }
+        ["replay", entity] if key.is_none() & limit.is_none() & !is_recovery => {
+            inspector()?.replay_model(entity, partition, &bounds)?
+        }
+        ["recover", entity] if key.is_none() & limit.is_none() & partition.is_none() => {
+            let snapshots = snapshots
+                .iter()
+                .map(|root| Inspector::load_copy(root))
+                .collect::<RuntimeResult<Vec<_>>>()?;
+            inspector()?.recover_model(entity, &snapshots, &bounds, with_rows)?
+        }
+        _ => return Err(usage("expected a command")),
+    };
+    print!("{out}");
+    Ok(())
+}
+
+fn encode_lsn(lsn: Option<u64>) -> String {
+    lsn.map_or_else(|| "null".into(), |lsn| lsn.to_string())
+}
+
+fn value(args: &mut impl Iterator<Item = String>, arg: &str) -> RuntimeResult<String> {
+    args.next()
+        .ok_or_else(|| usage(&format!("`{arg}` needs a value")))
+}
+
+fn number<T: std::str::FromStr>(
+    args: &mut impl Iterator<Item = String>,
+    arg: &str,
+) -> RuntimeResult<T> {
+    value(args, arg)?
+        .parse()
+        .map_err(|_| usage(&format!("`{arg}` needs a number")))
+}
+
+fn usage(problem: &str) -> Error {
+    ErrorKind::Other(format!("{problem}. {USAGE}")).into()
+}
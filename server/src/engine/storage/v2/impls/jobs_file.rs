@@ -0,0 +1,201 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    jobs file
+    ---
+    the records of the jobs that are running or that ended recently (see `fractal::jobs`). the file is rewritten in
+    full (into a temporary file that then replaces it) whenever a job starts or ends:
+
+    [magic:8B][version:8B][record count:8B]
+    [id:8B][kind:1B][status:1B][error:1B][started at:8B][finished at:8B][done:8B][total:8B]
+    [owner size:8B][owner][target size:8B][target][result size + 1:8B][result] (for every record)
+    [checksum:8B]
+
+    the finished at of a job that is running is zero, and so is the owner size of a job that has no owner. the result
+    size is zero if the job has no result. the checksum is the CRC64 of everything that comes before it
+*/
+
+use crate::engine::{
+    error::StorageError,
+    fractal::jobs::{JobKind, JobRecord, JobStatus},
+    mem::BufferedScanner,
+    storage::common::{
+        checksum::SCrc64,
+        interface::fs::{File, FileSystem, FileWrite, FileWriteExt},
+    },
+    RuntimeResult,
+};
+
+/// The version of the jobs file. This is bumped whenever the layout of the file changes incompatibly
+pub const JOBS_FILE_VERSION: u64 = 1;
+const JOBS_FILE_MAGIC: [u8; 8] = *b"SKYJOBS\0";
+
+const STATUS_RUNNING: u8 = 0;
+const STATUS_COMPLETED: u8 = 1;
+const STATUS_FAILED: u8 = 2;
+const STATUS_CANCELLED: u8 = 3;
+const STATUS_INTERRUPTED: u8 = 4;
+
+/// The jobs file (see the module docs for the layout)
+pub struct JobsFile;
+
+impl JobsFile {
+    pub fn encode(records: &[JobRecord]) -> Vec<u8> {
+        let mut buf = vec![];
+        buf.extend(JOBS_FILE_MAGIC);
+        buf.extend(JOBS_FILE_VERSION.to_le_bytes());
+        buf.extend((records.len() as u64).to_le_bytes());
+        for record in records {
+            let (status, error, result) = match &record.status {
+                JobStatus::Running => (STATUS_RUNNING, 0, None),
+                JobStatus::Completed(result) => (STATUS_COMPLETED, 0, result.as_deref()),
+                JobStatus::Failed(e) => (STATUS_FAILED, *e, None),
+                JobStatus::Cancelled => (STATUS_CANCELLED, 0, None),
+                JobStatus::Interrupted => (STATUS_INTERRUPTED, 0, None),
+            };
+            buf.extend(record.id.to_le_bytes());
+            buf.extend([record.kind.value_u8(), status, error]);
+            buf.extend(record.started_at.to_le_bytes());
+            buf.extend(record.finished_at.unwrap_or(0).to_le_bytes());
+            buf.extend(record.done.to_le_bytes());
+            buf.extend(record.total.to_le_bytes());
+            let owner = record.owner.as_deref().unwrap_or("");
+            buf.extend((owner.len() as u64).to_le_bytes());
+            buf.extend(owner.as_bytes());
+            buf.extend((record.target.len() as u64).to_le_bytes());
+            buf.extend(record.target.as_bytes());
+            buf.extend(result.map_or(0, |r| r.len() as u64 + 1).to_le_bytes());
+            buf.extend(result.unwrap_or("").as_bytes());
+        }
+        let mut crc = SCrc64::new();
+        crc.update(&buf);
+        buf.extend(crc.finish().to_le_bytes());
+        buf
+    }
+    pub fn decode(file: &[u8]) -> RuntimeResult<Vec<JobRecord>> {
+        if file.len() < sizeof!(u64) {
+            return Err(StorageError::JobsFileCorrupted.into());
+        }
+        let (body, checksum) = file.split_at(file.len() - sizeof!(u64));
+        let mut crc = SCrc64::new();
+        crc.update(body);
+        if Some(crc.finish()) != read_u64(&mut BufferedScanner::new(checksum)) {
+            return Err(StorageError::JobsFileCorrupted.into());
+        }
+        let mut scanner = BufferedScanner::new(body);
+        if scanner.try_next_variable_block(JOBS_FILE_MAGIC.len()) != Some(&JOBS_FILE_MAGIC[..]) {
+            return Err(StorageError::JobsFileCorrupted.into());
+        }
+        match read_u64(&mut scanner) {
+            Some(JOBS_FILE_VERSION) => {}
+            Some(_) => return Err(StorageError::HeaderDecodeVersionMismatch.into()),
+            None => return Err(StorageError::JobsFileCorrupted.into()),
+        }
+        let count = read_u64(&mut scanner).ok_or(StorageError::JobsFileCorrupted)?;
+        let mut records = vec![];
+        for _ in 0..count {
+            records.push(read_record(&mut scanner).ok_or(StorageError::JobsFileCorrupted)?);
+        }
+        if scanner.eof() {
+            Ok(records)
+        } else {
+            Err(StorageError::JobsFileCorrupted.into())
+        }
+    }
+    /// Replace the jobs file at `path` with the given records
+    pub fn write(path: &str, records: &[JobRecord]) -> RuntimeResult<()> {
+        let tmp = format!("{path}.tmp");
+        // left behind if we went down in the middle of a write
+        match FileSystem::remove_file(&tmp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let mut f = File::create(&tmp)?;
+        f.fwrite_all(&Self::encode(records))?;
+        f.fsync_all()?;
+        drop(f);
+        FileSystem::rename(&tmp, path)?;
+        Ok(())
+    }
+    /// Read the records in the jobs file at `path`. There are none if the file doesn't exist
+    pub fn read(path: &str) -> RuntimeResult<Vec<JobRecord>> {
+        match FileSystem::read(path) {
+            Ok(file) => Self::decode(&file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn read_record(scanner: &mut BufferedScanner) -> Option<JobRecord> {
+    let id = read_u64(scanner)?;
+    let kind = JobKind::try_from_u8(scanner.try_next_byte()?)?;
+    let (status, error) = (scanner.try_next_byte()?, scanner.try_next_byte()?);
+    let started_at = read_u64(scanner)?;
+    let finished_at = read_u64(scanner)?;
+    let done = read_u64(scanner)?;
+    let total = read_u64(scanner)?;
+    let owner_size = read_u64(scanner)?;
+    let owner = read_str(scanner, owner_size)?;
+    let target_size = read_u64(scanner)?;
+    let target = read_str(scanner, target_size)?;
+    let result = match read_u64(scanner)? {
+        0 => None,
+        size => Some(read_str(scanner, size - 1)?.into_string()),
+    };
+    let status = match status {
+        STATUS_RUNNING => JobStatus::Running,
+        STATUS_COMPLETED => JobStatus::Completed(result),
+        STATUS_FAILED => JobStatus::Failed(error),
+        STATUS_CANCELLED => JobStatus::Cancelled,
+        STATUS_INTERRUPTED => JobStatus::Interrupted,
+        _ => return None,
+    };
+    Some(JobRecord {
+        id,
+        kind,
+        owner: (!owner.is_empty()).then_some(owner),
+        target,
+        started_at,
+        finished_at: (finished_at != 0).then_some(finished_at),
+        done,
+        total,
+        status,
+    })
+}
+
+fn read_str(scanner: &mut BufferedScanner, size: u64) -> Option<Box<str>> {
+    let block = scanner.try_next_variable_block(usize::try_from(size).ok()?)?;
+    core::str::from_utf8(block).ok().map(Into::into)
+}
+
+fn read_u64(scanner: &mut BufferedScanner) -> Option<u64> {
+    scanner
+        .try_next_variable_block(sizeof!(u64))
+        .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+}
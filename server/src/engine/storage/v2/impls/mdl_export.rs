@@ -0,0 +1,613 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    logical decoding
+    ---
+    this module decodes a model's data journal into a documented, versioned stream of JSON lines for
+    external consumers (ETL, CDC and friends). a line is emitted for every event in every committed batch,
+    in journal order:
+
+    {"v":1,"lsn":4,"txn":12,"op":"update","key":"sayan","row":{"followers":100,"email":null}}
+
+    - `v`: the version of this format ([`EXPORT_FORMAT_VERSION`])
+    - `lsn`: the journal event that committed the batch. consumers should persist the largest lsn they have
+    seen and pass it back to resume
+    - `txn`: the transaction that changed the row (events in a batch are not ordered by txn)
+    - `op`: one of `insert`, `update`, `delete` or `tombstone` (a soft delete)
+    - `key`: the primary key
+    - `row`: the non primary key fields (absent for deletes)
+    - `tombstone`: the time (in seconds since the epoch) at which a row was soft deleted
+
+    binary values are hex strings and non-finite floats are null. fields are named using the model's current
+    layout, just like restore does
+*/
+
+use {
+    super::mdl_journal::{
+        self, BatchMetadata, BatchRestoreState, BatchType, DecodedBatchEvent,
+        DecodedBatchEventKind, EventType,
+    },
+    crate::engine::{
+        core::{index::PrimaryIndexKey, model::Model},
+        data::{
+            cell::Datacell,
+            tag::{TagClass, TagUnique},
+        },
+        idx::{meta::hash::HasherRawFx, STIndexSeq},
+        storage::{
+            common::{
+                interface::fs::{File, FileSystem, FileWrite, FileWriteExt},
+                sdss::sdss_r1::rw::TrackedReaderContext,
+            },
+            common_encoding::r1,
+            v2::raw::{
+                journal::{self, BatchAdapter, BatchAdapterSpec, ReplayAction},
+                spec::ModelDataBatchAofV1,
+            },
+        },
+        RuntimeResult,
+    },
+    std::{
+        cell::RefCell,
+        collections::{hash_map::Entry as HMEntry, HashMap},
+        fmt::Write,
+        hash::Hasher,
+        sync::atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// The version of the export format. This is bumped whenever the layout of a line changes incompatibly
+pub const EXPORT_FORMAT_VERSION: u64 = 1;
+
+static SNAPSHOT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A consistent copy of a model's journal (or a journal that no one writes to), waiting to be decoded
+pub struct JournalExport {
+    path: String,
+    /// set if `path` is a copy that we made, and must hence remove once done
+    is_snapshot: bool,
+    state: ExportState,
+}
+
+impl JournalExport {
+    /// Copy the journal of the given partition of the model so that it can be decoded without holding up the writer.
+    /// No batch can be committed while the copy is made, so the copy never ends with a partially written batch
+    pub fn snapshot(model: &Model, partition: usize, journal_path: &str) -> RuntimeResult<Self> {
+        let state = ExportState::new(model);
+        let snapshot_path = format!(
+            "{journal_path}-export-{}",
+            SNAPSHOT_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        let _driver = model.driver().batch_driver(partition).lock();
+        let journal = FileSystem::read(journal_path)?;
+        File::create(&snapshot_path)?.fwrite_all(&journal)?;
+        Ok(Self {
+            path: snapshot_path,
+            is_snapshot: true,
+            state,
+        })
+    }
+    /// Decode the journal where it is, without making a copy (and without ever writing to it). Only use this for
+    /// journals that no one is writing to, such as the journals of a server that isn't running
+    pub fn in_place(model: &Model, journal_path: &str) -> Self {
+        Self {
+            path: journal_path.into(),
+            is_snapshot: false,
+            state: ExportState::new(model),
+        }
+    }
+    /// Decode every event committed after `since` (or every event, if `since` is not set) into JSON lines.
+    /// The snapshot is removed once done
+    pub fn decode(self, since: Option<u64>) -> RuntimeResult<String> {
+        self.decode_batches(since)
+            .map(|batches| batches.into_iter().map(|(_, lines)| lines).collect())
+    }
+    /// Decode every event committed after `since` like [`Self::decode`] does, but keep the lines of every batch
+    /// (that has any events) apart, along with the LSN that committed it
+    pub fn decode_batches(self, since: Option<u64>) -> RuntimeResult<Vec<(u64, String)>> {
+        let mut batches = vec![];
+        let r = journal::decode_journal::<BatchAdapter<ModelDataExporter>>(
+            &self.path,
+            &self.state,
+            |lsn| {
+                let events = self.state.pending.take();
+                if since.map_or(true, |since| lsn > since) & !events.is_empty() {
+                    let mut lines = String::new();
+                    for event in events {
+                        encode_event(&mut lines, &self.state.fields, lsn, event);
+                    }
+                    batches.push((lsn, lines));
+                }
+            },
+        );
+        self.finish()?;
+        r.map(|_| batches)
+    }
+    /// Decode every committed event and replay it into the given rows. The snapshot is removed once done
+    pub fn replay(self, rows: &mut PersistedRows) -> RuntimeResult<()> {
+        self.replay_with(rows, |_| ReplayAction::Apply).map(|_| ())
+    }
+    /// Replay the journal into the given rows like [`Self::replay`] does, asking `action` what to do with every
+    /// batch (see [`journal::decode_journal_with`]). Returns the lsns of the batches that were skipped
+    pub fn replay_with(
+        self,
+        rows: &mut PersistedRows,
+        action: impl FnMut(u64) -> ReplayAction,
+    ) -> RuntimeResult<Vec<u64>> {
+        let r = journal::decode_journal_with::<BatchAdapter<ModelDataExporter>>(
+            &self.path,
+            &self.state,
+            action,
+            |lsn| {
+                rows.batches += 1;
+                rows.last_lsn = Some(lsn);
+                for event in self.state.pending.take() {
+                    rows.apply(event);
+                }
+            },
+        );
+        self.finish()?;
+        r
+    }
+    /// Count the batches and events committed to the journal. The snapshot (if any) is removed once done
+    pub fn summarize(self) -> RuntimeResult<JournalSummary> {
+        let mut summary = JournalSummary::default();
+        let r = journal::decode_journal::<BatchAdapter<ModelDataExporter>>(
+            &self.path,
+            &self.state,
+            |lsn| {
+                summary.batches += 1;
+                summary.last_lsn = Some(lsn);
+                for event in self.state.pending.take() {
+                    match event.kind {
+                        DecodedBatchEventKind::Insert(_) => summary.inserts += 1,
+                        DecodedBatchEventKind::Update(_) if event.tombstone.is_some() => {
+                            summary.tombstones += 1
+                        }
+                        DecodedBatchEventKind::Update(_) => summary.updates += 1,
+                        DecodedBatchEventKind::Delete => summary.deletes += 1,
+                    }
+                }
+            },
+        );
+        self.finish()?;
+        r.map(|_| summary)
+    }
+    fn finish(&self) -> RuntimeResult<()> {
+        if self.is_snapshot {
+            FileSystem::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+/// The batches and events committed to a journal, by kind
+pub struct JournalSummary {
+    batches: u64,
+    inserts: u64,
+    updates: u64,
+    tombstones: u64,
+    deletes: u64,
+    /// the event that committed the last batch
+    last_lsn: Option<u64>,
+}
+
+impl JournalSummary {
+    pub fn batches(&self) -> u64 {
+        self.batches
+    }
+    pub fn events(&self) -> u64 {
+        self.inserts + self.updates + self.tombstones + self.deletes
+    }
+    /// Returns the event that committed the last batch, if there was one
+    pub fn last_lsn(&self) -> Option<u64> {
+        self.last_lsn
+    }
+    /// Returns the summary as a JSON object
+    pub fn describe(&self) -> String {
+        format!(
+            "{{\"batches\":{},\"inserts\":{},\"updates\":{},\"tombstones\":{},\"deletes\":{},\"last_lsn\":{}}}",
+            self.batches,
+            self.inserts,
+            self.updates,
+            self.tombstones,
+            self.deletes,
+            self.last_lsn
+                .map_or_else(|| "null".into(), |lsn| lsn.to_string())
+        )
+    }
+}
+
+/*
+    verification
+    ---
+    replaying a journal (without applying it to the model) gives us the rows that a restore would load, which can
+    then be cross-checked against the rows in memory. rows are compared using a checksum of their persisted encoding
+*/
+
+/// The rows of a model as they would be restored from its journals
+#[derive(Default)]
+pub struct PersistedRows {
+    /// the latest version of every key; deletes are kept (as [`None`]) since events can be synced out of order
+    rows: HashMap<PrimaryIndexKey, (u64, Option<PersistedRow>)>,
+    batches: u64,
+    events: u64,
+    /// the event that committed the last batch that was replayed
+    last_lsn: Option<u64>,
+}
+
+/// A row as persisted in a journal
+pub struct PersistedRow {
+    fields: Vec<Datacell>,
+    tombstone: Option<u64>,
+}
+
+impl PersistedRows {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn apply(&mut self, event: DecodedBatchEvent) {
+        self.events += 1;
+        let txn = event.txn_id.value_u64();
+        let row = match event.kind {
+            DecodedBatchEventKind::Insert(fields) | DecodedBatchEventKind::Update(fields) => {
+                Some(PersistedRow {
+                    fields,
+                    tombstone: event.tombstone,
+                })
+            }
+            DecodedBatchEventKind::Delete => None,
+        };
+        match self.rows.entry(event.pk) {
+            // a newer version was synced first
+            HMEntry::Occupied(e) if e.get().0 > txn => {}
+            HMEntry::Occupied(mut e) => *e.get_mut() = (txn, row),
+            HMEntry::Vacant(e) => {
+                e.insert((txn, row));
+            }
+        }
+    }
+    /// Returns the number of batches that were replayed
+    pub fn batches(&self) -> u64 {
+        self.batches
+    }
+    /// Returns the number of events that were replayed
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+    /// Returns the event that committed the last batch replayed since this was last called, if any
+    pub fn take_last_lsn(&mut self) -> Option<u64> {
+        self.last_lsn.take()
+    }
+    /// Remove the given row, returning its checksum (see [`row_checksum`])
+    pub fn take_checksum(&mut self, pk: &PrimaryIndexKey) -> Option<u64> {
+        match self.rows.remove(pk)? {
+            (txn, Some(row)) => Some(row.checksum(pk, txn)),
+            (_, None) => None,
+        }
+    }
+    /// Returns the number of rows left and the sum of their checksums
+    pub fn remaining(&self) -> (usize, u64) {
+        self.rows
+            .iter()
+            .filter_map(|(pk, (txn, row))| row.as_ref().map(|row| row.checksum(pk, *txn)))
+            .fold((0, 0), |(count, sum), checksum| {
+                (count + 1, sum.wrapping_add(checksum))
+            })
+    }
+    /// Returns the rows (except deleted and soft deleted ones) with their fields in the model's layout (every field
+    /// but the primary key, in order)
+    pub fn into_rows(self) -> impl Iterator<Item = (PrimaryIndexKey, Vec<Datacell>)> {
+        self.rows.into_iter().filter_map(|(pk, (_, row))| {
+            let row = row?;
+            row.tombstone.is_none().then_some((pk, row.fields))
+        })
+    }
+    /// Returns the rows (except deleted ones) ordered by key as JSON lines, each with the `key`, the `row` and the
+    /// `tombstone` of a soft deleted row, just like the export. If `key` is set, only the row whose key is encoded as
+    /// `key` (or as the string `key`) is returned
+    pub fn encode_rows(&self, model: &Model, key: Option<&str>, limit: Option<usize>) -> String {
+        let fields = layout(model);
+        let mut rows: Vec<(&PrimaryIndexKey, String, &PersistedRow)> = self
+            .rows
+            .iter()
+            .filter_map(|(pk, (_, row))| {
+                let mut encoded = String::new();
+                encode_key(&mut encoded, pk);
+                Some((pk, encoded, row.as_ref()?))
+            })
+            .filter(|(_, encoded, _)| {
+                key.map_or(true, |key| {
+                    let mut as_str = String::new();
+                    encode_str(&mut as_str, key);
+                    *encoded == key || *encoded == as_str
+                })
+            })
+            .collect();
+        rows.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+        let mut out = String::new();
+        for (_, key, row) in rows.into_iter().take(limit.unwrap_or(usize::MAX)) {
+            let _ = write!(out, "{{\"key\":{key},\"row\":");
+            encode_row(&mut out, &fields, &row.fields);
+            if let Some(tombstone) = row.tombstone {
+                let _ = write!(out, ",\"tombstone\":{tombstone}");
+            }
+            out.push_str("}\n");
+        }
+        out
+    }
+    /// Write the rows to a new file at `path` as JSON lines (see [`Self::encode_rows`]), replacing a previous export
+    pub fn write_rows(&self, model: &Model, path: &str) -> RuntimeResult<()> {
+        match FileSystem::remove_file(path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let mut f = File::create(path)?;
+        f.fwrite_all(self.encode_rows(model, None, None).as_bytes())?;
+        f.fsync_all()?;
+        Ok(())
+    }
+}
+
+impl PersistedRow {
+    fn checksum(&self, pk: &PrimaryIndexKey, txn: u64) -> u64 {
+        row_checksum(pk, txn, self.tombstone, self.fields.iter().map(Some))
+    }
+}
+
+/// Returns the checksum of a row, computed over its key, version, tombstone and the persisted encoding of its (non
+/// primary key) fields, in the model's layout. A missing field is treated as null. Checksums are summed (with
+/// wrapping) to get the checksum of a set of rows
+pub fn row_checksum<'a>(
+    pk: &PrimaryIndexKey,
+    txn: u64,
+    tombstone: Option<u64>,
+    fields: impl Iterator<Item = Option<&'a Datacell>>,
+) -> u64 {
+    let mut buf = vec![pk.tag().value_u8()];
+    unsafe {
+        // UNSAFE(@ohsayan): +tagck
+        match pk.tag() {
+            TagUnique::UnsignedInt | TagUnique::SignedInt => {
+                buf.extend(pk.read_uint().to_le_bytes())
+            }
+            _ => {
+                buf.extend((pk.read_bin().len() as u64).to_le_bytes());
+                buf.extend(pk.read_bin());
+            }
+        }
+    }
+    buf.extend(txn.to_le_bytes());
+    buf.extend(tombstone.unwrap_or(u64::MAX).to_le_bytes());
+    for field in fields {
+        match field {
+            Some(dc) => r1::obj::cell::encode(&mut buf, dc),
+            None => buf.push(0),
+        }
+    }
+    let mut hasher = HasherRawFx::<u64>::new();
+    hasher.write(&buf);
+    hasher.finish()
+}
+
+/// State used while decoding: the model's layout and the events of the batch that was just decoded (which
+/// are only exported once the batch's checksum is verified)
+struct ExportState {
+    fields: Vec<Box<str>>,
+    pending: RefCell<Vec<DecodedBatchEvent>>,
+}
+
+impl ExportState {
+    fn new(model: &Model) -> Self {
+        Self {
+            fields: layout(model),
+            pending: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+/// Returns the names of the model's non primary key fields, in the order that they're persisted
+fn layout(model: &Model) -> Vec<Box<str>> {
+    model
+        .data()
+        .fields()
+        .stseq_ord_key()
+        .filter(|key| key.as_str() != model.data().p_key())
+        .map(|key| key.as_str().into())
+        .collect()
+}
+
+/// The journal adapter used for logical decoding. It reads batches exactly like [`mdl_journal::ModelDataAdapter`]
+/// but never applies them
+struct ModelDataExporter;
+
+impl BatchAdapterSpec for ModelDataExporter {
+    type Spec = ModelDataBatchAofV1;
+    type GlobalState = ExportState;
+    type BatchType = BatchType;
+    type EventType = EventType;
+    type BatchMetadata = BatchMetadata;
+    type BatchState = BatchRestoreState;
+    type CommitContext = ();
+    fn is_early_exit(event_type: &Self::EventType) -> bool {
+        EventType::EarlyExit.eq(event_type)
+    }
+    fn initialize_batch_state(_: &Self::GlobalState) -> Self::BatchState {
+        BatchRestoreState { events: Vec::new() }
+    }
+    fn decode_batch_metadata(
+        _: &Self::GlobalState,
+        f: &mut TrackedReaderContext<Self::Spec>,
+        batch_type: Self::BatchType,
+    ) -> RuntimeResult<Self::BatchMetadata> {
+        mdl_journal::decode_batch_metadata(f, batch_type)
+    }
+    fn update_state_for_new_event(
+        _: &Self::GlobalState,
+        bs: &mut Self::BatchState,
+        f: &mut TrackedReaderContext<Self::Spec>,
+        batch_info: &Self::BatchMetadata,
+        event_type: Self::EventType,
+    ) -> RuntimeResult<()> {
+        bs.events
+            .push(mdl_journal::decode_event(f, batch_info, event_type)?);
+        Ok(())
+    }
+    fn finish(
+        batch_state: Self::BatchState,
+        _: Self::BatchMetadata,
+        gs: &Self::GlobalState,
+    ) -> RuntimeResult<()> {
+        gs.pending.borrow_mut().extend(batch_state.events);
+        Ok(())
+    }
+}
+
+/*
+    encoding
+*/
+
+fn encode_event(out: &mut String, fields: &[Box<str>], lsn: u64, event: DecodedBatchEvent) {
+    let DecodedBatchEvent {
+        txn_id,
+        pk,
+        kind,
+        tombstone,
+    } = event;
+    let (op, row) = match kind {
+        DecodedBatchEventKind::Insert(row) => ("insert", Some(row)),
+        DecodedBatchEventKind::Update(row) if tombstone.is_some() => ("tombstone", Some(row)),
+        DecodedBatchEventKind::Update(row) => ("update", Some(row)),
+        DecodedBatchEventKind::Delete => ("delete", None),
+    };
+    let _ = write!(
+        out,
+        "{{\"v\":{EXPORT_FORMAT_VERSION},\"lsn\":{lsn},\"txn\":{},\"op\":\"{op}\",\"key\":",
+        txn_id.value_u64()
+    );
+    encode_key(out, &pk);
+    if let Some(row) = row {
+        out.push_str(",\"row\":");
+        encode_row(out, fields, &row);
+    }
+    if let Some(tombstone) = tombstone {
+        let _ = write!(out, ",\"tombstone\":{tombstone}");
+    }
+    out.push_str("}\n");
+}
+
+fn encode_row(out: &mut String, fields: &[Box<str>], row: &[Datacell]) {
+    out.push('{');
+    for (i, (field, value)) in fields.iter().zip(row.iter()).enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        encode_str(out, field);
+        out.push(':');
+        encode_value(out, value);
+    }
+    out.push('}');
+}
+
+/// Encode a primary key as a JSON value (like the keys of exported rows)
+pub fn encode_key(out: &mut String, pk: &PrimaryIndexKey) {
+    unsafe {
+        // UNSAFE(@ohsayan): +tagck
+        match pk.tag() {
+            TagUnique::UnsignedInt => {
+                let _ = write!(out, "{}", pk.read_uint());
+            }
+            TagUnique::SignedInt => {
+                let _ = write!(out, "{}", pk.read_sint());
+            }
+            TagUnique::Bin => encode_bin(out, pk.read_bin()),
+            TagUnique::Str => encode_str(out, pk.read_str()),
+            TagUnique::Illegal => unreachable!(),
+        }
+    }
+}
+
+fn encode_value(out: &mut String, dc: &Datacell) {
+    if dc.is_null() {
+        out.push_str("null");
+        return;
+    }
+    match dc.kind() {
+        TagClass::Bool => out.push_str(if dc.bool() { "true" } else { "false" }),
+        TagClass::UnsignedInt => {
+            let _ = write!(out, "{}", dc.uint());
+        }
+        TagClass::SignedInt => {
+            let _ = write!(out, "{}", dc.sint());
+        }
+        TagClass::Float if dc.float().is_finite() => {
+            let _ = write!(out, "{}", dc.float());
+        }
+        TagClass::Float => out.push_str("null"),
+        TagClass::Bin => encode_bin(out, dc.bin()),
+        TagClass::Str => encode_str(out, dc.str()),
+        TagClass::List => {
+            out.push('[');
+            for (i, item) in dc.list().read().iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                encode_value(out, item);
+            }
+            out.push(']');
+        }
+    }
+}
+
+fn encode_bin(out: &mut String, bin: &[u8]) {
+    out.push('"');
+    for byte in bin {
+        let _ = write!(out, "{byte:02x}");
+    }
+    out.push('"');
+}
+
+/// Write `s` as a JSON string
+pub fn encode_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -39,7 +39,7 @@ use {
                 tag::{DataTag, TagUnique},
             },
             error::StorageError,
-            idx::{MTIndex, STIndex, STIndexSeq},
+            idx::{STIndex, STIndexSeq},
             storage::{
                 common::sdss::sdss_r1::rw::{TrackedReaderContext, TrackedWriter},
                 common_encoding::r1,
@@ -73,6 +73,24 @@ impl ModelDriver {
     pub fn create_model_driver(model_data_file_path: &str) -> RuntimeResult<Self> {
         journal::create_journal(model_data_file_path)
     }
+    /// Open the event logs of every partition of the model (in order), restoring each into the model
+    pub fn open_model_drivers(
+        mdl: &ModelData,
+        partition_path: impl Fn(usize) -> String,
+    ) -> RuntimeResult<Vec<Self>> {
+        (0..mdl.primary_index().partitions())
+            .map(|partition| Self::open_model_driver(mdl, &partition_path(partition)))
+            .collect()
+    }
+    /// Create a new event log for every partition
+    pub fn create_model_drivers(
+        partitions: usize,
+        partition_path: impl Fn(usize) -> String,
+    ) -> RuntimeResult<Vec<Self>> {
+        (0..partitions)
+            .map(|partition| Self::create_model_driver(&partition_path(partition)))
+            .collect()
+    }
 }
 
 /// The model data adapter (abstract journal adapter impl)
@@ -97,6 +115,8 @@ pub enum EventType {
     Update = 2,
     /// owing to inconsistent reads, we exited early
     EarlyExit = 3,
+    /// a soft deleted row (encoded like an update, followed by the tombstone)
+    Tombstone = 4,
 }
 
 /*
@@ -133,22 +153,8 @@ impl<'b> RowWriter<'b> {
     /// write row metadata:
     /// - change type
     /// - txn id
-    fn write_row_metadata(
-        &mut self,
-        change: DataDeltaKind,
-        txn_id: DeltaVersion,
-    ) -> RuntimeResult<()> {
-        if cfg!(debug) {
-            let event_kind = EventType::try_from_raw(change.value_u8()).unwrap();
-            match (event_kind, change) {
-                (EventType::Delete, DataDeltaKind::Delete)
-                | (EventType::Insert, DataDeltaKind::Insert)
-                | (EventType::Update, DataDeltaKind::Update) => {}
-                (EventType::EarlyExit, _) => unreachable!(),
-                _ => panic!(),
-            }
-        }
-        let change_type = [change.value_u8()];
+    fn write_row_metadata(&mut self, event: EventType, txn_id: DeltaVersion) -> RuntimeResult<()> {
+        let change_type = [event.dscr()];
         self.f.dtrack_write(&change_type)?;
         let txn_id = txn_id.value_u64().u64_bytes_le();
         self.f.dtrack_write(&txn_id)?;
@@ -181,10 +187,10 @@ impl<'b> RowWriter<'b> {
         }
         Ok(())
     }
-    /// Encode a single cell
+    /// Encode a single cell (large strings and binaries are compressed if they compress well)
     fn write_cell(&mut self, value: &Datacell) -> RuntimeResult<()> {
         let mut buf = vec![];
-        r1::obj::cell::encode(&mut buf, value);
+        r1::obj::cell::encode_compressed(&mut buf, value);
         self.f.dtrack_write(&buf)?;
         Ok(())
     }
@@ -201,10 +207,32 @@ impl<'b> RowWriter<'b> {
         }
         Ok(())
     }
+    /// Encode an inserted or updated row. A soft deleted row is always encoded as a tombstone event
+    fn write_row(
+        &mut self,
+        model: &ModelData,
+        event: EventType,
+        txn_id: DeltaVersion,
+        pk: &PrimaryIndexKey,
+        row_data: &RowData,
+    ) -> RuntimeResult<()> {
+        let event = match row_data.tombstone() {
+            Some(_) => EventType::Tombstone,
+            None => event,
+        };
+        self.write_row_metadata(event, txn_id)?;
+        self.write_row_pk(pk)?;
+        self.write_row_data(model, row_data)?;
+        if let Some(tombstone) = row_data.tombstone() {
+            self.f.dtrack_write(&tombstone.u64_bytes_le())?;
+        }
+        Ok(())
+    }
 }
 
 struct BatchWriter<'a, 'b> {
     model: &'a ModelData,
+    partition: usize,
     row_writer: RowWriter<'b>,
     g: &'a Guard,
     sync_count: usize,
@@ -213,6 +241,7 @@ struct BatchWriter<'a, 'b> {
 impl<'a, 'b> BatchWriter<'a, 'b> {
     fn write_batch(
         model: &'a ModelData,
+        partition: usize,
         g: &'a Guard,
         expected: usize,
         f: &'b mut TrackedWriter<<BatchAdapter<ModelDataAdapter> as RawJournalAdapter>::Spec>,
@@ -229,15 +258,19 @@ impl<'a, 'b> BatchWriter<'a, 'b> {
 
             -- @ohsayan
         */
-        let mut me = Self::new(model, g, f)?;
+        let mut me = Self::new(model, partition, g, f)?;
         let mut i = 0;
         while i < expected {
-            let delta = me.model.delta_state().__data_delta_dequeue(me.g).unwrap();
+            let delta = me
+                .model
+                .delta_state()
+                .__data_delta_dequeue(me.partition, me.g)
+                .unwrap();
             match me.step(&delta) {
                 Ok(()) => i += 1,
                 Err(e) => {
                     // errored, so push this back in; we have written and flushed all prior deltas
-                    me.model.delta_state().append_new_data_delta(delta, me.g);
+                    me.model.delta_state().requeue_data_delta(delta, me.g);
                     batch_stat.set_actual(i);
                     return Err(e);
                 }
@@ -247,6 +280,7 @@ impl<'a, 'b> BatchWriter<'a, 'b> {
     }
     fn new(
         model: &'a ModelData,
+        partition: usize,
         g: &'a Guard,
         f: &'b mut TrackedWriter<<BatchAdapter<ModelDataAdapter> as RawJournalAdapter>::Spec>,
     ) -> RuntimeResult<Self> {
@@ -254,6 +288,7 @@ impl<'a, 'b> BatchWriter<'a, 'b> {
         row_writer.write_row_global_metadata(model)?;
         Ok(Self {
             model,
+            partition,
             row_writer,
             g,
             sync_count: 0,
@@ -263,7 +298,7 @@ impl<'a, 'b> BatchWriter<'a, 'b> {
         match delta.change() {
             DataDeltaKind::Delete => {
                 self.row_writer
-                    .write_row_metadata(delta.change(), delta.data_version())?;
+                    .write_row_metadata(EventType::Delete, delta.data_version())?;
                 self.row_writer.write_row_pk(delta.row().d_key())?;
             }
             DataDeltaKind::Insert | DataDeltaKind::Update => {
@@ -272,16 +307,24 @@ impl<'a, 'b> BatchWriter<'a, 'b> {
                     .row()
                     .resolve_schema_deltas_and_freeze_if(self.model.delta_state(), |row| {
                         row.get_txn_revised() <= delta.data_version()
-                    });
+                    })
+                    .map_err(|_| StorageError::OverflowReadFailed)?;
                 if row_data.get_txn_revised() > delta.data_version() {
                     // inconsistent read. there should already be another revised delta somewhere
                     return Ok(());
                 }
-                self.row_writer
-                    .write_row_metadata(delta.change(), delta.data_version())?;
+                let event = match delta.change() {
+                    DataDeltaKind::Insert => EventType::Insert,
+                    _ => EventType::Update,
+                };
                 // encode data
-                self.row_writer.write_row_pk(delta.row().d_key())?;
-                self.row_writer.write_row_data(self.model, &row_data)?;
+                self.row_writer.write_row(
+                    self.model,
+                    event,
+                    delta.data_version(),
+                    delta.row().d_key(),
+                    &row_data,
+                )?;
             }
         }
         self.row_writer.f.flush_buf()?;
@@ -290,12 +333,25 @@ impl<'a, 'b> BatchWriter<'a, 'b> {
     }
 }
 
-/// A standard model batch where atmost the given number of keys are flushed
-pub struct StdModelBatch<'a>(&'a ModelData, usize);
+/// A standard model batch where atmost the given number of keys (of the given partition) are flushed
+pub struct StdModelBatch<'a>(&'a ModelData, usize, usize);
 
 impl<'a> StdModelBatch<'a> {
-    pub fn new(model: &'a ModelData, observed_len: usize) -> Self {
-        Self(model, observed_len)
+    pub fn new(model: &'a ModelData, partition: usize, observed_len: usize) -> Self {
+        Self(model, partition, observed_len)
+    }
+    /// Take the changes of the batch off the model without writing them anywhere (for drivers that don't keep
+    /// data). Returns the number of changes taken
+    pub fn discard(self) -> usize {
+        let g = pin();
+        (0..self.2)
+            .take_while(|_| {
+                self.0
+                    .delta_state()
+                    .__data_delta_dequeue(self.1, &g)
+                    .is_some()
+            })
+            .count()
     }
 }
 
@@ -309,11 +365,11 @@ impl<'a> JournalAdapterEvent<BatchAdapter<ModelDataAdapter>> for StdModelBatch<'
         ctx: Rc<RefCell<BatchStats>>,
     ) -> RuntimeResult<()> {
         // [expected commit]
-        writer.dtrack_write(&self.1.u64_bytes_le())?;
+        writer.dtrack_write(&self.2.u64_bytes_le())?;
         let g = pin();
         let actual_commit =
-            BatchWriter::write_batch(self.0, &g, self.1, writer, &mut ctx.borrow_mut())?;
-        if actual_commit != self.1 {
+            BatchWriter::write_batch(self.0, self.1, &g, self.2, writer, &mut ctx.borrow_mut())?;
+        if actual_commit != self.2 {
             // early exit
             writer.dtrack_write(&[EventType::EarlyExit.dscr()])?;
         }
@@ -321,11 +377,12 @@ impl<'a> JournalAdapterEvent<BatchAdapter<ModelDataAdapter>> for StdModelBatch<'
     }
 }
 
-pub struct FullModel<'a>(&'a ModelData);
+/// Every row of a partition of the model
+pub struct FullModel<'a>(&'a ModelData, usize);
 
 impl<'a> FullModel<'a> {
-    pub fn new(model: &'a ModelData) -> Self {
-        Self(model)
+    pub fn new(model: &'a ModelData, partition: usize) -> Self {
+        Self(model, partition)
     }
 }
 
@@ -340,19 +397,28 @@ impl<'a> JournalAdapterEvent<BatchAdapter<ModelDataAdapter>> for FullModel<'a> {
     ) -> RuntimeResult<()> {
         let g = pin();
         let mut row_writer: RowWriter<'_> = RowWriter { f };
-        let index = self.0.primary_index().__raw_index();
-        let current_row_count = index.mt_len();
+        let index = self.0.primary_index();
+        let current_row_count = index.count_partition(self.1);
         // expect commit == current row count
         row_writer
             .f
             .dtrack_write(&current_row_count.u64_bytes_le())?;
         // [pk tag][schema version][column cnt]
         row_writer.write_row_global_metadata(self.0)?;
-        for (key, row_data) in index.mt_iter_kv(&g) {
-            let row_data = row_data.read();
-            row_writer.write_row_metadata(DataDeltaKind::Insert, row_data.get_txn_revised())?;
-            row_writer.write_row_pk(key)?;
-            row_writer.write_row_data(self.0, &row_data)?;
+        for row in index.iter_partition(self.1, &g) {
+            let (key, row_data) = (
+                row.d_key(),
+                row.d_data()
+                    .read()
+                    .map_err(|_| StorageError::OverflowReadFailed)?,
+            );
+            row_writer.write_row(
+                self.0,
+                EventType::Insert,
+                row_data.get_txn_revised(),
+                key,
+                &row_data,
+            )?;
         }
         // actual commit == current row count
         row_writer
@@ -362,6 +428,51 @@ impl<'a> JournalAdapterEvent<BatchAdapter<ModelDataAdapter>> for FullModel<'a> {
     }
 }
 
+/// Rows that were loaded in bulk, all of them in the same partition. They're written as one batch of inserts (just
+/// like the rows of a [`FullModel`])
+pub struct LoadedRows<'a>(&'a ModelData, &'a [Row]);
+
+impl<'a> LoadedRows<'a> {
+    pub fn new(model: &'a ModelData, rows: &'a [Row]) -> Self {
+        Self(model, rows)
+    }
+    pub fn row_count(&self) -> usize {
+        self.1.len()
+    }
+}
+
+impl<'a> JournalAdapterEvent<BatchAdapter<ModelDataAdapter>> for LoadedRows<'a> {
+    fn md(&self) -> u64 {
+        BatchType::Standard.dscr_u64()
+    }
+    fn write_direct(
+        self,
+        f: &mut TrackedWriter<<BatchAdapter<ModelDataAdapter> as RawJournalAdapter>::Spec>,
+        _: Rc<RefCell<BatchStats>>,
+    ) -> RuntimeResult<()> {
+        let mut row_writer: RowWriter<'_> = RowWriter { f };
+        // expect commit == row count
+        row_writer.f.dtrack_write(&self.1.len().u64_bytes_le())?;
+        // [pk tag][schema version][column cnt]
+        row_writer.write_row_global_metadata(self.0)?;
+        for row in self.1 {
+            let row_data = row
+                .d_data()
+                .read()
+                .map_err(|_| StorageError::OverflowReadFailed)?;
+            row_writer.write_row(
+                self.0,
+                EventType::Insert,
+                row_data.get_txn_revised(),
+                row.d_key(),
+                &row_data,
+            )?;
+        }
+        // actual commit == row count
+        e!(row_writer.f.dtrack_write(&self.1.len().u64_bytes_le()))
+    }
+}
+
 /*
     restore implementation
     ---
@@ -379,7 +490,7 @@ pub struct BatchMetadata {
     column_count: u64,
 }
 
-enum DecodedBatchEventKind {
+pub(super) enum DecodedBatchEventKind {
     Delete,
     Insert(Vec<Datacell>),
     Update(Vec<Datacell>),
@@ -387,13 +498,15 @@ enum DecodedBatchEventKind {
 
 /// State handling for any pending queries
 pub struct BatchRestoreState {
-    events: Vec<DecodedBatchEvent>,
+    pub(super) events: Vec<DecodedBatchEvent>,
 }
 
-struct DecodedBatchEvent {
-    txn_id: DeltaVersion,
-    pk: PrimaryIndexKey,
-    kind: DecodedBatchEventKind,
+pub(super) struct DecodedBatchEvent {
+    pub(super) txn_id: DeltaVersion,
+    pub(super) pk: PrimaryIndexKey,
+    pub(super) kind: DecodedBatchEventKind,
+    /// set if the row was soft deleted (a tombstone is restored just like an update)
+    pub(super) tombstone: Option<u64>,
 }
 
 impl DecodedBatchEvent {
@@ -402,6 +515,7 @@ impl DecodedBatchEvent {
             txn_id: DeltaVersion::__new(txn_id),
             pk,
             kind,
+            tombstone: None,
         }
     }
 }
@@ -444,19 +558,7 @@ impl BatchAdapterSpec for ModelDataAdapter {
         f: &mut TrackedReaderContext<Self::Spec>,
         batch_type: Self::BatchType,
     ) -> RuntimeResult<Self::BatchMetadata> {
-        // [pk tag][schema version][column cnt]
-        match batch_type {
-            BatchType::Standard => {}
-        }
-        let pk_tag = TagUnique::try_from_raw(f.read_block().map(|[b]| b)?)
-            .ok_or(StorageError::RawJournalCorrupted)?;
-        let schema_version = u64::from_le_bytes(f.read_block()?);
-        let column_count = u64::from_le_bytes(f.read_block()?);
-        Ok(BatchMetadata {
-            pk_tag,
-            schema_version,
-            column_count,
-        })
+        decode_batch_metadata(f, batch_type)
     }
     fn update_state_for_new_event(
         _: &Self::GlobalState,
@@ -465,38 +567,7 @@ impl BatchAdapterSpec for ModelDataAdapter {
         batch_info: &Self::BatchMetadata,
         event_type: Self::EventType,
     ) -> RuntimeResult<()> {
-        // get txn id
-        let txn_id = u64::from_le_bytes(f.read_block()?);
-        // get pk
-        let pk = restore_impls::decode_primary_key::<Self::Spec>(f, batch_info.pk_tag)?;
-        match event_type {
-            EventType::Delete => {
-                bs.events.push(DecodedBatchEvent::new(
-                    txn_id,
-                    pk,
-                    DecodedBatchEventKind::Delete,
-                ));
-            }
-            EventType::Insert | EventType::Update => {
-                // insert or update
-                // prepare row
-                let row = restore_impls::decode_row_data(batch_info, f)?;
-                if event_type == EventType::Insert {
-                    bs.events.push(DecodedBatchEvent::new(
-                        txn_id,
-                        pk,
-                        DecodedBatchEventKind::Insert(row),
-                    ));
-                } else {
-                    bs.events.push(DecodedBatchEvent::new(
-                        txn_id,
-                        pk,
-                        DecodedBatchEventKind::Update(row),
-                    ));
-                }
-            }
-            EventType::EarlyExit => unreachable!(),
-        }
+        bs.events.push(decode_event(f, batch_info, event_type)?);
         Ok(())
     }
     fn finish(
@@ -509,26 +580,35 @@ impl BatchAdapterSpec for ModelDataAdapter {
         */
         let g = unsafe { crossbeam_epoch::unprotected() };
         let mut pending_delete = HashMap::new();
-        let p_index = gs.primary_index().__raw_index();
+        let p_index = gs.primary_index();
         let m = gs;
         let mut real_last_txn_id = DeltaVersion::genesis();
-        for DecodedBatchEvent { txn_id, pk, kind } in batch_state.events {
+        for DecodedBatchEvent {
+            txn_id,
+            pk,
+            kind,
+            tombstone,
+        } in batch_state.events
+        {
             match kind {
                 DecodedBatchEventKind::Insert(new_row) | DecodedBatchEventKind::Update(new_row) => {
-                    let popped_row = p_index.mt_delete_return(&pk, &g);
-                    if let Some(row) = popped_row {
+                    if let Some(row) = p_index.select_key(&pk, &g).map(Row::d_data) {
                         /*
                             if a newer version of the row is received first and the older version is pending to be synced, the older
                             version is never synced. this is how the diffing algorithm works to ensure consistency.
                             the delta diff algorithm statically guarantees this.
                         */
-                        let row_txn_revised = row.read().get_txn_revised();
+                        let row_txn_revised = row
+                            .read()
+                            .map_err(|_| StorageError::OverflowReadFailed)?
+                            .get_txn_revised();
                         assert!(
                             row_txn_revised.value_u64() == 0 || row_txn_revised < txn_id,
                             "revised ID is {} but our row has version {}",
-                            row.read().get_txn_revised().value_u64(),
+                            row_txn_revised.value_u64(),
                             txn_id.value_u64()
                         );
+                        let _ = p_index.delete_key(&pk, &g);
                     }
                     if txn_id > real_last_txn_id {
                         real_last_txn_id = txn_id;
@@ -554,10 +634,14 @@ impl BatchAdapterSpec for ModelDataAdapter {
                         DeltaVersion::__new(batch_md.schema_version),
                         txn_id,
                     );
+                    row.d_data()
+                        .write()
+                        .map_err(|_| StorageError::OverflowReadFailed)?
+                        .set_tombstone(tombstone);
                     // resolve any deltas
                     let _ = row.resolve_schema_deltas_and_freeze(m.delta_state());
                     // put it back in (lol); blame @ohsayan for this joke
-                    p_index.mt_insert(row, &g);
+                    p_index.insert(row, &g);
                 }
                 DecodedBatchEventKind::Delete => {
                     /*
@@ -590,14 +674,20 @@ impl BatchAdapterSpec for ModelDataAdapter {
             if txn_id > real_last_txn_id {
                 real_last_txn_id = txn_id;
             }
-            match p_index.mt_get(&pk, &g) {
+            match p_index.select_key(&pk, &g) {
                 Some(row) => {
-                    if row.read().get_txn_revised() > txn_id {
+                    if row
+                        .d_data()
+                        .read()
+                        .map_err(|_| StorageError::OverflowReadFailed)?
+                        .get_txn_revised()
+                        > txn_id
+                    {
                         // our delete "happened before" this row was inserted
                         continue;
                     }
                     // yup, go ahead and chuck it
-                    let _ = p_index.mt_delete(&pk, &g);
+                    let _ = p_index.delete_key(&pk, &g);
                 }
                 None => {
                     // if we reach here it basically means that both an (insert and/or update) and a delete
@@ -613,6 +703,58 @@ impl BatchAdapterSpec for ModelDataAdapter {
     }
 }
 
+/// Decode the metadata at the start of a batch
+pub(super) fn decode_batch_metadata(
+    f: &mut TrackedReaderContext<ModelDataBatchAofV1>,
+    batch_type: BatchType,
+) -> RuntimeResult<BatchMetadata> {
+    // [pk tag][schema version][column cnt]
+    match batch_type {
+        BatchType::Standard => {}
+    }
+    let pk_tag = TagUnique::try_from_raw(f.read_block().map(|[b]| b)?)
+        .ok_or(StorageError::RawJournalCorrupted)?;
+    let schema_version = u64::from_le_bytes(f.read_block()?);
+    let column_count = u64::from_le_bytes(f.read_block()?);
+    Ok(BatchMetadata {
+        pk_tag,
+        schema_version,
+        column_count,
+    })
+}
+
+/// Decode a single (non early exit) event in a batch
+pub(super) fn decode_event(
+    f: &mut TrackedReaderContext<ModelDataBatchAofV1>,
+    batch_info: &BatchMetadata,
+    event_type: EventType,
+) -> RuntimeResult<DecodedBatchEvent> {
+    // get txn id
+    let txn_id = u64::from_le_bytes(f.read_block()?);
+    // get pk
+    let pk = restore_impls::decode_primary_key::<ModelDataBatchAofV1>(f, batch_info.pk_tag)?;
+    let event = match event_type {
+        EventType::Delete => DecodedBatchEvent::new(txn_id, pk, DecodedBatchEventKind::Delete),
+        EventType::Tombstone => {
+            let row = restore_impls::decode_row_data(batch_info, f)?;
+            let tombstone = u64::from_le_bytes(f.read_block()?);
+            let mut event = DecodedBatchEvent::new(txn_id, pk, DecodedBatchEventKind::Update(row));
+            event.tombstone = Some(tombstone);
+            event
+        }
+        EventType::Insert => {
+            let row = restore_impls::decode_row_data(batch_info, f)?;
+            DecodedBatchEvent::new(txn_id, pk, DecodedBatchEventKind::Insert(row))
+        }
+        EventType::Update => {
+            let row = restore_impls::decode_row_data(batch_info, f)?;
+            DecodedBatchEvent::new(txn_id, pk, DecodedBatchEventKind::Update(row))
+        }
+        EventType::EarlyExit => unreachable!(),
+    };
+    Ok(event)
+}
+
 mod restore_impls {
     use {
         super::BatchMetadata,
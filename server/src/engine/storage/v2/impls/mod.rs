@@ -24,7 +24,19 @@
  *
 */
 
+pub mod archive;
+pub mod benches;
+pub mod gns_export;
 pub mod gns_log;
+pub mod inspect;
+pub mod jobs_file;
+pub mod mdl_export;
 pub mod mdl_journal;
+pub mod relocate;
+pub mod selfcheck;
+pub mod snapshot_file;
 #[cfg(test)]
 mod tests;
+pub mod usage;
+pub mod warmup_file;
+pub mod webhook_cursor;
@@ -0,0 +1,246 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    data relocation
+    ---
+    the files that a data relocation moves (see `core::relocate`): everything in the data directory, the quarantine and
+    the snapshots directory of the batch volume. files are copied to where they would be in a copy of the data
+    directory at the new volume (see `paths_v1::in_copy`), so once the batch volume is switched over, every path
+    resolves to its copy. journals are only ever appended to, so a file is copied incrementally: only what was added
+    since the last copy is copied, and a file that is shorter than its copy was rewritten, so it's copied again from
+    the start. a file that was rewritten in place isn't caught by its length, which is why every copy is checked
+    against its source with a checksum before the switch over.
+
+    once the batch volume is switched over, a marker that holds the new volume is left in the old one, so that a
+    server that is restarted with the old volume refuses to start (instead of starting on the stale data)
+*/
+
+use {
+    super::usage::SNAPSHOT_INFIX,
+    crate::engine::{
+        storage::common::{
+            checksum::SCrc64,
+            interface::fs::{File, FileExt, FileRead, FileSystem, FileWrite, FileWriteExt},
+            paths_v1,
+        },
+        RuntimeResult,
+    },
+    std::{collections::HashSet, io::ErrorKind},
+};
+
+/// The most that is read of a file at a time
+const READ_BLOCK: u64 = 1 << 20;
+
+/// The move of the batch volume's files to a new volume
+#[derive(Debug)]
+pub struct DataRelocation {
+    from: Box<str>,
+    to: Box<str>,
+    /// the directories that are moved, on the old volume
+    dirs: [String; 3],
+}
+
+impl DataRelocation {
+    /// Prepare to move the files on the current batch volume to `to`
+    pub fn new(to: &str) -> Self {
+        Self {
+            from: paths_v1::placement().batch_volume().into(),
+            to: to.into(),
+            dirs: [
+                paths_v1::data_dir(),
+                paths_v1::quarantine_dir(),
+                paths_v1::snapshots_dir(),
+            ],
+        }
+    }
+    pub fn old_volume(&self) -> &str {
+        &self.from
+    }
+    pub fn new_volume(&self) -> &str {
+        &self.to
+    }
+    /// Returns the path of the copy of the file at `path`
+    pub fn copy_path(&self, path: &str) -> String {
+        paths_v1::in_copy(&self.to, path)
+    }
+    /// Returns the files to move along with their size. Copies of journals that are made for exports are left out
+    /// since they're thrown away once the export is done
+    pub fn files(&self) -> RuntimeResult<Vec<(String, u64)>> {
+        let mut ret = vec![];
+        for dir in &self.dirs {
+            let files = match FileSystem::list_files(dir) {
+                Ok(files) => files,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            ret.extend(
+                files
+                    .into_iter()
+                    .filter(|(name, _)| !name.contains(SNAPSHOT_INFIX))
+                    .map(|(name, len)| (format!("{dir}/{name}"), len)),
+            );
+        }
+        Ok(ret)
+    }
+    /// Returns true if the new volume already has a data directory (or a quarantine, or snapshots)
+    pub fn target_has_data(&self) -> bool {
+        self.dirs.iter().any(|dir| {
+            FileSystem::list_files(&self.copy_path(dir)).is_ok_and(|files| !files.is_empty())
+        })
+    }
+    /// Copy up to `max` bytes of what was added to the file at `path` since it was last copied, returning the number
+    /// of bytes copied (which is zero once the copy has caught up). A file that doesn't exist (anymore) has nothing to
+    /// copy
+    pub fn sync(&self, path: &str, max: u64) -> RuntimeResult<u64> {
+        let len = match FileSystem::file_len(path) {
+            Ok(len) => len,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let copy = self.copy_path(path);
+        let copied = match FileSystem::file_len(&copy) {
+            Ok(copied) => copied,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if let Some((parent, _)) = copy.rsplit_once('/') {
+                    FileSystem::create_dir_all(parent)?;
+                }
+                File::create(&copy)?.fsync_all()?;
+                0
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mut f_copy = File::open(&copy)?;
+        let at = if len < copied {
+            // the file was rewritten since, so copy it again
+            f_copy.f_truncate(0)?;
+            0
+        } else {
+            copied
+        };
+        let mut bytes = vec![0; (len - at).min(max) as usize];
+        if bytes.is_empty() {
+            return Ok(0);
+        }
+        let mut f = File::open_read_only(path)?;
+        f.f_seek_start(at)?;
+        f.fread_exact(&mut bytes)?;
+        f_copy.f_seek_start(at)?;
+        f_copy.fwrite_all(&bytes)?;
+        f_copy.fsync_all()?;
+        Ok(bytes.len() as u64)
+    }
+    /// Throw away the copy of the file at `path` and copy it again (see [`Self::sync`])
+    pub fn resync(&self, path: &str) -> RuntimeResult<u64> {
+        match FileSystem::remove_file(&self.copy_path(path)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let mut copied = 0;
+        loop {
+            match self.sync(path, READ_BLOCK)? {
+                0 => return Ok(copied),
+                n => copied += n,
+            }
+        }
+    }
+    /// Returns true if the copy of the file at `path` has the same checksum as the file
+    pub fn verify(&self, path: &str) -> RuntimeResult<bool> {
+        Ok(checksum(path)? == checksum(&self.copy_path(path))?)
+    }
+    /// Remove the files in the copied directories that are no longer on the old volume (such as the files of a model
+    /// that was dropped while its directory was being copied)
+    pub fn prune(&self, files: &[(String, u64)]) -> RuntimeResult<()> {
+        let files: HashSet<String> = files.iter().map(|(path, _)| self.copy_path(path)).collect();
+        for dir in &self.dirs {
+            let copy_dir = self.copy_path(dir);
+            let copies = match FileSystem::list_files(&copy_dir) {
+                Ok(copies) => copies,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for (name, _) in copies {
+                let copy = format!("{copy_dir}/{name}");
+                if !files.contains(&copy) {
+                    FileSystem::remove_file(&copy)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Remove the copies, when the relocation is given up on before the switch over
+    pub fn abort(&self) -> RuntimeResult<()> {
+        for dir in &self.dirs {
+            match FileSystem::remove_dir_all(&self.copy_path(dir)) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    /// Leave the marker on the old volume and remove the moved directories from it. Call this once the batch volume
+    /// is switched over
+    pub fn finish(&self) -> RuntimeResult<()> {
+        // a volume that files were moved back to isn't stale anymore
+        match FileSystem::remove_file(&paths_v1::relocation_marker(&self.to)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        let mut marker = File::create(&paths_v1::relocation_marker(&self.from))?;
+        marker.fwrite_all(self.to.as_bytes())?;
+        marker.fsync_all()?;
+        for dir in &self.dirs {
+            match FileSystem::remove_dir_all(dir) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the volume that the batch volume at `volume` was moved to, if files were moved off of it
+pub fn relocated_to(volume: &str) -> RuntimeResult<Option<String>> {
+    match FileSystem::read(&paths_v1::relocation_marker(volume)) {
+        Ok(to) => Ok(Some(String::from_utf8_lossy(&to).into_owned())),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn checksum(path: &str) -> RuntimeResult<u64> {
+    let mut f = File::open_read_only(path)?;
+    let mut len = f.f_len()?;
+    let mut crc = SCrc64::new();
+    let mut block = vec![0; len.min(READ_BLOCK) as usize];
+    while len != 0 {
+        let n = len.min(READ_BLOCK) as usize;
+        f.fread_exact(&mut block[..n])?;
+        crc.update(&block[..n]);
+        len -= n as u64;
+    }
+    Ok(crc.finish())
+}
@@ -0,0 +1,347 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    self-check
+    ---
+    once recovery is done, every file in the data directory must belong to a space and model in the GNS (recovery
+    has already opened every batch file that the GNS knows about, so the other direction is checked for free). files
+    that don't usually come from a model drop whose cleanup never ran (it happens in the background; space drops are
+    finished on restore) or from an export that was interrupted. they are harmless, but they take up space and hide real problems, so they are reported; with
+    `--repair` they are moved to a quarantine directory. nothing is ever deleted, and orphaned data is never adopted
+    because its schema only ever lived in the GNS
+*/
+
+use {
+    super::{
+        usage::{BATCH_SUFFIX, SNAPSHOT_INFIX},
+        webhook_cursor::WebhookCursor,
+    },
+    crate::{
+        engine::{
+            core::GNSData,
+            error::{ErrorKind, StorageError},
+            idx::STIndexSeq,
+            storage::{
+                common::{interface::fs::FileSystem, paths_v1, sdss::sdss_r1::rw::SdssFile},
+                v2::raw::spec::ModelDataBatchAofV1,
+            },
+            RuntimeResult,
+        },
+        util,
+    },
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        fmt,
+        io::ErrorKind as IoErrorKind,
+    },
+};
+
+#[derive(Debug, PartialEq)]
+/// A problem found by the self-check. Paths are relative to the data directory
+pub enum Finding {
+    /// a space directory that no space owns
+    OrphanSpaceDir {
+        dir: String,
+        bytes: u64,
+        foreign_version: bool,
+    },
+    /// a model directory (in a space that exists) that no model owns
+    OrphanModelDir {
+        dir: String,
+        bytes: u64,
+        foreign_version: bool,
+    },
+    /// a batch file for a partition that the model doesn't have
+    StrayPartition {
+        model: String,
+        file: String,
+        foreign_version: bool,
+    },
+    /// a copy of a batch file made by an export or verification that never finished
+    StaleSnapshot { file: String },
+    /// a file that the server never creates
+    UnknownFile { file: String },
+}
+
+impl Finding {
+    fn path(&self) -> &str {
+        match self {
+            Self::OrphanSpaceDir { dir, .. } | Self::OrphanModelDir { dir, .. } => dir,
+            Self::StrayPartition { file, .. }
+            | Self::StaleSnapshot { file }
+            | Self::UnknownFile { file } => file,
+        }
+    }
+    fn is_dir(&self) -> bool {
+        matches!(
+            self,
+            Self::OrphanSpaceDir { .. } | Self::OrphanModelDir { .. }
+        )
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn foreign(foreign_version: bool) -> &'static str {
+            if foreign_version {
+                " (written by a different version of Skytable)"
+            } else {
+                ""
+            }
+        }
+        match self {
+            Self::OrphanSpaceDir {
+                dir,
+                bytes,
+                foreign_version,
+            } => write!(
+                f,
+                "`{dir}` ({bytes} bytes) doesn't belong to any space{}",
+                foreign(*foreign_version)
+            ),
+            Self::OrphanModelDir {
+                dir,
+                bytes,
+                foreign_version,
+            } => write!(
+                f,
+                "`{dir}` ({bytes} bytes) doesn't belong to any model in its space{}",
+                foreign(*foreign_version)
+            ),
+            Self::StrayPartition {
+                model,
+                file,
+                foreign_version,
+            } => write!(
+                f,
+                "`{file}` is a batch file for a partition that `{model}` doesn't have{}",
+                foreign(*foreign_version)
+            ),
+            Self::StaleSnapshot { file } => write!(
+                f,
+                "`{file}` is a copy of a batch file left behind by an export or verification that didn't finish"
+            ),
+            Self::UnknownFile { file } => write!(f, "`{file}` wasn't created by the server"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+/// The result of a self-check
+pub struct SelfCheck {
+    findings: Vec<Finding>,
+}
+
+impl SelfCheck {
+    /// Check the given files (with their sizes; relative to `data_dir`) against the spaces and models in the GNS
+    pub fn inspect(gns: &GNSData, data_dir: &str, files: Vec<(String, u64)>) -> Self {
+        let relative = |path: String| path[data_dir.len() + 1..].to_owned();
+        let spaces_idx = gns.idx().read();
+        let spaces: HashSet<String> = spaces_idx
+            .iter()
+            .map(|(name, space)| relative(paths_v1::space_dir(name, space.get_uuid())))
+            .collect();
+        let mut models = HashMap::new();
+        for (id, model) in gns.idx_models().read().iter() {
+            let Some(space) = spaces_idx.get(id.space()) else {
+                continue;
+            };
+            let dir = relative(paths_v1::model_dir(
+                id.space(),
+                space.get_uuid(),
+                id.entity(),
+                model.data().get_uuid(),
+            ));
+            models.insert(
+                dir,
+                (
+                    format!("{}.{}", id.space(), id.entity()),
+                    model.data().primary_index().partitions(),
+                    model
+                        .data()
+                        .webhooks()
+                        .stseq_ord_key()
+                        .cloned()
+                        .collect::<HashSet<_>>(),
+                ),
+            );
+        }
+        let mut orphan_spaces = BTreeMap::new();
+        let mut orphan_models = BTreeMap::new();
+        let mut findings = vec![];
+        for (file, len) in files {
+            let is_foreign = || is_foreign_batch_file(&format!("{data_dir}/{file}"));
+            let mut components = file.splitn(3, '/');
+            let (space_dir, model_dir, name) =
+                (components.next(), components.next(), components.next());
+            match (space_dir, model_dir, name) {
+                (Some(space_dir), Some(_), _) if !spaces.contains(space_dir) => {
+                    let (bytes, foreign) = orphan_spaces
+                        .entry(space_dir.to_owned())
+                        .or_insert((0, false));
+                    *bytes += len;
+                    *foreign |= is_foreign();
+                }
+                (Some(space_dir), Some(model_dir), Some(name)) => {
+                    let dir = format!("{space_dir}/{model_dir}");
+                    match models.get(&dir) {
+                        None => {
+                            let (bytes, foreign) = orphan_models.entry(dir).or_insert((0, false));
+                            *bytes += len;
+                            *foreign |= is_foreign();
+                        }
+                        Some(_) if name.contains(SNAPSHOT_INFIX) => {
+                            findings.push(Finding::StaleSnapshot { file })
+                        }
+                        Some((model, partitions, webhooks)) => match partition_of(name) {
+                            Some(partition) if partition < *partitions => {}
+                            Some(_) => {
+                                let foreign_version = is_foreign();
+                                findings.push(Finding::StrayPartition {
+                                    model: model.clone(),
+                                    file,
+                                    foreign_version,
+                                })
+                            }
+                            None if WebhookCursor::webhook_of(name)
+                                .is_some_and(|webhook| webhooks.contains(webhook)) => {}
+                            None => findings.push(Finding::UnknownFile { file }),
+                        },
+                    }
+                }
+                _ => findings.push(Finding::UnknownFile { file }),
+            }
+        }
+        findings.extend(
+            orphan_spaces
+                .into_iter()
+                .map(|(dir, (bytes, foreign_version))| Finding::OrphanSpaceDir {
+                    dir,
+                    bytes,
+                    foreign_version,
+                }),
+        );
+        findings.extend(
+            orphan_models
+                .into_iter()
+                .map(|(dir, (bytes, foreign_version))| Finding::OrphanModelDir {
+                    dir,
+                    bytes,
+                    foreign_version,
+                }),
+        );
+        Self { findings }
+    }
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+    /// Move everything that was found into the quarantine directory, keeping the layout of the data directory
+    pub fn repair(&self, data_dir: &str, quarantine_dir: &str) -> RuntimeResult<()> {
+        for finding in &self.findings {
+            let (from, to) = (
+                format!("{data_dir}/{}", finding.path()),
+                format!("{quarantine_dir}/{}", finding.path()),
+            );
+            if finding.is_dir() {
+                for (file, _) in FileSystem::list_files(&from)? {
+                    quarantine_file(&format!("{from}/{file}"), &format!("{to}/{file}"))?;
+                }
+                FileSystem::remove_dir_all(&from)?;
+            } else {
+                quarantine_file(&from, &to)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check the data directory against the GNS, and report (or quarantine, if `repair` is set) anything that doesn't
+/// belong to a space or model
+pub fn self_check(gns: &GNSData, repair: bool) -> RuntimeResult<()> {
+    let data_dir = paths_v1::data_dir();
+    let files = match FileSystem::list_files(&data_dir) {
+        Err(e) if e.kind() == IoErrorKind::NotFound => vec![],
+        r => r?,
+    };
+    let check = SelfCheck::inspect(gns, &data_dir, files);
+    if check.findings.is_empty() {
+        info!("self-check found no problems");
+        return Ok(());
+    }
+    for finding in &check.findings {
+        warn!("self-check: {finding}");
+    }
+    if repair {
+        let quarantine_dir = format!(
+            "{}/{}",
+            paths_v1::quarantine_dir(),
+            util::time_now_with_postfix("selfcheck")
+        );
+        check.repair(&data_dir, &quarantine_dir)?;
+        info!(
+            "self-check: moved {} item(s) to {quarantine_dir}",
+            check.findings.len()
+        );
+    } else {
+        warn!(
+            "self-check found {} problem(s) in {data_dir}. restart with --repair to move them to quarantine",
+            check.findings.len()
+        );
+    }
+    Ok(())
+}
+
+/// Returns the partition of a batch file, given its name (see [`paths_v1::model_partition_path`])
+fn partition_of(name: &str) -> Option<usize> {
+    let stem = name.strip_suffix(BATCH_SUFFIX)?;
+    match stem {
+        "data" => Some(0),
+        _ => stem.strip_prefix("data.p")?.parse().ok(),
+    }
+}
+
+/// Returns true if this is a batch file that was written by a different version of the server
+fn is_foreign_batch_file(path: &str) -> bool {
+    if !path.ends_with(BATCH_SUFFIX) {
+        return false;
+    }
+    match SdssFile::<ModelDataBatchAofV1>::open(path) {
+        Ok(_) => false,
+        Err(e) => matches!(
+            e.kind(),
+            ErrorKind::Storage(StorageError::HeaderDecodeVersionMismatch)
+        ),
+    }
+}
+
+fn quarantine_file(from: &str, to: &str) -> RuntimeResult<()> {
+    if let Some((parent, _)) = to.rsplit_once('/') {
+        FileSystem::create_dir_all(parent)?;
+    }
+    FileSystem::rename(from, to)?;
+    Ok(())
+}
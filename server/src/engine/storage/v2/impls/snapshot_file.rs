@@ -0,0 +1,293 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    snapshot files
+    ---
+    a snapshot (see `core::snapshot`) is a copy of the global journal and of the batch file of every model partition,
+    in a directory of its own in the snapshots directory of the batch volume. it is laid out like a copy of the data
+    directory (see `paths_v1::in_copy`), so it can be restored from (`sysctl restore model`) or recovered from (`skyd
+    inspect recover --snapshot`). its manifest is written once everything else was copied, so a snapshot without one
+    is incomplete (the server went down while taking it):
+
+    [magic:8B][version:8B][created at:8B][bytes:8B][files:8B][schedule size:8B][schedule][checksum:8B]
+
+    the schedule size is zero for a snapshot that was taken by hand. the snapshot schedules are kept in the snapshots
+    directory too, and are rewritten in full (into a temporary file that then replaces it) whenever they change:
+
+    [magic:8B][version:8B][schedule count:8B]
+    [name size:8B][name][every:8B][keep:8B] (for every schedule)
+    [checksum:8B]
+
+    the checksum is the CRC64 of everything that comes before it
+*/
+
+use {
+    crate::engine::{
+        error::StorageError,
+        mem::BufferedScanner,
+        storage::common::{
+            checksum::SCrc64,
+            interface::fs::{File, FileSystem, FileWrite, FileWriteExt},
+            paths_v1,
+        },
+        RuntimeResult,
+    },
+    std::io::ErrorKind,
+};
+
+/// The version of the snapshot files. This is bumped whenever the layout of either file changes incompatibly
+pub const SNAPSHOT_FILE_VERSION: u64 = 1;
+const MANIFEST_MAGIC: [u8; 8] = *b"SKYSNAP\0";
+const SCHEDULES_MAGIC: [u8; 8] = *b"SKYSCHED";
+
+#[derive(Debug, PartialEq, Clone)]
+/// What is known about a complete snapshot
+pub struct SnapshotManifest {
+    /// the time (in seconds since the epoch) at which the snapshot was taken
+    pub created_at: u64,
+    pub bytes: u64,
+    pub files: u64,
+    /// the schedule that took the snapshot, if it wasn't taken by hand
+    pub schedule: Option<Box<str>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// `every` seconds, take a snapshot named after the schedule and keep the last `keep` of them
+pub struct SnapshotSchedule {
+    pub name: Box<str>,
+    pub every: u64,
+    pub keep: u64,
+}
+
+#[derive(Debug, PartialEq)]
+/// A snapshot in the snapshots directory
+pub struct SnapshotEntry {
+    pub name: Box<str>,
+    /// the size of everything in the snapshot's directory
+    pub bytes: u64,
+    /// [`None`] if the snapshot is incomplete
+    pub manifest: Option<SnapshotManifest>,
+}
+
+/// The snapshot files (see the module docs for the layout)
+pub struct SnapshotFiles;
+
+impl SnapshotFiles {
+    pub fn encode_manifest(manifest: &SnapshotManifest) -> Vec<u8> {
+        let mut buf = vec![];
+        buf.extend(MANIFEST_MAGIC);
+        buf.extend(SNAPSHOT_FILE_VERSION.to_le_bytes());
+        buf.extend(manifest.created_at.to_le_bytes());
+        buf.extend(manifest.bytes.to_le_bytes());
+        buf.extend(manifest.files.to_le_bytes());
+        let schedule = manifest.schedule.as_deref().unwrap_or("");
+        buf.extend((schedule.len() as u64).to_le_bytes());
+        buf.extend(schedule.as_bytes());
+        finish(buf)
+    }
+    pub fn decode_manifest(file: &[u8]) -> RuntimeResult<SnapshotManifest> {
+        let mut scanner = BufferedScanner::new(verify(file, &MANIFEST_MAGIC)?);
+        let mut read = || {
+            let created_at = read_u64(&mut scanner)?;
+            let bytes = read_u64(&mut scanner)?;
+            let files = read_u64(&mut scanner)?;
+            let schedule = read_str(&mut scanner)?;
+            Some(SnapshotManifest {
+                created_at,
+                bytes,
+                files,
+                schedule: (!schedule.is_empty()).then_some(schedule),
+            })
+        };
+        match read() {
+            Some(manifest) if scanner.eof() => Ok(manifest),
+            _ => Err(StorageError::SnapshotFileCorrupted.into()),
+        }
+    }
+    pub fn encode_schedules(schedules: &[SnapshotSchedule]) -> Vec<u8> {
+        let mut buf = vec![];
+        buf.extend(SCHEDULES_MAGIC);
+        buf.extend(SNAPSHOT_FILE_VERSION.to_le_bytes());
+        buf.extend((schedules.len() as u64).to_le_bytes());
+        for schedule in schedules {
+            buf.extend((schedule.name.len() as u64).to_le_bytes());
+            buf.extend(schedule.name.as_bytes());
+            buf.extend(schedule.every.to_le_bytes());
+            buf.extend(schedule.keep.to_le_bytes());
+        }
+        finish(buf)
+    }
+    pub fn decode_schedules(file: &[u8]) -> RuntimeResult<Vec<SnapshotSchedule>> {
+        let mut scanner = BufferedScanner::new(verify(file, &SCHEDULES_MAGIC)?);
+        let count = read_u64(&mut scanner).ok_or(StorageError::SnapshotFileCorrupted)?;
+        let mut schedules = vec![];
+        for _ in 0..count {
+            let mut read = || {
+                Some(SnapshotSchedule {
+                    name: read_str(&mut scanner)?,
+                    every: read_u64(&mut scanner)?,
+                    keep: read_u64(&mut scanner)?,
+                })
+            };
+            schedules.push(read().ok_or(StorageError::SnapshotFileCorrupted)?);
+        }
+        if scanner.eof() {
+            Ok(schedules)
+        } else {
+            Err(StorageError::SnapshotFileCorrupted.into())
+        }
+    }
+    /// Write the copy of the journal at `path` into the snapshot at `snapshot`
+    pub fn write_journal(snapshot: &str, path: &str, journal: &[u8]) -> RuntimeResult<()> {
+        let copy = paths_v1::in_copy(snapshot, path);
+        if let Some((parent, _)) = copy.rsplit_once('/') {
+            FileSystem::create_dir_all(parent)?;
+        }
+        let mut f = File::create(&copy)?;
+        f.fwrite_all(journal)?;
+        f.fsync_all()?;
+        Ok(())
+    }
+    /// Write the manifest of the snapshot at `snapshot`, which completes it
+    pub fn write_manifest(snapshot: &str, manifest: &SnapshotManifest) -> RuntimeResult<()> {
+        let mut f = File::create(&paths_v1::snapshot_manifest_path(snapshot))?;
+        f.fwrite_all(&Self::encode_manifest(manifest))?;
+        f.fsync_all()?;
+        Ok(())
+    }
+    /// Read the manifest of the snapshot at `snapshot`, or [`None`] if it is incomplete
+    pub fn read_manifest(snapshot: &str) -> RuntimeResult<Option<SnapshotManifest>> {
+        match FileSystem::read(&paths_v1::snapshot_manifest_path(snapshot)) {
+            Ok(file) => Self::decode_manifest(&file).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    /// Replace the schedules file at `path` with the given schedules
+    pub fn write_schedules(path: &str, schedules: &[SnapshotSchedule]) -> RuntimeResult<()> {
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            FileSystem::create_dir_all(parent)?;
+        }
+        let tmp = format!("{path}.tmp");
+        // left behind if we went down in the middle of a write
+        match FileSystem::remove_file(&tmp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let mut f = File::create(&tmp)?;
+        f.fwrite_all(&Self::encode_schedules(schedules))?;
+        f.fsync_all()?;
+        drop(f);
+        FileSystem::rename(&tmp, path)?;
+        Ok(())
+    }
+    /// Read the schedules in the schedules file at `path`. There are none if the file doesn't exist
+    pub fn read_schedules(path: &str) -> RuntimeResult<Vec<SnapshotSchedule>> {
+        match FileSystem::read(path) {
+            Ok(file) => Self::decode_schedules(&file),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+    /// Returns every snapshot in the snapshots directory at `dir`, by name. A snapshot with a manifest that can't be
+    /// read is listed as incomplete
+    pub fn list(dir: &str) -> RuntimeResult<Vec<SnapshotEntry>> {
+        let files = match FileSystem::list_files(dir) {
+            Ok(files) => files,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e.into()),
+        };
+        let mut snapshots: Vec<SnapshotEntry> = vec![];
+        // files that aren't in a directory (like the schedules file) aren't snapshots
+        for (path, len) in files {
+            let Some((name, _)) = path.split_once('/') else {
+                continue;
+            };
+            match snapshots.iter_mut().find(|s| &*s.name == name) {
+                Some(snapshot) => snapshot.bytes += len,
+                None => snapshots.push(SnapshotEntry {
+                    name: name.into(),
+                    bytes: len,
+                    manifest: None,
+                }),
+            }
+        }
+        snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+        for snapshot in &mut snapshots {
+            snapshot.manifest = Self::read_manifest(&format!("{dir}/{}", snapshot.name))
+                .ok()
+                .flatten();
+        }
+        Ok(snapshots)
+    }
+    /// Remove the snapshot at `snapshot` along with everything in it
+    pub fn remove(snapshot: &str) -> RuntimeResult<()> {
+        FileSystem::remove_dir_all(snapshot).map_err(Into::into)
+    }
+}
+
+/// Append the checksum of everything in the buffer
+fn finish(mut buf: Vec<u8>) -> Vec<u8> {
+    let mut crc = SCrc64::new();
+    crc.update(&buf);
+    buf.extend(crc.finish().to_le_bytes());
+    buf
+}
+
+/// Check the checksum, magic and version of a file, returning what comes after the version
+fn verify<'a>(file: &'a [u8], magic: &[u8; 8]) -> RuntimeResult<&'a [u8]> {
+    if file.len() < sizeof!(u64, 3) {
+        return Err(StorageError::SnapshotFileCorrupted.into());
+    }
+    let (body, checksum) = file.split_at(file.len() - sizeof!(u64));
+    let mut crc = SCrc64::new();
+    crc.update(body);
+    if Some(crc.finish()) != read_u64(&mut BufferedScanner::new(checksum)) {
+        return Err(StorageError::SnapshotFileCorrupted.into());
+    }
+    let (header, body) = body.split_at(sizeof!(u64, 2));
+    if header[..8] != magic[..] {
+        return Err(StorageError::SnapshotFileCorrupted.into());
+    }
+    match read_u64(&mut BufferedScanner::new(&header[8..])) {
+        Some(SNAPSHOT_FILE_VERSION) => Ok(body),
+        _ => Err(StorageError::HeaderDecodeVersionMismatch.into()),
+    }
+}
+
+fn read_str(scanner: &mut BufferedScanner) -> Option<Box<str>> {
+    let size = read_u64(scanner)?;
+    let block = scanner.try_next_variable_block(usize::try_from(size).ok()?)?;
+    core::str::from_utf8(block).ok().map(Into::into)
+}
+
+fn read_u64(scanner: &mut BufferedScanner) -> Option<u64> {
+    scanner
+        .try_next_variable_block(sizeof!(u64))
+        .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+}
@@ -27,20 +27,62 @@
 use {
     crate::{
         engine::{
-            core::{dml, index::RowData, model::ModelData, space::Space, EntityID, EntityIDRef},
-            data::lit::Lit,
-            error::QueryResult,
-            fractal::{test_utils::TestGlobal, GlobalInstanceLike},
+            core::{
+                dcl::import_gns,
+                dml,
+                index::{PrimaryIndexKey, RowData},
+                model::{
+                    create_webhook, describe_webhooks, drop_webhook, export_models, restore_model,
+                    verify_model,
+                    warmup::{self, HotModel},
+                    webhook, ModelData,
+                },
+                quota::{self, Access, Quota},
+                snapshot,
+                space::Space,
+                EntityID, EntityIDRef,
+            },
+            data::{cell::Datacell, lit::Lit},
+            error::{ErrorKind, QueryError, QueryResult, StorageError},
+            fractal::{
+                archive::Archive,
+                jobs::{self, JobKind, JobStatus, Jobs},
+                test_utils::TestGlobal,
+                webhook::{signature, SIGNATURE_HEADER},
+                GlobalInstanceLike,
+            },
+            idx::STIndex,
             ql::{
                 ast,
-                ddl::crt::{CreateModel, CreateSpace},
-                dml::{ins::InsertStatement, upd::UpdateStatement},
+                ddl::{
+                    crt::{CreateIndex, CreateModel, CreateSpace},
+                    drop::DropSpace,
+                },
+                dml::{del::DeleteStatement, ins::InsertStatement, upd::UpdateStatement},
                 tests::lex_insecure,
             },
+            storage::{
+                common::{
+                    interface::fs::{File, FileExt, FileSystem, FileWrite},
+                    paths_v1,
+                },
+                v2::impls::{
+                    inspect::{Inspector, ReplayBounds},
+                    selfcheck::{Finding, SelfCheck},
+                },
+                DataRelocation, DiskUsage, GNSExport, JobsFile, JournalExport, PersistedRows,
+                SnapshotFiles, SnapshotManifest, WarmupFile, WebhookCursor,
+            },
         },
         util::test_utils,
     },
     crossbeam_epoch::pin,
+    std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    },
 };
 
 const TEST_DATASET_SIZE: usize = 1000;
@@ -87,7 +129,13 @@ fn run_insert(global: &TestGlobal, insert: &str) -> QueryResult<()> {
 fn run_update(global: &TestGlobal, update: &str) -> QueryResult<()> {
     let tokens = lex_insecure(update.as_bytes()).unwrap();
     let insert: UpdateStatement = ast::parse_ast_node_full(&tokens[1..]).unwrap();
-    dml::update(global, insert)
+    dml::update(global, insert).map(|_| ())
+}
+
+fn run_delete(global: &TestGlobal, delete: &str) -> QueryResult<()> {
+    let tokens = lex_insecure(delete.as_bytes()).unwrap();
+    let delete: DeleteStatement = ast::parse_ast_node_full(&tokens[1..]).unwrap();
+    dml::delete(global, delete).map(|_| ())
 }
 
 fn auto_hook<T>(msg: &str, f: impl Fn() -> T) -> T {
@@ -154,7 +202,8 @@ fn run_sample_inserts<K, V>(
                                     .select(as_pk(username), &g)
                                     .unwrap()
                                     .d_data()
-                                    .read();
+                                    .read()
+                                    .unwrap();
                                 check_row(username, password, &row)
                             }
                             Ok(())
@@ -233,7 +282,8 @@ fn run_sample_updates<K, V>(
                                     .select(as_pk(username), &g)
                                     .unwrap()
                                     .d_data()
-                                    .read();
+                                    .read()
+                                    .unwrap();
                                 check_row(username, password, &row);
                                 assert_eq!(row.get_txn_revised().value_u64(), txn_id);
                                 Ok(())
@@ -315,3 +365,1804 @@ fn model_data_updates() {
         },
     );
 }
+
+#[test]
+fn model_data_partitioned() {
+    let key_values = create_test_kv_strings(TEST_DATASET_SIZE);
+    run_sample_inserts(
+        "model_data_partitioned_inserts",
+        "create model apps.social(user_name: string, password: string) with { partitions: 4 }",
+        key_values.clone(),
+        |k, v| format!("insert into apps.social('{k}', '{v}')"),
+        |k| Lit::new_str(k),
+        |_, v, row| assert_eq!(row.fields().get("password").unwrap().str(), v),
+    );
+    run_sample_updates(
+        "model_data_partitioned_updates",
+        "create model apps.social(user_name: uint64, password: string) with { partitions: 4 }",
+        create_test_kv_int(TEST_UPDATE_DATASET_SIZE),
+        |k, v| format!("insert into apps.social({k}, '{v}')"),
+        |k, _| format!("update apps.social set password = '' where user_name = {k}"),
+        |k| Lit::new_uint(*k),
+        |_, _, row| assert!(row.fields().get("password").unwrap().str().is_empty()),
+    );
+    // every partition is restored from its own batch file
+    test_utils::with_variable("model_data_partitioned_files", |log_name| {
+        let mdl_name;
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            mdl_name = create_model_and_space(
+                &global,
+                "create model apps.social(user_name: string, password: string) with { partitions: 4 }",
+            )
+            .unwrap();
+            for (username, password) in key_values.iter() {
+                run_insert(
+                    &global,
+                    &format!("insert into apps.social('{username}', '{password}')"),
+                )
+                .unwrap();
+            }
+        }
+        let global = TestGlobal::new_with_driver_id(log_name);
+        let space_uuid = global
+            .state()
+            .namespace()
+            .idx()
+            .read()
+            .get(mdl_name.space())
+            .unwrap()
+            .get_uuid();
+        global
+            .state()
+            .namespace()
+            .with_model(
+                EntityIDRef::new(mdl_name.space(), mdl_name.entity()),
+                |model| {
+                    let index = model.primary_index();
+                    assert_eq!(index.count(), TEST_DATASET_SIZE);
+                    for partition in 0..4 {
+                        assert_ne!(index.count_partition(partition), 0);
+                        assert!(FileSystem::read(&paths_v1::model_partition_path(
+                            mdl_name.space(),
+                            space_uuid,
+                            mdl_name.entity(),
+                            model.get_uuid(),
+                            partition,
+                        ))
+                        .is_ok());
+                    }
+                    let g = pin();
+                    assert_eq!(
+                        index
+                            .iter(&g)
+                            .filter(|row| row.d_key().partition(4) == 0)
+                            .count(),
+                        index.count_partition(0)
+                    );
+                    Ok(())
+                },
+            )
+            .unwrap()
+    })
+}
+
+#[test]
+fn model_data_bulk_load() {
+    let key_values = create_test_kv_strings(TEST_DATASET_SIZE);
+    test_utils::with_variable("model_data_bulk_load", |log_name| {
+        let mdl_name;
+        {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            mdl_name = create_model_and_space(
+                &global,
+                "create model apps.social(user_name: string, password: string) with { partitions: 4 }",
+            )
+            .unwrap();
+            run_insert(&global, "insert into apps.social('sayan', 'pass123')").unwrap();
+            run_delete(&global, "delete from apps.social where user_name = 'sayan'").unwrap();
+            let mut rows: Vec<_> = key_values
+                .iter()
+                .map(|(username, password)| {
+                    vec![
+                        Datacell::new_str(username.as_str().into()),
+                        Datacell::new_str(password.as_str().into()),
+                    ]
+                })
+                .collect();
+            // a key that was deleted before the load (but not written out yet) can be loaded again
+            rows.push(vec![
+                Datacell::new_str("sayan".into()),
+                Datacell::new_str("pass456".into()),
+            ]);
+            assert_eq!(
+                dml::bulk_load(
+                    &global,
+                    EntityIDRef::new(mdl_name.space(), mdl_name.entity()),
+                    rows
+                ),
+                Ok(TEST_DATASET_SIZE as u64 + 1)
+            );
+        }
+        // the loaded rows are restored from the batches
+        let global = TestGlobal::new_with_driver_id(log_name);
+        global
+            .state()
+            .namespace()
+            .with_model(
+                EntityIDRef::new(mdl_name.space(), mdl_name.entity()),
+                |model| {
+                    let g = pin();
+                    assert_eq!(model.primary_index().count(), TEST_DATASET_SIZE + 1);
+                    let password = |username: &str| {
+                        let row = model
+                            .primary_index()
+                            .select(Lit::new_str(username), &g)
+                            .unwrap()
+                            .d_data()
+                            .read()
+                            .unwrap();
+                        row.fields().get("password").unwrap().str().to_owned()
+                    };
+                    for (username, pass) in key_values.iter() {
+                        assert_eq!(&password(username), pass);
+                    }
+                    assert_eq!(password("sayan"), "pass456");
+                    Ok(())
+                },
+            )
+            .unwrap()
+    })
+}
+
+#[test]
+fn model_data_soft_deletes() {
+    test_utils::with_variable("model_data_soft_deletes", |log_name| {
+        let mdl_name;
+        let deleted = create_test_kv_strings(10);
+        let (deleted, live) = deleted.split_at(5);
+        // create, insert, soft delete half of the rows and close
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            mdl_name = create_model_and_space(
+                &global,
+                "create model apps.social(user_name: string, password: string) with { soft_delete: true }",
+            )
+            .unwrap();
+            for (username, password) in deleted.iter().chain(live) {
+                run_insert(
+                    &global,
+                    &format!("insert into apps.social('{username}', '{password}')"),
+                )
+                .unwrap();
+            }
+            for (username, _) in deleted {
+                run_delete(
+                    &global,
+                    &format!("delete from apps.social where user_name = '{username}'"),
+                )
+                .unwrap();
+            }
+        }
+        // reopen and verify that the tombstones were restored
+        let global = TestGlobal::new_with_driver_id(log_name);
+        global
+            .state()
+            .namespace()
+            .with_model(
+                EntityIDRef::new(mdl_name.space(), mdl_name.entity()),
+                |model| {
+                    let g = pin();
+                    for (username, password) in deleted.iter().chain(live) {
+                        let row = model
+                            .primary_index()
+                            .select(Lit::new_str(username), &g)
+                            .unwrap()
+                            .d_data()
+                            .read()
+                            .unwrap();
+                        assert_eq!(row.fields().get("password").unwrap().str(), password);
+                        assert_eq!(
+                            row.is_tombstoned(),
+                            deleted.iter().any(|(user, _)| user == username)
+                        );
+                    }
+                    Ok(())
+                },
+            )
+            .unwrap()
+    })
+}
+
+fn decode_journal(global: &TestGlobal, mdl_name: &EntityID, since: Option<u64>) -> String {
+    let export = {
+        let spaces = global.state().namespace().idx().read();
+        let models = global.state().namespace().idx_models().read();
+        let model = models
+            .get(&EntityIDRef::new(mdl_name.space(), mdl_name.entity()))
+            .unwrap();
+        let journal_path = paths_v1::model_path(
+            mdl_name.space(),
+            spaces.get(mdl_name.space()).unwrap().get_uuid(),
+            mdl_name.entity(),
+            model.data().get_uuid(),
+        );
+        JournalExport::snapshot(model, 0, &journal_path).unwrap()
+    };
+    export.decode(since).unwrap()
+}
+
+#[test]
+fn model_data_logical_decoding() {
+    test_utils::with_variable("model_data_logical_decoding", |log_name| {
+        let expected = [
+            r#"{"v":1,"lsn":0,"txn":0,"op":"insert","key":"sayan","row":{"followers":100,"bio":"says \"hi\""}}"#,
+            r#"{"v":1,"lsn":1,"txn":1,"op":"insert","key":"nandan","row":{"followers":1,"bio":"new\nline"}}"#,
+            r#"{"v":1,"lsn":2,"txn":2,"op":"update","key":"sayan","row":{"followers":200,"bio":"says \"hi\""}}"#,
+            r#"{"v":1,"lsn":3,"txn":3,"op":"delete","key":"nandan"}"#,
+        ];
+        let mdl_name;
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            mdl_name = create_model_and_space(
+                &global,
+                "create model apps.cdc(user_name: string, followers: uint64, bio: string)",
+            )
+            .unwrap();
+            run_insert(
+                &global,
+                r#"insert into apps.cdc('sayan', 100, 'says "hi"')"#,
+            )
+            .unwrap();
+            run_insert(&global, "insert into apps.cdc('nandan', 1, 'new\nline')").unwrap();
+            run_update(
+                &global,
+                "update apps.cdc set followers = 200 where user_name = 'sayan'",
+            )
+            .unwrap();
+            run_delete(&global, "delete from apps.cdc where user_name = 'nandan'").unwrap();
+            // the journal is still live
+            let lines = decode_journal(&global, &mdl_name, None);
+            assert_eq!(lines.lines().collect::<Vec<_>>(), expected);
+            // since is exclusive
+            let lines = decode_journal(&global, &mdl_name, Some(2));
+            assert_eq!(lines.lines().collect::<Vec<_>>(), &expected[3..]);
+            assert_eq!(decode_journal(&global, &mdl_name, Some(3)), "");
+        }
+        // reopen; the close and reopen events don't change the export
+        let global = TestGlobal::new_with_driver_id(log_name);
+        let lines = decode_journal(&global, &mdl_name, None);
+        assert_eq!(lines.lines().collect::<Vec<_>>(), expected);
+    })
+}
+
+/// Accept requests on a local port (acknowledging every one), and hand over the signature and body of each
+fn webhook_receiver() -> (String, mpsc::Receiver<(Option<String>, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hooks", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = vec![];
+            let mut buf = [0u8; 4096];
+            let head_len = loop {
+                let n = stream.read(&mut buf).unwrap();
+                assert_ne!(n, 0);
+                request.extend_from_slice(&buf[..n]);
+                if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break i + 4;
+                }
+            };
+            let head = String::from_utf8(request[..head_len].to_vec()).unwrap();
+            let header = |name: &str| {
+                head.lines().find_map(|line| {
+                    let (key, value) = line.split_once(':')?;
+                    key.eq_ignore_ascii_case(name)
+                        .then(|| value.trim().to_owned())
+                })
+            };
+            let content_length: usize = header("content-length").unwrap().parse().unwrap();
+            while request.len() < head_len + content_length {
+                let n = stream.read(&mut buf).unwrap();
+                assert_ne!(n, 0);
+                request.extend_from_slice(&buf[..n]);
+            }
+            let body = String::from_utf8(request[head_len..].to_vec()).unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            if tx.send((header(SIGNATURE_HEADER), body)).is_err() {
+                break;
+            }
+        }
+    });
+    (url, rx)
+}
+
+#[test]
+fn model_data_webhooks() {
+    test_utils::with_variable("model_data_webhooks", |log_name| {
+        let (url, requests) = webhook_receiver();
+        let entity = EntityIDRef::new("apps", "hooks");
+        let body = |events: &[&str]| {
+            format!(
+                r#"{{"webhook":"feed","model":"apps.hooks","events":[{}]}}"#,
+                events.join(",")
+            )
+        };
+        let mdl_name;
+        let cursor_path;
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            mdl_name = create_model_and_space(
+                &global,
+                "create model apps.hooks(user_name: string, followers: uint64)",
+            )
+            .unwrap();
+            // written before the webhook exists, so never delivered
+            run_insert(&global, "insert into apps.hooks('sayan', 100)").unwrap();
+            create_webhook(
+                &global,
+                "feed",
+                entity,
+                into_dict!(
+                    "url" => Lit::new_str(url.as_str()),
+                    "secret" => Lit::new_str("mysecret"),
+                    "batch" => Lit::new_uint(1)
+                ),
+            )
+            .unwrap();
+            assert_eq!(
+                create_webhook(
+                    &global,
+                    "feed",
+                    entity,
+                    into_dict!("url" => Lit::new_str(url.as_str()))
+                )
+                .unwrap_err(),
+                QueryError::QExecDdlObjectAlreadyExists
+            );
+            for props in [
+                into_dict!(),
+                into_dict!("url" => Lit::new_str("ftp://localhost/hooks")),
+                into_dict!("url" => Lit::new_str(url.as_str()), "batch" => Lit::new_uint(0)),
+                into_dict!("url" => Lit::new_str(url.as_str()), "retries" => Lit::new_uint(1)),
+                into_dict!(
+                    "url" => Lit::new_str("kafka://localhost:9092/changes"),
+                    "secret" => Lit::new_str("s")
+                ),
+                into_dict!("url" => Lit::new_str("kafka://sky@localhost:9092/changes")),
+                into_dict!(
+                    "url" => Lit::new_str("kafka://sky@localhost:9092/changes?sasl=plain"),
+                    "secret" => Lit::new_str("s")
+                ),
+            ] {
+                assert_eq!(
+                    create_webhook(&global, "other", entity, props).unwrap_err(),
+                    QueryError::QExecDdlInvalidProperties
+                );
+            }
+            run_insert(&global, "insert into apps.hooks('nandan', 1)").unwrap();
+            run_update(
+                &global,
+                "update apps.hooks set followers = 200 where user_name = 'sayan'",
+            )
+            .unwrap();
+            let expected = decode_journal(&global, &mdl_name, None);
+            let expected: Vec<&str> = expected.lines().skip(1).collect();
+            assert_eq!(expected.len(), 2);
+            // a batch of 1 sends every event on its own, in order
+            webhook::deliver(&global);
+            for &event in &expected {
+                let (signed, received) = requests.try_recv().unwrap();
+                assert_eq!(received, body(&[event]));
+                assert_eq!(signed.unwrap(), signature("mysecret", &received));
+            }
+            // nothing new to deliver
+            webhook::deliver(&global);
+            assert!(requests.try_recv().is_err());
+            let report = describe_webhooks(&global);
+            assert!(report.starts_with(&format!(
+                r#"[{{"model":"apps.hooks","name":"feed","url":"{url}","batch":1,"signed":true,"lsn":"#
+            )));
+            assert!(report.ends_with(r#","delivered":2,"failures":0,"lag":0,"last_error":null}]"#));
+            // the cursor belongs to the model
+            let gns = global.state().namespace();
+            let space_uuid = gns.idx().read().get("apps").unwrap().get_uuid();
+            let model_uuid = gns
+                .idx_models()
+                .read()
+                .get(&entity)
+                .unwrap()
+                .data()
+                .get_uuid();
+            let data_dir = paths_v1::data_dir();
+            let model_dir = paths_v1::model_dir("apps", space_uuid, "hooks", model_uuid);
+            cursor_path = WebhookCursor::path(&model_dir, "feed");
+            assert!(WebhookCursor::read(&cursor_path).unwrap().is_some());
+            let model_rel = &model_dir[data_dir.len() + 1..];
+            let files = FileSystem::list_files(&model_dir)
+                .unwrap()
+                .into_iter()
+                .map(|(file, len)| (format!("{model_rel}/{file}"), len))
+                .collect();
+            assert!(SelfCheck::inspect(gns, &data_dir, files)
+                .findings()
+                .is_empty());
+        }
+        // reopen; deliveries resume after the last batch that was delivered
+        let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+        run_insert(&global, "insert into apps.hooks('ohsayan', 7)").unwrap();
+        let expected = decode_journal(&global, &mdl_name, None);
+        let expected = expected.lines().last().unwrap();
+        webhook::deliver(&global);
+        let (_, received) = requests.try_recv().unwrap();
+        assert_eq!(received, body(&[expected]));
+        assert!(requests.try_recv().is_err());
+        // a dropped webhook takes its cursor with it
+        drop_webhook(&global, "feed", entity).unwrap();
+        assert_eq!(
+            drop_webhook(&global, "feed", entity).unwrap_err(),
+            QueryError::QExecObjectNotFound
+        );
+        assert_eq!(WebhookCursor::read(&cursor_path).unwrap(), None);
+        assert_eq!(describe_webhooks(&global), "[]");
+    })
+}
+
+#[test]
+fn model_data_offline_inspect() {
+    test_utils::with_variable("model_data_offline_inspect", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            create_model_and_space(
+                &global,
+                "create model apps.cdc(user_name: string, followers: uint64, bio: string)",
+            )
+            .unwrap();
+            run_insert(&global, "insert into apps.cdc('sayan', 100, 'hi')").unwrap();
+            run_insert(&global, "insert into apps.cdc('nandan', 1, 'hello')").unwrap();
+            run_update(
+                &global,
+                "update apps.cdc set followers = 200 where user_name = 'sayan'",
+            )
+            .unwrap();
+            run_delete(&global, "delete from apps.cdc where user_name = 'nandan'").unwrap();
+        }
+        let inspector = Inspector::load(log_name).unwrap();
+        let gns = inspector.describe_gns();
+        assert!(gns
+            .lines()
+            .any(|line| line.starts_with(r#"{"space":"apps","uuid":"#)
+                && line.ends_with(r#","models":["cdc"]}"#)));
+        assert!(gns
+            .lines()
+            .any(|line| line.starts_with(r#"{"model":"apps.cdc","uuid":"#)));
+        let journal = inspector.describe_journal("apps.cdc").unwrap();
+        assert_eq!(journal.lines().count(), 1);
+        assert!(journal.ends_with(
+            r#","journal":{"batches":4,"inserts":2,"updates":1,"tombstones":0,"deletes":1,"last_lsn":3}}
+"#
+        ));
+        let row = r#"{"key":"sayan","row":{"followers":200,"bio":"hi"}}
+"#;
+        assert_eq!(inspector.dump_rows("apps.cdc", None, None).unwrap(), row);
+        assert_eq!(
+            inspector
+                .dump_rows("apps.cdc", Some("sayan"), None)
+                .unwrap(),
+            row
+        );
+        assert_eq!(
+            inspector
+                .dump_rows("apps.cdc", Some("nandan"), None)
+                .unwrap(),
+            ""
+        );
+        assert!(inspector.dump_rows("apps.nope", None, None).is_err());
+    })
+}
+
+#[test]
+fn model_data_restore_from_snapshot() {
+    test_utils::with_variable("model_data_restore_from_snapshot", |log_name| {
+        let snapshot = format!("{log_name}-snapshot");
+        let (users, restored) = (
+            EntityIDRef::new("apps", "users"),
+            EntityIDRef::new("apps", "users_restored"),
+        );
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            create_model_and_space(
+                &global,
+                "create model apps.users(username: string, followers: uint64, null bio: string)",
+            )
+            .unwrap();
+            run_insert(&global, "insert into apps.users('sayan', 100, 'hi')").unwrap();
+            run_insert(&global, "insert into apps.users('nandan', 1, null)").unwrap();
+            run_insert(&global, "insert into apps.users('sky', 5, 'db')").unwrap();
+            run_delete(&global, "delete from apps.users where username = 'sky'").unwrap();
+        }
+        {
+            // take a snapshot of the data directory
+            let global = TestGlobal::new_with_driver_id(log_name);
+            let gns = global.state().namespace();
+            let space_uuid = gns.idx().read().get("apps").unwrap().get_uuid();
+            let model_uuid = gns
+                .idx_models()
+                .read()
+                .get(&users)
+                .unwrap()
+                .data()
+                .get_uuid();
+            let model_path = paths_v1::model_path("apps", space_uuid, "users", model_uuid);
+            for (from, to) in [
+                (log_name.to_owned(), paths_v1::gns_path()),
+                (model_path.clone(), model_path),
+            ] {
+                let to = paths_v1::in_copy(&snapshot, &to);
+                FileSystem::create_dir_all(to.rsplit_once('/').unwrap().0).unwrap();
+                File::create(&to)
+                    .unwrap()
+                    .fwrite_all(&FileSystem::read(&from).unwrap())
+                    .unwrap();
+            }
+            // the rows are lost
+            run_delete(&global, "delete from apps.users where username = 'sayan'").unwrap();
+            run_delete(&global, "delete from apps.users where username = 'nandan'").unwrap();
+            // and brought back from the snapshot
+            assert_eq!(
+                restore_model(&global, users, &snapshot, restored).unwrap(),
+                2
+            );
+            // but never over a model that exists
+            assert_eq!(
+                restore_model(&global, users, &snapshot, restored).unwrap_err(),
+                QueryError::QExecDdlObjectAlreadyExists
+            );
+            assert_eq!(
+                restore_model(
+                    &global,
+                    EntityIDRef::new("apps", "nope"),
+                    &snapshot,
+                    restored
+                )
+                .unwrap_err(),
+                QueryError::QExecObjectNotFound
+            );
+            assert_eq!(
+                restore_model(&global, users, "nosnapshot", restored).unwrap_err(),
+                QueryError::QExecObjectNotFound
+            );
+        }
+        // the restored rows were written to disk like any other insert
+        let inspector = Inspector::load(log_name).unwrap();
+        assert_eq!(
+            inspector
+                .dump_rows("apps.users_restored", None, None)
+                .unwrap(),
+            "{\"key\":\"nandan\",\"row\":{\"followers\":1,\"bio\":null}}\n\
+            {\"key\":\"sayan\",\"row\":{\"followers\":100,\"bio\":\"hi\"}}\n"
+        );
+        assert_eq!(inspector.dump_rows("apps.users", None, None).unwrap(), "");
+    })
+}
+
+#[test]
+fn model_data_consistent_export() {
+    test_utils::with_variable("model_data_consistent_export", |log_name| {
+        let dir = format!("{log_name}-export");
+        let read = |path: &str| String::from_utf8(FileSystem::read(path).unwrap()).unwrap();
+        let global = TestGlobal::new_with_driver_id(log_name);
+        create_model_and_space(
+            &global,
+            "create model apps.users(username: string, followers: uint64)",
+        )
+        .unwrap();
+        create_model_and_space(
+            &global,
+            "create model shop.orders(id: uint64, username: string)",
+        )
+        .unwrap();
+        run_insert(&global, "insert into apps.users('sayan', 100)").unwrap();
+        run_insert(&global, "insert into shop.orders(1, 'sayan')").unwrap();
+        let models = [
+            EntityIDRef::new("apps", "users"),
+            EntityIDRef::new("shop", "orders"),
+        ];
+        // nothing was written out yet, so the cut writes out the pending changes first
+        assert_eq!(
+            export_models(&global, &models, &dir).unwrap(),
+            format!(
+                "{{\"model\":\"apps.users\",\"path\":\"{dir}/apps.users.jsonl\",\"lsns\":[0],\"rows\":1}}\n\
+                {{\"model\":\"shop.orders\",\"path\":\"{dir}/shop.orders.jsonl\",\"lsns\":[0],\"rows\":1}}\n"
+            )
+        );
+        assert_eq!(
+            read(&format!("{dir}/apps.users.jsonl")),
+            "{\"key\":\"sayan\",\"row\":{\"followers\":100}}\n"
+        );
+        assert_eq!(
+            read(&format!("{dir}/shop.orders.jsonl")),
+            "{\"key\":1,\"row\":{\"username\":\"sayan\"}}\n"
+        );
+        // the next cut has the delete, and only in the model that it was made to
+        run_delete(&global, "delete from apps.users where username = 'sayan'").unwrap();
+        assert_eq!(
+            export_models(&global, &models, &dir).unwrap(),
+            format!(
+                "{{\"model\":\"apps.users\",\"path\":\"{dir}/apps.users.jsonl\",\"lsns\":[1],\"rows\":0}}\n\
+                {{\"model\":\"shop.orders\",\"path\":\"{dir}/shop.orders.jsonl\",\"lsns\":[0],\"rows\":1}}\n"
+            )
+        );
+        assert_eq!(read(&format!("{dir}/apps.users.jsonl")), "");
+        assert_eq!(
+            export_models(&global, &[EntityIDRef::new("apps", "nope")], &dir).unwrap_err(),
+            QueryError::QExecObjectNotFound
+        );
+    })
+}
+
+#[test]
+fn model_data_point_in_time_recovery() {
+    test_utils::with_variable("model_data_point_in_time_recovery", |log_name| {
+        let snapshot = format!("{log_name}-snapshot");
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            create_model_and_space(
+                &global,
+                "create model apps.users(username: string, followers: uint64)",
+            )
+            .unwrap();
+            run_insert(&global, "insert into apps.users('sayan', 100)").unwrap();
+            run_insert(&global, "insert into apps.users('nandan', 1)").unwrap();
+        }
+        {
+            // take a snapshot of the data directory
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            let gns = global.state().namespace();
+            let space_uuid = gns.idx().read().get("apps").unwrap().get_uuid();
+            let model_uuid = gns
+                .idx_models()
+                .read()
+                .get(&EntityIDRef::new("apps", "users"))
+                .unwrap()
+                .data()
+                .get_uuid();
+            let model_path = paths_v1::model_path("apps", space_uuid, "users", model_uuid);
+            for (from, to) in [
+                (log_name.to_owned(), paths_v1::gns_path()),
+                (model_path.clone(), model_path),
+            ] {
+                let to = paths_v1::in_copy(&snapshot, &to);
+                FileSystem::create_dir_all(to.rsplit_once('/').unwrap().0).unwrap();
+                File::create(&to)
+                    .unwrap()
+                    .fwrite_all(&FileSystem::read(&from).unwrap())
+                    .unwrap();
+            }
+            run_update(
+                &global,
+                "update apps.users set followers = 200 where username = 'sayan'",
+            )
+            .unwrap();
+            run_delete(&global, "delete from apps.users where username = 'nandan'").unwrap();
+        }
+        // lsns: the inserts are 0 and 1, then come the close and reopen, then the update (4) and the delete (5)
+        let inspector = Inspector::load(log_name).unwrap();
+        let snapshots = [Inspector::load_copy(&snapshot).unwrap()];
+        let recover = |snapshots: &[Inspector], until, with_rows| {
+            let out = inspector
+                .recover_model(
+                    "apps.users",
+                    snapshots,
+                    &ReplayBounds::new(until, vec![]),
+                    with_rows,
+                )
+                .unwrap();
+            let mut lines = out.lines();
+            let partition = lines.next().unwrap().to_owned();
+            let rows = lines.next().unwrap();
+            (
+                partition,
+                rows.rsplit_once(r#""rows":"#).unwrap().1.to_owned(),
+                lines.map(|line| format!("{line}\n")).collect::<String>(),
+            )
+        };
+        // everything after the snapshot
+        assert_eq!(
+            recover(&snapshots, None, false),
+            (
+                format!(
+                    r#"{{"partition":0,"snapshot":"{snapshot}","snapshot_lsn":1,"batches":2,"skipped":[],"recovered_lsn":5}}"#
+                ),
+                "1}".to_owned(),
+                String::new()
+            )
+        );
+        // right before the delete
+        assert_eq!(
+            recover(&snapshots, Some(4), true),
+            (
+                format!(
+                    r#"{{"partition":0,"snapshot":"{snapshot}","snapshot_lsn":1,"batches":1,"skipped":[],"recovered_lsn":4}}"#
+                ),
+                "2}".to_owned(),
+                "{\"key\":\"nandan\",\"row\":{\"followers\":1}}\n\
+                {\"key\":\"sayan\",\"row\":{\"followers\":200}}\n"
+                    .to_owned()
+            )
+        );
+        // the snapshot is past the recovery point, so the journal is replayed from its start
+        assert_eq!(
+            recover(&snapshots, Some(0), true),
+            (
+                r#"{"partition":0,"snapshot":null,"snapshot_lsn":null,"batches":1,"skipped":[],"recovered_lsn":0}"#
+                    .to_owned(),
+                "1}".to_owned(),
+                "{\"key\":\"sayan\",\"row\":{\"followers\":100}}\n".to_owned()
+            )
+        );
+        assert_eq!(
+            recover(&[], None, false),
+            (
+                r#"{"partition":0,"snapshot":null,"snapshot_lsn":null,"batches":4,"skipped":[],"recovered_lsn":5}"#
+                    .to_owned(),
+                "1}".to_owned(),
+                String::new()
+            )
+        );
+    })
+}
+
+#[test]
+fn model_data_offline_replay() {
+    test_utils::with_variable("model_data_offline_replay", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            create_model_and_space(
+                &global,
+                "create model apps.cdc(user_name: string, followers: uint64)",
+            )
+            .unwrap();
+            run_insert(&global, "insert into apps.cdc('sayan', 100)").unwrap();
+            run_insert(&global, "insert into apps.cdc('nandan', 1)").unwrap();
+            run_update(
+                &global,
+                "update apps.cdc set followers = 200 where user_name = 'sayan'",
+            )
+            .unwrap();
+            run_delete(&global, "delete from apps.cdc where user_name = 'nandan'").unwrap();
+        }
+        // the global journal
+        let replay = Inspector::load(log_name).unwrap().describe_replay();
+        assert_eq!(replay.lines().count(), 2);
+        assert!(replay
+            .lines()
+            .any(|line| line.ends_with(r#","events":2,"skipped":[]}"#)));
+        assert!(replay
+            .lines()
+            .any(|line| line.starts_with(r#"{"model":"apps.cdc","decl":"#)
+                && line.ends_with(r#","rows":1}"#)));
+        // stop before the model was created
+        let replay = Inspector::load_bounded(log_name, &ReplayBounds::new(Some(0), vec![]))
+            .unwrap()
+            .describe_replay();
+        assert_eq!(replay.lines().count(), 1);
+        assert!(replay.ends_with(
+            r#","events":1,"skipped":[]}
+"#
+        ));
+        // the model's journal
+        let inspector = Inspector::load(log_name).unwrap();
+        let replay = |until, skip| {
+            let out = inspector
+                .replay_model("apps.cdc", None, &ReplayBounds::new(until, skip))
+                .unwrap();
+            let mut lines = out.lines();
+            let partition = lines.next().unwrap().to_owned();
+            let rows = lines.next().unwrap();
+            assert!(lines.next().is_none());
+            (
+                partition,
+                rows.rsplit_once(r#""rows":"#).unwrap().1.to_owned(),
+            )
+        };
+        assert_eq!(
+            replay(None, vec![]),
+            (
+                r#"{"partition":0,"batches":4,"events":4,"skipped":[]}"#.to_owned(),
+                "1}".to_owned()
+            )
+        );
+        // only the inserts
+        assert_eq!(
+            replay(Some(1), vec![]),
+            (
+                r#"{"partition":0,"batches":2,"events":2,"skipped":[]}"#.to_owned(),
+                "2}".to_owned()
+            )
+        );
+        // leave out the delete
+        assert_eq!(
+            replay(None, vec![3]),
+            (
+                r#"{"partition":0,"batches":3,"events":3,"skipped":[3]}"#.to_owned(),
+                "2}".to_owned()
+            )
+        );
+        assert!(inspector
+            .replay_model("apps.cdc", Some(1), &ReplayBounds::default())
+            .is_err());
+    })
+}
+
+#[test]
+fn model_data_verify() {
+    test_utils::with_variable("model_data_verify", |log_name| {
+        let mdl_name;
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            mdl_name = create_model_and_space(
+                &global,
+                "create model apps.social(user_name: string, followers: uint64) with { partitions: 4 }",
+            )
+            .unwrap();
+            for i in 0..100 {
+                run_insert(&global, &format!("insert into apps.social('user{i}', {i})")).unwrap();
+            }
+            run_update(
+                &global,
+                "update apps.social set followers = 1000 where user_name = 'user0'",
+            )
+            .unwrap();
+            run_delete(&global, "delete from apps.social where user_name = 'user1'").unwrap();
+            let report = verify_model(
+                &global,
+                EntityIDRef::new(mdl_name.space(), mdl_name.entity()),
+                true,
+            )
+            .unwrap();
+            assert_eq!(report.consistent(), Some(true));
+            assert_eq!(report.rows(), 99);
+        }
+        // everything is restored from the journals
+        let global = TestGlobal::new_with_driver_id(log_name);
+        let report = verify_model(
+            &global,
+            EntityIDRef::new(mdl_name.space(), mdl_name.entity()),
+            false,
+        )
+        .unwrap();
+        assert_eq!(report.consistent(), Some(true));
+        assert_eq!(report.rows(), 99);
+        assert_eq!(report.invalid_rows(), 0);
+        assert_eq!(report.stale_rows(), 0);
+        assert!(report.rebuilt_indexes().is_empty());
+    })
+}
+
+#[test]
+fn model_data_disk_usage() {
+    test_utils::with_variable("model_data_disk_usage", |log_name| {
+        let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+        let mdl_name = create_model_and_space(
+            &global,
+            "create model apps.social(user_name: string, password: string) with { partitions: 4 }",
+        )
+        .unwrap();
+        for (username, password) in create_test_kv_strings(100) {
+            run_insert(
+                &global,
+                &format!("insert into apps.social('{username}', '{password}')"),
+            )
+            .unwrap();
+        }
+        let spaces = global.state().namespace().idx().read();
+        let models = global.state().namespace().idx_models().read();
+        let space_uuid = spaces.get(mdl_name.space()).unwrap().get_uuid();
+        let model = models
+            .get(&EntityIDRef::new(mdl_name.space(), mdl_name.entity()))
+            .unwrap();
+        let partition_paths: Vec<String> = (0..4)
+            .map(|partition| {
+                paths_v1::model_partition_path(
+                    mdl_name.space(),
+                    space_uuid,
+                    mdl_name.entity(),
+                    model.data().get_uuid(),
+                    partition,
+                )
+            })
+            .collect();
+        let batches: u64 = partition_paths
+            .iter()
+            .map(|path| FileSystem::read(path).unwrap().len() as u64)
+            .sum();
+        let usage = || {
+            DiskUsage::of_model(
+                mdl_name.space(),
+                space_uuid,
+                mdl_name.entity(),
+                model.data().get_uuid(),
+            )
+            .unwrap()
+        };
+        assert_eq!(usage().batches(), batches);
+        assert_eq!(usage().snapshots(), 0);
+        assert_eq!(usage().total(), batches);
+        // a copy of the journal is accounted for until the export is done
+        let export = JournalExport::snapshot(model, 0, &partition_paths[0]).unwrap();
+        assert_eq!(
+            usage().snapshots(),
+            FileSystem::read(&partition_paths[0]).unwrap().len() as u64
+        );
+        export.decode(None).unwrap();
+        assert_eq!(usage().snapshots(), 0);
+        // the space includes all its models
+        assert_eq!(
+            DiskUsage::of_space(mdl_name.space(), space_uuid)
+                .unwrap()
+                .total(),
+            batches
+        );
+    })
+}
+
+#[test]
+fn model_data_self_check() {
+    test_utils::with_variable("model_data_self_check", |log_name| {
+        let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+        let mdl_name = create_model_and_space(
+            &global,
+            "create model apps.social(user_name: string, password: string) with { partitions: 2 }",
+        )
+        .unwrap();
+        for (username, password) in create_test_kv_strings(10) {
+            run_insert(
+                &global,
+                &format!("insert into apps.social('{username}', '{password}')"),
+            )
+            .unwrap();
+        }
+        let gns = global.state().namespace();
+        let space_uuid = gns.idx().read().get(mdl_name.space()).unwrap().get_uuid();
+        let model_uuid = gns
+            .idx_models()
+            .read()
+            .get(&EntityIDRef::new(mdl_name.space(), mdl_name.entity()))
+            .unwrap()
+            .data()
+            .get_uuid();
+        let data_dir = paths_v1::data_dir();
+        let space_dir = paths_v1::space_dir(mdl_name.space(), space_uuid);
+        let model_dir =
+            paths_v1::model_dir(mdl_name.space(), space_uuid, mdl_name.entity(), model_uuid);
+        let space_rel = &space_dir[data_dir.len() + 1..];
+        let model_rel = &model_dir[data_dir.len() + 1..];
+        // other tests share the data directory, so only look at our space
+        let inspect = || {
+            let files = FileSystem::list_files(&space_dir)
+                .unwrap()
+                .into_iter()
+                .map(|(file, len)| (format!("{space_rel}/{file}"), len))
+                .collect();
+            SelfCheck::inspect(gns, &data_dir, files)
+        };
+        assert!(inspect().findings().is_empty());
+        // leave behind what a crash could: a dropped model's directory, a partition from before a repartition and an
+        // unfinished export
+        let batch = FileSystem::read(&format!("{model_dir}/data.db-btlog")).unwrap();
+        let write = |path: &str| File::create(path).unwrap().fwrite_all(&batch).unwrap();
+        FileSystem::create_dir_all(&format!("{space_dir}/mdl_dropped-0000")).unwrap();
+        write(&format!("{space_dir}/mdl_dropped-0000/data.db-btlog"));
+        write(&format!("{model_dir}/data.p2.db-btlog"));
+        write(&format!("{model_dir}/data.db-btlog-export-1"));
+        write(&format!("{model_dir}/notes.txt"));
+        let check = inspect();
+        let mut findings: Vec<String> = check.findings().iter().map(|f| f.to_string()).collect();
+        findings.sort();
+        let mut expected = vec![
+            Finding::OrphanModelDir {
+                dir: format!("{space_rel}/mdl_dropped-0000"),
+                bytes: batch.len() as u64,
+                foreign_version: false,
+            }
+            .to_string(),
+            Finding::StrayPartition {
+                model: "apps.social".into(),
+                file: format!("{model_rel}/data.p2.db-btlog"),
+                foreign_version: false,
+            }
+            .to_string(),
+            Finding::StaleSnapshot {
+                file: format!("{model_rel}/data.db-btlog-export-1"),
+            }
+            .to_string(),
+            Finding::UnknownFile {
+                file: format!("{model_rel}/notes.txt"),
+            }
+            .to_string(),
+        ];
+        expected.sort();
+        assert_eq!(findings, expected);
+        // repair moves everything into quarantine and leaves the model alone
+        let quarantine_dir = format!("{log_name}-quarantine");
+        check.repair(&data_dir, &quarantine_dir).unwrap();
+        assert!(inspect().findings().is_empty());
+        let mut quarantined: Vec<String> = FileSystem::list_files(&quarantine_dir)
+            .unwrap()
+            .into_iter()
+            .map(|(file, _)| file)
+            .collect();
+        quarantined.sort();
+        let mut expected = vec![
+            format!("{space_rel}/mdl_dropped-0000/data.db-btlog"),
+            format!("{model_rel}/data.p2.db-btlog"),
+            format!("{model_rel}/data.db-btlog-export-1"),
+            format!("{model_rel}/notes.txt"),
+        ];
+        expected.sort();
+        assert_eq!(quarantined, expected);
+        assert_eq!(
+            FileSystem::read(&format!("{model_dir}/data.db-btlog")).unwrap(),
+            batch
+        );
+    })
+}
+
+#[test]
+fn drop_space_finished_on_restore() {
+    test_utils::with_variable("drop_space_finished_on_restore", |log_name| {
+        let space_dir = {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            let mdl_name = create_model_and_space(
+                &global,
+                "create model dropped.social(user_name: string, password: string)",
+            )
+            .unwrap();
+            for (username, password) in create_test_kv_strings(10) {
+                run_insert(
+                    &global,
+                    &format!("insert into dropped.social('{username}', '{password}')"),
+                )
+                .unwrap();
+            }
+            let space_uuid = global
+                .state()
+                .namespace()
+                .idx()
+                .read()
+                .get(mdl_name.space())
+                .unwrap()
+                .get_uuid();
+            let tokens = lex_insecure(b"drop space allow not empty dropped").unwrap();
+            let drop: DropSpace = ast::parse_ast_node_full(&tokens[2..]).unwrap();
+            Space::transactional_exec_drop(&global, drop).unwrap();
+            assert!(!global.state().namespace().contains_space("dropped"));
+            assert!(global
+                .state()
+                .namespace()
+                .idx_models()
+                .read()
+                .get(&EntityIDRef::new("dropped", "social"))
+                .is_none());
+            // the test global never runs background tasks, which is what a crash right after the commit looks like
+            let space_dir = paths_v1::space_dir("dropped", space_uuid);
+            assert!(!FileSystem::list_files(&space_dir).unwrap().is_empty());
+            space_dir
+        };
+        // the drop is finished on restore, and doing it again is harmless
+        for _ in 0..2 {
+            let global = TestGlobal::new_with_driver_id(log_name);
+            assert!(!global.state().namespace().contains_space("dropped"));
+            assert!(global.state().namespace().idx_models().read().is_empty());
+            assert!(FileSystem::list_files(&space_dir).is_err());
+        }
+    })
+}
+
+#[test]
+fn gns_export_import() {
+    test_utils::with_variable(
+        ("gns_export_import_src", "gns_export_import_dst"),
+        |(src_log, dst_log)| {
+            let export_path = format!("{src_log}-export");
+            let (space_uuid, model_uuid);
+            {
+                let global = TestGlobal::new_with_driver_id_instant_update(src_log);
+                let mdl_name = create_model_and_space(
+                    &global,
+                    "create model apps.social(user_name: string, city: string)",
+                )
+                .unwrap();
+                let tokens = lex_insecure(b"create index bycity on apps.social(city)").unwrap();
+                let create_index: CreateIndex = ast::parse_ast_node_full(&tokens[2..]).unwrap();
+                ModelData::transactional_exec_create_index(&global, create_index).unwrap();
+                global
+                    .state()
+                    .namespace()
+                    .sys_db()
+                    .create_user(&global, "sayan".into(), "password")
+                    .unwrap();
+                global
+                    .state()
+                    .namespace()
+                    .sys_db()
+                    .create_statement(
+                        &global,
+                        "sayan",
+                        "by_city",
+                        "select * from apps . social where city = ?".into(),
+                    )
+                    .unwrap();
+                // the data is left out
+                run_insert(&global, "insert into apps.social('sayan', 'london')").unwrap();
+                GNSExport::write(&export_path, global.state().namespace()).unwrap();
+                space_uuid = global.state().namespace().idx().read()["apps"].get_uuid();
+                model_uuid = global.state().namespace().idx_models().read()
+                    [&EntityIDRef::new(mdl_name.space(), mdl_name.entity())]
+                    .data()
+                    .get_uuid();
+            }
+            // a new instance (with its own data directory)
+            FileSystem::remove_dir_all(&paths_v1::space_dir("apps", space_uuid)).unwrap();
+            {
+                let global = TestGlobal::new_with_driver_id_instant_update(dst_log);
+                import_gns(&global, &export_path).unwrap();
+                // only an empty instance can be bootstrapped
+                assert_eq!(
+                    import_gns(&global, &export_path).unwrap_err(),
+                    QueryError::QExecDdlNotEmpty
+                );
+            }
+            // everything was committed to the new instance's journal
+            let global = TestGlobal::new_with_driver_id(dst_log);
+            assert_eq!(
+                global.state().namespace().idx().read()["apps"].get_uuid(),
+                space_uuid
+            );
+            let models = global.state().namespace().idx_models().read();
+            let model = &models[&EntityIDRef::new("apps", "social")];
+            assert_eq!(model.data().get_uuid(), model_uuid);
+            assert_eq!(model.data().primary_index().count(), 0);
+            assert_eq!(
+                model
+                    .data()
+                    .secondary_indexes()
+                    .st_get("bycity")
+                    .unwrap()
+                    .field(),
+                "city"
+            );
+            assert!(global
+                .state()
+                .namespace()
+                .sys_db()
+                .users()
+                .read()
+                .contains_key("sayan"));
+            assert_eq!(
+                global
+                    .state()
+                    .namespace()
+                    .sys_db()
+                    .statement("sayan", "by_city")
+                    .unwrap()
+                    .body(),
+                "select * from apps . social where city = ?"
+            );
+        },
+    )
+}
+
+#[test]
+fn stored_statements_restore() {
+    test_utils::with_variable("stored_statements_restore", |log_name| {
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            let sys_db = global.state().namespace().sys_db();
+            sys_db
+                .create_user(&global, "sayan".into(), "password")
+                .unwrap();
+            for field in ["city", "user_name"] {
+                sys_db
+                    .create_statement(
+                        &global,
+                        "sayan",
+                        &format!("by_{field}"),
+                        format!("select * from apps . social where {field} = ?"),
+                    )
+                    .unwrap();
+            }
+            assert_eq!(
+                sys_db
+                    .create_statement(
+                        &global,
+                        "sayan",
+                        "by_city",
+                        "delete from apps . social".into()
+                    )
+                    .unwrap_err(),
+                QueryError::QExecDdlObjectAlreadyExists
+            );
+            sys_db
+                .drop_statement(&global, "sayan", "by_user_name")
+                .unwrap();
+            assert_eq!(
+                sys_db
+                    .drop_statement(&global, "sayan", "by_user_name")
+                    .unwrap_err(),
+                QueryError::QExecObjectNotFound
+            );
+        }
+        // the statements are restored from the GNS journal
+        let global = TestGlobal::new_with_driver_id(log_name);
+        let sys_db = global.state().namespace().sys_db();
+        let by_city = sys_db.statement("sayan", "by_city").unwrap();
+        assert_eq!(by_city.body(), "select * from apps . social where city = ?");
+        assert_eq!(by_city.params(), 1);
+        assert!(sys_db.statement("sayan", "by_user_name").is_none());
+        // a statement belongs to the user that stored it
+        assert!(sys_db.statement("root", "by_city").is_none());
+    })
+}
+
+#[test]
+fn quotas_restore() {
+    test_utils::with_variable("quotas_restore", |log_name| {
+        let entity = EntityIDRef::new("apps", "social");
+        {
+            let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+            create_model_and_space(
+                &global,
+                "create model apps.social(user_name: string, city: string)",
+            )
+            .unwrap();
+            global
+                .state()
+                .namespace()
+                .sys_db()
+                .create_user(&global, "sayan".into(), "password")
+                .unwrap();
+            quota::alter_user_quota(&global, "sayan", into_dict!("writes" => Lit::new_uint(2)))
+                .unwrap();
+            quota::alter_model_quota(&global, entity, into_dict!("reads" => Lit::new_uint(1)))
+                .unwrap();
+            // the user's writes and the model's reads are limited, and separately
+            for _ in 0..2 {
+                quota::admit(&global, "sayan", entity, Access::Write).unwrap();
+            }
+            assert_eq!(
+                quota::admit(&global, "sayan", entity, Access::Write).unwrap_err(),
+                QueryError::SysQuotaExceeded
+            );
+            quota::admit(&global, "root", entity, Access::Read).unwrap();
+            assert_eq!(
+                quota::admit(&global, "root", entity, Access::Read).unwrap_err(),
+                QueryError::SysQuotaExceeded
+            );
+            quota::admit(&global, "root", entity, Access::Write).unwrap();
+            assert_eq!(
+                quota::alter_user_quota(
+                    &global,
+                    "sayan",
+                    into_dict!("deletes" => Lit::new_uint(1))
+                )
+                .unwrap_err(),
+                QueryError::QExecDdlInvalidProperties
+            );
+            assert_eq!(
+                quota::alter_user_quota(&global, "nobody", into_dict!()).unwrap_err(),
+                QueryError::SysAuthError
+            );
+            // schema exports carry the quotas
+            let restored =
+                GNSExport::decode(&GNSExport::encode(global.state().namespace())).unwrap();
+            assert_eq!(
+                restored.sys_db().users().read()["sayan"].quota(),
+                &Quota::new(0, 2)
+            );
+            assert_eq!(
+                restored.idx_models().read()[&entity].data().quota(),
+                &Quota::new(1, 0)
+            );
+        }
+        // the quotas are restored from the GNS journal (with full buckets)
+        let global = TestGlobal::new_with_driver_id(log_name);
+        quota::admit(&global, "sayan", entity, Access::Write).unwrap();
+        assert_eq!(
+            quota::describe(&global),
+            concat!(
+                "{\"users\":{\"sayan\":{\"reads\":0,\"writes\":2,\"rejected_reads\":0,\"rejected_writes\":0}},",
+                "\"models\":{\"apps.social\":{\"reads\":1,\"writes\":0,\"rejected_reads\":0,\"rejected_writes\":0}}}"
+            )
+        );
+    })
+}
+
+#[test]
+fn gns_export_corrupted() {
+    test_utils::with_variable("gns_export_corrupted", |log_name| {
+        let global = TestGlobal::new_with_driver_id(log_name);
+        create_model_and_space(
+            &global,
+            "create model apps.social(user_name: string, password: string)",
+        )
+        .unwrap();
+        let package = GNSExport::encode(global.state().namespace());
+        let restored = GNSExport::decode(&package).unwrap();
+        assert!(restored.idx().read().contains_key("apps"));
+        assert!(restored
+            .idx_models()
+            .read()
+            .contains_key(&EntityIDRef::new("apps", "social")));
+        // every flipped byte and every truncation is caught
+        for i in 0..package.len() {
+            let mut corrupted = package.clone();
+            corrupted[i] ^= 0xFF;
+            assert!(GNSExport::decode(&corrupted).is_err());
+            assert!(GNSExport::decode(&package[..i]).is_err());
+        }
+    })
+}
+
+#[test]
+fn jobs_file_corrupted() {
+    let global = TestGlobal::new_with_driver_id("jobs_file_corrupted");
+    jobs::run(&global, JobKind::RebuildIndexes, "*".into(), |_, _| {
+        JobStatus::failed(QueryError::SysServerError)
+    });
+    let file = FileSystem::read("jobs_file_corrupted-jobs.db").unwrap();
+    let records = JobsFile::decode(&file).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].owner, None);
+    assert_eq!(
+        records[0].status,
+        JobStatus::Failed(QueryError::SysServerError.value_u8())
+    );
+    // every flipped byte and every truncation is caught
+    for i in 0..file.len() {
+        let mut corrupted = file.clone();
+        corrupted[i] ^= 0xFF;
+        assert!(JobsFile::decode(&corrupted).is_err());
+        assert!(JobsFile::decode(&file[..i]).is_err());
+    }
+    // but a corrupted file doesn't hold up a restart
+    let mut corrupted = file.clone();
+    corrupted[0] ^= 0xFF;
+    assert_eq!(
+        JobsFile::decode(&corrupted).unwrap_err().kind(),
+        &ErrorKind::Storage(StorageError::JobsFileCorrupted)
+    );
+    FileSystem::remove_file("jobs_file_corrupted-jobs.db").unwrap();
+    File::create("jobs_file_corrupted-jobs.db")
+        .unwrap()
+        .fwrite_all(&corrupted)
+        .unwrap();
+    let restarted = Jobs::load("jobs_file_corrupted-jobs.db").unwrap();
+    assert_eq!(restarted.describe(), "[]");
+}
+
+#[test]
+fn warmup_file_corrupted() {
+    test_utils::with_variable("warmup_file_corrupted", |log_name| {
+        let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+        create_model_and_space(
+            &global,
+            "create model apps.social(user_id: uint64, password: string)",
+        )
+        .unwrap();
+        for id in 0..4 {
+            run_insert(&global, &format!("insert into apps.social({id}, 'pass')")).unwrap();
+        }
+        let key = |k| PrimaryIndexKey::try_from_dc(Datacell::new_uint_default(k)).unwrap();
+        // the last run's hot keys, of a row that was deleted since and of a model that was dropped since
+        let hot = [
+            HotModel {
+                space: "apps".into(),
+                model: "social".into(),
+                keys: vec![(key(1), 1 << 12), (key(2), 1 << 11), (key(9), 1 << 10)],
+            },
+            HotModel {
+                space: "apps".into(),
+                model: "dropped".into(),
+                keys: vec![(key(1), 1 << 12)],
+            },
+        ];
+        warmup::seed(&global, &hot);
+        let collected = warmup::collect(&global);
+        assert_eq!(collected, &hot[..1]);
+        // only the rows that still exist are warmed
+        assert_eq!(warmup::warm(&global, &collected), 2);
+        let path = format!("{log_name}-warmup.db");
+        warmup::save(&global, &path).unwrap();
+        let file = FileSystem::read(&path).unwrap();
+        assert_eq!(WarmupFile::decode(&file).unwrap(), collected);
+        // every flipped byte and every truncation is caught
+        for i in 0..file.len() {
+            let mut corrupted = file.clone();
+            corrupted[i] ^= 0xFF;
+            assert!(WarmupFile::decode(&corrupted).is_err());
+            assert!(WarmupFile::decode(&file[..i]).is_err());
+        }
+        // but a corrupted file doesn't hold up a restart
+        let mut corrupted = file.clone();
+        corrupted[0] ^= 0xFF;
+        assert_eq!(
+            WarmupFile::decode(&corrupted).unwrap_err().kind(),
+            &ErrorKind::Storage(StorageError::WarmupFileCorrupted)
+        );
+        FileSystem::remove_file(&path).unwrap();
+        File::create(&path).unwrap().fwrite_all(&corrupted).unwrap();
+        assert_eq!(warmup::load(&path).unwrap(), vec![]);
+        FileSystem::remove_file(&path).unwrap();
+        // and neither does a missing one
+        assert_eq!(warmup::load(&path).unwrap(), vec![]);
+    })
+}
+
+#[test]
+fn model_data_archive() {
+    test_utils::with_variable("model_data_archive", |log_name| {
+        let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+        let mdl_name = create_model_and_space(
+            &global,
+            "create model apps.social(user_name: string, password: string) with { partitions: 2 }",
+        )
+        .unwrap();
+        for (username, password) in create_test_kv_strings(50) {
+            run_insert(
+                &global,
+                &format!("insert into apps.social('{username}', '{password}')"),
+            )
+            .unwrap();
+        }
+        let partition_paths: Vec<String> = {
+            let spaces = global.state().namespace().idx().read();
+            let models = global.state().namespace().idx_models().read();
+            let space_uuid = spaces.get(mdl_name.space()).unwrap().get_uuid();
+            let model = models
+                .get(&EntityIDRef::new(mdl_name.space(), mdl_name.entity()))
+                .unwrap();
+            (0..2)
+                .map(|partition| {
+                    paths_v1::model_partition_path(
+                        mdl_name.space(),
+                        space_uuid,
+                        mdl_name.entity(),
+                        model.data().get_uuid(),
+                        partition,
+                    )
+                })
+                .collect()
+        };
+        let archive_dir = format!("{log_name}-archive");
+        let journals_len = || -> u64 {
+            partition_paths
+                .iter()
+                .map(|path| FileSystem::file_len(path).unwrap())
+                .sum()
+        };
+        let assert_archived = || {
+            for path in &partition_paths {
+                assert_eq!(
+                    FileSystem::read(&paths_v1::in_copy(&archive_dir, path)).unwrap(),
+                    FileSystem::read(path).unwrap()
+                );
+            }
+        };
+        let archive = Archive::new(&archive_dir);
+        archive.pass(&global);
+        assert_archived();
+        assert_eq!(archive.lag(), journals_len());
+        // only what was committed since is copied
+        let before = journals_len();
+        for (username, password) in create_test_kv_strings(20) {
+            run_insert(
+                &global,
+                &format!("insert into apps.social('{username}', '{password}')"),
+            )
+            .unwrap();
+        }
+        archive.pass(&global);
+        assert_archived();
+        assert_eq!(archive.lag(), journals_len() - before);
+        archive.pass(&global);
+        assert_eq!(archive.lag(), 0);
+        // a journal that is shorter than its copy was rewritten, so the copy is moved aside
+        let copy = paths_v1::in_copy(&archive_dir, &partition_paths[0]);
+        let mut f = File::open(&copy).unwrap();
+        f.f_seek_start(FileSystem::file_len(&copy).unwrap())
+            .unwrap();
+        f.fwrite_all(b"not a batch").unwrap();
+        drop(f);
+        archive.pass(&global);
+        assert_archived();
+        let (copy_dir, _) = copy.rsplit_once('/').unwrap();
+        assert_eq!(
+            FileSystem::list_files(copy_dir)
+                .unwrap()
+                .into_iter()
+                .filter(|(name, _)| name.starts_with("data.db-btlog."))
+                .count(),
+            1
+        );
+        assert!(archive.describe().contains("\"failures\":0"));
+        FileSystem::remove_dir_all(&archive_dir).unwrap();
+    })
+}
+
+#[test]
+fn model_data_relocate() {
+    test_utils::with_variable("model_data_relocate", |log_name| {
+        let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+        let mdl_name = create_model_and_space(
+            &global,
+            "create model apps.social(user_name: string, password: string) with { partitions: 2 }",
+        )
+        .unwrap();
+        for (username, password) in create_test_kv_strings(50) {
+            run_insert(
+                &global,
+                &format!("insert into apps.social('{username}', '{password}')"),
+            )
+            .unwrap();
+        }
+        let space_uuid = global
+            .state()
+            .namespace()
+            .idx()
+            .read()
+            .get(mdl_name.space())
+            .unwrap()
+            .get_uuid();
+        let models = global.state().namespace().idx_models().read();
+        let model = models
+            .get(&EntityIDRef::new(mdl_name.space(), mdl_name.entity()))
+            .unwrap();
+        let partition_paths: Vec<String> = (0..2)
+            .map(|partition| {
+                paths_v1::model_partition_path(
+                    mdl_name.space(),
+                    space_uuid,
+                    mdl_name.entity(),
+                    model.data().get_uuid(),
+                    partition,
+                )
+            })
+            .collect();
+        let volume = format!("{log_name}-volume");
+        let relocation = DataRelocation::new(&volume);
+        // files are copied a block at a time, until the copy has caught up
+        for path in &partition_paths {
+            let mut copied = 0;
+            loop {
+                match relocation.sync(path, 64).unwrap() {
+                    0 => break,
+                    n => {
+                        assert!(n <= 64);
+                        copied += n;
+                    }
+                }
+            }
+            assert_eq!(copied, FileSystem::file_len(path).unwrap());
+            assert!(relocation.verify(path).unwrap());
+        }
+        // a copy that was changed in place fails the check, and is fixed by copying the file again
+        let copy = relocation.copy_path(&partition_paths[0]);
+        let mut f = File::open(&copy).unwrap();
+        f.fwrite_all(b"not a batch").unwrap();
+        drop(f);
+        assert_eq!(relocation.sync(&partition_paths[0], 64).unwrap(), 0);
+        assert!(!relocation.verify(&partition_paths[0]).unwrap());
+        assert_eq!(
+            relocation.resync(&partition_paths[0]).unwrap(),
+            FileSystem::file_len(&partition_paths[0]).unwrap()
+        );
+        assert!(relocation.verify(&partition_paths[0]).unwrap());
+        // once the journals are reopened on their copies, batches are only written to the copies
+        let journals_len = |paths: &[String]| -> u64 {
+            paths
+                .iter()
+                .map(|path| FileSystem::file_len(path).unwrap())
+                .sum()
+        };
+        let copy_paths: Vec<String> = partition_paths
+            .iter()
+            .map(|path| relocation.copy_path(path))
+            .collect();
+        for (partition, copy) in copy_paths.iter().enumerate() {
+            model
+                .driver()
+                .batch_driver(partition)
+                .lock()
+                .as_mut()
+                .unwrap()
+                .relocate(copy)
+                .unwrap();
+        }
+        drop(models);
+        let before = journals_len(&partition_paths);
+        for (username, password) in create_test_kv_strings(20) {
+            run_insert(
+                &global,
+                &format!("insert into apps.social('{username}', '{password}')"),
+            )
+            .unwrap();
+        }
+        assert_eq!(journals_len(&partition_paths), before);
+        assert!(journals_len(&copy_paths) > before);
+        // and the copies hold every row
+        let models = global.state().namespace().idx_models().read();
+        let model = models
+            .get(&EntityIDRef::new(mdl_name.space(), mdl_name.entity()))
+            .unwrap();
+        let mut rows = PersistedRows::new();
+        for (partition, copy) in copy_paths.iter().enumerate() {
+            JournalExport::snapshot(model, partition, copy)
+                .unwrap()
+                .replay(&mut rows)
+                .unwrap();
+        }
+        assert_eq!(rows.remaining().0, 70);
+        // a copy that isn't caught up can't be written to
+        let path = format!("{log_name}-short");
+        File::create(&path).unwrap();
+        assert!(model
+            .driver()
+            .batch_driver(0)
+            .lock()
+            .as_mut()
+            .unwrap()
+            .relocate(&path)
+            .is_err());
+        drop(models);
+        FileSystem::remove_file(&path).unwrap();
+        // the journals are closed on their copies
+        drop(global);
+        FileSystem::remove_dir_all(&volume).unwrap();
+    })
+}
+
+#[test]
+fn model_data_snapshot() {
+    test_utils::with_variable("model_data_snapshot", |log_name| {
+        let global = TestGlobal::new_with_driver_id_instant_update(log_name);
+        let mdl_name = create_model_and_space(
+            &global,
+            "create model apps.social(user_name: string, password: string) with { partitions: 2 }",
+        )
+        .unwrap();
+        for (username, password) in create_test_kv_strings(50) {
+            run_insert(
+                &global,
+                &format!("insert into apps.social('{username}', '{password}')"),
+            )
+            .unwrap();
+        }
+        let partition_paths: Vec<String> = {
+            let spaces = global.state().namespace().idx().read();
+            let models = global.state().namespace().idx_models().read();
+            let space_uuid = spaces.get(mdl_name.space()).unwrap().get_uuid();
+            let model = models
+                .get(&EntityIDRef::new(mdl_name.space(), mdl_name.entity()))
+                .unwrap();
+            (0..2)
+                .map(|partition| {
+                    paths_v1::model_partition_path(
+                        mdl_name.space(),
+                        space_uuid,
+                        mdl_name.entity(),
+                        model.data().get_uuid(),
+                        partition,
+                    )
+                })
+                .collect()
+        };
+        // every journal is copied, and the freeze is lifted once it's done
+        let name = format!("{log_name}_manual");
+        let path = paths_v1::snapshot_path(&name);
+        let report = snapshot::create_snapshot(&global, &name).unwrap();
+        assert!(report.contains("\"files\":"));
+        for journal in &partition_paths {
+            assert_eq!(
+                FileSystem::read(&paths_v1::in_copy(&path, journal)).unwrap(),
+                FileSystem::read(journal).unwrap()
+            );
+        }
+        assert!(!global.state().freeze().is_frozen());
+        assert_eq!(
+            snapshot::create_snapshot(&global, &name).unwrap_err(),
+            QueryError::QExecDdlObjectAlreadyExists
+        );
+        let manifest = SnapshotFiles::read_manifest(&path).unwrap().unwrap();
+        assert_eq!(manifest.schedule, None);
+        let file = SnapshotFiles::encode_manifest(&manifest);
+        for i in 0..file.len() {
+            let mut corrupted = file.clone();
+            corrupted[i] ^= 0xFF;
+            assert!(SnapshotFiles::decode_manifest(&corrupted).is_err());
+            assert!(SnapshotFiles::decode_manifest(&file[..i]).is_err());
+        }
+        // a snapshot that is being restored from can't be dropped
+        let restoring = global.state().snapshots().restoring(&format!("./{path}/"));
+        assert!(snapshot::describe(&global)
+            .unwrap()
+            .contains("\"restoring\":true"));
+        assert_eq!(
+            snapshot::drop_snapshot(&global, &name).unwrap_err(),
+            QueryError::QExecDdlNotEmpty
+        );
+        drop(restoring);
+        snapshot::drop_snapshot(&global, &name).unwrap();
+        assert_eq!(
+            snapshot::drop_snapshot(&global, &name).unwrap_err(),
+            QueryError::QExecObjectNotFound
+        );
+        // a schedule keeps its newest snapshots, except for one that is being restored from
+        for created_at in 1..=3 {
+            let old = paths_v1::snapshot_path(&format!("{log_name}_{created_at}"));
+            FileSystem::create_dir_all(&old).unwrap();
+            SnapshotFiles::write_manifest(
+                &old,
+                &SnapshotManifest {
+                    created_at,
+                    bytes: 0,
+                    files: 0,
+                    schedule: Some(log_name.into()),
+                },
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            snapshot::schedule_snapshots(&global, log_name, 1, 2).unwrap_err(),
+            QueryError::QExecDdlInvalidProperties
+        );
+        snapshot::schedule_snapshots(&global, log_name, 60, 2).unwrap();
+        assert_eq!(
+            snapshot::schedule_snapshots(&global, log_name, 60, 2).unwrap_err(),
+            QueryError::QExecDdlObjectAlreadyExists
+        );
+        let oldest = paths_v1::snapshot_path(&format!("{log_name}_1"));
+        let restoring = global.state().snapshots().restoring(&oldest);
+        snapshot::run_schedules(&global);
+        let taken: Vec<Box<str>> = SnapshotFiles::list(&paths_v1::snapshots_dir())
+            .unwrap()
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .manifest
+                    .as_ref()
+                    .is_some_and(|manifest| manifest.schedule.as_deref() == Some(log_name))
+            })
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(taken.len(), 3);
+        assert!(taken.contains(&format!("{log_name}_1").into_boxed_str()));
+        assert!(taken.contains(&format!("{log_name}_3").into_boxed_str()));
+        assert!(!taken.contains(&format!("{log_name}_2").into_boxed_str()));
+        // the schedule isn't due again until an interval later
+        snapshot::run_schedules(&global);
+        assert_eq!(
+            SnapshotFiles::list(&paths_v1::snapshots_dir())
+                .unwrap()
+                .into_iter()
+                .filter(|entry| entry.name.starts_with(log_name))
+                .count(),
+            3
+        );
+        // schedules outlive a restart
+        global.state().snapshots().load_schedules().unwrap();
+        assert!(snapshot::describe(&global).unwrap().contains(&format!(
+            "{{\"name\":\"{log_name}\",\"every\":60,\"keep\":2"
+        )));
+        drop(restoring);
+        snapshot::drop_schedule(&global, log_name).unwrap();
+        for name in taken {
+            snapshot::drop_snapshot(&global, &name).unwrap();
+        }
+        FileSystem::remove_file(&paths_v1::snapshot_schedules_path()).unwrap();
+    })
+}
@@ -0,0 +1,130 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    disk usage
+    ---
+    usage is computed by listing the files in a space or model directory and classifying them by name. nothing is
+    cached, so the numbers are exact (as of the time of listing) but every report costs a directory walk
+*/
+
+use {
+    crate::{
+        engine::{
+            data::uuid::Uuid,
+            storage::common::{interface::fs::FileSystem, paths_v1},
+            RuntimeResult,
+        },
+        IoResult,
+    },
+    std::{io::ErrorKind, ops::AddAssign},
+};
+
+/// Suffix of model batch files (see [`paths_v1::model_partition_path`])
+pub(super) const BATCH_SUFFIX: &str = ".db-btlog";
+/// Infix of journal copies made for export or verification (see [`super::mdl_export::JournalExport`])
+pub(super) const SNAPSHOT_INFIX: &str = ".db-btlog-export-";
+
+/// The space used on disk by a model or a space, by file class
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DiskUsage {
+    /// batch files
+    batches: u64,
+    /// copies of batch files made for an export or verification that is running
+    snapshots: u64,
+    /// any other file
+    other: u64,
+}
+
+impl DiskUsage {
+    /// Returns the usage of a model's directory
+    pub fn of_model(
+        space_name: &str,
+        space_uuid: Uuid,
+        model_name: &str,
+        model_uuid: Uuid,
+    ) -> RuntimeResult<Self> {
+        Self::of_dir(&paths_v1::model_dir(
+            space_name, space_uuid, model_name, model_uuid,
+        ))
+    }
+    /// Returns the usage of a space's directory, which includes all its models
+    pub fn of_space(space_name: &str, space_uuid: Uuid) -> RuntimeResult<Self> {
+        Self::of_dir(&paths_v1::space_dir(space_name, space_uuid))
+    }
+    /// Returns the size of the global namespace journal
+    pub fn gns_journal() -> RuntimeResult<u64> {
+        Ok(or_empty(FileSystem::file_len(&paths_v1::gns_path()))?)
+    }
+    fn of_dir(dir: &str) -> RuntimeResult<Self> {
+        let mut usage = Self::default();
+        // a directory is only created once its first file is
+        for (name, len) in or_empty(FileSystem::list_files(dir))? {
+            if name.contains(SNAPSHOT_INFIX) {
+                usage.snapshots += len;
+            } else if name.ends_with(BATCH_SUFFIX) {
+                usage.batches += len;
+            } else {
+                usage.other += len;
+            }
+        }
+        Ok(usage)
+    }
+    pub fn batches(&self) -> u64 {
+        self.batches
+    }
+    pub fn snapshots(&self) -> u64 {
+        self.snapshots
+    }
+    pub fn total(&self) -> u64 {
+        self.batches + self.snapshots + self.other
+    }
+    /// Returns the usage as a JSON object
+    pub fn describe(&self) -> String {
+        format!(
+            "{{\"batches\":{},\"snapshots\":{},\"other\":{},\"total\":{}}}",
+            self.batches,
+            self.snapshots,
+            self.other,
+            self.total()
+        )
+    }
+}
+
+impl AddAssign for DiskUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        self.batches += rhs.batches;
+        self.snapshots += rhs.snapshots;
+        self.other += rhs.other;
+    }
+}
+
+fn or_empty<T: Default>(r: IoResult<T>) -> IoResult<T> {
+    match r {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(T::default()),
+        r => r,
+    }
+}
@@ -0,0 +1,186 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    warmup file
+    ---
+    the hot keys of every model as of the last shutdown (see `core::model::warmup`), so that the access history of
+    the models survives restarts. the file is rewritten in full (into a temporary file that then replaces it) when
+    the server shuts down:
+
+    [magic:8B][version:8B][model count:8B]
+    [space size:8B][space][model size:8B][model][key count:8B] (for every model)
+    [key tag:1B][key][estimate:8B] (for every key of the model)
+    [checksum:8B]
+
+    a key is 8 bytes (little endian) for an unsigned or signed integer, and [size:8B][bytes] for binary or a string.
+    the checksum is the CRC64 of everything that comes before it
+*/
+
+use crate::engine::{
+    core::{index::PrimaryIndexKey, model::warmup::HotModel},
+    data::{cell::Datacell, tag::TagUnique},
+    error::StorageError,
+    mem::BufferedScanner,
+    storage::common::{
+        checksum::SCrc64,
+        interface::fs::{File, FileSystem, FileWrite, FileWriteExt},
+    },
+    RuntimeResult,
+};
+
+/// The version of the warmup file. This is bumped whenever the layout of the file changes incompatibly
+pub const WARMUP_FILE_VERSION: u64 = 1;
+const WARMUP_FILE_MAGIC: [u8; 8] = *b"SKYWARM\0";
+
+/// The warmup file (see the module docs for the layout)
+pub struct WarmupFile;
+
+impl WarmupFile {
+    pub fn encode(models: &[HotModel]) -> Vec<u8> {
+        fn block(buf: &mut Vec<u8>, block: &[u8]) {
+            buf.extend((block.len() as u64).to_le_bytes());
+            buf.extend(block);
+        }
+        let mut buf = vec![];
+        buf.extend(WARMUP_FILE_MAGIC);
+        buf.extend(WARMUP_FILE_VERSION.to_le_bytes());
+        buf.extend((models.len() as u64).to_le_bytes());
+        for model in models {
+            block(&mut buf, model.space.as_bytes());
+            block(&mut buf, model.model.as_bytes());
+            buf.extend((model.keys.len() as u64).to_le_bytes());
+            for (key, estimate) in &model.keys {
+                buf.push(key.tag().value_u8());
+                unsafe {
+                    // UNSAFE(@ohsayan): +tagck
+                    match key.tag() {
+                        TagUnique::UnsignedInt => buf.extend(key.read_uint().to_le_bytes()),
+                        TagUnique::SignedInt => buf.extend(key.read_sint().to_le_bytes()),
+                        TagUnique::Bin => block(&mut buf, key.read_bin()),
+                        TagUnique::Str => block(&mut buf, key.read_str().as_bytes()),
+                        TagUnique::Illegal => unreachable!(),
+                    }
+                }
+                buf.extend(estimate.to_le_bytes());
+            }
+        }
+        let mut crc = SCrc64::new();
+        crc.update(&buf);
+        buf.extend(crc.finish().to_le_bytes());
+        buf
+    }
+    pub fn decode(file: &[u8]) -> RuntimeResult<Vec<HotModel>> {
+        if file.len() < sizeof!(u64) {
+            return Err(StorageError::WarmupFileCorrupted.into());
+        }
+        let (body, checksum) = file.split_at(file.len() - sizeof!(u64));
+        let mut crc = SCrc64::new();
+        crc.update(body);
+        if Some(crc.finish()) != read_u64(&mut BufferedScanner::new(checksum)) {
+            return Err(StorageError::WarmupFileCorrupted.into());
+        }
+        let mut scanner = BufferedScanner::new(body);
+        if scanner.try_next_variable_block(WARMUP_FILE_MAGIC.len()) != Some(&WARMUP_FILE_MAGIC[..])
+        {
+            return Err(StorageError::WarmupFileCorrupted.into());
+        }
+        match read_u64(&mut scanner) {
+            Some(WARMUP_FILE_VERSION) => {}
+            Some(_) => return Err(StorageError::HeaderDecodeVersionMismatch.into()),
+            None => return Err(StorageError::WarmupFileCorrupted.into()),
+        }
+        let count = read_u64(&mut scanner).ok_or(StorageError::WarmupFileCorrupted)?;
+        let mut models = vec![];
+        for _ in 0..count {
+            models.push(read_model(&mut scanner).ok_or(StorageError::WarmupFileCorrupted)?);
+        }
+        if scanner.eof() {
+            Ok(models)
+        } else {
+            Err(StorageError::WarmupFileCorrupted.into())
+        }
+    }
+    /// Replace the warmup file at `path` with the given models
+    pub fn write(path: &str, models: &[HotModel]) -> RuntimeResult<()> {
+        let tmp = format!("{path}.tmp");
+        // left behind if we went down in the middle of a write
+        match FileSystem::remove_file(&tmp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let mut f = File::create(&tmp)?;
+        f.fwrite_all(&Self::encode(models))?;
+        f.fsync_all()?;
+        drop(f);
+        FileSystem::rename(&tmp, path)?;
+        Ok(())
+    }
+    /// Read the models in the warmup file at `path`. There are none if the file doesn't exist
+    pub fn read(path: &str) -> RuntimeResult<Vec<HotModel>> {
+        match FileSystem::read(path) {
+            Ok(file) => Self::decode(&file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn read_model(scanner: &mut BufferedScanner) -> Option<HotModel> {
+    let space = read_str(scanner)?;
+    let model = read_str(scanner)?;
+    let count = read_u64(scanner)?;
+    let mut keys = vec![];
+    for _ in 0..count {
+        let key = match TagUnique::try_from_raw(scanner.try_next_byte()?)? {
+            TagUnique::UnsignedInt => Datacell::new_uint_default(read_u64(scanner)?),
+            TagUnique::SignedInt => Datacell::new_sint_default(read_u64(scanner)? as i64),
+            TagUnique::Bin => Datacell::new_bin(read_block(scanner)?.into()),
+            TagUnique::Str => Datacell::new_str(read_str(scanner)?),
+            TagUnique::Illegal => return None,
+        };
+        keys.push((PrimaryIndexKey::try_from_dc(key)?, read_u64(scanner)?));
+    }
+    Some(HotModel { space, model, keys })
+}
+
+fn read_block<'a>(scanner: &mut BufferedScanner<'a>) -> Option<&'a [u8]> {
+    let size = read_u64(scanner)?;
+    scanner.try_next_variable_block(usize::try_from(size).ok()?)
+}
+
+fn read_str(scanner: &mut BufferedScanner) -> Option<Box<str>> {
+    core::str::from_utf8(read_block(scanner)?)
+        .ok()
+        .map(Into::into)
+}
+
+fn read_u64(scanner: &mut BufferedScanner) -> Option<u64> {
+    scanner
+        .try_next_variable_block(sizeof!(u64))
+        .map(|b| u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+}
@@ -0,0 +1,118 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    webhook cursors
+    ---
+    the LSN of the last journal batch that a webhook delivered (see `core::model::webhook`), kept in the model's
+    directory so that deliveries resume where they left off after a restart. the file is rewritten (into a temporary
+    file that then replaces it) after every delivery:
+
+    [lsn:8B][checksum:8B]
+
+    the checksum is the CRC64 of the LSN. a webhook that hasn't delivered anything (and was created when nothing had
+    been written to the journal) has no cursor file
+*/
+
+use crate::engine::{
+    error::StorageError,
+    storage::common::{
+        checksum::SCrc64,
+        interface::fs::{File, FileSystem, FileWrite, FileWriteExt},
+    },
+    RuntimeResult,
+};
+
+/// Prefix of the names of cursor files
+const CURSOR_PREFIX: &str = "webhook_";
+/// Suffix of the names of cursor files
+const CURSOR_SUFFIX: &str = ".lsn";
+
+/// The cursor file of a webhook (see the module docs for the layout)
+pub struct WebhookCursor;
+
+impl WebhookCursor {
+    /// Returns the path of the given webhook's cursor file, in the model's directory
+    pub fn path(model_dir: &str, webhook: &str) -> String {
+        format!("{model_dir}/{CURSOR_PREFIX}{webhook}{CURSOR_SUFFIX}")
+    }
+    /// Returns the webhook that a cursor file belongs to, given the file's name
+    pub fn webhook_of(name: &str) -> Option<&str> {
+        name.strip_prefix(CURSOR_PREFIX)?
+            .strip_suffix(CURSOR_SUFFIX)
+    }
+    pub fn encode(lsn: u64) -> [u8; 16] {
+        let mut crc = SCrc64::new();
+        crc.update(&lsn.to_le_bytes());
+        let mut buf = [0; 16];
+        buf[..8].copy_from_slice(&lsn.to_le_bytes());
+        buf[8..].copy_from_slice(&crc.finish().to_le_bytes());
+        buf
+    }
+    pub fn decode(file: &[u8]) -> RuntimeResult<u64> {
+        if file.len() != 16 {
+            return Err(StorageError::WebhookCursorCorrupted.into());
+        }
+        let lsn = u64::from_le_bytes(file[..8].try_into().unwrap());
+        if Self::encode(lsn) == file {
+            Ok(lsn)
+        } else {
+            Err(StorageError::WebhookCursorCorrupted.into())
+        }
+    }
+    /// Replace the cursor file at `path` with the given LSN
+    pub fn write(path: &str, lsn: u64) -> RuntimeResult<()> {
+        let tmp = format!("{path}.tmp");
+        // left behind if we went down in the middle of a write
+        match FileSystem::remove_file(&tmp) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let mut f = File::create(&tmp)?;
+        f.fwrite_all(&Self::encode(lsn))?;
+        f.fsync_all()?;
+        drop(f);
+        FileSystem::rename(&tmp, path)?;
+        Ok(())
+    }
+    /// Read the LSN in the cursor file at `path`, if there is one
+    pub fn read(path: &str) -> RuntimeResult<Option<u64>> {
+        match FileSystem::read(path) {
+            Ok(file) => Self::decode(&file).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    /// Remove the cursor file at `path`, if there is one
+    pub fn remove(path: &str) -> RuntimeResult<()> {
+        match FileSystem::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
@@ -26,14 +26,19 @@
 
 use {
     self::impls::mdl_journal::{BatchStats, FullModel},
-    super::{common::interface::fs::FileSystem, v1, SELoaded},
+    super::{
+        common::interface::fs::FileSystem,
+        driver::{model_journals, SDSSDriver},
+        v1, SELoaded,
+    },
     crate::engine::{
         config::Configuration,
         core::{
+            model,
             system_db::{SystemDatabase, VerifyUser},
             GNSData, GlobalNS,
         },
-        fractal::{context, FractalGNSDriver},
+        fractal::{context, jobs::Jobs, FractalGNSDriver},
         storage::common::paths_v1,
         txn::{
             gns::{
@@ -53,6 +58,12 @@ pub(super) mod raw;
 
 pub const GNS_PATH: &str = v1::GNS_PATH;
 pub const DATA_DIR: &str = v1::DATA_DIR;
+pub const JOBS_PATH: &str = "jobs.db";
+pub const WARMUP_PATH: &str = "warmup.db";
+pub const RELOCATION_MARKER: &str = "relocated";
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+pub const SNAPSHOT_MANIFEST: &str = "snapshot.db";
+pub const SNAPSHOT_SCHEDULES: &str = "schedules.db";
 
 pub fn recreate(gns: GNSData) -> RuntimeResult<SELoaded> {
     context::set_dmsg("creating gns");
@@ -74,19 +85,30 @@ pub fn recreate(gns: GNSData) -> RuntimeResult<SELoaded> {
             model_id.entity(),
             model_data.get_uuid(),
         ))?;
-        let mut model_driver = ModelDriver::create_model_driver(&paths_v1::model_path(
-            model_id.space(),
-            space_uuid,
-            model_id.entity(),
-            model_data.get_uuid(),
-        ))?;
+        let mut model_drivers = ModelDriver::create_model_drivers(
+            model_data.primary_index().partitions(),
+            |partition| {
+                paths_v1::model_partition_path(
+                    model_id.space(),
+                    space_uuid,
+                    model_id.entity(),
+                    model_data.get_uuid(),
+                    partition,
+                )
+            },
+        )?;
         gns_driver.commit_event(CreateModelTxn::new(
             SpaceIDRef::with_uuid(model_id.space(), space_uuid),
             model_id.entity(),
             model_data,
         ))?;
-        model_driver.commit_with_ctx(FullModel::new(model_data), BatchStats::new())?;
-        model.driver().initialize_model_driver(model_driver);
+        for (partition, model_driver) in model_drivers.iter_mut().enumerate() {
+            model_driver
+                .commit_with_ctx(FullModel::new(model_data, partition), BatchStats::new())?;
+        }
+        model
+            .driver()
+            .initialize_model_drivers(model_journals(model_drivers));
     }
     // create all users
     context::set_dmsg("creating all users");
@@ -94,12 +116,17 @@ pub fn recreate(gns: GNSData) -> RuntimeResult<SELoaded> {
         gns_driver.commit_event(CreateUserTxn::new(&user_name, user.hash()))?;
     }
     Ok(SELoaded {
-        gns: GlobalNS::new(gns, FractalGNSDriver::new(gns_driver)),
+        gns: GlobalNS::new(
+            gns,
+            Box::new(SDSSDriver),
+            FractalGNSDriver::new(Box::new(gns_driver)),
+            Jobs::load(&paths_v1::jobs_path())?,
+        ),
     })
 }
 
 pub fn initialize_new(config: &Configuration) -> RuntimeResult<SELoaded> {
-    FileSystem::create_dir_all(DATA_DIR)?;
+    FileSystem::create_dir_all(&paths_v1::data_dir())?;
     let mut gns_driver = impls::gns_log::GNSDriver::create_gns()?;
     let gns = GNSData::empty();
     let password_hash = rcrypt::hash(&config.auth.root_key, rcrypt::DEFAULT_COST).unwrap();
@@ -113,7 +140,12 @@ pub fn initialize_new(config: &Configuration) -> RuntimeResult<SELoaded> {
         password_hash.into_boxed_slice(),
     ));
     Ok(SELoaded {
-        gns: GlobalNS::new(gns, FractalGNSDriver::new(gns_driver)),
+        gns: GlobalNS::new(
+            gns,
+            Box::new(SDSSDriver),
+            FractalGNSDriver::new(Box::new(gns_driver)),
+            Jobs::load(&paths_v1::jobs_path())?,
+        ),
     })
 }
 
@@ -124,17 +156,29 @@ pub fn restore(cfg: &Configuration) -> RuntimeResult<SELoaded> {
     for (id, model) in gns.idx_models().write().iter_mut() {
         let model_data = model.data();
         let space_uuid = gns.idx().read().get(id.space()).unwrap().get_uuid();
-        let model_data_file_path =
-            paths_v1::model_path(id.space(), space_uuid, id.entity(), model_data.get_uuid());
-        context::set_dmsg(format!("loading model driver in {model_data_file_path}"));
-        let model_driver =
-            impls::mdl_journal::ModelDriver::open_model_driver(model_data, &model_data_file_path)?;
-        model.driver().initialize_model_driver(model_driver);
+        let model_drivers = ModelDriver::open_model_drivers(model_data, |partition| {
+            let model_data_file_path = paths_v1::model_partition_path(
+                id.space(),
+                space_uuid,
+                id.entity(),
+                model_data.get_uuid(),
+                partition,
+            );
+            context::set_dmsg(format!("loading model driver in {model_data_file_path}"));
+            model_data_file_path
+        })?;
+        model
+            .driver()
+            .initialize_model_drivers(model_journals(model_drivers));
         unsafe {
             // UNSAFE(@ohsayan): all pieces of data are upgraded by now, so vacuum
             model.data_mut().model_mutator().vacuum_stashed();
         }
     }
+    // views are linked to their source models once all models are loaded
+    model::link_views(&mut gns.idx_models().write());
+    context::set_dmsg("cleaning up dropped spaces");
+    finish_drops(&gns)?;
     // check if password has changed
     if gns
         .sys_db()
@@ -150,6 +194,25 @@ pub fn restore(cfg: &Configuration) -> RuntimeResult<SELoaded> {
             .__raw_alter_user(SystemDatabase::ROOT_ACCOUNT, phash.into_boxed_slice());
     }
     Ok(SELoaded {
-        gns: GlobalNS::new(gns, FractalGNSDriver::new(gns_driver)),
+        gns: GlobalNS::new(
+            gns,
+            Box::new(SDSSDriver),
+            FractalGNSDriver::new(Box::new(gns_driver)),
+            Jobs::load(&paths_v1::jobs_path())?,
+        ),
     })
 }
+
+/// Delete the directories of dropped spaces that are still around. A drop is committed to the GNS before its
+/// directory is deleted (in the background), so if the server went down in between, the deletion is finished here.
+/// Replaying the journal again finds the same drops, so this is safe to run any number of times
+pub fn finish_drops(gns: &GNSData) -> RuntimeResult<()> {
+    for dir in gns.take_dropped_dirs() {
+        match FileSystem::remove_dir_all(&dir) {
+            Ok(()) => info!("removed {dir} left behind by a dropped space"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
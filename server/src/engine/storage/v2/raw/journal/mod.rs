@@ -47,7 +47,8 @@ mod raw;
 #[cfg(test)]
 mod tests;
 pub use raw::{
-    create_journal, open_journal, RawJournalAdapter, RawJournalAdapterEvent as JournalAdapterEvent,
+    create_journal, decode_journal, decode_journal_with, open_journal, RawJournalAdapter,
+    RawJournalAdapterEvent as JournalAdapterEvent, ReplayAction,
 };
 
 /*
@@ -143,6 +144,20 @@ impl<EL: EventLogSpec> RawJournalAdapter for EventLogAdapter<EL> {
             gs, pl
         )
     }
+    fn decode_skip<'a>(
+        _: &Self::GlobalState,
+        _: Self::EventMeta,
+        file: &mut TrackedReader<Self::Spec>,
+    ) -> RuntimeResult<()> {
+        // the checksum isn't verified (the event is being skipped because it's bad), but the length must be sane
+        let _checksum = u64::from_le_bytes(file.read_block()?);
+        let plen = u64::from_le_bytes(file.read_block()?);
+        if plen > file.remaining() {
+            return Err(StorageError::RawJournalCorrupted.into());
+        }
+        let mut pl = vec![0; plen as usize];
+        e!(file.tracked_read(&mut pl))
+    }
 }
 
 /*
@@ -260,6 +275,26 @@ impl<BA: BatchAdapterSpec> RawJournalAdapter for BatchAdapter<BA> {
         gs: &Self::GlobalState,
         meta: Self::EventMeta,
         f: &mut TrackedReader<Self::Spec>,
+    ) -> RuntimeResult<()> {
+        Self::decode_batch(gs, meta, f, true)
+    }
+    fn decode_skip<'a>(
+        gs: &Self::GlobalState,
+        meta: Self::EventMeta,
+        f: &mut TrackedReader<Self::Spec>,
+    ) -> RuntimeResult<()> {
+        Self::decode_batch(gs, meta, f, false)
+    }
+}
+
+impl<BA: BatchAdapterSpec> BatchAdapter<BA> {
+    /// Decode a batch, and apply it if `apply` is set. A batch that isn't applied is only read past: its events
+    /// must still decode (since batches have no length prefix), but the commit size and checksum aren't verified
+    fn decode_batch(
+        gs: &<BA as BatchAdapterSpec>::GlobalState,
+        meta: <BA as BatchAdapterSpec>::BatchType,
+        f: &mut TrackedReader<<BA as BatchAdapterSpec>::Spec>,
+        apply: bool,
     ) -> RuntimeResult<()> {
         let mut f = f.context();
         {
@@ -295,17 +330,18 @@ impl<BA: BatchAdapterSpec> RawJournalAdapter for BatchAdapter<BA> {
             }
             // read actual commit size
             let _stored_actual_commit_size = u64::from_le_bytes(f.read_block()?);
-            if _stored_actual_commit_size == real_commit_size {
+            if apply {
+                if _stored_actual_commit_size != real_commit_size {
+                    return Err(StorageError::RawJournalCorrupted.into());
+                }
                 // finish applying batch
                 BA::finish(batch_state, batch_md, gs)?;
-            } else {
-                return Err(StorageError::RawJournalCorrupted.into());
             }
         }
         // and finally, verify checksum
         let (real_checksum, file) = f.finish();
         let stored_checksum = u64::from_le_bytes(file.read_block()?);
-        if real_checksum == stored_checksum {
+        if real_checksum == stored_checksum || !apply {
             Ok(())
         } else {
             Err(StorageError::RawJournalCorrupted.into())
@@ -73,6 +73,74 @@ where
     RawJournalWriter::new(initializer, file)
 }
 
+/// Decode every server event in an existing journal, without opening it for writes
+///
+/// Unlike [`open_journal`], this does not require the journal to have been closed and can hence be used on a
+/// (consistent) copy of a journal that is still live. `on_event` is called with the txn id of every server
+/// event, after it has been applied to the global state.
+pub fn decode_journal<J: RawJournalAdapter>(
+    log_path: &str,
+    gs: &J::GlobalState,
+    on_event: impl FnMut(u64),
+) -> RuntimeResult<()>
+where
+    J::Spec: FileSpecV1<DecodeArgs = ()>,
+{
+    decode_journal_with::<J>(log_path, gs, |_| ReplayAction::Apply, on_event).map(|_| ())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// What to do with the next event when decoding a journal with [`decode_journal_with`]
+pub enum ReplayAction {
+    /// decode and apply the event
+    Apply,
+    /// read past the event without applying it (or verifying it)
+    Skip,
+    /// stop decoding, leaving the event and everything after it out
+    Stop,
+}
+
+/// Decode the server events in an existing journal like [`decode_journal`] does, but ask `action` what to do with
+/// every event (given its txn id) before it is read. Driver events are never skipped, and an event can only be
+/// skipped if it can be read past (see [`RawJournalAdapter::decode_skip`]). Returns the txn ids of the server events
+/// that were skipped
+pub fn decode_journal_with<J: RawJournalAdapter>(
+    log_path: &str,
+    gs: &J::GlobalState,
+    mut action: impl FnMut(u64) -> ReplayAction,
+    mut on_event: impl FnMut(u64),
+) -> RuntimeResult<Vec<u64>>
+where
+    J::Spec: FileSpecV1<DecodeArgs = ()>,
+{
+    let log = SdssFile::<J::Spec>::open_read_only(log_path)?;
+    let reader = TrackedReader::with_cursor(
+        log,
+        <<J as RawJournalAdapter>::Spec as FileSpecV1>::SIZE as u64,
+    )?;
+    let mut me = RawJournalReader::<J>::new(reader, 0, 0, 0, 0);
+    let mut skipped = vec![];
+    while !me.tr.is_eof() {
+        let skip = match action(me.txn_id) {
+            ReplayAction::Apply => false,
+            ReplayAction::Skip => true,
+            ReplayAction::Stop => break,
+        };
+        let (server_events, skipped_events) = (me.stats.server_events, me.stats.skipped_events);
+        let closed = me._next_event_and_stop(gs, skip)?;
+        if me.stats.server_events != server_events {
+            on_event(me.last_txn_id);
+        }
+        if me.stats.skipped_events != skipped_events {
+            skipped.push(me.last_txn_id);
+        }
+        if closed {
+            break;
+        }
+    }
+    Ok(skipped)
+}
+
 #[derive(Debug)]
 pub struct JournalInitializer {
     cursor: u64,
@@ -273,6 +341,15 @@ pub trait RawJournalAdapter: Sized {
         meta: Self::EventMeta,
         file: &mut TrackedReader<Self::Spec>,
     ) -> RuntimeResult<()>;
+    /// read past the event without applying it, even if it is corrupted (as long as its end can still be found). by
+    /// default, events can't be skipped
+    fn decode_skip<'a>(
+        _: &Self::GlobalState,
+        _: Self::EventMeta,
+        _: &mut TrackedReader<Self::Spec>,
+    ) -> RuntimeResult<()> {
+        Err(StorageError::RawJournalCorrupted.into())
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -613,6 +690,8 @@ impl<J: RawJournalAdapter> RawJournalWriter<J> {
                 me.log_file.flush_sync()?;
             }
             me.log_file.tracked_write_through_buffer(&block)?;
+            // a driver event must be durable once it's committed (without a synced close event, we can't boot)
+            me.log_file.fsync()?;
             jtrace_writer!(DriverEventCompleted);
             Ok(())
         })
@@ -628,6 +707,11 @@ impl<J: RawJournalAdapter> RawJournalWriter<J> {
         Self::_commit_driver_event(me, DriverEventKind::Reopened)?;
         Ok(())
     }
+    /// Continue writing to the copy of the log at `path` (see [`TrackedWriter::relocate`])
+    pub fn relocate(me: &mut Self, path: &str) -> RuntimeResult<()> {
+        me.log_file.relocate(path)?;
+        Ok(())
+    }
 }
 
 pub struct RawJournalReader<J: RawJournalAdapter> {
@@ -643,6 +727,7 @@ pub struct RawJournalReader<J: RawJournalAdapter> {
 pub struct JournalStats {
     server_events: usize,
     driver_events: usize,
+    skipped_events: usize,
 }
 
 impl JournalStats {
@@ -650,6 +735,7 @@ impl JournalStats {
         Self {
             server_events: 0,
             driver_events: 0,
+            skipped_events: 0,
         }
     }
 }
@@ -706,6 +792,9 @@ impl<J: RawJournalAdapter> RawJournalReader<J> {
 
 impl<J: RawJournalAdapter> RawJournalReader<J> {
     fn _apply_next_event_and_stop(&mut self, gs: &J::GlobalState) -> RuntimeResult<bool> {
+        self._next_event_and_stop(gs, false)
+    }
+    fn _next_event_and_stop(&mut self, gs: &J::GlobalState, skip: bool) -> RuntimeResult<bool> {
         let txn_id = u128::from_le_bytes(self.tr.read_block()?);
         let meta = u64::from_le_bytes(self.tr.read_block()?);
         if txn_id != self.txn_id as u128 {
@@ -726,6 +815,12 @@ impl<J: RawJournalAdapter> RawJournalReader<J> {
                     jtrace_reader!(ServerEventMetadataParsed);
                     // now parse the actual event
                     let Self { tr: reader, .. } = self;
+                    if skip {
+                        J::decode_skip(gs, meta, reader)?;
+                        Self::__refresh_known_txn(self);
+                        self.stats.skipped_events += 1;
+                        return Ok(false);
+                    }
                     // we do not consider a parsed event a success signal; so we must actually apply it
                     match J::decode_apply(gs, meta, reader) {
                         Ok(()) => {
@@ -55,6 +55,8 @@ use {
     },
 };
 
+mod crash;
+
 // event definitions
 
 #[derive(TaggedEnum, Clone, Copy, Debug)]
@@ -0,0 +1,119 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    crash tests
+    ---
+    run a workload on a journal, then crash it at every write/sync boundary and make sure that recovery either
+    restores a committed (and at least synced) state or fails cleanly. since journals are never repaired, recovery
+    is only expected to succeed if the journal was cleanly closed
+*/
+
+use {
+    super::{BatchDB, BatchDBAdapter, TestDB, TestDBAdapter},
+    crate::engine::storage::{
+        common::interface::crash::Recorder,
+        v2::raw::journal::{
+            raw::{create_journal, open_journal, RawJournalWriter},
+            BatchAdapter, EventLogAdapter,
+        },
+    },
+};
+
+#[test]
+fn crash_event_log() {
+    const PATH: &str = "crash_event_log";
+    let mut recorder = Recorder::new(PATH);
+    {
+        let db = TestDB::default();
+        let mut log = create_journal::<EventLogAdapter<TestDBAdapter>>(PATH).unwrap();
+        recorder.commit(db._ref().clone());
+        for key in ["acai berry", "billberry", "cranberry"] {
+            db.push(&mut log, key).unwrap();
+            recorder.commit(db._ref().clone());
+        }
+        db.pop(&mut log).unwrap();
+        recorder.commit(db._ref().clone());
+        RawJournalWriter::close_driver(&mut log).unwrap();
+        recorder.shutdown(db._ref().clone());
+    }
+    {
+        let db = TestDB::default();
+        let mut log = open_journal::<EventLogAdapter<TestDBAdapter>>(PATH, &db).unwrap();
+        db.push(&mut log, "dewberry").unwrap();
+        recorder.commit(db._ref().clone());
+        db.clear(&mut log).unwrap();
+        recorder.commit(db._ref().clone());
+        db.push(&mut log, "elderberry").unwrap();
+        recorder.commit(db._ref().clone());
+        RawJournalWriter::close_driver(&mut log).unwrap();
+        recorder.shutdown(db._ref().clone());
+    }
+    let report = recorder.finish().run(|path| {
+        let db = TestDB::default();
+        let _ = open_journal::<EventLogAdapter<TestDBAdapter>>(path, &db)?;
+        Ok(db.data.into_inner())
+    });
+    assert_ne!(report.recovered, 0);
+}
+
+#[test]
+fn crash_batch_journal() {
+    const PATH: &str = "crash_batch_journal";
+    let mut recorder = Recorder::new(PATH);
+    let committed = |db: &BatchDB| {
+        let db = db._ref();
+        db.data[..db.last_flushed_at].to_vec()
+    };
+    {
+        let db = BatchDB::new();
+        let mut batch_drv = BatchAdapter::<BatchDBAdapter>::create(PATH).unwrap();
+        recorder.commit(committed(&db));
+        for key in ["key1", "key2", "key3", "key4"] {
+            db.push(&mut batch_drv, key).unwrap();
+            recorder.commit(committed(&db));
+        }
+        BatchAdapter::close(&mut batch_drv).unwrap();
+        recorder.shutdown(committed(&db));
+    }
+    {
+        let db = BatchDB::new();
+        let mut batch_drv = BatchAdapter::<BatchDBAdapter>::open(PATH, &db).unwrap();
+        for key in ["key5", "key6"] {
+            db.push(&mut batch_drv, key).unwrap();
+            recorder.commit(committed(&db));
+        }
+        BatchAdapter::close(&mut batch_drv).unwrap();
+        recorder.shutdown(committed(&db));
+    }
+    let report = recorder.finish().run(|path| {
+        let db = BatchDB::new();
+        let _ = BatchAdapter::<BatchDBAdapter>::open(path, &db)?;
+        let data = db._ref().data.clone();
+        Ok(data)
+    });
+    assert_ne!(report.recovered, 0);
+}
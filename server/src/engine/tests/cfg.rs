@@ -26,8 +26,10 @@
 
 use crate::{
     engine::config::{
-        self, AuthDriver, CLIConfigParseReturn, ConfigAuth, ConfigEndpoint, ConfigEndpointTcp,
-        ConfigEndpointTls, ConfigMode, ConfigReturn, ConfigSystem, Configuration, ParsedRawArgs,
+        self, AuthDriver, CLIConfigParseReturn, ConfigAuth, ConfigBlobTier, ConfigEndpoint,
+        ConfigEndpointTcp, ConfigEndpointTls, ConfigFlushFailure, ConfigHugePages, ConfigKeepalive,
+        ConfigMode, ConfigReturn, ConfigRuntime, ConfigShardMap, ConfigShardNode, ConfigStorage,
+        ConfigSystem, ConfigUpstream, Configuration, ParsedRawArgs,
     },
     util::test_utils::with_files,
 };
@@ -99,6 +101,7 @@ fn parse_validate_cli_args() {
                 --endpoint tcp@127.0.0.1:2003 \
                 --endpoint tls@127.0.0.2:2004 \
                 --service-window=600 \
+                --query-mem-limit=1048576 \
                 --tlskey {pkey} \
                 --tlscert {cert} \
                 --tls-passphrase {pass} \
@@ -123,7 +126,10 @@ fn parse_validate_cli_args() {
                         )
                     ),
                     ConfigMode::Dev,
-                    ConfigSystem::new(600),
+                    ConfigSystem {
+                        query_memory_limit: 1048576,
+                        ..ConfigSystem::new(600)
+                    },
                     ConfigAuth::new(AuthDriver::Pwd, "password12345678".into())
                 )
             )
@@ -131,6 +137,617 @@ fn parse_validate_cli_args() {
     );
 }
 #[test]
+fn parse_validate_cli_args_memory_watermarks() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --mem-high-watermark=1000");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    // the low watermark defaults to 90% of the high watermark
+    assert_eq!(
+        (
+            ret.system.memory_high_watermark,
+            ret.system.memory_low_watermark
+        ),
+        (1000, 900)
+    );
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --mem-high-watermark=1000 --mem-low-watermark=2000",
+    );
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_runtime() {
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.runtime, ConfigRuntime::MultiThreaded);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --runtime sharded");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.runtime, ConfigRuntime::Sharded);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --runtime=threadpool");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_numa() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --runtime sharded --numa true",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(ret.system.numa_aware);
+    // NUMA awareness needs shards
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --numa true");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --runtime sharded --numa yes");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_huge_pages() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --huge-pages transparent");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.huge_pages, ConfigHugePages::Transparent);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --huge-pages=always");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_storage() {
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --storage memory");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.storage, ConfigStorage::Memory);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.storage, ConfigStorage::Sdss);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --storage=lsm");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_flush_failure() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --flush-failure accumulate --flush-failure-cap 5000",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.flush_failure, ConfigFlushFailure::Accumulate);
+    assert_eq!(ret.system.flush_failure_cap, 5000);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.flush_failure, ConfigFlushFailure::Block);
+    assert_eq!(
+        ret.system.flush_failure_cap,
+        ConfigSystem::DEFAULT_FLUSH_FAILURE_CAP
+    );
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --flush-failure=drop");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_maintenance_rate() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --maintenance-rate 20000");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.maintenance_rate, 20000);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.maintenance_rate, 0);
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --maintenance-rate=fast");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_lock_wait_timeout() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --lock-wait-timeout 500");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.lock_wait_timeout, 500);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.lock_wait_timeout, 0);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --lock-wait-timeout=1s");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_sessions() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --statement-timeout 30000 --relaxed-sessions",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        (ret.system.statement_timeout, ret.system.relaxed_sessions),
+        (30000, true)
+    );
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        (ret.system.statement_timeout, ret.system.relaxed_sessions),
+        (0, false)
+    );
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --relaxed-sessions=maybe");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_case_insensitive_names() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --case-insensitive-names");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(ret.system.case_insensitive_names);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(!ret.system.case_insensitive_names);
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --case-insensitive-names=sometimes",
+    );
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_cli_args_check_config() {
+    // the checks are configured with the rest of the arguments
+    let cfg = extract_cli_args("skyd --check-config --auth-root-password password12345678");
+    assert_eq!(cfg.len(), 1);
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_ok());
+    assert_eq!(
+        extract_cli_args_raw("skyd --check-config"),
+        CLIConfigParseReturn::Default
+    );
+}
+#[test]
+fn parse_validate_cli_args_write_throttle() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --throttle-backlog 100000 --throttle-lag 67108864",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        (ret.system.throttle_backlog, ret.system.throttle_lag),
+        (100000, 67108864)
+    );
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        (ret.system.throttle_backlog, ret.system.throttle_lag),
+        (0, 0)
+    );
+}
+#[test]
+fn parse_validate_cli_args_response_limits() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --max-response-size 1048576 \
+        --response-quota 104857600 --response-quota-window 300",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        (
+            ret.system.max_response_size,
+            ret.system.response_quota,
+            ret.system.response_quota_window
+        ),
+        (1048576, 104857600, 300)
+    );
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        (
+            ret.system.max_response_size,
+            ret.system.response_quota,
+            ret.system.response_quota_window
+        ),
+        (0, 0, ConfigSystem::DEFAULT_RESPONSE_QUOTA_WINDOW)
+    );
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --response-quota-window 0");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_blob_max_size() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --blob-max-size 1048576");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.blob_max_size, 1048576);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.system.blob_max_size,
+        ConfigSystem::DEFAULT_BLOB_MAX_SIZE
+    );
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --blob-max-size 0");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_scan_prefetch() {
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --scan-prefetch 32");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.scan_prefetch, 32);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --scan-prefetch 0");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.scan_prefetch, 0);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.system.scan_prefetch,
+        ConfigSystem::DEFAULT_SCAN_PREFETCH
+    );
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --scan-prefetch 257");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_blob_tier() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --blob-tier s3.example.com:443/sky-blobs \
+        --blob-tier-access-key access --blob-tier-secret-key secret --blob-tier-threshold 65536",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.system.blob_tier,
+        Some(ConfigBlobTier {
+            endpoint: ConfigEndpointTcp::new("s3.example.com".into(), 443),
+            tls: true,
+            bucket: "sky-blobs".into(),
+            region: ConfigBlobTier::DEFAULT_REGION.into(),
+            access_key: "access".into(),
+            secret_key: "secret".into(),
+            threshold: 65536,
+        })
+    );
+    // plain HTTP is fine for a bucket on this host
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --blob-tier http://127.0.0.1:9000/sky-blobs \
+        --blob-tier-access-key access --blob-tier-secret-key secret",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(!ret.system.blob_tier.unwrap().tls);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.blob_tier, None);
+    for bad in [
+        // no bucket
+        "--blob-tier localhost:9000 --blob-tier-access-key a --blob-tier-secret-key s",
+        "--blob-tier localhost:9000/Blobs --blob-tier-access-key a --blob-tier-secret-key s",
+        // no credentials
+        "--blob-tier localhost:9000/blobs --blob-tier-access-key a",
+        "--blob-tier localhost:9000/blobs --blob-tier-access-key a --blob-tier-secret-key s --blob-tier-threshold 0",
+        // no bucket for the other settings
+        "--blob-tier-threshold 1024",
+        // plain HTTP to another host
+        "--blob-tier http://s3.example.com:80/blobs --blob-tier-access-key a --blob-tier-secret-key s",
+    ] {
+        let cfg = extract_cli_args(&format!("skyd --auth-root-password password12345678 {bad}"));
+        assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    }
+}
+#[test]
+fn parse_validate_cli_args_upstream() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --upstream central:2003 \
+        --upstream-user edge --upstream-password password12345678",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.system.upstream,
+        Some(ConfigUpstream {
+            endpoint: ConfigEndpointTcp::new("central".into(), 2003),
+            username: "edge".into(),
+            password: "password12345678".into(),
+        })
+    );
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.upstream, None);
+    for bad in [
+        "--upstream central --upstream-user edge --upstream-password pass",
+        // no credentials
+        "--upstream central:2003 --upstream-user edge",
+        // no upstream for the credentials
+        "--upstream-user edge --upstream-password pass",
+    ] {
+        let cfg = extract_cli_args(&format!("skyd --auth-root-password password12345678 {bad}"));
+        assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    }
+}
+#[test]
+fn parse_validate_cli_args_shardmap() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 \
+        --shardmap a@10.0.0.1:2003/0-1023,4096-65535;b@10.0.0.2:2003/1024-4095",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.system.shardmap,
+        Some(ConfigShardMap {
+            nodes: vec![
+                ConfigShardNode {
+                    name: "a".into(),
+                    endpoint: ConfigEndpointTcp::new("10.0.0.1".into(), 2003),
+                    ranges: vec![(0, 1023), (4096, 65535)],
+                },
+                ConfigShardNode {
+                    name: "b".into(),
+                    endpoint: ConfigEndpointTcp::new("10.0.0.2".into(), 2003),
+                    ranges: vec![(1024, 4095)],
+                },
+            ]
+        })
+    );
+    for bad in [
+        // a slot that no node owns
+        "a@10.0.0.1:2003/0-65534",
+        // a slot that two nodes own
+        "a@10.0.0.1:2003/0-65535;b@10.0.0.2:2003/100-200",
+        // the same node twice
+        "a@10.0.0.1:2003/0-1023;a@10.0.0.2:2003/1024-65535",
+        "a@10.0.0.1/0-65535",
+        "a@10.0.0.1:2003/65535-0",
+        "a@10.0.0.1:2003/0-65536",
+        "10.0.0.1:2003/0-65535",
+    ] {
+        let cfg = extract_cli_args(&format!(
+            "skyd --auth-root-password password12345678 --shardmap {bad}"
+        ));
+        assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    }
+}
+#[test]
+fn parse_validate_cli_args_volumes() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --journal-volume /nvme/sky --batch-volume /bulk/sky",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.journal_volume.as_deref(), Some("/nvme/sky"));
+    assert_eq!(ret.system.batch_volume.as_deref(), Some("/bulk/sky"));
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.journal_volume, None);
+    assert_eq!(ret.system.batch_volume, None);
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --batch-volume /a --batch-volume /b",
+    );
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_archive_dir() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --archive-dir /backup/sky");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.archive_dir.as_deref(), Some("/backup/sky"));
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.archive_dir, None);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --archive-dir=");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_crash_dir() {
+    let cfg =
+        extract_cli_args("skyd --auth-root-password password12345678 --crash-dir /var/crash/sky");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.crash_dir.as_deref(), Some("/var/crash/sky"));
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.crash_dir, None);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --crash-dir=");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn config_fingerprint_leaves_credentials_out() {
+    let load = |args: &str| {
+        config::apply_and_validate::<config::CSCommandLine>(extract_cli_args(args))
+            .unwrap()
+            .into_config()
+    };
+    let a = load("skyd --auth-root-password password12345678");
+    let b = load("skyd --auth-root-password another-password-1234");
+    let c = load("skyd --auth-root-password password12345678 --crash-dir /var/crash/sky");
+    assert!(!format!("{a:?}").contains("password12345678"));
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_ne!(a.fingerprint(), c.fingerprint());
+}
+#[test]
+fn parse_validate_cli_args_probe_endpoint() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --probe-endpoint 0.0.0.0:2005",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.system.probe_endpoint,
+        Some(ConfigEndpointTcp::new("0.0.0.0".into(), 2005))
+    );
+    for bad in ["0.0.0.0", ":2005", "0.0.0.0:http", "0.0.0.0:65536"] {
+        let cfg = extract_cli_args(&format!(
+            "skyd --auth-root-password password12345678 --probe-endpoint {bad}"
+        ));
+        assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    }
+}
+#[test]
+fn parse_validate_cli_args_tracing() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --otlp-endpoint localhost:4318 --trace-sample-rate 10",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.system.otlp_endpoint,
+        Some(ConfigEndpointTcp::new("localhost".into(), 4318))
+    );
+    assert_eq!(ret.system.trace_sample_rate, 10);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.otlp_endpoint, None);
+    assert_eq!(
+        ret.system.trace_sample_rate,
+        ConfigSystem::DEFAULT_TRACE_SAMPLE_RATE
+    );
+    for bad in [
+        "--otlp-endpoint localhost",
+        "--trace-sample-rate 0",
+        "--trace-sample-rate 101",
+        "--trace-sample-rate all",
+    ] {
+        let cfg = extract_cli_args(&format!("skyd --auth-root-password password12345678 {bad}"));
+        assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    }
+}
+#[test]
+fn parse_validate_cli_args_soft_limit() {
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(ret.system.soft_limit, ConfigSystem::DEFAULT_SOFT_LIMIT);
+    for (arg, pct) in [("--soft-limit 90", 90), ("--soft-limit=0", 0)] {
+        let cfg = extract_cli_args(&format!("skyd --auth-root-password password12345678 {arg}"));
+        let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+            .unwrap()
+            .into_config();
+        assert_eq!(ret.system.soft_limit, pct);
+    }
+    for bad in ["--soft-limit 100", "--soft-limit 256", "--soft-limit half"] {
+        let cfg = extract_cli_args(&format!("skyd --auth-root-password password12345678 {bad}"));
+        assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    }
+}
+#[test]
+fn parse_validate_cli_args_keepalive() {
+    let cfg = extract_cli_args(
+        "skyd --auth-root-password password12345678 --endpoint tcp@127.0.0.1:2003 \
+        --keepalive 60 --idle-timeout tcp:600 --idle-timeout tls:30",
+    );
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert_eq!(
+        ret.endpoints,
+        ConfigEndpoint::Insecure(
+            ConfigEndpointTcp::new("127.0.0.1".into(), 2003).with_keepalive(ConfigKeepalive {
+                probe_after: Some(60),
+                idle_timeout: Some(600),
+            })
+        )
+    );
+    for bad in [
+        // needs an endpoint
+        "--keepalive 60",
+        "--endpoint tcp@127.0.0.1:2003 --keepalive 0",
+        "--endpoint tcp@127.0.0.1:2003 --keepalive udp:60",
+        "--endpoint tcp@127.0.0.1:2003 --idle-timeout 60 --idle-timeout tcp:30",
+    ] {
+        let cfg = extract_cli_args(&format!("skyd --auth-root-password password12345678 {bad}"));
+        assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+    }
+}
+#[test]
+fn parse_validate_cli_args_repair() {
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --repair");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(ret.system.repair);
+    let cfg = extract_cli_args("skyd --repair --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(ret.system.repair);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --repair=false");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(!ret.system.repair);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --repair=maybe");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
+fn parse_validate_cli_args_warmup() {
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(!ret.system.warmup);
+    let cfg = extract_cli_args("skyd --warmup --repair --auth-root-password password12345678");
+    let ret = config::apply_and_validate::<config::CSCommandLine>(cfg)
+        .unwrap()
+        .into_config();
+    assert!(ret.system.warmup);
+    assert!(ret.system.repair);
+    let cfg = extract_cli_args("skyd --auth-root-password password12345678 --warmup=maybe");
+    assert!(config::apply_and_validate::<config::CSCommandLine>(cfg).is_err());
+}
+#[test]
 fn parse_validate_cli_args_help_and_version() {
     let pl1 = "skyd --help";
     let pl2 = "skyd --version";
@@ -251,6 +868,11 @@ const CONFIG_FILE: &str = "\
 system:
   mode: dev
   rs_window: 600
+  query_mem_limit: 1048576
+  runtime: sharded
+  huge_pages: explicit
+  batch_volume: /bulk/skytable
+  probe_endpoint: 127.0.0.1:2005
 
 auth:
   plugin: pwd
@@ -263,9 +885,11 @@ endpoints:
     cert: ._test_sample_cert.pem
     private_key: ._test_sample_private.key
     pkey_passphrase: ._test_sample_private.pass.txt
+    idle_timeout: 600
   insecure:
     host: 127.0.0.1
     port: 2003
+    keepalive: 60
     ";
 #[test]
 fn test_config_file() {
@@ -283,16 +907,33 @@ fn test_config_file() {
                 cfg,
                 Configuration::new(
                     ConfigEndpoint::Multi(
-                        ConfigEndpointTcp::new("127.0.0.1".into(), 2003),
+                        ConfigEndpointTcp::new("127.0.0.1".into(), 2003).with_keepalive(
+                            ConfigKeepalive {
+                                probe_after: Some(60),
+                                idle_timeout: None,
+                            }
+                        ),
                         ConfigEndpointTls::new(
-                            ConfigEndpointTcp::new("127.0.0.1".into(), 2004),
+                            ConfigEndpointTcp::new("127.0.0.1".into(), 2004).with_keepalive(
+                                ConfigKeepalive {
+                                    probe_after: None,
+                                    idle_timeout: Some(600),
+                                }
+                            ),
                             "".into(),
                             "".into(),
                             "".into()
                         )
                     ),
                     ConfigMode::Dev,
-                    ConfigSystem::new(600),
+                    ConfigSystem {
+                        query_memory_limit: 1048576,
+                        runtime: ConfigRuntime::Sharded,
+                        huge_pages: ConfigHugePages::Explicit,
+                        batch_volume: Some("/bulk/skytable".into()),
+                        probe_endpoint: Some(ConfigEndpointTcp::new("127.0.0.1".into(), 2005)),
+                        ..ConfigSystem::new(600)
+                    },
                     ConfigAuth::new(AuthDriver::Pwd, "password12345678".into())
                 )
             )
@@ -39,3 +39,32 @@ mod status {
             .unwrap();
     }
 }
+
+mod quota {
+    use {
+        crate::engine::error::QueryError,
+        sky_macros::dbtest,
+        skytable::{error::Error, query},
+    };
+    #[dbtest]
+    fn eval_is_throttled() {
+        let mut db = db!();
+        db.query_parse::<()>(&query!("create space quota_eval"))
+            .unwrap();
+        db.query_parse::<()>(&query!(
+            "create model quota_eval.users(username: string, logins: uint64)"
+        ))
+        .unwrap();
+        db.query_parse::<()>(&query!(
+            "sysctl alter quota model quota_eval.users with { writes: 1 }"
+        ))
+        .unwrap();
+        let upsert = "eval { if exists from quota_eval.users where username = 'sayan' then update quota_eval.users set logins += 1 where username = 'sayan' else insert into quota_eval.users('sayan', 1) }";
+        assert!(!db.query_parse::<bool>(&query!(upsert)).unwrap());
+        // the bucket only holds a second's worth of writes
+        assert_err_eq!(
+            db.query_parse::<bool>(&query!(upsert)),
+            Error::ServerError(QueryError::SysQuotaExceeded.value_u8() as u16)
+        );
+    }
+}
@@ -48,6 +48,14 @@ pub enum GNSTransactionCode {
     CreateUser = 8,
     AlterUser = 9,
     DropUser = 10,
+    CreateIndex = 11,
+    DropIndex = 12,
+    CreateStatement = 13,
+    DropStatement = 14,
+    CreateWebhook = 15,
+    DropWebhook = 16,
+    AlterUserQuota = 17,
+    AlterModelQuota = 18,
 }
 
 pub trait GNSTransaction {
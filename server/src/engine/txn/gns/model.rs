@@ -25,7 +25,11 @@
 */
 
 use crate::engine::{
-    core::model::{Field, ModelData},
+    core::{
+        index::Collation,
+        model::{Field, ModelData},
+    },
+    data::cell::Datacell,
     idx::{IndexST, IndexSTSeqCns},
     ql::lex::Ident,
     txn::{ModelIDRef, SpaceIDRef},
@@ -36,7 +40,12 @@ impl_gns_event!(
     AlterModelAddTxn<'_> = AlterModelAdd,
     AlterModelRemoveTxn<'_> = AlterModelRemove,
     AlterModelUpdateTxn<'_> = AlterModelUpdate,
-    DropModelTxn<'_> = DropModel
+    DropModelTxn<'_> = DropModel,
+    CreateIndexTxn<'_> = CreateIndex,
+    DropIndexTxn<'_> = DropIndex,
+    CreateWebhookTxn<'_> = CreateWebhook,
+    DropWebhookTxn<'_> = DropWebhook,
+    AlterModelQuotaTxn<'_> = AlterModelQuota
 );
 
 #[derive(Debug, Clone, Copy)]
@@ -153,3 +162,168 @@ impl<'a> DropModelTxn<'a> {
         self.model_id
     }
 }
+
+#[derive(Debug, Clone, Copy)]
+/// Transaction commit payload for a `create index ...` query
+pub struct CreateIndexTxn<'a> {
+    model_id: ModelIDRef<'a>,
+    index_name: &'a str,
+    /// the indexed fields, in order
+    fields: &'a [Box<str>],
+    /// the fields that a covering index stores along with each key
+    include: &'a [Box<str>],
+    /// the predicate of a partial index, as a flat list of `field, operator, value` triples
+    filter: Option<&'a Datacell>,
+    /// the collation that strings are compared with
+    collation: Collation,
+}
+
+impl<'a> CreateIndexTxn<'a> {
+    pub const fn new(
+        model_id: ModelIDRef<'a>,
+        index_name: &'a str,
+        fields: &'a [Box<str>],
+        include: &'a [Box<str>],
+        filter: Option<&'a Datacell>,
+        collation: Collation,
+    ) -> Self {
+        Self {
+            model_id,
+            index_name,
+            fields,
+            include,
+            filter,
+            collation,
+        }
+    }
+    pub fn model_id(&self) -> ModelIDRef<'_> {
+        self.model_id
+    }
+    pub fn index_name(&self) -> &str {
+        self.index_name
+    }
+    pub fn fields(&self) -> &[Box<str>] {
+        self.fields
+    }
+    pub fn include(&self) -> &[Box<str>] {
+        self.include
+    }
+    pub fn filter(&self) -> Option<&Datacell> {
+        self.filter
+    }
+    pub fn collation(&self) -> Collation {
+        self.collation
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Transaction commit payload for a `drop index ...` query
+pub struct DropIndexTxn<'a> {
+    model_id: ModelIDRef<'a>,
+    index_name: &'a str,
+}
+
+impl<'a> DropIndexTxn<'a> {
+    pub const fn new(model_id: ModelIDRef<'a>, index_name: &'a str) -> Self {
+        Self {
+            model_id,
+            index_name,
+        }
+    }
+    pub fn model_id(&self) -> ModelIDRef<'_> {
+        self.model_id
+    }
+    pub fn index_name(&self) -> &str {
+        self.index_name
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Transaction commit payload for a `sysctl create webhook ...` query
+pub struct CreateWebhookTxn<'a> {
+    model_id: ModelIDRef<'a>,
+    name: &'a str,
+    url: &'a str,
+    secret: &'a str,
+    batch: u64,
+}
+
+impl<'a> CreateWebhookTxn<'a> {
+    pub const fn new(
+        model_id: ModelIDRef<'a>,
+        name: &'a str,
+        url: &'a str,
+        secret: &'a str,
+        batch: u64,
+    ) -> Self {
+        Self {
+            model_id,
+            name,
+            url,
+            secret,
+            batch,
+        }
+    }
+    pub fn model_id(&self) -> ModelIDRef<'_> {
+        self.model_id
+    }
+    pub fn name(&self) -> &str {
+        self.name
+    }
+    pub fn url(&self) -> &str {
+        self.url
+    }
+    /// Returns the secret that requests are signed with (empty if they aren't signed)
+    pub fn secret(&self) -> &str {
+        self.secret
+    }
+    pub fn batch(&self) -> u64 {
+        self.batch
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Transaction commit payload for a `sysctl drop webhook ...` query
+pub struct DropWebhookTxn<'a> {
+    model_id: ModelIDRef<'a>,
+    name: &'a str,
+}
+
+impl<'a> DropWebhookTxn<'a> {
+    pub const fn new(model_id: ModelIDRef<'a>, name: &'a str) -> Self {
+        Self { model_id, name }
+    }
+    pub fn model_id(&self) -> ModelIDRef<'_> {
+        self.model_id
+    }
+    pub fn name(&self) -> &str {
+        self.name
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Transaction commit payload for a `sysctl alter quota model ...` query
+pub struct AlterModelQuotaTxn<'a> {
+    model_id: ModelIDRef<'a>,
+    reads: u64,
+    writes: u64,
+}
+
+impl<'a> AlterModelQuotaTxn<'a> {
+    pub const fn new(model_id: ModelIDRef<'a>, reads: u64, writes: u64) -> Self {
+        Self {
+            model_id,
+            reads,
+            writes,
+        }
+    }
+    pub fn model_id(&self) -> ModelIDRef<'_> {
+        self.model_id
+    }
+    pub fn reads(&self) -> u64 {
+        self.reads
+    }
+    pub fn writes(&self) -> u64 {
+        self.writes
+    }
+}
@@ -80,4 +80,82 @@ impl<'a> DropUserTxn<'a> {
     }
 }
 
-impl_gns_event!(CreateUserTxn<'_> = CreateUser, AlterUserTxn<'_> = AlterUser, DropUserTxn<'_> = DropUser);
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CreateStatementTxn<'a> {
+    username: &'a str,
+    name: &'a str,
+    body: &'a str,
+}
+
+impl<'a> CreateStatementTxn<'a> {
+    pub fn new(username: &'a str, name: &'a str, body: &'a str) -> Self {
+        Self {
+            username,
+            name,
+            body,
+        }
+    }
+    pub fn username(&self) -> &str {
+        self.username
+    }
+    pub fn name(&self) -> &str {
+        self.name
+    }
+    pub fn body(&self) -> &str {
+        self.body
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DropStatementTxn<'a> {
+    username: &'a str,
+    name: &'a str,
+}
+
+impl<'a> DropStatementTxn<'a> {
+    pub fn new(username: &'a str, name: &'a str) -> Self {
+        Self { username, name }
+    }
+    pub fn username(&self) -> &str {
+        self.username
+    }
+    pub fn name(&self) -> &str {
+        self.name
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+/// Transaction commit payload for a `sysctl alter quota user ...` query
+pub struct AlterUserQuotaTxn<'a> {
+    username: &'a str,
+    reads: u64,
+    writes: u64,
+}
+
+impl<'a> AlterUserQuotaTxn<'a> {
+    pub fn new(username: &'a str, reads: u64, writes: u64) -> Self {
+        Self {
+            username,
+            reads,
+            writes,
+        }
+    }
+    pub fn username(&self) -> &str {
+        self.username
+    }
+    pub fn reads(&self) -> u64 {
+        self.reads
+    }
+    pub fn writes(&self) -> u64 {
+        self.writes
+    }
+}
+
+impl_gns_event!(
+    CreateUserTxn<'_> = CreateUser,
+    AlterUserTxn<'_> = AlterUser,
+    DropUserTxn<'_> = DropUser,
+    CreateStatementTxn<'_> = CreateStatement,
+    DropStatementTxn<'_> = DropStatement,
+    AlterUserQuotaTxn<'_> = AlterUserQuota
+);
@@ -0,0 +1,188 @@
+/*
+ * Created on Thu Jul 02 2020
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2020, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+#![forbid(unused_crate_dependencies)]
+#![deny(unused_imports, unused_must_use)]
+#![cfg_attr(feature = "nightly", feature(test))]
+
+//! # Skytable
+//!
+//! The `skyd` crate (or the `server` folder) is Skytable's database server and maybe
+//! is the most important part of the project. There are several modules within this crate; see
+//! the modules for their respective documentation.
+
+use {env_logger::Builder, std::env};
+
+#[macro_use]
+extern crate log;
+#[macro_use]
+pub mod util;
+mod engine;
+
+#[doc(hidden)]
+pub use engine::{bench, fuzz};
+
+// only the benchmarks use it
+#[cfg(test)]
+use criterion as _;
+
+use libsky::{URL, VERSION};
+
+#[cfg(all(feature = "jemalloc", feature = "mimalloc"))]
+compile_error!(
+    "the `jemalloc` and `mimalloc` features are mutually exclusive (use `--no-default-features`)"
+);
+
+#[cfg(all(feature = "jemalloc", not(target_env = "msvc"), not(miri)))]
+#[global_allocator]
+/// Jemallocator - this is the default memory allocator for platforms other than msvc
+static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
+
+#[cfg(all(feature = "mimalloc", not(feature = "jemalloc"), not(miri)))]
+#[global_allocator]
+/// mimalloc, if selected with the `mimalloc` feature
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+/// The terminal art for `!noart` configurations
+const TEXT: &str = "
+███████ ██   ██ ██    ██ ████████  █████  ██████  ██      ███████
+██      ██  ██   ██  ██     ██    ██   ██ ██   ██ ██      ██
+███████ █████     ████      ██    ███████ ██████  ██      █████
+     ██ ██  ██     ██       ██    ██   ██ ██   ██ ██      ██
+███████ ██   ██    ██       ██    ██   ██ ██████  ███████ ███████
+";
+
+type IoResult<T> = std::io::Result<T>;
+const SKY_PID_FILE: &str = ".sky_pid";
+/// The number of worker threads for the main runtime when connections are served by shards
+const SHARDED_CONTROL_WORKERS: usize = 2;
+
+fn init_logger() {
+    Builder::new()
+        .parse_filters(&env::var("SKY_LOG").unwrap_or_else(|_| "info".to_owned()))
+        .init();
+}
+
+/// Run the offline data inspector (`sky-inspect`) with the command line arguments that it was started with
+pub fn inspect() {
+    self::init_logger();
+    if let Err(e) = engine::run_inspect(env::args().skip(1).collect()) {
+        exit_fatal!(error!("{e}"))
+    }
+}
+
+/// Run the server (with the command line arguments that it was started with) until it's stopped
+pub fn run() {
+    use crate::engine::config::ConfigReturn;
+    self::init_logger();
+    if env::args().nth(1).as_deref() == Some("testkit") {
+        match engine::run_testkit(env::args().skip(2).collect()) {
+            Ok(()) => return,
+            Err(e) => exit_fatal!(error!("{e}")),
+        }
+    }
+    if env::args()
+        .skip(1)
+        .any(|arg| arg == engine::config::CLI_CHECK_CONFIG)
+    {
+        match engine::run_config_check() {
+            true => exit!(0x00),
+            false => util::exit_error(),
+        }
+    }
+    let config = match engine::config::check_configuration() {
+        Ok(cfg) => match cfg {
+            ConfigReturn::Config(cfg) => cfg,
+            ConfigReturn::HelpMessage(msg) => {
+                exit!(eprintln!("{msg}"), 0x00)
+            }
+        },
+        Err(e) => exit_fatal!(error!("{e}")),
+    };
+    #[cfg(windows)]
+    let config = match util::os::run_as_service(config, self::entrypoint) {
+        // we ran (and stopped) as a service
+        Ok(()) => return,
+        Err(config) => config,
+    };
+    self::entrypoint(config)
+}
+
+fn entrypoint(mut config: engine::config::Configuration) {
+    println!("{TEXT}\nSkytable v{VERSION} | {URL}\n");
+    engine::init_huge_pages(&mut config);
+    let run = || {
+        let f_rt_start = || {
+            engine::set_context_init("locking PID file");
+            let pid_file = util::os::FileLock::new(SKY_PID_FILE)?;
+            engine::set_context_init("initializing runtime");
+            let mut runtime = tokio::runtime::Builder::new_multi_thread();
+            runtime.on_thread_start(engine::bind_thread_arena);
+            if let engine::config::ConfigRuntime::Sharded = config.system.runtime {
+                // connections are served by the shards, so this runtime only runs the listeners and services
+                runtime.worker_threads(SHARDED_CONTROL_WORKERS);
+            }
+            let runtime = runtime.thread_name("server").enable_all().build()?;
+            Ok((pid_file, runtime))
+        };
+        let (pid_file, runtime) = match f_rt_start() {
+            Ok((pf, rt)) => (pf, rt),
+            Err(e) => return (None, None, Err(e)),
+        };
+        let f_glob_init = runtime.block_on(async move {
+            engine::set_context_init("binding system signals");
+            let signal = util::os::TerminationSignal::init()?;
+            engine::set_context_init("starting probe listener");
+            engine::start_probes(&config).await?;
+            let (config, global) = tokio::task::spawn_blocking(|| engine::load_all(config))
+                .await
+                .unwrap()?;
+            engine::RuntimeResult::Ok((signal, config, global))
+        });
+        let (signal, config, global) = match f_glob_init {
+            Ok((sig, cfg, g)) => (sig, cfg, g),
+            Err(e) => return (Some(pid_file), None, Err(e)),
+        };
+        let g = global.global.clone();
+        let result_start =
+            runtime.block_on(async move { engine::start(signal, config, global).await });
+        (Some(pid_file), Some(g), result_start)
+    };
+    let (pid_file, global, result) = run();
+    if let Some(g) = global {
+        info!("cleaning up data");
+        engine::finish(g);
+    }
+    if let Some(_) = pid_file {
+        if let Err(e) = std::fs::remove_file(SKY_PID_FILE) {
+            error!("failed to remove PID file: {e}");
+        }
+    }
+    match result {
+        Ok(()) => println!("goodbye"),
+        Err(e) => exit_fatal!(error!("{e}")),
+    }
+}
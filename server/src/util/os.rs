@@ -30,6 +30,11 @@ pub use unix::*;
 pub use windows::*;
 mod flock;
 mod free_memory;
+mod numa;
+mod service;
+
+#[cfg(windows)]
+pub use service::run_as_service;
 
 use {
     crate::IoResult,
@@ -40,7 +45,12 @@ use {
         time::{SystemTime, UNIX_EPOCH},
     },
 };
-pub use {flock::FileLock, free_memory::free_memory_in_bytes};
+pub use {
+    flock::FileLock,
+    free_memory::{free_memory_in_bytes, process_memory_in_bytes},
+    numa::{numa_node_memory, numa_nodes, set_local_memory_policy, NumaNode, NumaNodeMemory},
+    service::{notify_ready, notify_stopping},
+};
 
 #[derive(Debug)]
 #[repr(transparent)]
@@ -54,6 +64,10 @@ impl SysIOError {
     pub fn kind(&self) -> std::io::ErrorKind {
         self.0.kind()
     }
+    /// Returns true if this error means that the disk (or the user's disk quota) is full
+    pub fn is_disk_full(&self) -> bool {
+        is_disk_full(&self.0)
+    }
 }
 
 impl From<std::io::Error> for SysIOError {
@@ -177,18 +191,28 @@ mod windows {
             pin::Pin,
             task::{Context, Poll},
         },
-        tokio::signal::windows::{ctrl_break, ctrl_c, CtrlBreak, CtrlC},
+        tokio::{
+            signal::windows::{ctrl_break, ctrl_c, CtrlBreak, CtrlC},
+            sync::futures::Notified,
+        },
     };
 
     pub struct TerminationSignal {
         ctrl_c: CtrlC,
         ctrl_break: CtrlBreak,
+        /// the service control manager asked us to stop (only if we're running as a service)
+        service_stop: Pin<Box<Notified<'static>>>,
     }
     impl TerminationSignal {
         pub fn init() -> crate::IoResult<Self> {
             let ctrl_c = ctrl_c()?;
             let ctrl_break = ctrl_break()?;
-            Ok(Self { ctrl_c, ctrl_break })
+            let service_stop = Box::pin(super::service::request_stop_signal());
+            Ok(Self {
+                ctrl_c,
+                ctrl_break,
+                service_stop,
+            })
         }
     }
     impl Future for TerminationSignal {
@@ -196,6 +220,9 @@ mod windows {
         fn poll(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
             let ctrl_c = self.ctrl_c.poll_recv(ctx);
             let ctrl_break = self.ctrl_break.poll_recv(ctx);
+            if let Poll::Ready(()) = self.service_stop.as_mut().poll(ctx) {
+                return Poll::Ready(Some(()));
+            }
             match (ctrl_c, ctrl_break) {
                 // if any of them are ready or closed, simply return
                 (Poll::Ready(p), _) | (_, Poll::Ready(p)) => Poll::Ready(p),
@@ -317,11 +344,143 @@ pub fn get_epoch_time() -> u128 {
         .as_nanos()
 }
 
+/// Returns the current epoch time in milliseconds
+pub fn get_epoch_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Returns the current epoch time in seconds
+pub fn get_epoch_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 /// Returns the hostname
 pub fn get_hostname() -> hostname_impl::Hostname {
     hostname_impl::Hostname::get()
 }
 
+/// Returns the CPUs that the calling thread is allowed to run on (or an empty list if this isn't supported on this
+/// platform)
+pub fn allowed_cpus() -> Vec<usize> {
+    affinity_impl::allowed_cpus()
+}
+
+/// Pins the calling thread to the given CPU. Returns false if the thread couldn't be pinned (or if pinning isn't
+/// supported on this platform)
+pub fn pin_current_thread(cpu: usize) -> bool {
+    affinity_impl::pin_current_thread(cpu)
+}
+
+mod affinity_impl {
+    #[cfg(target_os = "linux")]
+    pub(super) fn allowed_cpus() -> Vec<usize> {
+        unsafe {
+            // UNSAFE(@ohsayan): cpu_set_t is a plain bitmask, so zeroed is a valid (empty) set
+            let mut allowed: libc::cpu_set_t = std::mem::zeroed();
+            if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut allowed) != 0
+            {
+                return vec![];
+            }
+            (0..libc::CPU_SETSIZE as usize)
+                .filter(|cpu| libc::CPU_ISSET(*cpu, &allowed))
+                .collect()
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(super) fn pin_current_thread(cpu: usize) -> bool {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return false;
+        }
+        unsafe {
+            // UNSAFE(@ohsayan): cpu_set_t is a plain bitmask and we checked that the CPU fits in it
+            let mut target: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(cpu, &mut target);
+            libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &target) == 0
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub(super) fn allowed_cpus() -> Vec<usize> {
+        vec![]
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub(super) fn pin_current_thread(_: usize) -> bool {
+        false
+    }
+}
+
+/// Returns true if the error means that the disk (or the user's disk quota) is full
+pub fn is_disk_full(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        matches!(e.raw_os_error(), Some(libc::ENOSPC | libc::EDQUOT))
+    }
+    #[cfg(windows)]
+    {
+        // ERROR_HANDLE_DISK_FULL and ERROR_DISK_FULL
+        matches!(e.raw_os_error(), Some(39 | 112))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+/// Enables TCP keepalive on the socket so that the first probe is sent once the connection has been idle for `idle`
+/// (and then every third of that, giving up after three unanswered probes). Returns false if keepalive couldn't be
+/// enabled (or if this isn't supported on this platform)
+pub fn set_tcp_keepalive(socket: &tokio::net::TcpStream, idle: std::time::Duration) -> bool {
+    keepalive_impl::set_tcp_keepalive(socket, idle)
+}
+
+/// Returns true if [`set_tcp_keepalive`] is supported on this platform
+pub const fn supports_tcp_keepalive() -> bool {
+    cfg!(target_os = "linux")
+}
+
+mod keepalive_impl {
+    use std::time::Duration;
+
+    #[cfg(target_os = "linux")]
+    pub(super) fn set_tcp_keepalive(socket: &tokio::net::TcpStream, idle: Duration) -> bool {
+        use std::os::fd::AsRawFd;
+        let fd = socket.as_raw_fd();
+        let idle = idle.as_secs().clamp(1, libc::c_int::MAX as u64) as libc::c_int;
+        let options = [
+            (libc::SOL_SOCKET, libc::SO_KEEPALIVE, 1),
+            (libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, idle),
+            (libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, (idle / 3).max(1)),
+            (libc::IPPROTO_TCP, libc::TCP_KEEPCNT, 3),
+        ];
+        options
+            .into_iter()
+            .all(|(level, name, value): (_, _, libc::c_int)| unsafe {
+                // UNSAFE(@ohsayan): the socket stays open while we borrow it, and every option is a c_int
+                libc::setsockopt(
+                    fd,
+                    level,
+                    name,
+                    &value as *const libc::c_int as *const libc::c_void,
+                    std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+                ) == 0
+            })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub(super) fn set_tcp_keepalive(_: &tokio::net::TcpStream, _: Duration) -> bool {
+        false
+    }
+}
+
 mod uptime_impl {
     #[cfg(target_os = "linux")]
     pub(super) fn uptime() -> std::io::Result<u128> {
@@ -560,3 +719,13 @@ fn t_uptime() {
             <= (Duration::from_millis(uptime_1.try_into().unwrap()) + Duration::from_secs(10))
     )
 }
+
+#[test]
+fn disk_full() {
+    #[cfg(unix)]
+    let full = std::io::Error::from_raw_os_error(libc::ENOSPC);
+    #[cfg(windows)]
+    let full = std::io::Error::from_raw_os_error(112);
+    assert!(is_disk_full(&full));
+    assert!(!is_disk_full(&std::io::ErrorKind::NotFound.into()));
+}
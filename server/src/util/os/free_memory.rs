@@ -83,3 +83,25 @@ pub fn free_memory_in_bytes() -> u64 {
         return 0;
     }
 }
+
+/// Returns the memory used by this process (its resident set size) in bytes. Returns 0 if this isn't supported on the
+/// platform
+#[cfg(target_os = "linux")]
+pub fn process_memory_in_bytes() -> u64 {
+    let page_size = unsafe {
+        // UNSAFE(@ohsayan): sysconf is always safe to call
+        libc::sysconf(libc::_SC_PAGESIZE)
+    };
+    // the second field is the resident set size in pages
+    std::fs::read_to_string("/proc/self/statm")
+        .ok()
+        .and_then(|statm| statm.split_whitespace().nth(1)?.parse::<u64>().ok())
+        .map_or(0, |pages| pages.saturating_mul(page_size.max(0) as u64))
+}
+
+/// Returns the memory used by this process (its resident set size) in bytes. Returns 0 if this isn't supported on the
+/// platform
+#[cfg(not(target_os = "linux"))]
+pub fn process_memory_in_bytes() -> u64 {
+    0
+}
@@ -0,0 +1,174 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! NUMA topology, memory policy and per-node memory statistics. Everything here is Linux-only; on other platforms we
+//! report no nodes and all calls that would change placement fail (returning false)
+
+/// A NUMA node and the CPUs that belong to it
+#[derive(Debug, PartialEq)]
+pub struct NumaNode {
+    id: usize,
+    cpus: Vec<usize>,
+}
+
+impl NumaNode {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+    pub fn cpus(&self) -> &[usize] {
+        &self.cpus
+    }
+}
+
+/// Memory statistics (in bytes) for a NUMA node
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct NumaNodeMemory {
+    pub total: u64,
+    pub free: u64,
+}
+
+/// Parse a kernel CPU list like `0-3,8,10-11`
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = vec![];
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => cpus.extend(start.parse::<usize>().ok()?..=end.parse().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+/// Parse a node's `meminfo` (each line looks like `Node 0 MemTotal:  16303876 kB`)
+fn parse_node_meminfo(meminfo: &str) -> Option<NumaNodeMemory> {
+    let (mut total, mut free) = (None, None);
+    for line in meminfo.lines() {
+        let mut fields = line.split_whitespace().skip(2);
+        let (Some(key), Some(kb)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        match key {
+            "MemTotal:" => total = kb.parse::<u64>().ok(),
+            "MemFree:" => free = kb.parse::<u64>().ok(),
+            _ => {}
+        }
+    }
+    Some(NumaNodeMemory {
+        total: total? * 1024,
+        free: free? * 1024,
+    })
+}
+
+#[cfg(target_os = "linux")]
+const NODE_DIR: &str = "/sys/devices/system/node";
+
+/// Returns all NUMA nodes (ordered by ID) that have CPUs. Returns an empty list if the topology isn't available
+#[cfg(target_os = "linux")]
+pub fn numa_nodes() -> Vec<NumaNode> {
+    let Ok(entries) = std::fs::read_dir(NODE_DIR) else {
+        return vec![];
+    };
+    let mut nodes: Vec<NumaNode> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let id = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("node")?
+                .parse()
+                .ok()?;
+            let cpus =
+                parse_cpu_list(&std::fs::read_to_string(entry.path().join("cpulist")).ok()?)?;
+            Some(NumaNode { id, cpus })
+        })
+        .filter(|node| !node.cpus.is_empty())
+        .collect();
+    nodes.sort_unstable_by_key(|node| node.id);
+    nodes
+}
+
+/// Returns all NUMA nodes (ordered by ID) that have CPUs. Returns an empty list if the topology isn't available
+#[cfg(not(target_os = "linux"))]
+pub fn numa_nodes() -> Vec<NumaNode> {
+    vec![]
+}
+
+/// Returns the memory statistics for the given node
+#[cfg(target_os = "linux")]
+pub fn numa_node_memory(node: usize) -> Option<NumaNodeMemory> {
+    parse_node_meminfo(&std::fs::read_to_string(format!("{NODE_DIR}/node{node}/meminfo")).ok()?)
+}
+
+/// Returns the memory statistics for the given node
+#[cfg(not(target_os = "linux"))]
+pub fn numa_node_memory(_: usize) -> Option<NumaNodeMemory> {
+    None
+}
+
+/// Make all future page allocations by the calling thread come from the node that the thread is running on (even if
+/// the process was started with a different policy, for example with `numactl --interleave`)
+#[cfg(target_os = "linux")]
+pub fn set_local_memory_policy() -> bool {
+    const MPOL_LOCAL: libc::c_long = 4;
+    unsafe {
+        // UNSAFE(@ohsayan): MPOL_LOCAL takes no nodemask, so passing a null mask is correct
+        libc::syscall(
+            libc::SYS_set_mempolicy,
+            MPOL_LOCAL,
+            std::ptr::null::<libc::c_ulong>(),
+            0 as libc::c_ulong,
+        ) == 0
+    }
+}
+
+/// Make all future page allocations by the calling thread come from the node that the thread is running on
+#[cfg(not(target_os = "linux"))]
+pub fn set_local_memory_policy() -> bool {
+    false
+}
+
+#[test]
+fn parse_topology() {
+    assert_eq!(
+        parse_cpu_list("0-3,8,10-11\n"),
+        Some(vec![0, 1, 2, 3, 8, 10, 11])
+    );
+    assert_eq!(parse_cpu_list("\n"), Some(vec![]));
+    assert_eq!(parse_cpu_list("0-x"), None);
+    let meminfo = "\
+Node 1 MemTotal:       16303876 kB
+Node 1 MemFree:         1234567 kB
+Node 1 MemUsed:        15069309 kB
+";
+    assert_eq!(
+        parse_node_meminfo(meminfo),
+        Some(NumaNodeMemory {
+            total: 16303876 * 1024,
+            free: 1234567 * 1024
+        })
+    );
+    assert_eq!(parse_node_meminfo("Node 1 MemUsed: 1 kB"), None);
+}
@@ -0,0 +1,244 @@
+/*
+ * Created on Fri Oct 16 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+//! Integration with service managers. On Linux, systemd is notified (if it asked to be, with `Type=notify`) once the
+//! server is ready (that is, once all data has been recovered and the endpoints are bound) and once it starts
+//! stopping. On Windows, `skyd` runs as a service when it is started by the service control manager and reports the
+//! same transitions to it. Everywhere else (or when not started by a service manager) all of this is a no-op
+
+#[cfg(windows)]
+pub(super) use self::windows::request_stop_signal;
+#[cfg(windows)]
+pub use self::windows::run_as_service;
+
+/// Tell the service manager that the server is ready to serve connections
+pub fn notify_ready(status: &str) {
+    #[cfg(target_os = "linux")]
+    {
+        linux::sd_notify(&format!("READY=1\nSTATUS={status}"))
+    }
+    #[cfg(windows)]
+    {
+        let _ = status;
+        windows::set_state(windows::State::Running)
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = status;
+    }
+}
+
+/// Tell the service manager that the server is stopping
+pub fn notify_stopping() {
+    #[cfg(target_os = "linux")]
+    {
+        linux::sd_notify("STOPPING=1\nSTATUS=stopping")
+    }
+    #[cfg(windows)]
+    {
+        windows::set_state(windows::State::StopPending)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        env,
+        ffi::OsStr,
+        os::{
+            linux::net::SocketAddrExt,
+            unix::{
+                ffi::OsStrExt,
+                net::{SocketAddr, UnixDatagram},
+            },
+        },
+    };
+
+    /// Send the state to the socket in `NOTIFY_SOCKET` (see `sd_notify(3)`). The socket is either a path or (if it
+    /// begins with `@`) an abstract socket
+    pub(super) fn sd_notify(state: &str) {
+        let Some(socket) = env::var_os("NOTIFY_SOCKET") else {
+            return;
+        };
+        let socket = socket.as_bytes();
+        let addr = match socket.strip_prefix(b"@") {
+            Some(name) => SocketAddr::from_abstract_name(name),
+            None => SocketAddr::from_pathname(OsStr::from_bytes(socket)),
+        };
+        let r = addr.and_then(|addr| {
+            UnixDatagram::unbound()?.send_to_addr(state.as_bytes(), &addr)?;
+            Ok(())
+        });
+        if let Err(e) = r {
+            warn!("failed to notify service manager: {e}");
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use {
+        ::windows::{
+            core::{PCWSTR, PWSTR},
+            Win32::{
+                Foundation::ERROR_FAILED_SERVICE_CONTROLLER_CONNECT,
+                System::Services::{
+                    RegisterServiceCtrlHandlerExW, SetServiceStatus, StartServiceCtrlDispatcherW,
+                    SERVICE_ACCEPT_SHUTDOWN, SERVICE_ACCEPT_STOP, SERVICE_CONTROL_INTERROGATE,
+                    SERVICE_CONTROL_SHUTDOWN, SERVICE_CONTROL_STOP, SERVICE_RUNNING,
+                    SERVICE_START_PENDING, SERVICE_STATUS, SERVICE_STATUS_CURRENT_STATE,
+                    SERVICE_STATUS_HANDLE, SERVICE_STOPPED, SERVICE_STOP_PENDING,
+                    SERVICE_TABLE_ENTRYW, SERVICE_WIN32_OWN_PROCESS,
+                },
+            },
+        },
+        std::{
+            ffi::c_void,
+            sync::{Mutex, OnceLock},
+        },
+        tokio::sync::Notify,
+    };
+
+    /// The name of the service. For a service that runs in its own process, the name is ignored by the SCM
+    const SERVICE_NAME: &str = "skyd";
+    /// How long (in ms) the SCM should wait before it considers a pending start or stop to be hung. Recovery can
+    /// take a while, so we're generous here
+    const WAIT_HINT_MS: u32 = 60_000;
+    const NO_ERROR: u32 = 0;
+    const NOT_HANDLED: u32 = 120; // ERROR_CALL_NOT_IMPLEMENTED
+
+    static SERVICE_MAIN: Mutex<Option<Box<dyn FnOnce() + Send>>> = Mutex::new(None);
+    static STATUS_HANDLE: OnceLock<isize> = OnceLock::new();
+    static STOP: Notify = Notify::const_new();
+
+    #[derive(Clone, Copy)]
+    pub(super) enum State {
+        StartPending,
+        Running,
+        StopPending,
+        Stopped,
+    }
+
+    /// Run `main` as a service if we were started by the SCM, returning the argument if we weren't (in which case the
+    /// caller should run normally)
+    pub fn run_as_service<T: Send + 'static>(arg: T, main: fn(T)) -> Result<(), T> {
+        let arg = std::sync::Arc::new(Mutex::new(Some(arg)));
+        let arg_ = arg.clone();
+        *SERVICE_MAIN.lock().unwrap() =
+            Some(Box::new(move || main(arg_.lock().unwrap().take().unwrap())));
+        let mut name: Vec<u16> = SERVICE_NAME.encode_utf16().chain([0]).collect();
+        let table = [
+            SERVICE_TABLE_ENTRYW {
+                lpServiceName: PWSTR(name.as_mut_ptr()),
+                lpServiceProc: Some(service_main),
+            },
+            SERVICE_TABLE_ENTRYW::default(),
+        ];
+        // blocks until the service has stopped
+        match unsafe { StartServiceCtrlDispatcherW(table.as_ptr()) } {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                if e.code() != ERROR_FAILED_SERVICE_CONTROLLER_CONNECT.to_hresult() {
+                    warn!("failed to connect to the service control manager: {e}");
+                }
+                SERVICE_MAIN.lock().unwrap().take();
+                let arg = arg.lock().unwrap().take().unwrap();
+                Err(arg)
+            }
+        }
+    }
+
+    /// Returns a future that completes once the SCM asks us to stop
+    pub fn request_stop_signal() -> tokio::sync::futures::Notified<'static> {
+        STOP.notified()
+    }
+
+    unsafe extern "system" fn service_main(_argc: u32, _argv: *mut PWSTR) {
+        let name: Vec<u16> = SERVICE_NAME.encode_utf16().chain([0]).collect();
+        match RegisterServiceCtrlHandlerExW(PCWSTR(name.as_ptr()), Some(control_handler), None) {
+            Ok(handle) => {
+                let _ = STATUS_HANDLE.set(handle.0 as isize);
+            }
+            Err(e) => {
+                error!("failed to register service control handler: {e}");
+                return;
+            }
+        }
+        set_state(State::StartPending);
+        let main = SERVICE_MAIN.lock().unwrap().take();
+        if let Some(main) = main {
+            main();
+        }
+        set_state(State::Stopped);
+    }
+
+    unsafe extern "system" fn control_handler(
+        control: u32,
+        _event_type: u32,
+        _event_data: *mut c_void,
+        _context: *mut c_void,
+    ) -> u32 {
+        match control {
+            SERVICE_CONTROL_STOP | SERVICE_CONTROL_SHUTDOWN => {
+                set_state(State::StopPending);
+                // stores a permit if the server isn't waiting yet
+                STOP.notify_one();
+                NO_ERROR
+            }
+            SERVICE_CONTROL_INTERROGATE => NO_ERROR,
+            _ => NOT_HANDLED,
+        }
+    }
+
+    /// Report the state to the SCM (if we're running as a service)
+    pub(super) fn set_state(state: State) {
+        let Some(handle) = STATUS_HANDLE.get() else {
+            return;
+        };
+        let (current, accepted, wait_hint): (SERVICE_STATUS_CURRENT_STATE, u32, u32) = match state {
+            State::StartPending => (SERVICE_START_PENDING, 0, WAIT_HINT_MS),
+            State::Running => (
+                SERVICE_RUNNING,
+                SERVICE_ACCEPT_STOP | SERVICE_ACCEPT_SHUTDOWN,
+                0,
+            ),
+            State::StopPending => (SERVICE_STOP_PENDING, 0, WAIT_HINT_MS),
+            State::Stopped => (SERVICE_STOPPED, 0, 0),
+        };
+        let status = SERVICE_STATUS {
+            dwServiceType: SERVICE_WIN32_OWN_PROCESS,
+            dwCurrentState: current,
+            dwControlsAccepted: accepted,
+            dwWin32ExitCode: NO_ERROR,
+            dwServiceSpecificExitCode: 0,
+            dwCheckPoint: 0,
+            dwWaitHint: wait_hint,
+        };
+        if let Err(e) = unsafe { SetServiceStatus(SERVICE_STATUS_HANDLE(*handle as _), &status) } {
+            warn!("failed to report service status: {e}");
+        }
+    }
+}
@@ -91,6 +91,41 @@ pub fn random_number<T: SampleUniform + PartialOrd>(min: T, max: T, rng: &mut im
     rng.gen_range(min..max)
 }
 
+/// Randomly mutate the given input (replace, insert, delete, duplicate, swap or truncate) a few times. Used to fuzz
+/// decoders and parsers with inputs that are close to (but not quite) valid
+pub fn mutate<T: Clone, R: Rng>(
+    src: &[T],
+    rng: &mut R,
+    mut random_item: impl FnMut(&mut R) -> T,
+) -> Vec<T> {
+    let mut ret = src.to_vec();
+    for _ in 0..random_number(1, 5, rng) {
+        let len = ret.len();
+        if len == 0 {
+            ret.push(random_item(rng));
+            continue;
+        }
+        let (i, j) = (random_number(0, len, rng), random_number(0, len, rng));
+        match random_number(0, 6, rng) {
+            0 => ret[i] = random_item(rng),
+            1 => {
+                let item = random_item(rng);
+                ret.insert(i, item)
+            }
+            2 => {
+                ret.remove(i);
+            }
+            3 => {
+                let dup = ret[i.min(j)..i.max(j)].to_vec();
+                ret.splice(i.max(j)..i.max(j), dup);
+            }
+            4 => ret.swap(i, j),
+            _ => ret.truncate(i),
+        }
+    }
+    ret
+}
+
 pub fn random_string(rng: &mut impl Rng, l: usize) -> String {
     rng.sample_iter(Alphanumeric)
         .take(l)
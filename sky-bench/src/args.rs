@@ -42,6 +42,7 @@ enum TaskInner {
 pub enum Task {
     HelpMsg(String),
     BenchConfig(BenchConfig),
+    Replay(ReplayConfig),
 }
 
 #[derive(Debug, PartialEq)]
@@ -86,6 +87,27 @@ impl BenchConfig {
     }
 }
 
+#[derive(Debug)]
+pub struct ReplayConfig {
+    pub host: String,
+    pub port: u16,
+    pub root_pass: String,
+    pub path: String,
+    pub speed: f64,
+}
+
+impl ReplayConfig {
+    pub fn new(host: String, port: u16, root_pass: String, path: String, speed: f64) -> Self {
+        Self {
+            host,
+            port,
+            root_pass,
+            path,
+            speed,
+        }
+    }
+}
+
 fn load_env() -> BenchResult<TaskInner> {
     let action = libsky::parse_cli_args_disallow_duplicate()?;
     match action {
@@ -155,6 +177,30 @@ pub fn parse() -> BenchResult<Task> {
             }
         }
     };
+    // replay
+    if let Some(path) = args.remove("--replay") {
+        let speed = match args.remove("--speed") {
+            None => 1.0,
+            Some(s) => match s.parse::<f64>() {
+                Ok(s) if s.is_finite() && s > 0.0 => s,
+                Err(_) | Ok(_) => {
+                    return Err(BenchError::ArgsErr(
+                        "bad value for `--speed`. must be a positive number".into(),
+                    ))
+                }
+            },
+        };
+        return if args.is_empty() {
+            Ok(Task::Replay(ReplayConfig::new(
+                host, port, passsword, path, speed,
+            )))
+        } else {
+            Err(BenchError::ArgsErr(
+                "`--replay` can only be used along with `--endpoint`, `--password` and `--speed`"
+                    .into(),
+            ))
+        };
+    }
     // threads
     let thread_count = match args.remove("--threads") {
         None => num_cpus::get(),
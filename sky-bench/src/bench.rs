@@ -300,7 +300,7 @@ fn prepare_bench_spec(bench: &BenchConfig) -> Vec<BenchItem> {
     ]
 }
 
-fn fmt_u64(n: u64) -> String {
+pub fn fmt_u64(n: u64) -> String {
     let num_str = n.to_string();
     let mut result = String::new();
     let chars_rev: Vec<_> = num_str.chars().rev().collect();
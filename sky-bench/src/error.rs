@@ -41,6 +41,7 @@ pub enum BenchError {
     RookieEngineError(BombardError<BombardTask>),
     FuryEngineError(fury::FuryError),
     DirectDbError(Error),
+    ReplayErr(String),
 }
 
 impl From<fury::FuryError> for BenchError {
@@ -81,6 +82,7 @@ impl fmt::Display for BenchError {
             Self::DirectDbError(e) => write!(f, "direct operation on db failed. {e}"),
             Self::RookieEngineError(e) => write!(f, "benchmark failed (rookie engine): {e}"),
             Self::FuryEngineError(e) => write!(f, "benchmark failed (fury engine): {e}"),
+            Self::ReplayErr(e) => write!(f, "replay failed: {e}"),
         }
     }
 }
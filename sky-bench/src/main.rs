@@ -29,6 +29,7 @@ extern crate log;
 mod args;
 mod bench;
 mod error;
+mod replay;
 mod runtime;
 
 fn main() {
@@ -49,6 +50,7 @@ fn run() -> error::BenchResult<()> {
     match task {
         args::Task::HelpMsg(msg) => println!("{msg}"),
         args::Task::BenchConfig(bench) => bench::run(bench)?,
+        args::Task::Replay(replay) => replay::run(replay)?,
     }
     Ok(())
 }
@@ -0,0 +1,271 @@
+/*
+ * Created on Sat Oct 17 2026
+ *
+ * This file is a part of Skytable
+ * Skytable (formerly known as TerrabaseDB or Skybase) is a free and open-source
+ * NoSQL database written by Sayan Nandan ("the Author") with the
+ * vision to provide flexibility in data modelling without compromising
+ * on performance, queryability or scalability.
+ *
+ * Copyright (c) 2026, Sayan Nandan <ohsayan@outlook.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ *
+*/
+
+/*
+    replay
+    ---
+    runs a workload that was recorded with `sysctl record start` against another instance. every connection in the
+    recording gets a connection (authenticated as root) of its own, which runs that connection's statements in order,
+    each at the same time (since the start of the replay) that it was run at (since the start of the recording),
+    divided by the speed. if the instance can't keep up, statements start late: this is reported as the lag
+*/
+
+use {
+    crate::{
+        args::ReplayConfig,
+        bench::fmt_u64,
+        error::{BenchError, BenchResult},
+    },
+    skytable::{query::SQParam, Config, Query},
+    std::{
+        collections::BTreeMap,
+        fs,
+        sync::{Arc, Barrier},
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+/// The magic at the start of every recording
+const MAGIC: [u8; 8] = *b"SKYQLOG\0";
+/// The version of the recording format that we can replay
+const VERSION: u64 = 1;
+
+/// The parameters of a recorded statement, exactly as they were encoded by the client that ran it
+struct RecordedParams {
+    raw: Vec<u8>,
+    count: usize,
+}
+
+impl SQParam for RecordedParams {
+    fn append_param(&self, buf: &mut Vec<u8>) -> usize {
+        buf.extend(&self.raw);
+        self.count
+    }
+}
+
+struct Statement {
+    /// microseconds since the recording started
+    at: u64,
+    query: Query,
+}
+
+#[derive(Default)]
+struct ConnectionStats {
+    statements: u64,
+    errors: u64,
+    /// total latency in nanoseconds
+    latency: u128,
+    /// highest latency in nanoseconds
+    latency_max: u128,
+    /// highest lag in nanoseconds
+    lag_max: u128,
+}
+
+pub fn run(replay: ReplayConfig) -> BenchResult<()> {
+    let connections = load(&replay.path)?;
+    let statements: usize = connections.values().map(Vec::len).sum();
+    let recorded = connections
+        .values()
+        .filter_map(|statements| statements.last())
+        .map(|statement| statement.at)
+        .max()
+        .unwrap_or(0);
+    info!(
+        "replaying {} statements from {} connections (recorded over {:.2}s) at {}x speed",
+        fmt_u64(statements as u64),
+        connections.len(),
+        recorded as f64 / 1_000_000.0,
+        replay.speed
+    );
+    let config = Config::new(&replay.host, replay.port, "root", &replay.root_pass);
+    // every connection is established before the replay starts, so that connecting doesn't count as lag
+    let mut dbs = Vec::with_capacity(connections.len());
+    for _ in 0..connections.len() {
+        dbs.push(config.connect()?);
+    }
+    let barrier = Arc::new(Barrier::new(connections.len() + 1));
+    let mut workers = Vec::with_capacity(connections.len());
+    for ((id, statements), mut db) in connections.into_iter().zip(dbs) {
+        let barrier = barrier.clone();
+        let speed = replay.speed;
+        workers.push(thread::spawn(move || {
+            barrier.wait();
+            let start = Instant::now();
+            let mut stats = ConnectionStats::default();
+            for statement in statements {
+                let due = start + Duration::from_micros((statement.at as f64 / speed) as u64);
+                let now = Instant::now();
+                if due > now {
+                    thread::sleep(due - now);
+                }
+                let started = Instant::now();
+                let ret = db.query(&statement.query);
+                let latency = started.elapsed().as_nanos();
+                stats.statements += 1;
+                stats.latency += latency;
+                stats.latency_max = stats.latency_max.max(latency);
+                stats.lag_max = stats
+                    .lag_max
+                    .max(started.saturating_duration_since(due).as_nanos());
+                if let Err(e) = ret {
+                    trace!("statement from connection {id} failed: {e}");
+                    stats.errors += 1;
+                }
+            }
+            stats
+        }));
+    }
+    barrier.wait();
+    let start = Instant::now();
+    let mut total = ConnectionStats::default();
+    for worker in workers {
+        let stats = worker
+            .join()
+            .map_err(|_| BenchError::ReplayErr("a replay connection panicked".into()))?;
+        total.statements += stats.statements;
+        total.errors += stats.errors;
+        total.latency += stats.latency;
+        total.latency_max = total.latency_max.max(stats.latency_max);
+        total.lag_max = total.lag_max.max(stats.lag_max);
+    }
+    let elapsed = start.elapsed();
+    info!(
+        "replay complete in {:.2}s. {} statements run, {} failed",
+        elapsed.as_secs_f64(),
+        fmt_u64(total.statements),
+        fmt_u64(total.errors)
+    );
+    if total.errors != 0 {
+        warn!("some statements failed. set `SKYBENCH_LOG=trace` to see why");
+    }
+    println!("+-----------------------+------------------------+");
+    println!("| Metric                | Value                  |");
+    println!("+-----------------------+------------------------+");
+    println!(
+        "| Effective QPS         | {:>22.2} |",
+        total.statements as f64 / elapsed.as_secs_f64()
+    );
+    println!(
+        "| Mean latency (nanos)  | {:>22} |",
+        total.latency / total.statements.max(1) as u128
+    );
+    println!("| Slowest (nanos)       | {:>22} |", total.latency_max);
+    println!("| Highest lag (nanos)   | {:>22} |", total.lag_max);
+    println!("+-----------------------+------------------------+");
+    Ok(())
+}
+
+/// Load a recording, returning the statements of every connection (in the order in which they were run)
+fn load(path: &str) -> BenchResult<BTreeMap<u64, Vec<Statement>>> {
+    let recording = fs::read(path)
+        .map_err(|e| BenchError::ReplayErr(format!("failed to read `{path}`: {e}")))?;
+    let corrupted = || BenchError::ReplayErr(format!("`{path}` is not a valid recording"));
+    let mut scanner = Scanner(&recording);
+    if scanner.take(MAGIC.len()) != Some(&MAGIC[..]) {
+        return Err(corrupted());
+    }
+    match scanner.u64() {
+        Some(VERSION) => {}
+        Some(v) => {
+            return Err(BenchError::ReplayErr(format!(
+                "`{path}` has a recording of version {v}, but only version {VERSION} is supported"
+            )))
+        }
+        None => return Err(corrupted()),
+    }
+    let mut connections: BTreeMap<u64, Vec<Statement>> = BTreeMap::new();
+    while !scanner.0.is_empty() {
+        let (Some(at), Some(connection), Some(query), Some(params)) = (
+            scanner.u64(),
+            scanner.u64(),
+            scanner.block(),
+            scanner.block(),
+        ) else {
+            return Err(corrupted());
+        };
+        let (Ok(query), Some(count)) = (core::str::from_utf8(query), count_params(params)) else {
+            return Err(corrupted());
+        };
+        let mut query = Query::new(query);
+        if count != 0 {
+            query.push_param(RecordedParams {
+                raw: params.to_vec(),
+                count,
+            });
+        }
+        connections
+            .entry(connection)
+            .or_default()
+            .push(Statement { at, query });
+    }
+    Ok(connections)
+}
+
+struct Scanner<'a>(&'a [u8]);
+
+impl<'a> Scanner<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(taken)
+    }
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+    }
+    fn block(&mut self) -> Option<&'a [u8]> {
+        let size = self.u64()?;
+        self.take(usize::try_from(size).ok()?)
+    }
+}
+
+/// Returns the number of parameters in the given encoding (or [`None`] if it isn't valid)
+fn count_params(mut params: &[u8]) -> Option<usize> {
+    let mut count = 0;
+    while let Some((&tag, rest)) = params.split_first() {
+        params = match tag {
+            // null
+            0 => rest,
+            // bool
+            1 => rest.get(1..)?,
+            // uint, sint and float
+            2..=4 => &rest[rest.iter().position(|b| *b == b'\n')? + 1..],
+            // bin and str
+            5 | 6 => {
+                let end = rest.iter().position(|b| *b == b'\n')?;
+                let len: usize = core::str::from_utf8(&rest[..end]).ok()?.parse().ok()?;
+                rest.get(end + 1 + len..)?
+            }
+            _ => return None,
+        };
+        count += 1;
+    }
+    Some(count)
+}
@@ -28,7 +28,7 @@
 
 use {
     super::unsafe_apis,
-    std::{
+    core::{
         fmt, ops,
         ptr::{self, NonNull},
         slice,
@@ -46,6 +46,9 @@ pub struct FixedVec<T, const CAP: usize> {
     l: usize,
 }
 
+// the buffer is owned, just like a vec's
+unsafe impl<T: Send, const CAP: usize> Send for FixedVec<T, CAP> {}
+
 impl<T, const CAP: usize> Default for FixedVec<T, CAP> {
     fn default() -> Self {
         Self::allocate()
@@ -132,13 +132,13 @@ impl<T: PartialEq> PartialEq for RawSlice<T> {
 impl<T: Eq> Eq for RawSlice<T> {}
 
 impl<T: PartialOrd> PartialOrd for RawSlice<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
         self.as_slice().partial_cmp(other.as_slice())
     }
 }
 
 impl<T: Ord> Ord for RawSlice<T> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.as_slice().cmp(other.as_slice())
     }
 }
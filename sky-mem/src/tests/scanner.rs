@@ -24,7 +24,7 @@
  *
 */
 
-use crate::engine::mem::scanner::{BufferedScanner, ScannerDecodeResult};
+use crate::scanner::{BufferedScanner, ScannerDecodeResult};
 
 fn s(b: &[u8]) -> BufferedScanner {
     BufferedScanner::new(b)
@@ -25,7 +25,7 @@
 */
 
 use {
-    crate::engine::mem::{
+    crate::{
         word::{DwordQN, QwordNNNN, TwordNNN, WordIO, ZERO_BLOCK},
         NativeDword, NativeQword, NativeTword, SpecialPaddedWord,
     },
@@ -25,7 +25,7 @@
 */
 
 use {
-    crate::engine::mem::unsafe_apis,
+    crate::unsafe_apis,
     core::{
         fmt,
         hash::{Hash, Hasher},
@@ -31,15 +31,15 @@
 
 */
 
-use std::{
-    alloc::{self, Layout},
-    ptr::{self, NonNull},
+use {
+    alloc::alloc::{alloc, dealloc, Layout},
+    core::ptr::{self, NonNull},
 };
 
 /// Allocate the given layout. This will panic if the allocator returns an error
 #[inline(always)]
 pub unsafe fn alloc_layout<T>(layout: Layout) -> *mut T {
-    let ptr = alloc::alloc(layout);
+    let ptr = alloc(layout);
     assert!(!ptr.is_null(), "malloc failed");
     ptr as _
 }
@@ -57,7 +57,7 @@ pub unsafe fn alloc_array<T>(l: usize) -> *mut T {
 /// Deallocate the given layout
 #[inline(always)]
 pub unsafe fn dealloc_layout(ptr: *mut u8, layout: Layout) {
-    alloc::dealloc(ptr, layout)
+    dealloc(ptr, layout)
 }
 
 /// Deallocate an array of type `T` with size `l`. This function will ensure that nonzero calls to the
@@ -26,7 +26,8 @@
 
 use {
     super::unsafe_apis,
-    std::{
+    alloc::{string::String, vec::Vec},
+    core::{
         fmt,
         iter::FusedIterator,
         mem::{self, ManuallyDrop, MaybeUninit},
@@ -46,6 +47,10 @@ pub struct VInline<const N: usize, T> {
     c: usize,
 }
 
+// the elements are owned, just like a vec's
+unsafe impl<const N: usize, T: Send> Send for VInline<N, T> {}
+unsafe impl<const N: usize, T: Sync> Sync for VInline<N, T> {}
+
 impl<const N: usize, T> VInline<N, T> {
     #[inline(always)]
     pub const fn new() -> Self {
@@ -293,7 +298,6 @@ impl<T, const N: usize> Extend<T> for VInline<N, T> {
     }
 }
 
-#[cfg(test)]
 impl<T, const M: usize, const N: usize> From<[T; N]> for VInline<M, T> {
     fn from(a: [T; N]) -> Self {
         a.into_iter().collect()